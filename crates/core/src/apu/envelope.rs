@@ -2,11 +2,13 @@
 //!
 //! The envelope generator provides automatic volume fade-out for pulse and noise channels.
 
+use serde::{Deserialize, Serialize};
+
 /// Envelope generator component.
 ///
 /// Provides automatic volume control with decay from 15 to 0.
 /// Can also be used for constant volume mode.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Envelope {
     /// Start flag (set when a note is triggered)
     start_flag: bool,
@@ -25,6 +27,7 @@ pub struct Envelope {
 }
 
 impl Envelope {
+    /// Create an envelope at volume 0 with no pending restart
     pub fn new() -> Self {
         Self {
             start_flag: false,
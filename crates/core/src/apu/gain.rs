@@ -0,0 +1,119 @@
+//! Output gain stage shared by all systems.
+//!
+//! Each system's audio hardware produces samples in its own amplitude range
+//! (a single NES pulse channel peaks around ±4000, while Sound Blaster PCM
+//! uses the full 16-bit range). The gain stage scales a system's output by a
+//! per-system normalization gain and the user's master volume, saturating
+//! instead of wrapping when the result exceeds the 16-bit range.
+
+/// Maximum master volume (200%)
+pub const MAX_MASTER_VOLUME: f32 = 2.0;
+
+/// Combined normalization gain and master volume
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AudioGain {
+    system_gain: f32,
+    master_volume: f32,
+}
+
+impl AudioGain {
+    /// Create a gain stage from a system normalization gain and a master
+    /// volume (1.0 = 100%)
+    pub fn new(system_gain: f32, master_volume: f32) -> Self {
+        let mut gain = Self {
+            system_gain: 1.0,
+            master_volume: 1.0,
+        };
+        gain.set_system_gain(system_gain);
+        gain.set_master_volume(master_volume);
+        gain
+    }
+
+    /// Set the per-system normalization gain
+    pub fn set_system_gain(&mut self, gain: f32) {
+        self.system_gain = if gain.is_finite() { gain.max(0.0) } else { 1.0 };
+    }
+
+    /// Set the master volume, clamped to 0.0..=[`MAX_MASTER_VOLUME`]
+    pub fn set_master_volume(&mut self, volume: f32) {
+        self.master_volume = if volume.is_finite() {
+            volume.clamp(0.0, MAX_MASTER_VOLUME)
+        } else {
+            1.0
+        };
+    }
+
+    /// Fixed gain of the system's mixer
+    pub fn system_gain(&self) -> f32 {
+        self.system_gain
+    }
+
+    /// User volume applied on top of the system gain
+    pub fn master_volume(&self) -> f32 {
+        self.master_volume
+    }
+
+    /// Total multiplier applied to each sample
+    pub fn total(&self) -> f32 {
+        self.system_gain * self.master_volume
+    }
+
+    /// Scale samples in place, saturating at the 16-bit limits
+    pub fn apply(&self, samples: &mut [i16]) {
+        let total = self.total();
+        if total == 1.0 {
+            return;
+        }
+        for sample in samples.iter_mut() {
+            *sample = (*sample as f32 * total)
+                .round()
+                .clamp(i16::MIN as f32, i16::MAX as f32) as i16;
+        }
+    }
+}
+
+impl Default for AudioGain {
+    fn default() -> Self {
+        Self::new(1.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unity_gain_is_passthrough() {
+        let mut samples = vec![-32768, -1, 0, 1, 32767];
+        AudioGain::default().apply(&mut samples);
+        assert_eq!(samples, vec![-32768, -1, 0, 1, 32767]);
+    }
+
+    #[test]
+    fn test_gain_combines_system_and_master() {
+        let gain = AudioGain::new(2.0, 0.5);
+        assert_eq!(gain.total(), 1.0);
+
+        let mut samples = vec![1000, -1000];
+        AudioGain::new(3.0, 0.5).apply(&mut samples);
+        assert_eq!(samples, vec![1500, -1500]);
+    }
+
+    #[test]
+    fn test_gain_saturates() {
+        let mut samples = vec![20000, -20000];
+        AudioGain::new(4.0, 1.0).apply(&mut samples);
+        assert_eq!(samples, vec![32767, -32768]);
+    }
+
+    #[test]
+    fn test_master_volume_clamped() {
+        assert_eq!(AudioGain::new(1.0, 5.0).master_volume(), MAX_MASTER_VOLUME);
+        assert_eq!(AudioGain::new(1.0, -1.0).master_volume(), 0.0);
+        assert_eq!(AudioGain::new(f32::NAN, f32::NAN).total(), 1.0);
+
+        let mut samples = vec![12345];
+        AudioGain::new(1.0, 0.0).apply(&mut samples);
+        assert_eq!(samples, vec![0]);
+    }
+}
@@ -22,6 +22,11 @@
 //! - **RP2A07**: NES PAL audio chip
 //! - **AudioChip trait**: Common interface for pluggable audio chips
 //!
+//! ## Output Gain
+//!
+//! - **AudioGain**: Per-system normalization gain and master volume applied to
+//!   each system's output before it reaches the audio device
+//!
 //! ## Timing Support
 //!
 //! All components support both NTSC and PAL timing modes for accurate emulation
@@ -39,6 +44,7 @@
 pub mod audio_chip;
 pub mod envelope;
 pub mod frame_counter;
+pub mod gain;
 pub mod length_counter;
 pub mod noise;
 pub mod polynomial;
@@ -53,6 +59,7 @@ pub mod wave;
 pub use audio_chip::AudioChip;
 pub use envelope::Envelope;
 pub use frame_counter::FrameCounter;
+pub use gain::{AudioGain, MAX_MASTER_VOLUME};
 pub use length_counter::{LengthCounter, LENGTH_TABLE};
 pub use noise::NoiseChannel;
 pub use polynomial::PolynomialCounter;
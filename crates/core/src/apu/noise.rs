@@ -2,6 +2,8 @@
 //!
 //! The noise channel generates pseudo-random noise for percussion and sound effects.
 
+use serde::{Deserialize, Serialize};
+
 /// Noise channel that generates pseudo-random noise.
 ///
 /// The noise channel uses a 15-bit LFSR to generate pseudo-random bit sequences.
@@ -10,7 +12,7 @@
 /// - 16 preset period values
 /// - Length counter
 /// - Envelope generator for volume control
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NoiseChannel {
     /// Whether the channel is enabled
     pub enabled: bool,
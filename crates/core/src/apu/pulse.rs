@@ -3,6 +3,8 @@
 //! This module implements the pulse channel used in the NES APU and potentially
 //! reusable in other systems with similar square wave synthesis chips.
 
+use serde::{Deserialize, Serialize};
+
 /// Pulse channel that generates square wave samples.
 ///
 /// The pulse channel produces a variable-width pulse signal with support for:
@@ -10,7 +12,7 @@
 /// - 11-bit timer for frequency control
 /// - Length counter for note duration
 /// - Envelope generator for volume control
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PulseChannel {
     /// Duty cycle (0-3): 12.5%, 25%, 50%, 75%
     pub duty: u8,
@@ -18,9 +18,13 @@ use super::{
 /// The audio processing unit in the NTSC NES console.
 #[derive(Debug)]
 pub struct Rp2a03Apu {
+    /// Pulse channel 1 ($4000-$4003)
     pub pulse1: PulseChannel,
+    /// Pulse channel 2 ($4004-$4007)
     pub pulse2: PulseChannel,
+    /// Triangle channel ($4008-$400B)
     pub triangle: TriangleChannel,
+    /// Noise channel ($400C-$400F)
     pub noise: NoiseChannel,
     timing: TimingMode,
 }
@@ -14,9 +14,13 @@ use super::{
 /// Functionally identical to RP2A03 but with PAL timing.
 #[derive(Debug)]
 pub struct Rp2a07Apu {
+    /// Pulse channel 1 ($4000-$4003)
     pub pulse1: PulseChannel,
+    /// Pulse channel 2 ($4004-$4007)
     pub pulse2: PulseChannel,
+    /// Triangle channel ($4008-$400B)
     pub triangle: TriangleChannel,
+    /// Noise channel ($400C-$400F)
     pub noise: NoiseChannel,
     timing: TimingMode,
 }
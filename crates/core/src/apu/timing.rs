@@ -8,6 +8,11 @@ pub enum TimingMode {
     Ntsc,
     /// PAL (Europe, Australia) - 1.662607 MHz CPU clock
     Pal,
+    /// Dendy (famiclones, Eastern Europe) - 1.773448 MHz CPU clock
+    ///
+    /// PAL frame rate and scanline count with NTSC-length VBlank and NTSC
+    /// APU tables, since the 3:1 PPU/CPU clock ratio of NTSC is kept.
+    Dendy,
 }
 
 impl TimingMode {
@@ -16,6 +21,7 @@ impl TimingMode {
         match self {
             TimingMode::Ntsc => 1_789_773.0,
             TimingMode::Pal => 1_662_607.0,
+            TimingMode::Dendy => 1_773_448.0,
         }
     }
 
@@ -23,15 +29,19 @@ impl TimingMode {
     pub fn frame_rate_hz(&self) -> f64 {
         match self {
             TimingMode::Ntsc => 60.0988,
-            TimingMode::Pal => 50.0070,
+            TimingMode::Pal | TimingMode::Dendy => 50.0070,
         }
     }
 
     /// Get the frame counter frequency in Hz (240Hz NTSC, 200Hz PAL)
+    ///
+    /// Dendy steps the frame counter every 7457 CPU cycles like NTSC, which
+    /// is about 237.8Hz at its faster clock.
     pub fn frame_counter_hz(&self) -> f64 {
         match self {
             TimingMode::Ntsc => 240.0,
             TimingMode::Pal => 200.0,
+            TimingMode::Dendy => 237.8,
         }
     }
 }
@@ -2,6 +2,8 @@
 //!
 //! The triangle channel produces a quantized triangle wave with 32 steps.
 
+use serde::{Deserialize, Serialize};
+
 /// Triangle channel that generates triangle wave samples.
 ///
 /// The triangle channel has:
@@ -9,7 +11,7 @@
 /// - Length counter for note duration
 /// - Linear counter for additional duration control
 /// - No envelope generator (unlike pulse/noise)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TriangleChannel {
     /// 11-bit timer reload value
     pub timer_reload: u16,
@@ -5,7 +5,7 @@
 //!
 //! For detailed CPU reference documentation, see: `docs/references/cpu_6502.md`
 
-use crate::logging::{LogCategory, LogConfig, LogLevel};
+use crate::logging::{log, LogCategory, LogConfig, LogLevel};
 
 /// Memory interface trait for the 6502 CPU
 ///
@@ -95,6 +95,11 @@ impl<M: Memory6502> Cpu6502<M> {
         self.in_nmi
     }
 
+    /// Restore the NMI handler flag (used when loading save states)
+    pub fn set_in_nmi(&mut self, in_nmi: bool) {
+        self.in_nmi = in_nmi;
+    }
+
     /// Read a byte from memory
     #[inline]
     fn read(&self, addr: u16) -> u8 {
@@ -1416,12 +1421,13 @@ impl<M: Memory6502> Cpu6502<M> {
                 // before pushing.
                 let pc_to_push = self.pc.wrapping_add(1);
                 let brk_pc = self.pc.wrapping_sub(1);
-                if LogConfig::global().should_log(LogCategory::CPU, LogLevel::Debug) {
-                    eprintln!(
+                let status = self.status;
+                log(LogCategory::CPU, LogLevel::Debug, || {
+                    format!(
                         "CPU: BRK executed at PC={:04X}, pushing {:04X}, status={:02X}",
-                        brk_pc, pc_to_push, self.status
-                    );
-                }
+                        brk_pc, pc_to_push, status
+                    )
+                });
                 self.push_u16(pc_to_push);
 
                 let mut s = self.status;
@@ -1431,21 +1437,29 @@ impl<M: Memory6502> Cpu6502<M> {
 
                 self.status |= 0x04; // set I
                 self.pc = self.read_u16(0xFFFE);
-                if LogConfig::global().should_log(LogCategory::CPU, LogLevel::Debug) {
-                    eprintln!("CPU: BRK jumped to {:04X}", self.pc);
-                }
+                let pc = self.pc;
+                log(LogCategory::CPU, LogLevel::Debug, || {
+                    format!("CPU: BRK jumped to {:04X}", pc)
+                });
                 self.cycles += 7;
                 7
             }
             _ => {
                 // Unknown opcode: treat as NOP to keep forward progress
-                if LogConfig::global().should_log(LogCategory::Stubs, LogLevel::Info) {
-                    let pc = self.pc.wrapping_sub(1);
-                    eprintln!(
+                let (pc, a, x, y, sp, p) = (
+                    self.pc.wrapping_sub(1),
+                    self.a,
+                    self.x,
+                    self.y,
+                    self.sp,
+                    self.status,
+                );
+                log(LogCategory::Stubs, LogLevel::Info, || {
+                    format!(
                         "UNKNOWN OPCODE: pc=0x{:04X} op=0x{:02X} a=0x{:02X} x=0x{:02X} y=0x{:02X} sp=0x{:02X} p=0x{:02X}",
-                        pc, op, self.a, self.x, self.y, self.sp, self.status
-                    );
-                }
+                        pc, op, a, x, y, sp, p
+                    )
+                });
                 self.cycles += 2;
                 2
             }
@@ -1456,10 +1470,12 @@ impl<M: Memory6502> Cpu6502<M> {
 /// Simple array-based memory implementation for testing
 #[derive(Debug)]
 pub struct ArrayMemory {
+    /// The full 64KB address space
     pub data: [u8; 0x10000],
 }
 
 impl ArrayMemory {
+    /// Create a zero-filled 64KB memory
     pub fn new() -> Self {
         Self { data: [0; 0x10000] }
     }
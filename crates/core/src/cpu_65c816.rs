@@ -4518,6 +4518,7 @@ pub struct ArrayMemory {
 }
 
 impl ArrayMemory {
+    /// Create a zero-filled 16MB memory
     pub fn new() -> Self {
         Self {
             data: vec![0; 16 * 1024 * 1024], // 16MB address space
@@ -9,8 +9,12 @@
 
 #![allow(clippy::unnecessary_cast)] // Many casts are intentional for clarity
 
-use crate::cpu_8086_protected::ProtectedModeState;
-use crate::logging::{LogCategory, LogConfig, LogLevel};
+use crate::cpu_8086_protected::{
+    access_rights, page_entry, system_types, tss286, tss386, GateDescriptor, ProtectedModeFault,
+    ProtectedModeState, SegmentCache, SegmentDescriptor,
+};
+use crate::logging::{log, LogCategory, LogConfig, LogLevel};
+use std::cell::{Cell, RefCell};
 
 /// CPU model/variant selection
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
@@ -222,21 +226,29 @@ pub struct Cpu8086<M: Memory8086> {
     /// Halt flag
     halted: bool,
 
+    /// Shut down by a triple fault; only a reset restarts the CPU
+    shutdown: bool,
+
     /// Segment override for next instruction
     /// Set by segment override prefixes (0x26 ES:, 0x2E CS:, 0x36 SS:, 0x3E DS:, 0x64 FS:, 0x65 GS:)
     /// Consumed and cleared after the next memory-accessing instruction
     segment_override: Option<SegmentOverride>,
 
-    /// Operand-size override for next instruction (0x66 prefix, 80386+)
-    /// When set, 16-bit operations become 32-bit and vice versa
-    /// Consumed and cleared after the next instruction
+    /// 32-bit operand size for the current instruction (80386+)
+    /// Set at the start of each instruction from the code segment's D bit and
+    /// toggled by the 0x66 prefix; cleared after the instruction
     operand_size_override: bool,
 
-    /// Address-size override for next instruction (0x67 prefix, 80386+)
-    /// When set, 16-bit addressing becomes 32-bit and vice versa
-    /// Consumed and cleared after the next instruction
+    /// 32-bit address size for the current instruction (80386+)
+    /// Set at the start of each instruction from the code segment's D bit and
+    /// toggled by the 0x67 prefix; cleared after the instruction
     address_size_override: bool,
 
+    /// REP prefix for a string instruction reached through further prefixes
+    /// (Some(true) for REP/REPE, Some(false) for REPNE); cleared after the
+    /// instruction
+    rep_prefix: Option<bool>,
+
     /// CPU model (8086, 80186, 80286, etc.)
     model: CpuModel,
 
@@ -261,6 +273,17 @@ pub struct Cpu8086<M: Memory8086> {
     /// Instruction start IP - saved at the beginning of each instruction
     /// Used for CPU exceptions to point to the faulting instruction
     instruction_start_ip: u32,
+
+    /// Protected mode fault raised during the current instruction (limit or
+    /// descriptor check), delivered once the instruction finishes
+    pending_fault: Cell<Option<ProtectedModeFault>>,
+
+    /// Page directory/table entries whose accessed (and dirty) bits must be
+    /// set, written back once the instruction finishes
+    page_updates: RefCell<Vec<(u32, u32)>>,
+
+    /// Debug registers DR0-DR7 (80386+; stored, breakpoints not implemented)
+    debug_regs: [u32; 8],
 }
 
 // Flag bit positions in FLAGS/EFLAGS register
@@ -278,6 +301,17 @@ const FLAG_TF: u32 = 0x0100; // Trap Flag
 const FLAG_IF: u32 = 0x0200; // Interrupt Enable Flag
 const FLAG_DF: u32 = 0x0400; // Direction Flag
 const FLAG_OF: u32 = 0x0800; // Overflow Flag
+const FLAG_IOPL: u32 = 0x3000; // I/O Privilege Level (2 bits)
+const FLAG_NT: u32 = 0x4000; // Nested Task
+const FLAG_VM: u32 = 0x0002_0000; // Virtual 8086 Mode (80386)
+
+// Segment register indices (ModR/M encoding)
+const SEG_ES: u8 = 0;
+const SEG_CS: u8 = 1;
+const SEG_SS: u8 = 2;
+const SEG_DS: u8 = 3;
+const SEG_FS: u8 = 4;
+const SEG_GS: u8 = 5;
 
 impl<M: Memory8086> Cpu8086<M> {
     /// Create a new 8086 CPU with the given memory interface
@@ -307,7 +341,9 @@ impl<M: Memory8086> Cpu8086<M> {
             cycles: 0,
             memory,
             halted: false,
+            shutdown: false,
             segment_override: None,
+            rep_prefix: None,
             operand_size_override: false,
             address_size_override: false,
             model,
@@ -316,6 +352,9 @@ impl<M: Memory8086> Cpu8086<M> {
             msrs: std::collections::HashMap::new(),
             mmx_regs: [0; 8],
             instruction_start_ip: 0,
+            pending_fault: Cell::new(None),
+            page_updates: RefCell::new(Vec::new()),
+            debug_regs: [0; 8],
         }
     }
 
@@ -349,6 +388,7 @@ impl<M: Memory8086> Cpu8086<M> {
         self.flags = 0x0002;
         self.cycles = 0;
         self.halted = false;
+        self.shutdown = false;
         // Note: model is preserved across reset
         // Reset protected mode state
         self.protected_mode.reset();
@@ -357,6 +397,9 @@ impl<M: Memory8086> Cpu8086<M> {
         self.msrs.clear();
         // Reset MMX registers
         self.mmx_regs = [0; 8];
+        self.pending_fault.set(None);
+        self.page_updates.borrow_mut().clear();
+        self.debug_regs = [0; 8];
     }
 
     /// Get reference to protected mode state (80286+ only)
@@ -374,6 +417,14 @@ impl<M: Memory8086> Cpu8086<M> {
         self.halted
     }
 
+    /// Check if a triple fault shut the CPU down
+    ///
+    /// A PC/AT motherboard turns the shutdown cycle into a CPU reset, so
+    /// systems should [`reset`](Self::reset) when this is set.
+    pub fn is_shut_down(&self) -> bool {
+        self.shutdown
+    }
+
     /// Set the CPU halted state
     /// When halted, the CPU will not execute instructions until an interrupt occurs or it is unhalted
     pub fn set_halted(&mut self, halted: bool) {
@@ -397,20 +448,50 @@ impl<M: Memory8086> Cpu8086<M> {
 
     /// Calculate physical address from segment:offset
     #[inline]
-    fn physical_address(segment: u16, offset: u16) -> u32 {
-        ((segment as u32) << 4) + (offset as u32)
+    fn physical_address(segment: u16, offset: u32) -> u32 {
+        ((segment as u32) << 4).wrapping_add(offset)
     }
 
     /// Read a byte from memory using segment:offset
     #[inline]
     fn read(&self, segment: u16, offset: u16) -> u8 {
-        let addr = Self::physical_address(segment, offset);
-        self.memory.read(addr)
+        self.read_at(segment, offset as u32)
     }
 
     /// Write a byte to memory using segment:offset
     #[inline]
     fn write(&mut self, segment: u16, offset: u16, val: u8) {
+        self.write_at(segment, offset as u32, val);
+    }
+
+    /// Read a byte at segment:offset with a 32-bit offset (80386+ addressing)
+    #[inline]
+    fn read_at(&self, segment: u16, offset: u32) -> u8 {
+        if self.protected_mode.is_protected_mode() {
+            return match self
+                .protected_linear_address(segment, offset)
+                .and_then(|linear| self.translate_page(linear, false))
+            {
+                Some(addr) => self.memory.read(addr),
+                None => 0xFF,
+            };
+        }
+        let addr = Self::physical_address(segment, offset);
+        self.memory.read(addr)
+    }
+
+    /// Write a byte at segment:offset with a 32-bit offset (80386+ addressing)
+    #[inline]
+    fn write_at(&mut self, segment: u16, offset: u32, val: u8) {
+        if self.protected_mode.is_protected_mode() {
+            if let Some(addr) = self
+                .protected_linear_address(segment, offset)
+                .and_then(|linear| self.translate_page(linear, true))
+            {
+                self.memory.write(addr, val);
+            }
+            return;
+        }
         let addr = Self::physical_address(segment, offset);
         self.memory.write(addr, val);
     }
@@ -418,8 +499,9 @@ impl<M: Memory8086> Cpu8086<M> {
     /// Read a byte from code segment at IP
     #[inline]
     fn fetch_u8(&mut self) -> u8 {
-        let val = self.read(self.cs, self.ip as u16);
-        self.ip = (self.ip.wrapping_add(1)) & 0xFFFF; // Keep in 16-bit range for now
+        let val = self.read_at(self.cs, self.ip);
+        // IP wraps at 64KB unless the code segment is 32-bit
+        self.ip = self.ip.wrapping_add(1) & self.code_offset_mask();
         val
     }
 
@@ -459,12 +541,28 @@ impl<M: Memory8086> Cpu8086<M> {
         self.write(segment, offset.wrapping_add(1), hi);
     }
 
+    /// Read a word at segment:offset with a 32-bit offset
+    #[inline]
+    fn read_u16_at(&self, segment: u16, offset: u32) -> u16 {
+        let low_byte = self.read_at(segment, offset) as u16;
+        let high_byte = self.read_at(segment, offset.wrapping_add(1)) as u16;
+        (high_byte << 8) | low_byte
+    }
+
+    /// Write a word at segment:offset with a 32-bit offset
+    #[inline]
+    fn write_u16_at(&mut self, segment: u16, offset: u32, val: u16) {
+        let [lo, hi] = val.to_le_bytes();
+        self.write_at(segment, offset, lo);
+        self.write_at(segment, offset.wrapping_add(1), hi);
+    }
+
     /// Read a dword (32-bit) from memory at segment:offset
     #[inline]
     fn read_u32(&self, segment: u16, offset: u32) -> u32 {
         // x86 is little-endian: read low word first, then high word
-        let low_word = self.read_u16(segment, offset as u16) as u32;
-        let high_word = self.read_u16(segment, (offset as u16).wrapping_add(2)) as u32;
+        let low_word = self.read_u16_at(segment, offset) as u32;
+        let high_word = self.read_u16_at(segment, offset.wrapping_add(2)) as u32;
         (high_word << 16) | low_word
     }
 
@@ -473,31 +571,138 @@ impl<M: Memory8086> Cpu8086<M> {
     fn write_u32(&mut self, segment: u16, offset: u32, val: u32) {
         let low_word = (val & 0xFFFF) as u16;
         let high_word = ((val >> 16) & 0xFFFF) as u16;
-        self.write_u16(segment, offset as u16, low_word);
-        self.write_u16(segment, (offset as u16).wrapping_add(2), high_word);
+        self.write_u16_at(segment, offset, low_word);
+        self.write_u16_at(segment, offset.wrapping_add(2), high_word);
+    }
+
+    /// Jump relative to IP; the result wraps at 64KB with a 16-bit operand size
+    #[inline]
+    fn jump_relative(&mut self, disp: i32) {
+        let ip = self.ip.wrapping_add(disp as u32);
+        self.ip = if self.operand_size_override {
+            ip
+        } else {
+            ip & 0xFFFF
+        };
+    }
+
+    /// Mask of the count register: ECX with a 32-bit address size, else CX
+    #[inline]
+    fn count_mask(&self) -> u32 {
+        if self.address_size_override {
+            0xFFFF_FFFF
+        } else {
+            0xFFFF
+        }
+    }
+
+    /// Decrement CX (or ECX) for LOOP and REP, returning the new count
+    #[inline]
+    fn decrement_count(&mut self) -> u32 {
+        let mask = self.count_mask();
+        let count = self.cx.wrapping_sub(1) & mask;
+        self.cx = (self.cx & !mask) | count;
+        count
+    }
+
+    /// Whether CS is a 32-bit code segment (D bit set, protected mode only)
+    #[inline]
+    fn code_segment_is_32bit(&self) -> bool {
+        self.protected_mode.is_protected_mode()
+            && self.protected_mode.segment_cache[SEG_CS as usize].big
+    }
+
+    /// Mask applied to IP: 32-bit code segments use all of EIP
+    #[inline]
+    fn code_offset_mask(&self) -> u32 {
+        if self.code_segment_is_32bit() {
+            0xFFFF_FFFF
+        } else {
+            0xFFFF
+        }
+    }
+
+    /// Mask applied to SP: a 32-bit stack segment (B bit) uses all of ESP
+    #[inline]
+    fn stack_offset_mask(&self) -> u32 {
+        if self.protected_mode.is_protected_mode()
+            && self.protected_mode.segment_cache[SEG_SS as usize].big
+        {
+            0xFFFF_FFFF
+        } else {
+            0xFFFF
+        }
+    }
+
+    /// Move SP by `delta` bytes, keeping the upper half of ESP on a 16-bit stack
+    #[inline]
+    fn adjust_sp(&mut self, delta: i32) {
+        let mask = self.stack_offset_mask();
+        self.sp = (self.sp & !mask) | (self.sp.wrapping_add(delta as u32) & mask);
+    }
+
+    /// Current stack offset (SP or ESP)
+    #[inline]
+    fn stack_offset(&self) -> u32 {
+        self.sp & self.stack_offset_mask()
     }
 
     /// Push a word onto the stack
     #[inline]
     fn push(&mut self, val: u16) {
-        self.sp = (self.sp.wrapping_sub(2u32)) & 0xFFFF; // Keep in 16-bit range for now
-        self.write_u16(self.ss, self.sp as u16, val);
+        self.adjust_sp(-2);
+        self.write_u16_at(self.ss, self.stack_offset(), val);
     }
 
     /// Pop a word from the stack
     #[inline]
     fn pop(&mut self) -> u16 {
-        let val = self.read_u16(self.ss, self.sp as u16);
-        self.sp = (self.sp.wrapping_add(2u32)) & 0xFFFF; // Keep in 16-bit range for now
+        let val = self.read_u16_at(self.ss, self.stack_offset());
+        self.adjust_sp(2);
+        val
+    }
+
+    /// Push a dword onto the stack (32-bit operand size)
+    #[inline]
+    fn push32(&mut self, val: u32) {
+        self.adjust_sp(-4);
+        self.write_u32(self.ss, self.stack_offset(), val);
+    }
+
+    /// Pop a dword from the stack (32-bit operand size)
+    #[inline]
+    fn pop32(&mut self) -> u32 {
+        let val = self.read_u32(self.ss, self.stack_offset());
+        self.adjust_sp(4);
         val
     }
 
+    /// POP Sreg: SP only moves if the segment load succeeds
+    fn pop_segment(&mut self, seg: u8) {
+        let val = self.read_u16_at(self.ss, self.stack_offset());
+        if self.load_segment_or_fault(seg, val) {
+            let size = if self.operand_size_override { 4 } else { 2 };
+            self.adjust_sp(size);
+        }
+    }
+
     /// Trigger a software interrupt (INT) or CPU exception
     ///
     /// For software interrupts (is_exception=false): Uses current IP (after INT instruction)
     /// For CPU exceptions (is_exception=true): Uses instruction_start_ip (faulting instruction)
     #[inline]
     fn trigger_interrupt(&mut self, int_num: u8, is_exception: bool) {
+        if self.protected_mode.is_protected_mode() {
+            // Protected mode: dispatch through the IDT
+            if is_exception {
+                self.ip = self.instruction_start_ip;
+                self.protected_mode_interrupt(int_num, InterruptSource::Exception(None));
+            } else {
+                self.protected_mode_interrupt(int_num, InterruptSource::Software);
+            }
+            return;
+        }
+
         // Push FLAGS, CS, IP onto stack (in that order)
         self.push(self.flags as u16); // Only push low 16 bits of flags for now
         self.push(self.cs);
@@ -544,7 +749,11 @@ impl<M: Memory8086> Cpu8086<M> {
         }
 
         // Trigger the interrupt (hardware interrupts are not exceptions)
-        self.trigger_interrupt(int_num, false);
+        if self.protected_mode.is_protected_mode() {
+            self.protected_mode_interrupt(int_num, InterruptSource::Hardware);
+        } else {
+            self.trigger_interrupt(int_num, false);
+        }
         true
     }
 
@@ -741,7 +950,9 @@ impl<M: Memory8086> Cpu8086<M> {
             1 => self.cs,
             2 => self.ss,
             3 => self.ds,
-            _ => panic!("Invalid segment register index: {} (must be 0-3)", seg),
+            4 => self.fs,
+            5 => self.gs,
+            _ => panic!("Invalid segment register index: {} (must be 0-5)", seg),
         }
     }
 
@@ -754,7 +965,9 @@ impl<M: Memory8086> Cpu8086<M> {
             1 => self.cs = val,
             2 => self.ss = val,
             3 => self.ds = val,
-            _ => panic!("Invalid segment register index: {} (must be 0-3)", seg),
+            4 => self.fs = val,
+            5 => self.gs = val,
+            _ => panic!("Invalid segment register index: {} (must be 0-5)", seg),
         }
     }
 
@@ -1240,7 +1453,11 @@ impl<M: Memory8086> Cpu8086<M> {
 
     /// Calculate effective address from ModR/M byte
     /// Returns (segment, offset) and number of additional bytes consumed
-    fn calc_effective_address(&mut self, modbits: u8, rm: u8) -> (u16, u16, u8) {
+    /// (uses 32-bit ModR/M/SIB decoding when the address size is 32-bit)
+    fn calc_effective_address(&mut self, modbits: u8, rm: u8) -> (u16, u32, u8) {
+        if self.address_size_override {
+            return self.calc_effective_address_32(modbits, rm);
+        }
         let (default_seg, offset, bytes_read) = match modbits {
             // mod = 00: Memory mode with no displacement (except for special case rm=110)
             0b00 => {
@@ -1344,14 +1561,20 @@ impl<M: Memory8086> Cpu8086<M> {
 
         // Apply segment override if present
         let seg = self.get_segment_with_override(default_seg);
-        (seg, offset, bytes_read)
+        (seg, offset as u32, bytes_read)
     }
 
     /// Calculate effective offset from ModR/M byte without consuming segment override
     /// Used by LEA which doesn't access memory
     /// Returns offset only
-    fn calc_effective_offset(&mut self, modbits: u8, rm: u8) -> u16 {
-        match modbits {
+    fn calc_effective_offset(&mut self, modbits: u8, rm: u8) -> u32 {
+        if self.address_size_override {
+            let saved = self.segment_override;
+            let (_, offset, _) = self.calc_effective_address_32(modbits, rm);
+            self.segment_override = saved;
+            return offset;
+        }
+        let offset = match modbits {
             // mod = 00: Memory mode with no displacement (except for special case rm=110)
             0b00 => {
                 match rm {
@@ -1417,6 +1640,27 @@ impl<M: Memory8086> Cpu8086<M> {
             }
             // mod = 11: Register mode (no memory access)
             _ => 0, // Not used for register mode
+        };
+        offset as u32
+    }
+
+    /// Read a word at an effective address (wraps at 64KB with 16-bit addressing)
+    #[inline]
+    fn read_ea_u16(&self, segment: u16, offset: u32) -> u16 {
+        if self.address_size_override {
+            self.read_u16_at(segment, offset)
+        } else {
+            self.read_u16(segment, offset as u16)
+        }
+    }
+
+    /// Write a word at an effective address (wraps at 64KB with 16-bit addressing)
+    #[inline]
+    fn write_ea_u16(&mut self, segment: u16, offset: u32, val: u16) {
+        if self.address_size_override {
+            self.write_u16_at(segment, offset, val);
+        } else {
+            self.write_u16(segment, offset as u16, val);
         }
     }
 
@@ -1573,7 +1817,7 @@ impl<M: Memory8086> Cpu8086<M> {
         } else {
             // Memory mode
             let (seg, offset, _) = self.calc_effective_address(modbits, rm);
-            self.read(seg, offset)
+            self.read_at(seg, offset)
         }
     }
 
@@ -1589,7 +1833,7 @@ impl<M: Memory8086> Cpu8086<M> {
         } else {
             // Memory mode
             let (seg, offset, _) = self.calc_effective_address(modbits, rm);
-            self.write(seg, offset, val);
+            self.write_at(seg, offset, val);
         }
     }
 
@@ -1601,7 +1845,7 @@ impl<M: Memory8086> Cpu8086<M> {
         } else {
             // Memory mode
             let (seg, offset, _) = self.calc_effective_address(modbits, rm);
-            self.read_u16(seg, offset)
+            self.read_ea_u16(seg, offset)
         }
     }
 
@@ -1613,39 +1857,39 @@ impl<M: Memory8086> Cpu8086<M> {
         } else {
             // Memory mode
             let (seg, offset, _) = self.calc_effective_address(modbits, rm);
-            self.write_u16(seg, offset, val);
+            self.write_ea_u16(seg, offset, val);
         }
     }
 
     /// Helper for Read-Modify-Write operations on 16-bit values
     /// Returns (value_read, seg, offset) to avoid double-fetching EA
-    fn read_rmw16(&mut self, modbits: u8, rm: u8) -> (u16, u16, u16) {
+    fn read_rmw16(&mut self, modbits: u8, rm: u8) -> (u16, u16, u32) {
         if modbits == 0b11 {
             // Register mode - return dummy seg/offset
             (self.get_reg16(rm), 0, 0)
         } else {
             // Memory mode - calculate EA once and return it
             let (seg, offset, _) = self.calc_effective_address(modbits, rm);
-            let val = self.read_u16(seg, offset);
+            let val = self.read_ea_u16(seg, offset);
             (val, seg, offset)
         }
     }
 
     /// Helper for writing result of Read-Modify-Write operations on 16-bit values
     /// Uses cached seg/offset to avoid recalculating EA
-    fn write_rmw16(&mut self, modbits: u8, rm: u8, val: u16, seg: u16, offset: u16) {
+    fn write_rmw16(&mut self, modbits: u8, rm: u8, val: u16, seg: u16, offset: u32) {
         if modbits == 0b11 {
             // Register mode
             self.set_reg16(rm, val);
         } else {
             // Memory mode - use cached seg/offset
-            self.write_u16(seg, offset, val);
+            self.write_ea_u16(seg, offset, val);
         }
     }
 
     /// Helper for Read-Modify-Write operations on 8-bit values
     /// Returns (value_read, seg, offset) to avoid double-fetching EA
-    fn read_rmw8(&mut self, modbits: u8, rm: u8) -> (u8, u16, u16) {
+    fn read_rmw8(&mut self, modbits: u8, rm: u8) -> (u8, u16, u32) {
         if modbits == 0b11 {
             // Register mode - return dummy seg/offset
             let val = if rm < 4 {
@@ -1657,14 +1901,14 @@ impl<M: Memory8086> Cpu8086<M> {
         } else {
             // Memory mode - calculate EA once and return it
             let (seg, offset, _) = self.calc_effective_address(modbits, rm);
-            let val = self.read(seg, offset);
+            let val = self.read_at(seg, offset);
             (val, seg, offset)
         }
     }
 
     /// Helper for writing result of Read-Modify-Write operations on 8-bit values
     /// Uses cached seg/offset to avoid recalculating EA
-    fn write_rmw8(&mut self, modbits: u8, rm: u8, val: u8, seg: u16, offset: u16) {
+    fn write_rmw8(&mut self, modbits: u8, rm: u8, val: u8, seg: u16, offset: u32) {
         if modbits == 0b11 {
             // Register mode
             if rm < 4 {
@@ -1674,7 +1918,7 @@ impl<M: Memory8086> Cpu8086<M> {
             }
         } else {
             // Memory mode - use cached seg/offset
-            self.write(seg, offset, val);
+            self.write_at(seg, offset, val);
         }
     }
 
@@ -1744,8 +1988,359 @@ impl<M: Memory8086> Cpu8086<M> {
         }
     }
 
+    /// Read a register of the current operand size (16 or 32-bit)
+    fn get_reg_v(&self, reg: u8) -> u32 {
+        if self.operand_size_override {
+            self.get_reg32(reg)
+        } else {
+            self.get_reg16(reg) as u32
+        }
+    }
+
+    /// Write a register of the current operand size (16 or 32-bit)
+    fn set_reg_v(&mut self, reg: u8, val: u32) {
+        if self.operand_size_override {
+            self.set_reg32(reg, val);
+        } else {
+            self.set_reg16(reg, val as u16);
+        }
+    }
+
+    /// Read r/m of the current operand size (16 or 32-bit)
+    fn read_rm_v(&mut self, modbits: u8, rm: u8) -> u32 {
+        if self.operand_size_override {
+            self.read_rm32(modbits, rm)
+        } else {
+            self.read_rm16(modbits, rm) as u32
+        }
+    }
+
+    /// Read-modify-write access to r/m of the current operand size
+    fn read_rmw_v(&mut self, modbits: u8, rm: u8) -> (u32, u16, u32) {
+        if self.operand_size_override {
+            self.read_rmw32(modbits, rm)
+        } else {
+            let (val, seg, offset) = self.read_rmw16(modbits, rm);
+            (val as u32, seg, offset)
+        }
+    }
+
+    /// Write back the result of [`Self::read_rmw_v`]
+    fn write_rmw_v(&mut self, modbits: u8, rm: u8, val: u32, seg: u16, offset: u32) {
+        if self.operand_size_override {
+            self.write_rmw32(modbits, rm, val, seg, offset);
+        } else {
+            self.write_rmw16(modbits, rm, val as u16, seg, offset);
+        }
+    }
+
+    /// Width in bits of the current operand size
+    #[inline]
+    fn operand_bits(&self) -> u32 {
+        if self.operand_size_override {
+            32
+        } else {
+            16
+        }
+    }
+
+    /// Read a far pointer (offset16:16 or offset32:16) from memory
+    fn read_far_pointer(&mut self, modbits: u8, rm: u8) -> (u32, u16) {
+        let (seg, offset_ea, _) = self.calc_effective_address(modbits, rm);
+        if self.operand_size_override {
+            let offset = self.read_u32(seg, offset_ea);
+            (offset, self.read_u16_at(seg, offset_ea.wrapping_add(4)))
+        } else {
+            let offset = self.read_ea_u16(seg, offset_ea);
+            (
+                offset as u32,
+                self.read_ea_u16(seg, offset_ea.wrapping_add(2)),
+            )
+        }
+    }
+
+    /// LDS/LES/LSS/LFS/LGS: load a far pointer into a segment register and
+    /// a general register
+    fn load_far_pointer(&mut self, seg_reg: u8, modbits: u8, reg: u8, rm: u8) {
+        // Only memory operands are valid
+        if modbits != 0b11 {
+            let (offset, segment) = self.read_far_pointer(modbits, rm);
+            if self.load_segment_or_fault(seg_reg, segment) {
+                self.set_reg_v(reg, offset);
+            }
+        }
+    }
+
+    /// 32-bit ALU operation in group 1 order (ADD, OR, ADC, SBB, AND, SUB,
+    /// XOR, CMP), setting flags and returning the result
+    fn alu_op_32(&mut self, op: u8, a: u32, b: u32) -> u32 {
+        let carry_in = self.get_flag(FLAG_CF) as u32;
+        let (result, carry, overflow, af) = match op {
+            0 | 2 => {
+                let c = if op == 2 { carry_in } else { 0 };
+                let r = a.wrapping_add(b).wrapping_add(c);
+                let carry = (a as u64 + b as u64 + c as u64) > 0xFFFF_FFFF;
+                let overflow = ((a ^ r) & (b ^ r) & 0x8000_0000) != 0;
+                (
+                    r,
+                    carry,
+                    overflow,
+                    (((a & 0x0F) + (b & 0x0F) + c) & 0x10) != 0,
+                )
+            }
+            3 | 5 | 7 => {
+                let c = if op == 3 { carry_in } else { 0 };
+                let r = a.wrapping_sub(b).wrapping_sub(c);
+                let borrow = (a as u64) < (b as u64 + c as u64);
+                let overflow = ((a ^ b) & (a ^ r) & 0x8000_0000) != 0;
+                (r, borrow, overflow, (a & 0x0F) < ((b & 0x0F) + c))
+            }
+            1 => (a | b, false, false, self.get_flag(FLAG_AF)),
+            4 => (a & b, false, false, self.get_flag(FLAG_AF)),
+            _ => (a ^ b, false, false, self.get_flag(FLAG_AF)),
+        };
+        self.update_flags_32(result);
+        self.set_flag(FLAG_CF, carry);
+        self.set_flag(FLAG_OF, overflow);
+        self.set_flag(FLAG_AF, af);
+        result
+    }
+
+    /// Read a byte, word or dword for a string instruction
+    fn read_string(&self, segment: u16, offset: u32, width: u32) -> u32 {
+        match width {
+            1 => self.read_at(segment, offset) as u32,
+            2 => self.read_u16_at(segment, offset) as u32,
+            _ => self.read_u32(segment, offset),
+        }
+    }
+
+    /// Write a byte, word or dword for a string instruction
+    fn write_string(&mut self, segment: u16, offset: u32, width: u32, val: u32) {
+        match width {
+            1 => self.write_at(segment, offset, val as u8),
+            2 => self.write_u16_at(segment, offset, val as u16),
+            _ => self.write_u32(segment, offset, val),
+        }
+    }
+
+    /// Compare (a - b) for CMPS/SCAS, setting flags for the operand width
+    fn compare_string(&mut self, a: u32, b: u32, width: u32) {
+        match width {
+            1 => {
+                let (a, b) = (a as u8, b as u8);
+                let result = a.wrapping_sub(b);
+                self.update_flags_8(result);
+                self.set_flag(FLAG_CF, a < b);
+                self.set_flag(FLAG_OF, ((a ^ b) & (a ^ result) & 0x80) != 0);
+                self.set_flag(FLAG_AF, Self::calc_af_sub_8(a, b));
+            }
+            2 => {
+                let (a, b) = (a as u16, b as u16);
+                let result = a.wrapping_sub(b);
+                self.update_flags_16(result);
+                self.set_flag(FLAG_CF, a < b);
+                self.set_flag(FLAG_OF, ((a ^ b) & (a ^ result) & 0x8000) != 0);
+                self.set_flag(FLAG_AF, Self::calc_af_sub_16(a, b));
+            }
+            _ => {
+                self.alu_op_32(7, a, b);
+            }
+        }
+    }
+
+    /// MOVS/CMPS/STOS/LODS/SCAS with a 32-bit operand or address size, or
+    /// with a REP prefix that was followed by other prefixes. A repeated
+    /// instruction stops at a fault so it can be restarted with the
+    /// registers as they were after the last completed iteration.
+    fn string_op(&mut self, opcode: u8) -> u32 {
+        let width: u32 = if opcode & 1 == 0 {
+            1
+        } else if self.operand_size_override {
+            4
+        } else {
+            2
+        };
+        let mask = self.count_mask();
+        let src_seg = self.get_segment_with_override(self.ds);
+        let step = if self.get_flag(FLAG_DF) {
+            width.wrapping_neg()
+        } else {
+            width
+        };
+        let uses_si = !matches!(opcode, 0xAA | 0xAB | 0xAE | 0xAF);
+        let uses_di = !matches!(opcode, 0xAC | 0xAD);
+        let acc_mask = match width {
+            1 => 0xFF,
+            2 => 0xFFFF,
+            _ => 0xFFFF_FFFF,
+        };
+        let mut cycles = 0;
+
+        loop {
+            if self.rep_prefix.is_some() && self.cx & mask == 0 {
+                break;
+            }
+            let (si, di) = (self.si & mask, self.di & mask);
+            match opcode {
+                0xA4 | 0xA5 => {
+                    let val = self.read_string(src_seg, si, width);
+                    self.write_string(self.es, di, width, val);
+                }
+                0xA6 | 0xA7 => {
+                    let src = self.read_string(src_seg, si, width);
+                    let dst = self.read_string(self.es, di, width);
+                    self.compare_string(src, dst, width);
+                }
+                0xAA | 0xAB => self.write_string(self.es, di, width, self.ax),
+                0xAC | 0xAD => {
+                    let val = self.read_string(src_seg, si, width);
+                    self.ax = (self.ax & !acc_mask) | val;
+                }
+                _ => {
+                    let val = self.read_string(self.es, di, width);
+                    self.compare_string(self.ax & acc_mask, val, width);
+                }
+            }
+            if self.pending_fault.get().is_some() {
+                break;
+            }
+            if uses_si {
+                self.si = (self.si & !mask) | (si.wrapping_add(step) & mask);
+            }
+            if uses_di {
+                self.di = (self.di & !mask) | (di.wrapping_add(step) & mask);
+            }
+            cycles += if matches!(opcode, 0xA6 | 0xA7 | 0xAE | 0xAF) {
+                15
+            } else {
+                17
+            };
+
+            let Some(repe) = self.rep_prefix else {
+                break;
+            };
+            self.decrement_count();
+            if matches!(opcode, 0xA6 | 0xA7 | 0xAE | 0xAF) && self.get_flag(FLAG_ZF) != repe {
+                break;
+            }
+        }
+
+        let cycles = cycles.max(4);
+        self.cycles += cycles as u64;
+        cycles
+    }
+
+    /// Whether a REP prefix must be handled by [`Self::string_op`]: it is
+    /// followed by another prefix, or the instruction uses 32-bit operands
+    /// or addresses
+    fn rep_needs_prefix_path(&self) -> bool {
+        let next = self.read_at(self.cs, self.ip);
+        match next {
+            0x26 | 0x2E | 0x36 | 0x3E => true,
+            0x64..=0x67 => self.model.supports_80386_instructions(),
+            0xA4..=0xA7 | 0xAA..=0xAF => self.operand_size_override || self.address_size_override,
+            _ => false,
+        }
+    }
+
+    /// Two/three operand IMUL: reg = a * b, truncated to the operand size
+    /// (CF/OF set when the result does not fit)
+    fn imul_v(&mut self, reg: u8, a: u32, b: u32) {
+        let (result, fits) = if self.operand_size_override {
+            let full = (a as i32 as i64) * (b as i32 as i64);
+            (full as u32, full == full as i32 as i64)
+        } else {
+            let full = (a as i16 as i32) * (b as i16 as i32);
+            (full as u16 as u32, full == full as i16 as i32)
+        };
+        self.set_reg_v(reg, result);
+        self.set_flag(FLAG_CF, !fits);
+        self.set_flag(FLAG_OF, !fits);
+    }
+
+    /// BT/BTS/BTR/BTC r/m, reg (op 0-3). With a memory operand the bit
+    /// offset can address bits outside the word/dword at the effective address.
+    fn bit_test_op(&mut self, op: u8, modbits: u8, rm: u8, bit_offset: u32) {
+        let bits = self.operand_bits();
+        let bit = bit_offset & (bits - 1);
+        let (val, seg, offset) = if modbits == 0b11 {
+            self.read_rmw_v(modbits, rm)
+        } else {
+            // Bit string: move the operand by whole words/dwords
+            let (seg, offset, _) = self.calc_effective_address(modbits, rm);
+            let index = if bits == 32 {
+                (bit_offset as i32) >> 5
+            } else {
+                (bit_offset as i16 as i32) >> 4
+            };
+            let offset = offset.wrapping_add((index * (bits as i32 / 8)) as u32);
+            let val = if bits == 32 {
+                self.read_u32(seg, offset)
+            } else {
+                self.read_ea_u16(seg, offset) as u32
+            };
+            (val, seg, offset)
+        };
+        self.set_flag(FLAG_CF, (val >> bit) & 1 != 0);
+        let result = match op {
+            1 => val | (1 << bit),
+            2 => val & !(1 << bit),
+            3 => val ^ (1 << bit),
+            _ => return,
+        };
+        self.write_rmw_v(modbits, rm, result, seg, offset);
+    }
+
+    /// SHLD/SHRD: shift r/m, filling from the bits of a register
+    fn double_shift(&mut self, modbits: u8, reg: u8, rm: u8, left: bool, count_in_cl: bool) {
+        let bits = self.operand_bits();
+        let (dest, seg, offset) = self.read_rmw_v(modbits, rm);
+        // An immediate count follows any displacement bytes
+        let count = if count_in_cl {
+            self.cx as u8
+        } else {
+            self.fetch_u8()
+        };
+        let count = (count & 0x1F) as u32;
+        if count == 0 || count > bits {
+            return;
+        }
+        let src = self.get_reg_v(reg);
+        let mask = if bits == 32 { u64::MAX >> 32 } else { 0xFFFF };
+        let sign = 1u64 << (bits - 1);
+        let (result, carry) = if left {
+            let wide = ((dest as u64) << bits) | src as u64;
+            let shifted = wide << count;
+            (
+                (shifted >> bits) & mask,
+                (wide >> (2 * bits - count)) & 1 != 0,
+            )
+        } else {
+            let wide = ((src as u64) << bits) | dest as u64;
+            ((wide >> count) & mask, (wide >> (count - 1)) & 1 != 0)
+        };
+        self.write_rmw_v(modbits, rm, result as u32, seg, offset);
+        if bits == 32 {
+            self.update_flags_32(result as u32);
+        } else {
+            self.update_flags_16(result as u16);
+        }
+        self.set_flag(FLAG_CF, carry);
+        self.set_flag(FLAG_OF, (result ^ dest as u64) & sign != 0);
+    }
+
     /// Execute one instruction and return cycles used
     pub fn step(&mut self) -> u32 {
+        let cycles = self.execute();
+        self.flush_page_updates();
+        self.deliver_pending_fault();
+        cycles
+    }
+
+    /// Execute one instruction (faults recorded by protected mode checks are
+    /// delivered by `step` afterwards)
+    fn execute(&mut self) -> u32 {
         if self.halted {
             // Even when halted, TSC continues to increment
             if self.model.supports_pentium_instructions() {
@@ -1757,58 +2352,336 @@ impl<M: Memory8086> Cpu8086<M> {
         // Save instruction start IP for CPU exceptions
         self.instruction_start_ip = self.ip;
 
+        // A 32-bit code segment defaults to 32-bit operands and addresses
+        let code32 = self.code_segment_is_32bit();
+        self.operand_size_override = code32;
+        self.address_size_override = code32;
+
+        self.execute_opcode()
+    }
+
+    /// Fetch and execute an opcode; prefixes recurse into this so the
+    /// instruction start IP keeps pointing at the first prefix
+    fn execute_opcode(&mut self) -> u32 {
         let opcode = self.fetch_u8();
 
         let cycles_executed = match opcode {
-            // REP/REPE/REPZ prefix (0xF3)
-            0xF3 => {
-                let next_opcode = self.fetch_u8();
-                let mut total_cycles: u32 = 9; // Base prefix overhead
+            // 80386+ forms: 32-bit operand/address size and REP with further
+            // prefixes. The 16-bit forms of these opcodes are handled below.
+
+            // REP/REPNE followed by another prefix (segment, operand or address
+            // size) or by a 32-bit string instruction
+            0xF2 | 0xF3 if self.rep_needs_prefix_path() => {
+                self.rep_prefix = Some(opcode == 0xF3);
+                self.execute_opcode()
+            }
+
+            // MOVS/CMPS/STOS/LODS/SCAS with 32-bit operands or addresses, or
+            // repeated after further prefixes
+            0xA4..=0xA7 | 0xAA..=0xAF
+                if self.operand_size_override
+                    || self.address_size_override
+                    || self.rep_prefix.is_some() =>
+            {
+                self.string_op(opcode)
+            }
+
+            // ADD/OR/AND/SUB/XOR/CMP EAX, imm32
+            0x05 | 0x0D | 0x25 | 0x2D | 0x35 | 0x3D if self.operand_size_override => {
+                let val = self.fetch_u32();
+                let op = (opcode >> 3) & 0x07;
+                let result = self.alu_op_32(op, self.ax, val);
+                if op != 7 {
+                    self.ax = result;
+                }
+                self.cycles += 2;
+                2
+            }
 
-                match next_opcode {
-                    // MOVSB
-                    0xA4 => {
-                        // Apply segment override to source (DS:SI), destination is always ES:DI
-                        // Consume override once before the loop
-                        let src_seg = self.get_segment_with_override(self.ds);
-                        while (self.cx & 0xFFFF) != 0 {
-                            let val = self.read(src_seg, self.si as u16);
-                            self.write(self.es, self.di as u16, val);
-                            if self.get_flag(FLAG_DF) {
-                                self.si = self.si.wrapping_sub(1);
-                                self.di = self.di.wrapping_sub(1);
-                            } else {
-                                self.si = self.si.wrapping_add(1);
-                                self.di = self.di.wrapping_add(1);
-                            }
-                            let cx = ((self.cx as u16).wrapping_sub(1)) as u32;
-                            self.cx = (self.cx & 0xFFFF_0000) | cx;
-                            total_cycles += 17;
-                        }
-                        self.cycles += total_cycles as u64;
-                        total_cycles
-                    }
-                    // MOVSW
-                    0xA5 => {
-                        // Apply segment override to source (DS:SI), destination is always ES:DI
-                        // Consume override once before the loop
-                        let src_seg = self.get_segment_with_override(self.ds);
-                        while (self.cx & 0xFFFF) != 0 {
-                            let val = self.read_u16(src_seg, self.si as u16);
-                            self.write_u16(self.es, self.di as u16, val);
-                            if self.get_flag(FLAG_DF) {
-                                self.si = self.si.wrapping_sub(2u32);
-                                self.di = self.di.wrapping_sub(2u32);
-                            } else {
-                                self.si = self.si.wrapping_add(2u32);
-                                self.di = self.di.wrapping_add(2u32);
-                            }
-                            let cx = ((self.cx as u16).wrapping_sub(1)) as u32;
-                            self.cx = (self.cx & 0xFFFF_0000) | cx;
-                            total_cycles += 17;
-                        }
-                        self.cycles += total_cycles as u64;
-                        total_cycles
+            // TEST EAX, imm32 (0xA9)
+            0xA9 if self.operand_size_override => {
+                let val = self.fetch_u32();
+                self.update_flags_32(self.ax & val);
+                self.set_flag(FLAG_CF, false);
+                self.set_flag(FLAG_OF, false);
+                self.cycles += 2;
+                2
+            }
+
+            // MOV r32, imm32 (0xB8-0xBF)
+            0xB8..=0xBF if self.operand_size_override => {
+                let val = self.fetch_u32();
+                self.set_reg32(opcode & 0x07, val);
+                self.cycles += 2;
+                2
+            }
+
+            // MOV AL/AX/EAX <-> moffs with a 32-bit offset or operand (0xA0-0xA3)
+            0xA0..=0xA3 if self.operand_size_override || self.address_size_override => {
+                let offset = if self.address_size_override {
+                    self.fetch_u32()
+                } else {
+                    self.fetch_u16() as u32
+                };
+                let seg = self.get_segment_with_override(self.ds);
+                let width = if opcode & 1 == 0 {
+                    1
+                } else {
+                    self.operand_bits() / 8
+                };
+                let mask = u32::MAX >> (32 - width * 8);
+                if opcode & 2 == 0 {
+                    let val = self.read_string(seg, offset, width);
+                    self.ax = (self.ax & !mask) | val;
+                } else {
+                    self.write_string(seg, offset, width, self.ax & mask);
+                }
+                self.cycles += 4;
+                4
+            }
+
+            // XCHG r32, r/m32 (0x87)
+            0x87 if self.operand_size_override => {
+                let modrm = self.fetch_u8();
+                let (modbits, reg, rm) = Self::decode_modrm(modrm);
+                let reg_val = self.get_reg32(reg);
+                let (rm_val, seg, offset) = self.read_rmw32(modbits, rm);
+                self.set_reg32(reg, rm_val);
+                self.write_rmw32(modbits, rm, reg_val, seg, offset);
+                self.cycles += if modbits == 0b11 { 3 } else { 5 };
+                if modbits == 0b11 {
+                    3
+                } else {
+                    5
+                }
+            }
+
+            // XCHG EAX, r32 (0x91-0x97)
+            0x91..=0x97 if self.operand_size_override => {
+                let reg = opcode & 0x07;
+                let val = self.get_reg32(reg);
+                self.set_reg32(reg, self.ax);
+                self.ax = val;
+                self.cycles += 3;
+                3
+            }
+
+            // CWDE - Convert Word to Doubleword in EAX (0x98)
+            0x98 if self.operand_size_override => {
+                self.ax = self.ax as u16 as i16 as i32 as u32;
+                self.cycles += 3;
+                3
+            }
+
+            // CDQ - Convert Doubleword to Quadword in EDX:EAX (0x99)
+            0x99 if self.operand_size_override => {
+                self.dx = if self.ax & 0x8000_0000 != 0 {
+                    0xFFFF_FFFF
+                } else {
+                    0
+                };
+                self.cycles += 2;
+                2
+            }
+
+            // POP r/m32 (0x8F)
+            0x8F if self.operand_size_override => {
+                let modrm = self.fetch_u8();
+                let (modbits, _op, rm) = Self::decode_modrm(modrm);
+                // ESP is incremented before the destination address is computed
+                let val = self.pop32();
+                self.write_rm32(modbits, rm, val);
+                self.cycles += if modbits == 0b11 { 4 } else { 5 };
+                if modbits == 0b11 {
+                    4
+                } else {
+                    5
+                }
+            }
+
+            // PUSH imm32 (0x68)
+            0x68 if self.operand_size_override => {
+                let val = self.fetch_u32();
+                self.push32(val);
+                self.cycles += 2;
+                2
+            }
+
+            // Group 3 with 32-bit operands (0xF7): TEST, NOT, NEG, MUL, IMUL, DIV, IDIV
+            0xF7 if self.operand_size_override => {
+                let modrm = self.fetch_u8();
+                let (modbits, op, rm) = Self::decode_modrm(modrm);
+                match op {
+                    0 | 1 => {
+                        let val = self.read_rm32(modbits, rm);
+                        let imm = self.fetch_u32();
+                        self.update_flags_32(val & imm);
+                        self.set_flag(FLAG_CF, false);
+                        self.set_flag(FLAG_OF, false);
+                    }
+                    2 => {
+                        let (val, seg, offset) = self.read_rmw32(modbits, rm);
+                        self.write_rmw32(modbits, rm, !val, seg, offset);
+                    }
+                    3 => {
+                        let (val, seg, offset) = self.read_rmw32(modbits, rm);
+                        let result = self.alu_op_32(5, 0, val);
+                        self.write_rmw32(modbits, rm, result, seg, offset);
+                    }
+                    4 => {
+                        let val = self.read_rm32(modbits, rm);
+                        let result = self.ax as u64 * val as u64;
+                        self.ax = result as u32;
+                        self.dx = (result >> 32) as u32;
+                        self.set_flag(FLAG_CF, self.dx != 0);
+                        self.set_flag(FLAG_OF, self.dx != 0);
+                    }
+                    5 => {
+                        let val = self.read_rm32(modbits, rm);
+                        let result = (self.ax as i32 as i64) * (val as i32 as i64);
+                        self.ax = result as u32;
+                        self.dx = (result >> 32) as u32;
+                        let overflow = result != result as i32 as i64;
+                        self.set_flag(FLAG_CF, overflow);
+                        self.set_flag(FLAG_OF, overflow);
+                    }
+                    6 => {
+                        let divisor = self.read_rm32(modbits, rm) as u64;
+                        let dividend = ((self.dx as u64) << 32) | self.ax as u64;
+                        match dividend.checked_div(divisor) {
+                            Some(quotient) if quotient <= 0xFFFF_FFFF => {
+                                self.ax = quotient as u32;
+                                self.dx = (dividend % divisor) as u32;
+                            }
+                            // Division by zero or overflow
+                            _ => self.trigger_interrupt(0, true),
+                        }
+                    }
+                    _ => {
+                        let divisor = self.read_rm32(modbits, rm) as i32 as i64;
+                        let dividend = (((self.dx as u64) << 32) | self.ax as u64) as i64;
+                        match dividend.checked_div(divisor) {
+                            Some(quotient) if quotient == quotient as i32 as i64 => {
+                                self.ax = quotient as u32;
+                                self.dx = (dividend % divisor) as u32;
+                            }
+                            // Division by zero or overflow
+                            _ => self.trigger_interrupt(0, true),
+                        }
+                    }
+                }
+                let cycles = match op {
+                    4 | 5 => 38,
+                    6 | 7 => 43,
+                    _ => 6,
+                };
+                self.cycles += cycles as u64;
+                cycles
+            }
+
+            // Group 5 with 32-bit operands (0xFF): INC, DEC, CALL, JMP, PUSH
+            0xFF if self.operand_size_override => {
+                let modrm = self.fetch_u8();
+                let (modbits, op, rm) = Self::decode_modrm(modrm);
+                match op {
+                    0 | 1 => {
+                        // INC/DEC leave CF unchanged
+                        let (val, seg, offset) = self.read_rmw32(modbits, rm);
+                        let carry = self.get_flag(FLAG_CF);
+                        let result = self.alu_op_32(if op == 0 { 0 } else { 5 }, val, 1);
+                        self.set_flag(FLAG_CF, carry);
+                        self.write_rmw32(modbits, rm, result, seg, offset);
+                    }
+                    2 => {
+                        let target = self.read_rm32(modbits, rm);
+                        self.push32(self.ip);
+                        self.ip = target;
+                    }
+                    3 | 5 => {
+                        // CALL/JMP m16:32 (far)
+                        let (offset, segment) = self.read_far_pointer(modbits, rm);
+                        if self.protected_mode.is_protected_mode() {
+                            if let Err(fault) =
+                                self.protected_far_transfer(segment, offset, op == 3)
+                            {
+                                self.record_fault(fault);
+                            }
+                        } else {
+                            if op == 3 {
+                                self.push32(self.cs as u32);
+                                self.push32(self.ip);
+                            }
+                            self.ip = offset;
+                            self.cs = segment;
+                        }
+                    }
+                    4 => {
+                        self.ip = self.read_rm32(modbits, rm);
+                    }
+                    6 => {
+                        let val = self.read_rm32(modbits, rm);
+                        self.push32(val);
+                    }
+                    _ => {
+                        // Undefined; consume displacement bytes and continue
+                        if modbits != 0b11 {
+                            let _ = self.calc_effective_address(modbits, rm);
+                        }
+                    }
+                }
+                self.cycles += 7;
+                7
+            }
+
+            // REP/REPE/REPZ prefix (0xF3)
+            0xF3 => {
+                let next_opcode = self.fetch_u8();
+                let mut total_cycles: u32 = 9; // Base prefix overhead
+
+                match next_opcode {
+                    // MOVSB
+                    0xA4 => {
+                        // Apply segment override to source (DS:SI), destination is always ES:DI
+                        // Consume override once before the loop
+                        let src_seg = self.get_segment_with_override(self.ds);
+                        while (self.cx & 0xFFFF) != 0 {
+                            let val = self.read(src_seg, self.si as u16);
+                            self.write(self.es, self.di as u16, val);
+                            if self.get_flag(FLAG_DF) {
+                                self.si = self.si.wrapping_sub(1);
+                                self.di = self.di.wrapping_sub(1);
+                            } else {
+                                self.si = self.si.wrapping_add(1);
+                                self.di = self.di.wrapping_add(1);
+                            }
+                            let cx = ((self.cx as u16).wrapping_sub(1)) as u32;
+                            self.cx = (self.cx & 0xFFFF_0000) | cx;
+                            total_cycles += 17;
+                        }
+                        self.cycles += total_cycles as u64;
+                        total_cycles
+                    }
+                    // MOVSW
+                    0xA5 => {
+                        // Apply segment override to source (DS:SI), destination is always ES:DI
+                        // Consume override once before the loop
+                        let src_seg = self.get_segment_with_override(self.ds);
+                        while (self.cx & 0xFFFF) != 0 {
+                            let val = self.read_u16(src_seg, self.si as u16);
+                            self.write_u16(self.es, self.di as u16, val);
+                            if self.get_flag(FLAG_DF) {
+                                self.si = self.si.wrapping_sub(2u32);
+                                self.di = self.di.wrapping_sub(2u32);
+                            } else {
+                                self.si = self.si.wrapping_add(2u32);
+                                self.di = self.di.wrapping_add(2u32);
+                            }
+                            let cx = ((self.cx as u16).wrapping_sub(1)) as u32;
+                            self.cx = (self.cx & 0xFFFF_0000) | cx;
+                            total_cycles += 17;
+                        }
+                        self.cycles += total_cycles as u64;
+                        total_cycles
                     }
                     // STOSB
                     0xAA => {
@@ -2035,7 +2908,7 @@ impl<M: Memory8086> Cpu8086<M> {
                         // Since we already fetched it, we decrement IP to put it back
                         self.ip = self.ip.wrapping_sub(1);
                         // Now execute the instruction normally by recursing
-                        let cycles = self.step();
+                        let cycles = self.execute_opcode();
                         self.cycles = self.cycles.wrapping_sub(cycles as u64); // Remove the cycles we're about to re-add
                         cycles
                     }
@@ -2174,7 +3047,7 @@ impl<M: Memory8086> Cpu8086<M> {
                         // We need to "un-fetch" the opcode and execute it normally
                         self.ip = self.ip.wrapping_sub(1);
                         // Now execute the instruction normally by recursing
-                        let cycles = self.step();
+                        let cycles = self.execute_opcode();
                         self.cycles = self.cycles.wrapping_sub(cycles as u64); // Remove the cycles we're about to re-add
                         cycles
                     }
@@ -2318,7 +3191,7 @@ impl<M: Memory8086> Cpu8086<M> {
                 let modrm = self.fetch_u8();
                 let (modbits, seg, rm) = Self::decode_modrm(modrm);
                 let val = self.read_rm16(modbits, rm);
-                self.set_seg(seg & 0x03, val); // Only ES, CS, SS, DS (0-3)
+                self.load_segment_or_fault(seg & 0x03, val); // Only ES, CS, SS, DS (0-3)
                 self.cycles += if modbits == 0b11 { 2 } else { 8 };
                 if modbits == 0b11 {
                     2
@@ -2861,8 +3734,7 @@ impl<M: Memory8086> Cpu8086<M> {
             0x06 => {
                 if self.operand_size_override && self.model.supports_80386_instructions() {
                     // 32-bit push: push 32-bit zero-extended segment value
-                    self.sp = self.sp.wrapping_sub(4);
-                    self.write_u32(self.ss, self.sp, self.es as u32);
+                    self.push32(self.es as u32);
                 } else {
                     self.push(self.es);
                 }
@@ -2872,14 +3744,8 @@ impl<M: Memory8086> Cpu8086<M> {
 
             // POP ES (0x07)
             0x07 => {
-                if self.operand_size_override && self.model.supports_80386_instructions() {
-                    // 32-bit pop: pop 32-bit value but only use lower 16 bits for segment
-                    let val = self.read_u32(self.ss, self.sp);
-                    self.sp = self.sp.wrapping_add(4);
-                    self.es = val as u16;
-                } else {
-                    self.es = self.pop();
-                }
+                // With a 32-bit operand size a dword is popped (upper half discarded)
+                self.pop_segment(SEG_ES);
                 self.cycles += 8;
                 8
             }
@@ -3023,7 +3889,7 @@ impl<M: Memory8086> Cpu8086<M> {
             0x26 => {
                 // ES segment override prefix
                 self.segment_override = Some(SegmentOverride::ES);
-                self.step() // Execute next instruction with ES override
+                self.execute_opcode() // Execute next instruction with ES override
             }
 
             // DAA - Decimal Adjust After Addition (0x27)
@@ -3056,7 +3922,7 @@ impl<M: Memory8086> Cpu8086<M> {
             0x2E => {
                 // CS segment override prefix
                 self.segment_override = Some(SegmentOverride::CS);
-                self.step() // Execute next instruction with CS override
+                self.execute_opcode() // Execute next instruction with CS override
             }
 
             // DAS - Decimal Adjust After Subtraction (0x2F)
@@ -3346,8 +4212,7 @@ impl<M: Memory8086> Cpu8086<M> {
             0x0E => {
                 if self.operand_size_override && self.model.supports_80386_instructions() {
                     // Push 32-bit (zero-extend segment)
-                    self.sp = self.sp.wrapping_sub(4);
-                    self.write_u32(self.ss, self.sp, self.cs as u32);
+                    self.push32(self.cs as u32);
                 } else {
                     // Push 16-bit
                     self.push(self.cs);
@@ -3379,20 +4244,20 @@ impl<M: Memory8086> Cpu8086<M> {
                                 let limit = self.protected_mode.gdtr.limit;
                                 let base = self.protected_mode.gdtr.base;
 
-                                self.write(segment, offset, (limit & 0xFF) as u8);
-                                self.write(segment, offset.wrapping_add(1), (limit >> 8) as u8);
-                                self.write(segment, offset.wrapping_add(2), (base & 0xFF) as u8);
-                                self.write(
+                                self.write_at(segment, offset, (limit & 0xFF) as u8);
+                                self.write_at(segment, offset.wrapping_add(1), (limit >> 8) as u8);
+                                self.write_at(segment, offset.wrapping_add(2), (base & 0xFF) as u8);
+                                self.write_at(
                                     segment,
                                     offset.wrapping_add(3),
                                     ((base >> 8) & 0xFF) as u8,
                                 );
-                                self.write(
+                                self.write_at(
                                     segment,
                                     offset.wrapping_add(4),
                                     ((base >> 16) & 0xFF) as u8,
                                 );
-                                self.write(
+                                self.write_at(
                                     segment,
                                     offset.wrapping_add(5),
                                     ((base >> 24) & 0xFF) as u8,
@@ -3406,20 +4271,20 @@ impl<M: Memory8086> Cpu8086<M> {
                                 let limit = self.protected_mode.idtr.limit;
                                 let base = self.protected_mode.idtr.base;
 
-                                self.write(segment, offset, (limit & 0xFF) as u8);
-                                self.write(segment, offset.wrapping_add(1), (limit >> 8) as u8);
-                                self.write(segment, offset.wrapping_add(2), (base & 0xFF) as u8);
-                                self.write(
+                                self.write_at(segment, offset, (limit & 0xFF) as u8);
+                                self.write_at(segment, offset.wrapping_add(1), (limit >> 8) as u8);
+                                self.write_at(segment, offset.wrapping_add(2), (base & 0xFF) as u8);
+                                self.write_at(
                                     segment,
                                     offset.wrapping_add(3),
                                     ((base >> 8) & 0xFF) as u8,
                                 );
-                                self.write(
+                                self.write_at(
                                     segment,
                                     offset.wrapping_add(4),
                                     ((base >> 16) & 0xFF) as u8,
                                 );
-                                self.write(
+                                self.write_at(
                                     segment,
                                     offset.wrapping_add(5),
                                     ((base >> 24) & 0xFF) as u8,
@@ -3430,34 +4295,44 @@ impl<M: Memory8086> Cpu8086<M> {
                             2 => {
                                 // LGDT - Load Global Descriptor Table Register
                                 let (segment, offset, _) = self.calc_effective_address(modbits, rm);
-                                let limit_low = self.read(segment, offset) as u16;
-                                let limit_high = self.read(segment, offset.wrapping_add(1)) as u16;
+                                let limit_low = self.read_at(segment, offset) as u16;
+                                let limit_high =
+                                    self.read_at(segment, offset.wrapping_add(1)) as u16;
                                 let limit = limit_low | (limit_high << 8);
 
-                                let base_0 = self.read(segment, offset.wrapping_add(2)) as u32;
-                                let base_1 = self.read(segment, offset.wrapping_add(3)) as u32;
-                                let base_2 = self.read(segment, offset.wrapping_add(4)) as u32;
-                                let base_3 = self.read(segment, offset.wrapping_add(5)) as u32;
+                                let base_0 = self.read_at(segment, offset.wrapping_add(2)) as u32;
+                                let base_1 = self.read_at(segment, offset.wrapping_add(3)) as u32;
+                                let base_2 = self.read_at(segment, offset.wrapping_add(4)) as u32;
+                                let base_3 = self.read_at(segment, offset.wrapping_add(5)) as u32;
                                 let base = base_0 | (base_1 << 8) | (base_2 << 16) | (base_3 << 24);
 
                                 self.protected_mode.load_gdtr(base, limit);
+                                if self.operand_size_override {
+                                    // 32-bit operand size loads all 32 base bits
+                                    self.protected_mode.gdtr.base = base;
+                                }
                                 self.cycles += 11;
                                 11
                             }
                             3 => {
                                 // LIDT - Load Interrupt Descriptor Table Register
                                 let (segment, offset, _) = self.calc_effective_address(modbits, rm);
-                                let limit_low = self.read(segment, offset) as u16;
-                                let limit_high = self.read(segment, offset.wrapping_add(1)) as u16;
+                                let limit_low = self.read_at(segment, offset) as u16;
+                                let limit_high =
+                                    self.read_at(segment, offset.wrapping_add(1)) as u16;
                                 let limit = limit_low | (limit_high << 8);
 
-                                let base_0 = self.read(segment, offset.wrapping_add(2)) as u32;
-                                let base_1 = self.read(segment, offset.wrapping_add(3)) as u32;
-                                let base_2 = self.read(segment, offset.wrapping_add(4)) as u32;
-                                let base_3 = self.read(segment, offset.wrapping_add(5)) as u32;
+                                let base_0 = self.read_at(segment, offset.wrapping_add(2)) as u32;
+                                let base_1 = self.read_at(segment, offset.wrapping_add(3)) as u32;
+                                let base_2 = self.read_at(segment, offset.wrapping_add(4)) as u32;
+                                let base_3 = self.read_at(segment, offset.wrapping_add(5)) as u32;
                                 let base = base_0 | (base_1 << 8) | (base_2 << 16) | (base_3 << 24);
 
                                 self.protected_mode.load_idtr(base, limit);
+                                if self.operand_size_override {
+                                    // 32-bit operand size loads all 32 base bits
+                                    self.protected_mode.idtr.base = base;
+                                }
                                 self.cycles += 11;
                                 11
                             }
@@ -3470,8 +4345,9 @@ impl<M: Memory8086> Cpu8086<M> {
                             }
                             6 => {
                                 // LMSW - Load Machine Status Word
+                                // LMSW can set PE but not clear it
                                 let val = self.read_rm16(modbits, rm);
-                                self.protected_mode.set_msw(val);
+                                self.write_msw(val, false);
                                 self.cycles += 10;
                                 10
                             }
@@ -3497,15 +4373,24 @@ impl<M: Memory8086> Cpu8086<M> {
 
                         let modrm = self.fetch_u8();
                         let (modbits, reg, rm) = Self::decode_modrm(modrm);
-                        let _selector = self.read_rm16(modbits, rm);
-
-                        // Stub implementation: Set ZF=0 (invalid selector)
-                        // In a full implementation, this would:
-                        // 1. Check if selector is valid
-                        // 2. Load access rights from descriptor
-                        // 3. Set ZF=1 if valid, store access rights in destination
-                        self.set_flag(FLAG_ZF, false);
-                        self.set_reg16(reg, 0);
+                        let selector = self.read_rm16(modbits, rm);
+
+                        // Valid for code/data segments, TSSs, LDTs, call and task gates
+                        let desc = self.visible_descriptor(selector).filter(|desc| {
+                            !desc.is_system()
+                                || matches!(
+                                    desc.system_type(),
+                                    system_types::TSS_286_AVAILABLE
+                                        | system_types::LDT
+                                        | system_types::TSS_286_BUSY
+                                        | system_types::CALL_GATE_286
+                                        | system_types::TASK_GATE
+                                )
+                        });
+                        self.set_flag(FLAG_ZF, desc.is_some());
+                        if let Some(desc) = desc {
+                            self.set_reg16(reg, (desc.access as u16) << 8);
+                        }
 
                         self.cycles += 15;
                         15
@@ -3519,11 +4404,22 @@ impl<M: Memory8086> Cpu8086<M> {
 
                         let modrm = self.fetch_u8();
                         let (modbits, reg, rm) = Self::decode_modrm(modrm);
-                        let _selector = self.read_rm16(modbits, rm);
-
-                        // Stub implementation: Set ZF=0 (invalid selector)
-                        self.set_flag(FLAG_ZF, false);
-                        self.set_reg16(reg, 0);
+                        let selector = self.read_rm16(modbits, rm);
+
+                        // Valid for code/data segments, TSSs and LDTs
+                        let desc = self.visible_descriptor(selector).filter(|desc| {
+                            !desc.is_system()
+                                || matches!(
+                                    desc.system_type(),
+                                    system_types::TSS_286_AVAILABLE
+                                        | system_types::LDT
+                                        | system_types::TSS_286_BUSY
+                                )
+                        });
+                        self.set_flag(FLAG_ZF, desc.is_some());
+                        if let Some(desc) = desc {
+                            self.set_reg16(reg, (desc.limit & 0xFFFF) as u16);
+                        }
 
                         self.cycles += 15;
                         15
@@ -3569,32 +4465,42 @@ impl<M: Memory8086> Cpu8086<M> {
                             2 => {
                                 // LLDT - Load Local Descriptor Table Register
                                 let selector = self.read_rm16(modbits, rm);
-                                self.protected_mode.load_ldtr(selector);
+                                if !self.protected_mode.is_protected_mode() {
+                                    self.protected_mode.load_ldtr(selector);
+                                } else if let Err(fault) = self.load_ldt(selector) {
+                                    self.record_fault(fault);
+                                }
                                 self.cycles += 17;
                                 17
                             }
                             3 => {
                                 // LTR - Load Task Register
                                 let selector = self.read_rm16(modbits, rm);
-                                self.protected_mode.load_tr(selector);
+                                if !self.protected_mode.is_protected_mode() {
+                                    self.protected_mode.load_tr(selector);
+                                } else if let Err(fault) = self.load_task_register(selector) {
+                                    self.record_fault(fault);
+                                }
                                 self.cycles += 17;
                                 17
                             }
                             4 => {
                                 // VERR - Verify Segment for Reading
-                                let _selector = self.read_rm16(modbits, rm);
-                                // Stub: Set ZF=0 (segment not readable)
-                                // In full implementation: check descriptor access rights
-                                self.set_flag(FLAG_ZF, false);
+                                let selector = self.read_rm16(modbits, rm);
+                                let readable = self
+                                    .visible_descriptor(selector)
+                                    .is_some_and(|desc| desc.is_readable());
+                                self.set_flag(FLAG_ZF, readable);
                                 self.cycles += 10;
                                 10
                             }
                             5 => {
                                 // VERW - Verify Segment for Writing
-                                let _selector = self.read_rm16(modbits, rm);
-                                // Stub: Set ZF=0 (segment not writable)
-                                // In full implementation: check descriptor access rights
-                                self.set_flag(FLAG_ZF, false);
+                                let selector = self.read_rm16(modbits, rm);
+                                let writable = self
+                                    .visible_descriptor(selector)
+                                    .is_some_and(|desc| desc.is_writable_data());
+                                self.set_flag(FLAG_ZF, writable);
                                 self.cycles += 10;
                                 10
                             }
@@ -3606,18 +4512,21 @@ impl<M: Memory8086> Cpu8086<M> {
                         }
                     }
                     // MOVSX - Move with Sign Extension (0x0F 0xBE, 0xBF) - 80386+
-                    0xBE => {
+                    0xBE | 0xBF => {
                         if !self.model.supports_80386_instructions() {
                             // Invalid opcode on 8086/8088/80186/80286
                             self.cycles += 10;
                             return 10;
                         }
-                        // MOVSX r16, r/m8
+                        // MOVSX r16/32, r/m8 or r32, r/m16
                         let modrm = self.fetch_u8();
                         let (modbits, reg, rm) = Self::decode_modrm(modrm);
-                        let val = self.read_rm8(modbits, rm);
-                        let extended = (val as i8) as i16 as u16; // Sign extend
-                        self.set_reg16(reg, extended);
+                        let extended = if next_opcode == 0xBE {
+                            self.read_rm8(modbits, rm) as i8 as i32 as u32
+                        } else {
+                            self.read_rm16(modbits, rm) as i16 as i32 as u32
+                        };
+                        self.set_reg_v(reg, extended);
                         self.cycles += if modbits == 0b11 { 3 } else { 6 };
                         if modbits == 0b11 {
                             3
@@ -3625,29 +4534,22 @@ impl<M: Memory8086> Cpu8086<M> {
                             6
                         }
                     }
-                    0xBF => {
-                        if !self.model.supports_80386_instructions() {
-                            // Invalid opcode on 8086/8088/80186/80286
-                            self.cycles += 10;
-                            return 10;
-                        }
-                        // MOVSX r32, r/m16 (80386 only - not fully supported yet)
-                        let _modrm = self.fetch_u8();
-                        self.cycles += 3;
-                        3
-                    }
                     // MOVZX - Move with Zero Extension (0x0F 0xB6, 0xB7) - 80386+
-                    0xB6 => {
+                    0xB6 | 0xB7 => {
                         if !self.model.supports_80386_instructions() {
                             // Invalid opcode on 8086/8088/80186/80286
                             self.cycles += 10;
                             return 10;
                         }
-                        // MOVZX r16, r/m8
+                        // MOVZX r16/32, r/m8 or r32, r/m16
                         let modrm = self.fetch_u8();
                         let (modbits, reg, rm) = Self::decode_modrm(modrm);
-                        let val = self.read_rm8(modbits, rm);
-                        self.set_reg16(reg, val as u16); // Zero extend
+                        let val = if next_opcode == 0xB6 {
+                            self.read_rm8(modbits, rm) as u32
+                        } else {
+                            self.read_rm16(modbits, rm) as u32
+                        };
+                        self.set_reg_v(reg, val);
                         self.cycles += if modbits == 0b11 { 3 } else { 6 };
                         if modbits == 0b11 {
                             3
@@ -3655,17 +4557,6 @@ impl<M: Memory8086> Cpu8086<M> {
                             6
                         }
                     }
-                    0xB7 => {
-                        if !self.model.supports_80386_instructions() {
-                            // Invalid opcode on 8086/8088/80186/80286
-                            self.cycles += 10;
-                            return 10;
-                        }
-                        // MOVZX r32, r/m16 (80386 only - not fully supported yet)
-                        let _modrm = self.fetch_u8();
-                        self.cycles += 3;
-                        3
-                    }
                     // BSF - Bit Scan Forward (0x0F 0xBC) - 80386+
                     0xBC => {
                         if !self.model.supports_80386_instructions() {
@@ -3675,14 +4566,13 @@ impl<M: Memory8086> Cpu8086<M> {
                         }
                         let modrm = self.fetch_u8();
                         let (modbits, reg, rm) = Self::decode_modrm(modrm);
-                        let val = self.read_rm16(modbits, rm);
+                        let val = self.read_rm_v(modbits, rm);
                         if val == 0 {
                             // ZF = 1 if source is 0
                             self.set_flag(FLAG_ZF, true);
                         } else {
                             // Find first set bit from LSB
-                            let bit_pos = val.trailing_zeros() as u16;
-                            self.set_reg16(reg, bit_pos);
+                            self.set_reg_v(reg, val.trailing_zeros());
                             self.set_flag(FLAG_ZF, false);
                         }
                         self.cycles += if modbits == 0b11 { 10 } else { 11 };
@@ -3701,14 +4591,13 @@ impl<M: Memory8086> Cpu8086<M> {
                         }
                         let modrm = self.fetch_u8();
                         let (modbits, reg, rm) = Self::decode_modrm(modrm);
-                        let val = self.read_rm16(modbits, rm);
+                        let val = self.read_rm_v(modbits, rm);
                         if val == 0 {
                             // ZF = 1 if source is 0
                             self.set_flag(FLAG_ZF, true);
                         } else {
                             // Find first set bit from MSB
-                            let bit_pos = 15 - val.leading_zeros() as u16;
-                            self.set_reg16(reg, bit_pos);
+                            self.set_reg_v(reg, 31 - val.leading_zeros());
                             self.set_flag(FLAG_ZF, false);
                         }
                         self.cycles += if modbits == 0b11 { 10 } else { 11 };
@@ -3718,8 +4607,8 @@ impl<M: Memory8086> Cpu8086<M> {
                             11
                         }
                     }
-                    // BT - Bit Test (0x0F 0xA3) - 80386+
-                    0xA3 => {
+                    // BT/BTS/BTR/BTC - Bit Test (0x0F 0xA3, 0xAB, 0xB3, 0xBB) - 80386+
+                    0xA3 | 0xAB | 0xB3 | 0xBB => {
                         if !self.model.supports_80386_instructions() {
                             // Invalid opcode on 8086/8088/80186/80286
                             self.cycles += 10;
@@ -3727,81 +4616,61 @@ impl<M: Memory8086> Cpu8086<M> {
                         }
                         let modrm = self.fetch_u8();
                         let (modbits, reg, rm) = Self::decode_modrm(modrm);
-                        let bit_index = self.get_reg16(reg);
-                        let val = self.read_rm16(modbits, rm);
-                        let bit = (val >> (bit_index & 0x0F)) & 1;
-                        self.set_flag(FLAG_CF, bit != 0);
-                        self.cycles += if modbits == 0b11 { 3 } else { 12 };
-                        if modbits == 0b11 {
-                            3
+                        let bit_offset = self.get_reg_v(reg);
+                        let op = (next_opcode >> 3) & 0x03;
+                        self.bit_test_op(op, modbits, rm, bit_offset);
+                        if op == 0 {
+                            self.cycles += if modbits == 0b11 { 3 } else { 12 };
+                            if modbits == 0b11 {
+                                3
+                            } else {
+                                12
+                            }
                         } else {
-                            12
+                            self.cycles += if modbits == 0b11 { 6 } else { 13 };
+                            if modbits == 0b11 {
+                                6
+                            } else {
+                                13
+                            }
                         }
                     }
-                    // BTS - Bit Test and Set (0x0F 0xAB) - 80386+
-                    0xAB => {
+                    // BT/BTS/BTR/BTC r/m, imm8 (0x0F 0xBA /4-/7) - 80386+
+                    0xBA => {
                         if !self.model.supports_80386_instructions() {
                             // Invalid opcode on 8086/8088/80186/80286
                             self.cycles += 10;
                             return 10;
                         }
                         let modrm = self.fetch_u8();
-                        let (modbits, reg, rm) = Self::decode_modrm(modrm);
-                        let bit_index = self.get_reg16(reg);
-                        let val = self.read_rm16(modbits, rm);
-                        let bit = (val >> (bit_index & 0x0F)) & 1;
-                        self.set_flag(FLAG_CF, bit != 0);
-                        let new_val = val | (1 << (bit_index & 0x0F));
-                        self.write_rm16(modbits, rm, new_val);
-                        self.cycles += if modbits == 0b11 { 6 } else { 13 };
-                        if modbits == 0b11 {
-                            6
-                        } else {
-                            13
-                        }
-                    }
-                    // BTR - Bit Test and Reset (0x0F 0xB3) - 80386+
-                    0xB3 => {
-                        if !self.model.supports_80386_instructions() {
-                            // Invalid opcode on 8086/8088/80186/80286
-                            self.cycles += 10;
+                        let (modbits, op, rm) = Self::decode_modrm(modrm);
+                        if op < 4 {
+                            // Undefined encoding; consume the operand
+                            if modbits != 0b11 {
+                                let _ = self.calc_effective_address(modbits, rm);
+                            }
+                            let _ = self.fetch_u8();
+                            self.record_fault(ProtectedModeFault::InvalidOpcode);
                             return 10;
                         }
-                        let modrm = self.fetch_u8();
-                        let (modbits, reg, rm) = Self::decode_modrm(modrm);
-                        let bit_index = self.get_reg16(reg);
-                        let val = self.read_rm16(modbits, rm);
-                        let bit = (val >> (bit_index & 0x0F)) & 1;
-                        self.set_flag(FLAG_CF, bit != 0);
-                        let new_val = val & !(1 << (bit_index & 0x0F));
-                        self.write_rm16(modbits, rm, new_val);
-                        self.cycles += if modbits == 0b11 { 6 } else { 13 };
-                        if modbits == 0b11 {
-                            6
-                        } else {
-                            13
-                        }
-                    }
-                    // BTC - Bit Test and Complement (0x0F 0xBB) - 80386+
-                    0xBB => {
-                        if !self.model.supports_80386_instructions() {
-                            // Invalid opcode on 8086/8088/80186/80286
-                            self.cycles += 10;
-                            return 10;
+                        // The immediate follows any displacement bytes
+                        let (val, seg, offset) = self.read_rmw_v(modbits, rm);
+                        let bit = (self.fetch_u8() as u32) & (self.operand_bits() - 1);
+                        self.set_flag(FLAG_CF, (val >> bit) & 1 != 0);
+                        let result = match op {
+                            5 => Some(val | (1 << bit)),
+                            6 => Some(val & !(1 << bit)),
+                            7 => Some(val ^ (1 << bit)),
+                            _ => None,
+                        };
+                        if let Some(result) = result {
+                            self.write_rmw_v(modbits, rm, result, seg, offset);
                         }
-                        let modrm = self.fetch_u8();
-                        let (modbits, reg, rm) = Self::decode_modrm(modrm);
-                        let bit_index = self.get_reg16(reg);
-                        let val = self.read_rm16(modbits, rm);
-                        let bit = (val >> (bit_index & 0x0F)) & 1;
-                        self.set_flag(FLAG_CF, bit != 0);
-                        let new_val = val ^ (1 << (bit_index & 0x0F));
-                        self.write_rm16(modbits, rm, new_val);
-                        self.cycles += if modbits == 0b11 { 6 } else { 13 };
+                        self.cycles += if modbits == 0b11 { 6 } else { 8 };
                         if modbits == 0b11 {
                             6
                         } else {
-                            13
+                            8
                         }
                     }
                     // LSS - Load Far Pointer to SS (0x0F 0xB2) - 80386+
@@ -3813,14 +4682,7 @@ impl<M: Memory8086> Cpu8086<M> {
                         }
                         let modrm = self.fetch_u8();
                         let (modbits, reg, rm) = Self::decode_modrm(modrm);
-                        // LSS only works with memory operands
-                        if modbits != 0b11 {
-                            let (seg, offset_ea, _) = self.calc_effective_address(modbits, rm);
-                            let offset = self.read_u16(seg, offset_ea);
-                            let segment = self.read_u16(seg, offset_ea.wrapping_add(2));
-                            self.set_reg16(reg, offset);
-                            self.ss = segment;
-                        }
+                        self.load_far_pointer(SEG_SS, modbits, reg, rm);
                         self.cycles += 7;
                         7
                     }
@@ -3833,14 +4695,7 @@ impl<M: Memory8086> Cpu8086<M> {
                         }
                         let modrm = self.fetch_u8();
                         let (modbits, reg, rm) = Self::decode_modrm(modrm);
-                        // LFS only works with memory operands
-                        if modbits != 0b11 {
-                            let (seg, offset_ea, _) = self.calc_effective_address(modbits, rm);
-                            let offset = self.read_u16(seg, offset_ea);
-                            let segment = self.read_u16(seg, offset_ea.wrapping_add(2));
-                            self.set_reg16(reg, offset);
-                            self.fs = segment;
-                        }
+                        self.load_far_pointer(SEG_FS, modbits, reg, rm);
                         self.cycles += 7;
                         7
                     }
@@ -3853,68 +4708,68 @@ impl<M: Memory8086> Cpu8086<M> {
                         }
                         let modrm = self.fetch_u8();
                         let (modbits, reg, rm) = Self::decode_modrm(modrm);
-                        // LGS only works with memory operands
-                        if modbits != 0b11 {
-                            let (seg, offset_ea, _) = self.calc_effective_address(modbits, rm);
-                            let offset = self.read_u16(seg, offset_ea);
-                            let segment = self.read_u16(seg, offset_ea.wrapping_add(2));
-                            self.set_reg16(reg, offset);
-                            self.gs = segment;
-                        }
+                        self.load_far_pointer(SEG_GS, modbits, reg, rm);
                         self.cycles += 7;
                         7
                     }
-                    // SHLD - Double Precision Shift Left (0x0F 0xA4, 0xA5) - 80386+
-                    0xA4 => {
+                    // SHLD/SHRD - Double Precision Shift (0x0F 0xA4, 0xA5, 0xAC, 0xAD) - 80386+
+                    0xA4 | 0xA5 | 0xAC | 0xAD => {
                         if !self.model.supports_80386_instructions() {
                             // Invalid opcode on 8086/8088/80186/80286
                             self.cycles += 10;
                             return 10;
                         }
                         let modrm = self.fetch_u8();
-                        let (_modbits, _reg, _rm) = Self::decode_modrm(modrm);
-                        let _count = self.fetch_u8();
-                        // Stub: Not fully implemented
-                        self.cycles += 3;
-                        3
+                        let (modbits, reg, rm) = Self::decode_modrm(modrm);
+                        // Count is CL for 0xA5/0xAD, otherwise an immediate
+                        let count_in_cl = next_opcode & 1 != 0;
+                        self.double_shift(modbits, reg, rm, next_opcode < 0xA8, count_in_cl);
+                        self.cycles += if modbits == 0b11 { 3 } else { 7 };
+                        if modbits == 0b11 {
+                            3
+                        } else {
+                            7
+                        }
                     }
-                    0xA5 => {
+                    // Jcc near - Conditional jump with rel16/rel32 (0x0F 0x80-0x8F) - 80386+
+                    0x80..=0x8F => {
                         if !self.model.supports_80386_instructions() {
                             // Invalid opcode on 8086/8088/80186/80286
                             self.cycles += 10;
                             return 10;
                         }
-                        let _modrm = self.fetch_u8();
-                        // SHLD with CL
-                        // Stub: Not fully implemented
-                        self.cycles += 3;
-                        3
+                        let disp = if self.operand_size_override {
+                            self.fetch_u32() as i32
+                        } else {
+                            self.fetch_u16() as i16 as i32
+                        };
+                        if self.check_condition(next_opcode & 0x0F) {
+                            self.jump_relative(disp);
+                            self.cycles += 7;
+                            7
+                        } else {
+                            self.cycles += 3;
+                            3
+                        }
                     }
-                    // SHRD - Double Precision Shift Right (0x0F 0xAC, 0xAD) - 80386+
-                    0xAC => {
+                    // IMUL r16/32, r/m16/32 (0x0F 0xAF) - 80386+
+                    0xAF => {
                         if !self.model.supports_80386_instructions() {
                             // Invalid opcode on 8086/8088/80186/80286
                             self.cycles += 10;
                             return 10;
                         }
                         let modrm = self.fetch_u8();
-                        let (_modbits, _reg, _rm) = Self::decode_modrm(modrm);
-                        let _count = self.fetch_u8();
-                        // Stub: Not fully implemented
-                        self.cycles += 3;
-                        3
-                    }
-                    0xAD => {
-                        if !self.model.supports_80386_instructions() {
-                            // Invalid opcode on 8086/8088/80186/80286
-                            self.cycles += 10;
-                            return 10;
+                        let (modbits, reg, rm) = Self::decode_modrm(modrm);
+                        let src = self.read_rm_v(modbits, rm);
+                        let dest = self.get_reg_v(reg);
+                        self.imul_v(reg, dest, src);
+                        self.cycles += if modbits == 0b11 { 9 } else { 12 };
+                        if modbits == 0b11 {
+                            9
+                        } else {
+                            12
                         }
-                        let _modrm = self.fetch_u8();
-                        // SHRD with CL
-                        // Stub: Not fully implemented
-                        self.cycles += 3;
-                        3
                     }
                     // SETcc - Set Byte on Condition (0x0F 0x90-0x9F) - 80386+
                     0x90..=0x9F => {
@@ -3953,7 +4808,7 @@ impl<M: Memory8086> Cpu8086<M> {
                             self.cycles += 10;
                             return 10;
                         }
-                        self.fs = self.pop();
+                        self.pop_segment(SEG_FS);
                         self.cycles += 7;
                         7
                     }
@@ -3975,7 +4830,7 @@ impl<M: Memory8086> Cpu8086<M> {
                             self.cycles += 10;
                             return 10;
                         }
-                        self.gs = self.pop();
+                        self.pop_segment(SEG_GS);
                         self.cycles += 7;
                         7
                     }
@@ -3988,20 +4843,40 @@ impl<M: Memory8086> Cpu8086<M> {
 
                         let modrm = self.fetch_u8();
                         let (_, reg, rm) = Self::decode_modrm(modrm);
+                        if self.protected_mode.cpl != 0 {
+                            self.record_fault(ProtectedModeFault::GeneralProtection(0));
+                            return 6;
+                        }
 
-                        // Read from control register (only CR0 is commonly used)
+                        // Control registers always move to 32-bit registers
                         let cr_value = match reg {
-                            0 => self.protected_mode.get_cr0(), // CR0
-                            2 => 0, // CR2 (page fault linear address) - stub
-                            3 => 0, // CR3 (page directory base) - stub
-                            _ => 0, // Reserved
+                            0 => self.protected_mode.get_cr0(),
+                            2 => self.protected_mode.cr2, // Page fault linear address
+                            3 => self.protected_mode.cr3, // Page directory base
+                            _ => 0,                       // Reserved
                         };
 
-                        // Store to destination register
-                        self.set_reg16(rm, cr_value);
+                        self.set_reg32(rm, cr_value);
                         self.cycles += 6;
                         6
                     }
+                    // MOV reg, DRx - Move from Debug Register (0x0F 0x21) - 80386+
+                    0x21 => {
+                        if !self.model.supports_80386_instructions() {
+                            self.cycles += 6;
+                            return 6;
+                        }
+
+                        let modrm = self.fetch_u8();
+                        let (_, reg, rm) = Self::decode_modrm(modrm);
+                        if self.protected_mode.cpl != 0 {
+                            self.record_fault(ProtectedModeFault::GeneralProtection(0));
+                            return 22;
+                        }
+                        self.set_reg32(rm, self.debug_regs[reg as usize]);
+                        self.cycles += 22;
+                        22
+                    }
                     // MOV CRx, reg - Move to Control Register (0x0F 0x22) - 80386+
                     0x22 => {
                         if !self.model.supports_80386_instructions() {
@@ -4011,21 +4886,45 @@ impl<M: Memory8086> Cpu8086<M> {
 
                         let modrm = self.fetch_u8();
                         let (_, reg, rm) = Self::decode_modrm(modrm);
+                        if self.protected_mode.cpl != 0 {
+                            self.record_fault(ProtectedModeFault::GeneralProtection(0));
+                            return 10;
+                        }
 
-                        // Read from source register
-                        let value = self.get_reg16(rm);
-
-                        // Write to control register (only CR0 is commonly used)
+                        // Control registers always move from 32-bit registers
+                        let value = self.get_reg32(rm);
                         match reg {
-                            0 => self.protected_mode.set_cr0(value), // CR0
-                            2 => {} // CR2 (page fault linear address) - stub
-                            3 => {} // CR3 (page directory base) - stub
+                            0 => {
+                                self.write_msw(value as u16, true);
+                                // WP, AM, NW, CD and PG
+                                self.protected_mode.cr0_high = (value >> 16) as u16 & 0xE005;
+                            }
+                            2 => self.protected_mode.cr2 = value,
+                            3 => self.protected_mode.cr3 = value & page_entry::FRAME,
                             _ => {} // Reserved
                         }
 
                         self.cycles += 10;
                         10
                     }
+                    // MOV DRx, reg - Move to Debug Register (0x0F 0x23) - 80386+
+                    0x23 => {
+                        if !self.model.supports_80386_instructions() {
+                            self.cycles += 10;
+                            return 10;
+                        }
+
+                        let modrm = self.fetch_u8();
+                        let (_, reg, rm) = Self::decode_modrm(modrm);
+                        if self.protected_mode.cpl != 0 {
+                            self.record_fault(ProtectedModeFault::GeneralProtection(0));
+                            return 22;
+                        }
+                        // Stored only; breakpoints are not raised
+                        self.debug_regs[reg as usize] = self.get_reg32(rm);
+                        self.cycles += 22;
+                        22
+                    }
                     // INVD - Invalidate Cache (0x0F 0x08) - 80486+
                     0x08 => {
                         if !self.model.supports_80486_instructions() {
@@ -4327,8 +5226,8 @@ impl<M: Memory8086> Cpu8086<M> {
                         let (segment, offset, _) = self.calc_effective_address(modbits, rm);
 
                         // Read 32-bit value from memory (4 bytes)
-                        let mem_low = self.read_u16(segment, offset);
-                        let mem_high = self.read_u16(segment, offset.wrapping_add(2));
+                        let mem_low = self.read_ea_u16(segment, offset);
+                        let mem_high = self.read_ea_u16(segment, offset.wrapping_add(2));
                         let mem_val = (mem_low as u32) | ((mem_high as u32) << 16);
 
                         // Compare with DX:AX
@@ -4338,8 +5237,8 @@ impl<M: Memory8086> Cpu8086<M> {
                             // Equal: ZF=1, write CX:BX to memory
                             self.set_flag(FLAG_ZF, true);
                             let new_val = self.bx | (self.cx << 16);
-                            self.write_u16(segment, offset, (new_val & 0xFFFF) as u16);
-                            self.write_u16(
+                            self.write_ea_u16(segment, offset, (new_val & 0xFFFF) as u16);
+                            self.write_ea_u16(
                                 segment,
                                 offset.wrapping_add(2),
                                 ((new_val >> 16) & 0xFFFF) as u16,
@@ -4384,8 +5283,8 @@ impl<M: Memory8086> Cpu8086<M> {
                         } else {
                             // From memory (read 32 bits)
                             let (segment, offset, _) = self.calc_effective_address(modbits, rm);
-                            let low = self.read_u16(segment, offset);
-                            let high = self.read_u16(segment, offset.wrapping_add(2));
+                            let low = self.read_ea_u16(segment, offset);
+                            let high = self.read_ea_u16(segment, offset.wrapping_add(2));
                             ((high as u64) << 16) | (low as u64)
                         };
 
@@ -4414,8 +5313,8 @@ impl<M: Memory8086> Cpu8086<M> {
                         } else {
                             // To memory (write 32 bits)
                             let (segment, offset, _) = self.calc_effective_address(modbits, rm);
-                            self.write_u16(segment, offset, (value & 0xFFFF) as u16);
-                            self.write_u16(
+                            self.write_ea_u16(segment, offset, (value & 0xFFFF) as u16);
+                            self.write_ea_u16(
                                 segment,
                                 offset.wrapping_add(2),
                                 ((value >> 16) & 0xFFFF) as u16,
@@ -4447,7 +5346,7 @@ impl<M: Memory8086> Cpu8086<M> {
                             let (segment, offset, _) = self.calc_effective_address(modbits, rm);
                             let mut val = 0u64;
                             for i in 0..4 {
-                                let word = self.read_u16(segment, offset.wrapping_add(i * 2));
+                                let word = self.read_ea_u16(segment, offset.wrapping_add(i * 2));
                                 val |= (word as u64) << (i * 16);
                             }
                             val
@@ -4480,7 +5379,7 @@ impl<M: Memory8086> Cpu8086<M> {
                             let (segment, offset, _) = self.calc_effective_address(modbits, rm);
                             for i in 0..4 {
                                 let word = ((value >> (i * 16)) & 0xFFFF) as u16;
-                                self.write_u16(segment, offset.wrapping_add(i * 2), word);
+                                self.write_ea_u16(segment, offset.wrapping_add(i * 2), word);
                             }
                         }
 
@@ -4506,7 +5405,7 @@ impl<M: Memory8086> Cpu8086<M> {
                             let (segment, offset, _) = self.calc_effective_address(modbits, rm);
                             let mut val = 0u64;
                             for i in 0..4 {
-                                let word = self.read_u16(segment, offset.wrapping_add(i * 2));
+                                let word = self.read_ea_u16(segment, offset.wrapping_add(i * 2));
                                 val |= (word as u64) << (i * 16);
                             }
                             val
@@ -4546,7 +5445,7 @@ impl<M: Memory8086> Cpu8086<M> {
                             let (segment, offset, _) = self.calc_effective_address(modbits, rm);
                             let mut val = 0u64;
                             for i in 0..4 {
-                                let word = self.read_u16(segment, offset.wrapping_add(i * 2));
+                                let word = self.read_ea_u16(segment, offset.wrapping_add(i * 2));
                                 val |= (word as u64) << (i * 16);
                             }
                             val
@@ -4586,7 +5485,7 @@ impl<M: Memory8086> Cpu8086<M> {
                             let (segment, offset, _) = self.calc_effective_address(modbits, rm);
                             let mut val = 0u64;
                             for i in 0..4 {
-                                let word = self.read_u16(segment, offset.wrapping_add(i * 2));
+                                let word = self.read_ea_u16(segment, offset.wrapping_add(i * 2));
                                 val |= (word as u64) << (i * 16);
                             }
                             val
@@ -4626,7 +5525,7 @@ impl<M: Memory8086> Cpu8086<M> {
                             let (segment, offset, _) = self.calc_effective_address(modbits, rm);
                             let mut val = 0u64;
                             for i in 0..4 {
-                                let word = self.read_u16(segment, offset.wrapping_add(i * 2));
+                                let word = self.read_ea_u16(segment, offset.wrapping_add(i * 2));
                                 val |= (word as u64) << (i * 16);
                             }
                             val
@@ -4666,7 +5565,7 @@ impl<M: Memory8086> Cpu8086<M> {
                             let (segment, offset, _) = self.calc_effective_address(modbits, rm);
                             let mut val = 0u64;
                             for i in 0..4 {
-                                let word = self.read_u16(segment, offset.wrapping_add(i * 2));
+                                let word = self.read_ea_u16(segment, offset.wrapping_add(i * 2));
                                 val |= (word as u64) << (i * 16);
                             }
                             val
@@ -4706,7 +5605,7 @@ impl<M: Memory8086> Cpu8086<M> {
                             let (segment, offset, _) = self.calc_effective_address(modbits, rm);
                             let mut val = 0u64;
                             for i in 0..4 {
-                                let word = self.read_u16(segment, offset.wrapping_add(i * 2));
+                                let word = self.read_ea_u16(segment, offset.wrapping_add(i * 2));
                                 val |= (word as u64) << (i * 16);
                             }
                             val
@@ -4746,7 +5645,7 @@ impl<M: Memory8086> Cpu8086<M> {
                             let (segment, offset, _) = self.calc_effective_address(modbits, rm);
                             let mut val = 0u64;
                             for i in 0..4 {
-                                let word = self.read_u16(segment, offset.wrapping_add(i * 2));
+                                let word = self.read_ea_u16(segment, offset.wrapping_add(i * 2));
                                 val |= (word as u64) << (i * 16);
                             }
                             val
@@ -4775,7 +5674,7 @@ impl<M: Memory8086> Cpu8086<M> {
                             let (segment, offset, _) = self.calc_effective_address(modbits, rm);
                             let mut val = 0u64;
                             for i in 0..4 {
-                                let word = self.read_u16(segment, offset.wrapping_add(i * 2));
+                                let word = self.read_ea_u16(segment, offset.wrapping_add(i * 2));
                                 val |= (word as u64) << (i * 16);
                             }
                             val
@@ -4804,7 +5703,7 @@ impl<M: Memory8086> Cpu8086<M> {
                             let (segment, offset, _) = self.calc_effective_address(modbits, rm);
                             let mut val = 0u64;
                             for i in 0..4 {
-                                let word = self.read_u16(segment, offset.wrapping_add(i * 2));
+                                let word = self.read_ea_u16(segment, offset.wrapping_add(i * 2));
                                 val |= (word as u64) << (i * 16);
                             }
                             val
@@ -4833,7 +5732,7 @@ impl<M: Memory8086> Cpu8086<M> {
                             let (segment, offset, _) = self.calc_effective_address(modbits, rm);
                             let mut val = 0u64;
                             for i in 0..4 {
-                                let word = self.read_u16(segment, offset.wrapping_add(i * 2));
+                                let word = self.read_ea_u16(segment, offset.wrapping_add(i * 2));
                                 val |= (word as u64) << (i * 16);
                             }
                             val
@@ -4873,7 +5772,7 @@ impl<M: Memory8086> Cpu8086<M> {
                             let (segment, offset, _) = self.calc_effective_address(modbits, rm);
                             let mut val = 0u64;
                             for i in 0..4 {
-                                let word = self.read_u16(segment, offset.wrapping_add(i * 2));
+                                let word = self.read_ea_u16(segment, offset.wrapping_add(i * 2));
                                 val |= (word as u64) << (i * 16);
                             }
                             val
@@ -4913,7 +5812,7 @@ impl<M: Memory8086> Cpu8086<M> {
                             let (segment, offset, _) = self.calc_effective_address(modbits, rm);
                             let mut val = 0u64;
                             for i in 0..4 {
-                                let word = self.read_u16(segment, offset.wrapping_add(i * 2));
+                                let word = self.read_ea_u16(segment, offset.wrapping_add(i * 2));
                                 val |= (word as u64) << (i * 16);
                             }
                             val
@@ -5188,8 +6087,7 @@ impl<M: Memory8086> Cpu8086<M> {
             0x16 => {
                 if self.operand_size_override && self.model.supports_80386_instructions() {
                     // Push 32-bit (zero-extend segment)
-                    self.sp = self.sp.wrapping_sub(4);
-                    self.write_u32(self.ss, self.sp, self.ss as u32);
+                    self.push32(self.ss as u32);
                 } else {
                     // Push 16-bit
                     self.push(self.ss);
@@ -5200,15 +6098,8 @@ impl<M: Memory8086> Cpu8086<M> {
 
             // POP SS (0x17)
             0x17 => {
-                if self.operand_size_override && self.model.supports_80386_instructions() {
-                    // Pop 32-bit (discard upper 16 bits)
-                    self.sp = self.sp.wrapping_add(4);
-                    let value = self.read_u32(self.ss, self.sp.wrapping_sub(4));
-                    self.ss = (value & 0xFFFF) as u16;
-                } else {
-                    // Pop 16-bit
-                    self.ss = self.pop();
-                }
+                // With a 32-bit operand size a dword is popped (upper half discarded)
+                self.pop_segment(SEG_SS);
                 self.cycles += 8;
                 8
             }
@@ -5446,8 +6337,7 @@ impl<M: Memory8086> Cpu8086<M> {
             0x1E => {
                 if self.operand_size_override && self.model.supports_80386_instructions() {
                     // Push 32-bit (zero-extend segment)
-                    self.sp = self.sp.wrapping_sub(4);
-                    self.write_u32(self.ss, self.sp, self.ds as u32);
+                    self.push32(self.ds as u32);
                 } else {
                     // Push 16-bit
                     self.push(self.ds);
@@ -5458,16 +6348,8 @@ impl<M: Memory8086> Cpu8086<M> {
 
             // POP DS (0x1F)
             0x1F => {
-                if self.operand_size_override && self.model.supports_80386_instructions() {
-                    // Pop 32-bit (discard upper 16 bits)
-                    self.sp = self.sp.wrapping_add(4);
-                    let value = self.read_u32(self.ss, self.sp.wrapping_sub(4));
-                    self.ds = (value & 0xFFFF) as u16;
-                } else {
-                    // Pop 16-bit
-                    let val = self.pop();
-                    self.ds = val;
-                }
+                // With a 32-bit operand size a dword is popped (upper half discarded)
+                self.pop_segment(SEG_DS);
                 self.cycles += 8;
                 8
             }
@@ -5636,7 +6518,7 @@ impl<M: Memory8086> Cpu8086<M> {
             0x36 => {
                 // SS segment override prefix
                 self.segment_override = Some(SegmentOverride::SS);
-                self.step() // Execute next instruction with SS override
+                self.execute_opcode() // Execute next instruction with SS override
             }
 
             // AAA - ASCII Adjust After Addition (0x37)
@@ -5659,7 +6541,7 @@ impl<M: Memory8086> Cpu8086<M> {
             0x3E => {
                 // DS segment override prefix
                 self.segment_override = Some(SegmentOverride::DS);
-                self.step() // Execute next instruction with DS override
+                self.execute_opcode() // Execute next instruction with DS override
             }
 
             // AAS - ASCII Adjust After Subtraction (0x3F)
@@ -5684,7 +6566,7 @@ impl<M: Memory8086> Cpu8086<M> {
             0x70 => {
                 let offset = self.fetch_u8() as i8;
                 if self.get_flag(FLAG_OF) {
-                    self.ip = (self.ip.wrapping_add((offset as i16) as u32)) & 0xFFFF;
+                    self.jump_relative(offset as i32);
                     self.cycles += 16;
                     16
                 } else {
@@ -5697,7 +6579,7 @@ impl<M: Memory8086> Cpu8086<M> {
             0x71 => {
                 let offset = self.fetch_u8() as i8;
                 if !self.get_flag(FLAG_OF) {
-                    self.ip = (self.ip.wrapping_add((offset as i16) as u32)) & 0xFFFF;
+                    self.jump_relative(offset as i32);
                     self.cycles += 16;
                     16
                 } else {
@@ -5710,7 +6592,7 @@ impl<M: Memory8086> Cpu8086<M> {
             0x72 => {
                 let offset = self.fetch_u8() as i8;
                 if self.get_flag(FLAG_CF) {
-                    self.ip = (self.ip.wrapping_add((offset as i16) as u32)) & 0xFFFF;
+                    self.jump_relative(offset as i32);
                     self.cycles += 16;
                     16
                 } else {
@@ -5723,7 +6605,7 @@ impl<M: Memory8086> Cpu8086<M> {
             0x73 => {
                 let offset = self.fetch_u8() as i8;
                 if !self.get_flag(FLAG_CF) {
-                    self.ip = (self.ip.wrapping_add((offset as i16) as u32)) & 0xFFFF;
+                    self.jump_relative(offset as i32);
                     self.cycles += 16;
                     16
                 } else {
@@ -5736,7 +6618,7 @@ impl<M: Memory8086> Cpu8086<M> {
             0x74 => {
                 let offset = self.fetch_u8() as i8;
                 if self.get_flag(FLAG_ZF) {
-                    self.ip = (self.ip.wrapping_add((offset as i16) as u32)) & 0xFFFF;
+                    self.jump_relative(offset as i32);
                     self.cycles += 16;
                     16
                 } else {
@@ -5749,7 +6631,7 @@ impl<M: Memory8086> Cpu8086<M> {
             0x75 => {
                 let offset = self.fetch_u8() as i8;
                 if !self.get_flag(FLAG_ZF) {
-                    self.ip = (self.ip.wrapping_add((offset as i16) as u32)) & 0xFFFF;
+                    self.jump_relative(offset as i32);
                     self.cycles += 16;
                     16
                 } else {
@@ -5762,7 +6644,7 @@ impl<M: Memory8086> Cpu8086<M> {
             0x76 => {
                 let offset = self.fetch_u8() as i8;
                 if self.get_flag(FLAG_CF) || self.get_flag(FLAG_ZF) {
-                    self.ip = (self.ip.wrapping_add((offset as i16) as u32)) & 0xFFFF;
+                    self.jump_relative(offset as i32);
                     self.cycles += 16;
                     16
                 } else {
@@ -5775,7 +6657,7 @@ impl<M: Memory8086> Cpu8086<M> {
             0x77 => {
                 let offset = self.fetch_u8() as i8;
                 if !self.get_flag(FLAG_CF) && !self.get_flag(FLAG_ZF) {
-                    self.ip = (self.ip.wrapping_add((offset as i16) as u32)) & 0xFFFF;
+                    self.jump_relative(offset as i32);
                     self.cycles += 16;
                     16
                 } else {
@@ -5788,7 +6670,7 @@ impl<M: Memory8086> Cpu8086<M> {
             0x78 => {
                 let offset = self.fetch_u8() as i8;
                 if self.get_flag(FLAG_SF) {
-                    self.ip = (self.ip.wrapping_add((offset as i16) as u32)) & 0xFFFF;
+                    self.jump_relative(offset as i32);
                     self.cycles += 16;
                     16
                 } else {
@@ -5801,7 +6683,7 @@ impl<M: Memory8086> Cpu8086<M> {
             0x79 => {
                 let offset = self.fetch_u8() as i8;
                 if !self.get_flag(FLAG_SF) {
-                    self.ip = (self.ip.wrapping_add((offset as i16) as u32)) & 0xFFFF;
+                    self.jump_relative(offset as i32);
                     self.cycles += 16;
                     16
                 } else {
@@ -5814,7 +6696,7 @@ impl<M: Memory8086> Cpu8086<M> {
             0x7A => {
                 let offset = self.fetch_u8() as i8;
                 if self.get_flag(FLAG_PF) {
-                    self.ip = (self.ip.wrapping_add((offset as i16) as u32)) & 0xFFFF;
+                    self.jump_relative(offset as i32);
                     self.cycles += 16;
                     16
                 } else {
@@ -5827,7 +6709,7 @@ impl<M: Memory8086> Cpu8086<M> {
             0x7B => {
                 let offset = self.fetch_u8() as i8;
                 if !self.get_flag(FLAG_PF) {
-                    self.ip = (self.ip.wrapping_add((offset as i16) as u32)) & 0xFFFF;
+                    self.jump_relative(offset as i32);
                     self.cycles += 16;
                     16
                 } else {
@@ -5840,7 +6722,7 @@ impl<M: Memory8086> Cpu8086<M> {
             0x7C => {
                 let offset = self.fetch_u8() as i8;
                 if self.get_flag(FLAG_SF) != self.get_flag(FLAG_OF) {
-                    self.ip = (self.ip.wrapping_add((offset as i16) as u32)) & 0xFFFF;
+                    self.jump_relative(offset as i32);
                     self.cycles += 16;
                     16
                 } else {
@@ -5853,7 +6735,7 @@ impl<M: Memory8086> Cpu8086<M> {
             0x7D => {
                 let offset = self.fetch_u8() as i8;
                 if self.get_flag(FLAG_SF) == self.get_flag(FLAG_OF) {
-                    self.ip = (self.ip.wrapping_add((offset as i16) as u32)) & 0xFFFF;
+                    self.jump_relative(offset as i32);
                     self.cycles += 16;
                     16
                 } else {
@@ -5866,7 +6748,7 @@ impl<M: Memory8086> Cpu8086<M> {
             0x7E => {
                 let offset = self.fetch_u8() as i8;
                 if self.get_flag(FLAG_ZF) || (self.get_flag(FLAG_SF) != self.get_flag(FLAG_OF)) {
-                    self.ip = (self.ip.wrapping_add((offset as i16) as u32)) & 0xFFFF;
+                    self.jump_relative(offset as i32);
                     self.cycles += 16;
                     16
                 } else {
@@ -5879,7 +6761,7 @@ impl<M: Memory8086> Cpu8086<M> {
             0x7F => {
                 let offset = self.fetch_u8() as i8;
                 if !self.get_flag(FLAG_ZF) && (self.get_flag(FLAG_SF) == self.get_flag(FLAG_OF)) {
-                    self.ip = (self.ip.wrapping_add((offset as i16) as u32)) & 0xFFFF;
+                    self.jump_relative(offset as i32);
                     self.cycles += 16;
                     16
                 } else {
@@ -6198,7 +7080,7 @@ impl<M: Memory8086> Cpu8086<M> {
             // 0x82 - Same as 0x80 (alias for 8086)
             0x82 => {
                 self.ip = self.ip.wrapping_sub(1); // Back up and execute as 0x80
-                self.step()
+                self.execute_opcode()
             }
 
             // 0x83 - r/m16/32, imm8 (sign-extended)
@@ -6518,7 +7400,11 @@ impl<M: Memory8086> Cpu8086<M> {
                 // LEA only works with memory operands (not register mode)
                 if modbits != 0b11 {
                     let offset_ea = self.calc_effective_offset(modbits, rm);
-                    self.set_reg16(reg, offset_ea);
+                    if self.operand_size_override {
+                        self.set_reg32(reg, offset_ea);
+                    } else {
+                        self.set_reg16(reg, offset_ea as u16);
+                    }
                 }
                 self.cycles += 2;
                 2
@@ -6567,8 +7453,7 @@ impl<M: Memory8086> Cpu8086<M> {
             0x9C => {
                 if self.operand_size_override && self.model.supports_80386_instructions() {
                     // 32-bit: PUSHFD - push 32-bit EFLAGS
-                    self.sp = self.sp.wrapping_sub(4);
-                    self.write_u32(self.ss, self.sp, self.flags);
+                    self.push32(self.flags);
                 } else {
                     // 16-bit: PUSHF - push 16-bit FLAGS
                     self.push(self.flags as u16);
@@ -6581,8 +7466,7 @@ impl<M: Memory8086> Cpu8086<M> {
             0x9D => {
                 if self.operand_size_override && self.model.supports_80386_instructions() {
                     // 32-bit: POPFD - pop 32-bit EFLAGS
-                    self.flags = self.read_u32(self.ss, self.sp);
-                    self.sp = self.sp.wrapping_add(4);
+                    self.flags = self.pop32();
                 } else {
                     // 16-bit: POPF - pop 16-bit FLAGS
                     self.flags = self.pop() as u32;
@@ -6993,14 +7877,7 @@ impl<M: Memory8086> Cpu8086<M> {
             0xC4 => {
                 let modrm = self.fetch_u8();
                 let (modbits, reg, rm) = Self::decode_modrm(modrm);
-                // LES only works with memory operands
-                if modbits != 0b11 {
-                    let (seg, offset_ea, _) = self.calc_effective_address(modbits, rm);
-                    let offset = self.read_u16(seg, offset_ea);
-                    let segment = self.read_u16(seg, offset_ea.wrapping_add(2));
-                    self.set_reg16(reg, offset);
-                    self.es = segment;
-                }
+                self.load_far_pointer(SEG_ES, modbits, reg, rm);
                 self.cycles += 16;
                 16
             }
@@ -7009,14 +7886,7 @@ impl<M: Memory8086> Cpu8086<M> {
             0xC5 => {
                 let modrm = self.fetch_u8();
                 let (modbits, reg, rm) = Self::decode_modrm(modrm);
-                // LDS only works with memory operands
-                if modbits != 0b11 {
-                    let (seg, offset_ea, _) = self.calc_effective_address(modbits, rm);
-                    let offset = self.read_u16(seg, offset_ea);
-                    let segment = self.read_u16(seg, offset_ea.wrapping_add(2));
-                    self.set_reg16(reg, offset);
-                    self.ds = segment;
-                }
+                self.load_far_pointer(SEG_DS, modbits, reg, rm);
                 self.cycles += 16;
                 16
             }
@@ -7041,7 +7911,7 @@ impl<M: Memory8086> Cpu8086<M> {
                         // Memory mode - get effective address first to consume displacement bytes
                         let (seg, offset, _) = self.calc_effective_address(modbits, rm);
                         let imm = self.fetch_u8(); // Now fetch immediate after displacement
-                        self.write(seg, offset, imm);
+                        self.write_at(seg, offset, imm);
                         self.cycles += 10;
                         10
                     }
@@ -7109,7 +7979,7 @@ impl<M: Memory8086> Cpu8086<M> {
                             // Memory mode - get effective address first to consume displacement bytes
                             let (seg, offset, _) = self.calc_effective_address(modbits, rm);
                             let imm = self.fetch_u16(); // Now fetch immediate after displacement
-                            self.write_u16(seg, offset, imm);
+                            self.write_ea_u16(seg, offset, imm);
                             self.cycles += 10;
                             10
                         }
@@ -7247,11 +8117,15 @@ impl<M: Memory8086> Cpu8086<M> {
                     );
                 }
 
-                // Simplified implementation
-                self.push(self.bp as u16);
-                let frame_temp = self.sp;
-                self.bp = frame_temp;
-                self.sp = self.sp.wrapping_sub(size as u32);
+                // Simplified implementation (nesting level ignored)
+                if self.operand_size_override {
+                    self.push32(self.bp);
+                } else {
+                    self.push(self.bp as u16);
+                }
+                let frame_temp = self.stack_offset();
+                self.set_reg_v(5, frame_temp);
+                self.adjust_sp(-(size as i32));
 
                 if LogConfig::global().should_log(LogCategory::CPU, LogLevel::Trace) {
                     eprintln!("[ENTER] BP after={:04X}, SP after={:04X}", self.bp, self.sp);
@@ -7268,8 +8142,15 @@ impl<M: Memory8086> Cpu8086<M> {
                     self.cycles += 10;
                     return 10;
                 }
-                self.sp = self.bp;
-                self.bp = (self.bp & 0xFFFF_0000) | (self.pop() as u32);
+                // SP (or ESP for a 32-bit stack) = BP, then pop BP/EBP
+                let mask = self.stack_offset_mask();
+                self.sp = (self.sp & !mask) | (self.bp & mask);
+                let bp = if self.operand_size_override {
+                    self.pop32()
+                } else {
+                    self.pop() as u32
+                };
+                self.set_reg_v(5, bp);
                 self.cycles += 8;
                 8
             }
@@ -7472,11 +8353,9 @@ impl<M: Memory8086> Cpu8086<M> {
             // LOOPNE/LOOPNZ (0xE0)
             0xE0 => {
                 let offset = self.fetch_u8() as i8;
-                // Decrement CX as 16-bit value (preserve upper 16 bits)
-                let cx = ((self.cx as u16).wrapping_sub(1)) as u32;
-                self.cx = (self.cx & 0xFFFF_0000) | cx;
-                if (cx as u16) != 0 && !self.get_flag(FLAG_ZF) {
-                    self.ip = (self.ip.wrapping_add((offset as i16) as u32)) & 0xFFFF;
+                let count = self.decrement_count();
+                if count != 0 && !self.get_flag(FLAG_ZF) {
+                    self.jump_relative(offset as i32);
                     self.cycles += 19;
                     19
                 } else {
@@ -7488,11 +8367,9 @@ impl<M: Memory8086> Cpu8086<M> {
             // LOOPE/LOOPZ (0xE1)
             0xE1 => {
                 let offset = self.fetch_u8() as i8;
-                // Decrement CX as 16-bit value (preserve upper 16 bits)
-                let cx = ((self.cx as u16).wrapping_sub(1)) as u32;
-                self.cx = (self.cx & 0xFFFF_0000) | cx;
-                if (cx as u16) != 0 && self.get_flag(FLAG_ZF) {
-                    self.ip = (self.ip.wrapping_add((offset as i16) as u32)) & 0xFFFF;
+                let count = self.decrement_count();
+                if count != 0 && self.get_flag(FLAG_ZF) {
+                    self.jump_relative(offset as i32);
                     self.cycles += 18;
                     18
                 } else {
@@ -7504,11 +8381,9 @@ impl<M: Memory8086> Cpu8086<M> {
             // LOOP (0xE2)
             0xE2 => {
                 let offset = self.fetch_u8() as i8;
-                // Decrement CX as 16-bit value (preserve upper 16 bits)
-                let cx = ((self.cx as u16).wrapping_sub(1)) as u32;
-                self.cx = (self.cx & 0xFFFF_0000) | cx;
-                if (cx as u16) != 0 {
-                    self.ip = (self.ip.wrapping_add((offset as i16) as u32)) & 0xFFFF;
+                let count = self.decrement_count();
+                if count != 0 {
+                    self.jump_relative(offset as i32);
                     self.cycles += 17;
                     17
                 } else {
@@ -7520,8 +8395,8 @@ impl<M: Memory8086> Cpu8086<M> {
             // JCXZ - Jump if CX is Zero (0xE3)
             0xE3 => {
                 let offset = self.fetch_u8() as i8;
-                if (self.cx as u16) == 0 {
-                    self.ip = (self.ip.wrapping_add((offset as i16) as u32)) & 0xFFFF;
+                if self.cx & self.count_mask() == 0 {
+                    self.jump_relative(offset as i32);
                     self.cycles += 18;
                     18
                 } else {
@@ -7574,7 +8449,7 @@ impl<M: Memory8086> Cpu8086<M> {
                 } else {
                     // 16-bit near jump
                     let offset = self.fetch_u16() as i16;
-                    self.ip = (self.ip.wrapping_add((offset as i16) as u32)) & 0xFFFF;
+                    self.jump_relative(offset as i32);
                 }
                 self.cycles += 15;
                 15
@@ -7582,7 +8457,17 @@ impl<M: Memory8086> Cpu8086<M> {
 
             // JMP far absolute (0xEA)
             0xEA => {
-                if self.operand_size_override && self.model.supports_80386_instructions() {
+                if self.protected_mode.is_protected_mode() {
+                    let offset = if self.operand_size_override {
+                        self.fetch_u32()
+                    } else {
+                        self.fetch_u16() as u32
+                    };
+                    let selector = self.fetch_u16();
+                    if let Err(fault) = self.protected_far_transfer(selector, offset, false) {
+                        self.record_fault(fault);
+                    }
+                } else if self.operand_size_override && self.model.supports_80386_instructions() {
                     // 32-bit far jump
                     let offset = self.fetch_u32();
                     let segment = self.fetch_u16();
@@ -7635,7 +8520,7 @@ impl<M: Memory8086> Cpu8086<M> {
                 // LOCK prefix - for basic emulation, just execute next instruction
                 let _next_opcode = self.fetch_u8();
                 self.ip = self.ip.wrapping_sub(1);
-                self.step()
+                self.execute_opcode()
             }
 
             // Undefined/INT1 (0xF1)
@@ -7758,12 +8643,20 @@ impl<M: Memory8086> Cpu8086<M> {
                     3 => {
                         // CALL m16:16 (far)
                         let (seg, offset_ea, _) = self.calc_effective_address(modbits, rm);
-                        let offset = self.read_u16(seg, offset_ea);
-                        let segment = self.read_u16(seg, offset_ea.wrapping_add(2));
-                        self.push(self.cs);
-                        self.push(self.ip as u16);
-                        self.ip = offset as u32;
-                        self.cs = segment;
+                        let offset = self.read_ea_u16(seg, offset_ea);
+                        let segment = self.read_ea_u16(seg, offset_ea.wrapping_add(2));
+                        if self.protected_mode.is_protected_mode() {
+                            if let Err(fault) =
+                                self.protected_far_transfer(segment, offset as u32, true)
+                            {
+                                self.record_fault(fault);
+                            }
+                        } else {
+                            self.push(self.cs);
+                            self.push(self.ip as u16);
+                            self.ip = offset as u32;
+                            self.cs = segment;
+                        }
                         self.cycles += 37;
                         37
                     }
@@ -7781,10 +8674,18 @@ impl<M: Memory8086> Cpu8086<M> {
                     5 => {
                         // JMP m16:16 (far)
                         let (seg, offset_ea, _) = self.calc_effective_address(modbits, rm);
-                        let offset = self.read_u16(seg, offset_ea);
-                        let segment = self.read_u16(seg, offset_ea.wrapping_add(2));
-                        self.ip = offset as u32;
-                        self.cs = segment;
+                        let offset = self.read_ea_u16(seg, offset_ea);
+                        let segment = self.read_ea_u16(seg, offset_ea.wrapping_add(2));
+                        if self.protected_mode.is_protected_mode() {
+                            if let Err(fault) =
+                                self.protected_far_transfer(segment, offset as u32, false)
+                            {
+                                self.record_fault(fault);
+                            }
+                        } else {
+                            self.ip = offset as u32;
+                            self.cs = segment;
+                        }
                         self.cycles += 24;
                         24
                     }
@@ -7897,8 +8798,7 @@ impl<M: Memory8086> Cpu8086<M> {
                 if self.operand_size_override && self.model.supports_80386_instructions() {
                     // 32-bit push: push 32-bit register value
                     let val = self.get_reg32(reg);
-                    self.sp = self.sp.wrapping_sub(4);
-                    self.write_u32(self.ss, self.sp, val);
+                    self.push32(val);
                 } else {
                     // 16-bit push
                     let val = self.get_reg16(reg);
@@ -7913,8 +8813,7 @@ impl<M: Memory8086> Cpu8086<M> {
                 let reg = opcode & 0x07;
                 if self.operand_size_override && self.model.supports_80386_instructions() {
                     // 32-bit pop
-                    let val = self.read_u32(self.ss, self.sp);
-                    self.sp = self.sp.wrapping_add(4);
+                    let val = self.pop32();
                     self.set_reg32(reg, val);
                 } else {
                     // 16-bit pop
@@ -7935,22 +8834,14 @@ impl<M: Memory8086> Cpu8086<M> {
                 let temp_sp = self.sp;
                 if self.operand_size_override && self.model.supports_80386_instructions() {
                     // 32-bit: PUSHAD - push all 32-bit registers
-                    self.sp = self.sp.wrapping_sub(4);
-                    self.write_u32(self.ss, self.sp, self.ax);
-                    self.sp = self.sp.wrapping_sub(4);
-                    self.write_u32(self.ss, self.sp, self.cx);
-                    self.sp = self.sp.wrapping_sub(4);
-                    self.write_u32(self.ss, self.sp, self.dx);
-                    self.sp = self.sp.wrapping_sub(4);
-                    self.write_u32(self.ss, self.sp, self.bx);
-                    self.sp = self.sp.wrapping_sub(4);
-                    self.write_u32(self.ss, self.sp, temp_sp);
-                    self.sp = self.sp.wrapping_sub(4);
-                    self.write_u32(self.ss, self.sp, self.bp);
-                    self.sp = self.sp.wrapping_sub(4);
-                    self.write_u32(self.ss, self.sp, self.si);
-                    self.sp = self.sp.wrapping_sub(4);
-                    self.write_u32(self.ss, self.sp, self.di);
+                    self.push32(self.ax);
+                    self.push32(self.cx);
+                    self.push32(self.dx);
+                    self.push32(self.bx);
+                    self.push32(temp_sp);
+                    self.push32(self.bp);
+                    self.push32(self.si);
+                    self.push32(self.di);
                 } else {
                     // 16-bit: PUSHA - push all 16-bit registers
                     self.push(self.ax as u16);
@@ -7975,22 +8866,14 @@ impl<M: Memory8086> Cpu8086<M> {
                 }
                 if self.operand_size_override && self.model.supports_80386_instructions() {
                     // 32-bit: POPAD - pop all 32-bit registers
-                    self.di = self.read_u32(self.ss, self.sp);
-                    self.sp = self.sp.wrapping_add(4);
-                    self.si = self.read_u32(self.ss, self.sp);
-                    self.sp = self.sp.wrapping_add(4);
-                    self.bp = self.read_u32(self.ss, self.sp);
-                    self.sp = self.sp.wrapping_add(4);
-                    let _temp_sp = self.read_u32(self.ss, self.sp);
-                    self.sp = self.sp.wrapping_add(4);
-                    self.bx = self.read_u32(self.ss, self.sp);
-                    self.sp = self.sp.wrapping_add(4);
-                    self.dx = self.read_u32(self.ss, self.sp);
-                    self.sp = self.sp.wrapping_add(4);
-                    self.cx = self.read_u32(self.ss, self.sp);
-                    self.sp = self.sp.wrapping_add(4);
-                    self.ax = self.read_u32(self.ss, self.sp);
-                    self.sp = self.sp.wrapping_add(4);
+                    self.di = self.pop32();
+                    self.si = self.pop32();
+                    self.bp = self.pop32();
+                    let _temp_sp = self.pop32();
+                    self.bx = self.pop32();
+                    self.dx = self.pop32();
+                    self.cx = self.pop32();
+                    self.ax = self.pop32();
                 } else {
                     // 16-bit: POPA - pop all 16-bit registers
                     self.di = (self.di & 0xFFFF_0000) | (self.pop() as u32);
@@ -8017,8 +8900,8 @@ impl<M: Memory8086> Cpu8086<M> {
                 let (modbits, reg, rm) = Self::decode_modrm(modrm);
                 let index = self.get_reg16(reg) as i16;
                 let (_seg, ea, _bytes) = self.calc_effective_address(modbits, rm);
-                let lower_bound = self.read_u16(self.ds, ea) as i16;
-                let upper_bound = self.read_u16(self.ds, ea.wrapping_add(2)) as i16;
+                let lower_bound = self.read_ea_u16(self.ds, ea) as i16;
+                let upper_bound = self.read_ea_u16(self.ds, ea.wrapping_add(2)) as i16;
 
                 // If index is out of bounds, generate INT 5 as exception
                 if index < lower_bound || index > upper_bound {
@@ -8057,7 +8940,7 @@ impl<M: Memory8086> Cpu8086<M> {
                 }
                 // FS segment override prefix
                 self.segment_override = Some(SegmentOverride::FS);
-                self.step() // Execute next instruction with FS override
+                self.execute_opcode() // Execute next instruction with FS override
             }
 
             // GS segment override prefix (0x65) - 80386+
@@ -8069,7 +8952,7 @@ impl<M: Memory8086> Cpu8086<M> {
                 }
                 // GS segment override prefix
                 self.segment_override = Some(SegmentOverride::GS);
-                self.step() // Execute next instruction with GS override
+                self.execute_opcode() // Execute next instruction with GS override
             }
 
             // Operand-size override prefix (0x66) - 80386+
@@ -8081,9 +8964,9 @@ impl<M: Memory8086> Cpu8086<M> {
                 }
                 // Operand-size override prefix
                 // On 80386+, this toggles between 16-bit and 32-bit operand size
-                // For now, we set a flag and handle it in individual instructions
-                self.operand_size_override = true;
-                self.step() // Execute next instruction with operand size override
+                // (the default comes from the D bit of the code segment)
+                self.operand_size_override = !self.code_segment_is_32bit();
+                self.execute_opcode() // Execute next instruction with operand size override
             }
 
             // Address-size override prefix (0x67) - 80386+
@@ -8095,16 +8978,9 @@ impl<M: Memory8086> Cpu8086<M> {
                 }
                 // Address-size override prefix
                 // On 80386+, this toggles between 16-bit and 32-bit addressing
-                // For now, we set a flag but don't fully implement 32-bit addressing modes.
-                // IMPLEMENTATION NOTE: Full 32-bit addressing requires:
-                //   - 32-bit registers (EAX, EBX, ECX, EDX, ESI, EDI, EBP, ESP)
-                //   - SIB byte decoding for [EAX+EBX*4+disp32] style addressing
-                //   - ModR/M extensions for 32-bit modes
-                // Most 16-bit DOS/Windows software doesn't use this prefix in practice.
-                // FUTURE ENHANCEMENT: Implement full 32-bit addressing for complete 386+ compatibility.
-                //                     See CPU_REVIEW_RESULTS.md - marked as "Long Term" enhancement.
-                self.address_size_override = true;
-                self.step() // Execute next instruction with address size override
+                // (ModR/M with SIB byte, ESI/EDI/ECX for string instructions)
+                self.address_size_override = !self.code_segment_is_32bit();
+                self.execute_opcode() // Execute next instruction with address size override
             }
 
             // PUSH immediate word (0x68) - 80186+
@@ -8120,7 +8996,7 @@ impl<M: Memory8086> Cpu8086<M> {
                 3
             }
 
-            // IMUL r16, r/m16, imm16 (0x69) - 80186+
+            // IMUL r16/32, r/m16/32, imm16/32 (0x69) - 80186+
             0x69 => {
                 if !self.model.supports_80186_instructions() {
                     // Invalid opcode on 8086/8088
@@ -8129,18 +9005,14 @@ impl<M: Memory8086> Cpu8086<M> {
                 }
                 let modrm = self.fetch_u8();
                 let (modbits, reg, rm) = Self::decode_modrm(modrm);
-                let rm_val = self.read_rm16(modbits, rm) as i16;
-                let imm = self.fetch_u16() as i16;
-                let result = rm_val.wrapping_mul(imm);
-
-                self.set_reg16(reg, result as u16);
-
-                // Set CF and OF if result doesn't fit in signed 16-bit
-                // Check if the multiplication would overflow by comparing to extended multiply
-                let extended_result = (rm_val as i32) * (imm as i32);
-                let overflow = extended_result != (result as i32);
-                self.set_flag(FLAG_CF, overflow);
-                self.set_flag(FLAG_OF, overflow);
+                let rm_val = self.read_rm_v(modbits, rm);
+                let imm = if self.operand_size_override {
+                    self.fetch_u32()
+                } else {
+                    self.fetch_u16() as u32
+                };
+                // CF and OF are set if the result doesn't fit the operand size
+                self.imul_v(reg, rm_val, imm);
 
                 self.cycles += if modbits == 0b11 { 21 } else { 24 };
                 if modbits == 0b11 {
@@ -8161,8 +9033,7 @@ impl<M: Memory8086> Cpu8086<M> {
                 if self.operand_size_override && self.model.supports_80386_instructions() {
                     // 32-bit operand size: push 32-bit sign-extended value
                     let val = self.fetch_u8() as i8 as i32 as u32;
-                    self.sp = self.sp.wrapping_sub(4);
-                    self.write_u32(self.ss, self.sp, val);
+                    self.push32(val);
                 } else {
                     // 16-bit operand size: push 16-bit sign-extended value
                     let val = self.fetch_u8() as i8 as i16 as u16;
@@ -8172,7 +9043,7 @@ impl<M: Memory8086> Cpu8086<M> {
                 3
             }
 
-            // IMUL r16, r/m16, imm8 (0x6B) - 80186+
+            // IMUL r16/32, r/m16/32, imm8 (0x6B) - 80186+
             0x6B => {
                 if !self.model.supports_80186_instructions() {
                     // Invalid opcode on 8086/8088
@@ -8181,18 +9052,10 @@ impl<M: Memory8086> Cpu8086<M> {
                 }
                 let modrm = self.fetch_u8();
                 let (modbits, reg, rm) = Self::decode_modrm(modrm);
-                let rm_val = self.read_rm16(modbits, rm) as i16;
-                let imm = self.fetch_u8() as i8 as i16; // Sign extend
-                let result = rm_val.wrapping_mul(imm);
-
-                self.set_reg16(reg, result as u16);
-
-                // Set CF and OF if result doesn't fit in signed 16-bit
-                // Check if the multiplication would overflow by comparing to extended multiply
-                let extended_result = (rm_val as i32) * (imm as i32);
-                let overflow = extended_result != (result as i32);
-                self.set_flag(FLAG_CF, overflow);
-                self.set_flag(FLAG_OF, overflow);
+                let rm_val = self.read_rm_v(modbits, rm);
+                let imm = self.fetch_u8() as i8 as i32 as u32; // Sign extend
+                                                               // CF and OF are set if the result doesn't fit the operand size
+                self.imul_v(reg, rm_val, imm);
 
                 self.cycles += if modbits == 0b11 { 21 } else { 24 };
                 if modbits == 0b11 {
@@ -8294,7 +9157,7 @@ impl<M: Memory8086> Cpu8086<M> {
             0xEB => {
                 let offset = self.fetch_u8() as i8;
                 // Add signed offset to IP (wrapping_add_signed would be clearer but requires i16 cast)
-                self.ip = (self.ip.wrapping_add((offset as i16) as u32)) & 0xFFFF;
+                self.jump_relative(offset as i32);
                 self.cycles += 15;
                 15
             }
@@ -8512,14 +9375,13 @@ impl<M: Memory8086> Cpu8086<M> {
                 if self.operand_size_override && self.model.supports_80386_instructions() {
                     // 32-bit call: fetch 32-bit offset, push 32-bit return address
                     let offset = self.fetch_u32() as i32;
-                    self.sp = self.sp.wrapping_sub(4);
-                    self.write_u32(self.ss, self.sp, self.ip);
+                    self.push32(self.ip);
                     self.ip = self.ip.wrapping_add(offset as u32);
                 } else {
                     // 16-bit call: fetch 16-bit offset, push 16-bit return address
                     let offset = self.fetch_u16() as i16;
                     self.push(self.ip as u16);
-                    self.ip = (self.ip.wrapping_add((offset as i16) as u32)) & 0xFFFF;
+                    self.jump_relative(offset as i32);
                 }
                 self.cycles += 19;
                 19
@@ -8529,8 +9391,7 @@ impl<M: Memory8086> Cpu8086<M> {
             0xC3 => {
                 if self.operand_size_override && self.model.supports_80386_instructions() {
                     // 32-bit return: pop 32-bit address
-                    let ret_addr = self.read_u32(self.ss, self.sp);
-                    self.sp = self.sp.wrapping_add(4);
+                    let ret_addr = self.pop32();
                     self.ip = ret_addr;
                 } else {
                     // 16-bit return: pop 16-bit address
@@ -8545,29 +9406,36 @@ impl<M: Memory8086> Cpu8086<M> {
                 let pop_bytes = self.fetch_u16();
                 if self.operand_size_override && self.model.supports_80386_instructions() {
                     // 32-bit return
-                    let ret_addr = self.read_u32(self.ss, self.sp);
-                    self.sp = self.sp.wrapping_add(4);
+                    let ret_addr = self.pop32();
                     self.ip = ret_addr;
                 } else {
                     // 16-bit return
                     self.ip = self.pop() as u32;
                 }
-                self.sp = self.sp.wrapping_add(pop_bytes as u32);
+                self.adjust_sp(pop_bytes as i32);
                 self.cycles += 12;
                 12
             }
 
             // CALL far absolute (0x9A)
             0x9A => {
-                if self.operand_size_override && self.model.supports_80386_instructions() {
+                if self.protected_mode.is_protected_mode() {
+                    let offset = if self.operand_size_override {
+                        self.fetch_u32()
+                    } else {
+                        self.fetch_u16() as u32
+                    };
+                    let selector = self.fetch_u16();
+                    if let Err(fault) = self.protected_far_transfer(selector, offset, true) {
+                        self.record_fault(fault);
+                    }
+                } else if self.operand_size_override && self.model.supports_80386_instructions() {
                     // 32-bit far call: fetch 32-bit offset + 16-bit segment
                     let new_ip = self.fetch_u32();
                     let new_cs = self.fetch_u16();
                     // Push current CS and EIP (32-bit)
-                    self.sp = self.sp.wrapping_sub(4);
-                    self.write_u32(self.ss, self.sp, self.cs as u32);
-                    self.sp = self.sp.wrapping_sub(4);
-                    self.write_u32(self.ss, self.sp, self.ip);
+                    self.push32(self.cs as u32);
+                    self.push32(self.ip);
                     // Jump to far address
                     self.cs = new_cs;
                     self.ip = new_ip;
@@ -8588,12 +9456,14 @@ impl<M: Memory8086> Cpu8086<M> {
 
             // RET far (0xCB)
             0xCB => {
-                if self.operand_size_override && self.model.supports_80386_instructions() {
+                if self.protected_mode.is_protected_mode() {
+                    if let Err(fault) = self.protected_far_return(0) {
+                        self.record_fault(fault);
+                    }
+                } else if self.operand_size_override && self.model.supports_80386_instructions() {
                     // 32-bit far return: pop 32-bit EIP and 16-bit CS (actually 32-bit but use lower 16)
-                    self.ip = self.read_u32(self.ss, self.sp);
-                    self.sp = self.sp.wrapping_add(4);
-                    let cs_val = self.read_u32(self.ss, self.sp);
-                    self.sp = self.sp.wrapping_add(4);
+                    self.ip = self.pop32();
+                    let cs_val = self.pop32();
                     self.cs = cs_val as u16;
                 } else {
                     // 16-bit far return
@@ -8607,35 +9477,39 @@ impl<M: Memory8086> Cpu8086<M> {
             // RET far with immediate (0xCA) - pops return address and adds imm16 to SP
             0xCA => {
                 let pop_bytes = self.fetch_u16();
-                let ret_ip: u32;
-                let ret_cs: u16;
-
-                if self.operand_size_override && self.model.supports_80386_instructions() {
-                    // 32-bit far return with immediate
-                    ret_ip = self.read_u32(self.ss, self.sp);
-                    self.sp = self.sp.wrapping_add(4);
-                    let cs_val = self.read_u32(self.ss, self.sp);
-                    self.sp = self.sp.wrapping_add(4);
-                    ret_cs = cs_val as u16;
+                if self.protected_mode.is_protected_mode() {
+                    if let Err(fault) = self.protected_far_return(pop_bytes) {
+                        self.record_fault(fault);
+                    }
                 } else {
-                    // 16-bit far return with immediate
-                    ret_ip = self.pop() as u32;
-                    ret_cs = self.pop();
-                }
+                    let ret_ip: u32;
+                    let ret_cs: u16;
 
-                if LogConfig::global().should_log(LogCategory::CPU, LogLevel::Trace) {
-                    eprintln!(
-                        "[RETF] SP before={:04X}, pop_bytes={:04X}, ret_ip={:04X}, ret_cs={:04X}",
-                        self.sp.wrapping_add(4u32),
-                        pop_bytes,
-                        ret_ip,
-                        ret_cs
-                    );
-                }
+                    if self.operand_size_override && self.model.supports_80386_instructions() {
+                        // 32-bit far return with immediate
+                        ret_ip = self.pop32();
+                        let cs_val = self.pop32();
+                        ret_cs = cs_val as u16;
+                    } else {
+                        // 16-bit far return with immediate
+                        ret_ip = self.pop() as u32;
+                        ret_cs = self.pop();
+                    }
+
+                    if LogConfig::global().should_log(LogCategory::CPU, LogLevel::Trace) {
+                        eprintln!(
+                            "[RETF] SP before={:04X}, pop_bytes={:04X}, ret_ip={:04X}, ret_cs={:04X}",
+                            self.sp.wrapping_add(4u32),
+                            pop_bytes,
+                            ret_ip,
+                            ret_cs
+                        );
+                    }
 
-                self.ip = ret_ip as u32;
-                self.cs = ret_cs;
-                self.sp = self.sp.wrapping_add(pop_bytes as u32);
+                    self.ip = ret_ip as u32;
+                    self.cs = ret_cs;
+                    self.adjust_sp(pop_bytes as i32);
+                }
                 self.cycles += 17;
                 17
             }
@@ -8692,14 +9566,15 @@ impl<M: Memory8086> Cpu8086<M> {
 
             // IRET - Return from Interrupt
             0xCF => {
-                if self.operand_size_override && self.model.supports_80386_instructions() {
+                if self.protected_mode.is_protected_mode() {
+                    if let Err(fault) = self.protected_iret() {
+                        self.record_fault(fault);
+                    }
+                } else if self.operand_size_override && self.model.supports_80386_instructions() {
                     // 32-bit: IRETD - pop 32-bit EIP, CS, EFLAGS
-                    self.ip = self.read_u32(self.ss, self.sp);
-                    self.sp = self.sp.wrapping_add(4);
-                    self.cs = self.read_u32(self.ss, self.sp) as u16;
-                    self.sp = self.sp.wrapping_add(4);
-                    self.flags = self.read_u32(self.ss, self.sp);
-                    self.sp = self.sp.wrapping_add(4);
+                    self.ip = self.pop32();
+                    self.cs = self.pop32() as u16;
+                    self.flags = self.pop32();
                 } else {
                     // 16-bit: IRET - pop 16-bit IP, CS, FLAGS
                     self.ip = self.pop() as u32;
@@ -8730,6 +9605,7 @@ impl<M: Memory8086> Cpu8086<M> {
         // These flags are only valid for the immediately following instruction
         self.operand_size_override = false;
         self.address_size_override = false;
+        self.rep_prefix = None;
 
         // Increment TSC on Pentium+ processors
         // TSC increments by the number of cycles executed
@@ -8741,12 +9617,1085 @@ impl<M: Memory8086> Cpu8086<M> {
     }
 }
 
+/// How a protected-mode interrupt was raised (affects privilege checks,
+/// the EXT bit of error codes and whether an error code is pushed)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InterruptSource {
+    /// INT n, INT3 or INTO
+    Software,
+    /// External interrupt from a device
+    Hardware,
+    /// CPU exception, with its error code if it has one
+    Exception(Option<u16>),
+}
+
+/// Why a task switch happens (controls busy bits, NT and the back link)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TaskSwitch {
+    /// JMP to a TSS or task gate
+    Jump,
+    /// CALL or interrupt through a TSS or task gate (nests the new task)
+    Call,
+    /// IRET with NT set (returns to the back-linked task)
+    Iret,
+}
+
+/// 80286 protected mode: descriptor loading, limit checks, control transfers
+/// through gates and task switching
+impl<M: Memory8086> Cpu8086<M> {
+    /// Linear address of the current CS:IP
+    ///
+    /// Uses the CS descriptor cache in protected mode. System wrappers use this
+    /// to peek at the next instruction.
+    pub fn cs_ip_linear_address(&self) -> u32 {
+        if self.protected_mode.is_protected_mode() {
+            self.protected_mode.segment_cache[SEG_CS as usize]
+                .base
+                .wrapping_add(self.ip & 0xFFFF)
+        } else {
+            ((self.cs as u32) << 4) + self.ip
+        }
+    }
+
+    /// Translate segment:offset in protected mode, or record a fault
+    ///
+    /// The segment is looked up among the loaded segment registers (preferring
+    /// valid caches); a selector that is not loaded anywhere is read from the
+    /// descriptor table.
+    fn protected_linear_address(&self, segment: u16, offset: u32) -> Option<u32> {
+        let caches = &self.protected_mode.segment_cache;
+        let index = caches
+            .iter()
+            .position(|c| c.valid && c.selector == segment)
+            .or_else(|| caches.iter().position(|c| c.selector == segment));
+        let cache = match index {
+            Some(i) => caches[i],
+            None => match self.read_descriptor(segment) {
+                Some(desc) if segment & 0xFFFC != 0 => self.segment_cache_for(segment, &desc),
+                _ => SegmentCache::null(segment),
+            },
+        };
+
+        if cache.valid && cache.contains(offset, 1) {
+            return Some(cache.base.wrapping_add(offset));
+        }
+        self.record_fault(if index == Some(SEG_SS as usize) {
+            ProtectedModeFault::StackFault(0)
+        } else {
+            ProtectedModeFault::GeneralProtection(0)
+        });
+        None
+    }
+
+    /// Remember a fault to deliver once the current instruction finishes
+    /// (only the first fault of an instruction is kept)
+    fn record_fault(&self, fault: ProtectedModeFault) {
+        if self.pending_fault.get().is_none() {
+            self.pending_fault.set(Some(fault));
+        }
+    }
+
+    /// Segment cache for a descriptor (the 80386 adds 32-bit bases,
+    /// granular limits and the B/D bit)
+    fn segment_cache_for(&self, selector: u16, desc: &SegmentDescriptor) -> SegmentCache {
+        if self.model.supports_80386_instructions() {
+            SegmentCache::from_descriptor_386(selector, desc)
+        } else {
+            SegmentCache::from_descriptor(selector, desc)
+        }
+    }
+
+    /// Translate a linear address through the page tables when paging is
+    /// enabled. Accessed/dirty bits are queued and written back after the
+    /// instruction; a failed walk records a page fault.
+    fn translate_page(&self, linear: u32, write: bool) -> Option<u32> {
+        self.translate_page_as(linear, write, self.protected_mode.cpl == 3)
+    }
+
+    /// Page walk with an explicit privilege (descriptor table and TSS
+    /// accesses are always supervisor accesses)
+    fn translate_page_as(&self, linear: u32, write: bool, user: bool) -> Option<u32> {
+        if !self.protected_mode.is_paging() {
+            return Some(linear);
+        }
+
+        let fault_code =
+            |present: bool| (present as u16) | ((write as u16) << 1) | ((user as u16) << 2);
+        let wp = self.protected_mode.cr0_high & 0x0001 != 0;
+
+        let pde_addr = (self.protected_mode.cr3 & page_entry::FRAME) | ((linear >> 22) << 2);
+        let pde = self.read_physical_u32(pde_addr);
+        if pde & page_entry::PRESENT == 0 {
+            self.record_fault(ProtectedModeFault::PageFault(fault_code(false), linear));
+            return None;
+        }
+        let pte_addr = (pde & page_entry::FRAME) | (((linear >> 12) & 0x3FF) << 2);
+        let pte = self.read_physical_u32(pte_addr);
+        if pte & page_entry::PRESENT == 0 {
+            self.record_fault(ProtectedModeFault::PageFault(fault_code(false), linear));
+            return None;
+        }
+
+        // The effective rights are the more restrictive of the two levels
+        let user_ok = pde & pte & page_entry::USER != 0;
+        let writable = pde & pte & page_entry::WRITABLE != 0;
+        let denied = if user {
+            !user_ok || (write && !writable)
+        } else {
+            write && wp && !writable
+        };
+        if denied {
+            self.record_fault(ProtectedModeFault::PageFault(fault_code(true), linear));
+            return None;
+        }
+
+        let mut updates = self.page_updates.borrow_mut();
+        if pde & page_entry::ACCESSED == 0 {
+            updates.push((pde_addr, page_entry::ACCESSED));
+        }
+        let pte_bits = page_entry::ACCESSED | if write { page_entry::DIRTY } else { 0 };
+        if pte & pte_bits != pte_bits {
+            updates.push((pte_addr, pte_bits));
+        }
+        Some((pte & page_entry::FRAME) | (linear & 0xFFF))
+    }
+
+    /// Write back accessed/dirty bits set by page walks
+    fn flush_page_updates(&mut self) {
+        let updates = std::mem::take(&mut *self.page_updates.borrow_mut());
+        for (addr, bits) in updates {
+            let entry = self.read_physical_u32(addr);
+            for (i, b) in (entry | bits).to_le_bytes().iter().enumerate() {
+                self.memory.write(addr + i as u32, *b);
+            }
+        }
+    }
+
+    fn read_physical_u32(&self, addr: u32) -> u32 {
+        u32::from_le_bytes(std::array::from_fn(|i| self.memory.read(addr + i as u32)))
+    }
+
+    /// Physical address of a system structure access (descriptor tables and
+    /// TSS are accessed as supervisor even at CPL 3)
+    fn system_address(&self, linear: u32, write: bool) -> u32 {
+        self.translate_page_as(linear, write, false)
+            .unwrap_or(linear)
+    }
+
+    /// Linear address of the descriptor a selector refers to (GDT or LDT),
+    /// or None if it is outside the table
+    fn descriptor_address(&self, selector: u16) -> Option<u32> {
+        let index = (selector & 0xFFF8) as u32;
+        let (base, limit) = if selector & 0x04 != 0 {
+            let ldt = self.protected_mode.ldt_cache;
+            if !ldt.valid {
+                return None;
+            }
+            (ldt.base, ldt.limit)
+        } else {
+            let gdtr = self.protected_mode.gdtr;
+            (gdtr.base, gdtr.limit as u32)
+        };
+        (index + 7 <= limit).then_some(base + index)
+    }
+
+    fn read_descriptor_bytes(&self, addr: u32) -> [u8; 8] {
+        std::array::from_fn(|i| {
+            self.memory
+                .read(self.system_address(addr + i as u32, false))
+        })
+    }
+
+    /// Read the segment descriptor a selector refers to
+    fn read_descriptor(&self, selector: u16) -> Option<SegmentDescriptor> {
+        let addr = self.descriptor_address(selector)?;
+        Some(SegmentDescriptor::from_bytes(
+            &self.read_descriptor_bytes(addr),
+        ))
+    }
+
+    /// Read the descriptor for a non-null selector, faulting with `fault` if the
+    /// selector is null or outside its table
+    fn fetch_descriptor(
+        &self,
+        selector: u16,
+        fault: fn(u16) -> ProtectedModeFault,
+    ) -> Result<(u32, [u8; 8]), ProtectedModeFault> {
+        let code = selector & 0xFFFC;
+        if code == 0 {
+            return Err(fault(0));
+        }
+        let addr = self.descriptor_address(selector).ok_or(fault(code))?;
+        Ok((addr, self.read_descriptor_bytes(addr)))
+    }
+
+    fn read_linear_u16(&self, addr: u32) -> u16 {
+        u16::from_le_bytes([
+            self.memory.read(self.system_address(addr, false)),
+            self.memory.read(self.system_address(addr + 1, false)),
+        ])
+    }
+
+    fn write_linear_u16(&mut self, addr: u32, val: u16) {
+        let [lo, hi] = val.to_le_bytes();
+        let (lo_addr, hi_addr) = (
+            self.system_address(addr, true),
+            self.system_address(addr + 1, true),
+        );
+        self.memory.write(lo_addr, lo);
+        self.memory.write(hi_addr, hi);
+    }
+
+    fn read_linear_u32(&self, addr: u32) -> u32 {
+        self.read_linear_u16(addr) as u32 | (self.read_linear_u16(addr + 2) as u32) << 16
+    }
+
+    fn write_linear_u32(&mut self, addr: u32, val: u32) {
+        self.write_linear_u16(addr, val as u16);
+        self.write_linear_u16(addr + 2, (val >> 16) as u16);
+    }
+
+    /// Update the access byte of the descriptor at `addr`
+    fn update_descriptor_access(&mut self, addr: u32, set: u8, clear: u8) {
+        let addr = self.system_address(addr + 5, true);
+        let access = self.memory.read(addr);
+        self.memory.write(addr, (access | set) & !clear);
+    }
+
+    /// Write the MSW (LMSW or MOV CR0)
+    ///
+    /// Setting PE keeps the current segments usable: their real-mode bases
+    /// stay in the descriptor caches until the registers are reloaded.
+    fn write_msw(&mut self, value: u16, can_clear_pe: bool) {
+        let was_protected = self.protected_mode.is_protected_mode();
+        let value = if can_clear_pe {
+            value
+        } else {
+            value | (self.protected_mode.get_msw() & 0x0001)
+        };
+        self.protected_mode.set_msw(value);
+        if !was_protected && self.protected_mode.is_protected_mode() {
+            self.protected_mode
+                .load_real_mode_caches([self.es, self.cs, self.ss, self.ds, self.fs, self.gs]);
+            if !self.model.supports_80386_instructions() {
+                // No FS/GS on the 80286
+                self.protected_mode.segment_cache[SEG_FS as usize] = SegmentCache::null(self.fs);
+                self.protected_mode.segment_cache[SEG_GS as usize] = SegmentCache::null(self.gs);
+            }
+        }
+    }
+
+    /// Load a data or stack segment register (MOV, POP, LDS, ...)
+    ///
+    /// In real mode this just sets the register. In protected mode the
+    /// descriptor is checked and copied into the register's cache.
+    fn load_segment(&mut self, seg: u8, selector: u16) -> Result<(), ProtectedModeFault> {
+        if !self.protected_mode.is_protected_mode() {
+            self.set_seg(seg, selector);
+            return Ok(());
+        }
+        if seg == SEG_CS {
+            return Err(ProtectedModeFault::InvalidOpcode);
+        }
+
+        let cpl = self.protected_mode.cpl;
+        let rpl = (selector & 0x03) as u8;
+        let code = selector & 0xFFFC;
+        if code == 0 && seg != SEG_SS {
+            // A null selector may be loaded; using it faults
+            self.set_seg(seg, selector);
+            self.protected_mode.segment_cache[seg as usize] = SegmentCache::null(selector);
+            return Ok(());
+        }
+
+        let (addr, bytes) =
+            self.fetch_descriptor(selector, ProtectedModeFault::GeneralProtection)?;
+        let desc = SegmentDescriptor::from_bytes(&bytes);
+        if seg == SEG_SS {
+            if rpl != cpl || !desc.is_writable_data() || desc.dpl() != cpl {
+                return Err(ProtectedModeFault::GeneralProtection(code));
+            }
+            if !desc.is_present() {
+                return Err(ProtectedModeFault::StackFault(code));
+            }
+        } else {
+            if !desc.is_readable() || (!desc.is_conforming_code() && desc.dpl() < rpl.max(cpl)) {
+                return Err(ProtectedModeFault::GeneralProtection(code));
+            }
+            if !desc.is_present() {
+                return Err(ProtectedModeFault::SegmentNotPresent(code));
+            }
+        }
+
+        self.update_descriptor_access(addr, access_rights::ACCESSED, 0);
+        self.set_seg(seg, selector);
+        self.protected_mode.segment_cache[seg as usize] = self.segment_cache_for(selector, &desc);
+        Ok(())
+    }
+
+    /// Load a segment register, recording a fault on failure
+    ///
+    /// Returns false if the load faulted (the register is unchanged).
+    fn load_segment_or_fault(&mut self, seg: u8, selector: u16) -> bool {
+        match self.load_segment(seg, selector) {
+            Ok(()) => true,
+            Err(fault) => {
+                self.record_fault(fault);
+                false
+            }
+        }
+    }
+
+    /// Load CS from a checked code descriptor and make `cpl` the new privilege level
+    fn load_code_segment(&mut self, selector: u16, desc: &SegmentDescriptor, cpl: u8) {
+        let selector = (selector & 0xFFFC) | cpl as u16;
+        self.cs = selector;
+        self.protected_mode.segment_cache[SEG_CS as usize] = self.segment_cache_for(selector, desc);
+        self.protected_mode.cpl = cpl;
+    }
+
+    /// Check a stack segment selector for privilege level `cpl`
+    fn stack_descriptor(
+        &self,
+        selector: u16,
+        cpl: u8,
+        fault: fn(u16) -> ProtectedModeFault,
+    ) -> Result<SegmentDescriptor, ProtectedModeFault> {
+        let code = selector & 0xFFFC;
+        let (_, bytes) = self.fetch_descriptor(selector, fault)?;
+        let desc = SegmentDescriptor::from_bytes(&bytes);
+        if (selector & 0x03) as u8 != cpl || !desc.is_writable_data() || desc.dpl() != cpl {
+            return Err(fault(code));
+        }
+        if !desc.is_present() {
+            return Err(ProtectedModeFault::StackFault(code));
+        }
+        Ok(desc)
+    }
+
+    /// Switch SS:SP to an already checked stack segment
+    fn switch_stack(&mut self, selector: u16, desc: &SegmentDescriptor, sp: u32) {
+        self.ss = selector;
+        self.protected_mode.segment_cache[SEG_SS as usize] = self.segment_cache_for(selector, desc);
+        self.sp = sp;
+    }
+
+    /// Whether the current task uses a 32-bit (80386) TSS
+    fn current_tss_is_386(&self) -> bool {
+        // Types 9 and 0xB (available/busy 386 TSS) have bit 3 set
+        self.protected_mode.tr_cache.access & 0x08 != 0
+    }
+
+    /// Inner-level stack for privilege level `level`, from the current TSS
+    fn tss_stack(&self, level: u8) -> Result<(u16, u32), ProtectedModeFault> {
+        let tss = self.protected_mode.tr_cache;
+        if !tss.valid {
+            return Err(ProtectedModeFault::InvalidTss(
+                self.protected_mode.tr & 0xFFFC,
+            ));
+        }
+        if self.current_tss_is_386() {
+            let entry = tss.base + tss386::ESP0 + level as u32 * 8;
+            return Ok((self.read_linear_u16(entry + 4), self.read_linear_u32(entry)));
+        }
+        let entry = tss.base + tss286::SP0 + level as u32 * 4;
+        Ok((
+            self.read_linear_u16(entry + 2),
+            self.read_linear_u16(entry) as u32,
+        ))
+    }
+
+    /// Limit of a segment in bytes (the 80386 adds the granularity bit)
+    fn segment_byte_limit(&self, desc: &SegmentDescriptor) -> u32 {
+        if self.model.supports_80386_instructions() {
+            desc.byte_limit()
+        } else {
+            desc.limit & 0xFFFF
+        }
+    }
+
+    /// Push a word or, with a 32-bit operand size, a dword
+    fn push_sized(&mut self, val: u32, size32: bool) {
+        if size32 {
+            self.push32(val);
+        } else {
+            self.push(val as u16);
+        }
+    }
+
+    /// Read a word or dword from the stack at `offset` bytes above SP
+    fn stack_read(&self, offset: u32, size32: bool) -> u32 {
+        let addr = self.stack_offset().wrapping_add(offset) & self.stack_offset_mask();
+        if size32 {
+            self.read_u32(self.ss, addr)
+        } else {
+            self.read_u16_at(self.ss, addr) as u32
+        }
+    }
+
+    /// Null out data segment registers the new (outer) privilege level may not use
+    fn invalidate_data_segments(&mut self, cpl: u8) {
+        for seg in [SEG_ES, SEG_DS, SEG_FS, SEG_GS] {
+            let cache = self.protected_mode.segment_cache[seg as usize];
+            let conforming = (cache.access & 0x1C) == 0x1C;
+            if cache.valid && !conforming && (cache.access >> 5) & 0x03 < cpl {
+                self.set_seg(seg, 0);
+                self.protected_mode.segment_cache[seg as usize] = SegmentCache::null(0);
+            }
+        }
+    }
+
+    /// Far JMP or CALL in protected mode: to a code segment, through a call
+    /// gate, or to another task (TSS or task gate)
+    fn protected_far_transfer(
+        &mut self,
+        selector: u16,
+        offset: u32,
+        is_call: bool,
+    ) -> Result<(), ProtectedModeFault> {
+        let cpl = self.protected_mode.cpl;
+        let rpl = (selector & 0x03) as u8;
+        let code = selector & 0xFFFC;
+        let (_, bytes) = self.fetch_descriptor(selector, ProtectedModeFault::GeneralProtection)?;
+        let desc = SegmentDescriptor::from_bytes(&bytes);
+
+        if !desc.is_system() {
+            let allowed = if desc.is_conforming_code() {
+                desc.dpl() <= cpl
+            } else {
+                desc.is_code_segment() && rpl <= cpl && desc.dpl() == cpl
+            };
+            if !allowed {
+                return Err(ProtectedModeFault::GeneralProtection(code));
+            }
+            if !desc.is_present() {
+                return Err(ProtectedModeFault::SegmentNotPresent(code));
+            }
+            if offset > self.segment_byte_limit(&desc) {
+                return Err(ProtectedModeFault::GeneralProtection(0));
+            }
+            if is_call {
+                let size32 = self.operand_size_override;
+                self.push_sized(self.cs as u32, size32);
+                self.push_sized(self.ip, size32);
+            }
+            self.load_code_segment(selector, &desc, cpl);
+            self.ip = offset;
+            return Ok(());
+        }
+
+        if desc.dpl() < cpl.max(rpl) {
+            return Err(ProtectedModeFault::GeneralProtection(code));
+        }
+        let kind = if is_call {
+            TaskSwitch::Call
+        } else {
+            TaskSwitch::Jump
+        };
+        match desc.system_type() {
+            system_types::TSS_286_AVAILABLE
+            | system_types::TSS_386_AVAILABLE
+            | system_types::TASK_GATE
+            | system_types::CALL_GATE_286
+            | system_types::CALL_GATE_386
+                if !desc.is_present() =>
+            {
+                Err(ProtectedModeFault::SegmentNotPresent(code))
+            }
+            system_types::TSS_286_AVAILABLE | system_types::TSS_386_AVAILABLE
+                if self.model.supports_80386_instructions()
+                    || desc.system_type() == system_types::TSS_286_AVAILABLE =>
+            {
+                self.task_switch(selector, kind)
+            }
+            system_types::TASK_GATE => {
+                let gate = GateDescriptor::from_bytes(&bytes);
+                self.task_switch(gate.selector, kind)
+            }
+            system_types::CALL_GATE_286 => {
+                self.call_gate_transfer(GateDescriptor::from_bytes(&bytes), is_call)
+            }
+            system_types::CALL_GATE_386 if self.model.supports_80386_instructions() => {
+                self.call_gate_transfer(GateDescriptor::from_bytes(&bytes), is_call)
+            }
+            _ => Err(ProtectedModeFault::GeneralProtection(code)),
+        }
+    }
+
+    /// Transfer through a call gate, switching to an inner stack (and copying
+    /// the gate's parameter words) on a call to a more privileged level
+    fn call_gate_transfer(
+        &mut self,
+        gate: GateDescriptor,
+        is_call: bool,
+    ) -> Result<(), ProtectedModeFault> {
+        let cpl = self.protected_mode.cpl;
+        let code = gate.selector & 0xFFFC;
+        let (_, bytes) =
+            self.fetch_descriptor(gate.selector, ProtectedModeFault::GeneralProtection)?;
+        let desc = SegmentDescriptor::from_bytes(&bytes);
+        if desc.is_system() || !desc.is_code_segment() || desc.dpl() > cpl {
+            return Err(ProtectedModeFault::GeneralProtection(code));
+        }
+        if !desc.is_present() {
+            return Err(ProtectedModeFault::SegmentNotPresent(code));
+        }
+        if gate.offset > self.segment_byte_limit(&desc) {
+            return Err(ProtectedModeFault::GeneralProtection(0));
+        }
+
+        // A 386 call gate pushes dwords and copies dword parameters
+        let size32 = gate.is_32bit();
+        let new_cpl = if desc.is_conforming_code() {
+            cpl
+        } else {
+            desc.dpl()
+        };
+        if new_cpl < cpl {
+            if !is_call {
+                return Err(ProtectedModeFault::GeneralProtection(code));
+            }
+            let (new_ss, new_sp) = self.tss_stack(new_cpl)?;
+            let ss_desc = self.stack_descriptor(new_ss, new_cpl, ProtectedModeFault::InvalidTss)?;
+            let (old_ss, old_sp) = (self.ss, self.sp);
+            let width = if size32 { 4 } else { 2 };
+            let params: Vec<u32> = (0..gate.word_count as u32)
+                .map(|i| self.stack_read(i * width, size32))
+                .collect();
+            self.switch_stack(new_ss, &ss_desc, new_sp);
+            self.push_sized(old_ss as u32, size32);
+            self.push_sized(old_sp, size32);
+            for &param in params.iter().rev() {
+                self.push_sized(param, size32);
+            }
+        }
+        if is_call {
+            self.push_sized(self.cs as u32, size32);
+            self.push_sized(self.ip, size32);
+        }
+        self.load_code_segment(gate.selector, &desc, new_cpl);
+        self.ip = gate.offset;
+        Ok(())
+    }
+
+    /// Check the CS selector popped by RETF/IRET; returns its descriptor
+    fn return_code_descriptor(
+        &self,
+        selector: u16,
+    ) -> Result<SegmentDescriptor, ProtectedModeFault> {
+        let code = selector & 0xFFFC;
+        let rpl = (selector & 0x03) as u8;
+        let (_, bytes) = self.fetch_descriptor(selector, ProtectedModeFault::GeneralProtection)?;
+        let desc = SegmentDescriptor::from_bytes(&bytes);
+        let allowed = rpl >= self.protected_mode.cpl
+            && !desc.is_system()
+            && desc.is_code_segment()
+            && if desc.is_conforming_code() {
+                desc.dpl() <= rpl
+            } else {
+                desc.dpl() == rpl
+            };
+        if !allowed {
+            return Err(ProtectedModeFault::GeneralProtection(code));
+        }
+        if !desc.is_present() {
+            return Err(ProtectedModeFault::SegmentNotPresent(code));
+        }
+        Ok(desc)
+    }
+
+    /// Load CS:IP (and SS:SP when returning to an outer level) from the stack
+    /// for RETF/IRET; `frame` is the size of the IP/CS(/FLAGS) frame plus any
+    /// RETF immediate, and `size32` selects dword stack slots
+    fn protected_return(
+        &mut self,
+        new_ip: u32,
+        selector: u16,
+        frame: u32,
+        pop_bytes: u32,
+        size32: bool,
+    ) -> Result<(), ProtectedModeFault> {
+        let desc = self.return_code_descriptor(selector)?;
+        if new_ip > self.segment_byte_limit(&desc) {
+            return Err(ProtectedModeFault::GeneralProtection(0));
+        }
+        let rpl = (selector & 0x03) as u8;
+        if rpl == self.protected_mode.cpl {
+            self.load_code_segment(selector, &desc, rpl);
+            self.adjust_sp(frame as i32);
+        } else {
+            // Return to an outer privilege level: the caller's SS:SP follows
+            let new_sp = self.stack_read(frame, size32);
+            let new_ss = self.stack_read(frame + if size32 { 4 } else { 2 }, size32) as u16;
+            let ss_desc =
+                self.stack_descriptor(new_ss, rpl, ProtectedModeFault::GeneralProtection)?;
+            self.load_code_segment(selector, &desc, rpl);
+            let new_sp = if size32 {
+                new_sp.wrapping_add(pop_bytes)
+            } else {
+                (new_sp as u16).wrapping_add(pop_bytes as u16) as u32
+            };
+            self.switch_stack(new_ss, &ss_desc, new_sp);
+            self.invalidate_data_segments(rpl);
+        }
+        self.ip = new_ip;
+        Ok(())
+    }
+
+    /// RETF / RETF imm16 in protected mode
+    fn protected_far_return(&mut self, pop_bytes: u16) -> Result<(), ProtectedModeFault> {
+        let size32 = self.operand_size_override;
+        let width = if size32 { 4 } else { 2 };
+        let new_ip = self.stack_read(0, size32);
+        let selector = self.stack_read(width, size32) as u16;
+        let pop_bytes = pop_bytes as u32;
+        self.protected_return(new_ip, selector, width * 2 + pop_bytes, pop_bytes, size32)
+    }
+
+    /// IRET in protected mode: a task return if NT is set, otherwise a return
+    /// to the interrupted code (possibly at an outer privilege level)
+    fn protected_iret(&mut self) -> Result<(), ProtectedModeFault> {
+        if self.flags & FLAG_NT != 0 {
+            let back_link =
+                self.read_linear_u16(self.protected_mode.tr_cache.base + tss286::BACK_LINK);
+            return self.task_switch(back_link, TaskSwitch::Iret);
+        }
+
+        let cpl = self.protected_mode.cpl;
+        let size32 = self.operand_size_override;
+        let width = if size32 { 4 } else { 2 };
+        let new_ip = self.stack_read(0, size32);
+        let selector = self.stack_read(width, size32) as u16;
+        let new_flags = self.stack_read(width * 2, size32);
+        self.protected_return(new_ip, selector, width * 3, 0, size32)?;
+
+        // IOPL only changes at CPL 0, IF only if CPL <= IOPL
+        let iopl = ((self.flags & FLAG_IOPL) >> 12) as u8;
+        let mut keep = 0;
+        if cpl > 0 {
+            keep |= FLAG_IOPL;
+        }
+        if cpl > iopl {
+            keep |= FLAG_IF;
+        }
+        if size32 {
+            // IRETD loads EFLAGS; virtual 8086 mode is not supported
+            keep |= FLAG_VM;
+        } else {
+            keep |= 0xFFFF_0000;
+        }
+        self.flags = (self.flags & keep) | (new_flags & !keep) | 0x0002;
+        Ok(())
+    }
+
+    /// Dispatch an interrupt through the IDT, delivering a double fault (or
+    /// shutting down) if that itself faults
+    fn protected_mode_interrupt(&mut self, vector: u8, source: InterruptSource) {
+        let fault = match self.enter_interrupt_gate(vector, source) {
+            Err(fault) => Some(fault),
+            Ok(()) => self.pending_fault.take(),
+        };
+        let Some(fault) = fault else {
+            return;
+        };
+        match source {
+            InterruptSource::Exception(_) if vector == 8 => {
+                // Triple fault: the CPU shuts down until reset
+                log(LogCategory::CPU, LogLevel::Error, || {
+                    format!(
+                        "Triple fault at {:04X}:{:04X}, CPU shut down",
+                        self.cs, self.instruction_start_ip
+                    )
+                });
+                self.halted = true;
+                self.shutdown = true;
+            }
+            InterruptSource::Exception(_) => {
+                let df = ProtectedModeFault::DoubleFault;
+                self.protected_mode_interrupt(
+                    df.vector(),
+                    InterruptSource::Exception(df.error_code()),
+                );
+            }
+            InterruptSource::Software => {
+                // The INT instruction itself faulted
+                self.ip = self.instruction_start_ip;
+                self.protected_mode_interrupt(
+                    fault.vector(),
+                    InterruptSource::Exception(fault.error_code()),
+                );
+            }
+            InterruptSource::Hardware => {
+                self.protected_mode_interrupt(
+                    fault.vector(),
+                    InterruptSource::Exception(fault.error_code()),
+                );
+            }
+        }
+    }
+
+    /// Enter an interrupt, trap or task gate from the IDT
+    fn enter_interrupt_gate(
+        &mut self,
+        vector: u8,
+        source: InterruptSource,
+    ) -> Result<(), ProtectedModeFault> {
+        let ext = u16::from(source != InterruptSource::Software);
+        let gate_code = (vector as u16) * 8 + 2 + ext;
+        let entry = vector as u32 * 8;
+        let idtr = self.protected_mode.idtr;
+        if entry + 7 > idtr.limit as u32 {
+            return Err(ProtectedModeFault::GeneralProtection(gate_code));
+        }
+        let gate = GateDescriptor::from_bytes(&self.read_descriptor_bytes(idtr.base + entry));
+        let gate_type = match gate.gate_type() {
+            Some(
+                t @ (system_types::TASK_GATE
+                | system_types::INTERRUPT_GATE_286
+                | system_types::TRAP_GATE_286),
+            ) => t,
+            Some(t @ (system_types::INTERRUPT_GATE_386 | system_types::TRAP_GATE_386))
+                if self.model.supports_80386_instructions() =>
+            {
+                t
+            }
+            _ => return Err(ProtectedModeFault::GeneralProtection(gate_code)),
+        };
+        let cpl = self.protected_mode.cpl;
+        if source == InterruptSource::Software && gate.dpl() < cpl {
+            return Err(ProtectedModeFault::GeneralProtection(gate_code));
+        }
+        if !gate.is_present() {
+            return Err(ProtectedModeFault::SegmentNotPresent(gate_code));
+        }
+        let error_code = match source {
+            InterruptSource::Exception(code) => code,
+            _ => None,
+        };
+
+        if gate_type == system_types::TASK_GATE {
+            self.task_switch(gate.selector, TaskSwitch::Call)?;
+            if let Some(code) = error_code {
+                self.push_sized(code as u32, self.current_tss_is_386());
+            }
+            return Ok(());
+        }
+
+        let code = gate.selector & 0xFFFC;
+        let (_, bytes) =
+            self.fetch_descriptor(gate.selector, ProtectedModeFault::GeneralProtection)?;
+        let desc = SegmentDescriptor::from_bytes(&bytes);
+        if desc.is_system() || !desc.is_code_segment() || desc.dpl() > cpl {
+            return Err(ProtectedModeFault::GeneralProtection(code | ext));
+        }
+        if !desc.is_present() {
+            return Err(ProtectedModeFault::SegmentNotPresent(code | ext));
+        }
+
+        // A 386 gate pushes a dword frame (EFLAGS, CS, EIP)
+        let size32 = gate.is_32bit();
+        let (old_cs, old_ip, old_flags) = (self.cs, self.ip, self.flags);
+        let new_cpl = if desc.is_conforming_code() {
+            cpl
+        } else {
+            desc.dpl()
+        };
+        if new_cpl < cpl {
+            let (new_ss, new_sp) = self.tss_stack(new_cpl)?;
+            let ss_desc = self.stack_descriptor(new_ss, new_cpl, ProtectedModeFault::InvalidTss)?;
+            let (old_ss, old_sp) = (self.ss, self.sp);
+            self.switch_stack(new_ss, &ss_desc, new_sp);
+            self.push_sized(old_ss as u32, size32);
+            self.push_sized(old_sp, size32);
+        }
+        self.push_sized(old_flags, size32);
+        self.push_sized(old_cs as u32, size32);
+        self.push_sized(old_ip, size32);
+        if let Some(code) = error_code {
+            self.push_sized(code as u32, size32);
+        }
+
+        self.load_code_segment(gate.selector, &desc, new_cpl);
+        self.ip = gate.offset;
+        self.flags &= !(FLAG_TF | FLAG_NT);
+        if matches!(
+            gate_type,
+            system_types::INTERRUPT_GATE_286 | system_types::INTERRUPT_GATE_386
+        ) {
+            self.flags &= !FLAG_IF;
+        }
+        Ok(())
+    }
+
+    /// Deliver a fault recorded during the instruction that just executed
+    fn deliver_pending_fault(&mut self) {
+        if let Some(fault) = self.pending_fault.take() {
+            self.ip = self.instruction_start_ip;
+            if let ProtectedModeFault::PageFault(_, linear) = fault {
+                self.protected_mode.cr2 = linear;
+            }
+            if self.protected_mode.is_protected_mode() {
+                self.protected_mode_interrupt(
+                    fault.vector(),
+                    InterruptSource::Exception(fault.error_code()),
+                );
+            } else {
+                self.trigger_interrupt(fault.vector(), true);
+            }
+        }
+    }
+
+    /// Switch to the task whose TSS `selector` refers to (80286 TSS format)
+    fn task_switch(&mut self, selector: u16, kind: TaskSwitch) -> Result<(), ProtectedModeFault> {
+        let code = selector & 0xFFFC;
+        let invalid = if kind == TaskSwitch::Iret {
+            ProtectedModeFault::InvalidTss
+        } else {
+            ProtectedModeFault::GeneralProtection
+        };
+        if selector & 0x04 != 0 {
+            return Err(invalid(code));
+        }
+        let (addr, bytes) = self.fetch_descriptor(selector, invalid)?;
+        let desc = SegmentDescriptor::from_bytes(&bytes);
+        // The 386 TSS types differ from the 286 ones only in bit 3
+        let is386 = self.model.supports_80386_instructions() && desc.system_type() & 0x08 != 0;
+        let expected = match (kind == TaskSwitch::Iret, is386) {
+            (true, false) => system_types::TSS_286_BUSY,
+            (false, false) => system_types::TSS_286_AVAILABLE,
+            (true, true) => system_types::TSS_386_BUSY,
+            (false, true) => system_types::TSS_386_AVAILABLE,
+        };
+        if !desc.is_system() || desc.system_type() != expected {
+            return Err(invalid(code));
+        }
+        if !desc.is_present() {
+            return Err(ProtectedModeFault::SegmentNotPresent(code));
+        }
+        let min_limit = if is386 {
+            tss386::MIN_LIMIT
+        } else {
+            tss286::MIN_LIMIT
+        };
+        if self.segment_byte_limit(&desc) < min_limit {
+            return Err(ProtectedModeFault::InvalidTss(code));
+        }
+
+        // Save the outgoing task
+        let old_tr = self.protected_mode.tr;
+        let old_tss = self.protected_mode.tr_cache;
+        if old_tss.valid {
+            let base = old_tss.base;
+            let mut flags = self.flags;
+            if kind == TaskSwitch::Iret {
+                flags &= !FLAG_NT;
+            }
+            let regs = [
+                self.ax, self.cx, self.dx, self.bx, self.sp, self.bp, self.si, self.di,
+            ];
+            if self.current_tss_is_386() {
+                self.write_linear_u32(base + tss386::EIP, self.ip);
+                self.write_linear_u32(base + tss386::EFLAGS, flags);
+                for (i, reg) in regs.into_iter().enumerate() {
+                    self.write_linear_u32(base + tss386::EAX + i as u32 * 4, reg);
+                }
+                let segs = [self.es, self.cs, self.ss, self.ds, self.fs, self.gs];
+                for (i, seg) in segs.into_iter().enumerate() {
+                    self.write_linear_u16(base + tss386::ES + i as u32 * 4, seg);
+                }
+            } else {
+                self.write_linear_u16(base + tss286::IP, self.ip as u16);
+                self.write_linear_u16(base + tss286::FLAGS, flags as u16);
+                for (i, reg) in regs.into_iter().enumerate() {
+                    self.write_linear_u16(base + tss286::AX + i as u32 * 2, reg as u16);
+                }
+                for (i, seg) in [self.es, self.cs, self.ss, self.ds].into_iter().enumerate() {
+                    self.write_linear_u16(base + tss286::ES + i as u32 * 2, seg);
+                }
+            }
+            if kind != TaskSwitch::Call {
+                if let Some(old_addr) = self.descriptor_address(old_tr) {
+                    self.update_descriptor_access(old_addr, 0, 0x02);
+                }
+            }
+        }
+
+        // Switch TR to the incoming task
+        let base = if is386 {
+            desc.base
+        } else {
+            desc.base & 0x00FF_FFFF
+        };
+        if kind == TaskSwitch::Call {
+            // The back link is at offset 0 in both TSS formats
+            self.write_linear_u16(base + tss286::BACK_LINK, old_tr);
+        }
+        if kind != TaskSwitch::Iret {
+            self.update_descriptor_access(addr, 0x02, 0);
+        }
+        self.protected_mode.tr = selector;
+        self.protected_mode.tr_cache = self.segment_cache_for(selector, &desc);
+        self.protected_mode.tr_cache.access |= 0x02;
+        let msw = self.protected_mode.get_msw();
+        self.protected_mode.set_msw(msw | 0x0008); // TS
+
+        // Load the incoming task's registers
+        let (es, cs, ss, ds, fs_gs, ldt);
+        if is386 {
+            // A 386 task also switches the page directory
+            self.protected_mode.cr3 = self.read_linear_u32(base + tss386::CR3) & page_entry::FRAME;
+            self.ip = self.read_linear_u32(base + tss386::EIP);
+            let mut flags = self.read_linear_u32(base + tss386::EFLAGS) & !FLAG_VM | 0x0002;
+            if kind == TaskSwitch::Call {
+                flags |= FLAG_NT;
+            }
+            self.flags = flags;
+            let regs: [u32; 8] =
+                std::array::from_fn(|i| self.read_linear_u32(base + tss386::EAX + i as u32 * 4));
+            [
+                self.ax, self.cx, self.dx, self.bx, self.sp, self.bp, self.si, self.di,
+            ] = regs;
+            let segs: [u16; 6] =
+                std::array::from_fn(|i| self.read_linear_u16(base + tss386::ES + i as u32 * 4));
+            [es, cs, ss, ds] = [segs[0], segs[1], segs[2], segs[3]];
+            fs_gs = Some((segs[4], segs[5]));
+            ldt = self.read_linear_u16(base + tss386::LDT);
+        } else {
+            self.ip = self.read_linear_u16(base + tss286::IP) as u32;
+            let mut flags = self.read_linear_u16(base + tss286::FLAGS) as u32 | 0x0002;
+            if kind == TaskSwitch::Call {
+                flags |= FLAG_NT;
+            }
+            self.flags = (self.flags & 0xFFFF_0000) | flags;
+            let regs: [u32; 8] = std::array::from_fn(|i| {
+                self.read_linear_u16(base + tss286::AX + i as u32 * 2) as u32
+            });
+            [
+                self.ax, self.cx, self.dx, self.bx, self.sp, self.bp, self.si, self.di,
+            ] = regs;
+            let segs: [u16; 4] =
+                std::array::from_fn(|i| self.read_linear_u16(base + tss286::ES + i as u32 * 2));
+            [es, cs, ss, ds] = segs;
+            fs_gs = None;
+            ldt = self.read_linear_u16(base + tss286::LDT);
+        }
+        self.es = es;
+        self.cs = cs;
+        self.ss = ss;
+        self.ds = ds;
+
+        // Check and cache the new task's LDT and segments
+        let to_tss = |fault: ProtectedModeFault| match fault {
+            ProtectedModeFault::GeneralProtection(code) => ProtectedModeFault::InvalidTss(code),
+            other => other,
+        };
+        self.load_ldt(ldt).map_err(to_tss)?;
+        let cs_code = cs & 0xFFFC;
+        let (_, bytes) = self.fetch_descriptor(cs, ProtectedModeFault::InvalidTss)?;
+        let cs_desc = SegmentDescriptor::from_bytes(&bytes);
+        let cpl = (cs & 0x03) as u8;
+        let cs_ok = !cs_desc.is_system()
+            && cs_desc.is_code_segment()
+            && if cs_desc.is_conforming_code() {
+                cs_desc.dpl() <= cpl
+            } else {
+                cs_desc.dpl() == cpl
+            };
+        if !cs_ok {
+            return Err(ProtectedModeFault::InvalidTss(cs_code));
+        }
+        if !cs_desc.is_present() {
+            return Err(ProtectedModeFault::SegmentNotPresent(cs_code));
+        }
+        self.load_code_segment(cs, &cs_desc, cpl);
+        let ss_desc = self.stack_descriptor(ss, cpl, ProtectedModeFault::InvalidTss)?;
+        let sp = if ss_desc.is_big() {
+            self.sp
+        } else {
+            self.sp & 0xFFFF
+        };
+        self.switch_stack(ss, &ss_desc, sp);
+        self.load_segment(SEG_ES, es).map_err(to_tss)?;
+        self.load_segment(SEG_DS, ds).map_err(to_tss)?;
+        if let Some((fs, gs)) = fs_gs {
+            self.load_segment(SEG_FS, fs).map_err(to_tss)?;
+            self.load_segment(SEG_GS, gs).map_err(to_tss)?;
+        }
+        Ok(())
+    }
+
+    /// LLDT: load the LDT register (a null selector disables the LDT)
+    fn load_ldt(&mut self, selector: u16) -> Result<(), ProtectedModeFault> {
+        let code = selector & 0xFFFC;
+        if code == 0 {
+            self.protected_mode.load_ldtr(selector);
+            self.protected_mode.ldt_cache = SegmentCache::null(selector);
+            return Ok(());
+        }
+        if selector & 0x04 != 0 {
+            return Err(ProtectedModeFault::GeneralProtection(code));
+        }
+        let (_, bytes) = self.fetch_descriptor(selector, ProtectedModeFault::GeneralProtection)?;
+        let desc = SegmentDescriptor::from_bytes(&bytes);
+        if !desc.is_system() || desc.system_type() != system_types::LDT {
+            return Err(ProtectedModeFault::GeneralProtection(code));
+        }
+        if !desc.is_present() {
+            return Err(ProtectedModeFault::SegmentNotPresent(code));
+        }
+        self.protected_mode.load_ldtr(selector);
+        self.protected_mode.ldt_cache = self.segment_cache_for(selector, &desc);
+        Ok(())
+    }
+
+    /// LTR: load the task register and mark the TSS busy
+    fn load_task_register(&mut self, selector: u16) -> Result<(), ProtectedModeFault> {
+        let code = selector & 0xFFFC;
+        if selector & 0x04 != 0 {
+            return Err(ProtectedModeFault::GeneralProtection(code));
+        }
+        let (addr, bytes) =
+            self.fetch_descriptor(selector, ProtectedModeFault::GeneralProtection)?;
+        let desc = SegmentDescriptor::from_bytes(&bytes);
+        let tss_type = match desc.system_type() {
+            system_types::TSS_386_AVAILABLE => self.model.supports_80386_instructions(),
+            t => t == system_types::TSS_286_AVAILABLE,
+        };
+        if !desc.is_system() || !tss_type {
+            return Err(ProtectedModeFault::GeneralProtection(code));
+        }
+        if !desc.is_present() {
+            return Err(ProtectedModeFault::SegmentNotPresent(code));
+        }
+        self.update_descriptor_access(addr, 0x02, 0);
+        self.protected_mode.load_tr(selector);
+        self.protected_mode.tr_cache = self.segment_cache_for(selector, &desc);
+        self.protected_mode.tr_cache.access |= 0x02;
+        Ok(())
+    }
+
+    /// Descriptor for LAR/LSL/VERR/VERW, if the selector is valid and
+    /// accessible at the current privilege level
+    fn visible_descriptor(&self, selector: u16) -> Option<SegmentDescriptor> {
+        if !self.protected_mode.is_protected_mode() || selector & 0xFFFC == 0 {
+            return None;
+        }
+        let desc = self.read_descriptor(selector)?;
+        let rpl = (selector & 0x03) as u8;
+        if !desc.is_conforming_code() && desc.dpl() < rpl.max(self.protected_mode.cpl) {
+            return None;
+        }
+        Some(desc)
+    }
+}
+
 /// Simple array-based memory for testing
 pub struct ArrayMemory {
     data: Vec<u8>,
 }
 
 impl ArrayMemory {
+    /// Create a zero-filled 1MB memory
     pub fn new() -> Self {
         Self {
             data: vec![0; 0x100000], // 1MB of memory
@@ -8803,6 +10752,7 @@ mod tests {
     // Include organized test modules
     mod tests_16bit;
     mod tests_32bit;
+    mod tests_80386; // 80386 protected mode and paging
     mod tests_8bit;
     mod tests_addressing;
     mod tests_bcd;
@@ -8812,6 +10762,7 @@ mod tests {
     mod tests_jumps;
     mod tests_misc;
     mod tests_pr192_fixes; // Tests for PR #192 bug fixes
+    mod tests_protected_mode; // 80286 protected mode
     mod tests_shifts;
 
     // Helper function for tests to calculate physical address
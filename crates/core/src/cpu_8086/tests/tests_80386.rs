@@ -0,0 +1,259 @@
+//! Tests for 80386 protected mode
+//!
+//! This module contains tests for 32-bit code segments (default operand and
+//! address size), 32-bit gates and paging
+
+use crate::cpu_8086::ArrayMemory;
+use crate::cpu_8086::{Cpu8086, CpuModel, Memory8086, FLAG_IF};
+
+const GDT: u32 = 0x1000;
+const IDT: u32 = 0x2000;
+const CODE_BASE: u32 = 0x10000;
+const STACK_TOP: u32 = 0x31000;
+const PAGE_DIR: u32 = 0x80000;
+const PAGE_TABLE: u32 = 0x81000;
+
+const CODE_SEL: u16 = 0x08;
+const DATA_SEL: u16 = 0x10;
+const STACK_SEL: u16 = 0x18;
+
+/// Write a descriptor with a 20-bit limit and the G/D flags nibble
+fn put_descriptor(
+    cpu: &mut Cpu8086<ArrayMemory>,
+    selector: u16,
+    base: u32,
+    limit: u32,
+    access: u8,
+    flags: u8,
+) {
+    let addr = GDT + (selector & 0xFFF8) as u32;
+    let bytes = [
+        limit as u8,
+        (limit >> 8) as u8,
+        base as u8,
+        (base >> 8) as u8,
+        (base >> 16) as u8,
+        access,
+        (flags << 4) | ((limit >> 16) as u8 & 0x0F),
+        (base >> 24) as u8,
+    ];
+    for (i, b) in bytes.iter().enumerate() {
+        cpu.memory.write(addr + i as u32, *b);
+    }
+}
+
+fn put_idt_gate(cpu: &mut Cpu8086<ArrayMemory>, vector: u8, offset: u32, access: u8) {
+    let addr = IDT + vector as u32 * 8;
+    cpu.memory.write_u16(addr, offset as u16);
+    cpu.memory.write_u16(addr + 2, CODE_SEL);
+    cpu.memory.write(addr + 4, 0);
+    cpu.memory.write(addr + 5, access);
+    cpu.memory.write_u16(addr + 6, (offset >> 16) as u16);
+}
+
+fn code_at(cpu: &mut Cpu8086<ArrayMemory>, offset: u32, program: &[u8]) {
+    cpu.memory.load_program(CODE_BASE + offset, program);
+}
+
+fn read_u32(cpu: &Cpu8086<ArrayMemory>, addr: u32) -> u32 {
+    cpu.memory.read_u16(addr) as u32 | (cpu.memory.read_u16(addr + 2) as u32) << 16
+}
+
+fn write_u32(cpu: &mut Cpu8086<ArrayMemory>, addr: u32, val: u32) {
+    cpu.memory.write_u16(addr, val as u16);
+    cpu.memory.write_u16(addr + 2, (val >> 16) as u16);
+}
+
+/// Enter protected mode with a 32-bit code segment and flat 32-bit data and
+/// stack segments, then jump to CODE_SEL:0100
+fn enter_32bit_protected_mode() -> Cpu8086<ArrayMemory> {
+    let mut cpu = Cpu8086::with_model(ArrayMemory::new(), CpuModel::Intel80386);
+
+    put_descriptor(&mut cpu, CODE_SEL, CODE_BASE, 0xFFFFF, 0x9A, 0xC);
+    put_descriptor(&mut cpu, DATA_SEL, 0, 0xFFFFF, 0x92, 0xC);
+    put_descriptor(&mut cpu, STACK_SEL, 0, 0xFFFFF, 0x92, 0xC);
+    // #GP and #PF handlers (HLT) at 0800, INT 20h handler (IRETD) at 0900
+    put_idt_gate(&mut cpu, 13, 0x0800, 0x8E);
+    put_idt_gate(&mut cpu, 14, 0x0800, 0x8E);
+    put_idt_gate(&mut cpu, 0x20, 0x0900, 0xEE);
+    code_at(&mut cpu, 0x0800, &[0xF4]);
+    code_at(&mut cpu, 0x0900, &[0xCF]);
+
+    cpu.protected_mode_mut().load_gdtr(GDT, 0x1F);
+    cpu.protected_mode_mut().load_idtr(IDT, 0xFF * 8 + 7);
+
+    // Real mode at 0000:7C00: LMSW AX; JMP 0008:0000
+    cpu.memory
+        .load_program(0x7C00, &[0x0F, 0x01, 0xF0, 0xEA, 0x00, 0x00, 0x08, 0x00]);
+    cpu.cs = 0;
+    cpu.ip = 0x7C00;
+    cpu.ax = 1;
+
+    // MOV EAX,10h; MOV DS,AX; MOV ES,AX; MOV EAX,18h; MOV SS,AX;
+    // MOV ESP,31000h; JMP 0100h
+    code_at(
+        &mut cpu,
+        0,
+        &[
+            0xB8, 0x10, 0x00, 0x00, 0x00, 0x8E, 0xD8, 0x8E, 0xC0, 0xB8, 0x18, 0x00, 0x00, 0x00,
+            0x8E, 0xD0, 0xBC, 0x00, 0x10, 0x03, 0x00, 0xE9, 0xE6, 0x00, 0x00, 0x00,
+        ],
+    );
+    for _ in 0..9 {
+        cpu.step();
+    }
+    assert!(cpu.protected_mode().is_protected_mode());
+    assert_eq!((cpu.cs, cpu.ip), (CODE_SEL, 0x0100));
+    assert_eq!((cpu.ss, cpu.sp, cpu.ds), (STACK_SEL, STACK_TOP, DATA_SEL));
+    cpu
+}
+
+#[test]
+fn test_32bit_code_segment_defaults_to_32bit_operands() {
+    let mut cpu = enter_32bit_protected_mode();
+
+    // MOV EBX,20000h; MOV ECX,3; MOV EAX,12345678h; MOV [EBX+ECX*4+8],EAX;
+    // MOV AX,0ABCDh (operand size prefix)
+    code_at(
+        &mut cpu,
+        0x0100,
+        &[
+            0xBB, 0x00, 0x00, 0x02, 0x00, 0xB9, 0x03, 0x00, 0x00, 0x00, 0xB8, 0x78, 0x56, 0x34,
+            0x12, 0x89, 0x44, 0x8B, 0x08, 0x66, 0xB8, 0xCD, 0xAB,
+        ],
+    );
+    for _ in 0..5 {
+        cpu.step();
+    }
+
+    assert_eq!(read_u32(&cpu, 0x20014), 0x1234_5678);
+    assert_eq!(cpu.ax, 0x1234_ABCD);
+    assert_eq!(cpu.ip, 0x0117);
+}
+
+#[test]
+fn test_jcc_near_rel32() {
+    let mut cpu = enter_32bit_protected_mode();
+
+    // XOR EAX,EAX; JZ near +1000h
+    code_at(
+        &mut cpu,
+        0x0100,
+        &[0x31, 0xC0, 0x0F, 0x84, 0x00, 0x10, 0x00, 0x00],
+    );
+    cpu.step();
+    cpu.step();
+
+    assert_eq!(cpu.ip, 0x1108);
+}
+
+#[test]
+fn test_rep_movsd_uses_ecx_esi_edi() {
+    let mut cpu = enter_32bit_protected_mode();
+    for i in 0..16 {
+        cpu.memory.write(0x20000 + i, 0x40 + i as u8);
+    }
+
+    // MOV ESI,20000h; MOV EDI,21000h; MOV ECX,4; CLD; REP MOVSD
+    code_at(
+        &mut cpu,
+        0x0100,
+        &[
+            0xBE, 0x00, 0x00, 0x02, 0x00, 0xBF, 0x00, 0x10, 0x02, 0x00, 0xB9, 0x04, 0x00, 0x00,
+            0x00, 0xFC, 0xF3, 0xA5,
+        ],
+    );
+    for _ in 0..5 {
+        cpu.step();
+    }
+
+    assert_eq!(cpu.cx, 0);
+    assert_eq!((cpu.si, cpu.di), (0x20010, 0x21010));
+    for i in 0..16 {
+        assert_eq!(cpu.memory.read(0x21000 + i), 0x40 + i as u8);
+    }
+}
+
+#[test]
+fn test_movsx_movzx_and_shld() {
+    let mut cpu = enter_32bit_protected_mode();
+
+    // MOV EAX,1234F080h; MOVSX EBX,AL; MOVZX ECX,AX; MOV EDX,89ABCDEFh;
+    // SHLD EDX,EAX,8
+    code_at(
+        &mut cpu,
+        0x0100,
+        &[
+            0xB8, 0x80, 0xF0, 0x34, 0x12, 0x0F, 0xBE, 0xD8, 0x0F, 0xB7, 0xC8, 0xBA, 0xEF, 0xCD,
+            0xAB, 0x89, 0x0F, 0xA4, 0xC2, 0x08,
+        ],
+    );
+    for _ in 0..5 {
+        cpu.step();
+    }
+
+    assert_eq!(cpu.bx, 0xFFFF_FF80);
+    assert_eq!(cpu.cx, 0x0000_F080);
+    assert_eq!(cpu.dx, 0xABCD_EF12);
+}
+
+#[test]
+fn test_interrupt_gate_386_pushes_dwords_and_iretd_returns() {
+    let mut cpu = enter_32bit_protected_mode();
+
+    code_at(&mut cpu, 0x0100, &[0xFB, 0xCD, 0x20]); // STI; INT 20h
+    cpu.step();
+    cpu.step();
+
+    assert_eq!(cpu.ip, 0x0900);
+    assert_eq!(cpu.sp, STACK_TOP - 12);
+    assert_eq!(read_u32(&cpu, cpu.sp), 0x0103); // EIP
+    assert_eq!(read_u32(&cpu, cpu.sp + 4), CODE_SEL as u32); // CS
+    assert_ne!(read_u32(&cpu, cpu.sp + 8) & FLAG_IF, 0); // EFLAGS
+    assert_eq!(cpu.flags & FLAG_IF, 0);
+
+    cpu.step(); // IRETD
+    assert_eq!((cpu.cs, cpu.ip, cpu.sp), (CODE_SEL, 0x0103, STACK_TOP));
+    assert_ne!(cpu.flags & FLAG_IF, 0);
+}
+
+#[test]
+fn test_paging_translates_and_faults_on_not_present_page() {
+    let mut cpu = enter_32bit_protected_mode();
+
+    // Identity map the first megabyte, leaving the page at 50000h unmapped
+    write_u32(&mut cpu, PAGE_DIR, PAGE_TABLE | 0x07);
+    for page in 0..256u32 {
+        let entry = if page == 0x50 { 0 } else { (page << 12) | 0x03 };
+        write_u32(&mut cpu, PAGE_TABLE + page * 4, entry);
+    }
+    write_u32(&mut cpu, 0x20000, 0xCAFE_F00D);
+
+    // MOV EAX,80000h; MOV CR3,EAX; MOV EAX,CR0; OR EAX,80000000h; MOV CR0,EAX;
+    // MOV EBX,[20000h]; MOV [50004h],EBX
+    code_at(
+        &mut cpu,
+        0x0100,
+        &[
+            0xB8, 0x00, 0x00, 0x08, 0x00, 0x0F, 0x22, 0xD8, 0x0F, 0x20, 0xC0, 0x0D, 0x00, 0x00,
+            0x00, 0x80, 0x0F, 0x22, 0xC0, 0x8B, 0x1D, 0x00, 0x00, 0x02, 0x00, 0x89, 0x1D, 0x04,
+            0x00, 0x05, 0x00,
+        ],
+    );
+    for _ in 0..6 {
+        cpu.step();
+    }
+    assert!(cpu.protected_mode().is_paging());
+    assert_eq!(cpu.bx, 0xCAFE_F00D);
+    // The read set the accessed bits but not dirty
+    assert_ne!(read_u32(&cpu, PAGE_DIR) & 0x20, 0);
+    assert_eq!(read_u32(&cpu, PAGE_TABLE + 0x20 * 4) & 0x60, 0x20);
+
+    cpu.step(); // Write to the unmapped page
+    assert_eq!((cpu.cs, cpu.ip), (CODE_SEL, 0x0800));
+    assert_eq!(cpu.protected_mode().cr2, 0x50004);
+    assert_eq!(cpu.sp, STACK_TOP - 16);
+    assert_eq!(read_u32(&cpu, cpu.sp), 0x02); // Write to a not-present page
+    assert_eq!(read_u32(&cpu, cpu.sp + 4), 0x0119); // Faulting EIP
+    assert_eq!(read_u32(&cpu, 0x50004), 0);
+}
@@ -839,9 +839,7 @@ fn test_blackbox_shift_masking_observable() {
 
 /// Black box test 13: 32-bit operand override on different models
 /// Tests that 32-bit operations are only supported on 80386+
-/// **This test currently FAILS and documents a bug/limitation in the emulator!**
 #[test]
-#[should_panic(expected = "80386: Should support 32-bit immediate")]
 fn test_blackbox_32bit_operand_differences() {
     // On 8086: operand size override (0x66) should be ignored or treated as prefix to next instruction
     // On 80386: operand size override enables 32-bit operations
@@ -870,9 +868,7 @@ fn test_blackbox_32bit_operand_differences() {
             steps += 1;
         }
 
-        // BUG: On 80386 should load full 32-bit value 0x12345678
-        // Currently only loads 16-bit value 0x5678
-        // This test documents the bug by expecting it to panic
+        // On 80386 the prefix loads the full 32-bit value
         assert_eq!(
             cpu.get_reg32(0),
             0x12345678,
@@ -0,0 +1,410 @@
+//! Tests for 80286 protected mode
+//!
+//! This module contains tests for descriptor loading, segment limit checks,
+//! interrupts through the IDT, privilege level changes and task switching
+
+use crate::cpu_8086::ArrayMemory;
+use crate::cpu_8086::{Cpu8086, CpuModel, Memory8086, FLAG_IF, FLAG_ZF};
+
+const GDT: u32 = 0x1000;
+const IDT: u32 = 0x2000;
+const TSS_A: u32 = 0x5000;
+const TSS_B: u32 = 0x5100;
+const CODE_BASE: u32 = 0x10000;
+const DATA_BASE: u32 = 0x20000;
+const STACK_BASE: u32 = 0x30000;
+const USER_STACK_BASE: u32 = 0x40000;
+
+const CODE_SEL: u16 = 0x08;
+const DATA_SEL: u16 = 0x10;
+const STACK_SEL: u16 = 0x18;
+const TSS_A_SEL: u16 = 0x20;
+const TSS_B_SEL: u16 = 0x28;
+const USER_CODE_SEL: u16 = 0x33;
+const USER_STACK_SEL: u16 = 0x3B;
+const TASK_GATE_SEL: u16 = 0x40;
+
+fn put_descriptor(
+    cpu: &mut Cpu8086<ArrayMemory>,
+    selector: u16,
+    base: u32,
+    limit: u16,
+    access: u8,
+) {
+    let addr = GDT + (selector & 0xFFF8) as u32;
+    let bytes = [
+        limit as u8,
+        (limit >> 8) as u8,
+        base as u8,
+        (base >> 8) as u8,
+        (base >> 16) as u8,
+        access,
+        0,
+        0,
+    ];
+    for (i, b) in bytes.iter().enumerate() {
+        cpu.memory.write(addr + i as u32, *b);
+    }
+}
+
+fn put_gate(cpu: &mut Cpu8086<ArrayMemory>, addr: u32, selector: u16, offset: u16, access: u8) {
+    cpu.memory.write_u16(addr, offset);
+    cpu.memory.write_u16(addr + 2, selector);
+    cpu.memory.write(addr + 4, 0);
+    cpu.memory.write(addr + 5, access);
+}
+
+fn put_idt_gate(
+    cpu: &mut Cpu8086<ArrayMemory>,
+    vector: u8,
+    selector: u16,
+    offset: u16,
+    access: u8,
+) {
+    put_gate(cpu, IDT + vector as u32 * 8, selector, offset, access);
+}
+
+/// Place code at CODE_SEL:offset
+fn code_at(cpu: &mut Cpu8086<ArrayMemory>, offset: u16, program: &[u8]) {
+    cpu.memory.load_program(CODE_BASE + offset as u32, program);
+}
+
+/// Build the GDT/IDT, switch to protected mode with LMSW + far JMP, load
+/// SS/SP/DS and jump to CODE_SEL:0100
+fn enter_protected_mode() -> Cpu8086<ArrayMemory> {
+    let mut cpu = Cpu8086::with_model(ArrayMemory::new(), CpuModel::Intel80286);
+
+    put_descriptor(&mut cpu, CODE_SEL, CODE_BASE, 0xFFFF, 0x9A);
+    put_descriptor(&mut cpu, DATA_SEL, DATA_BASE, 0x00FF, 0x92);
+    put_descriptor(&mut cpu, STACK_SEL, STACK_BASE, 0xFFFF, 0x92);
+    put_descriptor(&mut cpu, TSS_A_SEL, TSS_A, 0x2B, 0x81);
+    put_descriptor(&mut cpu, TSS_B_SEL, TSS_B, 0x2B, 0x81);
+    put_descriptor(&mut cpu, USER_CODE_SEL, CODE_BASE, 0xFFFF, 0xFA);
+    put_descriptor(&mut cpu, USER_STACK_SEL, USER_STACK_BASE, 0xFFFF, 0xF2);
+    put_gate(&mut cpu, GDT + TASK_GATE_SEL as u32, TSS_B_SEL, 0, 0x85);
+    // #GP handler at 0800, INT 20h handler (IRET) at 0900
+    put_idt_gate(&mut cpu, 13, CODE_SEL, 0x0800, 0x86);
+    put_idt_gate(&mut cpu, 0x20, CODE_SEL, 0x0900, 0xE6);
+    code_at(&mut cpu, 0x0800, &[0xF4]); // HLT
+    code_at(&mut cpu, 0x0900, &[0xCF]); // IRET
+
+    // TSS A: ring 0 stack for privilege changes
+    cpu.memory.write_u16(TSS_A + 0x02, 0x0F00);
+    cpu.memory.write_u16(TSS_A + 0x04, STACK_SEL);
+
+    cpu.protected_mode_mut().load_gdtr(GDT, 0x47);
+    cpu.protected_mode_mut().load_idtr(IDT, 0xFF * 8 + 7);
+
+    // Real mode at 0000:7C00: LMSW AX; JMP 0008:0000
+    cpu.memory
+        .load_program(0x7C00, &[0x0F, 0x01, 0xF0, 0xEA, 0x00, 0x00, 0x08, 0x00]);
+    cpu.cs = 0;
+    cpu.ip = 0x7C00;
+    cpu.ax = 1;
+
+    // MOV AX,18h; MOV SS,AX; MOV SP,1000h; MOV AX,10h; MOV DS,AX; JMP 0100h
+    code_at(
+        &mut cpu,
+        0,
+        &[
+            0xB8, 0x18, 0x00, 0x8E, 0xD0, 0xBC, 0x00, 0x10, 0xB8, 0x10, 0x00, 0x8E, 0xD8, 0xE9,
+            0xF0, 0x00,
+        ],
+    );
+    for _ in 0..8 {
+        cpu.step();
+    }
+    assert!(cpu.protected_mode().is_protected_mode());
+    assert_eq!((cpu.cs, cpu.ip), (CODE_SEL, 0x0100));
+    assert_eq!((cpu.ss, cpu.sp, cpu.ds), (STACK_SEL, 0x1000, DATA_SEL));
+    cpu
+}
+
+fn stack_word(cpu: &Cpu8086<ArrayMemory>, base: u32, sp: u32) -> u16 {
+    cpu.memory.read_u16(base + sp)
+}
+
+#[test]
+fn test_segments_use_descriptor_base() {
+    let mut cpu = enter_protected_mode();
+    cpu.memory.write(DATA_BASE + 0x10, 0x5A);
+
+    code_at(&mut cpu, 0x0100, &[0xA0, 0x10, 0x00]); // MOV AL, [0010h]
+    cpu.step();
+
+    assert_eq!(cpu.ax & 0xFF, 0x5A);
+    assert_eq!(cpu.protected_mode().segment_cache[3].base, DATA_BASE);
+}
+
+#[test]
+fn test_segment_limit_violation_raises_gp() {
+    let mut cpu = enter_protected_mode();
+
+    code_at(&mut cpu, 0x0100, &[0xA0, 0x00, 0x01]); // MOV AL, [0100h] (limit is FFh)
+    cpu.step();
+
+    // #GP(0) handler entered with the faulting instruction's address saved
+    assert_eq!((cpu.cs, cpu.ip), (CODE_SEL, 0x0800));
+    assert_eq!(cpu.sp, 0x1000 - 8);
+    assert_eq!(stack_word(&cpu, STACK_BASE, cpu.sp), 0); // Error code
+    assert_eq!(stack_word(&cpu, STACK_BASE, cpu.sp + 2), 0x0100); // IP
+    assert_eq!(stack_word(&cpu, STACK_BASE, cpu.sp + 4), CODE_SEL); // CS
+}
+
+#[test]
+fn test_null_selector_access_raises_gp() {
+    let mut cpu = enter_protected_mode();
+
+    // XOR AX,AX; MOV ES,AX; MOV AL,ES:[0000h]
+    code_at(
+        &mut cpu,
+        0x0100,
+        &[0x31, 0xC0, 0x8E, 0xC0, 0x26, 0xA0, 0x00, 0x00],
+    );
+    cpu.step();
+    cpu.step();
+    assert_eq!(cpu.es, 0);
+    cpu.step();
+
+    assert_eq!(cpu.ip, 0x0800);
+    assert_eq!(stack_word(&cpu, STACK_BASE, cpu.sp + 2), 0x0104);
+}
+
+#[test]
+fn test_loading_invalid_selector_faults() {
+    let mut cpu = enter_protected_mode();
+
+    // MOV AX,28h (a TSS); MOV ES,AX
+    code_at(&mut cpu, 0x0100, &[0xB8, 0x28, 0x00, 0x8E, 0xC0]);
+    cpu.step();
+    cpu.step();
+
+    assert_eq!(cpu.ip, 0x0800);
+    assert_eq!(stack_word(&cpu, STACK_BASE, cpu.sp), TSS_B_SEL); // Error code = selector
+    assert_eq!(cpu.es, 0);
+}
+
+#[test]
+fn test_triple_fault_shuts_down_until_reset() {
+    let mut cpu = enter_protected_mode();
+
+    // With an empty IDT the #GP, then the double fault, cannot be delivered
+    cpu.protected_mode_mut().load_idtr(IDT, 0);
+    code_at(&mut cpu, 0x0100, &[0xA0, 0x00, 0x01]); // MOV AL, [0100h] (limit is FFh)
+    cpu.step();
+
+    assert!(cpu.is_shut_down());
+    assert!(cpu.is_halted());
+
+    cpu.reset();
+    assert!(!cpu.is_shut_down());
+    assert!(!cpu.protected_mode().is_protected_mode());
+}
+
+#[test]
+fn test_lmsw_cannot_leave_protected_mode() {
+    let mut cpu = enter_protected_mode();
+
+    code_at(&mut cpu, 0x0100, &[0x31, 0xC0, 0x0F, 0x01, 0xF0]); // XOR AX,AX; LMSW AX
+    cpu.step();
+    cpu.step();
+
+    assert!(cpu.protected_mode().is_protected_mode());
+}
+
+#[test]
+fn test_interrupt_through_idt_and_iret() {
+    let mut cpu = enter_protected_mode();
+    cpu.flags |= FLAG_IF;
+
+    code_at(&mut cpu, 0x0100, &[0xCD, 0x20]); // INT 20h
+    cpu.step();
+    assert_eq!((cpu.cs, cpu.ip), (CODE_SEL, 0x0900));
+    assert_eq!(cpu.flags & FLAG_IF, 0); // Interrupt gate clears IF
+    assert_eq!(stack_word(&cpu, STACK_BASE, cpu.sp), 0x0102);
+
+    cpu.step(); // IRET
+    assert_eq!((cpu.cs, cpu.ip, cpu.sp), (CODE_SEL, 0x0102, 0x1000));
+    assert_ne!(cpu.flags & FLAG_IF, 0);
+}
+
+#[test]
+fn test_interrupt_from_ring3_switches_to_tss_stack() {
+    let mut cpu = enter_protected_mode();
+
+    // MOV AX,20h; LTR AX; then IRET to ring 3 at 0C00 with SS:SP = 3B:2000
+    code_at(
+        &mut cpu,
+        0x0100,
+        &[0xB8, 0x20, 0x00, 0x0F, 0x00, 0xD8, 0xCF],
+    );
+    for (i, word) in [0x0C00, USER_CODE_SEL, 0x0002, 0x2000, USER_STACK_SEL]
+        .iter()
+        .enumerate()
+    {
+        cpu.memory
+            .write_u16(STACK_BASE + 0x0FF6 + i as u32 * 2, *word);
+    }
+    cpu.sp = 0x0FF6;
+    code_at(&mut cpu, 0x0C00, &[0xCD, 0x20]); // INT 20h (gate DPL 3)
+    for _ in 0..3 {
+        cpu.step();
+    }
+    assert_eq!(cpu.protected_mode().cpl, 3);
+    assert_eq!((cpu.cs, cpu.ip), (USER_CODE_SEL, 0x0C00));
+    assert_eq!((cpu.ss, cpu.sp), (USER_STACK_SEL, 0x2000));
+    assert_eq!(cpu.ds, 0); // DPL 0 data segment dropped on the way out
+
+    cpu.step(); // INT 20h
+    assert_eq!(cpu.protected_mode().cpl, 0);
+    assert_eq!((cpu.ss, cpu.sp), (STACK_SEL, 0x0F00 - 10));
+    assert_eq!(stack_word(&cpu, STACK_BASE, 0x0F00 - 2), USER_STACK_SEL);
+    assert_eq!(stack_word(&cpu, STACK_BASE, 0x0F00 - 4), 0x2000);
+
+    cpu.step(); // IRET back to ring 3
+    assert_eq!(cpu.protected_mode().cpl, 3);
+    assert_eq!((cpu.cs, cpu.ip), (USER_CODE_SEL, 0x0C02));
+    assert_eq!((cpu.ss, cpu.sp), (USER_STACK_SEL, 0x2000));
+}
+
+#[test]
+fn test_software_interrupt_checks_gate_dpl() {
+    let mut cpu = enter_protected_mode();
+    put_idt_gate(&mut cpu, 0x21, CODE_SEL, 0x0900, 0x86); // DPL 0
+
+    code_at(
+        &mut cpu,
+        0x0100,
+        &[0xB8, 0x20, 0x00, 0x0F, 0x00, 0xD8, 0xCF],
+    );
+    for (i, word) in [0x0C00, USER_CODE_SEL, 0x0002, 0x2000, USER_STACK_SEL]
+        .iter()
+        .enumerate()
+    {
+        cpu.memory
+            .write_u16(STACK_BASE + 0x0FF6 + i as u32 * 2, *word);
+    }
+    cpu.sp = 0x0FF6;
+    code_at(&mut cpu, 0x0C00, &[0xCD, 0x21]); // INT 21h from ring 3
+    for _ in 0..4 {
+        cpu.step();
+    }
+
+    assert_eq!((cpu.cs, cpu.ip), (CODE_SEL, 0x0800));
+    assert_eq!(stack_word(&cpu, STACK_BASE, cpu.sp), 0x21 * 8 + 2); // IDT error code
+    assert_eq!(stack_word(&cpu, STACK_BASE, cpu.sp + 2), 0x0C00);
+}
+
+fn setup_tss_b(cpu: &mut Cpu8086<ArrayMemory>) {
+    cpu.memory.write_u16(TSS_B + 0x0E, 0x0A00); // IP
+    cpu.memory.write_u16(TSS_B + 0x10, 0x0002); // FLAGS
+    cpu.memory.write_u16(TSS_B + 0x12, 0x1234); // AX
+    cpu.memory.write_u16(TSS_B + 0x1A, 0x0800); // SP
+    cpu.memory.write_u16(TSS_B + 0x22, DATA_SEL); // ES
+    cpu.memory.write_u16(TSS_B + 0x24, CODE_SEL); // CS
+    cpu.memory.write_u16(TSS_B + 0x26, STACK_SEL); // SS
+    cpu.memory.write_u16(TSS_B + 0x28, DATA_SEL); // DS
+}
+
+#[test]
+fn test_far_jump_to_tss_switches_task() {
+    let mut cpu = enter_protected_mode();
+    setup_tss_b(&mut cpu);
+
+    // MOV AX,20h; LTR AX; JMP 0028:0000
+    code_at(
+        &mut cpu,
+        0x0100,
+        &[
+            0xB8, 0x20, 0x00, 0x0F, 0x00, 0xD8, 0xEA, 0x00, 0x00, 0x28, 0x00,
+        ],
+    );
+    cpu.step();
+    cpu.step();
+    assert_eq!(cpu.memory.read(GDT + TSS_A_SEL as u32 + 5), 0x83); // Busy
+    cpu.step();
+
+    assert_eq!(cpu.protected_mode().tr, TSS_B_SEL);
+    assert_eq!((cpu.cs, cpu.ip), (CODE_SEL, 0x0A00));
+    assert_eq!((cpu.ax, cpu.sp, cpu.es), (0x1234, 0x0800, DATA_SEL));
+    assert_ne!(cpu.protected_mode().get_msw() & 0x0008, 0); // TS set
+
+    // Outgoing state saved, busy bits moved
+    assert_eq!(cpu.memory.read_u16(TSS_A + 0x0E), 0x010B);
+    assert_eq!(cpu.memory.read_u16(TSS_A + 0x12), 0x0020);
+    assert_eq!(cpu.memory.read(GDT + TSS_A_SEL as u32 + 5), 0x81);
+    assert_eq!(cpu.memory.read(GDT + TSS_B_SEL as u32 + 5), 0x83);
+}
+
+#[test]
+fn test_task_gate_interrupt_and_iret_return() {
+    let mut cpu = enter_protected_mode();
+    setup_tss_b(&mut cpu);
+    put_idt_gate(&mut cpu, 0x30, TSS_B_SEL, 0, 0x85); // Task gate
+    code_at(&mut cpu, 0x0A00, &[0xCF]); // IRET in task B
+
+    // MOV AX,20h; LTR AX; INT 30h
+    code_at(
+        &mut cpu,
+        0x0100,
+        &[0xB8, 0x20, 0x00, 0x0F, 0x00, 0xD8, 0xCD, 0x30],
+    );
+    for _ in 0..3 {
+        cpu.step();
+    }
+    assert_eq!(cpu.protected_mode().tr, TSS_B_SEL);
+    assert_ne!(cpu.flags & 0x4000, 0); // NT
+    assert_eq!(cpu.memory.read_u16(TSS_B), TSS_A_SEL); // Back link
+
+    cpu.step(); // IRET returns to task A
+    assert_eq!(cpu.protected_mode().tr, TSS_A_SEL);
+    assert_eq!((cpu.cs, cpu.ip, cpu.ax), (CODE_SEL, 0x0108, 0x0020));
+    assert_eq!(cpu.memory.read(GDT + TSS_B_SEL as u32 + 5), 0x81);
+}
+
+#[test]
+fn test_far_jump_through_task_gate_descriptor() {
+    let mut cpu = enter_protected_mode();
+    setup_tss_b(&mut cpu);
+
+    // MOV AX,20h; LTR AX; JMP 0040:0000
+    code_at(
+        &mut cpu,
+        0x0100,
+        &[
+            0xB8, 0x20, 0x00, 0x0F, 0x00, 0xD8, 0xEA, 0x00, 0x00, 0x40, 0x00,
+        ],
+    );
+    for _ in 0..3 {
+        cpu.step();
+    }
+    assert_eq!(cpu.protected_mode().tr, TSS_B_SEL);
+    assert_eq!(cpu.ip, 0x0A00);
+}
+
+#[test]
+fn test_lar_lsl_verr_verw() {
+    let mut cpu = enter_protected_mode();
+
+    // MOV AX,10h; LAR BX,AX; LSL CX,AX; VERW AX
+    code_at(
+        &mut cpu,
+        0x0100,
+        &[
+            0xB8, 0x10, 0x00, 0x0F, 0x02, 0xD8, 0x0F, 0x03, 0xC8, 0x0F, 0x00, 0xE8,
+        ],
+    );
+    for _ in 0..3 {
+        cpu.step();
+    }
+    assert_eq!(cpu.bx, 0x9300); // Access byte (accessed bit set by MOV DS)
+    assert_eq!(cpu.cx, 0x00FF);
+    cpu.step();
+    assert_ne!(cpu.flags & FLAG_ZF, 0);
+
+    // MOV AX,08h; VERW AX (code is not writable)
+    code_at(&mut cpu, 0x010C, &[0xB8, 0x08, 0x00, 0x0F, 0x00, 0xE8]);
+    cpu.step();
+    cpu.step();
+    assert_eq!(cpu.flags & FLAG_ZF, 0);
+}
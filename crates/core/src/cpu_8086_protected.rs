@@ -1,10 +1,12 @@
-//! Intel 80286 Protected Mode Support
+//! Intel 80286/80386 Protected Mode Support
 //!
-//! This module implements protected mode features for the 80286 CPU.
+//! This module implements protected mode features for the 80286 CPU and the
+//! 80386 extensions (32-bit segments, gates and TSSs, paging).
 //! Protected mode is only activated when the CPU model is Intel80286 or later.
 //!
 //! Key features:
 //! - Machine Status Word (MSW) / Control Register 0 (CR0) with PE bit
+//! - CR0 PG bit, CR2 and CR3 for two-level paging (80386+)
 //! - Global Descriptor Table (GDT) and Interrupt Descriptor Table (IDT)
 //! - Local Descriptor Table (LDT) support
 //! - Task State Segment (TSS) for task switching
@@ -38,6 +40,64 @@ pub struct ProtectedModeState {
 
     /// Task Register (TR)
     pub tr: u16,
+
+    /// Hidden descriptor caches of ES, CS, SS, DS, FS and GS (in that order,
+    /// matching the segment register encoding used by ModR/M)
+    #[serde(default)]
+    pub segment_cache: [SegmentCache; 6],
+
+    /// Descriptor cache of the current LDT
+    #[serde(default)]
+    pub ldt_cache: SegmentCache,
+
+    /// Descriptor cache of the current TSS
+    #[serde(default)]
+    pub tr_cache: SegmentCache,
+
+    /// Current privilege level (0 in real mode and right after setting PE)
+    #[serde(default)]
+    pub cpl: u8,
+
+    /// Upper half of CR0 (80386+)
+    /// Bit 16 (WP): Write Protect, Bit 31 (PG): Paging
+    #[serde(default)]
+    pub cr0_high: u16,
+
+    /// CR2: linear address of the last page fault (80386+)
+    #[serde(default)]
+    pub cr2: u32,
+
+    /// CR3: physical address of the page directory (80386+)
+    #[serde(default)]
+    pub cr3: u32,
+}
+
+/// Hidden (cached) part of a segment register
+///
+/// Loaded from the descriptor table when a selector is loaded in protected
+/// mode; memory accesses use the cached base and limit, so later changes to
+/// the descriptor table do not affect an already loaded segment.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SegmentCache {
+    /// Selector the cache was loaded from
+    pub selector: u16,
+
+    /// Segment base address (24-bit on 80286)
+    pub base: u32,
+
+    /// Segment limit (highest valid offset, or lowest invalid one for expand-down)
+    pub limit: u32,
+
+    /// Access rights byte
+    pub access: u8,
+
+    /// False for a null selector: every access through it faults
+    pub valid: bool,
+
+    /// D/B bit (80386+): 32-bit code, a 32-bit stack pointer (ESP), or a
+    /// 4GB upper bound for an expand-down data segment
+    #[serde(default)]
+    pub big: bool,
 }
 
 /// Descriptor Table Register (for GDTR/IDTR)
@@ -85,6 +145,13 @@ impl ProtectedModeState {
             idtr: DescriptorTableRegister { base: 0, limit: 0 },
             ldtr: 0,
             tr: 0,
+            segment_cache: [SegmentCache::default(); 6],
+            ldt_cache: SegmentCache::default(),
+            tr_cache: SegmentCache::default(),
+            cpl: 0,
+            cr0_high: 0,
+            cr2: 0,
+            cr3: 0,
         }
     }
 
@@ -121,16 +188,24 @@ impl ProtectedModeState {
         self.msw
     }
 
-    /// Get CR0 (Control Register 0) - alias for get_msw for 80386+
+    /// Get CR0 (Control Register 0): the MSW plus the 80386 upper bits
     #[inline]
-    pub fn get_cr0(&self) -> u16 {
-        self.msw
+    pub fn get_cr0(&self) -> u32 {
+        ((self.cr0_high as u32) << 16) | self.msw as u32
+    }
+
+    /// Set CR0 (Control Register 0)
+    #[inline]
+    pub fn set_cr0(&mut self, value: u32) {
+        self.set_msw(value as u16);
+        // WP, AM, NW, CD and PG
+        self.cr0_high = ((value >> 16) as u16) & 0xE005;
     }
 
-    /// Set CR0 (Control Register 0) - alias for set_msw for 80386+
+    /// Check if paging is enabled (CR0.PG, only effective in protected mode)
     #[inline]
-    pub fn set_cr0(&mut self, value: u16) {
-        self.set_msw(value);
+    pub fn is_paging(&self) -> bool {
+        self.is_protected_mode() && (self.cr0_high & 0x8000) != 0
     }
 
     /// Load the Global Descriptor Table Register
@@ -162,6 +237,82 @@ impl ProtectedModeState {
         self.idtr = DescriptorTableRegister { base: 0, limit: 0 };
         self.ldtr = 0;
         self.tr = 0;
+        self.segment_cache = [SegmentCache::default(); 6];
+        self.ldt_cache = SegmentCache::default();
+        self.tr_cache = SegmentCache::default();
+        self.cpl = 0;
+        self.cr0_high = 0;
+        self.cr2 = 0;
+        self.cr3 = 0;
+    }
+
+    /// Fill the segment caches with real-mode style values (base = selector * 16,
+    /// 64KB limit), as the CPU leaves them when PE is set
+    pub fn load_real_mode_caches(&mut self, selectors: [u16; 6]) {
+        for (cache, selector) in self.segment_cache.iter_mut().zip(selectors) {
+            *cache = SegmentCache::real_mode(selector);
+        }
+        self.cpl = 0;
+    }
+}
+
+impl SegmentCache {
+    /// Cache contents for a real-mode segment
+    pub fn real_mode(selector: u16) -> Self {
+        Self {
+            selector,
+            base: (selector as u32) << 4,
+            limit: 0xFFFF,
+            access: 0x93, // Present, writable data, accessed
+            valid: true,
+            big: false,
+        }
+    }
+
+    /// Cache contents for a descriptor loaded through `selector` (80286 format)
+    pub fn from_descriptor(selector: u16, descriptor: &SegmentDescriptor) -> Self {
+        Self {
+            selector,
+            base: descriptor.base & 0x00FFFFFF,
+            limit: descriptor.limit & 0xFFFF,
+            access: descriptor.access,
+            valid: true,
+            big: false,
+        }
+    }
+
+    /// Cache contents for a descriptor loaded on an 80386+: 32-bit base,
+    /// page-granular limit and the D/B bit
+    pub fn from_descriptor_386(selector: u16, descriptor: &SegmentDescriptor) -> Self {
+        Self {
+            selector,
+            base: descriptor.base,
+            limit: descriptor.byte_limit(),
+            access: descriptor.access,
+            valid: true,
+            big: descriptor.is_big(),
+        }
+    }
+
+    /// Cache for a null selector loaded into a data segment register
+    pub fn null(selector: u16) -> Self {
+        Self {
+            selector,
+            ..Self::default()
+        }
+    }
+
+    /// Check that `size` bytes starting at `offset` are within the segment limit
+    #[inline]
+    pub fn contains(&self, offset: u32, size: u32) -> bool {
+        let last = offset as u64 + size as u64 - 1;
+        let expand_down = (self.access & 0x1C) == 0x14; // Data segment with ED set
+        if expand_down {
+            let upper = if self.big { 0xFFFF_FFFF } else { 0xFFFF };
+            offset > self.limit && last <= upper
+        } else {
+            last <= self.limit as u64
+        }
     }
 }
 
@@ -216,6 +367,277 @@ impl SegmentDescriptor {
     pub fn is_data_segment(&self) -> bool {
         !self.is_code_segment()
     }
+
+    /// Check if this is a system descriptor (TSS, LDT or gate) rather than code/data
+    #[inline]
+    pub fn is_system(&self) -> bool {
+        (self.access & access_rights::DESCRIPTOR_TYPE) == 0
+    }
+
+    /// System descriptor type (see [`system_types`]); only meaningful if `is_system()`
+    #[inline]
+    pub fn system_type(&self) -> u8 {
+        self.access & 0x0F
+    }
+
+    /// Readable code or any data segment (valid for DS/ES/FS/GS)
+    #[inline]
+    pub fn is_readable(&self) -> bool {
+        !self.is_system()
+            && (self.is_data_segment() || (self.access & access_rights::WRITABLE_READABLE) != 0)
+    }
+
+    /// Writable data segment (the only kind valid for SS)
+    #[inline]
+    pub fn is_writable_data(&self) -> bool {
+        !self.is_system()
+            && self.is_data_segment()
+            && (self.access & access_rights::WRITABLE_READABLE) != 0
+    }
+
+    /// Conforming code segment
+    #[inline]
+    pub fn is_conforming_code(&self) -> bool {
+        !self.is_system()
+            && self.is_code_segment()
+            && (self.access & access_rights::EXPAND_DOWN_CONFORMING) != 0
+    }
+
+    /// Granularity bit (80386+): the limit counts 4KB pages
+    #[inline]
+    pub fn is_granular(&self) -> bool {
+        (self.flags & 0x08) != 0
+    }
+
+    /// D/B bit (80386+): 32-bit code or stack segment
+    #[inline]
+    pub fn is_big(&self) -> bool {
+        (self.flags & 0x04) != 0
+    }
+
+    /// Limit in bytes, scaled by the granularity bit
+    #[inline]
+    pub fn byte_limit(&self) -> u32 {
+        if self.is_granular() {
+            (self.limit << 12) | 0xFFF
+        } else {
+            self.limit
+        }
+    }
+}
+
+/// Gate descriptor (call, task, interrupt or trap gate)
+///
+/// Format:
+/// - Bytes 0-1: Destination offset
+/// - Bytes 2-3: Destination selector (TSS selector for task gates)
+/// - Byte 4: Word count (call gates only)
+/// - Byte 5: Access Rights
+/// - Bytes 6-7: Destination offset (bits 16-31) [80386 gates only]
+#[derive(Debug, Clone, Copy)]
+pub struct GateDescriptor {
+    /// Destination offset
+    pub offset: u32,
+
+    /// Destination code segment or TSS selector
+    pub selector: u16,
+
+    /// Number of stack words copied on an inter-privilege call (call gates)
+    pub word_count: u8,
+
+    /// Access rights byte
+    pub access: u8,
+}
+
+impl GateDescriptor {
+    /// Parse a gate descriptor from 8 bytes in memory
+    pub fn from_bytes(bytes: &[u8; 8]) -> Self {
+        let mut gate = Self {
+            offset: u16::from_le_bytes([bytes[0], bytes[1]]) as u32,
+            selector: u16::from_le_bytes([bytes[2], bytes[3]]),
+            word_count: bytes[4] & 0x1F,
+            access: bytes[5],
+        };
+        if gate.is_32bit() {
+            gate.offset |= (u16::from_le_bytes([bytes[6], bytes[7]]) as u32) << 16;
+        }
+        gate
+    }
+
+    /// 80386 call, interrupt or trap gate (32-bit offset, pushes dwords)
+    #[inline]
+    pub fn is_32bit(&self) -> bool {
+        matches!(
+            self.gate_type(),
+            Some(
+                system_types::CALL_GATE_386
+                    | system_types::INTERRUPT_GATE_386
+                    | system_types::TRAP_GATE_386
+            )
+        )
+    }
+
+    /// Check if this gate is present
+    #[inline]
+    pub fn is_present(&self) -> bool {
+        (self.access & access_rights::PRESENT) != 0
+    }
+
+    /// Get the gate's descriptor privilege level (DPL)
+    #[inline]
+    pub fn dpl(&self) -> u8 {
+        (self.access >> 5) & 0x03
+    }
+
+    /// Gate type (see [`system_types`]), or None if this is not a system descriptor
+    #[inline]
+    pub fn gate_type(&self) -> Option<u8> {
+        ((self.access & access_rights::DESCRIPTOR_TYPE) == 0).then_some(self.access & 0x0F)
+    }
+}
+
+/// Faults raised by protected mode checks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProtectedModeFault {
+    /// #DF - Double fault (vector 8)
+    DoubleFault,
+    /// #TS - Invalid TSS (vector 10)
+    InvalidTss(u16),
+    /// #NP - Segment not present (vector 11)
+    SegmentNotPresent(u16),
+    /// #SS - Stack fault (vector 12)
+    StackFault(u16),
+    /// #GP - General protection (vector 13)
+    GeneralProtection(u16),
+    /// #UD - Invalid opcode (vector 6), e.g. MOV CS in protected mode
+    InvalidOpcode,
+    /// #PF - Page fault (vector 14): error code and faulting linear address
+    PageFault(u16, u32),
+}
+
+impl ProtectedModeFault {
+    /// Interrupt vector of the exception
+    pub fn vector(&self) -> u8 {
+        match self {
+            Self::InvalidOpcode => 6,
+            Self::DoubleFault => 8,
+            Self::InvalidTss(_) => 10,
+            Self::SegmentNotPresent(_) => 11,
+            Self::StackFault(_) => 12,
+            Self::GeneralProtection(_) => 13,
+            Self::PageFault(..) => 14,
+        }
+    }
+
+    /// Error code pushed with the exception, if it has one
+    pub fn error_code(&self) -> Option<u16> {
+        match self {
+            Self::InvalidOpcode => None,
+            Self::DoubleFault => Some(0),
+            Self::InvalidTss(code)
+            | Self::SegmentNotPresent(code)
+            | Self::StackFault(code)
+            | Self::GeneralProtection(code)
+            | Self::PageFault(code, _) => Some(*code),
+        }
+    }
+}
+
+/// System descriptor types (low 4 bits of the access byte when S=0)
+pub mod system_types {
+    /// Available 80286 TSS
+    pub const TSS_286_AVAILABLE: u8 = 0x1;
+
+    /// Local Descriptor Table
+    pub const LDT: u8 = 0x2;
+
+    /// Busy 80286 TSS
+    pub const TSS_286_BUSY: u8 = 0x3;
+
+    /// 80286 call gate
+    pub const CALL_GATE_286: u8 = 0x4;
+
+    /// Task gate
+    pub const TASK_GATE: u8 = 0x5;
+
+    /// 80286 interrupt gate (clears IF)
+    pub const INTERRUPT_GATE_286: u8 = 0x6;
+
+    /// 80286 trap gate (leaves IF unchanged)
+    pub const TRAP_GATE_286: u8 = 0x7;
+
+    /// Available 80386 TSS
+    pub const TSS_386_AVAILABLE: u8 = 0x9;
+
+    /// Busy 80386 TSS
+    pub const TSS_386_BUSY: u8 = 0xB;
+
+    /// 80386 call gate
+    pub const CALL_GATE_386: u8 = 0xC;
+
+    /// 80386 interrupt gate (clears IF)
+    pub const INTERRUPT_GATE_386: u8 = 0xE;
+
+    /// 80386 trap gate (leaves IF unchanged)
+    pub const TRAP_GATE_386: u8 = 0xF;
+}
+
+/// Field offsets in an 80286 Task State Segment (44 bytes)
+pub mod tss286 {
+    /// Selector of the previous task (nested tasks)
+    pub const BACK_LINK: u32 = 0x00;
+    /// SP for privilege level 0 (SS0 follows, then SP1/SS1, SP2/SS2)
+    pub const SP0: u32 = 0x02;
+    /// Saved IP
+    pub const IP: u32 = 0x0E;
+    /// Saved FLAGS
+    pub const FLAGS: u32 = 0x10;
+    /// AX, CX, DX, BX, SP, BP, SI, DI in that order
+    pub const AX: u32 = 0x12;
+    /// ES, CS, SS, DS in that order
+    pub const ES: u32 = 0x22;
+    /// LDT selector of the task
+    pub const LDT: u32 = 0x2A;
+    /// Minimum limit of a valid 80286 TSS
+    pub const MIN_LIMIT: u32 = 0x2B;
+}
+
+/// Field offsets in an 80386 Task State Segment (104 bytes)
+pub mod tss386 {
+    /// Selector of the previous task (nested tasks)
+    pub const BACK_LINK: u32 = 0x00;
+    /// ESP for privilege level 0 (SS0 follows, then ESP1/SS1, ESP2/SS2)
+    pub const ESP0: u32 = 0x04;
+    /// Page directory base of the task
+    pub const CR3: u32 = 0x1C;
+    /// Saved EIP
+    pub const EIP: u32 = 0x20;
+    /// Saved EFLAGS
+    pub const EFLAGS: u32 = 0x24;
+    /// EAX, ECX, EDX, EBX, ESP, EBP, ESI, EDI in that order
+    pub const EAX: u32 = 0x28;
+    /// ES, CS, SS, DS, FS, GS in that order (one dword each)
+    pub const ES: u32 = 0x48;
+    /// LDT selector of the task
+    pub const LDT: u32 = 0x60;
+    /// Minimum limit of a valid 80386 TSS
+    pub const MIN_LIMIT: u32 = 0x67;
+}
+
+/// Page directory and page table entry bits (80386+ paging)
+pub mod page_entry {
+    /// Present
+    pub const PRESENT: u32 = 0x001;
+    /// Writable (read-only if clear)
+    pub const WRITABLE: u32 = 0x002;
+    /// User accessible (supervisor only if clear)
+    pub const USER: u32 = 0x004;
+    /// Accessed
+    pub const ACCESSED: u32 = 0x020;
+    /// Dirty (page tables only)
+    pub const DIRTY: u32 = 0x040;
+    /// Physical address of the page or page table
+    pub const FRAME: u32 = 0xFFFF_F000;
 }
 
 /// Access Rights Byte flags
@@ -357,4 +779,35 @@ mod tests {
         let desc = SegmentDescriptor::from_bytes(&bytes);
         assert_eq!(desc.dpl(), 3);
     }
+
+    #[test]
+    fn test_segment_cache_limit_checks() {
+        let mut cache = SegmentCache {
+            selector: 0x10,
+            base: 0x20000,
+            limit: 0x00FF,
+            access: 0x92,
+            valid: true,
+            big: false,
+        };
+        assert!(cache.contains(0x00FE, 2));
+        assert!(!cache.contains(0x00FF, 2));
+
+        // Expand-down: valid offsets are above the limit
+        cache.access = 0x96;
+        assert!(!cache.contains(0x00FF, 1));
+        assert!(cache.contains(0x0100, 1));
+        assert!(cache.contains(0xFFFF, 1));
+    }
+
+    #[test]
+    fn test_gate_descriptor_from_bytes() {
+        let gate = GateDescriptor::from_bytes(&[0x00, 0x09, 0x08, 0x00, 0x02, 0xE4, 0, 0]);
+        assert_eq!(gate.offset, 0x0900);
+        assert_eq!(gate.selector, 0x0008);
+        assert_eq!(gate.word_count, 2);
+        assert_eq!(gate.dpl(), 3);
+        assert!(gate.is_present());
+        assert_eq!(gate.gate_type(), Some(system_types::CALL_GATE_286));
+    }
 }
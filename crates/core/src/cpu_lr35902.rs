@@ -23,17 +23,21 @@ pub trait MemoryLr35902 {
 /// Sharp LR35902 CPU state
 #[derive(Debug)]
 pub struct CpuLr35902<M: MemoryLr35902> {
-    /// Accumulator & Flags (combined as AF)
+    /// Accumulator (high byte of AF)
     pub a: u8,
+    /// Flags: Z N H C in bits 7-4 (low byte of AF)
     pub f: u8,
-    /// BC register pair
+    /// BC register pair (high byte)
     pub b: u8,
+    /// BC register pair (low byte)
     pub c: u8,
-    /// DE register pair
+    /// DE register pair (high byte)
     pub d: u8,
+    /// DE register pair (low byte)
     pub e: u8,
-    /// HL register pair
+    /// HL register pair (high byte)
     pub h: u8,
+    /// HL register pair (low byte)
     pub l: u8,
     /// Stack pointer
     pub sp: u16,
@@ -1380,6 +1380,7 @@ pub struct ArrayMemory {
 }
 
 impl ArrayMemory {
+    /// Create a zero-filled 8MB memory
     pub fn new() -> Self {
         Self {
             data: vec![0; 8 * 1024 * 1024], // 8MB
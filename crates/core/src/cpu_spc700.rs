@@ -0,0 +1,1203 @@
+//! Sony SPC700 CPU core (SNES sound CPU)
+//!
+//! The SPC700 runs the SNES audio program out of 64KB of its own RAM and
+//! talks to the main CPU through four I/O ports. It is an 8-bit CPU loosely
+//! modelled on the 6502, with a movable direct page, bit-addressed
+//! instructions and 16-bit YA arithmetic.
+//!
+//! For detailed CPU reference documentation, see: `docs/references/cpu_spc700.md`
+
+/// Memory interface trait for the SPC700 CPU
+pub trait MemorySpc700 {
+    /// Read a byte from memory
+    fn read(&self, addr: u16) -> u8;
+
+    /// Write a byte to memory
+    fn write(&mut self, addr: u16, val: u8);
+}
+
+/// Sony SPC700 CPU state
+#[derive(Debug)]
+pub struct CpuSpc700<M: MemorySpc700> {
+    /// Accumulator
+    pub a: u8,
+    /// X index register
+    pub x: u8,
+    /// Y index register (high byte of YA)
+    pub y: u8,
+    /// Stack pointer (the stack lives in page $01)
+    pub sp: u8,
+    /// Program counter
+    pub pc: u16,
+    /// Program status word: N V P B H I Z C in bits 7-0
+    pub psw: u8,
+    /// Stopped by SLEEP or STOP (only a reset restarts the CPU)
+    pub halted: bool,
+    /// Total cycles executed
+    pub cycles: u64,
+    /// Memory interface
+    pub memory: M,
+}
+
+// Flag bit positions (in PSW)
+const FLAG_N: u8 = 0x80; // Negative
+const FLAG_V: u8 = 0x40; // Overflow
+const FLAG_P: u8 = 0x20; // Direct page at $0100
+const FLAG_B: u8 = 0x10; // Break
+const FLAG_H: u8 = 0x08; // Half carry
+const FLAG_I: u8 = 0x04; // Interrupt enable (no interrupt sources on the SNES)
+const FLAG_Z: u8 = 0x02; // Zero
+const FLAG_C: u8 = 0x01; // Carry
+
+/// Base cycle count of every opcode. Taken branches add two cycles.
+#[rustfmt::skip]
+const CYCLES: [u8; 256] = [
+    2, 8, 4, 5, 3, 4, 3, 6, 2, 6, 5, 4, 5, 4, 6, 8, // 0x
+    2, 8, 4, 5, 4, 5, 5, 6, 5, 5, 6, 5, 2, 2, 4, 6, // 1x
+    2, 8, 4, 5, 3, 4, 3, 6, 2, 6, 5, 4, 5, 4, 5, 4, // 2x
+    2, 8, 4, 5, 4, 5, 5, 6, 5, 5, 6, 5, 2, 2, 3, 8, // 3x
+    2, 8, 4, 5, 3, 4, 3, 6, 2, 6, 4, 4, 5, 4, 6, 6, // 4x
+    2, 8, 4, 5, 4, 5, 5, 6, 5, 5, 4, 5, 2, 2, 4, 3, // 5x
+    2, 8, 4, 5, 3, 4, 3, 6, 2, 6, 4, 4, 5, 4, 5, 5, // 6x
+    2, 8, 4, 5, 4, 5, 5, 6, 5, 5, 5, 5, 2, 2, 3, 6, // 7x
+    2, 8, 4, 5, 3, 4, 3, 6, 2, 6, 5, 4, 5, 2, 4, 5, // 8x
+    2, 8, 4, 5, 4, 5, 5, 6, 5, 5, 5, 5, 2, 2, 12, 5, // 9x
+    3, 8, 4, 5, 3, 4, 3, 6, 2, 6, 4, 4, 5, 2, 4, 4, // Ax
+    2, 8, 4, 5, 4, 5, 5, 6, 5, 5, 5, 5, 2, 2, 3, 4, // Bx
+    3, 8, 4, 5, 4, 5, 4, 7, 2, 5, 6, 4, 5, 2, 4, 9, // Cx
+    2, 8, 4, 5, 5, 6, 6, 7, 4, 5, 5, 5, 2, 2, 6, 3, // Dx
+    2, 8, 4, 5, 3, 4, 3, 6, 2, 4, 5, 3, 4, 3, 4, 3, // Ex
+    2, 8, 4, 5, 4, 5, 5, 6, 3, 4, 5, 4, 2, 2, 4, 3, // Fx
+];
+
+impl<M: MemorySpc700> CpuSpc700<M> {
+    /// Create a new SPC700 CPU
+    pub fn new(memory: M) -> Self {
+        Self {
+            a: 0,
+            x: 0,
+            y: 0,
+            sp: 0,
+            pc: 0,
+            psw: 0,
+            halted: false,
+            cycles: 0,
+            memory,
+        }
+    }
+
+    /// Reset the CPU and jump through the reset vector at $FFFE
+    pub fn reset(&mut self) {
+        self.a = 0;
+        self.x = 0;
+        self.y = 0;
+        self.sp = 0xEF;
+        self.psw = FLAG_Z;
+        self.pc = self.read_word(0xFFFE);
+        self.halted = false;
+        self.cycles = 0;
+    }
+
+    /// Execute one instruction
+    pub fn step(&mut self) -> u32 {
+        if self.halted {
+            self.cycles += 2;
+            return 2;
+        }
+
+        let opcode = self.fetch();
+        let cycles = CYCLES[opcode as usize] as u32 + self.execute(opcode);
+        self.cycles += cycles as u64;
+        cycles
+    }
+
+    /// YA register pair
+    pub fn ya(&self) -> u16 {
+        (self.y as u16) << 8 | self.a as u16
+    }
+
+    fn set_ya(&mut self, val: u16) {
+        self.a = val as u8;
+        self.y = (val >> 8) as u8;
+    }
+
+    fn fetch(&mut self) -> u8 {
+        let val = self.memory.read(self.pc);
+        self.pc = self.pc.wrapping_add(1);
+        val
+    }
+
+    fn fetch_word(&mut self) -> u16 {
+        let lo = self.fetch() as u16;
+        let hi = self.fetch() as u16;
+        hi << 8 | lo
+    }
+
+    fn read_word(&self, addr: u16) -> u16 {
+        let lo = self.memory.read(addr) as u16;
+        let hi = self.memory.read(addr.wrapping_add(1)) as u16;
+        hi << 8 | lo
+    }
+
+    /// Address of a direct page offset ($00xx or $01xx, selected by P)
+    fn dp(&self, offset: u8) -> u16 {
+        if self.psw & FLAG_P != 0 {
+            0x0100 | offset as u16
+        } else {
+            offset as u16
+        }
+    }
+
+    fn read_dp(&self, offset: u8) -> u8 {
+        self.memory.read(self.dp(offset))
+    }
+
+    fn write_dp(&mut self, offset: u8, val: u8) {
+        self.memory.write(self.dp(offset), val);
+    }
+
+    /// 16-bit direct page word; the high byte wraps within the page
+    fn read_dp_word(&self, offset: u8) -> u16 {
+        let lo = self.read_dp(offset) as u16;
+        let hi = self.read_dp(offset.wrapping_add(1)) as u16;
+        hi << 8 | lo
+    }
+
+    fn write_dp_word(&mut self, offset: u8, val: u16) {
+        self.write_dp(offset, val as u8);
+        self.write_dp(offset.wrapping_add(1), (val >> 8) as u8);
+    }
+
+    fn push(&mut self, val: u8) {
+        self.memory.write(0x0100 | self.sp as u16, val);
+        self.sp = self.sp.wrapping_sub(1);
+    }
+
+    fn pop(&mut self) -> u8 {
+        self.sp = self.sp.wrapping_add(1);
+        self.memory.read(0x0100 | self.sp as u16)
+    }
+
+    fn push_word(&mut self, val: u16) {
+        self.push((val >> 8) as u8);
+        self.push(val as u8);
+    }
+
+    fn pop_word(&mut self) -> u16 {
+        let lo = self.pop() as u16;
+        let hi = self.pop() as u16;
+        hi << 8 | lo
+    }
+
+    fn call(&mut self, addr: u16) {
+        self.push_word(self.pc);
+        self.pc = addr;
+    }
+
+    fn set_flag(&mut self, flag: u8, val: bool) {
+        if val {
+            self.psw |= flag;
+        } else {
+            self.psw &= !flag;
+        }
+    }
+
+    fn get_flag(&self, flag: u8) -> bool {
+        self.psw & flag != 0
+    }
+
+    fn set_nz(&mut self, val: u8) {
+        self.set_flag(FLAG_N, val & 0x80 != 0);
+        self.set_flag(FLAG_Z, val == 0);
+    }
+
+    fn set_nz16(&mut self, val: u16) {
+        self.set_flag(FLAG_N, val & 0x8000 != 0);
+        self.set_flag(FLAG_Z, val == 0);
+    }
+
+    /// Read the relative offset and branch if `cond` holds.
+    /// Returns the extra cycles of a taken branch.
+    fn branch(&mut self, cond: bool) -> u32 {
+        let rel = self.fetch() as i8;
+        if cond {
+            self.pc = self.pc.wrapping_add(rel as u16);
+            2
+        } else {
+            0
+        }
+    }
+
+    fn adc(&mut self, a: u8, b: u8) -> u8 {
+        let carry = self.get_flag(FLAG_C) as u16;
+        let result = a as u16 + b as u16 + carry;
+        let r = result as u8;
+        self.set_flag(FLAG_V, !(a ^ b) & (a ^ r) & 0x80 != 0);
+        self.set_flag(FLAG_H, (a & 0x0F) as u16 + (b & 0x0F) as u16 + carry > 0x0F);
+        self.set_flag(FLAG_C, result > 0xFF);
+        self.set_nz(r);
+        r
+    }
+
+    fn sbc(&mut self, a: u8, b: u8) -> u8 {
+        self.adc(a, !b)
+    }
+
+    fn compare(&mut self, a: u8, b: u8) {
+        self.set_flag(FLAG_C, a >= b);
+        self.set_nz(a.wrapping_sub(b));
+    }
+
+    /// One of the ALU operations selected by bits 5-7 of the opcode: OR, AND, EOR, CMP, ADC, SBC. CMP returns `a` unchanged.
+    fn alu(&mut self, op: u8, a: u8, b: u8) -> u8 {
+        let r = match op {
+            0 => a | b,
+            1 => a & b,
+            2 => a ^ b,
+            3 => {
+                self.compare(a, b);
+                return a;
+            }
+            4 => return self.adc(a, b),
+            _ => return self.sbc(a, b),
+        };
+        self.set_nz(r);
+        r
+    }
+
+    /// Shift/rotate selected by bits 5-6 of the opcode: ASL, ROL, LSR, ROR
+    fn shift(&mut self, op: u8, val: u8) -> u8 {
+        let carry_in = self.get_flag(FLAG_C) as u8;
+        let (r, carry_out) = match op {
+            0 => (val << 1, val & 0x80 != 0),
+            1 => (val << 1 | carry_in, val & 0x80 != 0),
+            2 => (val >> 1, val & 0x01 != 0),
+            _ => (val >> 1 | carry_in << 7, val & 0x01 != 0),
+        };
+        self.set_flag(FLAG_C, carry_out);
+        self.set_nz(r);
+        r
+    }
+
+    /// Operand address for the columns shared by the ALU and MOV A
+    /// instructions: opcode low nibble 4-7, with `indexed` set for the odd
+    /// high nibbles
+    fn operand_address(&mut self, column: u8, indexed: bool) -> u16 {
+        match (column, indexed) {
+            // d / d+X
+            (4, false) => {
+                let d = self.fetch();
+                self.dp(d)
+            }
+            (4, true) => {
+                let d = self.fetch().wrapping_add(self.x);
+                self.dp(d)
+            }
+            // !a / !a+X
+            (5, false) => self.fetch_word(),
+            (5, true) => self.fetch_word().wrapping_add(self.x as u16),
+            // (X) / !a+Y
+            (6, false) => self.dp(self.x),
+            (6, true) => self.fetch_word().wrapping_add(self.y as u16),
+            // [d+X] / [d]+Y
+            (7, false) => {
+                let d = self.fetch().wrapping_add(self.x);
+                self.read_dp_word(d)
+            }
+            _ => {
+                let d = self.fetch();
+                self.read_dp_word(d).wrapping_add(self.y as u16)
+            }
+        }
+    }
+
+    /// Operand of the single-bit instructions: a 13-bit address and a bit
+    /// number in the top three bits
+    fn fetch_mem_bit(&mut self) -> (u16, u8) {
+        let word = self.fetch_word();
+        (word & 0x1FFF, (word >> 13) as u8)
+    }
+
+    fn mem_bit(&self, addr: u16, bit: u8) -> bool {
+        self.memory.read(addr) & (1 << bit) != 0
+    }
+
+    /// Execute an opcode and return the extra cycles of taken branches
+    fn execute(&mut self, opcode: u8) -> u32 {
+        let hi = opcode >> 4;
+        let lo = opcode & 0x0F;
+
+        // TCALL n: call through the vector table below $FFDE
+        if lo == 0x01 {
+            let vector = 0xFFDE - 2 * hi as u16;
+            let addr = self.read_word(vector);
+            self.call(addr);
+            return 0;
+        }
+
+        // SET1 / CLR1 d.bit
+        if lo == 0x02 {
+            let d = self.fetch();
+            let mask = 1 << (hi >> 1);
+            let val = self.read_dp(d);
+            let val = if hi & 1 == 0 { val | mask } else { val & !mask };
+            self.write_dp(d, val);
+            return 0;
+        }
+
+        // BBS / BBC d.bit, r
+        if lo == 0x03 {
+            let d = self.fetch();
+            let set = self.read_dp(d) & (1 << (hi >> 1)) != 0;
+            return self.branch(set == (hi & 1 == 0));
+        }
+
+        // OR, AND, EOR, CMP, ADC, SBC with A and the shared addressing modes
+        if hi < 0xC && (0x4..=0x7).contains(&lo) {
+            let addr = self.operand_address(lo, hi & 1 != 0);
+            let val = self.memory.read(addr);
+            self.a = self.alu(hi >> 1, self.a, val);
+            return 0;
+        }
+
+        // MOV A to memory (Cx, Dx) and memory to A (Ex, Fx)
+        if hi >= 0xC && (0x4..=0x7).contains(&lo) {
+            let addr = self.operand_address(lo, hi & 1 != 0);
+            if hi < 0xE {
+                self.memory.write(addr, self.a);
+            } else {
+                self.a = self.memory.read(addr);
+                self.set_nz(self.a);
+            }
+            return 0;
+        }
+
+        if hi < 0xC && (lo == 0x8 || lo == 0x9) {
+            let op = hi >> 1;
+            match (lo, hi & 1 != 0) {
+                // A, #i
+                (0x8, false) => {
+                    let val = self.fetch();
+                    self.a = self.alu(op, self.a, val);
+                }
+                // d, #i
+                (0x8, true) => {
+                    let val = self.fetch();
+                    let d = self.fetch();
+                    let r = self.alu(op, self.read_dp(d), val);
+                    if op != 3 {
+                        self.write_dp(d, r);
+                    }
+                }
+                // dd, ds
+                (_, false) => {
+                    let s = self.fetch();
+                    let d = self.fetch();
+                    let val = self.read_dp(s);
+                    let r = self.alu(op, self.read_dp(d), val);
+                    if op != 3 {
+                        self.write_dp(d, r);
+                    }
+                }
+                // (X), (Y)
+                _ => {
+                    let val = self.read_dp(self.y);
+                    let r = self.alu(op, self.read_dp(self.x), val);
+                    if op != 3 {
+                        self.write_dp(self.x, r);
+                    }
+                }
+            }
+            return 0;
+        }
+
+        // ASL, ROL, LSR, ROR on d, d+X, !a and A
+        if hi < 0x8 && (lo == 0xB || lo == 0xC) {
+            let op = hi >> 1;
+            match (lo, hi & 1 != 0) {
+                (0xB, indexed) => {
+                    let d = self.fetch();
+                    let d = if indexed { d.wrapping_add(self.x) } else { d };
+                    let r = self.shift(op, self.read_dp(d));
+                    self.write_dp(d, r);
+                }
+                (_, false) => {
+                    let addr = self.fetch_word();
+                    let r = self.shift(op, self.memory.read(addr));
+                    self.memory.write(addr, r);
+                }
+                _ => self.a = self.shift(op, self.a),
+            }
+            return 0;
+        }
+
+        match opcode {
+            0x00 => {} // NOP
+
+            // Conditional branches
+            0x10 => return self.branch(!self.get_flag(FLAG_N)), // BPL
+            0x30 => return self.branch(self.get_flag(FLAG_N)),  // BMI
+            0x50 => return self.branch(!self.get_flag(FLAG_V)), // BVC
+            0x70 => return self.branch(self.get_flag(FLAG_V)),  // BVS
+            0x90 => return self.branch(!self.get_flag(FLAG_C)), // BCC
+            0xB0 => return self.branch(self.get_flag(FLAG_C)),  // BCS
+            0xD0 => return self.branch(!self.get_flag(FLAG_Z)), // BNE
+            0xF0 => return self.branch(self.get_flag(FLAG_Z)),  // BEQ
+            0x2F => {
+                // BRA (base cycles already include the jump)
+                self.branch(true);
+            }
+
+            // Flag instructions
+            0x20 => self.psw &= !FLAG_P,            // CLRP
+            0x40 => self.psw |= FLAG_P,             // SETP
+            0x60 => self.psw &= !FLAG_C,            // CLRC
+            0x80 => self.psw |= FLAG_C,             // SETC
+            0xA0 => self.psw |= FLAG_I,             // EI
+            0xC0 => self.psw &= !FLAG_I,            // DI
+            0xE0 => self.psw &= !(FLAG_V | FLAG_H), // CLRV
+            0xED => self.psw ^= FLAG_C,             // NOTC
+
+            // Single-bit carry operations
+            0x0A => {
+                // OR1 C, m.b
+                let (addr, bit) = self.fetch_mem_bit();
+                let c = self.get_flag(FLAG_C) | self.mem_bit(addr, bit);
+                self.set_flag(FLAG_C, c);
+            }
+            0x2A => {
+                // OR1 C, /m.b
+                let (addr, bit) = self.fetch_mem_bit();
+                let c = self.get_flag(FLAG_C) | !self.mem_bit(addr, bit);
+                self.set_flag(FLAG_C, c);
+            }
+            0x4A => {
+                // AND1 C, m.b
+                let (addr, bit) = self.fetch_mem_bit();
+                let c = self.get_flag(FLAG_C) & self.mem_bit(addr, bit);
+                self.set_flag(FLAG_C, c);
+            }
+            0x6A => {
+                // AND1 C, /m.b
+                let (addr, bit) = self.fetch_mem_bit();
+                let c = self.get_flag(FLAG_C) & !self.mem_bit(addr, bit);
+                self.set_flag(FLAG_C, c);
+            }
+            0x8A => {
+                // EOR1 C, m.b
+                let (addr, bit) = self.fetch_mem_bit();
+                let c = self.get_flag(FLAG_C) ^ self.mem_bit(addr, bit);
+                self.set_flag(FLAG_C, c);
+            }
+            0xAA => {
+                // MOV1 C, m.b
+                let (addr, bit) = self.fetch_mem_bit();
+                let c = self.mem_bit(addr, bit);
+                self.set_flag(FLAG_C, c);
+            }
+            0xCA => {
+                // MOV1 m.b, C
+                let (addr, bit) = self.fetch_mem_bit();
+                let val = self.memory.read(addr) & !(1 << bit);
+                let c = self.get_flag(FLAG_C) as u8;
+                self.memory.write(addr, val | c << bit);
+            }
+            0xEA => {
+                // NOT1 m.b
+                let (addr, bit) = self.fetch_mem_bit();
+                let val = self.memory.read(addr) ^ (1 << bit);
+                self.memory.write(addr, val);
+            }
+
+            // 16-bit operations
+            0x1A | 0x3A => {
+                // DECW / INCW d
+                let d = self.fetch();
+                let val = self.read_dp_word(d);
+                let r = if opcode == 0x1A {
+                    val.wrapping_sub(1)
+                } else {
+                    val.wrapping_add(1)
+                };
+                self.write_dp_word(d, r);
+                self.set_nz16(r);
+            }
+            0x5A => {
+                // CMPW YA, d
+                let d = self.fetch();
+                let val = self.read_dp_word(d);
+                let ya = self.ya();
+                self.set_flag(FLAG_C, ya >= val);
+                self.set_nz16(ya.wrapping_sub(val));
+            }
+            0x7A => {
+                // ADDW YA, d
+                let d = self.fetch();
+                let val = self.read_dp_word(d);
+                let ya = self.ya();
+                let result = ya as u32 + val as u32;
+                let r = result as u16;
+                self.set_flag(FLAG_C, result > 0xFFFF);
+                self.set_flag(FLAG_H, (ya & 0x0FFF) + (val & 0x0FFF) > 0x0FFF);
+                self.set_flag(FLAG_V, !(ya ^ val) & (ya ^ r) & 0x8000 != 0);
+                self.set_ya(r);
+                self.set_nz16(r);
+            }
+            0x9A => {
+                // SUBW YA, d
+                let d = self.fetch();
+                let val = self.read_dp_word(d);
+                let ya = self.ya();
+                let r = ya.wrapping_sub(val);
+                self.set_flag(FLAG_C, ya >= val);
+                self.set_flag(FLAG_H, (ya & 0x0FFF) >= (val & 0x0FFF));
+                self.set_flag(FLAG_V, (ya ^ val) & (ya ^ r) & 0x8000 != 0);
+                self.set_ya(r);
+                self.set_nz16(r);
+            }
+            0xBA => {
+                // MOVW YA, d
+                let d = self.fetch();
+                let val = self.read_dp_word(d);
+                self.set_ya(val);
+                self.set_nz16(val);
+            }
+            0xDA => {
+                // MOVW d, YA
+                let d = self.fetch();
+                self.write_dp_word(d, self.ya());
+            }
+
+            // Register moves and immediate loads
+            0x5D => {
+                self.x = self.a; // MOV X, A
+                self.set_nz(self.x);
+            }
+            0x7D => {
+                self.a = self.x; // MOV A, X
+                self.set_nz(self.a);
+            }
+            0xDD => {
+                self.a = self.y; // MOV A, Y
+                self.set_nz(self.a);
+            }
+            0xFD => {
+                self.y = self.a; // MOV Y, A
+                self.set_nz(self.y);
+            }
+            0x9D => {
+                self.x = self.sp; // MOV X, SP
+                self.set_nz(self.x);
+            }
+            0xBD => self.sp = self.x, // MOV SP, X
+            0xE8 => {
+                self.a = self.fetch(); // MOV A, #i
+                self.set_nz(self.a);
+            }
+            0xCD => {
+                self.x = self.fetch(); // MOV X, #i
+                self.set_nz(self.x);
+            }
+            0x8D => {
+                self.y = self.fetch(); // MOV Y, #i
+                self.set_nz(self.y);
+            }
+
+            // X and Y loads and stores
+            0xF8 | 0xF9 | 0xE9 => {
+                // MOV X, d / d+Y / !a
+                let addr = match opcode {
+                    0xF8 => {
+                        let d = self.fetch();
+                        self.dp(d)
+                    }
+                    0xF9 => {
+                        let d = self.fetch().wrapping_add(self.y);
+                        self.dp(d)
+                    }
+                    _ => self.fetch_word(),
+                };
+                self.x = self.memory.read(addr);
+                self.set_nz(self.x);
+            }
+            0xEB | 0xFB | 0xEC => {
+                // MOV Y, d / d+X / !a
+                let addr = match opcode {
+                    0xEB => {
+                        let d = self.fetch();
+                        self.dp(d)
+                    }
+                    0xFB => {
+                        let d = self.fetch().wrapping_add(self.x);
+                        self.dp(d)
+                    }
+                    _ => self.fetch_word(),
+                };
+                self.y = self.memory.read(addr);
+                self.set_nz(self.y);
+            }
+            0xD8 => {
+                let d = self.fetch(); // MOV d, X
+                self.write_dp(d, self.x);
+            }
+            0xD9 => {
+                let d = self.fetch().wrapping_add(self.y); // MOV d+Y, X
+                self.write_dp(d, self.x);
+            }
+            0xC9 => {
+                let addr = self.fetch_word(); // MOV !a, X
+                self.memory.write(addr, self.x);
+            }
+            0xCB => {
+                let d = self.fetch(); // MOV d, Y
+                self.write_dp(d, self.y);
+            }
+            0xDB => {
+                let d = self.fetch().wrapping_add(self.x); // MOV d+X, Y
+                self.write_dp(d, self.y);
+            }
+            0xCC => {
+                let addr = self.fetch_word(); // MOV !a, Y
+                self.memory.write(addr, self.y);
+            }
+            0x8F => {
+                // MOV d, #i
+                let val = self.fetch();
+                let d = self.fetch();
+                self.write_dp(d, val);
+            }
+            0xFA => {
+                // MOV dd, ds
+                let s = self.fetch();
+                let d = self.fetch();
+                let val = self.read_dp(s);
+                self.write_dp(d, val);
+            }
+            0xAF => {
+                // MOV (X)+, A
+                self.write_dp(self.x, self.a);
+                self.x = self.x.wrapping_add(1);
+            }
+            0xBF => {
+                // MOV A, (X)+
+                self.a = self.read_dp(self.x);
+                self.x = self.x.wrapping_add(1);
+                self.set_nz(self.a);
+            }
+
+            // X and Y compares
+            0xC8 => {
+                let val = self.fetch(); // CMP X, #i
+                self.compare(self.x, val);
+            }
+            0x3E => {
+                let d = self.fetch(); // CMP X, d
+                self.compare(self.x, self.read_dp(d));
+            }
+            0x1E => {
+                let addr = self.fetch_word(); // CMP X, !a
+                self.compare(self.x, self.memory.read(addr));
+            }
+            0xAD => {
+                let val = self.fetch(); // CMP Y, #i
+                self.compare(self.y, val);
+            }
+            0x7E => {
+                let d = self.fetch(); // CMP Y, d
+                self.compare(self.y, self.read_dp(d));
+            }
+            0x5E => {
+                let addr = self.fetch_word(); // CMP Y, !a
+                self.compare(self.y, self.memory.read(addr));
+            }
+
+            // Increments and decrements
+            0x8B | 0x9B | 0xAB | 0xBB => {
+                // DEC / INC d, d+X
+                let d = self.fetch();
+                let d = if hi & 1 != 0 {
+                    d.wrapping_add(self.x)
+                } else {
+                    d
+                };
+                let val = self.read_dp(d);
+                let r = if opcode < 0xA0 {
+                    val.wrapping_sub(1)
+                } else {
+                    val.wrapping_add(1)
+                };
+                self.write_dp(d, r);
+                self.set_nz(r);
+            }
+            0x8C | 0xAC => {
+                // DEC / INC !a
+                let addr = self.fetch_word();
+                let val = self.memory.read(addr);
+                let r = if opcode == 0x8C {
+                    val.wrapping_sub(1)
+                } else {
+                    val.wrapping_add(1)
+                };
+                self.memory.write(addr, r);
+                self.set_nz(r);
+            }
+            0x9C => {
+                self.a = self.a.wrapping_sub(1); // DEC A
+                self.set_nz(self.a);
+            }
+            0xBC => {
+                self.a = self.a.wrapping_add(1); // INC A
+                self.set_nz(self.a);
+            }
+            0x1D => {
+                self.x = self.x.wrapping_sub(1); // DEC X
+                self.set_nz(self.x);
+            }
+            0x3D => {
+                self.x = self.x.wrapping_add(1); // INC X
+                self.set_nz(self.x);
+            }
+            0xDC => {
+                self.y = self.y.wrapping_sub(1); // DEC Y
+                self.set_nz(self.y);
+            }
+            0xFC => {
+                self.y = self.y.wrapping_add(1); // INC Y
+                self.set_nz(self.y);
+            }
+
+            // Test-and-set / test-and-clear
+            0x0E | 0x4E => {
+                // TSET1 / TCLR1 !a
+                let addr = self.fetch_word();
+                let val = self.memory.read(addr);
+                self.set_nz(self.a.wrapping_sub(val));
+                let r = if opcode == 0x0E {
+                    val | self.a
+                } else {
+                    val & !self.a
+                };
+                self.memory.write(addr, r);
+            }
+
+            // Compare-and-branch and decrement-and-branch
+            0x2E | 0xDE => {
+                // CBNE d, r / CBNE d+X, r
+                let d = self.fetch();
+                let d = if opcode == 0xDE {
+                    d.wrapping_add(self.x)
+                } else {
+                    d
+                };
+                let val = self.read_dp(d);
+                return self.branch(self.a != val);
+            }
+            0x6E => {
+                // DBNZ d, r
+                let d = self.fetch();
+                let r = self.read_dp(d).wrapping_sub(1);
+                self.write_dp(d, r);
+                return self.branch(r != 0);
+            }
+            0xFE => {
+                // DBNZ Y, r
+                self.y = self.y.wrapping_sub(1);
+                return self.branch(self.y != 0);
+            }
+
+            // Stack
+            0x0D => self.push(self.psw),   // PUSH PSW
+            0x2D => self.push(self.a),     // PUSH A
+            0x4D => self.push(self.x),     // PUSH X
+            0x6D => self.push(self.y),     // PUSH Y
+            0x8E => self.psw = self.pop(), // POP PSW
+            0xAE => self.a = self.pop(),   // POP A
+            0xCE => self.x = self.pop(),   // POP X
+            0xEE => self.y = self.pop(),   // POP Y
+
+            // Jumps, calls and returns
+            0x5F => self.pc = self.fetch_word(), // JMP !a
+            0x1F => {
+                // JMP [!a+X]
+                let addr = self.fetch_word().wrapping_add(self.x as u16);
+                self.pc = self.read_word(addr);
+            }
+            0x3F => {
+                // CALL !a
+                let addr = self.fetch_word();
+                self.call(addr);
+            }
+            0x4F => {
+                // PCALL u
+                let addr = 0xFF00 | self.fetch() as u16;
+                self.call(addr);
+            }
+            0x0F => {
+                // BRK
+                self.push_word(self.pc);
+                self.push(self.psw);
+                self.psw = (self.psw | FLAG_B) & !FLAG_I;
+                self.pc = self.read_word(0xFFDE);
+            }
+            0x6F => self.pc = self.pop_word(), // RET
+            0x7F => {
+                // RETI
+                self.psw = self.pop();
+                self.pc = self.pop_word();
+            }
+
+            // Multiply, divide and decimal adjust
+            0xCF => {
+                // MUL YA
+                let r = self.y as u16 * self.a as u16;
+                self.set_ya(r);
+                self.set_nz(self.y);
+            }
+            0x9E => {
+                // DIV YA, X (with the hardware's result for overflowing
+                // quotients, which are computed against 256 - X)
+                let ya = self.ya() as u32;
+                let x = self.x as u32;
+                self.set_flag(FLAG_V, self.y as u32 >= x);
+                self.set_flag(FLAG_H, (self.y & 0x0F) >= (self.x & 0x0F));
+                if (self.y as u32) < x << 1 {
+                    self.a = (ya / x) as u8;
+                    self.y = (ya % x) as u8;
+                } else {
+                    self.a = (255 - (ya - (x << 9)) / (256 - x)) as u8;
+                    self.y = (x + (ya - (x << 9)) % (256 - x)) as u8;
+                }
+                self.set_nz(self.a);
+            }
+            0xDF => {
+                // DAA A
+                if self.get_flag(FLAG_C) || self.a > 0x99 {
+                    self.a = self.a.wrapping_add(0x60);
+                    self.psw |= FLAG_C;
+                }
+                if self.get_flag(FLAG_H) || self.a & 0x0F > 0x09 {
+                    self.a = self.a.wrapping_add(0x06);
+                }
+                self.set_nz(self.a);
+            }
+            0xBE => {
+                // DAS A
+                if !self.get_flag(FLAG_C) || self.a > 0x99 {
+                    self.a = self.a.wrapping_sub(0x60);
+                    self.psw &= !FLAG_C;
+                }
+                if !self.get_flag(FLAG_H) || self.a & 0x0F > 0x09 {
+                    self.a = self.a.wrapping_sub(0x06);
+                }
+                self.set_nz(self.a);
+            }
+            0x9F => {
+                // XCN A
+                self.a = self.a.rotate_left(4);
+                self.set_nz(self.a);
+            }
+
+            // SLEEP / STOP
+            0xEF | 0xFF => self.halted = true,
+
+            // Every other opcode was decoded by the column groups above
+            _ => unreachable!("SPC700 opcode {opcode:#04X} not decoded"),
+        }
+
+        0
+    }
+}
+
+impl<M: MemorySpc700> crate::Cpu for CpuSpc700<M> {
+    fn reset(&mut self) {
+        self.reset();
+    }
+
+    fn step(&mut self) -> u32 {
+        self.step()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct ArrayMemory(Vec<u8>);
+
+    impl MemorySpc700 for ArrayMemory {
+        fn read(&self, addr: u16) -> u8 {
+            self.0[addr as usize]
+        }
+
+        fn write(&mut self, addr: u16, val: u8) {
+            self.0[addr as usize] = val;
+        }
+    }
+
+    /// CPU with `program` at $0200 and PC pointing at it
+    fn make_cpu(program: &[u8]) -> CpuSpc700<ArrayMemory> {
+        let mut mem = vec![0; 0x10000];
+        mem[0x0200..0x0200 + program.len()].copy_from_slice(program);
+        mem[0xFFFE] = 0x00;
+        mem[0xFFFF] = 0x02;
+        let mut cpu = CpuSpc700::new(ArrayMemory(mem));
+        cpu.reset();
+        cpu
+    }
+
+    #[test]
+    fn test_reset_vector() {
+        let cpu = make_cpu(&[]);
+        assert_eq!(cpu.pc, 0x0200);
+        assert_eq!(cpu.sp, 0xEF);
+    }
+
+    #[test]
+    fn test_mov_immediate_and_flags() {
+        let mut cpu = make_cpu(&[0xE8, 0x80, 0xCD, 0x00, 0x8D, 0x7F]);
+        assert_eq!(cpu.step(), 2);
+        assert_eq!(cpu.a, 0x80);
+        assert!(cpu.get_flag(FLAG_N));
+        cpu.step();
+        assert_eq!(cpu.x, 0);
+        assert!(cpu.get_flag(FLAG_Z));
+        cpu.step();
+        assert_eq!(cpu.y, 0x7F);
+        assert!(!cpu.get_flag(FLAG_N) && !cpu.get_flag(FLAG_Z));
+    }
+
+    #[test]
+    fn test_direct_page_select() {
+        // MOV $10, #$AA ; SETP ; MOV $10, #$BB
+        let mut cpu = make_cpu(&[0x8F, 0xAA, 0x10, 0x40, 0x8F, 0xBB, 0x10]);
+        cpu.step();
+        cpu.step();
+        assert_eq!(cpu.step(), 5);
+        assert_eq!(cpu.memory.0[0x0010], 0xAA);
+        assert_eq!(cpu.memory.0[0x0110], 0xBB);
+    }
+
+    #[test]
+    fn test_adc_sbc() {
+        // MOV A, #$7F ; CLRC ; ADC A, #$01 ; SETC ; SBC A, #$01
+        let mut cpu = make_cpu(&[0xE8, 0x7F, 0x60, 0x88, 0x01, 0x80, 0xA8, 0x01]);
+        cpu.step();
+        cpu.step();
+        cpu.step();
+        assert_eq!(cpu.a, 0x80);
+        assert!(cpu.get_flag(FLAG_V));
+        assert!(cpu.get_flag(FLAG_H));
+        assert!(!cpu.get_flag(FLAG_C));
+        cpu.step();
+        cpu.step();
+        assert_eq!(cpu.a, 0x7F);
+        assert!(cpu.get_flag(FLAG_V));
+        assert!(cpu.get_flag(FLAG_C));
+    }
+
+    #[test]
+    fn test_alu_memory_to_memory() {
+        // OR $21, $20 ; CMP (X), (Y)
+        let mut cpu = make_cpu(&[0x09, 0x20, 0x21, 0x79]);
+        cpu.memory.0[0x20] = 0x0F;
+        cpu.memory.0[0x21] = 0xF0;
+        assert_eq!(cpu.step(), 6);
+        assert_eq!(cpu.memory.0[0x21], 0xFF);
+        assert!(cpu.get_flag(FLAG_N));
+
+        cpu.x = 0x21;
+        cpu.y = 0x20;
+        cpu.step();
+        assert_eq!(cpu.memory.0[0x21], 0xFF);
+        assert!(cpu.get_flag(FLAG_C));
+    }
+
+    #[test]
+    fn test_indirect_addressing() {
+        // MOV A, [$10+X] ; MOV A, [$12]+Y
+        let mut cpu = make_cpu(&[0xE7, 0x0E, 0xF7, 0x12]);
+        cpu.x = 2;
+        cpu.y = 1;
+        cpu.memory.0[0x10] = 0x00;
+        cpu.memory.0[0x11] = 0x30;
+        cpu.memory.0[0x12] = 0x00;
+        cpu.memory.0[0x13] = 0x31;
+        cpu.memory.0[0x3000] = 0x11;
+        cpu.memory.0[0x3101] = 0x22;
+        assert_eq!(cpu.step(), 6);
+        assert_eq!(cpu.a, 0x11);
+        cpu.step();
+        assert_eq!(cpu.a, 0x22);
+    }
+
+    #[test]
+    fn test_branches() {
+        // MOV A, #0 ; BNE +2 ; BEQ +2 ; NOP ; NOP ; BRA -2
+        let mut cpu = make_cpu(&[0xE8, 0x00, 0xD0, 0x02, 0xF0, 0x02, 0x00, 0x00, 0x2F, 0xFE]);
+        cpu.step();
+        assert_eq!(cpu.step(), 2);
+        assert_eq!(cpu.pc, 0x0204);
+        assert_eq!(cpu.step(), 4);
+        assert_eq!(cpu.pc, 0x0208);
+        assert_eq!(cpu.step(), 4);
+        assert_eq!(cpu.pc, 0x0208);
+    }
+
+    #[test]
+    fn test_bit_branches_and_set_clear() {
+        // SET1 $20.3 ; BBS $20.3, +1 ; NOP ; CLR1 $20.3 ; BBC $20.3, -2
+        let mut cpu = make_cpu(&[
+            0x62, 0x20, 0x63, 0x20, 0x01, 0x00, 0x72, 0x20, 0x73, 0x20, 0xFE,
+        ]);
+        cpu.step();
+        assert_eq!(cpu.memory.0[0x20], 0x08);
+        assert_eq!(cpu.step(), 7);
+        assert_eq!(cpu.pc, 0x0206);
+        cpu.step();
+        assert_eq!(cpu.memory.0[0x20], 0x00);
+        assert_eq!(cpu.step(), 7);
+        assert_eq!(cpu.pc, 0x0209);
+    }
+
+    #[test]
+    fn test_cbne_and_dbnz() {
+        // CBNE $30, -3 ; DBNZ Y, -2
+        let mut cpu = make_cpu(&[0x2E, 0x30, 0xFD, 0xFE, 0xFE]);
+        cpu.a = 5;
+        cpu.memory.0[0x30] = 5;
+        assert_eq!(cpu.step(), 5);
+        assert_eq!(cpu.pc, 0x0203);
+        cpu.y = 2;
+        assert_eq!(cpu.step(), 6);
+        assert_eq!(cpu.y, 1);
+        assert_eq!(cpu.pc, 0x0203);
+        assert_eq!(cpu.step(), 4);
+        assert_eq!(cpu.y, 0);
+        assert_eq!(cpu.pc, 0x0205);
+    }
+
+    #[test]
+    fn test_word_operations() {
+        // MOVW YA, $40 ; ADDW YA, $42 ; MOVW $44, YA ; INCW $44
+        let mut cpu = make_cpu(&[0xBA, 0x40, 0x7A, 0x42, 0xDA, 0x44, 0x3A, 0x44]);
+        cpu.memory.0[0x40..0x44].copy_from_slice(&[0xFF, 0x0F, 0x01, 0x00]);
+        cpu.step();
+        assert_eq!(cpu.ya(), 0x0FFF);
+        cpu.step();
+        assert_eq!(cpu.ya(), 0x1000);
+        assert!(cpu.get_flag(FLAG_H));
+        assert!(!cpu.get_flag(FLAG_C));
+        cpu.step();
+        cpu.step();
+        assert_eq!(cpu.memory.0[0x44], 0x01);
+        assert_eq!(cpu.memory.0[0x45], 0x10);
+    }
+
+    #[test]
+    fn test_call_ret_and_stack() {
+        // CALL $0300 ; ... $0300: PUSH A ; POP X ; RET
+        let mut cpu = make_cpu(&[0x3F, 0x00, 0x03]);
+        cpu.memory.0[0x0300..0x0303].copy_from_slice(&[0x2D, 0xCE, 0x6F]);
+        cpu.a = 0x42;
+        assert_eq!(cpu.step(), 8);
+        assert_eq!(cpu.pc, 0x0300);
+        assert_eq!(cpu.memory.0[0x01EF], 0x02);
+        assert_eq!(cpu.memory.0[0x01EE], 0x03);
+        cpu.step();
+        cpu.step();
+        assert_eq!(cpu.x, 0x42);
+        cpu.step();
+        assert_eq!(cpu.pc, 0x0203);
+        assert_eq!(cpu.sp, 0xEF);
+    }
+
+    #[test]
+    fn test_tcall_and_pcall() {
+        // TCALL 0 ; (vector at $FFDE) ; PCALL $10
+        let mut cpu = make_cpu(&[0x01]);
+        cpu.memory.0[0xFFDE] = 0x00;
+        cpu.memory.0[0xFFDF] = 0x04;
+        cpu.memory.0[0x0400] = 0x4F;
+        cpu.memory.0[0x0401] = 0x10;
+        cpu.step();
+        assert_eq!(cpu.pc, 0x0400);
+        assert_eq!(cpu.step(), 6);
+        assert_eq!(cpu.pc, 0xFF10);
+    }
+
+    #[test]
+    fn test_mul_div() {
+        // MUL YA ; DIV YA, X
+        let mut cpu = make_cpu(&[0xCF, 0x9E, 0x9E]);
+        cpu.y = 0x12;
+        cpu.a = 0x34;
+        assert_eq!(cpu.step(), 9);
+        assert_eq!(cpu.ya(), 0x12 * 0x34);
+        cpu.x = 0x10;
+        assert_eq!(cpu.step(), 12);
+        assert_eq!(cpu.a, (0x12 * 0x34 / 0x10) as u8);
+        assert_eq!(cpu.y, (0x12 * 0x34 % 0x10) as u8);
+        assert!(!cpu.get_flag(FLAG_V));
+
+        // Quotient overflow sets V
+        cpu.set_ya(0x4000);
+        cpu.x = 0x20;
+        cpu.step();
+        assert!(cpu.get_flag(FLAG_V));
+    }
+
+    #[test]
+    fn test_mem_bit_operations() {
+        // SETC ; MOV1 $0123.5, C ; AND1 C, /$0123.5 ; NOT1 $0123.5
+        let mut cpu = make_cpu(&[0x80, 0xCA, 0x23, 0xA1, 0x6A, 0x23, 0xA1, 0xEA, 0x23, 0xA1]);
+        cpu.step();
+        cpu.step();
+        assert_eq!(cpu.memory.0[0x0123], 0x20);
+        cpu.step();
+        assert!(!cpu.get_flag(FLAG_C));
+        cpu.step();
+        assert_eq!(cpu.memory.0[0x0123], 0x00);
+    }
+
+    #[test]
+    fn test_shifts_and_xcn() {
+        // MOV A, #$81 ; ASL A ; ROR A ; XCN A
+        let mut cpu = make_cpu(&[0xE8, 0x81, 0x1C, 0x7C, 0x9F]);
+        cpu.step();
+        cpu.step();
+        assert_eq!(cpu.a, 0x02);
+        assert!(cpu.get_flag(FLAG_C));
+        cpu.step();
+        assert_eq!(cpu.a, 0x81);
+        assert!(!cpu.get_flag(FLAG_C));
+        assert_eq!(cpu.step(), 5);
+        assert_eq!(cpu.a, 0x18);
+    }
+
+    #[test]
+    fn test_daa() {
+        // MOV A, #$19 ; CLRC ; ADC A, #$28 ; DAA
+        let mut cpu = make_cpu(&[0xE8, 0x19, 0x60, 0x88, 0x28, 0xDF]);
+        for _ in 0..4 {
+            cpu.step();
+        }
+        assert_eq!(cpu.a, 0x47);
+    }
+
+    #[test]
+    fn test_sleep_halts() {
+        let mut cpu = make_cpu(&[0xEF, 0x00]);
+        cpu.step();
+        assert!(cpu.halted);
+        cpu.step();
+        assert_eq!(cpu.pc, 0x0201);
+    }
+
+    #[test]
+    fn test_every_opcode_decodes() {
+        for opcode in 0..=0xFFu8 {
+            let mut cpu = make_cpu(&[opcode, 0x00, 0x00]);
+            cpu.step();
+        }
+    }
+}
@@ -28,46 +28,65 @@ pub trait MemoryZ80 {
 /// Zilog Z80 CPU state
 #[derive(Debug)]
 pub struct CpuZ80<M: MemoryZ80> {
-    /// Main registers
+    /// Accumulator
     pub a: u8,
+    /// Flags: S Z - H - P/V N C
     pub f: u8,
+    /// B register (high byte of BC)
     pub b: u8,
+    /// C register (low byte of BC)
     pub c: u8,
+    /// D register (high byte of DE)
     pub d: u8,
+    /// E register (low byte of DE)
     pub e: u8,
+    /// H register (high byte of HL)
     pub h: u8,
+    /// L register (low byte of HL)
     pub l: u8,
 
-    /// Shadow registers (Z80 specific)
+    /// Shadow accumulator (swapped by EX AF,AF')
     pub a_prime: u8,
+    /// Shadow flags (swapped by EX AF,AF')
     pub f_prime: u8,
+    /// Shadow B (swapped by EXX)
     pub b_prime: u8,
+    /// Shadow C (swapped by EXX)
     pub c_prime: u8,
+    /// Shadow D (swapped by EXX)
     pub d_prime: u8,
+    /// Shadow E (swapped by EXX)
     pub e_prime: u8,
+    /// Shadow H (swapped by EXX)
     pub h_prime: u8,
+    /// Shadow L (swapped by EXX)
     pub l_prime: u8,
 
-    /// Index registers (Z80 specific)
+    /// IX index register
     pub ix: u16,
+    /// IY index register
     pub iy: u16,
 
-    /// Special registers
-    pub i: u8, // Interrupt vector
-    pub r: u8, // Memory refresh
+    /// Interrupt vector base (high byte of the IM 2 table address)
+    pub i: u8,
+    /// Memory refresh counter
+    pub r: u8,
 
     /// Stack pointer
     pub sp: u16,
     /// Program counter
     pub pc: u16,
 
-    /// Interrupt flags
+    /// Interrupt enable flip-flop 1 (maskable interrupts accepted)
     pub iff1: bool,
+    /// Interrupt enable flip-flop 2 (copy of IFF1 saved during NMI)
     pub iff2: bool,
-    pub im: u8, // Interrupt mode (0, 1, or 2)
+    /// Interrupt mode (0, 1, or 2)
+    pub im: u8,
 
-    /// State
+    /// Halted by HALT until the next interrupt
     pub halted: bool,
+    /// Total cycles executed
     pub cycles: u64,
 
     /// Memory interface
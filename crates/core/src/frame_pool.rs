@@ -0,0 +1,125 @@
+//! Reuse of frame buffers across `step_frame` calls
+//!
+//! A system that builds a new [`Frame`] every frame allocates (and the
+//! frontend frees) a few hundred kilobytes per step. [`FramePool`] keeps the
+//! pixel buffers of frames the frontend hands back through
+//! [`System::recycle_frame`] and gives them out again, so a running system
+//! settles into reusing the same two or three buffers.
+//!
+//! [`System::recycle_frame`]: crate::System::recycle_frame
+
+use crate::types::Frame;
+
+/// Most spare buffers kept; the frontend holds at most a frame or two
+const MAX_SPARE_BUFFERS: usize = 4;
+
+/// Counts of where the frames of a [`FramePool`] came from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FramePoolStats {
+    /// Frames served from a recycled buffer
+    pub reused: u64,
+    /// Frames that needed a new allocation
+    pub allocated: u64,
+}
+
+impl FramePoolStats {
+    /// Share of frames served without allocating, from 0.0 to 1.0
+    pub fn reuse_ratio(&self) -> f64 {
+        let total = self.reused + self.allocated;
+        if total == 0 {
+            0.0
+        } else {
+            self.reused as f64 / total as f64
+        }
+    }
+}
+
+/// Pool of pixel buffers for building frames
+#[derive(Debug, Default)]
+pub struct FramePool {
+    spare: Vec<Vec<u32>>,
+    stats: FramePoolStats,
+}
+
+impl FramePool {
+    /// Create an empty pool
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get a frame of the given size with every pixel zeroed, reusing a spare
+    /// buffer when one is large enough
+    pub fn acquire(&mut self, width: u32, height: u32) -> Frame {
+        let len = (width * height) as usize;
+        let mut pixels = match self.spare.iter().position(|buf| buf.capacity() >= len) {
+            Some(index) => {
+                self.stats.reused += 1;
+                self.spare.swap_remove(index)
+            }
+            None => {
+                self.stats.allocated += 1;
+                Vec::with_capacity(len)
+            }
+        };
+        pixels.clear();
+        pixels.resize(len, 0);
+        Frame {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Return a frame's buffer to the pool
+    pub fn release(&mut self, frame: Frame) {
+        if self.spare.len() < MAX_SPARE_BUFFERS && frame.pixels.capacity() > 0 {
+            self.spare.push(frame.pixels);
+        }
+    }
+
+    /// How many frames were reused or allocated so far
+    pub fn stats(&self) -> FramePoolStats {
+        self.stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_released_buffers_are_reused() {
+        let mut pool = FramePool::new();
+        let mut frame = pool.acquire(4, 2);
+        assert_eq!(frame.pixels.len(), 8);
+        frame.pixels.fill(0xFFFF_FFFF);
+        pool.release(frame);
+
+        // Smaller frames fit in the old buffer and come back zeroed
+        let frame = pool.acquire(2, 2);
+        assert_eq!((frame.width, frame.height), (2, 2));
+        assert_eq!(frame.pixels, [0; 4]);
+        assert_eq!(
+            pool.stats(),
+            FramePoolStats {
+                reused: 1,
+                allocated: 1
+            }
+        );
+
+        // A larger frame needs a new buffer
+        pool.release(frame);
+        let _ = pool.acquire(8, 8);
+        assert_eq!(pool.stats().allocated, 2);
+        assert_eq!(pool.stats().reuse_ratio(), 1.0 / 3.0);
+    }
+
+    #[test]
+    fn test_spare_buffers_are_capped() {
+        let mut pool = FramePool::new();
+        for _ in 0..10 {
+            pool.release(Frame::new(2, 2));
+        }
+        assert_eq!(pool.spare.len(), MAX_SPARE_BUFFERS);
+    }
+}
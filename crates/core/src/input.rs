@@ -0,0 +1,196 @@
+//! Pluggable input sources
+//!
+//! An [`InputProvider`] hands out the input for each emulated frame, in the
+//! same [`MovieFrame`] form movies store. The frontend applies whatever its
+//! provider returns to the system's controller ports and keyboard, so where
+//! the input comes from is interchangeable: live keyboard and gamepads, a
+//! movie being played back ([`MoviePlayer`]), a [`ScriptedInput`] in tests,
+//! a netplay peer, or a closure driven by a scripting engine.
+//!
+//! ```
+//! use emu_core::input::{InputProvider, ScriptedInput};
+//!
+//! // Hold Start (bit 3) on port 0 for frames 10-11, then release it
+//! let mut script = ScriptedInput::new();
+//! script.set_controller(10, 0, 0x08);
+//! script.set_controller(12, 0, 0x00);
+//! script.end_at(20);
+//!
+//! let states: Vec<u16> = std::iter::from_fn(|| script.next_input())
+//!     .map(|input| input.controller(0))
+//!     .collect();
+//! assert_eq!(states.len(), 20);
+//! assert_eq!(states[9..13], [0x00, 0x08, 0x08, 0x00]);
+//! ```
+
+use crate::movie::{MovieFrame, MoviePlayer};
+
+/// Source of per-frame input
+pub trait InputProvider {
+    /// Input for the next frame, or None once the source has run out
+    fn next_input(&mut self) -> Option<MovieFrame>;
+}
+
+impl InputProvider for MoviePlayer {
+    fn next_input(&mut self) -> Option<MovieFrame> {
+        self.next_frame().cloned()
+    }
+}
+
+/// Any closure returning the next frame's input is a provider, which is how
+/// a scripting engine hooks in
+impl<F> InputProvider for F
+where
+    F: FnMut() -> Option<MovieFrame>,
+{
+    fn next_input(&mut self) -> Option<MovieFrame> {
+        self()
+    }
+}
+
+impl MovieFrame {
+    /// State of the controller in `port` (0 if the frame has none)
+    pub fn controller(&self, port: usize) -> u16 {
+        self.controllers.get(port).copied().unwrap_or(0)
+    }
+}
+
+/// One change made by a script
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ScriptEvent {
+    Controller { port: usize, state: u16 },
+    KeyPress(u32),
+    KeyRelease(u32),
+}
+
+/// Input scripted by frame number
+///
+/// A controller state set at some frame is held until the script changes it;
+/// key events happen only on the frame they are scheduled for. Frames are
+/// counted from 0, the first frame [`InputProvider::next_input`] returns.
+#[derive(Debug, Clone, Default)]
+pub struct ScriptedInput {
+    /// Events in the order they were added
+    events: Vec<(u64, ScriptEvent)>,
+    /// Frame after the last one returned, if the script ends
+    end: Option<u64>,
+    /// Next frame to return
+    frame: u64,
+    /// Controller states in effect
+    held: Vec<u16>,
+}
+
+impl ScriptedInput {
+    /// An empty script, holding every controller released forever
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the controller in `port` to `state` from `frame` on
+    pub fn set_controller(&mut self, frame: u64, port: usize, state: u16) {
+        self.events
+            .push((frame, ScriptEvent::Controller { port, state }));
+    }
+
+    /// Press a keyboard scancode on `frame`
+    pub fn press_key(&mut self, frame: u64, scancode: u32) {
+        self.events.push((frame, ScriptEvent::KeyPress(scancode)));
+    }
+
+    /// Release a keyboard scancode on `frame`
+    pub fn release_key(&mut self, frame: u64, scancode: u32) {
+        self.events.push((frame, ScriptEvent::KeyRelease(scancode)));
+    }
+
+    /// Stop providing input once `frame` frames have been returned
+    pub fn end_at(&mut self, frame: u64) {
+        self.end = Some(frame);
+    }
+
+    /// Frames returned so far
+    pub fn position(&self) -> u64 {
+        self.frame
+    }
+}
+
+impl InputProvider for ScriptedInput {
+    fn next_input(&mut self) -> Option<MovieFrame> {
+        if self.end.is_some_and(|end| self.frame >= end) {
+            return None;
+        }
+        let mut input = MovieFrame::default();
+        for &(_, event) in self.events.iter().filter(|(f, _)| *f == self.frame) {
+            match event {
+                ScriptEvent::Controller { port, state } => {
+                    if self.held.len() <= port {
+                        self.held.resize(port + 1, 0);
+                    }
+                    self.held[port] = state;
+                }
+                ScriptEvent::KeyPress(scancode) => input.key_presses.push(scancode),
+                ScriptEvent::KeyRelease(scancode) => input.key_releases.push(scancode),
+            }
+        }
+        input.controllers = self.held.clone();
+        self.frame += 1;
+        Some(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::movie::{InputMovie, MovieStart};
+
+    #[test]
+    fn test_scripted_input_holds_controllers_and_fires_keys_once() {
+        let mut script = ScriptedInput::new();
+        script.set_controller(1, 1, 0x81);
+        script.press_key(1, 30);
+        script.release_key(2, 30);
+        script.end_at(3);
+
+        let first = script.next_input().unwrap();
+        assert_eq!(first, MovieFrame::default());
+
+        let second = script.next_input().unwrap();
+        assert_eq!(second.controllers, vec![0x00, 0x81]);
+        assert_eq!(second.controller(1), 0x81);
+        assert_eq!(second.controller(3), 0x00);
+        assert_eq!(second.key_presses, vec![30]);
+
+        let third = script.next_input().unwrap();
+        assert_eq!(third.controllers, vec![0x00, 0x81]);
+        assert!(third.key_presses.is_empty());
+        assert_eq!(third.key_releases, vec![30]);
+
+        assert!(script.next_input().is_none());
+        assert_eq!(script.position(), 3);
+    }
+
+    #[test]
+    fn test_movie_player_and_closures_are_providers() {
+        let mut movie = InputMovie::new("nes", None, MovieStart::PowerOn);
+        movie.push_frame(MovieFrame {
+            controllers: vec![0x08],
+            ..Default::default()
+        });
+        let mut player = MoviePlayer::new(movie);
+        let mut counter = 0u16;
+        let mut closure = || {
+            counter += 1;
+            Some(MovieFrame {
+                controllers: vec![counter],
+                ..Default::default()
+            })
+        };
+
+        let providers: [&mut dyn InputProvider; 2] = [&mut player, &mut closure];
+        let states: Vec<_> = providers
+            .into_iter()
+            .map(|provider| provider.next_input().map(|input| input.controller(0)))
+            .collect();
+        assert_eq!(states, vec![Some(0x08), Some(1)]);
+        assert!(player.next_input().is_none());
+    }
+}
@@ -1,29 +1,206 @@
 //! Core emulator primitives and traits.
+//!
+//! `emu_core` holds everything the Hemulator systems share: the CPU cores,
+//! reusable audio and video building blocks, logging, save-state helpers and
+//! the [`System`] trait that frontends drive. Each system crate wraps one of
+//! the CPU cores with its own memory map and implements [`System`] on top.
+//!
+//! # Feature flags
+//!
+//! Every CPU core sits behind its own feature, all enabled by default. An
+//! embedder that needs a single core can depend on
+//! `emu_core = { version = "0.1", default-features = false, features = ["cpu-6502"] }`.
+//!
+//! | Feature           | Module                 | CPU                          |
+//! |-------------------|------------------------|------------------------------|
+//! | `cpu-6502`        | [`cpu_6502`]           | MOS 6502 (NES, Atari 2600)   |
+//! | `cpu-65c816`      | [`cpu_65c816`]         | WDC 65C816 (SNES)            |
+//! | `cpu-8080`        | [`cpu_8080`]           | Intel 8080                   |
+//! | `cpu-8086`        | [`cpu_8086`], [`cpu_8086_protected`] | Intel 8086-80486 (PC) |
+//! | `cpu-lr35902`     | [`cpu_lr35902`]        | Sharp LR35902 (Game Boy)     |
+//! | `cpu-mips-r4300i` | [`cpu_mips_r4300i`]    | NEC VR4300 (N64)             |
+//! | `cpu-spc700`      | [`cpu_spc700`]         | Sony SPC700 (SNES audio)     |
+//! | `cpu-z80`         | [`cpu_z80`]            | Zilog Z80                    |
+//!
+//! The remaining modules are always available.
+//!
+//! # Stability
+//!
+//! The crate follows semantic versioning. Within a minor series the public
+//! API (the [`System`] and [`Cpu`] traits, [`types`], the `Memory*` traits
+//! of each CPU core and the public CPU register fields) only grows; removals
+//! and signature changes wait for the next breaking release and are listed in
+//! `CHANGELOG.md`. CI runs `cargo semver-checks` against the last release to
+//! catch accidental breaks.
+//!
+//! # Embedding a system
+//!
+//! A system owns a CPU core and its memory map, and turns CPU cycles into
+//! frames:
+//!
+//! ```
+//! # #[cfg(feature = "cpu-6502")]
+//! # {
+//! use emu_core::cpu_6502::{Cpu6502, Memory6502};
+//! use emu_core::types::Frame;
+//! use emu_core::{MountPointInfo, System};
+//!
+//! /// 32KB of RAM followed by a 32KB cartridge
+//! struct Bus {
+//!     ram: Vec<u8>,
+//!     rom: Vec<u8>,
+//! }
+//!
+//! impl Memory6502 for Bus {
+//!     fn read(&self, addr: u16) -> u8 {
+//!         match addr {
+//!             0x0000..=0x7FFF => self.ram[addr as usize],
+//!             _ => self.rom.get(addr as usize - 0x8000).copied().unwrap_or(0xFF),
+//!         }
+//!     }
+//!
+//!     fn write(&mut self, addr: u16, val: u8) {
+//!         if addr < 0x8000 {
+//!             self.ram[addr as usize] = val;
+//!         }
+//!     }
+//! }
+//!
+//! #[derive(Debug, thiserror::Error)]
+//! #[error("no such mount point: {0}")]
+//! struct UnknownMount(String);
+//!
+//! struct TinySystem {
+//!     cpu: Cpu6502<Bus>,
+//! }
+//!
+//! impl System for TinySystem {
+//!     type Error = UnknownMount;
+//!
+//!     fn reset(&mut self) {
+//!         self.cpu.reset();
+//!     }
+//!
+//!     fn step_frame(&mut self) -> Result<Frame, Self::Error> {
+//!         // One 60 Hz frame of a 1 MHz CPU
+//!         let mut cycles = 0;
+//!         while cycles < 16_667 {
+//!             cycles += self.cpu.step();
+//!         }
+//!         // Show the first 256 bytes of RAM as a 16x16 greyscale image
+//!         let mut frame = Frame::new(16, 16);
+//!         for (pixel, &byte) in frame.pixels.iter_mut().zip(&self.cpu.memory.ram) {
+//!             *pixel = 0xFF00_0000 | u32::from(byte) * 0x01_0101;
+//!         }
+//!         Ok(frame)
+//!     }
+//!
+//!     fn save_state(&self) -> serde_json::Value {
+//!         serde_json::json!({ "pc": self.cpu.pc, "ram": self.cpu.memory.ram })
+//!     }
+//!
+//!     fn load_state(&mut self, v: &serde_json::Value) -> Result<(), serde_json::Error> {
+//!         self.cpu.pc = serde_json::from_value(v["pc"].clone())?;
+//!         self.cpu.memory.ram = serde_json::from_value(v["ram"].clone())?;
+//!         Ok(())
+//!     }
+//!
+//!     fn mount_points(&self) -> Vec<MountPointInfo> {
+//!         vec![MountPointInfo {
+//!             id: "Cartridge".to_string(),
+//!             name: "Cartridge Slot".to_string(),
+//!             extensions: vec!["bin".to_string()],
+//!             required: true,
+//!         }]
+//!     }
+//!
+//!     fn mount(&mut self, id: &str, data: &[u8]) -> Result<(), Self::Error> {
+//!         if id != "Cartridge" {
+//!             return Err(UnknownMount(id.to_string()));
+//!         }
+//!         self.cpu.memory.rom = data.to_vec();
+//!         self.reset();
+//!         Ok(())
+//!     }
+//!
+//!     fn unmount(&mut self, id: &str) -> Result<(), Self::Error> {
+//!         if id != "Cartridge" {
+//!             return Err(UnknownMount(id.to_string()));
+//!         }
+//!         self.cpu.memory.rom.clear();
+//!         Ok(())
+//!     }
+//!
+//!     fn is_mounted(&self, id: &str) -> bool {
+//!         id == "Cartridge" && !self.cpu.memory.rom.is_empty()
+//!     }
+//! }
+//!
+//! // INC $00 ; JMP $8000, with the reset vector pointing at $8000
+//! let mut rom = vec![0xEA; 0x8000];
+//! rom[..5].copy_from_slice(&[0xE6, 0x00, 0x4C, 0x00, 0x80]);
+//! rom[0x7FFC] = 0x00;
+//! rom[0x7FFD] = 0x80;
+//!
+//! let bus = Bus { ram: vec![0; 0x8000], rom: Vec::new() };
+//! let mut system = TinySystem { cpu: Cpu6502::new(bus) };
+//! system.mount("Cartridge", &rom).unwrap();
+//! let frame = system.step_frame().unwrap();
+//! assert_eq!((frame.width, frame.height), (16, 16));
+//! assert_ne!(system.cpu.memory.ram[0], 0);
+//! # }
+//! ```
+//!
+//! The system crates (`emu_nes`, `emu_gb`, `emu_pc` and so on) each show how
+//! to embed the real machines.
+
+#![warn(missing_docs)]
 
 pub mod apu;
+#[cfg(feature = "cpu-6502")]
 pub mod cpu_6502;
+#[cfg(feature = "cpu-65c816")]
 pub mod cpu_65c816;
+#[cfg(feature = "cpu-8080")]
 pub mod cpu_8080;
+#[cfg(feature = "cpu-8086")]
 pub mod cpu_8086;
+#[cfg(feature = "cpu-8086")]
 pub mod cpu_8086_protected;
+#[cfg(feature = "cpu-lr35902")]
 pub mod cpu_lr35902;
+#[cfg(feature = "cpu-mips-r4300i")]
 pub mod cpu_mips_r4300i;
+#[cfg(feature = "cpu-spc700")]
+pub mod cpu_spc700;
+#[cfg(feature = "cpu-z80")]
 pub mod cpu_z80;
+pub mod frame_pool;
 pub mod graphics;
+pub mod input;
 pub mod logging;
+pub mod movie;
 pub mod ppu;
 pub mod renderer;
+pub mod rewind;
+pub mod save_state;
+/// Data types exchanged between systems and frontends
 pub mod types {
     use serde::{Deserialize, Serialize};
 
+    /// A rendered video frame
     #[derive(Debug, Clone, Serialize, Deserialize)]
     pub struct Frame {
+        /// Width in pixels
         pub width: u32,
+        /// Height in pixels
         pub height: u32,
+        /// Pixels in row-major order, as 0xAARRGGBB
         pub pixels: Vec<u32>,
     }
 
     impl Frame {
+        /// Create a frame of the given size with every pixel zeroed
         pub fn new(width: u32, height: u32) -> Self {
             Self {
                 width,
@@ -33,6 +210,7 @@ pub mod types {
         }
     }
 
+    /// One signed 16-bit PCM audio sample
     pub type AudioSample = i16;
 }
 
@@ -40,7 +218,9 @@ use serde_json::Value;
 
 /// A CPU-like component that can be stepped; returns cycles consumed.
 pub trait Cpu {
+    /// Reset to the power-on state
     fn reset(&mut self);
+    /// Execute one instruction and return the cycles it took
     fn step(&mut self) -> u32;
 }
 
@@ -59,6 +239,7 @@ pub struct MountPointInfo {
 
 /// A high-level System trait tying components together.
 pub trait System {
+    /// Error returned by emulation and media operations
     type Error: std::error::Error + Send + Sync + 'static;
 
     /// Reset to initial power-on state
@@ -92,6 +273,27 @@ pub trait System {
 
     /// Check if a mount point has media loaded
     fn is_mounted(&self, mount_point_id: &str) -> bool;
+
+    /// Frames per second produced by `step_frame` in the current configuration
+    ///
+    /// Frontends pace emulation and budget audio samples per frame from this.
+    /// Systems with PAL modes or non-60 Hz video override it.
+    fn frame_rate(&self) -> f64 {
+        60.0
+    }
+
+    /// Hand back a frame returned by `step_frame` once it has been shown
+    ///
+    /// Systems that build frames from a [`frame_pool::FramePool`] reuse its
+    /// buffer for a later frame; the default just drops it.
+    fn recycle_frame(&mut self, frame: types::Frame) {
+        let _ = frame;
+    }
+
+    /// Reuse counts of the system's frame pool, if it has one
+    fn frame_pool_stats(&self) -> Option<frame_pool::FramePoolStats> {
+        None
+    }
 }
 
 #[cfg(test)]
@@ -146,6 +348,11 @@ mod tests {
         }
     }
 
+    #[test]
+    fn default_frame_rate_is_60hz() {
+        assert_eq!(MockSystem.frame_rate(), 60.0);
+    }
+
     #[test]
     fn mock_system_save_load_roundtrip() {
         let sys = MockSystem;
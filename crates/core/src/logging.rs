@@ -10,6 +10,9 @@
 //! - **LogLevel**: Hierarchical log levels (Off < Error < Warn < Info < Debug < Trace)
 //! - **LogCategory**: Different logging categories (CPU, Bus, PPU, APU, Interrupts, Stubs)
 //! - **log()**: Common logging function for all output with async file I/O
+//! - **History**: The most recent messages of each category are kept in an
+//!   in-memory ring buffer as [`LogRecord`]s (with a timestamp and level), so a
+//!   frontend can show them without reading stderr or the log file
 //!
 //! # Performance
 //!
@@ -29,12 +32,27 @@
 //!     format!("CPU: BRK at PC={:04X}", 0x1234)
 //! });
 //! ```
+//!
+//! Reading back recent messages:
+//!
+//! ```rust
+//! use emu_core::logging::{LogConfig, LogFilter, LogLevel};
+//!
+//! let filter = LogFilter {
+//!     max_level: LogLevel::Warn,
+//!     ..LogFilter::default()
+//! };
+//! for record in LogConfig::global().history(&filter) {
+//!     println!("{}", record);
+//! }
+//! ```
 
 use std::collections::VecDeque;
+use std::fmt;
 use std::fs::OpenOptions;
 use std::io::Write;
 use std::path::PathBuf;
-use std::sync::atomic::{AtomicBool, AtomicU8, AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, AtomicUsize, Ordering};
 use std::sync::mpsc::{channel, Sender};
 use std::sync::Mutex;
 use std::thread;
@@ -44,11 +62,17 @@ use std::time::{Duration, Instant};
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 #[repr(u8)]
 pub enum LogLevel {
+    /// Logging disabled
     Off = 0,
+    /// Failures the emulator cannot recover from
     Error = 1,
+    /// Unexpected but recoverable conditions
     Warn = 2,
+    /// Notable events
     Info = 3,
+    /// Detailed diagnostics
     Debug = 4,
+    /// Per-instruction or per-access tracing
     Trace = 5,
 }
 
@@ -84,6 +108,18 @@ impl LogLevel {
             _ => LogLevel::Off,
         }
     }
+
+    /// Short upper-case name used in formatted records
+    pub fn name(self) -> &'static str {
+        match self {
+            LogLevel::Off => "OFF",
+            LogLevel::Error => "ERROR",
+            LogLevel::Warn => "WARN",
+            LogLevel::Info => "INFO",
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Trace => "TRACE",
+        }
+    }
 }
 
 /// Log category for different emulator components
@@ -103,6 +139,117 @@ pub enum LogCategory {
     Stubs,
 }
 
+/// Number of log categories
+const CATEGORY_COUNT: usize = 6;
+
+impl LogCategory {
+    /// All categories, in display order
+    pub const ALL: [LogCategory; CATEGORY_COUNT] = [
+        LogCategory::CPU,
+        LogCategory::Bus,
+        LogCategory::PPU,
+        LogCategory::APU,
+        LogCategory::Interrupts,
+        LogCategory::Stubs,
+    ];
+
+    /// Display name of the category
+    pub fn name(self) -> &'static str {
+        match self {
+            LogCategory::CPU => "CPU",
+            LogCategory::Bus => "Bus",
+            LogCategory::PPU => "PPU",
+            LogCategory::APU => "APU",
+            LogCategory::Interrupts => "Interrupts",
+            LogCategory::Stubs => "Stubs",
+        }
+    }
+
+    /// Index for per-category arrays
+    fn index(self) -> usize {
+        match self {
+            LogCategory::CPU => 0,
+            LogCategory::Bus => 1,
+            LogCategory::PPU => 2,
+            LogCategory::APU => 3,
+            LogCategory::Interrupts => 4,
+            LogCategory::Stubs => 5,
+        }
+    }
+}
+
+/// A logged message kept in the in-memory history
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogRecord {
+    /// Position in the overall log (orders records across categories)
+    pub seq: u64,
+    /// Time since logging started
+    pub elapsed: Duration,
+    /// Category the message was logged under
+    pub category: LogCategory,
+    /// Severity of the message
+    pub level: LogLevel,
+    /// The formatted message
+    pub message: String,
+}
+
+impl fmt::Display for LogRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[{:>10.3}] {:<5} {}: {}",
+            self.elapsed.as_secs_f64(),
+            self.level.name(),
+            self.category.name(),
+            self.message
+        )
+    }
+}
+
+/// Selects records from the log history
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogFilter {
+    /// Categories to include (indexed like [`LogCategory::ALL`])
+    pub categories: [bool; CATEGORY_COUNT],
+    /// Least severe level to include
+    pub max_level: LogLevel,
+    /// Case-insensitive text the message must contain (empty matches all)
+    pub text: String,
+}
+
+impl Default for LogFilter {
+    fn default() -> Self {
+        Self {
+            categories: [true; CATEGORY_COUNT],
+            max_level: LogLevel::Trace,
+            text: String::new(),
+        }
+    }
+}
+
+impl LogFilter {
+    /// Whether a category is included
+    pub fn includes(&self, category: LogCategory) -> bool {
+        self.categories[category.index()]
+    }
+
+    /// Include or exclude a category
+    pub fn set_includes(&mut self, category: LogCategory, include: bool) {
+        self.categories[category.index()] = include;
+    }
+
+    /// Whether a record passes the filter
+    pub fn matches(&self, record: &LogRecord) -> bool {
+        self.includes(record.category)
+            && record.level <= self.max_level
+            && (self.text.is_empty()
+                || record
+                    .message
+                    .to_lowercase()
+                    .contains(&self.text.to_lowercase()))
+    }
+}
+
 /// Rate limiter for controlling log output frequency per category
 ///
 /// Uses a sliding window algorithm to track log timestamps and enforce
@@ -149,23 +296,11 @@ impl RateLimiter {
         self.max_logs_per_second.load(Ordering::Relaxed)
     }
 
-    /// Get the category index for array access
-    fn category_index(category: LogCategory) -> usize {
-        match category {
-            LogCategory::CPU => 0,
-            LogCategory::Bus => 1,
-            LogCategory::PPU => 2,
-            LogCategory::APU => 3,
-            LogCategory::Interrupts => 4,
-            LogCategory::Stubs => 5,
-        }
-    }
-
     /// Check if a log should be allowed based on rate limits
     /// Returns (allowed, dropped_count) where dropped_count is Some(n) if we should report drops
     fn should_allow(&self, category: LogCategory) -> (bool, Option<usize>) {
         let now = Instant::now();
-        let idx = Self::category_index(category);
+        let idx = category.index();
 
         let mut timestamps = self.timestamps.lock().unwrap();
         let mut dropped_counts = self.dropped_counts.lock().unwrap();
@@ -239,8 +374,19 @@ pub struct LogConfig {
     file_logging_enabled: AtomicBool,
     /// Rate limiter for controlling log output frequency
     rate_limiter: RateLimiter,
+    /// Recent records, one ring buffer per category
+    history: Mutex<[VecDeque<LogRecord>; CATEGORY_COUNT]>,
+    /// Maximum records kept per category
+    history_capacity: AtomicUsize,
+    /// Sequence number of the next record
+    next_seq: AtomicU64,
+    /// When logging started (record timestamps are relative to this)
+    start: Instant,
 }
 
+/// Records kept per category unless changed with `set_history_capacity`
+pub const DEFAULT_HISTORY_CAPACITY: usize = 1000;
+
 impl LogConfig {
     /// Create a new LogConfig with all logging disabled and default rate limit (60 logs/second)
     fn new() -> Self {
@@ -255,6 +401,10 @@ impl LogConfig {
             log_sender: Mutex::new(None),
             file_logging_enabled: AtomicBool::new(false),
             rate_limiter: RateLimiter::new(60), // Default: 60 logs per second
+            history: Mutex::new(Default::default()),
+            history_capacity: AtomicUsize::new(DEFAULT_HISTORY_CAPACITY),
+            next_seq: AtomicU64::new(0),
+            start: Instant::now(),
         }
     }
 
@@ -339,6 +489,70 @@ impl LogConfig {
         self.rate_limiter.get_max_logs_per_second()
     }
 
+    /// Set how many records are kept per category (older ones are dropped)
+    pub fn set_history_capacity(&self, capacity: usize) {
+        self.history_capacity.store(capacity, Ordering::Relaxed);
+        let mut history = self.history.lock().unwrap();
+        for buffer in history.iter_mut() {
+            while buffer.len() > capacity {
+                buffer.pop_front();
+            }
+        }
+    }
+
+    /// Get how many records are kept per category
+    pub fn history_capacity(&self) -> usize {
+        self.history_capacity.load(Ordering::Relaxed)
+    }
+
+    /// Recent records that pass `filter`, oldest first
+    pub fn history(&self, filter: &LogFilter) -> Vec<LogRecord> {
+        let history = self.history.lock().unwrap();
+        let mut records: Vec<LogRecord> = LogCategory::ALL
+            .iter()
+            .filter(|&&category| filter.includes(category))
+            .flat_map(|&category| history[category.index()].iter())
+            .filter(|record| filter.matches(record))
+            .cloned()
+            .collect();
+        records.sort_by_key(|record| record.seq);
+        records
+    }
+
+    /// Number of records currently kept for a category
+    pub fn history_len(&self, category: LogCategory) -> usize {
+        self.history.lock().unwrap()[category.index()].len()
+    }
+
+    /// Discard all kept records
+    pub fn clear_history(&self) {
+        let mut history = self.history.lock().unwrap();
+        for buffer in history.iter_mut() {
+            buffer.clear();
+        }
+    }
+
+    /// Add a message to the category's ring buffer
+    fn record(&self, category: LogCategory, level: LogLevel, message: &str) {
+        let capacity = self.history_capacity();
+        if capacity == 0 {
+            return;
+        }
+        let record = LogRecord {
+            seq: self.next_seq.fetch_add(1, Ordering::Relaxed),
+            elapsed: self.start.elapsed(),
+            category,
+            level,
+            message: message.to_string(),
+        };
+        let mut history = self.history.lock().unwrap();
+        let buffer = &mut history[category.index()];
+        while buffer.len() >= capacity {
+            buffer.pop_front();
+        }
+        buffer.push_back(record);
+    }
+
     /// Set the log file path
     ///
     /// Starts a background thread for async file I/O to prevent blocking the emulation.
@@ -427,6 +641,11 @@ impl LogConfig {
 /// * `level` - The log level (Error, Warn, Info, Debug, Trace)
 /// * `message_fn` - A closure that produces the message string
 ///
+/// # History
+///
+/// Messages that pass the rate limit are also added to the category's ring
+/// buffer (see [`LogConfig::history`]) before being written out.
+///
 /// # Performance
 ///
 /// - Zero overhead when logging is disabled (closure is never called)
@@ -459,6 +678,7 @@ where
                     "[{:?}] WARNING: Rate limit exceeded, {} log message(s) dropped in the last second",
                     category, count
                 );
+                config.record(category, LogLevel::Warn, &warning);
                 config.write_message(&warning);
             }
         }
@@ -466,6 +686,7 @@ where
         // Only evaluate and log the message if allowed by rate limiter
         if allowed {
             let message = message_fn();
+            config.record(category, level, &message);
             config.write_message(&message);
         }
     }
@@ -590,6 +811,66 @@ mod tests {
         assert_eq!(config.get_level(LogCategory::Bus), LogLevel::Off);
     }
 
+    #[test]
+    fn test_history_ring_buffer_per_category() {
+        let config = LogConfig::new();
+        config.set_history_capacity(3);
+        for i in 0..5 {
+            config.record(LogCategory::CPU, LogLevel::Debug, &format!("cpu {}", i));
+        }
+        config.record(LogCategory::PPU, LogLevel::Error, "ppu error");
+
+        // CPU keeps only its last 3; PPU is unaffected
+        assert_eq!(config.history_len(LogCategory::CPU), 3);
+        let all = config.history(&LogFilter::default());
+        let messages: Vec<&str> = all.iter().map(|r| r.message.as_str()).collect();
+        assert_eq!(messages, ["cpu 2", "cpu 3", "cpu 4", "ppu error"]);
+        assert!(all.windows(2).all(|w| w[0].seq < w[1].seq));
+
+        config.clear_history();
+        assert!(config.history(&LogFilter::default()).is_empty());
+    }
+
+    #[test]
+    fn test_history_filter() {
+        let config = LogConfig::new();
+        config.record(LogCategory::CPU, LogLevel::Trace, "PC=1234");
+        config.record(LogCategory::Bus, LogLevel::Warn, "Open bus read");
+        config.record(LogCategory::Stubs, LogLevel::Info, "Unhandled port");
+
+        let mut filter = LogFilter {
+            max_level: LogLevel::Info,
+            ..LogFilter::default()
+        };
+        assert_eq!(config.history(&filter).len(), 2);
+
+        filter.set_includes(LogCategory::Stubs, false);
+        let records = config.history(&filter);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].category, LogCategory::Bus);
+
+        let filter = LogFilter {
+            text: "pc=".to_string(),
+            ..LogFilter::default()
+        };
+        assert_eq!(config.history(&filter)[0].message, "PC=1234");
+    }
+
+    #[test]
+    fn test_log_record_display() {
+        let record = LogRecord {
+            seq: 0,
+            elapsed: Duration::from_millis(1500),
+            category: LogCategory::APU,
+            level: LogLevel::Warn,
+            message: "Buffer underrun".to_string(),
+        };
+        assert_eq!(
+            record.to_string(),
+            "[     1.500] WARN  APU: Buffer underrun"
+        );
+    }
+
     #[test]
     fn test_rate_limiter_allows_within_limit() {
         let limiter = RateLimiter::new(60);
@@ -0,0 +1,240 @@
+//! Input movies for reproducible playback
+//!
+//! An [`InputMovie`] records the input applied to every emulated frame, along
+//! with the point the recording started from: either a power-on reset or an
+//! embedded save state. Because every system is deterministic for a given
+//! starting state and input sequence, playing a movie back reproduces the
+//! original session exactly, which makes movies handy for bug reports.
+//!
+//! Movies are plain serde data and are stored as JSON.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Current movie format version
+pub const MOVIE_VERSION: u32 = 1;
+
+/// Where a movie begins
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum MovieStart {
+    /// The system is reset before the first frame
+    PowerOn,
+    /// The embedded save state is loaded before the first frame
+    SaveState(Value),
+}
+
+/// Input applied to a single frame
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MovieFrame {
+    /// Controller state per port (SNES uses the full 16 bits, others the low 8)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub controllers: Vec<u16>,
+    /// Keyboard scancodes pressed before this frame (PC)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub key_presses: Vec<u32>,
+    /// Keyboard scancodes released before this frame (PC)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub key_releases: Vec<u32>,
+}
+
+/// Errors that can occur when loading a movie
+#[derive(Debug, thiserror::Error)]
+pub enum MovieError {
+    /// The file is not a valid movie
+    #[error("Invalid movie file: {0}")]
+    Parse(#[from] serde_json::Error),
+    /// The movie was written by a newer format version
+    #[error("Unsupported movie version {0}")]
+    Version(u32),
+    /// The movie was recorded on a different system
+    #[error("Movie was recorded on {expected}, not {actual}")]
+    SystemMismatch {
+        /// System the movie was recorded on
+        expected: String,
+        /// System it is being played on
+        actual: String,
+    },
+    /// The movie was recorded with a different ROM
+    #[error("Movie was recorded with a different ROM")]
+    RomMismatch,
+}
+
+/// A recorded input sequence
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InputMovie {
+    /// Movie format version
+    pub version: u32,
+    /// Name of the system the movie was recorded on
+    pub system: String,
+    /// Hash of the ROM that was loaded, if any
+    #[serde(default)]
+    pub rom_hash: Option<String>,
+    /// State the system starts from when playback begins
+    pub start: MovieStart,
+    /// Input for each frame, in order
+    #[serde(default)]
+    pub frames: Vec<MovieFrame>,
+}
+
+impl InputMovie {
+    /// Start an empty movie
+    pub fn new(system: impl Into<String>, rom_hash: Option<String>, start: MovieStart) -> Self {
+        Self {
+            version: MOVIE_VERSION,
+            system: system.into(),
+            rom_hash,
+            start,
+            frames: Vec::new(),
+        }
+    }
+
+    /// Append the input for the next frame
+    pub fn push_frame(&mut self, frame: MovieFrame) {
+        self.frames.push(frame);
+    }
+
+    /// Number of recorded frames
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Whether no frames have been recorded
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Serialize to JSON
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+
+    /// Parse a movie, rejecting versions this build does not understand
+    pub fn from_json(json: &str) -> Result<Self, MovieError> {
+        let movie: Self = serde_json::from_str(json)?;
+        if movie.version > MOVIE_VERSION {
+            return Err(MovieError::Version(movie.version));
+        }
+        Ok(movie)
+    }
+
+    /// Check that the movie can be played on the given system and ROM
+    pub fn check_compatible(&self, system: &str, rom_hash: Option<&str>) -> Result<(), MovieError> {
+        if self.system != system {
+            return Err(MovieError::SystemMismatch {
+                expected: self.system.clone(),
+                actual: system.to_string(),
+            });
+        }
+        if let (Some(expected), Some(actual)) = (self.rom_hash.as_deref(), rom_hash) {
+            if expected != actual {
+                return Err(MovieError::RomMismatch);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Steps through a movie one frame at a time
+#[derive(Debug, Clone)]
+pub struct MoviePlayer {
+    movie: InputMovie,
+    position: usize,
+}
+
+impl MoviePlayer {
+    /// Play `movie` from its first frame
+    pub fn new(movie: InputMovie) -> Self {
+        Self { movie, position: 0 }
+    }
+
+    /// Input for the next frame, or None once the movie has ended
+    pub fn next_frame(&mut self) -> Option<&MovieFrame> {
+        let frame = self.movie.frames.get(self.position)?;
+        self.position += 1;
+        Some(frame)
+    }
+
+    /// Frames played so far
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Whether every frame has been played
+    pub fn is_finished(&self) -> bool {
+        self.position >= self.movie.frames.len()
+    }
+
+    /// The movie being played
+    pub fn movie(&self) -> &InputMovie {
+        &self.movie
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn sample_movie() -> InputMovie {
+        let mut movie = InputMovie::new(
+            "nes",
+            Some("abc123".to_string()),
+            MovieStart::SaveState(json!({ "pc": 0x8000 })),
+        );
+        movie.push_frame(MovieFrame {
+            controllers: vec![0x01, 0x00],
+            ..Default::default()
+        });
+        movie.push_frame(MovieFrame {
+            controllers: vec![0x81, 0x00],
+            key_presses: vec![4],
+            key_releases: vec![],
+        });
+        movie
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let movie = sample_movie();
+        let json = movie.to_json().unwrap();
+        assert_eq!(InputMovie::from_json(&json).unwrap(), movie);
+
+        // Empty input lists are left out of the file
+        assert!(!json.contains("key_releases"));
+    }
+
+    #[test]
+    fn test_rejects_newer_version() {
+        let mut movie = sample_movie();
+        movie.version = MOVIE_VERSION + 1;
+        let json = movie.to_json().unwrap();
+        assert!(matches!(
+            InputMovie::from_json(&json),
+            Err(MovieError::Version(_))
+        ));
+    }
+
+    #[test]
+    fn test_check_compatible() {
+        let movie = sample_movie();
+        assert!(movie.check_compatible("nes", Some("abc123")).is_ok());
+        assert!(matches!(
+            movie.check_compatible("snes", Some("abc123")),
+            Err(MovieError::SystemMismatch { .. })
+        ));
+        assert!(matches!(
+            movie.check_compatible("nes", Some("def456")),
+            Err(MovieError::RomMismatch)
+        ));
+    }
+
+    #[test]
+    fn test_player_steps_through_frames() {
+        let mut player = MoviePlayer::new(sample_movie());
+        assert_eq!(player.next_frame().unwrap().controllers, vec![0x01, 0x00]);
+        assert_eq!(player.next_frame().unwrap().key_presses, vec![4]);
+        assert!(player.is_finished());
+        assert_eq!(player.position(), 2);
+        assert!(player.next_frame().is_none());
+    }
+}
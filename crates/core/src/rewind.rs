@@ -0,0 +1,169 @@
+//! Rewind history built on save states
+//!
+//! [`RewindBuffer`] keeps a ring of recent [`System::save_state`] snapshots,
+//! taken every few frames. Rewinding pops the newest snapshot and loads it, so
+//! holding the rewind key steps backwards through recent gameplay. Snapshots
+//! are stored as compact JSON and the oldest are dropped once the buffer
+//! reaches its memory cap.
+//!
+//! [`System::save_state`]: crate::System::save_state
+
+use serde_json::Value;
+use std::collections::VecDeque;
+
+/// Ring buffer of recent save states
+#[derive(Debug, Clone)]
+pub struct RewindBuffer {
+    snapshots: VecDeque<Vec<u8>>,
+    /// Frames between snapshots
+    interval: u32,
+    /// Frames recorded since the last snapshot
+    frames_since_snapshot: u32,
+    /// Most bytes of snapshot data kept
+    capacity_bytes: usize,
+    used_bytes: usize,
+}
+
+impl RewindBuffer {
+    /// Snapshot every `interval` frames, keeping at most `capacity_bytes` of states
+    pub fn new(interval: u32, capacity_bytes: usize) -> Self {
+        Self {
+            snapshots: VecDeque::new(),
+            interval: interval.max(1),
+            frames_since_snapshot: 0,
+            capacity_bytes,
+            used_bytes: 0,
+        }
+    }
+
+    /// Count a frame, taking a snapshot from `save_state` when one is due
+    ///
+    /// `save_state` is only called on snapshot frames. Returns true if a
+    /// snapshot was stored.
+    pub fn record(&mut self, save_state: impl FnOnce() -> Value) -> bool {
+        self.frames_since_snapshot += 1;
+        if self.frames_since_snapshot < self.interval {
+            return false;
+        }
+        self.frames_since_snapshot = 0;
+        self.push(&save_state())
+    }
+
+    /// Store a snapshot, dropping the oldest ones to stay within the cap
+    ///
+    /// Returns false if the state alone is larger than the cap.
+    pub fn push(&mut self, state: &Value) -> bool {
+        let Ok(data) = serde_json::to_vec(state) else {
+            return false;
+        };
+        if data.len() > self.capacity_bytes {
+            return false;
+        }
+
+        while self.used_bytes + data.len() > self.capacity_bytes {
+            match self.snapshots.pop_front() {
+                Some(oldest) => self.used_bytes -= oldest.len(),
+                None => break,
+            }
+        }
+        self.used_bytes += data.len();
+        self.snapshots.push_back(data);
+        true
+    }
+
+    /// Remove and return the newest snapshot
+    pub fn pop(&mut self) -> Option<Value> {
+        let data = self.snapshots.pop_back()?;
+        self.used_bytes -= data.len();
+        // Restart the interval so play resumes from the restored point
+        self.frames_since_snapshot = 0;
+        serde_json::from_slice(&data).ok()
+    }
+
+    /// Drop all snapshots (e.g. when a different game is loaded)
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+        self.used_bytes = 0;
+        self.frames_since_snapshot = 0;
+    }
+
+    /// Number of snapshots held
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    /// Whether there is nothing to rewind to
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    /// Bytes of snapshot data held
+    pub fn used_bytes(&self) -> usize {
+        self.used_bytes
+    }
+
+    /// Frames between snapshots
+    pub fn interval(&self) -> u32 {
+        self.interval
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_records_every_interval() {
+        let mut rewind = RewindBuffer::new(3, 1 << 20);
+        let mut calls = 0;
+        for frame in 0..9 {
+            rewind.record(|| {
+                calls += 1;
+                json!({ "frame": frame })
+            });
+        }
+        assert_eq!(calls, 3);
+        assert_eq!(rewind.len(), 3);
+
+        // Newest first
+        assert_eq!(rewind.pop(), Some(json!({ "frame": 8 })));
+        assert_eq!(rewind.pop(), Some(json!({ "frame": 5 })));
+        assert_eq!(rewind.pop(), Some(json!({ "frame": 2 })));
+        assert_eq!(rewind.pop(), None);
+        assert_eq!(rewind.used_bytes(), 0);
+    }
+
+    #[test]
+    fn test_memory_cap_drops_oldest() {
+        let state = |n: u32| json!({ "ram": vec![n; 16] });
+        let size = serde_json::to_vec(&state(0)).unwrap().len();
+        let mut rewind = RewindBuffer::new(1, size * 3);
+
+        for n in 0..5 {
+            assert!(rewind.push(&state(n)));
+        }
+        assert_eq!(rewind.len(), 3);
+        assert!(rewind.used_bytes() <= size * 3);
+        assert_eq!(rewind.pop(), Some(state(4)));
+        assert_eq!(rewind.pop(), Some(state(3)));
+        assert_eq!(rewind.pop(), Some(state(2)));
+        assert!(rewind.is_empty());
+
+        // A state that can never fit is rejected without clearing history
+        rewind.push(&state(7));
+        assert!(!rewind.push(&json!({ "ram": vec![0; 1000] })));
+        assert_eq!(rewind.len(), 1);
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut rewind = RewindBuffer::new(1, 1 << 20);
+        rewind.push(&json!(1));
+        rewind.push(&json!(2));
+        rewind.clear();
+        assert!(rewind.is_empty());
+        assert_eq!(rewind.used_bytes(), 0);
+        assert_eq!(rewind.pop(), None);
+    }
+}
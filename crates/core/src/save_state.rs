@@ -0,0 +1,187 @@
+//! Save state compatibility helpers
+//!
+//! Save states are plain JSON, so a state written on one host (or by an
+//! older build) must restore identically everywhere. The system crates keep
+//! golden state fixtures next to their test ROMs and use [`check_golden_state`]
+//! to check that loading a fixture and saving again reproduces it.
+
+use crate::System;
+use serde_json::Value;
+
+/// Restore a golden save state fixture and check that it round-trips
+///
+/// Mounts `media` at `mount_point_id`, loads the `fixture` JSON, requires a
+/// fresh save to reproduce every fixture field (see [`state_mismatch`]) and
+/// runs one frame from the restored state. Returns a description of the first
+/// problem found.
+pub fn check_golden_state<S: System>(
+    system: &mut S,
+    mount_point_id: &str,
+    media: &[u8],
+    fixture: &str,
+) -> Result<(), String> {
+    let fixture: Value =
+        serde_json::from_str(fixture).map_err(|e| format!("invalid fixture: {}", e))?;
+    system
+        .mount(mount_point_id, media)
+        .map_err(|e| format!("mount failed: {}", e))?;
+    system
+        .load_state(&fixture)
+        .map_err(|e| format!("load_state failed: {}", e))?;
+    if let Some(path) = state_mismatch(&fixture, &system.save_state()) {
+        return Err(format!("restored state differs at {}", path));
+    }
+    system
+        .step_frame()
+        .map_err(|e| format!("step_frame failed after restore: {}", e))?;
+    Ok(())
+}
+
+/// Find the first field of `expected` that `actual` lacks or disagrees with
+///
+/// Returns the JSON path of the mismatch (e.g. `cpu.pc` or `bus.ram[12]`),
+/// or `None` if every value in `expected` is present and equal in `actual`.
+/// Fields only present in `actual` are ignored, so fixtures stay valid when
+/// newer builds add state.
+pub fn state_mismatch(expected: &Value, actual: &Value) -> Option<String> {
+    mismatch_at(expected, actual, String::new())
+}
+
+fn mismatch_at(expected: &Value, actual: &Value, path: String) -> Option<String> {
+    match (expected, actual) {
+        (Value::Object(expected), Value::Object(actual)) => {
+            expected.iter().find_map(|(key, value)| {
+                let path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                match actual.get(key) {
+                    Some(actual) => mismatch_at(value, actual, path),
+                    None => Some(path),
+                }
+            })
+        }
+        (Value::Array(expected), Value::Array(actual)) if expected.len() == actual.len() => {
+            expected
+                .iter()
+                .zip(actual)
+                .enumerate()
+                .find_map(|(i, (expected, actual))| {
+                    mismatch_at(expected, actual, format!("{}[{}]", path, i))
+                })
+        }
+        _ if expected == actual => None,
+        _ => Some(if path.is_empty() {
+            "<root>".to_string()
+        } else {
+            path
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_matching_state() {
+        let state = json!({"version": 1, "cpu": {"pc": 0x8000, "ram": [1, 2, 3]}});
+        assert_eq!(state_mismatch(&state, &state), None);
+
+        // Fields added by newer builds are allowed
+        let newer = json!({"version": 1, "cpu": {"pc": 0x8000, "ram": [1, 2, 3], "x": 0}});
+        assert_eq!(state_mismatch(&state, &newer), None);
+    }
+
+    #[test]
+    fn test_mismatch_paths() {
+        let state = json!({"cpu": {"pc": 0x8000, "ram": [1, 2, 3]}, "cycles": 10});
+        assert_eq!(
+            state_mismatch(&state, &json!({"cpu": {"pc": 0x8001, "ram": [1, 2, 3]}})),
+            Some("cpu.pc".to_string())
+        );
+        assert_eq!(
+            state_mismatch(
+                &state,
+                &json!({"cpu": {"pc": 0x8000, "ram": [1, 9, 3]}, "cycles": 10})
+            ),
+            Some("cpu.ram[1]".to_string())
+        );
+        assert_eq!(
+            state_mismatch(&state, &json!({"cpu": {"pc": 0x8000, "ram": [1, 2]}})),
+            Some("cpu.ram".to_string())
+        );
+        assert_eq!(
+            state_mismatch(
+                &state,
+                &json!({"cpu": {"pc": 0x8000, "ram": [1, 2, 3]}, "cycles": 10.0})
+            ),
+            Some("cycles".to_string())
+        );
+        assert_eq!(
+            state_mismatch(&json!(1), &json!(2)),
+            Some("<root>".to_string())
+        );
+    }
+
+    /// Keeps one register and drops anything it doesn't know on load
+    #[derive(Default)]
+    struct OneRegister {
+        pc: u64,
+    }
+
+    impl System for OneRegister {
+        type Error = std::convert::Infallible;
+
+        fn reset(&mut self) {}
+
+        fn step_frame(&mut self) -> Result<crate::types::Frame, Self::Error> {
+            Ok(crate::types::Frame::new(1, 1))
+        }
+
+        fn save_state(&self) -> Value {
+            json!({"version": 1, "pc": self.pc})
+        }
+
+        fn load_state(&mut self, v: &Value) -> Result<(), serde_json::Error> {
+            self.pc = v["pc"].as_u64().unwrap_or(0);
+            Ok(())
+        }
+
+        fn mount_points(&self) -> Vec<crate::MountPointInfo> {
+            vec![]
+        }
+
+        fn mount(&mut self, _mount_point_id: &str, _data: &[u8]) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        fn unmount(&mut self, _mount_point_id: &str) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        fn is_mounted(&self, _mount_point_id: &str) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn test_check_golden_state() {
+        let mut system = OneRegister::default();
+        let fixture = r#"{"version": 1, "pc": 32768}"#;
+        assert_eq!(
+            check_golden_state(&mut system, "Cartridge", &[], fixture),
+            Ok(())
+        );
+        assert_eq!(system.pc, 0x8000);
+
+        let fixture = r#"{"version": 1, "pc": 32768, "sp": 253}"#;
+        assert_eq!(
+            check_golden_state(&mut system, "Cartridge", &[], fixture),
+            Err("restored state differs at sp".to_string())
+        );
+        assert!(check_golden_state(&mut system, "Cartridge", &[], "{").is_err());
+    }
+}
@@ -0,0 +1,346 @@
+//! ROMs inside `.zip` and `.7z` archives
+//!
+//! Most ROM collections are stored compressed, so anything that opens a ROM
+//! goes through [`read_rom`]. An archive holding a single ROM opens as that
+//! ROM. When it holds several, one is picked with a member path
+//! `<archive>#<member>` (e.g. `roms/pack.zip#Tetris (W).gb`), which is what
+//! the chooser window hands back and what is stored in recent files. The
+//! ROM is decompressed in memory; nothing is extracted to disk.
+
+use std::fmt;
+use std::fs;
+use std::io::{self, Cursor, Read};
+use std::path::Path;
+
+/// Archive formats that can be opened, by extension
+pub const ARCHIVE_EXTENSIONS: &[&str] = &["zip", "7z"];
+
+/// Extensions of the files inside an archive that are treated as ROMs
+const ROM_EXTENSIONS: &[&str] = &[
+    "nes", "unf", "gb", "gbc", "bin", "a26", "smc", "sfc", "z64", "n64", "v64", "com", "exe",
+];
+
+/// Error reading a ROM from an archive
+#[derive(Debug)]
+pub enum ArchiveError {
+    Io(io::Error),
+    /// The archive could not be decoded
+    Corrupt(String),
+    /// The archive contains no file with a ROM extension
+    NoRom,
+    /// The member path names a file that is not in the archive
+    MissingMember(String),
+    /// The archive contains several ROMs; the member paths to choose from
+    MultipleRoms(Vec<String>),
+}
+
+impl fmt::Display for ArchiveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ArchiveError::Io(e) => write!(f, "{}", e),
+            ArchiveError::Corrupt(e) => write!(f, "Invalid archive: {}", e),
+            ArchiveError::NoRom => write!(f, "No ROM found in archive"),
+            ArchiveError::MissingMember(name) => write!(f, "{} not found in archive", name),
+            ArchiveError::MultipleRoms(members) => write!(
+                f,
+                "Archive contains {} ROMs; open one as <archive>#<file>",
+                members.len()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ArchiveError {}
+
+impl From<io::Error> for ArchiveError {
+    fn from(e: io::Error) -> Self {
+        ArchiveError::Io(e)
+    }
+}
+
+/// Supported archive formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArchiveKind {
+    Zip,
+    SevenZip,
+}
+
+impl ArchiveKind {
+    fn from_path(path: &str) -> Option<Self> {
+        let ext = Path::new(path).extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "zip" => Some(ArchiveKind::Zip),
+            "7z" => Some(ArchiveKind::SevenZip),
+            _ => None,
+        }
+    }
+}
+
+/// Split `<archive>#<member>` into the archive path and the member
+///
+/// A `#` only starts a member name after a `.zip` or `.7z` file name, so
+/// plain files with `#` in their names are left alone.
+pub fn split_member(path: &str) -> (&str, Option<&str>) {
+    let mut search = 0;
+    while let Some(pos) = path[search..].find('#') {
+        let split = search + pos;
+        if ArchiveKind::from_path(&path[..split]).is_some() {
+            return (&path[..split], Some(&path[split + 1..]));
+        }
+        search = split + 1;
+    }
+    (path, None)
+}
+
+/// Path naming one member of an archive
+pub fn member_path(archive: &str, member: &str) -> String {
+    format!("{}#{}", archive, member)
+}
+
+/// The path a ROM is named by: the member inside an archive, else the path
+///
+/// Used for titles and save file names, so `pack.zip#Tetris (W).gb` is
+/// known as `Tetris (W).gb`.
+pub fn rom_file_name(path: &str) -> &Path {
+    match split_member(path) {
+        (_, Some(member)) => Path::new(member),
+        (archive, None) => Path::new(archive),
+    }
+}
+
+/// The file on disk holding the ROM (the archive for a member path)
+pub fn file_on_disk(path: &str) -> &Path {
+    Path::new(split_member(path).0)
+}
+
+/// Read a ROM from a plain file, an archive with one ROM, or a member path
+pub fn read_rom(path: impl AsRef<Path>) -> Result<Vec<u8>, ArchiveError> {
+    let path = path.as_ref().to_string_lossy();
+    let (archive, member) = split_member(&path);
+    let Some(kind) = ArchiveKind::from_path(archive) else {
+        return Ok(fs::read(archive)?);
+    };
+
+    let data = fs::read(archive)?;
+    let member = match member {
+        Some(member) => member.to_string(),
+        None => {
+            let mut roms = list_roms(kind, &data)?;
+            match roms.len() {
+                0 => return Err(ArchiveError::NoRom),
+                1 => roms.remove(0),
+                _ => {
+                    return Err(ArchiveError::MultipleRoms(
+                        roms.iter().map(|rom| member_path(archive, rom)).collect(),
+                    ))
+                }
+            }
+        }
+    };
+    extract(kind, &data, &member)
+}
+
+/// Names of the ROMs in an archive, in archive order
+fn list_roms(kind: ArchiveKind, data: &[u8]) -> Result<Vec<String>, ArchiveError> {
+    let is_rom = |name: &str| {
+        Path::new(name)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ROM_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+    };
+
+    let mut names = Vec::new();
+    match kind {
+        ArchiveKind::Zip => {
+            let zip = zip::ZipArchive::new(Cursor::new(data))
+                .map_err(|e| ArchiveError::Corrupt(e.to_string()))?;
+            for name in zip.file_names() {
+                if !name.ends_with('/') && is_rom(name) {
+                    names.push(name.to_string());
+                }
+            }
+            // file_names() follows the central directory hash order
+            let order: Vec<&str> = (0..zip.len())
+                .filter_map(|i| zip.name_for_index(i))
+                .collect();
+            names.sort_by_key(|name| order.iter().position(|n| n == name));
+        }
+        ArchiveKind::SevenZip => {
+            let reader = sevenz_rust::SevenZReader::new(
+                Cursor::new(data),
+                data.len() as u64,
+                sevenz_rust::Password::empty(),
+            )
+            .map_err(|e| ArchiveError::Corrupt(e.to_string()))?;
+            for entry in &reader.archive().files {
+                if entry.has_stream() && !entry.is_directory() && is_rom(entry.name()) {
+                    names.push(entry.name().to_string());
+                }
+            }
+        }
+    }
+    Ok(names)
+}
+
+/// Decompress one member of an archive
+fn extract(kind: ArchiveKind, data: &[u8], member: &str) -> Result<Vec<u8>, ArchiveError> {
+    let mut contents = Vec::new();
+    match kind {
+        ArchiveKind::Zip => {
+            let mut zip = zip::ZipArchive::new(Cursor::new(data))
+                .map_err(|e| ArchiveError::Corrupt(e.to_string()))?;
+            let mut file = match zip.by_name(member) {
+                Ok(file) => file,
+                Err(zip::result::ZipError::FileNotFound) => {
+                    return Err(ArchiveError::MissingMember(member.to_string()))
+                }
+                Err(e) => return Err(ArchiveError::Corrupt(e.to_string())),
+            };
+            file.read_to_end(&mut contents)?;
+        }
+        ArchiveKind::SevenZip => {
+            let mut reader = sevenz_rust::SevenZReader::new(
+                Cursor::new(data),
+                data.len() as u64,
+                sevenz_rust::Password::empty(),
+            )
+            .map_err(|e| ArchiveError::Corrupt(e.to_string()))?;
+            let mut found = false;
+            reader
+                .for_each_entries(|entry, entry_reader| {
+                    if entry.name() != member {
+                        // Solid archives must still be read through
+                        io::copy(entry_reader, &mut io::sink())?;
+                        return Ok(true);
+                    }
+                    entry_reader.read_to_end(&mut contents)?;
+                    found = true;
+                    Ok(false)
+                })
+                .map_err(|e| ArchiveError::Corrupt(e.to_string()))?;
+            if !found {
+                return Err(ArchiveError::MissingMember(member.to_string()));
+            }
+        }
+    }
+    Ok(contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("hemu_archive_{}_{}", std::process::id(), name))
+    }
+
+    fn write_zip(path: &Path, files: &[(&str, &[u8])]) {
+        let mut zip = zip::ZipWriter::new(fs::File::create(path).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        for (name, data) in files {
+            zip.start_file(*name, options).unwrap();
+            zip.write_all(data).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn test_split_member() {
+        assert_eq!(
+            split_member("roms/a.zip#b.nes"),
+            ("roms/a.zip", Some("b.nes"))
+        );
+        assert_eq!(
+            split_member("roms/A.7Z#dir/b.gb"),
+            ("roms/A.7Z", Some("dir/b.gb"))
+        );
+        assert_eq!(split_member("roms/a.zip"), ("roms/a.zip", None));
+        // '#' in a plain file name, or in an archive's own name
+        assert_eq!(split_member("roms/#1 hit.nes"), ("roms/#1 hit.nes", None));
+        assert_eq!(
+            split_member("roms/#1.zip#hit.nes"),
+            ("roms/#1.zip", Some("hit.nes"))
+        );
+        assert_eq!(
+            rom_file_name("a.zip#Tetris (W).gb"),
+            Path::new("Tetris (W).gb")
+        );
+    }
+
+    #[test]
+    fn test_zip_single_rom() {
+        let path = temp_path("single.zip");
+        write_zip(
+            &path,
+            &[("readme.txt", b"hello"), ("game.nes", b"NES\x1Arom")],
+        );
+        assert_eq!(read_rom(&path).unwrap(), b"NES\x1Arom");
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_zip_multiple_roms() {
+        let path = temp_path("multi.zip");
+        write_zip(&path, &[("b.gb", b"gb"), ("a.nes", b"nes"), ("docs/", b"")]);
+        let archive = path.to_string_lossy().to_string();
+
+        match read_rom(&path) {
+            Err(ArchiveError::MultipleRoms(members)) => assert_eq!(
+                members,
+                vec![
+                    member_path(&archive, "b.gb"),
+                    member_path(&archive, "a.nes")
+                ]
+            ),
+            other => panic!("expected a choice, got {:?}", other),
+        }
+        assert_eq!(read_rom(member_path(&archive, "a.nes")).unwrap(), b"nes");
+        assert!(matches!(
+            read_rom(member_path(&archive, "c.sfc")),
+            Err(ArchiveError::MissingMember(_))
+        ));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_zip_without_rom() {
+        let path = temp_path("empty.zip");
+        write_zip(&path, &[("readme.txt", b"hello")]);
+        assert!(matches!(read_rom(&path), Err(ArchiveError::NoRom)));
+        fs::remove_file(&path).unwrap();
+
+        let path = temp_path("bad.zip");
+        fs::write(&path, b"not a zip").unwrap();
+        assert!(matches!(read_rom(&path), Err(ArchiveError::Corrupt(_))));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_7z() {
+        let dir = temp_path("7z_src");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("game.sfc"), vec![0x5A; 4096]).unwrap();
+        fs::write(dir.join("notes.txt"), b"notes").unwrap();
+        let path = temp_path("game.7z");
+        sevenz_rust::compress_to_path(&dir, &path).unwrap();
+
+        assert_eq!(read_rom(&path).unwrap(), vec![0x5A; 4096]);
+        let archive = path.to_string_lossy().to_string();
+        assert_eq!(
+            read_rom(member_path(&archive, "notes.txt")).unwrap(),
+            b"notes"
+        );
+        fs::remove_file(&path).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_plain_file() {
+        let path = temp_path("plain.gb");
+        fs::write(&path, b"gb rom").unwrap();
+        assert_eq!(read_rom(&path).unwrap(), b"gb rom");
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(read_rom(&path), Err(ArchiveError::Io(_))));
+    }
+}
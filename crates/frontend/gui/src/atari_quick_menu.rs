@@ -0,0 +1,311 @@
+//! Atari 2600 quick settings menu, driven by a gamepad
+//!
+//! Holding Back+Start on a gamepad opens a small menu over the game for the
+//! console switches (Game Reset, Game Select, difficulty, TV Type), swapping
+//! the controller ports and power cycling, so Atari games can be played from
+//! the couch without reaching for the keyboard. The D-pad or left stick moves
+//! through the menu, A changes the selected item and B (or the combo again)
+//! closes it. Game input is held back while the menu is open.
+
+use emu_atari2600::{Atari2600System, Difficulty};
+use sdl2::controller::Button;
+use std::collections::HashSet;
+
+/// Gamepad buttons the menu reacts to, as bits of a pad state
+pub const PAD_UP: u8 = 0x01;
+pub const PAD_DOWN: u8 = 0x02;
+pub const PAD_LEFT: u8 = 0x04;
+pub const PAD_RIGHT: u8 = 0x08;
+pub const PAD_A: u8 = 0x10;
+pub const PAD_B: u8 = 0x20;
+pub const PAD_BACK: u8 = 0x40;
+pub const PAD_START: u8 = 0x80;
+
+/// Buttons held together to open or close the menu
+const COMBO: u8 = PAD_BACK | PAD_START;
+
+/// Left stick deflection that counts as a D-pad direction
+const STICK_THRESHOLD: i16 = 16384;
+
+/// Host frames Game Reset or Game Select stays held after being chosen
+/// (games poll the switches once per frame)
+const BUTTON_HOLD_FRAMES: u32 = 10;
+
+/// Entries of the menu, top to bottom
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Item {
+    GameReset,
+    GameSelect,
+    LeftDifficulty,
+    RightDifficulty,
+    TvType,
+    Controllers,
+    PowerCycle,
+    Close,
+}
+
+const ITEMS: [Item; 8] = [
+    Item::GameReset,
+    Item::GameSelect,
+    Item::LeftDifficulty,
+    Item::RightDifficulty,
+    Item::TvType,
+    Item::Controllers,
+    Item::PowerCycle,
+    Item::Close,
+];
+
+/// Console change requested from the menu
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuickAction {
+    /// Flip a difficulty switch (0 = left, 1 = right)
+    ToggleDifficulty(usize),
+    /// Flip the TV Type switch
+    ToggleColor,
+    /// Swap the controller ports
+    TogglePorts,
+    /// Turn the console off and on again
+    PowerCycle,
+}
+
+/// State of the quick settings menu
+#[derive(Debug, Default)]
+pub struct AtariQuickMenu {
+    open: bool,
+    selected: usize,
+    /// Pad state of the previous update, to act on presses only
+    previous: u8,
+    /// Game Reset (true) or Game Select (false) held for some more frames
+    held_button: Option<(bool, u32)>,
+}
+
+impl AtariQuickMenu {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Handle one host frame of gamepad input (`PAD_*` bits)
+    ///
+    /// Returns the change to make to the console, if one was chosen.
+    pub fn update(&mut self, pad: u8) -> Option<QuickAction> {
+        let pressed = pad & !self.previous;
+        self.previous = pad;
+        if let Some((_, frames)) = &mut self.held_button {
+            *frames = frames.saturating_sub(1);
+            if *frames == 0 {
+                self.held_button = None;
+            }
+        }
+
+        // The combo toggles the menu once both buttons are down
+        if pad & COMBO == COMBO && pressed & COMBO != 0 {
+            self.open = !self.open;
+            self.selected = 0;
+            return None;
+        }
+        if !self.open {
+            return None;
+        }
+
+        if pressed & PAD_UP != 0 {
+            self.selected = (self.selected + ITEMS.len() - 1) % ITEMS.len();
+        }
+        if pressed & PAD_DOWN != 0 {
+            self.selected = (self.selected + 1) % ITEMS.len();
+        }
+        if pressed & PAD_B != 0 {
+            self.open = false;
+            return None;
+        }
+
+        let item = ITEMS[self.selected];
+        let choose = pressed & PAD_A != 0;
+        let flip = choose || pressed & (PAD_LEFT | PAD_RIGHT) != 0;
+        match item {
+            Item::GameReset | Item::GameSelect if choose => {
+                self.held_button = Some((item == Item::GameReset, BUTTON_HOLD_FRAMES));
+                // Back to the game after a reset; Select may be pressed repeatedly
+                self.open = item != Item::GameReset;
+                None
+            }
+            Item::LeftDifficulty if flip => Some(QuickAction::ToggleDifficulty(0)),
+            Item::RightDifficulty if flip => Some(QuickAction::ToggleDifficulty(1)),
+            Item::TvType if flip => Some(QuickAction::ToggleColor),
+            Item::Controllers if flip => Some(QuickAction::TogglePorts),
+            Item::PowerCycle if choose => {
+                self.open = false;
+                Some(QuickAction::PowerCycle)
+            }
+            Item::Close if choose => {
+                self.open = false;
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// Game Reset and Game Select buttons to hold down this frame
+    pub fn console_buttons(&self) -> (bool, bool) {
+        match self.held_button {
+            Some((reset, _)) => (reset, !reset),
+            None => (false, false),
+        }
+    }
+
+    /// Draw the menu over the emulator display
+    pub fn ui(&self, ctx: &egui::Context, sys: &Atari2600System) {
+        if !self.open {
+            return;
+        }
+        let difficulty = |player| match sys.difficulty(player) {
+            Difficulty::A => "A (Pro)",
+            Difficulty::B => "B (Amateur)",
+        };
+        egui::Window::new("Atari 2600")
+            .collapsible(false)
+            .resizable(false)
+            .title_bar(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(egui::RichText::new("Atari 2600 Console").strong());
+                ui.separator();
+                for (index, item) in ITEMS.iter().enumerate() {
+                    let text = match item {
+                        Item::GameReset => "Game Reset".to_string(),
+                        Item::GameSelect => "Game Select".to_string(),
+                        Item::LeftDifficulty => format!("Left Difficulty: {}", difficulty(0)),
+                        Item::RightDifficulty => format!("Right Difficulty: {}", difficulty(1)),
+                        Item::TvType => format!(
+                            "TV Type: {}",
+                            if sys.color_switch() { "Color" } else { "B&W" }
+                        ),
+                        Item::Controllers => format!(
+                            "Controllers: {}",
+                            if sys.ports_swapped() {
+                                "Swapped"
+                            } else {
+                                "Normal"
+                            }
+                        ),
+                        Item::PowerCycle => "Power Cycle".to_string(),
+                        Item::Close => "Close".to_string(),
+                    };
+                    let _ = ui.selectable_label(index == self.selected, text);
+                }
+                ui.separator();
+                ui.small("D-pad: move   A: change   B: close");
+            });
+    }
+}
+
+/// Pad state (`PAD_*` bits) from the buttons and left stick of all gamepads
+pub fn pad_state(buttons: &HashSet<u8>, stick_x: i16, stick_y: i16) -> u8 {
+    let mut pad = 0;
+    for (button, bit) in [
+        (Button::DPadUp, PAD_UP),
+        (Button::DPadDown, PAD_DOWN),
+        (Button::DPadLeft, PAD_LEFT),
+        (Button::DPadRight, PAD_RIGHT),
+        (Button::A, PAD_A),
+        (Button::B, PAD_B),
+        (Button::Back, PAD_BACK),
+        (Button::Start, PAD_START),
+    ] {
+        if buttons.contains(&(button as u8)) {
+            pad |= bit;
+        }
+    }
+    if stick_y < -STICK_THRESHOLD {
+        pad |= PAD_UP;
+    }
+    if stick_y > STICK_THRESHOLD {
+        pad |= PAD_DOWN;
+    }
+    if stick_x < -STICK_THRESHOLD {
+        pad |= PAD_LEFT;
+    }
+    if stick_x > STICK_THRESHOLD {
+        pad |= PAD_RIGHT;
+    }
+    pad
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Press and release a button
+    fn tap(menu: &mut AtariQuickMenu, pad: u8) -> Option<QuickAction> {
+        let action = menu.update(pad);
+        menu.update(0);
+        action
+    }
+
+    #[test]
+    fn test_combo_opens_and_closes_menu() {
+        let mut menu = AtariQuickMenu::new();
+        menu.update(PAD_BACK);
+        assert!(!menu.is_open());
+        menu.update(PAD_BACK | PAD_START);
+        assert!(menu.is_open());
+        // Holding the combo does not toggle again
+        menu.update(PAD_BACK | PAD_START);
+        assert!(menu.is_open());
+        menu.update(0);
+        tap(&mut menu, PAD_B);
+        assert!(!menu.is_open());
+    }
+
+    #[test]
+    fn test_menu_items() {
+        let mut menu = AtariQuickMenu::new();
+        tap(&mut menu, COMBO);
+
+        // Down twice to the left difficulty switch, flipped with left/right or A
+        tap(&mut menu, PAD_DOWN);
+        tap(&mut menu, PAD_DOWN);
+        assert_eq!(
+            tap(&mut menu, PAD_RIGHT),
+            Some(QuickAction::ToggleDifficulty(0))
+        );
+        assert_eq!(
+            tap(&mut menu, PAD_A),
+            Some(QuickAction::ToggleDifficulty(0))
+        );
+
+        // Up wraps around to Close
+        tap(&mut menu, PAD_UP);
+        tap(&mut menu, PAD_UP);
+        tap(&mut menu, PAD_UP);
+        assert_eq!(tap(&mut menu, PAD_A), None);
+        assert!(!menu.is_open());
+    }
+
+    #[test]
+    fn test_game_reset_is_held_for_a_few_frames() {
+        let mut menu = AtariQuickMenu::new();
+        tap(&mut menu, COMBO);
+        assert_eq!(tap(&mut menu, PAD_A), None);
+        assert!(!menu.is_open());
+        assert_eq!(menu.console_buttons(), (true, false));
+        for _ in 0..BUTTON_HOLD_FRAMES {
+            menu.update(0);
+        }
+        assert_eq!(menu.console_buttons(), (false, false));
+    }
+
+    #[test]
+    fn test_pad_state() {
+        let buttons = HashSet::from([Button::A as u8, Button::Back as u8]);
+        assert_eq!(pad_state(&buttons, 0, 0), PAD_A | PAD_BACK);
+        assert_eq!(
+            pad_state(&HashSet::new(), 20000, -20000),
+            PAD_RIGHT | PAD_UP
+        );
+        assert_eq!(pad_state(&HashSet::new(), 1000, 1000), 0);
+    }
+}
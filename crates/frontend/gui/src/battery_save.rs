@@ -0,0 +1,71 @@
+//! Battery-backed cartridge RAM (`.sav` files)
+//!
+//! Cartridges with a battery keep their save RAM when the console is off.
+//! The RAM is written to a `.sav` file next to the ROM (`zelda.nes` ->
+//! `zelda.sav`), the layout used by most other emulators, so existing saves
+//! can be dropped in alongside the ROM. A ROM inside an archive saves next
+//! to the archive (`pack.zip#zelda.nes` -> `zelda.sav`).
+
+use crate::archive;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Save file belonging to the ROM at `rom_path`
+pub fn sav_path(rom_path: &Path) -> PathBuf {
+    let path = rom_path.to_string_lossy();
+    match archive::split_member(&path) {
+        // Next to the archive, named after the ROM inside it
+        (archive, Some(member)) => {
+            let stem = Path::new(member).file_stem().unwrap_or_default();
+            Path::new(archive).with_file_name(format!("{}.sav", stem.to_string_lossy()))
+        }
+        (_, None) => rom_path.with_extension("sav"),
+    }
+}
+
+/// Contents of the ROM's save file, or `None` if it has none yet
+pub fn read(rom_path: &Path) -> io::Result<Option<Vec<u8>>> {
+    match fs::read(sav_path(rom_path)) {
+        Ok(data) => Ok(Some(data)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Write the ROM's save file
+pub fn write(rom_path: &Path, data: &[u8]) -> io::Result<()> {
+    fs::write(sav_path(rom_path), data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sav_path() {
+        assert_eq!(
+            sav_path(Path::new("roms/Zelda (U).nes")),
+            PathBuf::from("roms/Zelda (U).sav")
+        );
+        assert_eq!(sav_path(Path::new("game")), PathBuf::from("game.sav"));
+        assert_eq!(
+            sav_path(Path::new("roms/Zelda (U).zip")),
+            PathBuf::from("roms/Zelda (U).sav")
+        );
+        assert_eq!(
+            sav_path(Path::new("roms/pack.7z#sub/Tetris v1.1.gb")),
+            PathBuf::from("roms/Tetris v1.1.sav")
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let rom = std::env::temp_dir().join(format!("hemu_battery_{}.nes", std::process::id()));
+        assert_eq!(read(&rom).unwrap(), None);
+
+        write(&rom, &[1, 2, 3]).unwrap();
+        assert_eq!(read(&rom).unwrap(), Some(vec![1, 2, 3]));
+        fs::remove_file(sav_path(&rom)).unwrap();
+    }
+}
@@ -0,0 +1,182 @@
+//! Headless benchmark (`--bench`)
+//!
+//! Runs a ROM for a fixed number of frames as fast as possible, without a
+//! window, audio or frame limiter, and reports frame times and how much faster
+//! than real time the system ran. The same ROM and frame count give comparable
+//! numbers across releases, so performance regressions can be measured by
+//! users and in CI.
+
+use crate::rom_detect::{detect_rom_type, SystemType};
+use emu_core::System;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::path::Path;
+use std::time::Instant;
+
+/// Result of a benchmark run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchReport {
+    /// Hemulator version that produced the report
+    pub version: String,
+    /// Whether this is a debug or release build
+    pub profile: String,
+    pub system: String,
+    pub frames: u32,
+    /// Wall-clock time of the whole run
+    pub total_ms: f64,
+    pub avg_frame_ms: f64,
+    pub min_frame_ms: f64,
+    pub max_frame_ms: f64,
+    /// Frames per second the emulated system produces in real time
+    pub target_fps: f64,
+    /// Frames per second achieved on this host
+    pub fps: f64,
+    /// Emulated time divided by real time (1.0 = full speed)
+    pub speed: f64,
+}
+
+impl BenchReport {
+    /// Print the report to stdout
+    pub fn print(&self) {
+        println!(
+            "Hemulator {} benchmark ({}, {})",
+            self.version, self.system, self.profile
+        );
+        println!();
+        println!("Frames:       {}", self.frames);
+        println!("Total time:   {:.1} ms", self.total_ms);
+        println!(
+            "Frame time:   avg {:.3} ms, min {:.3} ms, max {:.3} ms",
+            self.avg_frame_ms, self.min_frame_ms, self.max_frame_ms
+        );
+        println!(
+            "Frame rate:   {:.1} fps (system runs at {:.2} fps)",
+            self.fps, self.target_fps
+        );
+        println!("Speed:        {:.2}x real time", self.speed);
+    }
+
+    /// Write the report as pretty-printed JSON
+    pub fn write_json(&self, path: &Path) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize report: {}", e))?;
+        std::fs::write(path, json).map_err(|e| format!("Failed to write report: {}", e))
+    }
+}
+
+/// Detect the system of a ROM, mount it and benchmark it
+pub fn run_rom(data: &[u8], frames: u32) -> Result<BenchReport, String> {
+    match detect_rom_type(data).map_err(|e| e.to_string())? {
+        SystemType::NES => run(
+            "NES",
+            emu_nes::NesSystem::default(),
+            "Cartridge",
+            data,
+            frames,
+        ),
+        SystemType::GameBoy => run(
+            "Game Boy",
+            emu_gb::GbSystem::new(),
+            "Cartridge",
+            data,
+            frames,
+        ),
+        SystemType::Atari2600 => run(
+            "Atari 2600",
+            emu_atari2600::Atari2600System::new(),
+            "Cartridge",
+            data,
+            frames,
+        ),
+        SystemType::PC => run("PC", emu_pc::PcSystem::new(), "Executable", data, frames),
+        SystemType::SNES => run(
+            "SNES",
+            emu_snes::SnesSystem::new(),
+            "Cartridge",
+            data,
+            frames,
+        ),
+        SystemType::N64 => run("N64", emu_n64::N64System::new(), "Cartridge", data, frames),
+    }
+}
+
+/// Mount media and time `frames` calls to `step_frame`
+pub fn run<S>(
+    system: &str,
+    mut sys: S,
+    mount_point: &str,
+    data: &[u8],
+    frames: u32,
+) -> Result<BenchReport, String>
+where
+    S: System,
+    S::Error: Display,
+{
+    if frames == 0 {
+        return Err("At least one frame must be run".to_string());
+    }
+    sys.mount(mount_point, data)
+        .map_err(|e| format!("Mount failed: {}", e))?;
+
+    let mut min = f64::INFINITY;
+    let mut max = 0.0f64;
+    let start = Instant::now();
+    for i in 0..frames {
+        let frame_start = Instant::now();
+        let frame = sys
+            .step_frame()
+            .map_err(|e| format!("Frame {} failed: {}", i, e))?;
+        let ms = frame_start.elapsed().as_secs_f64() * 1000.0;
+        // Hand the buffer back like the frontend does after presenting it
+        sys.recycle_frame(frame);
+        min = min.min(ms);
+        max = max.max(ms);
+    }
+    let total_ms = start.elapsed().as_secs_f64() * 1000.0;
+
+    let target_fps = sys.frame_rate();
+    let real_s = total_ms / 1000.0;
+    let emulated_s = frames as f64 / target_fps;
+    Ok(BenchReport {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        profile: if cfg!(debug_assertions) {
+            "debug".to_string()
+        } else {
+            "release".to_string()
+        },
+        system: system.to_string(),
+        frames,
+        total_ms,
+        avg_frame_ms: total_ms / frames as f64,
+        min_frame_ms: min,
+        max_frame_ms: max,
+        target_fps,
+        fps: frames as f64 / real_s,
+        speed: emulated_s / real_s,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bench_nes_rom() {
+        let report = run_rom(include_bytes!("../../../../test_roms/nes/test.nes"), 5).unwrap();
+        assert_eq!(report.system, "NES");
+        assert_eq!(report.frames, 5);
+        assert!(report.min_frame_ms <= report.avg_frame_ms);
+        assert!(report.avg_frame_ms <= report.max_frame_ms);
+        assert!(report.speed > 0.0);
+        assert_eq!(
+            report.target_fps,
+            emu_nes::NesSystem::default().frame_rate()
+        );
+    }
+
+    #[test]
+    fn test_bench_rejects_bad_input() {
+        assert!(run_rom(&[0; 4], 5).is_err());
+        assert!(run_rom(include_bytes!("../../../../test_roms/gb/test.gb"), 0).is_err());
+    }
+}
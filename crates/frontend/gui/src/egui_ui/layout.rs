@@ -1,9 +1,10 @@
 //! Main egui application layout
 
-use super::menu_bar::MenuBar;
+use super::menu_bar::{MenuAction, MenuBar};
 use super::property_pane::PropertyPane;
 use super::status_bar::StatusBarWidget;
 use super::tabs::TabManager;
+use crate::archive;
 use crate::settings::ScalingMode;
 use egui::{CentralPanel, Context, SidePanel, TopBottomPanel};
 
@@ -35,6 +36,9 @@ pub struct EguiApp {
 
     /// Frame texture for emulator display
     pub emulator_texture: Option<egui::TextureHandle>,
+
+    /// Member paths of an archive holding several ROMs, while one is picked
+    archive_chooser: Option<Vec<String>>,
 }
 
 impl EguiApp {
@@ -45,9 +49,17 @@ impl EguiApp {
             property_pane: PropertyPane::new(),
             status_bar: StatusBarWidget::new(),
             emulator_texture: None,
+            archive_chooser: None,
         }
     }
 
+    /// Ask which ROM of an archive to open
+    ///
+    /// The chosen member path is opened like a recent file.
+    pub fn show_archive_chooser(&mut self, members: Vec<String>) {
+        self.archive_chooser = Some(members);
+    }
+
     /// Update the emulator display texture
     pub fn update_emulator_texture(
         &mut self,
@@ -141,6 +153,42 @@ impl EguiApp {
                 self.tab_manager
                     .ui(ui, &self.emulator_texture, scaling_mode);
             });
+
+        self.archive_chooser_ui(ctx);
+    }
+
+    fn archive_chooser_ui(&mut self, ctx: &Context) {
+        let Some(members) = &self.archive_chooser else {
+            return;
+        };
+
+        let mut open = true;
+        let mut chosen = None;
+        egui::Window::new("Choose ROM")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label("This archive contains several ROMs:");
+                egui::ScrollArea::vertical()
+                    .max_height(300.0)
+                    .show(ui, |ui| {
+                        for member in members {
+                            let name = archive::rom_file_name(member).to_string_lossy();
+                            if ui.button(name).clicked() {
+                                chosen = Some(member.clone());
+                            }
+                        }
+                    });
+            });
+
+        if let Some(member) = chosen {
+            self.menu_bar.pending_action = Some(MenuAction::OpenRecentFile(member));
+            self.archive_chooser = None;
+        } else if !open {
+            self.archive_chooser = None;
+        }
     }
 }
 
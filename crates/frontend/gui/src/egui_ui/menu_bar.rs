@@ -12,12 +12,17 @@ pub enum MenuAction {
     ClearRecentFiles,
     OpenProject,
     SaveProject,
+    SaveDiskChanges,
     Exit,
 
     // Emulation menu
     Reset,
     Pause,
     Resume,
+    RecordMovieFromReset,
+    RecordMovieFromState,
+    PlayMovie,
+    StopMovie,
 
     // View menu
     Screenshot,
@@ -28,6 +33,8 @@ pub enum MenuAction {
     FullscreenWithGui,
     ShowLog,
     ShowDebug,
+    ShowSaveStates,
+    ToggleInputDisplay,
 
     // Help menu
     ShowHelp,
@@ -37,6 +44,7 @@ pub enum MenuAction {
 pub struct MenuBar {
     pub pending_action: Option<MenuAction>,
     pub recent_files: Vec<String>, // List of recent files to display
+    pub show_input_display: bool,  // Checked state of the input display toggle
 }
 
 impl MenuBar {
@@ -44,6 +52,7 @@ impl MenuBar {
         Self {
             pending_action: None,
             recent_files: Vec::new(),
+            show_input_display: false,
         }
     }
 
@@ -121,6 +130,14 @@ impl MenuBar {
                     self.pending_action = Some(MenuAction::SaveProject);
                     ui.close();
                 }
+                if ui
+                    .button("💽 Save Disk Changes")
+                    .on_hover_text("Write modified PC disk images back to their files")
+                    .clicked()
+                {
+                    self.pending_action = Some(MenuAction::SaveDiskChanges);
+                    ui.close();
+                }
                 ui.separator();
                 if ui
                     .button("🚪 Exit (ESC)")
@@ -158,6 +175,41 @@ impl MenuBar {
                     self.pending_action = Some(MenuAction::Resume);
                     ui.close();
                 }
+                ui.separator();
+                ui.menu_button("🎬 Input Movie", |ui| {
+                    if ui
+                        .button("⏺️ Record from Reset")
+                        .on_hover_text("Reset the system and record every frame's input")
+                        .clicked()
+                    {
+                        self.pending_action = Some(MenuAction::RecordMovieFromReset);
+                        ui.close();
+                    }
+                    if ui
+                        .button("⏺️ Record from Current State")
+                        .on_hover_text("Record input starting from a snapshot of the running game")
+                        .clicked()
+                    {
+                        self.pending_action = Some(MenuAction::RecordMovieFromState);
+                        ui.close();
+                    }
+                    if ui
+                        .button("▶️ Play Movie...")
+                        .on_hover_text("Replay a recorded .hmovie file")
+                        .clicked()
+                    {
+                        self.pending_action = Some(MenuAction::PlayMovie);
+                        ui.close();
+                    }
+                    if ui
+                        .button("⏹️ Stop Movie")
+                        .on_hover_text("Stop playback, or stop recording and save the movie")
+                        .clicked()
+                    {
+                        self.pending_action = Some(MenuAction::StopMovie);
+                        ui.close();
+                    }
+                });
             });
 
             // View menu
@@ -226,6 +278,22 @@ impl MenuBar {
                     self.pending_action = Some(MenuAction::ShowDebug);
                     ui.close();
                 }
+                if ui
+                    .button("💾 Save States")
+                    .on_hover_text("Manage save states for all games")
+                    .clicked()
+                {
+                    self.pending_action = Some(MenuAction::ShowSaveStates);
+                    ui.close();
+                }
+                if ui
+                    .selectable_label(self.show_input_display, "🎮 Input Display")
+                    .on_hover_text("Show live controller button presses on screen (NES)")
+                    .clicked()
+                {
+                    self.pending_action = Some(MenuAction::ToggleInputDisplay);
+                    ui.close();
+                }
 
                 ui.separator();
 
@@ -15,4 +15,6 @@ mod tabs;
 
 pub use layout::EguiApp;
 pub use property_pane::{InputConfigSource, PropertyAction};
-pub use tabs::{PcConfigInfo, Tab, TabAction};
+pub use tabs::{
+    EmulatorPointer, InputMappingsInfo, PcConfigInfo, SaveStateListItem, Tab, TabAction,
+};
@@ -1,7 +1,13 @@
 //! Right-side property pane with collapsible sections
 
 use crate::display_filter::DisplayFilter;
+use crate::input::{GamepadAssignment, PlayerGamepad};
+use crate::settings::PacingMode;
 use egui::{ScrollArea, Ui};
+use emu_atari2600::{TiaRevision, VideoStandard};
+use emu_core::apu::TimingMode;
+use emu_core::frame_pool::FramePoolStats;
+use emu_pc::CpuSpeed;
 
 /// Source of input configuration (global config.json or project-specific)
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -13,13 +19,38 @@ pub enum InputConfigSource {
 /// Actions that can be triggered from the property pane
 #[derive(Debug, Clone, PartialEq)]
 pub enum PropertyAction {
-    SaveState(u8),                     // Slot number 1-5
-    LoadState(u8),                     // Slot number 1-5
-    MountFile(String),                 // Mount point ID
-    EjectFile(String),                 // Mount point ID
-    ConfigureInput,                    // Open input configuration dialog
-    SetInputSource(InputConfigSource), // Switch between global/project input config
-    SetRenderer(String),               // Switch to specified renderer
+    SaveState(u8),                        // Slot number 1-5
+    LoadState(u8),                        // Slot number 1-5
+    MountFile(String),                    // Mount point ID
+    MountFolder(String),                  // Mount point ID (PC host folder drive)
+    EjectFile(String),                    // Mount point ID
+    ConfigureInput,                       // Open input configuration dialog
+    SetInputSource(InputConfigSource),    // Switch between global/project input config
+    SetRenderer(String),                  // Switch to specified renderer
+    SetAtariColorSwitch(bool),            // Atari 2600 TV Type switch (true = Color)
+    SetAtariVideoStandard(VideoStandard), // Atari 2600 NTSC, PAL or SECAM palette
+    SetAtariTiaRevision(TiaRevision),     // Atari 2600 early or late TIA chip
+    SetAtariBeamAccurate(bool), // Atari 2600 TIA drawn with the beam (false = per scanline)
+    SetPcCpuSpeed(CpuSpeed),    // PC CPU clock speed (model default, fixed or unlimited)
+    SetNesTiming(TimingMode),   // NES NTSC, PAL or Dendy timing
+    SetNesSpriteOverflowBug(bool), // NES hardware sprite overflow bug emulation
+    SetNesScanlinePpu(bool),    // NES scanline-accurate PPU scrolling
+    SetNesZapper(bool),         // NES Zapper light gun in port 2 (false = controller)
+    SetNesFourScore(bool),      // NES Four Score adapter for players 3 and 4
+    ExportAtariCartRam,         // Save Atari 2600 cartridge RAM to a file
+    ImportAtariCartRam,         // Load Atari 2600 cartridge RAM from a file
+}
+
+/// Fixed PC CPU speeds offered in the CPU speed selector (MHz)
+const PC_CPU_SPEED_PRESETS: [f64; 8] = [4.77, 8.0, 10.0, 12.0, 16.0, 25.0, 33.0, 66.0];
+
+/// Display label for a PC CPU speed setting
+fn pc_cpu_speed_label(speed: CpuSpeed) -> String {
+    match speed {
+        CpuSpeed::Model => "Model default".to_string(),
+        CpuSpeed::Fixed(mhz) => format!("{} MHz", mhz),
+        CpuSpeed::Unlimited => "Unlimited (turbo)".to_string(),
+    }
 }
 
 pub struct PropertyPane {
@@ -39,12 +70,17 @@ pub struct PropertyPane {
     // Target FPS from system timing mode (for sparkline reference line)
     pub target_fps: f32,
 
+    // Frame buffer reuse of systems with a frame pool
+    pub frame_pool_stats: Option<FramePoolStats>,
+
     // PC-specific BDA values (only populated for PC system)
     pub pc_bda_values: Option<PcBdaValues>,
 
     // Settings
     pub display_filter: DisplayFilter,
     pub emulation_speed_percent: i32, // 0-400
+    pub master_volume_percent: i32,   // 0-200
+    pub pacing_mode: PacingMode,
 
     // Input configuration (can be global or project-specific)
     pub input_config_source: InputConfigSource, // Global or Project
@@ -54,10 +90,28 @@ pub struct PropertyPane {
     pub mouse_sensitivity: f32,
     pub num_gamepads_detected: usize,
     pub num_joysticks_detected: usize,
+    pub gamepad_names: Vec<String>, // Connected gamepads, in connection order
+    pub player_gamepads: [PlayerGamepad; 4],
 
     // PC-specific settings (only shown for PC system)
     pub pc_cpu_model: Option<String>,
     pub pc_memory_kb: Option<u32>,
+    pub pc_cpu_speed: Option<CpuSpeed>,
+
+    // NES-specific settings (only shown for NES system)
+    pub nes_timing: Option<TimingMode>,
+    pub nes_sprite_overflow_bug: Option<bool>,
+    pub nes_scanline_ppu: Option<bool>,
+    pub nes_zapper: Option<bool>,
+    pub nes_four_score: Option<bool>,
+
+    // Atari 2600-specific settings (only shown for Atari 2600 system)
+    pub atari_color_switch: Option<bool>,
+    pub atari_video_standard: Option<VideoStandard>,
+    pub atari_tia_revision: Option<TiaRevision>,
+    pub atari_beam_accurate: Option<bool>,
+    /// Size of the cartridge's Superchip / CBS RAM Plus, if it has any
+    pub atari_cart_ram_size: Option<usize>,
 
     // Mount points
     pub mount_points: Vec<MountPoint>,
@@ -104,18 +158,34 @@ impl PropertyPane {
             available_renderers: vec!["Software".to_string()],
             fps_history: Vec::with_capacity(60),
             target_fps: 60.0,
+            frame_pool_stats: None,
             pc_bda_values: None,
             display_filter: DisplayFilter::None,
             emulation_speed_percent: 100,
+            master_volume_percent: 100,
+            pacing_mode: PacingMode::default(),
             input_config_source: InputConfigSource::Global,
             player1_enabled: true,
-            player2_enabled: false,
+            player2_enabled: true,
             mouse_enabled: false,
             mouse_sensitivity: 1.0,
             num_gamepads_detected: 0,
             num_joysticks_detected: 0,
+            gamepad_names: Vec::new(),
+            player_gamepads: Default::default(),
             pc_cpu_model: None,
             pc_memory_kb: None,
+            pc_cpu_speed: None,
+            nes_timing: None,
+            nes_sprite_overflow_bug: None,
+            nes_scanline_ppu: None,
+            nes_zapper: None,
+            nes_four_score: None,
+            atari_color_switch: None,
+            atari_video_standard: None,
+            atari_tia_revision: None,
+            atari_beam_accurate: None,
+            atari_cart_ram_size: None,
             mount_points: Vec::new(),
             metrics_open: true,
             settings_open: true,
@@ -247,6 +317,21 @@ impl PropertyPane {
                             });
                         }
 
+                        if let Some(stats) = self.frame_pool_stats {
+                            ui.add_space(3.0);
+                            ui.horizontal(|ui| {
+                                ui.label("Frame Buffers:");
+                                ui.label(format!(
+                                    "{:.1}% reused ({} allocated)",
+                                    stats.reuse_ratio() * 100.0,
+                                    stats.allocated
+                                ))
+                                .on_hover_text(
+                                    "Frames built in a recycled buffer instead of a new allocation",
+                                );
+                            });
+                        }
+
                         // Display PC-specific BDA values if available
                         if let Some(ref bda) = self.pc_bda_values {
                             ui.add_space(8.0);
@@ -405,6 +490,40 @@ impl PropertyPane {
                                     });
                             }
 
+                            // PC-specific settings: CPU speed (independent of model)
+                            if let Some(current) = self.pc_cpu_speed {
+                                let mut selected = current;
+                                ui.horizontal(|ui| {
+                                    ui.label("CPU Speed:");
+                                });
+                                egui::ComboBox::from_id_salt("cpu_speed_select")
+                                    .selected_text(pc_cpu_speed_label(current))
+                                    .show_ui(ui, |ui| {
+                                        ui.selectable_value(
+                                            &mut selected,
+                                            CpuSpeed::Model,
+                                            pc_cpu_speed_label(CpuSpeed::Model),
+                                        );
+                                        for mhz in PC_CPU_SPEED_PRESETS {
+                                            ui.selectable_value(
+                                                &mut selected,
+                                                CpuSpeed::Fixed(mhz),
+                                                pc_cpu_speed_label(CpuSpeed::Fixed(mhz)),
+                                            );
+                                        }
+                                        ui.selectable_value(
+                                            &mut selected,
+                                            CpuSpeed::Unlimited,
+                                            pc_cpu_speed_label(CpuSpeed::Unlimited),
+                                        );
+                                    });
+                                if selected != current {
+                                    self.pc_cpu_speed = Some(selected);
+                                    self.pending_action =
+                                        Some(PropertyAction::SetPcCpuSpeed(selected));
+                                }
+                            }
+
                             // PC-specific settings: Memory
                             if let Some(ref mut memory_kb) = self.pc_memory_kb {
                                 ui.horizontal(|ui| {
@@ -431,6 +550,187 @@ impl PropertyPane {
                             ui.add_space(3.0);
                         }
 
+                        // NES-specific settings: timing (region) and PPU accuracy
+                        if let Some(timing) = self.nes_timing {
+                            ui.add_space(5.0);
+                            ui.separator();
+                            ui.label(egui::RichText::new("NES Console").strong());
+
+                            ui.horizontal(|ui| {
+                                ui.label("Timing:");
+                                for (option, name) in [
+                                    (TimingMode::Ntsc, "NTSC"),
+                                    (TimingMode::Pal, "PAL"),
+                                    (TimingMode::Dendy, "Dendy"),
+                                ] {
+                                    if ui.radio(timing == option, name).clicked()
+                                        && timing != option
+                                    {
+                                        self.pending_action =
+                                            Some(PropertyAction::SetNesTiming(option));
+                                    }
+                                }
+                            });
+
+                            if let Some(scanline) = self.nes_scanline_ppu {
+                                ui.horizontal(|ui| {
+                                    ui.label("Rendering:");
+                                    if ui.radio(!scanline, "Frame").clicked() && scanline {
+                                        self.pending_action =
+                                            Some(PropertyAction::SetNesScanlinePpu(false));
+                                    }
+                                    if ui
+                                        .radio(scanline, "Scanline")
+                                        .on_hover_text(
+                                            "Scroll each line like the PPU does (for status bars split with sprite 0 hit or mid-frame scroll writes)",
+                                        )
+                                        .clicked()
+                                        && !scanline
+                                    {
+                                        self.pending_action =
+                                            Some(PropertyAction::SetNesScanlinePpu(true));
+                                    }
+                                });
+                            }
+                            if let Some(mut bug) = self.nes_sprite_overflow_bug {
+                                if ui
+                                    .checkbox(&mut bug, "Sprite overflow bug")
+                                    .on_hover_text(
+                                        "Emulate the PPU's buggy overflow check, which gives false positives and negatives like real hardware",
+                                    )
+                                    .changed()
+                                {
+                                    self.pending_action =
+                                        Some(PropertyAction::SetNesSpriteOverflowBug(bug));
+                                }
+                            }
+                            if let Some(mut zapper) = self.nes_zapper {
+                                if ui
+                                    .checkbox(&mut zapper, "Zapper in port 2")
+                                    .on_hover_text(
+                                        "Light gun aimed with the mouse: left click fires at the cursor, right click fires off screen",
+                                    )
+                                    .changed()
+                                {
+                                    self.pending_action =
+                                        Some(PropertyAction::SetNesZapper(zapper));
+                                }
+                            }
+                            if let Some(mut four_score) = self.nes_four_score {
+                                if ui
+                                    .checkbox(&mut four_score, "Four Score (4 players)")
+                                    .on_hover_text(
+                                        "Four-player adapter: players 3 and 4 use their key mappings from the Input tab",
+                                    )
+                                    .changed()
+                                {
+                                    self.pending_action =
+                                        Some(PropertyAction::SetNesFourScore(four_score));
+                                }
+                            }
+
+                            ui.add_space(5.0);
+                        }
+
+                        // Atari 2600-specific settings: TV Type switch, video standard,
+                        // TIA revision and rendering accuracy
+                        if let (Some(color), Some(standard), Some(revision), Some(beam)) = (
+                            self.atari_color_switch,
+                            self.atari_video_standard,
+                            self.atari_tia_revision,
+                            self.atari_beam_accurate,
+                        ) {
+                            ui.add_space(5.0);
+                            ui.separator();
+                            ui.label(egui::RichText::new("Atari 2600 Console").strong());
+
+                            ui.horizontal(|ui| {
+                                ui.label("TV Type:");
+                                if ui.radio(color, "Color").clicked() && !color {
+                                    self.pending_action =
+                                        Some(PropertyAction::SetAtariColorSwitch(true));
+                                }
+                                if ui.radio(!color, "B&W").clicked() && color {
+                                    self.pending_action =
+                                        Some(PropertyAction::SetAtariColorSwitch(false));
+                                }
+                            });
+                            ui.horizontal(|ui| {
+                                ui.label("Video:");
+                                for (option, name) in [
+                                    (VideoStandard::Ntsc, "NTSC"),
+                                    (VideoStandard::Pal, "PAL"),
+                                    (VideoStandard::Secam, "SECAM"),
+                                ] {
+                                    if ui.radio(standard == option, name).clicked()
+                                        && standard != option
+                                    {
+                                        self.pending_action =
+                                            Some(PropertyAction::SetAtariVideoStandard(option));
+                                    }
+                                }
+                            });
+                            ui.horizontal(|ui| {
+                                ui.label("TIA:");
+                                if ui
+                                    .radio(revision == TiaRevision::Early, "Early")
+                                    .on_hover_text(
+                                        "Early chip revision: hues drift with brightness and playfield writes land one pixel late",
+                                    )
+                                    .clicked()
+                                    && revision != TiaRevision::Early
+                                {
+                                    self.pending_action =
+                                        Some(PropertyAction::SetAtariTiaRevision(TiaRevision::Early));
+                                }
+                                if ui.radio(revision == TiaRevision::Late, "Late").clicked()
+                                    && revision != TiaRevision::Late
+                                {
+                                    self.pending_action =
+                                        Some(PropertyAction::SetAtariTiaRevision(TiaRevision::Late));
+                                }
+                            });
+                            ui.horizontal(|ui| {
+                                ui.label("Rendering:");
+                                if ui.radio(!beam, "Scanline").clicked() && beam {
+                                    self.pending_action =
+                                        Some(PropertyAction::SetAtariBeamAccurate(false));
+                                }
+                                if ui
+                                    .radio(beam, "Beam")
+                                    .on_hover_text(
+                                        "Draw each pixel as the beam reaches it (slower, for games that change graphics mid-scanline)",
+                                    )
+                                    .clicked()
+                                    && !beam
+                                {
+                                    self.pending_action =
+                                        Some(PropertyAction::SetAtariBeamAccurate(true));
+                                }
+                            });
+                            if let Some(size) = self.atari_cart_ram_size {
+                                ui.horizontal(|ui| {
+                                    ui.label(format!("Cartridge RAM: {} bytes", size));
+                                    if ui
+                                        .button("Export...")
+                                        .on_hover_text("Save high scores and progress kept in cartridge RAM to a file")
+                                        .clicked()
+                                    {
+                                        self.pending_action =
+                                            Some(PropertyAction::ExportAtariCartRam);
+                                    }
+                                    if ui.button("Import...").clicked() {
+                                        self.pending_action =
+                                            Some(PropertyAction::ImportAtariCartRam);
+                                    }
+                                });
+                            }
+
+                            ui.add_space(5.0);
+                            ui.separator();
+                            ui.add_space(3.0);
+                        }
+
                         // Display filter section
                         ui.label(egui::RichText::new("Display Filter").strong())
                             .on_hover_text("Apply CRT/LCD display simulation effects");
@@ -523,6 +823,60 @@ impl PropertyPane {
                             }
                         });
 
+                        ui.add_space(8.0);
+                        ui.label(egui::RichText::new("Master Volume").strong())
+                            .on_hover_text(
+                                "Output volume for all systems (each system's level is normalized first)",
+                            );
+                        ui.add_space(3.0);
+                        ui.horizontal(|ui| {
+                            ui.add(
+                                egui::Slider::new(&mut self.master_volume_percent, 0..=200)
+                                    .suffix("%")
+                                    .show_value(true),
+                            );
+                            let muted = self.master_volume_percent == 0;
+                            if ui
+                                .button(if muted { "🔇" } else { "🔊" })
+                                .on_hover_text(if muted { "Unmute" } else { "Mute" })
+                                .clicked()
+                            {
+                                self.master_volume_percent = if muted { 100 } else { 0 };
+                            }
+                        });
+
+                        ui.add_space(8.0);
+                        ui.label(egui::RichText::new("Frame Pacing").strong())
+                            .on_hover_text("How emulated frames are synchronized with the host");
+                        ui.add_space(3.0);
+                        egui::ComboBox::from_id_salt("pacing_mode")
+                            .selected_text(self.pacing_mode.name())
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(
+                                    &mut self.pacing_mode,
+                                    PacingMode::Vsync,
+                                    PacingMode::Vsync.name(),
+                                )
+                                .on_hover_text("Present on the display's refresh (default)");
+                                ui.selectable_value(
+                                    &mut self.pacing_mode,
+                                    PacingMode::AudioClock,
+                                    PacingMode::AudioClock.name(),
+                                )
+                                .on_hover_text(
+                                    "VSync off; the sound card's clock drives emulation. \
+                                     Smoothest audio, may tear",
+                                );
+                                ui.selectable_value(
+                                    &mut self.pacing_mode,
+                                    PacingMode::Timer,
+                                    PacingMode::Timer.name(),
+                                )
+                                .on_hover_text(
+                                    "VSync off; one frame per tick at the system's rate (60/50 Hz)",
+                                );
+                            });
+
                         // Input Configuration section
                         ui.add_space(10.0);
                         ui.separator();
@@ -581,9 +935,54 @@ impl PropertyPane {
                         ui.checkbox(&mut self.player1_enabled, "Player 1 Enabled");
                         ui.checkbox(&mut self.player2_enabled, "Player 2 Enabled");
 
+                        // Gamepad per player
+                        for (index, pad) in self.player_gamepads.iter_mut().take(2).enumerate() {
+                            ui.add_space(5.0);
+                            ui.label(format!("Player {} Gamepad:", index + 1));
+                            let selected = match &pad.device {
+                                GamepadAssignment::Auto => "Auto".to_string(),
+                                GamepadAssignment::Disabled => "None".to_string(),
+                                GamepadAssignment::Named(name) => name.clone(),
+                            };
+                            egui::ComboBox::from_id_salt(("player_gamepad", index))
+                                .selected_text(selected)
+                                .show_ui(ui, |ui| {
+                                    ui.selectable_value(
+                                        &mut pad.device,
+                                        GamepadAssignment::Auto,
+                                        "Auto",
+                                    )
+                                    .on_hover_text("Next connected gamepad");
+                                    ui.selectable_value(
+                                        &mut pad.device,
+                                        GamepadAssignment::Disabled,
+                                        "None",
+                                    );
+                                    for name in &self.gamepad_names {
+                                        ui.selectable_value(
+                                            &mut pad.device,
+                                            GamepadAssignment::Named(name.clone()),
+                                            name,
+                                        );
+                                    }
+                                });
+                            ui.horizontal(|ui| {
+                                ui.label("Stick threshold:");
+                                ui.add(
+                                    egui::Slider::new(&mut pad.axis_threshold, 0.1..=0.9)
+                                        .step_by(0.05)
+                                        .show_value(true),
+                                )
+                                .on_hover_text("How far the stick must tilt to count as the D-pad");
+                            });
+                        }
+
                         // Mouse configuration
                         ui.add_space(8.0);
-                        ui.checkbox(&mut self.mouse_enabled, "Mouse Input Enabled");
+                        ui.checkbox(&mut self.mouse_enabled, "Mouse Input Enabled")
+                            .on_hover_text(
+                                "PC: once a program loads the mouse driver, click the picture or press the capture key (F12 by default) to capture the mouse",
+                            );
                         if self.mouse_enabled {
                             ui.add_space(3.0);
                             ui.horizontal(|ui| {
@@ -660,6 +1059,23 @@ impl PropertyPane {
                                         self.pending_action =
                                             Some(PropertyAction::MountFile(mount.id.clone()));
                                     }
+                                    if mount.mounted_file.is_none()
+                                        && self.system_name == "pc"
+                                        && matches!(
+                                            mount.id.as_str(),
+                                            "FloppyA" | "FloppyB" | "HardDrive"
+                                        )
+                                        && ui
+                                            .button("Folder...")
+                                            .on_hover_text(format!(
+                                                "Use a host folder as {}",
+                                                mount.name
+                                            ))
+                                            .clicked()
+                                    {
+                                        self.pending_action =
+                                            Some(PropertyAction::MountFolder(mount.id.clone()));
+                                    }
                                 });
                                 ui.add_space(2.0);
                             }
@@ -1,8 +1,10 @@
 //! Tab manager for left panel
 
-use crate::settings::ScalingMode;
+use crate::settings::{PlayerMappings, ScalingMode};
 use crate::system_adapter::SystemDebugInfo;
 use egui::{ScrollArea, TextureHandle, Ui};
+use emu_core::logging::LogFilter;
+use std::collections::{BTreeMap, HashMap};
 
 /// Application version constant
 const APP_VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -15,13 +17,72 @@ pub enum Tab {
     Help,
     Debug,
     PcConfig, // PC-specific configuration tab (DBA: Disk/BIOS/Adapter)
+    SaveStates,
+    InputMappings,
     About,
 }
 
 /// Actions that can be triggered from tabs
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum TabAction {
-    CreateNewProject(String), // String is the system name
+    CreateNewProject(String),    // String is the system name
+    LoadSaveState(String, u8),   // ROM hash, slot
+    ExportSaveState(String, u8), // ROM hash, slot
+    DeleteSaveState(String, u8), // ROM hash, slot
+    ImportSaveState,
+    RefreshSaveStates,
+    CaptureDisplayList,
+    SaveDisplayListDump,
+    SaveInputMappings, // Store `TabManager::input_mappings` in the settings
+}
+
+/// A save state shown in the save-state manager tab
+#[derive(Clone)]
+pub struct SaveStateListItem {
+    pub rom_hash: String,
+    pub slot: u8,
+    pub game: String,
+    pub system: String,
+    pub timestamp: u64,
+    pub play_time_secs: u64,
+    pub thumbnail: Option<([usize; 2], Vec<u8>)>, // Size and RGBA pixels
+}
+
+/// Systems with a controller mapping, as (settings key, display name)
+const MAPPED_SYSTEMS: [(&str, &str); 5] = [
+    ("nes", "NES"),
+    ("gameboy", "Game Boy"),
+    ("snes", "SNES"),
+    ("atari2600", "Atari 2600"),
+    ("n64", "N64"),
+];
+
+/// Keyboard mapping profiles edited in the input mappings tab
+#[derive(Clone, Default)]
+pub struct InputMappingsInfo {
+    pub profiles: BTreeMap<String, PlayerMappings>,
+    /// Profile of each system (absent = global keys)
+    pub system_mappings: BTreeMap<String, String>,
+    /// Profile of each game by ROM hash (absent = system default)
+    pub game_mappings: BTreeMap<String, String>,
+    /// Global player mappings, copied into new profiles
+    pub global: PlayerMappings,
+    /// Frames each turbo button stays pressed, then released
+    pub turbo_frames_per_toggle: u32,
+    /// ROM hash and title of the running game
+    pub current_game: Option<(String, String)>,
+}
+
+/// Mouse over the emulator display
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EmulatorPointer {
+    /// Position from 0.0 to 1.0 across and down the picture
+    pub x: f32,
+    pub y: f32,
+    pub primary_down: bool,
+    pub secondary_down: bool,
+    /// Primary button went down this frame
+    pub primary_pressed: bool,
 }
 
 /// PC-specific configuration information for the DBA tab
@@ -49,6 +110,26 @@ pub struct TabManager {
     pub selected_system: String,
     pub pending_action: Option<TabAction>,
     pub pc_config_info: Option<PcConfigInfo>,
+    pub save_states_visible: bool,
+    pub save_state_items: Vec<SaveStateListItem>,
+    pub current_rom_hash: Option<String>,
+    pub input_mappings_visible: bool,
+    pub input_mappings: InputMappingsInfo,
+    /// Profile shown in the input mappings editor
+    selected_mapping_profile: Option<String>,
+    /// Name typed for a new mapping profile
+    new_mapping_profile: String,
+    save_state_textures: HashMap<(String, u8), TextureHandle>,
+    /// Captured N64 display list shown in the debug tab
+    pub display_list: Option<emu_n64::DisplayListCapture>,
+    /// Framebuffer preview and the step position it shows
+    display_list_texture: Option<(usize, TextureHandle)>,
+    /// Filter applied to the log viewer
+    log_filter: LogFilter,
+    /// Keep the log viewer scrolled to the newest record
+    log_auto_scroll: bool,
+    /// Mouse over the emulator display this frame (light gun aiming)
+    pub emulator_pointer: Option<EmulatorPointer>,
 }
 
 impl TabManager {
@@ -65,6 +146,19 @@ impl TabManager {
             selected_system: "NES".to_string(),
             pending_action: None,
             pc_config_info: None,
+            save_states_visible: false,
+            save_state_items: Vec::new(),
+            current_rom_hash: None,
+            input_mappings_visible: false,
+            input_mappings: InputMappingsInfo::default(),
+            selected_mapping_profile: None,
+            new_mapping_profile: String::new(),
+            save_state_textures: HashMap::new(),
+            display_list: None,
+            display_list_texture: None,
+            log_filter: LogFilter::default(),
+            log_auto_scroll: true,
+            emulator_pointer: None,
         }
     }
 
@@ -100,6 +194,36 @@ impl TabManager {
         self.active_tab = Tab::About;
     }
 
+    pub fn show_save_states_tab(&mut self) {
+        self.save_states_visible = true;
+        self.active_tab = Tab::SaveStates;
+    }
+
+    /// Replace the save-state manager list (thumbnails are re-uploaded lazily)
+    pub fn update_save_state_items(
+        &mut self,
+        items: Vec<SaveStateListItem>,
+        current_rom_hash: Option<String>,
+    ) {
+        self.save_state_items = items;
+        self.current_rom_hash = current_rom_hash;
+        self.save_state_textures.clear();
+    }
+
+    /// Open the input mappings editor on a copy of the settings
+    pub fn show_input_mappings_tab(&mut self, info: InputMappingsInfo) {
+        if !self
+            .selected_mapping_profile
+            .as_ref()
+            .is_some_and(|name| info.profiles.contains_key(name))
+        {
+            self.selected_mapping_profile = info.profiles.keys().next().cloned();
+        }
+        self.input_mappings = info;
+        self.input_mappings_visible = true;
+        self.active_tab = Tab::InputMappings;
+    }
+
     pub fn show_new_project_tab(&mut self) {
         self.new_project_visible = true;
         self.active_tab = Tab::NewProject;
@@ -109,6 +233,12 @@ impl TabManager {
         self.debug_info = Some(info);
     }
 
+    /// Show a new display list capture, starting before its first command
+    pub fn set_display_list_capture(&mut self, capture: emu_n64::DisplayListCapture) {
+        self.display_list = Some(capture);
+        self.display_list_texture = None;
+    }
+
     /// Get and clear any pending action
     pub fn take_action(&mut self) -> Option<TabAction> {
         self.pending_action.take()
@@ -184,6 +314,48 @@ impl TabManager {
                 }
             }
 
+            if self.save_states_visible {
+                ui.selectable_value(&mut self.active_tab, Tab::SaveStates, "💾 Save States");
+                // Use a colored button for the close icon to ensure visibility
+                let close_button = egui::Button::new(
+                    egui::RichText::new("✖").color(egui::Color32::from_rgb(220, 220, 220)),
+                )
+                .small();
+                if ui
+                    .add(close_button)
+                    .on_hover_text("Close Save States tab")
+                    .clicked()
+                {
+                    self.save_states_visible = false;
+                    if self.active_tab == Tab::SaveStates {
+                        self.active_tab = Tab::Emulator;
+                    }
+                }
+            }
+
+            if self.input_mappings_visible {
+                ui.selectable_value(
+                    &mut self.active_tab,
+                    Tab::InputMappings,
+                    "🎮 Input Mappings",
+                );
+                // Use a colored button for the close icon to ensure visibility
+                let close_button = egui::Button::new(
+                    egui::RichText::new("✖").color(egui::Color32::from_rgb(220, 220, 220)),
+                )
+                .small();
+                if ui
+                    .add(close_button)
+                    .on_hover_text("Close Input Mappings tab")
+                    .clicked()
+                {
+                    self.input_mappings_visible = false;
+                    if self.active_tab == Tab::InputMappings {
+                        self.active_tab = Tab::Emulator;
+                    }
+                }
+            }
+
             if self.about_visible {
                 ui.selectable_value(&mut self.active_tab, Tab::About, "ℹ️ About");
                 // Use a colored button for the close icon to ensure visibility
@@ -214,12 +386,15 @@ impl TabManager {
         ui.separator();
 
         // Tab content
+        self.emulator_pointer = None;
         match self.active_tab {
             Tab::Emulator => self.render_emulator_tab(ui, emulator_texture, scaling_mode),
             Tab::NewProject => self.render_new_project_tab(ui),
             Tab::Log => self.render_log_tab(ui),
             Tab::Help => self.render_help_tab(ui),
             Tab::Debug => self.render_debug_tab(ui),
+            Tab::SaveStates => self.render_save_states_tab(ui),
+            Tab::InputMappings => self.render_input_mappings_tab(ui),
             Tab::About => self.render_about_tab(ui),
             // Keep PcConfig render for backward compat, but it won't be accessible
             Tab::PcConfig => self.render_pc_config_tab(ui),
@@ -227,7 +402,7 @@ impl TabManager {
     }
 
     fn render_emulator_tab(
-        &self,
+        &mut self,
         ui: &mut Ui,
         emulator_texture: &Option<TextureHandle>,
         scaling_mode: ScalingMode,
@@ -257,7 +432,24 @@ impl TabManager {
 
                 let image = egui::Image::from_texture(texture)
                     .fit_to_exact_size(egui::vec2(display_width, display_height));
-                ui.add(image);
+                let response = ui.add(image);
+                if let Some(pos) = response.hover_pos() {
+                    let rect = response.rect;
+                    let (primary_down, secondary_down, primary_pressed) = ui.input(|i| {
+                        (
+                            i.pointer.primary_down(),
+                            i.pointer.secondary_down(),
+                            i.pointer.primary_pressed(),
+                        )
+                    });
+                    self.emulator_pointer = Some(EmulatorPointer {
+                        x: (pos.x - rect.min.x) / rect.width(),
+                        y: (pos.y - rect.min.y) / rect.height(),
+                        primary_down,
+                        secondary_down,
+                        primary_pressed,
+                    });
+                }
             } else {
                 // Welcome screen when no ROM is loaded
                 ui.vertical_centered(|ui| {
@@ -344,13 +536,17 @@ impl TabManager {
                             ui.label(egui::RichText::new("Shift+F5-F9").strong());
                             ui.label("Load state (slots 1-5)");
                             ui.end_row();
+
+                            ui.label(egui::RichText::new("Backspace").strong());
+                            ui.label("Rewind (hold)");
+                            ui.end_row();
                         });
                 });
             }
         });
     }
 
-    fn render_log_tab(&self, ui: &mut Ui) {
+    fn render_log_tab(&mut self, ui: &mut Ui) {
         use emu_core::logging::{LogCategory, LogConfig, LogLevel};
 
         let log_config = LogConfig::global();
@@ -467,10 +663,17 @@ impl TabManager {
                 ui.separator();
                 ui.add_space(10.0);
 
+                self.render_log_viewer(ui, &categories, &levels[1..]);
+
+                ui.add_space(10.0);
+                ui.separator();
+                ui.add_space(10.0);
+
                 // Info section
                 ui.heading("About Logging");
                 ui.add_space(5.0);
                 ui.label("Log messages are written to stderr by default.");
+                ui.label("Recent messages of each category are also kept for the viewer above.");
                 ui.label("Use --log-file <path> CLI argument to log to a file.");
                 ui.label("Category-specific levels override the global level.");
                 ui.label("Set a category to 'Off' to use the global level.");
@@ -492,6 +695,71 @@ impl TabManager {
             });
     }
 
+    /// Recent log records from the in-memory history, with filtering
+    fn render_log_viewer(
+        &mut self,
+        ui: &mut Ui,
+        categories: &[(emu_core::logging::LogCategory, &str)],
+        levels: &[(emu_core::logging::LogLevel, &str)],
+    ) {
+        use emu_core::logging::{LogConfig, LogLevel};
+
+        let log_config = LogConfig::global();
+
+        ui.heading("Log Viewer");
+        ui.add_space(5.0);
+
+        ui.horizontal_wrapped(|ui| {
+            for (category, name) in categories {
+                let mut include = self.log_filter.includes(*category);
+                if ui.checkbox(&mut include, *name).changed() {
+                    self.log_filter.set_includes(*category, include);
+                }
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Show up to:");
+            for (level, name) in levels {
+                ui.selectable_value(&mut self.log_filter.max_level, *level, *name);
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Filter:");
+            ui.text_edit_singleline(&mut self.log_filter.text);
+            ui.checkbox(&mut self.log_auto_scroll, "Auto-scroll");
+            if ui.button("Clear").clicked() {
+                log_config.clear_history();
+            }
+        });
+
+        let records = log_config.history(&self.log_filter);
+        ui.label(format!(
+            "{} message(s) shown (up to {} kept per category)",
+            records.len(),
+            log_config.history_capacity()
+        ));
+        ui.add_space(5.0);
+
+        ScrollArea::vertical()
+            .id_salt("log_viewer_records")
+            .max_height(300.0)
+            .auto_shrink([false, true])
+            .stick_to_bottom(self.log_auto_scroll)
+            .show(ui, |ui| {
+                for record in &records {
+                    let text = egui::RichText::new(record.to_string()).monospace();
+                    let text = match record.level {
+                        LogLevel::Error => text.color(egui::Color32::LIGHT_RED),
+                        LogLevel::Warn => text.color(egui::Color32::YELLOW),
+                        _ => text,
+                    };
+                    ui.label(text);
+                }
+            });
+    }
+
     fn render_new_project_tab(&mut self, ui: &mut Ui) {
         ui.vertical_centered(|ui| {
             ui.add_space(20.0);
@@ -651,6 +919,10 @@ impl TabManager {
                         ui.label(egui::RichText::new("Shift+F5-F9").strong().monospace());
                         ui.label("Quick load from slots 1-5");
                         ui.end_row();
+
+                        ui.label(egui::RichText::new("Backspace").strong().monospace());
+                        ui.label("Rewind recent gameplay (hold)");
+                        ui.end_row();
                     });
                 ui.add_space(10.0);
 
@@ -762,7 +1034,8 @@ impl TabManager {
             });
     }
 
-    fn render_debug_tab(&self, ui: &mut Ui) {
+    fn render_debug_tab(&mut self, ui: &mut Ui) {
+        let is_n64 = matches!(&self.debug_info, Some(info) if info.system_type == "N64");
         ScrollArea::vertical()
             .auto_shrink([false; 2])
             .show(ui, |ui| {
@@ -818,9 +1091,421 @@ impl TabManager {
                         );
                     });
                 }
+
+                if is_n64 {
+                    ui.add_space(15.0);
+                    ui.separator();
+                    self.render_display_list_debugger(ui);
+                }
+            });
+    }
+
+    /// N64 display list capture with single-stepping and framebuffer diffs
+    fn render_display_list_debugger(&mut self, ui: &mut Ui) {
+        ui.heading("Display List");
+        ui.horizontal(|ui| {
+            if ui
+                .button("📸 Capture Next")
+                .on_hover_text("Record the display list of the next RSP graphics task")
+                .clicked()
+            {
+                self.pending_action = Some(TabAction::CaptureDisplayList);
+            }
+            if self.display_list.is_some()
+                && ui
+                    .button("💾 Save Dump...")
+                    .on_hover_text("Write the decoded command listing to a text file")
+                    .clicked()
+            {
+                self.pending_action = Some(TabAction::SaveDisplayListDump);
+            }
+        });
+
+        let Some(capture) = &mut self.display_list else {
+            ui.label(egui::RichText::new("No display list captured yet").weak());
+            return;
+        };
+
+        let total = capture.steps().len();
+        ui.horizontal(|ui| {
+            if ui.button("⏮").on_hover_text("Back to the start").clicked() {
+                capture.seek(0);
+            }
+            if ui.button("◀ Step").clicked() {
+                capture.step_back();
+            }
+            if ui.button("Step ▶").clicked() {
+                capture.step_forward();
+            }
+            if ui.button("⏭").on_hover_text("Run to the end").clicked() {
+                capture.seek(total);
+            }
+            ui.label(format!("{} / {} commands", capture.position(), total));
+        });
+
+        // The command just executed and what it drew
+        let position = capture.position();
+        let last_step = position.checked_sub(1).map(|i| &capture.steps()[i]);
+        if let Some(step) = last_step {
+            let cmd = &step.command;
+            ui.label(egui::RichText::new(capture.decode(cmd)).monospace());
+            ui.label(match capture.changed_bounds(step) {
+                Some((x0, y0, x1, y1)) => format!(
+                    "{} pixels changed in ({},{})-({},{})",
+                    step.changes.len(),
+                    x0,
+                    y0,
+                    x1,
+                    y1
+                ),
+                None => "No pixels changed".to_string(),
+            });
+        }
+
+        // Framebuffer at this step, with the last command's pixels highlighted
+        if self.display_list_texture.as_ref().map(|(p, _)| *p) != Some(position) {
+            let mut rgba: Vec<u8> = capture
+                .frame()
+                .iter()
+                .flat_map(|&p| [(p >> 16) as u8, (p >> 8) as u8, p as u8, 255])
+                .collect();
+            if let Some(step) = last_step {
+                for change in &step.changes {
+                    let i = change.index as usize * 4;
+                    if let Some(pixel) = rgba.get_mut(i..i + 3) {
+                        pixel.copy_from_slice(&[255, 0, 255]);
+                    }
+                }
+            }
+            let size = [capture.width() as usize, capture.height() as usize];
+            if size[0] * size[1] * 4 == rgba.len() {
+                let image = egui::ColorImage::from_rgba_unmultiplied(size, &rgba);
+                let texture = ui.ctx().load_texture(
+                    "display_list_frame",
+                    image,
+                    egui::TextureOptions::NEAREST,
+                );
+                self.display_list_texture = Some((position, texture));
+            }
+        }
+        if let Some((_, texture)) = &self.display_list_texture {
+            ui.add(egui::Image::from_texture(texture).fit_to_exact_size(texture.size_vec2()));
+            ui.label(
+                egui::RichText::new("Magenta marks the pixels changed by the last command").weak(),
+            );
+        }
+
+        // Command listing; click a command to run up to and including it
+        let mut seek_to = None;
+        let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+        ScrollArea::vertical()
+            .id_salt("display_list_commands")
+            .max_height(300.0)
+            .show_rows(ui, row_height, total, |ui, rows| {
+                for i in rows {
+                    let cmd = &capture.steps()[i].command;
+                    let mut text = egui::RichText::new(format!(
+                        "{:5} {:08X} {}{}",
+                        i,
+                        cmd.addr,
+                        "  ".repeat(cmd.depth as usize),
+                        capture.decode(cmd)
+                    ))
+                    .monospace();
+                    if i >= position {
+                        text = text.weak();
+                    }
+                    if ui.selectable_label(i + 1 == position, text).clicked() {
+                        seek_to = Some(i + 1);
+                    }
+                }
+            });
+        if let Some(position) = seek_to {
+            capture.seek(position);
+        }
+    }
+
+    fn render_input_mappings_tab(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.heading("Input Mappings");
+            ui.add_space(10.0);
+            if ui
+                .button("💾 Save")
+                .on_hover_text("Save the profiles and assignments to config.json")
+                .clicked()
+            {
+                self.pending_action = Some(TabAction::SaveInputMappings);
+            }
+        });
+        ui.separator();
+
+        let info = &mut self.input_mappings;
+        let profile_names: Vec<String> = info.profiles.keys().cloned().collect();
+        ScrollArea::vertical()
+            .auto_shrink([false; 2])
+            .show(ui, |ui| {
+                // Which profile each system and the running game use
+                ui.label(egui::RichText::new("System Defaults").strong());
+                ui.add_space(3.0);
+                egui::Grid::new("system_mappings_grid")
+                    .num_columns(2)
+                    .spacing([15.0, 5.0])
+                    .show(ui, |ui| {
+                        for (system, name) in MAPPED_SYSTEMS {
+                            ui.label(name);
+                            profile_selector(
+                                ui,
+                                ("system_mapping", system),
+                                "Global keys",
+                                &profile_names,
+                                &mut info.system_mappings,
+                                system,
+                            );
+                            ui.end_row();
+                        }
+                        if let Some((hash, title)) = &info.current_game {
+                            ui.label(title);
+                            profile_selector(
+                                ui,
+                                "game_mapping",
+                                "System default",
+                                &profile_names,
+                                &mut info.game_mappings,
+                                hash,
+                            );
+                            ui.end_row();
+                        }
+                    });
+
+                ui.add_space(10.0);
+                ui.separator();
+                ui.label(egui::RichText::new("Turbo").strong());
+                ui.add_space(3.0);
+                ui.horizontal(|ui| {
+                    ui.label("Toggle every");
+                    ui.add(
+                        egui::DragValue::new(&mut info.turbo_frames_per_toggle)
+                            .range(1..=30)
+                            .suffix(" frames"),
+                    )
+                    .on_hover_text(
+                        "Turbo A/B press and release the button at this rate while held",
+                    );
+                });
+
+                ui.add_space(10.0);
+                ui.separator();
+                ui.label(egui::RichText::new("Profiles").strong());
+                ui.add_space(3.0);
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.new_mapping_profile)
+                            .hint_text("Profile name")
+                            .desired_width(150.0),
+                    );
+                    let name = self.new_mapping_profile.trim().to_string();
+                    if ui
+                        .add_enabled(
+                            !name.is_empty() && !info.profiles.contains_key(&name),
+                            egui::Button::new("➕ New"),
+                        )
+                        .on_hover_text("Create a profile from the global keys")
+                        .clicked()
+                    {
+                        info.profiles.insert(name.clone(), info.global.clone());
+                        self.selected_mapping_profile = Some(name);
+                        self.new_mapping_profile.clear();
+                    }
+                });
+
+                if info.profiles.is_empty() {
+                    ui.add_space(5.0);
+                    ui.label("No profiles yet; every system uses the global keys");
+                    return;
+                }
+
+                ui.add_space(5.0);
+                ui.horizontal(|ui| {
+                    let selected = self.selected_mapping_profile.clone().unwrap_or_default();
+                    egui::ComboBox::from_id_salt("mapping_profile")
+                        .selected_text(&selected)
+                        .show_ui(ui, |ui| {
+                            for name in &profile_names {
+                                ui.selectable_value(
+                                    &mut self.selected_mapping_profile,
+                                    Some(name.clone()),
+                                    name,
+                                );
+                            }
+                        });
+                    if ui
+                        .button("🗑 Delete")
+                        .on_hover_text("Systems and games using it go back to their default")
+                        .clicked()
+                    {
+                        info.profiles.remove(&selected);
+                        info.system_mappings.retain(|_, name| *name != selected);
+                        info.game_mappings.retain(|_, name| *name != selected);
+                        self.selected_mapping_profile = info.profiles.keys().next().cloned();
+                    }
+                });
+
+                let Some(profile) = self
+                    .selected_mapping_profile
+                    .as_ref()
+                    .and_then(|name| info.profiles.get_mut(name))
+                else {
+                    return;
+                };
+                ui.add_space(5.0);
+                let [p1, p2, p3, p4] = profile.players_mut();
+                let mut players = [
+                    p1.buttons_mut(),
+                    p2.buttons_mut(),
+                    p3.buttons_mut(),
+                    p4.buttons_mut(),
+                ];
+                egui::Grid::new("mapping_profile_grid")
+                    .num_columns(5)
+                    .spacing([10.0, 4.0])
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for header in ["Button", "Player 1", "Player 2", "Player 3", "Player 4"] {
+                            ui.label(egui::RichText::new(header).strong());
+                        }
+                        ui.end_row();
+
+                        for button in 0..players[0].len() {
+                            ui.label(players[0][button].0);
+                            for player in players.iter_mut() {
+                                ui.add(
+                                    egui::TextEdit::singleline(&mut *player[button].1)
+                                        .desired_width(90.0),
+                                )
+                                .on_hover_text("Key name, e.g. Z, Enter, LeftShift or Up");
+                            }
+                            ui.end_row();
+                        }
+                    });
             });
     }
 
+    fn render_save_states_tab(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.heading("Save States");
+            ui.add_space(10.0);
+            if ui
+                .button("📥 Import...")
+                .on_hover_text("Import a save state exported from another installation")
+                .clicked()
+            {
+                self.pending_action = Some(TabAction::ImportSaveState);
+            }
+            if ui.button("🔄 Refresh").clicked() {
+                self.pending_action = Some(TabAction::RefreshSaveStates);
+            }
+        });
+        ui.separator();
+
+        if self.save_state_items.is_empty() {
+            ui.vertical_centered(|ui| {
+                ui.add_space(40.0);
+                ui.label(egui::RichText::new("💾").size(48.0));
+                ui.add_space(10.0);
+                ui.heading("No Save States");
+                ui.add_space(10.0);
+                ui.label("Save states created with F5-F9 will appear here for every game");
+            });
+            return;
+        }
+
+        // Upload thumbnails that aren't cached yet
+        for item in &self.save_state_items {
+            let key = (item.rom_hash.clone(), item.slot);
+            if self.save_state_textures.contains_key(&key) {
+                continue;
+            }
+            if let Some((size, ref rgba)) = item.thumbnail {
+                let image = egui::ColorImage::from_rgba_unmultiplied(size, rgba);
+                let texture = ui.ctx().load_texture(
+                    format!("save_state_{}_{}", item.rom_hash, item.slot),
+                    image,
+                    egui::TextureOptions::NEAREST,
+                );
+                self.save_state_textures.insert(key, texture);
+            }
+        }
+
+        let mut action = None;
+        ScrollArea::vertical()
+            .auto_shrink([false; 2])
+            .show(ui, |ui| {
+                egui::Grid::new("save_states_grid")
+                    .num_columns(7)
+                    .spacing([15.0, 8.0])
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for header in ["", "Game", "System", "Slot", "Saved", "Play Time", ""] {
+                            ui.label(egui::RichText::new(header).strong());
+                        }
+                        ui.end_row();
+
+                        for item in &self.save_state_items {
+                            match self
+                                .save_state_textures
+                                .get(&(item.rom_hash.clone(), item.slot))
+                            {
+                                Some(texture) => {
+                                    ui.add(
+                                        egui::Image::from_texture(texture)
+                                            .fit_to_exact_size(texture.size_vec2()),
+                                    );
+                                }
+                                None => {
+                                    ui.label(egui::RichText::new("No preview").weak());
+                                }
+                            }
+                            ui.label(&item.game);
+                            ui.label(&item.system);
+                            ui.label(format!("{}", item.slot));
+                            ui.label(format_timestamp(item.timestamp));
+                            ui.label(format_play_time(item.play_time_secs));
+                            ui.horizontal(|ui| {
+                                let is_current =
+                                    self.current_rom_hash.as_deref() == Some(&item.rom_hash);
+                                if ui
+                                    .add_enabled(is_current, egui::Button::new("▶ Load"))
+                                    .on_disabled_hover_text("Load the matching game first")
+                                    .clicked()
+                                {
+                                    action = Some(TabAction::LoadSaveState(
+                                        item.rom_hash.clone(),
+                                        item.slot,
+                                    ));
+                                }
+                                if ui.button("📤 Export...").clicked() {
+                                    action = Some(TabAction::ExportSaveState(
+                                        item.rom_hash.clone(),
+                                        item.slot,
+                                    ));
+                                }
+                                if ui.button("🗑 Delete").clicked() {
+                                    action = Some(TabAction::DeleteSaveState(
+                                        item.rom_hash.clone(),
+                                        item.slot,
+                                    ));
+                                }
+                            });
+                            ui.end_row();
+                        }
+                    });
+            });
+
+        if action.is_some() {
+            self.pending_action = action;
+        }
+    }
+
     fn render_pc_config_tab(&self, ui: &mut Ui) {
         ScrollArea::vertical()
             .auto_shrink([false; 2])
@@ -1027,3 +1712,43 @@ impl Default for TabManager {
         Self::new()
     }
 }
+
+/// Format a Unix timestamp in local time for display
+fn format_timestamp(timestamp: u64) -> String {
+    chrono::DateTime::from_timestamp(timestamp as i64, 0)
+        .map(|t| {
+            t.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        })
+        .unwrap_or_else(|| "Unknown".to_string())
+}
+
+/// Format a play time in seconds as H:MM:SS
+fn format_play_time(secs: u64) -> String {
+    format!("{}:{:02}:{:02}", secs / 3600, (secs / 60) % 60, secs % 60)
+}
+
+/// Combo box choosing the mapping profile stored under `key` (or none)
+fn profile_selector(
+    ui: &mut Ui,
+    id: impl std::hash::Hash,
+    none_label: &str,
+    profile_names: &[String],
+    assignments: &mut BTreeMap<String, String>,
+    key: &str,
+) {
+    let mut selected = assignments.get(key).cloned();
+    egui::ComboBox::from_id_salt(id)
+        .selected_text(selected.as_deref().unwrap_or(none_label))
+        .show_ui(ui, |ui| {
+            ui.selectable_value(&mut selected, None, none_label);
+            for name in profile_names {
+                ui.selectable_value(&mut selected, Some(name.clone()), name);
+            }
+        });
+    match selected {
+        Some(name) => assignments.insert(key.to_string(), name),
+        None => assignments.remove(key),
+    };
+}
@@ -0,0 +1,211 @@
+//! Frame pacing for the main loop
+//!
+//! Decides how many emulated frames to run per loop iteration and how long to
+//! wait before the next one, according to the selected [`PacingMode`]:
+//! - **Host VSync**: buffer swaps block on the display refresh; emulated frames
+//!   follow the wall clock, so a 144 Hz display shows each frame 2-3 times
+//!   instead of missing sleeps
+//! - **Audio Clock**: vsync off; frames are emulated whenever the audio queue
+//!   drops below a few frames of samples, so the sound card's clock drives
+//!   emulation and audio never underruns or drifts
+//! - **Fixed Timer**: vsync off; a high-resolution timer ticks at the system's
+//!   frame rate (e.g. 60 Hz, 50 Hz for PAL, 59.73 Hz for the Game Boy) and each tick runs one frame per 100% of speed
+
+use crate::settings::PacingMode;
+use std::time::{Duration, Instant};
+
+/// Frames of audio the audio clock keeps queued (~50 ms at 60 Hz)
+const AUDIO_CLOCK_TARGET_FRAMES: f64 = 3.0;
+/// Most frames the audio clock runs in one iteration (refilling after a stall)
+const AUDIO_CLOCK_MAX_FRAMES: usize = 8;
+/// Shortest wait between iterations when not presenting on vsync
+const MIN_WAIT: Duration = Duration::from_millis(1);
+/// Remaining time that is spun off instead of slept, to absorb OS timer slack
+const SPIN_THRESHOLD: Duration = Duration::from_millis(2);
+/// GUI refresh rate while emulation is paused or no ROM is loaded
+const IDLE_RATE_HZ: f64 = 60.0;
+
+pub struct FramePacer {
+    mode: PacingMode,
+    /// Start of the next timer tick
+    next_deadline: Option<Instant>,
+    /// Fractional frames owed by the timer at speeds other than 100%
+    frame_credit: f64,
+}
+
+impl FramePacer {
+    pub fn new(mode: PacingMode) -> Self {
+        Self {
+            mode,
+            next_deadline: None,
+            frame_credit: 0.0,
+        }
+    }
+
+    pub fn mode(&self) -> PacingMode {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, mode: PacingMode) {
+        self.mode = mode;
+        self.next_deadline = None;
+        self.frame_credit = 0.0;
+    }
+
+    /// Whether the host swap should wait for vsync in this mode
+    pub fn wants_vsync(&self) -> bool {
+        self.mode == PacingMode::Vsync
+    }
+
+    /// Number of frames to emulate this iteration
+    ///
+    /// `behind` is how many frames emulation lags the wall clock at the
+    /// current speed; `queued_samples` is the audio queue level.
+    pub fn frames_to_step(
+        &mut self,
+        behind: usize,
+        queued_samples: usize,
+        samples_per_frame: usize,
+        speed: f64,
+    ) -> usize {
+        match self.mode {
+            PacingMode::Vsync => behind,
+            // Audio plays at 100%; other speeds fall back to the wall clock
+            PacingMode::AudioClock if speed != 1.0 => behind,
+            PacingMode::AudioClock => {
+                let target = AUDIO_CLOCK_TARGET_FRAMES * samples_per_frame.max(1) as f64;
+                let missing = (target - queued_samples as f64).max(0.0);
+                ((missing / samples_per_frame.max(1) as f64).ceil() as usize)
+                    .min(AUDIO_CLOCK_MAX_FRAMES)
+            }
+            PacingMode::Timer => {
+                self.frame_credit += speed;
+                let frames = self.frame_credit.floor();
+                self.frame_credit -= frames;
+                frames as usize
+            }
+        }
+    }
+
+    /// Block until the next loop iteration should start
+    ///
+    /// `emulating` is false while paused or without a ROM, in which case the
+    /// GUI is refreshed at a fixed idle rate in the non-vsync modes.
+    pub fn wait(
+        &mut self,
+        emulating: bool,
+        frame_rate: f64,
+        queued_samples: usize,
+        sample_rate: usize,
+    ) {
+        match self.mode {
+            PacingMode::Vsync => {}
+            PacingMode::AudioClock if emulating => {
+                let samples_per_frame = sample_rate as f64 / frame_rate;
+                let target = AUDIO_CLOCK_TARGET_FRAMES * samples_per_frame;
+                let excess = (queued_samples as f64 - target).max(0.0);
+                let wait = Duration::from_secs_f64(excess / sample_rate as f64)
+                    .clamp(MIN_WAIT, Duration::from_secs_f64(1.0 / frame_rate));
+                sleep_until(Instant::now() + wait);
+            }
+            PacingMode::AudioClock => self.wait_for_tick(IDLE_RATE_HZ),
+            PacingMode::Timer => {
+                self.wait_for_tick(if emulating { frame_rate } else { IDLE_RATE_HZ })
+            }
+        }
+    }
+
+    /// Sleep until the next timer tick at `rate_hz`
+    fn wait_for_tick(&mut self, rate_hz: f64) {
+        let period = Duration::from_secs_f64(1.0 / rate_hz);
+        let now = Instant::now();
+        // More than a tick late (window drag, breakpoint): resynchronize
+        // instead of bursting through the missed ticks
+        let deadline = self
+            .next_deadline
+            .filter(|&deadline| now < deadline + period)
+            .unwrap_or(now);
+        sleep_until(deadline);
+        self.next_deadline = Some(deadline + period);
+    }
+}
+
+/// Sleep until `deadline` with sub-millisecond precision
+///
+/// The OS sleep covers all but the last [`SPIN_THRESHOLD`], which is spun off
+/// with yields since `thread::sleep` may overshoot by a timer quantum.
+pub fn sleep_until(deadline: Instant) {
+    loop {
+        let now = Instant::now();
+        if now >= deadline {
+            return;
+        }
+        let remaining = deadline - now;
+        if remaining > SPIN_THRESHOLD {
+            std::thread::sleep(remaining - SPIN_THRESHOLD);
+        } else {
+            std::thread::yield_now();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vsync_follows_wall_clock() {
+        let mut pacer = FramePacer::new(PacingMode::Vsync);
+        assert!(pacer.wants_vsync());
+        assert_eq!(pacer.frames_to_step(2, 0, 735, 1.0), 2);
+        assert_eq!(pacer.frames_to_step(0, 0, 735, 1.0), 0);
+    }
+
+    #[test]
+    fn test_audio_clock_fills_queue() {
+        let mut pacer = FramePacer::new(PacingMode::AudioClock);
+        assert!(!pacer.wants_vsync());
+        // Empty queue: refill three frames of audio
+        assert_eq!(pacer.frames_to_step(0, 0, 735, 1.0), 3);
+        // Half a frame short: one more frame
+        assert_eq!(pacer.frames_to_step(0, 735 * 2 + 400, 735, 1.0), 1);
+        // Full queue: nothing to do, regardless of the wall clock
+        assert_eq!(pacer.frames_to_step(5, 735 * 4, 735, 1.0), 0);
+        // Other speeds use the wall clock
+        assert_eq!(pacer.frames_to_step(2, 0, 735, 2.0), 2);
+    }
+
+    #[test]
+    fn test_timer_accumulates_speed() {
+        let mut pacer = FramePacer::new(PacingMode::Timer);
+        let steps: Vec<usize> = (0..4)
+            .map(|_| pacer.frames_to_step(0, 0, 735, 0.5))
+            .collect();
+        assert_eq!(steps, vec![0, 1, 0, 1]);
+        assert_eq!(pacer.frames_to_step(0, 0, 735, 2.0), 2);
+
+        pacer.set_mode(PacingMode::Timer);
+        assert_eq!(pacer.frames_to_step(0, 0, 735, 1.0), 1);
+    }
+
+    #[test]
+    fn test_timer_ticks_at_frame_rate() {
+        let mut pacer = FramePacer::new(PacingMode::Timer);
+        pacer.wait(true, 100.0, 0, 44100); // First tick starts now
+        let start = Instant::now();
+        pacer.wait(true, 100.0, 0, 44100);
+        pacer.wait(true, 100.0, 0, 44100);
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(19), "{:?}", elapsed);
+    }
+
+    #[test]
+    fn test_sleep_until_deadline() {
+        let start = Instant::now();
+        sleep_until(start);
+        sleep_until(start + Duration::from_millis(3));
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(3), "{:?}", elapsed);
+        assert!(elapsed < Duration::from_millis(50), "{:?}", elapsed);
+    }
+}
@@ -57,9 +57,45 @@ pub struct HemuProject {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub memory_kb: Option<u32>,
     /// Video mode for PC systems (optional, defaults to "CGA")
-    /// Valid values: "CGA", "EGA", "VGA"
+    /// Valid values: "CGA", "EGA", "VGA", "Hercules" (or "MDA")
     #[serde(skip_serializing_if = "Option::is_none")]
     pub video_mode: Option<String>,
+    /// Expanded memory (EMS) size in KB for PC systems (optional, defaults to 2048)
+    /// 0 disables expanded memory
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ems_kb: Option<u32>,
+    /// EMS page frame segment for PC systems as hex (optional, defaults to "E000")
+    /// Valid values: "C000" to "E000" in steps of "0400"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ems_page_frame: Option<String>,
+    /// CPU clock speed for PC systems (optional, defaults to "model")
+    /// Valid values: "model" (CPU model's historical speed), "unlimited", or MHz such as "8"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu_speed: Option<String>,
+    /// Seconds added to the host clock (UTC) by the PC real-time clock (optional, defaults to 0)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rtc_offset_secs: Option<i64>,
+    /// Fixed time for a frozen PC real-time clock as "YYYY-MM-DD HH:MM:SS" (optional)
+    /// Takes precedence over rtc_offset_secs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rtc_frozen_time: Option<String>,
+    /// Battery-backed PC CMOS RAM (registers 0Eh-7Fh) as hex (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cmos: Option<String>,
+    /// Host connection for PC COM1 (optional)
+    /// Valid values: "tcp:<host>:<port>", "tcp-listen:[<address>:]<port>", "pty:<path>"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub serial1: Option<String>,
+    /// Host connection for PC COM2 (optional, same values as serial1)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub serial2: Option<String>,
+    /// Host capture file for PC LPT1 (optional)
+    /// Valid values: "raw:<path>", "text:<path>" or a bare path (raw)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lpt1: Option<String>,
+    /// Start the built-in DOS when no PC disk boots (optional, defaults to true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub builtin_dos: Option<bool>,
 }
 
 impl HemuProject {
@@ -76,6 +112,16 @@ impl HemuProject {
             cpu_model: None,
             memory_kb: None,
             video_mode: None,
+            ems_kb: None,
+            ems_page_frame: None,
+            cpu_speed: None,
+            rtc_offset_secs: None,
+            rtc_frozen_time: None,
+            cmos: None,
+            serial1: None,
+            serial2: None,
+            lpt1: None,
+            builtin_dos: None,
         }
     }
 
@@ -151,6 +197,111 @@ impl HemuProject {
         self.video_mode.as_ref()
     }
 
+    /// Set EMS size in KB and page frame segment (for PC systems)
+    #[allow(dead_code)]
+    pub fn set_ems(&mut self, size_kb: u32, page_frame: u16) {
+        self.ems_kb = Some(size_kb);
+        self.ems_page_frame = Some(format!("{:04X}", page_frame));
+    }
+
+    /// Get EMS size in KB
+    pub fn get_ems_kb(&self) -> Option<u32> {
+        self.ems_kb
+    }
+
+    /// Get EMS page frame segment (None if unset or not valid hex)
+    pub fn get_ems_page_frame(&self) -> Option<u16> {
+        let frame = self.ems_page_frame.as_ref()?;
+        u16::from_str_radix(frame.trim_end_matches(['h', 'H']), 16).ok()
+    }
+
+    /// Set CPU clock speed (for PC systems)
+    #[allow(dead_code)]
+    pub fn set_cpu_speed(&mut self, cpu_speed: String) {
+        self.cpu_speed = Some(cpu_speed);
+    }
+
+    /// Get CPU clock speed
+    pub fn get_cpu_speed(&self) -> Option<&String> {
+        self.cpu_speed.as_ref()
+    }
+
+    /// Set the real-time clock: an offset from the host clock, or a frozen time (for PC systems)
+    #[allow(dead_code)]
+    pub fn set_rtc(&mut self, offset_secs: i64, frozen_time: Option<String>) {
+        self.rtc_offset_secs = (offset_secs != 0).then_some(offset_secs);
+        self.rtc_frozen_time = frozen_time;
+    }
+
+    /// Get the real-time clock offset from the host clock in seconds
+    pub fn get_rtc_offset_secs(&self) -> Option<i64> {
+        self.rtc_offset_secs
+    }
+
+    /// Get the frozen real-time clock time
+    pub fn get_rtc_frozen_time(&self) -> Option<&String> {
+        self.rtc_frozen_time.as_ref()
+    }
+
+    /// Set the CMOS RAM contents (for PC systems)
+    #[allow(dead_code)]
+    pub fn set_cmos(&mut self, cmos: &[u8]) {
+        self.cmos = Some(cmos.iter().map(|byte| format!("{:02X}", byte)).collect());
+    }
+
+    /// Get the CMOS RAM contents (None if unset or not valid hex)
+    pub fn get_cmos(&self) -> Option<Vec<u8>> {
+        let hex = self.cmos.as_ref()?;
+        if hex.len() % 2 != 0 {
+            return None;
+        }
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+            .collect()
+    }
+
+    /// Set the host connection of a serial port (0 = COM1, 1 = COM2)
+    #[allow(dead_code)]
+    pub fn set_serial(&mut self, port: usize, binding: Option<String>) {
+        match port {
+            0 => self.serial1 = binding,
+            1 => self.serial2 = binding,
+            _ => {}
+        }
+    }
+
+    /// Get the host connection of a serial port (0 = COM1, 1 = COM2)
+    pub fn get_serial(&self, port: usize) -> Option<&String> {
+        match port {
+            0 => self.serial1.as_ref(),
+            1 => self.serial2.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Set the host capture file of the printer port (LPT1)
+    #[allow(dead_code)]
+    pub fn set_lpt1(&mut self, output: Option<String>) {
+        self.lpt1 = output;
+    }
+
+    /// Get the host capture file of the printer port (LPT1)
+    pub fn get_lpt1(&self) -> Option<&String> {
+        self.lpt1.as_ref()
+    }
+
+    /// Set whether the built-in DOS starts when no disk boots (for PC systems)
+    #[allow(dead_code)]
+    pub fn set_builtin_dos(&mut self, enabled: bool) {
+        self.builtin_dos = Some(enabled);
+    }
+
+    /// Get whether the built-in DOS starts when no disk boots
+    pub fn get_builtin_dos(&self) -> Option<bool> {
+        self.builtin_dos
+    }
+
     /// Set display settings
     #[allow(dead_code)]
     pub fn set_display_settings(&mut self, width: usize, height: usize, filter: DisplayFilter) {
@@ -181,8 +332,9 @@ impl HemuProject {
     #[allow(dead_code)]
     pub fn relevant_mount_points(&self) -> Vec<&str> {
         match self.system.as_str() {
-            "pc" => vec!["BIOS", "FloppyA", "FloppyB", "HardDrive"],
-            "nes" | "gb" | "gameboy" | "atari2600" | "snes" | "n64" => vec!["Cartridge"],
+            "pc" => vec!["BIOS", "FloppyA", "FloppyB", "HardDrive", "Cassette"],
+            "atari2600" => vec!["Cartridge", "SideB"],
+            "nes" | "gb" | "gameboy" | "snes" | "n64" => vec!["Cartridge"],
             _ => vec![],
         }
     }
@@ -300,6 +452,13 @@ mod tests {
         project.set_cpu_model("Intel80286".to_string());
         project.set_memory_kb(512);
         project.set_video_mode("EGA".to_string());
+        project.set_ems(4096, 0xD000);
+        project.set_cpu_speed("unlimited".to_string());
+        project.set_rtc(3600, Some("1995-08-24 09:30:00".to_string()));
+        project.set_cmos(&[0x00, 0x26, 0xFF]);
+        project.set_serial(1, Some("tcp:localhost:5555".to_string()));
+        project.set_lpt1(Some("text:printer.txt".to_string()));
+        project.set_builtin_dos(false);
 
         // Save
         project.save(&test_file).expect("Failed to save");
@@ -316,6 +475,22 @@ mod tests {
         assert_eq!(loaded.get_cpu_model(), Some(&"Intel80286".to_string()));
         assert_eq!(loaded.get_memory_kb(), Some(512));
         assert_eq!(loaded.get_video_mode(), Some(&"EGA".to_string()));
+        assert_eq!(loaded.get_ems_kb(), Some(4096));
+        assert_eq!(loaded.get_ems_page_frame(), Some(0xD000));
+        assert_eq!(loaded.get_cpu_speed(), Some(&"unlimited".to_string()));
+        assert_eq!(loaded.get_rtc_offset_secs(), Some(3600));
+        assert_eq!(
+            loaded.get_rtc_frozen_time(),
+            Some(&"1995-08-24 09:30:00".to_string())
+        );
+        assert_eq!(loaded.get_cmos(), Some(vec![0x00, 0x26, 0xFF]));
+        assert_eq!(loaded.get_serial(0), None);
+        assert_eq!(
+            loaded.get_serial(1),
+            Some(&"tcp:localhost:5555".to_string())
+        );
+        assert_eq!(loaded.get_lpt1(), Some(&"text:printer.txt".to_string()));
+        assert_eq!(loaded.get_builtin_dos(), Some(false));
 
         // Cleanup
         fs::remove_file(test_file).ok();
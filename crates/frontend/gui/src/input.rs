@@ -67,7 +67,7 @@ pub enum VirtualButton {
 }
 
 /// Controller profile that maps physical inputs to virtual buttons
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ControllerProfile {
     /// Profile name (e.g., "Keyboard WASD", "Xbox Controller", "PS4 DualShock")
     pub name: String,
@@ -169,11 +169,12 @@ impl ControllerProfile {
             .map(VirtualButton::B, InputSource::GamepadButton(1)) // B/Circle
             .map(VirtualButton::X, InputSource::GamepadButton(2)) // X/Square
             .map(VirtualButton::Y, InputSource::GamepadButton(3)) // Y/Triangle
-            .map(VirtualButton::L, InputSource::GamepadButton(4)) // L1/LB
-            .map(VirtualButton::R, InputSource::GamepadButton(5)) // R1/RB
-            .map(VirtualButton::Select, InputSource::GamepadButton(6)) // Back/Select
-            .map(VirtualButton::Start, InputSource::GamepadButton(7)) // Start
-            // D-pad via axis 0 (left/right) and axis 1 (up/down)
+            .map(VirtualButton::L, InputSource::GamepadButton(9)) // L1/LB
+            .map(VirtualButton::R, InputSource::GamepadButton(10)) // R1/RB
+            .map(VirtualButton::Select, InputSource::GamepadButton(4)) // Back/Select
+            .map(VirtualButton::Start, InputSource::GamepadButton(6)) // Start
+            // Directions via the left stick, axis 0 (left/right) and axis 1
+            // (up/down); the D-pad buttons always work as well
             .map(
                 VirtualButton::Left,
                 InputSource::GamepadAxis {
@@ -205,6 +206,108 @@ impl ControllerProfile {
     }
 }
 
+/// Which connected gamepad drives a player
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GamepadAssignment {
+    /// The next gamepad in connection order not picked by name for another player
+    #[default]
+    Auto,
+    /// No gamepad; the player uses the keyboard only
+    Disabled,
+    /// The first connected gamepad with this name (as reported by SDL2)
+    Named(String),
+}
+
+/// Gamepad settings of one player
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlayerGamepad {
+    #[serde(default)]
+    pub device: GamepadAssignment,
+    /// Stick deflection (0.1-0.9 of full tilt) at which a mapped axis counts
+    /// as pressed, e.g. the left stick as the D-pad
+    #[serde(default = "default_axis_threshold")]
+    pub axis_threshold: f32,
+    /// Button mapping; `None` uses the gamepad profile from the input
+    /// configuration's `profiles`, or [`ControllerProfile::gamepad_default`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<ControllerProfile>,
+}
+
+fn default_axis_threshold() -> f32 {
+    0.5
+}
+
+impl PlayerGamepad {
+    /// Axis threshold as a raw SDL2 axis value
+    pub fn axis_threshold_raw(&self) -> i16 {
+        (self.axis_threshold.clamp(0.1, 0.9) * i16::MAX as f32) as i16
+    }
+}
+
+impl Default for PlayerGamepad {
+    fn default() -> Self {
+        Self {
+            device: GamepadAssignment::Auto,
+            axis_threshold: default_axis_threshold(),
+            profile: None,
+        }
+    }
+}
+
+/// Pick the gamepad (SDL2 instance ID) driving each player
+///
+/// `connected` lists the gamepads as (instance ID, name) in connection order.
+/// Gamepads chosen by name are claimed first; `Auto` players then take the
+/// remaining gamepads in order.
+pub fn assign_gamepads(players: &[PlayerGamepad], connected: &[(u32, String)]) -> Vec<Option<u32>> {
+    let mut free: Vec<&(u32, String)> = connected.iter().collect();
+    let mut assigned: Vec<Option<u32>> = players
+        .iter()
+        .map(|player| match &player.device {
+            GamepadAssignment::Named(name) => {
+                let index = free.iter().position(|(_, n)| n == name)?;
+                Some(free.remove(index).0)
+            }
+            _ => None,
+        })
+        .collect();
+    let mut free = free.into_iter();
+    for (player, slot) in players.iter().zip(assigned.iter_mut()) {
+        if player.device == GamepadAssignment::Auto {
+            *slot = free.next().map(|(id, _)| *id);
+        }
+    }
+    assigned
+}
+
+/// Whether held turbo buttons read as pressed on a frame
+///
+/// Turbo buttons alternate between pressed and released every
+/// `frames_per_toggle` frames (at least 1).
+pub fn turbo_pressed(frame: u64, frames_per_toggle: u32) -> bool {
+    (frame / frames_per_toggle.max(1) as u64).is_multiple_of(2)
+}
+
+/// Scales captured relative mouse motion by the sensitivity setting
+///
+/// The fractions left over are carried into the next frame, so slow
+/// movements at a low sensitivity still add up.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MouseMotion {
+    remainder: (f32, f32),
+}
+
+impl MouseMotion {
+    /// Scale one frame's motion, returning whole mickeys
+    pub fn scale(&mut self, dx: i32, dy: i32, sensitivity: f32) -> (i32, i32) {
+        let x = dx as f32 * sensitivity + self.remainder.0;
+        let y = dy as f32 * sensitivity + self.remainder.1;
+        let (whole_x, whole_y) = (x.trunc(), y.trunc());
+        self.remainder = (x - whole_x, y - whole_y);
+        (whole_x as i32, whole_y as i32)
+    }
+}
+
 /// Input mapper that resolves virtual button states from physical inputs
 pub struct InputMapper {
     /// Active controller profiles (indexed by player number, 0-3)
@@ -277,6 +380,76 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_turbo_pressed() {
+        let cycle = |rate| {
+            (0..8)
+                .map(|frame| turbo_pressed(frame, rate))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            cycle(1),
+            [true, false, true, false, true, false, true, false]
+        );
+        assert_eq!(
+            cycle(3),
+            [true, true, true, false, false, false, true, true]
+        );
+        // A rate of 0 toggles every frame
+        assert_eq!(cycle(0), cycle(1));
+    }
+
+    #[test]
+    fn test_mouse_motion_carries_fractions() {
+        let mut motion = MouseMotion::default();
+        assert_eq!(motion.scale(10, -4, 2.0), (20, -8));
+
+        // 0.4 per frame: nothing until a whole mickey has built up
+        assert_eq!(motion.scale(1, -1, 0.4), (0, 0));
+        assert_eq!(motion.scale(1, -1, 0.4), (0, 0));
+        assert_eq!(motion.scale(1, -1, 0.4), (1, -1));
+    }
+
+    #[test]
+    fn test_assign_gamepads() {
+        let connected = vec![
+            (3, "Xbox Controller".to_string()),
+            (5, "PS4 Controller".to_string()),
+            (8, "Xbox Controller".to_string()),
+        ];
+        let player = |device| PlayerGamepad {
+            device,
+            ..PlayerGamepad::default()
+        };
+
+        // By default the gamepads go to the players in connection order
+        let players = vec![PlayerGamepad::default(); 4];
+        assert_eq!(
+            assign_gamepads(&players, &connected),
+            [Some(3), Some(5), Some(8), None]
+        );
+
+        // A named pick is claimed before the automatic ones
+        let players = vec![
+            player(GamepadAssignment::Auto),
+            player(GamepadAssignment::Named("PS4 Controller".to_string())),
+            player(GamepadAssignment::Disabled),
+            player(GamepadAssignment::Named("Missing".to_string())),
+        ];
+        assert_eq!(
+            assign_gamepads(&players, &connected),
+            [Some(3), Some(5), None, None]
+        );
+    }
+
+    #[test]
+    fn test_axis_threshold_is_clamped() {
+        let mut player = PlayerGamepad::default();
+        assert_eq!(player.axis_threshold_raw(), 16383);
+        player.axis_threshold = 2.0;
+        assert_eq!(player.axis_threshold_raw(), 29490);
+    }
+
     #[test]
     fn test_gamepad_profile() {
         let profile = ControllerProfile::gamepad_default();
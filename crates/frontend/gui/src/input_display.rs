@@ -0,0 +1,138 @@
+//! Controller input display overlay
+//!
+//! Draws a small controller diagram per port into the bottom-left corner of the
+//! emulator frame, highlighting the buttons that are currently held. Useful for
+//! streaming and for verifying recorded input playback.
+
+/// Size of one controller widget in pixels
+const WIDGET_WIDTH: usize = 42;
+const WIDGET_HEIGHT: usize = 20;
+/// Gap between widgets and between the widgets and the frame edge
+const MARGIN: usize = 4;
+
+const BACKGROUND: u32 = 0xA0000000;
+const BUTTON_IDLE: u32 = 0xFF505050;
+const BUTTON_PRESSED: u32 = 0xFFFFFFFF;
+const FACE_PRESSED: u32 = 0xFFE04040;
+
+/// NES button layout: (bit, x, y, width, height) relative to the widget origin.
+/// Bits follow the controller shift register order: A, B, Select, Start, Up,
+/// Down, Left, Right.
+const NES_BUTTONS: [(u8, usize, usize, usize, usize); 8] = [
+    (0, 35, 8, 4, 4),  // A
+    (1, 29, 8, 4, 4),  // B
+    (2, 17, 10, 5, 2), // Select
+    (3, 23, 10, 5, 2), // Start
+    (4, 7, 3, 4, 4),   // Up
+    (5, 7, 11, 4, 4),  // Down
+    (6, 3, 7, 4, 4),   // Left
+    (7, 11, 7, 4, 4),  // Right
+];
+
+/// Draw the NES input display for each controller port into a frame buffer
+pub fn draw_nes(buffer: &mut [u32], width: usize, height: usize, ports: &[u8]) {
+    if height < WIDGET_HEIGHT + MARGIN {
+        return;
+    }
+    let y = height - WIDGET_HEIGHT - MARGIN;
+
+    for (port, &state) in ports.iter().enumerate() {
+        let x = MARGIN + port * (WIDGET_WIDTH + MARGIN);
+        if x + WIDGET_WIDTH > width {
+            break;
+        }
+
+        blend_rect(buffer, width, x, y, WIDGET_WIDTH, WIDGET_HEIGHT, BACKGROUND);
+
+        // Port number as a row of dots in the top-left corner
+        for i in 0..=port {
+            fill_rect(buffer, width, x + 2 + i * 3, y + 1, 2, 1, BUTTON_PRESSED);
+        }
+
+        // D-pad center
+        fill_rect(buffer, width, x + 7, y + 7, 4, 4, BUTTON_IDLE);
+
+        for &(bit, bx, by, bw, bh) in NES_BUTTONS.iter() {
+            let pressed = state & (1 << bit) != 0;
+            let color = match (pressed, bit) {
+                (false, _) => BUTTON_IDLE,
+                (true, 0 | 1) => FACE_PRESSED,
+                (true, _) => BUTTON_PRESSED,
+            };
+            fill_rect(buffer, width, x + bx, y + by, bw, bh, color);
+        }
+    }
+}
+
+/// Fill a rectangle with a solid color
+fn fill_rect(buffer: &mut [u32], width: usize, x: usize, y: usize, w: usize, h: usize, color: u32) {
+    for row in y..y + h {
+        for col in x..(x + w).min(width) {
+            if let Some(pixel) = buffer.get_mut(row * width + col) {
+                *pixel = color;
+            }
+        }
+    }
+}
+
+/// Blend a rectangle of an ARGB color over the frame using its alpha channel
+fn blend_rect(
+    buffer: &mut [u32],
+    width: usize,
+    x: usize,
+    y: usize,
+    w: usize,
+    h: usize,
+    color: u32,
+) {
+    let alpha = (color >> 24) & 0xFF;
+    let inv = 255 - alpha;
+    let blend = |b: u32, o: u32| (b * inv + o * alpha) / 255;
+
+    for row in y..y + h {
+        for col in x..(x + w).min(width) {
+            if let Some(pixel) = buffer.get_mut(row * width + col) {
+                let p = *pixel;
+                let r = blend((p >> 16) & 0xFF, (color >> 16) & 0xFF);
+                let g = blend((p >> 8) & 0xFF, (color >> 8) & 0xFF);
+                let b = blend(p & 0xFF, color & 0xFF);
+                *pixel = 0xFF000000 | (r << 16) | (g << 8) | b;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_draw_nes_highlights_pressed_buttons() {
+        let (width, height) = (256, 240);
+        let mut buffer = vec![0xFF0000FF; width * height];
+        // Port 1: A + Up, port 2: nothing
+        draw_nes(&mut buffer, width, height, &[0x11, 0x00]);
+
+        let origin_y = height - WIDGET_HEIGHT - MARGIN;
+        let at = |x: usize, y: usize| buffer[(origin_y + y) * width + x];
+
+        // Port 1 A and Up are lit, B is idle
+        assert_eq!(at(MARGIN + 36, 9), FACE_PRESSED);
+        assert_eq!(at(MARGIN + 8, 4), BUTTON_PRESSED);
+        assert_eq!(at(MARGIN + 30, 9), BUTTON_IDLE);
+
+        // Port 2 A is idle
+        let port2_x = MARGIN * 2 + WIDGET_WIDTH;
+        assert_eq!(at(port2_x + 36, 9), BUTTON_IDLE);
+
+        // Pixels outside the widgets are untouched
+        assert_eq!(buffer[0], 0xFF0000FF);
+    }
+
+    #[test]
+    fn test_draw_nes_small_frame_is_noop() {
+        let mut buffer = vec![0xFF123456; 16 * 16];
+        draw_nes(&mut buffer, 16, 16, &[0xFF]);
+        assert!(buffer.iter().all(|&p| p == 0xFF123456));
+    }
+}
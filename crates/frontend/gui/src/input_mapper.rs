@@ -4,12 +4,44 @@
 //! to virtual controller buttons using controller profiles.
 
 use crate::input::{ControllerProfile, InputSource, VirtualButton};
-use crate::window_backend::WindowBackend;
+use crate::window_backend::{Sdl2Backend, Sdl2EguiBackend, WindowBackend};
 
-/// Axis threshold for activation (~50% of half-range, ~25% deflection from center)
-/// This represents approximately 25% deflection from center position.
-/// Full axis range is -32768 to 32767, so threshold at ±16384 is ~50% of half-range.
-const AXIS_THRESHOLD: i16 = 16384;
+/// SDL2 GameController D-pad buttons: up, down, left, right
+const DPAD_BUTTONS: [u8; 4] = [11, 12, 13, 14];
+
+/// Gamepad and joystick state of a window backend, by SDL2 instance ID
+pub trait ControllerInput {
+    fn is_gamepad_button_down(&self, instance_id: u32, button: u8) -> bool;
+    fn get_gamepad_axis(&self, instance_id: u32, axis: u8) -> i16;
+    fn is_joystick_button_down(&self, instance_id: u32, button: u8) -> bool;
+    fn get_joystick_axis(&self, instance_id: u32, axis: u8) -> i16;
+    fn get_joystick_hat(&self, instance_id: u32, hat: u8) -> u8;
+}
+
+macro_rules! impl_controller_input {
+    ($backend:ty) => {
+        impl ControllerInput for $backend {
+            fn is_gamepad_button_down(&self, instance_id: u32, button: u8) -> bool {
+                <$backend>::is_gamepad_button_down(self, instance_id, button)
+            }
+            fn get_gamepad_axis(&self, instance_id: u32, axis: u8) -> i16 {
+                <$backend>::get_gamepad_axis(self, instance_id, axis)
+            }
+            fn is_joystick_button_down(&self, instance_id: u32, button: u8) -> bool {
+                <$backend>::is_joystick_button_down(self, instance_id, button)
+            }
+            fn get_joystick_axis(&self, instance_id: u32, axis: u8) -> i16 {
+                <$backend>::get_joystick_axis(self, instance_id, axis)
+            }
+            fn get_joystick_hat(&self, instance_id: u32, hat: u8) -> u8 {
+                <$backend>::get_joystick_hat(self, instance_id, hat)
+            }
+        }
+    };
+}
+
+impl_controller_input!(Sdl2Backend);
+impl_controller_input!(Sdl2EguiBackend);
 
 /// Map a virtual button to its bit position in the controller state
 /// Returns None if the button is not part of the standard 8-button layout
@@ -33,11 +65,15 @@ pub fn virtual_button_to_bit(button: VirtualButton) -> Option<u8> {
 }
 
 /// Check if an input source is currently active
+///
+/// Axes count as pressed once deflected past `axis_threshold` (raw SDL2 axis
+/// value, full tilt is 32767) in the mapped direction.
 pub fn is_input_source_active(
     source: &InputSource,
     window: &dyn WindowBackend,
-    sdl2_backend: Option<&crate::window_backend::Sdl2Backend>,
+    sdl2_backend: Option<&dyn ControllerInput>,
     instance_id: u32,
+    axis_threshold: i16,
 ) -> bool {
     match source {
         InputSource::KeyboardKey(key_name) => {
@@ -65,8 +101,8 @@ pub fn is_input_source_active(
             if let Some(backend) = sdl2_backend {
                 let value = backend.get_gamepad_axis(instance_id, *axis);
                 match direction {
-                    -1 => value < -AXIS_THRESHOLD,
-                    1 => value > AXIS_THRESHOLD,
+                    -1 => value < -axis_threshold,
+                    1 => value > axis_threshold,
                     _ => false,
                 }
             } else {
@@ -84,8 +120,8 @@ pub fn is_input_source_active(
             if let Some(backend) = sdl2_backend {
                 let value = backend.get_joystick_axis(instance_id, *axis);
                 match direction {
-                    -1 => value < -AXIS_THRESHOLD,
-                    1 => value > AXIS_THRESHOLD,
+                    -1 => value < -axis_threshold,
+                    1 => value > axis_threshold,
                     _ => false,
                 }
             } else {
@@ -108,14 +144,23 @@ pub fn is_input_source_active(
 pub fn get_controller_state_from_profile(
     profile: &ControllerProfile,
     window: &dyn WindowBackend,
-    sdl2_backend: Option<&crate::window_backend::Sdl2Backend>,
+    sdl2_backend: Option<&dyn ControllerInput>,
     instance_id: u32,
+    axis_threshold: i16,
 ) -> u8 {
     let mut state = 0u8;
 
     for (virtual_button, input_source) in &profile.mappings {
         if let Some(bit) = virtual_button_to_bit(*virtual_button) {
-            if bit < 8 && is_input_source_active(input_source, window, sdl2_backend, instance_id) {
+            if bit < 8
+                && is_input_source_active(
+                    input_source,
+                    window,
+                    sdl2_backend,
+                    instance_id,
+                    axis_threshold,
+                )
+            {
                 state |= 1 << bit;
             }
         }
@@ -129,14 +174,21 @@ pub fn get_controller_state_from_profile(
 pub fn get_snes_controller_state_from_profile(
     profile: &ControllerProfile,
     window: &dyn WindowBackend,
-    sdl2_backend: Option<&crate::window_backend::Sdl2Backend>,
+    sdl2_backend: Option<&dyn ControllerInput>,
     instance_id: u32,
+    axis_threshold: i16,
 ) -> u16 {
     let mut state = 0u16;
 
     for (virtual_button, input_source) in &profile.mappings {
         if let Some(button_id) = virtual_button_to_bit(*virtual_button) {
-            if is_input_source_active(input_source, window, sdl2_backend, instance_id) {
+            if is_input_source_active(
+                input_source,
+                window,
+                sdl2_backend,
+                instance_id,
+                axis_threshold,
+            ) {
                 // Map button IDs to SNES button positions
                 let snes_bit = match button_id {
                     0 => 7,  // A -> bit 7
@@ -161,9 +213,222 @@ pub fn get_snes_controller_state_from_profile(
     state
 }
 
+/// Controller state (8-bit layout) from the gamepad driving a player
+///
+/// Buttons follow the gamepad profile; the D-pad always works as the
+/// directions as well, whatever the profile maps them to.
+pub fn get_gamepad_state(
+    profile: &ControllerProfile,
+    window: &dyn WindowBackend,
+    controllers: &dyn ControllerInput,
+    instance_id: u32,
+    axis_threshold: i16,
+) -> u8 {
+    let mut state = get_controller_state_from_profile(
+        profile,
+        window,
+        Some(controllers),
+        instance_id,
+        axis_threshold,
+    );
+    for (index, &button) in DPAD_BUTTONS.iter().enumerate() {
+        if controllers.is_gamepad_button_down(instance_id, button) {
+            state |= 1 << (4 + index); // Up, Down, Left, Right = bits 4-7
+        }
+    }
+    state
+}
+
+/// SNES controller state (16-bit) from the gamepad driving a player, with
+/// the D-pad working as the directions like [`get_gamepad_state`]
+pub fn get_snes_gamepad_state(
+    profile: &ControllerProfile,
+    window: &dyn WindowBackend,
+    controllers: &dyn ControllerInput,
+    instance_id: u32,
+    axis_threshold: i16,
+) -> u16 {
+    let mut state = get_snes_controller_state_from_profile(
+        profile,
+        window,
+        Some(controllers),
+        instance_id,
+        axis_threshold,
+    );
+    for (index, &button) in DPAD_BUTTONS.iter().enumerate() {
+        if controllers.is_gamepad_button_down(instance_id, button) {
+            state |= 1 << (11 - index); // Up, Down, Left, Right = bits 11-8
+        }
+    }
+    state
+}
+
+/// Controller state in the common layout with the extended buttons (16-bit,
+/// X, Y, L and R in bits 8-11) from the gamepad driving a player, with the
+/// D-pad working as the directions like [`get_gamepad_state`]
+pub fn get_extended_gamepad_state(
+    profile: &ControllerProfile,
+    window: &dyn WindowBackend,
+    controllers: &dyn ControllerInput,
+    instance_id: u32,
+    axis_threshold: i16,
+) -> u16 {
+    let mut state =
+        get_gamepad_state(profile, window, controllers, instance_id, axis_threshold) as u16;
+    for (virtual_button, input_source) in &profile.mappings {
+        if let Some(bit) = virtual_button_to_bit(*virtual_button) {
+            if bit >= 8
+                && is_input_source_active(
+                    input_source,
+                    window,
+                    Some(controllers),
+                    instance_id,
+                    axis_threshold,
+                )
+            {
+                state |= 1 << bit;
+            }
+        }
+    }
+    state
+}
+
+/// SDL2 GameController left stick axes
+const LEFT_STICK_X: u8 = 0;
+const LEFT_STICK_Y: u8 = 1;
+
+/// N64 analog stick position from a gamepad's left stick, scaled so full
+/// tilt reaches [`emu_n64::STICK_MAX`]
+///
+/// None while the stick rests inside `axis_threshold`, leaving the stick to
+/// the digital directions.
+pub fn get_n64_stick(
+    controllers: &dyn ControllerInput,
+    instance_id: u32,
+    axis_threshold: i16,
+) -> Option<(i8, i8)> {
+    let x = controllers.get_gamepad_axis(instance_id, LEFT_STICK_X) as i32;
+    let y = controllers.get_gamepad_axis(instance_id, LEFT_STICK_Y) as i32;
+    let threshold = axis_threshold as i32;
+    if x.abs() <= threshold && y.abs() <= threshold {
+        return None;
+    }
+    let scale = |value: i32| (value * emu_n64::STICK_MAX as i32 / 32767) as i8;
+    // SDL2's Y axis points down, the N64's up
+    Some((scale(x), scale(-y)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::window_backend::Key;
+    use std::collections::{HashMap, HashSet};
+
+    /// Window without keys and gamepads with the given buttons held
+    #[derive(Default)]
+    struct FakeInput {
+        buttons: HashMap<u32, HashSet<u8>>,
+        axes: HashMap<(u32, u8), i16>,
+    }
+
+    impl WindowBackend for FakeInput {
+        fn is_open(&self) -> bool {
+            true
+        }
+        fn is_key_down(&self, _key: Key) -> bool {
+            false
+        }
+        fn is_key_pressed(&self, _key: Key, _allow_repeat: bool) -> bool {
+            false
+        }
+        fn update_with_buffer(
+            &mut self,
+            _buffer: &[u32],
+            _width: usize,
+            _height: usize,
+        ) -> Result<(), Box<dyn std::error::Error>> {
+            Ok(())
+        }
+        fn get_size(&self) -> (usize, usize) {
+            (0, 0)
+        }
+        fn poll_events(&mut self) {}
+        fn name(&self) -> &str {
+            "fake"
+        }
+        fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+            self
+        }
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+    }
+
+    impl ControllerInput for FakeInput {
+        fn is_gamepad_button_down(&self, instance_id: u32, button: u8) -> bool {
+            self.buttons
+                .get(&instance_id)
+                .is_some_and(|buttons| buttons.contains(&button))
+        }
+        fn get_gamepad_axis(&self, instance_id: u32, axis: u8) -> i16 {
+            self.axes.get(&(instance_id, axis)).copied().unwrap_or(0)
+        }
+        fn is_joystick_button_down(&self, _instance_id: u32, _button: u8) -> bool {
+            false
+        }
+        fn get_joystick_axis(&self, _instance_id: u32, _axis: u8) -> i16 {
+            0
+        }
+        fn get_joystick_hat(&self, _instance_id: u32, _hat: u8) -> u8 {
+            0
+        }
+    }
+
+    #[test]
+    fn test_two_gamepads_drive_two_players() {
+        let profile = ControllerProfile::gamepad_default();
+        let mut input = FakeInput::default();
+        // Player 1: A + Start + D-pad right; player 2: Y + L + stick up
+        input.buttons.insert(3, HashSet::from([0, 6, 14]));
+        input.buttons.insert(7, HashSet::from([3, 9]));
+        input.axes.insert((7, 1), -32768);
+
+        let player1 = get_snes_gamepad_state(&profile, &input, &input, 3, 16384);
+        let player2 = get_snes_gamepad_state(&profile, &input, &input, 7, 16384);
+        assert_eq!(player1, 0x1180); // Start, Right, A
+        assert_eq!(player2, 0x4820); // Y, Up, L
+
+        assert_eq!(get_gamepad_state(&profile, &input, &input, 3, 16384), 0x89); // A, Start, Right
+        assert_eq!(get_gamepad_state(&profile, &input, &input, 9, 16384), 0);
+    }
+
+    #[test]
+    fn test_axis_threshold() {
+        let profile = ControllerProfile::gamepad_default();
+        let mut input = FakeInput::default();
+        input.axes.insert((1, 0), -12000); // Stick a little to the left
+
+        assert_eq!(get_gamepad_state(&profile, &input, &input, 1, 16384), 0);
+        assert_eq!(get_gamepad_state(&profile, &input, &input, 1, 8192), 0x40); // Left
+    }
+
+    #[test]
+    fn test_n64_gamepad_state_and_stick() {
+        let profile = ControllerProfile::gamepad_default();
+        let mut input = FakeInput::default();
+        // A + L, left stick fully up and half right
+        input.buttons.insert(2, HashSet::from([0, 9]));
+        input.axes.insert((2, 0), 16384);
+        input.axes.insert((2, 1), -32768);
+
+        let state = get_extended_gamepad_state(&profile, &input, &input, 2, 8192);
+        assert_eq!(state, 0x0400 | 0x80 | 0x10 | 0x01); // L, Right, Up, A
+        assert_eq!(get_n64_stick(&input, 2, 8192), Some((40, 80)));
+
+        // A resting stick leaves the stick to the directions
+        input.axes.clear();
+        assert_eq!(get_n64_stick(&input, 2, 8192), None);
+    }
 
     #[test]
     fn test_virtual_button_to_bit() {
@@ -3,6 +3,7 @@
 //! This crate provides the GUI frontend for the Hemulator multi-system emulator.
 //! It includes modular video processing with software and OpenGL backends.
 
+pub mod archive;
 pub mod display_filter;
 pub mod egui_ui;
 pub mod input;
@@ -1,28 +1,55 @@
+mod archive;
+mod atari_quick_menu;
+mod battery_save;
+mod bench;
 pub mod display_filter;
 pub mod egui_ui;
+mod frame_pacing;
 mod hemu_project;
 pub mod input;
+mod input_display;
 pub mod input_mapper;
+mod media_check;
+mod movie_session;
+mod now_playing;
 mod rom_detect;
+mod rom_watcher;
 mod save_state;
+mod self_test;
 mod settings;
 mod system_adapter;
 mod ui_render;
 pub mod video_processor;
 pub mod window_backend;
 
+use archive::ArchiveError;
+use atari_quick_menu::{AtariQuickMenu, QuickAction};
 use egui_ui::EguiApp;
-use emu_core::{types::Frame, System};
+use emu_core::{
+    apu::AudioGain,
+    movie::{InputMovie, MovieStart},
+    rewind::RewindBuffer,
+    types::Frame,
+    System,
+};
+use frame_pacing::FramePacer;
 use hemu_project::HemuProject;
+use input::{ControllerProfile, InputDeviceType};
+use movie_session::{MovieSession, MovieStep, MOVIE_EXTENSION};
+use now_playing::{NowPlayingFile, NowPlayingHub};
 use rodio::{OutputStream, Source};
 use rom_detect::{detect_rom_type, SystemType};
+use rom_watcher::RomWatcher;
 use save_state::GameSaves;
-use settings::Settings;
+use sdl2::controller::Button;
+use settings::{PacingMode, Settings};
 use std::collections::HashMap;
 use std::env;
 use std::fs;
 use std::path::PathBuf;
-use std::sync::mpsc::{sync_channel, Receiver};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use window_backend::{string_to_key, Key, Sdl2EguiBackend, WindowBackend};
 
@@ -267,45 +294,60 @@ impl EmulatorSystem {
             }
             EmulatorSystem::Atari2600(sys) => sys.set_controller(port, state),
             EmulatorSystem::PC(_) => {} // PC doesn't use controller input
-            EmulatorSystem::SNES(_) => {} // SNES controller support stub
-            EmulatorSystem::N64(sys) => {
-                // N64 controller mapping
-                // GUI state bits: 0=A, 1=B, 2=Select, 3=Start, 4=Up, 5=Down, 6=Left, 7=Right
-                // Map to N64 controller with proper button mapping
-                // Note: N64 uses active-high logic (1 = pressed, bit set means button pressed)
-                let mut n64_state = emu_n64::ControllerState::default();
-
-                // Map standard buttons (A, B, Start)
-                n64_state.buttons.a = (state & 0x01) != 0; // Bit 0
-                n64_state.buttons.b = (state & 0x02) != 0; // Bit 1
-                n64_state.buttons.start = (state & 0x08) != 0; // Bit 3
-
-                // Map D-pad
-                n64_state.buttons.d_up = (state & 0x10) != 0; // Bit 4
-                n64_state.buttons.d_down = (state & 0x20) != 0; // Bit 5
-                n64_state.buttons.d_left = (state & 0x40) != 0; // Bit 6
-                n64_state.buttons.d_right = (state & 0x80) != 0; // Bit 7
-
-                // Note: Select button (bit 2) is not used on N64
-                // Z, L, R, and C-buttons would need additional key mappings
-
-                // Set controller state based on port
-                match port {
-                    0 => sys.set_controller1(n64_state),
-                    1 => sys.set_controller2(n64_state),
-                    2 => sys.set_controller3(n64_state),
-                    3 => sys.set_controller4(n64_state),
-                    _ => {}
-                }
+            EmulatorSystem::SNES(sys) => {
+                // Standard layout (A, B, Select, Start, Up, Down, Left, Right) to
+                // SNES bits 7 and 15-8; X, Y, L and R come via set_controller_16
+                use emu_snes::controller;
+                let buttons = [
+                    controller::A,
+                    controller::B,
+                    controller::SELECT,
+                    controller::START,
+                    controller::UP,
+                    controller::DOWN,
+                    controller::LEFT,
+                    controller::RIGHT,
+                ];
+                let snes_state = buttons
+                    .iter()
+                    .enumerate()
+                    .filter(|(bit, _)| state & (1 << bit) != 0)
+                    .fold(0, |acc, (_, &button)| acc | button);
+                sys.set_controller(port, snes_state);
             }
+            // The directions drive the analog stick and Select is Z
+            EmulatorSystem::N64(sys) => sys.set_controller(port, state as u16),
         }
     }
 
     fn set_controller_16(&mut self, port: usize, state: u16) {
-        if let EmulatorSystem::SNES(sys) = self {
-            sys.set_controller(port, state)
+        match self {
+            EmulatorSystem::SNES(sys) => sys.set_controller(port, state),
+            // Common layout, X, Y, L and R in bits 8-11
+            EmulatorSystem::N64(sys) => sys.set_controller(port, state),
+            // Other systems use 8-bit set_controller
+            _ => {}
+        }
+    }
+
+    /// Apply one frame of recorded movie input in place of the live controls
+    fn apply_movie_input(&mut self, input: &emu_core::movie::MovieFrame) {
+        for (port, &state) in input.controllers.iter().enumerate() {
+            match self {
+                EmulatorSystem::SNES(_) | EmulatorSystem::N64(_) => {
+                    self.set_controller_16(port, state)
+                }
+                _ => self.set_controller(port, state as u8),
+            }
+        }
+        if let EmulatorSystem::PC(sys) = self {
+            for &scancode in &input.key_presses {
+                sys.key_press_sdl2(scancode);
+            }
+            for &scancode in &input.key_releases {
+                sys.key_release_sdl2(scancode);
+            }
         }
-        // Other systems use 8-bit set_controller
     }
 
     fn get_debug_info_nes(&self) -> Option<emu_nes::DebugInfo> {
@@ -418,14 +460,52 @@ impl EmulatorSystem {
         }
     }
 
-    fn timing(&self) -> emu_core::apu::TimingMode {
+    fn frame_rate(&self) -> f64 {
+        match self {
+            EmulatorSystem::NES(sys) => sys.frame_rate(),
+            EmulatorSystem::GameBoy(sys) => sys.frame_rate(),
+            EmulatorSystem::Atari2600(sys) => sys.frame_rate(),
+            EmulatorSystem::PC(sys) => sys.frame_rate(),
+            EmulatorSystem::SNES(sys) => sys.frame_rate(),
+            EmulatorSystem::N64(sys) => sys.frame_rate(),
+        }
+    }
+
+    /// Hand a shown frame back so the system can reuse its buffer
+    fn recycle_frame(&mut self, frame: Frame) {
+        match self {
+            EmulatorSystem::NES(sys) => sys.recycle_frame(frame),
+            EmulatorSystem::GameBoy(sys) => sys.recycle_frame(frame),
+            EmulatorSystem::Atari2600(sys) => sys.recycle_frame(frame),
+            EmulatorSystem::PC(sys) => sys.recycle_frame(frame),
+            EmulatorSystem::SNES(sys) => sys.recycle_frame(frame),
+            EmulatorSystem::N64(sys) => sys.recycle_frame(frame),
+        }
+    }
+
+    fn frame_pool_stats(&self) -> Option<emu_core::frame_pool::FramePoolStats> {
+        match self {
+            EmulatorSystem::NES(sys) => sys.frame_pool_stats(),
+            EmulatorSystem::GameBoy(sys) => sys.frame_pool_stats(),
+            EmulatorSystem::Atari2600(sys) => sys.frame_pool_stats(),
+            EmulatorSystem::PC(sys) => sys.frame_pool_stats(),
+            EmulatorSystem::SNES(sys) => sys.frame_pool_stats(),
+            EmulatorSystem::N64(sys) => sys.frame_pool_stats(),
+        }
+    }
+
+    /// Normalization gain bringing each system's output to a similar loudness
+    ///
+    /// Measured against typical peak levels: the NES and Game Boy mixers stay
+    /// well below full scale, while the TIA and Sound Blaster swing close to it.
+    fn audio_gain(&self) -> f32 {
         match self {
-            EmulatorSystem::NES(sys) => sys.timing(),
-            EmulatorSystem::GameBoy(_) => emu_core::apu::TimingMode::Ntsc,
-            EmulatorSystem::Atari2600(_) => emu_core::apu::TimingMode::Ntsc,
-            EmulatorSystem::PC(_) => emu_core::apu::TimingMode::Ntsc,
-            EmulatorSystem::SNES(_) => emu_core::apu::TimingMode::Ntsc,
-            EmulatorSystem::N64(_) => emu_core::apu::TimingMode::Ntsc,
+            EmulatorSystem::NES(_) => 2.0,
+            EmulatorSystem::GameBoy(_) => 2.0,
+            EmulatorSystem::Atari2600(_) => 0.6,
+            EmulatorSystem::PC(_) => 0.6,
+            EmulatorSystem::SNES(_) => 1.0,
+            EmulatorSystem::N64(_) => 1.0,
         }
     }
 
@@ -434,8 +514,8 @@ impl EmulatorSystem {
             EmulatorSystem::NES(sys) => sys.get_audio_samples(count),
             EmulatorSystem::GameBoy(sys) => sys.get_audio_samples(count),
             EmulatorSystem::Atari2600(sys) => sys.get_audio_samples(count),
-            EmulatorSystem::PC(_) => vec![0; count], // TODO: Implement audio for PC
-            EmulatorSystem::SNES(_) => vec![0; count], // TODO: Implement audio for SNES
+            EmulatorSystem::PC(sys) => sys.get_audio_samples(count),
+            EmulatorSystem::SNES(sys) => sys.get_audio_samples(count),
             EmulatorSystem::N64(_) => vec![0; count], // TODO: Implement audio for N64
         }
     }
@@ -470,19 +550,39 @@ impl EmulatorSystem {
     }
 
     /// Get disk image for saving (PC only)
-    fn get_disk_image(&self, mount_id: &str) -> Option<&[u8]> {
+    fn export_disk_image(&self, mount_id: &str) -> Option<Vec<u8>> {
         if let EmulatorSystem::PC(sys) = self {
-            match mount_id {
-                "FloppyA" => sys.get_floppy_a(),
-                "FloppyB" => sys.get_floppy_b(),
-                "HardDrive" => sys.get_hard_drive(),
-                _ => None,
-            }
+            sys.export_disk(mount_id)
         } else {
             None
         }
     }
 
+    /// Check if a disk image has been written since it was mounted or saved
+    /// (PC only)
+    fn is_disk_modified(&self, mount_id: &str) -> bool {
+        if let EmulatorSystem::PC(sys) = self {
+            sys.disk_is_dirty(mount_id)
+        } else {
+            false
+        }
+    }
+
+    /// Clear the modified flag after a disk image was written back
+    fn mark_disk_saved(&mut self, mount_id: &str) {
+        if let EmulatorSystem::PC(sys) = self {
+            sys.mark_disk_saved(mount_id);
+        }
+    }
+
+    /// Battery-backed cartridge RAM to keep in a `.sav` file (NES only)
+    fn battery_ram(&self) -> Option<&[u8]> {
+        match self {
+            EmulatorSystem::NES(sys) => sys.battery_ram(),
+            _ => None,
+        }
+    }
+
     /// Check if this system requires the host key to be held for function keys
     /// Only PC system requires this to allow ESC and function keys to pass through to the emulated system
     fn requires_host_key_for_function_keys(&self) -> bool {
@@ -512,7 +612,13 @@ impl EmulatorSystem {
                     "Software".to_string()
                 }
             }
-            EmulatorSystem::SNES(_) => "Software".to_string(),
+            EmulatorSystem::SNES(snes_sys) => {
+                if snes_sys.renderer_name().contains("OpenGL") {
+                    "OpenGL".to_string()
+                } else {
+                    "Software".to_string()
+                }
+            }
             EmulatorSystem::N64(_) => {
                 // N64 uses software renderer by default
                 // Note: OpenGL renderer would need to be exposed via debug info or separate method
@@ -535,8 +641,7 @@ impl EmulatorSystem {
                 // PC has both software and hardware video adapters available
                 vec!["Software".to_string(), "OpenGL".to_string()]
             }
-            EmulatorSystem::SNES(_) => vec!["Software".to_string()],
-            EmulatorSystem::N64(_) => {
+            EmulatorSystem::SNES(_) | EmulatorSystem::N64(_) => {
                 // OpenGL renderer is available when opengl feature is enabled
                 #[cfg(feature = "opengl")]
                 {
@@ -582,8 +687,31 @@ fn key_mapping_to_button(key: Key, mapping: &settings::KeyMapping) -> Option<u8>
     }
 }
 
+/// Turbo A and B keys held (bits 0 and 1, like A and B), when the autofire
+/// cycle is in its pressed half (`turbo_on`)
+fn get_turbo_state(
+    window: &dyn WindowBackend,
+    mapping: &settings::KeyMapping,
+    turbo_on: bool,
+) -> u8 {
+    if !turbo_on {
+        return 0;
+    }
+    [&mapping.turbo_a, &mapping.turbo_b]
+        .iter()
+        .enumerate()
+        .filter(|(_, key)| string_to_key(key).is_some_and(|k| window.is_key_down(k)))
+        .fold(0, |state, (bit, _)| state | 1 << bit)
+}
+
 /// Get controller state for a player from current keyboard state (8-bit for NES/GB/Atari)
-fn get_controller_state(window: &dyn WindowBackend, mapping: &settings::KeyMapping) -> u8 {
+///
+/// Held turbo keys press A or B when `turbo_on`.
+fn get_controller_state(
+    window: &dyn WindowBackend,
+    mapping: &settings::KeyMapping,
+    turbo_on: bool,
+) -> u8 {
     let keys_to_check: Vec<Key> = vec![
         string_to_key(&mapping.a),
         string_to_key(&mapping.b),
@@ -606,7 +734,23 @@ fn get_controller_state(window: &dyn WindowBackend, mapping: &settings::KeyMappi
             }
         }
     }
-    state
+    state | get_turbo_state(window, mapping, turbo_on)
+}
+
+/// Get controller state in the common layout with the extended buttons
+/// (16-bit): A, B, Select, Start, Up, Down, Left, Right in bits 0-7 like
+/// [`get_controller_state`], then X, Y, L, R in bits 8-11
+fn get_extended_controller_state(
+    window: &dyn WindowBackend,
+    mapping: &settings::KeyMapping,
+    turbo_on: bool,
+) -> u16 {
+    let extended = [&mapping.x, &mapping.y, &mapping.l, &mapping.r]
+        .iter()
+        .enumerate()
+        .filter(|(_, name)| string_to_key(name).is_some_and(|k| window.is_key_down(k)))
+        .fold(0u16, |acc, (bit, _)| acc | 1 << (8 + bit));
+    get_controller_state(window, mapping, turbo_on) as u16 | extended
 }
 
 /// Get SNES controller state from current keyboard state (16-bit)
@@ -645,7 +789,13 @@ fn get_controller_state(window: &dyn WindowBackend, mapping: &settings::KeyMappi
 /// - Bit 5: L shoulder
 /// - Bit 4: R shoulder
 /// - Bits 3-0: Unused (always 0)
-fn get_snes_controller_state(window: &dyn WindowBackend, mapping: &settings::KeyMapping) -> u16 {
+///
+/// Held turbo keys press A or B when `turbo_on`.
+fn get_snes_controller_state(
+    window: &dyn WindowBackend,
+    mapping: &settings::KeyMapping,
+    turbo_on: bool,
+) -> u16 {
     let keys_to_check: Vec<Key> = vec![
         string_to_key(&mapping.a),
         string_to_key(&mapping.b),
@@ -690,6 +840,13 @@ fn get_snes_controller_state(window: &dyn WindowBackend, mapping: &settings::Key
             }
         }
     }
+    let turbo = get_turbo_state(window, mapping, turbo_on);
+    if turbo & 0x01 != 0 {
+        state |= 1 << 7; // A
+    }
+    if turbo & 0x02 != 0 {
+        state |= 1 << 15; // B
+    }
     state
 }
 
@@ -698,13 +855,21 @@ fn get_snes_controller_state(window: &dyn WindowBackend, mapping: &settings::Key
 struct StreamSource {
     rx: Receiver<i16>,
     sample_rate: u32,
+    /// Samples sent but not yet played (shared with the main loop)
+    queued: Arc<AtomicUsize>,
 }
 
 impl Iterator for StreamSource {
     type Item = f32;
 
     fn next(&mut self) -> Option<f32> {
-        let s = self.rx.try_recv().unwrap_or(0);
+        let s = match self.rx.try_recv() {
+            Ok(s) => {
+                self.queued.fetch_sub(1, Ordering::Relaxed);
+                s
+            }
+            Err(_) => 0,
+        };
         Some(s as f32 / 32768.0)
     }
 }
@@ -727,6 +892,324 @@ impl Source for StreamSource {
     }
 }
 
+/// Apply the project's expanded memory (EMS) settings to a PC system
+fn configure_pc_ems(pc_sys: &mut emu_pc::PcSystem, project: &HemuProject) {
+    let size_kb = project.get_ems_kb().unwrap_or(emu_pc::DEFAULT_EMS_KB);
+    let page_frame = project
+        .get_ems_page_frame()
+        .unwrap_or(emu_pc::DEFAULT_EMS_PAGE_FRAME);
+    if let Err(e) = pc_sys.configure_ems(size_kb, page_frame) {
+        eprintln!("{}, using the default EMS configuration", e);
+    }
+}
+
+/// Apply the project's CPU clock speed setting to a PC system
+fn configure_pc_cpu_speed(pc_sys: &mut emu_pc::PcSystem, project: &HemuProject) {
+    if let Some(speed) = project.get_cpu_speed() {
+        match speed.parse::<emu_pc::CpuSpeed>() {
+            Ok(speed) => pc_sys.set_cpu_speed(speed),
+            Err(e) => eprintln!("{}, using the CPU model's speed", e),
+        }
+    }
+}
+
+/// Apply the project's real-time clock and CMOS RAM to a PC system
+fn configure_pc_rtc(pc_sys: &mut emu_pc::PcSystem, project: &HemuProject) {
+    let offset_secs = project.get_rtc_offset_secs().unwrap_or(0);
+    let clock = match project.get_rtc_frozen_time() {
+        Some(time) => match time.parse::<emu_pc::RtcDateTime>() {
+            Ok(date_time) => emu_pc::RtcClock::Frozen {
+                time: date_time.timestamp(),
+            },
+            Err(e) => {
+                eprintln!("{}, using the host clock", e);
+                emu_pc::RtcClock::Host { offset_secs }
+            }
+        },
+        None => emu_pc::RtcClock::Host { offset_secs },
+    };
+    pc_sys.set_rtc_clock(clock);
+
+    if let Some(cmos) = project.get_cmos() {
+        if let Err(e) = pc_sys.load_cmos_ram(&cmos) {
+            eprintln!("{}, using the default CMOS contents", e);
+        }
+    }
+}
+
+/// Connect a PC system's serial ports as configured in the project
+fn configure_pc_serial(pc_sys: &mut emu_pc::PcSystem, project: &HemuProject) {
+    for port in 0..2 {
+        if let Some(binding) = project.get_serial(port) {
+            attach_pc_serial(pc_sys, port, binding);
+        }
+    }
+}
+
+/// Connect one PC serial port (0 = COM1, 1 = COM2) to the host
+fn attach_pc_serial(pc_sys: &mut emu_pc::PcSystem, port: usize, binding: &str) {
+    let result = binding
+        .parse::<emu_pc::SerialBinding>()
+        .and_then(|binding| {
+            pc_sys
+                .attach_serial(port, &binding)
+                .map_err(|e| e.to_string())
+        });
+    match result {
+        Ok(()) => println!("COM{}: {}", port + 1, binding),
+        Err(e) => eprintln!("COM{}: {}", port + 1, e),
+    }
+}
+
+/// Capture a PC system's printer output as configured in the project
+fn configure_pc_printer(pc_sys: &mut emu_pc::PcSystem, project: &HemuProject) {
+    if let Some(output) = project.get_lpt1() {
+        attach_pc_printer(pc_sys, output);
+    }
+}
+
+/// Apply the project's built-in DOS setting to a PC system
+fn configure_pc_builtin_dos(pc_sys: &mut emu_pc::PcSystem, project: &HemuProject) {
+    if let Some(enabled) = project.get_builtin_dos() {
+        pc_sys.set_builtin_dos(enabled);
+    }
+}
+
+/// Capture the PC printer port (LPT1) to a host file
+fn attach_pc_printer(pc_sys: &mut emu_pc::PcSystem, output: &str) {
+    let result = output
+        .parse::<emu_pc::PrinterOutput>()
+        .and_then(|output| pc_sys.attach_printer(&output).map_err(|e| e.to_string()));
+    match result {
+        Ok(()) => println!("LPT1: {}", output),
+        Err(e) => eprintln!("LPT1: {}", e),
+    }
+}
+
+/// Close the serial connections and printer capture of the running PC
+/// system so a replacement system can open the same ports, devices and files
+fn release_pc_host_io(sys: &mut EmulatorSystem) {
+    if let EmulatorSystem::PC(pc_sys) = sys {
+        for port in 0..2 {
+            pc_sys.detach_serial(port);
+        }
+        pc_sys.detach_printer();
+    }
+}
+
+/// PC mount points the guest can write to
+const WRITABLE_DISKS: &[&str] = &["FloppyA", "FloppyB", "HardDrive"];
+
+/// Read a PC disk image to mount; a folder is turned into a host folder drive
+fn read_disk_image(mount_id: &str, path: &std::path::Path) -> Result<Vec<u8>, String> {
+    if path.is_dir() {
+        let kind = if mount_id == "HardDrive" {
+            emu_pc::HostDriveKind::HardDrive
+        } else {
+            emu_pc::HostDriveKind::Floppy
+        };
+        emu_pc::build_host_folder_image(path, kind).map_err(|e| e.to_string())
+    } else {
+        fs::read(path).map_err(|e| e.to_string())
+    }
+}
+
+/// Write a disk image back to where it was mounted from
+///
+/// For a host folder drive, new and changed files are copied into the folder.
+fn save_disk_image(path: &str, data: &[u8]) -> Result<(), String> {
+    let path = std::path::Path::new(path);
+    if path.is_dir() {
+        emu_pc::sync_host_folder(data, path)
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    } else {
+        fs::write(path, data).map_err(|e| e.to_string())
+    }
+}
+
+/// Ask whether to load the other side of an Atari 2600 multi-load
+fn prompt_atari_next_load(
+    sys: &mut emu_atari2600::Atari2600System,
+    runtime_state: &RuntimeState,
+    egui_app: &mut EguiApp,
+) {
+    let (next, mount_id) = match sys.active_side() {
+        emu_atari2600::LoadSide::A => ("B", "SideB"),
+        emu_atari2600::LoadSide::B => ("A", "Cartridge"),
+    };
+    let file_name = runtime_state
+        .get_mount(mount_id)
+        .and_then(|p| std::path::Path::new(p).file_name())
+        .and_then(|n| n.to_str())
+        .unwrap_or(mount_id)
+        .to_string();
+
+    let answer = rfd::MessageDialog::new()
+        .set_level(rfd::MessageLevel::Info)
+        .set_title("Next load")
+        .set_description(format!(
+            "The game is asking for its next load. Load side {} ({}) now?",
+            next, file_name
+        ))
+        .set_buttons(rfd::MessageButtons::YesNo)
+        .show();
+    if answer != rfd::MessageDialogResult::Yes {
+        egui_app
+            .status_bar
+            .set_message("Multi-load request ignored".to_string());
+        return;
+    }
+
+    match sys.next_load() {
+        Ok(_) => {
+            egui_app
+                .status_bar
+                .set_message(format!("Loaded side {}", next));
+            egui_app
+                .tab_manager
+                .add_log(format!("Multi-load: loaded side {} ({})", next, file_name));
+        }
+        Err(e) => {
+            egui_app
+                .status_bar
+                .set_message(format!("Next load failed: {}", e));
+        }
+    }
+}
+
+/// Write a modified disk image back to the file it was mounted from and
+/// clear its modified flag
+fn write_back_disk(sys: &mut EmulatorSystem, mount_id: &str, path: &str) -> Result<(), String> {
+    let data = sys
+        .export_disk_image(mount_id)
+        .ok_or_else(|| format!("{} is not mounted", mount_id))?;
+    save_disk_image(path, &data)?;
+    sys.mark_disk_saved(mount_id);
+    Ok(())
+}
+
+/// Save every modified writable disk without asking ("Save Disk Changes")
+///
+/// Returns the number of images written back, or stops at the first error.
+fn save_modified_disks(
+    sys: &mut EmulatorSystem,
+    runtime_state: &RuntimeState,
+    egui_app: &mut EguiApp,
+) -> Result<usize, String> {
+    let mut saved = 0;
+    for &mount_id in WRITABLE_DISKS {
+        if !sys.is_disk_modified(mount_id) {
+            continue;
+        }
+        let Some(path) = runtime_state.get_mount(mount_id).cloned() else {
+            continue;
+        };
+        write_back_disk(sys, mount_id, &path)
+            .map_err(|e| format!("Error saving {}: {}", mount_id, e))?;
+        saved += 1;
+        egui_app
+            .tab_manager
+            .add_log(format!("Saved {} to {}", mount_id, path));
+    }
+    Ok(saved)
+}
+
+/// Offer to write modified disk images back to the files they were mounted from
+///
+/// Asks once per modified image among `mount_ids`. Returns false if the user
+/// cancelled, in which case the eject or exit should not go ahead.
+fn prompt_save_modified_disks(
+    sys: &mut EmulatorSystem,
+    runtime_state: &RuntimeState,
+    mount_ids: &[&str],
+    egui_app: &mut EguiApp,
+) -> bool {
+    for &mount_id in mount_ids {
+        if !sys.is_disk_modified(mount_id) {
+            continue;
+        }
+        let Some(path) = runtime_state.get_mount(mount_id).cloned() else {
+            continue;
+        };
+        let file_name = std::path::Path::new(&path)
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or(&path)
+            .to_string();
+
+        let answer = rfd::MessageDialog::new()
+            .set_level(rfd::MessageLevel::Warning)
+            .set_title("Save disk changes?")
+            .set_description(format!(
+                "{} ({}) has been modified. Save the changes to the image file?",
+                mount_id, file_name
+            ))
+            .set_buttons(rfd::MessageButtons::YesNoCancel)
+            .show();
+
+        match answer {
+            rfd::MessageDialogResult::Yes => match write_back_disk(sys, mount_id, &path) {
+                Ok(()) => {
+                    egui_app
+                        .tab_manager
+                        .add_log(format!("Saved {} to {}", mount_id, file_name));
+                }
+                Err(e) => {
+                    egui_app
+                        .status_bar
+                        .set_message(format!("Error saving {}: {}", file_name, e));
+                    return false;
+                }
+            },
+            rfd::MessageDialogResult::No => {
+                egui_app
+                    .tab_manager
+                    .add_log(format!("Discarded changes to {}", file_name));
+            }
+            _ => return false,
+        }
+    }
+    true
+}
+
+/// Reload the `.sav` file next to a freshly mounted NES cartridge with a battery
+///
+/// Returns a message for the log if a save file was found.
+fn load_nes_battery_save(nes_sys: &mut emu_nes::NesSystem, rom_path: &str) -> Option<String> {
+    if !nes_sys.has_battery() {
+        return None;
+    }
+    let rom_path = std::path::Path::new(rom_path);
+    let sav_path = battery_save::sav_path(rom_path);
+    match battery_save::read(rom_path) {
+        Ok(Some(data)) => Some(match nes_sys.load_battery_ram(&data) {
+            Ok(()) => format!("Loaded battery save {}", sav_path.display()),
+            Err(e) => format!("Ignored battery save {}: {}", sav_path.display(), e),
+        }),
+        Ok(None) => None,
+        Err(e) => Some(format!(
+            "Error reading battery save {}: {}",
+            sav_path.display(),
+            e
+        )),
+    }
+}
+
+/// Write battery-backed cartridge RAM to the `.sav` file next to the mounted ROM
+///
+/// Called before the cartridge is replaced and on exit. Returns a message for
+/// the log if the system has battery RAM to save.
+fn write_battery_save(sys: &EmulatorSystem, runtime_state: &RuntimeState) -> Option<String> {
+    let data = sys.battery_ram()?;
+    let rom_path = std::path::Path::new(runtime_state.get_mount("Cartridge")?);
+    let sav_path = battery_save::sav_path(rom_path);
+    Some(match battery_save::write(rom_path, data) {
+        Ok(()) => format!("Saved battery RAM to {}", sav_path.display()),
+        Err(e) => format!("Error writing battery save {}: {}", sav_path.display(), e),
+    })
+}
+
 /// Save current emulation state to a .hemu project file
 /// Works for all systems, not just PC
 #[allow(dead_code)]
@@ -750,8 +1233,9 @@ fn save_project(
         // Get system name first to avoid borrowing issue
         let system_name = sys.system_name();
         let relevant_mounts: Vec<&str> = match system_name {
-            "pc" => vec!["BIOS", "FloppyA", "FloppyB", "HardDrive"],
-            "nes" | "gameboy" | "atari2600" | "snes" | "n64" => vec!["Cartridge"],
+            "pc" => vec!["BIOS", "FloppyA", "FloppyB", "HardDrive", "Cassette"],
+            "atari2600" => vec!["Cartridge", "SideB"],
+            "nes" | "gameboy" | "snes" | "n64" => vec!["Cartridge"],
             _ => vec![],
         };
 
@@ -782,6 +1266,7 @@ fn save_project(
                 emu_pc::BootPriority::HardDriveFirst => "HardDriveFirst",
                 emu_pc::BootPriority::FloppyOnly => "FloppyOnly",
                 emu_pc::BootPriority::HardDriveOnly => "HardDriveOnly",
+                emu_pc::BootPriority::CdRomFirst => "CdRomFirst",
             };
             project.set_boot_priority(priority_str.to_string());
 
@@ -807,6 +1292,21 @@ fn save_project(
             // Get memory size from PC system
             let memory_kb = pc_sys.memory_kb();
             project.set_memory_kb(memory_kb);
+            project.set_ems(pc_sys.ems_kb(), pc_sys.ems_page_frame());
+            project.set_cpu_speed(pc_sys.cpu_speed().to_string());
+            match pc_sys.rtc_clock() {
+                emu_pc::RtcClock::Host { offset_secs } => project.set_rtc(offset_secs, None),
+                emu_pc::RtcClock::Frozen { time } => {
+                    let frozen = emu_pc::RtcDateTime::from_timestamp(time).to_string();
+                    project.set_rtc(0, Some(frozen));
+                }
+            }
+            project.set_cmos(&pc_sys.cmos_ram());
+            for port in 0..2 {
+                project.set_serial(port, pc_sys.serial_binding(port).map(|b| b.to_string()));
+            }
+            project.set_lpt1(pc_sys.printer_output().map(|o| o.to_string()));
+            project.set_builtin_dos(pc_sys.builtin_dos());
 
             // Get video mode from PC system
             let video_name = pc_sys.video_adapter_name();
@@ -814,6 +1314,8 @@ fn save_project(
                 "VGA"
             } else if video_name.contains("EGA") {
                 "EGA"
+            } else if video_name.contains("Hercules") {
+                "Hercules"
             } else {
                 "CGA"
             };
@@ -839,6 +1341,54 @@ fn save_project(
     None
 }
 
+/// Build the save-state manager list from every game's save file
+fn save_state_list_items() -> Vec<egui_ui::SaveStateListItem> {
+    GameSaves::list_all()
+        .into_iter()
+        .map(|entry| {
+            let meta = entry.save.meta;
+            let thumbnail = meta.thumbnail.as_ref().and_then(|thumb| {
+                thumb
+                    .to_rgba()
+                    .map(|rgba| ([thumb.width as usize, thumb.height as usize], rgba))
+            });
+            egui_ui::SaveStateListItem {
+                game: meta.rom_name.unwrap_or_else(|| {
+                    format!("ROM {}", &entry.rom_hash[..8.min(entry.rom_hash.len())])
+                }),
+                system: meta.system.unwrap_or_else(|| "Unknown".to_string()),
+                rom_hash: entry.rom_hash,
+                slot: entry.slot,
+                timestamp: entry.save.timestamp,
+                play_time_secs: meta.play_time_secs,
+                thumbnail,
+            }
+        })
+        .collect()
+}
+
+/// Decode a save slot and restore it into the running system.
+/// Returns the play time recorded with the state.
+fn load_state_slot(
+    sys: &mut EmulatorSystem,
+    saves: &GameSaves,
+    slot: u8,
+    rom_hash: &str,
+) -> Result<u64, String> {
+    let data = saves
+        .load_slot(slot, rom_hash)
+        .map_err(|e| format!("Error loading state: {}", e))?;
+    let state_str = String::from_utf8(data).map_err(|_| "Invalid state encoding".to_string())?;
+    let state = serde_json::from_str(&state_str).map_err(|_| "Invalid state data".to_string())?;
+    sys.load_state(&state)
+        .map_err(|e| format!("Error loading state: {}", e))?;
+    Ok(saves
+        .slots
+        .get(&slot)
+        .map(|s| s.meta.play_time_secs)
+        .unwrap_or(0))
+}
+
 /// Save a screenshot to the screenshots directory
 /// Format: screenshots/<system-name>/YYYYMMDDHHMMSSRRR.png
 /// where RRR is a random number between 000 and 999
@@ -930,6 +1480,132 @@ fn enable_n64_opengl_renderer(
     None
 }
 
+/// Switch a SNES system to the OpenGL renderer if the settings ask for it
+///
+/// Returns a message for the log when the switch fails.
+#[cfg(feature = "opengl")]
+fn apply_snes_renderer_preference(
+    snes_sys: &mut emu_snes::SnesSystem,
+    video_backend: &str,
+    backend: &Sdl2EguiBackend,
+) -> Option<String> {
+    if video_backend != "opengl" {
+        return None;
+    }
+    let gl = backend.gl_context()?;
+    match snes_sys.enable_opengl_renderer(gl) {
+        Ok(()) => None,
+        Err(e) => {
+            eprintln!("Failed to enable OpenGL renderer: {}", e);
+            Some(format!(
+                "Failed to enable OpenGL renderer, using Software: {}",
+                e
+            ))
+        }
+    }
+}
+
+/// Stub for when opengl feature is not enabled
+#[cfg(not(feature = "opengl"))]
+fn apply_snes_renderer_preference(
+    _snes_sys: &mut emu_snes::SnesSystem,
+    _video_backend: &str,
+    _backend: &Sdl2EguiBackend,
+) -> Option<String> {
+    None
+}
+
+/// Move N64 RSP/RDP processing to a worker thread on multi-core hosts
+///
+/// Only takes effect with the software renderer; call it after
+/// `enable_n64_opengl_renderer`.
+fn enable_n64_threaded_rcp(sys: &mut EmulatorSystem) {
+    if let EmulatorSystem::N64(n64_sys) = sys {
+        let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+        if cores > 1 {
+            if let Err(e) = n64_sys.set_threaded_rcp(true) {
+                eprintln!("N64 RSP/RDP stays on the emulation thread: {}", e);
+            }
+        }
+    }
+}
+
+/// Release every controller button so movies start from a known input state
+fn release_all_controllers(sys: &mut EmulatorSystem) {
+    for port in 0..2 {
+        sys.set_controller(port, 0);
+    }
+}
+
+/// Frames run by [`step_frames`]
+struct SteppedFrames {
+    /// Frames emulated (fewer than asked for if the system reported an error)
+    count: usize,
+    /// Movie playback ran out of frames
+    movie_finished: bool,
+}
+
+/// Emulate up to `count` frames, queueing their audio and recording rewind
+/// snapshots
+///
+/// Only the last frame is kept, in `last_frame`, for display; the frames it
+/// replaces are recycled.
+#[allow(clippy::too_many_arguments)]
+fn step_frames(
+    sys: &mut EmulatorSystem,
+    count: usize,
+    settings: &Settings,
+    movie_session: &mut MovieSession,
+    rewind: &mut RewindBuffer,
+    samples_per_frame: f64,
+    audio_sample_credit: &mut f64,
+    audio_tx: &SyncSender<i16>,
+    audio_queued: &AtomicUsize,
+    last_frame: &mut Option<Frame>,
+) -> SteppedFrames {
+    let mut stepped = SteppedFrames {
+        count: 0,
+        movie_finished: false,
+    };
+    for _ in 0..count {
+        match movie_session.next_frame() {
+            MovieStep::Live => {}
+            MovieStep::Play(input) => sys.apply_movie_input(&input),
+            MovieStep::Finished => stepped.movie_finished = true,
+        }
+
+        let frame = match sys.step_frame() {
+            Ok(frame) => frame,
+            Err(e) => {
+                eprintln!("Emulation error: {}", e);
+                break;
+            }
+        };
+        stepped.count += 1;
+
+        if let Some(skipped) = last_frame.replace(frame) {
+            sys.recycle_frame(skipped);
+        }
+
+        if settings.rewind_memory_mb > 0 && sys.supports_save_states() {
+            rewind.record(|| sys.save_state());
+        }
+
+        // Handle audio for each stepped frame
+        *audio_sample_credit += samples_per_frame;
+        let sample_count = *audio_sample_credit as usize;
+        *audio_sample_credit -= sample_count as f64;
+        let mut audio_samples = sys.get_audio_samples(sample_count);
+        AudioGain::new(sys.audio_gain(), settings.master_volume).apply(&mut audio_samples);
+        for sample in audio_samples {
+            if audio_tx.try_send(sample).is_ok() {
+                audio_queued.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+    stepped
+}
+
 /// Create a file dialog with individual filters for each file type plus an "All Files" option
 /// This improves the user experience by allowing them to filter by specific file types
 #[allow(dead_code)]
@@ -977,10 +1653,17 @@ struct CliArgs {
     slot3: Option<String>,  // FloppyB
     slot4: Option<String>,  // HardDrive
     slot5: Option<String>,  // Reserved for future use
+    serial: [Option<String>; 2], // Host connections for PC COM1/COM2
+    lpt1: Option<String>,   // Host capture file for PC LPT1
     create_blank_disk: Option<(String, String)>, // (path, format)
     show_help: bool,        // Show help message
     show_version: bool,     // Show version
     benchmark: bool,        // Benchmark mode: disable frame limiter to measure raw performance
+    watch: bool,            // Reload the Atari 2600 cartridge when its file changes
+    self_test: bool,        // Run built-in test ROMs and exit
+    self_test_report: Option<String>, // Self-test JSON report path
+    bench_frames: Option<u32>, // Run the ROM headlessly for this many frames and report timing
+    bench_report: Option<String>, // Benchmark JSON report path
     // Logging configuration
     log_level: Option<String>,      // Global log level
     log_cpu: Option<String>,        // CPU log level
@@ -1009,6 +1692,36 @@ impl CliArgs {
                 "--benchmark" => {
                     args.benchmark = true;
                 }
+                "--watch" => {
+                    args.watch = true;
+                }
+                "--self-test" => {
+                    args.self_test = true;
+                }
+                "--self-test-report" => {
+                    if let Some(path) = arg_iter.next() {
+                        args.self_test = true;
+                        args.self_test_report = Some(path);
+                    } else {
+                        eprintln!("Error: --self-test-report requires a file path.");
+                        std::process::exit(1);
+                    }
+                }
+                "--bench" => match arg_iter.next().and_then(|n| n.parse::<u32>().ok()) {
+                    Some(frames) if frames > 0 => args.bench_frames = Some(frames),
+                    _ => {
+                        eprintln!("Error: --bench requires a frame count (e.g., 600).");
+                        std::process::exit(1);
+                    }
+                },
+                "--bench-report" => {
+                    if let Some(path) = arg_iter.next() {
+                        args.bench_report = Some(path);
+                    } else {
+                        eprintln!("Error: --bench-report requires a file path.");
+                        std::process::exit(1);
+                    }
+                }
                 "--system" | "-S" => {
                     if let Some(system) = arg_iter.next() {
                         args.system = Some(system);
@@ -1034,6 +1747,26 @@ impl CliArgs {
                 "--slot5" => {
                     args.slot5 = arg_iter.next();
                 }
+                "--serial1" | "--serial2" => {
+                    let port = if arg == "--serial1" { 0 } else { 1 };
+                    if let Some(binding) = arg_iter.next() {
+                        args.serial[port] = Some(binding);
+                    } else {
+                        eprintln!(
+                            "Error: {} requires a connection (e.g., 'tcp:localhost:5555').",
+                            arg
+                        );
+                        std::process::exit(1);
+                    }
+                }
+                "--lpt1" => {
+                    if let Some(output) = arg_iter.next() {
+                        args.lpt1 = Some(output);
+                    } else {
+                        eprintln!("Error: --lpt1 requires a file (e.g., 'text:printer.txt').");
+                        std::process::exit(1);
+                    }
+                }
                 "--create-blank-disk" => {
                     if let Some(path) = arg_iter.next() {
                         if let Some(format) = arg_iter.next() {
@@ -1138,6 +1871,13 @@ impl CliArgs {
         eprintln!(
             "  --benchmark              Disable frame limiter to measure raw emulation performance"
         );
+        eprintln!(
+            "  --bench <FRAMES>         Run FILE headlessly for FRAMES frames and report frame times"
+        );
+        eprintln!("  --bench-report <path>    Also write the benchmark results as JSON");
+        eprintln!(
+            "  --watch                  Reload and reset the Atari 2600 cartridge when its file changes"
+        );
         eprintln!(
             "  -S, --system <SYSTEM>    Start clean system (pc, nes, gb, atari2600, snes, n64)"
         );
@@ -1146,8 +1886,16 @@ impl CliArgs {
         eprintln!("  --slot3 <file>           Load file into slot 3 (Floppy B for PC)");
         eprintln!("  --slot4 <file>           Load file into slot 4 (Hard Drive for PC)");
         eprintln!("  --slot5 <file>           Load file into slot 5 (reserved)");
+        eprintln!("  --serial1 <conn>         Connect PC COM1 to the host (tcp:<host>:<port>,");
+        eprintln!("                           tcp-listen:[<address>:]<port> or pty:<path>)");
+        eprintln!("  --serial2 <conn>         Connect PC COM2 to the host");
+        eprintln!("  --lpt1 <file>            Capture PC printer output to a file (raw:<path>");
+        eprintln!("                           or text:<path>; a bare path is raw)");
         eprintln!("  --create-blank-disk <path> <format>");
         eprintln!("                           Create a blank disk image");
+        eprintln!("  --self-test              Run built-in test ROMs for every system and exit");
+        eprintln!("  --self-test-report <path>");
+        eprintln!("                           Self-test JSON report path (default: self_test_report.json)");
         eprintln!();
         eprintln!("Logging Options:");
         eprintln!("  --log-level <LEVEL>      Set global log level (off, error, warn, info, debug, trace)");
@@ -1168,6 +1916,9 @@ impl CliArgs {
         eprintln!(
             "  hemu --benchmark game.nes                      # Benchmark mode (no frame limiter)"
         );
+        eprintln!(
+            "  hemu --bench 600 game.nes                      # Time 600 frames without a window"
+        );
         eprintln!(
             "  hemu test.com                                  # Load DOS COM file (auto-detect)"
         );
@@ -1188,10 +1939,18 @@ impl CliArgs {
         eprintln!(
             "  hemu --slot2 boot.img --slot4 hdd.img         # Load PC with floppy and hard drive"
         );
+        eprintln!(
+            "  hemu --system pc --serial1 tcp-listen:5555     # PC with COM1 waiting for a null-modem peer"
+        );
+        eprintln!(
+            "  hemu --system pc --serial1 tcp:localhost:5555  # Second PC linked to the first"
+        );
+        eprintln!("  hemu --system pc --lpt1 text:printer.txt       # PC printing to a text file");
         eprintln!("  hemu --create-blank-disk floppy.img 1.44m      # Create 1.44MB floppy image");
         eprintln!(
             "  hemu --create-blank-disk hdd.img 20m           # Create 20MB hard drive image"
         );
+        eprintln!("  hemu --self-test                               # Run compatibility self-test");
     }
 
     /// Print version information
@@ -1279,13 +2038,60 @@ fn main() {
         std::process::exit(0);
     }
 
-    // Handle --create-blank-disk command
-    if let Some((path, format_str)) = &cli_args.create_blank_disk {
-        match format_str.to_lowercase().as_str() {
-            "360k" => {
-                let disk = emu_pc::create_blank_floppy(emu_pc::FloppyFormat::Floppy360K);
-                if let Err(e) = fs::write(path, disk) {
-                    eprintln!("Error creating disk image: {}", e);
+    // Handle --self-test: run built-in test ROMs headlessly and write a local report
+    if cli_args.self_test {
+        let report = self_test::run_all();
+        report.print_table();
+
+        let report_path = cli_args
+            .self_test_report
+            .as_deref()
+            .unwrap_or("self_test_report.json");
+        match report.write_json(std::path::Path::new(report_path)) {
+            Ok(()) => println!("Report written to {}", report_path),
+            Err(e) => eprintln!("Error: {}", e),
+        }
+
+        std::process::exit(if report.all_passed() { 0 } else { 1 });
+    }
+
+    // Handle --bench: run the ROM headlessly for a fixed number of frames
+    if let Some(frames) = cli_args.bench_frames {
+        let Some(rom_path) = &cli_args.rom_path else {
+            eprintln!("Error: --bench requires a ROM file.");
+            std::process::exit(1);
+        };
+        let report = archive::read_rom(rom_path)
+            .map_err(|e| format!("Failed to read ROM file: {}", e))
+            .and_then(|data| bench::run_rom(&data, frames));
+        match report {
+            Ok(report) => {
+                report.print();
+                if let Some(path) = &cli_args.bench_report {
+                    match report.write_json(std::path::Path::new(path)) {
+                        Ok(()) => println!("Report written to {}", path),
+                        Err(e) => {
+                            eprintln!("Error: {}", e);
+                            std::process::exit(1);
+                        }
+                    }
+                }
+                std::process::exit(0);
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    // Handle --create-blank-disk command
+    if let Some((path, format_str)) = &cli_args.create_blank_disk {
+        match format_str.to_lowercase().as_str() {
+            "360k" => {
+                let disk = emu_pc::create_blank_floppy(emu_pc::FloppyFormat::Floppy360K);
+                if let Err(e) = fs::write(path, disk) {
+                    eprintln!("Error creating disk image: {}", e);
                     std::process::exit(1);
                 }
                 println!("Created 360KB floppy disk: {}", path);
@@ -1468,6 +2274,8 @@ fn main() {
     let mut rom_hash: Option<String> = None;
     let mut rom_loaded = false;
     let mut status_message = String::new();
+    // Archive given on the command line that holds several ROMs
+    let mut archive_choice = None;
 
     // Initialize system based on --system parameter if specified
     if let Some(ref system_name) = cli_args.system {
@@ -1481,7 +2289,7 @@ fn main() {
                 // If a file is provided with --system nes, load it directly
                 if let Some(ref p) = rom_path {
                     if !p.to_lowercase().ends_with(".hemu") {
-                        match std::fs::read(p) {
+                        match archive::read_rom(p) {
                             Ok(data) => {
                                 rom_hash = Some(GameSaves::rom_hash(&data));
                                 if let EmulatorSystem::NES(nes_sys) = &mut sys {
@@ -1491,6 +2299,9 @@ fn main() {
                                         rom_hash = None;
                                     } else {
                                         rom_loaded = true;
+                                        if let Some(msg) = load_nes_battery_save(nes_sys, p) {
+                                            println!("{}", msg);
+                                        }
                                         runtime_state.set_mount("Cartridge".to_string(), p.clone());
                                         settings.last_rom_path = Some(p.clone());
                                         if let Err(e) = settings.save() {
@@ -1517,7 +2328,7 @@ fn main() {
                 // If a file is provided with --system gb, load it directly
                 if let Some(ref p) = rom_path {
                     if !p.to_lowercase().ends_with(".hemu") {
-                        match std::fs::read(p) {
+                        match archive::read_rom(p) {
                             Ok(data) => {
                                 rom_hash = Some(GameSaves::rom_hash(&data));
                                 if let EmulatorSystem::GameBoy(gb_sys) = &mut sys {
@@ -1553,7 +2364,7 @@ fn main() {
                 // If a file is provided with --system atari2600, load it directly
                 if let Some(ref p) = rom_path {
                     if !p.to_lowercase().ends_with(".hemu") {
-                        match std::fs::read(p) {
+                        match archive::read_rom(p) {
                             Ok(data) => {
                                 rom_hash = Some(GameSaves::rom_hash(&data));
                                 if let EmulatorSystem::Atari2600(atari_sys) = &mut sys {
@@ -1589,7 +2400,7 @@ fn main() {
                 // If a file is provided with --system pc, mount it to FloppyB
                 if let Some(ref p) = rom_path {
                     if !p.to_lowercase().ends_with(".hemu") {
-                        match std::fs::read(p) {
+                        match archive::read_rom(p) {
                             Ok(data) => {
                                 if let EmulatorSystem::PC(pc_sys) = &mut sys {
                                     if let Err(e) = pc_sys.mount("FloppyB", &data) {
@@ -1623,7 +2434,7 @@ fn main() {
                 // If a file is provided with --system snes, load it directly
                 if let Some(ref p) = rom_path {
                     if !p.to_lowercase().ends_with(".hemu") {
-                        match std::fs::read(p) {
+                        match archive::read_rom(p) {
                             Ok(data) => {
                                 rom_hash = Some(GameSaves::rom_hash(&data));
                                 if let EmulatorSystem::SNES(snes_sys) = &mut sys {
@@ -1659,7 +2470,7 @@ fn main() {
                 // If a file is provided with --system n64, load it directly
                 if let Some(ref p) = rom_path {
                     if !p.to_lowercase().ends_with(".hemu") {
-                        match std::fs::read(p) {
+                        match archive::read_rom(p) {
                             Ok(data) => {
                                 rom_hash = Some(GameSaves::rom_hash(&data));
                                 if let EmulatorSystem::N64(n64_sys) = &mut sys {
@@ -1755,6 +2566,10 @@ fn main() {
                                         println!("Video mode: VGA");
                                         Box::new(emu_pc::SoftwareVgaAdapter::new())
                                     }
+                                    "Hercules" | "MDA" => {
+                                        println!("Video mode: Hercules");
+                                        Box::new(emu_pc::SoftwareHerculesAdapter::new())
+                                    }
                                     "CGA" => {
                                         println!("Video mode: CGA");
                                         Box::new(emu_pc::SoftwareCgaAdapter::new())
@@ -1772,6 +2587,12 @@ fn main() {
                         // Create PC system with configuration
                         let mut pc_sys =
                             emu_pc::PcSystem::with_config(cpu_model, memory_kb, video_adapter);
+                        configure_pc_ems(&mut pc_sys, &project);
+                        configure_pc_cpu_speed(&mut pc_sys, &project);
+                        configure_pc_rtc(&mut pc_sys, &project);
+                        configure_pc_serial(&mut pc_sys, &project);
+                        configure_pc_printer(&mut pc_sys, &project);
+                        configure_pc_builtin_dos(&mut pc_sys, &project);
 
                         // Load boot priority if specified
                         if let Some(priority_str) = project.boot_priority.as_ref() {
@@ -1780,6 +2601,7 @@ fn main() {
                                 "HardDriveFirst" => emu_pc::BootPriority::HardDriveFirst,
                                 "FloppyOnly" => emu_pc::BootPriority::FloppyOnly,
                                 "HardDriveOnly" => emu_pc::BootPriority::HardDriveOnly,
+                                "CdRomFirst" => emu_pc::BootPriority::CdRomFirst,
                                 _ => emu_pc::BootPriority::FloppyFirst,
                             };
                             pc_sys.set_boot_priority(priority);
@@ -1792,7 +2614,7 @@ fn main() {
                             .unwrap_or(std::path::Path::new("."));
                         for (mount_id, relative_path) in &project.mounts {
                             let full_path = project_dir.join(relative_path);
-                            match fs::read(&full_path) {
+                            match read_disk_image(mount_id, &full_path) {
                                 Ok(data) => {
                                     if let Err(e) = pc_sys.mount(mount_id, &data) {
                                         eprintln!("Failed to mount {}: {}", mount_id, e);
@@ -1835,7 +2657,7 @@ fn main() {
             }
         } else {
             // Regular ROM file detection (not a .hemu file)
-            match std::fs::read(p) {
+            match archive::read_rom(p) {
                 Ok(data) => match detect_rom_type(&data) {
                     Ok(SystemType::NES) => {
                         rom_hash = Some(GameSaves::rom_hash(&data));
@@ -1849,6 +2671,9 @@ fn main() {
                             // Enable OpenGL renderer if requested (note: GL context not available at startup)
                             // OpenGL can be enabled later when switching renderers
                             rom_loaded = true;
+                            if let Some(msg) = load_nes_battery_save(&mut nes_sys, p) {
+                                println!("{}", msg);
+                            }
                             sys = EmulatorSystem::NES(Box::new(nes_sys));
                             runtime_state.set_mount("Cartridge".to_string(), p.clone());
                             settings.last_rom_path = Some(p.clone()); // Keep for backward compat
@@ -1898,16 +2723,24 @@ fn main() {
                         }
                     }
                     Ok(SystemType::PC) => {
-                        // PC executables should be on disk images, not loaded directly
-                        // Create a new PC system and let user mount disk images via F3
-                        status_message =
-                            "PC system detected. Use F3 to mount disk images.".to_string();
+                        // The program runs on the built-in DOS after POST;
+                        // disk images can still be mounted via F3
                         rom_hash = None; // PC systems don't use ROM hash
-                        let pc_sys = emu_pc::PcSystem::new();
-                        sys = EmulatorSystem::PC(Box::new(pc_sys));
-                        // Don't save mount points for PC since they use disk images
-                        eprintln!("PC executable detected. Please mount disk images using F3.");
-                        println!("Initialized PC system. Mount disk images to proceed.");
+                        let mut pc_sys = emu_pc::PcSystem::new();
+                        if let Err(e) = pc_sys.mount("Executable", &data) {
+                            eprintln!("Failed to load DOS program: {}", e);
+                            status_message = format!("Error: {}", e);
+                        } else {
+                            rom_loaded = true;
+                            sys = EmulatorSystem::PC(Box::new(pc_sys));
+                            runtime_state.set_mount("Executable".to_string(), p.clone());
+                            settings.last_rom_path = Some(p.clone());
+                            if let Err(e) = settings.save() {
+                                eprintln!("Warning: Failed to save settings: {}", e);
+                            }
+                            status_message = "PC executable loaded".to_string();
+                            println!("Loaded DOS program: {}", p);
+                        }
                     }
                     Ok(SystemType::SNES) => {
                         rom_hash = Some(GameSaves::rom_hash(&data));
@@ -1952,6 +2785,10 @@ fn main() {
                         status_message = format!("Unsupported ROM: {}", e);
                     }
                 }, // closes inner match detect_rom_type
+                Err(ArchiveError::MultipleRoms(members)) => {
+                    status_message = "Choose a ROM from the archive".to_string();
+                    archive_choice = Some(members);
+                }
                 Err(e) => {
                     eprintln!("Failed to read ROM file: {}", e);
                 }
@@ -1994,7 +2831,7 @@ fn main() {
 
         // Slot 2: Floppy A
         if let Some(ref slot2_path) = cli_args.slot2 {
-            match fs::read(slot2_path) {
+            match read_disk_image("FloppyA", std::path::Path::new(slot2_path)) {
                 Ok(data) => {
                     if let Err(e) = pc_sys.mount("FloppyA", &data) {
                         eprintln!("Failed to mount Floppy A from slot 2: {}", e);
@@ -2009,7 +2846,7 @@ fn main() {
 
         // Slot 3: Floppy B
         if let Some(ref slot3_path) = cli_args.slot3 {
-            match fs::read(slot3_path) {
+            match read_disk_image("FloppyB", std::path::Path::new(slot3_path)) {
                 Ok(data) => {
                     if let Err(e) = pc_sys.mount("FloppyB", &data) {
                         eprintln!("Failed to mount Floppy B from slot 3: {}", e);
@@ -2024,7 +2861,7 @@ fn main() {
 
         // Slot 4: Hard Drive
         if let Some(ref slot4_path) = cli_args.slot4 {
-            match fs::read(slot4_path) {
+            match read_disk_image("HardDrive", std::path::Path::new(slot4_path)) {
                 Ok(data) => {
                     if let Err(e) = pc_sys.mount("HardDrive", &data) {
                         eprintln!("Failed to mount Hard Drive from slot 4: {}", e);
@@ -2048,6 +2885,27 @@ fn main() {
                 eprintln!("Warning: Failed to save settings: {}", e);
             }
         }
+
+        // Serial ports given on the command line replace the project's
+        for (port, binding) in cli_args.serial.iter().enumerate() {
+            if let Some(binding) = binding {
+                pc_sys.detach_serial(port);
+                attach_pc_serial(pc_sys, port, binding);
+            }
+        }
+        if let Some(output) = &cli_args.lpt1 {
+            pc_sys.detach_printer();
+            attach_pc_printer(pc_sys, output);
+        }
+    } else {
+        if cli_args.serial.iter().any(Option::is_some) {
+            eprintln!(
+                "Warning: --serial1/--serial2 only apply to the PC system and will be ignored"
+            );
+        }
+        if cli_args.lpt1.is_some() {
+            eprintln!("Warning: --lpt1 only applies to the PC system and will be ignored");
+        }
     }
 
     // Get resolution from the system
@@ -2059,7 +2917,7 @@ fn main() {
 
     // Create egui backend
     let mut egui_backend = match Sdl2EguiBackend::new(
-        "Hemulator - Multi-System Emulator",
+        now_playing::APP_TITLE,
         window_width as u32,
         window_height as u32,
     ) {
@@ -2089,8 +2947,26 @@ fn main() {
     egui_app.property_pane.available_renderers = sys.get_available_renderers();
     egui_app.property_pane.display_filter = settings.display_filter; // Initialize from settings
     egui_app.status_bar.set_message(status_message.clone());
+    if let Some(members) = archive_choice {
+        egui_app.show_archive_chooser(members);
+    }
     // Initialize recent files menu
     egui_app.update_recent_files(settings.get_recent_files().to_vec());
+    egui_app.menu_bar.show_input_display = settings.show_input_display;
+    egui_app.property_pane.master_volume_percent = (settings.master_volume * 100.0).round() as i32;
+    egui_app.property_pane.pacing_mode = settings.pacing_mode;
+
+    // Frame pacing: vsync is only wanted when presenting on the host refresh
+    let mut frame_pacer = FramePacer::new(settings.pacing_mode);
+    let mut rom_watcher: Option<RomWatcher> = None;
+    if let Err(e) = egui_backend.set_vsync(frame_pacer.wants_vsync()) {
+        eprintln!(
+            "Warning: Failed to set vsync: {}. Using fixed timer pacing.",
+            e
+        );
+        frame_pacer.set_mode(PacingMode::Timer);
+        egui_app.property_pane.pacing_mode = PacingMode::Timer;
+    }
 
     // Show New Project tab on startup if no ROM/project was loaded
     if !rom_loaded {
@@ -2101,6 +2977,7 @@ fn main() {
     if let Some(renderer_name) = enable_n64_opengl_renderer(&mut sys, &egui_backend) {
         egui_app.property_pane.rendering_backend = renderer_name;
     }
+    enable_n64_threaded_rcp(&mut sys);
 
     // Initialize audio output
     let (_stream, stream_handle) = match OutputStream::try_default() {
@@ -2114,10 +2991,12 @@ fn main() {
         }
     };
     let (audio_tx, audio_rx) = sync_channel::<i16>(44100 * 2);
+    let audio_queued = Arc::new(AtomicUsize::new(0));
     if let Err(e) = stream_handle.play_raw(
         StreamSource {
             rx: audio_rx,
             sample_rate: 44100,
+            queued: Arc::clone(&audio_queued),
         }
         .convert_samples(),
     ) {
@@ -2127,6 +3006,7 @@ fn main() {
     // Timing trackers - reset when ROM is loaded
     let mut emulation_start_time = Instant::now(); // Time when emulation started
     let mut total_emulated_time = Duration::ZERO; // Total time emulated so far
+    let mut audio_sample_credit = 0.0; // Fraction of a sample owed to the next frame
     let mut last_frame = Instant::now();
 
     // FPS tracking - display FPS only
@@ -2135,6 +3015,34 @@ fn main() {
 
     // GUI update throttling
     let mut frame_counter: u64 = 0;
+
+    // Emulated play time of the current game (stored with save states)
+    let mut play_time_secs: f64 = 0.0;
+    let mut play_time_hash: Option<String> = None;
+
+    // Recent save states for rewinding (cleared when the game changes)
+    let mut rewind = RewindBuffer::new(
+        settings.rewind_interval_frames,
+        settings.rewind_memory_mb * 1024 * 1024,
+    );
+    let mut rewind_hash: Option<String> = None;
+
+    // Input movie being recorded or played back
+    let mut movie_session = MovieSession::default();
+
+    // Captured mouse motion, scaled by the sensitivity setting
+    let mut mouse_motion = input::MouseMotion::default();
+
+    // Running game, published to the window title/icon and any hooks
+    let mut now_playing = NowPlayingHub::default();
+    if let Some(path) = &settings.now_playing_file {
+        now_playing.add_hook(Box::new(NowPlayingFile::new(path)));
+    }
+    let mut now_playing_key = None;
+    // Atari 2600 console switches menu opened with a gamepad
+    let mut atari_quick_menu = AtariQuickMenu::new();
+    // Mounts (mount_id -> path) already checked for bad dumps
+    let mut checked_mounts: HashMap<String, String> = HashMap::new();
     const GUI_UPDATE_INTERVAL: u64 = 15; // Update GUI every 15th frame
 
     // Track when emulation becomes active to reset timing
@@ -2202,11 +3110,84 @@ fn main() {
         } else {
             true // Always update when paused or no ROM
         };
-        // Handle SDL2 events and update egui input
-        if !egui_backend.handle_events() {
+        // Handle SDL2 events and update egui input. While the OS holds the
+        // event loop for a window move or resize, the pump keeps emulating at
+        // the wall clock's pace so the game and its sound carry on; the
+        // picture is shown once the loop returns.
+        let mut modal_frame: Option<Frame> = None;
+        let mut modal_stepped = SteppedFrames {
+            count: 0,
+            movie_finished: false,
+        };
+        let pumping = rom_loaded && settings.emulation_speed > 0.0 && was_emulation_active;
+        let pump_start = Instant::now();
+        let mut modal_pump = || {
+            if !pumping {
+                return;
+            }
+            let frame_rate = sys.frame_rate();
+            let owed = (pump_start.elapsed().as_secs_f64() * settings.emulation_speed * frame_rate)
+                as usize;
+            let stepped = step_frames(
+                &mut sys,
+                owed.saturating_sub(modal_stepped.count).min(30),
+                &settings,
+                &mut movie_session,
+                &mut rewind,
+                SAMPLE_RATE as f64 / frame_rate,
+                &mut audio_sample_credit,
+                &audio_tx,
+                &audio_queued,
+                &mut modal_frame,
+            );
+            modal_stepped.count += stepped.count;
+            modal_stepped.movie_finished |= stepped.movie_finished;
+            total_emulated_time += Duration::from_secs_f64(stepped.count as f64 / frame_rate);
+            play_time_secs += stepped.count as f64 / frame_rate;
+        };
+        let window_open = egui_backend.handle_events_with_pump(&mut modal_pump);
+        if !window_open
+            && prompt_save_modified_disks(&mut sys, &runtime_state, WRITABLE_DISKS, &mut egui_app)
+        {
             break; // Window closed
         }
 
+        // Homebrew hot-reload: remount the cartridge when the assembler rewrites it.
+        // Mounting resets the console but keeps its switch settings.
+        if cli_args.watch {
+            if let (EmulatorSystem::Atari2600(a2600_sys), Some(path)) =
+                (&mut sys, runtime_state.get_mount("Cartridge"))
+            {
+                let watcher = rom_watcher.get_or_insert_with(|| RomWatcher::new(path));
+                watcher.watch(std::path::Path::new(path));
+                if let Some(data) = watcher.poll() {
+                    let file_name = watcher
+                        .path()
+                        .file_name()
+                        .and_then(|n| n.to_str())
+                        .unwrap_or("cartridge")
+                        .to_string();
+                    match a2600_sys.mount("Cartridge", &data) {
+                        Ok(()) => {
+                            rom_hash = Some(GameSaves::rom_hash(&data));
+                            egui_app
+                                .status_bar
+                                .set_message(format!("Reloaded {}", file_name));
+                            egui_app.tab_manager.add_log(format!(
+                                "Cartridge changed on disk, reloaded {}",
+                                file_name
+                            ));
+                        }
+                        Err(e) => {
+                            egui_app
+                                .status_bar
+                                .set_message(format!("Reload of {} failed: {}", file_name, e));
+                        }
+                    }
+                }
+            }
+        }
+
         // Begin egui frame
         egui_backend.begin_frame();
 
@@ -2218,14 +3199,23 @@ fn main() {
             egui_app.property_pane.cpu_freq_target = sys.get_cpu_freq_target();
             egui_app.property_pane.emulation_speed_percent =
                 (settings.emulation_speed * 100.0) as i32;
+            egui_app.property_pane.master_volume_percent =
+                (settings.master_volume * 100.0).round() as i32;
+            egui_app.property_pane.pacing_mode = frame_pacer.mode();
 
             // Update input device counts from backend
             egui_app.property_pane.num_gamepads_detected = egui_backend.num_gamepads();
             egui_app.property_pane.num_joysticks_detected = egui_backend.num_joysticks();
+            egui_app.property_pane.gamepad_names = egui_backend
+                .gamepads()
+                .into_iter()
+                .map(|(_, name)| name)
+                .collect();
 
             // Update input configuration from settings
             egui_app.property_pane.mouse_enabled = settings.input.mouse_enabled;
             egui_app.property_pane.mouse_sensitivity = settings.input.mouse_sensitivity;
+            egui_app.property_pane.player_gamepads = settings.input.player_gamepads.clone();
 
             // Determine input config source
             if runtime_state.input_override.is_some() {
@@ -2236,8 +3226,7 @@ fn main() {
 
             // Update target FPS from system timing
             if rom_loaded {
-                let timing = sys.timing();
-                egui_app.property_pane.target_fps = timing.frame_rate_hz() as f32;
+                egui_app.property_pane.target_fps = sys.frame_rate() as f32;
             }
 
             // Update mount points from current system
@@ -2299,11 +3288,54 @@ fn main() {
 
                     // Set PC memory for dropdown
                     egui_app.property_pane.pc_memory_kb = Some(pc_sys.memory_kb());
+                    egui_app.property_pane.pc_cpu_speed = Some(pc_sys.cpu_speed());
                 } else {
                     // Clear PC-specific fields for non-PC systems
                     egui_app.property_pane.pc_bda_values = None;
                     egui_app.property_pane.pc_cpu_model = None;
                     egui_app.property_pane.pc_memory_kb = None;
+                    egui_app.property_pane.pc_cpu_speed = None;
+                }
+
+                // Set NES timing and PPU accuracy for the property pane
+                egui_app.property_pane.nes_timing = match &sys {
+                    EmulatorSystem::NES(nes_sys) => Some(nes_sys.timing()),
+                    _ => None,
+                };
+                egui_app.property_pane.nes_sprite_overflow_bug = match &sys {
+                    EmulatorSystem::NES(nes_sys) => Some(nes_sys.sprite_overflow_bug()),
+                    _ => None,
+                };
+                egui_app.property_pane.nes_scanline_ppu = match &sys {
+                    EmulatorSystem::NES(nes_sys) => {
+                        Some(nes_sys.ppu_accuracy() == emu_nes::PpuAccuracy::Scanline)
+                    }
+                    _ => None,
+                };
+                egui_app.property_pane.nes_zapper = match &sys {
+                    EmulatorSystem::NES(nes_sys) => Some(nes_sys.zapper_connected()),
+                    _ => None,
+                };
+                egui_app.property_pane.nes_four_score = match &sys {
+                    EmulatorSystem::NES(nes_sys) => Some(nes_sys.four_score()),
+                    _ => None,
+                };
+
+                // Set Atari 2600 console switches for the property pane
+                if let EmulatorSystem::Atari2600(a2600_sys) = &sys {
+                    egui_app.property_pane.atari_color_switch = Some(a2600_sys.color_switch());
+                    egui_app.property_pane.atari_video_standard = Some(a2600_sys.video_standard());
+                    egui_app.property_pane.atari_tia_revision = Some(a2600_sys.tia_revision());
+                    egui_app.property_pane.atari_beam_accurate =
+                        Some(a2600_sys.tia_accuracy() == emu_atari2600::TiaAccuracy::Beam);
+                    egui_app.property_pane.atari_cart_ram_size =
+                        a2600_sys.cart_ram().map(|ram| ram.len());
+                } else {
+                    egui_app.property_pane.atari_color_switch = None;
+                    egui_app.property_pane.atari_video_standard = None;
+                    egui_app.property_pane.atari_tia_revision = None;
+                    egui_app.property_pane.atari_beam_accurate = None;
+                    egui_app.property_pane.atari_cart_ram_size = None;
                 }
             }
 
@@ -2319,6 +3351,7 @@ fn main() {
                         emu_pc::BootPriority::HardDriveFirst => "Hard Drive First",
                         emu_pc::BootPriority::FloppyOnly => "Floppy Only",
                         emu_pc::BootPriority::HardDriveOnly => "Hard Drive Only",
+                        emu_pc::BootPriority::CdRomFirst => "CD-ROM First",
                     };
 
                     let cpu_model_str = match pc_sys.cpu_model() {
@@ -2369,12 +3402,146 @@ fn main() {
                     EmulatorSystem::N64(s) => SystemDebugInfo::from_n64(&s.get_debug_info()),
                 };
                 egui_app.tab_manager.update_debug_info(debug_info);
+
+                if let EmulatorSystem::N64(s) = &mut sys {
+                    if let Some(capture) = s.take_display_list_capture() {
+                        egui_app.tab_manager.add_log(format!(
+                            "Captured N64 display list ({} commands)",
+                            capture.steps().len()
+                        ));
+                        egui_app.tab_manager.set_display_list_capture(capture);
+                    }
+                }
+            }
+        }
+
+        // Update the window title and icon when the running game changes
+        let key = (
+            sys.system_name().to_string(),
+            rom_loaded,
+            rom_hash.clone(),
+            runtime_state.get_project_filename(),
+        );
+        if now_playing_key.as_ref() != Some(&key) {
+            let system = key.0.as_str();
+            let display_name = now_playing::system_display_name(system);
+            let title = if !rom_loaded {
+                None
+            } else if let Some(path) = runtime_state
+                .get_mount("Cartridge")
+                .filter(|_| system != "pc")
+            {
+                let data = archive::read_rom(path).unwrap_or_default();
+                Some(now_playing::game_title(
+                    system,
+                    &data,
+                    archive::rom_file_name(path),
+                ))
+            } else {
+                Some(
+                    key.3
+                        .as_deref()
+                        .and_then(|f| std::path::Path::new(f).file_stem())
+                        .map(|s| s.to_string_lossy().to_string())
+                        .unwrap_or_else(|| display_name.to_string()),
+                )
+            };
+            if now_playing.update(
+                title
+                    .as_deref()
+                    .map(|title| (display_name, title, rom_hash.as_deref())),
+            ) {
+                let _ = egui_backend.set_title(&now_playing::window_title(now_playing.current()));
+            }
+            let mut icon = now_playing::system_icon(system);
+            let size = now_playing::ICON_SIZE;
+            if let Err(e) = egui_backend.set_icon(&mut icon, size, size) {
+                eprintln!("Failed to set window icon: {}", e);
             }
+            now_playing_key = Some(key);
+        }
+
+        // Check newly mounted media against the known dump database and
+        // its own headers (bad dumps, overdumps, dirty headers)
+        if checked_mounts != runtime_state.current_mounts {
+            for (mount_id, path) in &runtime_state.current_mounts {
+                if checked_mounts.get(mount_id) == Some(path) {
+                    continue;
+                }
+                // Host folder drives are directories and are skipped here
+                let Ok(data) = archive::read_rom(path) else {
+                    continue;
+                };
+                let file_name = archive::rom_file_name(path)
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| path.clone());
+                let report = media_check::check_media(sys.system_name(), mount_id, &data);
+                for line in report.log_lines(&file_name) {
+                    egui_app.tab_manager.add_log(line);
+                }
+                if let Some(summary) = report.warning_summary(&file_name) {
+                    egui_app.status_bar.set_warning(summary);
+                }
+            }
+            checked_mounts = runtime_state.current_mounts.clone();
         }
 
         // Render egui UI
         egui_app.ui(egui_backend.egui_ctx(), settings.scaling_mode);
 
+        // Atari 2600 quick settings menu (gamepad Back+Start)
+        if let EmulatorSystem::Atari2600(a2600_sys) = &mut sys {
+            let pad = atari_quick_menu::pad_state(
+                &egui_backend.gamepad_buttons_down(),
+                egui_backend.any_gamepad_axis(0),
+                egui_backend.any_gamepad_axis(1),
+            );
+            if let Some(action) = atari_quick_menu.update(pad) {
+                let message = match action {
+                    QuickAction::ToggleDifficulty(player) => {
+                        let difficulty = match a2600_sys.difficulty(player) {
+                            emu_atari2600::Difficulty::A => emu_atari2600::Difficulty::B,
+                            emu_atari2600::Difficulty::B => emu_atari2600::Difficulty::A,
+                        };
+                        a2600_sys.set_difficulty(player, difficulty);
+                        format!(
+                            "{} difficulty set to {:?}",
+                            if player == 0 { "Left" } else { "Right" },
+                            difficulty
+                        )
+                    }
+                    QuickAction::ToggleColor => {
+                        a2600_sys.set_color_switch(!a2600_sys.color_switch());
+                        format!(
+                            "TV Type switch set to {}",
+                            if a2600_sys.color_switch() {
+                                "Color"
+                            } else {
+                                "B&W"
+                            }
+                        )
+                    }
+                    QuickAction::TogglePorts => {
+                        a2600_sys.set_ports_swapped(!a2600_sys.ports_swapped());
+                        if a2600_sys.ports_swapped() {
+                            "Controller ports swapped".to_string()
+                        } else {
+                            "Controller ports restored".to_string()
+                        }
+                    }
+                    QuickAction::PowerCycle => {
+                        a2600_sys.reset();
+                        "Console power cycled".to_string()
+                    }
+                };
+                egui_app.status_bar.set_message(message);
+            }
+            let (reset, select) = atari_quick_menu.console_buttons();
+            a2600_sys.set_console_buttons(reset, select);
+            atari_quick_menu.ui(egui_backend.egui_ctx(), a2600_sys);
+        }
+
         // Handle menu actions
         if let Some(action) = egui_app.menu_bar.take_action() {
             use egui_ui::menu_bar::MenuAction;
@@ -2389,14 +3556,18 @@ fn main() {
                             "ROM Files",
                             &[
                                 "nes", "gb", "gbc", "bin", "a26", "smc", "sfc", "z64", "n64",
-                                "com", "exe",
+                                "com", "exe", "zip", "7z",
                             ],
                         )
                         .add_filter("All Files", &["*"])
                         .pick_file()
                     {
+                        // The current cartridge is about to be replaced
+                        if let Some(msg) = write_battery_save(&sys, &runtime_state) {
+                            egui_app.tab_manager.add_log(msg);
+                        }
                         let path_str = path.to_string_lossy().to_string();
-                        match std::fs::read(&path) {
+                        match archive::read_rom(&path) {
                             Ok(data) => match detect_rom_type(&data) {
                                 Ok(SystemType::NES) => {
                                     rom_hash = Some(GameSaves::rom_hash(&data));
@@ -2410,6 +3581,11 @@ fn main() {
                                         rom_hash = None;
                                     } else {
                                         rom_loaded = true;
+                                        if let Some(msg) =
+                                            load_nes_battery_save(&mut nes_sys, &path_str)
+                                        {
+                                            egui_app.tab_manager.add_log(msg);
+                                        }
                                         sys = EmulatorSystem::NES(Box::new(nes_sys));
                                         egui_app.property_pane.system_name = "NES".to_string();
                                         egui_app.property_pane.rendering_backend =
@@ -2512,7 +3688,7 @@ fn main() {
                                 Ok(SystemType::PC) => {
                                     rom_hash = Some(GameSaves::rom_hash(&data));
                                     let mut pc_sys = emu_pc::PcSystem::new();
-                                    if let Err(e) = pc_sys.mount("Disk", &data) {
+                                    if let Err(e) = pc_sys.mount("Executable", &data) {
                                         egui_app.status_bar.set_message(format!("Error: {}", e));
                                         rom_hash = None;
                                     } else {
@@ -2524,7 +3700,7 @@ fn main() {
                                         egui_app.property_pane.available_renderers =
                                             sys.get_available_renderers();
                                         runtime_state
-                                            .set_mount("Disk".to_string(), path_str.clone());
+                                            .set_mount("Executable".to_string(), path_str.clone());
                                         settings.last_rom_path = Some(path_str.clone());
                                         // Add to recent files
                                         settings.add_recent_file(path_str.clone());
@@ -2552,6 +3728,13 @@ fn main() {
                                         rom_hash = None;
                                     } else {
                                         rom_loaded = true;
+                                        if let Some(msg) = apply_snes_renderer_preference(
+                                            &mut snes_sys,
+                                            &settings.video_backend,
+                                            &egui_backend,
+                                        ) {
+                                            egui_app.tab_manager.add_log(msg);
+                                        }
                                         sys = EmulatorSystem::SNES(Box::new(snes_sys));
                                         egui_app.property_pane.system_name = "SNES".to_string();
                                         egui_app.property_pane.rendering_backend =
@@ -2601,6 +3784,7 @@ fn main() {
                                             egui_app.property_pane.rendering_backend =
                                                 sys.get_current_renderer_name();
                                         }
+                                        enable_n64_threaded_rcp(&mut sys);
 
                                         egui_app.property_pane.system_name = "N64".to_string();
                                         // Set renderer display based on settings preference
@@ -2639,6 +3823,12 @@ fn main() {
                                         .set_error(format!("Failed to detect ROM type: {}", e));
                                 }
                             },
+                            Err(ArchiveError::MultipleRoms(members)) => {
+                                egui_app
+                                    .status_bar
+                                    .set_message("Choose a ROM from the archive".to_string());
+                                egui_app.show_archive_chooser(members);
+                            }
                             Err(e) => {
                                 egui_app
                                     .status_bar
@@ -2648,6 +3838,9 @@ fn main() {
                     }
                 }
                 MenuAction::OpenRecentFile(file_path) => {
+                    if let Some(msg) = write_battery_save(&sys, &runtime_state) {
+                        egui_app.tab_manager.add_log(msg);
+                    }
                     // Determine if this is a .hemu project or a ROM file
                     let path = PathBuf::from(&file_path);
 
@@ -2720,6 +3913,9 @@ fn main() {
                                                 "VGA" => {
                                                     Box::new(emu_pc::SoftwareVgaAdapter::new())
                                                 }
+                                                "Hercules" | "MDA" => {
+                                                    Box::new(emu_pc::SoftwareHerculesAdapter::new())
+                                                }
                                                 "CGA" => {
                                                     Box::new(emu_pc::SoftwareCgaAdapter::new())
                                                 }
@@ -2735,6 +3931,13 @@ fn main() {
                                         memory_kb,
                                         video_adapter,
                                     );
+                                    configure_pc_ems(&mut pc_sys, &project);
+                                    configure_pc_cpu_speed(&mut pc_sys, &project);
+                                    configure_pc_rtc(&mut pc_sys, &project);
+                                    release_pc_host_io(&mut sys);
+                                    configure_pc_serial(&mut pc_sys, &project);
+                                    configure_pc_printer(&mut pc_sys, &project);
+                                    configure_pc_builtin_dos(&mut pc_sys, &project);
 
                                     // Set boot priority
                                     let boot_priority = project
@@ -2745,6 +3948,7 @@ fn main() {
                                         "HardDriveFirst" => emu_pc::BootPriority::HardDriveFirst,
                                         "FloppyOnly" => emu_pc::BootPriority::FloppyOnly,
                                         "HardDriveOnly" => emu_pc::BootPriority::HardDriveOnly,
+                                        "CdRomFirst" => emu_pc::BootPriority::CdRomFirst,
                                         _ => emu_pc::BootPriority::FloppyFirst,
                                     };
                                     pc_sys.set_boot_priority(priority);
@@ -2755,7 +3959,7 @@ fn main() {
                                         path.parent().unwrap_or_else(|| std::path::Path::new("."));
                                     for (mount_id, relative_path) in &project.mounts {
                                         let full_path = project_dir.join(relative_path);
-                                        match fs::read(&full_path) {
+                                        match read_disk_image(mount_id, &full_path) {
                                             Ok(data) => {
                                                 if let Err(e) = pc_sys.mount(mount_id, &data) {
                                                     eprintln!(
@@ -2811,7 +4015,7 @@ fn main() {
                         }
                     } else {
                         // Load as a ROM file
-                        match fs::read(&path) {
+                        match archive::read_rom(&path) {
                             Ok(data) => match detect_rom_type(&data) {
                                 Ok(SystemType::NES) => {
                                     rom_hash = Some(GameSaves::rom_hash(&data));
@@ -2841,6 +4045,11 @@ fn main() {
                                             }
                                         }
 
+                                        if let Some(msg) =
+                                            load_nes_battery_save(&mut nes_sys, &file_path)
+                                        {
+                                            egui_app.tab_manager.add_log(msg);
+                                        }
                                         sys = EmulatorSystem::NES(Box::new(nes_sys));
                                         egui_app.property_pane.system_name = "NES".to_string();
                                         egui_app.property_pane.rendering_backend =
@@ -2937,7 +4146,7 @@ fn main() {
                                 Ok(SystemType::PC) => {
                                     rom_hash = Some(GameSaves::rom_hash(&data));
                                     let mut pc_sys = emu_pc::PcSystem::new();
-                                    if let Err(e) = pc_sys.mount("Disk", &data) {
+                                    if let Err(e) = pc_sys.mount("Executable", &data) {
                                         egui_app.status_bar.set_message(format!("Error: {}", e));
                                         rom_hash = None;
                                     } else {
@@ -2949,7 +4158,7 @@ fn main() {
                                         egui_app.property_pane.available_renderers =
                                             sys.get_available_renderers();
                                         runtime_state
-                                            .set_mount("Disk".to_string(), file_path.clone());
+                                            .set_mount("Executable".to_string(), file_path.clone());
                                         settings.last_rom_path = Some(file_path.clone());
                                         settings.add_recent_file(file_path.clone());
                                         if let Err(e) = settings.save() {
@@ -2975,6 +4184,13 @@ fn main() {
                                         rom_hash = None;
                                     } else {
                                         rom_loaded = true;
+                                        if let Some(msg) = apply_snes_renderer_preference(
+                                            &mut snes_sys,
+                                            &settings.video_backend,
+                                            &egui_backend,
+                                        ) {
+                                            egui_app.tab_manager.add_log(msg);
+                                        }
                                         sys = EmulatorSystem::SNES(Box::new(snes_sys));
                                         egui_app.property_pane.system_name = "SNES".to_string();
                                         egui_app.property_pane.rendering_backend =
@@ -3009,6 +4225,7 @@ fn main() {
                                     } else {
                                         rom_loaded = true;
                                         sys = EmulatorSystem::N64(Box::new(n64_sys));
+                                        enable_n64_threaded_rcp(&mut sys);
                                         egui_app.property_pane.system_name = "N64".to_string();
                                         egui_app.property_pane.rendering_backend =
                                             sys.get_current_renderer_name();
@@ -3039,6 +4256,12 @@ fn main() {
                                         .set_error(format!("Unknown ROM format: {}", e));
                                 }
                             },
+                            Err(ArchiveError::MultipleRoms(members)) => {
+                                egui_app
+                                    .status_bar
+                                    .set_message("Choose a ROM from the archive".to_string());
+                                egui_app.show_archive_chooser(members);
+                            }
                             Err(e) => {
                                 egui_app
                                     .status_bar
@@ -3069,6 +4292,97 @@ fn main() {
                     settings.emulation_speed = 1.0;
                     egui_app.status_bar.set_message("Resumed".to_string());
                 }
+                MenuAction::RecordMovieFromReset => {
+                    sys.reset();
+                    release_all_controllers(&mut sys);
+                    movie_session = MovieSession::record(InputMovie::new(
+                        sys.system_name(),
+                        rom_hash.clone(),
+                        MovieStart::PowerOn,
+                    ));
+                    egui_app
+                        .status_bar
+                        .set_message("Recording movie from reset".to_string());
+                }
+                MenuAction::RecordMovieFromState => {
+                    if sys.supports_save_states() {
+                        release_all_controllers(&mut sys);
+                        movie_session = MovieSession::record(InputMovie::new(
+                            sys.system_name(),
+                            rom_hash.clone(),
+                            MovieStart::SaveState(sys.save_state()),
+                        ));
+                        egui_app
+                            .status_bar
+                            .set_message("Recording movie from current state".to_string());
+                    } else {
+                        egui_app.status_bar.set_error(
+                            "This system does not support save states; record from reset instead"
+                                .to_string(),
+                        );
+                    }
+                }
+                MenuAction::PlayMovie => {
+                    if let Some(path) = rfd::FileDialog::new()
+                        .add_filter("Input Movies", &[MOVIE_EXTENSION])
+                        .pick_file()
+                    {
+                        let result = fs::read_to_string(&path)
+                            .map_err(|e| e.to_string())
+                            .and_then(|json| {
+                                InputMovie::from_json(&json).map_err(|e| e.to_string())
+                            })
+                            .and_then(|movie| {
+                                movie
+                                    .check_compatible(sys.system_name(), rom_hash.as_deref())
+                                    .map_err(|e| e.to_string())?;
+                                match &movie.start {
+                                    MovieStart::PowerOn => sys.reset(),
+                                    MovieStart::SaveState(state) => {
+                                        sys.load_state(state).map_err(|e| e.to_string())?
+                                    }
+                                }
+                                Ok(movie)
+                            });
+                        match result {
+                            Ok(movie) => {
+                                release_all_controllers(&mut sys);
+                                egui_app
+                                    .status_bar
+                                    .set_message(format!("Playing movie ({} frames)", movie.len()));
+                                movie_session = MovieSession::play(movie);
+                            }
+                            Err(e) => egui_app
+                                .status_bar
+                                .set_error(format!("Failed to play movie: {}", e)),
+                        }
+                    }
+                }
+                MenuAction::StopMovie => match movie_session.stop() {
+                    Some(movie) => {
+                        if let Some(path) = rfd::FileDialog::new()
+                            .add_filter("Input Movies", &[MOVIE_EXTENSION])
+                            .set_file_name(format!("{}.{}", sys.system_name(), MOVIE_EXTENSION))
+                            .save_file()
+                        {
+                            let result = movie
+                                .to_json()
+                                .map_err(|e| e.to_string())
+                                .and_then(|json| fs::write(&path, json).map_err(|e| e.to_string()));
+                            match result {
+                                Ok(()) => egui_app.status_bar.set_message(format!(
+                                    "Movie saved: {} ({} frames)",
+                                    path.display(),
+                                    movie.len()
+                                )),
+                                Err(e) => egui_app
+                                    .status_bar
+                                    .set_error(format!("Failed to save movie: {}", e)),
+                            }
+                        }
+                    }
+                    None => egui_app.status_bar.set_message("Movie stopped".to_string()),
+                },
                 MenuAction::Screenshot => {
                     // Take screenshot of current frame
                     if rom_loaded {
@@ -3163,6 +4477,27 @@ fn main() {
                 MenuAction::ShowDebug => {
                     egui_app.tab_manager.show_debug_tab();
                 }
+                MenuAction::ShowSaveStates => {
+                    egui_app
+                        .tab_manager
+                        .update_save_state_items(save_state_list_items(), rom_hash.clone());
+                    egui_app.tab_manager.show_save_states_tab();
+                }
+                MenuAction::ToggleInputDisplay => {
+                    settings.show_input_display = !settings.show_input_display;
+                    egui_app.menu_bar.show_input_display = settings.show_input_display;
+                    if let Err(e) = settings.save() {
+                        eprintln!("Warning: Failed to save settings: {}", e);
+                    }
+                    egui_app.status_bar.set_message(
+                        if settings.show_input_display {
+                            "Input display enabled"
+                        } else {
+                            "Input display disabled"
+                        }
+                        .to_string(),
+                    );
+                }
                 MenuAction::OpenProject => {
                     // Open .hemu project file dialog
                     if let Some(path) = rfd::FileDialog::new()
@@ -3238,6 +4573,9 @@ fn main() {
                                                 "VGA" => {
                                                     Box::new(emu_pc::SoftwareVgaAdapter::new())
                                                 }
+                                                "Hercules" | "MDA" => {
+                                                    Box::new(emu_pc::SoftwareHerculesAdapter::new())
+                                                }
                                                 "CGA" => {
                                                     Box::new(emu_pc::SoftwareCgaAdapter::new())
                                                 }
@@ -3253,6 +4591,13 @@ fn main() {
                                         memory_kb,
                                         video_adapter,
                                     );
+                                    configure_pc_ems(&mut pc_sys, &project);
+                                    configure_pc_cpu_speed(&mut pc_sys, &project);
+                                    configure_pc_rtc(&mut pc_sys, &project);
+                                    release_pc_host_io(&mut sys);
+                                    configure_pc_serial(&mut pc_sys, &project);
+                                    configure_pc_printer(&mut pc_sys, &project);
+                                    configure_pc_builtin_dos(&mut pc_sys, &project);
 
                                     // Load boot priority if specified
                                     if let Some(priority_str) = project.boot_priority.as_ref() {
@@ -3263,6 +4608,7 @@ fn main() {
                                             }
                                             "FloppyOnly" => emu_pc::BootPriority::FloppyOnly,
                                             "HardDriveOnly" => emu_pc::BootPriority::HardDriveOnly,
+                                            "CdRomFirst" => emu_pc::BootPriority::CdRomFirst,
                                             _ => emu_pc::BootPriority::FloppyFirst,
                                         };
                                         pc_sys.set_boot_priority(priority);
@@ -3274,7 +4620,7 @@ fn main() {
                                         .unwrap_or(std::path::Path::new("."));
                                     for (mount_id, relative_path) in &project.mounts {
                                         let full_path = project_dir.join(relative_path);
-                                        match fs::read(&full_path) {
+                                        match read_disk_image(mount_id, &full_path) {
                                             Ok(data) => {
                                                 if let Err(e) = pc_sys.mount(mount_id, &data) {
                                                     eprintln!(
@@ -3350,9 +4696,33 @@ fn main() {
                             .set_message("No system loaded to save".to_string());
                     }
                 }
+                MenuAction::SaveDiskChanges => {
+                    if rom_loaded {
+                        match save_modified_disks(&mut sys, &runtime_state, &mut egui_app) {
+                            Ok(0) => egui_app
+                                .status_bar
+                                .set_message("No disk changes to save".to_string()),
+                            Ok(saved) => egui_app
+                                .status_bar
+                                .set_message(format!("Saved {} modified disk(s)", saved)),
+                            Err(e) => egui_app.status_bar.set_error(e),
+                        }
+                    } else {
+                        egui_app
+                            .status_bar
+                            .set_message("No system loaded".to_string());
+                    }
+                }
                 MenuAction::Exit => {
                     // Exit the application by breaking out of the main loop
-                    break;
+                    if prompt_save_modified_disks(
+                        &mut sys,
+                        &runtime_state,
+                        WRITABLE_DISKS,
+                        &mut egui_app,
+                    ) {
+                        break;
+                    }
                 }
             }
         }
@@ -3367,10 +4737,29 @@ fn main() {
                             if sys.supports_save_states() {
                                 let state = sys.save_state();
                                 let state_json = serde_json::to_string(&state).unwrap_or_default();
-                                // save_slot will persist to disk internally
-                                if let Err(e) =
-                                    _game_saves.save_slot(slot, state_json.as_bytes(), hash)
-                                {
+                                let meta = save_state::SaveMetadata {
+                                    system: Some(sys.system_name().to_string()),
+                                    rom_name: settings
+                                        .last_rom_path
+                                        .as_deref()
+                                        .and_then(|p| std::path::Path::new(p).file_name())
+                                        .map(|n| n.to_string_lossy().to_string()),
+                                    play_time_secs: play_time_secs as u64,
+                                    thumbnail: latest_frame_buffer.as_ref().and_then(
+                                        |(buffer, width, height)| {
+                                            save_state::Thumbnail::from_frame(
+                                                buffer, *width, *height,
+                                            )
+                                        },
+                                    ),
+                                };
+                                // save_slot_with_metadata will persist to disk internally
+                                if let Err(e) = _game_saves.save_slot_with_metadata(
+                                    slot,
+                                    state_json.as_bytes(),
+                                    hash,
+                                    meta,
+                                ) {
                                     egui_app
                                         .status_bar
                                         .set_message(format!("Error saving state: {}", e));
@@ -3381,6 +4770,12 @@ fn main() {
                                     egui_app
                                         .tab_manager
                                         .add_log(format!("State saved to slot {}", slot));
+                                    if egui_app.tab_manager.save_states_visible {
+                                        egui_app.tab_manager.update_save_state_items(
+                                            save_state_list_items(),
+                                            rom_hash.clone(),
+                                        );
+                                    }
                                 }
                             } else {
                                 egui_app.status_bar.set_message(
@@ -3396,40 +4791,18 @@ fn main() {
                     if rom_loaded {
                         if let Some(ref hash) = rom_hash {
                             if sys.supports_save_states() {
-                                match _game_saves.load_slot(slot, hash) {
-                                    Ok(data) => {
-                                        if let Ok(state_str) = String::from_utf8(data) {
-                                            if let Ok(state) = serde_json::from_str(&state_str) {
-                                                if let Err(e) = sys.load_state(&state) {
-                                                    egui_app.status_bar.set_message(format!(
-                                                        "Error loading state: {}",
-                                                        e
-                                                    ));
-                                                } else {
-                                                    egui_app.status_bar.set_message(format!(
-                                                        "Loaded from slot {}",
-                                                        slot
-                                                    ));
-                                                    egui_app.tab_manager.add_log(format!(
-                                                        "State loaded from slot {}",
-                                                        slot
-                                                    ));
-                                                }
-                                            } else {
-                                                egui_app
-                                                    .status_bar
-                                                    .set_message("Invalid state data".to_string());
-                                            }
-                                        } else {
-                                            egui_app
-                                                .status_bar
-                                                .set_message("Invalid state encoding".to_string());
-                                        }
-                                    }
-                                    Err(e) => {
+                                match load_state_slot(&mut sys, &_game_saves, slot, hash) {
+                                    Ok(saved_play_time) => {
+                                        play_time_secs = saved_play_time as f64;
                                         egui_app
                                             .status_bar
-                                            .set_message(format!("Error loading state: {}", e));
+                                            .set_message(format!("Loaded from slot {}", slot));
+                                        egui_app
+                                            .tab_manager
+                                            .add_log(format!("State loaded from slot {}", slot));
+                                    }
+                                    Err(e) => {
+                                        egui_app.status_bar.set_message(e);
                                     }
                                 }
                             } else {
@@ -3447,21 +4820,51 @@ fn main() {
                     let mount_points = sys.mount_points();
                     if let Some(mount_info) = mount_points.iter().find(|mp| mp.id == mount_id) {
                         // Create file dialog with appropriate filters
-                        let extensions: Vec<&str> =
+                        let mut extensions: Vec<&str> =
                             mount_info.extensions.iter().map(|s| s.as_str()).collect();
-                        if let Some(path) = rfd::FileDialog::new()
+                        // Cartridges can also be picked from inside an archive
+                        let cartridge = mount_id == "Cartridge";
+                        if cartridge {
+                            extensions.extend_from_slice(archive::ARCHIVE_EXTENSIONS);
+                        }
+                        // Mounting replaces the current image, so offer to save it first
+                        if !prompt_save_modified_disks(
+                            &mut sys,
+                            &runtime_state,
+                            &[mount_id.as_str()],
+                            &mut egui_app,
+                        ) {
+                            egui_app
+                                .status_bar
+                                .set_message("Mount cancelled".to_string());
+                        } else if let Some(path) = rfd::FileDialog::new()
                             .add_filter(&mount_info.name, &extensions)
                             .add_filter("All Files", &["*"])
                             .pick_file()
                         {
-                            match fs::read(&path) {
+                            let read = if cartridge {
+                                archive::read_rom(&path)
+                            } else {
+                                fs::read(&path).map_err(ArchiveError::Io)
+                            };
+                            match read {
                                 Ok(data) => {
+                                    if let Some(msg) = write_battery_save(&sys, &runtime_state) {
+                                        egui_app.tab_manager.add_log(msg);
+                                    }
                                     if let Err(e) = sys.mount(&mount_id, &data) {
                                         egui_app
                                             .status_bar
                                             .set_message(format!("Error mounting: {}", e));
                                     } else {
                                         let path_str = path.to_string_lossy().to_string();
+                                        if let EmulatorSystem::NES(nes_sys) = &mut sys {
+                                            if let Some(msg) =
+                                                load_nes_battery_save(nes_sys, &path_str)
+                                            {
+                                                egui_app.tab_manager.add_log(msg);
+                                            }
+                                        }
                                         runtime_state.set_mount(mount_id.clone(), path_str.clone());
                                         egui_app.status_bar.set_message(format!(
                                             "Mounted {}",
@@ -3478,6 +4881,12 @@ fn main() {
                                         ));
                                     }
                                 }
+                                Err(ArchiveError::MultipleRoms(members)) => {
+                                    egui_app
+                                        .status_bar
+                                        .set_message("Choose a ROM from the archive".to_string());
+                                    egui_app.show_archive_chooser(members);
+                                }
                                 Err(e) => {
                                     egui_app
                                         .status_bar
@@ -3487,30 +4896,99 @@ fn main() {
                         }
                     }
                 }
+                PropertyAction::MountFolder(mount_id) => {
+                    if !prompt_save_modified_disks(
+                        &mut sys,
+                        &runtime_state,
+                        &[mount_id.as_str()],
+                        &mut egui_app,
+                    ) {
+                        egui_app
+                            .status_bar
+                            .set_message("Mount cancelled".to_string());
+                    } else if let Some(path) = rfd::FileDialog::new().pick_folder() {
+                        let folder_name = path
+                            .file_name()
+                            .and_then(|n| n.to_str())
+                            .unwrap_or("folder")
+                            .to_string();
+                        match read_disk_image(&mount_id, &path) {
+                            Ok(data) => {
+                                if let Err(e) = sys.mount(&mount_id, &data) {
+                                    egui_app
+                                        .status_bar
+                                        .set_message(format!("Error mounting: {}", e));
+                                } else {
+                                    runtime_state.set_mount(
+                                        mount_id.clone(),
+                                        path.to_string_lossy().to_string(),
+                                    );
+                                    egui_app
+                                        .status_bar
+                                        .set_message(format!("Mounted folder {}", folder_name));
+                                    egui_app.tab_manager.add_log(format!(
+                                        "Mounted host folder {} to {}",
+                                        path.display(),
+                                        mount_id
+                                    ));
+                                }
+                            }
+                            Err(e) => {
+                                egui_app.status_bar.set_error(format!(
+                                    "Error building drive from {}: {}",
+                                    folder_name, e
+                                ));
+                            }
+                        }
+                    }
+                }
                 PropertyAction::EjectFile(mount_id) => {
-                    if let Err(e) = sys.unmount(&mount_id) {
+                    if !prompt_save_modified_disks(
+                        &mut sys,
+                        &runtime_state,
+                        &[mount_id.as_str()],
+                        &mut egui_app,
+                    ) {
                         egui_app
                             .status_bar
-                            .set_message(format!("Error ejecting: {}", e));
+                            .set_message("Eject cancelled".to_string());
                     } else {
-                        runtime_state.current_mounts.remove(&mount_id);
-                        egui_app.status_bar.set_message("Ejected".to_string());
-                        egui_app
-                            .tab_manager
-                            .add_log(format!("Ejected {}", mount_id));
+                        if mount_id == "Cartridge" {
+                            if let Some(msg) = write_battery_save(&sys, &runtime_state) {
+                                egui_app.tab_manager.add_log(msg);
+                            }
+                        }
+                        if let Err(e) = sys.unmount(&mount_id) {
+                            egui_app
+                                .status_bar
+                                .set_message(format!("Error ejecting: {}", e));
+                        } else {
+                            runtime_state.current_mounts.remove(&mount_id);
+                            egui_app.status_bar.set_message("Ejected".to_string());
+                            egui_app
+                                .tab_manager
+                                .add_log(format!("Ejected {}", mount_id));
+                        }
                     }
                 }
                 PropertyAction::ConfigureInput => {
-                    // TODO: Open input configuration dialog
-                    // For now, just show a message that this feature is coming soon
-                    egui_app.status_bar.set_message(
-                        "Input configuration dialog coming soon. Edit config.json manually for now."
-                            .to_string(),
-                    );
-                    egui_app.tab_manager.add_log(
-                        "Input configuration: Feature in development. Use config.json for now."
-                            .to_string(),
-                    );
+                    // Edit a copy of the mapping profiles; saved with TabAction::SaveInputMappings
+                    let current_game = rom_hash.clone().map(|hash| {
+                        let title = now_playing
+                            .current()
+                            .map_or_else(|| "This game".to_string(), |game| game.title.clone());
+                        (hash, title)
+                    });
+                    egui_app
+                        .tab_manager
+                        .show_input_mappings_tab(egui_ui::InputMappingsInfo {
+                            profiles: settings.input.mapping_profiles.clone(),
+                            system_mappings: settings.input.system_mappings.clone(),
+                            game_mappings: settings.input.game_mappings.clone(),
+                            global: settings.input.global_mappings(),
+                            turbo_frames_per_toggle: settings.input.turbo_frames_per_toggle,
+                            current_game,
+                        });
                 }
                 PropertyAction::SetInputSource(source) => {
                     use egui_ui::InputConfigSource;
@@ -3617,6 +5095,51 @@ fn main() {
                                 }
                             }
                             #[cfg(feature = "opengl")]
+                            EmulatorSystem::SNES(snes_sys) => {
+                                if renderer_name == "OpenGL" {
+                                    // Get GL context from egui backend
+                                    if let Some(gl) = egui_backend.gl_context() {
+                                        match snes_sys.enable_opengl_renderer(gl) {
+                                            Ok(()) => {
+                                                switched = true;
+                                                egui_app.property_pane.rendering_backend =
+                                                    "OpenGL".to_string();
+                                                egui_app.status_bar.set_success(
+                                                    "Switched to OpenGL renderer".to_string(),
+                                                );
+                                                egui_app.tab_manager.add_log(
+                                                    "SNES: Switched to OpenGL hardware renderer"
+                                                        .to_string(),
+                                                );
+                                            }
+                                            Err(e) => {
+                                                eprintln!(
+                                                    "Failed to enable OpenGL renderer: {}",
+                                                    e
+                                                );
+                                                egui_app.status_bar.set_error(format!(
+                                                    "Failed to enable OpenGL renderer: {}",
+                                                    e
+                                                ));
+                                            }
+                                        }
+                                    } else {
+                                        egui_app
+                                            .status_bar
+                                            .set_error("OpenGL context not available".to_string());
+                                    }
+                                } else {
+                                    // Cannot switch back to software without recreating system
+                                    egui_app.status_bar.set_message(
+                                        "Renderer preference saved. Reload ROM to switch to Software renderer.".to_string(),
+                                    );
+                                    egui_app.tab_manager.add_log(
+                                        "Renderer preference saved: Software (reload ROM to apply)"
+                                            .to_string(),
+                                    );
+                                }
+                            }
+                            #[cfg(feature = "opengl")]
                             EmulatorSystem::N64(n64_sys) => {
                                 if renderer_name == "OpenGL" {
                                     if let Some(gl) = egui_backend.gl_context() {
@@ -3672,6 +5195,172 @@ fn main() {
                         }
                     }
                 }
+                PropertyAction::SetAtariColorSwitch(color) => {
+                    if let EmulatorSystem::Atari2600(a2600_sys) = &mut sys {
+                        a2600_sys.set_color_switch(color);
+                        egui_app.status_bar.set_message(format!(
+                            "TV Type switch set to {}",
+                            if color { "Color" } else { "B&W" }
+                        ));
+                    }
+                }
+                PropertyAction::SetPcCpuSpeed(speed) => {
+                    if let EmulatorSystem::PC(pc_sys) = &mut sys {
+                        pc_sys.set_cpu_speed(speed);
+                        egui_app.status_bar.set_message(match pc_sys.cpu_speed() {
+                            emu_pc::CpuSpeed::Model => format!(
+                                "CPU speed set to model default ({} MHz)",
+                                pc_sys.cpu_speed_mhz()
+                            ),
+                            emu_pc::CpuSpeed::Fixed(mhz) => format!("CPU speed set to {} MHz", mhz),
+                            emu_pc::CpuSpeed::Unlimited => "CPU speed set to unlimited".to_string(),
+                        });
+                    }
+                }
+                PropertyAction::SetNesTiming(timing) => {
+                    if let EmulatorSystem::NES(nes_sys) = &mut sys {
+                        nes_sys.set_timing_override(Some(timing));
+                        egui_app.status_bar.set_message(format!(
+                            "Timing set to {}",
+                            match timing {
+                                emu_core::apu::TimingMode::Ntsc => "NTSC",
+                                emu_core::apu::TimingMode::Pal => "PAL",
+                                emu_core::apu::TimingMode::Dendy => "Dendy",
+                            }
+                        ));
+                    }
+                }
+                PropertyAction::SetNesSpriteOverflowBug(enabled) => {
+                    if let EmulatorSystem::NES(nes_sys) = &mut sys {
+                        nes_sys.set_sprite_overflow_bug(enabled);
+                        egui_app.status_bar.set_message(format!(
+                            "Sprite overflow bug {}",
+                            if enabled { "enabled" } else { "disabled" }
+                        ));
+                    }
+                }
+                PropertyAction::SetNesScanlinePpu(scanline) => {
+                    if let EmulatorSystem::NES(nes_sys) = &mut sys {
+                        nes_sys.set_ppu_accuracy(if scanline {
+                            emu_nes::PpuAccuracy::Scanline
+                        } else {
+                            emu_nes::PpuAccuracy::Frame
+                        });
+                        egui_app.status_bar.set_message(format!(
+                            "PPU rendering set to {}",
+                            if scanline { "Scanline" } else { "Frame" }
+                        ));
+                    }
+                }
+                PropertyAction::SetAtariVideoStandard(standard) => {
+                    if let EmulatorSystem::Atari2600(a2600_sys) = &mut sys {
+                        a2600_sys.set_video_standard(standard);
+                        egui_app.status_bar.set_message(format!(
+                            "Video standard set to {}",
+                            match standard {
+                                emu_atari2600::VideoStandard::Ntsc => "NTSC",
+                                emu_atari2600::VideoStandard::Pal => "PAL",
+                                emu_atari2600::VideoStandard::Secam => "SECAM",
+                            }
+                        ));
+                    }
+                }
+                PropertyAction::SetAtariTiaRevision(revision) => {
+                    if let EmulatorSystem::Atari2600(a2600_sys) = &mut sys {
+                        a2600_sys.set_tia_revision(revision);
+                        egui_app.status_bar.set_message(format!(
+                            "TIA revision set to {}",
+                            match revision {
+                                emu_atari2600::TiaRevision::Early => "Early",
+                                emu_atari2600::TiaRevision::Late => "Late",
+                            }
+                        ));
+                    }
+                }
+                PropertyAction::SetNesZapper(connected) => {
+                    if let EmulatorSystem::NES(nes_sys) = &mut sys {
+                        nes_sys.set_zapper_connected(connected);
+                        egui_app.status_bar.set_message(
+                            if connected {
+                                "Zapper connected to port 2"
+                            } else {
+                                "Controller connected to port 2"
+                            }
+                            .to_string(),
+                        );
+                    }
+                }
+                PropertyAction::SetNesFourScore(connected) => {
+                    if let EmulatorSystem::NES(nes_sys) = &mut sys {
+                        nes_sys.set_four_score(connected);
+                        egui_app.status_bar.set_message(
+                            if connected {
+                                "Four Score connected (4 players)"
+                            } else {
+                                "Four Score disconnected"
+                            }
+                            .to_string(),
+                        );
+                    }
+                }
+                PropertyAction::ExportAtariCartRam => {
+                    if let EmulatorSystem::Atari2600(a2600_sys) = &sys {
+                        if let Some(ram) = a2600_sys.cart_ram() {
+                            let file_name = runtime_state
+                                .get_mount("Cartridge")
+                                .and_then(|p| std::path::Path::new(p).file_stem())
+                                .map_or_else(
+                                    || "cartridge.ram".to_string(),
+                                    |stem| format!("{}.ram", stem.to_string_lossy()),
+                                );
+                            if let Some(path) = rfd::FileDialog::new()
+                                .add_filter("Cartridge RAM", &["ram"])
+                                .set_file_name(file_name)
+                                .save_file()
+                            {
+                                egui_app
+                                    .status_bar
+                                    .set_message(match fs::write(&path, ram) {
+                                        Ok(()) => {
+                                            format!("Exported cartridge RAM to {}", path.display())
+                                        }
+                                        Err(e) => format!("Error exporting cartridge RAM: {}", e),
+                                    });
+                            }
+                        }
+                    }
+                }
+                PropertyAction::ImportAtariCartRam => {
+                    if let EmulatorSystem::Atari2600(a2600_sys) = &mut sys {
+                        if let Some(path) = rfd::FileDialog::new()
+                            .add_filter("Cartridge RAM", &["ram"])
+                            .add_filter("All Files", &["*"])
+                            .pick_file()
+                        {
+                            let result =
+                                fs::read(&path).map_err(|e| e.to_string()).and_then(|data| {
+                                    a2600_sys.load_cart_ram(&data).map_err(|e| e.to_string())
+                                });
+                            egui_app.status_bar.set_message(match result {
+                                Ok(()) => format!("Imported cartridge RAM from {}", path.display()),
+                                Err(e) => format!("Error importing cartridge RAM: {}", e),
+                            });
+                        }
+                    }
+                }
+                PropertyAction::SetAtariBeamAccurate(beam) => {
+                    if let EmulatorSystem::Atari2600(a2600_sys) = &mut sys {
+                        a2600_sys.set_tia_accuracy(if beam {
+                            emu_atari2600::TiaAccuracy::Beam
+                        } else {
+                            emu_atari2600::TiaAccuracy::Scanline
+                        });
+                        egui_app.status_bar.set_message(format!(
+                            "TIA rendering set to {}",
+                            if beam { "Beam" } else { "Scanline" }
+                        ));
+                    }
+                }
             }
         }
 
@@ -3681,6 +5370,33 @@ fn main() {
         // Handle display filter changes from property pane
         settings.display_filter = egui_app.property_pane.display_filter;
 
+        // Handle master volume changes from property pane
+        let master_volume = egui_app.property_pane.master_volume_percent as f32 / 100.0;
+        if (settings.master_volume - master_volume).abs() > f32::EPSILON {
+            settings.master_volume = master_volume;
+            if let Err(e) = settings.save() {
+                eprintln!("Failed to save master volume: {}", e);
+            }
+        }
+
+        // Handle frame pacing changes from property pane
+        if egui_app.property_pane.pacing_mode != frame_pacer.mode() {
+            let mode = egui_app.property_pane.pacing_mode;
+            frame_pacer.set_mode(mode);
+            if let Err(e) = egui_backend.set_vsync(frame_pacer.wants_vsync()) {
+                eprintln!("Failed to set vsync: {}", e);
+            }
+            // Restart wall-clock tracking so the new mode doesn't catch up
+            was_emulation_active = false;
+            settings.pacing_mode = mode;
+            egui_app
+                .status_bar
+                .set_message(format!("Frame pacing: {}", mode.name()));
+            if let Err(e) = settings.save() {
+                eprintln!("Failed to save frame pacing: {}", e);
+            }
+        }
+
         // Handle log rate limit changes
         let current_rate_limit = emu_core::logging::LogConfig::global().get_rate_limit();
         if settings.log_rate_limit != current_rate_limit {
@@ -3692,11 +5408,12 @@ fn main() {
         }
 
         // Handle input configuration changes from property pane
-        // Sync mouse settings back to the appropriate config (global or project-specific)
+        // Sync mouse and gamepad settings back to the appropriate config (global or project-specific)
         let input_config_changed = settings.input.mouse_enabled
             != egui_app.property_pane.mouse_enabled
             || (settings.input.mouse_sensitivity - egui_app.property_pane.mouse_sensitivity).abs()
-                > 0.01;
+                > 0.01
+            || settings.input.player_gamepads != egui_app.property_pane.player_gamepads;
 
         if input_config_changed {
             match egui_app.property_pane.input_config_source {
@@ -3704,6 +5421,7 @@ fn main() {
                     // Update global settings
                     settings.input.mouse_enabled = egui_app.property_pane.mouse_enabled;
                     settings.input.mouse_sensitivity = egui_app.property_pane.mouse_sensitivity;
+                    settings.input.player_gamepads = egui_app.property_pane.player_gamepads.clone();
                     // Auto-save global config
                     if let Err(e) = settings.save() {
                         eprintln!("Failed to save global input config: {}", e);
@@ -3721,6 +5439,8 @@ fn main() {
                     if let Some(ref mut input_override) = runtime_state.input_override {
                         input_override.mouse_enabled = egui_app.property_pane.mouse_enabled;
                         input_override.mouse_sensitivity = egui_app.property_pane.mouse_sensitivity;
+                        input_override.player_gamepads =
+                            egui_app.property_pane.player_gamepads.clone();
                         // Note: Project will be saved when user explicitly saves the project
                         egui_app.status_bar.set_message(
                             "Project input config updated (save project to persist)".to_string(),
@@ -3734,7 +5454,148 @@ fn main() {
         if let Some(action) = egui_app.tab_manager.take_action() {
             use egui_ui::TabAction;
             match action {
+                TabAction::LoadSaveState(hash, slot) => {
+                    if rom_hash.as_deref() == Some(hash.as_str()) && sys.supports_save_states() {
+                        match load_state_slot(&mut sys, &_game_saves, slot, &hash) {
+                            Ok(saved_play_time) => {
+                                play_time_secs = saved_play_time as f64;
+                                egui_app
+                                    .status_bar
+                                    .set_message(format!("Loaded from slot {}", slot));
+                                egui_app
+                                    .tab_manager
+                                    .add_log(format!("State loaded from slot {}", slot));
+                                egui_app.tab_manager.active_tab = egui_ui::Tab::Emulator;
+                            }
+                            Err(e) => {
+                                egui_app.status_bar.set_message(e);
+                            }
+                        }
+                    } else {
+                        egui_app
+                            .status_bar
+                            .set_message("Load the matching game first".to_string());
+                    }
+                }
+                TabAction::ExportSaveState(hash, slot) => {
+                    if let Some(path) = rfd::FileDialog::new()
+                        .add_filter("Hemulator Save State", &["hstate"])
+                        .set_file_name(format!("slot{}.hstate", slot))
+                        .save_file()
+                    {
+                        match GameSaves::load(&hash).export_slot(slot, &hash, &path) {
+                            Ok(()) => {
+                                egui_app.status_bar.set_message(format!(
+                                    "Exported slot {} to {}",
+                                    slot,
+                                    path.display()
+                                ));
+                            }
+                            Err(e) => {
+                                egui_app
+                                    .status_bar
+                                    .set_message(format!("Error exporting state: {}", e));
+                            }
+                        }
+                    }
+                }
+                TabAction::DeleteSaveState(hash, slot) => {
+                    let mut saves = GameSaves::load(&hash);
+                    match saves.delete_slot(slot, &hash) {
+                        Ok(()) => {
+                            if rom_hash.as_deref() == Some(hash.as_str()) {
+                                _game_saves = saves;
+                            }
+                            egui_app
+                                .status_bar
+                                .set_message(format!("Deleted save state in slot {}", slot));
+                        }
+                        Err(e) => {
+                            egui_app
+                                .status_bar
+                                .set_message(format!("Error deleting state: {}", e));
+                        }
+                    }
+                    egui_app
+                        .tab_manager
+                        .update_save_state_items(save_state_list_items(), rom_hash.clone());
+                }
+                TabAction::ImportSaveState => {
+                    if let Some(path) = rfd::FileDialog::new()
+                        .add_filter("Hemulator Save State", &["hstate"])
+                        .add_filter("All Files", &["*"])
+                        .pick_file()
+                    {
+                        match GameSaves::import_file(&path) {
+                            Ok((hash, slot)) => {
+                                if rom_hash.as_deref() == Some(hash.as_str()) {
+                                    _game_saves = GameSaves::load(&hash);
+                                }
+                                egui_app
+                                    .status_bar
+                                    .set_message(format!("Imported save state into slot {}", slot));
+                            }
+                            Err(e) => {
+                                egui_app
+                                    .status_bar
+                                    .set_message(format!("Error importing state: {}", e));
+                            }
+                        }
+                        egui_app
+                            .tab_manager
+                            .update_save_state_items(save_state_list_items(), rom_hash.clone());
+                    }
+                }
+                TabAction::CaptureDisplayList => {
+                    if let EmulatorSystem::N64(s) = &mut sys {
+                        s.capture_display_list();
+                        egui_app
+                            .status_bar
+                            .set_message("Capturing the next display list...".to_string());
+                    }
+                }
+                TabAction::SaveDisplayListDump => {
+                    if let Some(capture) = &egui_app.tab_manager.display_list {
+                        if let Some(path) = rfd::FileDialog::new()
+                            .add_filter("Text Files", &["txt"])
+                            .set_file_name("display_list.txt")
+                            .save_file()
+                        {
+                            match fs::write(&path, capture.dump()) {
+                                Ok(()) => egui_app
+                                    .status_bar
+                                    .set_message(format!("Display list saved: {}", path.display())),
+                                Err(e) => egui_app
+                                    .status_bar
+                                    .set_error(format!("Failed to save display list: {}", e)),
+                            }
+                        }
+                    }
+                }
+                TabAction::SaveInputMappings => {
+                    let info = &egui_app.tab_manager.input_mappings;
+                    settings.input.mapping_profiles = info.profiles.clone();
+                    settings.input.system_mappings = info.system_mappings.clone();
+                    settings.input.game_mappings = info.game_mappings.clone();
+                    settings.input.turbo_frames_per_toggle = info.turbo_frames_per_toggle;
+                    match settings.save() {
+                        Ok(()) => egui_app
+                            .status_bar
+                            .set_message("Input mappings saved".to_string()),
+                        Err(e) => egui_app
+                            .status_bar
+                            .set_message(format!("Failed to save input mappings: {}", e)),
+                    }
+                }
+                TabAction::RefreshSaveStates => {
+                    egui_app
+                        .tab_manager
+                        .update_save_state_items(save_state_list_items(), rom_hash.clone());
+                }
                 TabAction::CreateNewProject(system_name) => {
+                    if let Some(msg) = write_battery_save(&sys, &runtime_state) {
+                        egui_app.tab_manager.add_log(msg);
+                    }
                     // Create a new system based on the selected type
                     match system_name.as_str() {
                         "NES" => {
@@ -3825,6 +5686,7 @@ fn main() {
                                 egui_app.property_pane.rendering_backend =
                                     sys.get_current_renderer_name();
                             }
+                            enable_n64_threaded_rcp(&mut sys);
 
                             egui_app.property_pane.available_renderers =
                                 sys.get_available_renderers();
@@ -3877,6 +5739,39 @@ fn main() {
             }
         }
 
+        // The PC mouse driver takes the host mouse once a program installs it:
+        // clicking the picture or the capture key captures it, the capture key
+        // releases it
+        let can_capture_mouse = rom_loaded
+            && egui_app.property_pane.mouse_enabled
+            && matches!(&sys, EmulatorSystem::PC(pc_sys) if pc_sys.mouse_driver_installed());
+        let capture_key_pressed = string_to_key(&settings.input.mouse_capture_key)
+            .is_some_and(|k| egui_backend.is_key_pressed(k, false));
+        if !can_capture_mouse {
+            if egui_backend.is_mouse_captured() {
+                egui_backend.set_mouse_captured(false);
+            }
+        } else if egui_backend.is_mouse_captured() {
+            if capture_key_pressed {
+                egui_backend.set_mouse_captured(false);
+                egui_app
+                    .status_bar
+                    .set_message("Mouse released".to_string());
+            }
+        } else if capture_key_pressed
+            || egui_app
+                .tab_manager
+                .emulator_pointer
+                .is_some_and(|pointer| pointer.primary_pressed)
+        {
+            egui_backend.set_mouse_captured(true);
+            mouse_motion = input::MouseMotion::default();
+            egui_app.status_bar.set_message(format!(
+                "Mouse captured ({} to release)",
+                settings.input.mouse_capture_key
+            ));
+        }
+
         // Step emulation frame if ROM is loaded and not paused
         if rom_loaded && settings.emulation_speed > 0.0 {
             // Reset timing when emulation becomes active or speed changes
@@ -3895,8 +5790,7 @@ fn main() {
             let time_since_start = emulation_start_time.elapsed();
 
             // Get target frame time
-            let timing = sys.timing();
-            let frame_rate = timing.frame_rate_hz();
+            let frame_rate = sys.frame_rate();
             let target_frame_duration = Duration::from_secs_f64(1.0 / frame_rate);
 
             // Calculate how many frames we need to emulate to catch up
@@ -3910,41 +5804,92 @@ fn main() {
             // Calculate the actual number of frames we need to catch up
             // We step all necessary frames but only render the last one for smooth visuals
             let frames_behind = (time_diff_secs / target_frame_duration.as_secs_f64()) as usize;
-            let frames_to_step = if frames_behind > 0 {
-                // Cap frames per iteration to prevent pathological catch-up behavior
-                // Higher cap (30) allows faster recovery from lag spikes without audio desync
-                let max_frames_per_iteration: usize = 30;
-                frames_behind.min(max_frames_per_iteration)
-            } else {
-                0
-            };
+            // Cap frames per iteration to prevent pathological catch-up behavior
+            // Higher cap (30) allows faster recovery from lag spikes without audio desync
+            let max_frames_per_iteration: usize = 30;
+            // Rates like 59.73 Hz don't divide the sample rate evenly; carry the
+            // fraction so the audio budget doesn't drift
+            let samples_per_frame = SAMPLE_RATE as f64 / frame_rate;
+            let mut frames_to_step = frame_pacer.frames_to_step(
+                frames_behind.min(max_frames_per_iteration),
+                audio_queued.load(Ordering::Relaxed),
+                samples_per_frame.round() as usize,
+                emulation_speed,
+            );
 
-            let mut last_frame_opt: Option<emu_core::types::Frame> = None;
+            let mut last_frame_opt: Option<emu_core::types::Frame> = modal_frame.take();
 
-            // Step the calculated number of frames
-            for _ in 0..frames_to_step {
-                // Step the frame
-                match sys.step_frame() {
-                    Ok(frame) => {
-                        last_frame_opt = Some(frame);
+            if rewind_hash != rom_hash {
+                rewind_hash = rom_hash.clone();
+                rewind.clear();
+            }
 
-                        // Handle audio for each stepped frame
-                        let samples_per_frame = (SAMPLE_RATE as f64 / frame_rate) as usize;
-                        let audio_samples = sys.get_audio_samples(samples_per_frame);
-                        for sample in audio_samples {
-                            let _ = audio_tx.try_send(sample);
+            // Holding Backspace (host modifier + Backspace on PC) steps back one
+            // snapshot per loop iteration instead of running forward
+            let host_key_down = string_to_key(&settings.input.host_modifier)
+                .is_some_and(|k| egui_backend.is_key_down(k));
+            let rewind_held = egui_backend.is_key_down(Key::Backspace)
+                && (host_key_down || !matches!(&sys, EmulatorSystem::PC(_)))
+                && sys.supports_save_states()
+                && settings.rewind_memory_mb > 0
+                && !movie_session.is_active();
+            if rewind_held {
+                frames_to_step = 0;
+                // Resume at normal pace once the key is released
+                emulation_start_time = Instant::now();
+                total_emulated_time = Duration::ZERO;
+
+                match rewind.pop() {
+                    Some(state) => match sys.load_state(&state) {
+                        // Run one silent frame to have a picture of the restored point
+                        Ok(()) => last_frame_opt = sys.step_frame().ok(),
+                        Err(e) => {
+                            eprintln!("Rewind failed: {}", e);
+                            rewind.clear();
                         }
-                    }
-                    Err(e) => {
-                        eprintln!("Emulation error: {}", e);
-                        break;
-                    }
+                    },
+                    None => egui_app
+                        .status_bar
+                        .set_message("Nothing left to rewind".to_string()),
+                }
+            }
+
+            // Step the calculated number of frames
+            let stepped = step_frames(
+                &mut sys,
+                frames_to_step,
+                &settings,
+                &mut movie_session,
+                &mut rewind,
+                samples_per_frame,
+                &mut audio_sample_credit,
+                &audio_tx,
+                &audio_queued,
+                &mut last_frame_opt,
+            );
+            if stepped.movie_finished || modal_stepped.movie_finished {
+                egui_app
+                    .status_bar
+                    .set_message("Movie playback finished".to_string());
+            }
+
+            // A stacked multi-load game asked for its next load
+            if let EmulatorSystem::Atari2600(a2600_sys) = &mut sys {
+                if a2600_sys.take_load_request() {
+                    prompt_atari_next_load(a2600_sys, &runtime_state, &mut egui_app);
                 }
             }
 
             // Accumulate emulated time outside the loop (based on frames actually stepped)
             total_emulated_time += target_frame_duration * frames_to_step as u32;
 
+            // Track play time per game for save-state metadata
+            if play_time_hash != rom_hash {
+                play_time_hash = rom_hash.clone();
+                play_time_secs = 0.0;
+            }
+            play_time_secs += frames_to_step as f64 / frame_rate;
+
             // Render only the last frame to the display (always update client screen - requirement 3.2)
             if let Some(mut frame) = last_frame_opt {
                 // Apply display filter to the frame
@@ -3954,7 +5899,19 @@ fn main() {
                     frame.height as usize,
                 );
 
-                // Store frame buffer for screenshots (after filter is applied)
+                // Draw the controller input display on top of the filtered frame
+                if settings.show_input_display {
+                    if let EmulatorSystem::NES(s) = &sys {
+                        input_display::draw_nes(
+                            &mut frame.pixels,
+                            frame.width as usize,
+                            frame.height as usize,
+                            &[s.controller_state(0), s.controller_state(1)],
+                        );
+                    }
+                }
+
+                // Store frame buffer for screenshots (after filter and overlays are applied)
                 latest_frame_buffer = Some((
                     frame.pixels.clone(),
                     frame.width as usize,
@@ -3968,16 +5925,147 @@ fn main() {
                     frame.width as usize,
                     frame.height as usize,
                 );
+                sys.recycle_frame(frame);
+                egui_app.property_pane.frame_pool_stats = sys.frame_pool_stats();
             }
 
             // Handle keyboard input for emulator
-            if !matches!(&sys, EmulatorSystem::PC(_)) {
-                // For non-PC systems, use standard controller mapping
-                let controller_state = get_controller_state(&egui_backend, &settings.input.player1);
-                let snes_state = get_snes_controller_state(&egui_backend, &settings.input.player1);
-                match &mut sys {
-                    EmulatorSystem::SNES(s) => s.set_controller(0, snes_state),
-                    _ => sys.set_controller(0, controller_state),
+            if movie_session.is_playing() {
+                // The movie supplies input frame by frame
+            } else if !matches!(&sys, EmulatorSystem::PC(_)) {
+                // For non-PC systems, each player's mapping drives its own port
+                // (single-port systems ignore port 1), together with the
+                // gamepad assigned to the player; disabled players read as
+                // released
+                let player_gamepads = &settings.input.player_gamepads;
+                let gamepads = input::assign_gamepads(player_gamepads, &egui_backend.gamepads());
+                let gamepad_profile = settings
+                    .input
+                    .profiles
+                    .iter()
+                    .flatten()
+                    .find(|profile| profile.device_type == Some(InputDeviceType::Gamepad))
+                    .cloned()
+                    .unwrap_or_else(ControllerProfile::gamepad_default);
+                // The game's or system's mapping profile, else the global keys
+                let mappings = settings
+                    .input
+                    .key_mappings(sys.system_name(), rom_hash.as_deref());
+                // Held turbo keys press and release A/B every few frames
+                let turbo_on =
+                    input::turbo_pressed(frame_counter, settings.input.turbo_frames_per_toggle);
+                let mut players = vec![
+                    (egui_app.property_pane.player1_enabled, mappings[0]),
+                    (egui_app.property_pane.player2_enabled, mappings[1]),
+                ];
+                // A Four Score adds players 3 and 4
+                if matches!(&sys, EmulatorSystem::NES(nes_sys) if nes_sys.four_score()) {
+                    players.push((true, mappings[2]));
+                    players.push((true, mappings[3]));
+                }
+                for (port, (enabled, mapping)) in players.into_iter().enumerate() {
+                    let pad = &player_gamepads[port];
+                    let pad_profile = pad.profile.as_ref().unwrap_or(&gamepad_profile);
+                    let pad_id = gamepads[port];
+                    match &sys {
+                        EmulatorSystem::SNES(_) => {
+                            let state = if enabled {
+                                get_snes_controller_state(&egui_backend, mapping, turbo_on)
+                                    | pad_id.map_or(0, |id| {
+                                        input_mapper::get_snes_gamepad_state(
+                                            pad_profile,
+                                            &egui_backend,
+                                            &egui_backend,
+                                            id,
+                                            pad.axis_threshold_raw(),
+                                        )
+                                    })
+                            } else {
+                                0
+                            };
+                            sys.set_controller_16(port, state);
+                            movie_session.note_controller(port, state);
+                        }
+                        // Keys and buttons in the common layout, and the
+                        // gamepad's left stick as the analog stick
+                        EmulatorSystem::N64(_) => {
+                            let (state, stick) = if enabled {
+                                let (pad_state, stick) = pad_id.map_or((0, None), |id| {
+                                    (
+                                        input_mapper::get_extended_gamepad_state(
+                                            pad_profile,
+                                            &egui_backend,
+                                            &egui_backend,
+                                            id,
+                                            pad.axis_threshold_raw(),
+                                        ),
+                                        input_mapper::get_n64_stick(
+                                            &egui_backend,
+                                            id,
+                                            pad.axis_threshold_raw(),
+                                        ),
+                                    )
+                                });
+                                (
+                                    get_extended_controller_state(&egui_backend, mapping, turbo_on)
+                                        | pad_state,
+                                    stick,
+                                )
+                            } else {
+                                (0, None)
+                            };
+                            if let EmulatorSystem::N64(n64) = &mut sys {
+                                let mut controller = emu_n64::ControllerState::from_pad(state);
+                                if let Some((x, y)) = stick {
+                                    controller.stick_x = x;
+                                    controller.stick_y = y;
+                                }
+                                n64.set_controller_state(port, controller);
+                                n64.set_controller_connected(port, enabled);
+                            }
+                            movie_session.note_controller(port, state);
+                        }
+                        // The Atari quick settings menu takes the input while open
+                        EmulatorSystem::Atari2600(_) if atari_quick_menu.is_open() => {
+                            sys.set_controller(port, 0);
+                            movie_session.note_controller(port, 0);
+                        }
+                        _ => {
+                            let state = if enabled {
+                                get_controller_state(&egui_backend, mapping, turbo_on)
+                                    | pad_id.map_or(0, |id| {
+                                        input_mapper::get_gamepad_state(
+                                            pad_profile,
+                                            &egui_backend,
+                                            &egui_backend,
+                                            id,
+                                            pad.axis_threshold_raw(),
+                                        )
+                                    })
+                            } else {
+                                0
+                            };
+                            sys.set_controller(port, state);
+                            movie_session.note_controller(port, state as u16);
+                        }
+                    }
+                }
+
+                // A Zapper in port 2 aims at the mouse over the picture; right
+                // click fires off screen (not recorded in movies)
+                if let EmulatorSystem::NES(nes_sys) = &mut sys {
+                    if nes_sys.zapper_connected() {
+                        let (x, y, trigger) = match egui_app.tab_manager.emulator_pointer {
+                            Some(pointer) if pointer.secondary_down => (-1, -1, true),
+                            Some(pointer) => (
+                                (pointer.x * 256.0) as i32,
+                                (pointer.y * 240.0) as i32,
+                                pointer.primary_down,
+                            ),
+                            None => (-1, -1, false),
+                        };
+                        nes_sys.set_zapper(x, y, trigger);
+                    }
                 }
             } else {
                 // PC systems handle keyboard directly via scancodes
@@ -3986,9 +6074,34 @@ fn main() {
                 if let EmulatorSystem::PC(pc_sys) = &mut sys {
                     for scancode in pressed {
                         pc_sys.key_press_sdl2(*scancode as u32);
+                        movie_session.note_key(*scancode as u32, true);
                     }
                     for scancode in released {
                         pc_sys.key_release_sdl2(*scancode as u32);
+                        movie_session.note_key(*scancode as u32, false);
+                    }
+
+                    // Gamepad sticks and A/B/X/Y drive the joysticks on the game port
+                    let joystick = (egui_backend.num_gamepads() > 0).then(|| {
+                        let held = egui_backend.gamepad_buttons_down();
+                        let buttons = [Button::A, Button::B, Button::X, Button::Y]
+                            .iter()
+                            .enumerate()
+                            .filter(|(_, button)| held.contains(&(**button as u8)))
+                            .fold(0, |bits, (bit, _)| bits | 1 << bit);
+                        emu_pc::JoystickState {
+                            axes: [0, 1, 2, 3].map(|axis| egui_backend.any_gamepad_axis(axis)),
+                            buttons,
+                        }
+                    });
+                    pc_sys.set_joystick(joystick);
+
+                    // The captured mouse drives the INT 33h mouse driver
+                    if egui_backend.is_mouse_captured() {
+                        let (dx, dy) = egui_backend.mouse_motion();
+                        let (dx, dy) =
+                            mouse_motion.scale(dx, dy, egui_app.property_pane.mouse_sensitivity);
+                        pc_sys.move_mouse(dx, dy, egui_backend.mouse_buttons() as u16);
                     }
                 }
             }
@@ -4002,6 +6115,7 @@ fn main() {
 
         // Display FPS tracking (separate from emulation FPS - requirement 3.3)
         let frame_dt = last_frame.elapsed();
+        last_frame = Instant::now();
         display_frame_times.push(frame_dt);
         if display_frame_times.len() > 60 {
             display_frame_times.remove(0);
@@ -4014,16 +6128,19 @@ fn main() {
             }
         }
 
-        // Frame timing - skip sleep in benchmark mode
+        // Frame pacing - skip waiting in benchmark mode
         if !cli_args.benchmark {
-            // Target 120 FPS for display refresh (allows quicker catch-up)
-            let target_display_time = Duration::from_secs_f64(1.0 / 120.0);
-
-            // Sleep to maintain consistent display frame rate
-            if frame_dt < target_display_time {
-                std::thread::sleep(target_display_time - frame_dt);
-            }
+            frame_pacer.wait(
+                rom_loaded && settings.emulation_speed > 0.0,
+                sys.frame_rate(),
+                audio_queued.load(Ordering::Relaxed),
+                SAMPLE_RATE,
+            );
         }
-        last_frame = Instant::now();
+    }
+
+    // Keep in-game saves of battery-backed cartridges
+    if let Some(msg) = write_battery_save(&sys, &runtime_state) {
+        println!("{}", msg);
     }
 }
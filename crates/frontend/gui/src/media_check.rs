@@ -0,0 +1,476 @@
+//! Post-load integrity checks for mounted media
+//!
+//! Every file mounted in the GUI is hashed and looked up in a small bundled
+//! database of known dumps (`known_dumps.json`). Files the database knows to
+//! be good are trusted as-is; known bad dumps, overdumps and bad headers are
+//! reported with the database's note. Unknown files are checked against their
+//! own headers instead (declared size, header checksums, dirty or copier
+//! headers), which catches most truncated and overdumped ROMs without needing
+//! a full database.
+
+use crate::save_state::GameSaves;
+use serde::Deserialize;
+use std::sync::OnceLock;
+
+/// Bundled database of known dumps, keyed by SHA-256 of the whole file
+const KNOWN_DUMPS_JSON: &str = include_str!("known_dumps.json");
+
+/// What the database knows about a dump
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DumpStatus {
+    /// Verified good dump
+    Good,
+    /// Corrupt or incomplete dump
+    BadDump,
+    /// Dump with extra data beyond the real ROM size
+    Overdump,
+    /// Good ROM data behind a wrong or modified header
+    BadHeader,
+}
+
+impl DumpStatus {
+    fn describe(self) -> &'static str {
+        match self {
+            DumpStatus::Good => "known good dump",
+            DumpStatus::BadDump => "known bad dump",
+            DumpStatus::Overdump => "known overdump",
+            DumpStatus::BadHeader => "known bad header",
+        }
+    }
+}
+
+/// One entry of the known dump database
+#[derive(Debug, Clone, Deserialize)]
+pub struct KnownDump {
+    /// SHA-256 of the whole file (lowercase hex)
+    pub sha256: String,
+    /// System name as used by the frontend ("nes", "snes", "pc", ...)
+    pub system: String,
+    /// Display name of the dump
+    pub name: String,
+    pub status: DumpStatus,
+    /// Optional explanation shown with bad entries
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+/// How serious a finding is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// Harmless, worth logging (e.g. a copier header the emulator strips)
+    Notice,
+    /// The file is likely broken and may not run correctly
+    Warning,
+}
+
+/// One problem found in a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// Result of checking one mounted file
+#[derive(Debug, Clone, Default)]
+pub struct MediaReport {
+    /// Database entry matching the file's hash
+    pub known: Option<KnownDump>,
+    pub findings: Vec<Finding>,
+}
+
+impl MediaReport {
+    /// Log lines describing the report for a file
+    pub fn log_lines(&self, file_name: &str) -> Vec<String> {
+        let mut lines = Vec::new();
+        if let Some(known) = &self.known {
+            lines.push(format!(
+                "{}: {} ({})",
+                file_name,
+                known.status.describe(),
+                known.name
+            ));
+        }
+        for finding in &self.findings {
+            let prefix = match finding.severity {
+                Severity::Notice => "note",
+                Severity::Warning => "warning",
+            };
+            lines.push(format!("{} {}: {}", file_name, prefix, finding.message));
+        }
+        lines
+    }
+
+    /// One-line summary of the first warning, for the status bar
+    pub fn warning_summary(&self, file_name: &str) -> Option<String> {
+        let first = self
+            .findings
+            .iter()
+            .find(|f| f.severity == Severity::Warning)?;
+        let more = self
+            .findings
+            .iter()
+            .filter(|f| f.severity == Severity::Warning)
+            .count()
+            - 1;
+        Some(if more > 0 {
+            format!("{}: {} (+{} more, see Log)", file_name, first.message, more)
+        } else {
+            format!("{}: {}", file_name, first.message)
+        })
+    }
+
+    fn notice(&mut self, message: String) {
+        self.findings.push(Finding {
+            severity: Severity::Notice,
+            message,
+        });
+    }
+
+    fn warn(&mut self, message: String) {
+        self.findings.push(Finding {
+            severity: Severity::Warning,
+            message,
+        });
+    }
+}
+
+/// The bundled known dump database
+pub fn known_dumps() -> &'static [KnownDump] {
+    static DB: OnceLock<Vec<KnownDump>> = OnceLock::new();
+    DB.get_or_init(|| {
+        serde_json::from_str(KNOWN_DUMPS_JSON).unwrap_or_else(|e| {
+            eprintln!("Warning: Failed to parse known dump database: {}", e);
+            Vec::new()
+        })
+    })
+}
+
+/// Look up a file hash for a system in the known dump database
+pub fn lookup(system: &str, sha256: &str) -> Option<&'static KnownDump> {
+    known_dumps()
+        .iter()
+        .find(|d| d.system == system && d.sha256.eq_ignore_ascii_case(sha256))
+}
+
+/// Check a file mounted on `system` (frontend system name) at `mount_id`
+pub fn check_media(system: &str, mount_id: &str, data: &[u8]) -> MediaReport {
+    let mut report = MediaReport::default();
+
+    if let Some(known) = lookup(system, &GameSaves::rom_hash(data)) {
+        report.known = Some(known.clone());
+        if known.status != DumpStatus::Good {
+            let mut message = format!("{} ({})", known.status.describe(), known.name);
+            if let Some(note) = &known.note {
+                message.push_str(" - ");
+                message.push_str(note);
+            }
+            report.warn(message);
+        }
+        // The database is authoritative; header heuristics could only disagree
+        return report;
+    }
+
+    match (system, mount_id) {
+        ("nes", "Cartridge") => check_nes(data, &mut report),
+        ("snes", "Cartridge") => check_snes(data, &mut report),
+        ("gameboy", "Cartridge") => check_gameboy(data, &mut report),
+        _ => {}
+    }
+    report
+}
+
+/// Describe a size difference against a declared size
+fn check_declared_size(actual: usize, declared: usize, what: &str, report: &mut MediaReport) {
+    if actual < declared {
+        report.warn(format!(
+            "truncated: {} declares {} bytes but the file has {} (bad dump)",
+            what, declared, actual
+        ));
+    } else if actual > declared {
+        report.warn(format!(
+            "{} extra bytes beyond the {} bytes the {} declares (overdump or appended data)",
+            actual - declared,
+            declared,
+            what
+        ));
+    }
+}
+
+/// iNES / NES 2.0 header checks
+fn check_nes(data: &[u8], report: &mut MediaReport) {
+    if data.len() < 16 || &data[0..4] != b"NES\x1A" {
+        return;
+    }
+
+    let nes2 = data[7] & 0x0C == 0x08;
+    if !nes2 && (data[7] & 0x0C == 0x04 || data[12..16].iter().any(|&b| b != 0)) {
+        // Old dumping tools wrote signatures such as "DiskDude!" into bytes 7-15
+        report.warn(
+            "dirty iNES header (bytes 7-15 hold junk such as \"DiskDude!\"); \
+             the mapper number may be read wrong"
+                .to_string(),
+        );
+    }
+
+    let (prg_units, chr_units) = if nes2 {
+        let prg_msb = (data[9] & 0x0F) as usize;
+        let chr_msb = (data[9] >> 4) as usize;
+        // The exponent-multiplier size form is rare; skip the size check for it
+        if prg_msb == 0x0F || chr_msb == 0x0F {
+            return;
+        }
+        (
+            (prg_msb << 8) | data[4] as usize,
+            (chr_msb << 8) | data[5] as usize,
+        )
+    } else {
+        (data[4] as usize, data[5] as usize)
+    };
+
+    let trainer = if data[6] & 0x04 != 0 { 512 } else { 0 };
+    let declared = 16 + trainer + prg_units * 16 * 1024 + chr_units * 8 * 1024;
+    // PlayChoice-10 dumps carry an 8KB INST-ROM plus PROM after the CHR data
+    let playchoice = if data[7] & 0x02 != 0 { 8192 + 32 } else { 0 };
+    if data.len() > declared && data.len() <= declared + playchoice {
+        return;
+    }
+    check_declared_size(data.len(), declared, "iNES header", report);
+}
+
+/// Sum of all bytes, mirroring a non-power-of-two tail up to the next power
+/// of two the way the SNES header checksum is defined
+fn snes_checksum(rom: &[u8]) -> Option<u16> {
+    let sum = |bytes: &[u8]| {
+        bytes
+            .iter()
+            .fold(0u32, |acc, &b| acc.wrapping_add(b as u32))
+    };
+    if rom.len().is_power_of_two() {
+        return Some(sum(rom) as u16);
+    }
+    let base = 1usize << (usize::BITS - 1 - rom.len().leading_zeros());
+    let tail = &rom[base..];
+    if !tail.len().is_power_of_two() {
+        return None;
+    }
+    let repeats = (base / tail.len()) as u32;
+    Some(sum(&rom[..base]).wrapping_add(sum(tail).wrapping_mul(repeats)) as u16)
+}
+
+/// SNES copier header, internal header size and checksum checks
+fn check_snes(data: &[u8], report: &mut MediaReport) {
+    let rom = if data.len() % 1024 == 512 {
+        report
+            .notice("512-byte copier (SMC) header present; it is skipped when loading".to_string());
+        &data[512..]
+    } else {
+        data
+    };
+
+    if rom.len() % (32 * 1024) != 0 {
+        report.warn(format!(
+            "size {} is not a multiple of 32KB (bad dump or trailing data)",
+            rom.len()
+        ));
+    }
+
+    // Internal header: LoROM at $7FC0, HiROM at $FFC0. It is only trusted
+    // when the checksum and its complement agree.
+    let header = [0x7FC0usize, 0xFFC0].into_iter().find_map(|offset| {
+        let h = rom.get(offset..offset + 0x20)?;
+        let complement = u16::from_le_bytes([h[0x1C], h[0x1D]]);
+        let checksum = u16::from_le_bytes([h[0x1E], h[0x1F]]);
+        (complement ^ checksum == 0xFFFF).then_some((h[0x17], checksum))
+    });
+    let Some((size_code, checksum)) = header else {
+        return;
+    };
+
+    if (5..=13).contains(&size_code) {
+        let declared = 1024usize << size_code;
+        // Sizes between powers of two are declared as the next power of two
+        if rom.len() > declared || rom.len() <= declared / 2 {
+            check_declared_size(rom.len(), declared, "internal header", report);
+        }
+    }
+
+    if let Some(actual) = snes_checksum(rom) {
+        if actual != checksum {
+            report.warn(format!(
+                "internal checksum {:04X}h does not match the data ({:04X}h); \
+                 bad dump or patched ROM",
+                checksum, actual
+            ));
+        }
+    }
+}
+
+/// Game Boy header checksum, global checksum and ROM size checks
+fn check_gameboy(data: &[u8], report: &mut MediaReport) {
+    if data.len() < 0x150 {
+        return;
+    }
+
+    let header_checksum = data[0x134..=0x14C]
+        .iter()
+        .fold(0u8, |acc, &b| acc.wrapping_sub(b).wrapping_sub(1));
+    if header_checksum != data[0x14D] {
+        report.warn(format!(
+            "header checksum {:02X}h does not match ({:02X}h); real hardware will not boot it",
+            data[0x14D], header_checksum
+        ));
+        // Without a valid header the other fields can't be trusted either
+        return;
+    }
+
+    if data[0x148] <= 8 {
+        let declared = (32 * 1024) << data[0x148];
+        check_declared_size(data.len(), declared, "cartridge header", report);
+    }
+
+    let stored = u16::from_be_bytes([data[0x14E], data[0x14F]]);
+    let global = data
+        .iter()
+        .enumerate()
+        .filter(|&(i, _)| i != 0x14E && i != 0x14F)
+        .fold(0u16, |acc, (_, &b)| acc.wrapping_add(b as u16));
+    if stored != global {
+        report.warn(format!(
+            "global checksum {:04X}h does not match the data ({:04X}h); bad dump or patched ROM",
+            stored, global
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_dump_database() {
+        let db = known_dumps();
+        assert!(!db.is_empty());
+        for (i, entry) in db.iter().enumerate() {
+            assert_eq!(entry.sha256.len(), 64, "{}", entry.name);
+            assert!(entry.sha256.chars().all(|c| c.is_ascii_hexdigit()));
+            assert!(
+                ["nes", "gameboy", "atari2600", "pc", "snes", "n64"]
+                    .contains(&entry.system.as_str()),
+                "{}",
+                entry.name
+            );
+            assert!(db[..i].iter().all(|other| other.sha256 != entry.sha256));
+        }
+        let first = &db[0];
+        assert_eq!(
+            lookup(&first.system, &first.sha256.to_uppercase()).map(|d| d.name.as_str()),
+            Some(first.name.as_str())
+        );
+    }
+
+    fn nes_rom(prg_units: u8, chr_units: u8) -> Vec<u8> {
+        let mut rom = vec![0u8; 16 + prg_units as usize * 16384 + chr_units as usize * 8192];
+        rom[0..4].copy_from_slice(b"NES\x1A");
+        rom[4] = prg_units;
+        rom[5] = chr_units;
+        rom
+    }
+
+    #[test]
+    fn test_nes_checks() {
+        let rom = nes_rom(2, 1);
+        assert!(check_media("nes", "Cartridge", &rom).findings.is_empty());
+
+        let report = check_media("nes", "Cartridge", &rom[..rom.len() - 100]);
+        assert!(report.warning_summary("game").is_some());
+        assert!(report.findings[0].message.starts_with("truncated"));
+
+        let mut overdump = rom.clone();
+        overdump.extend_from_slice(&[0xFF; 16384]);
+        let report = check_media("nes", "Cartridge", &overdump);
+        assert!(report.findings[0].message.contains("16384 extra bytes"));
+
+        let mut dirty = rom.clone();
+        dirty[7..16].copy_from_slice(b"DiskDude!");
+        let report = check_media("nes", "Cartridge", &dirty);
+        assert!(report.findings[0].message.contains("dirty iNES header"));
+    }
+
+    #[test]
+    fn test_gameboy_checks() {
+        let mut rom = vec![0u8; 32 * 1024];
+        rom[0x134..0x13A].copy_from_slice(b"HEMUGB");
+        rom[0x14D] = rom[0x134..=0x14C]
+            .iter()
+            .fold(0u8, |acc, &b| acc.wrapping_sub(b).wrapping_sub(1));
+        let global = rom.iter().fold(0u16, |acc, &b| acc.wrapping_add(b as u16));
+        rom[0x14E..0x150].copy_from_slice(&global.to_be_bytes());
+        assert!(check_media("gameboy", "Cartridge", &rom)
+            .findings
+            .is_empty());
+
+        // A flipped data byte only breaks the global checksum
+        let mut bad = rom.clone();
+        bad[0x4000] = 0x55;
+        let report = check_media("gameboy", "Cartridge", &bad);
+        assert_eq!(report.findings.len(), 1);
+        assert!(report.findings[0].message.starts_with("global checksum"));
+
+        // Header corruption is reported on its own
+        bad[0x134] = b'X';
+        let report = check_media("gameboy", "Cartridge", &bad);
+        assert_eq!(report.findings.len(), 1);
+        assert!(report.findings[0].message.starts_with("header checksum"));
+    }
+
+    #[test]
+    fn test_snes_checks() {
+        // 64KB LoROM with a valid checksum
+        let mut rom = vec![0u8; 64 * 1024];
+        rom[0x7FC0 + 0x17] = 6; // Declares 64KB
+        rom[0x1234] = 0x42;
+        // Checksum bytes themselves always add 0xFF + 0xFF
+        let checksum = (snes_checksum(&rom).unwrap() as u32 + 0x1FE) as u16;
+        rom[0x7FDC..0x7FDE].copy_from_slice(&(!checksum).to_le_bytes());
+        rom[0x7FDE..0x7FE0].copy_from_slice(&checksum.to_le_bytes());
+        assert!(check_media("snes", "Cartridge", &rom).findings.is_empty());
+
+        // A copier header is only a notice
+        let mut headered = vec![0u8; 512];
+        headered.extend_from_slice(&rom);
+        let report = check_media("snes", "Cartridge", &headered);
+        assert_eq!(report.findings.len(), 1);
+        assert!(report.warning_summary("game").is_none());
+
+        // An overdump (doubled ROM) breaks both size and checksum
+        let mut doubled = rom.clone();
+        doubled.extend_from_slice(&rom);
+        let report = check_media("snes", "Cartridge", &doubled);
+        assert!(report
+            .findings
+            .iter()
+            .any(|f| f.message.contains("65536 extra bytes")));
+        assert!(report.warning_summary("game.sfc").unwrap().contains("more"));
+    }
+
+    #[test]
+    fn test_snes_checksum_mirrors_tail() {
+        // 3 x 32KB: the last 32KB is counted twice to fill 128KB
+        let mut rom = vec![1u8; 96 * 1024];
+        rom[64 * 1024..].fill(2);
+        let expected = (64 * 1024 + 2 * 2 * 32 * 1024) as u32 as u16;
+        assert_eq!(snes_checksum(&rom), Some(expected));
+    }
+
+    #[test]
+    fn test_unchecked_media() {
+        // Systems and mounts without header checks report nothing
+        assert!(check_media("pc", "FloppyA", &[0u8; 1000])
+            .findings
+            .is_empty());
+        assert!(check_media("atari2600", "Cartridge", &[0u8; 4096])
+            .findings
+            .is_empty());
+    }
+}
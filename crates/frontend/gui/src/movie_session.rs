@@ -0,0 +1,160 @@
+//! Recording and playback state for input movies
+//!
+//! The main loop reports the input it applies through [`MovieSession`] and
+//! calls [`MovieSession::next_frame`] before stepping each frame. While
+//! recording, the input in effect is appended to the movie; while playing, the
+//! input of an [`InputProvider`] (a movie, or any scripted source) is returned
+//! so it can replace the live controls.
+
+use emu_core::input::InputProvider;
+use emu_core::movie::{InputMovie, MovieFrame, MoviePlayer};
+
+/// File extension used for saved movies
+pub const MOVIE_EXTENSION: &str = "hmovie";
+
+#[derive(Default)]
+pub enum MovieSession {
+    #[default]
+    Idle,
+    Recording {
+        movie: InputMovie,
+        /// Input in effect for the next frame
+        pending: MovieFrame,
+    },
+    Playing(Box<dyn InputProvider>),
+}
+
+/// What the main loop should do before stepping a frame
+pub enum MovieStep {
+    /// Use live input
+    Live,
+    /// Apply this input from the movie
+    Play(MovieFrame),
+    /// Playback just ran out of frames
+    Finished,
+}
+
+impl MovieSession {
+    pub fn record(movie: InputMovie) -> Self {
+        MovieSession::Recording {
+            movie,
+            pending: MovieFrame::default(),
+        }
+    }
+
+    pub fn play(movie: InputMovie) -> Self {
+        Self::provide(Box::new(MoviePlayer::new(movie)))
+    }
+
+    /// Replace the live controls with input from `provider` until it runs out
+    pub fn provide(provider: Box<dyn InputProvider>) -> Self {
+        MovieSession::Playing(provider)
+    }
+
+    /// Whether a movie is being recorded or played
+    pub fn is_active(&self) -> bool {
+        !matches!(self, MovieSession::Idle)
+    }
+
+    pub fn is_playing(&self) -> bool {
+        matches!(self, MovieSession::Playing(_))
+    }
+
+    /// Record the controller state the live input just applied to a port
+    pub fn note_controller(&mut self, port: usize, state: u16) {
+        if let MovieSession::Recording { pending, .. } = self {
+            if pending.controllers.len() <= port {
+                pending.controllers.resize(port + 1, 0);
+            }
+            pending.controllers[port] = state;
+        }
+    }
+
+    /// Record a keyboard scancode event the live input just sent
+    pub fn note_key(&mut self, scancode: u32, pressed: bool) {
+        if let MovieSession::Recording { pending, .. } = self {
+            if pressed {
+                pending.key_presses.push(scancode);
+            } else {
+                pending.key_releases.push(scancode);
+            }
+        }
+    }
+
+    /// Advance one frame, before it is stepped
+    pub fn next_frame(&mut self) -> MovieStep {
+        match self {
+            MovieSession::Idle => MovieStep::Live,
+            MovieSession::Recording { movie, pending } => {
+                // Key events happen once; held controller state carries over
+                movie.push_frame(pending.clone());
+                pending.key_presses.clear();
+                pending.key_releases.clear();
+                MovieStep::Live
+            }
+            MovieSession::Playing(provider) => match provider.next_input() {
+                Some(frame) => MovieStep::Play(frame),
+                None => {
+                    *self = MovieSession::Idle;
+                    MovieStep::Finished
+                }
+            },
+        }
+    }
+
+    /// End the session, returning the movie if one was being recorded
+    pub fn stop(&mut self) -> Option<InputMovie> {
+        match std::mem::take(self) {
+            MovieSession::Recording { movie, .. } => Some(movie),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use emu_core::input::ScriptedInput;
+    use emu_core::movie::MovieStart;
+
+    #[test]
+    fn test_recording_captures_held_input_and_key_events_once() {
+        let mut session = MovieSession::record(InputMovie::new("pc", None, MovieStart::PowerOn));
+        session.note_controller(1, 0x08);
+        session.note_key(30, true);
+        assert!(matches!(session.next_frame(), MovieStep::Live));
+        assert!(matches!(session.next_frame(), MovieStep::Live));
+
+        let movie = session.stop().unwrap();
+        assert_eq!(movie.frames[0].controllers, vec![0, 0x08]);
+        assert_eq!(movie.frames[0].key_presses, vec![30]);
+        assert_eq!(movie.frames[1].controllers, vec![0, 0x08]);
+        assert!(movie.frames[1].key_presses.is_empty());
+        assert!(matches!(session, MovieSession::Idle));
+    }
+
+    #[test]
+    fn test_playback_returns_to_idle_when_finished() {
+        let mut movie = InputMovie::new("nes", None, MovieStart::PowerOn);
+        movie.push_frame(MovieFrame {
+            controllers: vec![0x01],
+            ..Default::default()
+        });
+        let mut session = MovieSession::play(movie);
+        assert!(matches!(session.next_frame(), MovieStep::Play(f) if f.controllers == vec![0x01]));
+        assert!(matches!(session.next_frame(), MovieStep::Finished));
+        assert!(!session.is_playing());
+        assert!(session.stop().is_none());
+    }
+
+    #[test]
+    fn test_scripted_input_drives_playback() {
+        let mut script = ScriptedInput::new();
+        script.set_controller(1, 0, 0x08);
+        script.end_at(2);
+        let mut session = MovieSession::provide(Box::new(script));
+        assert!(matches!(session.next_frame(), MovieStep::Play(f) if f.controllers.is_empty()));
+        assert!(matches!(session.next_frame(), MovieStep::Play(f) if f.controllers == vec![0x08]));
+        assert!(matches!(session.next_frame(), MovieStep::Finished));
+    }
+}
@@ -0,0 +1,290 @@
+//! "Now playing" information for the window and external integrations
+//!
+//! The frontend works out which game is running (from the ROM header where the
+//! format has a title, otherwise the file name) and publishes it through
+//! [`NowPlayingHub`]. The hub sets nothing itself; it forwards changes to
+//! registered [`NowPlayingHook`]s, which is the extension point for things
+//! like Discord-style rich presence. [`NowPlayingFile`] is the built-in hook:
+//! it mirrors the current game to a JSON file that other programs can watch.
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Base window title when nothing is running
+pub const APP_TITLE: &str = "Hemulator - Multi-System Emulator";
+
+/// The game currently running
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NowPlaying {
+    /// Display name of the system, e.g. "Game Boy"
+    pub system: String,
+    /// Game title from the ROM header, or the file name
+    pub title: String,
+    /// Hash of the loaded ROM, if any
+    pub rom_hash: Option<String>,
+    /// When the game was started (seconds since the Unix epoch)
+    pub started_at: u64,
+}
+
+/// Receives "now playing" changes (e.g. a rich presence bridge)
+pub trait NowPlayingHook {
+    /// Called when the running game changes; `None` when nothing is running
+    fn now_playing_changed(&mut self, now_playing: Option<&NowPlaying>);
+}
+
+/// Tracks the running game and notifies hooks when it changes
+#[derive(Default)]
+pub struct NowPlayingHub {
+    hooks: Vec<Box<dyn NowPlayingHook>>,
+    current: Option<NowPlaying>,
+}
+
+impl NowPlayingHub {
+    pub fn add_hook(&mut self, hook: Box<dyn NowPlayingHook>) {
+        self.hooks.push(hook);
+    }
+
+    pub fn current(&self) -> Option<&NowPlaying> {
+        self.current.as_ref()
+    }
+
+    /// Report the running game, or `None` when nothing is running
+    ///
+    /// Hooks are only notified if the system, title or ROM changed. Returns
+    /// true in that case.
+    pub fn update(&mut self, game: Option<(&str, &str, Option<&str>)>) -> bool {
+        let unchanged = match (&self.current, game) {
+            (None, None) => true,
+            (Some(cur), Some((system, title, rom_hash))) => {
+                cur.system == system && cur.title == title && cur.rom_hash.as_deref() == rom_hash
+            }
+            _ => false,
+        };
+        if unchanged {
+            return false;
+        }
+
+        self.current = game.map(|(system, title, rom_hash)| NowPlaying {
+            system: system.to_string(),
+            title: title.to_string(),
+            rom_hash: rom_hash.map(str::to_string),
+            started_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+        });
+        for hook in &mut self.hooks {
+            hook.now_playing_changed(self.current.as_ref());
+        }
+        true
+    }
+}
+
+/// Writes the running game to a JSON file (`null` when idle)
+pub struct NowPlayingFile {
+    path: PathBuf,
+}
+
+impl NowPlayingFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl NowPlayingHook for NowPlayingFile {
+    fn now_playing_changed(&mut self, now_playing: Option<&NowPlaying>) {
+        let result = serde_json::to_string_pretty(&now_playing)
+            .map_err(|e| e.to_string())
+            .and_then(|json| std::fs::write(&self.path, json).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            eprintln!(
+                "Failed to write now-playing file {}: {}",
+                self.path.display(),
+                e
+            );
+        }
+    }
+}
+
+/// Window title for the running game
+pub fn window_title(now_playing: Option<&NowPlaying>) -> String {
+    match now_playing {
+        Some(np) => format!("{} ({}) - Hemulator", np.title, np.system),
+        None => APP_TITLE.to_string(),
+    }
+}
+
+/// Display name for a system id (as returned by `EmulatorSystem::system_name`)
+pub fn system_display_name(system: &str) -> &str {
+    match system {
+        "nes" => "NES",
+        "gameboy" => "Game Boy",
+        "atari2600" => "Atari 2600",
+        "pc" => "PC",
+        "snes" => "SNES",
+        "n64" => "N64",
+        other => other,
+    }
+}
+
+/// Game title for a ROM: the header title where the format has one,
+/// otherwise the file name without extension
+pub fn game_title(system: &str, data: &[u8], path: &Path) -> String {
+    header_title(system, data).unwrap_or_else(|| {
+        path.file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| system_display_name(system).to_string())
+    })
+}
+
+/// Title stored in the ROM header (Game Boy, SNES and N64 only)
+pub fn header_title(system: &str, data: &[u8]) -> Option<String> {
+    match system {
+        "gameboy" => ascii_title(data.get(0x134..0x144)?),
+        "n64" => {
+            // Normalize the first header words to big-endian (.z64) order
+            let mut header = data.get(0..0x40)?.to_vec();
+            match header[0..4] {
+                [0x37, 0x80, 0x40, 0x12] => header.chunks_mut(2).for_each(|c| c.swap(0, 1)),
+                [0x40, 0x12, 0x37, 0x80] => header.chunks_mut(4).for_each(|c| c.reverse()),
+                _ => {}
+            }
+            ascii_title(&header[0x20..0x34])
+        }
+        "snes" => {
+            // Skip a copier header, then use whichever internal header has a
+            // valid checksum complement
+            let rom = if data.len() % 1024 == 512 {
+                &data[512..]
+            } else {
+                data
+            };
+            [0x7FC0, 0xFFC0].iter().find_map(|&offset| {
+                let header = rom.get(offset..offset + 0x20)?;
+                let complement = u16::from_le_bytes([header[0x1C], header[0x1D]]);
+                let checksum = u16::from_le_bytes([header[0x1E], header[0x1F]]);
+                if complement ^ checksum != 0xFFFF {
+                    return None;
+                }
+                ascii_title(&header[..21])
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Printable ASCII up to the first NUL, trimmed; None if empty or binary
+fn ascii_title(bytes: &[u8]) -> Option<String> {
+    let bytes = bytes.split(|&b| b == 0).next().unwrap_or(&[]);
+    // Game Boy color flags share the last title byte
+    let bytes = match bytes.last() {
+        Some(&flag) if flag >= 0x80 => &bytes[..bytes.len() - 1],
+        _ => bytes,
+    };
+    if !bytes.iter().all(|b| (0x20..0x7F).contains(b)) {
+        return None;
+    }
+    let title = String::from_utf8_lossy(bytes).trim().to_string();
+    (!title.is_empty()).then_some(title)
+}
+
+/// Size of the generated window icons
+pub const ICON_SIZE: u32 = 32;
+
+/// A simple RGBA window icon in the system's accent colour: a console-shaped
+/// block with a dark "screen"
+pub fn system_icon(system: &str) -> Vec<u8> {
+    let accent: [u8; 3] = match system {
+        "nes" => [200, 40, 40],
+        "gameboy" => [120, 140, 40],
+        "atari2600" => [180, 110, 40],
+        "pc" => [90, 100, 120],
+        "snes" => [110, 80, 170],
+        "n64" => [40, 110, 190],
+        _ => [80, 80, 80],
+    };
+    let size = ICON_SIZE as usize;
+    let mut rgba = vec![0u8; size * size * 4];
+    for y in 0..size {
+        for x in 0..size {
+            let body = (2..size - 2).contains(&x) && (4..size - 4).contains(&y);
+            let screen = (7..size - 7).contains(&x) && (8..size - 12).contains(&y);
+            let color = if screen {
+                [20, 24, 28, 255]
+            } else if body {
+                [accent[0], accent[1], accent[2], 255]
+            } else {
+                [0, 0, 0, 0]
+            };
+            rgba[(y * size + x) * 4..][..4].copy_from_slice(&color);
+        }
+    }
+    rgba
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_gameboy_header_title() {
+        let mut rom = vec![0u8; 0x150];
+        rom[0x134..0x13F].copy_from_slice(b"TETRIS DX  ");
+        rom[0x143] = 0x80; // CGB flag
+        assert_eq!(header_title("gameboy", &rom), Some("TETRIS DX".to_string()));
+    }
+
+    #[test]
+    fn test_n64_header_title_byte_orders() {
+        let mut z64 = vec![0u8; 0x40];
+        z64[0..4].copy_from_slice(&[0x80, 0x37, 0x12, 0x40]);
+        z64[0x20..0x2C].copy_from_slice(b"SUPER MARIO ");
+        assert_eq!(header_title("n64", &z64), Some("SUPER MARIO".to_string()));
+
+        let v64: Vec<u8> = z64.chunks(2).flat_map(|c| [c[1], c[0]]).collect();
+        assert_eq!(header_title("n64", &v64), Some("SUPER MARIO".to_string()));
+    }
+
+    #[test]
+    fn test_snes_header_title_needs_valid_checksum() {
+        let mut rom = vec![0u8; 0x10000];
+        rom[0x7FC0..0x7FC0 + 21].copy_from_slice(b"SUPER GAME           ");
+        assert_eq!(header_title("snes", &rom), None);
+
+        rom[0x7FDC..0x7FE0].copy_from_slice(&[0x34, 0x12, 0xCB, 0xED]);
+        assert_eq!(header_title("snes", &rom), Some("SUPER GAME".to_string()));
+    }
+
+    #[test]
+    fn test_game_title_falls_back_to_file_name() {
+        let path = Path::new("/roms/Pitfall (1982).a26");
+        assert_eq!(game_title("atari2600", &[0; 16], path), "Pitfall (1982)");
+    }
+
+    struct Recorder(Rc<RefCell<Vec<Option<String>>>>);
+
+    impl NowPlayingHook for Recorder {
+        fn now_playing_changed(&mut self, now_playing: Option<&NowPlaying>) {
+            self.0
+                .borrow_mut()
+                .push(now_playing.map(|np| np.title.clone()));
+        }
+    }
+
+    #[test]
+    fn test_hub_notifies_only_on_change() {
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let mut hub = NowPlayingHub::default();
+        hub.add_hook(Box::new(Recorder(seen.clone())));
+
+        assert!(hub.update(Some(("NES", "Game", Some("abc")))));
+        assert!(!hub.update(Some(("NES", "Game", Some("abc")))));
+        assert!(hub.update(None));
+        assert!(!hub.update(None));
+        assert_eq!(*seen.borrow(), vec![Some("Game".to_string()), None]);
+        assert_eq!(window_title(hub.current()), APP_TITLE);
+    }
+}
@@ -0,0 +1,126 @@
+//! Cartridge file watcher for `--watch`
+//!
+//! Polls the modification time and size of the mounted ROM so homebrew
+//! developers can reassemble and see the result without reloading by hand.
+//! A change is only reported once the file has stopped changing for one poll
+//! interval, so an assembler still writing the file is not read half-way.
+//! For a ROM inside an archive the archive file is watched.
+
+use crate::archive;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// How often the file's metadata is checked
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Modification time and size, used to detect rewrites
+type Stamp = (Option<SystemTime>, u64);
+
+pub struct RomWatcher {
+    path: PathBuf,
+    /// Stamp of the contents last handed out (or present when watching began)
+    loaded: Option<Stamp>,
+    /// New stamp seen on the previous poll, waiting to settle
+    pending: Option<Stamp>,
+    last_poll: Option<Instant>,
+}
+
+impl RomWatcher {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        Self {
+            loaded: stamp(&path),
+            path,
+            pending: None,
+            last_poll: None,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Follow a different file, e.g. after another ROM was opened
+    pub fn watch(&mut self, path: &Path) {
+        if self.path != path {
+            *self = Self::new(path);
+        }
+    }
+
+    /// New contents of the file if it changed and has settled
+    pub fn poll(&mut self) -> Option<Vec<u8>> {
+        let now = Instant::now();
+        if self
+            .last_poll
+            .is_some_and(|last| now.duration_since(last) < POLL_INTERVAL)
+        {
+            return None;
+        }
+        self.last_poll = Some(now);
+        self.check()
+    }
+
+    /// One poll step without the rate limit
+    fn check(&mut self) -> Option<Vec<u8>> {
+        // A missing file (mid-rename or deleted) keeps the current ROM
+        let current = stamp(&self.path)?;
+        if Some(current) == self.loaded {
+            self.pending = None;
+            return None;
+        }
+        if self.pending != Some(current) {
+            self.pending = Some(current);
+            return None;
+        }
+
+        self.pending = None;
+        let data = archive::read_rom(&self.path).ok()?;
+        self.loaded = Some(current);
+        Some(data)
+    }
+}
+
+fn stamp(path: &Path) -> Option<Stamp> {
+    let meta = fs::metadata(archive::file_on_disk(&path.to_string_lossy())).ok()?;
+    Some((meta.modified().ok(), meta.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reports_settled_changes_once() {
+        let path = std::env::temp_dir().join(format!("hemu_watch_{}.a26", std::process::id()));
+        fs::write(&path, [0xEA; 2048]).unwrap();
+        let mut watcher = RomWatcher::new(&path);
+        assert_eq!(watcher.check(), None);
+
+        // Size change is picked up after it settles for one poll
+        fs::write(&path, [0x4C; 4096]).unwrap();
+        assert_eq!(watcher.check(), None);
+        assert_eq!(watcher.check(), Some(vec![0x4C; 4096]));
+        assert_eq!(watcher.check(), None);
+
+        // Deleting the file is not a change
+        fs::remove_file(&path).unwrap();
+        assert_eq!(watcher.check(), None);
+        assert_eq!(watcher.check(), None);
+    }
+
+    #[test]
+    fn test_poll_is_rate_limited() {
+        let path = std::env::temp_dir().join(format!("hemu_watch_rate_{}.a26", std::process::id()));
+        fs::write(&path, [0; 2048]).unwrap();
+        let mut watcher = RomWatcher::new(&path);
+        fs::write(&path, [1; 4096]).unwrap();
+
+        // First poll sees the change, the second comes too soon to confirm it
+        assert_eq!(watcher.poll(), None);
+        assert_eq!(watcher.poll(), None);
+        std::thread::sleep(POLL_INTERVAL);
+        assert_eq!(watcher.poll(), Some(vec![1; 4096]));
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -3,17 +3,105 @@ use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Maximum number of save slots per game
 pub const MAX_SAVE_SLOTS: u8 = 5;
 
+/// Maximum thumbnail width in pixels (height is scaled to keep aspect ratio)
+const THUMBNAIL_MAX_WIDTH: usize = 80;
+
+/// Format identifier written into exported save state files
+const EXPORT_FORMAT: &str = "hemulator-save-state";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SaveSlot {
     pub data: String, // Base64 encoded save state data
     pub timestamp: u64,
     #[serde(default)]
     pub rom_hash: Option<String>, // Hash of the ROM this state was saved with
+    #[serde(default, flatten)]
+    pub meta: SaveMetadata,
+}
+
+/// Descriptive metadata stored alongside a save state (all optional so
+/// older save files still load)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SaveMetadata {
+    #[serde(default)]
+    pub system: Option<String>, // System name, e.g. "NES"
+    #[serde(default)]
+    pub rom_name: Option<String>, // ROM file name (without directory)
+    #[serde(default)]
+    pub play_time_secs: u64, // Emulated play time when the state was saved
+    #[serde(default)]
+    pub thumbnail: Option<Thumbnail>,
+}
+
+/// Small preview image of the frame at save time
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Thumbnail {
+    pub width: u32,
+    pub height: u32,
+    pub data: String, // Base64 encoded RGB bytes
+}
+
+impl Thumbnail {
+    /// Downscale an ARGB frame buffer (nearest neighbor) into a thumbnail
+    pub fn from_frame(pixels: &[u32], width: usize, height: usize) -> Option<Self> {
+        if width == 0 || height == 0 || pixels.len() < width * height {
+            return None;
+        }
+
+        let thumb_width = width.min(THUMBNAIL_MAX_WIDTH);
+        let thumb_height = (height * thumb_width / width).max(1);
+        let mut rgb = Vec::with_capacity(thumb_width * thumb_height * 3);
+        for y in 0..thumb_height {
+            let src_y = y * height / thumb_height;
+            for x in 0..thumb_width {
+                let src_x = x * width / thumb_width;
+                let pixel = pixels[src_y * width + src_x];
+                rgb.push((pixel >> 16) as u8);
+                rgb.push((pixel >> 8) as u8);
+                rgb.push(pixel as u8);
+            }
+        }
+
+        Some(Self {
+            width: thumb_width as u32,
+            height: thumb_height as u32,
+            data: BASE64.encode(rgb),
+        })
+    }
+
+    /// Decode the thumbnail into RGBA bytes (for display)
+    pub fn to_rgba(&self) -> Option<Vec<u8>> {
+        let rgb = BASE64.decode(&self.data).ok()?;
+        if rgb.len() != (self.width * self.height * 3) as usize {
+            return None;
+        }
+        Some(
+            rgb.chunks_exact(3)
+                .flat_map(|c| [c[0], c[1], c[2], 0xFF])
+                .collect(),
+        )
+    }
+}
+
+/// A save state found on disk, as listed by the save-state manager
+#[derive(Debug, Clone)]
+pub struct SaveStateEntry {
+    pub rom_hash: String,
+    pub slot: u8,
+    pub save: SaveSlot,
+}
+
+/// Portable single-state file used for export/import
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ExportedState {
+    format: String,
+    rom_hash: String,
+    save: SaveSlot,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -29,6 +117,12 @@ impl GameSaves {
         format!("{:x}", hasher.finalize())
     }
 
+    /// Check that a string is a ROM hash as produced by `rom_hash`
+    /// (64 lowercase hex digits)
+    fn is_rom_hash(hash: &str) -> bool {
+        hash.len() == 64 && hash.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+    }
+
     /// Get the saves directory path
     pub fn saves_dir() -> PathBuf {
         let mut path = std::env::current_exe()
@@ -83,11 +177,23 @@ impl GameSaves {
     }
 
     /// Save state data to a specific slot (1-MAX_SAVE_SLOTS)
+    #[cfg(test)]
     pub fn save_slot(
         &mut self,
         slot: u8,
         data: &[u8],
         rom_hash: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.save_slot_with_metadata(slot, data, rom_hash, SaveMetadata::default())
+    }
+
+    /// Save state data with descriptive metadata to a specific slot (1-MAX_SAVE_SLOTS)
+    pub fn save_slot_with_metadata(
+        &mut self,
+        slot: u8,
+        data: &[u8],
+        rom_hash: &str,
+        meta: SaveMetadata,
     ) -> Result<(), Box<dyn std::error::Error>> {
         if !(1..=MAX_SAVE_SLOTS).contains(&slot) {
             return Err(format!("Slot must be between 1 and {}", MAX_SAVE_SLOTS).into());
@@ -104,6 +210,7 @@ impl GameSaves {
                 data: encoded,
                 timestamp,
                 rom_hash: Some(rom_hash.to_string()), // Store ROM hash for verification
+                meta,
             },
         );
 
@@ -142,6 +249,104 @@ impl GameSaves {
         }
     }
 
+    /// Remove a slot and persist the change
+    pub fn delete_slot(
+        &mut self,
+        slot: u8,
+        rom_hash: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if self.slots.remove(&slot).is_none() {
+            return Err(format!("No save data in slot {}", slot).into());
+        }
+        self.save(rom_hash)
+    }
+
+    /// List every save state across all games, newest first
+    pub fn list_all() -> Vec<SaveStateEntry> {
+        Self::list_in(&Self::saves_dir())
+    }
+
+    /// List every save state stored under `saves_dir`, newest first
+    fn list_in(saves_dir: &Path) -> Vec<SaveStateEntry> {
+        let mut entries = Vec::new();
+        let Ok(dirs) = fs::read_dir(saves_dir) else {
+            return entries;
+        };
+
+        for dir in dirs.flatten() {
+            let rom_hash = dir.file_name().to_string_lossy().to_string();
+            let Ok(contents) = fs::read_to_string(dir.path().join("states.json")) else {
+                continue;
+            };
+            let Ok(saves) = serde_json::from_str::<GameSaves>(&contents) else {
+                continue;
+            };
+            for (slot, save) in saves.slots {
+                entries.push(SaveStateEntry {
+                    rom_hash: rom_hash.clone(),
+                    slot,
+                    save,
+                });
+            }
+        }
+
+        entries.sort_by(|a, b| {
+            b.save
+                .timestamp
+                .cmp(&a.save.timestamp)
+                .then(a.slot.cmp(&b.slot))
+        });
+        entries
+    }
+
+    /// Export a slot to a portable save state file
+    pub fn export_slot(
+        &self,
+        slot: u8,
+        rom_hash: &str,
+        path: &Path,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let save = self
+            .slots
+            .get(&slot)
+            .ok_or_else(|| format!("No save data in slot {}", slot))?;
+        let exported = ExportedState {
+            format: EXPORT_FORMAT.to_string(),
+            rom_hash: rom_hash.to_string(),
+            save: save.clone(),
+        };
+        fs::write(path, serde_json::to_string_pretty(&exported)?)?;
+        Ok(())
+    }
+
+    /// Import a portable save state file into the first free slot of the
+    /// game it was exported from. Returns the ROM hash and slot used.
+    pub fn import_file(path: &Path) -> Result<(String, u8), Box<dyn std::error::Error>> {
+        let exported: ExportedState = serde_json::from_str(&fs::read_to_string(path)?)?;
+        if exported.format != EXPORT_FORMAT {
+            return Err("Not a Hemulator save state file".into());
+        }
+        // The hash becomes a directory name under saves/, so only accept a
+        // real SHA-256 digest (never a path)
+        if !Self::is_rom_hash(&exported.rom_hash) {
+            return Err("Save state file has an invalid ROM hash".into());
+        }
+
+        let mut saves = Self::load(&exported.rom_hash);
+        let slot = saves.import(exported.save)?;
+        saves.save(&exported.rom_hash)?;
+        Ok((exported.rom_hash, slot))
+    }
+
+    /// Place a save into the first free slot
+    fn import(&mut self, save: SaveSlot) -> Result<u8, Box<dyn std::error::Error>> {
+        let slot = (1..=MAX_SAVE_SLOTS)
+            .find(|slot| !self.slots.contains_key(slot))
+            .ok_or("All save slots for this game are in use")?;
+        self.slots.insert(slot, save);
+        Ok(slot)
+    }
+
     /// Check if a slot has data
     #[cfg(test)]
     pub fn has_slot(&self, slot: u8) -> bool {
@@ -223,6 +428,130 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_legacy_slot_without_metadata() {
+        let json = r#"{"slots":{"1":{"data":"AAE=","timestamp":42}}}"#;
+        let saves: GameSaves = serde_json::from_str(json).unwrap();
+        let slot = &saves.slots[&1];
+        assert_eq!(slot.timestamp, 42);
+        assert!(slot.meta.system.is_none());
+        assert_eq!(slot.meta.play_time_secs, 0);
+        assert!(slot.meta.thumbnail.is_none());
+    }
+
+    #[test]
+    fn test_thumbnail_downscale() {
+        let pixels = vec![0xFF112233u32; 256 * 240];
+        let thumb = Thumbnail::from_frame(&pixels, 256, 240).unwrap();
+        assert_eq!(thumb.width, 80);
+        assert_eq!(thumb.height, 75);
+
+        let rgba = thumb.to_rgba().unwrap();
+        assert_eq!(rgba.len(), 80 * 75 * 4);
+        assert_eq!(&rgba[0..4], &[0x11, 0x22, 0x33, 0xFF]);
+
+        assert!(Thumbnail::from_frame(&[], 0, 0).is_none());
+    }
+
+    #[test]
+    fn test_list_import_and_delete() {
+        let dir = std::env::temp_dir().join("hemulator_test_list_saves");
+        let _ = fs::remove_dir_all(&dir);
+        let meta = SaveMetadata {
+            system: Some("NES".to_string()),
+            rom_name: Some("game.nes".to_string()),
+            play_time_secs: 90,
+            thumbnail: None,
+        };
+        let mut saves = GameSaves::default();
+        saves.slots.insert(
+            2,
+            SaveSlot {
+                data: BASE64.encode(b"state"),
+                timestamp: 100,
+                rom_hash: Some("abc".to_string()),
+                meta,
+            },
+        );
+        fs::create_dir_all(dir.join("abc")).unwrap();
+        fs::write(
+            dir.join("abc").join("states.json"),
+            serde_json::to_string(&saves).unwrap(),
+        )
+        .unwrap();
+
+        let entries = GameSaves::list_in(&dir);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].rom_hash, "abc");
+        assert_eq!(entries[0].slot, 2);
+        assert_eq!(entries[0].save.meta.system.as_deref(), Some("NES"));
+        assert_eq!(entries[0].save.meta.play_time_secs, 90);
+
+        // Importing fills the first free slot
+        let imported = saves.import(entries[0].save.clone()).unwrap();
+        assert_eq!(imported, 1);
+        for _ in 0..3 {
+            saves.import(entries[0].save.clone()).unwrap();
+        }
+        assert!(saves.import(entries[0].save.clone()).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_export_import_roundtrip() {
+        let mut saves = GameSaves::default();
+        let rom_hash = GameSaves::rom_hash(b"test export rom");
+        let rom_hash = rom_hash.as_str();
+        saves.save_slot(3, b"exported state", rom_hash).unwrap();
+
+        let path = std::env::temp_dir().join("hemulator_test_export.hstate");
+        saves.export_slot(3, rom_hash, &path).unwrap();
+        assert!(saves
+            .export_slot(4, rom_hash, &path.with_extension("x"))
+            .is_err());
+
+        // Delete the original slot, then import it back
+        saves.delete_slot(3, rom_hash).unwrap();
+        assert!(saves.delete_slot(3, rom_hash).is_err());
+        let (hash, slot) = GameSaves::import_file(&path).unwrap();
+        assert_eq!(hash, rom_hash);
+        assert_eq!(slot, 1);
+
+        let loaded = GameSaves::load(rom_hash);
+        assert_eq!(loaded.load_slot(1, rom_hash).unwrap(), b"exported state");
+
+        let _ = fs::remove_file(&path);
+        let _ = fs::remove_dir_all(GameSaves::saves_dir().join(rom_hash));
+    }
+
+    #[test]
+    fn test_import_rejects_invalid_rom_hash() {
+        let path = std::env::temp_dir().join("hemulator_test_bad_hash.hstate");
+        let exported = ExportedState {
+            format: EXPORT_FORMAT.to_string(),
+            rom_hash: "../../x".to_string(),
+            save: SaveSlot {
+                data: BASE64.encode(b"state"),
+                timestamp: 0,
+                rom_hash: None,
+                meta: SaveMetadata::default(),
+            },
+        };
+        fs::write(&path, serde_json::to_string(&exported).unwrap()).unwrap();
+
+        let target = GameSaves::game_save_path("../../x");
+        assert!(!target.exists());
+        assert!(GameSaves::import_file(&path).is_err());
+        assert!(!target.exists());
+        assert!(!target.parent().unwrap().exists());
+
+        assert!(GameSaves::is_rom_hash(&GameSaves::rom_hash(b"rom")));
+        assert!(!GameSaves::is_rom_hash(&"A".repeat(64)));
+
+        let _ = fs::remove_file(&path);
+    }
+
     #[test]
     fn test_rom_hash_verification() {
         let mut saves = GameSaves::default();
@@ -0,0 +1,358 @@
+//! Compatibility self-test
+//!
+//! Runs the built-in test ROMs for every system headlessly and reports
+//! pass/fail results. The report is written locally as JSON so users can
+//! attach it to bug reports; nothing is ever sent over the network and the
+//! report contains no user-identifying information (no paths, hostnames or
+//! ROM names beyond the bundled test ROMs).
+
+use emu_core::{types::Frame, System};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::path::Path;
+use std::time::Instant;
+
+/// Result of a single self-test case
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelfTestResult {
+    pub system: String,
+    pub test: String,
+    pub passed: bool,
+    pub detail: String,
+    pub duration_ms: u64,
+}
+
+/// Full self-test report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelfTestReport {
+    /// Hemulator version that produced the report
+    pub version: String,
+    /// Host operating system (e.g. "linux", "windows", "macos")
+    pub os: String,
+    /// Host CPU architecture (e.g. "x86_64", "aarch64")
+    pub arch: String,
+    /// Whether this is a debug or release build
+    pub profile: String,
+    /// UTC timestamp of the run (RFC 3339)
+    pub timestamp: String,
+    pub results: Vec<SelfTestResult>,
+}
+
+impl SelfTestReport {
+    /// True if every test case passed
+    pub fn all_passed(&self) -> bool {
+        self.results.iter().all(|r| r.passed)
+    }
+
+    /// Print the results as a table to stdout
+    pub fn print_table(&self) {
+        println!(
+            "Hemulator {} self-test ({}/{}, {})",
+            self.version, self.os, self.arch, self.profile
+        );
+        println!();
+        println!(
+            "{:<12} {:<24} {:<6} {:>8}  Detail",
+            "System", "Test", "Result", "Time"
+        );
+        println!("{}", "-".repeat(72));
+        for r in &self.results {
+            println!(
+                "{:<12} {:<24} {:<6} {:>6}ms  {}",
+                r.system,
+                r.test,
+                if r.passed { "PASS" } else { "FAIL" },
+                r.duration_ms,
+                r.detail
+            );
+        }
+        println!("{}", "-".repeat(72));
+        let passed = self.results.iter().filter(|r| r.passed).count();
+        println!("{}/{} tests passed", passed, self.results.len());
+    }
+
+    /// Write the report as pretty-printed JSON
+    pub fn write_json(&self, path: &Path) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize report: {}", e))?;
+        std::fs::write(path, json).map_err(|e| format!("Failed to write report: {}", e))
+    }
+}
+
+/// Run all built-in self-tests and build a report
+pub fn run_all() -> SelfTestReport {
+    let results = vec![
+        run_case(
+            "NES",
+            "test.nes",
+            emu_nes::NesSystem::default(),
+            "Cartridge",
+            include_bytes!("../../../../test_roms/nes/test.nes"),
+            10,
+            check_nes,
+        ),
+        run_case(
+            "Game Boy",
+            "test.gb",
+            emu_gb::GbSystem::new(),
+            "Cartridge",
+            include_bytes!("../../../../test_roms/gb/test.gb"),
+            10,
+            check_gb,
+        ),
+        run_case(
+            "Game Boy",
+            "test.gbc",
+            emu_gb::GbSystem::new(),
+            "Cartridge",
+            include_bytes!("../../../../test_roms/gbc/test.gbc"),
+            10,
+            |f| check_non_black(f, 160, 144, 2000),
+        ),
+        run_case(
+            "Atari 2600",
+            "test.bin",
+            emu_atari2600::Atari2600System::new(),
+            "Cartridge",
+            include_bytes!("../../../../test_roms/atari2600/test.bin"),
+            10,
+            |f| check_non_black(f, 160, 192, 100),
+        ),
+        run_case(
+            "SNES",
+            "test.sfc",
+            emu_snes::SnesSystem::new(),
+            "Cartridge",
+            include_bytes!("../../../../test_roms/snes/test.sfc"),
+            11,
+            check_snes,
+        ),
+        run_case(
+            "N64",
+            "test.z64",
+            emu_n64::N64System::new(),
+            "Cartridge",
+            include_bytes!("../../../../test_roms/n64/test.z64"),
+            6,
+            check_n64,
+        ),
+        run_case(
+            "PC",
+            "boot.bin",
+            emu_pc::PcSystem::new(),
+            "FloppyA",
+            &pc_boot_floppy(),
+            5,
+            |f| check_non_black(f, f.width, f.height, 1),
+        ),
+    ];
+
+    SelfTestReport {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        profile: if cfg!(debug_assertions) {
+            "debug".to_string()
+        } else {
+            "release".to_string()
+        },
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        results,
+    }
+}
+
+/// Mount a ROM, run a number of frames and check the final frame
+fn run_case<S, F>(
+    system: &str,
+    test: &str,
+    mut sys: S,
+    mount_point: &str,
+    data: &[u8],
+    frames: usize,
+    check: F,
+) -> SelfTestResult
+where
+    S: System,
+    S::Error: Display,
+    F: Fn(&Frame) -> Result<(), String>,
+{
+    let start = Instant::now();
+    let outcome = (|| {
+        sys.mount(mount_point, data)
+            .map_err(|e| format!("Mount failed: {}", e))?;
+        let mut frame = None;
+        for i in 0..frames {
+            frame = Some(
+                sys.step_frame()
+                    .map_err(|e| format!("Frame {} failed: {}", i, e))?,
+            );
+        }
+        let frame = frame.ok_or_else(|| "No frames were run".to_string())?;
+        check(&frame)
+    })();
+
+    SelfTestResult {
+        system: system.to_string(),
+        test: test.to_string(),
+        passed: outcome.is_ok(),
+        detail: match outcome {
+            Ok(()) => "OK".to_string(),
+            Err(e) => e,
+        },
+        duration_ms: start.elapsed().as_millis() as u64,
+    }
+}
+
+/// Build a 1.44MB floppy image with the PC test boot sector
+fn pc_boot_floppy() -> Vec<u8> {
+    let boot_sector = include_bytes!("../../../../test_roms/pc/basic_boot/boot.bin");
+    let mut floppy = vec![0; 1474560];
+    floppy[..boot_sector.len()].copy_from_slice(boot_sector);
+    floppy
+}
+
+fn check_size(frame: &Frame, width: u32, height: u32) -> Result<(), String> {
+    if frame.width != width || frame.height != height {
+        return Err(format!(
+            "Expected {}x{} frame, got {}x{}",
+            width, height, frame.width, frame.height
+        ));
+    }
+    if frame.pixels.len() != (width * height) as usize {
+        return Err(format!(
+            "Expected {} pixels, got {}",
+            width * height,
+            frame.pixels.len()
+        ));
+    }
+    Ok(())
+}
+
+fn pixel(frame: &Frame, x: u32, y: u32) -> u32 {
+    frame.pixels[(y * frame.width + x) as usize]
+}
+
+fn check_non_black(frame: &Frame, width: u32, height: u32, min: usize) -> Result<(), String> {
+    check_size(frame, width, height)?;
+    let non_black = frame
+        .pixels
+        .iter()
+        .filter(|&&p| p != 0xFF000000 && p != 0)
+        .count();
+    if non_black < min {
+        return Err(format!(
+            "Expected at least {} non-black pixels, got {}",
+            min, non_black
+        ));
+    }
+    Ok(())
+}
+
+/// The NES test ROM draws a two-color checkerboard covering about half the
+/// screen each
+fn check_nes(frame: &Frame) -> Result<(), String> {
+    check_size(frame, 256, 240)?;
+    let mut counts = std::collections::HashMap::new();
+    for &p in &frame.pixels {
+        *counts.entry(p).or_insert(0usize) += 1;
+    }
+    if counts.len() != 2 {
+        return Err(format!("Expected 2 colors, got {}", counts.len()));
+    }
+    let total = frame.pixels.len();
+    for count in counts.values() {
+        let percent = count * 100 / total;
+        if !(45..=55).contains(&percent) {
+            return Err(format!("Unbalanced checkerboard ({}%)", percent));
+        }
+    }
+    Ok(())
+}
+
+/// The GB test ROM draws alternating white and dark gray tiles
+fn check_gb(frame: &Frame) -> Result<(), String> {
+    check_size(frame, 160, 144)?;
+    for x in 0..16 {
+        let expected = if x < 8 { 0xFFFFFFFF } else { 0xFF555555 };
+        let actual = pixel(frame, x, 0);
+        if actual != expected {
+            return Err(format!(
+                "Pixel ({},0) is 0x{:08X}, expected 0x{:08X}",
+                x, actual, expected
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// The SNES test ROM draws a checkerboard of 8x8 tiles
+fn check_snes(frame: &Frame) -> Result<(), String> {
+    check_size(frame, 256, 224)?;
+    let a = pixel(frame, 4, 4);
+    let b = pixel(frame, 12, 4);
+    if a == b {
+        return Err(format!("Adjacent tiles have the same color 0x{:08X}", a));
+    }
+    Ok(())
+}
+
+/// The N64 test ROM draws a red and a green rectangle on a black background
+fn check_n64(frame: &Frame) -> Result<(), String> {
+    check_size(frame, 320, 240)?;
+    for (x, y, expected) in [(100, 100, 0xFFFF0000), (185, 115, 0xFF00FF00), (0, 0, 0)] {
+        let actual = pixel(frame, x, y);
+        if actual != expected {
+            return Err(format!(
+                "Pixel ({},{}) is 0x{:08X}, expected 0x{:08X}",
+                x, y, actual, expected
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_self_test_all_pass() {
+        let report = run_all();
+        for r in &report.results {
+            assert!(r.passed, "{} {} failed: {}", r.system, r.test, r.detail);
+        }
+        assert!(report.all_passed());
+    }
+
+    #[test]
+    fn test_report_serialization() {
+        let report = SelfTestReport {
+            version: "0.0.0".to_string(),
+            os: "linux".to_string(),
+            arch: "x86_64".to_string(),
+            profile: "debug".to_string(),
+            timestamp: "2024-01-01T00:00:00+00:00".to_string(),
+            results: vec![SelfTestResult {
+                system: "NES".to_string(),
+                test: "test.nes".to_string(),
+                passed: false,
+                detail: "Expected 2 colors, got 1".to_string(),
+                duration_ms: 12,
+            }],
+        };
+        assert!(!report.all_passed());
+
+        let json = serde_json::to_string(&report).unwrap();
+        let parsed: SelfTestReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.results.len(), 1);
+        assert_eq!(parsed.results[0].system, "NES");
+        assert!(!parsed.results[0].passed);
+    }
+
+    #[test]
+    fn test_check_size_mismatch() {
+        let frame = Frame::new(10, 10);
+        assert!(check_size(&frame, 10, 10).is_ok());
+        assert!(check_size(&frame, 20, 10).is_err());
+    }
+}
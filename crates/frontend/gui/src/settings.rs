@@ -1,8 +1,8 @@
 use crate::display_filter::DisplayFilter;
-use crate::input::ControllerProfile;
+use crate::input::{ControllerProfile, PlayerGamepad};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::PathBuf;
 
@@ -28,8 +28,30 @@ impl ScalingMode {
     }
 }
 
+/// How the main loop paces emulated frames against the host
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum PacingMode {
+    /// Present on host vsync; emulated frames follow the wall clock
+    #[default]
+    Vsync,
+    /// Vsync off; emulate just enough frames to keep the audio queue filled
+    AudioClock,
+    /// Vsync off; one tick per emulated frame at the system's rate (60/50 Hz)
+    Timer,
+}
+
+impl PacingMode {
+    pub fn name(&self) -> &str {
+        match self {
+            PacingMode::Vsync => "Host VSync",
+            PacingMode::AudioClock => "Audio Clock",
+            PacingMode::Timer => "Fixed Timer",
+        }
+    }
+}
+
 /// Standard controller button mapping (for NES, SNES, GB, etc.)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct KeyMapping {
     pub a: String,
     pub b: String,
@@ -47,6 +69,12 @@ pub struct KeyMapping {
     pub down: String,
     pub left: String,
     pub right: String,
+    /// Autofire versions of A and B, pressed and released at
+    /// `InputConfig::turbo_frames_per_toggle` while held
+    #[serde(default = "default_empty_string")]
+    pub turbo_a: String,
+    #[serde(default = "default_empty_string")]
+    pub turbo_b: String,
 }
 
 fn default_empty_string() -> String {
@@ -68,6 +96,8 @@ impl Default for KeyMapping {
             down: "Down".to_string(),
             left: "Left".to_string(),
             right: "Right".to_string(),
+            turbo_a: "A".to_string(),
+            turbo_b: "S".to_string(),
         }
     }
 }
@@ -88,6 +118,8 @@ impl KeyMapping {
             down: "K".to_string(),
             left: "J".to_string(),
             right: "L".to_string(),
+            turbo_a: String::new(),
+            turbo_b: String::new(),
         }
     }
 
@@ -106,9 +138,31 @@ impl KeyMapping {
             down: String::new(),
             left: String::new(),
             right: String::new(),
+            turbo_a: String::new(),
+            turbo_b: String::new(),
         }
     }
 
+    /// Host key name of every button, with its label, for editing
+    pub fn buttons_mut(&mut self) -> [(&'static str, &mut String); 14] {
+        [
+            ("A", &mut self.a),
+            ("B", &mut self.b),
+            ("X", &mut self.x),
+            ("Y", &mut self.y),
+            ("L", &mut self.l),
+            ("R", &mut self.r),
+            ("Select", &mut self.select),
+            ("Start", &mut self.start),
+            ("Up", &mut self.up),
+            ("Down", &mut self.down),
+            ("Left", &mut self.left),
+            ("Right", &mut self.right),
+            ("Turbo A", &mut self.turbo_a),
+            ("Turbo B", &mut self.turbo_b),
+        ]
+    }
+
     /// Default mapping for Player 4 (unmapped by default, but structure available)
     pub fn player4_default() -> Self {
         Self {
@@ -124,6 +178,49 @@ impl KeyMapping {
             down: String::new(),
             left: String::new(),
             right: String::new(),
+            turbo_a: String::new(),
+            turbo_b: String::new(),
+        }
+    }
+}
+
+/// Keyboard mappings of players 1-4, saved as a named mapping profile
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerMappings {
+    #[serde(default)]
+    pub player1: KeyMapping,
+    #[serde(default = "KeyMapping::player2_default")]
+    pub player2: KeyMapping,
+    #[serde(default = "KeyMapping::player3_default")]
+    pub player3: KeyMapping,
+    #[serde(default = "KeyMapping::player4_default")]
+    pub player4: KeyMapping,
+}
+
+impl PlayerMappings {
+    /// Mappings in player order
+    pub fn players(&self) -> [&KeyMapping; 4] {
+        [&self.player1, &self.player2, &self.player3, &self.player4]
+    }
+
+    /// Mutable mappings in player order
+    pub fn players_mut(&mut self) -> [&mut KeyMapping; 4] {
+        [
+            &mut self.player1,
+            &mut self.player2,
+            &mut self.player3,
+            &mut self.player4,
+        ]
+    }
+}
+
+impl Default for PlayerMappings {
+    fn default() -> Self {
+        Self {
+            player1: KeyMapping::default(),
+            player2: KeyMapping::player2_default(),
+            player3: KeyMapping::player3_default(),
+            player4: KeyMapping::player4_default(),
         }
     }
 }
@@ -133,11 +230,11 @@ impl KeyMapping {
 pub struct InputConfig {
     #[serde(default)]
     pub player1: KeyMapping,
-    #[serde(default)]
+    #[serde(default = "KeyMapping::player2_default")]
     pub player2: KeyMapping,
-    #[serde(default)]
+    #[serde(default = "KeyMapping::player3_default")]
     pub player3: KeyMapping,
-    #[serde(default)]
+    #[serde(default = "KeyMapping::player4_default")]
     pub player4: KeyMapping,
     /// Host modifier key for switching to host mode (function keys) in PC emulation
     /// Default: RightCtrl
@@ -155,6 +252,30 @@ pub struct InputConfig {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub profiles: Option<Vec<ControllerProfile>>,
 
+    /// Gamepad device, stick threshold and optional button mapping of
+    /// players 1-4
+    #[serde(default)]
+    pub player_gamepads: [PlayerGamepad; 4],
+
+    /// Named keyboard mapping profiles, used instead of `player1`-`player4`
+    /// by the systems and games that select them
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub mapping_profiles: BTreeMap<String, PlayerMappings>,
+
+    /// Mapping profile of each system ("nes", "gameboy", "snes",
+    /// "atari2600", "n64"), unless the game has its own
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub system_mappings: BTreeMap<String, String>,
+
+    /// Mapping profile of individual games, by ROM hash
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub game_mappings: BTreeMap<String, String>,
+
+    /// Frames each turbo button stays pressed, then released (default: 2,
+    /// 15 presses a second at 60 fps)
+    #[serde(default = "default_turbo_frames_per_toggle")]
+    pub turbo_frames_per_toggle: u32,
+
     /// Mouse sensitivity multiplier (default: 1.0)
     #[serde(default = "default_mouse_sensitivity")]
     pub mouse_sensitivity: f32,
@@ -162,16 +283,29 @@ pub struct InputConfig {
     /// Enable mouse input for systems that support it
     #[serde(default)]
     pub mouse_enabled: bool,
+
+    /// Key that captures and releases the mouse for the PC mouse driver
+    /// Default: F12
+    #[serde(default = "default_mouse_capture_key")]
+    pub mouse_capture_key: String,
 }
 
 fn default_host_modifier() -> String {
     "RightCtrl".to_string()
 }
 
+fn default_turbo_frames_per_toggle() -> u32 {
+    2
+}
+
 fn default_mouse_sensitivity() -> f32 {
     1.0
 }
 
+fn default_mouse_capture_key() -> String {
+    "F12".to_string()
+}
+
 impl Default for InputConfig {
     fn default() -> Self {
         Self {
@@ -181,8 +315,47 @@ impl Default for InputConfig {
             player4: KeyMapping::player4_default(),
             host_modifier: default_host_modifier(),
             profiles: None,
+            player_gamepads: Default::default(),
+            mapping_profiles: BTreeMap::new(),
+            system_mappings: BTreeMap::new(),
+            game_mappings: BTreeMap::new(),
+            turbo_frames_per_toggle: default_turbo_frames_per_toggle(),
             mouse_sensitivity: default_mouse_sensitivity(),
             mouse_enabled: false,
+            mouse_capture_key: default_mouse_capture_key(),
+        }
+    }
+}
+
+impl InputConfig {
+    /// Name of the mapping profile used by a game: its own, else its
+    /// system's (None means the global `player1`-`player4` mappings)
+    ///
+    /// Profiles that no longer exist are skipped.
+    pub fn mapping_profile_name(&self, system: &str, rom_hash: Option<&str>) -> Option<&str> {
+        let game = rom_hash.and_then(|hash| self.game_mappings.get(hash));
+        [game, self.system_mappings.get(system)]
+            .into_iter()
+            .flatten()
+            .find(|name| self.mapping_profiles.contains_key(*name))
+            .map(String::as_str)
+    }
+
+    /// Keyboard mappings of players 1-4 for a game
+    pub fn key_mappings(&self, system: &str, rom_hash: Option<&str>) -> [&KeyMapping; 4] {
+        match self.mapping_profile_name(system, rom_hash) {
+            Some(name) => self.mapping_profiles[name].players(),
+            None => [&self.player1, &self.player2, &self.player3, &self.player4],
+        }
+    }
+
+    /// The global `player1`-`player4` mappings as a profile
+    pub fn global_mappings(&self) -> PlayerMappings {
+        PlayerMappings {
+            player1: self.player1.clone(),
+            player2: self.player2.clone(),
+            player3: self.player3.clone(),
+            player4: self.player4.clone(),
         }
     }
 }
@@ -219,6 +392,18 @@ pub struct Settings {
     pub fullscreen_with_gui: bool, // Fullscreen with GUI overlay
     #[serde(default = "default_log_rate_limit")]
     pub log_rate_limit: usize, // Maximum logs per second per category (default: 60)
+    #[serde(default)]
+    pub show_input_display: bool, // Draw controller input overlay on the frame
+    #[serde(default = "default_master_volume")]
+    pub master_volume: f32, // Output volume multiplier applied after per-system gain (1.0 = 100%)
+    #[serde(default)]
+    pub pacing_mode: PacingMode, // Frame pacing strategy
+    #[serde(default = "default_rewind_interval_frames")]
+    pub rewind_interval_frames: u32, // Frames between rewind snapshots
+    #[serde(default = "default_rewind_memory_mb")]
+    pub rewind_memory_mb: usize, // Memory cap for rewind snapshots (0 disables rewind)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub now_playing_file: Option<String>, // JSON file kept up to date with the running game
     #[serde(default, flatten, skip_serializing_if = "HashMap::is_empty")]
     pub extra: HashMap<String, Value>,
 }
@@ -239,6 +424,18 @@ fn default_emulation_speed() -> f64 {
     1.0 // Normal speed
 }
 
+fn default_master_volume() -> f32 {
+    1.0 // 100%
+}
+
+fn default_rewind_interval_frames() -> u32 {
+    5 // 12 snapshots per second at 60 Hz
+}
+
+fn default_rewind_memory_mb() -> usize {
+    64
+}
+
 fn default_video_backend() -> String {
     "software".to_string()
 }
@@ -259,6 +456,12 @@ impl Default for Settings {
             fullscreen: false,
             fullscreen_with_gui: false,
             log_rate_limit: 60, // Default: 60 logs per second
+            show_input_display: false,
+            master_volume: default_master_volume(),
+            pacing_mode: PacingMode::default(),
+            rewind_interval_frames: default_rewind_interval_frames(),
+            rewind_memory_mb: default_rewind_memory_mb(),
+            now_playing_file: None,
             extra: HashMap::new(),
         }
     }
@@ -348,6 +551,9 @@ mod tests {
         assert_eq!(settings.window_width, 512);
         assert_eq!(settings.window_height, 480);
         assert_eq!(settings.last_rom_path, None);
+        assert_eq!(settings.rewind_interval_frames, 5);
+        assert_eq!(settings.rewind_memory_mb, 64);
+        assert_eq!(settings.now_playing_file, None);
     }
 
     #[test]
@@ -435,6 +641,98 @@ mod tests {
         assert!(settings.input.player3.a.is_empty());
         assert!(settings.input.player4.a.is_empty());
     }
+
+    #[test]
+    fn test_player_defaults_do_not_collide() {
+        fn keys(m: &KeyMapping) -> Vec<&str> {
+            [
+                &m.a, &m.b, &m.x, &m.y, &m.l, &m.r, &m.select, &m.start, &m.up, &m.down, &m.left,
+                &m.right, &m.turbo_a, &m.turbo_b,
+            ]
+            .into_iter()
+            .map(|k| k.as_str())
+            .filter(|k| !k.is_empty())
+            .collect()
+        }
+        let input = InputConfig::default();
+        let player1 = keys(&input.player1);
+        assert!(keys(&input.player2).iter().all(|k| !player1.contains(k)));
+
+        // A config that only lists player 1 keeps the separate player 2 keys
+        let json = r#"{"player1": {"a": "Z", "b": "X", "select": "LeftShift",
+            "start": "Enter", "up": "Up", "down": "Down", "left": "Left", "right": "Right"}}"#;
+        let input: InputConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(input.player2.a, "U");
+        assert!(input.player3.a.is_empty());
+        assert!(input.player1.turbo_a.is_empty());
+        assert_eq!(input.turbo_frames_per_toggle, 2);
+    }
+
+    #[test]
+    fn test_player_gamepads_persist() {
+        use crate::input::GamepadAssignment;
+
+        let mut input = InputConfig::default();
+        assert_eq!(input.player_gamepads[1].device, GamepadAssignment::Auto);
+        input.player_gamepads[0].device = GamepadAssignment::Named("Xbox Controller".to_string());
+        input.player_gamepads[1].device = GamepadAssignment::Disabled;
+        input.player_gamepads[1].axis_threshold = 0.25;
+        input.player_gamepads[1].profile = Some(ControllerProfile::gamepad_default());
+
+        let json = serde_json::to_string(&input).unwrap();
+        let parsed: InputConfig = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.player_gamepads, input.player_gamepads);
+
+        // Partial entries fill in the defaults
+        let json = r#"{"player_gamepads": [{"device": "Disabled"}, {}, {}, {}]}"#;
+        let parsed: InputConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            parsed.player_gamepads[0].device,
+            GamepadAssignment::Disabled
+        );
+        assert_eq!(parsed.player_gamepads[0].axis_threshold, 0.5);
+        assert!(parsed.player_gamepads[1].profile.is_none());
+    }
+
+    #[test]
+    fn test_mapping_profile_lookup() {
+        let mut input = InputConfig::default();
+        let mut snes = PlayerMappings::default();
+        snes.player1.a = "S".to_string();
+        let mut puzzle = PlayerMappings::default();
+        puzzle.player1.a = "Space".to_string();
+        input.mapping_profiles.insert("SNES pad".to_string(), snes);
+        input.mapping_profiles.insert("Puzzle".to_string(), puzzle);
+        input
+            .system_mappings
+            .insert("snes".to_string(), "SNES pad".to_string());
+        input
+            .game_mappings
+            .insert("abc123".to_string(), "Puzzle".to_string());
+
+        // Systems without a profile use the global mappings
+        assert_eq!(input.mapping_profile_name("nes", None), None);
+        assert_eq!(input.key_mappings("nes", None)[0].a, "Z");
+
+        // Game override, then system default
+        assert_eq!(input.key_mappings("snes", Some("abc123"))[0].a, "Space");
+        assert_eq!(input.key_mappings("snes", Some("other"))[0].a, "S");
+        assert_eq!(input.key_mappings("snes", None)[1].a, "U");
+        assert_eq!(input.key_mappings("nes", Some("abc123"))[0].a, "Space");
+
+        // A deleted profile falls back to the next level
+        input.mapping_profiles.remove("Puzzle");
+        assert_eq!(
+            input.mapping_profile_name("snes", Some("abc123")),
+            Some("SNES pad")
+        );
+
+        let json = serde_json::to_string(&input).unwrap();
+        let parsed: InputConfig = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.mapping_profiles, input.mapping_profiles);
+        assert_eq!(parsed.system_mappings, input.system_mappings);
+        assert_eq!(parsed.game_mappings, input.game_mappings);
+    }
 }
 
 #[test]
@@ -541,6 +839,15 @@ fn test_settings_empty_json_deserialize() {
                 settings.input.host_modifier, "RightCtrl",
                 "host_modifier should default to 'RightCtrl'"
             );
+            assert_eq!(
+                settings.master_volume, 1.0,
+                "master_volume should default to 100%"
+            );
+            assert_eq!(
+                settings.pacing_mode,
+                PacingMode::Vsync,
+                "pacing_mode should default to host vsync"
+            );
             println!("Test passed: Empty JSON uses all defaults correctly");
         }
         Err(e) => {
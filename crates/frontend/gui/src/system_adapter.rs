@@ -56,6 +56,7 @@ impl SystemDebugInfo {
         debug_info.add_field("Banking Scheme".to_string(), info.banking_scheme.clone());
         debug_info.add_field("Current Bank".to_string(), format!("{}", info.current_bank));
         debug_info.add_field("Scanline".to_string(), format!("{}", info.scanline));
+        debug_info.add_field("TV Type".to_string(), info.tv_type.clone());
         debug_info
     }
 
@@ -82,6 +83,10 @@ impl SystemDebugInfo {
         let mut debug_info = Self::new("SNES".to_string());
         debug_info.add_field("ROM Size".to_string(), format!("{} bytes", info.rom_size));
         debug_info.add_field("SMC Header".to_string(), format!("{}", info.has_smc_header));
+        debug_info.add_field(
+            "Region".to_string(),
+            format!("${:02X} ({:?})", info.region, info.timing),
+        );
         debug_info.add_field(
             "PBR:PC".to_string(),
             format!("{:02X}:{:04X}", info.pbr, info.pc),
@@ -108,6 +113,7 @@ impl SystemDebugInfo {
             "Framebuffer".to_string(),
             info.framebuffer_resolution.clone(),
         );
+        debug_info.add_field("RDP Renderer".to_string(), info.rdp_renderer.clone());
         debug_info
     }
 }
@@ -7,6 +7,11 @@ use sdl2::joystick::Joystick;
 use std::any::Any;
 use std::collections::{HashMap, HashSet};
 use std::error::Error;
+use std::time::{Duration, Instant};
+
+/// How often `handle_events_with_pump` runs its pump while the OS holds the
+/// event loop
+const MODAL_PUMP_INTERVAL: Duration = Duration::from_millis(10);
 
 pub struct Sdl2EguiBackend {
     #[allow(dead_code)]
@@ -17,6 +22,7 @@ pub struct Sdl2EguiBackend {
     egui_state: EguiStateHandler,
     egui_ctx: egui::Context,
     event_pump: sdl2::EventPump,
+    event_subsystem: sdl2::EventSubsystem,
     _game_controller_subsystem: sdl2::GameControllerSubsystem,
     _joystick_subsystem: sdl2::JoystickSubsystem,
 
@@ -41,6 +47,14 @@ pub struct Sdl2EguiBackend {
     joystick_axes: HashMap<u32, HashMap<u8, i16>>,
     /// Joystick hat values (indexed by instance ID, then hat ID, value is bitmask: 1=up, 2=right, 4=down, 8=left)
     joystick_hats: HashMap<u32, HashMap<u8, u8>>,
+
+    // Mouse capture state
+    /// Mouse is captured for the emulated system (relative mode)
+    mouse_captured: bool,
+    /// Relative motion this frame while captured
+    mouse_motion: (i32, i32),
+    /// Buttons held while captured (bit 0 = left, bit 1 = right, bit 2 = middle)
+    mouse_buttons: u8,
 }
 
 impl Sdl2EguiBackend {
@@ -73,6 +87,9 @@ impl Sdl2EguiBackend {
         let egui_ctx = egui::Context::default();
 
         let event_pump = sdl_context.event_pump()?;
+        let event_subsystem = sdl_context.event()?;
+        #[cfg(windows)]
+        modal_timer::register(&event_subsystem)?;
 
         // Initialize gamepad and joystick subsystems
         let game_controller_subsystem = sdl_context.game_controller()?;
@@ -142,6 +159,7 @@ impl Sdl2EguiBackend {
             egui_state,
             egui_ctx,
             event_pump,
+            event_subsystem,
             _game_controller_subsystem: game_controller_subsystem,
             _joystick_subsystem: joystick_subsystem,
             keys_down: std::collections::HashSet::new(),
@@ -155,6 +173,9 @@ impl Sdl2EguiBackend {
             joystick_buttons,
             joystick_axes,
             joystick_hats,
+            mouse_captured: false,
+            mouse_motion: (0, 0),
+            mouse_buttons: 0,
         })
     }
 
@@ -207,28 +228,86 @@ impl Sdl2EguiBackend {
         self.window.gl_swap_window();
     }
 
+    /// Handle SDL2 events like `handle_events`, running `pump` every few
+    /// milliseconds for as long as the OS holds the event loop
+    ///
+    /// While a window is dragged or resized, Windows runs its own message
+    /// loop until the mouse button is released (macOS does the same for live
+    /// resizes), so polling does not return. The window events sent meanwhile
+    /// still reach SDL's event watchers, and on Windows a timer sends ticks
+    /// while the mouse holds still; `pump` is called from there. Polling that
+    /// returns promptly never calls it.
+    pub fn handle_events_with_pump(&mut self, pump: &mut dyn FnMut()) -> bool {
+        // Watchers are also called from other threads (device hotplug);
+        // only the thread that is stuck in the modal loop may pump
+        let thread = std::thread::current().id();
+        let mut last_pump = Instant::now();
+        let _watch = self.event_subsystem.add_event_watch(|_event| {
+            if std::thread::current().id() == thread && last_pump.elapsed() >= MODAL_PUMP_INTERVAL {
+                // Set first: the pump may send events of its own
+                last_pump = Instant::now();
+                pump();
+            }
+        });
+        #[cfg(windows)]
+        let _timer = modal_timer::ModalTimer::start(MODAL_PUMP_INTERVAL);
+        self.handle_events()
+    }
+
     /// Handle SDL2 events and update egui input
     /// Returns false if the window should close
     pub fn handle_events(&mut self) -> bool {
         self.keys_pressed.clear();
         self.sdl2_scancodes_pressed.clear();
         self.sdl2_scancodes_released.clear();
+        self.mouse_motion = (0, 0);
 
         // Collect events first to avoid borrow checker issues
         let events: Vec<_> = self.event_pump.poll_iter().collect();
 
         for event in events {
-            // Process event with egui state handler
-            self.egui_state
-                .process_input(&self.window, event.clone(), &mut self.painter);
+            // Process event with egui state handler, unless the emulated
+            // system has the mouse (releases still go through so egui does
+            // not see a button stuck down)
+            let captured_event = self.mouse_captured
+                && matches!(
+                    event,
+                    sdl2::event::Event::MouseMotion { .. }
+                        | sdl2::event::Event::MouseButtonDown { .. }
+                        | sdl2::event::Event::MouseWheel { .. }
+                );
+            if !captured_event {
+                self.egui_state
+                    .process_input(&self.window, event.clone(), &mut self.painter);
+            }
 
             // Also process for emulator controls
             match event {
                 sdl2::event::Event::Quit { .. } => {
                     return false;
                 }
+                sdl2::event::Event::MouseMotion { xrel, yrel, .. } if self.mouse_captured => {
+                    self.mouse_motion.0 += xrel;
+                    self.mouse_motion.1 += yrel;
+                }
+                sdl2::event::Event::MouseButtonDown { mouse_btn, .. } if self.mouse_captured => {
+                    self.mouse_buttons |= mouse_button_bit(mouse_btn);
+                }
+                sdl2::event::Event::MouseButtonUp { mouse_btn, .. } => {
+                    self.mouse_buttons &= !mouse_button_bit(mouse_btn);
+                }
+                // Give the mouse back when switching to another window
+                sdl2::event::Event::Window {
+                    win_event: sdl2::event::WindowEvent::FocusLost,
+                    ..
+                } => {
+                    self.set_mouse_captured(false);
+                }
                 sdl2::event::Event::KeyDown {
-                    keycode, scancode, ..
+                    keycode,
+                    scancode,
+                    repeat,
+                    ..
                 } => {
                     if let Some(keycode) = keycode {
                         if let Some(key) = sdl_keycode_to_key(keycode) {
@@ -236,7 +315,8 @@ impl Sdl2EguiBackend {
                             self.keys_pressed.insert(key);
                         }
                     }
-                    if let Some(scancode) = scancode {
+                    // The emulated PC keyboard repeats held keys itself
+                    if let (Some(scancode), false) = (scancode, repeat) {
                         self.sdl2_scancodes_pressed.push(scancode);
                     }
                 }
@@ -395,6 +475,43 @@ impl Sdl2EguiBackend {
         &self.sdl2_scancodes_released
     }
 
+    /// Capture the mouse for the emulated system, or release it
+    ///
+    /// While captured the cursor is hidden and held in the window, and
+    /// movement and buttons go to the system instead of the GUI.
+    pub fn set_mouse_captured(&mut self, captured: bool) {
+        self.sdl_context.mouse().set_relative_mouse_mode(captured);
+        self.mouse_captured = captured;
+        self.mouse_motion = (0, 0);
+        self.mouse_buttons = 0;
+    }
+
+    /// True while the emulated system has the mouse
+    pub fn is_mouse_captured(&self) -> bool {
+        self.mouse_captured
+    }
+
+    /// Relative mouse motion this frame while captured
+    pub fn mouse_motion(&self) -> (i32, i32) {
+        self.mouse_motion
+    }
+
+    /// Mouse buttons held while captured (bit 0 = left, bit 1 = right,
+    /// bit 2 = middle)
+    pub fn mouse_buttons(&self) -> u8 {
+        self.mouse_buttons
+    }
+
+    /// Enable or disable waiting for vsync on buffer swaps
+    pub fn set_vsync(&mut self, enabled: bool) -> Result<(), String> {
+        let interval = if enabled {
+            sdl2::video::SwapInterval::VSync
+        } else {
+            sdl2::video::SwapInterval::Immediate
+        };
+        self.video_subsystem().gl_set_swap_interval(interval)
+    }
+
     /// Toggle fullscreen mode
     pub fn set_fullscreen(&mut self, fullscreen: bool) -> Result<(), Box<dyn Error>> {
         if fullscreen {
@@ -412,6 +529,25 @@ impl Sdl2EguiBackend {
         self.window.fullscreen_state() != sdl2::video::FullscreenType::Off
     }
 
+    /// Set the window title
+    pub fn set_title(&mut self, title: &str) -> Result<(), Box<dyn Error>> {
+        self.window.set_title(title)?;
+        Ok(())
+    }
+
+    /// Set the window icon from RGBA pixels
+    pub fn set_icon(&mut self, rgba: &mut [u8], width: u32, height: u32) -> Result<(), String> {
+        let surface = sdl2::surface::Surface::from_data(
+            rgba,
+            width,
+            height,
+            width * 4,
+            sdl2::pixels::PixelFormatEnum::RGBA32,
+        )?;
+        self.window.set_icon(surface);
+        Ok(())
+    }
+
     /// Check if a gamepad button is pressed
     /// instance_id: SDL2 controller instance ID (usually 0 for first controller)
     /// button: SDL2 GameController button ID
@@ -433,11 +569,37 @@ impl Sdl2EguiBackend {
             .unwrap_or(0)
     }
 
+    /// Buttons held on any connected gamepad (SDL2 GameController button IDs)
+    pub fn gamepad_buttons_down(&self) -> HashSet<u8> {
+        self.gamepad_buttons.values().flatten().copied().collect()
+    }
+
+    /// Axis value furthest from center across all connected gamepads
+    pub fn any_gamepad_axis(&self, axis: u8) -> i16 {
+        self.gamepad_axes
+            .values()
+            .filter_map(|axes| axes.get(&axis).copied())
+            .max_by_key(|value| value.unsigned_abs())
+            .unwrap_or(0)
+    }
+
     /// Get number of connected gamepads
     pub fn num_gamepads(&self) -> usize {
         self.game_controllers.len()
     }
 
+    /// Instance IDs and names of the connected gamepads, in the order they
+    /// were connected
+    pub fn gamepads(&self) -> Vec<(u32, String)> {
+        let mut gamepads: Vec<(u32, String)> = self
+            .game_controllers
+            .iter()
+            .map(|(&id, controller)| (id, controller.name()))
+            .collect();
+        gamepads.sort_unstable_by_key(|&(id, _)| id);
+        gamepads
+    }
+
     /// Check if a joystick button is pressed
     pub fn is_joystick_button_down(&self, instance_id: u32, button: u8) -> bool {
         self.joystick_buttons
@@ -513,6 +675,16 @@ impl WindowBackend for Sdl2EguiBackend {
     }
 }
 
+/// Button bit of an SDL mouse button (INT 33h order)
+fn mouse_button_bit(button: sdl2::mouse::MouseButton) -> u8 {
+    match button {
+        sdl2::mouse::MouseButton::Left => 0x01,
+        sdl2::mouse::MouseButton::Right => 0x02,
+        sdl2::mouse::MouseButton::Middle => 0x04,
+        _ => 0,
+    }
+}
+
 // Helper function to convert SDL2 keycode to our Key enum
 fn sdl_keycode_to_key(keycode: sdl2::keyboard::Keycode) -> Option<Key> {
     use sdl2::keyboard::Keycode;
@@ -583,3 +755,69 @@ fn sdl_keycode_to_key(keycode: sdl2::keyboard::Keycode) -> Option<Key> {
         _ => None,
     }
 }
+
+/// Timer that keeps the event watchers of `handle_events_with_pump` running
+/// in a window move or resize loop
+///
+/// Windows sends no window messages while the mouse holds still in those
+/// loops. A thread timer's callback is still run by the loop's message
+/// dispatch, and it pushes a tick event, which SDL hands to the watchers.
+/// Other platforms keep sending window events during live resizes.
+#[cfg(windows)]
+mod modal_timer {
+    use std::ffi::c_void;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::time::Duration;
+
+    /// SDL event type of the ticks (0 until registered)
+    static TICK_EVENT: AtomicU32 = AtomicU32::new(0);
+
+    type TimerProc = unsafe extern "system" fn(*mut c_void, u32, usize, u32);
+
+    #[link(name = "user32")]
+    extern "system" {
+        fn SetTimer(hwnd: *mut c_void, id: usize, elapse: u32, proc: Option<TimerProc>) -> usize;
+        fn KillTimer(hwnd: *mut c_void, id: usize) -> i32;
+    }
+
+    /// Register the tick event type with SDL
+    pub fn register(events: &sdl2::EventSubsystem) -> Result<(), String> {
+        // SAFETY: the type is only used for the payload-free ticks
+        let event_type = unsafe { events.register_event()? };
+        TICK_EVENT.store(event_type, Ordering::Relaxed);
+        Ok(())
+    }
+
+    unsafe extern "system" fn tick(_hwnd: *mut c_void, _msg: u32, _id: usize, _time: u32) {
+        let mut event: sdl2::sys::SDL_Event = std::mem::zeroed();
+        event.type_ = TICK_EVENT.load(Ordering::Relaxed);
+        sdl2::sys::SDL_PushEvent(&mut event);
+    }
+
+    /// A running timer, stopped when dropped
+    pub struct ModalTimer(usize);
+
+    impl ModalTimer {
+        pub fn start(interval: Duration) -> Option<Self> {
+            // SAFETY: a thread timer (no window) with a plain callback
+            let id = unsafe {
+                SetTimer(
+                    std::ptr::null_mut(),
+                    0,
+                    interval.as_millis() as u32,
+                    Some(tick),
+                )
+            };
+            (id != 0).then_some(Self(id))
+        }
+    }
+
+    impl Drop for ModalTimer {
+        fn drop(&mut self) {
+            // SAFETY: the timer was created by this thread in `start`
+            unsafe {
+                KillTimer(std::ptr::null_mut(), self.0);
+            }
+        }
+    }
+}
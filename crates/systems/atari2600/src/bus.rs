@@ -12,7 +12,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::cartridge::Cartridge;
 use crate::riot::Riot;
-use crate::tia::Tia;
+use crate::tia::{Tia, TiaAccuracy};
 
 /// Atari 2600 memory bus
 #[derive(Debug, Serialize, Deserialize)]
@@ -23,6 +23,9 @@ pub struct Atari2600Bus {
     pub cartridge: Option<Cartridge>,
     #[serde(skip)]
     wsync_request: bool,
+    // TIA writes held until the instruction's cycles have been clocked (beam mode)
+    #[serde(skip)]
+    pending_tia_writes: Vec<(u8, u8)>,
 }
 
 impl Default for Atari2600Bus {
@@ -39,6 +42,7 @@ impl Atari2600Bus {
             riot: Riot::new(),
             cartridge: None,
             wsync_request: false,
+            pending_tia_writes: Vec::new(),
         }
     }
 
@@ -52,6 +56,7 @@ impl Atari2600Bus {
         self.tia.reset();
         self.riot.reset();
         self.wsync_request = false;
+        self.pending_tia_writes.clear();
     }
 
     /// Check if WSYNC was requested and clear the flag
@@ -67,6 +72,23 @@ impl Atari2600Bus {
             self.tia.clock();
         }
         self.riot.clock(cycles as u16);
+
+        // The CPU stores on an instruction's last cycle, so deferred writes
+        // land once the beam has caught up with it
+        for (reg, val) in std::mem::take(&mut self.pending_tia_writes) {
+            self.tia.write(reg, val);
+        }
+    }
+
+    /// Write a TIA register, deferring it to the end of the instruction when
+    /// the TIA draws with the beam
+    fn write_tia(&mut self, addr: u16, val: u8) {
+        let reg = (addr & 0x3F) as u8;
+        if self.tia.accuracy() == TiaAccuracy::Beam {
+            self.pending_tia_writes.push((reg, val));
+        } else {
+            self.tia.write(reg, val);
+        }
     }
 }
 
@@ -136,7 +158,7 @@ impl Memory6502 for Atari2600Bus {
                 if (addr & 0x3F) == 0x02 {
                     self.wsync_request = true;
                 }
-                self.tia.write((addr & 0x3F) as u8, val);
+                self.write_tia(addr, val);
             }
             0x002D..=0x003F => {} // Unused
 
@@ -147,7 +169,7 @@ impl Memory6502 for Atari2600Bus {
                 if (addr & 0x3F) == 0x02 {
                     self.wsync_request = true;
                 }
-                self.tia.write((addr & 0x3F) as u8, val);
+                self.write_tia(addr, val);
                 self.riot.write(addr, val);
             }
 
@@ -159,7 +181,7 @@ impl Memory6502 for Atari2600Bus {
                 if (addr & 0x3F) == 0x02 {
                     self.wsync_request = true;
                 }
-                self.tia.write((addr & 0x3F) as u8, val);
+                self.write_tia(addr, val);
             }
 
             // TIA + RAM mirrors (0x0140-0x017F) - mirrors the dual-write behavior of 0x40-0x7F
@@ -167,7 +189,7 @@ impl Memory6502 for Atari2600Bus {
                 if (addr & 0x3F) == 0x02 {
                     self.wsync_request = true;
                 }
-                self.tia.write((addr & 0x3F) as u8, val);
+                self.write_tia(addr, val);
                 self.riot.write(addr, val);
             }
 
@@ -181,10 +203,10 @@ impl Memory6502 for Atari2600Bus {
             // RIOT I/O and timer
             0x0280..=0x029F => self.riot.write(addr, val),
 
-            // Everything else maps to cartridge ROM (for bank switching)
+            // Everything else maps to the cartridge (bank switching and RAM)
             _ => {
                 if let Some(cart) = &mut self.cartridge {
-                    cart.write(addr);
+                    cart.write(addr, val);
                 }
             }
         }
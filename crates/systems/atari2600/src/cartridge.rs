@@ -40,6 +40,7 @@
 //!   - Read from $1FF9 → select bank 1
 //!   - Read from $1FFA → select bank 2
 //! - **Games**: CBS games (e.g., Omega Race)
+//! - **RAM**: Every FA cartridge has 256 bytes of CBS RAM Plus (see below)
 //!
 //! ### F6 Banking (16K)
 //! - **Size**: 16384 bytes (4 banks of 4K each)
@@ -64,6 +65,25 @@
 //! - **Games**: Large games (e.g., Fatal Run)
 //! - **Note**: This is the largest standard Atari 2600 cartridge format
 //!
+//! # Cartridge RAM
+//!
+//! The console only has 128 bytes of RAM, so some cartridges add their own.
+//! The cartridge port has no write line: a RAM chip on the cartridge is
+//! wired to two address ranges, one where writes go in and one where the
+//! contents are read back.
+//!
+//! - **Superchip** (F8SC, F6SC, F4SC): 128 bytes, written at $1000-$107F and
+//!   read at $1080-$10FF in every bank. The bytes under the RAM are unused in
+//!   the ROM image, so an F8/F6/F4 image whose banks each start with two
+//!   identical 128-byte blocks is taken to have a Superchip (the same check
+//!   other emulators use)
+//! - **CBS RAM Plus** (FA): 256 bytes, written at $1000-$10FF and read at
+//!   $1100-$11FF
+//!
+//! High-score tables and game progress live in this RAM, so it is part of
+//! save states and can be exported and imported with
+//! [`Cartridge::ram`] and [`Cartridge::load_ram`].
+//!
 //! # Bank Switching Mechanics
 //!
 //! Bank switching on the Atari 2600 is **triggered by reads or writes** to specific addresses.
@@ -111,9 +131,17 @@
 //! - ✅ Properly handles bank switching via read/write access
 //! - ✅ Maintains current bank state across frames
 //! - ✅ Supports save states (bank state is serializable)
+//! - ✅ Superchip and CBS RAM Plus cartridge RAM
 //! - ❌ Does not support more exotic schemes (e.g., DPC, FE, 3F, E0, etc.)
 //!
 //! The implemented schemes cover the vast majority of commercially released Atari 2600 games.
+//!
+//! # Stacked Multi-Loads
+//!
+//! A game split across two files (side A/B) asks for its next load by
+//! reading [`LOAD_REQUEST_HOTSPOT`] from a 2K or 4K image. Those images have
+//! no bank hotspots, so the read only latches a request; the system decides
+//! whether a second image is mounted to load.
 
 use serde::{Deserialize, Serialize};
 use std::cell::Cell;
@@ -125,8 +153,21 @@ pub enum CartridgeError {
     InvalidSize(usize),
     #[error("Unsupported banking scheme")]
     UnsupportedBanking,
+    #[error("Cartridge has no RAM")]
+    NoRam,
+    #[error("Cartridge RAM size mismatch: expected {expected} bytes, got {actual}")]
+    RamSize { expected: usize, actual: usize },
 }
 
+/// Address a stacked multi-load program reads to ask for its next load
+/// (the Supercharger control hotspot; only 2K/4K images recognise it)
+pub const LOAD_REQUEST_HOTSPOT: u16 = 0x1FF8;
+
+/// Size of Superchip RAM (F8SC, F6SC, F4SC)
+const SUPERCHIP_RAM_SIZE: usize = 128;
+/// Size of CBS RAM Plus (FA)
+const RAM_PLUS_SIZE: usize = 256;
+
 /// Banking scheme types
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum BankingScheme {
@@ -153,20 +194,43 @@ pub struct Cartridge {
     current_bank: Cell<usize>,
     /// Banking scheme
     scheme: BankingScheme,
+    /// Set when the program reads the multi-load request hotspot
+    load_requested: Cell<bool>,
+    /// Cartridge RAM (empty if the cartridge has none)
+    ram: Vec<u8>,
 }
 
 impl Cartridge {
     /// Create a new cartridge from ROM data
     pub fn new(rom: Vec<u8>) -> Result<Self, CartridgeError> {
         let scheme = Self::detect_banking(&rom)?;
+        let ram_size = Self::detect_ram(&rom, scheme);
 
         Ok(Self {
             rom,
             current_bank: Cell::new(0),
             scheme,
+            load_requested: Cell::new(false),
+            ram: vec![0; ram_size],
         })
     }
 
+    /// Detect cartridge RAM: always on FA, on F8/F6/F4 when every bank
+    /// starts with two identical 128-byte blocks (the Superchip's ports)
+    fn detect_ram(rom: &[u8], scheme: BankingScheme) -> usize {
+        match scheme {
+            BankingScheme::FA => RAM_PLUS_SIZE,
+            BankingScheme::F8 | BankingScheme::F6 | BankingScheme::F4
+                if rom
+                    .chunks(4096)
+                    .all(|bank| bank[..SUPERCHIP_RAM_SIZE] == bank[SUPERCHIP_RAM_SIZE..256]) =>
+            {
+                SUPERCHIP_RAM_SIZE
+            }
+            _ => 0,
+        }
+    }
+
     /// Detect banking scheme from ROM size
     fn detect_banking(rom: &[u8]) -> Result<BankingScheme, CartridgeError> {
         match rom.len() {
@@ -186,6 +250,13 @@ impl Cartridge {
         // Because the CPU memory interface is `read(&self)`, we use interior mutability.
         self.maybe_bank_switch(addr);
 
+        // Cartridge RAM read port, just above the write port
+        let offset = (addr & 0x0FFF) as usize;
+        let ram_size = self.ram.len();
+        if (ram_size..2 * ram_size).contains(&offset) {
+            return self.ram[offset - ram_size];
+        }
+
         match self.scheme {
             BankingScheme::Rom2K => {
                 // 2K ROM mapped to $F800-$FFFF (mirrored)
@@ -228,7 +299,11 @@ impl Cartridge {
     fn maybe_bank_switch(&self, addr: u16) {
         // Address is already masked to 13 bits by the bus, so hot-spots are in $1FF4-$1FFB.
         match self.scheme {
-            BankingScheme::Rom2K | BankingScheme::Rom4K => {}
+            BankingScheme::Rom2K | BankingScheme::Rom4K => {
+                if addr == LOAD_REQUEST_HOTSPOT {
+                    self.load_requested.set(true);
+                }
+            }
             BankingScheme::F8 => match addr {
                 0x1FF8 => self.current_bank.set(0),
                 0x1FF9 => self.current_bank.set(1),
@@ -261,10 +336,16 @@ impl Cartridge {
         }
     }
 
-    /// Write to cartridge (for bank switching)
-    pub fn write(&mut self, addr: u16) {
+    /// Write to cartridge (for bank switching and cartridge RAM)
+    pub fn write(&mut self, addr: u16, val: u8) {
         // Some carts also switch on writes; keep this for compatibility.
         self.maybe_bank_switch(addr);
+
+        // Cartridge RAM write port
+        let offset = (addr & 0x0FFF) as usize;
+        if let Some(byte) = self.ram.get_mut(offset) {
+            *byte = val;
+        }
     }
 
     /// Get the current banking scheme
@@ -277,10 +358,42 @@ impl Cartridge {
         self.current_bank.get()
     }
 
+    /// Select a bank directly (save state restore); clamped to the ROM's banks
+    pub fn set_current_bank(&self, bank: usize) {
+        let banks = (self.rom.len() / 4096).max(1);
+        self.current_bank.set(bank.min(banks - 1));
+    }
+
     /// Get ROM size
     pub fn size(&self) -> usize {
         self.rom.len()
     }
+
+    /// Cartridge RAM contents (empty if the cartridge has none)
+    pub fn ram(&self) -> &[u8] {
+        &self.ram
+    }
+
+    /// Replace the cartridge RAM contents, e.g. from an exported file or a
+    /// save state
+    pub fn load_ram(&mut self, data: &[u8]) -> Result<(), CartridgeError> {
+        if self.ram.is_empty() {
+            return Err(CartridgeError::NoRam);
+        }
+        if data.len() != self.ram.len() {
+            return Err(CartridgeError::RamSize {
+                expected: self.ram.len(),
+                actual: data.len(),
+            });
+        }
+        self.ram.copy_from_slice(data);
+        Ok(())
+    }
+
+    /// Whether the program asked for its next load since the last call
+    pub fn take_load_request(&self) -> bool {
+        self.load_requested.replace(false)
+    }
 }
 
 #[cfg(test)]
@@ -327,12 +440,12 @@ mod tests {
         assert_eq!(cart.read(0xF000), 0x11);
 
         // Switch to bank 1
-        cart.write(0x1FF9);
+        cart.write(0x1FF9, 0);
         assert_eq!(cart.current_bank(), 1);
         assert_eq!(cart.read(0xF000), 0x22);
 
         // Switch back to bank 0
-        cart.write(0x1FF8);
+        cart.write(0x1FF8, 0);
         assert_eq!(cart.current_bank(), 0);
         assert_eq!(cart.read(0xF000), 0x11);
     }
@@ -350,7 +463,7 @@ mod tests {
 
         // Test all 4 banks
         for bank in 0..4 {
-            cart.write(0x1FF6 + bank as u16);
+            cart.write(0x1FF6 + bank as u16, 0);
             assert_eq!(cart.current_bank(), bank);
             assert_eq!(cart.read(0xF000), (0x10 + bank) as u8);
         }
@@ -365,11 +478,72 @@ mod tests {
 
         // Test all 8 banks
         for bank in 0..8 {
-            cart.write(0x1FF4 + bank as u16);
+            cart.write(0x1FF4 + bank as u16, 0);
             assert_eq!(cart.current_bank(), bank);
         }
     }
 
+    #[test]
+    fn test_load_request_hotspot_only_on_unbanked_images() {
+        let cart = Cartridge::new(vec![0; 4096]).unwrap();
+        assert!(!cart.take_load_request());
+        cart.read(LOAD_REQUEST_HOTSPOT);
+        assert!(cart.take_load_request());
+        assert!(!cart.take_load_request());
+
+        // On F8 the same address selects bank 0
+        let cart = Cartridge::new(vec![0; 8192]).unwrap();
+        cart.read(LOAD_REQUEST_HOTSPOT);
+        assert!(!cart.take_load_request());
+    }
+
+    #[test]
+    fn test_superchip_ram() {
+        // Code in the first 256 bytes of a bank rules out a Superchip
+        let mut rom = vec![0xFF; 8192];
+        rom[0] = 0xA9;
+        assert!(Cartridge::new(rom).unwrap().ram().is_empty());
+
+        let mut cart = Cartridge::new(vec![0xFF; 8192]).unwrap();
+        assert_eq!(cart.ram().len(), 128);
+        cart.write(0x1005, 0x42);
+        assert_eq!(cart.read(0x1085), 0x42);
+        // RAM is shared by all banks
+        cart.read(0x1FF9);
+        assert_eq!(cart.read(0x1085), 0x42);
+        // Above the read port is ROM again
+        assert_eq!(cart.read(0x1105), 0xFF);
+    }
+
+    #[test]
+    fn test_cbs_ram_plus() {
+        let mut rom = vec![0x00; 12288];
+        rom[0x200] = 0x77;
+        let mut cart = Cartridge::new(rom).unwrap();
+        assert_eq!(cart.scheme(), BankingScheme::FA);
+        assert_eq!(cart.ram().len(), 256);
+        cart.write(0x10FF, 0x99);
+        assert_eq!(cart.read(0x11FF), 0x99);
+        assert_eq!(cart.read(0x1200), 0x77);
+
+        let mut data = vec![0; 256];
+        data[0] = 0x12;
+        cart.load_ram(&data).unwrap();
+        assert_eq!(cart.read(0x1100), 0x12);
+        assert!(matches!(
+            cart.load_ram(&[0; 128]),
+            Err(CartridgeError::RamSize {
+                expected: 256,
+                actual: 128
+            })
+        ));
+        let mut cart = Cartridge::new(vec![0; 4096]).unwrap();
+        assert!(matches!(
+            cart.load_ram(&[0; 128]),
+            Err(CartridgeError::NoRam)
+        ));
+    }
+
     #[test]
     fn test_invalid_rom_size() {
         let rom = vec![0x00; 1000];
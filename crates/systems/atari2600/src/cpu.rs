@@ -5,6 +5,18 @@ use serde::{Deserialize, Serialize};
 
 use crate::bus::Atari2600Bus;
 
+/// 6507 register snapshot for save states
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CpuState {
+    pub a: u8,
+    pub x: u8,
+    pub y: u8,
+    pub sp: u8,
+    pub status: u8,
+    pub pc: u16,
+    pub cycles: u64,
+}
+
 /// Atari 2600 CPU (6507 - 6502 variant with 13-bit address bus)
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Atari2600Cpu {
@@ -46,6 +58,32 @@ impl Atari2600Cpu {
         self.cpu.as_mut().map(|cpu| &mut cpu.memory)
     }
 
+    /// Capture register state for a save state
+    pub fn save_state(&self) -> Option<CpuState> {
+        self.cpu.as_ref().map(|cpu| CpuState {
+            a: cpu.a,
+            x: cpu.x,
+            y: cpu.y,
+            sp: cpu.sp,
+            status: cpu.status,
+            pc: cpu.pc,
+            cycles: cpu.cycles,
+        })
+    }
+
+    /// Restore register state from a save state
+    pub fn load_state(&mut self, state: &CpuState) {
+        if let Some(cpu) = &mut self.cpu {
+            cpu.a = state.a;
+            cpu.x = state.x;
+            cpu.y = state.y;
+            cpu.sp = state.sp;
+            cpu.status = state.status;
+            cpu.pc = state.pc;
+            cpu.cycles = state.cycles;
+        }
+    }
+
     /// Replace the bus
     #[allow(dead_code)]
     pub fn with_bus(mut self, bus: Atari2600Bus) -> Self {
@@ -22,7 +22,8 @@
 //!
 //! **Video Features:**
 //! - Resolution: 160x192 pixels (visible area on NTSC)
-//! - 128-color NTSC palette
+//! - 128-color NTSC palette, 104-color PAL palette, 8-color SECAM palette and grayscale (B&W TV Type switch)
+//! - Early and late TIA revisions (early chips shift hues with luminance)
 //! - Playfield: 40-bit wide, can be mirrored or repeated
 //! - 2 Player sprites (8 pixels wide)
 //! - 2 Missiles (1 pixel wide each)
@@ -58,6 +59,15 @@
 //!
 //! Bank switching is performed by reading from specific addresses in the cartridge ROM space.
 //!
+//! ## Stacked Multi-Loads
+//!
+//! Games split across two files (side A/B) mount the second file at the
+//! `SideB` mount point. When the running 2K/4K program reads the load request
+//! hotspot ($1FF8), [`Atari2600System::take_load_request`] reports it and the
+//! frontend calls [`Atari2600System::next_load`], which swaps in the other
+//! side and restarts the CPU at its reset vector. RIOT RAM is kept, so the
+//! game can pass state between loads.
+//!
 //! # Memory Map
 //!
 //! The 6507's 13-bit address bus creates an 8KB address space:
@@ -100,19 +110,26 @@
 //!
 //! # Usage Example
 //!
-//! ```no_run
+//! ```
 //! use emu_atari2600::Atari2600System;
 //! use emu_core::System;
 //!
 //! let mut system = Atari2600System::new();
 //!
-//! // Load a 4KB ROM
-//! let rom_data = vec![0u8; 4096]; // Your ROM data here
+//! // Load a 4KB ROM: NOPs with the reset vector at $F000
+//! let mut rom_data = vec![0xEA; 4096]; // Your ROM data here
+//! rom_data[0xFFC] = 0x00;
+//! rom_data[0xFFD] = 0xF0;
 //! system.mount("Cartridge", &rom_data).unwrap();
 //!
 //! // Run one frame
 //! let frame = system.step_frame().unwrap();
-//! // frame.pixels contains 160x192 RGBA pixels
+//! // frame.pixels contains 160x192 ARGB pixels
+//! assert_eq!((frame.width, frame.height), (160, 192));
+//!
+//! // Save states round-trip through JSON
+//! let state = system.save_state();
+//! system.load_state(&state).unwrap();
 //! ```
 //!
 //! # Testing
@@ -127,7 +144,7 @@
 //! Total: 39 tests, all passing
 
 #![allow(clippy::upper_case_acronyms)]
-use emu_core::logging::{LogCategory, LogConfig, LogLevel};
+use emu_core::logging::{log, LogCategory, LogConfig, LogLevel};
 
 mod bus;
 mod cartridge;
@@ -144,6 +161,8 @@ use serde_json::Value;
 use thiserror::Error;
 use tia_renderer::{SoftwareTiaRenderer, TiaRenderer};
 
+pub use tia::{TiaAccuracy, TiaRevision, VideoStandard};
+
 #[derive(Debug, Error)]
 pub enum Atari2600Error {
     #[error("Cartridge error: {0}")]
@@ -152,13 +171,58 @@ pub enum Atari2600Error {
     NoCartridge,
     #[error("Invalid mount point: {0}")]
     InvalidMountPoint(String),
+    #[error("No side B mounted for a multi-load")]
+    NoNextLoad,
+}
+
+/// Side of a stacked multi-load that is in the cartridge slot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoadSide {
+    A,
+    B,
 }
 
+/// Position of a difficulty switch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Difficulty {
+    /// A (Pro): the harder setting
+    A,
+    /// B (Amateur): the easier setting, where most games are played
+    #[default]
+    B,
+}
+
+/// TIA color clocks per second (NTSC, and the PAL crystal also used by SECAM consoles)
+const NTSC_COLOR_CLOCK_HZ: f64 = 3_579_545.0;
+const PAL_COLOR_CLOCK_HZ: f64 = 3_546_894.0;
+
+/// Frame lengths accepted when measuring the frame rate; anything else is a
+/// ROM that is not generating VSYNC (the frame loop bails at 320 lines)
+const FRAME_SCANLINES_RANGE: std::ops::RangeInclusive<u64> = 200..=320;
+
 /// Atari 2600 system
 pub struct Atari2600System {
     cpu: Atari2600Cpu,
     cycles: u64,
     renderer: Box<dyn TiaRenderer>,
+    // Console hardware and switch settings (persist across reset and state loads)
+    video_standard: VideoStandard,
+    color_switch: bool,
+    difficulty: [Difficulty; 2],
+    tia_accuracy: TiaAccuracy,
+    tia_revision: TiaRevision,
+    // Game Reset and Game Select buttons held down
+    game_reset: bool,
+    game_select: bool,
+    // Player 1 plugged into the right controller port and player 2 into the left
+    ports_swapped: bool,
+    // Scanlines in the last complete frame (games choose their own frame length)
+    frame_scanlines: u16,
+    frame_end_counter: u64,
+    // Stacked multi-load images; the active one is also in the cartridge slot
+    side_a: Option<Vec<u8>>,
+    side_b: Option<Vec<u8>>,
+    active_side: LoadSide,
 }
 
 impl Default for Atari2600System {
@@ -177,9 +241,223 @@ impl Atari2600System {
             cpu,
             cycles: 0,
             renderer: Box::new(SoftwareTiaRenderer::new()),
+            video_standard: VideoStandard::Ntsc,
+            color_switch: true,
+            difficulty: [Difficulty::B; 2],
+            tia_accuracy: TiaAccuracy::Scanline,
+            tia_revision: TiaRevision::Late,
+            game_reset: false,
+            game_select: false,
+            ports_swapped: false,
+            frame_scanlines: 262,
+            frame_end_counter: 0,
+            side_a: None,
+            side_b: None,
+            active_side: LoadSide::A,
+        }
+    }
+
+    /// Whether a second image is mounted for a stacked multi-load
+    pub fn has_next_load(&self) -> bool {
+        self.side_b.is_some()
+    }
+
+    /// Side of the multi-load currently in the cartridge slot
+    pub fn active_side(&self) -> LoadSide {
+        self.active_side
+    }
+
+    /// Whether the game asked for its next load since the last call
+    ///
+    /// Only reported when a side B is mounted; single-file games that happen
+    /// to read the hotspot are ignored.
+    pub fn take_load_request(&mut self) -> bool {
+        let requested = self
+            .cpu
+            .bus()
+            .and_then(|bus| bus.cartridge.as_ref())
+            .is_some_and(|cart| cart.take_load_request());
+        requested && self.has_next_load()
+    }
+
+    /// Load the other side of a stacked multi-load
+    ///
+    /// The new image starts from its reset vector. RAM, TIA and RIOT state are
+    /// kept, as on hardware where only the program is replaced.
+    pub fn next_load(&mut self) -> Result<LoadSide, Atari2600Error> {
+        let next = match self.active_side {
+            LoadSide::A => LoadSide::B,
+            LoadSide::B => LoadSide::A,
+        };
+        self.load_side(next)?;
+        self.cpu.reset();
+        Ok(next)
+    }
+
+    /// Put one side's image in the cartridge slot
+    fn load_side(&mut self, side: LoadSide) -> Result<(), Atari2600Error> {
+        let data = match side {
+            LoadSide::A => self.side_a.as_ref().ok_or(Atari2600Error::NoCartridge)?,
+            LoadSide::B => self.side_b.as_ref().ok_or(Atari2600Error::NoNextLoad)?,
+        };
+        let cartridge = Cartridge::new(data.clone())?;
+        if let Some(bus) = self.cpu.bus_mut() {
+            bus.load_cartridge(cartridge);
+        }
+        self.active_side = side;
+        Ok(())
+    }
+
+    /// Power the console back on with the cartridge in the slot
+    ///
+    /// Cartridges are swapped with the power off, so a newly mounted one
+    /// starts on a cold console: the CPU, TIA (including its audio
+    /// channels) and RIOT RAM and timer are reset, the picture is cleared
+    /// and the frame length is measured again. Console switches and
+    /// settings are kept, as is a mounted side B.
+    fn power_on(&mut self) {
+        self.reset();
+        self.renderer.reset();
+        self.frame_scanlines = 262;
+    }
+
+    /// Set the TV Type switch (true = Color, false = B&W)
+    ///
+    /// Updates SWCHB bit 3 for software that reads the switch, and selects the
+    /// grayscale palette when set to B&W.
+    pub fn set_color_switch(&mut self, color: bool) {
+        self.color_switch = color;
+        self.apply_console_switches();
+    }
+
+    /// Get the TV Type switch position (true = Color, false = B&W)
+    pub fn color_switch(&self) -> bool {
+        self.color_switch
+    }
+
+    /// Set a difficulty switch (player 0 = left, 1 = right)
+    ///
+    /// Updates SWCHB bit 6 (left) or 7 (right), which is low for A.
+    pub fn set_difficulty(&mut self, player: usize, difficulty: Difficulty) {
+        if let Some(switch) = self.difficulty.get_mut(player) {
+            *switch = difficulty;
+            self.apply_console_switches();
+        }
+    }
+
+    /// Get a difficulty switch position (player 0 = left, 1 = right)
+    pub fn difficulty(&self, player: usize) -> Difficulty {
+        self.difficulty.get(player).copied().unwrap_or_default()
+    }
+
+    /// Hold down or release the Game Reset and Game Select buttons
+    ///
+    /// Games poll these (SWCHB bits 0 and 1) once per frame, so a press
+    /// should be held for a few frames to be seen.
+    pub fn set_console_buttons(&mut self, reset: bool, select: bool) {
+        self.game_reset = reset;
+        self.game_select = select;
+        self.apply_console_switches();
+    }
+
+    /// Plug player 1's joystick into the right port and player 2's into the
+    /// left, for games that read the right port
+    pub fn set_ports_swapped(&mut self, swapped: bool) {
+        self.ports_swapped = swapped;
+    }
+
+    /// Whether the controller ports are swapped
+    pub fn ports_swapped(&self) -> bool {
+        self.ports_swapped
+    }
+
+    /// Select the console's video standard (NTSC, PAL or SECAM palette)
+    ///
+    /// PAL and SECAM also select the PAL color clock for frame pacing.
+    pub fn set_video_standard(&mut self, standard: VideoStandard) {
+        self.video_standard = standard;
+        self.apply_console_switches();
+    }
+
+    /// Get the console's video standard
+    pub fn video_standard(&self) -> VideoStandard {
+        self.video_standard
+    }
+
+    /// Select how closely TIA rendering follows the beam
+    ///
+    /// `TiaAccuracy::Beam` draws each pixel as the beam reaches it, for games
+    /// that rewrite playfield or player registers mid-scanline. It is slower
+    /// than the default per-scanline rendering.
+    pub fn set_tia_accuracy(&mut self, accuracy: TiaAccuracy) {
+        self.tia_accuracy = accuracy;
+        self.apply_console_switches();
+    }
+
+    /// Get the TIA rendering accuracy
+    pub fn tia_accuracy(&self) -> TiaAccuracy {
+        self.tia_accuracy
+    }
+
+    /// Select the TIA chip revision
+    ///
+    /// `TiaRevision::Early` reproduces the color-luminance mixing of early
+    /// consoles (hues drift as the luminance rises and light shades are paler)
+    /// and their late playfield writes, which show in beam rendering.
+    pub fn set_tia_revision(&mut self, revision: TiaRevision) {
+        self.tia_revision = revision;
+        self.apply_console_switches();
+    }
+
+    /// Get the TIA chip revision
+    pub fn tia_revision(&self) -> TiaRevision {
+        self.tia_revision
+    }
+
+    /// Push the switch settings into RIOT and TIA (they are reset with the bus)
+    fn apply_console_switches(&mut self) {
+        let (standard, color) = (self.video_standard, self.color_switch);
+        let (accuracy, revision) = (self.tia_accuracy, self.tia_revision);
+        let (reset, select, difficulty) = (self.game_reset, self.game_select, self.difficulty);
+        if let Some(bus) = self.cpu.bus_mut() {
+            bus.riot.set_console_switch(0, reset);
+            bus.riot.set_console_switch(1, select);
+            // BW/Color switch is active low: 0 = B&W
+            bus.riot.set_console_switch(3, !color);
+            // Difficulty switches are low for A
+            bus.riot
+                .set_console_switch(6, difficulty[0] == Difficulty::A);
+            bus.riot
+                .set_console_switch(7, difficulty[1] == Difficulty::A);
+            bus.tia.set_revision(revision);
+            bus.tia.set_palette(standard, !color);
+            bus.tia.set_accuracy(accuracy);
         }
     }
 
+    /// Superchip or CBS RAM Plus contents of the mounted cartridge, to
+    /// export so high scores and game progress outlive the session
+    ///
+    /// `None` if no cartridge is mounted or it has no RAM.
+    pub fn cart_ram(&self) -> Option<&[u8]> {
+        self.cpu
+            .bus()
+            .and_then(|bus| bus.cartridge.as_ref())
+            .map(|cart| cart.ram())
+            .filter(|ram| !ram.is_empty())
+    }
+
+    /// Restore cartridge RAM exported with `cart_ram`, after mounting
+    pub fn load_cart_ram(&mut self, data: &[u8]) -> Result<(), Atari2600Error> {
+        let cart = self
+            .cpu
+            .bus_mut()
+            .and_then(|bus| bus.cartridge.as_mut())
+            .ok_or(Atari2600Error::NoCartridge)?;
+        cart.load_ram(data)?;
+        Ok(())
+    }
+
     /// Get debug information
     pub fn debug_info(&self) -> Option<DebugInfo> {
         self.cpu.bus().and_then(|bus| {
@@ -188,6 +466,19 @@ impl Atari2600System {
                 banking_scheme: format!("{:?}", cart.scheme()),
                 current_bank: cart.current_bank(),
                 scanline: bus.tia.get_scanline_counter(),
+                tv_type: format!(
+                    "{} {}{}",
+                    match self.video_standard {
+                        VideoStandard::Ntsc => "NTSC",
+                        VideoStandard::Pal => "PAL",
+                        VideoStandard::Secam => "SECAM",
+                    },
+                    if self.color_switch { "Color" } else { "B&W" },
+                    match self.tia_revision {
+                        TiaRevision::Early => " (early TIA)",
+                        TiaRevision::Late => "",
+                    }
+                ),
             })
         })
     }
@@ -222,6 +513,11 @@ impl Atari2600System {
         if player > 1 {
             return; // Only support 2 players
         }
+        let player = if self.ports_swapped {
+            player ^ 1
+        } else {
+            player
+        };
 
         if let Some(bus) = self.cpu.bus_mut() {
             // Extract button states (standard: 1=pressed, 0=released)
@@ -250,6 +546,7 @@ pub struct DebugInfo {
     pub banking_scheme: String,
     pub current_bank: usize,
     pub scanline: u64,
+    pub tv_type: String,
 }
 
 impl System for Atari2600System {
@@ -261,6 +558,8 @@ impl System for Atari2600System {
             bus.reset();
         }
         self.cycles = 0;
+        self.frame_end_counter = 0;
+        self.apply_console_switches();
     }
 
     fn step_frame(&mut self) -> Result<Frame, Self::Error> {
@@ -279,7 +578,9 @@ impl System for Atari2600System {
 
         // VSYNC edge tracking
         let mut prev_vsync = self.cpu.bus().map(|b| b.tia.vsync()).unwrap_or(false);
-        let mut saw_vsync_rise = false;
+        // The previous frame ended on a rising edge, so starting inside the
+        // pulse means the rise has already been seen
+        let mut saw_vsync_rise = prev_vsync;
         let mut started_frame_capture = false;
 
         let debug_vsync = LogConfig::global().should_log(LogCategory::PPU, LogLevel::Debug);
@@ -408,6 +709,23 @@ impl System for Atari2600System {
             );
         }
 
+        // Frames run from VSYNC to VSYNC, so the scanlines since the last one
+        // give this game's frame length
+        let standard = self.video_standard;
+        if let Some(bus) = self.cpu.bus_mut() {
+            let counter = bus.tia.get_scanline_counter();
+            let lines = counter.saturating_sub(self.frame_end_counter);
+            if FRAME_SCANLINES_RANGE.contains(&lines) {
+                self.frame_scanlines = lines as u16;
+            }
+            self.frame_end_counter = counter;
+
+            // A PAL TV relies on the color phase alternating between frames,
+            // which an odd number of lines breaks, so the picture loses color
+            bus.tia
+                .set_color_loss(standard == VideoStandard::Pal && lines % 2 == 1);
+        }
+
         // Render the frame using the renderer
         if let Some(bus) = self.cpu.bus_mut() {
             let current_scanline = bus.tia.get_scanline();
@@ -422,33 +740,27 @@ impl System for Atari2600System {
             // Determine visible window based on VBLANK timing within the current frame.
             let visible_start = bus.tia.visible_window_start_scanline();
 
-            if LogConfig::global().should_log(LogCategory::PPU, LogLevel::Info) {
-                eprintln!(
+            log(LogCategory::PPU, LogLevel::Info, || {
+                format!(
                     "[ATARI RENDER] visible_start={} current_scanline={} scanlines_seen={} (will render TIA scanlines {}-{})",
                     visible_start, current_scanline, scanlines_seen,
                     visible_start,
                     (visible_start + 191) % 262
-                );
-            }
+                )
+            });
 
             // Use renderer to render the frame
             self.renderer.render_frame(&bus.tia, visible_start);
 
             // Debug: Check if framebuffer is stable
-            if LogConfig::global().should_log(LogCategory::PPU, LogLevel::Info) {
+            let frame = self.renderer.get_frame();
+            log(LogCategory::PPU, LogLevel::Info, || {
                 use std::collections::hash_map::DefaultHasher;
                 use std::hash::{Hash, Hasher};
                 let mut hasher = DefaultHasher::new();
-                self.renderer.get_frame().pixels.hash(&mut hasher);
-                let frame_hash = hasher.finish();
-                eprintln!("[ATARI RENDER] Frame hash: {:016x}", frame_hash);
-            }
-        }
-
-        // Detect collisions for the frame (must be done after rendering)
-        if let Some(bus) = self.cpu.bus_mut() {
-            let visible_start = bus.tia.visible_window_start_scanline();
-            bus.tia.detect_collisions_for_frame(visible_start);
+                frame.pixels.hash(&mut hasher);
+                format!("[ATARI RENDER] Frame hash: {:016x}", hasher.finish())
+            });
         }
 
         if LogConfig::global().should_log(LogCategory::PPU, LogLevel::Trace) {
@@ -508,6 +820,13 @@ impl System for Atari2600System {
             "version": 1,
             "system": "atari2600",
             "cycles": self.cycles,
+            "cpu": self.cpu.save_state(),
+            "bank": self.cpu.bus().and_then(|b| b.cartridge.as_ref()).map(|c| c.current_bank()),
+            "cart_ram": self.cart_ram(),
+            "side": match self.active_side {
+                LoadSide::A => "A",
+                LoadSide::B => "B",
+            },
             "bus": self.cpu.bus(),
         })
     }
@@ -524,13 +843,46 @@ impl System for Atari2600System {
         }
 
         self.cycles = v["cycles"].as_u64().unwrap_or(0);
+        // The TIA's scanline counter is not saved and restarts from zero
+        self.frame_end_counter = 0;
 
         if let Some(bus_value) = v.get("bus") {
-            let bus: Atari2600Bus = serde_json::from_value(bus_value.clone())?;
+            let mut bus: Atari2600Bus = serde_json::from_value(bus_value.clone())?;
+            // ROM contents are not part of the state; keep the mounted cartridge
+            bus.cartridge = self.cpu.bus_mut().and_then(|b| b.cartridge.take());
             // Create a new CPU with the loaded bus
             self.cpu = Atari2600Cpu::new(bus);
         }
 
+        // A multi-load state may have been saved on the other side
+        let side = match v["side"].as_str() {
+            Some("B") => LoadSide::B,
+            _ => LoadSide::A,
+        };
+        if side != self.active_side && self.load_side(side).is_err() {
+            return Err(serde_json::from_str::<()>("invalid").unwrap_err());
+        }
+
+        // States from before registers were saved restart the program instead
+        match v.get("cpu").filter(|cpu| !cpu.is_null()) {
+            Some(cpu) => self.cpu.load_state(&serde_json::from_value(cpu.clone())?),
+            None => self.cpu.reset(),
+        }
+        if let (Some(bank), Some(cart)) = (
+            v["bank"].as_u64(),
+            self.cpu.bus().and_then(|b| b.cartridge.as_ref()),
+        ) {
+            cart.set_current_bank(bank as usize);
+        }
+        // Superchip / CBS RAM Plus contents, if the cartridge has RAM
+        if let Some(ram) = v.get("cart_ram").filter(|ram| !ram.is_null()) {
+            let ram: Vec<u8> = serde_json::from_value(ram.clone())?;
+            if self.load_cart_ram(&ram).is_err() {
+                return Err(serde_json::from_str::<()>("invalid").unwrap_err());
+            }
+        }
+        self.apply_console_switches();
+
         Ok(())
     }
 
@@ -539,54 +891,86 @@ impl System for Atari2600System {
     }
 
     fn mount_points(&self) -> Vec<MountPointInfo> {
-        vec![MountPointInfo {
-            id: "Cartridge".to_string(),
-            name: "Cartridge Slot".to_string(),
-            extensions: vec!["a26".to_string(), "bin".to_string()],
-            required: true,
-        }]
+        vec![
+            MountPointInfo {
+                id: "Cartridge".to_string(),
+                name: "Cartridge Slot".to_string(),
+                extensions: vec!["a26".to_string(), "bin".to_string()],
+                required: true,
+            },
+            MountPointInfo {
+                id: "SideB".to_string(),
+                name: "Multi-Load Side B".to_string(),
+                extensions: vec!["a26".to_string(), "bin".to_string()],
+                required: false,
+            },
+        ]
     }
 
     fn mount(&mut self, mount_point_id: &str, data: &[u8]) -> Result<(), Self::Error> {
-        if mount_point_id != "Cartridge" {
-            return Err(Atari2600Error::InvalidMountPoint(
-                mount_point_id.to_string(),
-            ));
-        }
+        match mount_point_id {
+            "Cartridge" => {
+                let cartridge = Cartridge::new(data.to_vec())?;
 
-        let cartridge = Cartridge::new(data.to_vec())?;
+                if let Some(bus) = self.cpu.bus_mut() {
+                    bus.load_cartridge(cartridge);
+                }
+                self.side_a = Some(data.to_vec());
+                self.active_side = LoadSide::A;
 
-        if let Some(bus) = self.cpu.bus_mut() {
-            bus.load_cartridge(cartridge);
+                self.power_on();
+                Ok(())
+            }
+            "SideB" => {
+                // Validate now so a bad image is reported when mounted
+                Cartridge::new(data.to_vec())?;
+                self.side_b = Some(data.to_vec());
+                Ok(())
+            }
+            _ => Err(Atari2600Error::InvalidMountPoint(
+                mount_point_id.to_string(),
+            )),
         }
-
-        self.reset();
-        Ok(())
     }
 
     fn unmount(&mut self, mount_point_id: &str) -> Result<(), Self::Error> {
-        if mount_point_id != "Cartridge" {
-            return Err(Atari2600Error::InvalidMountPoint(
+        match mount_point_id {
+            "Cartridge" => {
+                if let Some(bus) = self.cpu.bus_mut() {
+                    bus.cartridge = None;
+                }
+                self.side_a = None;
+                self.active_side = LoadSide::A;
+                Ok(())
+            }
+            "SideB" => {
+                self.side_b = None;
+                Ok(())
+            }
+            _ => Err(Atari2600Error::InvalidMountPoint(
                 mount_point_id.to_string(),
-            ));
-        }
-
-        if let Some(bus) = self.cpu.bus_mut() {
-            bus.cartridge = None;
+            )),
         }
-
-        Ok(())
     }
 
     fn is_mounted(&self, mount_point_id: &str) -> bool {
-        if mount_point_id != "Cartridge" {
-            return false;
+        match mount_point_id {
+            "Cartridge" => self
+                .cpu
+                .bus()
+                .map(|bus| bus.cartridge.is_some())
+                .unwrap_or(false),
+            "SideB" => self.side_b.is_some(),
+            _ => false,
         }
+    }
 
-        self.cpu
-            .bus()
-            .map(|bus| bus.cartridge.is_some())
-            .unwrap_or(false)
+    fn frame_rate(&self) -> f64 {
+        let color_clock = match self.video_standard {
+            VideoStandard::Ntsc => NTSC_COLOR_CLOCK_HZ,
+            VideoStandard::Pal | VideoStandard::Secam => PAL_COLOR_CLOCK_HZ,
+        };
+        color_clock / (228.0 * self.frame_scanlines as f64)
     }
 }
 
@@ -605,9 +989,290 @@ mod tests {
         let sys = Atari2600System::new();
         let mounts = sys.mount_points();
 
-        assert_eq!(mounts.len(), 1);
+        assert_eq!(mounts.len(), 2);
         assert_eq!(mounts[0].id, "Cartridge");
         assert!(mounts[0].required);
+        assert_eq!(mounts[1].id, "SideB");
+        assert!(!mounts[1].required);
+    }
+
+    #[test]
+    fn test_stacked_multiload_keeps_ram() {
+        // Side A: LDA #$42; STA $80; loop: LDA $1FF8 (request the next load); JMP loop
+        let mut side_a = vec![0xEA; 4096];
+        side_a[..10].copy_from_slice(&[0xA9, 0x42, 0x85, 0x80, 0xAD, 0xF8, 0x1F, 0x4C, 0x04, 0xF0]);
+        side_a[0xFFC] = 0x00;
+        side_a[0xFFD] = 0xF0;
+        // Side B: LDA $80; STA $81; JMP *
+        let mut side_b = vec![0xEA; 2048];
+        side_b[..7].copy_from_slice(&[0xA5, 0x80, 0x85, 0x81, 0x4C, 0x04, 0xF8]);
+        side_b[0x7FC] = 0x00;
+        side_b[0x7FD] = 0xF8;
+
+        let mut sys = Atari2600System::new();
+        sys.mount("Cartridge", &side_a).unwrap();
+        sys.step_frame().unwrap();
+        // Without a side B the request is ignored
+        assert!(!sys.take_load_request());
+        assert!(matches!(sys.next_load(), Err(Atari2600Error::NoNextLoad)));
+
+        sys.mount("SideB", &side_b).unwrap();
+        sys.step_frame().unwrap();
+        assert!(sys.take_load_request());
+        assert_eq!(sys.next_load().unwrap(), LoadSide::B);
+        assert_eq!(sys.debug_info().unwrap().rom_size, 2048);
+
+        sys.step_frame().unwrap();
+        assert_eq!(sys.cpu.bus().unwrap().riot.read(0x81), 0x42);
+
+        // A saved state restores the side it was taken on
+        let state = sys.save_state();
+        sys.mount("Cartridge", &side_a).unwrap();
+        assert_eq!(sys.active_side(), LoadSide::A);
+        sys.load_state(&state).unwrap();
+        assert_eq!(sys.active_side(), LoadSide::B);
+    }
+
+    #[test]
+    fn test_mounting_cartridge_mid_session_powers_on() {
+        // COLUBK = RAM $80 = $44, AUDC0 = 4, AUDV0 = 15, then 312-line frames
+        let mut first = vec![0xEA; 4096];
+        first[..0x2B].copy_from_slice(&[
+            0xA9, 0x44, 0x85, 0x09, 0x85, 0x80, // COLUBK, RAM
+            0xA9, 0x04, 0x85, 0x15, 0xA9, 0x0F, 0x85, 0x19, // AUDC0, AUDV0
+            0xA9, 0x02, 0x85, 0x00, 0x85, 0x02, 0x85, 0x02, 0x85, 0x02, // VSYNC on
+            0xA9, 0x00, 0x85, 0x00, // VSYNC off
+            0xA2, 0xC8, 0x85, 0x02, 0xCA, 0xD0, 0xFB, // 200 lines
+            0xA2, 0x6D, 0x85, 0x02, 0xCA, 0xD0, 0xFB, // 109 lines
+            0x4C,
+        ]);
+        first[0x2B..0x2D].copy_from_slice(&[0x0E, 0xF0]);
+        first[0xFFC] = 0x00;
+        first[0xFFD] = 0xF0;
+        // Second cartridge: JMP *
+        let mut second = vec![0xEA; 4096];
+        second[..3].copy_from_slice(&[0x4C, 0x00, 0xF0]);
+        second[0xFFC] = 0x00;
+        second[0xFFD] = 0xF0;
+
+        let mut sys = Atari2600System::new();
+        sys.mount("Cartridge", &first).unwrap();
+        sys.set_color_switch(false);
+        for _ in 0..3 {
+            sys.step_frame().unwrap();
+        }
+        assert!((sys.frame_rate() - 50.32).abs() < 0.01);
+        assert_eq!(sys.cpu.bus().unwrap().riot.read(0x80), 0x44);
+        let samples = sys.get_audio_samples(100);
+        assert!(samples.iter().any(|&s| s != samples[0]));
+        assert!(sys
+            .renderer
+            .get_frame()
+            .pixels
+            .iter()
+            .any(|&p| p != 0xFF000000));
+
+        sys.mount("Cartridge", &second).unwrap();
+        assert_eq!(sys.cycles, 0);
+        assert!((sys.frame_rate() - 59.92).abs() < 0.01);
+        assert_eq!(sys.cpu.bus().unwrap().riot.read(0x80), 0x00);
+        let samples = sys.get_audio_samples(100);
+        assert!(samples.iter().all(|&s| s == samples[0]));
+        assert!(sys
+            .renderer
+            .get_frame()
+            .pixels
+            .iter()
+            .all(|&p| p == 0xFF000000));
+        // The console's own switches stay where they were
+        assert!(!sys.color_switch());
+        assert_eq!(sys.cpu.bus().unwrap().riot.read(0x0282) & 0x08, 0);
+    }
+
+    #[test]
+    fn test_frame_rate_follows_frame_length() {
+        // VSYNC for 3 lines, then 200 + `tail` WSYNC lines per frame
+        fn vsync_rom(tail: u8) -> Vec<u8> {
+            let mut rom = vec![0xEA; 4096];
+            rom[..0x1F].copy_from_slice(&[
+                0xA9, 0x02, 0x85, 0x00, 0x85, 0x02, 0x85, 0x02, 0x85, 0x02, // VSYNC on
+                0xA9, 0x00, 0x85, 0x00, // VSYNC off
+                0xA2, 0xC8, 0x85, 0x02, 0xCA, 0xD0, 0xFB, // 200 lines
+                0xA2, tail, 0x85, 0x02, 0xCA, 0xD0, 0xFB, // tail lines
+                0x4C, 0x00, 0xF0,
+            ]);
+            rom[0xFFC] = 0x00;
+            rom[0xFFD] = 0xF0;
+            rom
+        }
+
+        let mut sys = Atari2600System::new();
+        sys.mount("Cartridge", &vsync_rom(59)).unwrap();
+        for _ in 0..3 {
+            sys.step_frame().unwrap();
+        }
+        assert!((sys.frame_rate() - 59.92).abs() < 0.01);
+
+        // A 312-line (PAL-style) frame runs at ~50 Hz
+        sys.mount("Cartridge", &vsync_rom(109)).unwrap();
+        for _ in 0..3 {
+            sys.step_frame().unwrap();
+        }
+        assert!((sys.frame_rate() - 50.32).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_pal_odd_frames_lose_color() {
+        // VSYNC for 3 lines, COLUBK = $44, then 200 + `tail` WSYNC lines per frame
+        fn colored_rom(tail: u8) -> Vec<u8> {
+            let mut rom = vec![0xEA; 4096];
+            rom[..0x23].copy_from_slice(&[
+                0xA9, 0x02, 0x85, 0x00, 0x85, 0x02, 0x85, 0x02, 0x85, 0x02, // VSYNC on
+                0xA9, 0x00, 0x85, 0x00, // VSYNC off
+                0xA9, 0x44, 0x85, 0x09, // COLUBK
+                0xA2, 0xC8, 0x85, 0x02, 0xCA, 0xD0, 0xFB, // 200 lines
+                0xA2, tail, 0x85, 0x02, 0xCA, 0xD0, 0xFB, // tail lines
+                0x4C, 0x00, 0xF0,
+            ]);
+            rom[0xFFC] = 0x00;
+            rom[0xFFD] = 0xF0;
+            rom
+        }
+        let background = |sys: &mut Atari2600System| {
+            for _ in 0..3 {
+                sys.step_frame().unwrap();
+            }
+            sys.step_frame().unwrap().pixels[80 * 160 + 80]
+        };
+
+        let mut sys = Atari2600System::new();
+        sys.set_video_standard(VideoStandard::Pal);
+        sys.mount("Cartridge", &colored_rom(109)).unwrap();
+        let color = background(&mut sys);
+        assert_ne!(color, 0xFF6C6C6C);
+        assert!((sys.frame_rate() - 49.86).abs() < 0.01);
+
+        // 313 lines per frame: luminance only
+        sys.mount("Cartridge", &colored_rom(110)).unwrap();
+        assert_eq!(background(&mut sys), 0xFF6C6C6C);
+
+        // NTSC keeps its color whatever the frame length
+        sys.set_video_standard(VideoStandard::Ntsc);
+        assert_ne!(background(&mut sys), 0xFF6C6C6C);
+    }
+
+    #[test]
+    fn test_tia_revision_setting() {
+        // LDA #$4C; STA COLUBK; JMP $F004
+        let mut rom = vec![0xEA; 4096];
+        rom[..7].copy_from_slice(&[0xA9, 0x4C, 0x85, 0x09, 0x4C, 0x04, 0xF0]);
+        rom[0xFFC] = 0x00;
+        rom[0xFFD] = 0xF0;
+
+        let mut sys = Atari2600System::new();
+        sys.mount("Cartridge", &rom).unwrap();
+        assert_eq!(sys.tia_revision(), TiaRevision::Late);
+        sys.step_frame().unwrap();
+        let late = sys.step_frame().unwrap().pixels[80 * 160 + 80];
+
+        sys.set_tia_revision(TiaRevision::Early);
+        let early = sys.step_frame().unwrap().pixels[80 * 160 + 80];
+        assert_ne!(early, late);
+        assert!(sys.debug_info().unwrap().tv_type.contains("early TIA"));
+
+        // Setting survives reset
+        sys.reset();
+        assert_eq!(sys.tia_revision(), TiaRevision::Early);
+        assert_eq!(sys.cpu.bus().unwrap().tia.revision(), TiaRevision::Early);
+    }
+
+    #[test]
+    fn test_tia_accuracy_beam_mode() {
+        // STA WSYNC; LDA #$0E; STA COLUBK; 22x NOP; LDA #$44; STA COLUBK; JMP $F000
+        let mut rom = vec![0xEA; 4096];
+        rom[..6].copy_from_slice(&[0x85, 0x02, 0xA9, 0x0E, 0x85, 0x09]);
+        rom[0x1C..0x23].copy_from_slice(&[0xA9, 0x44, 0x85, 0x09, 0x4C, 0x00, 0xF0]);
+        rom[0xFFC] = 0x00;
+        rom[0xFFD] = 0xF0;
+
+        let mut sys = Atari2600System::new();
+        sys.mount("Cartridge", &rom).unwrap();
+        assert_eq!(sys.tia_accuracy(), TiaAccuracy::Scanline);
+        sys.step_frame().unwrap();
+        let frame = sys.step_frame().unwrap();
+        let row = 80 * 160;
+        assert_eq!(frame.pixels[row + 20], frame.pixels[row + 140]);
+
+        // The background change lands mid-line when drawing with the beam
+        sys.set_tia_accuracy(TiaAccuracy::Beam);
+        sys.step_frame().unwrap();
+        let frame = sys.step_frame().unwrap();
+        assert_ne!(frame.pixels[row + 20], frame.pixels[row + 140]);
+        assert_eq!(frame.pixels[row + 140], frame.pixels[row + 159]);
+
+        // Setting survives reset
+        sys.reset();
+        assert_eq!(sys.tia_accuracy(), TiaAccuracy::Beam);
+        assert_eq!(sys.cpu.bus().unwrap().tia.accuracy(), TiaAccuracy::Beam);
+    }
+
+    #[test]
+    fn test_tv_type_switch() {
+        // LDA #$44; STA COLUBK; JMP $F004
+        let mut rom = vec![0xEA; 4096];
+        rom[..7].copy_from_slice(&[0xA9, 0x44, 0x85, 0x09, 0x4C, 0x04, 0xF0]);
+        rom[0xFFC] = 0x00;
+        rom[0xFFD] = 0xF0;
+
+        let mut sys = Atari2600System::new();
+        sys.mount("Cartridge", &rom).unwrap();
+        sys.step_frame().unwrap();
+        let color = sys.step_frame().unwrap().pixels[80 * 160 + 80];
+        assert_ne!(color, 0xFF6C6C6C);
+
+        // B&W: SWCHB bit 3 clear and background drawn in gray
+        sys.set_color_switch(false);
+        assert_eq!(sys.cpu.bus().unwrap().riot.read(0x0282) & 0x08, 0);
+        let gray = sys.step_frame().unwrap().pixels[80 * 160 + 80];
+        assert_eq!(gray, 0xFF6C6C6C);
+
+        // Switch position survives reset
+        sys.reset();
+        assert!(!sys.color_switch());
+        assert_eq!(sys.cpu.bus().unwrap().riot.read(0x0282) & 0x08, 0);
+
+        // SECAM color: luminance 2 is red
+        sys.set_color_switch(true);
+        sys.set_video_standard(VideoStandard::Secam);
+        sys.step_frame().unwrap();
+        let secam = sys.step_frame().unwrap().pixels[80 * 160 + 80];
+        assert_eq!(secam, 0xFFF03C79);
+    }
+
+    #[test]
+    fn test_console_switches_and_swapped_ports() {
+        let mut sys = Atari2600System::new();
+        let swchb = |sys: &Atari2600System| sys.cpu.bus().unwrap().riot.read(0x0282);
+        assert_eq!(swchb(&sys) & 0xC3, 0xC3);
+
+        // Left difficulty A clears bit 6; held Game Reset clears bit 0
+        sys.set_difficulty(0, Difficulty::A);
+        sys.set_console_buttons(true, false);
+        assert_eq!(swchb(&sys) & 0xC3, 0x82);
+        sys.set_console_buttons(false, true);
+        assert_eq!(swchb(&sys) & 0xC3, 0x81);
+        sys.set_console_buttons(false, false);
+
+        // Difficulty survives reset
+        sys.reset();
+        assert_eq!(sys.difficulty(0), Difficulty::A);
+        assert_eq!(sys.difficulty(1), Difficulty::B);
+        assert_eq!(swchb(&sys) & 0xC3, 0x83);
+
+        // Player 1's joystick drives the other port when swapped
+        sys.set_ports_swapped(true);
+        sys.set_controller(0, 0x10); // Up
+        assert_eq!(sys.cpu.bus().unwrap().riot.read(0x0280), 0xEF);
     }
 
     #[test]
@@ -667,6 +1332,78 @@ mod tests {
         assert!(sys2.load_state(&state).is_ok());
     }
 
+    #[test]
+    fn test_cart_ram_in_save_state() {
+        // F8 Superchip image: bank 1 stores $42 in cartridge RAM and loops
+        let mut rom = vec![0xFF; 8192];
+        let code = [0xA9, 0x42, 0x8D, 0x10, 0x10, 0x4C, 0x08, 0xF2];
+        rom[0x1203..0x120B].copy_from_slice(&code);
+        for bank in [0x0000, 0x1000] {
+            rom[bank + 0xFFC] = 0x00;
+            rom[bank + 0xFFD] = 0xF2;
+        }
+        // Bank 0 switches to bank 1, which continues at $F203
+        rom[0x200..0x203].copy_from_slice(&[0xAD, 0xF9, 0x1F]);
+
+        let mut sys = Atari2600System::new();
+        sys.mount("Cartridge", &rom).unwrap();
+        sys.step_frame().unwrap();
+        assert_eq!(sys.cart_ram().unwrap()[0x10], 0x42);
+        let state = sys.save_state();
+
+        // Exported RAM comes back on a fresh mount, and so does a state
+        let exported = sys.cart_ram().unwrap().to_vec();
+        let mut restored = Atari2600System::new();
+        restored.mount("Cartridge", &rom).unwrap();
+        assert_eq!(restored.cart_ram().unwrap()[0x10], 0);
+        restored.load_cart_ram(&exported).unwrap();
+        assert_eq!(restored.cart_ram().unwrap(), &exported[..]);
+        restored.load_cart_ram(&[0; 128]).unwrap();
+        restored.load_state(&state).unwrap();
+        assert_eq!(restored.cart_ram().unwrap()[0x10], 0x42);
+
+        // Plain 4K cartridges have no RAM to export
+        restored
+            .mount(
+                "Cartridge",
+                include_bytes!("../../../../test_roms/atari2600/test.bin"),
+            )
+            .unwrap();
+        assert!(restored.cart_ram().is_none());
+        assert!(restored.load_cart_ram(&exported).is_err());
+    }
+
+    #[test]
+    fn test_golden_save_state() {
+        // Saved after 30 frames of the test ROM
+        emu_core::save_state::check_golden_state(
+            &mut Atari2600System::new(),
+            "Cartridge",
+            include_bytes!("../../../../test_roms/atari2600/test.bin"),
+            include_str!("../../../../test_roms/atari2600/test.state.json"),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_save_state_resumes_on_fresh_system() {
+        let rom = include_bytes!("../../../../test_roms/atari2600/test.bin");
+        let mut sys = Atari2600System::new();
+        sys.mount("Cartridge", rom).unwrap();
+        for _ in 0..10 {
+            sys.step_frame().unwrap();
+        }
+        let state = sys.save_state();
+        sys.step_frame().unwrap();
+
+        // The cartridge stays mounted and the CPU continues where it left off
+        let mut restored = Atari2600System::new();
+        restored.mount("Cartridge", rom).unwrap();
+        restored.load_state(&state).unwrap();
+        restored.step_frame().unwrap();
+        assert_eq!(restored.save_state(), sys.save_state());
+    }
+
     #[test]
     fn test_atari2600_smoke_test_rom() {
         // Load the test ROM
@@ -358,7 +358,6 @@ impl Riot {
     /// Bit 3: BW/Color (0 = BW, 1 = Color)
     /// Bit 6: Left difficulty (0 = A/Pro, 1 = B/Amateur)
     /// Bit 7: Right difficulty (0 = A/Pro, 1 = B/Amateur)
-    #[allow(dead_code)]
     pub fn set_console_switch(&mut self, bit: u8, pressed: bool) {
         if pressed {
             self.swchb &= !(1 << bit);
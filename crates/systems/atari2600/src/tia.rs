@@ -54,6 +54,18 @@
 //!
 //! This implementation includes a proper NTSC palette table mapping these values to RGB.
 //!
+//! PAL consoles show hues 0, 1, 14 and 15 as gray and step the others around
+//! the color wheel in two interleaved directions, giving 104 distinct colors.
+//! A PAL TV also loses color on frames with an odd number of scanlines.
+//! SECAM consoles ignore the hue bits and map the three luminance bits to a
+//! fixed set of 8 colors. When the console's TV Type switch is set to B&W,
+//! only the luminance is shown as a grayscale level.
+//!
+//! Early TIA revisions ([`TiaRevision::Early`]) mix luminance into the color
+//! signal: the hue drifts as the luminance rises and light shades are paler.
+//! Their playfield registers also take effect one color clock after a write,
+//! which shifts mid-line playfield changes by a pixel in beam rendering.
+//!
 //! ## Priority and Collision
 //!
 //! **Drawing Priority** (when playfield priority is off - default):
@@ -70,9 +82,10 @@
 //! 4. Background
 //!
 //! **Collision Detection**: The TIA has hardware collision detection registers that set bits
-//! when different objects overlap. This implementation tracks collisions pixel-by-pixel during
-//! rendering and updates all 8 collision registers (CXM0P, CXM1P, CXP0FB, CXP1FB, CXM0FB, CXM1FB,
-//! CXBLPF, CXPPMM). Collision registers can be cleared using CXCLR (0x2C).
+//! when different objects overlap. This implementation checks each scanline pixel-by-pixel as
+//! soon as the beam finishes it and updates all 8 collision registers (CXM0P, CXM1P, CXP0FB,
+//! CXP1FB, CXM0FB, CXM1FB, CXBLPF, CXPPMM), so they can be read later in the same frame.
+//! Collision registers can be cleared using CXCLR (0x2C).
 //!
 //! # Audio Generation
 //!
@@ -97,11 +110,14 @@
 //! # Implementation Details
 //!
 //! ## Rendering Model
-//! This implementation uses **frame-based rendering** rather than cycle-accurate scanline generation:
+//! By default this implementation uses **frame-based rendering** rather than cycle-accurate scanline generation:
 //! - TIA state (colors, graphics) is updated during CPU execution
 //! - At frame end, all 192 visible scanlines are rendered at once
 //! - Each pixel's color is determined by checking all graphics objects at that position
 //!
+//! [`TiaAccuracy::Beam`] instead draws each pixel as the beam passes it, so register
+//! writes in the middle of a scanline only affect the pixels after them.
+//!
 //! ## Implemented Features
 //!
 //! 1. **Player/Missile Sizing (NUSIZ)**: Full support for sprite sizing (1x, 2x, 4x) and duplication modes
@@ -110,7 +126,7 @@
 //!
 //! ## Known Limitations
 //!
-//! 1. **Frame-based rendering**: Uses scanline state latching rather than cycle-accurate generation
+//! 1. **Frame-based rendering**: The default mode uses scanline state latching rather than cycle-accurate generation
 //! 2. **Paddle controllers**: Not implemented (INPT0-INPT3 always return 0)
 //!
 //! These limitations represent acceptable trade-offs for a functional emulator. Most games
@@ -158,6 +174,50 @@ struct ScanlineState {
     ball_size: u8, // Ball size (1, 2, 4, or 8 pixels)
 }
 
+/// One snapshot per scanline index (0-261); not saved in save states
+fn blank_scanline_states() -> Vec<ScanlineState> {
+    vec![ScanlineState::default(); 262]
+}
+
+/// One line of beam-drawn pixels (color register values) per scanline; not saved
+fn blank_beam_lines() -> Vec<[u8; 160]> {
+    vec![[0; 160]; 262]
+}
+
+/// How closely the TIA follows the beam when drawing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TiaAccuracy {
+    /// Each scanline is drawn from the register state at the end of the line
+    #[default]
+    Scanline,
+    /// Pixels are drawn as the beam passes them, so mid-line register writes
+    /// (racing the beam) take effect at the current color clock
+    Beam,
+}
+
+/// Color encoding of the console's video output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VideoStandard {
+    /// 128-color NTSC palette (hue + luminance)
+    #[default]
+    Ntsc,
+    /// 104-color PAL palette (hues 0, 1, 14 and 15 are gray)
+    Pal,
+    /// 8 fixed colors selected by luminance only
+    Secam,
+}
+
+/// TIA chip revision
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TiaRevision {
+    /// Early chips: luminance shifts the hue and pales light shades, and
+    /// playfield writes take effect one color clock late
+    Early,
+    /// Later chips with stable colors
+    #[default]
+    Late,
+}
+
 /// TIA chip state
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Tia {
@@ -239,9 +299,15 @@ pub struct Tia {
     scanline_counter: u64,
 
     // Per-scanline state snapshots for rendering
-    #[serde(skip)]
+    #[serde(skip, default = "blank_scanline_states")]
     scanline_states: Vec<ScanlineState>,
 
+    // Rendering accuracy and the pixels drawn by the beam in `TiaAccuracy::Beam`
+    #[serde(skip)]
+    accuracy: TiaAccuracy,
+    #[serde(skip, default = "blank_beam_lines")]
+    beam_lines: Vec<[u8; 160]>,
+
     // Audio channels
     #[serde(skip)]
     audio0: PolynomialCounter,
@@ -283,6 +349,28 @@ pub struct Tia {
     // Cached visible window start (to prevent vertical jumping)
     #[serde(skip)]
     cached_visible_start: Option<u16>,
+
+    // Palette selection (console hardware/switch state, applied by the system)
+    #[serde(skip)]
+    video_standard: VideoStandard,
+    #[serde(skip)]
+    black_and_white: bool,
+    #[serde(skip)]
+    revision: TiaRevision,
+    // RGB for each color register value (bits 1-7), built from the settings above
+    #[serde(skip, default = "default_palette")]
+    palette: [u32; 128],
+    // The TV lost color lock for this frame (odd-length frames on PAL)
+    #[serde(skip)]
+    color_loss: bool,
+    // Pixels of the current scanline already drawn by a late playfield write
+    #[serde(skip)]
+    beam_drawn_to: u16,
+}
+
+/// Palette of a late NTSC TIA
+fn default_palette() -> [u32; 128] {
+    build_palette(VideoStandard::Ntsc, TiaRevision::Late)
 }
 
 impl Default for Tia {
@@ -294,11 +382,22 @@ impl Default for Tia {
 impl Tia {
     // Horizontal timing: ~68 color clocks of horizontal blank, 160 visible
     const HBLANK_COLOR_CLOCKS: i16 = 68;
+    // Color clocks between a RESxx strobe and the object's first pixel
+    const PLAYER_RESET_DELAY: i16 = 5;
+    const OBJECT_RESET_DELAY: i16 = 4;
 
-    /// Get current visible x position (accounting for horizontal blank)
-    fn current_visible_x(&self) -> u8 {
+    /// Position of an object reset by a RESxx strobe at the current pixel
+    ///
+    /// Objects start drawing `delay` color clocks after the strobe (5 for
+    /// players, 4 for missiles and the ball). A strobe during horizontal
+    /// blank takes effect at the start of the line, two clocks earlier.
+    fn reset_position(&self, delay: i16) -> u8 {
         let x = (self.pixel as i16) - Self::HBLANK_COLOR_CLOCKS;
-        x.clamp(0, 159) as u8
+        if x < 0 {
+            (delay - 2) as u8
+        } else {
+            ((x + delay) % 160) as u8
+        }
     }
 
     /// Apply horizontal motion to a position
@@ -373,7 +472,10 @@ impl Tia {
 
             scanline_counter: 0,
 
-            scanline_states: vec![ScanlineState::default(); 262],
+            scanline_states: blank_scanline_states(),
+
+            accuracy: TiaAccuracy::Scanline,
+            beam_lines: blank_beam_lines(),
 
             audio0: PolynomialCounter::new(),
             audio1: PolynomialCounter::new(),
@@ -398,6 +500,55 @@ impl Tia {
             writes_colors_nonzero: 0,
 
             cached_visible_start: None,
+
+            video_standard: VideoStandard::Ntsc,
+            black_and_white: false,
+            revision: TiaRevision::Late,
+            palette: default_palette(),
+            color_loss: false,
+            beam_drawn_to: 0,
+        }
+    }
+
+    /// Select the palette used to convert color registers to RGB
+    pub fn set_palette(&mut self, video_standard: VideoStandard, black_and_white: bool) {
+        self.video_standard = video_standard;
+        self.black_and_white = black_and_white;
+        self.palette = build_palette(self.video_standard, self.revision);
+    }
+
+    /// Select the chip revision, which affects colors and playfield timing
+    pub fn set_revision(&mut self, revision: TiaRevision) {
+        self.revision = revision;
+        self.palette = build_palette(self.video_standard, self.revision);
+    }
+
+    /// Get the chip revision
+    pub fn revision(&self) -> TiaRevision {
+        self.revision
+    }
+
+    /// Show the next frames without color, as a TV that lost color lock
+    pub fn set_color_loss(&mut self, color_loss: bool) {
+        self.color_loss = color_loss;
+    }
+
+    /// Select how closely rendering follows the beam
+    pub fn set_accuracy(&mut self, accuracy: TiaAccuracy) {
+        self.accuracy = accuracy;
+    }
+
+    /// Get the rendering accuracy
+    pub fn accuracy(&self) -> TiaAccuracy {
+        self.accuracy
+    }
+
+    /// Convert a color register value to RGB using the selected palette
+    fn color_to_rgb(&self, color: u8) -> u32 {
+        if self.black_and_white || self.color_loss {
+            grayscale_to_rgb(color)
+        } else {
+            self.palette[(color >> 1) as usize]
         }
     }
 
@@ -473,7 +624,12 @@ impl Tia {
         }
 
         let idx = (scanline as usize).min(261);
-        self.scanline_states[idx] = ScanlineState {
+        self.scanline_states[idx] = self.current_state();
+    }
+
+    /// Snapshot of the registers as they affect the pixels being drawn now
+    fn current_state(&self) -> ScanlineState {
+        ScanlineState {
             vblank: self.vblank,
             pf0: self.pf0,
             pf1: self.pf1,
@@ -506,8 +662,16 @@ impl Tia {
             vdelp1: self.vdelp1,
             enam0: self.enam0,
             enam1: self.enam1,
-            missile0_x: self.missile0_x,
-            missile1_x: self.missile1_x,
+            missile0_x: if self.resmp0 {
+                self.player0_x.saturating_add(4)
+            } else {
+                self.missile0_x
+            },
+            missile1_x: if self.resmp1 {
+                self.player1_x.saturating_add(4)
+            } else {
+                self.missile1_x
+            },
             enabl: if self.vdelbl {
                 (self.enabl_old & 0x02) != 0
             } else {
@@ -515,7 +679,24 @@ impl Tia {
             },
             ball_x: self.ball_x,
             ball_size: self.ball_size,
-        };
+        }
+    }
+
+    /// Draw the visible pixels the beam passes between two color clocks of the
+    /// current scanline, recording their collisions as it goes
+    fn draw_beam_pixels(&mut self, from: u16, to: u16) {
+        let state = self.current_state();
+        let line = (self.scanline as usize).min(261);
+        let start = from.max(Self::HBLANK_COLOR_CLOCKS as u16);
+        let end = to.min(228);
+
+        for clock in start..end {
+            let x = (clock - Self::HBLANK_COLOR_CLOCKS as u16) as usize;
+            self.beam_lines[line][x] = Self::get_pixel_color(&state, x);
+            if !state.vblank {
+                self.record_collisions(&state, x);
+            }
+        }
     }
 
     /// Reset TIA to power-on state
@@ -537,6 +718,16 @@ impl Tia {
             );
         }
 
+        // Early TIAs latch the playfield one color clock late, so the beam
+        // draws one more pixel with the old bits
+        if matches!(addr, 0x0D..=0x0F)
+            && self.revision == TiaRevision::Early
+            && self.accuracy == TiaAccuracy::Beam
+        {
+            self.draw_beam_pixels(self.pixel, self.pixel + 1);
+            self.beam_drawn_to = self.pixel + 1;
+        }
+
         match addr {
             0x00 => {
                 self.writes_vsync = self.writes_vsync.saturating_add(1);
@@ -634,21 +825,21 @@ impl Tia {
             }
 
             // Player position resets (RESP0, RESP1, RESM0, RESM1, RESBL)
-            0x10 => self.player0_x = self.current_visible_x(),
-            0x11 => self.player1_x = self.current_visible_x(),
+            0x10 => self.player0_x = self.reset_position(Self::PLAYER_RESET_DELAY),
+            0x11 => self.player1_x = self.reset_position(Self::PLAYER_RESET_DELAY),
             0x12 => {
                 if !self.resmp0 {
                     // Only set position if not locked to player
-                    self.missile0_x = self.current_visible_x();
+                    self.missile0_x = self.reset_position(Self::OBJECT_RESET_DELAY);
                 }
             }
             0x13 => {
                 if !self.resmp1 {
                     // Only set position if not locked to player
-                    self.missile1_x = self.current_visible_x();
+                    self.missile1_x = self.reset_position(Self::OBJECT_RESET_DELAY);
                 }
             }
-            0x14 => self.ball_x = self.current_visible_x(),
+            0x14 => self.ball_x = self.reset_position(Self::OBJECT_RESET_DELAY),
 
             // Audio
             0x15 => {
@@ -685,8 +876,9 @@ impl Tia {
                         eprintln!("[TIA] GRP0 = 0x{:02X} at scanline {}", val, self.scanline);
                     }
                 }
-                self.grp0_old = self.grp0; // Save old value before writing new
+                // Writing GRP0 copies GRP1 into its delayed register
                 self.grp0 = val;
+                self.grp1_old = self.grp1;
             }
             0x1C => {
                 self.writes_grp1 = self.writes_grp1.saturating_add(1);
@@ -696,17 +888,16 @@ impl Tia {
                         eprintln!("[TIA] GRP1 = 0x{:02X} at scanline {}", val, self.scanline);
                     }
                 }
-                self.grp1_old = self.grp1; // Save old value before writing new
+                // Writing GRP1 copies GRP0 and ENABL into their delayed registers
                 self.grp1 = val;
+                self.grp0_old = self.grp0;
+                self.enabl_old = if self.enabl { 0x02 } else { 0x00 };
             }
 
             // Enable missiles and ball
             0x1D => self.enam0 = (val & 0x02) != 0,
             0x1E => self.enam1 = (val & 0x02) != 0,
-            0x1F => {
-                self.enabl_old = if self.enabl { 0x02 } else { 0x00 }; // Save old value
-                self.enabl = (val & 0x02) != 0;
-            }
+            0x1F => self.enabl = (val & 0x02) != 0,
 
             // Horizontal motion
             0x20 => self.hmp0 = (val as i8) >> 4,
@@ -783,7 +974,11 @@ impl Tia {
 
     /// Clock the TIA for one CPU cycle (3 color clocks)
     pub fn clock(&mut self) {
-        // Simplified: just advance pixel counter
+        if self.accuracy == TiaAccuracy::Beam {
+            let from = self.pixel.max(std::mem::take(&mut self.beam_drawn_to));
+            self.draw_beam_pixels(from, self.pixel + 3);
+        }
+
         self.pixel += 3; // 3 color clocks per CPU cycle
 
         if self.pixel >= 228 {
@@ -794,6 +989,13 @@ impl Tia {
             // This ensures we capture the final state of the scanline after all register writes
             self.latch_scanline_state(old_scanline);
 
+            // Collisions latch as the beam draws the line, so the CPU can read
+            // them later in the same frame (games usually check in overscan).
+            // In beam mode they were already recorded pixel by pixel.
+            if self.accuracy == TiaAccuracy::Scanline {
+                self.detect_collisions_for_scanline(old_scanline);
+            }
+
             self.scanline += 1;
 
             self.scanline_counter = self.scanline_counter.saturating_add(1);
@@ -848,6 +1050,9 @@ impl Tia {
         for state in &mut self.scanline_states {
             *state = ScanlineState::default();
         }
+        for line in &mut self.beam_lines {
+            *line = [0; 160];
+        }
         // DO NOT clear cached_visible_start here - it must persist across frames
         // to prevent vertical jumping (as documented in visible_window_start_scanline)
 
@@ -986,22 +1191,26 @@ impl Tia {
             return; // Only visible lines
         }
 
+        let line = (tia_scanline as usize).min(261);
+        if self.accuracy == TiaAccuracy::Beam {
+            for (x, &color) in self.beam_lines[line].iter().enumerate() {
+                buffer[visible_line * 160 + x] = self.color_to_rgb(color);
+            }
+            return;
+        }
+
         // Get latched state for this scanline
-        let state = self
-            .scanline_states
-            .get((tia_scanline as usize).min(261))
-            .copied()
-            .unwrap_or_default();
+        let state = self.scanline_states.get(line).copied().unwrap_or_default();
 
         // Atari 2600 has 160 pixels per scanline
         for x in 0..160 {
             let color = Self::get_pixel_color(&state, x);
-            buffer[visible_line * 160 + x] = color;
+            buffer[visible_line * 160 + x] = self.color_to_rgb(color);
         }
     }
 
-    /// Detect and record collisions for a scanline (called during frame rendering)
-    /// This should be called once per scanline to update collision registers
+    /// Detect and record collisions for a scanline
+    /// Called from `clock` once the scanline's final state has been latched
     fn detect_collisions_for_scanline(&mut self, tia_scanline: u16) {
         let state = self
             .scanline_states
@@ -1009,93 +1218,93 @@ impl Tia {
             .copied()
             .unwrap_or_default();
 
+        // Nothing is drawn while the beam is blanked
+        if state.vblank {
+            return;
+        }
+
         // Check all 160 pixels for collisions
         for x in 0..160 {
-            let p0 = Self::is_player_pixel(&state, 0, x);
-            let p1 = Self::is_player_pixel(&state, 1, x);
-            let m0 = Self::is_missile_pixel(&state, 0, x);
-            let m1 = Self::is_missile_pixel(&state, 1, x);
-            let bl = Self::is_ball_pixel(&state, x);
-            let pf = Self::is_playfield_pixel(&state, x);
-
-            // Missile 0 to Player collisions (CXM0P)
-            if m0 && p1 {
-                self.cxm0p |= 0x80; // M0P1
-            }
-            if m0 && p0 {
-                self.cxm0p |= 0x40; // M0P0
-            }
+            self.record_collisions(&state, x);
+        }
+    }
 
-            // Missile 1 to Player collisions (CXM1P)
-            if m1 && p0 {
-                self.cxm1p |= 0x80; // M1P0
-            }
-            if m1 && p1 {
-                self.cxm1p |= 0x40; // M1P1
-            }
+    /// Set the collision latches for the objects overlapping at pixel `x`
+    fn record_collisions(&mut self, state: &ScanlineState, x: usize) {
+        let p0 = Self::is_player_pixel(state, 0, x);
+        let p1 = Self::is_player_pixel(state, 1, x);
+        let m0 = Self::is_missile_pixel(state, 0, x);
+        let m1 = Self::is_missile_pixel(state, 1, x);
+        let bl = Self::is_ball_pixel(state, x);
+        let pf = Self::is_playfield_pixel(state, x);
+
+        // Missile 0 to Player collisions (CXM0P)
+        if m0 && p1 {
+            self.cxm0p |= 0x80; // M0P1
+        }
+        if m0 && p0 {
+            self.cxm0p |= 0x40; // M0P0
+        }
 
-            // Player 0 to Playfield/Ball collisions (CXP0FB)
-            if p0 && pf {
-                self.cxp0fb |= 0x80; // P0PF
-            }
-            if p0 && bl {
-                self.cxp0fb |= 0x40; // P0BL
-            }
+        // Missile 1 to Player collisions (CXM1P)
+        if m1 && p0 {
+            self.cxm1p |= 0x80; // M1P0
+        }
+        if m1 && p1 {
+            self.cxm1p |= 0x40; // M1P1
+        }
 
-            // Player 1 to Playfield/Ball collisions (CXP1FB)
-            if p1 && pf {
-                self.cxp1fb |= 0x80; // P1PF
-            }
-            if p1 && bl {
-                self.cxp1fb |= 0x40; // P1BL
-            }
+        // Player 0 to Playfield/Ball collisions (CXP0FB)
+        if p0 && pf {
+            self.cxp0fb |= 0x80; // P0PF
+        }
+        if p0 && bl {
+            self.cxp0fb |= 0x40; // P0BL
+        }
 
-            // Missile 0 to Playfield/Ball collisions (CXM0FB)
-            if m0 && pf {
-                self.cxm0fb |= 0x80; // M0PF
-            }
-            if m0 && bl {
-                self.cxm0fb |= 0x40; // M0BL
-            }
+        // Player 1 to Playfield/Ball collisions (CXP1FB)
+        if p1 && pf {
+            self.cxp1fb |= 0x80; // P1PF
+        }
+        if p1 && bl {
+            self.cxp1fb |= 0x40; // P1BL
+        }
 
-            // Missile 1 to Playfield/Ball collisions (CXM1FB)
-            if m1 && pf {
-                self.cxm1fb |= 0x80; // M1PF
-            }
-            if m1 && bl {
-                self.cxm1fb |= 0x40; // M1BL
-            }
+        // Missile 0 to Playfield/Ball collisions (CXM0FB)
+        if m0 && pf {
+            self.cxm0fb |= 0x80; // M0PF
+        }
+        if m0 && bl {
+            self.cxm0fb |= 0x40; // M0BL
+        }
 
-            // Ball to Playfield collisions (CXBLPF)
-            if bl && pf {
-                self.cxblpf |= 0x80; // BLPF
-            }
+        // Missile 1 to Playfield/Ball collisions (CXM1FB)
+        if m1 && pf {
+            self.cxm1fb |= 0x80; // M1PF
+        }
+        if m1 && bl {
+            self.cxm1fb |= 0x40; // M1BL
+        }
 
-            // Player and Missile collisions (CXPPMM)
-            if m0 && m1 {
-                self.cxppmm |= 0x80; // M0M1
-            }
-            if p0 && p1 {
-                self.cxppmm |= 0x40; // P0P1
-            }
+        // Ball to Playfield collisions (CXBLPF)
+        if bl && pf {
+            self.cxblpf |= 0x80; // BLPF
         }
-    }
 
-    /// Detect collisions for the entire frame (should be called after rendering)
-    /// This updates the collision registers based on the current frame state
-    pub fn detect_collisions_for_frame(&mut self, visible_start: u16) {
-        // Detect collisions for all 192 visible scanlines
-        for visible_line in 0..192 {
-            let tia_scanline = (visible_start + visible_line) % 262;
-            self.detect_collisions_for_scanline(tia_scanline);
+        // Player and Missile collisions (CXPPMM)
+        if m0 && m1 {
+            self.cxppmm |= 0x80; // M0M1
+        }
+        if p0 && p1 {
+            self.cxppmm |= 0x40; // P0P1
         }
     }
 
-    /// Get the color of a pixel at the given position using latched state
-    fn get_pixel_color(state: &ScanlineState, x: usize) -> u32 {
+    /// Get the color register value of a pixel at the given position using latched state
+    fn get_pixel_color(state: &ScanlineState, x: usize) -> u8 {
         // During VBLANK, all pixels are black (video signal is blanked)
         if state.vblank {
-            return 0xFF000000; // Black
+            return 0x00; // Black
         }
 
         // Priority order (when playfield priority is off):
@@ -1115,58 +1324,58 @@ impl Tia {
         if !state.playfield_priority {
             // Check Player 0
             if Self::is_player_pixel(state, 0, x) {
-                return ntsc_to_rgb(state.colup0);
+                return state.colup0;
             }
 
             // Check Missile 0
             if Self::is_missile_pixel(state, 0, x) {
-                return ntsc_to_rgb(state.colup0);
+                return state.colup0;
             }
 
             // Check Player 1
             if Self::is_player_pixel(state, 1, x) {
-                return ntsc_to_rgb(state.colup1);
+                return state.colup1;
             }
 
             // Check Missile 1
             if Self::is_missile_pixel(state, 1, x) {
-                return ntsc_to_rgb(state.colup1);
+                return state.colup1;
             }
 
             // Check Ball
             if Self::is_ball_pixel(state, x) {
-                return ntsc_to_rgb(state.colupf);
+                return state.colupf;
             }
         }
 
         // Check playfield
         if Self::is_playfield_pixel(state, x) {
-            return ntsc_to_rgb(state.colupf);
+            return state.colupf;
         }
 
         // Check Ball (if playfield priority)
         if state.playfield_priority && Self::is_ball_pixel(state, x) {
-            return ntsc_to_rgb(state.colupf);
+            return state.colupf;
         }
 
         // Check players and missiles (if playfield priority)
         if state.playfield_priority {
             if Self::is_player_pixel(state, 0, x) {
-                return ntsc_to_rgb(state.colup0);
+                return state.colup0;
             }
             if Self::is_missile_pixel(state, 0, x) {
-                return ntsc_to_rgb(state.colup0);
+                return state.colup0;
             }
             if Self::is_player_pixel(state, 1, x) {
-                return ntsc_to_rgb(state.colup1);
+                return state.colup1;
             }
             if Self::is_missile_pixel(state, 1, x) {
-                return ntsc_to_rgb(state.colup1);
+                return state.colup1;
             }
         }
 
         // Background color
-        ntsc_to_rgb(state.colubk)
+        state.colubk
     }
 
     /// Check if a player pixel is visible at the given x position
@@ -1190,35 +1399,17 @@ impl Tia {
         // NUSIZ bits 0-2 control number and size
         // Bits 0-2: 000=one, 001=two close, 010=two medium, 011=three close,
         //           100=two wide, 101=double size, 110=three medium, 111=quad size
-        let nusiz_mode = nusiz & 0x07;
-
-        // Get player size (1x, 2x, or 4x)
-        let player_size = match nusiz_mode {
-            0x05 => 2, // Double width (2x)
-            0x07 => 4, // Quad width (4x)
-            _ => 1,    // Normal width (1x)
+        // Stretched players start one pixel later than normal ones, since the
+        // TIA's scan counter takes an extra clock to start at the lower rate.
+        let (player_size, delay) = match nusiz & 0x07 {
+            0x05 => (2, 1), // Double width (2x)
+            0x07 => (4, 1), // Quad width (4x)
+            _ => (1, 0),    // Normal width (1x)
         };
 
-        // Get number of copies and their spacing
-        let (num_copies, spacing) = match nusiz_mode {
-            0x00 => (1, 0),  // One copy
-            0x01 => (2, 16), // Two copies close together
-            0x02 => (2, 32), // Two copies medium spacing
-            0x03 => (3, 16), // Three copies close together
-            0x04 => (2, 64), // Two copies wide spacing
-            0x05 => (1, 0),  // One double-width copy
-            0x06 => (3, 32), // Three copies medium spacing
-            0x07 => (1, 0),  // One quad-width copy
-            _ => (1, 0),
-        };
-
-        // Check each copy
-        for copy in 0..num_copies {
-            let copy_pos = pos as usize + copy * spacing;
-            if copy_pos >= 160 {
-                continue;
-            }
-            let offset = x.wrapping_sub(copy_pos);
+        // Check each copy; copies and sprites past the right edge wrap around
+        for &copy in Self::nusiz_copy_offsets(nusiz) {
+            let offset = Self::wrapped_offset(x, pos as usize + copy + delay);
 
             if offset < 8 * player_size {
                 // Which pixel of the 8-pixel sprite?
@@ -1262,34 +1453,29 @@ impl Tia {
             _ => 1,
         };
 
-        // Missiles use the same duplication pattern as players (bits 0-2)
-        let nusiz_mode = nusiz & 0x07;
-        let (num_copies, spacing) = match nusiz_mode {
-            0x00 => (1, 0),  // One copy
-            0x01 => (2, 16), // Two copies close together
-            0x02 => (2, 32), // Two copies medium spacing
-            0x03 => (3, 16), // Three copies close together
-            0x04 => (2, 64), // Two copies wide spacing
-            0x05 => (1, 0),  // One copy (double size doesn't affect missiles)
-            0x06 => (3, 32), // Three copies medium spacing
-            0x07 => (1, 0),  // One copy (quad size doesn't affect missiles)
-            _ => (1, 0),
-        };
-
-        // Check each copy
-        for copy in 0..num_copies {
-            let copy_pos = pos as usize + copy * spacing;
-            if copy_pos >= 160 {
-                continue;
-            }
-            let offset = x.wrapping_sub(copy_pos);
+        // Missiles use the same duplication pattern as players (bits 0-2);
+        // double and quad player sizes don't stretch them
+        Self::nusiz_copy_offsets(nusiz)
+            .iter()
+            .any(|&copy| Self::wrapped_offset(x, pos as usize + copy) < missile_size)
+    }
 
-            if offset < missile_size {
-                return true;
-            }
+    /// Horizontal offsets of the player/missile copies selected by NUSIZ bits 0-2
+    fn nusiz_copy_offsets(nusiz: u8) -> &'static [usize] {
+        match nusiz & 0x07 {
+            0x01 => &[0, 16],     // Two copies close together
+            0x02 => &[0, 32],     // Two copies medium spacing
+            0x03 => &[0, 16, 32], // Three copies close together
+            0x04 => &[0, 64],     // Two copies wide spacing
+            0x06 => &[0, 32, 64], // Three copies medium spacing
+            _ => &[0],            // One copy (modes 5 and 7 stretch the player instead)
         }
+    }
 
-        false
+    /// Distance of pixel `x` from an object starting at `start`, wrapping
+    /// around the 160-pixel line like the TIA's position counters
+    fn wrapped_offset(x: usize, start: usize) -> usize {
+        (x + 160 - start % 160) % 160
     }
 
     /// Check if the ball pixel is visible at the given x position
@@ -1423,9 +1609,111 @@ fn ntsc_to_rgb(ntsc: u8) -> u32 {
         0xFFFCE08C,
     ];
 
-    // Mask to 7 bits to ensure we're within the 128-color palette bounds
     // NTSC color encoding only uses bits 1-7 (bit 0 is unused)
-    NTSC_PALETTE[ntsc as usize & 0x7F]
+    NTSC_PALETTE[(ntsc >> 1) as usize]
+}
+
+/// Gray level of each luminance value
+const GRAY_LEVELS: [u32; 8] = [0x00, 0x40, 0x6C, 0x90, 0xB0, 0xC8, 0xDC, 0xEC];
+
+/// Convert a color register value to a grayscale level (B&W TV type)
+/// Only the luminance bits (1-3) are used
+fn grayscale_to_rgb(color: u8) -> u32 {
+    let level = GRAY_LEVELS[((color >> 1) & 0x07) as usize];
+    0xFF000000 | (level << 16) | (level << 8) | level
+}
+
+/// PAL hue 2 (yellow) as an angle in the U/V plane, in degrees
+const PAL_FIRST_HUE_ANGLE: f64 = 167.0;
+/// Phase step between PAL hues of the same parity, in degrees
+const PAL_HUE_STEP: f64 = 30.0;
+/// PAL color saturation (chroma amplitude on the 0-255 scale)
+const PAL_CHROMA: f64 = 60.0;
+
+/// Convert a color register value to RGB on a PAL console
+///
+/// Hues 0, 1, 14 and 15 are gray. The others start at yellow and step
+/// around the color wheel in opposite directions, even hues through red and
+/// odd hues through green, meeting at blue.
+fn pal_to_rgb(color: u8) -> u32 {
+    let hue = color >> 4;
+    let angle = match hue {
+        0 | 1 | 14 | 15 => return grayscale_to_rgb(color),
+        h if h % 2 == 0 => PAL_FIRST_HUE_ANGLE - PAL_HUE_STEP * f64::from((h - 2) / 2),
+        h => PAL_FIRST_HUE_ANGLE + PAL_HUE_STEP * f64::from((h - 1) / 2),
+    };
+    let y = f64::from(GRAY_LEVELS[((color >> 1) & 0x07) as usize]);
+    let (sin, cos) = angle.to_radians().sin_cos();
+    yuv_to_rgb(y, PAL_CHROMA * cos, PAL_CHROMA * sin)
+}
+
+/// Hue drift per luminance step on early TIAs, in degrees
+const EARLY_PHASE_SHIFT: f64 = 4.0;
+/// Saturation lost per luminance step on early TIAs
+const EARLY_SATURATION_LOSS: f64 = 0.06;
+
+/// Apply an early TIA's luminance-to-chroma crosstalk to a palette color
+fn early_tia_color(rgb: u32, luminance: u8) -> u32 {
+    let (y, u, v) = rgb_to_yuv(rgb);
+    let (sin, cos) = (f64::from(luminance) * EARLY_PHASE_SHIFT)
+        .to_radians()
+        .sin_cos();
+    let gain = 1.0 - f64::from(luminance) * EARLY_SATURATION_LOSS;
+    yuv_to_rgb(y, (u * cos - v * sin) * gain, (u * sin + v * cos) * gain)
+}
+
+/// Split an ARGB color into luma and chroma (0-255 scale)
+fn rgb_to_yuv(rgb: u32) -> (f64, f64, f64) {
+    let r = f64::from((rgb >> 16) & 0xFF);
+    let g = f64::from((rgb >> 8) & 0xFF);
+    let b = f64::from(rgb & 0xFF);
+    let y = 0.299 * r + 0.587 * g + 0.114 * b;
+    (y, 0.492 * (b - y), 0.877 * (r - y))
+}
+
+/// Combine luma and chroma (0-255 scale) into an ARGB color
+fn yuv_to_rgb(y: f64, u: f64, v: f64) -> u32 {
+    let r = y + v / 0.877;
+    let b = y + u / 0.492;
+    let g = (y - 0.299 * r - 0.114 * b) / 0.587;
+    let channel = |c: f64| c.round().clamp(0.0, 255.0) as u32;
+    0xFF000000 | (channel(r) << 16) | (channel(g) << 8) | channel(b)
+}
+
+/// Build the RGB palette for each color register value (indexed by bits 1-7)
+fn build_palette(standard: VideoStandard, revision: TiaRevision) -> [u32; 128] {
+    let mut palette = [0; 128];
+    for (index, entry) in palette.iter_mut().enumerate() {
+        let color = (index as u8) << 1;
+        let rgb = match standard {
+            VideoStandard::Ntsc => ntsc_to_rgb(color),
+            VideoStandard::Pal => pal_to_rgb(color),
+            VideoStandard::Secam => secam_to_rgb(color),
+        };
+        // The SECAM encoder outputs fixed colors, so crosstalk does not apply
+        *entry = if revision == TiaRevision::Early && standard != VideoStandard::Secam {
+            early_tia_color(rgb, (color >> 1) & 0x07)
+        } else {
+            rgb
+        };
+    }
+    palette
+}
+
+/// Convert a color register value to RGB on a SECAM console
+/// SECAM ignores the hue and maps the luminance bits to 8 fixed colors
+fn secam_to_rgb(color: u8) -> u32 {
+    const SECAM_PALETTE: [u32; 8] = [
+        0xFF000000, // Black
+        0xFF2121FF, // Blue
+        0xFFF03C79, // Red
+        0xFFFF50FF, // Magenta
+        0xFF7FFF00, // Green
+        0xFF7FFFFF, // Cyan
+        0xFFFFFF3F, // Yellow
+        0xFFFFFFFF, // White
+    ];
+    SECAM_PALETTE[((color >> 1) & 0x07) as usize]
 }
 
 #[cfg(test)]
@@ -1601,7 +1889,7 @@ mod tests {
     fn test_tia_ball_size() {
         let mut tia = Tia::new();
 
-        tia.write(0x14, 0x00); // RESBL - position ball at x=0
+        tia.ball_x = 0;
         tia.write(0x1F, 0x02); // ENABL - enable ball
 
         // Test 1-pixel ball (CTRLPF bits 4-5 = 00)
@@ -1644,39 +1932,41 @@ mod tests {
     fn test_vdelbl_delayed_ball_graphics() {
         let mut tia = Tia::new();
 
-        tia.write(0x14, 0x00); // RESBL - position ball at x=0
+        tia.write(0x14, 0x00); // RESBL
         tia.write(0x08, 0x0E); // COLUPF - set color
 
         // Enable delayed ball graphics
         tia.write(0x27, 0x01); // VDELBL
 
-        // Enable ball first
+        // Enable ball: the delayed copy is still off
         tia.write(0x1F, 0x02); // ENABL = 1
         tia.latch_scanline_state(0);
-        let state = tia.scanline_states[0];
-        // With VDELBL, should still be using old value (which was 0 at start)
-        assert!(!state.enabl);
+        assert!(!tia.scanline_states[0].enabl);
 
-        // Write again to trigger the delay
-        tia.write(0x1F, 0x02); // ENABL = 1 again
+        // Rewriting ENABL does not update the delayed copy
+        tia.write(0x1F, 0x02);
         tia.latch_scanline_state(1);
-        let state = tia.scanline_states[1];
-        // Now it should show the previous write's enabled state
-        assert!(state.enabl);
+        assert!(!tia.scanline_states[1].enabl);
 
-        // Disable ball
-        tia.write(0x1F, 0x00); // ENABL = 0
+        // A GRP1 write copies ENABL into the delayed register
+        tia.write(0x1C, 0x00); // GRP1
         tia.latch_scanline_state(2);
-        let state = tia.scanline_states[2];
-        // Should still be enabled (showing previous state)
-        assert!(state.enabl);
+        assert!(tia.scanline_states[2].enabl);
 
-        // Write again to update the delay
-        tia.write(0x1F, 0x00); // ENABL = 0 again
+        // Disabling the ball keeps the delayed copy until the next GRP1 write
+        tia.write(0x1F, 0x00); // ENABL = 0
         tia.latch_scanline_state(3);
-        let state = tia.scanline_states[3];
-        // Now it should be disabled
-        assert!(!state.enabl);
+        assert!(tia.scanline_states[3].enabl);
+
+        tia.write(0x1C, 0x00); // GRP1
+        tia.latch_scanline_state(4);
+        assert!(!tia.scanline_states[4].enabl);
+
+        // Without VDELBL the current value is used
+        tia.write(0x1F, 0x02);
+        tia.write(0x27, 0x00);
+        tia.latch_scanline_state(5);
+        assert!(tia.scanline_states[5].enabl);
     }
 
     #[test]
@@ -1684,14 +1974,17 @@ mod tests {
         let mut tia = Tia::new();
 
         // Position player 0 at x=50 (pixel is in color clocks, not screen pixels)
-        tia.pixel = 68 + 50; // HBLANK + 50 color clocks
+        // (players start 5 color clocks after the strobe)
+        tia.pixel = 68 + 45; // HBLANK + 45 color clocks
         tia.write(0x10, 0x00); // RESP0
+        assert_eq!(tia.player0_x, 50);
 
         // Enable missile 0
         tia.write(0x1D, 0x02); // ENAM0
 
-        // Position missile 0 at x=10 initially (without RESMP)
-        tia.pixel = 68 + 10;
+        // Position missile 0 at x=10 initially (without RESMP); missiles
+        // start 4 color clocks after the strobe
+        tia.pixel = 68 + 6;
         tia.write(0x12, 0x00); // RESM0
         assert_eq!(tia.missile0_x, 10);
 
@@ -1710,7 +2003,7 @@ mod tests {
 
         // Disable RESMP0
         tia.write(0x28, 0x00); // RESMP0 = 0
-        tia.pixel = 68 + 20;
+        tia.pixel = 68 + 16;
         tia.write(0x12, 0x00); // RESM0 - should work now
         assert_eq!(tia.missile0_x, 20); // Free to move again
     }
@@ -1784,6 +2077,79 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_palette_selection() {
+        let mut tia = Tia::new();
+
+        // NTSC color by default
+        assert_eq!(tia.color_to_rgb(0x44), ntsc_to_rgb(0x44));
+
+        // B&W: same luminance gives the same gray regardless of hue
+        tia.set_palette(VideoStandard::Ntsc, true);
+        assert_eq!(tia.color_to_rgb(0x44), tia.color_to_rgb(0x84));
+        assert_eq!(tia.color_to_rgb(0x00), 0xFF000000);
+        assert_eq!(tia.color_to_rgb(0x0E), 0xFFECECEC);
+
+        // SECAM: luminance selects one of 8 fixed colors, hue is ignored
+        tia.set_palette(VideoStandard::Secam, false);
+        assert_eq!(tia.color_to_rgb(0x02), 0xFF2121FF);
+        assert_eq!(tia.color_to_rgb(0xF2), 0xFF2121FF);
+        assert_eq!(tia.color_to_rgb(0x0E), 0xFFFFFFFF);
+
+        // SECAM with B&W switch is grayscale too
+        tia.set_palette(VideoStandard::Secam, true);
+        assert_eq!(tia.color_to_rgb(0x0E), 0xFFECECEC);
+
+        // PAL: hues 0, 1, 14 and 15 are gray, the others are colored
+        tia.set_palette(VideoStandard::Pal, false);
+        assert_eq!(tia.color_to_rgb(0x16), 0xFF909090);
+        assert_eq!(tia.color_to_rgb(0xE4), 0xFF6C6C6C);
+        assert_ne!(tia.color_to_rgb(0x24), 0xFF6C6C6C);
+        assert_ne!(tia.color_to_rgb(0x24), tia.color_to_rgb(0x34));
+
+        // A TV that lost color lock shows luminance only
+        tia.set_color_loss(true);
+        assert_eq!(tia.color_to_rgb(0x24), 0xFF6C6C6C);
+    }
+
+    #[test]
+    fn test_pal_hue_pairs() {
+        let channels = |rgb: u32| ((rgb >> 16) & 0xFF, (rgb >> 8) & 0xFF, rgb & 0xFF);
+        // Both sequences start near yellow and end near blue
+        let (r, g, b) = channels(pal_to_rgb(0x28));
+        assert!(r > b && g > b);
+        let (r, g, b) = channels(pal_to_rgb(0xD8));
+        assert!(b > r && b > g);
+        // Even hues pass through red, odd hues through green
+        let (r, g, _) = channels(pal_to_rgb(0x68));
+        assert!(r > g);
+        let (r, g, _) = channels(pal_to_rgb(0x58));
+        assert!(g > r);
+    }
+
+    #[test]
+    fn test_early_revision_palette() {
+        let mut tia = Tia::new();
+        tia.set_revision(TiaRevision::Early);
+        assert_eq!(tia.revision(), TiaRevision::Early);
+
+        // Grays and the darkest shade are unaffected
+        assert_eq!(tia.color_to_rgb(0x0A), ntsc_to_rgb(0x0A));
+        assert_eq!(tia.color_to_rgb(0x40), ntsc_to_rgb(0x40));
+        // Brighter shades drift in hue and lose saturation
+        assert_ne!(tia.color_to_rgb(0x4C), ntsc_to_rgb(0x4C));
+        let saturation = |rgb: u32| {
+            let c = [(rgb >> 16) & 0xFF, (rgb >> 8) & 0xFF, rgb & 0xFF];
+            c.iter().max().unwrap() - c.iter().min().unwrap()
+        };
+        assert!(saturation(tia.color_to_rgb(0x4C)) < saturation(ntsc_to_rgb(0x4C)));
+
+        // SECAM colors come from the encoder and stay fixed
+        tia.set_palette(VideoStandard::Secam, false);
+        assert_eq!(tia.color_to_rgb(0x0E), 0xFFFFFFFF);
+        assert_eq!(tia.color_to_rgb(0x04), 0xFFF03C79);
+    }
+
     #[test]
     fn test_nusiz_normal_width() {
         let mut tia = Tia::new();
@@ -1818,10 +2184,11 @@ mod tests {
         tia.latch_scanline_state(0);
         tia.render_scanline(&mut frame, 0, 0);
 
-        // Double width: 16 pixels (8 pixels * 2)
-        assert_ne!(frame[80], ntsc_to_rgb(0));
-        assert_ne!(frame[95], ntsc_to_rgb(0));
-        assert_eq!(frame[96], ntsc_to_rgb(0)); // Outside sprite
+        // Double width: 16 pixels (8 pixels * 2), starting one pixel late
+        assert_eq!(frame[80], ntsc_to_rgb(0));
+        assert_ne!(frame[81], ntsc_to_rgb(0));
+        assert_ne!(frame[96], ntsc_to_rgb(0));
+        assert_eq!(frame[97], ntsc_to_rgb(0)); // Outside sprite
     }
 
     #[test]
@@ -1838,10 +2205,60 @@ mod tests {
         tia.latch_scanline_state(0);
         tia.render_scanline(&mut frame, 0, 0);
 
-        // Quad width: 32 pixels (8 pixels * 4)
-        assert_ne!(frame[80], ntsc_to_rgb(0));
-        assert_ne!(frame[111], ntsc_to_rgb(0));
-        assert_eq!(frame[112], ntsc_to_rgb(0)); // Outside sprite
+        // Quad width: 32 pixels (8 pixels * 4), starting one pixel late
+        assert_eq!(frame[80], ntsc_to_rgb(0));
+        assert_ne!(frame[81], ntsc_to_rgb(0));
+        assert_ne!(frame[112], ntsc_to_rgb(0));
+        assert_eq!(frame[113], ntsc_to_rgb(0)); // Outside sprite
+    }
+
+    #[test]
+    fn test_nusiz_copies_wrap_around() {
+        let mut tia = Tia::new();
+
+        // Two copies wide (mode 100): the second copy lands past the right edge
+        tia.write(0x04, 0x04);
+        tia.player0_x = 156;
+        tia.write(0x1B, 0xFF); // GRP0
+        tia.write(0x06, 0x28); // COLUP0
+
+        let mut frame = vec![0u32; 160];
+        tia.latch_scanline_state(0);
+        tia.render_scanline(&mut frame, 0, 0);
+
+        // First copy is split across the edge: 156..=159 and 0..=3
+        assert_eq!(frame[155], ntsc_to_rgb(0));
+        assert_ne!(frame[159], ntsc_to_rgb(0));
+        assert_ne!(frame[0], ntsc_to_rgb(0));
+        assert_ne!(frame[3], ntsc_to_rgb(0));
+        assert_eq!(frame[4], ntsc_to_rgb(0));
+
+        // Second copy wraps to (156 + 64) % 160 = 60
+        assert_eq!(frame[59], ntsc_to_rgb(0));
+        assert_ne!(frame[60], ntsc_to_rgb(0));
+        assert_ne!(frame[67], ntsc_to_rgb(0));
+        assert_eq!(frame[68], ntsc_to_rgb(0));
+    }
+
+    #[test]
+    fn test_missile_copies_wrap_around() {
+        let mut tia = Tia::new();
+
+        // Three copies medium (mode 110), 1 pixel missiles
+        tia.write(0x04, 0x06);
+        tia.missile0_x = 120;
+        tia.write(0x1D, 0x02); // ENAM0
+        tia.write(0x06, 0x28); // COLUP0
+
+        let mut frame = vec![0u32; 160];
+        tia.latch_scanline_state(0);
+        tia.render_scanline(&mut frame, 0, 0);
+
+        assert_ne!(frame[120], ntsc_to_rgb(0));
+        assert_ne!(frame[152], ntsc_to_rgb(0));
+        // Third copy wraps to (120 + 64) % 160 = 24
+        assert_ne!(frame[24], ntsc_to_rgb(0));
+        assert_eq!(frame[25], ntsc_to_rgb(0));
     }
 
     #[test]
@@ -1952,6 +2369,40 @@ mod tests {
         assert_ne!(tia.read(0x07) & 0x40, 0); // CXPPMM bit 6 (P0P1)
     }
 
+    #[test]
+    fn test_collisions_latch_during_frame() {
+        let mut tia = Tia::new();
+
+        // Overlapping players on a visible scanline
+        tia.player0_x = 40;
+        tia.player1_x = 44;
+        tia.write(0x1B, 0xFF); // GRP0
+        tia.write(0x1C, 0xFF); // GRP1
+
+        // Collisions can be read once the beam has finished the line
+        assert_eq!(tia.read(0x07) & 0x40, 0);
+        for _ in 0..76 {
+            tia.clock();
+        }
+        assert_eq!(tia.read(0x07) & 0x40, 0x40); // P0P1
+
+        // CXCLR mid-frame clears them until the next overlapping line
+        tia.write(0x2C, 0x00);
+        tia.write(0x1B, 0x00);
+        for _ in 0..76 {
+            tia.clock();
+        }
+        assert_eq!(tia.read(0x07), 0);
+
+        // Blanked lines don't collide
+        tia.write(0x1B, 0xFF);
+        tia.write(0x01, 0x02); // VBLANK on
+        for _ in 0..76 {
+            tia.clock();
+        }
+        assert_eq!(tia.read(0x07), 0);
+    }
+
     #[test]
     fn test_collision_clear() {
         let mut tia = Tia::new();
@@ -1980,23 +2431,148 @@ mod tests {
     fn test_vdelp_delayed_graphics() {
         let mut tia = Tia::new();
 
-        // Write initial graphics
+        // Writing GRP0 does not touch its own delayed register
         tia.write(0x1B, 0xAA); // GRP0 = 0xAA
         assert_eq!(tia.grp0, 0xAA);
-        assert_eq!(tia.grp0_old, 0x00); // Old value is 0
+        assert_eq!(tia.grp0_old, 0x00);
 
-        // Write new graphics - old value should be saved
+        // Writing GRP1 copies GRP0 into the delayed register
+        tia.write(0x1C, 0x55); // GRP1 = 0x55
+        assert_eq!(tia.grp0_old, 0xAA);
+        assert_eq!(tia.grp1_old, 0x00);
+
+        // Writing GRP0 copies GRP1 into its delayed register
         tia.write(0x1B, 0xFF); // GRP0 = 0xFF
         assert_eq!(tia.grp0, 0xFF);
-        assert_eq!(tia.grp0_old, 0xAA); // Old value saved
+        assert_eq!(tia.grp0_old, 0xAA);
+        assert_eq!(tia.grp1_old, 0x55);
 
         // Enable delayed graphics
         tia.write(0x25, 0x01); // VDELP0
+        tia.write(0x26, 0x01); // VDELP1
         assert!(tia.vdelp0);
 
-        // When latching state, delayed graphics should use old value
+        // When latching state, delayed graphics should use the old values
         tia.latch_scanline_state(0);
         let state = tia.scanline_states[0];
-        assert_eq!(state.grp0, 0xAA); // Uses old value when VDELP0 is set
+        assert_eq!(state.grp0, 0xAA);
+        assert_eq!(state.grp1, 0x55);
+    }
+
+    #[test]
+    fn test_reset_position_delay() {
+        let mut tia = Tia::new();
+
+        // Strobes during horizontal blank land near the left edge
+        tia.pixel = 10;
+        tia.write(0x10, 0x00); // RESP0
+        tia.write(0x12, 0x00); // RESM0
+        tia.write(0x14, 0x00); // RESBL
+        assert_eq!(tia.player0_x, 3);
+        assert_eq!(tia.missile0_x, 2);
+        assert_eq!(tia.ball_x, 2);
+
+        // Visible strobes are delayed 5 clocks for players, 4 for the rest
+        tia.pixel = 68 + 100;
+        tia.write(0x11, 0x00); // RESP1
+        tia.write(0x13, 0x00); // RESM1
+        tia.write(0x14, 0x00); // RESBL
+        assert_eq!(tia.player1_x, 105);
+        assert_eq!(tia.missile1_x, 104);
+        assert_eq!(tia.ball_x, 104);
+
+        // Strobes at the right edge wrap to the next line's left side
+        tia.pixel = 68 + 158;
+        tia.write(0x10, 0x00); // RESP0
+        assert_eq!(tia.player0_x, 3);
+    }
+
+    /// Run the beam to color clock `pixel` of the current scanline
+    fn clock_to(tia: &mut Tia, pixel: u16) {
+        while tia.pixel < pixel {
+            tia.clock();
+        }
+    }
+
+    #[test]
+    fn test_beam_mode_mid_scanline_write() {
+        let mut tia = Tia::new();
+        tia.set_accuracy(TiaAccuracy::Beam);
+        tia.write(0x09, 0x0E); // COLUBK
+
+        // Change the background halfway across the line
+        // (the beam moves 3 color clocks per CPU cycle)
+        clock_to(&mut tia, 68 + 79);
+        tia.write(0x09, 0x44);
+        while tia.scanline == 0 {
+            tia.clock();
+        }
+
+        let mut frame = vec![0u32; 160];
+        tia.render_scanline(&mut frame, 0, 0);
+        assert_eq!(frame[0], ntsc_to_rgb(0x0E));
+        assert_eq!(frame[78], ntsc_to_rgb(0x0E));
+        assert_eq!(frame[79], ntsc_to_rgb(0x44));
+        assert_eq!(frame[159], ntsc_to_rgb(0x44));
+
+        // Scanline mode draws the whole line with the final value
+        tia.set_accuracy(TiaAccuracy::Scanline);
+        tia.render_scanline(&mut frame, 0, 0);
+        assert_eq!(frame[0], ntsc_to_rgb(0x44));
+    }
+
+    #[test]
+    fn test_early_revision_delays_playfield() {
+        // Turn on PF0 bit 5 (pixels 4-7) just as the beam reaches pixel 4
+        let draw = |revision: TiaRevision| {
+            let mut tia = Tia::new();
+            tia.set_accuracy(TiaAccuracy::Beam);
+            tia.set_revision(revision);
+            tia.write(0x08, 0x0E); // COLUPF
+            clock_to(&mut tia, 68 + 4);
+            tia.write(0x0D, 0x20); // PF0
+            while tia.scanline == 0 {
+                tia.clock();
+            }
+            let mut frame = vec![0u32; 160];
+            tia.render_scanline(&mut frame, 0, 0);
+            frame
+        };
+
+        let late = draw(TiaRevision::Late);
+        assert_eq!(late[3], ntsc_to_rgb(0x00));
+        assert_eq!(late[4], ntsc_to_rgb(0x0E));
+
+        // The early chip draws one more pixel with the old playfield
+        let early = draw(TiaRevision::Early);
+        assert_eq!(early[4], ntsc_to_rgb(0x00));
+        assert_eq!(early[5], ntsc_to_rgb(0x0E));
+        assert_eq!(early[7], ntsc_to_rgb(0x0E));
+    }
+
+    #[test]
+    fn test_beam_mode_collisions_mid_scanline() {
+        let mut tia = Tia::new();
+        tia.set_accuracy(TiaAccuracy::Beam);
+        tia.player0_x = 10;
+        tia.player1_x = 10;
+        tia.write(0x1B, 0xFF); // GRP0
+        tia.write(0x1C, 0xFF); // GRP1
+
+        // Not drawn yet
+        clock_to(&mut tia, 68 + 9);
+        assert_eq!(tia.read(0x07) & 0x40, 0);
+
+        // Readable as soon as the beam has passed the overlap
+        clock_to(&mut tia, 68 + 20);
+        assert_eq!(tia.read(0x07) & 0x40, 0x40); // CXPPMM P0P1
+
+        // Objects removed later in the line keep the latch set
+        tia.write(0x2C, 0x00); // CXCLR
+        tia.write(0x1B, 0x00);
+        while tia.scanline == 0 {
+            tia.clock();
+        }
+        assert_eq!(tia.read(0x07), 0);
     }
 }
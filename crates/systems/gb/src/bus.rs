@@ -57,6 +57,16 @@
 //! - `$FF4A (WY)`: Window Y position
 //! - `$FF4B (WX)`: Window X position
 //!
+//! ## CGB Registers
+//! - `$FF4D (KEY1)`: Speed switch (bit 7 current speed, bit 0 switch armed)
+//! - `$FF4F (VBK)`: VRAM bank select
+//! - `$FF51-$FF54 (HDMA1-4)`: VRAM DMA source and destination
+//! - `$FF55 (HDMA5)`: VRAM DMA length, mode and start
+//! - `$FF68-$FF6B`: BG and OBJ color palette index/data
+//! - `$FF70 (SVBK)`: Work RAM bank select (1-7)
+//!
+//! CGB registers read as $FF and ignore writes when a DMG cartridge is loaded.
+//!
 //! ## Other
 //! - `$FF50`: Boot ROM disable (write 1 to disable)
 //!
@@ -68,8 +78,10 @@
 //! ## Implemented
 //! - MBC0: No mapper (32KB ROM max)
 //! - MBC1: Most common (up to 2MB ROM, 32KB RAM)
-//! - MBC3: With RTC support (up to 2MB ROM, 32KB RAM)
+//! - MBC2: Built-in 512×4 bits RAM (up to 256KB ROM)
+//! - MBC3: With real-time clock (up to 2MB ROM, 32KB RAM)
 //! - MBC5: For larger ROMs (up to 8MB ROM, 128KB RAM)
+//! - HuC1: Hudson Soft (up to 1MB ROM, 32KB RAM)
 //!
 //! # Current Implementation
 //!
@@ -77,7 +89,7 @@
 //! - ✅ Full memory map with proper mirroring
 //! - ✅ VRAM access via PPU (8KB)
 //! - ✅ OAM access via PPU (160 bytes)
-//! - ✅ Work RAM (8KB)
+//! - ✅ Work RAM (8KB, 32KB in 4KB banks on CGB)
 //! - ✅ High RAM (127 bytes)
 //! - ✅ Joypad register with matrix selection
 //! - ✅ PPU registers (LCDC, STAT, palettes, scroll, etc.)
@@ -87,13 +99,13 @@
 //! - ✅ Boot ROM disable register
 //! - ✅ Cartridge ROM loading (up to size)
 //! - ✅ Cartridge RAM with size detection
-//! - ✅ MBC0, MBC1, MBC3, MBC5 mappers
+//! - ✅ MBC0, MBC1, MBC2, MBC3, MBC5, HuC1 mappers
+//! - ✅ OAM DMA register
+//! - ✅ CGB registers (speed switch, VRAM/WRAM banks, HDMA, color palettes)
 //!
 //! ## Not Implemented
-//! - ❌ MBC2 mapper (built-in 512×4 bits RAM)
 //! - ❌ Serial transfer
-//! - ❌ DMA register
-//! - ❌ CGB-specific registers
+//! - ❌ HDMA/GDMA CPU stall cycles
 
 use crate::apu::GbApu;
 use crate::mappers::Mapper;
@@ -101,10 +113,13 @@ use crate::ppu::Ppu;
 use crate::timer::Timer;
 use emu_core::cpu_lr35902::MemoryLr35902;
 
+/// Size of one work RAM bank
+const WRAM_BANK_SIZE: usize = 0x1000;
+
 /// Game Boy memory bus
 pub struct GbBus {
-    /// Work RAM (8KB)
-    wram: [u8; 0x2000],
+    /// Work RAM (8 banks of 4KB; DMG only uses banks 0 and 1)
+    wram: [u8; 0x8000],
     /// High RAM (127 bytes)
     hram: [u8; 0x7F],
     /// Interrupt Enable register
@@ -113,6 +128,8 @@ pub struct GbBus {
     if_reg: u8,
     /// Cartridge mapper (handles ROM/RAM banking)
     mapper: Option<Mapper>,
+    /// Cartridge RAM is battery-backed (from the cartridge type byte)
+    battery: bool,
     /// Boot ROM enabled flag
     boot_rom_enabled: bool,
     /// PPU (Picture Processing Unit)
@@ -127,16 +144,31 @@ pub struct GbBus {
     button_state: u8,
     /// CGB mode flag (true if Game Boy Color features are enabled)
     cgb_mode: bool,
+    /// Work RAM bank mapped at $D000-$DFFF (SVBK, CGB only)
+    wram_bank: u8,
+    /// CPU runs at 8.4 MHz (KEY1 bit 7, CGB only)
+    double_speed: bool,
+    /// Speed switch armed, performed by the next STOP (KEY1 bit 0)
+    speed_switch_armed: bool,
+    /// VRAM DMA source address (HDMA1/HDMA2)
+    hdma_source: u16,
+    /// VRAM DMA destination offset within VRAM (HDMA3/HDMA4)
+    hdma_dest: u16,
+    /// 16-byte blocks left in the active HBlank DMA, minus one (HDMA5 bits 0-6)
+    hdma_remaining: u8,
+    /// HBlank DMA in progress
+    hdma_active: bool,
 }
 
 impl GbBus {
     pub fn new() -> Self {
         Self {
-            wram: [0; 0x2000],
+            wram: [0; 0x8000],
             hram: [0; 0x7F],
             ie: 0,
             if_reg: 0,
             mapper: None,
+            battery: false,
             boot_rom_enabled: true,
             ppu: Ppu::new(),
             apu: GbApu::new(),
@@ -144,6 +176,97 @@ impl GbBus {
             joypad: 0xFF,
             button_state: 0xFF,
             cgb_mode: false,
+            wram_bank: 1,
+            double_speed: false,
+            speed_switch_armed: false,
+            hdma_source: 0,
+            hdma_dest: 0,
+            hdma_remaining: 0x7F,
+            hdma_active: false,
+        }
+    }
+
+    /// Return the CGB speed, WRAM bank and VRAM DMA state to power-on values
+    pub fn reset_cgb_state(&mut self) {
+        self.wram_bank = 1;
+        self.double_speed = false;
+        self.speed_switch_armed = false;
+        self.hdma_source = 0;
+        self.hdma_dest = 0;
+        self.hdma_remaining = 0x7F;
+        self.hdma_active = false;
+        self.ppu.set_vram_bank(0);
+    }
+
+    /// Check if the CPU runs in CGB double speed mode
+    pub fn is_double_speed(&self) -> bool {
+        self.double_speed
+    }
+
+    /// Perform an armed speed switch; called when the CPU executes STOP
+    ///
+    /// Returns true if the speed changed, in which case the CPU resumes
+    /// instead of entering stop mode.
+    pub fn try_speed_switch(&mut self) -> bool {
+        if !self.cgb_mode || !self.speed_switch_armed {
+            return false;
+        }
+        self.double_speed = !self.double_speed;
+        self.speed_switch_armed = false;
+        true
+    }
+
+    /// Transfer the next HBlank DMA block; called at the end of each visible scanline
+    pub fn hblank(&mut self) {
+        if !self.hdma_active {
+            return;
+        }
+        self.hdma_copy_block();
+        if self.hdma_remaining == 0 {
+            self.hdma_active = false;
+            self.hdma_remaining = 0x7F;
+        } else {
+            self.hdma_remaining -= 1;
+        }
+    }
+
+    /// Copy one 16-byte VRAM DMA block and advance both addresses
+    fn hdma_copy_block(&mut self) {
+        for i in 0..0x10 {
+            let byte = self.read(self.hdma_source.wrapping_add(i));
+            self.ppu
+                .write_vram(self.hdma_dest.wrapping_add(i) & 0x1FFF, byte);
+        }
+        self.hdma_source = self.hdma_source.wrapping_add(0x10);
+        self.hdma_dest = (self.hdma_dest + 0x10) & 0x1FF0;
+    }
+
+    /// HDMA5 write: start a general purpose or HBlank DMA, or cancel HBlank DMA
+    fn write_hdma5(&mut self, val: u8) {
+        if self.hdma_active && val & 0x80 == 0 {
+            // Stopping an HBlank DMA; HDMA5 keeps the remaining length
+            self.hdma_active = false;
+            return;
+        }
+        self.hdma_remaining = val & 0x7F;
+        if val & 0x80 != 0 {
+            self.hdma_active = true;
+        } else {
+            // General purpose DMA: the whole transfer happens at once
+            for _ in 0..=self.hdma_remaining {
+                self.hdma_copy_block();
+            }
+            self.hdma_remaining = 0x7F;
+        }
+    }
+
+    /// Work RAM offset for a $C000-$DFFF (or echo) address
+    fn wram_offset(&self, addr: u16) -> usize {
+        let offset = (addr & 0x1FFF) as usize;
+        if offset < WRAM_BANK_SIZE {
+            offset
+        } else {
+            self.wram_bank as usize * WRAM_BANK_SIZE + offset - WRAM_BANK_SIZE
         }
     }
 
@@ -169,13 +292,22 @@ impl GbBus {
         self.cgb_mode
     }
 
+    /// Advance cartridge hardware (MBC3 real-time clock) by `cycles` CPU cycles
+    pub fn step_mapper(&mut self, cycles: u32) {
+        if let Some(mapper) = &mut self.mapper {
+            mapper.step(cycles);
+        }
+    }
+
     pub fn load_cart(&mut self, data: &[u8]) {
         // Parse cart header
         if data.len() < 0x150 {
             // Too small to be a valid cart, but load it anyway
             self.mapper = Some(Mapper::from_cart(data.to_vec(), vec![], 0x00));
+            self.battery = false;
             self.boot_rom_enabled = false;
             self.cgb_mode = false;
+            self.reset_cgb_state();
             return;
         }
 
@@ -187,6 +319,7 @@ impl GbBus {
         // 0xC0 = CGB only
         let cgb_flag = data[0x143];
         self.cgb_mode = cgb_flag == 0x80 || cgb_flag == 0xC0;
+        self.reset_cgb_state();
 
         // Enable CGB mode in PPU if CGB ROM
         if self.cgb_mode {
@@ -210,8 +343,28 @@ impl GbBus {
         };
 
         self.mapper = Some(Mapper::from_cart(data.to_vec(), ram, cart_type));
+        // MBC1/MBC2/MBC3/MBC5 types with a battery, and HuC1
+        self.battery = matches!(
+            cart_type,
+            0x03 | 0x06 | 0x0F | 0x10 | 0x13 | 0x1B | 0x1E | 0xFF
+        );
         self.boot_rom_enabled = false; // Skip boot ROM for now
     }
+
+    /// Whether the mounted cartridge keeps its RAM on a battery
+    pub fn has_battery(&self) -> bool {
+        self.battery
+    }
+
+    /// Cartridge RAM, if a cartridge is loaded
+    pub fn cart_ram(&self) -> Option<&[u8]> {
+        self.mapper.as_ref().map(Mapper::ram)
+    }
+
+    /// Mutable cartridge RAM, if a cartridge is loaded
+    pub fn cart_ram_mut(&mut self) -> Option<&mut [u8]> {
+        self.mapper.as_mut().map(Mapper::ram_mut)
+    }
 }
 
 impl MemoryLr35902 for GbBus {
@@ -238,10 +391,10 @@ impl MemoryLr35902 for GbBus {
                     0xFF
                 }
             }
-            // Work RAM (8KB)
-            0xC000..=0xDFFF => self.wram[(addr - 0xC000) as usize],
+            // Work RAM (bank 0 fixed, bank 1-7 switchable on CGB)
+            0xC000..=0xDFFF => self.wram[self.wram_offset(addr)],
             // Echo RAM (mirror of C000-DDFF)
-            0xE000..=0xFDFF => self.wram[(addr - 0xE000) as usize],
+            0xE000..=0xFDFF => self.wram[self.wram_offset(addr)],
             // OAM (Object Attribute Memory) - delegate to PPU
             0xFE00..=0xFE9F => self.ppu.read_oam(addr - 0xFE00),
             // Not usable
@@ -283,11 +436,21 @@ impl MemoryLr35902 for GbBus {
                 0xFF4A => self.ppu.wy,
                 0xFF4B => self.ppu.wx,
                 // CGB registers
+                0xFF4D | 0xFF4F | 0xFF55 | 0xFF68..=0xFF6B | 0xFF70 if !self.cgb_mode => 0xFF,
+                0xFF4D => {
+                    // KEY1 - speed switch
+                    0x7E | (self.double_speed as u8) << 7 | self.speed_switch_armed as u8
+                }
                 0xFF4F => self.ppu.get_vram_bank(), // VBK - VRAM bank
-                0xFF68 => self.ppu.read_bgpi(),     // BCPS/BGPI - BG palette index
-                0xFF69 => self.ppu.read_bgpd(),     // BCPD/BGPD - BG palette data
-                0xFF6A => self.ppu.read_obpi(),     // OCPS/OBPI - OBJ palette index
-                0xFF6B => self.ppu.read_obpd(),     // OCPD/OBPD - OBJ palette data
+                0xFF55 => {
+                    // HDMA5 - bit 7 clear while an HBlank DMA is running
+                    (!self.hdma_active as u8) << 7 | self.hdma_remaining
+                }
+                0xFF68 => self.ppu.read_bgpi(), // BCPS/BGPI - BG palette index
+                0xFF69 => self.ppu.read_bgpd(), // BCPD/BGPD - BG palette data
+                0xFF6A => self.ppu.read_obpi(), // OCPS/OBPI - OBJ palette index
+                0xFF6B => self.ppu.read_obpd(), // OCPD/OBPD - OBJ palette data
+                0xFF70 => 0xF8 | self.wram_bank, // SVBK - WRAM bank
                 _ => 0xFF,
             },
             // High RAM
@@ -314,9 +477,9 @@ impl MemoryLr35902 for GbBus {
                 }
             }
             // Work RAM
-            0xC000..=0xDFFF => self.wram[(addr - 0xC000) as usize] = val,
+            0xC000..=0xDFFF => self.wram[self.wram_offset(addr)] = val,
             // Echo RAM
-            0xE000..=0xFDFF => self.wram[(addr - 0xE000) as usize] = val,
+            0xE000..=0xFDFF => self.wram[self.wram_offset(addr)] = val,
             // OAM - delegate to PPU
             0xFE00..=0xFE9F => self.ppu.write_oam(addr - 0xFE00, val),
             // Not usable
@@ -353,11 +516,22 @@ impl MemoryLr35902 for GbBus {
                     0xFF4A => self.ppu.wy = val,
                     0xFF4B => self.ppu.wx = val,
                     // CGB registers
-                    0xFF4F => self.ppu.set_vram_bank(val), // VBK - VRAM bank
-                    0xFF68 => self.ppu.write_bgpi(val),    // BCPS/BGPI
-                    0xFF69 => self.ppu.write_bgpd(val),    // BCPD/BGPD
-                    0xFF6A => self.ppu.write_obpi(val),    // OCPS/OBPI
-                    0xFF6B => self.ppu.write_obpd(val),    // OCPD/OBPD
+                    0xFF4D | 0xFF4F | 0xFF51..=0xFF55 | 0xFF68..=0xFF6B | 0xFF70
+                        if !self.cgb_mode => {}
+                    0xFF4D => self.speed_switch_armed = val & 0x01 != 0, // KEY1
+                    0xFF4F => self.ppu.set_vram_bank(val),               // VBK - VRAM bank
+                    0xFF51 => self.hdma_source = (self.hdma_source & 0x00F0) | (val as u16) << 8,
+                    0xFF52 => self.hdma_source = (self.hdma_source & 0xFF00) | (val & 0xF0) as u16,
+                    0xFF53 => {
+                        self.hdma_dest = (self.hdma_dest & 0x00F0) | ((val & 0x1F) as u16) << 8
+                    }
+                    0xFF54 => self.hdma_dest = (self.hdma_dest & 0x1F00) | (val & 0xF0) as u16,
+                    0xFF55 => self.write_hdma5(val), // HDMA5 - start/stop VRAM DMA
+                    0xFF68 => self.ppu.write_bgpi(val), // BCPS/BGPI
+                    0xFF69 => self.ppu.write_bgpd(val), // BCPD/BGPD
+                    0xFF6A => self.ppu.write_obpi(val), // OCPS/OBPI
+                    0xFF6B => self.ppu.write_obpd(val), // OCPD/OBPD
+                    0xFF70 => self.wram_bank = (val & 0x07).max(1), // SVBK - bank 0 selects 1
                     0xFF50 => self.boot_rom_enabled = false, // Disable boot ROM
                     _ => {}
                 }
@@ -19,7 +19,6 @@
 //! ## PPU (Picture Processing Unit)
 //! - Resolution: 160x144 pixels
 //! - Display modes: DMG (4 shades of gray), CGB (32,768 colors)
-//! - **Current implementation**: DMG mode only
 //! - Supports:
 //!   - Background layer with scrolling (SCX/SCY registers)
 //!   - Window layer (overlay window with separate position)
@@ -36,7 +35,7 @@
 //! - `$4000-$7FFF`: ROM Bank 1-N (16KB, switchable via MBC)
 //! - `$8000-$9FFF`: VRAM (8KB, video RAM)
 //! - `$A000-$BFFF`: External RAM (8KB, switchable via MBC)
-//! - `$C000-$DFFF`: Work RAM (8KB)
+//! - `$C000-$DFFF`: Work RAM (8KB; CGB banks $D000-$DFFF among 7 banks via SVBK)
 //! - `$E000-$FDFF`: Echo RAM (mirror of $C000-$DDFF)
 //! - `$FE00-$FE9F`: OAM (Object Attribute Memory - 160 bytes)
 //! - `$FF00-$FF7F`: I/O Registers
@@ -59,7 +58,12 @@
 //! - `$FF45`: LY Compare (LYC)
 //! - `$FF47-$FF49`: Palette registers (BGP, OBP0, OBP1)
 //! - `$FF4A-$FF4B`: Window position (WY, WX)
+//! - `$FF4D`: CGB speed switch (KEY1)
+//! - `$FF4F`: CGB VRAM bank (VBK)
 //! - `$FF50`: Boot ROM disable
+//! - `$FF51-$FF55`: CGB VRAM DMA (HDMA1-HDMA5)
+//! - `$FF68-$FF6B`: CGB color palettes (BCPS/BCPD, OCPS/OCPD)
+//! - `$FF70`: CGB WRAM bank (SVBK)
 //! - `$FFFF`: Interrupt Enable (IE)
 //!
 //! ## Joypad Input
@@ -95,6 +99,9 @@
 //! - ✅ I/O: Essential PPU and joypad registers
 //! - ✅ I/O: CGB palette registers (BCPS/BCPD, OCPS/OCPD)
 //! - ✅ I/O: CGB VRAM bank select (VBK)
+//! - ✅ CGB: WRAM banking (7 switchable 4KB banks via SVBK)
+//! - ✅ CGB: General purpose and HBlank VRAM DMA (HDMA1-HDMA5)
+//! - ✅ CGB: Double speed mode (KEY1 + STOP)
 //! - ✅ Save states: Full CPU state preservation
 //! - ✅ APU: 4 sound channels (pulse 1/2, wave, noise)
 //! - ✅ APU: Frame sequencer and envelope/sweep control
@@ -125,19 +132,21 @@
 //!    - VRAM banking (2 banks of 8KB)
 //!    - Color palettes (8 BG + 8 OBJ, 4 colors each, 15-bit RGB)
 //!    - Tile attributes (palette selection, VRAM bank, flipping)
+//!    - WRAM banking, VRAM DMA and double speed mode
 //!    - Backward compatible with DMG games
+//!    - VRAM DMA transfers do not stall the CPU
 //!
 //! # Usage Example
 //!
-//! ```rust,no_run
+//! ```rust
 //! use emu_core::System;
 //! use emu_gb::GbSystem;
 //!
 //! // Create a new Game Boy system
 //! let mut gb = GbSystem::new();
 //!
-//! // Load a ROM
-//! let rom_data = std::fs::read("game.gb").unwrap();
+//! // Load a ROM (here a blank 32KB cartridge; normally `std::fs::read("game.gb")`)
+//! let rom_data = vec![0u8; 0x8000];
 //! gb.mount("Cartridge", &rom_data).unwrap();
 //!
 //! // Set controller state (buttons: Right=0, Left=1, Up=2, Down=3, A=4, B=5, Select=6, Start=7)
@@ -241,6 +250,44 @@ impl GbSystem {
             lcdc: self.cpu.memory.ppu.lcdc,
         }
     }
+
+    /// Whether the mounted cartridge has battery-backed RAM (from the
+    /// cartridge type byte at $0147)
+    pub fn has_battery(&self) -> bool {
+        self.cart_loaded && self.cpu.memory.has_battery()
+    }
+
+    /// Battery-backed cartridge RAM ($A000-$BFFF, all banks) to write to a
+    /// `.sav` file
+    ///
+    /// `None` if no cartridge with a battery is mounted.
+    pub fn battery_ram(&self) -> Option<&[u8]> {
+        if !self.has_battery() {
+            return None;
+        }
+        self.cpu.memory.cart_ram()
+    }
+
+    /// Restore battery-backed cartridge RAM, e.g. from a `.sav` file, after
+    /// mounting
+    pub fn load_battery_ram(&mut self, data: &[u8]) -> Result<(), GbError> {
+        if !self.has_battery() {
+            return Err(GbError::NoBatteryRam);
+        }
+        let ram = self
+            .cpu
+            .memory
+            .cart_ram_mut()
+            .ok_or(GbError::NoBatteryRam)?;
+        if data.len() != ram.len() {
+            return Err(GbError::BatteryRamSize {
+                expected: ram.len(),
+                actual: data.len(),
+            });
+        }
+        ram.copy_from_slice(data);
+        Ok(())
+    }
 }
 
 /// Debug information about the Game Boy system
@@ -264,13 +311,24 @@ pub enum GbError {
     NoCartridge,
     #[error("Invalid mount point")]
     InvalidMountPoint,
+    #[error("Cartridge has no battery-backed RAM")]
+    NoBatteryRam,
+    #[error("Battery RAM size mismatch: expected {expected} bytes, got {actual}")]
+    BatteryRamSize { expected: usize, actual: usize },
 }
 
+/// Game Boy CPU clock (4.194304 MHz)
+const CPU_CLOCK_HZ: u32 = 4_194_304;
+
+/// Cycles per frame (154 lines of 456 dots), giving ~59.73 Hz
+const CYCLES_PER_FRAME: u32 = 70_224;
+
 impl System for GbSystem {
     type Error = GbError;
 
     fn reset(&mut self) {
         self.cpu.reset();
+        self.cpu.memory.reset_cgb_state();
     }
 
     fn step_frame(&mut self) -> Result<Frame, Self::Error> {
@@ -278,18 +336,26 @@ impl System for GbSystem {
             return Err(GbError::NoCartridge);
         }
 
-        // Game Boy runs at ~4.194304 MHz
-        // Frame rate is ~59.73 Hz
-        // Cycles per frame: 4194304 / 59.73 ≈ 70224 cycles
-        const CYCLES_PER_FRAME: u32 = 70224;
-
         let mut cycles = 0;
         while cycles < CYCLES_PER_FRAME {
             let cpu_cycles = self.cpu.step();
-            cycles += cpu_cycles;
+
+            // STOP with KEY1 armed switches CGB speed instead of stopping
+            if self.cpu.stopped && self.cpu.memory.try_speed_switch() {
+                self.cpu.stopped = false;
+            }
+
+            // In double speed mode the CPU and timer run twice as fast as
+            // the PPU, APU and cartridge clock
+            let dot_cycles = if self.cpu.memory.is_double_speed() {
+                cpu_cycles / 2
+            } else {
+                cpu_cycles
+            };
+            cycles += dot_cycles;
 
             // Accumulate cycles for audio generation
-            self.audio_cycles_accumulated += cpu_cycles;
+            self.audio_cycles_accumulated += dot_cycles;
 
             // Step timer and handle timer interrupt
             if self.cpu.memory.timer.step(cpu_cycles) {
@@ -297,11 +363,20 @@ impl System for GbSystem {
                 self.cpu.memory.request_interrupt(0x04);
             }
 
+            // Step cartridge RTC
+            self.cpu.memory.step_mapper(dot_cycles);
+
             // Step PPU and handle VBlank interrupt
-            if self.cpu.memory.ppu.step(cpu_cycles) {
+            let ly = self.cpu.memory.ppu.ly;
+            if self.cpu.memory.ppu.step(dot_cycles) {
                 // V-Blank started - request VBlank interrupt (bit 0)
                 self.cpu.memory.request_interrupt(0x01);
             }
+
+            // A visible scanline finished: run the HBlank DMA block
+            if ly < 144 && self.cpu.memory.ppu.ly != ly {
+                self.cpu.memory.hblank();
+            }
         }
 
         // Render the frame using the renderer
@@ -411,6 +486,10 @@ impl System for GbSystem {
     fn is_mounted(&self, mount_point_id: &str) -> bool {
         mount_point_id == "Cartridge" && self.cart_loaded
     }
+
+    fn frame_rate(&self) -> f64 {
+        CPU_CLOCK_HZ as f64 / CYCLES_PER_FRAME as f64
+    }
 }
 
 #[cfg(test)]
@@ -424,6 +503,12 @@ mod tests {
         assert!(!sys.cart_loaded);
     }
 
+    #[test]
+    fn test_gb_frame_rate() {
+        let sys = GbSystem::new();
+        assert!((sys.frame_rate() - 59.7275).abs() < 0.001);
+    }
+
     #[test]
     fn test_gb_mount_points() {
         let sys = GbSystem::new();
@@ -464,6 +549,68 @@ mod tests {
         assert!(sys.supports_save_states());
     }
 
+    #[test]
+    fn test_gb_battery_ram() {
+        let mut sys = GbSystem::new();
+        sys.mount("Cartridge", &vec![0; 0x8000]).unwrap();
+        assert!(!sys.has_battery());
+        assert_eq!(sys.battery_ram(), None);
+        assert!(matches!(
+            sys.load_battery_ram(&[0; 0x2000]),
+            Err(GbError::NoBatteryRam)
+        ));
+
+        // MBC1+RAM+BATTERY with 8KB of RAM
+        let mut rom = vec![0; 0x8000];
+        rom[0x147] = 0x03;
+        rom[0x149] = 0x02;
+        sys.mount("Cartridge", &rom).unwrap();
+        assert!(sys.has_battery());
+        sys.cpu.memory.write(0x0000, 0x0A);
+        sys.cpu.memory.write(0xA123, 0x5A);
+        let saved = sys.battery_ram().unwrap().to_vec();
+        assert_eq!(saved.len(), 0x2000);
+        assert_eq!(saved[0x123], 0x5A);
+
+        assert!(matches!(
+            sys.load_battery_ram(&[0; 0x1000]),
+            Err(GbError::BatteryRamSize {
+                expected: 0x2000,
+                actual: 0x1000
+            })
+        ));
+    }
+
+    #[test]
+    fn test_gb_golden_battery_ram() {
+        // A `.sav` written by any build must come back byte for byte
+        let fixture = include_bytes!("../../../../test_roms/gb/test.sav");
+        let mut rom = include_bytes!("../../../../test_roms/gb/test.gb").to_vec();
+        rom[0x147] = 0x03; // MBC1+RAM+BATTERY
+        rom[0x149] = 0x02; // 8KB of RAM
+        let mut sys = GbSystem::new();
+        sys.mount("Cartridge", &rom).unwrap();
+        sys.load_battery_ram(fixture).unwrap();
+        assert_eq!(sys.battery_ram(), Some(&fixture[..]));
+
+        sys.cpu.memory.write(0x0000, 0x0A);
+        assert!((0..0x2000u16).all(|i| sys.cpu.memory.read(0xA000 + i) == fixture[i as usize]));
+        sys.step_frame().unwrap();
+        assert_eq!(sys.battery_ram(), Some(&fixture[..]));
+    }
+
+    #[test]
+    fn test_gb_golden_save_state() {
+        // Saved after 30 frames of the test ROM
+        emu_core::save_state::check_golden_state(
+            &mut GbSystem::new(),
+            "Cartridge",
+            include_bytes!("../../../../test_roms/gb/test.gb"),
+            include_str!("../../../../test_roms/gb/test.state.json"),
+        )
+        .unwrap();
+    }
+
     #[test]
     fn test_gb_step_frame_without_cart() {
         let mut sys = GbSystem::new();
@@ -914,4 +1061,135 @@ mod tests {
             );
         }
     }
+
+    fn cgb_test_system(program: &[u8]) -> GbSystem {
+        let mut rom = vec![0; 0x8000];
+        rom[0x143] = 0xC0; // CGB only
+        rom[0x100..0x100 + program.len()].copy_from_slice(program);
+        let mut sys = GbSystem::new();
+        sys.mount("Cartridge", &rom).unwrap();
+        sys
+    }
+
+    #[test]
+    fn test_gbc_wram_banking() {
+        let mut sys = cgb_test_system(&[0x18, 0xFE]); // JR -2
+        let bus = &mut sys.cpu.memory;
+
+        bus.write(0xC000, 0x11);
+        bus.write(0xFF70, 0x02);
+        bus.write(0xD000, 0x22);
+        bus.write(0xFF70, 0x07);
+        bus.write(0xD000, 0x77);
+        assert_eq!(bus.read(0xFF70), 0xFF);
+
+        bus.write(0xFF70, 0x02);
+        assert_eq!(bus.read(0xD000), 0x22);
+        // Echo RAM follows the selected bank; bank 0 stays fixed
+        assert_eq!(bus.read(0xF000), 0x22);
+        assert_eq!(bus.read(0xC000), 0x11);
+
+        // Selecting bank 0 maps bank 1
+        bus.write(0xFF70, 0x00);
+        assert_eq!(bus.read(0xFF70), 0xF9);
+        assert_ne!(bus.read(0xD000), 0x22);
+
+        // DMG cartridges have no SVBK
+        let mut dmg = GbSystem::new();
+        dmg.mount("Cartridge", &vec![0; 0x8000]).unwrap();
+        dmg.cpu.memory.write(0xFF70, 0x03);
+        assert_eq!(dmg.cpu.memory.read(0xFF70), 0xFF);
+    }
+
+    #[test]
+    fn test_gbc_general_purpose_dma() {
+        let mut sys = cgb_test_system(&[0x18, 0xFE]);
+        let bus = &mut sys.cpu.memory;
+        for i in 0..0x20 {
+            bus.write(0xC100 + i, i as u8 + 1);
+        }
+
+        // 2 blocks from $C100 to $8810 in VRAM bank 1
+        bus.write(0xFF4F, 0x01);
+        bus.write(0xFF51, 0xC1);
+        bus.write(0xFF52, 0x00);
+        bus.write(0xFF53, 0x08);
+        bus.write(0xFF54, 0x10);
+        bus.write(0xFF55, 0x01);
+
+        assert_eq!(bus.read(0xFF55), 0xFF);
+        for i in 0..0x20 {
+            assert_eq!(bus.read(0x8810 + i), i as u8 + 1);
+        }
+        bus.write(0xFF4F, 0x00);
+        assert_eq!(bus.read(0x8810), 0x00);
+    }
+
+    #[test]
+    fn test_gbc_hblank_dma() {
+        let mut sys = cgb_test_system(&[0x18, 0xFE]);
+        {
+            let bus = &mut sys.cpu.memory;
+            for i in 0..0x30 {
+                bus.write(0xC000 + i, 0xA0 + i as u8);
+            }
+            bus.write(0xFF51, 0xC0);
+            bus.write(0xFF52, 0x00);
+            bus.write(0xFF53, 0x00);
+            bus.write(0xFF54, 0x00);
+
+            // 3 blocks, one per HBlank
+            bus.write(0xFF55, 0x82);
+            assert_eq!(bus.read(0xFF55), 0x02);
+            assert_eq!(bus.read(0x8000), 0x00);
+
+            bus.hblank();
+            assert_eq!(bus.read(0xFF55), 0x01);
+            assert_eq!(bus.read(0x800F), 0xAF);
+            assert_eq!(bus.read(0x8010), 0x00);
+
+            // Writing bit 7 clear stops the transfer and keeps the length
+            bus.write(0xFF55, 0x00);
+            assert_eq!(bus.read(0xFF55), 0x81);
+            bus.hblank();
+            assert_eq!(bus.read(0x8010), 0x00);
+
+            // Restart the last 2 blocks
+            bus.write(0xFF55, 0x81);
+        }
+
+        // The frame loop runs a block at the end of each visible scanline
+        sys.step_frame().unwrap();
+        assert_eq!(sys.cpu.memory.read(0xFF55), 0xFF);
+        assert_eq!(sys.cpu.memory.read(0x8010), 0xB0);
+        assert_eq!(sys.cpu.memory.read(0x802F), 0xCF);
+    }
+
+    #[test]
+    fn test_gbc_double_speed_switch() {
+        // LD A,1; LDH (KEY1),A; STOP; JR -2
+        let mut sys = cgb_test_system(&[0x3E, 0x01, 0xE0, 0x4D, 0x10, 0x00, 0x18, 0xFE]);
+        assert_eq!(sys.cpu.memory.read(0xFF4D), 0x7E);
+
+        // TIMA counts at 4096 Hz of CPU time
+        let tima_after_frame = |sys: &mut GbSystem| {
+            sys.cpu.memory.write(0xFF05, 0x00);
+            sys.cpu.memory.write(0xFF07, 0x04);
+            sys.step_frame().unwrap();
+            sys.cpu.memory.read(0xFF05)
+        };
+        let single_speed_ticks = tima_after_frame(&mut cgb_test_system(&[0x18, 0xFE]));
+        let ticks = tima_after_frame(&mut sys);
+
+        assert!(sys.cpu.memory.is_double_speed());
+        assert!(!sys.cpu.stopped);
+        assert_eq!(sys.cpu.memory.read(0xFF4D), 0xFE);
+        // The CPU and timer get twice the cycles per frame
+        assert_eq!(single_speed_ticks, 68);
+        assert!(ticks > 130, "{}", ticks);
+
+        // Reset returns to normal speed
+        sys.reset();
+        assert!(!sys.cpu.memory.is_double_speed());
+    }
 }
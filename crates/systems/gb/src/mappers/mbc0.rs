@@ -28,6 +28,16 @@ impl Mbc0 {
         // No banking commands for MBC0
     }
 
+    /// Cartridge RAM contents, all banks in order
+    pub fn ram(&self) -> &[u8] {
+        &self.ram
+    }
+
+    /// Mutable cartridge RAM contents, all banks in order
+    pub fn ram_mut(&mut self) -> &mut [u8] {
+        &mut self.ram
+    }
+
     pub fn read_ram(&self, addr: u16) -> u8 {
         let offset = (addr - 0xA000) as usize;
         if offset < self.ram.len() {
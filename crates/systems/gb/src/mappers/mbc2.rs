@@ -80,6 +80,16 @@ impl Mbc2 {
         }
     }
 
+    /// Cartridge RAM contents, all banks in order
+    pub fn ram(&self) -> &[u8] {
+        &self.ram
+    }
+
+    /// Mutable cartridge RAM contents, all banks in order
+    pub fn ram_mut(&mut self) -> &mut [u8] {
+        &mut self.ram
+    }
+
     pub fn read_ram(&self, addr: u16) -> u8 {
         if !self.ram_enabled {
             return 0xFF;
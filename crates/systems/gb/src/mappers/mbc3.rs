@@ -19,9 +19,88 @@
 //! - 0x0A: RTC Hours (0-23)
 //! - 0x0B: RTC Days (lower 8 bits)
 //! - 0x0C: RTC Days (upper 1 bit) + Halt + Day Carry flags
+//!
+//! The clock counts emulated time (one second per 4,194,304 CPU cycles), so
+//! it stops while emulation is paused. Reads return the values captured by
+//! the last latch; writes set the running clock directly.
 
 use emu_core::logging::{log, LogCategory, LogLevel};
 
+/// CPU cycles per RTC second
+const RTC_CYCLES_PER_SECOND: u32 = 4_194_304;
+/// Day counter high register: bit 0 is day bit 8
+const RTC_DH_DAY_HIGH: u8 = 0x01;
+/// Day counter high register: clock halted
+const RTC_DH_HALT: u8 = 0x40;
+/// Day counter high register: day counter overflowed past 511
+const RTC_DH_CARRY: u8 = 0x80;
+
+/// RTC counter registers (0x08-0x0C)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct RtcRegisters {
+    s: u8,  // Seconds
+    m: u8,  // Minutes
+    h: u8,  // Hours
+    dl: u8, // Days lower
+    dh: u8, // Days upper + flags
+}
+
+impl RtcRegisters {
+    fn read(&self, reg: u8) -> u8 {
+        match reg {
+            0x08 => self.s,
+            0x09 => self.m,
+            0x0A => self.h,
+            0x0B => self.dl,
+            0x0C => self.dh,
+            _ => 0xFF,
+        }
+    }
+
+    fn write(&mut self, reg: u8, val: u8) {
+        match reg {
+            0x08 => self.s = val & 0x3F,
+            0x09 => self.m = val & 0x3F,
+            0x0A => self.h = val & 0x1F,
+            0x0B => self.dl = val,
+            0x0C => self.dh = val & (RTC_DH_DAY_HIGH | RTC_DH_HALT | RTC_DH_CARRY),
+            _ => {}
+        }
+    }
+
+    /// Advance by one second
+    ///
+    /// Counters only carry when they reach their normal limit; values written
+    /// out of range count up to the register's bit width and wrap to 0
+    /// without carrying, as on hardware.
+    fn tick_second(&mut self) {
+        self.s = (self.s + 1) & 0x3F;
+        if self.s != 60 {
+            return;
+        }
+        self.s = 0;
+
+        self.m = (self.m + 1) & 0x3F;
+        if self.m != 60 {
+            return;
+        }
+        self.m = 0;
+
+        self.h = (self.h + 1) & 0x1F;
+        if self.h != 24 {
+            return;
+        }
+        self.h = 0;
+
+        let days = ((((self.dh & RTC_DH_DAY_HIGH) as u16) << 8) | self.dl as u16) + 1;
+        self.dl = days as u8;
+        self.dh = (self.dh & !RTC_DH_DAY_HIGH) | ((days >> 8) as u8 & RTC_DH_DAY_HIGH);
+        if days > 0x1FF {
+            self.dh |= RTC_DH_CARRY;
+        }
+    }
+}
+
 /// MBC3 mapper
 #[derive(Debug)]
 pub struct Mbc3 {
@@ -30,13 +109,10 @@ pub struct Mbc3 {
     ram_rtc_enabled: bool,
     rom_bank: u8,
     ram_bank: u8,
-    // RTC registers (stubbed for now)
-    rtc_s: u8,     // Seconds
-    rtc_m: u8,     // Minutes
-    rtc_h: u8,     // Hours
-    rtc_dl: u8,    // Days lower
-    rtc_dh: u8,    // Days upper + flags
-    rtc_latch: u8, // For latching RTC (0x00 -> 0x01 sequence)
+    rtc: RtcRegisters,         // Running clock
+    rtc_latched: RtcRegisters, // Values visible to reads
+    rtc_cycles: u32,           // CPU cycles into the current second
+    rtc_latch: u8,             // For latching RTC (0x00 -> 0x01 sequence)
 }
 
 impl Mbc3 {
@@ -47,15 +123,25 @@ impl Mbc3 {
             ram_rtc_enabled: false,
             rom_bank: 1,
             ram_bank: 0,
-            rtc_s: 0,
-            rtc_m: 0,
-            rtc_h: 0,
-            rtc_dl: 0,
-            rtc_dh: 0,
+            rtc: RtcRegisters::default(),
+            rtc_latched: RtcRegisters::default(),
+            rtc_cycles: 0,
             rtc_latch: 0xFF,
         }
     }
 
+    /// Advance the real-time clock by `cycles` CPU cycles
+    pub fn step(&mut self, cycles: u32) {
+        if self.rtc.dh & RTC_DH_HALT != 0 {
+            return;
+        }
+        self.rtc_cycles += cycles;
+        while self.rtc_cycles >= RTC_CYCLES_PER_SECOND {
+            self.rtc_cycles -= RTC_CYCLES_PER_SECOND;
+            self.rtc.tick_second();
+        }
+    }
+
     fn rom_bank_count(&self) -> usize {
         self.rom.len().div_ceil(0x4000)
     }
@@ -127,10 +213,15 @@ impl Mbc3 {
             0x6000..=0x7FFF => {
                 // Latch Clock Data (0x00 -> 0x01 latches RTC)
                 if self.rtc_latch == 0x00 && val == 0x01 {
-                    // RTC latch operation (stub - clock doesn't actually run)
-                    log(LogCategory::Stubs, LogLevel::Debug, || {
-                        "GB MBC3: RTC latch operation (stub - clock doesn't actually tick)"
-                            .to_string()
+                    self.rtc_latched = self.rtc;
+                    log(LogCategory::Bus, LogLevel::Debug, || {
+                        format!(
+                            "GB MBC3: RTC latched {}d {:02}:{:02}:{:02}",
+                            ((self.rtc.dh as u16 & 1) << 8) | self.rtc.dl as u16,
+                            self.rtc.h,
+                            self.rtc.m,
+                            self.rtc.s
+                        )
                     });
                 }
                 self.rtc_latch = val;
@@ -139,6 +230,16 @@ impl Mbc3 {
         }
     }
 
+    /// Cartridge RAM contents, all banks in order
+    pub fn ram(&self) -> &[u8] {
+        &self.ram
+    }
+
+    /// Mutable cartridge RAM contents, all banks in order
+    pub fn ram_mut(&mut self) -> &mut [u8] {
+        &mut self.ram
+    }
+
     pub fn read_ram(&self, addr: u16) -> u8 {
         if !self.ram_rtc_enabled {
             return 0xFF;
@@ -158,36 +259,7 @@ impl Mbc3 {
                     0xFF
                 }
             }
-            0x08 => {
-                log(LogCategory::Stubs, LogLevel::Debug, || {
-                    format!("GB MBC3: RTC Seconds read (stub, value={})", self.rtc_s)
-                });
-                self.rtc_s
-            }
-            0x09 => {
-                log(LogCategory::Stubs, LogLevel::Debug, || {
-                    format!("GB MBC3: RTC Minutes read (stub, value={})", self.rtc_m)
-                });
-                self.rtc_m
-            }
-            0x0A => {
-                log(LogCategory::Stubs, LogLevel::Debug, || {
-                    format!("GB MBC3: RTC Hours read (stub, value={})", self.rtc_h)
-                });
-                self.rtc_h
-            }
-            0x0B => {
-                log(LogCategory::Stubs, LogLevel::Debug, || {
-                    format!("GB MBC3: RTC Days lower read (stub, value={})", self.rtc_dl)
-                });
-                self.rtc_dl
-            }
-            0x0C => {
-                log(LogCategory::Stubs, LogLevel::Debug, || {
-                    format!("GB MBC3: RTC Days upper read (stub, value={})", self.rtc_dh)
-                });
-                self.rtc_dh
-            }
+            0x08..=0x0C => self.rtc_latched.read(self.ram_bank),
             _ => 0xFF,
         }
     }
@@ -209,35 +281,21 @@ impl Mbc3 {
                     self.ram[offset] = val;
                 }
             }
-            0x08 => {
-                log(LogCategory::Stubs, LogLevel::Debug, || {
-                    format!("GB MBC3: RTC Seconds write (stub, value={})", val)
-                });
-                self.rtc_s = val;
-            }
-            0x09 => {
-                log(LogCategory::Stubs, LogLevel::Debug, || {
-                    format!("GB MBC3: RTC Minutes write (stub, value={})", val)
-                });
-                self.rtc_m = val;
-            }
-            0x0A => {
-                log(LogCategory::Stubs, LogLevel::Debug, || {
-                    format!("GB MBC3: RTC Hours write (stub, value={})", val)
-                });
-                self.rtc_h = val;
-            }
-            0x0B => {
-                log(LogCategory::Stubs, LogLevel::Debug, || {
-                    format!("GB MBC3: RTC Days lower write (stub, value={})", val)
-                });
-                self.rtc_dl = val;
-            }
-            0x0C => {
-                log(LogCategory::Stubs, LogLevel::Debug, || {
-                    format!("GB MBC3: RTC Days upper write (stub, value={})", val)
+            0x08..=0x0C => {
+                log(LogCategory::Bus, LogLevel::Debug, || {
+                    format!(
+                        "GB MBC3: RTC register 0x{:02X} write: 0x{:02X}",
+                        self.ram_bank, val
+                    )
                 });
-                self.rtc_dh = val;
+                if self.ram_bank == 0x08 {
+                    // Writing seconds restarts the current second
+                    self.rtc_cycles = 0;
+                }
+                // Update the latched copy too so the value reads back
+                // without a new latch
+                self.rtc.write(self.ram_bank, val);
+                self.rtc_latched.write(self.ram_bank, val);
             }
             _ => {}
         }
@@ -381,7 +439,83 @@ mod tests {
 
         mbc.write_rom(0x6000, 0x01);
         assert_eq!(mbc.rtc_latch, 0x01);
-        // RTC values are latched (though our stub doesn't actually update them)
+    }
+
+    /// Select an RTC register and read its latched value
+    fn read_rtc(mbc: &mut Mbc3, reg: u8) -> u8 {
+        mbc.write_rom(0x4000, reg);
+        mbc.read_ram(0xA000)
+    }
+
+    fn latch(mbc: &mut Mbc3) {
+        mbc.write_rom(0x6000, 0x00);
+        mbc.write_rom(0x6000, 0x01);
+    }
+
+    #[test]
+    fn test_mbc3_rtc_ticks_and_latches() {
+        let mut mbc = Mbc3::new(vec![0; 0x8000], vec![]);
+        mbc.write_rom(0x0000, 0x0A);
+
+        mbc.step(RTC_CYCLES_PER_SECOND * 3 - 1);
+        latch(&mut mbc);
+        assert_eq!(read_rtc(&mut mbc, 0x08), 2);
+
+        // Latched value holds while the clock keeps running
+        mbc.step(1);
+        assert_eq!(read_rtc(&mut mbc, 0x08), 2);
+        latch(&mut mbc);
+        assert_eq!(read_rtc(&mut mbc, 0x08), 3);
+    }
+
+    #[test]
+    fn test_mbc3_rtc_rollover_and_day_carry() {
+        let mut mbc = Mbc3::new(vec![0; 0x8000], vec![]);
+        mbc.write_rom(0x0000, 0x0A);
+
+        // Day 511, 23:59:59
+        for (reg, val) in [
+            (0x08, 59),
+            (0x09, 59),
+            (0x0A, 23),
+            (0x0B, 0xFF),
+            (0x0C, 0x01),
+        ] {
+            mbc.write_rom(0x4000, reg);
+            mbc.write_ram(0xA000, val);
+        }
+        mbc.step(RTC_CYCLES_PER_SECOND);
+        latch(&mut mbc);
+
+        assert_eq!(read_rtc(&mut mbc, 0x08), 0);
+        assert_eq!(read_rtc(&mut mbc, 0x09), 0);
+        assert_eq!(read_rtc(&mut mbc, 0x0A), 0);
+        assert_eq!(read_rtc(&mut mbc, 0x0B), 0);
+        assert_eq!(read_rtc(&mut mbc, 0x0C), RTC_DH_CARRY);
+    }
+
+    #[test]
+    fn test_mbc3_rtc_halt_and_out_of_range() {
+        let mut mbc = Mbc3::new(vec![0; 0x8000], vec![]);
+        mbc.write_rom(0x0000, 0x0A);
+
+        // Halted clock doesn't advance
+        mbc.write_rom(0x4000, 0x0C);
+        mbc.write_ram(0xA000, RTC_DH_HALT);
+        mbc.step(RTC_CYCLES_PER_SECOND * 5);
+        latch(&mut mbc);
+        assert_eq!(read_rtc(&mut mbc, 0x08), 0);
+
+        // Resume with seconds at 63: wraps to 0 without carrying into minutes
+        mbc.write_rom(0x4000, 0x0C);
+        mbc.write_ram(0xA000, 0);
+        mbc.write_rom(0x4000, 0x08);
+        mbc.write_ram(0xA000, 0xFF);
+        assert_eq!(mbc.read_ram(0xA000), 63);
+        mbc.step(RTC_CYCLES_PER_SECOND);
+        latch(&mut mbc);
+        assert_eq!(read_rtc(&mut mbc, 0x08), 0);
+        assert_eq!(read_rtc(&mut mbc, 0x09), 0);
     }
 
     #[test]
@@ -78,6 +78,30 @@ impl Mapper {
         }
     }
 
+    /// Cartridge RAM contents, all banks in order
+    pub fn ram(&self) -> &[u8] {
+        match self {
+            Mapper::Huc1(m) => m.ram(),
+            Mapper::Mbc0(m) => m.ram(),
+            Mapper::Mbc1(m) => m.ram(),
+            Mapper::Mbc2(m) => m.ram(),
+            Mapper::Mbc3(m) => m.ram(),
+            Mapper::Mbc5(m) => m.ram(),
+        }
+    }
+
+    /// Mutable cartridge RAM contents, all banks in order
+    pub fn ram_mut(&mut self) -> &mut [u8] {
+        match self {
+            Mapper::Huc1(m) => m.ram_mut(),
+            Mapper::Mbc0(m) => m.ram_mut(),
+            Mapper::Mbc1(m) => m.ram_mut(),
+            Mapper::Mbc2(m) => m.ram_mut(),
+            Mapper::Mbc3(m) => m.ram_mut(),
+            Mapper::Mbc5(m) => m.ram_mut(),
+        }
+    }
+
     /// Read from RAM address space
     pub fn read_ram(&self, addr: u16) -> u8 {
         match self {
@@ -102,6 +126,13 @@ impl Mapper {
         }
     }
 
+    /// Advance cartridge hardware clocked by the CPU (the MBC3 real-time clock)
+    pub fn step(&mut self, cycles: u32) {
+        if let Mapper::Mbc3(m) = self {
+            m.step(cycles);
+        }
+    }
+
     /// Get the cartridge type name
     #[cfg(test)]
     pub fn name(&self) -> &str {
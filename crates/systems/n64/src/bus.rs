@@ -1,14 +1,23 @@
 //! N64 memory bus implementation
 
 use crate::cartridge::Cartridge;
+use crate::dl_debug::DisplayListCapture;
 use crate::mi::MipsInterface;
 use crate::pif::Pif;
-use crate::rdp::Rdp;
-use crate::rsp::Rsp;
+use crate::rcp_thread::RcpThread;
+use crate::rdp::{Rdp, RdpState};
+use crate::rsp::{Rsp, RspState};
+use crate::si::{SerialInterface, SiDma, SI_DMA_SIZE};
 use crate::vi::VideoInterface;
 use crate::N64Error;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
 use emu_core::cpu_mips_r4300i::MemoryMips;
 use emu_core::logging::{log, LogCategory, LogLevel};
+use emu_core::types::Frame;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use std::io::{Read, Write};
 
 /// N64 memory bus
 pub struct N64Bus {
@@ -26,8 +35,12 @@ pub struct N64Bus {
     vi: VideoInterface,
     /// MI (MIPS Interface - interrupt controller)
     mi: MipsInterface,
+    /// SI (Serial Interface - PIF RAM DMA)
+    si: SerialInterface,
     /// Entry point from ROM header (set during cartridge load)
     entry_point: Option<u64>,
+    /// Worker running RSP tasks and RDP command lists (None = inline)
+    rcp_thread: Option<RcpThread>,
 }
 
 impl N64Bus {
@@ -40,7 +53,9 @@ impl N64Bus {
             rsp: Rsp::new(),
             vi: VideoInterface::new(),
             mi: MipsInterface::new(),
+            si: SerialInterface::new(),
             entry_point: None,
+            rcp_thread: None,
         };
 
         // Initialize PIF ROM
@@ -66,6 +81,35 @@ impl N64Bus {
         self.pif.set_controller4(state);
     }
 
+    /// Plug a controller into or unplug it from a port (0-3)
+    pub fn set_controller_connected(&mut self, port: usize, connected: bool) {
+        self.pif.set_controller_connected(port, connected);
+    }
+
+    /// Run an SI DMA between RDRAM and the PIF RAM joybus area
+    ///
+    /// Writing the block's last byte (the PIF control byte) runs the
+    /// controller commands in it.
+    fn run_si_dma(&mut self, dma: SiDma) {
+        const JOYBUS_AREA: u32 = 0x7C0;
+        match dma {
+            SiDma::PifToRdram(dram_addr) => {
+                for i in 0..SI_DMA_SIZE {
+                    let addr = (dram_addr as usize + i) % self.rdram.len();
+                    self.rdram[addr] = self.pif.read_ram(JOYBUS_AREA + i as u32);
+                }
+            }
+            SiDma::RdramToPif(dram_addr) => {
+                for i in 0..SI_DMA_SIZE {
+                    let addr = (dram_addr as usize + i) % self.rdram.len();
+                    self.pif.write_ram(JOYBUS_AREA + i as u32, self.rdram[addr]);
+                }
+            }
+        }
+        self.si.raise_interrupt();
+        self.mi.set_interrupt(super::mi::MI_INTR_SI);
+    }
+
     pub fn load_cartridge(&mut self, data: &[u8]) -> Result<(), N64Error> {
         log(LogCategory::Bus, LogLevel::Info, || {
             format!("N64 Bus: Loading cartridge, size={} bytes", data.len())
@@ -135,16 +179,35 @@ impl N64Bus {
         &mut self.rsp
     }
 
-    #[allow(dead_code)] // Reserved for future use when VI is integrated with frame rendering
     pub fn vi(&self) -> &VideoInterface {
         &self.vi
     }
 
-    #[allow(dead_code)] // Reserved for future use when VI is integrated with frame rendering
     pub fn vi_mut(&mut self) -> &mut VideoInterface {
         &mut self.vi
     }
 
+    /// Frame shown by the VI this frame
+    ///
+    /// The RDP draws to its own framebuffer rather than RDRAM, so when
+    /// VI_ORIGIN points into a color image the RDP drew to, its framebuffer
+    /// is shown. Otherwise the VI scans out RDRAM (games that draw with the
+    /// CPU). Until the VI is set up the RDP framebuffer is shown as well, so
+    /// homebrew that never programs the VI still displays.
+    pub fn video_frame(&mut self) -> Frame {
+        let rdp_image = self
+            .rdp
+            .drew_color_image_at(self.vi.get_framebuffer_origin(), self.vi.image_bytes());
+        if !self.vi.is_enabled() || rdp_image {
+            return self.rdp.get_frame().clone();
+        }
+        self.vi.scanout(&self.rdram).unwrap_or_else(|| {
+            // No visible area (e.g. a zero display window)
+            let (width, height) = self.rdp.resolution();
+            Frame::new(width, height)
+        })
+    }
+
     #[allow(dead_code)] // Reserved for future use
     pub fn mi(&self) -> &MipsInterface {
         &self.mi
@@ -155,11 +218,139 @@ impl N64Bus {
     }
 
     /// Enable OpenGL hardware rendering for RDP (requires OpenGL feature)
+    ///
+    /// The GL context belongs to the calling thread, so this stops the RCP
+    /// thread if it is running.
     #[cfg(feature = "opengl")]
     pub fn enable_opengl_renderer(&mut self, gl: glow::Context) -> Result<(), String> {
+        self.set_threaded_rcp(false)?;
         self.rdp.enable_opengl_renderer(gl)
     }
 
+    /// Run RSP tasks and RDP command lists on a worker thread
+    ///
+    /// Only available with the software renderer. Disabling waits for the
+    /// worker to finish and brings its state back to the bus.
+    pub fn set_threaded_rcp(&mut self, enabled: bool) -> Result<(), String> {
+        if enabled == self.rcp_thread.is_some() {
+            return Ok(());
+        }
+
+        if enabled {
+            if self.rdp.is_hardware_accelerated() {
+                return Err(format!(
+                    "the {} renderer must run on the emulation thread",
+                    self.rdp.renderer_name()
+                ));
+            }
+
+            // The worker gets the real RDP with its render state; the bus
+            // keeps the registers and the last frame
+            let (width, height) = self.rdp.resolution();
+            let mut front = Rdp::with_resolution(width, height);
+            front.copy_registers_from(&self.rdp);
+            front.show_frame(self.rdp.get_frame());
+            front.set_color_images(self.rdp.color_images());
+            let rdp = std::mem::replace(&mut self.rdp, front);
+
+            let hle = self.rsp.take_hle();
+            match RcpThread::spawn(hle, rdp) {
+                Ok(thread) => self.rcp_thread = Some(thread),
+                Err(e) => return Err(format!("failed to start RCP thread: {}", e)),
+            }
+        } else {
+            self.sync_rcp();
+            if let Some((hle, mut rdp)) = self.rcp_thread.take().and_then(RcpThread::stop) {
+                rdp.copy_registers_from(&self.rdp);
+                self.rdp = rdp;
+                self.rsp.restore_hle(hle);
+            }
+        }
+        Ok(())
+    }
+
+    /// True if RSP tasks and RDP command lists run on a worker thread
+    pub fn is_threaded_rcp(&self) -> bool {
+        self.rcp_thread.is_some()
+    }
+
+    /// Wait for the RCP thread and apply the results of its work
+    ///
+    /// Halts the RSP (raising the SP interrupt if enabled), raises the DP
+    /// interrupt for display lists that reached a full sync, marks queued
+    /// command lists as done and shows the new frame. If the worker stopped
+    /// unexpectedly, processing falls back to running inline.
+    pub fn sync_rcp(&mut self) {
+        let Some(thread) = &mut self.rcp_thread else {
+            return;
+        };
+
+        match thread.sync() {
+            Ok(frame) => {
+                if let Some(frame) = frame {
+                    self.rdp.show_frame(&frame);
+                    self.rdp.set_color_images(thread.color_images());
+                }
+                let (rsp_task, display_list) = thread.take_completions();
+                if rsp_task && self.rsp.complete_task() {
+                    self.mi.set_interrupt(super::mi::MI_INTR_SP);
+                }
+                if thread.take_full_sync() {
+                    self.mi.set_interrupt(super::mi::MI_INTR_DP);
+                }
+                if display_list {
+                    self.rdp.complete_display_list();
+                }
+            }
+            Err(e) => {
+                log(LogCategory::PPU, LogLevel::Warn, || {
+                    format!("N64 Bus: {}, processing RSP/RDP work inline", e)
+                });
+                let (rsp_task, display_list) = thread.take_completions();
+                self.rcp_thread = None;
+                if rsp_task {
+                    self.rsp.complete_task();
+                }
+                if display_list {
+                    self.rdp.complete_display_list();
+                }
+            }
+        }
+    }
+
+    /// Record the display list of the next RSP graphics task
+    pub fn request_display_list_capture(&mut self) {
+        match &mut self.rcp_thread {
+            Some(thread) => thread.request_capture(),
+            None => self.rsp.request_display_list_capture(),
+        }
+    }
+
+    /// Take the most recently completed display list capture
+    pub fn take_display_list_capture(&mut self) -> Option<DisplayListCapture> {
+        match &mut self.rcp_thread {
+            Some(thread) => thread.take_capture(),
+            None => self.rsp.take_display_list_capture(),
+        }
+    }
+
+    /// Number of vertices in the RSP vertex buffer (as of the last sync
+    /// when the RCP thread is running)
+    pub fn rsp_vertex_count(&self) -> usize {
+        match &self.rcp_thread {
+            Some(thread) => thread.vertex_count(),
+            None => self.rsp.vertex_count(),
+        }
+    }
+
+    /// Reset the RDP (on the RCP thread too, if it is running)
+    pub fn reset_rdp(&mut self) {
+        if let Some(thread) = &mut self.rcp_thread {
+            thread.reset_rdp();
+        }
+        self.rdp.reset();
+    }
+
     /// Execute pending RSP task if RSP is not halted
     /// Returns true if an SP interrupt should be triggered
     pub fn process_rsp_task(&mut self) -> bool {
@@ -168,10 +359,23 @@ impl N64Bus {
             "N64 Bus: process_rsp_task() called".to_string()
         });
 
+        // The RSP halts and interrupts at the next sync
+        if let Some(thread) = &mut self.rcp_thread {
+            if !self.rsp.is_halted() {
+                thread.queue_rsp_task(self.rsp.dmem(), self.rsp.microcode_type(), &self.rdram);
+            }
+            return false;
+        }
+
         // Clone RDRAM reference to avoid borrow checker issues
         let rdram_clone = self.rdram.clone();
         let (_cycles, should_interrupt) = self.rsp.execute_task(&rdram_clone, &mut self.rdp);
 
+        // The RDP finished the task's display list
+        if self.rsp.take_full_sync() {
+            self.mi.set_interrupt(super::mi::MI_INTR_DP);
+        }
+
         if should_interrupt {
             log(LogCategory::PPU, LogLevel::Info, || {
                 "N64 Bus: RSP task complete, triggering SP interrupt".to_string()
@@ -185,17 +389,92 @@ impl N64Bus {
 
     /// Process pending RDP display list if needed
     pub fn process_rdp_display_list(&mut self) {
-        if self.rdp.needs_processing() {
-            self.rdp.process_display_list(&self.rdram);
+        if !self.rdp.needs_processing() {
+            return;
+        }
+        match &mut self.rcp_thread {
+            Some(thread) => {
+                let start = self.rdp.read_register(0x00);
+                let end = self.rdp.read_register(0x04);
+                thread.queue_display_list(start, end, &self.rdram);
+            }
+            None => self.rdp.process_display_list(&self.rdram),
         }
     }
 
+    /// Serialize RDRAM (deflated and base64 encoded), the RDP, RSP, VI, MI and
+    /// SI registers and PIF RAM
+    ///
+    /// With the RCP thread running, the RDP render state lives on the worker
+    /// and only the DPC registers are saved.
+    pub fn save_state(&self) -> serde_json::Value {
+        serde_json::json!({
+            "rdram": compress(&self.rdram),
+            "rdp": self.rdp.save_state(),
+            "rsp": self.rsp.save_state(),
+            "vi": self.vi,
+            "mi": self.mi,
+            "si": self.si,
+            "pif_ram": self.pif.ram(),
+        })
+    }
+
+    /// Restore state saved by [`N64Bus::save_state`]
+    pub fn load_state(&mut self, state: &serde_json::Value) -> Result<(), serde_json::Error> {
+        use serde::de::Error;
+
+        let encoded: String = serde_json::from_value(state["rdram"].clone())?;
+        let rdram = decompress(&encoded).map_err(Error::custom)?;
+        if rdram.len() != self.rdram.len() {
+            return Err(Error::custom("save state has wrong RDRAM size"));
+        }
+        let rdp: RdpState = serde_json::from_value(state["rdp"].clone())?;
+        let rsp: RspState = serde_json::from_value(state["rsp"].clone())?;
+        let vi: VideoInterface = serde_json::from_value(state["vi"].clone())?;
+        let mi: MipsInterface = serde_json::from_value(state["mi"].clone())?;
+        let si: SerialInterface = serde_json::from_value(state["si"].clone())?;
+        let pif_ram: Vec<u8> = serde_json::from_value(state["pif_ram"].clone())?;
+
+        // Bring the render state back from the RCP thread so it is replaced too
+        let threaded = self.is_threaded_rcp();
+        self.set_threaded_rcp(false).map_err(Error::custom)?;
+
+        self.pif.load_ram(&pif_ram).map_err(Error::custom)?;
+        self.rdp.load_state(&rdp).map_err(Error::custom)?;
+        self.rsp.load_state(&rsp).map_err(Error::custom)?;
+        self.rdram.copy_from_slice(&rdram);
+        self.vi = vi;
+        self.mi = mi;
+        self.si = si;
+
+        self.set_threaded_rcp(threaded).map_err(Error::custom)
+    }
+
     fn translate_address(&self, addr: u32) -> u32 {
         // Simple address translation (unmapped addresses)
         addr & 0x1FFFFFFF
     }
 }
 
+/// Deflate and base64 encode memory for a save state
+fn compress(data: &[u8]) -> String {
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::fast());
+    encoder
+        .write_all(data)
+        .expect("writing to a Vec cannot fail");
+    BASE64.encode(encoder.finish().expect("writing to a Vec cannot fail"))
+}
+
+/// Decode memory encoded by [`compress`]
+fn decompress(encoded: &str) -> Result<Vec<u8>, String> {
+    let compressed = BASE64.decode(encoded).map_err(|e| e.to_string())?;
+    let mut data = Vec::new();
+    DeflateDecoder::new(compressed.as_slice())
+        .read_to_end(&mut data)
+        .map_err(|e| e.to_string())?;
+    Ok(data)
+}
+
 impl Default for N64Bus {
     fn default() -> Self {
         Self::new()
@@ -245,7 +524,7 @@ impl MemoryMips for N64Bus {
 
     fn read_halfword(&self, addr: u32) -> u16 {
         let b0 = self.read_byte(addr);
-        let b1 = self.read_byte(addr + 1);
+        let b1 = self.read_byte(addr.wrapping_add(1));
         u16::from_be_bytes([b0, b1])
     }
 
@@ -262,7 +541,8 @@ impl MemoryMips for N64Bus {
         match phys_addr {
             // RDRAM
             0x0000_0000..=0x003F_FFFF => {
-                let offset = (phys_addr & 0x003FFFFF) as usize;
+                // Word accesses ignore the low address bits
+                let offset = (phys_addr & 0x003F_FFFC) as usize;
                 u32::from_be_bytes([
                     self.rdram[offset],
                     self.rdram[offset + 1],
@@ -290,6 +570,11 @@ impl MemoryMips for N64Bus {
                 let offset = phys_addr & 0x3F;
                 self.vi.read_register(offset)
             }
+            // SI registers (0x04800000 - 0x0480001B)
+            0x0480_0000..=0x0480_001B => {
+                let offset = phys_addr & 0x1F;
+                self.si.read_register(offset)
+            }
             // Cartridge ROM
             0x1000_0000..=0x1FBF_FFFF => {
                 if let Some(ref cart) = self.cartridge {
@@ -306,9 +591,9 @@ impl MemoryMips for N64Bus {
             }
             _ => {
                 let b0 = self.read_byte(addr);
-                let b1 = self.read_byte(addr + 1);
-                let b2 = self.read_byte(addr + 2);
-                let b3 = self.read_byte(addr + 3);
+                let b1 = self.read_byte(addr.wrapping_add(1));
+                let b2 = self.read_byte(addr.wrapping_add(2));
+                let b3 = self.read_byte(addr.wrapping_add(3));
                 u32::from_be_bytes([b0, b1, b2, b3])
             }
         }
@@ -316,7 +601,7 @@ impl MemoryMips for N64Bus {
 
     fn read_doubleword(&self, addr: u32) -> u64 {
         let hi = self.read_word(addr) as u64;
-        let lo = self.read_word(addr + 4) as u64;
+        let lo = self.read_word(addr.wrapping_add(4)) as u64;
         (hi << 32) | lo
     }
 
@@ -357,7 +642,7 @@ impl MemoryMips for N64Bus {
     fn write_halfword(&mut self, addr: u32, val: u16) {
         let bytes = val.to_be_bytes();
         self.write_byte(addr, bytes[0]);
-        self.write_byte(addr + 1, bytes[1]);
+        self.write_byte(addr.wrapping_add(1), bytes[1]);
     }
 
     fn write_word(&mut self, addr: u32, val: u32) {
@@ -373,7 +658,8 @@ impl MemoryMips for N64Bus {
         match phys_addr {
             // RDRAM
             0x0000_0000..=0x003F_FFFF => {
-                let offset = (phys_addr & 0x003FFFFF) as usize;
+                // Word accesses ignore the low address bits
+                let offset = (phys_addr & 0x003F_FFFC) as usize;
                 let bytes = val.to_be_bytes();
                 self.rdram[offset] = bytes[0];
                 self.rdram[offset + 1] = bytes[1];
@@ -410,13 +696,28 @@ impl MemoryMips for N64Bus {
             0x0440_0000..=0x0440_0037 => {
                 let offset = phys_addr & 0x3F;
                 self.vi.write_register(offset, val);
+                // Writing VI_CURRENT acknowledges the interrupt
+                if offset == super::vi::VI_CURRENT {
+                    self.mi.clear_interrupt(super::mi::MI_INTR_VI);
+                }
+            }
+            // SI registers (0x04800000 - 0x0480001B)
+            0x0480_0000..=0x0480_001B => {
+                let offset = phys_addr & 0x1F;
+                if let Some(dma) = self.si.write_register(offset, val) {
+                    self.run_si_dma(dma);
+                }
+                // Writing SI_STATUS acknowledges the interrupt
+                if !self.si.interrupt_pending() {
+                    self.mi.clear_interrupt(super::mi::MI_INTR_SI);
+                }
             }
             _ => {
                 let bytes = val.to_be_bytes();
                 self.write_byte(addr, bytes[0]);
-                self.write_byte(addr + 1, bytes[1]);
-                self.write_byte(addr + 2, bytes[2]);
-                self.write_byte(addr + 3, bytes[3]);
+                self.write_byte(addr.wrapping_add(1), bytes[1]);
+                self.write_byte(addr.wrapping_add(2), bytes[2]);
+                self.write_byte(addr.wrapping_add(3), bytes[3]);
             }
         }
     }
@@ -425,6 +726,6 @@ impl MemoryMips for N64Bus {
         let hi = (val >> 32) as u32;
         let lo = val as u32;
         self.write_word(addr, hi);
-        self.write_word(addr + 4, lo);
+        self.write_word(addr.wrapping_add(4), lo);
     }
 }
@@ -0,0 +1,544 @@
+//! Display list capture and single-stepping for debugging
+//!
+//! When a capture is requested, the RSP HLE records every GBI command of the
+//! next graphics task in execution order (nested `G_DL` calls are flattened
+//! and tagged with their depth), along with the framebuffer pixels each
+//! command changed. The resulting [`DisplayListCapture`] can be dumped as text
+//! or stepped forwards and backwards, reconstructing the framebuffer as it was
+//! after each command.
+
+use super::rsp_hle::MicrocodeType;
+use std::fmt::Write;
+
+/// A single 64-bit GBI command as it was executed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DlCommand {
+    /// Physical RDRAM address of the command
+    pub addr: u32,
+    /// Display list nesting depth (0 = task's root list)
+    pub depth: u8,
+    pub word0: u32,
+    pub word1: u32,
+}
+
+/// A framebuffer pixel changed by a command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PixelChange {
+    /// Index into the framebuffer (y * width + x)
+    pub index: u32,
+    pub before: u32,
+    pub after: u32,
+}
+
+/// A command and the pixels it changed
+#[derive(Debug, Clone)]
+pub struct DlStep {
+    pub command: DlCommand,
+    pub changes: Vec<PixelChange>,
+}
+
+/// Recorded display list of one graphics task
+#[derive(Debug, Clone)]
+pub struct DisplayListCapture {
+    width: u32,
+    height: u32,
+    steps: Vec<DlStep>,
+    /// Number of steps applied to `frame`
+    position: usize,
+    /// Framebuffer after `position` steps
+    frame: Vec<u32>,
+    /// Latest framebuffer seen while recording
+    recorded: Vec<u32>,
+    /// Microcode the task ran, which determines the command encoding
+    microcode: MicrocodeType,
+}
+
+impl DisplayListCapture {
+    /// Start recording on top of the framebuffer as it was before the task
+    pub(crate) fn new(width: u32, height: u32, pixels: &[u32], microcode: MicrocodeType) -> Self {
+        Self {
+            width,
+            height,
+            steps: Vec::new(),
+            position: 0,
+            frame: pixels.to_vec(),
+            recorded: pixels.to_vec(),
+            microcode,
+        }
+    }
+
+    /// Record a command about to be executed
+    pub(crate) fn begin_command(&mut self, command: DlCommand) {
+        self.steps.push(DlStep {
+            command,
+            changes: Vec::new(),
+        });
+    }
+
+    /// Attribute framebuffer changes since the last call to the newest command
+    ///
+    /// Commands that call nested display lists end after their children, so
+    /// by then the children have already claimed the pixels they drew.
+    pub(crate) fn end_command(&mut self, pixels: &[u32]) {
+        let Some(step) = self.steps.last_mut() else {
+            return;
+        };
+        if pixels.len() != self.recorded.len() {
+            // Framebuffer was resized mid-task; start diffing afresh
+            self.recorded = pixels.to_vec();
+            return;
+        }
+        for (index, (old, &new)) in self.recorded.iter_mut().zip(pixels).enumerate() {
+            if *old != new {
+                step.changes.push(PixelChange {
+                    index: index as u32,
+                    before: *old,
+                    after: new,
+                });
+                *old = new;
+            }
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn steps(&self) -> &[DlStep] {
+        &self.steps
+    }
+
+    /// Number of commands executed so far when stepping
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Framebuffer after the commands executed so far
+    pub fn frame(&self) -> &[u32] {
+        &self.frame
+    }
+
+    /// Execute the next command, returning it
+    pub fn step_forward(&mut self) -> Option<&DlStep> {
+        let step = self.steps.get(self.position)?;
+        for change in &step.changes {
+            if let Some(pixel) = self.frame.get_mut(change.index as usize) {
+                *pixel = change.after;
+            }
+        }
+        self.position += 1;
+        Some(step)
+    }
+
+    /// Undo the last executed command, returning it
+    pub fn step_back(&mut self) -> Option<&DlStep> {
+        self.position = self.position.checked_sub(1)?;
+        let step = &self.steps[self.position];
+        for change in &step.changes {
+            if let Some(pixel) = self.frame.get_mut(change.index as usize) {
+                *pixel = change.before;
+            }
+        }
+        Some(step)
+    }
+
+    /// Step to a given position (clamped to the number of commands)
+    pub fn seek(&mut self, position: usize) {
+        let position = position.min(self.steps.len());
+        while self.position < position {
+            self.step_forward();
+        }
+        while self.position > position {
+            self.step_back();
+        }
+    }
+
+    /// Bounding box (x0, y0, x1, y1 inclusive) of the pixels a step changed
+    pub fn changed_bounds(&self, step: &DlStep) -> Option<(u32, u32, u32, u32)> {
+        if self.width == 0 {
+            return None;
+        }
+        step.changes.iter().fold(None, |bounds, change| {
+            let x = change.index % self.width;
+            let y = change.index / self.width;
+            Some(match bounds {
+                None => (x, y, x, y),
+                Some((x0, y0, x1, y1)) => (x0.min(x), y0.min(y), x1.max(x), y1.max(y)),
+            })
+        })
+    }
+
+    /// Decode a captured command with the encoding of the task's microcode
+    pub fn decode(&self, command: &DlCommand) -> String {
+        if self.microcode == MicrocodeType::F3DEX {
+            decode_f3dex_command(command.word0, command.word1)
+        } else {
+            decode_command(command.word0, command.word1)
+        }
+    }
+
+    /// Readable listing of every captured command
+    pub fn dump(&self) -> String {
+        let mut out = String::new();
+        for (i, step) in self.steps.iter().enumerate() {
+            let cmd = &step.command;
+            let _ = write!(
+                out,
+                "{:5}  {:08X}  {:08X} {:08X}  {}{}",
+                i,
+                cmd.addr,
+                cmd.word0,
+                cmd.word1,
+                "  ".repeat(cmd.depth as usize),
+                self.decode(cmd)
+            );
+            if !step.changes.is_empty() {
+                let _ = write!(out, "  ; {} px", step.changes.len());
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Decode an F3DEX2 command into readable text
+pub fn decode_command(word0: u32, word1: u32) -> String {
+    match word0 >> 24 {
+        0x00 => "G_SPNOOP".to_string(),
+        0x01 => {
+            let n = (word0 >> 12) & 0xFF;
+            format!(
+                "G_VTX n={} v0={} addr=0x{:08X}",
+                n,
+                ((word0 >> 1) & 0x7F).saturating_sub(n),
+                word1
+            )
+        }
+        0x02 => format!("G_MODIFYVTX 0x{:08X}", word1),
+        0x03 => format!(
+            "G_CULLDL v={}..={}",
+            (word0 & 0xFFFF) / 2,
+            (word1 & 0xFFFF) / 2
+        ),
+        0x04 => format!("G_BRANCH_Z z=0x{:08X}", word1),
+        0x05 => format!("G_TRI1 {}", decode_triangle(word0)),
+        0x06 => format!(
+            "G_TRI2 {} {}",
+            decode_triangle(word0),
+            decode_triangle(word1)
+        ),
+        0x07 => format!(
+            "G_QUAD {} {}",
+            decode_triangle(word0),
+            decode_triangle(word1)
+        ),
+        0xD7 => format!(
+            "G_TEXTURE tile={} on={} scale=0x{:04X},0x{:04X}",
+            (word0 >> 8) & 0x07,
+            (word0 >> 1) & 0x7F,
+            word1 >> 16,
+            word1 & 0xFFFF
+        ),
+        0xD8 => format!("G_POPMTX n={}", word1 / 64),
+        0xD9 => format!(
+            "G_GEOMETRYMODE keep=0x{:06X} set=0x{:08X}",
+            word0 & 0x00FF_FFFF,
+            word1
+        ),
+        // G_MTX_PUSH is inverted in F3DEX2
+        0xDA => decode_matrix((word0 & 0xFF) ^ 0x01, word1),
+        0xDB => format!(
+            "G_MOVEWORD index=0x{:02X} offset=0x{:04X} value=0x{:08X}",
+            (word0 >> 16) & 0xFF,
+            word0 & 0xFFFF,
+            word1
+        ),
+        0xDC => format!(
+            "G_MOVEMEM index={} offset=0x{:03X} addr=0x{:08X}",
+            word0 & 0xFF,
+            ((word0 >> 8) & 0xFF) * 8,
+            word1
+        ),
+        0xDD => format!("G_LOAD_UCODE addr=0x{:08X}", word1),
+        0xDE => decode_display_list(word0, word1),
+        0xDF => "G_ENDDL".to_string(),
+        0xE0 => "G_NOOP".to_string(),
+        0xE1 => format!("G_RDPHALF_1 0x{:08X}", word1),
+        0xE2 | 0xE3 => {
+            let len = (word0 & 0xFF) + 1;
+            format!(
+                "G_SETOTHERMODE_{} shift={} len={} data=0x{:08X}",
+                if word0 >> 24 == 0xE3 { 'H' } else { 'L' },
+                32u32.saturating_sub(((word0 >> 8) & 0xFF) + len),
+                len,
+                word1
+            )
+        }
+        0xF1 => format!("G_RDPHALF_2 0x{:08X}", word1),
+        0xE4..=0xFF => decode_rdp_command(word0, word1),
+        id => format!("UNKNOWN 0x{:02X}", id),
+    }
+}
+
+/// Decode an F3DEX (GBI 1.x) command into readable text
+fn decode_f3dex_command(word0: u32, word1: u32) -> String {
+    match word0 >> 24 {
+        0x00 => "G_SPNOOP".to_string(),
+        0x01 => decode_matrix((word0 >> 16) & 0xFF, word1),
+        0x03 => format!(
+            "G_MOVEMEM index=0x{:02X} addr=0x{:08X}",
+            (word0 >> 16) & 0xFF,
+            word1
+        ),
+        0x04 => format!(
+            "G_VTX n={} v0={} addr=0x{:08X}",
+            (word0 >> 10) & 0x3F,
+            ((word0 >> 16) & 0xFF) / 2,
+            word1
+        ),
+        0x06 => decode_display_list(word0, word1),
+        0xAF => format!("G_LOAD_UCODE addr=0x{:08X}", word1),
+        0xB0 => format!("G_BRANCH_Z z=0x{:08X}", word1),
+        0xB1 => format!(
+            "G_TRI2 {} {}",
+            decode_triangle(word0),
+            decode_triangle(word1)
+        ),
+        0xB3 => format!("G_RDPHALF_2 0x{:08X}", word1),
+        0xB4 => format!("G_RDPHALF_1 0x{:08X}", word1),
+        0xB5 => {
+            let v = |shift: u32| ((word1 >> shift) & 0xFF) / 2;
+            format!("G_QUAD v={},{},{},{}", v(24), v(16), v(8), v(0))
+        }
+        0xB6 => format!("G_CLEARGEOMETRYMODE 0x{:08X}", word1),
+        0xB7 => format!("G_SETGEOMETRYMODE 0x{:08X}", word1),
+        0xB8 => "G_ENDDL".to_string(),
+        0xB9 | 0xBA => format!(
+            "G_SETOTHERMODE_{} shift={} len={} data=0x{:08X}",
+            if word0 >> 24 == 0xBA { 'H' } else { 'L' },
+            (word0 >> 8) & 0xFF,
+            word0 & 0xFF,
+            word1
+        ),
+        0xBB => format!(
+            "G_TEXTURE tile={} on={} scale=0x{:04X},0x{:04X}",
+            (word0 >> 8) & 0x07,
+            word0 & 0xFF,
+            word1 >> 16,
+            word1 & 0xFFFF
+        ),
+        0xBC => format!(
+            "G_MOVEWORD index=0x{:02X} offset=0x{:04X} value=0x{:08X}",
+            word0 & 0xFF,
+            (word0 >> 8) & 0xFFFF,
+            word1
+        ),
+        0xBD => "G_POPMTX".to_string(),
+        0xBE => format!(
+            "G_CULLDL v={}..={}",
+            (word0 & 0xFFFF) / 2,
+            (word1 & 0xFFFF) / 2
+        ),
+        0xBF => format!("G_TRI1 {}", decode_triangle(word1)),
+        0xE4..=0xFF => decode_rdp_command(word0, word1),
+        id => format!("UNKNOWN 0x{:02X}", id),
+    }
+}
+
+/// Decode three vertex indices (times 2) packed in the low 24 bits
+fn decode_triangle(word: u32) -> String {
+    let v = |shift: u32| ((word >> shift) & 0xFF) / 2;
+    format!("v={},{},{}", v(16), v(8), v(0))
+}
+
+/// Decode G_MTX parameters (G_MTX_PUSH, G_MTX_LOAD, G_MTX_PROJECTION)
+fn decode_matrix(params: u32, addr: u32) -> String {
+    format!(
+        "G_MTX {} {}{} addr=0x{:08X}",
+        if params & 0x04 != 0 {
+            "PROJECTION"
+        } else {
+            "MODELVIEW"
+        },
+        if params & 0x02 != 0 { "LOAD" } else { "MUL" },
+        if params & 0x01 != 0 { " PUSH" } else { "" },
+        addr
+    )
+}
+
+/// Decode a G_DL call or branch
+fn decode_display_list(word0: u32, addr: u32) -> String {
+    format!(
+        "G_DL {} addr=0x{:08X}",
+        if (word0 >> 16) & 0xFF == 0 {
+            "CALL"
+        } else {
+            "BRANCH"
+        },
+        addr
+    )
+}
+
+/// Decode an RDP command passed through the display list
+fn decode_rdp_command(word0: u32, word1: u32) -> String {
+    // Rectangle corners are 10.2 fixed point; word0 holds the far corner
+    let corner = |word: u32| ((word >> 14) & 0x3FF, (word >> 2) & 0x3FF);
+    match (word0 >> 24) & 0x3F {
+        0x24 => "G_TEXRECT".to_string(),
+        0x25 => "G_TEXRECTFLIP".to_string(),
+        0x26 => "G_RDPLOADSYNC".to_string(),
+        0x27 => "G_RDPPIPESYNC".to_string(),
+        0x28 => "G_RDPTILESYNC".to_string(),
+        0x29 => "G_RDPFULLSYNC".to_string(),
+        0x2D => {
+            let (x0, y0) = corner(word1);
+            let (x1, y1) = corner(word0);
+            format!("G_SETSCISSOR ({},{})-({},{})", x0, y0, x1, y1)
+        }
+        0x2F => format!(
+            "G_RDPSETOTHERMODE 0x{:06X}{:08X}",
+            word0 & 0x00FF_FFFF,
+            word1
+        ),
+        0x30 => "G_LOADTLUT".to_string(),
+        0x32 => "G_SETTILESIZE".to_string(),
+        0x33 => "G_LOADBLOCK".to_string(),
+        0x34 => "G_LOADTILE".to_string(),
+        0x35 => "G_SETTILE".to_string(),
+        0x36 => {
+            let (x0, y0) = corner(word1);
+            let (x1, y1) = corner(word0);
+            format!("G_FILLRECT ({},{})-({},{})", x0, y0, x1, y1)
+        }
+        0x37 => format!("G_SETFILLCOLOR 0x{:08X}", word1),
+        0x38 => format!("G_SETFOGCOLOR 0x{:08X}", word1),
+        0x39 => format!("G_SETBLENDCOLOR 0x{:08X}", word1),
+        0x3A => format!("G_SETPRIMCOLOR 0x{:08X}", word1),
+        0x3B => format!("G_SETENVCOLOR 0x{:08X}", word1),
+        0x3C => format!("G_SETCOMBINE 0x{:06X}{:08X}", word0 & 0x00FF_FFFF, word1),
+        0x3D => format!("G_SETTIMG addr=0x{:08X}", word1),
+        0x3E => format!("G_SETZIMG addr=0x{:08X}", word1),
+        0x3F => format!(
+            "G_SETCIMG width={} addr=0x{:08X}",
+            (word0 & 0x3FF) + 1,
+            word1
+        ),
+        id => format!("RDP 0x{:02X}", id),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_commands() {
+        assert_eq!(
+            decode_command(0x0100_3006, 0x0000_0200),
+            "G_VTX n=3 v0=0 addr=0x00000200"
+        );
+        assert_eq!(decode_command(0x0500_0204, 0), "G_TRI1 v=0,1,2");
+        assert_eq!(
+            decode_command(0xDA38_0002, 0x8010_0000),
+            "G_MTX MODELVIEW LOAD PUSH addr=0x80100000"
+        );
+        assert_eq!(
+            decode_command(0xDE00_0000, 0x0000_0300),
+            "G_DL CALL addr=0x00000300"
+        );
+        assert_eq!(decode_command(0xDF00_0000, 0), "G_ENDDL");
+        assert_eq!(
+            decode_command(0xF700_0000, 0xFFFF_0000),
+            "G_SETFILLCOLOR 0xFFFF0000"
+        );
+        assert_eq!(
+            decode_command(0xF600_0000 | (20 << 14) | (10 << 2), (5 << 14) | (2 << 2)),
+            "G_FILLRECT (5,2)-(20,10)"
+        );
+    }
+
+    #[test]
+    fn test_decode_f3dex_commands() {
+        let capture = DisplayListCapture::new(1, 1, &[0], MicrocodeType::F3DEX);
+        let decode = |word0, word1| {
+            capture.decode(&DlCommand {
+                addr: 0,
+                depth: 0,
+                word0,
+                word1,
+            })
+        };
+        assert_eq!(
+            decode(0x0406_0C2F, 0x0300_0000),
+            "G_VTX n=3 v0=3 addr=0x03000000"
+        );
+        assert_eq!(decode(0xBF00_0000, 0x0000_0204), "G_TRI1 v=0,1,2");
+        assert_eq!(
+            decode(0x0106_0040, 0x8010_0000),
+            "G_MTX PROJECTION LOAD addr=0x80100000"
+        );
+        assert_eq!(decode(0xB800_0000, 0), "G_ENDDL");
+        assert_eq!(
+            decode(0xF700_0000, 0xFFFF_0000),
+            "G_SETFILLCOLOR 0xFFFF0000"
+        );
+    }
+
+    fn command(word0: u32) -> DlCommand {
+        DlCommand {
+            addr: 0x100,
+            depth: 0,
+            word0,
+            word1: 0,
+        }
+    }
+
+    #[test]
+    fn test_capture_steps_forward_and_back() {
+        // 4x2 framebuffer, two commands each drawing one pixel
+        let mut capture = DisplayListCapture::new(4, 2, &[0; 8], MicrocodeType::F3DEX2);
+        capture.begin_command(command(0xF700_0000));
+        capture.end_command(&[0, 0, 0, 0, 0, 7, 0, 0]);
+        capture.begin_command(command(0xF600_0000));
+        capture.end_command(&[0, 0, 0, 0, 0, 7, 0, 9]);
+
+        assert_eq!(capture.steps()[0].changes.len(), 1);
+        assert_eq!(
+            capture.changed_bounds(&capture.steps()[0]),
+            Some((1, 1, 1, 1))
+        );
+
+        assert_eq!(capture.frame(), &[0; 8]);
+        capture.step_forward();
+        assert_eq!(capture.frame()[5], 7);
+        assert_eq!(capture.frame()[7], 0);
+        capture.step_forward();
+        assert_eq!(capture.frame()[7], 9);
+        assert!(capture.step_forward().is_none());
+
+        capture.step_back();
+        assert_eq!(capture.position(), 1);
+        assert_eq!(capture.frame()[7], 0);
+        capture.seek(0);
+        assert_eq!(capture.frame(), &[0; 8]);
+        assert!(capture.step_back().is_none());
+    }
+
+    #[test]
+    fn test_nested_call_changes_belong_to_children() {
+        let mut capture = DisplayListCapture::new(2, 1, &[0, 0], MicrocodeType::F3DEX2);
+        capture.begin_command(command(0xDE00_0000));
+        capture.begin_command(command(0xF600_0000));
+        capture.end_command(&[5, 0]);
+        // The G_DL call ends after its child and gets no pixels of its own
+        capture.end_command(&[5, 0]);
+
+        assert!(capture.steps()[0].changes.is_empty());
+        assert_eq!(capture.steps()[1].changes.len(), 1);
+        assert!(capture.dump().contains("G_FILLRECT"));
+    }
+}
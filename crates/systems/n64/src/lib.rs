@@ -9,14 +9,34 @@
 //!   - **RSP**: Reality Signal Processor (geometry/audio processing - stub)
 //! - **Memory**: 4MB RDRAM + cartridge ROM
 //! - **Timing**: NTSC (~60 Hz frame rate)
+//!
+//! # Usage Example
+//!
+//! ```
+//! use emu_core::System;
+//! use emu_n64::N64System;
+//!
+//! // A blank big-endian (.z64) image: just the header magic
+//! let mut rom = vec![0u8; 0x1000];
+//! rom[..4].copy_from_slice(&[0x80, 0x37, 0x12, 0x40]);
+//!
+//! let mut n64 = N64System::new();
+//! n64.mount("Cartridge", &rom).unwrap();
+//! assert!(n64.is_mounted("Cartridge"));
+//!
+//! let frame = n64.step_frame().unwrap();
+//! assert_eq!(frame.pixels.len(), (frame.width * frame.height) as usize);
+//! ```
 
 #![allow(clippy::upper_case_acronyms)]
 
 mod bus;
 mod cartridge;
 mod cpu;
+mod dl_debug;
 mod mi;
 mod pif;
+mod rcp_thread;
 mod rdp;
 mod rdp_renderer;
 #[cfg(feature = "opengl")]
@@ -24,6 +44,7 @@ mod rdp_renderer_opengl;
 mod rdp_renderer_software;
 mod rsp;
 mod rsp_hle;
+mod si;
 mod vi;
 
 use bus::N64Bus;
@@ -66,6 +87,8 @@ pub struct DebugInfo {
     pub rdp_status: u32,
     /// Frame buffer resolution
     pub framebuffer_resolution: String,
+    /// Active RDP rendering backend
+    pub rdp_renderer: String,
 }
 
 /// N64 system implementation
@@ -76,7 +99,9 @@ pub struct N64System {
 }
 
 // Re-export controller types for convenience
-pub use pif::{ControllerButtons, ControllerState};
+pub use pif::{pad, ControllerButtons, ControllerState, STICK_MAX};
+
+pub use dl_debug::{decode_command, DisplayListCapture, DlCommand, DlStep, PixelChange};
 
 impl N64System {
     /// Create a new N64 system
@@ -109,6 +134,29 @@ impl N64System {
         self.cpu.bus_mut().set_controller4(state);
     }
 
+    /// Update the controller on a port (0-3)
+    pub fn set_controller_state(&mut self, port: usize, state: ControllerState) {
+        match port {
+            0 => self.set_controller1(state),
+            1 => self.set_controller2(state),
+            2 => self.set_controller3(state),
+            3 => self.set_controller4(state),
+            _ => {}
+        }
+    }
+
+    /// Update a controller from the frontend's common button layout (see
+    /// [`pad`]), with the directions driving the analog stick
+    pub fn set_controller(&mut self, port: usize, state: u16) {
+        self.set_controller_state(port, ControllerState::from_pad(state));
+    }
+
+    /// Plug a controller into or unplug it from a port (0-3); games only see
+    /// controllers that are plugged in, and only port 1 is at power on
+    pub fn set_controller_connected(&mut self, port: usize, connected: bool) {
+        self.cpu.bus_mut().set_controller_connected(port, connected);
+    }
+
     /// Enable OpenGL hardware rendering (requires OpenGL feature)
     /// This should be called from the frontend after obtaining a GL context
     #[cfg(feature = "opengl")]
@@ -116,6 +164,35 @@ impl N64System {
         self.cpu.bus_mut().enable_opengl_renderer(gl)
     }
 
+    /// Run RSP tasks and RDP command lists on a worker thread
+    ///
+    /// The CPU loop then no longer waits for rasterization. The results are
+    /// picked up at the VI interrupt and at the end of each frame, so the RSP
+    /// halts and DPC_STATUS reports the command buffer ready only then.
+    /// Fails with the OpenGL renderer, which must stay on the thread that
+    /// owns the GL context; enabling OpenGL later stops the worker.
+    pub fn set_threaded_rcp(&mut self, enabled: bool) -> Result<(), String> {
+        self.cpu.bus_mut().set_threaded_rcp(enabled)
+    }
+
+    /// True if RSP tasks and RDP command lists run on a worker thread
+    pub fn is_threaded_rcp(&self) -> bool {
+        self.cpu.bus().is_threaded_rcp()
+    }
+
+    /// Capture the display list of the next RSP graphics task
+    ///
+    /// Collect the result with [`take_display_list_capture`](Self::take_display_list_capture)
+    /// once a frame has run.
+    pub fn capture_display_list(&mut self) {
+        self.cpu.bus_mut().request_display_list_capture();
+    }
+
+    /// Take the most recently completed display list capture
+    pub fn take_display_list_capture(&mut self) -> Option<DisplayListCapture> {
+        self.cpu.bus_mut().take_display_list_capture()
+    }
+
     /// Get debug information for the GUI overlay
     pub fn get_debug_info(&self) -> DebugInfo {
         let bus = self.cpu.bus();
@@ -148,14 +225,17 @@ impl N64System {
         };
 
         // Get RSP vertex count
-        let rsp_vertex_count = bus.rsp().vertex_count();
+        let rsp_vertex_count = bus.rsp_vertex_count();
 
         // Get RDP status
         let rdp_status = bus.rdp().read_register(0x0C); // DPC_STATUS register
 
-        // Get framebuffer resolution
-        let frame = bus.rdp().get_frame();
-        let framebuffer_resolution = format!("{}x{}", frame.width, frame.height);
+        // Get framebuffer resolution (the RDP's until the VI is set up)
+        let (width, height) = bus
+            .vi()
+            .resolution()
+            .unwrap_or_else(|| bus.rdp().resolution());
+        let framebuffer_resolution = format!("{}x{}", width, height);
 
         DebugInfo {
             rom_name,
@@ -165,6 +245,7 @@ impl N64System {
             rsp_vertex_count,
             rdp_status,
             framebuffer_resolution,
+            rdp_renderer: bus.rdp().renderer_name().to_string(),
         }
     }
 }
@@ -180,7 +261,7 @@ impl System for N64System {
 
     fn reset(&mut self) {
         self.cpu.reset();
-        self.cpu.bus_mut().rdp_mut().reset();
+        self.cpu.bus_mut().reset_rdp();
         self.current_cycles = 0;
     }
 
@@ -199,12 +280,13 @@ impl System for N64System {
             }
         }
 
-        // NTSC has 262 scanlines per frame
-        const SCANLINES_PER_FRAME: u32 = 262;
-        let cycles_per_scanline = self.frame_cycles / SCANLINES_PER_FRAME;
+        // Lines per frame come from VI_V_SYNC (263 for NTSC, 313 for PAL;
+        // interlaced fields alternate between two counts)
+        let scanlines = self.cpu.bus().vi().lines_per_field();
+        let cycles_per_scanline = self.frame_cycles / scanlines;
 
         // Execute CPU cycles for one frame, updating VI scanline
-        for scanline in 0..SCANLINES_PER_FRAME {
+        for scanline in 0..scanlines {
             let target_cycles = (scanline + 1) * cycles_per_scanline;
 
             // Execute CPU until we reach the cycles for this scanline
@@ -230,11 +312,19 @@ impl System for N64System {
                         self.cpu.cpu.set_interrupt(5);
                     }
                 }
+                // The VI line drops once the interrupt is acknowledged, so
+                // the next one comes at VI_INTR of the next frame
+                if pending & crate::mi::MI_INTR_VI == 0 {
+                    self.cpu.cpu.clear_interrupt(3);
+                }
             }
 
             // Update VI scanline and check for interrupt
             let bus = self.cpu.bus_mut();
             if bus.vi_mut().update_scanline(scanline) {
+                // Work queued to the RCP thread completes before the handler runs
+                bus.sync_rcp();
+
                 // VI interrupt triggered - set interrupt in MI (only if not already set)
                 let mi_status = bus.mi().get_interrupt_status();
                 if (mi_status & crate::mi::MI_INTR_VI) == 0 {
@@ -247,12 +337,19 @@ impl System for N64System {
             }
         }
 
-        // Get frame from RDP
-        let frame = self.cpu.bus().rdp().get_frame().clone();
+        // Scan out the frame, waiting for the RCP thread and submitting any
+        // batched GPU work first
+        let bus = self.cpu.bus_mut();
+        bus.sync_rcp();
+        bus.rdp_mut().finish_frame();
+        let frame = bus.video_frame();
+        bus.vi_mut().end_frame();
         Ok(frame)
     }
 
     fn save_state(&self) -> serde_json::Value {
+        // FPU registers are stored as bit patterns; JSON has no NaN or infinity
+        let fpr: Vec<u64> = self.cpu.cpu.fpr.iter().map(|f| f.to_bits()).collect();
         serde_json::json!({
             "version": 1,
             "cpu": {
@@ -261,7 +358,11 @@ impl System for N64System {
                 "hi": self.cpu.cpu.hi,
                 "lo": self.cpu.cpu.lo,
                 "cycles": self.cpu.cpu.cycles,
-            }
+                "cp0": self.cpu.cpu.cp0,
+                "fpr": fpr,
+                "fcr31": self.cpu.cpu.fcr31,
+            },
+            "bus": self.cpu.bus().save_state(),
         })
     }
 
@@ -278,6 +379,22 @@ impl System for N64System {
             self.cpu.cpu.hi = cpu_state["hi"].as_u64().unwrap_or(0);
             self.cpu.cpu.lo = cpu_state["lo"].as_u64().unwrap_or(0);
             self.cpu.cpu.cycles = cpu_state["cycles"].as_u64().unwrap_or(0);
+
+            // States from before COP0/COP1 were saved keep the current values
+            if let Some(cp0) = cpu_state.get("cp0") {
+                self.cpu.cpu.cp0 = serde_json::from_value(cp0.clone())?;
+            }
+            if let Some(fpr) = cpu_state.get("fpr") {
+                let bits: [u64; 32] = serde_json::from_value(fpr.clone())?;
+                self.cpu.cpu.fpr = bits.map(f64::from_bits);
+            }
+            if let Some(fcr31) = cpu_state.get("fcr31") {
+                self.cpu.cpu.fcr31 = serde_json::from_value(fcr31.clone())?;
+            }
+        }
+        // Likewise memory and the RCP, which older states did not include
+        if let Some(bus_state) = v.get("bus") {
+            self.cpu.bus_mut().load_state(bus_state)?;
         }
         Ok(())
     }
@@ -355,6 +472,45 @@ mod tests {
         assert!(sys2.load_state(&state).is_ok());
     }
 
+    #[test]
+    fn test_save_state_restores_memory_and_rcp() {
+        let rom = include_bytes!("../../../../test_roms/n64/test_pong3d.z64");
+        let mut sys = N64System::new();
+        sys.mount("Cartridge", rom).unwrap();
+        for _ in 0..5 {
+            sys.step_frame().unwrap();
+        }
+        sys.cpu.cpu.fpr[3] = f64::NAN;
+        let word = sys.cpu.bus().read_word(0x0010_0000);
+        let state = sys.save_state();
+        let frame = sys.step_frame().unwrap();
+
+        // A fresh system resumes where the state was saved
+        let mut sys2 = N64System::new();
+        sys2.mount("Cartridge", rom).unwrap();
+        sys2.cpu.bus_mut().write_word(0x0010_0000, !word);
+        sys2.load_state(&state).unwrap();
+        assert_eq!(sys2.cpu.bus().read_word(0x0010_0000), word);
+        assert_eq!(
+            emu_core::save_state::state_mismatch(&state, &sys2.save_state()),
+            None
+        );
+        assert!(sys2.cpu.cpu.fpr[3].is_nan());
+        assert_eq!(sys2.step_frame().unwrap().pixels, frame.pixels);
+    }
+
+    #[test]
+    fn test_golden_save_state() {
+        // Saved after 30 frames of the test ROM
+        emu_core::save_state::check_golden_state(
+            &mut N64System::new(),
+            "Cartridge",
+            include_bytes!("../../../../test_roms/n64/test.z64"),
+            include_str!("../../../../test_roms/n64/test.state.json"),
+        )
+        .unwrap();
+    }
+
     #[test]
     fn test_rdp_integration() {
         let sys = N64System::new();
@@ -384,6 +540,29 @@ mod tests {
         assert_ne!(status, 0); // Should have CBUF_READY bit set
     }
 
+    #[test]
+    fn test_unaligned_word_access_at_end_of_rdram() {
+        use emu_core::cpu_mips_r4300i::MemoryMips;
+
+        let mut sys = N64System::new();
+        let bus = sys.cpu.bus_mut();
+
+        // Word accesses drop the low address bits instead of running off
+        // the end of RDRAM
+        bus.write_word(0x003F_FFFF, 0x1234_5678);
+        assert_eq!(bus.read_word(0x003F_FFFC), 0x1234_5678);
+        assert_eq!(bus.read_word(0x003F_FFFE), 0x1234_5678);
+
+        // Multi-byte accesses wrap at the top of the address space: the
+        // second word of this doubleword is RDRAM word 0
+        bus.write_word(0x0000_0000, 0xCAFE_BABE);
+        assert_eq!(bus.read_doubleword(0xFFFF_FFFC), 0x0000_0000_CAFE_BABE);
+
+        // Only the first byte lands on unmapped space; the rest wrap to $0
+        bus.write_word(0xFFFF_FFFF, 0x1122_3344);
+        assert_eq!(bus.read_word(0x0000_0000), 0x2233_44BE);
+    }
+
     #[test]
     fn test_step_frame_returns_rdp_frame() {
         let mut sys = N64System::new();
@@ -395,6 +574,89 @@ mod tests {
         assert_eq!(frame.height, 240);
     }
 
+    /// Program the VI for a 16-bit 320x240 framebuffer at `origin`
+    fn setup_vi_320x240(bus: &mut N64Bus, origin: u32) {
+        use emu_core::cpu_mips_r4300i::MemoryMips;
+
+        bus.write_word(0x04400000, 0x0000_3002); // VI_STATUS: 16-bit
+        bus.write_word(0x04400004, origin); // VI_ORIGIN
+        bus.write_word(0x04400008, 320); // VI_WIDTH
+    }
+
+    #[test]
+    fn test_vi_scans_out_cpu_drawn_framebuffer() {
+        use emu_core::cpu_mips_r4300i::MemoryMips;
+
+        let mut sys = N64System::new();
+        let bus = sys.cpu.bus_mut();
+        // Blue pixels at (0,0) and (1,0), red at (10,20)
+        bus.write_word(0x0020_0000, 0x003F_003F);
+        bus.write_halfword(0x0020_0000 + (20 * 320 + 10) * 2, 0xF801);
+        setup_vi_320x240(bus, 0x0020_0000);
+
+        let frame = sys.step_frame().unwrap();
+        assert_eq!((frame.width, frame.height), (320, 237));
+        assert_eq!(frame.pixels[0], 0xFF0000FF);
+        assert_eq!(frame.pixels[1], 0xFF0000FF);
+        assert_eq!(frame.pixels[20 * 320 + 10], 0xFFFF0000);
+        assert_eq!(frame.pixels[2], 0xFF000000);
+        assert_eq!(sys.get_debug_info().framebuffer_resolution, "320x237");
+    }
+
+    #[test]
+    fn test_vi_shows_rdp_frame_for_its_color_image() {
+        use emu_core::cpu_mips_r4300i::MemoryMips;
+
+        let mut sys = N64System::new();
+        let bus = sys.cpu.bus_mut();
+        // SET_COLOR_IMAGE 0x200000, SET_FILL_COLOR red, FILL_RECTANGLE
+        bus.write_word(0x00100000, 0x3F10_013F);
+        bus.write_word(0x00100004, 0x0020_0000);
+        bus.write_word(0x00100008, 0x37000000);
+        bus.write_word(0x0010000C, 0xFFFF0000);
+        bus.write_word(0x00100010, (0x36 << 24) | (0x258 << 12) | 0x258);
+        bus.write_word(0x00100014, (0xC8 << 12) | 0xC8);
+        bus.write_word(0x04100000, 0x00100000); // DPC_START
+        bus.write_word(0x04100004, 0x00100018); // DPC_END
+
+        // libultra points VI_ORIGIN one line into the framebuffer
+        setup_vi_320x240(bus, 0x0020_0000 + 640);
+        let frame = sys.step_frame().unwrap();
+        assert_eq!((frame.width, frame.height), (320, 240));
+        assert_eq!(frame.pixels[100 * 320 + 100], 0xFFFF0000);
+
+        // Another buffer was not drawn by the RDP and comes from RDRAM
+        setup_vi_320x240(sys.cpu.bus_mut(), 0x0030_0000);
+        let frame = sys.step_frame().unwrap();
+        assert_eq!(frame.height, 237);
+        assert_eq!(frame.pixels[100 * 320 + 100], 0xFF000000);
+    }
+
+    #[test]
+    fn test_vi_interrupt_at_vi_intr_line() {
+        use emu_core::cpu_mips_r4300i::MemoryMips;
+
+        let mut sys = N64System::new();
+        let bus = sys.cpu.bus_mut();
+        bus.write_word(0x0430000C, 0x0800); // Enable VI in MI
+        bus.write_word(0x0440000C, 2 * 100); // VI_INTR: line 100
+        sys.step_frame().unwrap();
+
+        let bus = sys.cpu.bus_mut();
+        assert_eq!(bus.read_word(0x04300008), crate::mi::MI_INTR_VI);
+        // VI_CURRENT has moved on to the last line of the frame
+        assert_eq!(bus.read_word(0x04400010), 2 * 262);
+
+        // Writing VI_CURRENT acknowledges the interrupt
+        bus.write_word(0x04400010, 0);
+        assert_eq!(bus.read_word(0x04300008), 0);
+
+        // A line past the end of the frame never interrupts
+        bus.write_word(0x0440000C, 2 * 300);
+        sys.step_frame().unwrap();
+        assert_eq!(sys.cpu.bus().read_word(0x04300008), 0);
+    }
+
     #[test]
     fn test_n64_display_list_smoke_test() {
         use emu_core::cpu_mips_r4300i::MemoryMips;
@@ -838,10 +1100,12 @@ mod tests {
         // Simulate game reading controller via PIF RAM (at 0x1FC007C0)
         let bus = sys.cpu.bus_mut();
 
-        // Write controller read command
+        // Write controller read command and run it
         bus.write_byte(0x1FC007C0, 0x01); // T=1 byte
         bus.write_byte(0x1FC007C1, 0x04); // R=4 bytes
         bus.write_byte(0x1FC007C2, 0x01); // Command 0x01 (read controller)
+        bus.write_byte(0x1FC007C7, 0xFE); // End of block
+        bus.write_byte(0x1FC007FF, 0x01); // PIF control byte
 
         // Read response
         let buttons_hi = bus.read_byte(0x1FC007C3);
@@ -877,18 +1141,22 @@ mod tests {
         state2.buttons.b = true;
         sys.set_controller2(state2);
 
+        sys.set_controller_connected(1, true);
+
         let bus = sys.cpu.bus_mut();
 
-        // Read controller 1 (at 0x1FC007C0)
-        bus.write_byte(0x1FC007C0, 0x01);
-        bus.write_byte(0x1FC007C1, 0x04);
-        bus.write_byte(0x1FC007C2, 0x01);
+        // Read controller 1 (channel 0 at 0x1FC007C0), then controller 2
+        // (channel 1 at 0x1FC007C8), each block padded to 8 bytes
+        for channel in 0..2 {
+            let base = 0x1FC007C0 + channel * 8;
+            bus.write_byte(base, 0x01);
+            bus.write_byte(base + 1, 0x04);
+            bus.write_byte(base + 2, 0x01);
+            bus.write_byte(base + 7, 0xFF);
+        }
+        bus.write_byte(0x1FC007D0, 0xFE);
+        bus.write_byte(0x1FC007FF, 0x01);
         let buttons1 = u16::from_be_bytes([bus.read_byte(0x1FC007C3), bus.read_byte(0x1FC007C4)]);
-
-        // Read controller 2 (at 0x1FC007C8)
-        bus.write_byte(0x1FC007C8, 0x01);
-        bus.write_byte(0x1FC007C9, 0x04);
-        bus.write_byte(0x1FC007CA, 0x01);
         let buttons2 = u16::from_be_bytes([bus.read_byte(0x1FC007CB), bus.read_byte(0x1FC007CC)]);
 
         // Verify controller 1 has A pressed
@@ -900,6 +1168,47 @@ mod tests {
         assert_ne!(buttons2 & (1 << 14), 0);
     }
 
+    #[test]
+    fn test_controller_read_through_si_dma() {
+        use emu_core::cpu_mips_r4300i::MemoryMips;
+
+        let mut sys = N64System::new();
+        sys.set_controller(0, pad::A | pad::LEFT);
+
+        let bus = sys.cpu.bus_mut();
+
+        // Command block in RDRAM: read controller 1, controller 2 is absent
+        let block: [u8; 16] = [
+            0xFF, 0x01, 0x04, 0x01, 0xFF, 0xFF, 0xFF, 0xFF, // Channel 0
+            0xFF, 0x01, 0x04, 0x01, 0xFF, 0xFF, 0xFF, 0xFF, // Channel 1
+        ];
+        for (i, &byte) in block.iter().enumerate() {
+            bus.write_byte(0x1000 + i as u32, byte);
+        }
+        bus.write_byte(0x1010, 0xFE);
+        bus.write_byte(0x103F, 0x01); // PIF control byte
+
+        // SI_DRAM_ADDR, then SI_PIF_ADDR_WR64B runs the block
+        bus.write_word(0xA480_0000, 0x1000);
+        bus.write_word(0xA480_0010, 0x1FC0_07C0);
+        assert_ne!(bus.read_word(0xA430_0008) & 0x02, 0, "MI SI interrupt");
+        assert_ne!(bus.read_word(0xA480_0018) & (1 << 12), 0, "SI_STATUS");
+
+        // Writing SI_STATUS acknowledges the interrupt
+        bus.write_word(0xA480_0018, 0);
+        assert_eq!(bus.read_word(0xA430_0008) & 0x02, 0);
+
+        // SI_PIF_ADDR_RD64B copies the responses back
+        bus.write_word(0xA480_0000, 0x2000);
+        bus.write_word(0xA480_0004, 0x1FC0_07C0);
+        let buttons = u16::from_be_bytes([bus.read_byte(0x2004), bus.read_byte(0x2005)]);
+        assert_eq!(buttons, 1 << 15);
+        assert_eq!(bus.read_byte(0x2006) as i8, -STICK_MAX);
+        assert_eq!(bus.read_byte(0x2007), 0);
+        assert_eq!(bus.read_byte(0x200A), 0x04 | 0x80, "no controller 2");
+        assert_eq!(bus.read_byte(0x203F), 0x00, "control byte cleared");
+    }
+
     #[test]
     fn test_enhanced_rom_interrupts() {
         // Test the enhanced ROM that properly sets up and handles interrupts
@@ -955,6 +1264,75 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_threaded_rcp_display_list() {
+        use emu_core::cpu_mips_r4300i::MemoryMips;
+
+        let mut sys = N64System::new();
+        assert!(!sys.is_threaded_rcp());
+        sys.set_threaded_rcp(true).unwrap();
+        assert!(sys.is_threaded_rcp());
+
+        let bus = sys.cpu.bus_mut();
+        // SET_FILL_COLOR red, FILL_RECTANGLE (50,50)-(150,150)
+        bus.write_word(0x00100000, 0x37000000);
+        bus.write_word(0x00100004, 0xFFFF0000);
+        bus.write_word(0x00100008, (0x36 << 24) | (0x258 << 12) | 0x258);
+        bus.write_word(0x0010000C, (0xC8 << 12) | 0xC8);
+        bus.write_word(0x04100000, 0x00100000); // DPC_START
+        bus.write_word(0x04100004, 0x00100010); // DPC_END
+
+        // The command list runs on the worker snapshot even if RDRAM changes
+        bus.write_word(0x00100004, 0xFF0000FF);
+
+        // Nothing is visible until the sync point
+        let dpc_status = bus.read_word(0x0410000C);
+        assert_eq!(dpc_status & 0x80, 0, "command buffer busy until sync");
+        assert_eq!(bus.rdp().get_frame().pixels[100 * 320 + 100], 0);
+
+        bus.sync_rcp();
+        assert_ne!(bus.read_word(0x0410000C) & 0x80, 0);
+        assert_eq!(bus.read_word(0x04100008), 0x00100010); // DPC_CURRENT
+        assert_eq!(bus.rdp().get_frame().pixels[100 * 320 + 100], 0xFFFF0000);
+
+        // Stopping the worker keeps the frame and the render state
+        sys.set_threaded_rcp(false).unwrap();
+        assert!(!sys.is_threaded_rcp());
+        let bus = sys.cpu.bus_mut();
+        assert_eq!(bus.rdp().get_frame().pixels[100 * 320 + 100], 0xFFFF0000);
+        bus.write_word(0x00100010, (0x36 << 24) | (0x28 << 12) | 0x28);
+        bus.write_word(0x00100014, 0);
+        bus.write_word(0x04100000, 0x00100010);
+        bus.write_word(0x04100004, 0x00100018);
+        // Fill color set by the worker is still in effect
+        assert_eq!(bus.rdp().get_frame().pixels[5 * 320 + 5], 0xFFFF0000);
+    }
+
+    #[test]
+    fn test_threaded_rcp_matches_inline() {
+        let test_rom = include_bytes!("../../../../test_roms/n64/test_pong3d.z64");
+        let run = |threaded: bool| {
+            let mut sys = N64System::default();
+            sys.mount("Cartridge", test_rom).unwrap();
+            sys.set_threaded_rcp(threaded).unwrap();
+            let rsp = sys.cpu.bus_mut().rsp_mut();
+            rsp.write_imem(0, 0x12);
+            for i in 1..256 {
+                rsp.write_imem(i, ((i / 4) as u8).wrapping_mul(17));
+            }
+            let mut frame = sys.step_frame().unwrap();
+            for _ in 0..20 {
+                frame = sys.step_frame().unwrap();
+            }
+            frame
+        };
+
+        let inline = run(false);
+        let threaded = run(true);
+        assert!(inline.pixels.iter().any(|&p| p != 0));
+        assert_eq!(inline.pixels, threaded.pixels);
+    }
+
     #[test]
     fn test_pong3d_rom_rendering() {
         // Test the 3D Pong ROM that uses RSP F3DEX display lists and 3D rendering
@@ -1057,24 +1435,13 @@ mod tests {
             "Should have some colored pixels from 3D rendering"
         );
 
-        // TODO: Currently all pixels are green, which suggests the rendering pipeline
-        // is partially working but the 3D geometry (paddles and ball) may not be
-        // rendering correctly yet. This could be due to:
-        // 1. Viewport/clipping issues
-        // 2. Matrix transformation issues
-        // 3. Triangle rasterization issues
-        // For now, we verify that *something* is being rendered, which confirms
-        // the RSP task structure reading and display list parsing is working.
+        // Both paddles and the ball are drawn
+        assert!(red_pixels > 0, "Left paddle should be drawn");
+        assert!(blue_pixels > 0, "Right paddle should be drawn");
+        assert!(green_pixels > 0, "Ball should be drawn");
 
         println!("  ✓ RSP task processing and rendering pipeline is working!");
-        println!("  Note: Full 3D geometry rendering needs more investigation");
-
-        // Note: Full 3D rendering verification would require:
-        // 1. RSP task DMA from ROM to DMEM
-        // 2. F3DEX display list parsing ✓
-        // 3. Matrix transformations
-        // 4. Vertex processing and triangle generation
-        // 5. RDP rasterization
+
         // This test validates the ROM structure and basic RSP integration
     }
 }
@@ -23,6 +23,8 @@
 //! - Bit 4: PI (Peripheral Interface)
 //! - Bit 5: DP (Display Processor)
 
+use serde::{Deserialize, Serialize};
+
 /// MI register offsets (relative to 0x04300000)
 const MI_MODE: u32 = 0x00;
 const MI_VERSION: u32 = 0x04;
@@ -31,8 +33,7 @@ const MI_INTR_MASK: u32 = 0x0C;
 
 /// Interrupt bit positions
 pub const MI_INTR_SP: u32 = 0x01; // Bit 0
-#[allow(dead_code)]
-const MI_INTR_SI: u32 = 0x02; // Bit 1
+pub const MI_INTR_SI: u32 = 0x02; // Bit 1
 #[allow(dead_code)]
 const MI_INTR_AI: u32 = 0x04; // Bit 2
 pub const MI_INTR_VI: u32 = 0x08; // Bit 3
@@ -41,6 +42,7 @@ const MI_INTR_PI: u32 = 0x10; // Bit 4
 pub const MI_INTR_DP: u32 = 0x20; // Bit 5
 
 /// MIPS Interface (MI) - Interrupt controller
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MipsInterface {
     /// MI_MODE register - system mode control
     mode: u32,
@@ -144,7 +146,6 @@ impl MipsInterface {
     }
 
     /// Clear an interrupt bit (called by hardware components)
-    #[allow(dead_code)] // Reserved for future use
     pub fn clear_interrupt(&mut self, interrupt_bit: u32) {
         self.intr &= !interrupt_bit;
     }
@@ -14,8 +14,21 @@
 //! - **Command 0x02**: Read controller pak (memory card)
 //! - **Command 0x03**: Write controller pak
 //!
-//! Controller state is accessed via PIF RAM at address 0x1FC007C0-0x1FC007FF
-//! Games write command blocks to PIF RAM, then read response blocks.
+//! Controller state is accessed via PIF RAM at address 0x1FC007C0-0x1FC007FF.
+//! Games DMA a command block there through the SI (or write it directly),
+//! set bit 0 of the control byte at 0x1FC007FF, and read the responses back.
+//!
+//! ## Joybus Command Blocks
+//!
+//! The block holds one entry per channel (channels 0-3 are the controller
+//! ports, channel 4 the cartridge):
+//! - **0x00**: skip this channel
+//! - **0xFF**: padding, ignored
+//! - **0xFE**: end of the block
+//! - otherwise **T R cmd data... response...**: T bytes sent (including the
+//!   command), R bytes received into the response area that follows
+//!
+//! A channel with no device sets bit 7 of its R byte ("no response").
 //!
 //! ## Button State Convention
 //!
@@ -38,12 +51,16 @@
 //! - X axis: -128 (left) to +127 (right)
 //! - Y axis: -128 (down) to +127 (up)
 //!
+//! A real stick only reaches about ±80, which is what [`STICK_MAX`] gives
+//! keyboard directions.
+//!
 //! # Implementation
 //!
 //! This is a simplified PIF implementation:
-//! - Basic controller communication (buttons and analog stick)
-//! - No memory card support (yet)
-//! - No EEPROM support (yet)
+//! - Controller status, button and stick reads on channels 0-3
+//! - No Controller Pak: status reports none inserted and pak reads and
+//!   writes answer with a bad data CRC, as an empty slot does
+//! - No EEPROM support (yet), the cartridge channel does not respond
 //! - Minimal boot ROM (just enough to start games)
 
 /// Physical address of the exception vector in RDRAM
@@ -148,6 +165,46 @@ impl ControllerButtons {
     }
 }
 
+/// Start of the joybus command area in PIF RAM
+const JOYBUS_START: usize = 0x7C0;
+
+/// PIF control byte, bit 0 asks the PIF to run the command block
+const PIF_CONTROL: usize = 0x7FF;
+
+/// Joybus commands
+const JOYBUS_STATUS: u8 = 0x00;
+const JOYBUS_READ_BUTTONS: u8 = 0x01;
+const JOYBUS_READ_PAK: u8 = 0x02;
+const JOYBUS_WRITE_PAK: u8 = 0x03;
+const JOYBUS_RESET: u8 = 0xFF;
+
+/// Status response of a standard controller with no pak inserted
+const CONTROLLER_STATUS: [u8; 3] = [0x05, 0x00, 0x00];
+
+/// Bit 7 of a channel's R byte: no device answered
+const JOYBUS_NO_RESPONSE: u8 = 0x80;
+
+/// Stick deflection used for digital directions (a real stick tops out
+/// around ±80)
+pub const STICK_MAX: i8 = 80;
+
+/// Buttons of the frontend's common controller layout, by bit:
+/// A, B, Select, Start, Up, Down, Left, Right, X, Y, L, R
+pub mod pad {
+    pub const A: u16 = 1 << 0;
+    pub const B: u16 = 1 << 1;
+    pub const SELECT: u16 = 1 << 2;
+    pub const START: u16 = 1 << 3;
+    pub const UP: u16 = 1 << 4;
+    pub const DOWN: u16 = 1 << 5;
+    pub const LEFT: u16 = 1 << 6;
+    pub const RIGHT: u16 = 1 << 7;
+    pub const X: u16 = 1 << 8;
+    pub const Y: u16 = 1 << 9;
+    pub const L: u16 = 1 << 10;
+    pub const R: u16 = 1 << 11;
+}
+
 /// Controller state (buttons + analog stick)
 #[derive(Debug, Clone, Copy, Default)]
 pub struct ControllerState {
@@ -159,6 +216,36 @@ pub struct ControllerState {
     pub stick_y: i8,
 }
 
+impl ControllerState {
+    /// Controller for the frontend's common layout (see [`pad`])
+    ///
+    /// The directions push the analog stick to [`STICK_MAX`], Select is Z,
+    /// X and Y are C-Down and C-Left, and L and R are the triggers.
+    pub fn from_pad(state: u16) -> Self {
+        let held = |bit| state & bit != 0;
+        let axis = |negative, positive| match (held(negative), held(positive)) {
+            (true, false) => -STICK_MAX,
+            (false, true) => STICK_MAX,
+            _ => 0,
+        };
+        Self {
+            buttons: ControllerButtons {
+                a: held(pad::A),
+                b: held(pad::B),
+                z: held(pad::SELECT),
+                start: held(pad::START),
+                l: held(pad::L),
+                r: held(pad::R),
+                c_down: held(pad::X),
+                c_left: held(pad::Y),
+                ..ControllerButtons::default()
+            },
+            stick_x: axis(pad::LEFT, pad::RIGHT),
+            stick_y: axis(pad::DOWN, pad::UP),
+        }
+    }
+}
+
 /// PIF (Peripheral Interface) state
 pub struct Pif {
     /// PIF RAM (2KB)
@@ -175,6 +262,9 @@ pub struct Pif {
 
     /// Controller 4 state
     controller4: ControllerState,
+
+    /// Controllers plugged into ports 1-4
+    connected: [bool; 4],
 }
 
 impl Pif {
@@ -186,6 +276,7 @@ impl Pif {
             controller2: ControllerState::default(),
             controller3: ControllerState::default(),
             controller4: ControllerState::default(),
+            connected: [true, false, false, false],
         }
     }
 
@@ -265,6 +356,20 @@ impl Pif {
         entry_point
     }
 
+    /// PIF RAM contents (for save states)
+    pub fn ram(&self) -> &[u8] {
+        &self.ram
+    }
+
+    /// Restore PIF RAM from a save state
+    pub fn load_ram(&mut self, ram: &[u8]) -> Result<(), String> {
+        if ram.len() != self.ram.len() {
+            return Err(format!("PIF RAM size mismatch: {}", ram.len()));
+        }
+        self.ram.copy_from_slice(ram);
+        Ok(())
+    }
+
     /// Read from PIF RAM
     pub fn read_ram(&self, offset: u32) -> u8 {
         let addr = (offset & 0x7FF) as usize;
@@ -272,51 +377,131 @@ impl Pif {
     }
 
     /// Write to PIF RAM
+    ///
+    /// Setting bit 0 of the control byte runs the joybus command block.
     pub fn write_ram(&mut self, offset: u32, value: u8) {
         let addr = (offset & 0x7FF) as usize;
         self.ram[addr] = value;
 
-        // Check if this is a controller command write (PIF RAM offset 0x7C0-0x7FF)
-        // This is where games write controller command blocks
-        if addr >= 0x7C0 {
-            use emu_core::logging::{log, LogCategory, LogLevel};
-            log(LogCategory::PPU, LogLevel::Info, || {
-                format!("PIF: Write to offset 0x{:03X} = 0x{:02X}", addr, value)
-            });
+        if addr == PIF_CONTROL && value & 0x01 != 0 {
             self.process_controller_commands();
         }
     }
 
-    /// Process controller command blocks in PIF RAM
+    /// Run the joybus command block in PIF RAM (0x7C0-0x7FE)
     fn process_controller_commands(&mut self) {
-        // Command block format in PIF RAM (at 0x7C0+):
-        // Each channel has: [T, R, command bytes...] where T=transmit, R=receive
-        // Simplified implementation: just look for read controller command (0x01)
-
-        // Controller 1 command at offset 0x7C0
-        if self.ram[0x7C0] == 0x01 && self.ram[0x7C1] == 0x04 && self.ram[0x7C2] == 0x01 {
-            // Command: 1 byte transmit, 4 bytes receive, read controller state
-            let state = self.controller1; // Copy state to avoid borrow issues
-            self.write_controller_state(0x7C3, &state);
-        }
+        use emu_core::logging::{log, LogCategory, LogLevel};
+
+        let mut channel = 0;
+        let mut i = JOYBUS_START;
+        while i < PIF_CONTROL {
+            match self.ram[i] {
+                0xFE => break,
+                0xFF => {
+                    i += 1;
+                    continue;
+                }
+                0x00 => {
+                    channel += 1;
+                    i += 1;
+                    continue;
+                }
+                _ => {}
+            }
+
+            let tx_len = (self.ram[i] & 0x3F) as usize;
+            let rx = i + 1;
+            if rx >= PIF_CONTROL || self.ram[rx] == 0xFE {
+                break;
+            }
+            let rx_len = (self.ram[rx] & 0x3F) as usize;
+            let command = rx + 1;
+            let response = command + tx_len;
+            let end = response + rx_len;
+            if end > PIF_CONTROL {
+                break;
+            }
 
-        // Controller 2 command at offset 0x7C8 (8 bytes per channel)
-        if self.ram[0x7C8] == 0x01 && self.ram[0x7C9] == 0x04 && self.ram[0x7CA] == 0x01 {
-            let state = self.controller2;
-            self.write_controller_state(0x7CB, &state);
+            let answered = tx_len > 0
+                && channel < self.connected.len()
+                && self.connected[channel]
+                && self.controller_command(channel, command, response, rx_len);
+            if !answered {
+                self.ram[rx] |= JOYBUS_NO_RESPONSE;
+            }
+            log(LogCategory::Bus, LogLevel::Trace, || {
+                format!(
+                    "PIF: Channel {} command 0x{:02X} {}",
+                    channel,
+                    self.ram[command],
+                    if answered { "answered" } else { "no response" }
+                )
+            });
+
+            channel += 1;
+            i = end;
         }
 
-        // Controller 3 and 4 similar (not implemented yet - rarely used)
+        // The PIF clears the request once the block has run
+        self.ram[PIF_CONTROL] &= !0x01;
+    }
+
+    /// Answer a joybus command for the controller on `channel`, writing up
+    /// to `rx_len` bytes at `response`; false if it does not respond
+    fn controller_command(
+        &mut self,
+        channel: usize,
+        command: usize,
+        response: usize,
+        rx_len: usize,
+    ) -> bool {
+        let mut reply = [0u8; 33];
+        let len = match self.ram[command] {
+            JOYBUS_STATUS | JOYBUS_RESET => {
+                reply[..3].copy_from_slice(&CONTROLLER_STATUS);
+                3
+            }
+            JOYBUS_READ_BUTTONS => {
+                let state = self.controller(channel);
+                reply[..2].copy_from_slice(&state.buttons.to_u16().to_be_bytes());
+                reply[2] = state.stick_x as u8;
+                reply[3] = state.stick_y as u8;
+                4
+            }
+            // An empty pak slot reads zeros with a data CRC that never
+            // matches, which libultra takes as no pak
+            JOYBUS_READ_PAK => {
+                reply[32] = 0xFF;
+                33
+            }
+            JOYBUS_WRITE_PAK => {
+                reply[0] = 0xFF;
+                1
+            }
+            _ => return false,
+        };
+        let len = len.min(rx_len);
+        self.ram[response..response + len].copy_from_slice(&reply[..len]);
+        true
+    }
+
+    /// State of the controller on a port (0-3)
+    fn controller(&self, port: usize) -> ControllerState {
+        match port {
+            0 => self.controller1,
+            1 => self.controller2,
+            2 => self.controller3,
+            _ => self.controller4,
+        }
     }
 
-    /// Write controller state to PIF RAM response block
-    fn write_controller_state(&mut self, offset: usize, state: &ControllerState) {
-        // Response format: [buttons_hi, buttons_lo, stick_x, stick_y]
-        let buttons = state.buttons.to_u16();
-        self.ram[offset] = (buttons >> 8) as u8; // High byte
-        self.ram[offset + 1] = (buttons & 0xFF) as u8; // Low byte
-        self.ram[offset + 2] = state.stick_x as u8;
-        self.ram[offset + 3] = state.stick_y as u8;
+    /// Plug a controller into or unplug it from a port (0-3)
+    ///
+    /// Only port 1 has a controller at power on.
+    pub fn set_controller_connected(&mut self, port: usize, connected: bool) {
+        if let Some(slot) = self.connected.get_mut(port) {
+            *slot = connected;
+        }
     }
 
     /// Update controller 1 state
@@ -356,6 +541,14 @@ impl Default for Pif {
 mod tests {
     use super::*;
 
+    /// Write a joybus command block and ask the PIF to run it
+    fn run_joybus(pif: &mut Pif, block: &[u8]) {
+        for (i, &byte) in block.iter().enumerate() {
+            pif.write_ram((JOYBUS_START + i) as u32, byte);
+        }
+        pif.write_ram(PIF_CONTROL as u32, 0x01);
+    }
+
     #[test]
     fn test_pif_creation() {
         let pif = Pif::new();
@@ -403,9 +596,16 @@ mod tests {
         pif.set_controller1(state);
 
         // Simulate game writing controller read command
-        pif.write_ram(0x7C0, 0x01); // T=1 byte
-        pif.write_ram(0x7C1, 0x04); // R=4 bytes
-        pif.write_ram(0x7C2, 0x01); // Command 0x01 (read controller)
+        run_joybus(
+            &mut pif,
+            &[
+                0x01, // T=1 byte
+                0x04, // R=4 bytes
+                0x01, // Command 0x01 (read controller)
+                0xFF, 0xFF, 0xFF, 0xFF, // Response area
+                0xFE, // End of block
+            ],
+        );
 
         // Response should be written at 0x7C3
         let buttons_hi = pif.read_ram(0x7C3);
@@ -452,20 +652,22 @@ mod tests {
         state2.buttons.b = true;
         pif.set_controller2(state2);
 
-        // Read controller 1 state
-        pif.write_ram(0x7C0, 0x01);
-        pif.write_ram(0x7C1, 0x04);
-        pif.write_ram(0x7C2, 0x01);
+        pif.set_controller_connected(1, true);
+
+        // Read both controllers, one channel after the other
+        run_joybus(
+            &mut pif,
+            &[
+                0x01, 0x04, 0x01, 0xFF, 0xFF, 0xFF, 0xFF, // Channel 0
+                0x01, 0x04, 0x01, 0xFF, 0xFF, 0xFF, 0xFF, // Channel 1
+                0xFE,
+            ],
+        );
 
         let buttons1 = u16::from_be_bytes([pif.read_ram(0x7C3), pif.read_ram(0x7C4)]);
         assert_eq!(buttons1 & (1 << 15), 1 << 15); // A button
 
-        // Read controller 2 state
-        pif.write_ram(0x7C8, 0x01);
-        pif.write_ram(0x7C9, 0x04);
-        pif.write_ram(0x7CA, 0x01);
-
-        let buttons2 = u16::from_be_bytes([pif.read_ram(0x7CB), pif.read_ram(0x7CC)]);
+        let buttons2 = u16::from_be_bytes([pif.read_ram(0x7CA), pif.read_ram(0x7CB)]);
         assert_eq!(buttons2 & (1 << 14), 1 << 14); // B button
     }
 
@@ -527,4 +729,77 @@ mod tests {
         assert_eq!(state.stick_x, -128);
         assert_eq!(state.stick_y, -128);
     }
+
+    #[test]
+    fn test_block_runs_only_on_control_byte() {
+        let mut pif = Pif::new();
+        pif.set_controller1(ControllerState {
+            stick_x: 10,
+            ..ControllerState::default()
+        });
+
+        for (i, &byte) in [0x01, 0x04, 0x01, 0xFF, 0xFF, 0xFF, 0xFF, 0xFE]
+            .iter()
+            .enumerate()
+        {
+            pif.write_ram((JOYBUS_START + i) as u32, byte);
+        }
+        assert_eq!(pif.read_ram(0x7C5), 0xFF);
+
+        pif.write_ram(PIF_CONTROL as u32, 0x01);
+        assert_eq!(pif.read_ram(0x7C5), 10);
+        assert_eq!(pif.read_ram(PIF_CONTROL as u32), 0x00);
+    }
+
+    #[test]
+    fn test_status_and_padding() {
+        let mut pif = Pif::new();
+
+        // libultra pads each channel with 0xFF and skips channels with 0x00
+        run_joybus(
+            &mut pif,
+            &[
+                0xFF, 0x01, 0x03, 0x00, 0xFF, 0xFF, 0xFF, // Channel 0 status
+                0x00, // Skip channel 1
+                0xFF, 0x01, 0x03, 0x00, 0xFF, 0xFF, 0xFF, // Channel 2 status
+                0xFE,
+            ],
+        );
+
+        // Controller with no pak inserted
+        assert_eq!(pif.ram()[0x7C4..0x7C7], [0x05, 0x00, 0x00]);
+
+        // Nothing plugged into port 3
+        assert_eq!(pif.read_ram(0x7CA), 0x03 | JOYBUS_NO_RESPONSE);
+        assert_eq!(pif.ram()[0x7CC..0x7CF], [0xFF, 0xFF, 0xFF]);
+    }
+
+    #[test]
+    fn test_empty_pak_slot_fails_crc() {
+        let mut pif = Pif::new();
+
+        let mut block = vec![0x03, 0x21, JOYBUS_READ_PAK, 0x80, 0x01];
+        block.extend([0xAA; 33]);
+        block.push(0xFE);
+        run_joybus(&mut pif, &block);
+
+        assert!(pif.ram()[0x7C5..0x7C5 + 32].iter().all(|&b| b == 0));
+        assert_eq!(pif.read_ram(0x7C5 + 32), 0xFF);
+    }
+
+    #[test]
+    fn test_pad_synthesizes_stick() {
+        let state = ControllerState::from_pad(pad::RIGHT | pad::UP | pad::A | pad::SELECT);
+        assert_eq!((state.stick_x, state.stick_y), (STICK_MAX, STICK_MAX));
+        assert!(state.buttons.a && state.buttons.z);
+        assert!(!state.buttons.d_right && !state.buttons.d_up);
+
+        let state = ControllerState::from_pad(pad::LEFT | pad::DOWN | pad::L | pad::X);
+        assert_eq!((state.stick_x, state.stick_y), (-STICK_MAX, -STICK_MAX));
+        assert!(state.buttons.l && state.buttons.c_down);
+
+        // Opposite directions cancel out
+        let state = ControllerState::from_pad(pad::LEFT | pad::RIGHT);
+        assert_eq!(state.stick_x, 0);
+    }
 }
@@ -0,0 +1,267 @@
+//! RCP worker thread - RSP tasks and RDP command lists off the CPU loop
+//!
+//! When enabled, the bus no longer runs RSP tasks and RDP command lists
+//! inline when the CPU starts them. Instead it takes a snapshot of RDRAM
+//! (and DMEM for RSP tasks) and queues the work to a worker thread that owns
+//! the RSP's HLE state and the RDP with its renderer. The CPU keeps running
+//! while the worker rasterizes.
+//!
+//! # Synchronization
+//!
+//! The bus waits for the worker at the VI interrupt and at the end of each
+//! frame. Only then does the RSP halt (raising the SP interrupt if enabled),
+//! DPC_STATUS report the command buffer as ready, and the rendered frame
+//! become visible. Because the inputs are snapshots and results are only
+//! picked up at these fixed points, emulation stays deterministic regardless
+//! of how fast the worker runs.
+//!
+//! The OpenGL renderer is bound to the thread that owns the GL context, so
+//! the worker is only used with the software renderer.
+
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use super::dl_debug::DisplayListCapture;
+use super::rdp::Rdp;
+use super::rsp_hle::{MicrocodeType, RspHle};
+use emu_core::logging::{log, LogCategory, LogLevel};
+use emu_core::types::Frame;
+
+/// Work queued to the RCP thread
+enum RcpJob {
+    /// Run an RSP task with the given DMEM contents
+    RspTask {
+        dmem: Box<[u8; 4096]>,
+        microcode: MicrocodeType,
+        rdram: Arc<[u8]>,
+    },
+    /// Run the RDP command list from `start` to `end`
+    DisplayList {
+        start: u32,
+        end: u32,
+        rdram: Arc<[u8]>,
+    },
+    /// Record the display list of the next graphics task
+    RequestCapture,
+    /// Reset the RDP
+    ResetRdp,
+    /// Report the results of all earlier jobs
+    Sync,
+}
+
+/// Results of the jobs completed before a sync
+struct RcpSynced {
+    frame: Frame,
+    color_images: Vec<u32>,
+    vertex_count: usize,
+    capture: Option<DisplayListCapture>,
+    full_sync: bool,
+}
+
+/// Worker thread running RSP tasks and RDP command lists
+pub struct RcpThread {
+    jobs: Option<Sender<RcpJob>>,
+    results: Receiver<RcpSynced>,
+    handle: Option<JoinHandle<(RspHle, Rdp)>>,
+    /// An RSP task was queued since the last sync
+    rsp_task_pending: bool,
+    /// An RDP command list was queued since the last sync
+    display_list_pending: bool,
+    /// Any job that changes the frame was queued since the last sync
+    dirty: bool,
+    /// Color images the RDP drew to as of the last sync
+    color_images: Vec<u32>,
+    /// Vertex count of the RSP after the last sync
+    vertex_count: usize,
+    /// Most recently completed display list capture
+    capture: Option<DisplayListCapture>,
+    /// A display list reached G_RDPFULLSYNC since the DP interrupt was raised
+    full_sync: bool,
+}
+
+impl RcpThread {
+    /// Start the worker, handing it the RSP's HLE state and the RDP
+    pub fn spawn(hle: RspHle, rdp: Rdp) -> std::io::Result<Self> {
+        let (job_tx, job_rx) = channel();
+        let (result_tx, result_rx) = channel();
+        let vertex_count = hle.vertex_count();
+        let color_images = rdp.color_images().to_vec();
+        let handle = thread::Builder::new()
+            .name("n64-rcp".to_string())
+            .spawn(move || run(hle, rdp, job_rx, result_tx))?;
+
+        log(LogCategory::PPU, LogLevel::Info, || {
+            "N64 RCP: RSP/RDP processing moved to worker thread".to_string()
+        });
+
+        Ok(Self {
+            jobs: Some(job_tx),
+            results: result_rx,
+            handle: Some(handle),
+            rsp_task_pending: false,
+            display_list_pending: false,
+            dirty: false,
+            color_images,
+            vertex_count,
+            capture: None,
+            full_sync: false,
+        })
+    }
+
+    /// Queue an RSP task, unless one is already running
+    pub fn queue_rsp_task(&mut self, dmem: &[u8; 4096], microcode: MicrocodeType, rdram: &[u8]) {
+        if self.rsp_task_pending {
+            return;
+        }
+        self.rsp_task_pending = true;
+        self.dirty = true;
+        self.send(RcpJob::RspTask {
+            dmem: Box::new(*dmem),
+            microcode,
+            rdram: Arc::from(rdram),
+        });
+    }
+
+    /// Queue an RDP command list
+    pub fn queue_display_list(&mut self, start: u32, end: u32, rdram: &[u8]) {
+        self.display_list_pending = true;
+        self.dirty = true;
+        self.send(RcpJob::DisplayList {
+            start,
+            end,
+            rdram: Arc::from(rdram),
+        });
+    }
+
+    /// Record the display list of the next graphics task
+    pub fn request_capture(&mut self) {
+        self.send(RcpJob::RequestCapture);
+    }
+
+    /// Take the most recently completed display list capture
+    pub fn take_capture(&mut self) -> Option<DisplayListCapture> {
+        self.capture.take()
+    }
+
+    /// Reset the worker's RDP
+    pub fn reset_rdp(&mut self) {
+        self.dirty = true;
+        self.send(RcpJob::ResetRdp);
+    }
+
+    /// Color images the RDP drew to as of the last sync
+    pub fn color_images(&self) -> &[u32] {
+        &self.color_images
+    }
+
+    /// Vertex count of the RSP as of the last sync
+    pub fn vertex_count(&self) -> usize {
+        self.vertex_count
+    }
+
+    /// Wait for all queued jobs
+    ///
+    /// Returns the new frame if any job changed it, or an error if the
+    /// worker has stopped (e.g. it panicked).
+    pub fn sync(&mut self) -> Result<Option<Frame>, String> {
+        if !self.dirty {
+            return Ok(None);
+        }
+        self.send(RcpJob::Sync);
+        let synced = self
+            .results
+            .recv()
+            .map_err(|_| "RCP thread stopped".to_string())?;
+
+        self.dirty = false;
+        self.color_images = synced.color_images;
+        self.vertex_count = synced.vertex_count;
+        if synced.capture.is_some() {
+            self.capture = synced.capture;
+        }
+        self.full_sync |= synced.full_sync;
+        Ok(Some(synced.frame))
+    }
+
+    /// Take the RSP task and display list completions since the last sync
+    pub fn take_completions(&mut self) -> (bool, bool) {
+        let completions = (self.rsp_task_pending, self.display_list_pending);
+        self.rsp_task_pending = false;
+        self.display_list_pending = false;
+        completions
+    }
+
+    /// Take whether a display list reached G_RDPFULLSYNC as of the last sync
+    pub fn take_full_sync(&mut self) -> bool {
+        std::mem::take(&mut self.full_sync)
+    }
+
+    /// Stop the worker and take back the RSP's HLE state and the RDP
+    ///
+    /// Returns `None` if the worker panicked.
+    pub fn stop(mut self) -> Option<(RspHle, Rdp)> {
+        self.jobs = None;
+        self.handle.take()?.join().ok()
+    }
+
+    fn send(&self, job: RcpJob) {
+        if let Some(jobs) = &self.jobs {
+            // A stopped worker is reported by the next sync
+            let _ = jobs.send(job);
+        }
+    }
+}
+
+impl Drop for RcpThread {
+    fn drop(&mut self) {
+        self.jobs = None;
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Worker loop; runs until the job channel closes
+fn run(
+    mut hle: RspHle,
+    mut rdp: Rdp,
+    jobs: Receiver<RcpJob>,
+    results: Sender<RcpSynced>,
+) -> (RspHle, Rdp) {
+    for job in jobs {
+        match job {
+            RcpJob::RspTask {
+                dmem,
+                microcode,
+                rdram,
+            } => {
+                hle.set_microcode(microcode);
+                let cycles = hle.execute_task(&dmem, &rdram, &mut rdp);
+                log(LogCategory::PPU, LogLevel::Debug, || {
+                    format!("N64 RCP: RSP task complete ({} cycles)", cycles)
+                });
+            }
+            RcpJob::DisplayList { start, end, rdram } => {
+                rdp.set_dpc_start(start);
+                rdp.set_dpc_end(end);
+                rdp.process_display_list(&rdram);
+            }
+            RcpJob::RequestCapture => hle.request_capture(),
+            RcpJob::ResetRdp => rdp.reset(),
+            RcpJob::Sync => {
+                let synced = RcpSynced {
+                    frame: rdp.get_frame().clone(),
+                    color_images: rdp.color_images().to_vec(),
+                    vertex_count: hle.vertex_count(),
+                    capture: hle.take_capture(),
+                    full_sync: hle.take_full_sync(),
+                };
+                if results.send(synced).is_err() {
+                    break;
+                }
+            }
+        }
+    }
+    (hle, rdp)
+}
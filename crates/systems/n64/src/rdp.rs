@@ -64,6 +64,12 @@
 //! ### OpenGL Renderer (Optional)
 //! - **GPU-accelerated**: Uses OpenGL 3.3 Core Profile
 //! - **Hardware depth testing**: Leverages GPU Z-buffer
+//! - **Batching**: Triangles sharing render state go out in one draw call;
+//!   the frame is read back once per `finish_frame()`
+//! - **Combiner approximation**: Evaluates cycle 0 of SET_COMBINE_MODE with
+//!   the primitive and environment colors in a fragment shader
+//! - **Runtime fallback**: A GL error reported at `finish_frame()` switches
+//!   the RDP back to the software renderer, keeping the last frame
 //! - **Feature parity**: Implements all RdpRenderer methods
 //! - **Coordinate conversion**: Handles Y-axis flip (OpenGL bottom-up → framebuffer top-down)
 //! - **Color format conversion**: ARGB ↔ RGBA conversion for OpenGL compatibility
@@ -71,7 +77,8 @@
 //! - **Implementation**: `OpenGLRdpRenderer` in `rdp_renderer_opengl.rs`
 //! - **Enabled**: Feature-gated behind `opengl` feature flag
 //!
-//! Both renderers produce identical output and pass the same test suite.
+//! Both renderers produce identical output for the default (pass-through)
+//! combine mode. The software renderer does not evaluate the combiner.
 //!
 //! Full RDP emulation would require:
 //! - Complete display list command execution
@@ -80,13 +87,14 @@
 //! - Full blending pipeline
 //! - Accurate timing and synchronization
 
-use super::rdp_renderer::{RdpRenderer, ScissorBox};
+use super::rdp_renderer::{CombinerState, RdpRenderer, ScissorBox};
 #[cfg(feature = "opengl")]
 use super::rdp_renderer_opengl::OpenGLRdpRenderer;
 use super::rdp_renderer_software::SoftwareRdpRenderer;
 use emu_core::graphics::ColorOps;
 use emu_core::logging::{log, LogCategory, LogLevel};
 use emu_core::types::Frame;
+use serde::{Deserialize, Serialize};
 
 /// RDP register addresses (relative to 0x04100000)
 const DPC_START: u32 = 0x00; // DMA start address
@@ -107,6 +115,9 @@ const DPC_STATUS_CBUF_READY: u32 = 0x080; // Command buffer ready
 const DPC_STATUS_DMA_BUSY: u32 = 0x100; // DMA in progress
 const DPC_STATUS_PIPE_BUSY: u32 = 0x200; // Pipe busy
 
+/// Color images remembered for the VI (enough for triple buffering)
+const MAX_COLOR_IMAGES: usize = 3;
+
 /// Color format for framebuffer
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[allow(dead_code)] // Used for future color format support
@@ -118,7 +129,7 @@ pub enum ColorFormat {
 }
 
 /// Texture tile descriptor
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 #[allow(dead_code)] // Fields reserved for future texture mapping implementation
 struct TileDescriptor {
     format: u32,    // Texture format (RGBA, CI, IA, I)
@@ -172,6 +183,20 @@ pub struct Rdp {
     /// Z-buffer image address in RDRAM
     z_image_addr: u32,
 
+    /// Color image address in RDRAM (fills aimed at the Z image clear the
+    /// Z-buffer)
+    color_image_addr: u32,
+
+    /// Color images drawn to, most recent last (the VI shows the internal
+    /// framebuffer when it scans out one of them)
+    color_images: Vec<u32>,
+
+    /// SET_OTHER_MODES command (cycle type, render mode, ...)
+    other_modes: u64,
+
+    /// Z-buffer testing enabled (kept here so a fallback renderer inherits it)
+    zbuffer_enabled: bool,
+
     /// DPC registers
     dpc_start: u32,
     dpc_end: u32,
@@ -179,6 +204,30 @@ pub struct Rdp {
     dpc_status: u32,
 }
 
+/// DPC registers and render state saved in save states
+///
+/// The framebuffer is not included; the game redraws it on the next frame.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RdpState {
+    fill_color: u32,
+    scissor: ScissorBox,
+    tmem: Vec<u8>,
+    tiles: [TileDescriptor; 8],
+    texture_image_addr: u32,
+    blend_color: u32,
+    prim_color: u32,
+    env_color: u32,
+    fog_color: u32,
+    combine_mode: u64,
+    z_image_addr: u32,
+    color_images: Vec<u32>,
+    zbuffer_enabled: bool,
+    dpc_start: u32,
+    dpc_end: u32,
+    dpc_current: u32,
+    dpc_status: u32,
+}
+
 impl Rdp {
     /// Create a new RDP with default resolution (320x240)
     pub fn new() -> Self {
@@ -226,6 +275,10 @@ impl Rdp {
             fog_color: 0xFF000000,   // Black
             combine_mode: 0,         // No combine mode
             z_image_addr: 0,
+            color_image_addr: 0,
+            color_images: Vec::new(),
+            other_modes: 0,
+            zbuffer_enabled: false,
             dpc_start: 0,
             dpc_end: 0,
             dpc_current: 0,
@@ -239,8 +292,13 @@ impl Rdp {
     pub fn enable_opengl_renderer(&mut self, gl: glow::Context) -> Result<(), String> {
         let mut new_renderer = Box::new(OpenGLRdpRenderer::new(gl, self.width, self.height)?);
 
-        // Initialize to black
-        new_renderer.clear(0x00000000);
+        // Carry over the current frame and render state
+        new_renderer
+            .get_frame_mut()
+            .pixels
+            .copy_from_slice(&self.renderer.get_frame().pixels);
+        new_renderer.set_zbuffer_enabled(self.zbuffer_enabled);
+        new_renderer.set_combiner(&self.combiner_state());
 
         // Replace the software renderer with OpenGL renderer
         self.renderer = new_renderer;
@@ -286,6 +344,10 @@ impl Rdp {
         self.fog_color = 0xFF000000;
         self.combine_mode = 0;
         self.z_image_addr = 0;
+        self.color_image_addr = 0;
+        self.color_images.clear();
+        self.other_modes = 0;
+        self.zbuffer_enabled = false;
         self.dpc_start = 0;
         self.dpc_end = 0;
         self.dpc_current = 0;
@@ -326,6 +388,7 @@ impl Rdp {
     /// Enable or disable Z-buffer testing
     #[allow(dead_code)] // Public API for future use
     pub fn set_zbuffer_enabled(&mut self, enabled: bool) {
+        self.zbuffer_enabled = enabled;
         self.renderer.set_zbuffer_enabled(enabled);
     }
 
@@ -425,11 +488,160 @@ impl Rdp {
         );
     }
 
+    /// Draw a triangle textured from a tile, with Z-buffer support
+    /// Texture coordinates are in texels
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_triangle_textured_zbuffer(
+        &mut self,
+        x0: i32,
+        y0: i32,
+        z0: u16,
+        s0: f32,
+        t0: f32,
+        x1: i32,
+        y1: i32,
+        z1: u16,
+        s1: f32,
+        t1: f32,
+        x2: i32,
+        y2: i32,
+        z2: u16,
+        s2: f32,
+        t2: f32,
+        tile: usize,
+    ) {
+        let tmem = &self.tmem;
+        let tile_desc = self.tiles[tile & 7];
+        // Negative coordinates wrap through the tile mask
+        let sampler = |s: f32, t: f32| -> u32 {
+            Self::sample_tmem(
+                tmem,
+                &tile_desc,
+                s.floor() as i32 as u32,
+                t.floor() as i32 as u32,
+            )
+        };
+        self.renderer.draw_triangle_textured_zbuffer(
+            x0,
+            y0,
+            z0,
+            s0,
+            t0,
+            x1,
+            y1,
+            z1,
+            s1,
+            t1,
+            x2,
+            y2,
+            z2,
+            s2,
+            t2,
+            &sampler,
+            &self.scissor,
+        );
+    }
+
+    /// Draw a TEXTURE_RECTANGLE (or TEXTURE_RECTANGLE_FLIP) command
+    ///
+    /// `word0`/`word1` are the command's first 64 bits; `st` holds the S and
+    /// T of the top-left corner (S10.5) and `dsdx_dtdy` the per-pixel steps
+    /// (S5.10). Texels with zero alpha are skipped.
+    pub fn texture_rectangle(&mut self, word0: u32, word1: u32, st: u32, dsdx_dtdy: u32) {
+        let flip = (word0 >> 24) & 0x3F == 0x25;
+        let mut xh = ((word0 >> 12) & 0xFFF) / 4;
+        let mut yh = (word0 & 0xFFF) / 4;
+        let xl = ((word1 >> 12) & 0xFFF) / 4;
+        let yl = (word1 & 0xFFF) / 4;
+        let tile = ((word1 >> 24) & 0x07) as usize;
+
+        let s = (st >> 16) as i16 as f32 / 32.0;
+        let t = st as i16 as f32 / 32.0;
+        let mut dsdx = (dsdx_dtdy >> 16) as i16 as f32 / 1024.0;
+        let dtdy = dsdx_dtdy as i16 as f32 / 1024.0;
+
+        // Copy and fill modes include the lower-right edge, and copy mode
+        // steps four texels per pixel
+        let cycle_type = (self.other_modes >> 52) & 0x3;
+        if cycle_type >= 2 {
+            xh += 1;
+            yh += 1;
+            if cycle_type == 2 {
+                dsdx /= 4.0;
+            }
+        }
+
+        let x_end = xh.min(self.width).min(self.scissor.x_max);
+        let y_end = yh.min(self.height).min(self.scissor.y_max);
+        for y in yl.max(self.scissor.y_min)..y_end {
+            for x in xl.max(self.scissor.x_min)..x_end {
+                let dx = (x - xl) as f32;
+                let dy = (y - yl) as f32;
+                let (ts, tt) = if flip {
+                    (s + dsdx * dy, t + dtdy * dx)
+                } else {
+                    (s + dsdx * dx, t + dtdy * dy)
+                };
+                let color =
+                    self.sample_texture(tile, ts.floor() as i32 as u32, tt.floor() as i32 as u32);
+                if color >> 24 != 0 {
+                    self.renderer.set_pixel(x, y, color);
+                }
+            }
+        }
+    }
+
     /// Get the current framebuffer
     pub fn get_frame(&self) -> &Frame {
         self.renderer.get_frame()
     }
 
+    /// Submit batched rendering so `get_frame()` is up to date
+    ///
+    /// If the renderer reports that it can no longer draw (e.g. a lost GL
+    /// context), the RDP falls back to the software renderer, keeping the
+    /// last frame it produced.
+    pub fn finish_frame(&mut self) {
+        if let Err(e) = self.renderer.flush() {
+            log(LogCategory::Stubs, LogLevel::Warn, || {
+                format!(
+                    "N64 RDP: {} failed ({}), falling back to Software renderer",
+                    self.renderer.name(),
+                    e
+                )
+            });
+
+            let mut software = SoftwareRdpRenderer::new(self.width, self.height);
+            software
+                .get_frame_mut()
+                .pixels
+                .copy_from_slice(&self.renderer.get_frame().pixels);
+            software.set_zbuffer_enabled(self.zbuffer_enabled);
+            software.set_combiner(&self.combiner_state());
+            self.renderer = Box::new(software);
+        }
+    }
+
+    /// Name of the active rendering backend
+    pub fn renderer_name(&self) -> &str {
+        self.renderer.name()
+    }
+
+    /// Combiner state for the renderer
+    fn combiner_state(&self) -> CombinerState {
+        CombinerState {
+            combine_mode: self.combine_mode,
+            prim_color: self.prim_color,
+            env_color: self.env_color,
+        }
+    }
+
+    /// Send the current combiner state to the renderer
+    fn update_combiner(&mut self) {
+        let combiner = self.combiner_state();
+        self.renderer.set_combiner(&combiner);
+    }
+
     /// Read from RDP register
     pub fn read_register(&self, offset: u32) -> u32 {
         match offset {
@@ -559,16 +771,114 @@ impl Rdp {
             addr += 8;
         }
 
-        // Update current pointer and clear busy flags
-        self.dpc_current = self.dpc_end;
-        self.dpc_status |= DPC_STATUS_CBUF_READY;
-        self.dpc_status &= !DPC_STATUS_DMA_BUSY;
+        // Make the results visible to get_frame()
+        self.finish_frame();
+        self.complete_display_list();
 
         log(LogCategory::PPU, LogLevel::Debug, || {
             "N64 RDP: Display list processing completed".to_string()
         });
     }
 
+    /// Mark the queued display list as done (DPC_CURRENT reaches DPC_END)
+    pub fn complete_display_list(&mut self) {
+        self.dpc_current = self.dpc_end;
+        self.dpc_status |= DPC_STATUS_CBUF_READY;
+        self.dpc_status &= !DPC_STATUS_DMA_BUSY;
+    }
+
+    /// Show a frame rendered elsewhere (by the RCP thread)
+    pub fn show_frame(&mut self, frame: &Frame) {
+        self.renderer.get_frame_mut().clone_from(frame);
+    }
+
+    /// True if the active renderer runs on the GPU
+    pub fn is_hardware_accelerated(&self) -> bool {
+        self.renderer.is_hardware_accelerated()
+    }
+
+    /// Framebuffer size
+    pub fn resolution(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// DPC registers and render state for a save state
+    pub fn save_state(&self) -> RdpState {
+        RdpState {
+            fill_color: self.fill_color,
+            scissor: self.scissor,
+            tmem: self.tmem.to_vec(),
+            tiles: self.tiles,
+            texture_image_addr: self.texture_image_addr,
+            blend_color: self.blend_color,
+            prim_color: self.prim_color,
+            env_color: self.env_color,
+            fog_color: self.fog_color,
+            combine_mode: self.combine_mode,
+            z_image_addr: self.z_image_addr,
+            color_images: self.color_images.clone(),
+            zbuffer_enabled: self.zbuffer_enabled,
+            dpc_start: self.dpc_start,
+            dpc_end: self.dpc_end,
+            dpc_current: self.dpc_current,
+            dpc_status: self.dpc_status,
+        }
+    }
+
+    /// Restore state saved by [`Rdp::save_state`], passing the render state
+    /// on to the renderer
+    pub fn load_state(&mut self, state: &RdpState) -> Result<(), String> {
+        if state.tmem.len() != self.tmem.len() {
+            return Err(format!("TMEM size mismatch: {}", state.tmem.len()));
+        }
+        self.fill_color = state.fill_color;
+        self.scissor = state.scissor;
+        self.tmem.copy_from_slice(&state.tmem);
+        self.tiles = state.tiles;
+        self.texture_image_addr = state.texture_image_addr;
+        self.blend_color = state.blend_color;
+        self.prim_color = state.prim_color;
+        self.env_color = state.env_color;
+        self.fog_color = state.fog_color;
+        self.combine_mode = state.combine_mode;
+        self.z_image_addr = state.z_image_addr;
+        self.color_images.clone_from(&state.color_images);
+        self.dpc_start = state.dpc_start;
+        self.dpc_end = state.dpc_end;
+        self.dpc_current = state.dpc_current;
+        self.dpc_status = state.dpc_status;
+        self.set_zbuffer_enabled(state.zbuffer_enabled);
+        self.update_combiner();
+        Ok(())
+    }
+
+    /// Color images drawn to, most recent last
+    pub fn color_images(&self) -> &[u32] {
+        &self.color_images
+    }
+
+    /// Take over the color images drawn to by another RDP (the RCP thread's)
+    pub fn set_color_images(&mut self, images: &[u32]) {
+        self.color_images.clear();
+        self.color_images.extend_from_slice(images);
+    }
+
+    /// True if `origin` points into a color image of `size` bytes that was
+    /// drawn to
+    pub fn drew_color_image_at(&self, origin: u32, size: u32) -> bool {
+        self.color_images
+            .iter()
+            .any(|&addr| origin >= addr && origin - addr < size)
+    }
+
+    /// Copy the DPC registers (the CPU's view of the command buffer)
+    pub fn copy_registers_from(&mut self, other: &Rdp) {
+        self.dpc_start = other.dpc_start;
+        self.dpc_end = other.dpc_end;
+        self.dpc_current = other.dpc_current;
+        self.dpc_status = other.dpc_status;
+    }
+
     /// Load texture data from RDRAM to TMEM (used by LOAD_BLOCK and LOAD_TILE)
     fn load_texture_to_tmem(
         &mut self,
@@ -625,13 +935,18 @@ impl Rdp {
 
     /// Sample a texel from TMEM at the given tile and texture coordinates
     /// Returns RGBA8888 color
-    #[allow(dead_code)] // Will be used for textured triangle rendering
     fn sample_texture(&self, tile: usize, s: u32, t: u32) -> u32 {
         if tile >= 8 {
             return 0xFFFF00FF; // Return magenta for invalid tile (debugging)
         }
+        Self::sample_tmem(&self.tmem, &self.tiles[tile], s, t)
+    }
 
-        let tile_desc = &self.tiles[tile];
+    /// Sample a texel of one tile from TMEM
+    ///
+    /// Takes TMEM rather than `self` so a sampler closure can borrow it while
+    /// the renderer is borrowed mutably.
+    fn sample_tmem(tmem: &[u8; 4096], tile_desc: &TileDescriptor, s: u32, t: u32) -> u32 {
         let format = tile_desc.format;
         let size = tile_desc.size;
         let tmem_addr = (tile_desc.tmem_addr * 8) as usize;
@@ -668,15 +983,15 @@ impl Rdp {
         let addr = tmem_addr + texel_offset;
 
         // Sample based on format and size
-        if addr >= self.tmem.len() {
+        if addr >= tmem.len() {
             return 0xFFFF00FF; // Magenta for out of bounds
         }
 
         match (format, size) {
             // RGBA 16-bit (5-5-5-1)
             (0, 2) => {
-                if addr + 1 < self.tmem.len() {
-                    let texel = u16::from_be_bytes([self.tmem[addr], self.tmem[addr + 1]]);
+                if addr + 1 < tmem.len() {
+                    let texel = u16::from_be_bytes([tmem[addr], tmem[addr + 1]]);
                     let r = ((texel >> 11) & 0x1F) as u32;
                     let g = ((texel >> 6) & 0x1F) as u32;
                     let b = ((texel >> 1) & 0x1F) as u32;
@@ -693,13 +1008,8 @@ impl Rdp {
             }
             // RGBA 32-bit (8-8-8-8)
             (0, 3) => {
-                if addr + 3 < self.tmem.len() {
-                    u32::from_be_bytes([
-                        self.tmem[addr],
-                        self.tmem[addr + 1],
-                        self.tmem[addr + 2],
-                        self.tmem[addr + 3],
-                    ])
+                if addr + 3 < tmem.len() {
+                    u32::from_be_bytes([tmem[addr], tmem[addr + 1], tmem[addr + 2], tmem[addr + 3]])
                 } else {
                     0xFFFF00FF
                 }
@@ -707,14 +1017,13 @@ impl Rdp {
             // CI (Color Index) 8-bit (format=2, size=1)
             (2, 1) => {
                 // Color index format - lookup in palette
-                let index = self.tmem[addr] as usize;
+                let index = tmem[addr] as usize;
                 // Palette is stored in upper half of TMEM (0x800-0xFFF)
                 let palette_offset = 0x800 + (tile_desc.palette as usize * 16 * 2);
                 let color_addr = palette_offset + (index * 2);
 
-                if color_addr + 1 < self.tmem.len() {
-                    let texel =
-                        u16::from_be_bytes([self.tmem[color_addr], self.tmem[color_addr + 1]]);
+                if color_addr + 1 < tmem.len() {
+                    let texel = u16::from_be_bytes([tmem[color_addr], tmem[color_addr + 1]]);
                     let r = ((texel >> 11) & 0x1F) as u32;
                     let g = ((texel >> 6) & 0x1F) as u32;
                     let b = ((texel >> 1) & 0x1F) as u32;
@@ -731,7 +1040,7 @@ impl Rdp {
             // CI (Color Index) 4-bit (format=2, size=0)
             (2, 0) => {
                 // 4-bit color index - two texels per byte
-                let byte = self.tmem[addr];
+                let byte = tmem[addr];
                 let index = if s & 1 == 0 {
                     (byte >> 4) & 0x0F // High nibble
                 } else {
@@ -741,9 +1050,8 @@ impl Rdp {
                 let palette_offset = 0x800 + (tile_desc.palette as usize * 16 * 2);
                 let color_addr = palette_offset + (index * 2);
 
-                if color_addr + 1 < self.tmem.len() {
-                    let texel =
-                        u16::from_be_bytes([self.tmem[color_addr], self.tmem[color_addr + 1]]);
+                if color_addr + 1 < tmem.len() {
+                    let texel = u16::from_be_bytes([tmem[color_addr], tmem[color_addr + 1]]);
                     let r = ((texel >> 11) & 0x1F) as u32;
                     let g = ((texel >> 6) & 0x1F) as u32;
                     let b = ((texel >> 1) & 0x1F) as u32;
@@ -759,8 +1067,8 @@ impl Rdp {
             }
             // IA (Intensity + Alpha) 16-bit (format=3, size=2)
             (3, 2) => {
-                if addr + 1 < self.tmem.len() {
-                    let texel = u16::from_be_bytes([self.tmem[addr], self.tmem[addr + 1]]);
+                if addr + 1 < tmem.len() {
+                    let texel = u16::from_be_bytes([tmem[addr], tmem[addr + 1]]);
                     let intensity = ((texel >> 8) & 0xFF) as u32;
                     let alpha = (texel & 0xFF) as u32;
                     (alpha << 24) | (intensity << 16) | (intensity << 8) | intensity
@@ -770,7 +1078,7 @@ impl Rdp {
             }
             // IA (Intensity + Alpha) 8-bit (format=3, size=1)
             (3, 1) => {
-                let texel = self.tmem[addr];
+                let texel = tmem[addr];
                 let intensity = ((texel >> 4) & 0x0F) as u32;
                 let alpha = (texel & 0x0F) as u32;
                 let i8 = (intensity * 255 / 15) & 0xFF;
@@ -779,7 +1087,7 @@ impl Rdp {
             }
             // IA (Intensity + Alpha) 4-bit (format=3, size=0)
             (3, 0) => {
-                let byte = self.tmem[addr];
+                let byte = tmem[addr];
                 let texel = if s & 1 == 0 {
                     (byte >> 4) & 0x0F
                 } else {
@@ -793,12 +1101,12 @@ impl Rdp {
             }
             // I (Intensity) 8-bit (format=4, size=1)
             (4, 1) => {
-                let intensity = self.tmem[addr] as u32;
+                let intensity = tmem[addr] as u32;
                 0xFF000000 | (intensity << 16) | (intensity << 8) | intensity
             }
             // I (Intensity) 4-bit (format=4, size=0)
             (4, 0) => {
-                let byte = self.tmem[addr];
+                let byte = tmem[addr];
                 let nibble = if s & 1 == 0 {
                     (byte >> 4) & 0x0F
                 } else {
@@ -999,6 +1307,12 @@ impl Rdp {
                 // word1: XL(bits 23-12) | YL(bits 11-0) - START coordinates
                 // Coordinates are in 10.2 fixed point format (divide by 4 to get pixels)
 
+                // Games clear the Z-buffer by filling the Z image as a color image
+                if self.z_image_addr != 0 && self.color_image_addr == self.z_image_addr {
+                    self.clear_zbuffer();
+                    return;
+                }
+
                 let xh = ((word0 >> 12) & 0xFFF).div_ceil(4); // Right/end X, round up
                 let yh = (word0 & 0xFFF).div_ceil(4); // Bottom/end Y, round up
                 let xl = ((word1 >> 12) & 0xFFF) / 4; // Left/start X
@@ -1091,11 +1405,12 @@ impl Rdp {
             // SET_OTHER_MODES (0x2F - full 64-bit command)
             0x2F => {
                 // Configure rendering modes (cycle type, alpha blend, Z-buffer, etc.)
-                // For now, we just accept and ignore these settings
-                // Full implementation would configure the rendering pipeline
+                // Only the cycle type is used (by texture_rectangle); the
+                // blender and Z modes are not emulated
+                self.other_modes = ((word0 as u64 & 0x00FF_FFFF) << 32) | word1 as u64;
                 log(LogCategory::Stubs, LogLevel::Debug, || {
                     format!(
-                        "N64 RDP: SET_OTHER_MODES stub - ignoring rendering mode configuration (word0=0x{:08X}, word1=0x{:08X})",
+                        "N64 RDP: SET_OTHER_MODES (word0=0x{:08X}, word1=0x{:08X})",
                         word0, word1
                     )
                 });
@@ -1214,7 +1529,7 @@ impl Rdp {
             // SYNC_FULL (0x29)
             0x29 => {
                 // Full synchronization - wait for all rendering to complete
-                // In frame-based implementation, this is a no-op
+                self.finish_frame();
             }
             // SET_FOG_COLOR (0x38)
             0x38 => {
@@ -1237,6 +1552,7 @@ impl Rdp {
                 // word0: cmd | min_level(8) | prim_level(8)
                 // word1: primitive color (RGBA8888)
                 self.prim_color = word1;
+                self.update_combiner();
                 log(LogCategory::PPU, LogLevel::Debug, || {
                     format!("N64 RDP: SET_PRIM_COLOR = 0x{:08X}", word1)
                 });
@@ -1245,6 +1561,7 @@ impl Rdp {
             0x3B => {
                 // word1 contains the environment color (RGBA8888)
                 self.env_color = word1;
+                self.update_combiner();
                 log(LogCategory::PPU, LogLevel::Debug, || {
                     format!("N64 RDP: SET_ENV_COLOR = 0x{:08X}", word1)
                 });
@@ -1254,6 +1571,7 @@ impl Rdp {
                 // 64-bit combine mode command
                 // word0 and word1 together form the combine mode settings
                 self.combine_mode = ((word0 as u64) << 32) | (word1 as u64);
+                self.update_combiner();
                 log(LogCategory::PPU, LogLevel::Debug, || {
                     format!("N64 RDP: SET_COMBINE_MODE = 0x{:08X}{:08X}", word0, word1)
                 });
@@ -1270,7 +1588,18 @@ impl Rdp {
             0x3F => {
                 // word0: bits 21-19 = format, bits 18-17 = size, bits 11-0 = width-1
                 // word1: DRAM address of color buffer
-                // For now, we ignore this and use our internal framebuffer
+                // Drawing still goes to the internal framebuffer; the address
+                // tells Z-buffer clears apart from color fills and which
+                // images the VI should show it for
+                self.color_image_addr = word1 & 0x00FFFFFF;
+                if self.color_image_addr != self.z_image_addr
+                    && !self.color_images.contains(&self.color_image_addr)
+                {
+                    if self.color_images.len() == MAX_COLOR_IMAGES {
+                        self.color_images.remove(0);
+                    }
+                    self.color_images.push(self.color_image_addr);
+                }
             }
             // SYNC_PIPE (0x27), SYNC_TILE (0x28), SYNC_LOAD (0x26)
             0x26..=0x28 => {
@@ -2301,4 +2630,162 @@ mod tests {
         // Check that red channel is high
         assert!((pixel >> 16) & 0xFF > 200);
     }
+
+    /// Renderer whose GPU context is gone: draws nothing, fails to flush
+    struct LostContextRenderer {
+        frame: Frame,
+    }
+
+    impl RdpRenderer for LostContextRenderer {
+        fn init(&mut self, _: u32, _: u32) {}
+        fn get_frame(&self) -> &Frame {
+            &self.frame
+        }
+        fn get_frame_mut(&mut self) -> &mut Frame {
+            &mut self.frame
+        }
+        fn clear(&mut self, _: u32) {}
+        fn fill_rect(&mut self, _: u32, _: u32, _: u32, _: u32, _: u32, _: &ScissorBox) {}
+        fn set_pixel(&mut self, _: u32, _: u32, _: u32) {}
+        fn draw_triangle(
+            &mut self,
+            _: i32,
+            _: i32,
+            _: i32,
+            _: i32,
+            _: i32,
+            _: i32,
+            _: u32,
+            _: &ScissorBox,
+        ) {
+        }
+        fn draw_triangle_zbuffer(
+            &mut self,
+            _: i32,
+            _: i32,
+            _: u16,
+            _: i32,
+            _: i32,
+            _: u16,
+            _: i32,
+            _: i32,
+            _: u16,
+            _: u32,
+            _: &ScissorBox,
+        ) {
+        }
+        fn draw_triangle_shaded(
+            &mut self,
+            _: i32,
+            _: i32,
+            _: u32,
+            _: i32,
+            _: i32,
+            _: u32,
+            _: i32,
+            _: i32,
+            _: u32,
+            _: &ScissorBox,
+        ) {
+        }
+        fn draw_triangle_shaded_zbuffer(
+            &mut self,
+            _: i32,
+            _: i32,
+            _: u16,
+            _: u32,
+            _: i32,
+            _: i32,
+            _: u16,
+            _: u32,
+            _: i32,
+            _: i32,
+            _: u16,
+            _: u32,
+            _: &ScissorBox,
+        ) {
+        }
+        fn draw_triangle_textured(
+            &mut self,
+            _: i32,
+            _: i32,
+            _: f32,
+            _: f32,
+            _: i32,
+            _: i32,
+            _: f32,
+            _: f32,
+            _: i32,
+            _: i32,
+            _: f32,
+            _: f32,
+            _: &dyn Fn(f32, f32) -> u32,
+            _: &ScissorBox,
+        ) {
+        }
+        fn draw_triangle_textured_zbuffer(
+            &mut self,
+            _: i32,
+            _: i32,
+            _: u16,
+            _: f32,
+            _: f32,
+            _: i32,
+            _: i32,
+            _: u16,
+            _: f32,
+            _: f32,
+            _: i32,
+            _: i32,
+            _: u16,
+            _: f32,
+            _: f32,
+            _: &dyn Fn(f32, f32) -> u32,
+            _: &ScissorBox,
+        ) {
+        }
+        fn clear_zbuffer(&mut self) {}
+        fn set_zbuffer_enabled(&mut self, _: bool) {}
+        fn resize(&mut self, _: u32, _: u32) {}
+        fn reset(&mut self) {}
+        fn name(&self) -> &str {
+            "Lost Context Renderer"
+        }
+        fn flush(&mut self) -> Result<(), String> {
+            Err("context lost".to_string())
+        }
+    }
+
+    #[test]
+    fn test_rdp_renderer_fallback_keeps_frame() {
+        let mut rdp = Rdp::new();
+        let mut frame = Frame::new(320, 240);
+        frame.pixels[100] = 0xFF123456;
+        rdp.renderer = Box::new(LostContextRenderer { frame });
+        rdp.set_zbuffer_enabled(true);
+
+        rdp.finish_frame();
+        assert_eq!(rdp.renderer_name(), "Software RDP Renderer");
+        assert_eq!(rdp.get_frame().pixels[100], 0xFF123456);
+
+        // The software renderer keeps drawing with the inherited Z-buffer state
+        rdp.clear_zbuffer();
+        rdp.draw_triangle_zbuffer(10, 10, 1000, 50, 10, 1000, 30, 50, 1000, 0xFF00FF00);
+        rdp.draw_triangle_zbuffer(10, 10, 2000, 50, 10, 2000, 30, 50, 2000, 0xFFFF0000);
+        assert_eq!(rdp.get_frame().pixels[20 * 320 + 30], 0xFF00FF00);
+    }
+
+    #[test]
+    fn test_rdp_combiner_state() {
+        let mut rdp = Rdp::new();
+        let rdram = vec![0u8; 1024];
+        rdp.execute_rdp_command(0x3A, 0x3A000000, 0xFF8000FF, &rdram);
+        rdp.execute_rdp_command(0x3B, 0x3B000000, 0x00FF00FF, &rdram);
+        rdp.execute_rdp_command(0x3C, 0x3C121824, 0xFF33FFFF, &rdram);
+
+        let state = rdp.combiner_state();
+        assert_eq!(state.prim_color, 0xFF8000FF);
+        assert_eq!(state.env_color, 0x00FF00FF);
+        assert_eq!(state.combine_mode, 0x3C12_1824_FF33_FFFF);
+    }
 }
@@ -41,11 +41,15 @@
 //! - Rectangle filling with scissor clipping
 //! - Z-buffer operations (clear, enable/disable)
 //! - Texture sampling and mapping
+//! - Color combiner state (`set_combiner()`), used by backends that can
+//!   evaluate it per pixel
+//! - `flush()`: submit batched work and bring `get_frame()` up to date
 
 use emu_core::types::Frame;
+use serde::{Deserialize, Serialize};
 
 /// Scissor box for clipping (shared between RDP and renderers)
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct ScissorBox {
     pub x_min: u32,
     pub y_min: u32,
@@ -53,6 +57,20 @@ pub struct ScissorBox {
     pub y_max: u32,
 }
 
+/// Color combiner state from SET_COMBINE_MODE, SET_PRIM_COLOR and SET_ENV_COLOR
+///
+/// Only cycle 0 is decoded; inputs without an equivalent here (noise, key,
+/// LOD fraction, YUV conversion constants) read as zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CombinerState {
+    /// Raw 64-bit combine mode (0 = pass the shade or texel color through)
+    pub combine_mode: u64,
+    /// Primitive color (RGBA8888)
+    pub prim_color: u32,
+    /// Environment color (RGBA8888)
+    pub env_color: u32,
+}
+
 /// Trait for RDP rendering backends
 ///
 /// This trait abstracts the actual rasterization work, allowing different
@@ -218,4 +236,17 @@ pub trait RdpRenderer: Send {
     fn is_hardware_accelerated(&self) -> bool {
         false
     }
+
+    /// Update the color combiner used by subsequent triangles
+    ///
+    /// Backends that don't evaluate the combiner ignore this.
+    fn set_combiner(&mut self, _combiner: &CombinerState) {}
+
+    /// Submit any batched work and make `get_frame()` reflect it
+    ///
+    /// An error means the backend can no longer render (e.g. the GPU context
+    /// was lost) and the caller should switch to another renderer.
+    fn flush(&mut self) -> Result<(), String> {
+        Ok(())
+    }
 }
@@ -6,22 +6,131 @@
 //! **Architecture**:
 //! - Uses OpenGL 3.3 Core Profile
 //! - Renders to FBO (Framebuffer Object) for offscreen rendering
-//! - Supports flat and Gouraud shading via separate shader programs
+//! - Triangles and rectangles are batched into one vertex buffer and drawn
+//!   with a single call until the render state (scissor, depth test, texture,
+//!   combiner) changes
+//! - One shader program approximates the RDP color combiner (cycle 0)
 //! - Hardware depth testing for Z-buffer
-//! - Pixels read back to Frame for compatibility with current API
+//! - Pixels are read back to the Frame only on `flush()` or when the CPU
+//!   touches the framebuffer (`set_pixel`, `get_frame_mut`)
 //!
 //! **Integration**:
 //! - Requires OpenGL context from frontend (SDL2)
 //! - Feature-gated behind `opengl` feature flag
-//! - Falls back to software renderer if GL context unavailable
+//! - Falls back to software renderer if GL context unavailable, or if `flush()`
+//!   reports a GL error at runtime
 
 #[cfg(feature = "opengl")]
-use super::rdp_renderer::{RdpRenderer, ScissorBox};
+use super::rdp_renderer::{CombinerState, RdpRenderer, ScissorBox};
 #[cfg(feature = "opengl")]
 use emu_core::types::Frame;
 #[cfg(feature = "opengl")]
 use glow::HasContext;
 
+/// Floats per vertex: position (2), depth (1), shade color (4), texture coordinate (2)
+#[cfg(feature = "opengl")]
+const FLOATS_PER_VERTEX: usize = 9;
+
+/// Vertices queued before a batch is submitted regardless of state changes
+#[cfg(feature = "opengl")]
+const MAX_BATCH_VERTICES: usize = 3 * 4096;
+
+/// Largest CPU-sampled texture uploaded for a textured triangle
+#[cfg(feature = "opengl")]
+const MAX_TEXTURE_SIZE: u32 = 1024;
+
+/// Color combiner input, normalized across the A/B/C/D slots
+///
+/// The RDP encodes the inputs of `(A - B) * C + D` differently for every slot
+/// and for color vs. alpha. Decoding them into one enum lets a backend
+/// evaluate both equations with the same lookup.
+#[cfg(feature = "opengl")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CombinerInput {
+    Zero = 0,
+    One = 1,
+    /// Output of the previous cycle (the shade color in cycle 0)
+    Combined = 2,
+    Texel0 = 3,
+    Texel1 = 4,
+    Primitive = 5,
+    Shade = 6,
+    Environment = 7,
+    CombinedAlpha = 8,
+    Texel0Alpha = 9,
+    Texel1Alpha = 10,
+    PrimitiveAlpha = 11,
+    ShadeAlpha = 12,
+    EnvironmentAlpha = 13,
+}
+
+#[cfg(feature = "opengl")]
+impl CombinerState {
+    /// True if no combine mode was set and the source color is used as-is
+    fn is_passthrough(&self) -> bool {
+        self.combine_mode == 0
+    }
+
+    /// Cycle 0 color inputs in A, B, C, D order
+    fn color_inputs(&self) -> [CombinerInput; 4] {
+        use CombinerInput::*;
+        let mode = self.combine_mode;
+        let field = |shift: u32, bits: u32| ((mode >> shift) & ((1 << bits) - 1)) as u8;
+
+        let a = match field(52, 4) {
+            6 => One,
+            n => Self::common_input(n),
+        };
+        let b = Self::common_input(field(28, 4));
+        let c = match field(47, 5) {
+            7 => CombinedAlpha,
+            8 => Texel0Alpha,
+            9 => Texel1Alpha,
+            10 => PrimitiveAlpha,
+            11 => ShadeAlpha,
+            12 => EnvironmentAlpha,
+            n => Self::common_input(n),
+        };
+        let d = match field(15, 3) {
+            6 => One,
+            n => Self::common_input(n),
+        };
+        [a, b, c, d]
+    }
+
+    /// Cycle 0 alpha inputs in A, B, C, D order
+    fn alpha_inputs(&self) -> [CombinerInput; 4] {
+        use CombinerInput::*;
+        let mode = self.combine_mode;
+        let field = |shift: u32| ((mode >> shift) & 0x7) as u8;
+        let add_sub = |n: u8| match n {
+            6 => One,
+            n => Self::common_input(n),
+        };
+
+        let c = match field(41) {
+            // 0 is the LOD fraction, not the combined alpha
+            0 => Zero,
+            n => Self::common_input(n),
+        };
+        [add_sub(field(44)), add_sub(field(12)), c, add_sub(field(9))]
+    }
+
+    /// Inputs 0-5, shared by every slot; everything else reads as zero
+    fn common_input(n: u8) -> CombinerInput {
+        use CombinerInput::*;
+        match n {
+            0 => Combined,
+            1 => Texel0,
+            2 => Texel1,
+            3 => Primitive,
+            4 => Shade,
+            5 => Environment,
+            _ => Zero,
+        }
+    }
+}
+
 /// Wrapper for glow::Context that implements Send
 /// Safety: OpenGL contexts are generally safe to send between threads as long as
 /// they're not actively being used on multiple threads simultaneously. The RDP
@@ -41,13 +150,28 @@ impl std::ops::Deref for SendContext {
     }
 }
 
-/// Shader program type for different rendering modes
+/// Render state shared by every triangle in a batch
+#[cfg(feature = "opengl")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct BatchState {
+    /// GL scissor rectangle (x, y, width, height), bottom-up
+    scissor: [i32; 4],
+    depth_test: bool,
+    textured: bool,
+    /// Apply the color combiner (fill rectangles bypass it)
+    combine: bool,
+}
+
+/// Uniform locations of the combiner program
 #[cfg(feature = "opengl")]
-#[derive(Debug, Clone, Copy, PartialEq)]
-enum ShaderProgram {
-    Flat,     // Solid color triangles
-    Gouraud,  // Per-vertex color interpolation
-    Textured, // Textured triangles
+struct Uniforms {
+    texture: Option<glow::UniformLocation>,
+    textured: Option<glow::UniformLocation>,
+    combine: Option<glow::UniformLocation>,
+    color_inputs: Option<glow::UniformLocation>,
+    alpha_inputs: Option<glow::UniformLocation>,
+    prim_color: Option<glow::UniformLocation>,
+    env_color: Option<glow::UniformLocation>,
 }
 
 /// OpenGL-based RDP renderer
@@ -63,23 +187,33 @@ pub struct OpenGLRdpRenderer {
     color_texture: glow::Texture,
     depth_renderbuffer: glow::Renderbuffer,
 
-    // Shader programs
-    flat_program: glow::Program,
-    gouraud_program: glow::Program,
-    textured_program: glow::Program,
-    current_program: ShaderProgram,
+    // Combiner shader program
+    program: glow::Program,
+    uniforms: Uniforms,
 
     // Vertex data
     vao: glow::VertexArray,
     vbo: glow::Buffer,
 
+    // Pending batch
+    vertices: Vec<f32>,
+    batch_state: Option<BatchState>,
+
     // Dynamic texture for CPU-sampled textures
     dynamic_texture: glow::Texture,
     dynamic_texture_width: u32,
     dynamic_texture_height: u32,
+    dynamic_texture_pixels: Vec<u8>,
+
+    combiner: CombinerState,
 
     // Z-buffer state
     zbuffer_enabled: bool,
+
+    /// The FBO holds draws that haven't been read back into `framebuffer`
+    gpu_dirty: bool,
+    /// `framebuffer` holds CPU writes that haven't been uploaded to the FBO
+    cpu_dirty: bool,
 }
 
 #[cfg(feature = "opengl")]
@@ -152,18 +286,42 @@ impl OpenGLRdpRenderer {
                 return Err(format!("Framebuffer incomplete: status = 0x{:X}", status));
             }
 
-            // Create shader programs
-            let flat_program = create_flat_program(&gl)?;
-            let gouraud_program = create_gouraud_program(&gl)?;
-            let textured_program = create_textured_program(&gl)?;
-
-            // Create VAO and VBO
+            // Create shader program
+            let program = create_combiner_program(&gl)?;
+            let uniforms = Uniforms {
+                texture: gl.get_uniform_location(program, "uTexture"),
+                textured: gl.get_uniform_location(program, "uTextured"),
+                combine: gl.get_uniform_location(program, "uCombine"),
+                color_inputs: gl.get_uniform_location(program, "uColorInputs"),
+                alpha_inputs: gl.get_uniform_location(program, "uAlphaInputs"),
+                prim_color: gl.get_uniform_location(program, "uPrimColor"),
+                env_color: gl.get_uniform_location(program, "uEnvColor"),
+            };
+
+            // Create VAO and VBO; the attribute layout is fixed for all batches
             let vao = gl
                 .create_vertex_array()
                 .map_err(|e| format!("Failed to create VAO: {}", e))?;
             let vbo = gl
                 .create_buffer()
                 .map_err(|e| format!("Failed to create VBO: {}", e))?;
+            gl.bind_vertex_array(Some(vao));
+            gl.bind_buffer(glow::ARRAY_BUFFER, Some(vbo));
+            let float_size = std::mem::size_of::<f32>() as i32;
+            let stride = FLOATS_PER_VERTEX as i32 * float_size;
+            // Position, depth, shade color, texture coordinate
+            for (index, (size, offset)) in [(2, 0), (1, 2), (4, 3), (2, 7)].iter().enumerate() {
+                gl.vertex_attrib_pointer_f32(
+                    index as u32,
+                    *size,
+                    glow::FLOAT,
+                    false,
+                    stride,
+                    offset * float_size,
+                );
+                gl.enable_vertex_attrib_array(index as u32);
+            }
+            gl.bind_vertex_array(None);
 
             // Create dynamic texture for CPU-sampled textures
             let dynamic_texture = gl
@@ -205,20 +363,120 @@ impl OpenGLRdpRenderer {
                 fbo,
                 color_texture,
                 depth_renderbuffer,
-                flat_program,
-                gouraud_program,
-                textured_program,
-                current_program: ShaderProgram::Flat,
+                program,
+                uniforms,
                 vao,
                 vbo,
+                vertices: Vec::with_capacity(MAX_BATCH_VERTICES * FLOATS_PER_VERTEX),
+                batch_state: None,
                 dynamic_texture,
                 dynamic_texture_width: 0,
                 dynamic_texture_height: 0,
+                dynamic_texture_pixels: Vec::new(),
+                combiner: CombinerState::default(),
                 zbuffer_enabled: false,
+                gpu_dirty: false,
+                cpu_dirty: false,
             })
         }
     }
 
+    /// Queue a triangle, submitting the pending batch first if its state differs
+    fn queue_triangle(&mut self, state: BatchState, vertices: [[f32; FLOATS_PER_VERTEX]; 3]) {
+        let queued = self.vertices.len() / FLOATS_PER_VERTEX;
+        if self.batch_state != Some(state) || queued + 3 > MAX_BATCH_VERTICES {
+            unsafe { self.submit_batch() };
+        }
+        self.batch_state = Some(state);
+        for vertex in &vertices {
+            self.vertices.extend_from_slice(vertex);
+        }
+    }
+
+    /// Draw the pending batch into the FBO with a single draw call
+    unsafe fn submit_batch(&mut self) {
+        let Some(state) = self.batch_state.take() else {
+            return;
+        };
+        if self.vertices.is_empty() {
+            return;
+        }
+        if self.cpu_dirty {
+            self.upload_framebuffer();
+        }
+
+        self.gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.fbo));
+        self.gl
+            .viewport(0, 0, self.width as i32, self.height as i32);
+        self.gl.use_program(Some(self.program));
+
+        // Combiner uniforms
+        let combine = state.combine && !self.combiner.is_passthrough();
+        self.gl
+            .uniform_1_i32(self.uniforms.combine.as_ref(), combine as i32);
+        let [ca, cb, cc, cd] = self.combiner.color_inputs().map(|i| i as i32);
+        self.gl
+            .uniform_4_i32(self.uniforms.color_inputs.as_ref(), ca, cb, cc, cd);
+        let [aa, ab, ac, ad] = self.combiner.alpha_inputs().map(|i| i as i32);
+        self.gl
+            .uniform_4_i32(self.uniforms.alpha_inputs.as_ref(), aa, ab, ac, ad);
+        let [r, g, b, a] = rgba8888_to_vec4(self.combiner.prim_color);
+        self.gl
+            .uniform_4_f32(self.uniforms.prim_color.as_ref(), r, g, b, a);
+        let [r, g, b, a] = rgba8888_to_vec4(self.combiner.env_color);
+        self.gl
+            .uniform_4_f32(self.uniforms.env_color.as_ref(), r, g, b, a);
+
+        // Texture
+        self.gl
+            .uniform_1_i32(self.uniforms.textured.as_ref(), state.textured as i32);
+        if state.textured {
+            self.gl.active_texture(glow::TEXTURE0);
+            self.gl
+                .bind_texture(glow::TEXTURE_2D, Some(self.dynamic_texture));
+            self.gl.uniform_1_i32(self.uniforms.texture.as_ref(), 0);
+        }
+
+        // Scissor and depth test
+        let [x, y, w, h] = state.scissor;
+        self.gl.enable(glow::SCISSOR_TEST);
+        self.gl.scissor(x, y, w, h);
+        if state.depth_test {
+            self.gl.enable(glow::DEPTH_TEST);
+            self.gl.depth_func(glow::LESS);
+        }
+
+        self.gl.bind_vertex_array(Some(self.vao));
+        self.gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.vbo));
+        self.gl.buffer_data_u8_slice(
+            glow::ARRAY_BUFFER,
+            bytemuck::cast_slice(&self.vertices),
+            glow::STREAM_DRAW,
+        );
+        self.gl.draw_arrays(
+            glow::TRIANGLES,
+            0,
+            (self.vertices.len() / FLOATS_PER_VERTEX) as i32,
+        );
+
+        self.gl.bind_vertex_array(None);
+        self.gl.disable(glow::DEPTH_TEST);
+        self.gl.disable(glow::SCISSOR_TEST);
+        self.gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+
+        self.vertices.clear();
+        self.gpu_dirty = true;
+    }
+
+    /// Submit pending work and read the FBO back if it changed
+    unsafe fn sync_framebuffer(&mut self) {
+        self.submit_batch();
+        if self.gpu_dirty {
+            self.read_pixels();
+            self.gpu_dirty = false;
+        }
+    }
+
     /// Read pixels from framebuffer to CPU memory
     unsafe fn read_pixels(&mut self) {
         self.gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.fbo));
@@ -236,24 +494,69 @@ impl OpenGLRdpRenderer {
         );
 
         // Convert RGBA to ARGB and flip vertically (OpenGL Y is bottom-up)
-        for y in 0..self.height {
-            for x in 0..self.width {
-                let src_idx = ((self.height - 1 - y) * self.width + x) * 4;
-                let dst_idx = (y * self.width + x) as usize;
-
-                let r = pixels[src_idx as usize];
-                let g = pixels[src_idx as usize + 1];
-                let b = pixels[src_idx as usize + 2];
-                let a = pixels[src_idx as usize + 3];
-
-                self.framebuffer.pixels[dst_idx] =
-                    ((a as u32) << 24) | ((r as u32) << 16) | ((g as u32) << 8) | (b as u32);
+        let width = self.width as usize;
+        for (y, row) in self.framebuffer.pixels.chunks_mut(width).enumerate() {
+            let src_row = (self.height as usize - 1 - y) * width * 4;
+            for (x, pixel) in row.iter_mut().enumerate() {
+                let src = src_row + x * 4;
+                *pixel = rgba_bytes_to_argb(&pixels[src..src + 4]);
             }
         }
 
         self.gl.bind_framebuffer(glow::FRAMEBUFFER, None);
     }
 
+    /// Upload CPU writes to the framebuffer into the FBO color texture
+    unsafe fn upload_framebuffer(&mut self) {
+        let width = self.width as usize;
+        let mut pixels = vec![0u8; width * self.height as usize * 4];
+        for (y, row) in self.framebuffer.pixels.chunks(width).enumerate() {
+            let dst_row = (self.height as usize - 1 - y) * width * 4;
+            for (x, &color) in row.iter().enumerate() {
+                let dst = dst_row + x * 4;
+                pixels[dst..dst + 4].copy_from_slice(&argb_to_rgba_bytes(color));
+            }
+        }
+
+        self.gl
+            .bind_texture(glow::TEXTURE_2D, Some(self.color_texture));
+        self.gl.tex_sub_image_2d(
+            glow::TEXTURE_2D,
+            0,
+            0,
+            0,
+            self.width as i32,
+            self.height as i32,
+            glow::RGBA,
+            glow::UNSIGNED_BYTE,
+            glow::PixelUnpackData::Slice(&pixels),
+        );
+        self.cpu_dirty = false;
+    }
+
+    /// Build one vertex in the batch layout
+    fn vertex(&self, x: i32, y: i32, z: u16, color: u32, s: f32, t: f32) -> [f32; 9] {
+        let (nx, ny) = self.screen_to_ndc(x, y);
+        let [r, g, b, a] = Self::argb_to_rgba(color);
+        [nx, ny, Self::zbuffer_to_depth(z), r, g, b, a, s, t]
+    }
+
+    /// State for a draw with the given scissor
+    fn batch_state(
+        &self,
+        scissor: &ScissorBox,
+        depth_test: bool,
+        textured: bool,
+        combine: bool,
+    ) -> BatchState {
+        BatchState {
+            scissor: gl_scissor_rect(scissor, self.height),
+            depth_test: depth_test && self.zbuffer_enabled,
+            textured,
+            combine,
+        }
+    }
+
     /// Convert screen coordinates to normalized device coordinates
     fn screen_to_ndc(&self, x: i32, y: i32) -> (f32, f32) {
         let nx = (x as f32 / self.width as f32) * 2.0 - 1.0;
@@ -275,40 +578,35 @@ impl OpenGLRdpRenderer {
         z as f32 / 65535.0
     }
 
-    /// Upload texture data from CPU-sampled texture function
-    /// This samples the texture function and uploads it to GPU
-    unsafe fn upload_dynamic_texture(
+    /// Make a CPU-sampled texture current for the next textured triangle
+    ///
+    /// Triangles that sample identical texels keep batching; a different
+    /// texture submits the pending batch before it is replaced.
+    unsafe fn bind_dynamic_texture(
         &mut self,
         width: u32,
         height: u32,
         texture_fn: &dyn Fn(f32, f32) -> u32,
     ) {
-        // Only recreate if size changed
-        if width != self.dynamic_texture_width || height != self.dynamic_texture_height {
-            self.dynamic_texture_width = width;
-            self.dynamic_texture_height = height;
-        }
-
         // Sample the texture function into a buffer
         let mut pixels = vec![0u8; (width * height * 4) as usize];
         for y in 0..height {
             for x in 0..width {
-                let color = texture_fn(x as f32, y as f32);
                 let idx = ((y * width + x) * 4) as usize;
-
-                // Convert ARGB to RGBA
-                let a = ((color >> 24) & 0xFF) as u8;
-                let r = ((color >> 16) & 0xFF) as u8;
-                let g = ((color >> 8) & 0xFF) as u8;
-                let b = (color & 0xFF) as u8;
-
-                pixels[idx] = r;
-                pixels[idx + 1] = g;
-                pixels[idx + 2] = b;
-                pixels[idx + 3] = a;
+                pixels[idx..idx + 4]
+                    .copy_from_slice(&argb_to_rgba_bytes(texture_fn(x as f32, y as f32)));
             }
         }
 
+        if width == self.dynamic_texture_width
+            && height == self.dynamic_texture_height
+            && pixels == self.dynamic_texture_pixels
+        {
+            return;
+        }
+
+        self.submit_batch();
+
         // Upload to GPU
         self.gl
             .bind_texture(glow::TEXTURE_2D, Some(self.dynamic_texture));
@@ -323,16 +621,93 @@ impl OpenGLRdpRenderer {
             glow::UNSIGNED_BYTE,
             Some(&pixels),
         );
+
+        self.dynamic_texture_width = width;
+        self.dynamic_texture_height = height;
+        self.dynamic_texture_pixels = pixels;
+    }
+
+    /// Queue a textured triangle, optionally depth tested
+    fn queue_textured_triangle(
+        &mut self,
+        v: [(i32, i32, u16, f32, f32); 3],
+        depth_test: bool,
+        texture: &dyn Fn(f32, f32) -> u32,
+        scissor: &ScissorBox,
+    ) {
+        let (tex_width, tex_height) = texture_size(v.map(|(_, _, _, s, t)| (s, t)));
+        unsafe { self.bind_dynamic_texture(tex_width, tex_height, texture) };
+
+        // Normalize texture coordinates to [0, 1] range
+        let state = self.batch_state(scissor, depth_test, true, true);
+        let vertices = v.map(|(x, y, z, s, t)| {
+            self.vertex(
+                x,
+                y,
+                z,
+                0xFFFFFFFF,
+                s / tex_width as f32,
+                t / tex_height as f32,
+            )
+        });
+        self.queue_triangle(state, vertices);
     }
 }
 
+/// Convert a scissor box (top-down) to a GL scissor rectangle (bottom-up)
+#[cfg(feature = "opengl")]
+fn gl_scissor_rect(scissor: &ScissorBox, height: u32) -> [i32; 4] {
+    let y_max = scissor.y_max.min(height);
+    let y_min = scissor.y_min.min(y_max);
+    [
+        scissor.x_min as i32,
+        (height - y_max) as i32,
+        scissor.x_max.saturating_sub(scissor.x_min) as i32,
+        (y_max - y_min) as i32,
+    ]
+}
+
+/// Size of the texture to sample for a triangle, from its largest coordinates
+#[cfg(feature = "opengl")]
+fn texture_size(coords: [(f32, f32); 3]) -> (u32, u32) {
+    let max_s = coords.iter().fold(0.0f32, |m, &(s, _)| m.max(s));
+    let max_t = coords.iter().fold(0.0f32, |m, &(_, t)| m.max(t));
+    (
+        (max_s.ceil() as u32).clamp(1, MAX_TEXTURE_SIZE),
+        (max_t.ceil() as u32).clamp(1, MAX_TEXTURE_SIZE),
+    )
+}
+
+/// Convert ARGB color to RGBA bytes
+#[cfg(feature = "opengl")]
+fn argb_to_rgba_bytes(color: u32) -> [u8; 4] {
+    let [a, r, g, b] = color.to_be_bytes();
+    [r, g, b, a]
+}
+
+/// Convert RGBA bytes to ARGB color
+#[cfg(feature = "opengl")]
+fn rgba_bytes_to_argb(rgba: &[u8]) -> u32 {
+    u32::from_be_bytes([rgba[3], rgba[0], rgba[1], rgba[2]])
+}
+
+/// Convert an RDP RGBA8888 color register to an RGBA vec4
+#[cfg(feature = "opengl")]
+fn rgba8888_to_vec4(color: u32) -> [f32; 4] {
+    color.to_be_bytes().map(|c| c as f32 / 255.0)
+}
+
 #[cfg(feature = "opengl")]
 impl RdpRenderer for OpenGLRdpRenderer {
     fn init(&mut self, width: u32, height: u32) {
         unsafe {
+            self.vertices.clear();
+            self.batch_state = None;
             self.width = width;
             self.height = height;
             self.framebuffer = Frame::new(width, height);
+            self.gpu_dirty = false;
+            self.cpu_dirty = true;
 
             // Resize textures
             self.gl
@@ -367,20 +742,29 @@ impl RdpRenderer for OpenGLRdpRenderer {
     }
 
     fn get_frame_mut(&mut self) -> &mut Frame {
+        unsafe { self.sync_framebuffer() };
+        self.cpu_dirty = true;
         &mut self.framebuffer
     }
 
     fn clear(&mut self, color: u32) {
+        // Everything queued so far would be overwritten
+        self.vertices.clear();
+        self.batch_state = None;
         unsafe {
             self.gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.fbo));
 
             let rgba = Self::argb_to_rgba(color);
             self.gl.clear_color(rgba[0], rgba[1], rgba[2], rgba[3]);
-            self.gl
-                .clear(glow::COLOR_BUFFER_BIT | glow::DEPTH_BUFFER_BIT);
+            self.gl.clear(glow::COLOR_BUFFER_BIT);
 
-            self.read_pixels();
+            self.gl.bind_framebuffer(glow::FRAMEBUFFER, None);
         }
+
+        // The result is known without reading it back
+        self.framebuffer.pixels.fill(color);
+        self.gpu_dirty = false;
+        self.cpu_dirty = false;
     }
 
     fn fill_rect(
@@ -392,77 +776,25 @@ impl RdpRenderer for OpenGLRdpRenderer {
         color: u32,
         scissor: &ScissorBox,
     ) {
-        unsafe {
-            self.gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.fbo));
-
-            // Enable scissor test
-            self.gl.enable(glow::SCISSOR_TEST);
-            self.gl.scissor(
-                scissor.x_min as i32,
-                (self.height - scissor.y_max) as i32,
-                (scissor.x_max - scissor.x_min) as i32,
-                (scissor.y_max - scissor.y_min) as i32,
-            );
-
-            // Use flat shader
-            self.gl.use_program(Some(self.flat_program));
-            self.current_program = ShaderProgram::Flat;
-
-            // Set color uniform
-            let rgba = Self::argb_to_rgba(color);
-            let u_color = self.gl.get_uniform_location(self.flat_program, "uColor");
-            if let Some(loc) = u_color {
-                self.gl
-                    .uniform_4_f32(Some(&loc), rgba[0], rgba[1], rgba[2], rgba[3]);
-            }
-
-            // Create rectangle as two triangles
-            let (x0, y0) = self.screen_to_ndc(x as i32, y as i32);
-            let (x1, y1) = self.screen_to_ndc((x + width) as i32, (y + height) as i32);
-
-            #[rustfmt::skip]
-            let vertices: [f32; 12] = [
-                x0, y0,  // Triangle 1, Vertex 1
-                x1, y0,  // Triangle 1, Vertex 2
-                x0, y1,  // Triangle 1, Vertex 3
-                x0, y1,  // Triangle 2, Vertex 1
-                x1, y0,  // Triangle 2, Vertex 2
-                x1, y1,  // Triangle 2, Vertex 3
-            ];
-
-            self.gl.bind_vertex_array(Some(self.vao));
-            self.gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.vbo));
-            self.gl.buffer_data_u8_slice(
-                glow::ARRAY_BUFFER,
-                bytemuck::cast_slice(&vertices),
-                glow::STREAM_DRAW,
-            );
-
-            // Position attribute
-            self.gl.vertex_attrib_pointer_f32(
-                0,
-                2,
-                glow::FLOAT,
-                false,
-                2 * std::mem::size_of::<f32>() as i32,
-                0,
-            );
-            self.gl.enable_vertex_attrib_array(0);
-
-            self.gl.draw_arrays(glow::TRIANGLES, 0, 6);
-
-            self.gl.disable(glow::SCISSOR_TEST);
-            self.read_pixels();
-        }
+        // Rectangle as two triangles; fill mode bypasses the combiner
+        let state = self.batch_state(scissor, false, false, false);
+        let (x0, y0) = (x as i32, y as i32);
+        let (x1, y1) = ((x + width) as i32, (y + height) as i32);
+        let v = |x, y| self.vertex(x, y, 0, color, 0.0, 0.0);
+        let first = [v(x0, y0), v(x1, y0), v(x0, y1)];
+        let second = [v(x0, y1), v(x1, y0), v(x1, y1)];
+        self.queue_triangle(state, first);
+        self.queue_triangle(state, second);
     }
 
     fn set_pixel(&mut self, x: u32, y: u32, color: u32) {
-        // For individual pixels, just update the frame directly (software fallback)
+        // Individual pixels are written on the CPU side and uploaded before
+        // the next batch is drawn
         if x < self.width && y < self.height {
+            unsafe { self.sync_framebuffer() };
             let idx = (y * self.width + x) as usize;
-            if idx < self.framebuffer.pixels.len() {
-                self.framebuffer.pixels[idx] = color;
-            }
+            self.framebuffer.pixels[idx] = color;
+            self.cpu_dirty = true;
         }
     }
 
@@ -477,66 +809,13 @@ impl RdpRenderer for OpenGLRdpRenderer {
         color: u32,
         scissor: &ScissorBox,
     ) {
-        unsafe {
-            self.gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.fbo));
-
-            // Enable scissor test
-            self.gl.enable(glow::SCISSOR_TEST);
-            self.gl.scissor(
-                scissor.x_min as i32,
-                (self.height - scissor.y_max) as i32,
-                (scissor.x_max - scissor.x_min) as i32,
-                (scissor.y_max - scissor.y_min) as i32,
-            );
-
-            // Use flat shader
-            self.gl.use_program(Some(self.flat_program));
-            self.current_program = ShaderProgram::Flat;
-
-            // Set color uniform
-            let rgba = Self::argb_to_rgba(color);
-            let u_color = self.gl.get_uniform_location(self.flat_program, "uColor");
-            if let Some(loc) = u_color {
-                self.gl
-                    .uniform_4_f32(Some(&loc), rgba[0], rgba[1], rgba[2], rgba[3]);
-            }
-
-            // Convert to NDC
-            let (nx0, ny0) = self.screen_to_ndc(x0, y0);
-            let (nx1, ny1) = self.screen_to_ndc(x1, y1);
-            let (nx2, ny2) = self.screen_to_ndc(x2, y2);
-
-            #[rustfmt::skip]
-            let vertices: [f32; 6] = [
-                nx0, ny0,
-                nx1, ny1,
-                nx2, ny2,
-            ];
-
-            self.gl.bind_vertex_array(Some(self.vao));
-            self.gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.vbo));
-            self.gl.buffer_data_u8_slice(
-                glow::ARRAY_BUFFER,
-                bytemuck::cast_slice(&vertices),
-                glow::STREAM_DRAW,
-            );
-
-            // Position attribute
-            self.gl.vertex_attrib_pointer_f32(
-                0,
-                2,
-                glow::FLOAT,
-                false,
-                2 * std::mem::size_of::<f32>() as i32,
-                0,
-            );
-            self.gl.enable_vertex_attrib_array(0);
-
-            self.gl.draw_arrays(glow::TRIANGLES, 0, 3);
-
-            self.gl.disable(glow::SCISSOR_TEST);
-            self.read_pixels();
-        }
+        let state = self.batch_state(scissor, false, false, true);
+        let vertices = [
+            self.vertex(x0, y0, 0, color, 0.0, 0.0),
+            self.vertex(x1, y1, 0, color, 0.0, 0.0),
+            self.vertex(x2, y2, 0, color, 0.0, 0.0),
+        ];
+        self.queue_triangle(state, vertices);
     }
 
     fn draw_triangle_zbuffer(
@@ -553,76 +832,13 @@ impl RdpRenderer for OpenGLRdpRenderer {
         color: u32,
         scissor: &ScissorBox,
     ) {
-        unsafe {
-            self.gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.fbo));
-
-            // Enable depth testing
-            if self.zbuffer_enabled {
-                self.gl.enable(glow::DEPTH_TEST);
-                self.gl.depth_func(glow::LESS);
-            }
-
-            // Enable scissor test
-            self.gl.enable(glow::SCISSOR_TEST);
-            self.gl.scissor(
-                scissor.x_min as i32,
-                (self.height - scissor.y_max) as i32,
-                (scissor.x_max - scissor.x_min) as i32,
-                (scissor.y_max - scissor.y_min) as i32,
-            );
-
-            // Use flat shader
-            self.gl.use_program(Some(self.flat_program));
-            self.current_program = ShaderProgram::Flat;
-
-            // Set color uniform
-            let rgba = Self::argb_to_rgba(color);
-            let u_color = self.gl.get_uniform_location(self.flat_program, "uColor");
-            if let Some(loc) = u_color {
-                self.gl
-                    .uniform_4_f32(Some(&loc), rgba[0], rgba[1], rgba[2], rgba[3]);
-            }
-
-            // Convert to NDC and depth
-            let (nx0, ny0) = self.screen_to_ndc(x0, y0);
-            let (nx1, ny1) = self.screen_to_ndc(x1, y1);
-            let (nx2, ny2) = self.screen_to_ndc(x2, y2);
-            let d0 = Self::zbuffer_to_depth(z0);
-            let d1 = Self::zbuffer_to_depth(z1);
-            let d2 = Self::zbuffer_to_depth(z2);
-
-            #[rustfmt::skip]
-            let vertices: [f32; 9] = [
-                nx0, ny0, d0,
-                nx1, ny1, d1,
-                nx2, ny2, d2,
-            ];
-
-            self.gl.bind_vertex_array(Some(self.vao));
-            self.gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.vbo));
-            self.gl.buffer_data_u8_slice(
-                glow::ARRAY_BUFFER,
-                bytemuck::cast_slice(&vertices),
-                glow::STREAM_DRAW,
-            );
-
-            // Position + depth attribute
-            self.gl.vertex_attrib_pointer_f32(
-                0,
-                3,
-                glow::FLOAT,
-                false,
-                3 * std::mem::size_of::<f32>() as i32,
-                0,
-            );
-            self.gl.enable_vertex_attrib_array(0);
-
-            self.gl.draw_arrays(glow::TRIANGLES, 0, 3);
-
-            self.gl.disable(glow::DEPTH_TEST);
-            self.gl.disable(glow::SCISSOR_TEST);
-            self.read_pixels();
-        }
+        let state = self.batch_state(scissor, true, false, true);
+        let vertices = [
+            self.vertex(x0, y0, z0, color, 0.0, 0.0),
+            self.vertex(x1, y1, z1, color, 0.0, 0.0),
+            self.vertex(x2, y2, z2, color, 0.0, 0.0),
+        ];
+        self.queue_triangle(state, vertices);
     }
 
     fn draw_triangle_shaded(
@@ -638,68 +854,13 @@ impl RdpRenderer for OpenGLRdpRenderer {
         c2: u32,
         scissor: &ScissorBox,
     ) {
-        unsafe {
-            self.gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.fbo));
-
-            // Enable scissor test
-            self.gl.enable(glow::SCISSOR_TEST);
-            self.gl.scissor(
-                scissor.x_min as i32,
-                (self.height - scissor.y_max) as i32,
-                (scissor.x_max - scissor.x_min) as i32,
-                (scissor.y_max - scissor.y_min) as i32,
-            );
-
-            // Use Gouraud shader
-            self.gl.use_program(Some(self.gouraud_program));
-            self.current_program = ShaderProgram::Gouraud;
-
-            // Convert to NDC and RGBA
-            let (nx0, ny0) = self.screen_to_ndc(x0, y0);
-            let (nx1, ny1) = self.screen_to_ndc(x1, y1);
-            let (nx2, ny2) = self.screen_to_ndc(x2, y2);
-            let rgba0 = Self::argb_to_rgba(c0);
-            let rgba1 = Self::argb_to_rgba(c1);
-            let rgba2 = Self::argb_to_rgba(c2);
-
-            #[rustfmt::skip]
-            let vertices: [f32; 18] = [
-                nx0, ny0, rgba0[0], rgba0[1], rgba0[2], rgba0[3],
-                nx1, ny1, rgba1[0], rgba1[1], rgba1[2], rgba1[3],
-                nx2, ny2, rgba2[0], rgba2[1], rgba2[2], rgba2[3],
-            ];
-
-            self.gl.bind_vertex_array(Some(self.vao));
-            self.gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.vbo));
-            self.gl.buffer_data_u8_slice(
-                glow::ARRAY_BUFFER,
-                bytemuck::cast_slice(&vertices),
-                glow::STREAM_DRAW,
-            );
-
-            let stride = 6 * std::mem::size_of::<f32>() as i32;
-
-            // Position attribute
-            self.gl
-                .vertex_attrib_pointer_f32(0, 2, glow::FLOAT, false, stride, 0);
-            self.gl.enable_vertex_attrib_array(0);
-
-            // Color attribute
-            self.gl.vertex_attrib_pointer_f32(
-                1,
-                4,
-                glow::FLOAT,
-                false,
-                stride,
-                2 * std::mem::size_of::<f32>() as i32,
-            );
-            self.gl.enable_vertex_attrib_array(1);
-
-            self.gl.draw_arrays(glow::TRIANGLES, 0, 3);
-
-            self.gl.disable(glow::SCISSOR_TEST);
-            self.read_pixels();
-        }
+        let state = self.batch_state(scissor, false, false, true);
+        let vertices = [
+            self.vertex(x0, y0, 0, c0, 0.0, 0.0),
+            self.vertex(x1, y1, 0, c1, 0.0, 0.0),
+            self.vertex(x2, y2, 0, c2, 0.0, 0.0),
+        ];
+        self.queue_triangle(state, vertices);
     }
 
     fn draw_triangle_shaded_zbuffer(
@@ -718,89 +879,13 @@ impl RdpRenderer for OpenGLRdpRenderer {
         c2: u32,
         scissor: &ScissorBox,
     ) {
-        unsafe {
-            self.gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.fbo));
-
-            // Enable depth testing
-            if self.zbuffer_enabled {
-                self.gl.enable(glow::DEPTH_TEST);
-                self.gl.depth_func(glow::LESS);
-            }
-
-            // Enable scissor test
-            self.gl.enable(glow::SCISSOR_TEST);
-            self.gl.scissor(
-                scissor.x_min as i32,
-                (self.height - scissor.y_max) as i32,
-                (scissor.x_max - scissor.x_min) as i32,
-                (scissor.y_max - scissor.y_min) as i32,
-            );
-
-            // Use Gouraud shader
-            self.gl.use_program(Some(self.gouraud_program));
-            self.current_program = ShaderProgram::Gouraud;
-
-            // Convert to NDC, depth, and RGBA
-            let (nx0, ny0) = self.screen_to_ndc(x0, y0);
-            let (nx1, ny1) = self.screen_to_ndc(x1, y1);
-            let (nx2, ny2) = self.screen_to_ndc(x2, y2);
-            let d0 = Self::zbuffer_to_depth(z0);
-            let d1 = Self::zbuffer_to_depth(z1);
-            let d2 = Self::zbuffer_to_depth(z2);
-            let rgba0 = Self::argb_to_rgba(c0);
-            let rgba1 = Self::argb_to_rgba(c1);
-            let rgba2 = Self::argb_to_rgba(c2);
-
-            #[rustfmt::skip]
-            let vertices: [f32; 21] = [
-                nx0, ny0, rgba0[0], rgba0[1], rgba0[2], rgba0[3], d0,
-                nx1, ny1, rgba1[0], rgba1[1], rgba1[2], rgba1[3], d1,
-                nx2, ny2, rgba2[0], rgba2[1], rgba2[2], rgba2[3], d2,
-            ];
-
-            self.gl.bind_vertex_array(Some(self.vao));
-            self.gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.vbo));
-            self.gl.buffer_data_u8_slice(
-                glow::ARRAY_BUFFER,
-                bytemuck::cast_slice(&vertices),
-                glow::STREAM_DRAW,
-            );
-
-            let stride = 7 * std::mem::size_of::<f32>() as i32;
-
-            // Position attribute
-            self.gl
-                .vertex_attrib_pointer_f32(0, 2, glow::FLOAT, false, stride, 0);
-            self.gl.enable_vertex_attrib_array(0);
-
-            // Color attribute
-            self.gl.vertex_attrib_pointer_f32(
-                1,
-                4,
-                glow::FLOAT,
-                false,
-                stride,
-                2 * std::mem::size_of::<f32>() as i32,
-            );
-            self.gl.enable_vertex_attrib_array(1);
-
-            // Depth attribute
-            self.gl.vertex_attrib_pointer_f32(
-                2,
-                1,
-                glow::FLOAT,
-                false,
-                stride,
-                6 * std::mem::size_of::<f32>() as i32,
-            );
-            self.gl.enable_vertex_attrib_array(2);
-
-            self.gl.draw_arrays(glow::TRIANGLES, 0, 3);
-
-            self.gl.disable(glow::DEPTH_TEST);
-            self.gl.disable(glow::SCISSOR_TEST);
-            self.read_pixels();
-        }
+        let state = self.batch_state(scissor, true, false, true);
+        let vertices = [
+            self.vertex(x0, y0, z0, c0, 0.0, 0.0),
+            self.vertex(x1, y1, z1, c1, 0.0, 0.0),
+            self.vertex(x2, y2, z2, c2, 0.0, 0.0),
+        ];
+        self.queue_triangle(state, vertices);
     }
 
     fn draw_triangle_textured(
@@ -820,94 +905,16 @@ impl RdpRenderer for OpenGLRdpRenderer {
         texture: &dyn Fn(f32, f32) -> u32,
         scissor: &ScissorBox,
     ) {
-        unsafe {
-            self.gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.fbo));
-
-            // Enable scissor test
-            self.gl.enable(glow::SCISSOR_TEST);
-            self.gl.scissor(
-                scissor.x_min as i32,
-                (self.height - scissor.y_max) as i32,
-                (scissor.x_max - scissor.x_min) as i32,
-                (scissor.y_max - scissor.y_min) as i32,
-            );
-
-            // Determine texture size based on texture coordinates
-            // Use max coordinate as a heuristic for texture size
-            let max_s = s0.max(s1).max(s2).ceil() as u32;
-            let max_t = t0.max(t1).max(t2).ceil() as u32;
-            let tex_width = max_s.max(1);
-            let tex_height = max_t.max(1);
-
-            // Upload texture data
-            self.upload_dynamic_texture(tex_width, tex_height, texture);
-
-            // Use textured shader
-            self.gl.use_program(Some(self.textured_program));
-            self.current_program = ShaderProgram::Textured;
-
-            // Bind dynamic texture
-            self.gl.active_texture(glow::TEXTURE0);
-            self.gl
-                .bind_texture(glow::TEXTURE_2D, Some(self.dynamic_texture));
-            let u_texture = self
-                .gl
-                .get_uniform_location(self.textured_program, "uTexture");
-            if let Some(loc) = u_texture {
-                self.gl.uniform_1_i32(Some(&loc), 0);
-            }
-
-            // Convert to NDC and normalize texture coordinates
-            let (nx0, ny0) = self.screen_to_ndc(x0, y0);
-            let (nx1, ny1) = self.screen_to_ndc(x1, y1);
-            let (nx2, ny2) = self.screen_to_ndc(x2, y2);
-
-            // Normalize texture coordinates to [0, 1] range
-            let ns0 = s0 / tex_width as f32;
-            let nt0 = t0 / tex_height as f32;
-            let ns1 = s1 / tex_width as f32;
-            let nt1 = t1 / tex_height as f32;
-            let ns2 = s2 / tex_width as f32;
-            let nt2 = t2 / tex_height as f32;
-
-            #[rustfmt::skip]
-            let vertices: [f32; 12] = [
-                nx0, ny0, ns0, nt0,
-                nx1, ny1, ns1, nt1,
-                nx2, ny2, ns2, nt2,
-            ];
-
-            self.gl.bind_vertex_array(Some(self.vao));
-            self.gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.vbo));
-            self.gl.buffer_data_u8_slice(
-                glow::ARRAY_BUFFER,
-                bytemuck::cast_slice(&vertices),
-                glow::STREAM_DRAW,
-            );
-
-            let stride = 4 * std::mem::size_of::<f32>() as i32;
-
-            // Position attribute
-            self.gl
-                .vertex_attrib_pointer_f32(0, 2, glow::FLOAT, false, stride, 0);
-            self.gl.enable_vertex_attrib_array(0);
-
-            // Texture coordinate attribute
-            self.gl.vertex_attrib_pointer_f32(
-                1,
-                2,
-                glow::FLOAT,
-                false,
-                stride,
-                2 * std::mem::size_of::<f32>() as i32,
-            );
-            self.gl.enable_vertex_attrib_array(1);
-
-            self.gl.draw_arrays(glow::TRIANGLES, 0, 3);
-
-            self.gl.disable(glow::SCISSOR_TEST);
-            self.read_pixels();
-        }
+        self.queue_textured_triangle(
+            [
+                (x0, y0, 0, s0, t0),
+                (x1, y1, 0, s1, t1),
+                (x2, y2, 0, s2, t2),
+            ],
+            false,
+            texture,
+            scissor,
+        );
     }
 
     fn draw_triangle_textured_zbuffer(
@@ -930,120 +937,26 @@ impl RdpRenderer for OpenGLRdpRenderer {
         texture: &dyn Fn(f32, f32) -> u32,
         scissor: &ScissorBox,
     ) {
-        unsafe {
-            self.gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.fbo));
-
-            // Enable depth testing
-            if self.zbuffer_enabled {
-                self.gl.enable(glow::DEPTH_TEST);
-                self.gl.depth_func(glow::LESS);
-            }
-
-            // Enable scissor test
-            self.gl.enable(glow::SCISSOR_TEST);
-            self.gl.scissor(
-                scissor.x_min as i32,
-                (self.height - scissor.y_max) as i32,
-                (scissor.x_max - scissor.x_min) as i32,
-                (scissor.y_max - scissor.y_min) as i32,
-            );
-
-            // Determine texture size based on texture coordinates
-            let max_s = s0.max(s1).max(s2).ceil() as u32;
-            let max_t = t0.max(t1).max(t2).ceil() as u32;
-            let tex_width = max_s.max(1);
-            let tex_height = max_t.max(1);
-
-            // Upload texture data
-            self.upload_dynamic_texture(tex_width, tex_height, texture);
-
-            // Use textured shader
-            self.gl.use_program(Some(self.textured_program));
-            self.current_program = ShaderProgram::Textured;
-
-            // Bind dynamic texture
-            self.gl.active_texture(glow::TEXTURE0);
-            self.gl
-                .bind_texture(glow::TEXTURE_2D, Some(self.dynamic_texture));
-            let u_texture = self
-                .gl
-                .get_uniform_location(self.textured_program, "uTexture");
-            if let Some(loc) = u_texture {
-                self.gl.uniform_1_i32(Some(&loc), 0);
-            }
-
-            // Convert to NDC, depth, and normalize texture coordinates
-            let (nx0, ny0) = self.screen_to_ndc(x0, y0);
-            let (nx1, ny1) = self.screen_to_ndc(x1, y1);
-            let (nx2, ny2) = self.screen_to_ndc(x2, y2);
-            let d0 = Self::zbuffer_to_depth(z0);
-            let d1 = Self::zbuffer_to_depth(z1);
-            let d2 = Self::zbuffer_to_depth(z2);
-
-            // Normalize texture coordinates to [0, 1] range
-            let ns0 = s0 / tex_width as f32;
-            let nt0 = t0 / tex_height as f32;
-            let ns1 = s1 / tex_width as f32;
-            let nt1 = t1 / tex_height as f32;
-            let ns2 = s2 / tex_width as f32;
-            let nt2 = t2 / tex_height as f32;
-
-            #[rustfmt::skip]
-            let vertices: [f32; 15] = [
-                nx0, ny0, ns0, nt0, d0,
-                nx1, ny1, ns1, nt1, d1,
-                nx2, ny2, ns2, nt2, d2,
-            ];
-
-            self.gl.bind_vertex_array(Some(self.vao));
-            self.gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.vbo));
-            self.gl.buffer_data_u8_slice(
-                glow::ARRAY_BUFFER,
-                bytemuck::cast_slice(&vertices),
-                glow::STREAM_DRAW,
-            );
-
-            let stride = 5 * std::mem::size_of::<f32>() as i32;
-
-            // Position attribute
-            self.gl
-                .vertex_attrib_pointer_f32(0, 2, glow::FLOAT, false, stride, 0);
-            self.gl.enable_vertex_attrib_array(0);
-
-            // Texture coordinate attribute
-            self.gl.vertex_attrib_pointer_f32(
-                1,
-                2,
-                glow::FLOAT,
-                false,
-                stride,
-                2 * std::mem::size_of::<f32>() as i32,
-            );
-            self.gl.enable_vertex_attrib_array(1);
-
-            // Depth attribute
-            self.gl.vertex_attrib_pointer_f32(
-                2,
-                1,
-                glow::FLOAT,
-                false,
-                stride,
-                4 * std::mem::size_of::<f32>() as i32,
-            );
-            self.gl.enable_vertex_attrib_array(2);
-
-            self.gl.draw_arrays(glow::TRIANGLES, 0, 3);
-
-            self.gl.disable(glow::DEPTH_TEST);
-            self.gl.disable(glow::SCISSOR_TEST);
-            self.read_pixels();
-        }
+        self.queue_textured_triangle(
+            [
+                (x0, y0, z0, s0, t0),
+                (x1, y1, z1, s1, t1),
+                (x2, y2, z2, s2, t2),
+            ],
+            true,
+            texture,
+            scissor,
+        );
     }
 
     fn clear_zbuffer(&mut self) {
         unsafe {
+            // Queued depth-tested triangles must see the old depth values
+            self.submit_batch();
             self.gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.fbo));
+            self.gl.clear_depth_f32(1.0);
             self.gl.clear(glow::DEPTH_BUFFER_BIT);
+            self.gl.bind_framebuffer(glow::FRAMEBUFFER, None);
         }
     }
 
@@ -1058,6 +971,8 @@ impl RdpRenderer for OpenGLRdpRenderer {
     fn reset(&mut self) {
         self.clear(0xFF000000);
         self.clear_zbuffer();
+        self.zbuffer_enabled = false;
+        self.combiner = CombinerState::default();
     }
 
     fn name(&self) -> &str {
@@ -1067,6 +982,24 @@ impl RdpRenderer for OpenGLRdpRenderer {
     fn is_hardware_accelerated(&self) -> bool {
         true
     }
+
+    fn set_combiner(&mut self, combiner: &CombinerState) {
+        if *combiner != self.combiner {
+            // Combiner uniforms apply to the whole batch
+            unsafe { self.submit_batch() };
+            self.combiner = *combiner;
+        }
+    }
+
+    fn flush(&mut self) -> Result<(), String> {
+        unsafe {
+            self.sync_framebuffer();
+            match self.gl.get_error() {
+                glow::NO_ERROR => Ok(()),
+                error => Err(format!("OpenGL error 0x{:04X}", error)),
+            }
+        }
+    }
 }
 
 /// Helper function to compile a shader
@@ -1092,9 +1025,9 @@ unsafe fn compile_shader(
     Ok(shader)
 }
 
-/// Create flat shading program
+/// Create the combiner program used for every batch
 #[cfg(feature = "opengl")]
-fn create_flat_program(gl: &SendContext) -> Result<glow::Program, String> {
+fn create_combiner_program(gl: &SendContext) -> Result<glow::Program, String> {
     unsafe {
         let vertex_shader =
             compile_shader(gl, glow::VERTEX_SHADER, include_str!("shaders/vertex.glsl"))?;
@@ -1102,78 +1035,7 @@ fn create_flat_program(gl: &SendContext) -> Result<glow::Program, String> {
         let fragment_shader = compile_shader(
             gl,
             glow::FRAGMENT_SHADER,
-            include_str!("shaders/fragment_flat.glsl"),
-        )?;
-
-        let program = gl
-            .create_program()
-            .map_err(|e| format!("Failed to create program: {}", e))?;
-
-        gl.attach_shader(program, vertex_shader);
-        gl.attach_shader(program, fragment_shader);
-        gl.link_program(program);
-
-        if !gl.get_program_link_status(program) {
-            let log = gl.get_program_info_log(program);
-            gl.delete_program(program);
-            return Err(format!("Program linking failed: {}", log));
-        }
-
-        gl.delete_shader(vertex_shader);
-        gl.delete_shader(fragment_shader);
-
-        Ok(program)
-    }
-}
-
-/// Create Gouraud shading program
-#[cfg(feature = "opengl")]
-fn create_gouraud_program(gl: &SendContext) -> Result<glow::Program, String> {
-    unsafe {
-        let vertex_shader =
-            compile_shader(gl, glow::VERTEX_SHADER, include_str!("shaders/vertex.glsl"))?;
-
-        let fragment_shader = compile_shader(
-            gl,
-            glow::FRAGMENT_SHADER,
-            include_str!("shaders/fragment_gouraud.glsl"),
-        )?;
-
-        let program = gl
-            .create_program()
-            .map_err(|e| format!("Failed to create program: {}", e))?;
-
-        gl.attach_shader(program, vertex_shader);
-        gl.attach_shader(program, fragment_shader);
-        gl.link_program(program);
-
-        if !gl.get_program_link_status(program) {
-            let log = gl.get_program_info_log(program);
-            gl.delete_program(program);
-            return Err(format!("Program linking failed: {}", log));
-        }
-
-        gl.delete_shader(vertex_shader);
-        gl.delete_shader(fragment_shader);
-
-        Ok(program)
-    }
-}
-
-/// Create textured shading program
-#[cfg(feature = "opengl")]
-fn create_textured_program(gl: &SendContext) -> Result<glow::Program, String> {
-    unsafe {
-        let vertex_shader = compile_shader(
-            gl,
-            glow::VERTEX_SHADER,
-            include_str!("shaders/vertex_textured.glsl"),
-        )?;
-
-        let fragment_shader = compile_shader(
-            gl,
-            glow::FRAGMENT_SHADER,
-            include_str!("shaders/fragment_textured.glsl"),
+            include_str!("shaders/fragment_combiner.glsl"),
         )?;
 
         let program = gl
@@ -1205,9 +1067,7 @@ impl Drop for OpenGLRdpRenderer {
             self.gl.delete_framebuffer(self.fbo);
             self.gl.delete_texture(self.color_texture);
             self.gl.delete_renderbuffer(self.depth_renderbuffer);
-            self.gl.delete_program(self.flat_program);
-            self.gl.delete_program(self.gouraud_program);
-            self.gl.delete_program(self.textured_program);
+            self.gl.delete_program(self.program);
             self.gl.delete_vertex_array(self.vao);
             self.gl.delete_buffer(self.vbo);
             self.gl.delete_texture(self.dynamic_texture);
@@ -1218,18 +1078,103 @@ impl Drop for OpenGLRdpRenderer {
 #[cfg(test)]
 #[cfg(feature = "opengl")]
 mod tests {
-    #[allow(unused_imports)] // Test module for documentation purposes
     use super::*;
 
+    // Creating the renderer needs a GL context from the frontend, so these
+    // tests cover the CPU-side conversions the batches are built from.
+
+    #[test]
+    fn test_gl_scissor_rect_flips_y() {
+        let scissor = ScissorBox {
+            x_min: 10,
+            y_min: 20,
+            x_max: 110,
+            y_max: 60,
+        };
+        assert_eq!(gl_scissor_rect(&scissor, 240), [10, 180, 100, 40]);
+
+        // Boxes past the bottom edge are clamped instead of underflowing
+        let scissor = ScissorBox {
+            x_min: 0,
+            y_min: 0,
+            x_max: 320,
+            y_max: 480,
+        };
+        assert_eq!(gl_scissor_rect(&scissor, 240), [0, 0, 320, 240]);
+    }
+
     #[test]
-    fn test_opengl_renderer_creation_requires_context() {
-        // This test documents that the OpenGL renderer now requires a GL context
-        // We can't easily test actual OpenGL functionality in unit tests without
-        // a headless GL context, so this is primarily documentation
-
-        // Note: In the real application, the GL context comes from SDL2 frontend
-        // The renderer is created like:
-        // let gl = glow::Context::from_loader_function(...);
-        // let renderer = OpenGLRdpRenderer::new(gl, 320, 240)?;
+    fn test_color_conversions_round_trip() {
+        let argb = 0x80FF4020;
+        assert_eq!(argb_to_rgba_bytes(argb), [0xFF, 0x40, 0x20, 0x80]);
+        assert_eq!(rgba_bytes_to_argb(&argb_to_rgba_bytes(argb)), argb);
+        assert_eq!(rgba8888_to_vec4(0xFF0000FF), [1.0, 0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn test_texture_size_clamped() {
+        assert_eq!(
+            texture_size([(0.0, 0.0), (31.5, 0.0), (0.0, 16.0)]),
+            (32, 16)
+        );
+        assert_eq!(texture_size([(-4.0, -4.0); 3]), (1, 1));
+        assert_eq!(
+            texture_size([(0.0, 0.0), (5000.0, 5000.0), (0.0, 0.0)]),
+            (MAX_TEXTURE_SIZE, MAX_TEXTURE_SIZE)
+        );
+    }
+
+    #[test]
+    fn test_combiner_passthrough() {
+        let state = CombinerState::default();
+        assert!(state.is_passthrough());
+        assert_eq!(state.color_inputs()[3], CombinerInput::Combined);
+    }
+
+    #[test]
+    fn test_combiner_decode_modulate() {
+        // G_CC_MODULATERGBA in both cycles: (TEXEL0 - 0) * SHADE + 0
+        let state = CombinerState {
+            combine_mode: 0x3C12_1824_FF33_FFFF,
+            ..Default::default()
+        };
+        // Command byte is ignored; B = 0xF and D = 7 both select zero
+        assert_eq!(
+            state.color_inputs(),
+            [
+                CombinerInput::Texel0,
+                CombinerInput::Zero,
+                CombinerInput::Shade,
+                CombinerInput::Zero
+            ]
+        );
+        assert_eq!(
+            state.alpha_inputs(),
+            [
+                CombinerInput::Texel0,
+                CombinerInput::Zero,
+                CombinerInput::Shade,
+                CombinerInput::Zero
+            ]
+        );
+    }
+
+    #[test]
+    fn test_combiner_decode_alpha_multipliers() {
+        // Color C = 10 (primitive alpha), alpha C = 0 (LOD fraction, unsupported)
+        let state = CombinerState {
+            combine_mode: (10u64 << 47) | (5u64 << 52) | (6u64 << 15),
+            ..Default::default()
+        };
+        assert_eq!(
+            state.color_inputs(),
+            [
+                CombinerInput::Environment,
+                CombinerInput::Combined,
+                CombinerInput::PrimitiveAlpha,
+                CombinerInput::One
+            ]
+        );
+        assert_eq!(state.alpha_inputs()[2], CombinerInput::Zero);
     }
 }
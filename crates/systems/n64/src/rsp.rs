@@ -85,8 +85,10 @@ const SP_STATUS_SIG6: u32 = 0x2000; // Signal 6
 #[allow(dead_code)]
 const SP_STATUS_SIG7: u32 = 0x4000; // Signal 7
 
+use super::dl_debug::DisplayListCapture;
 use super::rdp::Rdp;
 use super::rsp_hle::RspHle;
+use serde::{Deserialize, Serialize};
 
 /// RSP (Reality Signal Processor) state
 pub struct Rsp {
@@ -114,6 +116,24 @@ pub struct Rsp {
     hle: RspHle,
 }
 
+/// DMEM, IMEM and registers saved in save states
+///
+/// The HLE state (vertex buffer, matrices) is not included; the microcode
+/// type is detected again from IMEM on load.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RspState {
+    dmem: Vec<u8>,
+    imem: Vec<u8>,
+    sp_mem_addr: u32,
+    sp_dram_addr: u32,
+    sp_rd_len: u32,
+    sp_wr_len: u32,
+    sp_status: u32,
+    sp_dma_full: u32,
+    sp_dma_busy: u32,
+    sp_semaphore: u32,
+}
+
 impl Rsp {
     /// Create a new RSP
     pub fn new() -> Self {
@@ -150,6 +170,40 @@ impl Rsp {
         self.hle = RspHle::new();
     }
 
+    pub fn save_state(&self) -> RspState {
+        RspState {
+            dmem: self.dmem.to_vec(),
+            imem: self.imem.to_vec(),
+            sp_mem_addr: self.sp_mem_addr,
+            sp_dram_addr: self.sp_dram_addr,
+            sp_rd_len: self.sp_rd_len,
+            sp_wr_len: self.sp_wr_len,
+            sp_status: self.sp_status,
+            sp_dma_full: self.sp_dma_full,
+            sp_dma_busy: self.sp_dma_busy,
+            sp_semaphore: self.sp_semaphore,
+        }
+    }
+
+    /// Restore state saved by [`Rsp::save_state`]
+    pub fn load_state(&mut self, state: &RspState) -> Result<(), String> {
+        if state.dmem.len() != self.dmem.len() || state.imem.len() != self.imem.len() {
+            return Err("RSP memory size mismatch".to_string());
+        }
+        self.dmem.copy_from_slice(&state.dmem);
+        self.imem.copy_from_slice(&state.imem);
+        self.sp_mem_addr = state.sp_mem_addr;
+        self.sp_dram_addr = state.sp_dram_addr;
+        self.sp_rd_len = state.sp_rd_len;
+        self.sp_wr_len = state.sp_wr_len;
+        self.sp_status = state.sp_status;
+        self.sp_dma_full = state.sp_dma_full;
+        self.sp_dma_busy = state.sp_dma_busy;
+        self.sp_semaphore = state.sp_semaphore;
+        self.hle.detect_microcode(&self.imem);
+        Ok(())
+    }
+
     /// Read from DMEM
     pub fn read_dmem(&self, offset: u32) -> u8 {
         let addr = (offset & 0xFFF) as usize;
@@ -341,13 +395,39 @@ impl Rsp {
             format!("RSP: Task complete ({} cycles)", cycles)
         });
 
+        (cycles, self.complete_task())
+    }
+
+    /// True if the RSP is halted (no task to run)
+    pub fn is_halted(&self) -> bool {
+        self.sp_status & SP_STATUS_HALT != 0
+    }
+
+    /// Copy of DMEM, holding the task the RSP was started with
+    pub fn dmem(&self) -> &[u8; 4096] {
+        &self.dmem
+    }
+
+    /// Halt the RSP after a task finished
+    /// Returns true if an SP interrupt should be triggered
+    pub fn complete_task(&mut self) -> bool {
         // Set broke flag and halt after task completion
         self.sp_status |= SP_STATUS_BROKE | SP_STATUS_HALT;
 
         // Check if interrupt on break is enabled
-        let should_interrupt = (self.sp_status & SP_STATUS_INTR_BREAK) != 0;
+        (self.sp_status & SP_STATUS_INTR_BREAK) != 0
+    }
 
-        (cycles, should_interrupt)
+    /// Hand the HLE state to the RCP thread, keeping the detected microcode
+    pub fn take_hle(&mut self) -> RspHle {
+        let mut hle = RspHle::new();
+        hle.set_microcode(self.hle.microcode());
+        std::mem::replace(&mut self.hle, hle)
+    }
+
+    /// Take back the HLE state from the RCP thread
+    pub fn restore_hle(&mut self, hle: RspHle) {
+        self.hle = hle;
     }
 
     /// Get current microcode type (for debugging/monitoring)
@@ -365,6 +445,21 @@ impl Rsp {
     pub fn vertex_count(&self) -> usize {
         self.hle.vertex_count()
     }
+
+    /// Record the display list of the next graphics task
+    pub fn request_display_list_capture(&mut self) {
+        self.hle.request_capture();
+    }
+
+    /// Take the most recently completed display list capture
+    pub fn take_display_list_capture(&mut self) -> Option<DisplayListCapture> {
+        self.hle.take_capture()
+    }
+
+    /// Take whether a display list reached G_RDPFULLSYNC since the last call
+    pub fn take_full_sync(&mut self) -> bool {
+        self.hle.take_full_sync()
+    }
 }
 
 impl Default for Rsp {
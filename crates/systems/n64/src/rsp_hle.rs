@@ -13,27 +13,43 @@
 //! # Architecture
 //!
 //! When the CPU loads microcode into RSP IMEM, we analyze the code signature
-//! to determine which microcode it is. Then when the RSP is triggered to run,
-//! we execute the high-level behavior:
+//! to determine which microcode it is. Tasks started through libultra also
+//! leave an OSTask at the end of DMEM, whose microcode data holds a version
+//! string naming the microcode. When the RSP is triggered to run, we execute
+//! the high-level behavior:
 //!
-//! 1. Parse display list commands from RDRAM
-//! 2. Process vertex data, apply transforms
-//! 3. Generate RDP display lists for triangle rendering
-//! 4. Handle lighting, texture coordinates, etc.
+//! 1. Parse display list commands from RDRAM, resolving segmented addresses
+//! 2. Process vertex data, apply transforms and lighting
+//! 3. Draw the triangles and rectangles through the RDP
+//! 4. Raise the DP interrupt when the display list reaches G_RDPFULLSYNC
 //!
-//! # F3DEX Display List Commands
+//! # Display List Commands
 //!
-//! Common F3DEX commands (command ID in upper byte):
-//! - 0x01: G_VTX - Load vertices into vertex buffer
-//! - 0x04: G_TRI1 - Draw single triangle
-//! - 0x05: G_TRI2 - Draw two triangles  
-//! - 0x06: G_QUAD - Draw quadrilateral (two triangles)
-//! - 0xDA: G_MTX - Load transformation matrix
-//! - 0xD9: G_GEOMETRYMODE - Set rendering mode flags
-//! - 0xDF: G_ENDDL - End of display list
-//! - 0xBF: G_RDPHALF_1 - RDP command data (part 1)
-//! - 0xE0-0xFF: Various RDP passthrough commands
+//! F3DEX (GBI 1.x) and F3DEX2 assign different IDs (upper byte) to the
+//! same commands:
+//!
+//! | Command              | F3DEX | F3DEX2 |
+//! |----------------------|-------|--------|
+//! | G_VTX                | 0x04  | 0x01   |
+//! | G_TRI1               | 0xBF  | 0x05   |
+//! | G_TRI2               | 0xB1  | 0x06   |
+//! | G_QUAD               | 0xB5  | 0x07   |
+//! | G_MTX                | 0x01  | 0xDA   |
+//! | G_POPMTX             | 0xBD  | 0xD8   |
+//! | G_MOVEMEM            | 0x03  | 0xDC   |
+//! | G_MOVEWORD           | 0xBC  | 0xDB   |
+//! | G_DL                 | 0x06  | 0xDE   |
+//! | G_ENDDL              | 0xB8  | 0xDF   |
+//! | G_BRANCH_Z           | 0xB0  | 0x04   |
+//! | G_CULLDL             | 0xBE  | 0x03   |
+//! | G_TEXTURE            | 0xBB  | 0xD7   |
+//! | G_SET/CLEARGEOMETRYMODE | 0xB7/0xB6 | 0xD9 |
+//! | G_SETOTHERMODE_H/L   | 0xBA/0xB9 | 0xE3/0xE2 |
+//! | G_RDPHALF_1/2        | 0xB4/0xB3 | 0xE1/0xF1 |
+//!
+//! 0xE4-0xFF are RDP commands in both, passed through to the RDP.
 
+use super::dl_debug::{DisplayListCapture, DlCommand};
 use super::rdp::Rdp;
 use emu_core::logging::{log, LogCategory, LogLevel};
 
@@ -52,27 +68,51 @@ pub enum MicrocodeType {
 }
 
 /// Geometry mode flags
-#[allow(dead_code)] // Reserved for future use when geometry modes are fully implemented
 const G_ZBUFFER: u32 = 0x00000001; // Enable Z-buffer
-#[allow(dead_code)]
-const G_TEXTURE_ENABLE: u32 = 0x00000002; // Enable texture mapping (custom flag for demo)
-#[allow(dead_code)]
-const G_SHADE: u32 = 0x00000004; // Enable shading (Gouraud)
-#[allow(dead_code)]
-const G_CULL_FRONT: u32 = 0x00000200; // Cull front-facing triangles
-#[allow(dead_code)]
-const G_CULL_BACK: u32 = 0x00000400; // Cull back-facing triangles
+const G_LIGHTING: u32 = 0x00020000; // Vertex colors are normals to light
+const F3DEX_CULL_FRONT: u32 = 0x00001000; // Cull front-facing triangles (F3DEX)
+const F3DEX_CULL_BACK: u32 = 0x00002000; // Cull back-facing triangles (F3DEX)
+const F3DEX2_CULL_FRONT: u32 = 0x00000200; // Cull front-facing triangles (F3DEX2)
+const F3DEX2_CULL_BACK: u32 = 0x00000400; // Cull back-facing triangles (F3DEX2)
+
+/// G_MTX parameters
+const G_MTX_PUSH: u32 = 0x01;
+const G_MTX_LOAD: u32 = 0x02;
+const G_MTX_PROJECTION: u32 = 0x04;
+
+/// G_MOVEWORD indices
+const G_MW_NUMLIGHT: u32 = 0x02;
+const G_MW_SEGMENT: u32 = 0x06;
+
+/// OSTask location in DMEM and task types
+const OS_TASK_OFFSET: usize = 0xFC0;
+const M_GFXTASK: u32 = 1;
+const M_AUDTASK: u32 = 2;
+
+/// Commands run per display list before it is assumed to loop forever
+const MAX_DL_COMMANDS: usize = 0x10000;
+
+/// Display list nesting limit (the microcodes' call stack holds 18)
+const MAX_DL_DEPTH: u8 = 18;
+
+/// How parsing continues after a display list command
+enum DlFlow {
+    /// The following command
+    Next,
+    /// A (segmented) address, ending the current list
+    Branch(u32),
+    /// Return from the display list
+    End,
+}
 
 /// Vertex structure for graphics microcode
 #[derive(Debug, Clone, Copy)]
 pub struct Vertex {
     /// Position (x, y, z) in object space
     pub pos: [i16; 3],
-    /// Texture coordinates (s, t) in 16.16 fixed point
-    #[allow(dead_code)] // Reserved for future texture mapping
+    /// Texture coordinates (s, t) in S10.5 fixed point
     pub tex: [i16; 2],
-    /// Color (RGBA) 0-255 per channel
-    #[allow(dead_code)] // Reserved for future vertex color support
+    /// Color (RGBA) 0-255 per channel, or a normal when lighting is on
     pub color: [u8; 4],
 }
 
@@ -124,10 +164,30 @@ pub struct RspHle {
 
     /// Temporary storage for G_RDPHALF_1 data
     /// Used for 2-word RDP commands split across display list entries
-    #[allow(dead_code)] // Reserved for future use with 2-word RDP commands
     rdp_half: u32,
 
-    /// Light data (up to 8 lights)
+    /// Segment base addresses set by G_MOVEWORD
+    segments: [u32; 16],
+
+    /// Texturing enabled by G_TEXTURE
+    texture_on: bool,
+
+    /// Tile descriptor triangles are textured from
+    texture_tile: usize,
+
+    /// S and T scale applied to vertex texture coordinates
+    texture_scale: (f32, f32),
+
+    /// Other mode words (high, low) built by G_SETOTHERMODE_H/L
+    other_mode: (u32, u32),
+
+    /// TEXTURE_RECTANGLE waiting for its G_RDPHALF_1/2 coordinates
+    pending_texrect: Option<(u32, u32)>,
+
+    /// G_RDPFULLSYNC reached since the DP interrupt was last raised
+    full_sync: bool,
+
+    /// Light data (up to 7 directional lights, followed by the ambient light)
     /// Each light has 7 elements: [dx, dy, dz, r, g, b, type]
     /// - dx, dy, dz: direction vector (normalized, -1.0 to 1.0)
     /// - r, g, b: color components (0.0 to 1.0)
@@ -137,9 +197,17 @@ pub struct RspHle {
     /// Number of active lights
     num_lights: usize,
 
-    /// Ambient light color (RGB as floats 0.0-1.0)
-    #[allow(dead_code)] // Reserved for future lighting implementation
-    ambient_light: [f32; 3],
+    /// Nesting depth of the display list being parsed
+    dl_depth: u8,
+
+    /// Capture the next graphics task's display list
+    capture_requested: bool,
+
+    /// Display list capture being recorded
+    capture: Option<DisplayListCapture>,
+
+    /// Most recently completed display list capture
+    last_capture: Option<DisplayListCapture>,
 }
 
 impl RspHle {
@@ -159,9 +227,19 @@ impl RspHle {
             viewport: (0.0, 0.0, 320.0, 240.0, 160.0, 120.0),
             dl_stack: Vec::with_capacity(10),
             rdp_half: 0,
+            segments: [0; 16],
+            texture_on: false,
+            texture_tile: 0,
+            texture_scale: (1.0, 1.0),
+            other_mode: (0, 0),
+            pending_texrect: None,
+            full_sync: false,
             lights: [[0.0; 7]; 8],
             num_lights: 0,
-            ambient_light: [0.3, 0.3, 0.3], // Default ambient light
+            dl_depth: 0,
+            capture_requested: false,
+            capture: None,
+            last_capture: None,
         }
     }
 
@@ -214,7 +292,8 @@ impl RspHle {
     }
 
     /// Load a 4x4 matrix from RDRAM
-    /// N64 matrices are stored as 16 signed 16.16 fixed-point values (32 bits each)
+    /// N64 matrices are stored as 16 signed 16.16 fixed-point values: the 16
+    /// integer halves first, then the 16 fraction halves
     fn load_matrix_from_rdram(&self, rdram: &[u8], addr: u32) -> [f32; 16] {
         let mut matrix = [0.0f32; 16];
         let addr = Self::virt_to_phys(addr);
@@ -224,17 +303,11 @@ impl RspHle {
             return Self::identity_matrix();
         }
 
-        // Read 16 32-bit fixed-point values (16.16 format)
         for (i, elem) in matrix.iter_mut().enumerate() {
-            let offset = addr + i * 4;
-            // Read as signed 32-bit integer
-            let fixed_point = i32::from_be_bytes([
-                rdram[offset],
-                rdram[offset + 1],
-                rdram[offset + 2],
-                rdram[offset + 3],
-            ]);
+            let integer = u16::from_be_bytes([rdram[addr + i * 2], rdram[addr + i * 2 + 1]]);
+            let fraction = u16::from_be_bytes([rdram[addr + 32 + i * 2], rdram[addr + 33 + i * 2]]);
             // Convert from 16.16 fixed-point to float
+            let fixed_point = ((integer as u32) << 16 | fraction as u32) as i32;
             *elem = (fixed_point as f32) / 65536.0;
         }
 
@@ -320,17 +393,59 @@ impl RspHle {
         self.microcode
     }
 
+    /// Set the microcode type detected elsewhere (the RCP thread's copy)
+    pub fn set_microcode(&mut self, microcode: MicrocodeType) {
+        self.microcode = microcode;
+    }
+
     /// Get current vertex count in vertex buffer
     pub fn vertex_count(&self) -> usize {
         self.vertex_count
     }
 
+    /// Record the display list of the next graphics task
+    pub fn request_capture(&mut self) {
+        self.capture_requested = true;
+    }
+
+    /// Take the most recently completed display list capture
+    pub fn take_capture(&mut self) -> Option<DisplayListCapture> {
+        self.last_capture.take()
+    }
+
     /// Execute HLE task (called when RSP is triggered)
     /// Returns number of cycles consumed
-    pub fn execute_task(&mut self, dmem: &[u8; 4096], rdram: &[u8], _rdp: &mut Rdp) -> u32 {
+    pub fn execute_task(&mut self, dmem: &[u8; 4096], rdram: &[u8], rdp: &mut Rdp) -> u32 {
+        // Games start tasks through libultra, which leaves the OSTask at the
+        // end of DMEM. Its type and microcode data name the microcode more
+        // reliably than IMEM, which only holds the boot microcode.
+        match self.read_u32(dmem, OS_TASK_OFFSET) {
+            M_GFXTASK => {
+                let ucode_data = self.read_u32(dmem, OS_TASK_OFFSET + 0x18);
+                let ucode_data_size = self.read_u32(dmem, OS_TASK_OFFSET + 0x1C);
+                match Self::identify_gfx_microcode(rdram, ucode_data, ucode_data_size) {
+                    Some(microcode) => self.microcode = microcode,
+                    None if !matches!(
+                        self.microcode,
+                        MicrocodeType::F3DEX | MicrocodeType::F3DEX2
+                    ) =>
+                    {
+                        log(LogCategory::PPU, LogLevel::Warn, || {
+                            "RSP HLE: Graphics task without a microcode name, assuming F3DEX2"
+                                .to_string()
+                        });
+                        self.microcode = MicrocodeType::F3DEX2;
+                    }
+                    None => {}
+                }
+            }
+            M_AUDTASK => self.microcode = MicrocodeType::Audio,
+            _ => {}
+        }
+
         match self.microcode {
             MicrocodeType::F3DEX | MicrocodeType::F3DEX2 => {
-                self.execute_graphics_task(dmem, rdram, _rdp)
+                self.execute_graphics_task(dmem, rdram, rdp)
             }
             MicrocodeType::Audio => {
                 // Audio tasks not yet implemented
@@ -343,13 +458,91 @@ impl RspHle {
         }
     }
 
+    /// Identify a graphics microcode from the version string in its data
+    /// segment, e.g. "RSP Gfx ucode F3DEX       fifo 2.08  Yoshitaka Yasumoto"
+    ///
+    /// F3DEX 1.x and F3DEX2 (2.x) use different command encodings; the line
+    /// and Z variants (F3DLX, F3DLP, F3DZEX) share them. Returns `None` if
+    /// there is no version string, and `Unknown` for other microcodes (e.g.
+    /// S2DEX).
+    fn identify_gfx_microcode(
+        rdram: &[u8],
+        ucode_data: u32,
+        ucode_data_size: u32,
+    ) -> Option<MicrocodeType> {
+        const PREFIX: &[u8] = b"RSP Gfx ucode ";
+
+        let start = Self::virt_to_phys(ucode_data);
+        let len = (ucode_data_size as usize).clamp(PREFIX.len(), 0x1000);
+        let data = rdram.get(start..start.checked_add(len)?.min(rdram.len()))?;
+        let pos = data.windows(PREFIX.len()).position(|w| w == PREFIX)?;
+        let name: Vec<u8> = data[pos + PREFIX.len()..]
+            .iter()
+            .take(64)
+            .take_while(|&&b| b != 0 && b != b'\n')
+            .copied()
+            .collect();
+
+        let microcode = if !name.starts_with(b"F3D") {
+            MicrocodeType::Unknown
+        } else {
+            // The major version is the digit before the first "d.dd"
+            let major = name
+                .windows(4)
+                .find(|w| {
+                    w[0].is_ascii_digit()
+                        && w[1] == b'.'
+                        && w[2].is_ascii_digit()
+                        && w[3].is_ascii_digit()
+                })
+                .map(|w| w[0]);
+            if major == Some(b'2') {
+                MicrocodeType::F3DEX2
+            } else {
+                MicrocodeType::F3DEX
+            }
+        };
+
+        log(LogCategory::PPU, LogLevel::Info, || {
+            format!(
+                "RSP HLE: Task microcode \"{}\" -> {:?}",
+                String::from_utf8_lossy(&name).trim_end(),
+                microcode
+            )
+        });
+        Some(microcode)
+    }
+
+    /// Reset the state a microcode reloads from its data segment at the start
+    /// of every task
+    fn begin_os_task(&mut self) {
+        self.segments = [0; 16];
+        self.matrix_stack_ptr = 0;
+        self.projection_matrix = Self::identity_matrix();
+        self.modelview_matrix = Self::identity_matrix();
+        self.geometry_mode = 0;
+        self.num_lights = 0;
+        self.texture_on = false;
+        self.other_mode = (0, 0);
+        self.pending_texrect = None;
+    }
+
+    /// Take whether a G_RDPFULLSYNC ran since the last call (real hardware
+    /// raises the DP interrupt when the RDP reaches it)
+    pub fn take_full_sync(&mut self) -> bool {
+        std::mem::take(&mut self.full_sync)
+    }
+
     /// Execute graphics microcode task (F3DEX/F3DEX2)
     fn execute_graphics_task(&mut self, dmem: &[u8; 4096], rdram: &[u8], rdp: &mut Rdp) -> u32 {
-        // Try to read task structure from DMEM first
-        let mut data_ptr = self.read_u32(dmem, 0x30);
-        let mut data_size = self.read_u32(dmem, 0x34);
-        let mut output_buff = self.read_u32(dmem, 0x28);
-        let mut output_buff_size = self.read_u32(dmem, 0x2C);
+        // An OSTask at the end of DMEM comes from libultra; test ROMs leave
+        // the task structure at the start of DMEM or in RDRAM
+        let os_task = self.read_u32(dmem, OS_TASK_OFFSET) == M_GFXTASK;
+        let task = if os_task { OS_TASK_OFFSET } else { 0 };
+        let mut data_ptr = self.read_u32(dmem, task + 0x30);
+        let mut data_size = self.read_u32(dmem, task + 0x34);
+        let mut output_buff = self.read_u32(dmem, task + 0x28);
+        let mut output_buff_size = self.read_u32(dmem, task + 0x2C);
 
         log(LogCategory::PPU, LogLevel::Info, || {
             format!(
@@ -358,9 +551,11 @@ impl RspHle {
             )
         });
 
-        // If DMEM task structure is empty/invalid, try reading from common RDRAM locations
-        // Many test ROMs store task structure at 0x00200000 without DMA to DMEM
-        if data_ptr == 0 || data_ptr >= 0x00800000 {
+        if os_task {
+            self.begin_os_task();
+        } else if data_ptr == 0 || data_ptr >= 0x00800000 {
+            // If DMEM task structure is empty/invalid, try reading from common RDRAM locations
+            // Many test ROMs store task structure at 0x00200000 without DMA to DMEM
             // Try reading from RDRAM at 0x00200000 (common task structure location)
             const TASK_STRUCT_ADDR: usize = 0x00200000;
             if TASK_STRUCT_ADDR + 0x40 <= rdram.len() {
@@ -379,18 +574,30 @@ impl RspHle {
                     });
 
                     // If we found a valid display list but microcode is Unknown,
-                    // assume F3DEX (most common graphics microcode)
+                    // assume F3DEX2 (the encoding the test ROMs use)
                     if self.microcode == MicrocodeType::Unknown {
                         log(LogCategory::PPU, LogLevel::Info, || {
-                            "RSP HLE: Detected graphics task with Unknown microcode, assuming F3DEX"
+                            "RSP HLE: Detected graphics task with Unknown microcode, assuming F3DEX2"
                                 .to_string()
                         });
-                        self.microcode = MicrocodeType::F3DEX;
+                        self.microcode = MicrocodeType::F3DEX2;
                     }
                 }
             }
         }
 
+        if self.capture_requested {
+            self.capture_requested = false;
+            rdp.finish_frame();
+            let frame = rdp.get_frame();
+            self.capture = Some(DisplayListCapture::new(
+                frame.width,
+                frame.height,
+                &frame.pixels,
+                self.microcode,
+            ));
+        }
+
         // Parse F3DEX display list if data_ptr is provided
         if data_ptr > 0 && data_size > 0 {
             log(LogCategory::PPU, LogLevel::Info, || {
@@ -409,9 +616,20 @@ impl RspHle {
             });
         }
 
+        if let Some(capture) = self.capture.take() {
+            log(LogCategory::PPU, LogLevel::Info, || {
+                format!(
+                    "RSP HLE: Captured {} display list commands",
+                    capture.steps().len()
+                )
+            });
+            self.last_capture = Some(capture);
+        }
+
         // If there's an output buffer with data (pre-generated RDP commands),
-        // forward it directly to the RDP for processing
-        if output_buff > 0 && output_buff_size > 0 {
+        // forward it directly to the RDP for processing. An OSTask's output
+        // buffer is the microcode's RDP FIFO, which HLE bypasses.
+        if !os_task && output_buff > 0 && output_buff_size > 0 {
             log(LogCategory::PPU, LogLevel::Info, || {
                 format!(
                     "RSP HLE: Processing RDP output buffer at 0x{:08X}, size=0x{:X}",
@@ -426,7 +644,17 @@ impl RspHle {
         2000 // Average cycles for a graphics task
     }
 
-    /// Parse F3DEX display list and generate RDP commands
+    /// Resolve a segmented address (segment in bits 24-27) to a physical
+    /// RDRAM address, as the microcode does for every address it is given
+    ///
+    /// KSEG0/KSEG1 addresses land in segment 0, whose base is 0.
+    fn segment_address(&self, addr: u32) -> u32 {
+        let segment = ((addr >> 24) & 0x0F) as usize;
+        self.segments[segment].wrapping_add(addr & 0x00FFFFFF) & 0x00FFFFFF
+    }
+
+    /// Parse an F3DEX or F3DEX2 display list (by the detected microcode) and
+    /// draw it through the RDP
     fn parse_f3dex_display_list(
         &mut self,
         rdram: &[u8],
@@ -434,19 +662,30 @@ impl RspHle {
         _size: u32,
         rdp: &mut Rdp,
     ) {
-        // Convert virtual address to physical address
-        let mut addr = Self::virt_to_phys(start_addr);
-        let max_commands = 1000; // Safety limit to prevent infinite loops
+        let depth = self.dl_depth;
+        if depth >= MAX_DL_DEPTH {
+            log(LogCategory::PPU, LogLevel::Warn, || {
+                format!(
+                    "RSP HLE: Display list 0x{:08X} nested too deeply, skipped",
+                    start_addr
+                )
+            });
+            return;
+        }
+        self.dl_depth = depth + 1;
+
+        let mut addr = self.segment_address(start_addr) as usize;
         let mut commands_processed = 0;
 
         log(LogCategory::PPU, LogLevel::Info, || {
             format!(
-                "RSP HLE: Parsing F3DEX display list at virt:0x{:08X} phys:0x{:08X}",
-                start_addr, addr
+                "RSP HLE: Parsing {:?} display list at 0x{:08X} phys:0x{:08X}",
+                self.microcode, start_addr, addr
             )
         });
 
-        while addr + 7 < rdram.len() && commands_processed < max_commands {
+        // The command limit stops display lists that branch in a loop
+        while addr + 7 < rdram.len() && commands_processed < MAX_DL_COMMANDS {
             // Read 64-bit F3DEX command
             let word0 = u32::from_be_bytes([
                 rdram[addr],
@@ -461,562 +700,601 @@ impl RspHle {
                 rdram[addr + 7],
             ]);
 
-            let cmd_id = (word0 >> 24) & 0xFF;
+            if let Some(capture) = &mut self.capture {
+                capture.begin_command(DlCommand {
+                    addr: addr as u32,
+                    depth,
+                    word0,
+                    word1,
+                });
+            }
+
+            log(LogCategory::PPU, LogLevel::Debug, || {
+                format!(
+                    "{:?} cmd: 0x{:02X} w0:0x{:08X} w1:0x{:08X}",
+                    self.microcode,
+                    word0 >> 24,
+                    word0,
+                    word1
+                )
+            });
 
-            // Process F3DEX command
-            let should_continue = self.execute_f3dex_command(cmd_id, word0, word1, rdram, rdp);
+            let step = if self.microcode == MicrocodeType::F3DEX {
+                self.execute_f3dex_command(word0, word1, rdram, rdp)
+            } else {
+                self.execute_f3dex2_command(word0, word1, rdram, rdp)
+            };
 
-            if !should_continue {
-                break; // G_ENDDL or branch command
+            if let Some(capture) = &mut self.capture {
+                rdp.finish_frame();
+                capture.end_command(&rdp.get_frame().pixels);
             }
 
-            addr += 8;
+            match step {
+                DlFlow::Next => addr += 8,
+                DlFlow::Branch(target) => addr = self.segment_address(target) as usize,
+                DlFlow::End => break,
+            }
             commands_processed += 1;
         }
+
+        self.dl_depth = depth;
     }
 
-    /// Execute a single F3DEX display list command
-    /// Returns false if display list should terminate (G_ENDDL)
+    /// Execute a single F3DEX (GBI 1.x) display list command
     fn execute_f3dex_command(
         &mut self,
-        cmd_id: u32,
         word0: u32,
         word1: u32,
         rdram: &[u8],
         rdp: &mut Rdp,
-    ) -> bool {
-        // Log F3DEX commands for debugging
-        log(LogCategory::PPU, LogLevel::Debug, || {
-            format!(
-                "F3DEX cmd: 0x{:02X} w0:0x{:08X} w1:0x{:08X}",
-                cmd_id, word0, word1
-            )
-        });
-
-        match cmd_id {
-            // G_BRANCH_Z (0xB0) - Conditional branch based on Z-buffer
-            0xB0 => {
-                // word0: cmd_id | vtx (vertex index, bits 11-1) | zval (Z value for comparison)
-                // word1: RDRAM address to branch to if condition is met
-                let vertex_index = ((word0 >> 1) & 0x7FF) as usize / 2;
-                let branch_addr = word1;
-
-                // For now, implement simplified version that always branches
-                // Full implementation would:
-                // 1. Get Z value from specified vertex
-                // 2. Compare with RDP Z-buffer at vertex's screen position
-                // 3. Branch only if Z test passes (vertex is visible)
-                // Since we don't have per-vertex Z-buffer access yet, we conditionally branch
-                // based on whether the vertex is in bounds (simplified heuristic)
-
-                if vertex_index < self.vertex_count {
-                    // Vertex exists, parse the branch target display list
-                    // In a more complete implementation, we'd check Z-buffer value
-                    self.parse_f3dex_display_list(rdram, branch_addr, 10000, rdp);
+    ) -> DlFlow {
+        match word0 >> 24 {
+            // G_MTX (0x01): word0 = cmd | params << 16 | size
+            0x01 => {
+                self.load_matrix(rdram, word1, (word0 >> 16) & 0xFF);
+                DlFlow::Next
+            }
+            // G_MOVEMEM (0x03): word0 = cmd | index << 16 | size
+            0x03 => {
+                match (word0 >> 16) & 0xFF {
+                    0x80 => self.load_viewport(rdram, word1),
+                    // G_MV_L0..G_MV_L7
+                    index @ 0x86..=0x94 if index % 2 == 0 => {
+                        self.load_light(rdram, word1, ((index - 0x86) / 2) as usize)
+                    }
+                    index => log(LogCategory::Stubs, LogLevel::Debug, || {
+                        format!(
+                            "N64 RSP HLE: G_MOVEMEM stub - index=0x{:02X}, addr=0x{:08X}",
+                            index, word1
+                        )
+                    }),
                 }
-                true
+                DlFlow::Next
+            }
+            // G_VTX (0x04): word0 = cmd | v0 * 2 << 16 | n << 10 | size - 1
+            0x04 => {
+                let v0 = ((word0 >> 16) & 0xFF) as usize / 2;
+                let count = ((word0 >> 10) & 0x3F) as usize;
+                self.load_vertices(rdram, word1, v0, count);
+                DlFlow::Next
+            }
+            // G_DL (0x06): bits 16-23 are 0 to call, 1 to branch
+            0x06 => self.display_list(rdram, word1, (word0 >> 16) & 0xFF == 0, rdp),
+            // G_LOAD_UCODE (0xAF)
+            0xAF => {
+                self.log_load_ucode(word1);
+                DlFlow::Next
+            }
+            // G_BRANCH_Z (0xB0)
+            0xB0 => self.branch_z(),
+            // G_TRI2 (0xB1): both words hold three vertex indices
+            0xB1 => {
+                self.draw_packed_triangle(word0, rdp);
+                self.draw_packed_triangle(word1, rdp);
+                DlFlow::Next
+            }
+            // G_RDPHALF_2 (0xB3)
+            0xB3 => {
+                self.rdp_half_2(word1, rdp);
+                DlFlow::Next
+            }
+            // G_RDPHALF_1 (0xB4)
+            0xB4 => {
+                self.rdp_half = word1;
+                DlFlow::Next
+            }
+            // G_QUAD (0xB5): word1 = v0 | v1 | v2 | v3, drawn as (v0, v1, v2)
+            // and (v0, v2, v3)
+            0xB5 => {
+                let index = |shift: u32| ((word1 >> shift) & 0xFF) as usize / 2;
+                self.draw_triangle(index(24), index(16), index(8), rdp);
+                self.draw_triangle(index(24), index(8), index(0), rdp);
+                DlFlow::Next
             }
-            // G_CLEARGEOMETRYMODE (0xB6) - Clear geometry mode bits
+            // G_CLEARGEOMETRYMODE (0xB6)
             0xB6 => {
-                // word1: bits to clear
-                let clear_bits = word1;
-                self.geometry_mode &= !clear_bits;
-                log(LogCategory::PPU, LogLevel::Debug, || {
-                    format!(
-                        "RSP HLE: G_CLEARGEOMETRYMODE - cleared 0x{:08X}, mode now 0x{:08X}",
-                        clear_bits, self.geometry_mode
-                    )
-                });
-                true
+                self.geometry_mode &= !word1;
+                DlFlow::Next
             }
-            // G_SETGEOMETRYMODE (0xB7) - Set geometry mode bits
+            // G_SETGEOMETRYMODE (0xB7)
             0xB7 => {
-                // word1: bits to set
-                let set_bits = word1;
-                self.geometry_mode |= set_bits;
-                log(LogCategory::PPU, LogLevel::Debug, || {
-                    format!(
-                        "RSP HLE: G_SETGEOMETRYMODE - set 0x{:08X}, mode now 0x{:08X}",
-                        set_bits, self.geometry_mode
-                    )
-                });
-                true
+                self.geometry_mode |= word1;
+                DlFlow::Next
             }
-            // G_VTX (0x01) - Load vertices
-            0x01 => {
-                // word0: cmd_id | vn (vertex count, bits 20-11) | v0 (buffer index, bits 16-1)
-                // word1: vertex data address in RDRAM
-                let vertex_count = ((word0 >> 12) & 0xFF) as usize;
-                let buffer_index = ((word0 >> 1) & 0x7F) as usize;
-                let vertex_addr = word1;
-
-                // Load vertices from RDRAM into vertex buffer
-                for i in 0..vertex_count.min(32 - buffer_index) {
-                    let vaddr = vertex_addr + (i as u32 * 16);
-                    self.load_vertex(rdram, vaddr, buffer_index + i);
-                }
-                true
+            // G_ENDDL (0xB8)
+            0xB8 => DlFlow::End,
+            // G_SETOTHERMODE_L (0xB9) / G_SETOTHERMODE_H (0xBA):
+            // word0 = cmd | shift << 8 | length
+            0xB9 | 0xBA => {
+                let shift = (word0 >> 8) & 0xFF;
+                let length = word0 & 0xFF;
+                self.set_other_mode(word0 >> 24 == 0xBA, shift, length, word1, rdram, rdp);
+                DlFlow::Next
             }
-            // G_TRI1 (0x04) - Draw single triangle (alternate encoding)
-            0x04 => {
-                // Alternative encoding used by some games
-                // word0: cmd_id | v0_index | v1_index | v2_index
-                let v0 = ((word0 >> 16) & 0xFF) as usize / 2;
-                let v1 = ((word0 >> 8) & 0xFF) as usize / 2;
-                let v2 = (word0 & 0xFF) as usize / 2;
+            // G_TEXTURE (0xBB): word0 = cmd | level << 11 | tile << 8 | on
+            0xBB => {
+                self.set_texture(word0 & 0xFF != 0, (word0 >> 8) & 0x07, word1);
+                DlFlow::Next
+            }
+            // G_MOVEWORD (0xBC): word0 = cmd | offset << 8 | index
+            0xBC => {
+                self.move_word(word0 & 0xFF, (word0 >> 8) & 0xFFFF, word1);
+                DlFlow::Next
+            }
+            // G_POPMTX (0xBD): pops one modelview matrix
+            0xBD => {
+                self.pop_matrices(1);
+                DlFlow::Next
+            }
+            // G_CULLDL (0xBE): word0 = cmd | first * 2, word1 = last * 2
+            0xBE => {
+                self.cull_display_list((word0 & 0xFFFF) as usize / 2, (word1 & 0xFFFF) as usize / 2)
+            }
+            // G_TRI1 (0xBF): word1 = flag | v0 | v1 | v2
+            0xBF => {
+                self.draw_packed_triangle(word1, rdp);
+                DlFlow::Next
+            }
+            // RDP commands
+            0xE4..=0xFF => self.rdp_command(word0, word1, rdram, rdp),
+            // G_SPNOOP, G_RDPHALF_CONT and the rest - skip
+            _ => DlFlow::Next,
+        }
+    }
 
-                if v0 < self.vertex_count && v1 < self.vertex_count && v2 < self.vertex_count {
-                    self.draw_transformed_triangle(v0, v1, v2, rdp);
-                }
-                true
+    /// Execute a single F3DEX2 display list command
+    fn execute_f3dex2_command(
+        &mut self,
+        word0: u32,
+        word1: u32,
+        rdram: &[u8],
+        rdp: &mut Rdp,
+    ) -> DlFlow {
+        match word0 >> 24 {
+            // G_VTX (0x01): word0 = cmd | n << 12 | (v0 + n) << 1
+            0x01 => {
+                let count = ((word0 >> 12) & 0xFF) as usize;
+                let end = ((word0 >> 1) & 0x7F) as usize;
+                self.load_vertices(rdram, word1, end.saturating_sub(count), count);
+                DlFlow::Next
             }
-            // G_TRI1 (0x05) - Draw single triangle
+            // G_CULLDL (0x03): word0 = cmd | first * 2, word1 = last * 2
+            0x03 => {
+                self.cull_display_list((word0 & 0xFFFF) as usize / 2, (word1 & 0xFFFF) as usize / 2)
+            }
+            // G_BRANCH_Z (0x04)
+            0x04 => self.branch_z(),
+            // G_TRI1 (0x05): word0 = cmd | v0 | v1 | v2
             0x05 => {
-                // word0: cmd_id | v0_index (bits 16-23) | v1_index (bits 8-15) | v2_index (bits 0-7)
-                let v0 = ((word0 >> 16) & 0xFF) as usize / 2;
-                let v1 = ((word0 >> 8) & 0xFF) as usize / 2;
-                let v2 = (word0 & 0xFF) as usize / 2;
-
-                if v0 < self.vertex_count && v1 < self.vertex_count && v2 < self.vertex_count {
-                    self.draw_transformed_triangle(v0, v1, v2, rdp);
-                }
-                true
+                self.draw_packed_triangle(word0, rdp);
+                DlFlow::Next
             }
-            // G_TRI2 (0x06) - Draw two triangles
-            0x06 => {
-                // First triangle
-                let v0 = ((word0 >> 16) & 0xFF) as usize / 2;
-                let v1 = ((word0 >> 8) & 0xFF) as usize / 2;
-                let v2 = (word0 & 0xFF) as usize / 2;
-
-                if v0 < self.vertex_count && v1 < self.vertex_count && v2 < self.vertex_count {
-                    self.draw_transformed_triangle(v0, v1, v2, rdp);
-                }
-
-                // Second triangle
-                let v3 = ((word1 >> 16) & 0xFF) as usize / 2;
-                let v4 = ((word1 >> 8) & 0xFF) as usize / 2;
-                let v5 = (word1 & 0xFF) as usize / 2;
-
-                if v3 < self.vertex_count && v4 < self.vertex_count && v5 < self.vertex_count {
-                    self.draw_transformed_triangle(v3, v4, v5, rdp);
-                }
-                true
+            // G_TRI2 (0x06) and G_QUAD (0x07, sent as (v0, v1, v2) and
+            // (v0, v2, v3)): both words hold three vertex indices
+            0x06 | 0x07 => {
+                self.draw_packed_triangle(word0, rdp);
+                self.draw_packed_triangle(word1, rdp);
+                DlFlow::Next
             }
-            // G_QUAD (0x07) - Draw quadrilateral (two triangles)
-            0x07 => {
-                // Quad is drawn as two triangles sharing an edge
-                // word0: cmd_id | v0_index | v1_index | v2_index
-                // word1: v0_index | v2_index | v3_index (second triangle)
-                let v0 = ((word0 >> 16) & 0xFF) as usize / 2;
-                let v1 = ((word0 >> 8) & 0xFF) as usize / 2;
-                let v2 = (word0 & 0xFF) as usize / 2;
-
-                // First triangle: v0, v1, v2
-                if v0 < self.vertex_count && v1 < self.vertex_count && v2 < self.vertex_count {
-                    self.draw_transformed_triangle(v0, v1, v2, rdp);
-                }
-
-                // Second triangle uses vertices from word1
-                let v0_2 = ((word1 >> 16) & 0xFF) as usize / 2;
-                let v2_2 = ((word1 >> 8) & 0xFF) as usize / 2;
-                let v3 = (word1 & 0xFF) as usize / 2;
-
-                if v0_2 < self.vertex_count && v2_2 < self.vertex_count && v3 < self.vertex_count {
-                    self.draw_transformed_triangle(v0_2, v2_2, v3, rdp);
-                }
-                true
+            // G_TEXTURE (0xD7): word0 = cmd | level << 11 | tile << 8 | on << 1
+            0xD7 => {
+                self.set_texture((word0 >> 1) & 0x7F != 0, (word0 >> 8) & 0x07, word1);
+                DlFlow::Next
             }
-            // G_POPMTX (0xD8) - Pop matrix from stack
+            // G_POPMTX (0xD8): word1 = bytes to pop (64 per matrix)
             0xD8 => {
-                // word0: cmd_id | padding
-                // word1: number of matrices to pop (in units of 64 bytes each)
-                let num_matrices = ((word1 >> 6) & 0xFF) as usize; // Divide by 64 to get count
-
-                // Pop the specified number of matrices from the modelview stack
-                for _ in 0..num_matrices.min(self.matrix_stack_ptr) {
-                    if self.matrix_stack_ptr > 0 {
-                        self.matrix_stack_ptr -= 1;
-                        self.modelview_matrix = self.matrix_stack[self.matrix_stack_ptr];
-                    }
-                }
-                true
-            }
-            // G_MTX (0xDA) - Load transformation matrix
-            0xDA => {
-                // word0: cmd_id | param (push/nopush, load/mul, projection/modelview)
-                // word1: RDRAM address of matrix (64 bytes, 4x4 matrix of 16.16 fixed point)
-                let param = word0 & 0xFF;
-                let matrix_addr = word1;
-
-                // Parse matrix parameters
-                let push = (param & 0x01) != 0; // G_MTX_PUSH
-                let load = (param & 0x02) == 0; // G_MTX_LOAD (vs G_MTX_MUL)
-                let projection = (param & 0x04) != 0; // G_MTX_PROJECTION (vs G_MTX_MODELVIEW)
-
-                // Load matrix from RDRAM
-                let matrix = self.load_matrix_from_rdram(rdram, matrix_addr);
-
-                log(LogCategory::PPU, LogLevel::Debug, || {
-                    format!(
-                        "RSP HLE: G_MTX addr=0x{:08X} type={} mode={} push={}",
-                        matrix_addr,
-                        if projection { "PROJ" } else { "MV" },
-                        if load { "LOAD" } else { "MUL" },
-                        push
-                    )
-                });
-
-                // Apply matrix based on type
-                if projection {
-                    // Projection matrix
-                    if load {
-                        // Replace projection matrix
-                        self.projection_matrix = matrix;
-                    } else {
-                        // Multiply with existing projection matrix
-                        self.projection_matrix =
-                            Self::multiply_matrix(&self.projection_matrix, &matrix);
-                    }
-                } else {
-                    // Modelview matrix
-                    if push {
-                        // Push current modelview matrix to stack
-                        if self.matrix_stack_ptr < 10 {
-                            self.matrix_stack[self.matrix_stack_ptr] = self.modelview_matrix;
-                            self.matrix_stack_ptr += 1;
-                        }
-                    }
-                    if load {
-                        // Replace modelview matrix
-                        self.modelview_matrix = matrix;
-                    } else {
-                        // Multiply with existing modelview matrix
-                        self.modelview_matrix =
-                            Self::multiply_matrix(&self.modelview_matrix, &matrix);
-                    }
-                }
-                true
+                self.pop_matrices((word1 / 64) as usize);
+                DlFlow::Next
             }
-            // G_GEOMETRYMODE (0xD9) - Set rendering mode flags
+            // G_GEOMETRYMODE (0xD9): word0 = cmd | bits to keep, word1 = bits to set
             0xD9 => {
-                // word0: bits to clear (inverted mask)
-                // word1: bits to set
-                let clear_bits = word0 & 0x00FFFFFF;
-                let set_bits = word1;
-
-                // Clear specified bits then set new bits
-                self.geometry_mode = (self.geometry_mode & !clear_bits) | set_bits;
-                true
+                self.geometry_mode = (self.geometry_mode & (word0 & 0x00FFFFFF)) | word1;
+                DlFlow::Next
             }
-            // G_MOVEWORD (0xDB) - Modify internal state word
+            // G_MTX (0xDA): word0 = cmd | size | params, with G_MTX_PUSH inverted
+            0xDA => {
+                self.load_matrix(rdram, word1, (word0 & 0xFF) ^ G_MTX_PUSH);
+                DlFlow::Next
+            }
+            // G_MOVEWORD (0xDB): word0 = cmd | index << 16 | offset
             0xDB => {
-                // word0: cmd_id | index (which word to modify) | offset (within that word)
-                // word1: value to write
-                let index = (word0 >> 16) & 0xFF;
-                let _offset = word0 & 0xFFFF;
-                let _value = word1;
-
-                // Common indices:
-                // 0x00: G_MW_MATRIX - Modify current matrix
-                // 0x02: G_MW_NUMLIGHT - Set number of lights
-                // 0x04: G_MW_CLIP - Modify clipping planes
-                // 0x06: G_MW_SEGMENT - Set segment address
-                // 0x08: G_MW_FOG - Fog parameters
-                // 0x0A: G_MW_LIGHTCOL - Light color
-                // 0x0C: G_MW_POINTS - Point rendering params
-                // 0x0E: G_MW_PERSPNORM - Perspective normalization
-
-                // For HLE, we log but don't implement most of these
-                // Full LLE would modify RSP internal state
-                log(LogCategory::Stubs, LogLevel::Debug, || {
-                    format!(
-                        "N64 RSP HLE: G_MOVEWORD stub - index=0x{:02X}, offset=0x{:04X}, value=0x{:08X}",
-                        index, _offset, _value
-                    )
-                });
-                true
+                self.move_word((word0 >> 16) & 0xFF, word0 & 0xFFFF, word1);
+                DlFlow::Next
             }
-            // G_MOVEMEM (0xDC) - Load memory segment
+            // G_MOVEMEM (0xDC): word0 = cmd | size | offset / 8 << 8 | index
             0xDC => {
-                // word0: cmd_id | size | offset
-                // word1: RDRAM address to load from
-                let size = ((word0 >> 16) & 0xFF) as usize;
-                let offset = (word0 & 0xFFFF) as usize;
-                let rdram_addr = word1;
-
-                // G_MOVEMEM indices (offset values):
-                // 0x80 = G_MV_VIEWPORT (8 bytes)
-                // 0x00 = G_MV_MATRIX (64 bytes, not commonly used via MOVEMEM)
-                // 0x82 = G_MV_LIGHT (16 bytes per light, offset determines which light)
-
-                // Check if this is a viewport load (offset 0x80)
-                const G_MV_VIEWPORT: usize = 0x80;
-                const G_MV_LIGHT_BASE: usize = 0x82; // Base offset for lights
-
-                if offset == G_MV_VIEWPORT && size >= 8 {
-                    // Load viewport data from RDRAM
-                    // Viewport format (8 words = 16 bytes in 16.16 fixed point):
-                    // vscale[0] = width/2, vscale[1] = height/2
-                    // vtrans[0] = x + width/2, vtrans[1] = y + height/2
-                    let addr = Self::virt_to_phys(rdram_addr);
-                    if addr + 15 < rdram.len() {
-                        // Read scale values (vscale[0], vscale[1])
-                        let vscale_x = i32::from_be_bytes([
-                            rdram[addr],
-                            rdram[addr + 1],
-                            rdram[addr + 2],
-                            rdram[addr + 3],
-                        ]) as f32
-                            / 65536.0;
-
-                        let vscale_y = i32::from_be_bytes([
-                            rdram[addr + 4],
-                            rdram[addr + 5],
-                            rdram[addr + 6],
-                            rdram[addr + 7],
-                        ]) as f32
-                            / 65536.0;
-
-                        // Read translation values (vtrans[0], vtrans[1])
-                        let vtrans_x = i32::from_be_bytes([
-                            rdram[addr + 8],
-                            rdram[addr + 9],
-                            rdram[addr + 10],
-                            rdram[addr + 11],
-                        ]) as f32
-                            / 65536.0;
-
-                        let vtrans_y = i32::from_be_bytes([
-                            rdram[addr + 12],
-                            rdram[addr + 13],
-                            rdram[addr + 14],
-                            rdram[addr + 15],
-                        ]) as f32
-                            / 65536.0;
-
-                        // Calculate viewport bounds
-                        // x = vtrans_x - vscale_x, y = vtrans_y - vscale_y
-                        // width = vscale_x * 2, height = vscale_y * 2
-                        let vp_x = vtrans_x - vscale_x;
-                        let vp_y = vtrans_y - vscale_y;
-                        let vp_width = vscale_x * 2.0;
-                        let vp_height = vscale_y * 2.0;
-
-                        self.viewport = (vp_x, vp_y, vp_width, vp_height, vscale_x, vscale_y);
-
-                        log(LogCategory::PPU, LogLevel::Debug, || {
-                            format!(
-                                "RSP HLE: G_MOVEMEM viewport - x={:.1}, y={:.1}, w={:.1}, h={:.1}",
-                                vp_x, vp_y, vp_width, vp_height
-                            )
-                        });
+                let offset = ((word0 >> 8) & 0xFF) * 8;
+                match word0 & 0xFF {
+                    // G_MV_VIEWPORT
+                    8 => self.load_viewport(rdram, word1),
+                    // G_MV_LIGHT: two lookat vectors, then the lights
+                    10 if offset >= 48 => {
+                        self.load_light(rdram, word1, ((offset - 48) / 24) as usize)
                     }
-                } else if (G_MV_LIGHT_BASE..=0x92).contains(&offset) && size >= 16 {
-                    // Load light data from RDRAM
-                    // Light format (16 bytes):
-                    // bytes 0-2: R, G, B color (0-255)
-                    // bytes 3-5: padding
-                    // bytes 6-8: X, Y, Z direction (signed bytes, normalized)
-                    // bytes 9-15: padding
-
-                    // Calculate which light this is (offset 0x82 = light 0, 0x84 = light 1, etc.)
-                    let light_index = (offset - G_MV_LIGHT_BASE) / 2;
-
-                    if light_index < 8 {
-                        let addr = Self::virt_to_phys(rdram_addr);
-                        if addr + 15 < rdram.len() {
-                            // Read light color (RGB, 0-255)
-                            let r = rdram[addr] as f32 / 255.0;
-                            let g = rdram[addr + 1] as f32 / 255.0;
-                            let b = rdram[addr + 2] as f32 / 255.0;
-
-                            // Read light direction (signed bytes, treat as normalized -1 to 1)
-                            let dx = (rdram[addr + 6] as i8) as f32 / 127.0;
-                            let dy = (rdram[addr + 7] as i8) as f32 / 127.0;
-                            let dz = (rdram[addr + 8] as i8) as f32 / 127.0;
-
-                            // Store light data: [dx, dy, dz, r, g, b, type]
-                            // type: 0.0 = directional, 1.0 = point (for future use)
-                            self.lights[light_index] = [dx, dy, dz, r, g, b, 0.0];
-
-                            if light_index >= self.num_lights {
-                                self.num_lights = light_index + 1;
-                            }
-
-                            log(LogCategory::PPU, LogLevel::Debug, || {
-                                format!(
-                                    "RSP HLE: G_MOVEMEM light {} - color=({:.2},{:.2},{:.2}), dir=({:.2},{:.2},{:.2})",
-                                    light_index, r, g, b, dx, dy, dz
-                                )
-                            });
-                        }
-                    }
-                } else {
-                    // Other MOVEMEM types - log but don't implement
-                    log(LogCategory::Stubs, LogLevel::Debug, || {
+                    index => log(LogCategory::Stubs, LogLevel::Debug, || {
                         format!(
-                            "N64 RSP HLE: G_MOVEMEM stub - size={}, offset=0x{:04X}, addr=0x{:08X}",
-                            size, offset, rdram_addr
+                            "N64 RSP HLE: G_MOVEMEM stub - index={}, offset=0x{:X}, addr=0x{:08X}",
+                            index, offset, word1
                         )
-                    });
+                    }),
                 }
-                true
+                DlFlow::Next
             }
-            // G_TEXTURE (0xD7) - Configure texture settings
-            0xD7 => {
-                // word0: cmd_id | level (mipmap level) | tile | on (enable/disable)
-                // word1: scaleS(16) | scaleT(16) - texture coordinate scaling
-                let _level = (word0 >> 11) & 0x07;
-                let _tile = (word0 >> 8) & 0x07;
-                let _on = (word0 >> 1) & 0x7F; // Non-zero = texture on
-                let _scale_s = (word1 >> 16) & 0xFFFF;
-                let _scale_t = word1 & 0xFFFF;
-
-                // For HLE, we could update geometry mode or pass to RDP
-                // For now, log and continue
-                log(LogCategory::Stubs, LogLevel::Debug, || {
-                    format!(
-                        "N64 RSP HLE: G_TEXTURE - tile={}, on={}, scaleS=0x{:04X}, scaleT=0x{:04X}",
-                        _tile, _on, _scale_s, _scale_t
-                    )
-                });
-                true
+            // G_LOAD_UCODE (0xDD)
+            0xDD => {
+                self.log_load_ucode(word1);
+                DlFlow::Next
             }
-            // G_SETOTHERMODE_L (0xB2) - Set lower other modes
-            0xB2 => {
-                // word0: cmd_id | shift | length
-                // word1: data to set
-                let _shift = (word0 >> 8) & 0xFF;
-                let _length = word0 & 0xFF;
-                let _data = word1;
-
-                // Other modes control rendering pipeline settings (alpha blend, Z-mode, etc.)
-                log(LogCategory::Stubs, LogLevel::Debug, || {
-                    format!(
-                        "N64 RSP HLE: G_SETOTHERMODE_L stub - shift={}, len={}, data=0x{:08X}",
-                        _shift, _length, _data
-                    )
-                });
-                true
+            // G_DL (0xDE): bits 16-23 are 0 to call, 1 to branch
+            0xDE => self.display_list(rdram, word1, (word0 >> 16) & 0xFF == 0, rdp),
+            // G_ENDDL (0xDF)
+            0xDF => DlFlow::End,
+            // G_RDPHALF_1 (0xE1)
+            0xE1 => {
+                self.rdp_half = word1;
+                DlFlow::Next
             }
-            // G_SETOTHERMODE_H (0xB3) - Set upper other modes
-            0xB3 => {
-                // word0: cmd_id | shift | length
-                // word1: data to set
-                let _shift = (word0 >> 8) & 0xFF;
-                let _length = word0 & 0xFF;
-                let _data = word1;
-
-                // Other modes control rendering pipeline settings
-                log(LogCategory::Stubs, LogLevel::Debug, || {
-                    format!(
-                        "N64 RSP HLE: G_SETOTHERMODE_H stub - shift={}, len={}, data=0x{:08X}",
-                        _shift, _length, _data
-                    )
-                });
-                true
+            // G_SETOTHERMODE_L (0xE2) / G_SETOTHERMODE_H (0xE3):
+            // word0 = cmd | 32 - shift - length << 8 | length - 1
+            0xE2 | 0xE3 => {
+                let length = (word0 & 0xFF) + 1;
+                let shift = 32u32.saturating_sub(((word0 >> 8) & 0xFF) + length);
+                self.set_other_mode(word0 >> 24 == 0xE3, shift, length, word1, rdram, rdp);
+                DlFlow::Next
             }
-            // G_LOAD_UCODE (0xAF) - Load new microcode
-            0xAF => {
-                // word0: cmd_id | size
-                // word1: RDRAM address of microcode
-                let _size = (word0 & 0xFFFF) as usize;
-                let _ucode_addr = word1;
-
-                // For HLE, we don't actually load and execute microcode
-                // Instead, we detect the microcode type by signature
-                // A full LLE implementation would copy from RDRAM to IMEM
-                log(LogCategory::Stubs, LogLevel::Debug, || {
-                    format!(
-                        "N64 RSP HLE: G_LOAD_UCODE - size=0x{:04X}, addr=0x{:08X}",
-                        _size, _ucode_addr
-                    )
-                });
-                true
+            // G_RDPHALF_2 (0xF1)
+            0xF1 => {
+                self.rdp_half_2(word1, rdp);
+                DlFlow::Next
+            }
+            // RDP commands
+            0xE4..=0xFF => self.rdp_command(word0, word1, rdram, rdp),
+            // G_SPNOOP, G_MODIFYVTX, G_NOOP and the rest - skip
+            _ => DlFlow::Next,
+        }
+    }
+
+    /// G_MTX: load or multiply the projection or modelview matrix
+    fn load_matrix(&mut self, rdram: &[u8], addr: u32, params: u32) {
+        let push = params & G_MTX_PUSH != 0;
+        let load = params & G_MTX_LOAD != 0;
+        let projection = params & G_MTX_PROJECTION != 0;
+        let matrix = self.load_matrix_from_rdram(rdram, self.segment_address(addr));
+
+        log(LogCategory::PPU, LogLevel::Debug, || {
+            format!(
+                "RSP HLE: G_MTX addr=0x{:08X} type={} mode={} push={}",
+                addr,
+                if projection { "PROJ" } else { "MV" },
+                if load { "LOAD" } else { "MUL" },
+                push
+            )
+        });
+
+        // The new matrix is applied first: vertices are row vectors, so
+        // N64 matrices multiply on the left of the current one
+        if projection {
+            self.projection_matrix = if load {
+                matrix
+            } else {
+                Self::multiply_matrix(&self.projection_matrix, &matrix)
+            };
+        } else {
+            if push && self.matrix_stack_ptr < 10 {
+                self.matrix_stack[self.matrix_stack_ptr] = self.modelview_matrix;
+                self.matrix_stack_ptr += 1;
             }
-            // G_DL (0xDE) - Display list branch/call
-            0xDE => {
-                // word0: cmd_id | branch_type (0 = call with return, 1 = branch no return)
-                // word1: RDRAM address of display list to execute
-                let branch_type = (word0 >> 16) & 0xFF;
-                let dl_addr = word1;
-
-                // branch_type: 0 = G_DL_PUSH (call, will return), 1 = G_DL_NOPUSH (branch, no return)
-                let is_push = branch_type == 0;
-
-                // For now, we implement a simple non-recursive version
-                // A full implementation would use a stack to handle nested display lists
-                // To prevent infinite loops, we only support one level of nesting here
-                if is_push {
-                    // This is a display list call - we should save state and execute the nested DL
-                    // For simplicity, we parse it inline without full recursion support
-                    // Full implementation would push return address to a stack
-                    self.parse_f3dex_display_list(rdram, dl_addr, 10000, rdp);
+            self.modelview_matrix = if load {
+                matrix
+            } else {
+                Self::multiply_matrix(&self.modelview_matrix, &matrix)
+            };
+        }
+    }
+
+    /// G_POPMTX: restore modelview matrices from the stack
+    fn pop_matrices(&mut self, count: usize) {
+        for _ in 0..count.min(self.matrix_stack_ptr) {
+            self.matrix_stack_ptr -= 1;
+            self.modelview_matrix = self.matrix_stack[self.matrix_stack_ptr];
+        }
+    }
+
+    /// G_MOVEMEM viewport: scale and translation as 4 + 4 signed 14.2 values
+    fn load_viewport(&mut self, rdram: &[u8], addr: u32) {
+        let addr = self.segment_address(addr) as usize;
+        let Some(vp) = rdram.get(addr..addr + 16) else {
+            return;
+        };
+        let value = |i: usize| i16::from_be_bytes([vp[i * 2], vp[i * 2 + 1]]) as f32 / 4.0;
+        let (vscale_x, vscale_y) = (value(0), value(1));
+        let (vtrans_x, vtrans_y) = (value(4), value(5));
+
+        // x = vtrans - vscale, width = vscale * 2
+        let vp_x = vtrans_x - vscale_x;
+        let vp_y = vtrans_y - vscale_y;
+        self.viewport = (
+            vp_x,
+            vp_y,
+            vscale_x * 2.0,
+            vscale_y * 2.0,
+            vscale_x,
+            vscale_y,
+        );
+
+        log(LogCategory::PPU, LogLevel::Debug, || {
+            format!(
+                "RSP HLE: G_MOVEMEM viewport - x={:.1}, y={:.1}, w={:.1}, h={:.1}",
+                vp_x,
+                vp_y,
+                vscale_x * 2.0,
+                vscale_y * 2.0
+            )
+        });
+    }
+
+    /// G_MOVEMEM light: color (bytes 0-2) and direction (bytes 8-10)
+    fn load_light(&mut self, rdram: &[u8], addr: u32, index: usize) {
+        let addr = self.segment_address(addr) as usize;
+        let (Some(light), Some(slot)) = (rdram.get(addr..addr + 16), self.lights.get_mut(index))
+        else {
+            return;
+        };
+        let r = light[0] as f32 / 255.0;
+        let g = light[1] as f32 / 255.0;
+        let b = light[2] as f32 / 255.0;
+        let dx = light[8] as i8 as f32 / 127.0;
+        let dy = light[9] as i8 as f32 / 127.0;
+        let dz = light[10] as i8 as f32 / 127.0;
+        // Store light data: [dx, dy, dz, r, g, b, type]
+        *slot = [dx, dy, dz, r, g, b, 0.0];
+
+        log(LogCategory::PPU, LogLevel::Debug, || {
+            format!(
+                "RSP HLE: G_MOVEMEM light {} - color=({:.2},{:.2},{:.2}), dir=({:.2},{:.2},{:.2})",
+                index, r, g, b, dx, dy, dz
+            )
+        });
+    }
+
+    /// G_MOVEWORD: write a word of microcode state
+    fn move_word(&mut self, index: u32, offset: u32, value: u32) {
+        match index {
+            // G_MW_NUMLIGHT: F3DEX stores (n + 1) * 32 | 0x80000000, F3DEX2 n * 24
+            G_MW_NUMLIGHT => {
+                let count = if self.microcode == MicrocodeType::F3DEX {
+                    ((value & 0x7FFFFFFF) / 32).saturating_sub(1)
                 } else {
-                    // This is a branch - we don't return, but we still parse it inline
-                    // In the real implementation, this would update the current DL pointer
-                    self.parse_f3dex_display_list(rdram, dl_addr, 10000, rdp);
-                }
-                true
+                    value / 24
+                };
+                self.num_lights = (count as usize).min(7);
             }
-            // G_ENDDL (0xDF) - End display list
-            0xDF => false,
-            // G_RDPHALF_2 (0xB4) - Second half of 2-word RDP command
-            0xB4 => {
-                // word0: cmd_id | padding
-                // word1: data (second word for RDP command)
-                // This completes a 2-word RDP command using the stored rdp_half value
-                // Common use: TEXTURE_RECTANGLE where word0 comes from RDPHALF_1
-                log(LogCategory::Stubs, LogLevel::Debug, || {
-                    format!("N64 RSP HLE: G_RDPHALF_2 - data=0x{:08X}, combining with rdp_half=0x{:08X}", word1, self.rdp_half)
-                });
-                true
+            // G_MW_SEGMENT: offset = segment * 4
+            G_MW_SEGMENT => {
+                self.segments[((offset / 4) & 0x0F) as usize] = value & 0x00FFFFFF;
             }
-            // G_RDPHALF_1 (0xBF) - First half of 2-word RDP command
-            0xBF => {
-                // word0: cmd_id | padding
-                // word1: data (first word for RDP command)
-                // Store the data for use by subsequent command
-                self.rdp_half = word1;
-                log(LogCategory::Stubs, LogLevel::Debug, || {
-                    format!("N64 RSP HLE: G_RDPHALF_1 - stored data=0x{:08X}", word1)
-                });
-                true
+            _ => log(LogCategory::Stubs, LogLevel::Debug, || {
+                format!(
+                    "N64 RSP HLE: G_MOVEWORD stub - index=0x{:02X}, offset=0x{:04X}, value=0x{:08X}",
+                    index, offset, value
+                )
+            }),
+        }
+    }
+
+    /// G_TEXTURE: enable texturing with a tile and S/T scale (0.16 each)
+    fn set_texture(&mut self, on: bool, tile: u32, scale: u32) {
+        self.texture_on = on;
+        self.texture_tile = tile as usize;
+        self.texture_scale = (
+            (scale >> 16) as f32 / 65536.0,
+            (scale & 0xFFFF) as f32 / 65536.0,
+        );
+    }
+
+    /// G_SETOTHERMODE_H/L: update bits of an other mode word and pass both
+    /// on to the RDP as SET_OTHER_MODES
+    fn set_other_mode(
+        &mut self,
+        high: bool,
+        shift: u32,
+        length: u32,
+        data: u32,
+        rdram: &[u8],
+        rdp: &mut Rdp,
+    ) {
+        let mask = (((1u64 << length.min(32)) - 1) << shift.min(32)) as u32;
+        let word = if high {
+            &mut self.other_mode.0
+        } else {
+            &mut self.other_mode.1
+        };
+        *word = (*word & !mask) | (data & mask);
+
+        let (high_word, low_word) = self.other_mode;
+        rdp.execute_rdp_command(
+            0x2F,
+            0xEF00_0000 | (high_word & 0x00FFFFFF),
+            low_word,
+            rdram,
+        );
+    }
+
+    /// G_DL: call a display list, or branch to it (ending the current one)
+    fn display_list(&mut self, rdram: &[u8], addr: u32, push: bool, rdp: &mut Rdp) -> DlFlow {
+        if push {
+            self.parse_f3dex_display_list(rdram, addr, 0, rdp);
+            DlFlow::Next
+        } else {
+            DlFlow::Branch(addr)
+        }
+    }
+
+    /// G_BRANCH_Z: branch to the G_RDPHALF_1 address if a vertex is closer
+    /// than a depth. Games use it to pick a level of detail; the depth test
+    /// is not emulated, so the (detailed) branch is always taken.
+    fn branch_z(&self) -> DlFlow {
+        DlFlow::Branch(self.rdp_half)
+    }
+
+    /// G_CULLDL: end the display list if vertices `first..=last` are all
+    /// outside the same clip plane
+    fn cull_display_list(&self, first: usize, last: usize) -> DlFlow {
+        let last = last.min(self.vertex_count.saturating_sub(1));
+        if first > last {
+            return DlFlow::Next;
+        }
+        let clips: Vec<[f32; 4]> = self.vertices[first..=last]
+            .iter()
+            .map(|v| self.transform_vertex_to_clip(v))
+            .collect();
+        let outside = (0..3).any(|axis| {
+            clips.iter().all(|c| c[axis] < -c[3]) || clips.iter().all(|c| c[axis] > c[3])
+        });
+        if outside {
+            DlFlow::End
+        } else {
+            DlFlow::Next
+        }
+    }
+
+    /// Log a G_LOAD_UCODE. HLE keeps interpreting the display list with the
+    /// microcode the task started with.
+    fn log_load_ucode(&self, addr: u32) {
+        log(LogCategory::Stubs, LogLevel::Debug, || {
+            format!("N64 RSP HLE: G_LOAD_UCODE stub - addr=0x{:08X}", addr)
+        });
+    }
+
+    /// Pass an RDP command in the display list on to the RDP
+    fn rdp_command(&mut self, word0: u32, word1: u32, rdram: &[u8], rdp: &mut Rdp) -> DlFlow {
+        // The RDP command ID is in the lower 6 bits of the command byte
+        let rdp_cmd_id = (word0 >> 24) & 0x3F;
+        match rdp_cmd_id {
+            // TEXTURE_RECTANGLE(_FLIP): the texture coordinates follow in
+            // G_RDPHALF_1 and G_RDPHALF_2
+            0x24 | 0x25 => self.pending_texrect = Some((word0, word1)),
+            // SET_TEXTURE_IMAGE, SET_Z_IMAGE, SET_COLOR_IMAGE take
+            // segmented addresses
+            0x3D..=0x3F => {
+                rdp.execute_rdp_command(rdp_cmd_id, word0, self.segment_address(word1), rdram)
             }
-            // G_SETPRIMDEPTH (0xEE) - Set primitive depth
-            0xEE => {
-                // word0: cmd_id | padding
-                // word1: z (16-bit) | dz (16-bit) - depth value and delta
-                let _z = (word1 >> 16) & 0xFFFF;
-                let _dz = word1 & 0xFFFF;
-
-                // For HLE, we log but don't implement primitive depth override
-                // Full implementation would set a base depth for subsequent primitives
-                log(LogCategory::Stubs, LogLevel::Debug, || {
-                    format!(
-                        "N64 RSP HLE: G_SETPRIMDEPTH - z=0x{:04X}, dz=0x{:04X}",
-                        _z, _dz
-                    )
-                });
-                true
+            _ => {
+                // SYNC_FULL
+                if rdp_cmd_id == 0x29 {
+                    self.full_sync = true;
+                }
+                rdp.execute_rdp_command(rdp_cmd_id, word0, word1, rdram);
             }
-            // RDP passthrough commands (0xE0-0xFF) - forward directly to RDP
-            0xE0..=0xFF => {
-                // These are RDP commands embedded in F3DEX display list
-                // Forward them directly to the RDP for execution
-                // Common commands: SET_FILL_COLOR (0xF7/0x37), SET_SCISSOR (0xED/0x2D), etc.
+        }
+        DlFlow::Next
+    }
 
-                // The RDP command ID is in the lower 6 bits of the command byte
-                let rdp_cmd_id = (word0 >> 24) & 0x3F;
+    /// G_RDPHALF_2: completes a TEXTURE_RECTANGLE with the steps in `word1`
+    /// and the coordinates from G_RDPHALF_1
+    fn rdp_half_2(&mut self, word1: u32, rdp: &mut Rdp) {
+        if let Some((texrect0, texrect1)) = self.pending_texrect.take() {
+            rdp.texture_rectangle(texrect0, texrect1, self.rdp_half, word1);
+        }
+    }
 
-                // Call RDP's execute_command directly with the command data
-                // Pass rdram for texture loading and other DRAM-dependent commands
-                rdp.execute_rdp_command(rdp_cmd_id, word0, word1, rdram);
-                true
+    /// Load `count` vertices from a segmented address into the vertex buffer,
+    /// starting at `first`
+    fn load_vertices(&mut self, rdram: &[u8], addr: u32, first: usize, count: usize) {
+        let addr = self.segment_address(addr);
+        for i in 0..count.min(32usize.saturating_sub(first)) {
+            self.load_vertex(rdram, addr + (i as u32 * 16), first + i);
+            if self.geometry_mode & G_LIGHTING != 0 {
+                self.light_vertex(first + i);
             }
-            // Unknown/unsupported command - skip it
-            _ => true,
+        }
+    }
+
+    /// Replace a vertex's color (which holds its normal when G_LIGHTING is
+    /// on) with the light it receives: the ambient light, stored after the
+    /// directional lights, plus each light by the cosine of its angle
+    fn light_vertex(&mut self, index: usize) {
+        let vertex = &self.vertices[index];
+        let n = [
+            vertex.color[0] as i8 as f32,
+            vertex.color[1] as i8 as f32,
+            vertex.color[2] as i8 as f32,
+        ];
+
+        // Lights are given in the space the modelview matrix transforms to
+        let m = &self.modelview_matrix;
+        let mut normal = [0.0f32; 3];
+        for (i, elem) in normal.iter_mut().enumerate() {
+            *elem = m[i] * n[0] + m[i + 4] * n[1] + m[i + 8] * n[2];
+        }
+        let length = normal.iter().map(|c| c * c).sum::<f32>().sqrt();
+        if length > 0.0 {
+            normal.iter_mut().for_each(|c| *c /= length);
+        }
+
+        let ambient = &self.lights[self.num_lights];
+        let mut rgb = [ambient[3], ambient[4], ambient[5]];
+        for light in &self.lights[..self.num_lights] {
+            let dir_length = (light[0] * light[0] + light[1] * light[1] + light[2] * light[2])
+                .sqrt()
+                .max(0.0001);
+            let intensity = ((normal[0] * light[0] + normal[1] * light[1] + normal[2] * light[2])
+                / dir_length)
+                .max(0.0);
+            for (channel, color) in rgb.iter_mut().zip(&light[3..6]) {
+                *channel += intensity * color;
+            }
+        }
+
+        for (out, channel) in self.vertices[index].color.iter_mut().zip(rgb) {
+            *out = (channel * 255.0).clamp(0.0, 255.0) as u8;
+        }
+    }
+
+    /// Draw the triangle whose vertex indices (times 2) are packed in the low
+    /// 24 bits of a command word
+    fn draw_packed_triangle(&self, word: u32, rdp: &mut Rdp) {
+        let v0 = ((word >> 16) & 0xFF) as usize / 2;
+        let v1 = ((word >> 8) & 0xFF) as usize / 2;
+        let v2 = (word & 0xFF) as usize / 2;
+        self.draw_triangle(v0, v1, v2, rdp);
+    }
+
+    /// Draw a triangle of loaded vertices, skipping out-of-range indices
+    fn draw_triangle(&self, v0: usize, v1: usize, v2: usize, rdp: &mut Rdp) {
+        if v0 < self.vertex_count && v1 < self.vertex_count && v2 < self.vertex_count {
+            self.draw_transformed_triangle(v0, v1, v2, rdp);
+        }
+    }
+
+    /// Geometry mode bits that cull front and back faces, which moved
+    /// between F3DEX and F3DEX2
+    fn cull_flags(&self) -> (u32, u32) {
+        if self.microcode == MicrocodeType::F3DEX {
+            (F3DEX_CULL_FRONT, F3DEX_CULL_BACK)
+        } else {
+            (F3DEX2_CULL_FRONT, F3DEX2_CULL_BACK)
         }
     }
 
@@ -1052,6 +1330,15 @@ impl RspHle {
         let (x1, y1, z1) = self.clip_to_screen(&clip1);
         let (x2, y2, z2) = self.clip_to_screen(&clip2);
 
+        // Front faces wind counter-clockwise with Y up, so clockwise on screen
+        let area = (x1 - x0) as i64 * (y2 - y0) as i64 - (y1 - y0) as i64 * (x2 - x0) as i64;
+        let (cull_front, cull_back) = self.cull_flags();
+        if (area < 0 && self.geometry_mode & cull_front != 0)
+            || (area > 0 && self.geometry_mode & cull_back != 0)
+        {
+            return;
+        }
+
         log(LogCategory::PPU, LogLevel::Debug, || {
             format!(
                 "RSP HLE: Triangle v{}({},{},{}) v{}({},{},{}) v{}({},{},{}) -> screen ({},{},{}) ({},{},{}) ({},{},{})",
@@ -1062,16 +1349,51 @@ impl RspHle {
             )
         });
 
+        // Draw with Z-buffer (assuming depth values fit in u16), testing
+        // depth only if G_ZBUFFER is set
+        let z0_u16 = z0.clamp(0, 0xFFFF) as u16;
+        let z1_u16 = z1.clamp(0, 0xFFFF) as u16;
+        let z2_u16 = z2.clamp(0, 0xFFFF) as u16;
+        rdp.set_zbuffer_enabled(self.geometry_mode & G_ZBUFFER != 0);
+
+        if self.texture_on {
+            // Vertex texture coordinates are S10.5, scaled by G_TEXTURE
+            let (scale_s, scale_t) = self.texture_scale;
+            let st = |v: &Vertex| {
+                (
+                    v.tex[0] as f32 * scale_s / 32.0,
+                    v.tex[1] as f32 * scale_t / 32.0,
+                )
+            };
+            let (s0, t0) = st(vert0);
+            let (s1, t1) = st(vert1);
+            let (s2, t2) = st(vert2);
+            rdp.draw_triangle_textured_zbuffer(
+                x0,
+                y0,
+                z0_u16,
+                s0,
+                t0,
+                x1,
+                y1,
+                z1_u16,
+                s1,
+                t1,
+                x2,
+                y2,
+                z2_u16,
+                s2,
+                t2,
+                self.texture_tile,
+            );
+            return;
+        }
+
         // Convert vertex colors to ARGB format
         let c0 = u32::from_be_bytes([0xFF, vert0.color[0], vert0.color[1], vert0.color[2]]);
         let c1 = u32::from_be_bytes([0xFF, vert1.color[0], vert1.color[1], vert1.color[2]]);
         let c2 = u32::from_be_bytes([0xFF, vert2.color[0], vert2.color[1], vert2.color[2]]);
 
-        // Draw shaded triangle with Z-buffer (assuming depth values fit in u16)
-        let z0_u16 = z0.clamp(0, 0xFFFF) as u16;
-        let z1_u16 = z1.clamp(0, 0xFFFF) as u16;
-        let z2_u16 = z2.clamp(0, 0xFFFF) as u16;
-
         rdp.draw_triangle_shaded_zbuffer(
             x0, y0, z0_u16, c0, x1, y1, z1_u16, c1, x2, y2, z2_u16, c2,
         );
@@ -1326,6 +1648,27 @@ impl Default for RspHle {
 mod tests {
     use super::*;
 
+    /// Write a matrix in the N64 Mtx layout: 16 integer halves, then 16
+    /// fraction halves
+    fn write_matrix(rdram: &mut [u8], addr: usize, matrix: &[f32; 16]) {
+        for (i, &value) in matrix.iter().enumerate() {
+            let fixed = (value * 65536.0) as i32 as u32;
+            rdram[addr + i * 2..addr + i * 2 + 2]
+                .copy_from_slice(&((fixed >> 16) as u16).to_be_bytes());
+            rdram[addr + 32 + i * 2..addr + 34 + i * 2]
+                .copy_from_slice(&(fixed as u16).to_be_bytes());
+        }
+    }
+
+    /// Write display list commands starting at `addr`
+    fn write_display_list(rdram: &mut [u8], addr: usize, commands: &[(u32, u32)]) {
+        for (i, &(word0, word1)) in commands.iter().enumerate() {
+            let addr = addr + i * 8;
+            rdram[addr..addr + 4].copy_from_slice(&word0.to_be_bytes());
+            rdram[addr + 4..addr + 8].copy_from_slice(&word1.to_be_bytes());
+        }
+    }
+
     #[test]
     fn test_rsp_hle_creation() {
         let hle = RspHle::new();
@@ -1442,7 +1785,7 @@ mod tests {
     #[test]
     fn test_f3dex_display_list_parsing() {
         let mut hle = RspHle::new();
-        hle.microcode = MicrocodeType::F3DEX;
+        hle.microcode = MicrocodeType::F3DEX2;
 
         let mut rdram = vec![0u8; 1024];
         let mut rdp = Rdp::new();
@@ -1451,8 +1794,8 @@ mod tests {
         let dl_addr = 0x100;
 
         // G_VTX command - Load 3 vertices at address 0x200
-        // word0: cmd(0x01) | count(3 << 12) | index(0)
-        let vtx_cmd_word0: u32 = (0x01 << 24) | (3 << 12);
+        // word0: cmd(0x01) | count(3 << 12) | end index(3 << 1)
+        let vtx_cmd_word0: u32 = (0x01 << 24) | (3 << 12) | (3 << 1);
         let vtx_cmd_word1: u32 = 0x200; // Vertex data address
         rdram[dl_addr..dl_addr + 4].copy_from_slice(&vtx_cmd_word0.to_be_bytes());
         rdram[dl_addr + 4..dl_addr + 8].copy_from_slice(&vtx_cmd_word1.to_be_bytes());
@@ -1503,7 +1846,7 @@ mod tests {
     #[test]
     fn test_f3dex_quad_command() {
         let mut hle = RspHle::new();
-        hle.microcode = MicrocodeType::F3DEX;
+        hle.microcode = MicrocodeType::F3DEX2;
 
         let mut rdram = vec![0u8; 1024];
         let mut rdp = Rdp::new();
@@ -1512,7 +1855,7 @@ mod tests {
         let dl_addr = 0x100;
 
         // G_VTX command - Load 4 vertices
-        let vtx_cmd_word0: u32 = (0x01 << 24) | (4 << 12);
+        let vtx_cmd_word0: u32 = (0x01 << 24) | (4 << 12) | (4 << 1);
         let vtx_cmd_word1: u32 = 0x200;
         rdram[dl_addr..dl_addr + 4].copy_from_slice(&vtx_cmd_word0.to_be_bytes());
         rdram[dl_addr + 4..dl_addr + 8].copy_from_slice(&vtx_cmd_word1.to_be_bytes());
@@ -1562,7 +1905,7 @@ mod tests {
     #[test]
     fn test_f3dex_geometrymode_command() {
         let mut hle = RspHle::new();
-        hle.microcode = MicrocodeType::F3DEX;
+        hle.microcode = MicrocodeType::F3DEX2;
 
         let mut rdram = vec![0u8; 1024];
         let mut rdp = Rdp::new();
@@ -1573,9 +1916,9 @@ mod tests {
         assert_eq!(hle.geometry_mode, 0);
 
         // G_GEOMETRYMODE command - Set some flags
-        // word0: cmd(0xD9) | clear_bits (bits to clear)
+        // word0: cmd(0xD9) | bits to keep
         // word1: set_bits (bits to set)
-        let geom_cmd_word0: u32 = 0xD9 << 24; // Don't clear any bits
+        let geom_cmd_word0: u32 = 0xD9 << 24; // Keep no bits
         let geom_cmd_word1: u32 = 0x00000123; // Set some test flags
         rdram[dl_addr..dl_addr + 4].copy_from_slice(&geom_cmd_word0.to_be_bytes());
         rdram[dl_addr + 4..dl_addr + 8].copy_from_slice(&geom_cmd_word1.to_be_bytes());
@@ -1628,21 +1971,12 @@ mod tests {
         let hle = RspHle::new();
         let mut rdram = vec![0u8; 1024];
 
-        // Create an identity matrix in RDRAM (16.16 fixed point format)
-        // Identity: diagonal = 1.0 = 0x00010000 in 16.16 fixed point
-        let identity_fixed: i32 = 0x00010000; // 1.0 in 16.16 fixed point
-        let zero_fixed: i32 = 0x00000000; // 0.0 in 16.16 fixed point
-
+        // Create a matrix in RDRAM (16.16 fixed point, Mtx layout)
         let addr = 0x100;
-        for i in 0..16 {
-            let value = if i == 0 || i == 5 || i == 10 || i == 15 {
-                identity_fixed
-            } else {
-                zero_fixed
-            };
-            let offset = addr + i * 4;
-            rdram[offset..offset + 4].copy_from_slice(&value.to_be_bytes());
-        }
+        let mut value = RspHle::identity_matrix();
+        value[1] = -1.5;
+        value[12] = 100.25;
+        write_matrix(&mut rdram, addr, &value);
 
         let matrix = hle.load_matrix_from_rdram(&rdram, addr as u32);
 
@@ -1651,42 +1985,33 @@ mod tests {
         assert_eq!(matrix[5], 1.0);
         assert_eq!(matrix[10], 1.0);
         assert_eq!(matrix[15], 1.0);
-        assert_eq!(matrix[1], 0.0);
+        assert_eq!(matrix[1], -1.5);
         assert_eq!(matrix[2], 0.0);
+        assert_eq!(matrix[12], 100.25);
     }
 
     #[test]
     fn test_g_mtx_command() {
         let mut hle = RspHle::new();
-        hle.microcode = MicrocodeType::F3DEX;
+        hle.microcode = MicrocodeType::F3DEX2;
 
         let mut rdram = vec![0u8; 1024];
         let mut rdp = Rdp::new();
 
         // Create a scaling matrix in RDRAM (scale by 2.0)
         let addr = 0x200;
-        let scale2_fixed: i32 = 0x00020000; // 2.0 in 16.16 fixed point
-        let zero_fixed: i32 = 0x00000000;
-        let one_fixed: i32 = 0x00010000;
-
-        for i in 0..16 {
-            let value = if i == 0 || i == 5 || i == 10 {
-                scale2_fixed
-            } else if i == 15 {
-                one_fixed
-            } else {
-                zero_fixed
-            };
-            let offset = addr + i * 4;
-            rdram[offset..offset + 4].copy_from_slice(&value.to_be_bytes());
-        }
+        let mut scale2 = RspHle::identity_matrix();
+        scale2[0] = 2.0;
+        scale2[5] = 2.0;
+        scale2[10] = 2.0;
+        write_matrix(&mut rdram, addr, &scale2);
 
         // Create display list with G_MTX command
         let dl_addr = 0x100;
 
         // G_MTX command (0xDA) - load modelview matrix
-        // param: G_MTX_MODELVIEW | G_MTX_LOAD (0x00)
-        let mtx_cmd_word0: u32 = 0xDA << 24; // Load modelview (param = 0x00)
+        // param: G_MTX_MODELVIEW | G_MTX_LOAD, G_MTX_PUSH inverted (0x03)
+        let mtx_cmd_word0: u32 = (0xDA << 24) | 0x03; // Load modelview
         let mtx_cmd_word1: u32 = addr as u32; // Matrix address
         rdram[dl_addr..dl_addr + 4].copy_from_slice(&mtx_cmd_word0.to_be_bytes());
         rdram[dl_addr + 4..dl_addr + 8].copy_from_slice(&mtx_cmd_word1.to_be_bytes());
@@ -1708,31 +2033,20 @@ mod tests {
     #[test]
     fn test_g_mtx_projection() {
         let mut hle = RspHle::new();
-        hle.microcode = MicrocodeType::F3DEX;
+        hle.microcode = MicrocodeType::F3DEX2;
 
         let mut rdram = vec![0u8; 1024];
         let mut rdp = Rdp::new();
 
         // Create a projection matrix in RDRAM
         let addr = 0x200;
-        let one_fixed: i32 = 0x00010000;
-        let zero_fixed: i32 = 0x00000000;
-
-        for i in 0..16 {
-            let value = if i == 0 || i == 5 || i == 10 || i == 15 {
-                one_fixed
-            } else {
-                zero_fixed
-            };
-            let offset = addr + i * 4;
-            rdram[offset..offset + 4].copy_from_slice(&value.to_be_bytes());
-        }
+        write_matrix(&mut rdram, addr, &RspHle::identity_matrix());
 
         let dl_addr = 0x100;
 
         // G_MTX command (0xDA) - load projection matrix
-        // param: G_MTX_PROJECTION | G_MTX_LOAD (0x04)
-        let mtx_cmd_word0: u32 = (0xDA << 24) | 0x04; // Load projection
+        // param: G_MTX_PROJECTION | G_MTX_LOAD, G_MTX_PUSH inverted (0x07)
+        let mtx_cmd_word0: u32 = (0xDA << 24) | 0x07; // Load projection
         let mtx_cmd_word1: u32 = addr as u32;
         rdram[dl_addr..dl_addr + 4].copy_from_slice(&mtx_cmd_word0.to_be_bytes());
         rdram[dl_addr + 4..dl_addr + 8].copy_from_slice(&mtx_cmd_word1.to_be_bytes());
@@ -1750,7 +2064,7 @@ mod tests {
     #[test]
     fn test_g_dl_command() {
         let mut hle = RspHle::new();
-        hle.microcode = MicrocodeType::F3DEX;
+        hle.microcode = MicrocodeType::F3DEX2;
 
         let mut rdram = vec![0u8; 2048];
         let mut rdp = Rdp::new();
@@ -1759,7 +2073,7 @@ mod tests {
         let nested_dl_addr = 0x200;
 
         // G_VTX in nested DL
-        let vtx_cmd_word0: u32 = (0x01 << 24) | (2 << 12);
+        let vtx_cmd_word0: u32 = (0x01 << 24) | (2 << 12) | (2 << 1);
         let vtx_cmd_word1: u32 = 0x300;
         rdram[nested_dl_addr..nested_dl_addr + 4].copy_from_slice(&vtx_cmd_word0.to_be_bytes());
         rdram[nested_dl_addr + 4..nested_dl_addr + 8].copy_from_slice(&vtx_cmd_word1.to_be_bytes());
@@ -1830,55 +2144,37 @@ mod tests {
     #[test]
     fn test_matrix_stack_push_pop() {
         let mut hle = RspHle::new();
-        hle.microcode = MicrocodeType::F3DEX;
+        hle.microcode = MicrocodeType::F3DEX2;
 
         let mut rdram = vec![0u8; 2048];
         let mut rdp = Rdp::new();
 
         // Create a scaling matrix (scale by 2)
         let addr1 = 0x200;
-        let scale2_fixed: i32 = 0x00020000; // 2.0 in 16.16 fixed point
-        let one_fixed: i32 = 0x00010000;
-        let zero_fixed: i32 = 0x00000000;
-
-        for i in 0..16 {
-            let value = if i == 0 || i == 5 || i == 10 {
-                scale2_fixed
-            } else if i == 15 {
-                one_fixed
-            } else {
-                zero_fixed
-            };
-            let offset = addr1 + i * 4;
-            rdram[offset..offset + 4].copy_from_slice(&value.to_be_bytes());
-        }
+        let mut scale2 = RspHle::identity_matrix();
+        scale2[0] = 2.0;
+        scale2[5] = 2.0;
+        scale2[10] = 2.0;
+        write_matrix(&mut rdram, addr1, &scale2);
 
         // Create another matrix (scale by 3)
         let addr2 = 0x300;
-        let scale3_fixed: i32 = 0x00030000; // 3.0 in 16.16 fixed point
-
-        for i in 0..16 {
-            let value = if i == 0 || i == 5 || i == 10 {
-                scale3_fixed
-            } else if i == 15 {
-                one_fixed
-            } else {
-                zero_fixed
-            };
-            let offset = addr2 + i * 4;
-            rdram[offset..offset + 4].copy_from_slice(&value.to_be_bytes());
-        }
+        let mut scale3 = RspHle::identity_matrix();
+        scale3[0] = 3.0;
+        scale3[5] = 3.0;
+        scale3[10] = 3.0;
+        write_matrix(&mut rdram, addr2, &scale3);
 
         let dl_addr = 0x100;
 
         // G_MTX with PUSH - load scale-by-2 matrix
-        let mtx_cmd_word0: u32 = (0xDA << 24) | 0x01; // PUSH flag set
+        let mtx_cmd_word0: u32 = (0xDA << 24) | 0x02; // PUSH | LOAD, PUSH inverted
         let mtx_cmd_word1: u32 = addr1 as u32;
         rdram[dl_addr..dl_addr + 4].copy_from_slice(&mtx_cmd_word0.to_be_bytes());
         rdram[dl_addr + 4..dl_addr + 8].copy_from_slice(&mtx_cmd_word1.to_be_bytes());
 
         // G_MTX with PUSH - load scale-by-3 matrix
-        let mtx_cmd_word0_2: u32 = (0xDA << 24) | 0x01; // PUSH flag set
+        let mtx_cmd_word0_2: u32 = (0xDA << 24) | 0x02; // PUSH | LOAD, PUSH inverted
         let mtx_cmd_word1_2: u32 = addr2 as u32;
         rdram[dl_addr + 8..dl_addr + 12].copy_from_slice(&mtx_cmd_word0_2.to_be_bytes());
         rdram[dl_addr + 12..dl_addr + 16].copy_from_slice(&mtx_cmd_word1_2.to_be_bytes());
@@ -1905,7 +2201,7 @@ mod tests {
     #[test]
     fn test_g_popmtx_command() {
         let mut hle = RspHle::new();
-        hle.microcode = MicrocodeType::F3DEX;
+        hle.microcode = MicrocodeType::F3DEX2;
 
         let mut rdram = vec![0u8; 2048];
         let mut rdp = Rdp::new();
@@ -1946,7 +2242,7 @@ mod tests {
     #[test]
     fn test_g_branch_z_command() {
         let mut hle = RspHle::new();
-        hle.microcode = MicrocodeType::F3DEX;
+        hle.microcode = MicrocodeType::F3DEX2;
 
         let mut rdram = vec![0u8; 2048];
         let mut rdp = Rdp::new();
@@ -1959,7 +2255,7 @@ mod tests {
         let dl_addr = 0x100;
 
         // G_VTX - load 1 vertex
-        let vtx_cmd_word0: u32 = (0x01 << 24) | (1 << 12);
+        let vtx_cmd_word0: u32 = (0x01 << 24) | (1 << 12) | (1 << 1);
         let vtx_cmd_word1: u32 = vtx_data_addr as u32;
         rdram[dl_addr..dl_addr + 4].copy_from_slice(&vtx_cmd_word0.to_be_bytes());
         rdram[dl_addr + 4..dl_addr + 8].copy_from_slice(&vtx_cmd_word1.to_be_bytes());
@@ -1971,23 +2267,29 @@ mod tests {
         let vdata2: [u8; 16] = [0, 20, 0, 20, 0, 0, 0, 0, 0, 0, 0, 0, 128, 128, 128, 255];
         rdram[vtx2_data_addr..vtx2_data_addr + 16].copy_from_slice(&vdata2);
 
-        // Load 1 vertex at buffer index 1: (1 << 12) for count, (1 << 1) for buffer_index
-        let vtx2_cmd_word0: u32 = (0x01 << 24) | (1 << 12) | (1 << 1);
+        // Load 1 vertex at buffer index 1: (1 << 12) for count, (2 << 1) for the end index
+        let vtx2_cmd_word0: u32 = (0x01 << 24) | (1 << 12) | (2 << 1);
         let vtx2_cmd_word1: u32 = vtx2_data_addr as u32;
         rdram[branch_target..branch_target + 4].copy_from_slice(&vtx2_cmd_word0.to_be_bytes());
         rdram[branch_target + 4..branch_target + 8].copy_from_slice(&vtx2_cmd_word1.to_be_bytes());
         rdram[branch_target + 8..branch_target + 12].copy_from_slice(&0xDF000000u32.to_be_bytes());
         rdram[branch_target + 12..branch_target + 16].copy_from_slice(&0u32.to_be_bytes());
 
+        // G_RDPHALF_1 - branch target
+        let half_cmd_word0: u32 = 0xE1 << 24;
+        let half_cmd_word1: u32 = branch_target as u32;
+        rdram[dl_addr + 8..dl_addr + 12].copy_from_slice(&half_cmd_word0.to_be_bytes());
+        rdram[dl_addr + 12..dl_addr + 16].copy_from_slice(&half_cmd_word1.to_be_bytes());
+
         // G_BRANCH_Z - conditional branch to nested DL
-        let branch_cmd_word0: u32 = 0xB0 << 24; // Vertex index 0
-        let branch_cmd_word1: u32 = branch_target as u32;
-        rdram[dl_addr + 8..dl_addr + 12].copy_from_slice(&branch_cmd_word0.to_be_bytes());
-        rdram[dl_addr + 12..dl_addr + 16].copy_from_slice(&branch_cmd_word1.to_be_bytes());
+        let branch_cmd_word0: u32 = 0x04 << 24; // Vertex index 0
+        let branch_cmd_word1: u32 = 0; // Depth
+        rdram[dl_addr + 16..dl_addr + 20].copy_from_slice(&branch_cmd_word0.to_be_bytes());
+        rdram[dl_addr + 20..dl_addr + 24].copy_from_slice(&branch_cmd_word1.to_be_bytes());
 
         // G_ENDDL
-        rdram[dl_addr + 16..dl_addr + 20].copy_from_slice(&0xDF000000u32.to_be_bytes());
-        rdram[dl_addr + 20..dl_addr + 24].copy_from_slice(&0u32.to_be_bytes());
+        rdram[dl_addr + 24..dl_addr + 28].copy_from_slice(&0xDF000000u32.to_be_bytes());
+        rdram[dl_addr + 28..dl_addr + 32].copy_from_slice(&0u32.to_be_bytes());
 
         // Parse display list
         hle.parse_f3dex_display_list(&rdram, dl_addr as u32, 24, &mut rdp);
@@ -1995,4 +2297,306 @@ mod tests {
         // Verify that the branch was taken and vertex was loaded
         assert_eq!(hle.vertex_count, 2);
     }
+
+    #[test]
+    fn test_display_list_capture() {
+        let mut hle = RspHle::new();
+        hle.microcode = MicrocodeType::F3DEX2;
+        let mut rdp = Rdp::new();
+        let mut rdram = vec![0u8; 0x1000];
+        let mut dmem = [0u8; 4096];
+
+        // Task: root list at 0x100 calls a nested list at 0x200
+        dmem[0x30..0x34].copy_from_slice(&0x100u32.to_be_bytes());
+        dmem[0x34..0x38].copy_from_slice(&0x18u32.to_be_bytes());
+        let root = [
+            (0xF700_0000, 0xFF00_FF00), // G_SETFILLCOLOR
+            (0xDE00_0000, 0x0000_0200), // G_DL call
+            (0xDF00_0000, 0),           // G_ENDDL
+        ];
+        let nested = [
+            (0xF600_0000 | (80 << 12) | 80, (40 << 12) | 40), // G_FILLRECT (10,10)-(20,20)
+            (0xDF00_0000, 0),                                 // G_ENDDL
+        ];
+        for (base, list) in [(0x100, &root[..]), (0x200, &nested[..])] {
+            for (i, &(word0, word1)) in list.iter().enumerate() {
+                let addr = base + i * 8;
+                rdram[addr..addr + 4].copy_from_slice(&u32::to_be_bytes(word0));
+                rdram[addr + 4..addr + 8].copy_from_slice(&u32::to_be_bytes(word1));
+            }
+        }
+
+        // Nothing is recorded unless requested
+        hle.execute_task(&dmem, &rdram, &mut Rdp::new());
+        assert!(hle.take_capture().is_none());
+
+        hle.request_capture();
+        hle.execute_task(&dmem, &rdram, &mut rdp);
+        let mut capture = hle.take_capture().expect("capture recorded");
+
+        let depths: Vec<u8> = capture.steps().iter().map(|s| s.command.depth).collect();
+        assert_eq!(depths, vec![0, 0, 1, 1, 0]);
+        // Only the nested fill changed pixels
+        let changed: Vec<usize> = capture.steps().iter().map(|s| s.changes.len()).collect();
+        assert_eq!(changed, vec![0, 0, 100, 0, 0]);
+        assert_eq!(
+            capture.changed_bounds(&capture.steps()[2]),
+            Some((10, 10, 19, 19))
+        );
+        assert!(capture.dump().contains("G_FILLRECT (10,10)-(20,20)"));
+
+        // Capturing is one-shot
+        hle.execute_task(&dmem, &rdram, &mut rdp);
+        assert!(hle.take_capture().is_none());
+
+        capture.seek(capture.steps().len());
+        assert_eq!(capture.frame()[15 * 320 + 15], 0xFF00FF00);
+    }
+
+    /// Three red vertices making a triangle around the screen center once
+    /// projected with a 1/128 scale
+    fn write_triangle_vertices(rdram: &mut [u8], addr: usize) {
+        for (i, (x, y)) in [(-64i16, -64i16), (64, -64), (0, 64)].iter().enumerate() {
+            let v = addr + i * 16;
+            rdram[v..v + 2].copy_from_slice(&x.to_be_bytes());
+            rdram[v + 2..v + 4].copy_from_slice(&y.to_be_bytes());
+            rdram[v + 12..v + 16].copy_from_slice(&[255, 0, 0, 255]);
+        }
+    }
+
+    /// Write a projection matrix scaling x, y and z by 1/128
+    fn write_projection(rdram: &mut [u8], addr: usize) {
+        let mut projection = RspHle::identity_matrix();
+        projection[0] = 1.0 / 128.0;
+        projection[5] = 1.0 / 128.0;
+        projection[10] = 1.0 / 128.0;
+        write_matrix(rdram, addr, &projection);
+    }
+
+    #[test]
+    fn test_f3dex_gbi1_display_list() {
+        let mut hle = RspHle::new();
+        hle.microcode = MicrocodeType::F3DEX;
+        let mut rdram = vec![0u8; 0x1000];
+        let mut rdp = Rdp::new();
+
+        write_projection(&mut rdram, 0x400);
+        write_triangle_vertices(&mut rdram, 0x600);
+        write_display_list(
+            &mut rdram,
+            0x100,
+            &[
+                (0xBC00_0C06, 0x0000_0600), // G_MOVEWORD segment 3 = 0x600
+                (0x0106_0040, 0x0000_0400), // G_MTX projection, load
+                (0x0400_0C2F, 0x0300_0000), // G_VTX 3 vertices from segment 3
+                (0xBF00_0000, 0x0000_0204), // G_TRI1 0, 1, 2
+                (0xB800_0000, 0),           // G_ENDDL
+            ],
+        );
+
+        hle.parse_f3dex_display_list(&rdram, 0x100, 40, &mut rdp);
+
+        assert_eq!(hle.segments[3], 0x600);
+        assert_eq!(hle.vertex_count, 3);
+        assert_eq!(hle.vertices[1].pos, [64, -64, 0]);
+        assert_eq!(hle.projection_matrix[0], 1.0 / 128.0);
+        rdp.finish_frame();
+        assert_eq!(rdp.get_frame().pixels[150 * 320 + 160], 0xFFFF0000);
+    }
+
+    #[test]
+    fn test_f3dex_culling() {
+        // Front faces are clockwise on screen
+        for (cull, drawn) in [(F3DEX_CULL_FRONT, false), (F3DEX_CULL_BACK, true)] {
+            let mut hle = RspHle::new();
+            hle.microcode = MicrocodeType::F3DEX;
+            let mut rdram = vec![0u8; 0x1000];
+            let mut rdp = Rdp::new();
+
+            write_projection(&mut rdram, 0x400);
+            write_triangle_vertices(&mut rdram, 0x600);
+            write_display_list(
+                &mut rdram,
+                0x100,
+                &[
+                    (0xB700_0000, cull),        // G_SETGEOMETRYMODE
+                    (0x0106_0040, 0x0000_0400), // G_MTX projection, load
+                    (0x0400_0C2F, 0x0000_0600), // G_VTX 3 vertices
+                    (0xBF00_0000, 0x0000_0204), // G_TRI1 0, 1, 2
+                    (0xB800_0000, 0),           // G_ENDDL
+                ],
+            );
+
+            hle.parse_f3dex_display_list(&rdram, 0x100, 40, &mut rdp);
+
+            rdp.finish_frame();
+            let pixel = rdp.get_frame().pixels[150 * 320 + 160];
+            assert_eq!(pixel == 0xFFFF0000, drawn, "cull mode 0x{:04X}", cull);
+        }
+    }
+
+    #[test]
+    fn test_f3dex2_segmented_display_list_call() {
+        let mut hle = RspHle::new();
+        hle.microcode = MicrocodeType::F3DEX2;
+        let mut rdram = vec![0u8; 0x1000];
+        let mut rdp = Rdp::new();
+
+        write_triangle_vertices(&mut rdram, 0x600);
+        write_display_list(
+            &mut rdram,
+            0x100,
+            &[
+                (0xDB06_0018, 0x8000_0400), // G_MOVEWORD segment 6 = 0x400
+                (0xDE00_0000, 0x0600_0000), // G_DL call segment 6
+                (0xDF00_0000, 0),           // G_ENDDL
+            ],
+        );
+        write_display_list(
+            &mut rdram,
+            0x400,
+            &[
+                (0x0100_3006, 0x0600_0200), // G_VTX 3 vertices from segment 6
+                (0xDF00_0000, 0),           // G_ENDDL
+            ],
+        );
+
+        hle.parse_f3dex_display_list(&rdram, 0x100, 24, &mut rdp);
+
+        assert_eq!(hle.segments[6], 0x400);
+        assert_eq!(hle.segment_address(0x0600_0010), 0x410);
+        assert_eq!(hle.segment_address(0x8000_0010), 0x10);
+        assert_eq!(hle.vertex_count, 3);
+        assert_eq!(hle.vertices[2].pos, [0, 64, 0]);
+    }
+
+    #[test]
+    fn test_f3dex2_lighting() {
+        let mut hle = RspHle::new();
+        hle.microcode = MicrocodeType::F3DEX2;
+        let mut rdram = vec![0u8; 0x1000];
+        let mut rdp = Rdp::new();
+
+        // Red light shining along +Z, then a dim blue ambient light
+        rdram[0x300..0x303].copy_from_slice(&[255, 0, 0]);
+        rdram[0x308..0x30B].copy_from_slice(&[0, 0, 127]);
+        rdram[0x310..0x313].copy_from_slice(&[0, 0, 51]);
+        // Vertex with a +Z normal in place of its color
+        rdram[0x20C..0x210].copy_from_slice(&[0, 0, 127, 255]);
+        write_display_list(
+            &mut rdram,
+            0x100,
+            &[
+                (0xD9FF_FFFF, G_LIGHTING),  // G_GEOMETRYMODE
+                (0xDB02_0000, 24),          // G_MOVEWORD 1 light
+                (0xDC08_060A, 0x0000_0300), // G_MOVEMEM light 0
+                (0xDC08_090A, 0x0000_0310), // G_MOVEMEM light 1 (ambient)
+                (0x0100_1002, 0x0000_0200), // G_VTX 1 vertex
+                (0xDF00_0000, 0),           // G_ENDDL
+            ],
+        );
+
+        hle.parse_f3dex_display_list(&rdram, 0x100, 48, &mut rdp);
+
+        assert_eq!(hle.num_lights, 1);
+        assert_eq!(hle.vertices[0].color, [255, 0, 51, 255]);
+    }
+
+    #[test]
+    fn test_f3dex2_texture_rectangle() {
+        let mut hle = RspHle::new();
+        hle.microcode = MicrocodeType::F3DEX2;
+        let mut rdram = vec![0u8; 0x1000];
+        let mut rdp = Rdp::new();
+
+        // 4x2 RGBA16 texture: red, green on the first row, blue, white on the second
+        let texels: [u16; 8] = [0xF801, 0x07C1, 0, 0, 0x003F, 0xFFFF, 0, 0];
+        for (i, texel) in texels.iter().enumerate() {
+            rdram[0x800 + i * 2..0x802 + i * 2].copy_from_slice(&texel.to_be_bytes());
+        }
+        write_display_list(
+            &mut rdram,
+            0x100,
+            &[
+                (0xDB06_0008, 0x0000_0800), // G_MOVEWORD segment 2 = 0x800
+                (0xFD10_0003, 0x0200_0000), // G_SETTIMG from segment 2
+                (0xF510_0200, 0x0000_4010), // G_SETTILE 0, RGBA16, 2x2 mask
+                (0xF300_0000, 0x0000_7000), // G_LOADBLOCK 8 texels
+                (0xE403_8038, 0x0002_8028), // G_TEXRECT (10,10)-(14,14)
+                (0xE100_0000, 0),           // G_RDPHALF_1 s=0, t=0
+                (0xF100_0000, 0x0400_0400), // G_RDPHALF_2 dsdx=1, dtdy=1
+                (0xE900_0000, 0),           // G_RDPFULLSYNC
+                (0xDF00_0000, 0),           // G_ENDDL
+            ],
+        );
+
+        hle.parse_f3dex_display_list(&rdram, 0x100, 72, &mut rdp);
+        rdp.finish_frame();
+        let pixels = &rdp.get_frame().pixels;
+
+        let red = |p: u32| (p >> 16) & 0xFF;
+        let green = |p: u32| (p >> 8) & 0xFF;
+        let blue = |p: u32| p & 0xFF;
+        assert!(red(pixels[10 * 320 + 10]) > 200);
+        assert!(green(pixels[10 * 320 + 11]) > 200);
+        assert!(blue(pixels[11 * 320 + 10]) > 200);
+        // The 2x2 mask repeats the texture
+        assert!(red(pixels[12 * 320 + 12]) > 200);
+        // Lower-right edge is exclusive in 1-cycle mode
+        assert_eq!(pixels[14 * 320 + 14], 0);
+
+        // The full sync raises the DP interrupt once
+        assert!(hle.take_full_sync());
+        assert!(!hle.take_full_sync());
+    }
+
+    #[test]
+    fn test_os_task_microcode_detection() {
+        let task = |rdram: &mut Vec<u8>, name: &str| {
+            rdram.fill(0);
+            let text = format!("RSP Gfx ucode {}  Yoshitaka Yasumoto 1998", name);
+            rdram[0x1040..0x1040 + text.len()].copy_from_slice(text.as_bytes());
+            let mut dmem = [0u8; 4096];
+            dmem[0xFC0..0xFC4].copy_from_slice(&M_GFXTASK.to_be_bytes());
+            dmem[0xFD8..0xFDC].copy_from_slice(&0x8000_1000u32.to_be_bytes());
+            dmem[0xFDC..0xFE0].copy_from_slice(&0x800u32.to_be_bytes());
+            dmem
+        };
+        let mut rdram = vec![0u8; 0x2000];
+        let mut rdp = Rdp::new();
+
+        for (name, expected) in [
+            ("F3DEX       fifo 2.08", MicrocodeType::F3DEX2),
+            ("F3DZEX      fifo 2.08J", MicrocodeType::F3DEX2),
+            ("F3DEX       fifo 1.23", MicrocodeType::F3DEX),
+            ("F3DLX.Rej   fifo 1.21", MicrocodeType::F3DEX),
+            ("S2DEX  fifo 1.05", MicrocodeType::Unknown),
+        ] {
+            let mut hle = RspHle::new();
+            hle.segments[3] = 0x1234;
+            hle.matrix_stack_ptr = 2;
+            let dmem = task(&mut rdram, name);
+            hle.execute_task(&dmem, &rdram, &mut rdp);
+            assert_eq!(hle.microcode, expected, "{}", name);
+            if expected != MicrocodeType::Unknown {
+                // Each task starts with the microcode's initial state
+                assert_eq!(hle.segments[3], 0);
+                assert_eq!(hle.matrix_stack_ptr, 0);
+            }
+        }
+
+        // Without a name the microcode detected from IMEM is kept
+        let mut dmem = task(&mut rdram, "");
+        dmem[0xFD8..0xFDC].copy_from_slice(&0u32.to_be_bytes());
+        rdram[0..0x2000].fill(0);
+        let mut hle = RspHle::new();
+        hle.microcode = MicrocodeType::F3DEX;
+        hle.execute_task(&dmem, &rdram, &mut rdp);
+        assert_eq!(hle.microcode, MicrocodeType::F3DEX);
+
+        // Audio tasks select the audio microcode
+        dmem[0xFC0..0xFC4].copy_from_slice(&M_AUDTASK.to_be_bytes());
+        hle.execute_task(&dmem, &rdram, &mut rdp);
+        assert_eq!(hle.microcode, MicrocodeType::Audio);
+    }
 }
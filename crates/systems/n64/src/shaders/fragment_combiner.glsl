@@ -0,0 +1,58 @@
+#version 330 core
+
+// Fragment shader approximating the RDP color combiner
+// Evaluates (A - B) * C + D for color and alpha using cycle 0 inputs.
+// Input codes match CombinerInput in rdp_renderer.rs.
+
+uniform sampler2D uTexture;  // Texel 0 (CPU-sampled texture)
+uniform bool uTextured;      // Batch has a texture bound
+uniform bool uCombine;       // Evaluate the combiner (false = pass through)
+uniform ivec4 uColorInputs;  // Color A, B, C, D
+uniform ivec4 uAlphaInputs;  // Alpha A, B, C, D
+uniform vec4 uPrimColor;     // Primitive color (RGBA)
+uniform vec4 uEnvColor;      // Environment color (RGBA)
+
+in vec4 vColor;     // Interpolated shade color
+in vec2 vTexCoord;  // Interpolated texture coordinates
+
+out vec4 FragColor;
+
+vec4 combinerInput(int code, vec4 shade, vec4 texel) {
+    switch (code) {
+        case 1: return vec4(1.0);
+        case 2: return shade;   // Combined: cycle 0 has only the shade color
+        case 3: return texel;
+        case 4: return texel;   // Texel 1: single texture, reuse texel 0
+        case 5: return uPrimColor;
+        case 6: return shade;
+        case 7: return uEnvColor;
+        case 8: return vec4(shade.a);
+        case 9: return vec4(texel.a);
+        case 10: return vec4(texel.a);
+        case 11: return vec4(uPrimColor.a);
+        case 12: return vec4(shade.a);
+        case 13: return vec4(uEnvColor.a);
+        default: return vec4(0.0);
+    }
+}
+
+void main() {
+    vec4 shade = vColor;
+    vec4 texel = uTextured ? texture(uTexture, vTexCoord) : vec4(1.0);
+
+    if (!uCombine) {
+        FragColor = uTextured ? texel : shade;
+        return;
+    }
+
+    vec3 color = (combinerInput(uColorInputs.x, shade, texel).rgb
+                  - combinerInput(uColorInputs.y, shade, texel).rgb)
+                 * combinerInput(uColorInputs.z, shade, texel).rgb
+                 + combinerInput(uColorInputs.w, shade, texel).rgb;
+    float alpha = (combinerInput(uAlphaInputs.x, shade, texel).a
+                   - combinerInput(uAlphaInputs.y, shade, texel).a)
+                  * combinerInput(uAlphaInputs.z, shade, texel).a
+                  + combinerInput(uAlphaInputs.w, shade, texel).a;
+
+    FragColor = clamp(vec4(color, alpha), 0.0, 1.0);
+}
@@ -1,17 +1,18 @@
 #version 330 core
 
-// Vertex shader for N64 RDP triangle rendering
-// Supports both 2D and 3D rendering with color and depth
+// Vertex shader for batched N64 RDP primitives
+// Every primitive uses the same layout so one draw call can cover a batch
 
 layout(location = 0) in vec2 aPosition;  // Vertex position (normalized device coords)
-layout(location = 1) in vec4 aColor;     // Vertex color (RGBA)
-layout(location = 2) in float aDepth;    // Vertex depth (for Z-buffer)
+layout(location = 1) in float aDepth;    // Vertex depth (0.0-1.0, for Z-buffer)
+layout(location = 2) in vec4 aColor;     // Shade color (RGBA)
+layout(location = 3) in vec2 aTexCoord;  // Texture coordinates (normalized s, t)
 
-out vec4 vColor;   // Pass color to fragment shader
-out float vDepth;  // Pass depth to fragment shader
+out vec4 vColor;     // Pass shade color to fragment shader
+out vec2 vTexCoord;  // Pass texture coordinates to fragment shader
 
 void main() {
-    gl_Position = vec4(aPosition, 0.0, 1.0);
+    gl_Position = vec4(aPosition, aDepth * 2.0 - 1.0, 1.0);
     vColor = aColor;
-    vDepth = aDepth;
+    vTexCoord = aTexCoord;
 }
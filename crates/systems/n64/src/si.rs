@@ -0,0 +1,125 @@
+//! SI (Serial Interface) - DMA between RDRAM and PIF RAM
+//!
+//! Games talk to the controllers through the 64-byte joybus area at the end
+//! of PIF RAM. The SI copies a command block from RDRAM into that area, the
+//! PIF runs it, and a second DMA copies the responses back to RDRAM. Each
+//! finished DMA raises the SI interrupt in the MI.
+//!
+//! ## Memory Map
+//!
+//! SI registers are memory-mapped at 0x04800000-0x0480001B:
+//! - 0x04800000: SI_DRAM_ADDR - RDRAM address of the 64-byte block
+//! - 0x04800004: SI_PIF_ADDR_RD64B - Write starts a PIF RAM to RDRAM DMA
+//! - 0x04800010: SI_PIF_ADDR_WR64B - Write starts an RDRAM to PIF RAM DMA
+//! - 0x04800018: SI_STATUS - Status (read), write clears the SI interrupt
+//!
+//! DMAs complete instantly, so the busy bits always read as clear.
+
+use serde::{Deserialize, Serialize};
+
+/// SI register offsets (relative to 0x04800000)
+const SI_DRAM_ADDR: u32 = 0x00;
+const SI_PIF_ADDR_RD64B: u32 = 0x04;
+const SI_PIF_ADDR_WR64B: u32 = 0x10;
+const SI_STATUS: u32 = 0x18;
+
+/// SI_STATUS interrupt bit
+const SI_STATUS_INTERRUPT: u32 = 1 << 12;
+
+/// Size of an SI DMA (the PIF RAM joybus area)
+pub const SI_DMA_SIZE: usize = 64;
+
+/// DMA requested by an SI register write
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SiDma {
+    /// Copy the joybus area of PIF RAM to RDRAM at the given address
+    PifToRdram(u32),
+    /// Copy 64 bytes of RDRAM at the given address into the joybus area
+    RdramToPif(u32),
+}
+
+/// Serial Interface (SI)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SerialInterface {
+    /// SI_DRAM_ADDR register
+    dram_addr: u32,
+    /// Interrupt raised by the last DMA and not yet acknowledged
+    interrupt: bool,
+}
+
+impl SerialInterface {
+    /// Create a new Serial Interface
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read from SI register
+    pub fn read_register(&self, offset: u32) -> u32 {
+        match offset {
+            SI_DRAM_ADDR => self.dram_addr,
+            SI_STATUS if self.interrupt => SI_STATUS_INTERRUPT,
+            _ => 0,
+        }
+    }
+
+    /// Write to SI register, returning the DMA it starts
+    pub fn write_register(&mut self, offset: u32, value: u32) -> Option<SiDma> {
+        // Blocks are 8-byte aligned in RDRAM
+        let dram_addr = self.dram_addr & 0x00FF_FFF8;
+        match offset {
+            SI_DRAM_ADDR => {
+                self.dram_addr = value & 0x00FF_FFFF;
+                None
+            }
+            SI_PIF_ADDR_RD64B => Some(SiDma::PifToRdram(dram_addr)),
+            SI_PIF_ADDR_WR64B => Some(SiDma::RdramToPif(dram_addr)),
+            SI_STATUS => {
+                self.interrupt = false;
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// Flag a finished DMA (the bus raises the MI interrupt)
+    pub fn raise_interrupt(&mut self) {
+        self.interrupt = true;
+    }
+
+    /// Whether a finished DMA has not been acknowledged yet
+    pub fn interrupt_pending(&self) -> bool {
+        self.interrupt
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dma_requests_use_dram_addr() {
+        let mut si = SerialInterface::new();
+        assert_eq!(si.write_register(SI_DRAM_ADDR, 0x8000_1234), None);
+        assert_eq!(si.read_register(SI_DRAM_ADDR), 0x0000_1234);
+
+        assert_eq!(
+            si.write_register(SI_PIF_ADDR_WR64B, 0x1FC0_07C0),
+            Some(SiDma::RdramToPif(0x1230))
+        );
+        assert_eq!(
+            si.write_register(SI_PIF_ADDR_RD64B, 0x1FC0_07C0),
+            Some(SiDma::PifToRdram(0x1230))
+        );
+    }
+
+    #[test]
+    fn test_status_write_acknowledges_interrupt() {
+        let mut si = SerialInterface::new();
+        si.raise_interrupt();
+        assert_eq!(si.read_register(SI_STATUS), SI_STATUS_INTERRUPT);
+
+        si.write_register(SI_STATUS, 0);
+        assert!(!si.interrupt_pending());
+        assert_eq!(si.read_register(SI_STATUS), 0);
+    }
+}
@@ -5,6 +5,26 @@
 //! - Generating video output (NTSC/PAL timing)
 //! - Handling display configuration (resolution, format, etc.)
 //!
+//! ## Scanout
+//!
+//! Each frame the VI reads the image at VI_ORIGIN, VI_WIDTH pixels per line,
+//! in the 16-bit (RGBA 5551) or 32-bit (RGBA 8888) format from VI_STATUS.
+//! The size of the image comes from the display window (H_START/V_START)
+//! and the scale registers, so a 320x240 framebuffer shown across the
+//! 640-pixel NTSC window with X_SCALE 0x200 scans out at 320x240.
+//!
+//! With the serrate bit set the output is interlaced: the VI alternates
+//! fields each frame, and the game points VI_ORIGIN at the even or odd
+//! lines of its framebuffer. The fields are woven into one frame of twice
+//! the height.
+//!
+//! ## Timing
+//!
+//! VI_V_SYNC holds the number of half-lines per frame minus one (0x20D for
+//! 263 NTSC lines, 0x20C for interlaced fields of 262 and 263 lines).
+//! VI_CURRENT counts half-lines, and the VI interrupt fires when the line
+//! reaches VI_INTR. Writing VI_CURRENT acknowledges the interrupt.
+//!
 //! ## Memory Map
 //!
 //! VI registers are memory-mapped at 0x04400000-0x04400037:
@@ -12,7 +32,7 @@
 //! - 0x04400004: VI_ORIGIN - Framebuffer origin address
 //! - 0x04400008: VI_WIDTH - Framebuffer line width
 //! - 0x0440000C: VI_INTR - Vertical interrupt
-//! - 0x04400010: VI_CURRENT - Current scanline
+//! - 0x04400010: VI_CURRENT - Current half-line
 //! - 0x04400014: VI_BURST - Timing register
 //! - 0x04400018: VI_V_SYNC - Vertical sync
 //! - 0x0440001C: VI_H_SYNC - Horizontal sync
@@ -23,12 +43,15 @@
 //! - 0x04400030: VI_X_SCALE - Horizontal scale
 //! - 0x04400034: VI_Y_SCALE - Vertical scale
 
+use emu_core::types::Frame;
+use serde::{Deserialize, Serialize};
+
 /// VI register offsets (relative to 0x04400000)
 const VI_STATUS: u32 = 0x00;
 const VI_ORIGIN: u32 = 0x04;
 const VI_WIDTH: u32 = 0x08;
 const VI_INTR: u32 = 0x0C;
-const VI_CURRENT: u32 = 0x10;
+pub const VI_CURRENT: u32 = 0x10;
 const VI_BURST: u32 = 0x14;
 const VI_V_SYNC: u32 = 0x18;
 const VI_H_SYNC: u32 = 0x1C;
@@ -40,9 +63,8 @@ const VI_X_SCALE: u32 = 0x30;
 const VI_Y_SCALE: u32 = 0x34;
 
 /// VI_STATUS register bits
-#[allow(dead_code)]
+const VI_STATUS_TYPE_MASK: u32 = 0x03; // 0 = blank, 1 = reserved
 const VI_STATUS_TYPE_16: u32 = 0x02; // 16-bit color mode
-#[allow(dead_code)]
 const VI_STATUS_TYPE_32: u32 = 0x03; // 32-bit color mode
 #[allow(dead_code)]
 const VI_STATUS_GAMMA_DITHER: u32 = 0x04;
@@ -50,19 +72,23 @@ const VI_STATUS_GAMMA_DITHER: u32 = 0x04;
 const VI_STATUS_GAMMA: u32 = 0x08;
 #[allow(dead_code)]
 const VI_STATUS_DIVOT: u32 = 0x10;
-#[allow(dead_code)]
-const VI_STATUS_SERRATE: u32 = 0x40;
+const VI_STATUS_SERRATE: u32 = 0x40; // Interlaced output
 #[allow(dead_code)]
 const VI_STATUS_AA_MODE_SHIFT: u32 = 8;
 
+/// Largest image the VI scans out (the VI_WIDTH register allows 4095)
+const MAX_WIDTH: u32 = 640;
+const MAX_HEIGHT: u32 = 480;
+
 /// Video Interface controller
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VideoInterface {
     /// VI registers
     status: u32,
     origin: u32,  // Framebuffer address in RDRAM
     width: u32,   // Width in pixels
     intr: u32,    // Vertical interrupt scanline
-    current: u32, // Current scanline
+    current: u32, // Current half-line
     burst: u32,
     v_sync: u32, // Vertical sync period
     h_sync: u32, // Horizontal sync period
@@ -72,6 +98,11 @@ pub struct VideoInterface {
     v_burst: u32,
     x_scale: u32, // Horizontal scale factor
     y_scale: u32, // Vertical scale factor
+    /// Field being displayed when interlaced (false = even)
+    field: bool,
+    /// Frame the fields are woven into; rebuilt from RDRAM when missing
+    #[serde(skip)]
+    woven: Option<Frame>,
 }
 
 impl VideoInterface {
@@ -88,10 +119,12 @@ impl VideoInterface {
             h_sync: 0x0C15,    // NTSC horizontal sync
             leap: 0x0C150C15,
             h_start: 0x006C02EC, // NTSC horizontal start/end
-            v_start: 0x002502FF, // NTSC vertical start/end
+            v_start: 0x002501FF, // NTSC vertical start/end (240 lines)
             v_burst: 0x000E0204,
             x_scale: 0x0200, // 1:1 scale
             y_scale: 0x0400, // 1:1 scale
+            field: false,
+            woven: None,
         }
     }
 
@@ -134,7 +167,13 @@ impl VideoInterface {
         }
 
         match offset {
-            VI_STATUS => self.status = value,
+            VI_STATUS => {
+                if (value ^ self.status) & VI_STATUS_SERRATE != 0 {
+                    self.field = false;
+                    self.woven = None;
+                }
+                self.status = value;
+            }
             VI_ORIGIN => self.origin = value & 0x00FFFFFF, // 24-bit address
             VI_WIDTH => self.width = value & 0xFFF,
             VI_INTR => {
@@ -148,8 +187,8 @@ impl VideoInterface {
                 });
             }
             VI_CURRENT => {
-                // Writing to VI_CURRENT clears interrupt
-                self.current = 0;
+                // Writing to VI_CURRENT acknowledges the interrupt (cleared
+                // in the MI by the bus); the line counter keeps running
             }
             VI_BURST => self.burst = value,
             VI_V_SYNC => self.v_sync = value & 0x3FF,
@@ -167,14 +206,26 @@ impl VideoInterface {
     /// Update current scanline (called per frame)
     /// Returns true if scanline matches VI_INTR and interrupt should be triggered
     pub fn update_scanline(&mut self, scanline: u32) -> bool {
-        self.current = scanline;
+        // VI_CURRENT counts half-lines; its low bit is the field
+        self.current = (scanline << 1) | self.field as u32;
 
         // Check if scanline matches interrupt line
         scanline == (self.intr >> 1) // VI_INTR is stored as scanline * 2
     }
 
+    /// Number of lines in the current field (frame, when not interlaced)
+    pub fn lines_per_field(&self) -> u32 {
+        // An odd number of half-lines splits into fields of n and n+1 lines
+        let half_lines = (self.v_sync & 0x3FF) + 1 + self.field as u32;
+        (half_lines / 2).max(1)
+    }
+
+    /// Finish the frame, moving on to the next field when interlaced
+    pub fn end_frame(&mut self) {
+        self.field = self.is_interlaced() && !self.field;
+    }
+
     /// Get the framebuffer origin address
-    #[allow(dead_code)] // Reserved for future use
     pub fn get_framebuffer_origin(&self) -> u32 {
         self.origin
     }
@@ -186,7 +237,6 @@ impl VideoInterface {
     }
 
     /// Check if display is enabled
-    #[allow(dead_code)] // Reserved for future use
     pub fn is_enabled(&self) -> bool {
         self.status & 0x03 != 0
     }
@@ -196,6 +246,125 @@ impl VideoInterface {
     pub fn get_color_depth(&self) -> u32 {
         self.status & 0x03
     }
+
+    /// Check if the output is interlaced (serrate bit)
+    pub fn is_interlaced(&self) -> bool {
+        self.status & VI_STATUS_SERRATE != 0
+    }
+
+    /// Bytes per pixel of the framebuffer, or None while the display is blank
+    fn bytes_per_pixel(&self) -> Option<u32> {
+        match self.status & VI_STATUS_TYPE_MASK {
+            VI_STATUS_TYPE_16 => Some(2),
+            VI_STATUS_TYPE_32 => Some(4),
+            _ => None,
+        }
+    }
+
+    /// Size of the image read from RDRAM each field
+    fn field_size(&self) -> Option<(u32, u32)> {
+        let h_start = (self.h_start >> 16) & 0x3FF;
+        let h_end = self.h_start & 0x3FF;
+        let v_start = (self.v_start >> 16) & 0x3FF;
+        let v_end = self.v_start & 0x3FF;
+
+        // Scales are 2.10 fixed point source pixels per output pixel; the
+        // vertical window is in half-lines
+        let width = (h_end.saturating_sub(h_start) * (self.x_scale & 0xFFF)) >> 10;
+        let height = ((v_end.saturating_sub(v_start) >> 1) * (self.y_scale & 0xFFF)) >> 10;
+
+        let width = width.min(self.width).min(MAX_WIDTH);
+        let height = height.min(MAX_HEIGHT);
+        (width > 0 && height > 0).then_some((width, height))
+    }
+
+    /// Size of the scanned out frame, or None while the display is blank
+    pub fn resolution(&self) -> Option<(u32, u32)> {
+        self.bytes_per_pixel()?;
+        let (width, height) = self.field_size()?;
+        if self.is_interlaced() {
+            Some((width, (height * 2).min(MAX_HEIGHT)))
+        } else {
+            Some((width, height))
+        }
+    }
+
+    /// Bytes of RDRAM covered by one field, starting at VI_ORIGIN
+    pub fn image_bytes(&self) -> u32 {
+        match (self.bytes_per_pixel(), self.field_size()) {
+            (Some(bpp), Some((_, height))) => self.width * bpp * height,
+            _ => 0,
+        }
+    }
+
+    /// Read the current field from RDRAM
+    ///
+    /// Returns None while the display is blank. Interlaced fields are woven
+    /// into the previous frame; the first field after a mode change fills
+    /// both.
+    pub fn scanout(&mut self, rdram: &[u8]) -> Option<Frame> {
+        let bpp = self.bytes_per_pixel()?;
+        let (width, height) = self.field_size()?;
+        let (_, frame_height) = self.resolution()?;
+
+        let mut frame = match self.woven.take() {
+            Some(frame) if frame.width == width && frame.height == frame_height => frame,
+            _ => Frame::new(width, frame_height),
+        };
+        let fresh = frame.pixels.iter().all(|&p| p == 0);
+
+        let stride = (self.width * bpp) as usize;
+        for y in 0..height {
+            let row = self.origin as usize + y as usize * stride;
+            for x in 0..width {
+                let addr = row + (x * bpp) as usize;
+                let color = match bpp {
+                    2 => read_rgba5551(rdram, addr),
+                    _ => read_rgba8888(rdram, addr),
+                };
+
+                if self.is_interlaced() {
+                    let line = y * 2 + self.field as u32;
+                    if line < frame_height {
+                        frame.pixels[(line * width + x) as usize] = color;
+                    }
+                    // Until the other field arrives, show this one doubled
+                    let other = y * 2 + !self.field as u32;
+                    if fresh && other < frame_height {
+                        frame.pixels[(other * width + x) as usize] = color;
+                    }
+                } else {
+                    frame.pixels[(y * width + x) as usize] = color;
+                }
+            }
+        }
+
+        if self.is_interlaced() {
+            self.woven = Some(frame.clone());
+        }
+        Some(frame)
+    }
+}
+
+/// Read a big-endian RGBA 5551 pixel as 0xAARRGGBB (black past the end of RDRAM)
+fn read_rgba5551(rdram: &[u8], addr: usize) -> u32 {
+    let Some(bytes) = rdram.get(addr..addr + 2) else {
+        return 0xFF000000;
+    };
+    let pixel = u16::from_be_bytes([bytes[0], bytes[1]]) as u32;
+    let expand = |c: u32| (c << 3) | (c >> 2);
+    let r = expand((pixel >> 11) & 0x1F);
+    let g = expand((pixel >> 6) & 0x1F);
+    let b = expand((pixel >> 1) & 0x1F);
+    0xFF000000 | (r << 16) | (g << 8) | b
+}
+
+/// Read a big-endian RGBA 8888 pixel as 0xAARRGGBB (black past the end of RDRAM)
+fn read_rgba8888(rdram: &[u8], addr: usize) -> u32 {
+    let Some(bytes) = rdram.get(addr..addr + 4) else {
+        return 0xFF000000;
+    };
+    0xFF000000 | (u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) >> 8)
 }
 
 impl Default for VideoInterface {
@@ -247,11 +416,17 @@ mod tests {
     fn test_vi_current_register() {
         let mut vi = VideoInterface::new();
         vi.update_scanline(100);
-        assert_eq!(vi.read_register(VI_CURRENT), 100);
+        assert_eq!(vi.read_register(VI_CURRENT), 200);
 
-        // Writing to VI_CURRENT clears it
+        // Writing to VI_CURRENT only acknowledges the interrupt
         vi.write_register(VI_CURRENT, 0);
-        assert_eq!(vi.read_register(VI_CURRENT), 0);
+        assert_eq!(vi.read_register(VI_CURRENT), 200);
+
+        // The second field of an interlaced frame counts odd half-lines
+        vi.write_register(VI_STATUS, VI_STATUS_TYPE_16 | VI_STATUS_SERRATE);
+        vi.end_frame();
+        vi.update_scanline(100);
+        assert_eq!(vi.read_register(VI_CURRENT), 201);
     }
 
     #[test]
@@ -320,4 +495,107 @@ mod tests {
         vi.write_register(VI_INTR, 0xFFFFFFFF);
         assert_eq!(vi.read_register(VI_INTR), 0x3FF);
     }
+
+    #[test]
+    fn test_vi_lines_per_field() {
+        let mut vi = VideoInterface::new();
+        assert_eq!(vi.lines_per_field(), 263);
+
+        // PAL
+        vi.write_register(VI_V_SYNC, 0x271);
+        assert_eq!(vi.lines_per_field(), 313);
+
+        // Interlaced NTSC: 525 half-lines alternate 262 and 263 line fields
+        vi.write_register(VI_V_SYNC, 0x20C);
+        vi.write_register(VI_STATUS, VI_STATUS_TYPE_16 | VI_STATUS_SERRATE);
+        assert_eq!(vi.lines_per_field(), 262);
+        vi.end_frame();
+        assert_eq!(vi.lines_per_field(), 263);
+        vi.end_frame();
+        assert_eq!(vi.lines_per_field(), 262);
+    }
+
+    /// Set up a 320x240 framebuffer at 0x100000 in the given color mode
+    fn setup_320x240(vi: &mut VideoInterface, status: u32) {
+        vi.write_register(VI_STATUS, status);
+        vi.write_register(VI_ORIGIN, 0x0010_0000);
+        vi.write_register(VI_WIDTH, 320);
+    }
+
+    #[test]
+    fn test_vi_blank_has_no_scanout() {
+        let mut vi = VideoInterface::new();
+        assert_eq!(vi.resolution(), None);
+        assert!(vi.scanout(&[0; 1024]).is_none());
+    }
+
+    #[test]
+    fn test_vi_scanout_16bit() {
+        let mut vi = VideoInterface::new();
+        setup_320x240(&mut vi, VI_STATUS_TYPE_16);
+        assert_eq!(vi.resolution(), Some((320, 237)));
+        assert_eq!(vi.image_bytes(), 320 * 2 * 237);
+
+        let mut rdram = vec![0u8; 0x0020_0000];
+        // Red at (0,0), green at (319,0), white at (5,10)
+        rdram[0x10_0000..0x10_0002].copy_from_slice(&0xF801u16.to_be_bytes());
+        let addr = 0x10_0000 + 319 * 2;
+        rdram[addr..addr + 2].copy_from_slice(&0x07C1u16.to_be_bytes());
+        let addr = 0x10_0000 + (10 * 320 + 5) * 2;
+        rdram[addr..addr + 2].copy_from_slice(&0xFFFFu16.to_be_bytes());
+
+        let frame = vi.scanout(&rdram).unwrap();
+        assert_eq!((frame.width, frame.height), (320, 237));
+        assert_eq!(frame.pixels[0], 0xFFFF0000);
+        assert_eq!(frame.pixels[319], 0xFF00FF00);
+        assert_eq!(frame.pixels[10 * 320 + 5], 0xFFFFFFFF);
+        assert_eq!(frame.pixels[1], 0xFF000000);
+    }
+
+    #[test]
+    fn test_vi_scanout_32bit() {
+        let mut vi = VideoInterface::new();
+        setup_320x240(&mut vi, VI_STATUS_TYPE_32);
+
+        let mut rdram = vec![0u8; 0x0020_0000];
+        let addr = 0x10_0000 + (2 * 320 + 3) * 4;
+        rdram[addr..addr + 4].copy_from_slice(&0x12345678u32.to_be_bytes());
+
+        let frame = vi.scanout(&rdram).unwrap();
+        assert_eq!(frame.pixels[2 * 320 + 3], 0xFF123456);
+
+        // Past the end of RDRAM reads as black
+        vi.write_register(VI_ORIGIN, 0x001F_FF00);
+        let frame = vi.scanout(&rdram).unwrap();
+        assert_eq!(frame.pixels[320 * 236], 0xFF000000);
+    }
+
+    #[test]
+    fn test_vi_interlaced_fields_are_woven() {
+        let mut vi = VideoInterface::new();
+        // A 320x474 framebuffer: each field skips every other line
+        setup_320x240(&mut vi, VI_STATUS_TYPE_16 | VI_STATUS_SERRATE);
+        vi.write_register(VI_WIDTH, 640);
+        assert_eq!(vi.resolution(), Some((320, 474)));
+
+        let mut rdram = vec![0u8; 0x0020_0000];
+        for line in 0..474 {
+            let color: u16 = if line % 2 == 0 { 0xF801 } else { 0x003F };
+            let addr = 0x10_0000 + line * 320 * 2;
+            rdram[addr..addr + 2].copy_from_slice(&color.to_be_bytes());
+        }
+
+        // The first field is shown doubled until the second one arrives
+        let frame = vi.scanout(&rdram).unwrap();
+        assert_eq!(frame.pixels[0], 0xFFFF0000);
+        assert_eq!(frame.pixels[320], 0xFFFF0000);
+
+        vi.end_frame();
+        vi.write_register(VI_ORIGIN, 0x0010_0000 + 320 * 2);
+        let frame = vi.scanout(&rdram).unwrap();
+        assert_eq!(frame.pixels[0], 0xFFFF0000);
+        assert_eq!(frame.pixels[320], 0xFF0000FF);
+        assert_eq!(frame.pixels[2 * 320], 0xFFFF0000);
+        assert_eq!(frame.pixels[3 * 320], 0xFF0000FF);
+    }
 }
@@ -15,10 +15,8 @@
 //! - **Envelope**: Volume envelope with decay
 //! - **Frame Counter**: Timing controller (4-step and 5-step modes)
 //! - **Frame IRQ**: Frame counter interrupt support
-//!
-//! ## Not Yet Implemented
-//!
-//! - **DMC Channel**: Delta modulation channel for sample playback
+//! - **DMC Channel**: Delta modulation sample playback with DMA fetches from
+//!   CPU memory, CPU stall cycles and the end-of-sample IRQ
 //!
 //! ## Register Interface
 //!
@@ -26,7 +24,7 @@
 //! - **$4004-$4007**: Pulse channel 2 (duty, envelope, frequency, length)
 //! - **$4008-$400B**: Triangle channel (control, linear counter, frequency, length)
 //! - **$400C-$400F**: Noise channel (envelope, mode/period, length)
-//! - **$4010-$4013**: DMC channel (not implemented)
+//! - **$4010-$4013**: DMC channel (IRQ/loop/rate, direct load, sample address, sample length)
 //! - **$4015**: Status/enable register
 //! - **$4017**: Frame counter mode and IRQ control
 //!
@@ -35,18 +33,25 @@
 //! The APU generates 44.1 kHz stereo audio by:
 //!
 //! 1. Clocking the APU at CPU speed (1.789773 MHz NTSC or 1.662607 MHz PAL)
-//! 2. Mixing the active channels using linear approximation
+//! 2. Mixing the active channels and any cartridge expansion channels
+//!    with the [`Mixer`] using linear approximation
 //! 3. Downsampling to the target sample rate
 //!
 //! The current implementation uses a simple average mixing strategy.
 //! Future enhancements could include proper NES mixer simulation with
 //! non-linear output curves.
 
+use crate::mixer::Mixer;
 use emu_core::apu::{
     Envelope, NoiseChannel, PulseChannel, TimingMode, TriangleChannel, LENGTH_TABLE,
 };
 use emu_core::logging::{log, LogCategory, LogLevel};
+use serde::{Deserialize, Serialize};
 use std::cell::Cell;
+use std::collections::VecDeque;
+
+/// Output sample rate of `APU::generate_samples`
+const SAMPLE_HZ: f64 = 44_100.0;
 
 /// NES-specific sweep unit for pulse channels.
 ///
@@ -55,7 +60,7 @@ use std::cell::Cell;
 /// - Silences channel when frequency > 0x7FF (2047)
 /// - Pulse 1 uses one's complement for negation
 /// - Pulse 2 uses two's complement for negation
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub(crate) struct NesSweep {
     pub(crate) enabled: bool,
     pub(crate) period: u8,
@@ -153,7 +158,151 @@ impl Default for NesSweep {
     }
 }
 
-/// NES APU with pulse, triangle, and noise channels.
+/// DMC output unit periods in CPU cycles, indexed by the rate in $4010 (NTSC)
+const DMC_RATES_NTSC: [u16; 16] = [
+    428, 380, 340, 320, 286, 254, 226, 214, 190, 160, 142, 128, 106, 84, 72, 54,
+];
+/// DMC output unit periods in CPU cycles (PAL)
+const DMC_RATES_PAL: [u16; 16] = [
+    398, 354, 316, 298, 276, 236, 210, 198, 176, 148, 132, 118, 98, 78, 66, 50,
+];
+
+/// CPU cycles stalled by each DMC sample fetch
+pub const DMC_DMA_CYCLES: u32 = 4;
+
+/// Most DMC output levels buffered for `generate_samples` (~0.1 s of audio);
+/// older levels are dropped if the frontend stops pulling samples
+const DMC_LEVEL_QUEUE_MAX: usize = 4410;
+
+/// NES delta modulation channel.
+///
+/// Plays 1-bit delta encoded samples read from CPU memory ($8000-$FFFF): each
+/// bit moves the 7-bit output level up or down by 2. The memory reader fetches
+/// a new byte by DMA whenever the one-byte sample buffer empties, and raises
+/// an IRQ at the end of a non-looping sample if enabled.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct Dmc {
+    irq_enabled: bool,
+    loop_flag: bool,
+    rate_index: u8,
+    timer: u16,
+    output_level: u8,
+    /// Sample start address ($C000 + A * 64) and length (L * 16 + 1)
+    sample_address: u16,
+    sample_length: u16,
+    current_address: u16,
+    bytes_remaining: u16,
+    sample_buffer: Option<u8>,
+    shift_register: u8,
+    bits_remaining: u8,
+    silence: bool,
+    irq_pending: bool,
+}
+
+impl Dmc {
+    fn new() -> Self {
+        Self {
+            irq_enabled: false,
+            loop_flag: false,
+            rate_index: 0,
+            timer: 0,
+            output_level: 0,
+            sample_address: 0xC000,
+            sample_length: 1,
+            current_address: 0xC000,
+            bytes_remaining: 0,
+            sample_buffer: None,
+            shift_register: 0,
+            bits_remaining: 8,
+            silence: true,
+            irq_pending: false,
+        }
+    }
+
+    /// $4010: IRQ enable, loop and rate
+    fn write_control(&mut self, val: u8) {
+        self.irq_enabled = (val & 0x80) != 0;
+        self.loop_flag = (val & 0x40) != 0;
+        self.rate_index = val & 0x0F;
+        if !self.irq_enabled {
+            self.irq_pending = false;
+        }
+    }
+
+    /// $4015 bit 4: stop the sample, or start it if none is playing
+    fn set_enabled(&mut self, enabled: bool) {
+        self.irq_pending = false;
+        if !enabled {
+            self.bytes_remaining = 0;
+        } else if self.bytes_remaining == 0 {
+            self.restart();
+        }
+    }
+
+    fn restart(&mut self) {
+        self.current_address = self.sample_address;
+        self.bytes_remaining = self.sample_length;
+    }
+
+    /// Address of the next sample byte if the reader needs one
+    fn fetch_address(&self) -> Option<u16> {
+        (self.sample_buffer.is_none() && self.bytes_remaining > 0).then_some(self.current_address)
+    }
+
+    /// Hand the byte read from `fetch_address` to the reader
+    fn fill_buffer(&mut self, byte: u8) {
+        self.sample_buffer = Some(byte);
+        // The address wraps from $FFFF to $8000
+        self.current_address = self.current_address.checked_add(1).unwrap_or(0x8000);
+        self.bytes_remaining -= 1;
+        if self.bytes_remaining == 0 {
+            if self.loop_flag {
+                self.restart();
+            } else if self.irq_enabled {
+                self.irq_pending = true;
+            }
+        }
+    }
+
+    /// Advance the output unit by one CPU cycle
+    fn clock(&mut self, rates: &[u16; 16]) {
+        if self.timer > 0 {
+            self.timer -= 1;
+            return;
+        }
+        self.timer = rates[self.rate_index as usize] - 1;
+
+        if !self.silence {
+            if self.shift_register & 1 != 0 {
+                if self.output_level <= 125 {
+                    self.output_level += 2;
+                }
+            } else if self.output_level >= 2 {
+                self.output_level -= 2;
+            }
+        }
+        self.shift_register >>= 1;
+        self.bits_remaining -= 1;
+        if self.bits_remaining == 0 {
+            self.bits_remaining = 8;
+            match self.sample_buffer.take() {
+                Some(byte) => {
+                    self.shift_register = byte;
+                    self.silence = false;
+                }
+                None => self.silence = true,
+            }
+        }
+    }
+}
+
+impl Default for Dmc {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// NES APU with pulse, triangle, noise and DMC channels.
 ///
 /// Uses core APU components for audio synthesis.
 ///
@@ -165,7 +314,8 @@ impl Default for NesSweep {
 /// - $4004-$4007: Pulse 2 (same as pulse 1)
 /// - $4008-$400B: Triangle (control, unused, timer low, timer high/length)
 /// - $400C-$400F: Noise (envelope, unused, mode/period, length)
-/// - $4015: Enable register (bits 0-3 enable pulse 1-2, triangle, noise)
+/// - $4010-$4013: DMC (IRQ/loop/rate, direct load, sample address, sample length)
+/// - $4015: Enable register (bits 0-4 enable pulse 1-2, triangle, noise, DMC)
 /// - $4017: Frame counter mode (bit 7 = 5-step, bit 6 = IRQ inhibit)
 ///
 /// # Timing
@@ -184,7 +334,9 @@ impl Default for NesSweep {
 ///
 /// In 4-step mode, the frame counter generates an IRQ at the end of step 4
 /// unless the IRQ inhibit flag is set. Reading $4015 clears the pending IRQ.
-#[derive(Debug)]
+/// The DMC raises its own IRQ at the end of a non-looping sample when enabled
+/// in $4010; writing $4015 clears it.
+#[derive(Debug, Serialize, Deserialize)]
 pub struct APU {
     pub pulse1: PulseChannel,
     pub pulse2: PulseChannel,
@@ -195,8 +347,25 @@ pub struct APU {
     pub triangle: TriangleChannel,
     pub noise: NoiseChannel,
     envelope_noise: Envelope,
+    #[serde(default)]
+    pub(crate) dmc: Dmc,
+    /// DMC output levels per audio sample, produced on the CPU timeline by
+    /// `clock_dmc` and consumed by `generate_samples`
+    #[serde(skip)]
+    dmc_levels: VecDeque<u8>,
+    #[serde(skip)]
+    dmc_level_sum: u32,
+    #[serde(skip)]
+    dmc_level_cycles: u32,
+    #[serde(skip)]
+    dmc_sample_clock: f64,
     cycle_accum: f64,
+    /// Set from the cartridge, not part of save states
+    #[serde(skip)]
     timing: TimingMode,
+    /// Output stage, holding the cartridge's expansion channels
+    #[serde(skip)]
+    pub(crate) mixer: Mixer,
     /// Frame counter for clocking length counters and envelopes
     /// Counts CPU cycles and triggers quarter/half frame events
     frame_counter_cycles: u32,
@@ -224,8 +393,14 @@ impl APU {
             triangle: TriangleChannel::new(),
             noise: NoiseChannel::new(),
             envelope_noise: Envelope::new(),
+            dmc: Dmc::new(),
+            dmc_levels: VecDeque::new(),
+            dmc_level_sum: 0,
+            dmc_level_cycles: 0,
+            dmc_sample_clock: 0.0,
             cycle_accum: 0.0,
             timing,
+            mixer: Mixer::new(),
             frame_counter_cycles: 0,
             frame_counter_mode: false,
             irq_frame_counter_cycles: 0,
@@ -239,6 +414,16 @@ impl APU {
         self.timing = timing;
     }
 
+    /// Restore channel and frame counter state from a save state, keeping
+    /// the current timing mode and expansion channels
+    pub fn restore_state(&mut self, state: APU) {
+        let timing = self.timing;
+        let mixer = std::mem::take(&mut self.mixer);
+        *self = state;
+        self.timing = timing;
+        self.mixer = mixer;
+    }
+
     /// Process APU register writes
     pub fn write_register(&mut self, addr: u16, val: u8) {
         match addr {
@@ -402,21 +587,37 @@ impl APU {
                 self.envelope_noise.restart();
             }
 
+            // DMC registers
+            0x4010 => self.dmc.write_control(val),
+            0x4011 => {
+                // Direct load of the 7-bit output level
+                self.dmc.output_level = val & 0x7F;
+            }
+            0x4012 => {
+                // Sample address: $C000 + A * 64
+                self.dmc.sample_address = 0xC000 | ((val as u16) << 6);
+            }
+            0x4013 => {
+                // Sample length: L * 16 + 1 bytes
+                self.dmc.sample_length = ((val as u16) << 4) | 1;
+            }
+
             // APU Enable register
             0x4015 => {
                 self.pulse1.enabled = (val & 0x01) != 0;
                 self.pulse2.enabled = (val & 0x02) != 0;
                 self.triangle.enabled = (val & 0x04) != 0;
                 self.noise.enabled = (val & 0x08) != 0;
-                // DMC enable at bit 4 (not yet implemented)
+                self.dmc.set_enabled((val & 0x10) != 0);
 
                 log(LogCategory::APU, LogLevel::Debug, || {
                     format!(
-                        "APU Channel enable: Pulse1={} Pulse2={} Triangle={} Noise={}",
+                        "APU Channel enable: Pulse1={} Pulse2={} Triangle={} Noise={} DMC={}",
                         self.pulse1.enabled,
                         self.pulse2.enabled,
                         self.triangle.enabled,
-                        self.noise.enabled
+                        self.noise.enabled,
+                        self.dmc.bytes_remaining > 0
                     )
                 });
             }
@@ -463,10 +664,10 @@ impl APU {
                 // Bit 1: Pulse 2 length counter > 0
                 // Bit 2: Triangle length counter > 0
                 // Bit 3: Noise length counter > 0
-                // Bit 4: DMC active (not implemented, return 0)
+                // Bit 4: DMC sample bytes remaining
                 // Bit 5: unused (return 0)
                 // Bit 6: Frame interrupt
-                // Bit 7: DMC interrupt (not implemented, return 0)
+                // Bit 7: DMC interrupt
                 let mut status = 0u8;
                 if self.pulse1.length_counter > 0 {
                     status |= 0x01;
@@ -480,6 +681,12 @@ impl APU {
                 if self.noise.length_counter > 0 {
                     status |= 0x08;
                 }
+                if self.dmc.bytes_remaining > 0 {
+                    status |= 0x10;
+                }
+                if self.dmc.irq_pending {
+                    status |= 0x80;
+                }
                 if self.irq_pending.get() {
                     status |= 0x40;
                     self.irq_pending.set(false); // Reading $4015 clears frame interrupt
@@ -491,7 +698,48 @@ impl APU {
     }
 
     pub fn irq_pending(&self) -> bool {
-        self.irq_pending.get()
+        self.irq_pending.get() || self.dmc.irq_pending
+    }
+
+    /// Run the DMC for `cycles` CPU cycles, reading sample bytes with `read`.
+    ///
+    /// Returns the extra CPU cycles stalled by sample DMA; the DMC keeps
+    /// running through them. Output levels are buffered per audio sample
+    /// for `generate_samples`, which runs on its own timeline.
+    pub fn clock_dmc(&mut self, cycles: u32, read: impl Fn(u16) -> u8) -> u32 {
+        let rates = match self.timing {
+            // Dendy keeps the NTSC rate table
+            TimingMode::Ntsc | TimingMode::Dendy => &DMC_RATES_NTSC,
+            TimingMode::Pal => &DMC_RATES_PAL,
+        };
+        let cycles_per_sample = self.timing.cpu_clock_hz() / SAMPLE_HZ;
+
+        let mut stalled = 0;
+        let mut remaining = cycles;
+        while remaining > 0 {
+            remaining -= 1;
+            if let Some(addr) = self.dmc.fetch_address() {
+                self.dmc.fill_buffer(read(addr));
+                stalled += DMC_DMA_CYCLES;
+                remaining += DMC_DMA_CYCLES;
+            }
+            self.dmc.clock(rates);
+
+            self.dmc_level_sum += self.dmc.output_level as u32;
+            self.dmc_level_cycles += 1;
+            self.dmc_sample_clock += 1.0;
+            if self.dmc_sample_clock >= cycles_per_sample {
+                self.dmc_sample_clock -= cycles_per_sample;
+                if self.dmc_levels.len() >= DMC_LEVEL_QUEUE_MAX {
+                    self.dmc_levels.pop_front();
+                }
+                self.dmc_levels
+                    .push_back((self.dmc_level_sum / self.dmc_level_cycles) as u8);
+                self.dmc_level_sum = 0;
+                self.dmc_level_cycles = 0;
+            }
+        }
+        stalled
     }
 
     pub fn clock_irq(&mut self, cycles: u32) {
@@ -520,9 +768,9 @@ impl APU {
     }
 
     /// Generate audio samples for a given count, stepping APU in CPU-cycle time
-    /// using the configured timing mode and sample rate of 44.1 kHz.
+    /// using the configured timing mode and sample rate of 44.1 kHz. The
+    /// mixer runs any expansion channels over the same cycles.
     pub fn generate_samples(&mut self, sample_count: usize) -> Vec<i16> {
-        const SAMPLE_HZ: f64 = 44_100.0;
         let cpu_hz = self.timing.cpu_clock_hz();
         let cycles_per_sample = cpu_hz / SAMPLE_HZ;
 
@@ -645,9 +893,11 @@ impl APU {
             }
 
             let avg = acc / cycles as i32;
-            const CHANNEL_COUNT: i32 = 4;
-            let mixed = avg / CHANNEL_COUNT; // Average for 4 channels
-            out.push(mixed.clamp(-32768, 32767) as i16);
+            // The DMC runs on the CPU timeline (see `clock_dmc`); without buffered
+            // levels (no CPU running) it holds its current level. Its 7-bit level
+            // is scaled so the full range spans about one pulse channel.
+            let dmc = self.dmc_levels.pop_front().unwrap_or(self.dmc.output_level) as i32;
+            out.push(self.mixer.mix(avg, dmc << 8, cycles));
         }
 
         out
@@ -1075,4 +1325,94 @@ mod tests {
             restarted_level
         );
     }
+
+    #[test]
+    fn test_dmc_status_and_enable() {
+        let mut apu = APU::new();
+        apu.write_register(0x4012, 0x01); // $C040
+        apu.write_register(0x4013, 0x02); // 33 bytes
+        assert_eq!(apu.read_register(0x4015) & 0x10, 0);
+
+        apu.write_register(0x4015, 0x10);
+        assert_eq!(apu.dmc.current_address, 0xC040);
+        assert_eq!(apu.dmc.bytes_remaining, 33);
+        assert_eq!(apu.read_register(0x4015) & 0x10, 0x10);
+
+        apu.write_register(0x4015, 0x00);
+        assert_eq!(apu.read_register(0x4015) & 0x10, 0);
+
+        // Direct load sets the 7-bit output level
+        apu.write_register(0x4011, 0xC5);
+        assert_eq!(apu.dmc.output_level, 0x45);
+    }
+
+    #[test]
+    fn test_dmc_playback_dma_and_irq() {
+        let mut apu = APU::new();
+        apu.write_register(0x4010, 0x8F); // IRQ enabled, fastest rate (54 cycles)
+        apu.write_register(0x4011, 0x40);
+        apu.write_register(0x4013, 0x00); // 1 byte
+        apu.write_register(0x4015, 0x10);
+
+        let reads = Cell::new(Vec::new());
+        let read = |addr: u16| {
+            let mut r = reads.take();
+            r.push(addr);
+            reads.set(r);
+            0xFF // all bits up
+        };
+
+        // The empty sample buffer is filled right away, stalling the CPU
+        assert_eq!(apu.clock_dmc(1, read), DMC_DMA_CYCLES);
+        assert_eq!(reads.take(), vec![0xC000]);
+        // One-byte sample finished: IRQ, but nothing left to fetch
+        assert!(apu.irq_pending());
+        assert_eq!(apu.read_register(0x4015) & 0x90, 0x80);
+
+        // The byte waits for the current 8-bit output cycle to end, then
+        // raises the level by 2 per bit; afterwards the channel is silent
+        apu.clock_dmc(54 * 20, read);
+        assert_eq!(apu.dmc.output_level, 0x40 + 16);
+        assert!(reads.take().is_empty());
+
+        // Reading the status does not acknowledge the DMC IRQ, writing $4015 does
+        assert!(apu.irq_pending());
+        apu.write_register(0x4015, 0x00);
+        assert!(!apu.irq_pending());
+    }
+
+    #[test]
+    fn test_dmc_loop_and_address_wrap() {
+        let mut apu = APU::new();
+        apu.write_register(0x4010, 0xC0); // IRQ enabled but looping
+        apu.write_register(0x4012, 0xFF); // $FFC0
+        apu.write_register(0x4013, 0x04); // 65 bytes
+        apu.write_register(0x4015, 0x10);
+
+        let mut addrs = Vec::new();
+        for _ in 0..66 {
+            let addr = apu.dmc.fetch_address().unwrap();
+            addrs.push(addr);
+            apu.dmc.fill_buffer(0);
+            apu.dmc.sample_buffer = None;
+        }
+        assert_eq!(addrs[63], 0xFFFF);
+        assert_eq!(addrs[64], 0x8000);
+        assert_eq!(addrs[65], 0xFFC0); // Looped back to the start
+        assert!(!apu.irq_pending());
+    }
+
+    #[test]
+    fn test_dmc_levels_are_mixed() {
+        let mut apu = APU::new();
+        let silent = apu.generate_samples(10);
+        assert!(silent.iter().all(|&s| s == 0));
+
+        // Levels produced on the CPU timeline show up in the next samples
+        apu.write_register(0x4011, 0x7F);
+        apu.clock_dmc(41 * 100, |_| 0);
+        assert!(apu.dmc_levels.len() >= 99);
+        let loud = apu.generate_samples(10);
+        assert!(loud.iter().all(|&s| s == (0x7F << 8) / 5), "{:?}", loud);
+    }
 }
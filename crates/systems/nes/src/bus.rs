@@ -1,11 +1,15 @@
 use crate::apu::APU;
 use crate::cartridge::Cartridge;
 use crate::mappers::Mapper;
-use crate::ppu::Ppu;
+use crate::ppu::{Ppu, PpuState};
+use crate::zapper::Zapper;
 use emu_core::logging::{log, LogCategory, LogLevel};
 use std::cell::{Cell, RefCell};
 use std::rc::{Rc, Weak};
 
+/// PPU dots in one scanline
+const PPU_DOTS_PER_LINE: u64 = 341;
+
 pub trait Bus {
     fn read(&self, addr: u16) -> u8;
     fn write(&mut self, addr: u16, val: u8);
@@ -42,12 +46,19 @@ pub struct NesBus {
     pub apu: APU,
     mapper: Option<Rc<RefCell<Mapper>>>,
     // Simple controller state: each u8 is 8-button shift register (bit0 first)
-    pub controller_state: [u8; 2],
-    controller_shift: [Cell<u8>; 2],
+    pub controller_state: [u8; 4],
+    // Bits still to be shifted out of each port (24 with a Four Score)
+    controller_shift: [Cell<u32>; 2],
     controller_read_count: [Cell<u8>; 2],
+    /// Zapper in port 2 instead of a standard controller
+    pub zapper: Option<Zapper>,
+    /// Four Score adapter: controllers 3 and 4 follow 1 and 2 on each port
+    pub four_score: bool,
     strobe: Cell<bool>,
     // CPU cycle counter for mapper timing (e.g., MMC1 consecutive write detection)
     cpu_cycles: Cell<u64>,
+    // PPU dot the last scanline fed to the mapper's A12 input started at
+    a12_line_start: u64,
 }
 
 impl NesBus {
@@ -58,11 +69,14 @@ impl NesBus {
             ppu,
             apu: APU::new(),
             mapper: None,
-            controller_state: [0; 2],
+            controller_state: [0; 4],
             controller_shift: [Cell::new(0), Cell::new(0)],
             controller_read_count: [Cell::new(0), Cell::new(0)],
+            zapper: None,
+            four_score: false,
             strobe: Cell::new(false),
             cpu_cycles: Cell::new(0),
+            a12_line_start: 0,
         }
     }
 
@@ -86,6 +100,21 @@ impl NesBus {
             }
         })));
 
+        // Register the cartridge's expansion audio channels with the mixer.
+        self.apu.mixer.clear_expansion_channels();
+        let channels = rc.borrow().expansion_audio_channels();
+        for (channel, name) in channels.iter().enumerate() {
+            let weak_audio: Weak<RefCell<Mapper>> = Rc::downgrade(&rc);
+            self.apu.mixer.add_expansion_channel(
+                name,
+                Box::new(move |cycles| {
+                    weak_audio
+                        .upgrade()
+                        .map_or(0, |m| m.borrow_mut().clock_audio(channel, cycles))
+                }),
+            );
+        }
+
         self.mapper = Some(rc);
     }
 
@@ -98,13 +127,37 @@ impl NesBus {
         mapper_irq || self.apu.irq_pending()
     }
 
-    /// Synthesize a PPU A12 low->high transition for mappers that use it for scanline IRQs
-    /// (notably MMC3). This is a pragmatic approximation used by the frame-based renderer.
-    pub fn clock_mapper_a12_rising_edge(&mut self) {
+    /// Run one rendered scanline's pattern fetches past the mapper: their A12
+    /// edges clock the MMC3 IRQ counter through its low filter, and MMC5's
+    /// scanline counter is clocked. `sprite_line` is the line the sprite
+    /// slots are fetched for (see [`Ppu::a12_fetch_edges`]).
+    pub fn clock_mapper_scanline(&mut self, sprite_line: Option<u32>) {
+        let Some(m) = &self.mapper else {
+            return;
+        };
+        // The line starts at the current PPU dot, or right after the previous
+        // line when several are run at once
+        let start = (self.cpu_cycles.get() * 3).max(self.a12_line_start + PPU_DOTS_PER_LINE);
+        self.a12_line_start = start;
+
+        let mut mapper = m.borrow_mut();
+        for (dot, a12_high) in self.ppu.a12_fetch_edges(sprite_line) {
+            mapper.notify_a12_at(a12_high, start + u64::from(dot));
+        }
+        mapper.notify_scanline();
+    }
+
+    /// Tell the mapper rendering stopped for VBlank
+    pub fn notify_mapper_vblank(&mut self) {
+        if let Some(m) = &mut self.mapper {
+            m.borrow_mut().notify_vblank();
+        }
+    }
+
+    /// Run the mapper's CPU cycle IRQ counters
+    pub fn clock_mapper(&mut self, cycles: u32) {
         if let Some(m) = &mut self.mapper {
-            // Ensure we generate a rising edge even if the last sampled value was high.
-            m.borrow_mut().notify_a12(false);
-            m.borrow_mut().notify_a12(true);
+            m.borrow_mut().clock_cpu(cycles);
         }
     }
 
@@ -129,13 +182,125 @@ impl NesBus {
             .unwrap_or(0)
     }
 
-    /// Set controller state (8 buttons) for controller `idx` (0 or 1).
+    /// Set controller state (8 buttons) for controller `idx` (0-3; 2 and 3
+    /// are only read through a Four Score).
     pub fn set_controller(&mut self, idx: usize, state: u8) {
-        if idx < 2 {
+        if idx < 4 {
             self.controller_state[idx] = state;
         }
     }
 
+    /// Bits a port shifts out after a strobe: the controller, then with a
+    /// Four Score controller 3 or 4 and the adapter's signature
+    fn latch_controller_port(&self, port: usize) -> u32 {
+        let state = self.controller_state[port] as u32;
+        if self.four_score {
+            // Signature read order is 0,0,0,1,0,0,0,0 on $4016 and
+            // 0,0,1,0,0,0,0,0 on $4017
+            let signature: u32 = if port == 0 { 0x08 } else { 0x04 };
+            state | (self.controller_state[port + 2] as u32) << 8 | signature << 16
+        } else {
+            state
+        }
+    }
+
+    /// Read a controller port ($4016 or $4017)
+    fn read_controller_port(&self, port: usize) -> u8 {
+        // When strobed, return current button A state (bit 0).
+        // When not strobed, shift out latched controller bits.
+        if self.strobe.get() {
+            return self.controller_state[port] & 1;
+        }
+        let count = self.controller_read_count[port].get();
+        self.controller_read_count[port].set(count.saturating_add(1));
+
+        // After all bits are read, return 1 (open bus behavior)
+        let bits = if self.four_score { 24 } else { 8 };
+        if count >= bits {
+            1
+        } else {
+            let cur = self.controller_shift[port].get();
+            self.controller_shift[port].set(cur >> 1);
+            (cur & 1) as u8
+        }
+    }
+
+    /// Serialize RAM, PPU, APU, mapper registers and controller latches
+    pub fn save_state(&self) -> serde_json::Value {
+        serde_json::json!({
+            "ram": self.ram.to_vec(),
+            "wram": self.wram.to_vec(),
+            "ppu": self.ppu.save_state(),
+            "apu": self.apu,
+            "mapper_number": self.mapper_number(),
+            "mapper": self.mapper.as_ref().map(|m| m.borrow().save_state()),
+            "controller_shift": [self.controller_shift[0].get(), self.controller_shift[1].get()],
+            "controller_read_count": [
+                self.controller_read_count[0].get(),
+                self.controller_read_count[1].get()
+            ],
+            "strobe": self.strobe.get(),
+            "cpu_cycles": self.cpu_cycles.get(),
+        })
+    }
+
+    /// Restore state saved by [`NesBus::save_state`] into the bus with the
+    /// same cartridge installed
+    pub fn load_state(&mut self, state: &serde_json::Value) -> Result<(), serde_json::Error> {
+        use serde::de::Error;
+
+        let mapper_number: Option<u8> = serde_json::from_value(state["mapper_number"].clone())?;
+        if mapper_number != self.mapper_number() {
+            return Err(Error::custom(format!(
+                "save state is for mapper {:?}, cartridge uses mapper {:?}",
+                mapper_number,
+                self.mapper_number()
+            )));
+        }
+
+        let ram: Vec<u8> = serde_json::from_value(state["ram"].clone())?;
+        let wram: Vec<u8> = serde_json::from_value(state["wram"].clone())?;
+        if ram.len() != self.ram.len() || wram.len() != self.wram.len() {
+            return Err(Error::custom("save state has wrong RAM size"));
+        }
+        let ppu: PpuState = serde_json::from_value(state["ppu"].clone())?;
+        let apu: APU = serde_json::from_value(state["apu"].clone())?;
+        let controller_shift: [u32; 2] = serde_json::from_value(state["controller_shift"].clone())?;
+        let controller_read_count: [u8; 2] =
+            serde_json::from_value(state["controller_read_count"].clone())?;
+        let strobe: bool = serde_json::from_value(state["strobe"].clone())?;
+        let cpu_cycles: u64 = serde_json::from_value(state["cpu_cycles"].clone())?;
+
+        if let Some(m) = &self.mapper {
+            m.borrow_mut().load_state(&state["mapper"])?;
+        }
+        self.ppu.load_state(&ppu).map_err(Error::custom)?;
+        self.ram.copy_from_slice(&ram);
+        self.wram.copy_from_slice(&wram);
+        self.apu.restore_state(apu);
+        for i in 0..2 {
+            self.controller_shift[i].set(controller_shift[i]);
+            self.controller_read_count[i].set(controller_read_count[i]);
+        }
+        self.strobe.set(strobe);
+        self.cpu_cycles.set(cpu_cycles);
+        Ok(())
+    }
+
+    /// Run the APU's DMC for `cycles` CPU cycles, fetching its samples from PRG.
+    ///
+    /// Returns the CPU cycles stalled by the sample DMA.
+    pub fn clock_dmc(&mut self, cycles: u32) -> u32 {
+        // Sample addresses are always in $8000-$FFFF
+        let mapper = self.mapper.clone();
+        self.apu.clock_dmc(cycles, |addr| {
+            mapper
+                .as_ref()
+                .map(|m| m.borrow().read_prg(addr))
+                .unwrap_or(0)
+        })
+    }
+
     /// Add CPU cycles to the bus cycle counter (for mapper timing).
     /// The counter wraps on overflow, which is expected and handled correctly
     /// by mappers that check for consecutive writes.
@@ -166,50 +331,29 @@ impl Bus for NesBus {
                         // APU status register
                         self.apu.read_register(addr)
                     }
-                    0x4016 => {
-                        // When strobed, return current button A state (bit 0).
-                        // When not strobed, shift out latched controller bits.
-                        if self.strobe.get() {
-                            self.controller_state[0] & 1
-                        } else {
-                            let count = self.controller_read_count[0].get();
-                            self.controller_read_count[0].set(count.saturating_add(1));
-
-                            // After 8 reads, return 1 (open bus behavior)
-                            if count >= 8 {
-                                1
-                            } else {
-                                let cur = self.controller_shift[0].get();
-                                let v = cur & 1;
-                                self.controller_shift[0].set(cur >> 1);
-                                v
-                            }
-                        }
-                    }
+                    0x4016 => self.read_controller_port(0),
                     0x4017 => {
-                        if self.strobe.get() {
-                            self.controller_state[1] & 1
+                        if let Some(zapper) = &self.zapper {
+                            zapper.read()
                         } else {
-                            let count = self.controller_read_count[1].get();
-                            self.controller_read_count[1].set(count.saturating_add(1));
-
-                            // After 8 reads, return 1 (open bus behavior)
-                            if count >= 8 {
-                                1
-                            } else {
-                                let cur = self.controller_shift[1].get();
-                                let v = cur & 1;
-                                self.controller_shift[1].set(cur >> 1);
-                                v
-                            }
+                            self.read_controller_port(1)
                         }
                     }
                     _ => 0,
                 }
             }
+            0x4020..=0x5FFF => self
+                .mapper
+                .as_ref()
+                .and_then(|m| m.borrow_mut().read_low(addr))
+                .unwrap_or(0),
             0x6000..=0x7FFF => {
-                let off = (addr - 0x6000) as usize;
-                self.wram[off]
+                // Boards can map ROM or registers over the WRAM
+                let mapped = self
+                    .mapper
+                    .as_ref()
+                    .and_then(|m| m.borrow_mut().read_low(addr));
+                mapped.unwrap_or(self.wram[(addr - 0x6000) as usize])
             }
             0x8000..=0xFFFF => self
                 .mapper
@@ -254,7 +398,7 @@ impl Bus for NesBus {
             }
             0x4000..=0x4017 => {
                 // APU registers and controller strobe
-                if (0x4000..=0x4007).contains(&addr) || addr == 0x4015 || addr == 0x4017 {
+                if addr != 0x4016 {
                     log(LogCategory::APU, LogLevel::Debug, || {
                         format!("APU WRITE: addr=0x{:04X} val=0x{:02X}", addr, val)
                     });
@@ -266,8 +410,8 @@ impl Bus for NesBus {
                     let st = (val & 1) != 0;
                     self.strobe.set(st);
                     if st {
-                        self.controller_shift[0].set(self.controller_state[0]);
-                        self.controller_shift[1].set(self.controller_state[1]);
+                        self.controller_shift[0].set(self.latch_controller_port(0));
+                        self.controller_shift[1].set(self.latch_controller_port(1));
                     } else {
                         // Reset read counters when strobe goes low
                         self.controller_read_count[0].set(0);
@@ -275,9 +419,17 @@ impl Bus for NesBus {
                     }
                 }
             }
+            0x4020..=0x5FFF => {
+                if let Some(m) = &mut self.mapper {
+                    m.borrow_mut().write_low(addr, val, &mut self.ppu);
+                }
+            }
             0x6000..=0x7FFF => {
                 let off = (addr - 0x6000) as usize;
                 self.wram[off] = val;
+                if let Some(m) = &mut self.mapper {
+                    m.borrow_mut().write_low(addr, val, &mut self.ppu);
+                }
             }
             0x8000..=0xFFFF => {
                 if let Some(m) = &mut self.mapper {
@@ -1,10 +1,11 @@
 use emu_core::apu::TimingMode;
 use emu_core::logging::{log, LogCategory, LogLevel};
+use serde::{Deserialize, Serialize};
 use std::fs::File;
 use std::io::Read;
 use std::path::Path;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Mirroring {
     Horizontal,
     Vertical,
@@ -20,6 +21,8 @@ pub struct Cartridge {
     pub mapper: u8,
     pub mirroring: Mirroring,
     pub timing: TimingMode,
+    /// PRG-RAM at $6000-$7FFF is battery backed (iNES flags 6 bit 1)
+    pub battery: bool,
 }
 
 impl Cartridge {
@@ -52,7 +55,9 @@ impl Cartridge {
 
         // iNES flags 6:
         // bit 0 = mirroring (0 horizontal, 1 vertical)
+        // bit 1 = battery-backed PRG-RAM
         // bit 3 = four-screen VRAM
+        let battery = (header[6] & 0x02) != 0;
         let four_screen = (header[6] & 0x08) != 0;
         let vertical = (header[6] & 0x01) != 0;
         let mirroring = if four_screen {
@@ -63,7 +68,7 @@ impl Cartridge {
             Mirroring::Horizontal
         };
 
-        // Auto-detect PAL/NTSC from iNES 2.0 header (byte 12) or NES 2.0 flags
+        // Auto-detect NTSC/PAL/Dendy from iNES 2.0 header (byte 12) or NES 2.0 flags
         // If byte 7 & 0x0C == 0x08, it's NES 2.0 format
         let is_nes2 = (header[7] & 0x0C) == 0x08;
         let timing = if is_nes2 && data.len() > 12 {
@@ -71,7 +76,8 @@ impl Cartridge {
             // 0 = NTSC, 1 = PAL, 2 = Dual compatible, 3 = Dendy
             match header[12] & 0x03 {
                 1 => TimingMode::Pal,
-                _ => TimingMode::Ntsc, // Default to NTSC for dual/dendy/ntsc
+                3 => TimingMode::Dendy,
+                _ => TimingMode::Ntsc, // Default to NTSC for dual/ntsc
             }
         } else {
             // iNES 1.0: no timing flag, default to NTSC
@@ -114,12 +120,13 @@ impl Cartridge {
 
         log(LogCategory::Bus, LogLevel::Info, || {
             format!(
-                "NES: Loaded cartridge - Mapper {} ({} KB PRG, {} KB CHR, {:?}, {:?})",
+                "NES: Loaded cartridge - Mapper {} ({} KB PRG, {} KB CHR, {:?}, {:?}{})",
                 mapper,
                 prg_size / 1024,
                 chr_size / 1024,
                 mirroring,
-                timing
+                timing,
+                if battery { ", battery" } else { "" }
             )
         });
 
@@ -129,6 +136,7 @@ impl Cartridge {
             mapper,
             mirroring,
             timing,
+            battery,
         })
     }
 
@@ -155,7 +163,9 @@ impl Cartridge {
 
         // iNES flags 6:
         // bit 0 = mirroring (0 horizontal, 1 vertical)
+        // bit 1 = battery-backed PRG-RAM
         // bit 3 = four-screen VRAM
+        let battery = (header[6] & 0x02) != 0;
         let four_screen = (header[6] & 0x08) != 0;
         let vertical = (header[6] & 0x01) != 0;
         let mirroring = if four_screen {
@@ -166,12 +176,13 @@ impl Cartridge {
             Mirroring::Horizontal
         };
 
-        // Auto-detect PAL/NTSC from iNES 2.0 header (byte 12) or NES 2.0 flags
+        // Auto-detect NTSC/PAL/Dendy from iNES 2.0 header (byte 12) or NES 2.0 flags
         let is_nes2 = (header[7] & 0x0C) == 0x08;
         let timing = if is_nes2 {
             // NES 2.0: byte 12 bits 0-1 indicate timing (always present in 16-byte header)
             match header[12] & 0x03 {
                 1 => TimingMode::Pal,
+                3 => TimingMode::Dendy,
                 _ => TimingMode::Ntsc,
             }
         } else {
@@ -204,6 +215,7 @@ impl Cartridge {
             mapper,
             mirroring,
             timing,
+            battery,
         })
     }
 }
@@ -301,6 +313,25 @@ mod tests {
         assert_eq!(cart.chr_rom[0], 0x55); // First CHR byte
     }
 
+    #[test]
+    fn test_battery_flag() {
+        let mut data = vec![
+            0x4E, 0x45, 0x53, 0x1A, // NES<EOF>
+            0x01, 0x01, // 16KB PRG, 8KB CHR
+            0x12, // Flags 6: Mapper 1, battery-backed PRG-RAM (bit 1)
+            0x00, // Flags 7
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+        data.extend(vec![0; 16 * 1024 + 8 * 1024]);
+
+        let cart = Cartridge::from_bytes(&data).unwrap();
+        assert!(cart.battery);
+        assert_eq!(cart.mapper, 1);
+
+        data[6] = 0x10;
+        assert!(!Cartridge::from_bytes(&data).unwrap().battery);
+    }
+
     #[test]
     fn test_four_screen_mirroring() {
         // Edge case: Four-screen VRAM (bit 3 of flags 6)
@@ -371,6 +402,22 @@ mod tests {
         assert_eq!(cart.timing, TimingMode::Pal);
     }
 
+    #[test]
+    fn test_nes2_dendy_timing_detection() {
+        let mut data = vec![
+            0x4E, 0x45, 0x53, 0x1A, // NES<EOF>
+            0x01, 0x01, // 16KB PRG, 8KB CHR
+            0x00, // Flags 6
+            0x08, // Flags 7: NES 2.0 format
+            0x00, 0x00, 0x00, 0x00, 0x03, // Byte 12: Dendy timing (bits 0-1 = 11)
+            0x00, 0x00, 0x00,
+        ];
+        data.extend(vec![0; 16 * 1024 + 8 * 1024]);
+
+        let cart = Cartridge::from_bytes(&data).unwrap();
+        assert_eq!(cart.timing, TimingMode::Dendy);
+    }
+
     #[test]
     fn test_mapper_number_extraction() {
         // Edge case: Mapper number from both nibbles of flags 6 and 7
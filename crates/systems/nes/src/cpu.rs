@@ -2,6 +2,7 @@
 
 use crate::bus::Bus;
 use emu_core::cpu_6502::{Cpu6502, Memory6502};
+use serde::{Deserialize, Serialize};
 
 /// NES-specific memory implementation that uses NES bus or fallback array
 #[derive(Debug)]
@@ -52,6 +53,19 @@ impl Memory6502 for NesMemory {
     }
 }
 
+/// 6502 register snapshot for save states
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CpuState {
+    pub a: u8,
+    pub x: u8,
+    pub y: u8,
+    pub sp: u8,
+    pub status: u8,
+    pub pc: u16,
+    pub cycles: u64,
+    pub in_nmi: bool,
+}
+
 /// NES CPU - wrapper around the reusable 6502 core
 #[derive(Debug)]
 pub struct NesCpu {
@@ -104,10 +118,33 @@ impl NesCpu {
         self.cpu.trigger_irq();
     }
 
-    // Public accessors for CPU state (used by NES system)
-    pub fn a(&self) -> u8 {
-        self.cpu.a
+    /// Capture register state for a save state
+    pub fn save_state(&self) -> CpuState {
+        CpuState {
+            a: self.cpu.a,
+            x: self.cpu.x,
+            y: self.cpu.y,
+            sp: self.cpu.sp,
+            status: self.cpu.status,
+            pc: self.cpu.pc,
+            cycles: self.cpu.cycles,
+            in_nmi: self.cpu.is_in_nmi(),
+        }
     }
+
+    /// Restore register state from a save state
+    pub fn load_state(&mut self, state: &CpuState) {
+        self.cpu.a = state.a;
+        self.cpu.x = state.x;
+        self.cpu.y = state.y;
+        self.cpu.sp = state.sp;
+        self.cpu.status = state.status;
+        self.cpu.pc = state.pc;
+        self.cpu.cycles = state.cycles;
+        self.cpu.set_in_nmi(state.in_nmi);
+    }
+
+    // Public accessors for CPU state (used by NES system)
     pub fn pc(&self) -> u16 {
         self.cpu.pc
     }
@@ -17,14 +17,23 @@
 //! - **2 (UxROM)**: 16KB switchable + 16KB fixed PRG banks
 //! - **3 (CNROM)**: Switchable CHR banks only
 //! - **4 (MMC3/TxROM)**: Advanced banking with scanline IRQ counter
+//! - **5 (MMC5/ExROM)**: Four PRG/CHR banking modes, ExRAM, multiplier and
+//!   scanline IRQ
 //! - **7 (AxROM)**: 32KB switchable PRG banks, single-screen mirroring
 //! - **9 (MMC2/PxROM)**: Latch-based CHR switching (Punch-Out!!)
 //! - **10 (MMC4/FxROM)**: Similar to MMC2 (Fire Emblem)
 //! - **11 (Color Dreams)**: Simple PRG/CHR banking
+//! - **19 (Namco 163)**: 8KB PRG / 1KB CHR banking, CPU cycle IRQ and wavetable audio
+//! - **21, 22, 23, 25 (VRC2/VRC4)**: Konami banking with per-board register
+//!   address lines and a CPU cycle IRQ
+//! - **24, 26 (VRC6a/VRC6b)**: Konami banking, CPU cycle IRQ and expansion audio
 //! - **34 (BNROM)**: 32KB switchable PRG banks
 //! - **66 (GxROM)**: Combined PRG/CHR banking
+//! - **69 (Sunsoft FME-7)**: 8KB PRG / 1KB CHR banking with a CPU cycle IRQ
 //! - **71 (Camerica)**: 16KB switchable PRG banks
 //! - **79 (NINA-03/06)**: AVE mapper with PRG/CHR banking
+//! - **87, 89, 93, 140, 184, 185**: Discrete-logic boards (Jaleco, Sunsoft-1/2,
+//!   CNROM with CHR disable), table-driven in `mappers::Discrete`
 //! - **206 (Namco 118)**: Variant of MMC3 without IRQ support
 //!
 //! ## PPU Features
@@ -58,10 +67,36 @@
 //! - **NTSC**: ~29,780 CPU cycles per frame (~60.1 Hz)
 //! - **PAL**: ~33,247 CPU cycles per frame (~50.0 Hz)
 //! - **VBlank**: Simulated at end of frame with appropriate cycle count
-//! - **Scanline IRQs**: Synthesized for mappers like MMC3
+//! - **Scanline IRQs**: Each rendered line's pattern fetches drive the mapper's A12
+//!   input, filtered by the MMC3 like on hardware
 //!
 //! This model is suitable for most games but may not handle edge cases requiring
 //! precise PPU timing (mid-scanline effects, exact sprite 0 hit timing, etc.).
+//!
+//! # Usage Example
+//!
+//! ```
+//! use emu_core::System;
+//! use emu_nes::NesSystem;
+//!
+//! // A minimal NROM image: iNES header, 32KB PRG looping at $8000, 8KB CHR
+//! let mut rom = vec![0x4E, 0x45, 0x53, 0x1A, 2, 1, 0, 0];
+//! rom.resize(16, 0);
+//! let mut prg = vec![0xEA; 0x8000];
+//! prg[..3].copy_from_slice(&[0x4C, 0x00, 0x80]); // JMP $8000
+//! prg[0x7FFA..].copy_from_slice(&[0x00, 0x80, 0x00, 0x80, 0x00, 0x80]);
+//! rom.extend_from_slice(&prg);
+//! rom.extend(vec![0; 0x2000]);
+//!
+//! let mut nes = NesSystem::default();
+//! nes.mount("Cartridge", &rom).unwrap();
+//! nes.set_controller(0, 0x08); // Start held on controller 1
+//!
+//! let frame = nes.step_frame().unwrap();
+//! assert_eq!((frame.width, frame.height), (256, 240));
+//! let audio = nes.get_audio_samples(735); // 44.1 kHz / 60 fps
+//! assert_eq!(audio.len(), 735);
+//! ```
 
 #![allow(clippy::upper_case_acronyms)]
 #![allow(clippy::unnecessary_cast)]
@@ -73,21 +108,40 @@ mod bus;
 mod cartridge;
 mod cpu;
 mod mappers;
+mod mixer;
 mod ppu;
 pub mod ppu_renderer;
 #[cfg(feature = "opengl")]
 pub mod ppu_renderer_opengl;
+mod zapper;
 
 use crate::bus::Bus;
 use crate::cartridge::Mirroring;
 use bus::NesBus;
-use cpu::NesCpu;
+use cpu::{CpuState, NesCpu};
 use emu_core::logging::{log, LogCategory, LogLevel};
 use emu_core::renderer::Renderer;
 use emu_core::{apu::TimingMode, types::Frame, MountPointInfo, System};
 use ppu::Ppu;
+pub use ppu::PpuAccuracy;
 use ppu_renderer::{NesPpuRenderer, SoftwareNesPpuRenderer};
 use std::collections::HashMap;
+use zapper::Zapper;
+
+/// PPU cycles per scanline (the PPU runs at 3x the CPU clock)
+const PPU_CYCLES_PER_SCANLINE: u32 = 341;
+
+/// Dot at which a visible line's pixels are done and the PPU reloads the
+/// horizontal scroll for the next line
+const SCROLL_RELOAD_DOT: u32 = 257;
+
+/// Render a visible scanline and let the Zapper sample it
+fn draw_scanline(renderer: &mut dyn NesPpuRenderer, b: &mut NesBus, y: u32) {
+    renderer.render_scanline(&mut b.ppu, y);
+    if let Some(zapper) = &mut b.zapper {
+        zapper.scanline_rendered(renderer.get_frame(), y);
+    }
+}
 
 /// Debug information for the NES system.
 ///
@@ -95,7 +149,7 @@ use std::collections::HashMap;
 /// for display in debug overlays.
 #[derive(Debug, Clone)]
 pub struct DebugInfo {
-    /// Current timing mode (NTSC or PAL)
+    /// Current timing mode (NTSC, PAL or Dendy)
     pub timing_mode: TimingMode,
     /// Human-readable mapper name (e.g., "MMC3/TxROM")
     pub mapper_name: String,
@@ -134,7 +188,8 @@ pub struct RuntimeStats {
     pub irqs: u32,
     /// Number of NMI interrupts fired this frame
     pub nmis: u32,
-    /// Number of MMC3 A12 rising edges this frame (for IRQ timing)
+    /// Number of scanlines of pattern fetches fed to the mapper's A12 input this
+    /// frame (for MMC3 IRQ timing)
     pub mmc3_a12_edges: u32,
     /// Current PPUCTRL register value
     pub ppu_ctrl: u8,
@@ -154,6 +209,30 @@ pub struct RuntimeStats {
     pub pc_hotspots: [PcHotspot; 3],
 }
 
+/// Bookkeeping for the frame being emulated.
+///
+/// Kept between calls so `step_cycles` and `step_scanline` can stop anywhere
+/// in a frame and `step_frame` picks up where they left off.
+#[derive(Debug, Default)]
+struct FrameProgress {
+    /// CPU cycles run so far in this frame
+    cycles: u32,
+    /// The visible portion is done and VBlank has started
+    in_vblank: bool,
+    /// PPU cycles toward the next synthesized scanline edge
+    ppu_cycles_accum: u32,
+    /// Scanlines rendered so far (0-240)
+    rendered_scanlines: u32,
+    /// Rendering was enabled at some point during the frame
+    rendering_happened: bool,
+    cpu_steps: u32,
+    irqs: u32,
+    nmis: u32,
+    mmc3_a12_edges: u32,
+    /// Execution count per PC, for the hotspot stats
+    pc_hist: HashMap<u16, u16>,
+}
+
 /// NES system implementation.
 ///
 /// Combines the 6502 CPU, PPU, APU, and cartridge mappers into a complete
@@ -163,26 +242,51 @@ pub struct RuntimeStats {
 pub struct NesSystem {
     cpu: NesCpu,
     timing: TimingMode,
+    /// User-selected timing mode that replaces the cartridge's, if any
+    timing_override: Option<TimingMode>,
+    /// Timing mode from the mounted cartridge header
+    cartridge_timing: TimingMode,
     cartridge_loaded: bool,
+    /// Mounted cartridge keeps its PRG-RAM on a battery
+    battery: bool,
+    /// Emulate the PPU's buggy sprite overflow scan
+    sprite_overflow_bug: bool,
+    /// Where scanlines take their background scroll from
+    ppu_accuracy: PpuAccuracy,
+    /// Zapper light gun plugged into port 2
+    zapper_connected: bool,
+    /// Four Score adapter for controllers 3 and 4
+    four_score: bool,
     frame_index: u64,
+    /// Frame started by `step_cycles`/`step_scanline` and not yet completed
+    frame: Option<FrameProgress>,
     last_stats: RuntimeStats,
     renderer: Box<dyn NesPpuRenderer>,
 }
 
 impl NesSystem {
-    /// Set controller 0 or 1 button state (bits 0..7 correspond to controller buttons).
+    /// Set controller 0-3 button state (bits 0..7 correspond to controller buttons).
+    /// Controllers 2 and 3 are only read with the Four Score connected.
     pub fn set_controller(&mut self, idx: usize, state: u8) {
         if let Some(b) = self.cpu.bus_mut() {
             b.set_controller(idx, state);
         }
     }
 
+    /// Get the current button state of controller 0-3 (same bit layout as `set_controller`).
+    pub fn controller_state(&self, idx: usize) -> u8 {
+        self.cpu
+            .bus()
+            .and_then(|b| b.controller_state.get(idx).copied())
+            .unwrap_or(0)
+    }
+
     /// Set the PPU renderer
     pub fn set_renderer(&mut self, renderer: Box<dyn NesPpuRenderer>) {
         self.renderer = renderer;
     }
 
-    /// Get audio samples from the APU
+    /// Get audio samples from the APU and the cartridge's expansion audio
     pub fn get_audio_samples(&mut self, count: usize) -> Vec<i16> {
         if let Some(b) = self.cpu.bus_mut() {
             b.apu.generate_samples(count)
@@ -191,7 +295,10 @@ impl NesSystem {
         }
     }
 
-    /// Set timing mode (NTSC/PAL)
+    /// Set timing mode (NTSC/PAL/Dendy)
+    ///
+    /// Mounting a cartridge switches to its timing mode again unless an
+    /// override is set with `set_timing_override`.
     pub fn set_timing(&mut self, timing: TimingMode) {
         self.timing = timing;
         if let Some(b) = self.cpu.bus_mut() {
@@ -204,6 +311,94 @@ impl NesSystem {
         self.timing
     }
 
+    /// Force a timing mode for this and later cartridges, or pass `None` to
+    /// follow the cartridge header (NTSC for iNES 1.0 ROMs without a PAL flag)
+    pub fn set_timing_override(&mut self, timing: Option<TimingMode>) {
+        self.timing_override = timing;
+        self.set_timing(timing.unwrap_or(self.cartridge_timing));
+    }
+
+    /// Get the user-selected timing mode override, if any
+    pub fn timing_override(&self) -> Option<TimingMode> {
+        self.timing_override
+    }
+
+    /// Plug a Zapper light gun (true) or a standard controller (false) into
+    /// controller port 2, for this and later cartridges
+    pub fn set_zapper_connected(&mut self, connected: bool) {
+        self.zapper_connected = connected;
+        if let Some(b) = self.cpu.bus_mut() {
+            b.zapper = connected.then(Zapper::default);
+        }
+    }
+
+    /// Whether a Zapper is plugged into controller port 2
+    pub fn zapper_connected(&self) -> bool {
+        self.zapper_connected
+    }
+
+    /// Aim the Zapper at frame pixel (`x`, `y`) and pull (`trigger`) or
+    /// release the trigger. Coordinates outside the 256x240 picture aim off
+    /// screen. Ignored unless a Zapper is connected.
+    pub fn set_zapper(&mut self, x: i32, y: i32, trigger: bool) {
+        if let Some(zapper) = self.cpu.bus_mut().and_then(|b| b.zapper.as_mut()) {
+            zapper.x = x;
+            zapper.y = y;
+            zapper.trigger = trigger;
+        }
+    }
+
+    /// Connect a Four Score adapter (true) for four-player games, for this
+    /// and later cartridges. Controllers 3 and 4 are read after 1 and 2 on
+    /// $4016 and $4017, followed by the adapter's signature.
+    pub fn set_four_score(&mut self, connected: bool) {
+        self.four_score = connected;
+        if let Some(b) = self.cpu.bus_mut() {
+            b.four_score = connected;
+        }
+    }
+
+    /// Whether a Four Score adapter is connected
+    pub fn four_score(&self) -> bool {
+        self.four_score
+    }
+
+    /// Emulate the hardware sprite overflow bug (diagonal OAM scan) for this
+    /// and later cartridges, for games and test ROMs that rely on the flag's
+    /// false positives and negatives
+    pub fn set_sprite_overflow_bug(&mut self, enabled: bool) {
+        self.sprite_overflow_bug = enabled;
+        if let Some(b) = self.cpu.bus_mut() {
+            b.ppu.set_sprite_overflow_bug(enabled);
+        }
+    }
+
+    /// Whether the hardware sprite overflow bug is emulated
+    pub fn sprite_overflow_bug(&self) -> bool {
+        self.sprite_overflow_bug
+    }
+
+    /// Select how closely PPU rendering follows the hardware's timing, for
+    /// this and later cartridges
+    ///
+    /// Both settings scroll each line from the PPU's internal VRAM address,
+    /// updated per line like the hardware does, so status bars split off
+    /// with sprite 0 hit or mid-frame scroll writes stay in place.
+    /// `PpuAccuracy::Frame` synthesizes lines once the PPU time of a line has
+    /// passed with rendering enabled; `PpuAccuracy::Scanline` draws each line
+    /// as the PPU reaches dot 257, whether or not anything is shown.
+    pub fn set_ppu_accuracy(&mut self, accuracy: PpuAccuracy) {
+        self.ppu_accuracy = accuracy;
+        if let Some(b) = self.cpu.bus_mut() {
+            b.ppu.set_accuracy(accuracy);
+        }
+    }
+
+    /// Get the PPU rendering accuracy
+    pub fn ppu_accuracy(&self) -> PpuAccuracy {
+        self.ppu_accuracy
+    }
+
     /// Get debug information for the GUI overlay.
     pub fn get_debug_info(&self) -> DebugInfo {
         let mut mapper_name = "Unknown".to_string();
@@ -220,10 +415,25 @@ impl NesSystem {
                     2 => "UxROM".to_string(),
                     3 => "CNROM".to_string(),
                     4 => "MMC3/TxROM".to_string(),
+                    5 => "MMC5/ExROM".to_string(),
                     7 => "AxROM".to_string(),
                     9 => "MMC2/PxROM".to_string(),
                     10 => "MMC4/FxROM".to_string(),
                     11 => "Color Dreams".to_string(),
+                    19 => "Namco 163".to_string(),
+                    21 => "VRC4a/VRC4c".to_string(),
+                    22 => "VRC2a".to_string(),
+                    23 => "VRC2b/VRC4e/VRC4f".to_string(),
+                    24 => "VRC6a".to_string(),
+                    25 => "VRC2c/VRC4b/VRC4d".to_string(),
+                    26 => "VRC6b".to_string(),
+                    69 => "Sunsoft FME-7".to_string(),
+                    87 => "Jaleco J87".to_string(),
+                    89 => "Sunsoft-2 (Sunsoft-3)".to_string(),
+                    93 => "Sunsoft-2 (Sunsoft-3R)".to_string(),
+                    140 => "Jaleco JF-11/14".to_string(),
+                    184 => "Sunsoft-1".to_string(),
+                    185 => "CNROM + CHR disable".to_string(),
                     _ => format!("Mapper {}", mapper_number),
                 };
                 prg_banks = (b.prg_rom_size() / 16384).max(1); // 16KB banks
@@ -275,6 +485,37 @@ impl NesSystem {
     pub fn renderer_name(&self) -> &str {
         self.renderer.name()
     }
+
+    /// Whether the mounted cartridge has battery-backed PRG-RAM (iNES flags 6 bit 1)
+    pub fn has_battery(&self) -> bool {
+        self.battery
+    }
+
+    /// Battery-backed PRG-RAM ($6000-$7FFF) to write to a `.sav` file
+    ///
+    /// `None` if no cartridge with a battery is mounted.
+    pub fn battery_ram(&self) -> Option<&[u8]> {
+        if !self.battery {
+            return None;
+        }
+        self.cpu.bus().map(|b| &b.wram[..])
+    }
+
+    /// Restore battery-backed PRG-RAM, e.g. from a `.sav` file, after mounting
+    pub fn load_battery_ram(&mut self, data: &[u8]) -> Result<(), NesError> {
+        if !self.battery {
+            return Err(NesError::NoBatteryRam);
+        }
+        let bus = self.cpu.bus_mut().ok_or(NesError::NoBatteryRam)?;
+        if data.len() != bus.wram.len() {
+            return Err(NesError::BatteryRamSize {
+                expected: bus.wram.len(),
+                actual: data.len(),
+            });
+        }
+        bus.wram.copy_from_slice(data);
+        Ok(())
+    }
 }
 
 impl Default for NesSystem {
@@ -288,8 +529,16 @@ impl Default for NesSystem {
         Self {
             cpu,
             timing: TimingMode::Ntsc,
+            timing_override: None,
+            cartridge_timing: TimingMode::Ntsc,
             cartridge_loaded: false,
+            battery: false,
+            sprite_overflow_bug: false,
+            ppu_accuracy: PpuAccuracy::Frame,
+            zapper_connected: false,
+            four_score: false,
             frame_index: 0,
+            frame: None,
             last_stats: RuntimeStats::default(),
             renderer: Box::new(SoftwareNesPpuRenderer::new()),
         }
@@ -297,269 +546,246 @@ impl Default for NesSystem {
 }
 
 impl NesSystem {
-    /// Common cartridge setup logic
-    fn setup_cartridge(&mut self, cart: cartridge::Cartridge) -> Result<(), std::io::Error> {
-        // Set timing mode from cartridge
-        self.timing = cart.timing;
-
-        // Derive the reset vector from the last PRG bank (mirrors hardware vectors).
-        if cart.prg_rom.len() < 0x2000 {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                "PRG ROM too small",
-            ));
+    /// Run CPU instructions for at least `cycles` CPU cycles.
+    ///
+    /// Per-instruction bookkeeping (scanline rendering, mapper IRQ clocks,
+    /// VBlank and NMI) is the same as in `step_frame`, and crossing the end of
+    /// a frame starts the next one. Returns the cycles actually run, which may
+    /// overshoot by part of an instruction.
+    pub fn step_cycles(&mut self, cycles: u32) -> u32 {
+        let mut ran = 0u32;
+        while ran < cycles {
+            ran = ran.wrapping_add(self.step_instruction().0);
         }
-        let last_bank = cart.prg_rom.len() - 0x2000;
-        let reset_lo = cart.prg_rom.get(last_bank + 0x1FFC).copied().unwrap_or(0) as u16;
-        let reset_hi = cart.prg_rom.get(last_bank + 0x1FFD).copied().unwrap_or(0) as u16;
-        self.cpu.set_pc((reset_hi << 8) | reset_lo);
-
-        // For mappers with CHR banking (e.g., MMC3), provide a 8KB pattern slot the mapper fills.
-        let chr_backing = if cart.mapper == 4 && !cart.chr_rom.is_empty() {
-            vec![0u8; 0x2000]
-        } else {
-            cart.chr_rom.clone()
-        };
-
-        let ppu = Ppu::new(chr_backing, cart.mirroring);
-        let mut nb = NesBus::new(ppu);
-        // Set APU timing to match cartridge
-        nb.apu.set_timing(cart.timing);
-        nb.install_cart(cart);
-        self.cpu.set_bus(nb);
-        self.cartridge_loaded = true;
-        Ok(())
+        ran
     }
 
-    /// Load a ROM from byte data
-    pub fn load_rom(&mut self, data: &[u8]) -> Result<(), std::io::Error> {
-        let cart = cartridge::Cartridge::from_bytes(data)?;
-        self.setup_cartridge(cart)
+    /// Run until the PPU reaches the next scanline, returning the CPU cycles used.
+    ///
+    /// Scanlines are 341 PPU (113.67 CPU) cycles; 0-239 are visible and the
+    /// rest of the frame is VBlank. See `scanline` for the current position.
+    pub fn step_scanline(&mut self) -> u32 {
+        let start = self.scanline();
+        let mut ran = 0u32;
+        loop {
+            let (used, frame_done) = self.step_instruction();
+            ran = ran.wrapping_add(used);
+            if frame_done || self.scanline() != start {
+                return ran;
+            }
+        }
     }
 
-    /// Load a mapper-0 (NROM) iNES ROM into CPU memory. This writes PRG ROM
-    /// into 0x8000.. and mirrors 16KB banks into 0xC000 when necessary.
-    pub fn load_rom_from_path<P: AsRef<std::path::Path>>(
-        &mut self,
-        path: P,
-    ) -> Result<(), std::io::Error> {
-        let cart = cartridge::Cartridge::from_file(path)?;
-        self.setup_cartridge(cart)
+    /// Scanline of the frame in progress, derived from the CPU cycles run in it
+    pub fn scanline(&self) -> u32 {
+        self.frame
+            .as_ref()
+            .map_or(0, |p| p.cycles * 3 / PPU_CYCLES_PER_SCANLINE)
     }
-}
 
-#[derive(thiserror::Error, Debug)]
-pub enum NesError {
-    #[error("Invalid ROM format")]
-    InvalidRom,
-    #[error("Unsupported mapper: {0}")]
-    UnsupportedMapper(u8),
-    #[error("Invalid mount point: {0}")]
-    InvalidMountPoint(String),
-    #[error("ROM too small: expected at least {expected} bytes, got {actual}")]
-    RomTooSmall { expected: usize, actual: usize },
-}
-
-impl System for NesSystem {
-    type Error = NesError;
+    /// Picture rendered so far, including scanlines of the frame in progress
+    pub fn frame(&self) -> &Frame {
+        self.renderer.get_frame()
+    }
 
-    fn reset(&mut self) {
-        self.cpu.reset();
+    /// CPU cycles per frame and the length of the VBlank tail for the timing mode
+    fn frame_timing(&self) -> (u32, u32) {
+        match self.timing {
+            TimingMode::Ntsc => (29780, 2500),
+            TimingMode::Pal => (33247, 2798), // PAL has more cycles per frame
+            // 312 scanlines like PAL at NTSC's 3:1 PPU/CPU ratio, with an
+            // NTSC-length VBlank after 51 extra post-render lines
+            TimingMode::Dendy => (35464, 2500),
+        }
     }
 
-    fn step_frame(&mut self) -> Result<Frame, Self::Error> {
-        // Run CPU cycles for one frame.
-        // NTSC: ~29780 CPU cycles, PAL: ~33247 CPU cycles
+    /// Execute one CPU instruction and the frame bookkeeping that follows it.
+    ///
+    /// Returns the cycles used and whether the instruction completed the frame.
+    fn step_instruction(&mut self) -> (u32, bool) {
         // Model VBlank as the *tail* of the frame and trigger NMI at VBlank start.
         // IMPORTANT: render at the end of the *visible* portion (right before VBlank)
         // so we don't sample while games temporarily disable PPUMASK during their NMI.
-
-        let (cycles_per_frame, vblank_cycles) = match self.timing {
-            TimingMode::Ntsc => (29780u32, 2500u32),
-            TimingMode::Pal => (33247u32, 2798u32), // PAL has more cycles per frame
-        };
+        let (cycles_per_frame, vblank_cycles) = self.frame_timing();
         let visible_cycles = cycles_per_frame - vblank_cycles;
 
-        // Approximate PPU scanline timing so mappers like MMC3 can clock their IRQ counter.
-        // The NES PPU runs at 3x the CPU clock and has 341 PPU cycles per scanline.
-        // In this frame-based renderer we synthesize one A12 rising edge per scanline.
-        let mut ppu_cycles_accum: u32 = 0;
-        let ppu_cycles_per_scanline: u32 = 341;
-
-        self.frame_index = self.frame_index.wrapping_add(1);
-
-        let mut cpu_steps: u32 = 0;
-        let mut cpu_cycles_used: u32 = 0;
-        let mut irqs: u32 = 0;
-        let mut nmis: u32 = 0;
-        let mut mmc3_a12_edges: u32 = 0;
-        let mut rendering_happened: bool = false;
-
-        // Track PC histogram for trace logging
-        let mut pc_hist: Option<HashMap<u16, u16>> = Some(HashMap::with_capacity(1024));
+        let mut p = match self.frame.take() {
+            Some(p) => p,
+            None => self.begin_frame(),
+        };
 
-        // Prepare an output frame and render scanlines incrementally during visible time.
-        let mut rendered_scanlines: u32 = 0;
+        let e = p.pc_hist.entry(self.cpu.pc()).or_insert(0);
+        *e = e.saturating_add(1);
 
-        // Visible portion (VBlank low)
+        let mut used = self.cpu.step();
         if let Some(b) = self.cpu.bus_mut() {
-            b.ppu.set_vblank(false);
+            // DMC sample fetches stall the CPU while the DMA reads memory
+            used += b.clock_dmc(used);
         }
-        let mut cycles = 0u32;
-        while cycles < visible_cycles {
-            if let Some(h) = pc_hist.as_mut() {
-                let pc = self.cpu.pc();
-                let e = h.entry(pc).or_insert(0);
-                *e = e.saturating_add(1);
-            }
+        p.cpu_steps = p.cpu_steps.wrapping_add(1);
+        p.cycles = p.cycles.wrapping_add(used);
 
-            let used = self.cpu.step();
-            cpu_steps = cpu_steps.wrapping_add(1);
-            cpu_cycles_used = cpu_cycles_used.wrapping_add(used);
-            cycles = cycles.wrapping_add(used);
+        let mut irq_to_fire = false;
+        let mut nmi_to_fire = false;
 
+        if let Some(b) = self.cpu.bus_mut() {
             // Update bus cycle counter for mapper timing
-            if let Some(b) = self.cpu.bus_mut() {
-                b.add_cycles(used);
-            }
+            b.add_cycles(used);
+            b.clock_mapper(used);
 
-            // Clock APU IRQ counter
-            if let Some(b) = self.cpu.bus_mut() {
+            if !p.in_vblank {
+                // Clock APU IRQ counter
                 b.apu.clock_irq(used);
-            }
-
-            let mut irq_to_fire = false;
-            let mut nmi_to_fire = false;
 
-            // Synthesize scanline edges for mapper IRQs during visible time.
-            // Only do this when rendering is enabled (background or sprites).
-            if let Some(b) = self.cpu.bus_mut() {
+                // Approximate PPU scanline timing so mappers like MMC3 can clock their IRQ
+                // counter. The NES PPU runs at 3x the CPU clock and has 341 PPU cycles per
+                // scanline; each line rendered with rendering enabled (background or
+                // sprites) runs its pattern fetches past the mapper's A12 input.
                 let rendering_enabled = (b.ppu.mask() & 0x18) != 0;
                 if rendering_enabled {
-                    rendering_happened = true;
-                    ppu_cycles_accum = ppu_cycles_accum.saturating_add(used.saturating_mul(3));
-                    while ppu_cycles_accum >= ppu_cycles_per_scanline {
-                        ppu_cycles_accum -= ppu_cycles_per_scanline;
-
-                        // Render the scanline that just completed using the state that was in
-                        // effect during that scanline. MMC3 IRQ-triggered bank changes typically
-                        // affect the *next* scanline.
-                        if rendered_scanlines < 240 {
-                            self.renderer
-                                .render_scanline(&mut b.ppu, rendered_scanlines);
-                            rendered_scanlines += 1;
+                    p.rendering_happened = true;
+                }
+                match self.ppu_accuracy {
+                    PpuAccuracy::Frame if rendering_enabled => {
+                        p.ppu_cycles_accum =
+                            p.ppu_cycles_accum.saturating_add(used.saturating_mul(3));
+                        while p.ppu_cycles_accum >= PPU_CYCLES_PER_SCANLINE {
+                            p.ppu_cycles_accum -= PPU_CYCLES_PER_SCANLINE;
+
+                            // Render the scanline that just completed using the state that was in
+                            // effect during that scanline. MMC3 IRQ-triggered bank changes typically
+                            // affect the *next* scanline.
+                            if p.rendered_scanlines < 240 {
+                                draw_scanline(self.renderer.as_mut(), b, p.rendered_scanlines);
+                                p.rendered_scanlines += 1;
+                                b.ppu.next_line_scroll();
+                            }
+
+                            b.clock_mapper_scanline(Some(p.rendered_scanlines));
+                            p.mmc3_a12_edges = p.mmc3_a12_edges.wrapping_add(1);
+                            if b.take_irq_pending() {
+                                irq_to_fire = true;
+                            }
                         }
-
-                        b.clock_mapper_a12_rising_edge();
-                        mmc3_a12_edges = mmc3_a12_edges.wrapping_add(1);
-                        if b.take_irq_pending() {
-                            irq_to_fire = true;
+                    }
+                    PpuAccuracy::Frame => {}
+                    PpuAccuracy::Scanline => {
+                        // Lines follow the frame's own clock: each is drawn once the PPU
+                        // passes its dot 257, so sprite 0 hit shows up during the line it
+                        // happens on, and `v` then moves on to the next line.
+                        while p.rendered_scanlines < 240
+                            && p.cycles * 3
+                                >= p.rendered_scanlines * PPU_CYCLES_PER_SCANLINE
+                                    + SCROLL_RELOAD_DOT
+                        {
+                            draw_scanline(self.renderer.as_mut(), b, p.rendered_scanlines);
+                            p.rendered_scanlines += 1;
+
+                            if rendering_enabled {
+                                b.ppu.end_scanline_scroll();
+                                b.clock_mapper_scanline(Some(p.rendered_scanlines));
+                                p.mmc3_a12_edges = p.mmc3_a12_edges.wrapping_add(1);
+                                if b.take_irq_pending() {
+                                    irq_to_fire = true;
+                                }
+                            }
                         }
                     }
                 }
-
-                // Also check for any mapper IRQs not driven by the synthesized scanline clock.
-                if b.take_irq_pending() {
-                    irq_to_fire = true;
-                }
-
-                if b.ppu.take_nmi_pending() {
-                    nmi_to_fire = true;
-                }
             }
 
-            if irq_to_fire {
-                log(LogCategory::Interrupts, LogLevel::Info, || {
-                    "System: Firing IRQ! Mapper/APU pending.".to_string()
-                });
-                self.cpu.trigger_irq();
-                irqs = irqs.wrapping_add(1);
+            // Also check for any mapper IRQs not driven by the synthesized scanline clock.
+            if b.take_irq_pending() {
+                irq_to_fire = true;
             }
-            if nmi_to_fire {
-                log(LogCategory::Interrupts, LogLevel::Debug, || {
-                    "System: Firing NMI".to_string()
-                });
-                self.cpu.trigger_nmi();
-                nmis = nmis.wrapping_add(1);
+
+            if b.ppu.take_nmi_pending() {
+                nmi_to_fire = true;
             }
         }
 
-        // If we didn't reach exactly 240 synthesized scanlines (e.g., timing edge cases),
-        // render any remaining scanlines using the final visible-state.
-        if rendered_scanlines < 240 {
-            if let Some(b) = self.cpu.bus_mut() {
-                while rendered_scanlines < 240 {
-                    self.renderer
-                        .render_scanline(&mut b.ppu, rendered_scanlines);
-                    rendered_scanlines += 1;
-                }
-            }
+        if irq_to_fire {
+            log(LogCategory::Interrupts, LogLevel::Info, || {
+                "System: Firing IRQ! Mapper/APU pending.".to_string()
+            });
+            self.cpu.trigger_irq();
+            p.irqs = p.irqs.wrapping_add(1);
+        }
+        if nmi_to_fire {
+            log(LogCategory::Interrupts, LogLevel::Debug, || {
+                "System: Firing NMI".to_string()
+            });
+            self.cpu.trigger_nmi();
+            p.nmis = p.nmis.wrapping_add(1);
         }
 
-        // Apply any pending CHR updates from MMC2/MMC4 latch switching during rendering.
-        if let Some(b) = self.cpu.bus_mut() {
-            b.apply_mapper_chr_update();
+        if !p.in_vblank && p.cycles >= visible_cycles {
+            self.begin_vblank(&mut p);
         }
+        if p.cycles >= cycles_per_frame {
+            self.end_frame(p);
+            return (used, true);
+        }
+        self.frame = Some(p);
+        (used, false)
+    }
 
-        // VBlank start
+    /// Start a new frame with VBlank low
+    fn begin_frame(&mut self) -> FrameProgress {
+        self.frame_index = self.frame_index.wrapping_add(1);
         if let Some(b) = self.cpu.bus_mut() {
-            b.ppu.set_vblank(true);
+            b.ppu.set_vblank(false);
         }
+        FrameProgress {
+            pc_hist: HashMap::with_capacity(1024),
+            ..FrameProgress::default()
+        }
+    }
 
-        // Run the rest of the frame (VBlank time).
-        while cycles < cycles_per_frame {
-            if let Some(h) = pc_hist.as_mut() {
-                let pc = self.cpu.pc();
-                let e = h.entry(pc).or_insert(0);
-                *e = e.saturating_add(1);
+    /// End of the visible portion: finish the picture and raise VBlank
+    fn begin_vblank(&mut self, p: &mut FrameProgress) {
+        p.in_vblank = true;
+        if let Some(b) = self.cpu.bus_mut() {
+            // If we didn't reach exactly 240 synthesized scanlines (e.g., timing edge cases),
+            // render any remaining scanlines using the final visible-state.
+            while p.rendered_scanlines < 240 {
+                draw_scanline(self.renderer.as_mut(), b, p.rendered_scanlines);
+                p.rendered_scanlines += 1;
+                if self.ppu_accuracy == PpuAccuracy::Frame && (b.ppu.mask() & 0x18) != 0 {
+                    b.ppu.next_line_scroll();
+                }
             }
 
-            let used = self.cpu.step();
-            cpu_steps = cpu_steps.wrapping_add(1);
-            cpu_cycles_used = cpu_cycles_used.wrapping_add(used);
-            cycles = cycles.wrapping_add(used);
-
-            // Update bus cycle counter for mapper timing
-            if let Some(b) = self.cpu.bus_mut() {
-                b.add_cycles(used);
-            }
+            // Apply any pending CHR updates from MMC2/MMC4 latch switching during rendering.
+            b.apply_mapper_chr_update();
+            b.notify_mapper_vblank();
 
-            // Check for mapper IRQs during VBlank as well.
-            let mut irq_to_fire = false;
-            let mut nmi_to_fire = false;
-            if let Some(b) = self.cpu.bus_mut() {
-                if b.take_irq_pending() {
-                    irq_to_fire = true;
-                }
-                if b.ppu.take_nmi_pending() {
-                    nmi_to_fire = true;
-                }
-            }
-            if irq_to_fire {
-                self.cpu.trigger_irq();
-                irqs = irqs.wrapping_add(1);
-            }
-            if nmi_to_fire {
-                self.cpu.trigger_nmi();
-                nmis = nmis.wrapping_add(1);
-            }
+            b.ppu.set_vblank(true);
         }
+    }
 
-        // VBlank end / Pre-render scanline start
+    /// VBlank end / pre-render scanline: wrap up the frame and publish its stats
+    fn end_frame(&mut self, mut p: FrameProgress) {
         // Clear sprite flags (sprite 0 hit and sprite overflow) at start of pre-render scanline
         if let Some(b) = self.cpu.bus_mut() {
             b.ppu.clear_sprite_flags();
             b.ppu.set_vblank(false);
+            if (b.ppu.mask() & 0x18) != 0 {
+                b.ppu.reload_scroll();
+            }
+            b.ppu.capture_line_scroll();
+            if let Some(zapper) = &mut b.zapper {
+                zapper.end_frame();
+            }
         }
 
-        // Many MMC3 games clock the IRQ counter 241 times per frame (one per visible scanline plus
-        // an additional clock during the pre-render scanline). Our frame model naturally produces
-        // 240 clocks during the 240 visible scanlines, so add one extra "pre-render" clock when
-        // rendering was enabled at any point during the frame.
-        if rendering_happened {
+        // The pre-render scanline does the same pattern fetches as the visible ones, so
+        // MMC3 games see 241 IRQ counter clocks per frame. Run it when rendering was
+        // enabled at any point during the frame.
+        if p.rendering_happened {
             if let Some(b) = self.cpu.bus_mut() {
-                b.clock_mapper_a12_rising_edge();
-                mmc3_a12_edges = mmc3_a12_edges.wrapping_add(1);
+                b.clock_mapper_scanline(None);
+                p.mmc3_a12_edges = p.mmc3_a12_edges.wrapping_add(1);
             }
         }
 
@@ -589,29 +815,27 @@ impl System for NesSystem {
             PcHotspot::default(),
             PcHotspot::default(),
         ];
-        if let Some(h) = pc_hist {
-            for (pc, count) in h {
-                let s = PcHotspot { pc, count };
-                if s.count > hotspots[0].count {
-                    hotspots[2] = hotspots[1];
-                    hotspots[1] = hotspots[0];
-                    hotspots[0] = s;
-                } else if s.count > hotspots[1].count {
-                    hotspots[2] = hotspots[1];
-                    hotspots[1] = s;
-                } else if s.count > hotspots[2].count {
-                    hotspots[2] = s;
-                }
+        for (pc, count) in std::mem::take(&mut p.pc_hist) {
+            let s = PcHotspot { pc, count };
+            if s.count > hotspots[0].count {
+                hotspots[2] = hotspots[1];
+                hotspots[1] = hotspots[0];
+                hotspots[0] = s;
+            } else if s.count > hotspots[1].count {
+                hotspots[2] = hotspots[1];
+                hotspots[1] = s;
+            } else if s.count > hotspots[2].count {
+                hotspots[2] = s;
             }
         }
 
         self.last_stats = RuntimeStats {
             frame_index: self.frame_index,
-            cpu_steps,
-            cpu_cycles: cpu_cycles_used,
-            irqs,
-            nmis,
-            mmc3_a12_edges,
+            cpu_steps: p.cpu_steps,
+            cpu_cycles: p.cycles,
+            irqs: p.irqs,
+            nmis: p.nmis,
+            mmc3_a12_edges: p.mmc3_a12_edges,
             ppu_ctrl,
             ppu_mask,
             ppu_vblank,
@@ -660,6 +884,91 @@ impl System for NesSystem {
                 String::new()
             }
         });
+    }
+
+    /// Common cartridge setup logic
+    fn setup_cartridge(&mut self, cart: cartridge::Cartridge) -> Result<(), std::io::Error> {
+        // Set timing mode from cartridge, unless the user picked one
+        self.cartridge_timing = cart.timing;
+        self.timing = self.timing_override.unwrap_or(cart.timing);
+
+        // Derive the reset vector from the last PRG bank (mirrors hardware vectors).
+        if cart.prg_rom.len() < 0x2000 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "PRG ROM too small",
+            ));
+        }
+        let last_bank = cart.prg_rom.len() - 0x2000;
+        let reset_lo = cart.prg_rom.get(last_bank + 0x1FFC).copied().unwrap_or(0) as u16;
+        let reset_hi = cart.prg_rom.get(last_bank + 0x1FFD).copied().unwrap_or(0) as u16;
+        self.cpu.set_pc((reset_hi << 8) | reset_lo);
+
+        // For mappers with CHR banking (e.g., MMC3), provide a 8KB pattern slot the mapper fills.
+        let chr_backing = if cart.mapper == 4 && !cart.chr_rom.is_empty() {
+            vec![0u8; 0x2000]
+        } else {
+            cart.chr_rom.clone()
+        };
+
+        let mut ppu = Ppu::new(chr_backing, cart.mirroring);
+        ppu.set_sprite_overflow_bug(self.sprite_overflow_bug);
+        ppu.set_accuracy(self.ppu_accuracy);
+        let mut nb = NesBus::new(ppu);
+        nb.apu.set_timing(self.timing);
+        nb.zapper = self.zapper_connected.then(Zapper::default);
+        nb.four_score = self.four_score;
+        self.battery = cart.battery;
+        nb.install_cart(cart);
+        self.cpu.set_bus(nb);
+        self.cartridge_loaded = true;
+        Ok(())
+    }
+
+    /// Load a ROM from byte data
+    pub fn load_rom(&mut self, data: &[u8]) -> Result<(), std::io::Error> {
+        let cart = cartridge::Cartridge::from_bytes(data)?;
+        self.setup_cartridge(cart)
+    }
+
+    /// Load a mapper-0 (NROM) iNES ROM into CPU memory. This writes PRG ROM
+    /// into 0x8000.. and mirrors 16KB banks into 0xC000 when necessary.
+    pub fn load_rom_from_path<P: AsRef<std::path::Path>>(
+        &mut self,
+        path: P,
+    ) -> Result<(), std::io::Error> {
+        let cart = cartridge::Cartridge::from_file(path)?;
+        self.setup_cartridge(cart)
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum NesError {
+    #[error("Invalid ROM format")]
+    InvalidRom,
+    #[error("Unsupported mapper: {0}")]
+    UnsupportedMapper(u8),
+    #[error("Invalid mount point: {0}")]
+    InvalidMountPoint(String),
+    #[error("ROM too small: expected at least {expected} bytes, got {actual}")]
+    RomTooSmall { expected: usize, actual: usize },
+    #[error("Cartridge has no battery-backed RAM")]
+    NoBatteryRam,
+    #[error("Battery RAM size mismatch: expected {expected} bytes, got {actual}")]
+    BatteryRamSize { expected: usize, actual: usize },
+}
+
+impl System for NesSystem {
+    type Error = NesError;
+
+    fn reset(&mut self) {
+        self.cpu.reset();
+        self.frame = None;
+    }
+
+    fn step_frame(&mut self) -> Result<Frame, Self::Error> {
+        // Finish the frame in progress (or run a whole one) and hand out its picture.
+        while !self.step_instruction().1 {}
 
         // Return the rendered frame from the renderer by taking ownership
         // This avoids cloning 61,440 pixels (245KB) every frame (60 times/second)
@@ -667,32 +976,48 @@ impl System for NesSystem {
     }
 
     fn save_state(&self) -> serde_json::Value {
-        // Note: This is a minimal save state implementation.
-        // A complete implementation would include:
-        // - CPU registers (A, X, Y, SP, P, PC)
-        // - RAM and WRAM contents
-        // - PPU registers and VRAM
-        // - APU state
-        // - Mapper state (bank registers, IRQ counters, etc.)
-        // - Controller latch state
-        //
-        // Currently only saves a minimal placeholder to validate the interface.
-        // ROM verification is handled by the frontend via ROM hash.
-        serde_json::json!({ "system": "nes", "version": 1, "a": self.cpu.a() })
+        // ROM contents are not included; the frontend verifies the ROM hash
+        // and the cartridge stays installed across loads.
+        serde_json::json!({
+            "system": "nes",
+            "version": 2,
+            "cpu": self.cpu.save_state(),
+            "bus": self.cpu.bus().map(|b| b.save_state()),
+            "frame_index": self.frame_index,
+        })
     }
 
     fn load_state(&mut self, v: &serde_json::Value) -> Result<(), serde_json::Error> {
-        // Basic validation: check system type if present
+        use serde::de::Error;
+
         if let Some(system) = v.get("system").and_then(|s| s.as_str()) {
             if system != "nes" {
-                // Wrong system type - use deserialization to generate proper error
-                let _: () = serde_json::from_value(v.clone())?;
+                return Err(Error::custom(format!("not an NES save state: {}", system)));
+            }
+        }
+
+        match v.get("version").and_then(|v| v.as_u64()) {
+            // Version 1 states were a placeholder holding only the A register
+            Some(1) => return Ok(()),
+            Some(2) => {}
+            other => {
+                return Err(Error::custom(format!(
+                    "unsupported NES save state version: {:?}",
+                    other
+                )))
             }
         }
 
-        // Note: ROM verification is handled by the frontend via ROM hash.
-        // Full state restoration will be implemented when save state format is finalized.
-        // Currently validates the state structure only.
+        let cpu: CpuState = serde_json::from_value(v["cpu"].clone())?;
+        let bus = self
+            .cpu
+            .bus_mut()
+            .ok_or_else(|| Error::custom("no NES bus to restore"))?;
+        bus.load_state(&v["bus"])?;
+        self.cpu.load_state(&cpu);
+        self.frame_index = v["frame_index"].as_u64().unwrap_or(self.frame_index);
+        // States are taken between frames; drop any partially run frame
+        self.frame = None;
         Ok(())
     }
 
@@ -729,6 +1054,10 @@ impl System for NesSystem {
     fn is_mounted(&self, mount_point_id: &str) -> bool {
         mount_point_id == "Cartridge" && self.cartridge_loaded
     }
+
+    fn frame_rate(&self) -> f64 {
+        self.timing.frame_rate_hz()
+    }
 }
 
 #[cfg(test)]
@@ -748,6 +1077,167 @@ mod tests {
         assert!(mount_points[0].extensions.contains(&"nes".to_string()));
     }
 
+    #[test]
+    fn test_nes_frame_rate_follows_timing() {
+        let mut sys = NesSystem::default();
+        assert_eq!(sys.frame_rate(), TimingMode::Ntsc.frame_rate_hz());
+        sys.set_timing(TimingMode::Pal);
+        assert_eq!(sys.frame_rate(), TimingMode::Pal.frame_rate_hz());
+    }
+
+    #[test]
+    fn test_nes_dendy_timing_override() {
+        let mut sys = NesSystem::default();
+        sys.set_timing_override(Some(TimingMode::Dendy));
+        assert_eq!(sys.timing(), TimingMode::Dendy);
+
+        // The override outlives mounting a cartridge with NTSC timing
+        let mut rom = vec![0x4E, 0x45, 0x53, 0x1A, 0x01, 0x01];
+        rom.resize(16, 0);
+        rom.extend(vec![0; 16 * 1024 + 8 * 1024]);
+        sys.mount("Cartridge", &rom).unwrap();
+        assert_eq!(sys.timing(), TimingMode::Dendy);
+
+        // Dendy frames are as long as PAL scanline counts make them
+        sys.step_frame().unwrap();
+        let cycles = sys.get_runtime_stats().cpu_cycles;
+        assert!((35464..35480).contains(&cycles), "{} cycles", cycles);
+
+        sys.set_timing_override(None);
+        assert_eq!(sys.timing(), TimingMode::Ntsc);
+    }
+
+    #[test]
+    fn test_nes_sprite_overflow_bug_survives_mount() {
+        let mut sys = NesSystem::default();
+        sys.set_sprite_overflow_bug(true);
+
+        let mut rom = vec![0x4E, 0x45, 0x53, 0x1A, 0x01, 0x01];
+        rom.resize(16, 0);
+        rom.extend(vec![0; 16 * 1024 + 8 * 1024]);
+        sys.mount("Cartridge", &rom).unwrap();
+        assert!(sys.sprite_overflow_bug());
+        assert!(sys.cpu.bus().unwrap().ppu.sprite_overflow_bug());
+    }
+
+    #[test]
+    fn test_nes_scanline_accurate_ppu_steps_scroll() {
+        use crate::bus::Bus;
+
+        let mut sys = NesSystem::default();
+        sys.set_ppu_accuracy(PpuAccuracy::Scanline);
+        sys.mount("Cartridge", &build_test_rom(0)).unwrap();
+        assert_eq!(sys.ppu_accuracy(), PpuAccuracy::Scanline);
+        assert_eq!(sys.cpu.bus().unwrap().ppu.accuracy(), PpuAccuracy::Scanline);
+
+        // Background on, scrolled to coarse X 1, coarse Y 2
+        let bus = sys.cpu.bus_mut().unwrap();
+        bus.write(0x2001, 0x08);
+        bus.write(0x2005, 0x08);
+        bus.write(0x2005, 0x10);
+
+        // The pre-render line loads the scroll into v...
+        sys.step_frame().unwrap();
+        assert_eq!(sys.cpu.bus().unwrap().ppu.vram_addr.get(), 0x0041);
+
+        // ...which then moves down one row per visible line
+        while sys.scanline() < 3 {
+            sys.step_scanline();
+        }
+        assert_eq!(sys.cpu.bus().unwrap().ppu.vram_addr.get(), 0x3041);
+    }
+
+    #[test]
+    fn test_nes_synthesized_scanlines_step_scroll() {
+        use crate::bus::Bus;
+
+        let mut sys = NesSystem::default();
+        sys.mount("Cartridge", &build_test_rom(0)).unwrap();
+        assert_eq!(sys.ppu_accuracy(), PpuAccuracy::Frame);
+
+        // Background on, scrolled to coarse X 1, coarse Y 2
+        let bus = sys.cpu.bus_mut().unwrap();
+        bus.write(0x2001, 0x08);
+        bus.write(0x2005, 0x08);
+        bus.write(0x2005, 0x10);
+        sys.step_frame().unwrap();
+        assert_eq!(sys.cpu.bus().unwrap().ppu.vram_addr.get(), 0x0041);
+
+        // A Y write during the frame waits for the pre-render line
+        let bus = sys.cpu.bus_mut().unwrap();
+        bus.write(0x2005, 0x08);
+        bus.write(0x2005, 0x50);
+        while sys.scanline() < 3 {
+            sys.step_scanline();
+        }
+        assert_eq!(sys.cpu.bus().unwrap().ppu.vram_addr.get(), 0x3041);
+        sys.step_frame().unwrap();
+        assert_eq!(sys.cpu.bus().unwrap().ppu.vram_addr.get(), 0x0141);
+    }
+
+    #[test]
+    fn test_nes_zapper_in_port_2() {
+        let mut sys = NesSystem::default();
+        sys.set_zapper_connected(true);
+
+        let mut rom = vec![0x4E, 0x45, 0x53, 0x1A, 0x01, 0x01];
+        rom.resize(16, 0);
+        rom.extend(vec![0; 16 * 1024 + 8 * 1024]);
+        sys.mount("Cartridge", &rom).unwrap();
+        assert!(sys.zapper_connected());
+
+        // Trigger pulled, aimed at a black screen: no light
+        sys.set_zapper(128, 120, true);
+        sys.step_frame().unwrap();
+        assert_eq!(sys.cpu.bus().unwrap().read(0x4017), 0x18);
+
+        sys.set_zapper_connected(false);
+        sys.set_zapper(128, 120, true);
+        assert_eq!(sys.cpu.bus().unwrap().read(0x4017), 0x00);
+    }
+
+    #[test]
+    fn test_nes_four_score() {
+        use crate::bus::Bus;
+
+        let mut sys = NesSystem::default();
+        sys.set_four_score(true);
+
+        let mut rom = vec![0x4E, 0x45, 0x53, 0x1A, 0x01, 0x01];
+        rom.resize(16, 0);
+        rom.extend(vec![0; 16 * 1024 + 8 * 1024]);
+        sys.mount("Cartridge", &rom).unwrap();
+        assert!(sys.four_score());
+        sys.set_controller(0, 0x01); // Player 1: A
+        sys.set_controller(1, 0x02); // Player 2: B
+        sys.set_controller(2, 0x08); // Player 3: Start
+        sys.set_controller(3, 0x80); // Player 4: Right
+        assert_eq!(sys.controller_state(3), 0x80);
+
+        let bus = sys.cpu.bus_mut().unwrap();
+        bus.write(0x4016, 1);
+        bus.write(0x4016, 0);
+        let read =
+            |bus: &NesBus, addr| -> Vec<u8> { (0..25).map(|_| bus.read(addr) & 1).collect() };
+        let port1 = read(bus, 0x4016);
+        let port2 = read(bus, 0x4017);
+        // Controller 1, controller 3, signature, then 1s
+        assert_eq!(port1[..8], [1, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(port1[8..16], [0, 0, 0, 1, 0, 0, 0, 0]);
+        assert_eq!(port1[16..], [0, 0, 0, 1, 0, 0, 0, 0, 1]);
+        // Controller 2, controller 4, signature, then 1s
+        assert_eq!(port2[..8], [0, 1, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(port2[8..16], [0, 0, 0, 0, 0, 0, 0, 1]);
+        assert_eq!(port2[16..], [0, 0, 1, 0, 0, 0, 0, 0, 1]);
+
+        // Without the adapter, controllers 3 and 4 are never read
+        sys.set_four_score(false);
+        let bus = sys.cpu.bus_mut().unwrap();
+        bus.write(0x4016, 1);
+        bus.write(0x4016, 0);
+        assert_eq!(read(bus, 0x4016)[7..10], [0, 1, 1]);
+    }
+
     #[test]
     fn test_nes_save_state_support() {
         let sys = NesSystem::default();
@@ -786,6 +1276,278 @@ mod tests {
         assert!(sys.load_state(&wrong_state).is_err());
     }
 
+    /// 32KB PRG (first byte of each 8KB bank is its index) running
+    /// `INC $10; JMP $E000` from the fixed last bank, plus 8KB CHR
+    fn build_test_rom(mapper: u8) -> Vec<u8> {
+        let mut rom = vec![0x4E, 0x45, 0x53, 0x1A, 2, 1, mapper << 4, mapper & 0xF0];
+        rom.resize(16, 0);
+        let mut prg = vec![0u8; 0x8000];
+        for bank in 0..4 {
+            prg[bank * 0x2000] = bank as u8;
+        }
+        prg[0x6000..0x6005].copy_from_slice(&[0xE6, 0x10, 0x4C, 0x00, 0xE0]);
+        prg[0x6010] = 0x40; // RTI
+        prg[0x7FFA..0x8000].copy_from_slice(&[0x10, 0xE0, 0x00, 0xE0, 0x10, 0xE0]);
+        rom.extend_from_slice(&prg);
+        rom.extend((0..0x2000).map(|i| i as u8));
+        rom
+    }
+
+    #[test]
+    fn test_apu_register_routing_and_dmc_dma() {
+        use crate::bus::Bus;
+
+        let mut sys = NesSystem::default();
+        sys.mount("Cartridge", &build_test_rom(0)).unwrap();
+        let bus = sys.cpu.bus_mut().unwrap();
+
+        // Triangle, noise and DMC registers reach the APU
+        bus.write(0x4013, 0x01);
+        bus.write(0x4015, 0x1F);
+        bus.write(0x400B, 0x08);
+        bus.write(0x400F, 0x08);
+        assert!(bus.apu.triangle.length_counter > 0);
+        assert!(bus.apu.noise.length_counter > 0);
+        assert_eq!(bus.apu.read_register(0x4015) & 0x1C, 0x1C);
+
+        // The first sample byte is fetched at once, stealing CPU cycles
+        assert_eq!(bus.clock_dmc(2), apu::DMC_DMA_CYCLES);
+        assert_eq!(bus.clock_dmc(2), 0);
+    }
+
+    #[test]
+    fn test_expansion_audio_reaches_output() {
+        use crate::bus::Bus;
+
+        let mut sys = NesSystem::default();
+        sys.mount("Cartridge", &build_test_rom(0)).unwrap();
+        let bus = sys.cpu.bus_mut().unwrap();
+        assert!(bus.apu.mixer.expansion_channel_names().is_empty());
+
+        sys.mount("Cartridge", &build_test_rom(24)).unwrap();
+        let state = sys.save_state();
+        let bus = sys.cpu.bus_mut().unwrap();
+        assert_eq!(
+            bus.apu.mixer.expansion_channel_names(),
+            vec!["VRC6 Pulse 1", "VRC6 Pulse 2", "VRC6 Saw"]
+        );
+
+        // VRC6 pulse 1 in digitized mode holds its full volume
+        bus.write(0x9000, 0x8F);
+        bus.write(0x9002, 0x80);
+        let samples = sys.get_audio_samples(100);
+        assert!(samples.iter().all(|&s| s == (15 << 10) / 5));
+
+        // Loading a state keeps the channels registered
+        sys.load_state(&state).unwrap();
+        let bus = sys.cpu.bus_mut().unwrap();
+        assert_eq!(bus.apu.mixer.expansion_channel_names().len(), 3);
+        bus.write(0x9000, 0x8F);
+        bus.write(0x9002, 0x80);
+        assert_eq!(sys.get_audio_samples(1), vec![(15 << 10) / 5]);
+    }
+
+    #[test]
+    fn test_battery_ram() {
+        use crate::bus::Bus;
+
+        let mut sys = NesSystem::default();
+        sys.mount("Cartridge", &build_test_rom(1)).unwrap();
+        assert!(!sys.has_battery());
+        assert_eq!(sys.battery_ram(), None);
+        assert!(matches!(
+            sys.load_battery_ram(&[0; 0x2000]),
+            Err(NesError::NoBatteryRam)
+        ));
+
+        let mut rom = build_test_rom(1);
+        rom[6] |= 0x02;
+        sys.mount("Cartridge", &rom).unwrap();
+        assert!(sys.has_battery());
+        sys.cpu.bus_mut().unwrap().write(0x6123, 0x5A);
+        let saved = sys.battery_ram().unwrap().to_vec();
+        assert_eq!(saved.len(), 0x2000);
+        assert_eq!(saved[0x123], 0x5A);
+
+        // A fresh mount starts with cleared RAM until the save is loaded back
+        sys.mount("Cartridge", &rom).unwrap();
+        assert_eq!(sys.cpu.bus().unwrap().read(0x6123), 0);
+        sys.load_battery_ram(&saved).unwrap();
+        assert_eq!(sys.cpu.bus().unwrap().read(0x6123), 0x5A);
+
+        assert!(matches!(
+            sys.load_battery_ram(&[0; 0x1000]),
+            Err(NesError::BatteryRamSize {
+                expected: 0x2000,
+                actual: 0x1000
+            })
+        ));
+    }
+
+    #[test]
+    fn test_nes_save_state_round_trip() {
+        use crate::bus::Bus;
+
+        let mut sys = NesSystem::default();
+        sys.mount("Cartridge", &build_test_rom(4)).unwrap();
+        assert!(sys.supports_save_states());
+        sys.step_frame().unwrap();
+
+        let bus = sys.cpu.bus_mut().unwrap();
+        // MMC3: R6 selects PRG bank 2 at $8000, IRQ latch 0x20
+        bus.write(0x8000, 0x06);
+        bus.write(0x8001, 0x02);
+        bus.write(0xC000, 0x20);
+        // Palette entry and WRAM byte
+        bus.write(0x2006, 0x3F);
+        bus.write(0x2006, 0x00);
+        bus.write(0x2007, 0x21);
+        bus.write(0x6000, 0x5A);
+        // Latch controller 1 and shift out one bit
+        bus.set_controller(0, 0x81);
+        bus.write(0x4016, 1);
+        bus.write(0x4016, 0);
+        assert_eq!(bus.read(0x4016), 1);
+        // Pulse 1 playing
+        bus.write(0x4015, 0x01);
+        bus.write(0x4000, 0xBF);
+        bus.write(0x4003, 0x08);
+
+        let state = sys.save_state();
+        assert_eq!(state["version"], 2);
+        let counter = sys.cpu.bus().unwrap().ram[0x10];
+
+        sys.step_frame().unwrap();
+        let bus = sys.cpu.bus_mut().unwrap();
+        bus.write(0x8000, 0x06);
+        bus.write(0x8001, 0x01);
+        bus.write(0x6000, 0x00);
+        bus.write(0x4015, 0x00);
+        assert_ne!(sys.save_state(), state);
+
+        sys.load_state(&state).unwrap();
+        assert_eq!(sys.save_state(), state);
+
+        let bus = sys.cpu.bus().unwrap();
+        assert_eq!(bus.ram[0x10], counter);
+        assert_eq!(bus.read(0x8000), 2);
+        assert_eq!(bus.read(0x6000), 0x5A);
+        assert_eq!(bus.ppu.palette[0], 0x21);
+        assert!(bus.apu.pulse1.length_counter > 0);
+        // Controller continues shifting from the restored latch
+        assert_eq!(bus.read(0x4016), 0);
+    }
+
+    #[test]
+    fn test_nes_load_state_rejects_other_cartridge() {
+        let mut mmc3 = NesSystem::default();
+        mmc3.mount("Cartridge", &build_test_rom(4)).unwrap();
+        let state = mmc3.save_state();
+
+        let mut nrom = NesSystem::default();
+        nrom.mount("Cartridge", &build_test_rom(0)).unwrap();
+        assert!(nrom.load_state(&state).is_err());
+
+        let mut future = state.clone();
+        future["version"] = serde_json::json!(99);
+        assert!(mmc3.load_state(&future).is_err());
+    }
+
+    #[test]
+    fn test_nes_golden_save_state() {
+        // Saved after 30 frames of the test ROM
+        emu_core::save_state::check_golden_state(
+            &mut NesSystem::default(),
+            "Cartridge",
+            include_bytes!("../../../../test_roms/nes/test.nes"),
+            include_str!("../../../../test_roms/nes/test.state.json"),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_nes_golden_battery_ram() {
+        use crate::bus::Bus;
+
+        // A `.sav` written by any build must come back byte for byte
+        let fixture = include_bytes!("../../../../test_roms/nes/test.sav");
+        let mut rom = include_bytes!("../../../../test_roms/nes/test.nes").to_vec();
+        rom[6] |= 0x02; // Battery-backed PRG-RAM
+        let mut sys = NesSystem::default();
+        sys.mount("Cartridge", &rom).unwrap();
+        sys.load_battery_ram(fixture).unwrap();
+        assert_eq!(sys.battery_ram(), Some(&fixture[..]));
+
+        let bus = sys.cpu.bus().unwrap();
+        assert!((0..0x2000u16).all(|i| bus.read(0x6000 + i) == fixture[i as usize]));
+        sys.step_frame().unwrap();
+        assert_eq!(sys.battery_ram(), Some(&fixture[..]));
+    }
+
+    #[test]
+    fn test_nes_golden_v1_save_state() {
+        // Placeholder states from before version 2 still load, as a no-op
+        let fixture: serde_json::Value =
+            serde_json::from_str(include_str!("../../../../test_roms/nes/test.v1.state.json"))
+                .unwrap();
+        let mut sys = NesSystem::default();
+        sys.mount(
+            "Cartridge",
+            include_bytes!("../../../../test_roms/nes/test.nes"),
+        )
+        .unwrap();
+        sys.step_frame().unwrap();
+        let before = sys.save_state();
+        sys.load_state(&fixture).unwrap();
+        assert_eq!(sys.save_state(), before);
+    }
+
+    #[test]
+    fn test_nes_step_cycles_and_scanlines() {
+        let mut sys = NesSystem::default();
+        sys.mount("Cartridge", &build_test_rom(0)).unwrap();
+
+        let ran = sys.step_cycles(1000);
+        assert!((1000..1008).contains(&ran), "{}", ran);
+        assert_eq!(sys.scanline(), ran * 3 / 341);
+
+        // Walk to the end of the visible portion one scanline at a time
+        while sys.scanline() < 240 {
+            let used = sys.step_scanline();
+            assert!((1..=120).contains(&used), "{}", used);
+        }
+        assert!(sys.cpu.bus().unwrap().ppu.vblank_flag());
+        assert_eq!(sys.get_runtime_stats().frame_index, 0);
+
+        // step_frame finishes the frame in progress
+        sys.step_frame().unwrap();
+        assert_eq!(sys.get_runtime_stats().frame_index, 1);
+        assert_eq!(sys.get_runtime_stats().cpu_cycles / 10, 29780 / 10);
+        assert_eq!(sys.scanline(), 0);
+    }
+
+    #[test]
+    fn test_nes_partial_steps_match_step_frame() {
+        let mut whole = NesSystem::default();
+        whole.mount("Cartridge", &build_test_rom(4)).unwrap();
+        let mut split = NesSystem::default();
+        split.mount("Cartridge", &build_test_rom(4)).unwrap();
+
+        for _ in 0..3 {
+            whole.step_frame().unwrap();
+        }
+        split.step_cycles(12345);
+        for _ in 0..20 {
+            split.step_scanline();
+        }
+        // Completes the partial frame, then two more
+        for _ in 0..3 {
+            split.step_frame().unwrap();
+        }
+
+        assert_eq!(whole.save_state(), split.save_state());
+    }
+
     #[test]
     fn test_nes_controller_input() {
         use crate::bus::Bus;
@@ -807,6 +1569,10 @@ mod tests {
             assert_eq!(bus.controller_state[1], 0);
         }
 
+        assert_eq!(sys.controller_state(0), buttons);
+        assert_eq!(sys.controller_state(1), 0);
+        assert_eq!(sys.controller_state(2), 0);
+
         // Set controller 1 state
         let buttons2 = 0b11110000; // D-pad all pressed
         sys.set_controller(1, buttons2);
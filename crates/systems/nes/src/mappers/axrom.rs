@@ -2,14 +2,16 @@ use crate::cartridge::{Cartridge, Mirroring};
 use crate::ppu::Ppu;
 #[cfg(test)]
 use emu_core::apu::TimingMode;
+use serde::{Deserialize, Serialize};
 
 /// AxROM (Mapper 7) - 32KB PRG switching with single-screen mirroring
 ///
 /// AxROM allows switching between multiple 32KB PRG-ROM banks.
 /// The entire CPU address space ($8000-$FFFF) is swapped at once.
 /// Supports configurable single-screen mirroring via bit 4 of the bank select.
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Axrom {
+    #[serde(skip)]
     prg_rom: Vec<u8>,
     prg_bank: u8,
 }
@@ -55,6 +57,14 @@ impl Axrom {
     pub fn prg_rom(&self) -> &[u8] {
         &self.prg_rom
     }
+
+    /// Restore bank registers from a save state, keeping the loaded ROM
+    pub fn restore_state(&mut self, state: Self) {
+        *self = Self {
+            prg_rom: std::mem::take(&mut self.prg_rom),
+            ..state
+        };
+    }
 }
 
 #[cfg(test)]
@@ -68,6 +78,7 @@ mod tests {
             chr_rom: vec![],
             mapper: 7,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Vertical, // Will be overridden by mapper
         };
 
@@ -94,6 +105,7 @@ mod tests {
             chr_rom: vec![],
             mapper: 7,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -123,6 +135,7 @@ mod tests {
             chr_rom: vec![],
             mapper: 7,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -155,6 +168,7 @@ mod tests {
             chr_rom: vec![],
             mapper: 7,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -189,6 +203,7 @@ mod tests {
             chr_rom: vec![],
             mapper: 7,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -220,6 +235,7 @@ mod tests {
             chr_rom: vec![],
             mapper: 7,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -244,6 +260,7 @@ mod tests {
             chr_rom: vec![],
             mapper: 7,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
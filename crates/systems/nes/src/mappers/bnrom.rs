@@ -2,6 +2,7 @@ use crate::cartridge::Cartridge;
 use crate::ppu::Ppu;
 #[cfg(test)]
 use emu_core::apu::TimingMode;
+use serde::{Deserialize, Serialize};
 
 /// BNROM (Mapper 34) - Simple 32KB PRG bank switching with CHR-RAM
 ///
@@ -23,8 +24,9 @@ use emu_core::apu::TimingMode;
 /// focuses on BNROM (NES 2.0 submapper 2), the more common variant.
 ///
 /// Used in games like Deadly Towers, some homebrew titles
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Bnrom {
+    #[serde(skip)]
     prg_rom: Vec<u8>,
     bank_select: u8,
 }
@@ -63,6 +65,14 @@ impl Bnrom {
     pub fn prg_rom(&self) -> &[u8] {
         &self.prg_rom
     }
+
+    /// Restore bank registers from a save state, keeping the loaded ROM
+    pub fn restore_state(&mut self, state: Self) {
+        *self = Self {
+            prg_rom: std::mem::take(&mut self.prg_rom),
+            ..state
+        };
+    }
 }
 
 #[cfg(test)]
@@ -81,6 +91,7 @@ mod tests {
             chr_rom: vec![], // BNROM uses CHR-RAM
             mapper: 34,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Vertical,
         };
 
@@ -108,6 +119,7 @@ mod tests {
             chr_rom: vec![],
             mapper: 34,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -131,6 +143,7 @@ mod tests {
             chr_rom: vec![],
             mapper: 34,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Vertical,
         };
 
@@ -155,6 +168,7 @@ mod tests {
             chr_rom: vec![],
             mapper: 34,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Vertical,
         };
 
@@ -184,6 +198,7 @@ mod tests {
             chr_rom: vec![],
             mapper: 34,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
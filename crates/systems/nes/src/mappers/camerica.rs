@@ -2,6 +2,7 @@ use crate::cartridge::{Cartridge, Mirroring};
 use crate::ppu::Ppu;
 #[cfg(test)]
 use emu_core::apu::TimingMode;
+use serde::{Deserialize, Serialize};
 
 /// Camerica/Codemasters (Mapper 71) - UxROM variant with optional 1-screen mirroring
 ///
@@ -27,8 +28,9 @@ use emu_core::apu::TimingMode;
 /// for bank switching without intending to change mirroring.
 ///
 /// Used in games like Fire Hawk, Micro Machines, Dizzy series, etc.
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Camerica {
+    #[serde(skip)]
     prg_rom: Vec<u8>,
     bank_select: u8,
 }
@@ -95,6 +97,14 @@ impl Camerica {
     pub fn prg_rom(&self) -> &[u8] {
         &self.prg_rom
     }
+
+    /// Restore bank registers from a save state, keeping the loaded ROM
+    pub fn restore_state(&mut self, state: Self) {
+        *self = Self {
+            prg_rom: std::mem::take(&mut self.prg_rom),
+            ..state
+        };
+    }
 }
 
 #[cfg(test)]
@@ -113,6 +123,7 @@ mod tests {
             chr_rom: vec![],
             mapper: 71,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Vertical,
         };
 
@@ -141,6 +152,7 @@ mod tests {
             chr_rom: vec![],
             mapper: 71,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -162,6 +174,7 @@ mod tests {
             chr_rom: vec![],
             mapper: 71,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Vertical,
         };
 
@@ -186,6 +199,7 @@ mod tests {
             chr_rom: vec![],
             mapper: 71,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -212,6 +226,7 @@ mod tests {
             chr_rom: vec![],
             mapper: 71,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Vertical, // Initial mirroring from header
         };
 
@@ -254,6 +269,7 @@ mod tests {
             chr_rom: vec![],
             mapper: 71,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal, // Fixed mirroring from header
         };
 
@@ -2,15 +2,18 @@ use crate::cartridge::Cartridge;
 use crate::ppu::Ppu;
 #[cfg(test)]
 use emu_core::apu::TimingMode;
+use serde::{Deserialize, Serialize};
 
 /// CNROM (Mapper 3) - Simple CHR bank switching
 ///
 /// CNROM allows switching between multiple 8KB CHR-ROM banks.
 /// The entire CHR address space ($0000-$1FFF) is swapped at once.
 /// PRG-ROM uses NROM-style addressing (16KB or 32KB, mirrored as needed).
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Cnrom {
+    #[serde(skip)]
     prg_rom: Vec<u8>,
+    #[serde(skip)]
     chr_rom: Vec<u8>,
     chr_bank: u8,
 }
@@ -82,6 +85,15 @@ impl Cnrom {
     pub fn prg_rom(&self) -> &[u8] {
         &self.prg_rom
     }
+
+    /// Restore bank registers from a save state, keeping the loaded ROM
+    pub fn restore_state(&mut self, state: Self) {
+        *self = Self {
+            prg_rom: std::mem::take(&mut self.prg_rom),
+            chr_rom: std::mem::take(&mut self.chr_rom),
+            ..state
+        };
+    }
 }
 
 #[cfg(test)]
@@ -101,6 +113,7 @@ mod tests {
             chr_rom: chr,
             mapper: 3,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Vertical,
         };
 
@@ -123,6 +136,7 @@ mod tests {
             chr_rom: vec![0; 0x2000],
             mapper: 3,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -146,6 +160,7 @@ mod tests {
             chr_rom: vec![0; 0x2000],
             mapper: 3,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -171,6 +186,7 @@ mod tests {
             chr_rom: chr,
             mapper: 3,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -196,6 +212,7 @@ mod tests {
             chr_rom: chr,
             mapper: 3,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -222,6 +239,7 @@ mod tests {
             chr_rom: chr,
             mapper: 3,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -252,6 +270,7 @@ mod tests {
             chr_rom: chr,
             mapper: 3,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -2,15 +2,18 @@ use crate::cartridge::Cartridge;
 use crate::ppu::Ppu;
 #[cfg(test)]
 use emu_core::apu::TimingMode;
+use serde::{Deserialize, Serialize};
 
 /// Color Dreams (Mapper 11) - Simple PRG and CHR bank switching
 ///
 /// Used primarily in unlicensed Color Dreams and Wisdom Tree games.
 /// Supports up to 4 PRG banks (32KB each) and 16 CHR banks (8KB each).
 /// Bank selection is via writes to $8000-$FFFF.
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ColorDreams {
+    #[serde(skip)]
     prg_rom: Vec<u8>,
+    #[serde(skip)]
     chr_rom: Vec<u8>,
     prg_bank: u8,
     chr_bank: u8,
@@ -81,6 +84,15 @@ impl ColorDreams {
     pub fn prg_rom(&self) -> &[u8] {
         &self.prg_rom
     }
+
+    /// Restore bank registers from a save state, keeping the loaded ROM
+    pub fn restore_state(&mut self, state: Self) {
+        *self = Self {
+            prg_rom: std::mem::take(&mut self.prg_rom),
+            chr_rom: std::mem::take(&mut self.chr_rom),
+            ..state
+        };
+    }
 }
 
 #[cfg(test)]
@@ -102,6 +114,7 @@ mod tests {
             chr_rom: vec![0; 0x2000],
             mapper: 11,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Vertical,
         };
 
@@ -136,6 +149,7 @@ mod tests {
             chr_rom: chr,
             mapper: 11,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -166,6 +180,7 @@ mod tests {
             chr_rom: chr,
             mapper: 11,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Vertical,
         };
 
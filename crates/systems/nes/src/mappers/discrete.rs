@@ -0,0 +1,428 @@
+use crate::cartridge::{Cartridge, Mirroring};
+use crate::ppu::Ppu;
+#[cfg(test)]
+use emu_core::apu::TimingMode;
+use serde::{Deserialize, Serialize};
+
+/// Address range a discrete board decodes its bank register from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RegisterRange {
+    /// $6000-$7FFF (no conflicts, since nothing drives the bus there)
+    Low,
+    /// $8000-$FFFF, on top of the PRG-ROM
+    Rom,
+}
+
+/// PRG-ROM layout of a discrete board
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PrgLayout {
+    /// One 32KB bank at $8000 (16KB ROMs are mirrored)
+    Switch32,
+    /// Switchable 16KB bank at $8000, last bank fixed at $C000
+    Switch16,
+}
+
+/// CHR mapping selected by the register
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChrBanks {
+    /// One 8KB bank at PPU $0000
+    Bank8(usize),
+    /// 4KB banks at PPU $0000 and $1000
+    Banks4(usize, usize),
+    /// CHR-ROM disconnected; reads return open bus (used as copy protection)
+    Disabled,
+}
+
+/// Banks selected by one register value
+#[derive(Debug, Clone, Copy)]
+struct Banks {
+    prg: usize,
+    chr: ChrBanks,
+    /// Mirroring selected by the register (`None` keeps the header's)
+    mirroring: Option<Mirroring>,
+}
+
+/// One discrete-logic board: where its latch sits and how its bits are wired
+#[derive(Debug)]
+struct Board {
+    mapper: u8,
+    register: RegisterRange,
+    bus_conflicts: bool,
+    prg: PrgLayout,
+    decode: fn(u8) -> Banks,
+}
+
+/// Supported boards, keyed by iNES mapper number
+const BOARDS: &[Board] = &[
+    // Jaleco J87: CHR bank with bits 0 and 1 swapped
+    Board {
+        mapper: 87,
+        register: RegisterRange::Low,
+        bus_conflicts: false,
+        prg: PrgLayout::Switch32,
+        decode: |v| Banks {
+            prg: 0,
+            chr: ChrBanks::Bank8((((v & 0x01) << 1) | ((v >> 1) & 0x01)) as usize),
+            mirroring: None,
+        },
+    },
+    // Sunsoft-2 on the Sunsoft-3 board: CPPP MCCC, one-screen mirroring
+    Board {
+        mapper: 89,
+        register: RegisterRange::Rom,
+        bus_conflicts: true,
+        prg: PrgLayout::Switch16,
+        decode: |v| Banks {
+            prg: ((v >> 4) & 0x07) as usize,
+            chr: ChrBanks::Bank8((((v & 0x80) >> 4) | (v & 0x07)) as usize),
+            mirroring: Some(if v & 0x08 != 0 {
+                Mirroring::SingleScreenUpper
+            } else {
+                Mirroring::SingleScreenLower
+            }),
+        },
+    },
+    // Sunsoft-2 on the Sunsoft-3R board: xPPP xxxE, CHR-RAM
+    Board {
+        mapper: 93,
+        register: RegisterRange::Rom,
+        bus_conflicts: true,
+        prg: PrgLayout::Switch16,
+        decode: |v| Banks {
+            prg: ((v >> 4) & 0x07) as usize,
+            chr: ChrBanks::Bank8(0),
+            mirroring: None,
+        },
+    },
+    // Jaleco JF-11/JF-14: xxPP CCCC
+    Board {
+        mapper: 140,
+        register: RegisterRange::Low,
+        bus_conflicts: false,
+        prg: PrgLayout::Switch32,
+        decode: |v| Banks {
+            prg: ((v >> 4) & 0x03) as usize,
+            chr: ChrBanks::Bank8((v & 0x0F) as usize),
+            mirroring: None,
+        },
+    },
+    // Sunsoft-1: xHHH xLLL, two 4KB CHR banks; the upper bank always has
+    // bit 2 set by the board
+    Board {
+        mapper: 184,
+        register: RegisterRange::Low,
+        bus_conflicts: false,
+        prg: PrgLayout::Switch32,
+        decode: |v| Banks {
+            prg: 0,
+            chr: ChrBanks::Banks4((v & 0x07) as usize, (((v >> 4) & 0x07) | 0x04) as usize),
+            mirroring: None,
+        },
+    },
+    // CNROM with a CHR-ROM enable: only the value the game expects connects
+    // the CHR-ROM, others read back open bus
+    Board {
+        mapper: 185,
+        register: RegisterRange::Rom,
+        bus_conflicts: true,
+        prg: PrgLayout::Switch32,
+        decode: |v| Banks {
+            prg: 0,
+            chr: if v & 0x0F != 0 && v != 0x13 {
+                ChrBanks::Bank8(0)
+            } else {
+                ChrBanks::Disabled
+            },
+            mirroring: None,
+        },
+    },
+];
+
+/// Discrete-logic mappers (87, 89, 93, 140, 184, 185)
+///
+/// These boards are a single 74xx latch wired to PRG/CHR bank lines, and
+/// differ only in where the latch sits and which bits go where. Each one is
+/// a [`Board`] entry in a table instead of its own mapper type.
+///
+/// Used by a long tail of Japanese releases, e.g. Argus (87), Tenka no
+/// Goikenban: Mito Koumon (89), Fantasy Zone (93), Bio Senshi Dan (140),
+/// Atlantis no Nazo (184) and Mighty Bomb Jack (185).
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Discrete {
+    #[serde(skip)]
+    prg_rom: Vec<u8>,
+    #[serde(skip)]
+    chr_rom: Vec<u8>,
+    #[serde(skip, default = "Discrete::default_board")]
+    board: &'static Board,
+    register: u8,
+}
+
+impl Discrete {
+    /// Check whether `mapper` is one of the table-driven discrete boards
+    pub fn supports(mapper: u8) -> bool {
+        BOARDS.iter().any(|board| board.mapper == mapper)
+    }
+
+    pub fn new(cart: Cartridge, ppu: &mut Ppu) -> Self {
+        ppu.set_mirroring(cart.mirroring);
+        let board = BOARDS
+            .iter()
+            .find(|board| board.mapper == cart.mapper)
+            .unwrap_or_else(Self::default_board);
+        let discrete = Self {
+            prg_rom: cart.prg_rom,
+            chr_rom: cart.chr_rom,
+            board,
+            register: 0,
+        };
+        // Power-on: first banks, CHR connected
+        discrete.update_chr_mapping(ChrBanks::Bank8(0), ppu);
+        discrete
+    }
+
+    fn default_board() -> &'static Board {
+        &BOARDS[0]
+    }
+
+    pub fn mapper_number(&self) -> u8 {
+        self.board.mapper
+    }
+
+    fn update_chr_mapping(&self, chr: ChrBanks, ppu: &mut Ppu) {
+        if ppu.chr.len() < 0x2000 {
+            ppu.chr.resize(0x2000, 0);
+        }
+
+        // CHR-RAM carts skip copying since PPU owns the RAM view
+        if self.chr_rom.is_empty() {
+            return;
+        }
+
+        match chr {
+            ChrBanks::Bank8(bank) => self.copy_chr(ppu, 0x0000, bank * 0x2000, 0x2000),
+            ChrBanks::Banks4(low, high) => {
+                self.copy_chr(ppu, 0x0000, low * 0x1000, 0x1000);
+                self.copy_chr(ppu, 0x1000, high * 0x1000, 0x1000);
+            }
+            ChrBanks::Disabled => ppu.chr[0..0x2000].fill(0xFF),
+        }
+    }
+
+    fn copy_chr(&self, ppu: &mut Ppu, dest: usize, src: usize, len: usize) {
+        // Banks past the end of the ROM wrap, like the unconnected latch bits
+        let src = src % self.chr_rom.len();
+        match self.chr_rom.get(src..src + len) {
+            Some(bank) => ppu.chr[dest..dest + len].copy_from_slice(bank),
+            None => ppu.chr[dest..dest + len].fill(0),
+        }
+    }
+
+    pub fn read_prg(&self, addr: u16) -> u8 {
+        if self.prg_rom.is_empty() {
+            return 0;
+        }
+        let bank = (self.board.decode)(self.register).prg;
+        let offset = addr as usize - 0x8000;
+        let idx = match self.board.prg {
+            PrgLayout::Switch32 => bank * 0x8000 + offset,
+            PrgLayout::Switch16 if offset < 0x4000 => bank * 0x4000 + offset,
+            PrgLayout::Switch16 => self.prg_rom.len().saturating_sub(0x4000) + (offset - 0x4000),
+        };
+        self.prg_rom[idx % self.prg_rom.len()]
+    }
+
+    /// Write to $8000-$FFFF
+    pub fn write_prg(&mut self, addr: u16, val: u8, ppu: &mut Ppu, _cpu_cycles: u64) {
+        if self.board.register == RegisterRange::Rom && addr >= 0x8000 {
+            // The ROM drives the bus too, so the latch sees both values ANDed
+            let val = if self.board.bus_conflicts {
+                val & self.read_prg(addr)
+            } else {
+                val
+            };
+            self.write_register(val, ppu);
+        }
+    }
+
+    /// Write to $6000-$7FFF
+    pub fn write_low(&mut self, addr: u16, val: u8, ppu: &mut Ppu) {
+        if self.board.register == RegisterRange::Low && (0x6000..=0x7FFF).contains(&addr) {
+            self.write_register(val, ppu);
+        }
+    }
+
+    fn write_register(&mut self, val: u8, ppu: &mut Ppu) {
+        self.register = val;
+        let banks = (self.board.decode)(val);
+        if let Some(mirroring) = banks.mirroring {
+            ppu.set_mirroring(mirroring);
+        }
+        self.update_chr_mapping(banks.chr, ppu);
+    }
+
+    pub fn prg_rom(&self) -> &[u8] {
+        &self.prg_rom
+    }
+
+    /// Restore bank registers from a save state, keeping the loaded ROM
+    pub fn restore_state(&mut self, state: Self) {
+        *self = Self {
+            prg_rom: std::mem::take(&mut self.prg_rom),
+            chr_rom: std::mem::take(&mut self.chr_rom),
+            board: self.board,
+            ..state
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cart(mapper: u8, prg_rom: Vec<u8>, chr_rom: Vec<u8>) -> Cartridge {
+        Cartridge {
+            prg_rom,
+            chr_rom,
+            mapper,
+            timing: TimingMode::Ntsc,
+            battery: false,
+            mirroring: Mirroring::Vertical,
+        }
+    }
+
+    /// CHR-ROM whose `size`-byte banks start with their bank number
+    fn numbered_chr(banks: usize, size: usize) -> Vec<u8> {
+        let mut chr = vec![0; banks * size];
+        for bank in 0..banks {
+            chr[bank * size] = bank as u8;
+        }
+        chr
+    }
+
+    #[test]
+    fn discrete_supported_mappers() {
+        for mapper in [87, 89, 93, 140, 184, 185] {
+            assert!(Discrete::supports(mapper));
+        }
+        assert!(!Discrete::supports(3));
+    }
+
+    #[test]
+    fn jaleco_j87_swaps_chr_bits() {
+        let mut ppu = Ppu::new(vec![], Mirroring::Vertical);
+        let mut m = Discrete::new(cart(87, vec![0; 0x8000], numbered_chr(4, 0x2000)), &mut ppu);
+        assert_eq!(m.mapper_number(), 87);
+
+        // Register is at $6000-$7FFF; $8000 writes are ignored
+        m.write_prg(0x8000, 0x01, &mut ppu, 0);
+        assert_eq!(ppu.chr[0], 0);
+        m.write_low(0x6000, 0x01, &mut ppu);
+        assert_eq!(ppu.chr[0], 2);
+        m.write_low(0x7FFF, 0x02, &mut ppu);
+        assert_eq!(ppu.chr[0], 1);
+    }
+
+    #[test]
+    fn sunsoft2_prg_chr_and_mirroring() {
+        // 8 PRG banks of 16KB filled with their number, except the bytes at
+        // the write address, which decide the bus conflicts
+        let mut prg = vec![0; 8 * 0x4000];
+        for (i, byte) in prg.iter_mut().enumerate() {
+            *byte = (i / 0x4000) as u8;
+        }
+        prg[8 * 0x4000 - 1] = 0xFF; // $FFFF in the fixed bank
+        let mut ppu = Ppu::new(vec![], Mirroring::Vertical);
+        let mut m = Discrete::new(cart(89, prg, numbered_chr(16, 0x2000)), &mut ppu);
+
+        // Last bank fixed at $C000
+        assert_eq!(m.read_prg(0x8000), 0);
+        assert_eq!(m.read_prg(0xC000), 7);
+
+        // PRG 3, CHR 8 + 5, upper one-screen
+        m.write_prg(0xFFFF, 0xBD, &mut ppu, 0);
+        assert_eq!(m.read_prg(0x8000), 3);
+        assert_eq!(m.read_prg(0xC000), 7);
+        assert_eq!(ppu.chr[0], 13);
+        assert_eq!(ppu.get_mirroring(), Mirroring::SingleScreenUpper);
+
+        // Bus conflict: $C000 reads 7, so only the low bits get through
+        m.write_prg(0xC000, 0xFF, &mut ppu, 0);
+        assert_eq!(m.read_prg(0x8000), 0);
+        assert_eq!(ppu.chr[0], 7);
+        assert_eq!(ppu.get_mirroring(), Mirroring::SingleScreenLower);
+    }
+
+    #[test]
+    fn sunsoft2_chr_ram_board() {
+        let mut prg = vec![0xFF; 4 * 0x4000];
+        prg[0x4000] = 0x11;
+        let mut ppu = Ppu::new(vec![], Mirroring::Vertical);
+        let mut m = Discrete::new(cart(93, prg, vec![]), &mut ppu);
+        ppu.chr[0] = 0x5A;
+
+        m.write_prg(0x8000, 0x11, &mut ppu, 0);
+        assert_eq!(m.read_prg(0x8000), 0x11);
+        assert_eq!(ppu.chr[0], 0x5A); // CHR-RAM untouched
+    }
+
+    #[test]
+    fn jaleco_jf11_prg_and_chr() {
+        let mut prg = vec![0; 4 * 0x8000];
+        prg[2 * 0x8000] = 0x22;
+        let mut ppu = Ppu::new(vec![], Mirroring::Vertical);
+        let mut m = Discrete::new(cart(140, prg, numbered_chr(16, 0x2000)), &mut ppu);
+
+        m.write_low(0x6000, 0x2B, &mut ppu);
+        assert_eq!(m.read_prg(0x8000), 0x22);
+        assert_eq!(ppu.chr[0], 11);
+    }
+
+    #[test]
+    fn sunsoft1_4k_chr_banks() {
+        let mut ppu = Ppu::new(vec![], Mirroring::Vertical);
+        let mut m = Discrete::new(
+            cart(184, vec![0; 0x8000], numbered_chr(8, 0x1000)),
+            &mut ppu,
+        );
+
+        m.write_low(0x6000, 0x53, &mut ppu);
+        assert_eq!(ppu.chr[0x0000], 3);
+        assert_eq!(ppu.chr[0x1000], 5);
+
+        // The upper bank can only select banks 4-7
+        m.write_low(0x6000, 0x00, &mut ppu);
+        assert_eq!(ppu.chr[0x0000], 0);
+        assert_eq!(ppu.chr[0x1000], 4);
+    }
+
+    #[test]
+    fn cnrom_chr_disable() {
+        let mut ppu = Ppu::new(vec![], Mirroring::Vertical);
+        let mut m = Discrete::new(cart(185, vec![0xFF; 0x4000], vec![0x42; 0x2000]), &mut ppu);
+
+        // CHR is connected at power-on
+        assert_eq!(ppu.chr[0], 0x42);
+        m.write_prg(0x8000, 0x00, &mut ppu, 0);
+        assert_eq!(ppu.chr[0], 0xFF);
+        m.write_prg(0x8000, 0x21, &mut ppu, 0);
+        assert_eq!(ppu.chr[0], 0x42);
+        m.write_prg(0x8000, 0x13, &mut ppu, 0);
+        assert_eq!(ppu.chr[0], 0xFF);
+    }
+
+    #[test]
+    fn discrete_save_state_keeps_board() {
+        let mut prg = vec![0xFF; 4 * 0x4000];
+        prg[0x4000] = 0x11;
+        let mut ppu = Ppu::new(vec![], Mirroring::Vertical);
+        let mut m = Discrete::new(cart(93, prg.clone(), vec![]), &mut ppu);
+        m.write_prg(0x8000, 0x10, &mut ppu, 0);
+        let state = serde_json::json!(m);
+
+        let mut restored = Discrete::new(cart(93, prg, vec![]), &mut ppu);
+        restored.restore_state(serde_json::from_value(state).unwrap());
+        assert_eq!(restored.mapper_number(), 93);
+        assert_eq!(restored.read_prg(0x8000), 0x11);
+    }
+}
@@ -0,0 +1,243 @@
+use crate::cartridge::{Cartridge, Mirroring};
+use crate::ppu::Ppu;
+#[cfg(test)]
+use emu_core::apu::TimingMode;
+use serde::{Deserialize, Serialize};
+
+/// Sunsoft FME-7 (Mapper 69) - PRG/CHR banking with a CPU cycle IRQ counter
+///
+/// # Hardware Behavior (per NESdev wiki)
+/// - **$8000**: Command register (selects one of 16 internal registers)
+/// - **$A000**: Parameter register (writes the selected register)
+/// - **Commands 0-7**: 1KB CHR banks at $0000-$1FFF
+/// - **Command 8**: $6000-$7FFF: bits 0-5 bank, bit 6 RAM (1) or ROM (0),
+///   bit 7 RAM enable
+/// - **Commands 9-B**: 8KB PRG banks at $8000/$A000/$C000 ($E000 is fixed to
+///   the last bank)
+/// - **Command C**: Mirroring (vertical, horizontal, one-screen lower,
+///   one-screen upper)
+/// - **Command D**: IRQ control (bit 0 IRQ enable, bit 7 counter enable);
+///   writing it acknowledges a pending IRQ
+/// - **Commands E-F**: IRQ counter low/high byte
+/// - **IRQ**: The 16-bit counter decrements every CPU cycle and fires when it
+///   wraps from $0000 to $FFFF
+///
+/// # Implementation Notes
+/// The Sunsoft 5B expansion audio of Gimmick! is not emulated. PRG-RAM at
+/// $6000 is the bus's 8KB WRAM whatever bank is selected.
+///
+/// Used in Gimmick!, Batman: Return of the Joker and Hebereke.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Fme7 {
+    #[serde(skip)]
+    prg_rom: Vec<u8>,
+    #[serde(skip)]
+    chr_rom: Vec<u8>,
+    command: u8,
+    chr_regs: [u8; 8],
+    /// Command 8: $6000 bank and RAM select/enable
+    low_bank: u8,
+    prg_regs: [u8; 3],
+    irq_enabled: bool,
+    irq_counter_enabled: bool,
+    irq_counter: u16,
+    irq_pending: bool,
+}
+
+impl Fme7 {
+    pub fn new(cart: Cartridge, ppu: &mut Ppu) -> Self {
+        let m = Self {
+            prg_rom: cart.prg_rom,
+            chr_rom: cart.chr_rom,
+            command: 0,
+            chr_regs: [0, 1, 2, 3, 4, 5, 6, 7],
+            low_bank: 0,
+            prg_regs: [0, 1, 2],
+            irq_enabled: false,
+            irq_counter_enabled: false,
+            irq_counter: 0,
+            irq_pending: false,
+        };
+        m.update_chr_mapping(ppu);
+        ppu.set_mirroring(cart.mirroring);
+        m
+    }
+
+    fn read_bank(&self, bank: u8, addr: u16) -> u8 {
+        let count = std::cmp::max(1, self.prg_rom.len() / 0x2000);
+        let idx = (bank as usize % count) * 0x2000 + (addr as usize & 0x1FFF);
+        self.prg_rom.get(idx).copied().unwrap_or(0)
+    }
+
+    fn update_chr_mapping(&self, ppu: &mut Ppu) {
+        if ppu.chr.len() < 0x2000 {
+            ppu.chr.resize(0x2000, 0);
+        }
+
+        // For CHR RAM carts, skip copying (PPU owns RAM)
+        if self.chr_rom.is_empty() {
+            return;
+        }
+
+        let chr_count = std::cmp::max(1, self.chr_rom.len() / 0x0400);
+        for (i, &bank) in self.chr_regs.iter().enumerate() {
+            let dst_start = i * 0x0400;
+            let src_start = (bank as usize % chr_count) * 0x0400;
+            ppu.chr[dst_start..dst_start + 0x0400]
+                .copy_from_slice(&self.chr_rom[src_start..src_start + 0x0400]);
+        }
+    }
+
+    pub fn read_prg(&self, addr: u16) -> u8 {
+        match addr {
+            0x8000..=0xDFFF => self.read_bank(self.prg_regs[(addr as usize - 0x8000) >> 13], addr),
+            _ => self.read_bank(0xFF, addr),
+        }
+    }
+
+    /// ROM mapped at $6000-$7FFF, or None when RAM is selected there
+    pub fn read_low(&self, addr: u16) -> Option<u8> {
+        if !(0x6000..=0x7FFF).contains(&addr) || self.low_bank & 0x40 != 0 {
+            return None;
+        }
+        Some(self.read_bank(self.low_bank & 0x3F, addr))
+    }
+
+    pub fn write_prg(&mut self, addr: u16, val: u8, ppu: &mut Ppu, _cpu_cycles: u64) {
+        match addr {
+            0x8000..=0x9FFF => self.command = val & 0x0F,
+            0xA000..=0xBFFF => match self.command {
+                0x0..=0x7 => {
+                    self.chr_regs[self.command as usize] = val;
+                    self.update_chr_mapping(ppu);
+                }
+                0x8 => self.low_bank = val,
+                0x9..=0xB => self.prg_regs[self.command as usize - 9] = val & 0x3F,
+                0xC => {
+                    let mirroring = match val & 0x03 {
+                        0 => Mirroring::Vertical,
+                        1 => Mirroring::Horizontal,
+                        2 => Mirroring::SingleScreenLower,
+                        _ => Mirroring::SingleScreenUpper,
+                    };
+                    ppu.set_mirroring(mirroring);
+                }
+                0xD => {
+                    self.irq_enabled = val & 0x01 != 0;
+                    self.irq_counter_enabled = val & 0x80 != 0;
+                    self.irq_pending = false;
+                }
+                0xE => self.irq_counter = (self.irq_counter & 0xFF00) | val as u16,
+                _ => self.irq_counter = (self.irq_counter & 0x00FF) | ((val as u16) << 8),
+            },
+            // $C000-$FFFF is the Sunsoft 5B audio on boards that have it
+            _ => {}
+        }
+    }
+
+    /// Run the IRQ counter for `cycles` CPU cycles
+    pub fn clock_cpu(&mut self, cycles: u32) {
+        if !self.irq_counter_enabled {
+            return;
+        }
+        let wrapped = cycles > self.irq_counter as u32;
+        self.irq_counter = (self.irq_counter as u32).wrapping_sub(cycles) as u16;
+        if wrapped && self.irq_enabled {
+            self.irq_pending = true;
+        }
+    }
+
+    pub fn take_irq_pending(&mut self) -> bool {
+        self.irq_pending
+    }
+
+    pub fn prg_rom(&self) -> &[u8] {
+        &self.prg_rom
+    }
+
+    /// Restore bank registers from a save state, keeping the loaded ROM
+    pub fn restore_state(&mut self, state: Self) {
+        *self = Self {
+            prg_rom: std::mem::take(&mut self.prg_rom),
+            chr_rom: std::mem::take(&mut self.chr_rom),
+            ..state
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fme7() -> (Fme7, Ppu) {
+        let mut prg = vec![0; 0x40000]; // 32 banks of 8KB
+        for i in 0..32 {
+            prg[i * 0x2000] = i as u8;
+        }
+        let mut chr = vec![0; 0x8000]; // 32 banks of 1KB
+        for i in 0..32 {
+            chr[i * 0x400] = i as u8;
+        }
+        let cart = Cartridge {
+            prg_rom: prg,
+            chr_rom: chr,
+            mapper: 69,
+            timing: TimingMode::Ntsc,
+            battery: false,
+            mirroring: Mirroring::Vertical,
+        };
+        let mut ppu = Ppu::new(vec![], Mirroring::Vertical);
+        let m = Fme7::new(cart, &mut ppu);
+        (m, ppu)
+    }
+
+    fn command(m: &mut Fme7, ppu: &mut Ppu, command: u8, val: u8) {
+        m.write_prg(0x8000, command, ppu, 0);
+        m.write_prg(0xA000, val, ppu, 0);
+    }
+
+    #[test]
+    fn fme7_banking() {
+        let (mut m, mut ppu) = fme7();
+        command(&mut m, &mut ppu, 0x9, 5);
+        command(&mut m, &mut ppu, 0xA, 6);
+        command(&mut m, &mut ppu, 0xB, 7);
+        assert_eq!(m.read_prg(0x8000), 5);
+        assert_eq!(m.read_prg(0xA000), 6);
+        assert_eq!(m.read_prg(0xC000), 7);
+        assert_eq!(m.read_prg(0xE000), 31);
+
+        // ROM at $6000, then RAM
+        command(&mut m, &mut ppu, 0x8, 12);
+        assert_eq!(m.read_low(0x6000), Some(12));
+        command(&mut m, &mut ppu, 0x8, 0xC0);
+        assert_eq!(m.read_low(0x6000), None);
+
+        command(&mut m, &mut ppu, 0x3, 25);
+        assert_eq!(ppu.chr[0x0C00], 25);
+
+        command(&mut m, &mut ppu, 0xC, 3);
+        assert_eq!(ppu.get_mirroring(), Mirroring::SingleScreenUpper);
+    }
+
+    #[test]
+    fn fme7_irq_fires_when_counter_wraps() {
+        let (mut m, mut ppu) = fme7();
+        command(&mut m, &mut ppu, 0xE, 100);
+        command(&mut m, &mut ppu, 0xF, 0);
+        command(&mut m, &mut ppu, 0xD, 0x81);
+        m.clock_cpu(100);
+        assert!(!m.take_irq_pending());
+        m.clock_cpu(1);
+        assert!(m.take_irq_pending());
+
+        // Writing the control register acknowledges it
+        command(&mut m, &mut ppu, 0xD, 0x81);
+        assert!(!m.take_irq_pending());
+
+        // Counting without IRQs enabled never fires
+        command(&mut m, &mut ppu, 0xD, 0x80);
+        m.clock_cpu(0x20000);
+        assert!(!m.take_irq_pending());
+    }
+}
@@ -2,6 +2,7 @@ use crate::cartridge::Cartridge;
 use crate::ppu::Ppu;
 #[cfg(test)]
 use emu_core::apu::TimingMode;
+use serde::{Deserialize, Serialize};
 
 /// GxROM (Mapper 66) - Simple dual PRG/CHR bank switching
 ///
@@ -11,9 +12,11 @@ use emu_core::apu::TimingMode;
 /// - Bits 4-5: Select 8KB CHR bank
 ///
 /// Used in games like SMB + Duck Hunt, Doraemon, etc.
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Gxrom {
+    #[serde(skip)]
     prg_rom: Vec<u8>,
+    #[serde(skip)]
     chr_rom: Vec<u8>,
     prg_bank: u8,
     chr_bank: u8,
@@ -88,6 +91,15 @@ impl Gxrom {
     pub fn prg_rom(&self) -> &[u8] {
         &self.prg_rom
     }
+
+    /// Restore bank registers from a save state, keeping the loaded ROM
+    pub fn restore_state(&mut self, state: Self) {
+        *self = Self {
+            prg_rom: std::mem::take(&mut self.prg_rom),
+            chr_rom: std::mem::take(&mut self.chr_rom),
+            ..state
+        };
+    }
 }
 
 #[cfg(test)]
@@ -107,6 +119,7 @@ mod tests {
             chr_rom: vec![0; 0x2000],
             mapper: 66,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Vertical,
         };
 
@@ -134,6 +147,7 @@ mod tests {
             chr_rom: chr,
             mapper: 66,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -168,6 +182,7 @@ mod tests {
             chr_rom: chr,
             mapper: 66,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Vertical,
         };
 
@@ -203,6 +218,7 @@ mod tests {
             chr_rom: chr,
             mapper: 66,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Vertical,
         };
 
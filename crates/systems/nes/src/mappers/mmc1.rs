@@ -2,11 +2,14 @@ use crate::cartridge::{Cartridge, Mirroring};
 use crate::ppu::Ppu;
 #[cfg(test)]
 use emu_core::apu::TimingMode;
+use serde::{Deserialize, Serialize};
 
 /// MMC1 (Mapper 1/SxROM) - Switchable PRG and CHR banks with configurable mirroring
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Mmc1 {
+    #[serde(skip)]
     prg_rom: Vec<u8>,
+    #[serde(skip)]
     chr_rom: Vec<u8>,
     shift_reg: u8,
     write_count: u8,
@@ -174,6 +177,15 @@ impl Mmc1 {
     pub fn prg_rom(&self) -> &[u8] {
         &self.prg_rom
     }
+
+    /// Restore bank registers from a save state, keeping the loaded ROM
+    pub fn restore_state(&mut self, state: Self) {
+        *self = Self {
+            prg_rom: std::mem::take(&mut self.prg_rom),
+            chr_rom: std::mem::take(&mut self.chr_rom),
+            ..state
+        };
+    }
 }
 
 #[cfg(test)]
@@ -187,6 +199,7 @@ mod tests {
             chr_rom: vec![0; 0x2000], // 2 banks
             mapper: 1,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -212,6 +225,7 @@ mod tests {
             chr_rom: vec![],
             mapper: 1,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -243,6 +257,7 @@ mod tests {
             chr_rom: vec![],
             mapper: 1,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -262,6 +277,7 @@ mod tests {
             chr_rom: vec![],
             mapper: 1,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -281,6 +297,46 @@ mod tests {
         assert_eq!(mmc1.write_count, 3, "Write count should be 3");
     }
 
+    #[test]
+    fn mmc1_save_state_mid_shift_sequence() {
+        let cart = || {
+            let mut prg = vec![0; 0x20000]; // 8 banks of 16KB
+            prg[0x4000 * 5] = 0x55;
+            Cartridge {
+                prg_rom: prg,
+                chr_rom: vec![],
+                mapper: 1,
+                timing: TimingMode::Ntsc,
+                battery: false,
+                mirroring: Mirroring::Horizontal,
+            }
+        };
+
+        let mut ppu = Ppu::new(vec![], Mirroring::Horizontal);
+        let mut mmc1 = Mmc1::new(cart(), &mut ppu);
+
+        // First 3 bits of PRG bank 5 (0b00101), then save
+        mmc1.write_prg(0xE000, 1, &mut ppu, 10);
+        mmc1.write_prg(0xE000, 0, &mut ppu, 12);
+        mmc1.write_prg(0xE000, 1, &mut ppu, 14);
+        let state = serde_json::json!(mmc1);
+
+        let mut restored = Mmc1::new(cart(), &mut ppu);
+        restored.restore_state(serde_json::from_value(state).unwrap());
+        assert_eq!(restored.write_count, 3);
+        assert_eq!(restored.shift_reg, mmc1.shift_reg);
+        assert_eq!(restored.last_write_cycle, 14);
+
+        // A write on the same cycle as the last one is still ignored
+        restored.write_prg(0xE000, 0, &mut ppu, 14);
+        assert_eq!(restored.write_count, 3);
+
+        // The last 2 bits complete the register
+        restored.write_prg(0xE000, 0, &mut ppu, 16);
+        restored.write_prg(0xE000, 0, &mut ppu, 18);
+        assert_eq!(restored.read_prg(0x8000), 0x55);
+    }
+
     #[test]
     fn mmc1_32kb_prg_mode() {
         let mut prg = vec![0; 0x10000]; // 4 banks
@@ -293,6 +349,7 @@ mod tests {
             chr_rom: vec![],
             mapper: 1,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -328,6 +385,7 @@ mod tests {
             chr_rom: vec![],
             mapper: 1,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -367,6 +425,7 @@ mod tests {
             chr_rom: chr,
             mapper: 1,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -407,6 +466,7 @@ mod tests {
             chr_rom: chr,
             mapper: 1,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -431,6 +491,7 @@ mod tests {
             chr_rom: vec![],
             mapper: 1,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -473,6 +534,7 @@ mod tests {
             chr_rom: vec![],
             mapper: 1,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -498,6 +560,7 @@ mod tests {
             chr_rom: chr,
             mapper: 1,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -2,6 +2,7 @@ use crate::cartridge::{Cartridge, Mirroring};
 use crate::ppu::Ppu;
 #[cfg(test)]
 use emu_core::apu::TimingMode;
+use serde::{Deserialize, Serialize};
 
 /// MMC2 (Mapper 9) - Used primarily in Punch-Out!!
 ///
@@ -21,9 +22,11 @@ use emu_core::apu::TimingMode;
 /// Latch switching is now fully implemented via CHR read callbacks. When the PPU
 /// reads from latch trigger addresses during rendering, the mapper tracks latch
 /// state changes and applies CHR bank updates after each frame completes.
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Mmc2 {
+    #[serde(skip)]
     prg_rom: Vec<u8>,
+    #[serde(skip)]
     chr_rom: Vec<u8>,
     prg_bank: u8,
     // CHR banks for left pattern table ($0000-$0FFF)
@@ -248,6 +251,15 @@ impl Mmc2 {
     pub fn prg_rom(&self) -> &[u8] {
         &self.prg_rom
     }
+
+    /// Restore bank registers from a save state, keeping the loaded ROM
+    pub fn restore_state(&mut self, state: Self) {
+        *self = Self {
+            prg_rom: std::mem::take(&mut self.prg_rom),
+            chr_rom: std::mem::take(&mut self.chr_rom),
+            ..state
+        };
+    }
 }
 
 #[cfg(test)]
@@ -266,6 +278,7 @@ mod tests {
             chr_rom: vec![0; 0x2000],
             mapper: 9,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Vertical,
         };
 
@@ -295,6 +308,7 @@ mod tests {
             chr_rom: chr,
             mapper: 9,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Vertical,
         };
 
@@ -317,6 +331,43 @@ mod tests {
         assert_eq!(ppu.chr[0], 0x33);
     }
 
+    #[test]
+    fn mmc2_save_state_keeps_latches() {
+        let cart = || {
+            let mut chr = vec![0; 0x8000]; // 8 banks of 4KB each
+            chr[0x1000] = 0x22; // Bank 1
+            chr[0x2000] = 0x33; // Bank 2
+            Cartridge {
+                prg_rom: vec![0; 0x8000],
+                chr_rom: chr,
+                mapper: 9,
+                timing: TimingMode::Ntsc,
+                battery: false,
+                mirroring: Mirroring::Vertical,
+            }
+        };
+
+        let mut ppu = Ppu::new(vec![], Mirroring::Vertical);
+        let mut mmc2 = Mmc2::new(cart(), &mut ppu);
+        mmc2.write_prg(0xB000, 1, &mut ppu, 0); // FD/0000
+        mmc2.write_prg(0xC000, 2, &mut ppu, 0); // FE/0000
+
+        // Latch flipped during rendering, CHR not yet updated
+        mmc2.notify_chr_read(0x0FD8);
+        let state = serde_json::json!(mmc2);
+
+        let mut restored_ppu = Ppu::new(vec![], Mirroring::Vertical);
+        let mut restored = Mmc2::new(cart(), &mut restored_ppu);
+        restored.restore_state(serde_json::from_value(state).unwrap());
+        assert_eq!(restored.latch_0, 0xFD);
+        assert_eq!(restored.chr_bank_fd, 1);
+        assert_eq!(restored.chr_bank_fe, 2);
+
+        // The pending update is applied at the end of the frame
+        restored.apply_chr_update(&mut restored_ppu);
+        assert_eq!(restored_ppu.chr[0], 0x22);
+    }
+
     #[test]
     fn mmc2_mirroring_control() {
         let cart = Cartridge {
@@ -324,6 +375,7 @@ mod tests {
             chr_rom: vec![0; 0x2000],
             mapper: 9,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Vertical,
         };
 
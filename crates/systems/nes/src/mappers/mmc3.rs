@@ -2,6 +2,13 @@ use crate::cartridge::{Cartridge, Mirroring};
 use crate::ppu::Ppu;
 #[cfg(test)]
 use emu_core::apu::TimingMode;
+use serde::{Deserialize, Serialize};
+
+/// PPU dots A12 must stay low before a rise clocks the IRQ counter (about
+/// three M2 cycles). The 4-dot lows between pattern fetches are filtered out,
+/// as are the 9 dots between the background prefetch of one line and the
+/// first pattern fetch of the next.
+const A12_LOW_FILTER_DOTS: u64 = 10;
 
 /// MMC3 (Mapper 4/TxROM) - Advanced mapper with PRG/CHR banking and scanline IRQ counter
 ///
@@ -16,6 +23,7 @@ use emu_core::apu::TimingMode;
 ///   * Mode 0: Two 2KB banks at $0000/$0800, four 1KB banks at $1000-$1FFF
 ///   * Mode 1: Four 1KB banks at $0000-$0FFF, two 2KB banks at $1000/$1800
 /// - **IRQ Counter**: Scanline-based counter triggered by PPU A12 rising edges
+///   that follow A12 being low for a few M2 cycles
 ///   * $C000: IRQ latch (reload value)
 ///   * $C001: IRQ reload (clears counter, sets reload flag)
 ///   * $E000: IRQ disable (also clears pending)
@@ -25,9 +33,11 @@ use emu_core::apu::TimingMode;
 /// # Implementation Notes
 /// This implementation uses the "new/sharp" IRQ behavior where the counter
 /// triggers IRQ only when it decrements to 0, not when it reloads to 0.
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Mmc3 {
+    #[serde(skip)]
     prg_rom: Vec<u8>,
+    #[serde(skip)]
     chr_rom: Vec<u8>,
     bank_select: u8,
     bank_regs: [u8; 8],
@@ -41,6 +51,9 @@ pub struct Mmc3 {
     irq_enabled: bool,
     irq_pending: bool,
     last_a12: bool,
+    /// PPU dot at which A12 last went low, for the A12 low filter
+    #[serde(default)]
+    a12_low_dot: u64,
     /// PRG RAM protection register ($A001)
     /// Bit 7: 1=Enable chip, 0=Disable chip
     /// Bit 6: 0=Allow writes, 1=Deny writes
@@ -65,6 +78,7 @@ impl Mmc3 {
             irq_enabled: false,
             irq_pending: false,
             last_a12: false,
+            a12_low_dot: 0,
             // Default to enabled and writable to match common emulator behavior
             // and previous "always on" behavior of NesBus.
             prg_ram_protect: 0x80,
@@ -239,27 +253,48 @@ impl Mmc3 {
 
     pub fn notify_a12(&mut self, a12_high: bool) {
         if !self.last_a12 && a12_high {
-            // Rising edge clocks the counter per MMC3 spec.
-            // When clocked: if counter==0 OR reload flag set, reload from latch; else decrement.
-            let did_decrement = if self.irq_reload || self.irq_counter == 0 {
-                self.irq_counter = self.irq_latch;
-                self.irq_reload = false;
-                false // Reloaded, did not decrement
-            } else {
-                self.irq_counter = self.irq_counter.saturating_sub(1);
-                true // Decremented
-            };
-
-            // MMC3B/C (Sharp/new) behavior: trigger IRQ only when counter DECREMENTS to 0.
-            // MMC3A (NEC/old/alternate) behavior would trigger when counter==0 regardless of reload.
-            // We use MMC3B/C by default as it's more common and matches most emulator behavior.
-            if self.irq_counter == 0 && self.irq_enabled && did_decrement {
-                self.irq_pending = true;
+            self.clock_irq_counter();
+        }
+        self.last_a12 = a12_high;
+    }
+
+    /// A12 transition at PPU dot `dot`, filtered like the real chip.
+    ///
+    /// The MMC3 only counts a rise after A12 has been low for a few M2
+    /// cycles, so the brief lows between the pattern fetches of a scanline
+    /// are ignored and the counter is clocked once per line: when the sprite
+    /// fetches follow background fetches from the other pattern table.
+    pub fn notify_a12_at(&mut self, a12_high: bool, dot: u64) {
+        if a12_high && !self.last_a12 {
+            if dot.saturating_sub(self.a12_low_dot) >= A12_LOW_FILTER_DOTS {
+                self.clock_irq_counter();
             }
+        } else if !a12_high && self.last_a12 {
+            self.a12_low_dot = dot;
         }
         self.last_a12 = a12_high;
     }
 
+    /// Clock the scanline counter on a counted A12 rise
+    fn clock_irq_counter(&mut self) {
+        // When clocked: if counter==0 OR reload flag set, reload from latch; else decrement.
+        let did_decrement = if self.irq_reload || self.irq_counter == 0 {
+            self.irq_counter = self.irq_latch;
+            self.irq_reload = false;
+            false // Reloaded, did not decrement
+        } else {
+            self.irq_counter = self.irq_counter.saturating_sub(1);
+            true // Decremented
+        };
+
+        // MMC3B/C (Sharp/new) behavior: trigger IRQ only when counter DECREMENTS to 0.
+        // MMC3A (NEC/old/alternate) behavior would trigger when counter==0 regardless of reload.
+        // We use MMC3B/C by default as it's more common and matches most emulator behavior.
+        if self.irq_counter == 0 && self.irq_enabled && did_decrement {
+            self.irq_pending = true;
+        }
+    }
+
     pub fn take_irq_pending(&mut self) -> bool {
         self.irq_pending
     }
@@ -268,6 +303,15 @@ impl Mmc3 {
         &self.prg_rom
     }
 
+    /// Restore bank registers from a save state, keeping the loaded ROM
+    pub fn restore_state(&mut self, state: Self) {
+        *self = Self {
+            prg_rom: std::mem::take(&mut self.prg_rom),
+            chr_rom: std::mem::take(&mut self.chr_rom),
+            ..state
+        };
+    }
+
     #[allow(dead_code)] // Part of mapper API, not yet integrated with bus
     pub fn wram_access(&self) -> (bool, bool) {
         // Bit 7: 1=Enable, 0=Disable
@@ -294,6 +338,7 @@ mod tests {
             chr_rom: vec![],
             mapper: 4,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -312,6 +357,51 @@ mod tests {
         assert_eq!(mmc3.read_prg(0x8000), 0x22); // Now bank 1
     }
 
+    #[test]
+    fn mmc3_a12_filter_clocks_once_per_scanline() {
+        let cart = Cartridge {
+            prg_rom: vec![0; 0x8000],
+            chr_rom: vec![0; 0x2000],
+            mapper: 4,
+            timing: TimingMode::Ntsc,
+            battery: false,
+            mirroring: Mirroring::Horizontal,
+        };
+
+        let mut ppu = Ppu::new(vec![], Mirroring::Horizontal);
+        let mut mmc3 = Mmc3::new(cart, &mut ppu);
+        mmc3.write_prg(0xC000, 255, &mut ppu, 0);
+        mmc3.write_prg(0xC001, 0, &mut ppu, 0);
+
+        // Run `lines` scanlines of fetches with the given PPUCTRL and return
+        // how many times the counter was clocked
+        let mut dot = 0;
+        let mut run = |mmc3: &mut Mmc3, ctrl: u8, lines: u32| {
+            ppu.write_register(0, ctrl);
+            let before = mmc3.irq_counter;
+            for line in 0..lines {
+                for (edge, a12_high) in ppu.a12_fetch_edges(Some(line + 1)) {
+                    mmc3.notify_a12_at(a12_high, dot + u64::from(edge));
+                }
+                dot += 341;
+            }
+            before.wrapping_sub(mmc3.irq_counter)
+        };
+
+        // Sprites at $1000: reload on the first line, then one decrement per line
+        assert_eq!(run(&mut mmc3, 0x08, 1), 1);
+        assert_eq!(mmc3.irq_counter, 255);
+        assert_eq!(run(&mut mmc3, 0x08, 10), 10);
+        // Background at $1000, sprites at $0000: clocked by the prefetch, the 9
+        // dots low before the next line's first tile are filtered out (the
+        // line switching tables also counts its first background fetch)
+        assert_eq!(run(&mut mmc3, 0x10, 1), 2);
+        assert_eq!(run(&mut mmc3, 0x10, 10), 10);
+        // The pattern fetches' short lows never get through the filter
+        assert_eq!(run(&mut mmc3, 0x18, 10), 0);
+        assert_eq!(run(&mut mmc3, 0x00, 10), 0);
+    }
+
     #[test]
     fn mmc3_irq_counter() {
         let cart = Cartridge {
@@ -319,6 +409,7 @@ mod tests {
             chr_rom: vec![0; 0x2000],
             mapper: 4,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -358,6 +449,7 @@ mod tests {
             chr_rom: vec![],
             mapper: 4,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -380,6 +472,7 @@ mod tests {
             chr_rom: vec![0; 0x2000],
             mapper: 4,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -420,6 +513,7 @@ mod tests {
             chr_rom: vec![0; 0x2000],
             mapper: 4,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -454,6 +548,7 @@ mod tests {
             chr_rom: vec![0; 0x2000],
             mapper: 4,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -504,6 +599,7 @@ mod tests {
             chr_rom: vec![0x11; 0x10000], // 128KB CHR (128 1KB banks)
             mapper: 4,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -541,6 +637,7 @@ mod tests {
             chr_rom: vec![0; 0x2000], // Only 8 1KB banks
             mapper: 4,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -573,6 +670,7 @@ mod tests {
             chr_rom: vec![],
             mapper: 4,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -613,6 +711,7 @@ mod tests {
             chr_rom: chr,
             mapper: 4,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -650,6 +749,7 @@ mod tests {
             chr_rom: vec![0; 0x2000],
             mapper: 4,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -697,6 +797,7 @@ mod tests {
             chr_rom: vec![0; 0x2000],
             mapper: 4,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -739,6 +840,7 @@ mod tests {
             chr_rom: vec![0; 0x2000],
             mapper: 4,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -765,6 +867,7 @@ mod tests {
             chr_rom: vec![],
             mapper: 4,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal, // Start with horizontal
         };
 
@@ -798,6 +901,7 @@ mod tests {
             chr_rom: vec![0; 0x2000],
             mapper: 4,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -847,6 +951,7 @@ mod tests {
             chr_rom: vec![],
             mapper: 4,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -868,6 +973,7 @@ mod tests {
             chr_rom: vec![0; 0x4000], // 16 banks
             mapper: 4,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -900,6 +1006,7 @@ mod tests {
             chr_rom: vec![0; 0x1C00], // 7 banks (7KB) - odd count
             mapper: 4,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -955,6 +1062,7 @@ mod tests {
             chr_rom: vec![],
             mapper: 4,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -1007,6 +1115,7 @@ mod tests {
             chr_rom: vec![],
             mapper: 4,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -1055,6 +1164,7 @@ mod tests {
             chr_rom: vec![],
             mapper: 4,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -1075,6 +1185,7 @@ mod tests {
             chr_rom: vec![],
             mapper: 4,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -1099,4 +1210,59 @@ mod tests {
         assert!(enabled, "PRG RAM should be enabled when bit 7 is 1");
         assert!(writable, "PRG RAM writes should be allowed when bit 6 is 0");
     }
+
+    #[test]
+    fn mmc3_save_state_keeps_irq_counter() {
+        let cart = || Cartridge {
+            prg_rom: vec![0; 0x8000],
+            chr_rom: vec![0; 0x2000],
+            mapper: 4,
+            timing: TimingMode::Ntsc,
+            battery: false,
+            mirroring: Mirroring::Horizontal,
+        };
+        let a12_edge = |m: &mut Mmc3| {
+            m.notify_a12(false);
+            m.notify_a12(true);
+        };
+
+        let mut ppu = Ppu::new(vec![], Mirroring::Horizontal);
+        let mut mmc3 = Mmc3::new(cart(), &mut ppu);
+        mmc3.write_prg(0xC000, 3, &mut ppu, 0);
+        mmc3.write_prg(0xC001, 0, &mut ppu, 0);
+        mmc3.write_prg(0xE001, 0, &mut ppu, 0);
+        a12_edge(&mut mmc3); // Reload to 3
+        a12_edge(&mut mmc3); // 2
+                             // A new latch value only takes effect at the next reload
+        mmc3.write_prg(0xC000, 5, &mut ppu, 0);
+
+        let state = serde_json::json!(mmc3);
+        let mut restored = Mmc3::new(cart(), &mut ppu);
+        restored.restore_state(serde_json::from_value(state).unwrap());
+        assert_eq!(restored.irq_counter, 2);
+        assert_eq!(restored.irq_latch, 5);
+        assert!(restored.irq_enabled);
+        assert!(!restored.irq_reload);
+        assert!(restored.last_a12);
+
+        // The IRQ fires on the same scanline in both
+        for m in [&mut mmc3, &mut restored] {
+            a12_edge(m);
+            assert!(!m.take_irq_pending());
+            a12_edge(m);
+            assert!(m.take_irq_pending());
+        }
+
+        // A pending reload request survives too
+        mmc3.write_prg(0xE000, 0, &mut ppu, 0);
+        mmc3.write_prg(0xC001, 0, &mut ppu, 0);
+        let state = serde_json::json!(mmc3);
+        let mut restored = Mmc3::new(cart(), &mut ppu);
+        restored.restore_state(serde_json::from_value(state).unwrap());
+        assert!(restored.irq_reload);
+        assert!(!restored.irq_enabled);
+        assert!(!restored.take_irq_pending());
+        a12_edge(&mut restored);
+        assert_eq!(restored.irq_counter, 5);
+    }
 }
@@ -2,6 +2,7 @@ use crate::cartridge::{Cartridge, Mirroring};
 use crate::ppu::Ppu;
 #[cfg(test)]
 use emu_core::apu::TimingMode;
+use serde::{Deserialize, Serialize};
 
 /// MMC4 (Mapper 10) - Similar to MMC2 but with different CHR latch addresses
 ///
@@ -22,9 +23,11 @@ use emu_core::apu::TimingMode;
 /// Latch switching is now fully implemented via CHR read callbacks. When the PPU
 /// reads from latch trigger addresses during rendering, the mapper tracks latch
 /// state changes and applies CHR bank updates after each frame completes.
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Mmc4 {
+    #[serde(skip)]
     prg_rom: Vec<u8>,
+    #[serde(skip)]
     chr_rom: Vec<u8>,
     prg_bank: u8,
     // CHR banks for left pattern table ($0000-$0FFF)
@@ -249,6 +252,15 @@ impl Mmc4 {
     pub fn prg_rom(&self) -> &[u8] {
         &self.prg_rom
     }
+
+    /// Restore bank registers from a save state, keeping the loaded ROM
+    pub fn restore_state(&mut self, state: Self) {
+        *self = Self {
+            prg_rom: std::mem::take(&mut self.prg_rom),
+            chr_rom: std::mem::take(&mut self.chr_rom),
+            ..state
+        };
+    }
 }
 
 #[cfg(test)]
@@ -267,6 +279,7 @@ mod tests {
             chr_rom: vec![0; 0x2000],
             mapper: 10,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Vertical,
         };
 
@@ -296,6 +309,7 @@ mod tests {
             chr_rom: chr,
             mapper: 10,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Vertical,
         };
 
@@ -325,6 +339,7 @@ mod tests {
             chr_rom: vec![0; 0x2000],
             mapper: 10,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Vertical,
         };
 
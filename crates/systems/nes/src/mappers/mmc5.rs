@@ -0,0 +1,383 @@
+use crate::cartridge::{Cartridge, Mirroring};
+use crate::ppu::Ppu;
+#[cfg(test)]
+use emu_core::apu::TimingMode;
+use serde::{Deserialize, Serialize};
+
+/// MMC5 (Mapper 5/ExROM) - Nintendo's most capable mapper
+///
+/// # Hardware Behavior (per NESdev wiki)
+/// - **$5100**: PRG mode (0: 32KB, 1: 16KB+16KB, 2: 16KB+8KB+8KB, 3: 4x8KB)
+/// - **$5101**: CHR mode (0: 8KB, 1: 4KB, 2: 2KB, 3: 1KB banks)
+/// - **$5104**: ExRAM mode (0/1: nametable, 2: CPU RAM, 3: CPU read-only)
+/// - **$5105**: Nametable mapping, 2 bits per nametable (0: CIRAM page 0,
+///   1: CIRAM page 1, 2: ExRAM, 3: fill mode)
+/// - **$5113-$5117**: PRG banks ($5117 is always ROM and powers on as $FF)
+/// - **$5120-$5127**: Sprite CHR banks (set A), **$5128-$512B**: background
+///   CHR banks for 8x16 sprite mode (set B), **$5130**: upper CHR bank bits
+/// - **$5203**: IRQ scanline compare, **$5204**: IRQ enable (write) and
+///   status (read: bit 7 pending, bit 6 in frame; reading acknowledges)
+/// - **$5205/$5206**: 8x8 unsigned multiplier (write operands, read product)
+/// - **$5C00-$5FFF**: 1KB ExRAM
+///
+/// # Implementation Notes
+/// The scanline IRQ counts the bus's synthesized scanline clock; it is reset
+/// at VBlank. Only one CHR set is visible at a time: the one written last,
+/// as most simple MMC5 implementations do. Nametables can only select CIRAM
+/// pages (ExRAM and fill-mode nametables, extended attributes, split screen
+/// and the MMC5 audio are not emulated), and PRG-RAM is the bus's 8KB WRAM at
+/// $6000-$7FFF (RAM banks in $8000-$DFFF read as ROM).
+///
+/// Used in Castlevania III, Just Breed, Uncharted Waters and Laser Invasion.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Mmc5 {
+    #[serde(skip)]
+    prg_rom: Vec<u8>,
+    #[serde(skip)]
+    chr_rom: Vec<u8>,
+    prg_mode: u8,
+    chr_mode: u8,
+    exram_mode: u8,
+    nametable_mapping: u8,
+    /// $5113-$5117
+    prg_regs: [u8; 5],
+    /// $5120-$512B, with the $5130 upper bits they were written with
+    chr_regs: [u16; 12],
+    chr_upper: u8,
+    /// Background set (B) written last
+    chr_set_b: bool,
+    exram: Vec<u8>,
+    irq_compare: u8,
+    irq_enabled: bool,
+    irq_pending: bool,
+    in_frame: bool,
+    scanline: u8,
+    multiplicand: u8,
+    multiplier: u8,
+}
+
+/// ExRAM size in bytes
+const EXRAM_SIZE: usize = 0x400;
+
+impl Mmc5 {
+    pub fn new(cart: Cartridge, ppu: &mut Ppu) -> Self {
+        let m = Self {
+            prg_rom: cart.prg_rom,
+            chr_rom: cart.chr_rom,
+            prg_mode: 3,
+            chr_mode: 3,
+            exram_mode: 0,
+            nametable_mapping: 0,
+            prg_regs: [0, 0, 0, 0, 0xFF],
+            chr_regs: [0, 1, 2, 3, 4, 5, 6, 7, 0, 1, 2, 3],
+            chr_upper: 0,
+            chr_set_b: false,
+            exram: vec![0; EXRAM_SIZE],
+            irq_compare: 0,
+            irq_enabled: false,
+            irq_pending: false,
+            in_frame: false,
+            scanline: 0,
+            multiplicand: 0xFF,
+            multiplier: 0xFF,
+        };
+        m.update_chr_mapping(ppu);
+        ppu.set_mirroring(cart.mirroring);
+        m
+    }
+
+    /// 8KB PRG banks at $8000/$A000/$C000/$E000
+    fn prg_banks(&self) -> [usize; 4] {
+        let reg = |i: usize| (self.prg_regs[i] & 0x7F) as usize;
+        match self.prg_mode {
+            0 => {
+                let base = reg(4) & !3;
+                [base, base + 1, base + 2, base + 3]
+            }
+            1 => {
+                let (low, high) = (reg(2) & !1, reg(4) & !1);
+                [low, low + 1, high, high + 1]
+            }
+            2 => {
+                let low = reg(2) & !1;
+                [low, low + 1, reg(3), reg(4)]
+            }
+            _ => [reg(1), reg(2), reg(3), reg(4)],
+        }
+    }
+
+    /// 1KB CHR banks at $0000-$1FFF from set A or B
+    fn chr_banks(&self) -> [usize; 8] {
+        let reg = |i: usize| self.chr_regs[i] as usize;
+        std::array::from_fn(|i| {
+            if self.chr_set_b {
+                match self.chr_mode {
+                    0 => reg(11) * 8 + i,
+                    1 => reg(11) * 4 + i % 4,
+                    2 => reg([9, 11][(i / 2) % 2]) * 2 + i % 2,
+                    _ => reg(8 + i % 4),
+                }
+            } else {
+                match self.chr_mode {
+                    0 => reg(7) * 8 + i,
+                    1 => reg([3, 7][i / 4]) * 4 + i % 4,
+                    2 => reg([1, 3, 5, 7][i / 2]) * 2 + i % 2,
+                    _ => reg(i),
+                }
+            }
+        })
+    }
+
+    fn update_chr_mapping(&self, ppu: &mut Ppu) {
+        if ppu.chr.len() < 0x2000 {
+            ppu.chr.resize(0x2000, 0);
+        }
+
+        // For CHR RAM carts, skip copying (PPU owns RAM)
+        if self.chr_rom.is_empty() {
+            return;
+        }
+
+        let chr_count = std::cmp::max(1, self.chr_rom.len() / 0x0400);
+        for (i, bank) in self.chr_banks().into_iter().enumerate() {
+            let dst_start = i * 0x0400;
+            let src_start = (bank % chr_count) * 0x0400;
+            ppu.chr[dst_start..dst_start + 0x0400]
+                .copy_from_slice(&self.chr_rom[src_start..src_start + 0x0400]);
+        }
+    }
+
+    /// Mirroring matching the CIRAM pages of the nametable mapping
+    fn update_mirroring(&self, ppu: &mut Ppu) {
+        let pages: [u8; 4] = std::array::from_fn(|i| (self.nametable_mapping >> (i * 2)) & 0x03);
+        let mirroring = match pages {
+            [0, 1, 0, 1] => Mirroring::Vertical,
+            [0, 0, 1, 1] => Mirroring::Horizontal,
+            [0, 0, 0, 0] => Mirroring::SingleScreenLower,
+            [1, 1, 1, 1] => Mirroring::SingleScreenUpper,
+            // ExRAM and fill-mode nametables are not supported
+            _ => return,
+        };
+        ppu.set_mirroring(mirroring);
+    }
+
+    pub fn read_prg(&self, addr: u16) -> u8 {
+        let count = std::cmp::max(1, self.prg_rom.len() / 0x2000);
+        let bank = self.prg_banks()[(addr as usize - 0x8000) >> 13];
+        let idx = (bank % count) * 0x2000 + (addr as usize & 0x1FFF);
+        self.prg_rom.get(idx).copied().unwrap_or(0)
+    }
+
+    pub fn write_prg(&mut self, _addr: u16, _val: u8, _ppu: &mut Ppu, _cpu_cycles: u64) {
+        // All registers are in $5000-$5FFF
+    }
+
+    /// Read the IRQ status, multiplier and ExRAM in $5000-$5FFF
+    pub fn read_low(&mut self, addr: u16) -> Option<u8> {
+        match addr {
+            0x5204 => {
+                let status =
+                    if self.irq_pending { 0x80 } else { 0 } | if self.in_frame { 0x40 } else { 0 };
+                self.irq_pending = false;
+                Some(status)
+            }
+            0x5205 => Some((self.multiplicand as u16 * self.multiplier as u16) as u8),
+            0x5206 => Some(((self.multiplicand as u16 * self.multiplier as u16) >> 8) as u8),
+            0x5C00..=0x5FFF if self.exram_mode >= 2 => Some(self.exram[addr as usize - 0x5C00]),
+            _ => None,
+        }
+    }
+
+    /// Write the registers and ExRAM in $5000-$5FFF
+    pub fn write_low(&mut self, addr: u16, val: u8, ppu: &mut Ppu) {
+        match addr {
+            0x5100 => self.prg_mode = val & 0x03,
+            0x5101 => {
+                self.chr_mode = val & 0x03;
+                self.update_chr_mapping(ppu);
+            }
+            0x5104 => self.exram_mode = val & 0x03,
+            0x5105 => {
+                self.nametable_mapping = val;
+                self.update_mirroring(ppu);
+            }
+            0x5113..=0x5117 => self.prg_regs[addr as usize - 0x5113] = val,
+            0x5120..=0x512B => {
+                let index = addr as usize - 0x5120;
+                self.chr_regs[index] = val as u16 | ((self.chr_upper as u16) << 8);
+                self.chr_set_b = index >= 8;
+                self.update_chr_mapping(ppu);
+            }
+            0x5130 => self.chr_upper = val & 0x03,
+            0x5203 => self.irq_compare = val,
+            0x5204 => self.irq_enabled = val & 0x80 != 0,
+            0x5205 => self.multiplicand = val,
+            0x5206 => self.multiplier = val,
+            // Mode 3 is read-only
+            0x5C00..=0x5FFF if self.exram_mode != 3 => self.exram[addr as usize - 0x5C00] = val,
+            _ => {}
+        }
+    }
+
+    /// One scanline rendered: the first starts the frame, each later one
+    /// counts up and is compared with $5203
+    pub fn notify_scanline(&mut self) {
+        if !self.in_frame {
+            self.in_frame = true;
+            self.scanline = 0;
+            return;
+        }
+        self.scanline = self.scanline.wrapping_add(1);
+        if self.irq_compare != 0 && self.scanline == self.irq_compare {
+            self.irq_pending = true;
+        }
+    }
+
+    /// Rendering stopped for VBlank
+    pub fn notify_vblank(&mut self) {
+        self.in_frame = false;
+    }
+
+    pub fn take_irq_pending(&mut self) -> bool {
+        self.irq_pending && self.irq_enabled
+    }
+
+    pub fn prg_rom(&self) -> &[u8] {
+        &self.prg_rom
+    }
+
+    /// Restore bank registers from a save state, keeping the loaded ROM
+    pub fn restore_state(&mut self, state: Self) {
+        *self = Self {
+            prg_rom: std::mem::take(&mut self.prg_rom),
+            chr_rom: std::mem::take(&mut self.chr_rom),
+            ..state
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mmc5() -> (Mmc5, Ppu) {
+        let mut prg = vec![0; 0x40000]; // 32 banks of 8KB
+        for i in 0..32 {
+            prg[i * 0x2000] = i as u8;
+        }
+        let mut chr = vec![0; 0x40000]; // 256 banks of 1KB
+        for i in 0..256 {
+            chr[i * 0x400] = i as u8;
+        }
+        let cart = Cartridge {
+            prg_rom: prg,
+            chr_rom: chr,
+            mapper: 5,
+            timing: TimingMode::Ntsc,
+            battery: false,
+            mirroring: Mirroring::Vertical,
+        };
+        let mut ppu = Ppu::new(vec![], Mirroring::Vertical);
+        let m = Mmc5::new(cart, &mut ppu);
+        (m, ppu)
+    }
+
+    #[test]
+    fn mmc5_prg_modes() {
+        let (mut m, mut ppu) = mmc5();
+        // Power-on: mode 3 with $5117 = $FF (last bank) at $E000
+        assert_eq!(m.read_prg(0xE000), 31);
+
+        for (reg, bank) in [
+            (0x5114, 0x84),
+            (0x5115, 0x85),
+            (0x5116, 0x86),
+            (0x5117, 0x87),
+        ] {
+            m.write_low(reg, bank, &mut ppu);
+        }
+        assert_eq!(
+            [0x8000, 0xA000, 0xC000, 0xE000].map(|a| m.read_prg(a)),
+            [4, 5, 6, 7]
+        );
+
+        m.write_low(0x5100, 2, &mut ppu);
+        assert_eq!(
+            [0x8000, 0xA000, 0xC000, 0xE000].map(|a| m.read_prg(a)),
+            [4, 5, 6, 7]
+        );
+
+        m.write_low(0x5100, 1, &mut ppu);
+        assert_eq!(
+            [0x8000, 0xA000, 0xC000, 0xE000].map(|a| m.read_prg(a)),
+            [4, 5, 6, 7]
+        );
+
+        m.write_low(0x5117, 0x8B, &mut ppu);
+        m.write_low(0x5100, 0, &mut ppu);
+        assert_eq!(
+            [0x8000, 0xA000, 0xC000, 0xE000].map(|a| m.read_prg(a)),
+            [8, 9, 10, 11]
+        );
+    }
+
+    #[test]
+    fn mmc5_chr_modes_and_sets() {
+        let (mut m, mut ppu) = mmc5();
+        m.write_low(0x5130, 1, &mut ppu);
+        m.write_low(0x5123, 0x10, &mut ppu); // 1KB bank $110 = 272 wraps to 16
+        assert_eq!(ppu.chr[0x0C00], 16);
+
+        m.write_low(0x5130, 0, &mut ppu);
+        m.write_low(0x5101, 1, &mut ppu); // 4KB banks
+        m.write_low(0x5127, 3, &mut ppu);
+        assert_eq!(ppu.chr[0x1000], 12);
+        assert_eq!(ppu.chr[0x1C00], 15);
+
+        // Writing a background register shows set B
+        m.write_low(0x512B, 5, &mut ppu);
+        assert_eq!(ppu.chr[0x0000], 20);
+        assert_eq!(ppu.chr[0x1000], 20);
+    }
+
+    #[test]
+    fn mmc5_scanline_irq() {
+        let (mut m, mut ppu) = mmc5();
+        m.write_low(0x5203, 3, &mut ppu);
+        m.write_low(0x5204, 0x80, &mut ppu);
+
+        m.notify_scanline(); // Frame starts
+        assert_eq!(m.read_low(0x5204), Some(0x40));
+        m.notify_scanline();
+        m.notify_scanline();
+        assert!(!m.take_irq_pending());
+        m.notify_scanline();
+        assert!(m.take_irq_pending());
+        assert_eq!(m.read_low(0x5204), Some(0xC0));
+        assert!(!m.take_irq_pending());
+
+        m.notify_vblank();
+        assert_eq!(m.read_low(0x5204), Some(0x00));
+    }
+
+    #[test]
+    fn mmc5_multiplier_exram_and_nametables() {
+        let (mut m, mut ppu) = mmc5();
+        m.write_low(0x5205, 200, &mut ppu);
+        m.write_low(0x5206, 100, &mut ppu);
+        assert_eq!(m.read_low(0x5205), Some((20000 & 0xFF) as u8));
+        assert_eq!(m.read_low(0x5206), Some((20000 >> 8) as u8));
+
+        m.write_low(0x5104, 2, &mut ppu);
+        m.write_low(0x5C10, 0x42, &mut ppu);
+        assert_eq!(m.read_low(0x5C10), Some(0x42));
+        m.write_low(0x5104, 3, &mut ppu);
+        m.write_low(0x5C10, 0x00, &mut ppu);
+        assert_eq!(m.read_low(0x5C10), Some(0x42));
+
+        m.write_low(0x5105, 0x50, &mut ppu); // 0, 0, 1, 1
+        assert_eq!(ppu.get_mirroring(), Mirroring::Horizontal);
+        m.write_low(0x5105, 0x44, &mut ppu); // 0, 1, 0, 1
+        assert_eq!(ppu.get_mirroring(), Mirroring::Vertical);
+    }
+}
@@ -8,30 +8,44 @@ mod bnrom;
 mod camerica;
 mod cnrom;
 mod colordreams;
+mod discrete;
+mod fme7;
 mod gxrom;
 mod mmc1;
 mod mmc2;
 mod mmc3;
 mod mmc4;
+mod mmc5;
 mod namco118;
+mod namco163;
 mod nina;
 mod nrom;
+mod sunsoft4;
 mod uxrom;
+mod vrc4;
+mod vrc6;
 
 pub use axrom::Axrom;
 pub use bnrom::Bnrom;
 pub use camerica::Camerica;
 pub use cnrom::Cnrom;
 pub use colordreams::ColorDreams;
+pub use discrete::Discrete;
+pub use fme7::Fme7;
 pub use gxrom::Gxrom;
 pub use mmc1::Mmc1;
 pub use mmc2::Mmc2;
 pub use mmc3::Mmc3;
 pub use mmc4::Mmc4;
+pub use mmc5::Mmc5;
 pub use namco118::Namco118;
+pub use namco163::Namco163;
 pub use nina::Nina;
 pub use nrom::Nrom;
+pub use sunsoft4::Sunsoft4;
 pub use uxrom::Uxrom;
+pub use vrc4::Vrc4;
+pub use vrc6::Vrc6;
 
 use crate::cartridge::Cartridge;
 use crate::ppu::Ppu;
@@ -53,6 +67,13 @@ pub enum Mapper {
     Namco118(Namco118),
     Bnrom(Bnrom),
     Nina(Nina),
+    Mmc5(Mmc5),
+    Vrc4(Vrc4),
+    Vrc6(Vrc6),
+    Fme7(Fme7),
+    Sunsoft4(Sunsoft4),
+    Namco163(Namco163),
+    Discrete(Discrete),
 }
 
 impl Mapper {
@@ -63,15 +84,22 @@ impl Mapper {
             2 => Mapper::Uxrom(Uxrom::new(cart, ppu)),
             3 => Mapper::Cnrom(Cnrom::new(cart, ppu)),
             4 => Mapper::Mmc3(Mmc3::new(cart, ppu)),
+            5 => Mapper::Mmc5(Mmc5::new(cart, ppu)),
             7 => Mapper::Axrom(Axrom::new(cart, ppu)),
             9 => Mapper::Mmc2(Mmc2::new(cart, ppu)),
             10 => Mapper::Mmc4(Mmc4::new(cart, ppu)),
             11 => Mapper::ColorDreams(ColorDreams::new(cart, ppu)),
+            19 => Mapper::Namco163(Namco163::new(cart, ppu)),
+            21 | 22 | 23 | 25 => Mapper::Vrc4(Vrc4::new(cart, ppu)),
+            24 | 26 => Mapper::Vrc6(Vrc6::new(cart, ppu)),
             34 => Mapper::Bnrom(Bnrom::new(cart, ppu)),
             66 => Mapper::Gxrom(Gxrom::new(cart, ppu)),
+            68 => Mapper::Sunsoft4(Sunsoft4::new(cart, ppu)),
+            69 => Mapper::Fme7(Fme7::new(cart, ppu)),
             71 => Mapper::Camerica(Camerica::new(cart, ppu)),
             79 => Mapper::Nina(Nina::new(cart, ppu)),
             206 => Mapper::Namco118(Namco118::new(cart, ppu)),
+            m if Discrete::supports(m) => Mapper::Discrete(Discrete::new(cart, ppu)),
             _ => Mapper::Nrom(Nrom::new(cart)),
         }
     }
@@ -93,6 +121,13 @@ impl Mapper {
             Mapper::Namco118(m) => m.read_prg(addr),
             Mapper::Bnrom(m) => m.read_prg(addr),
             Mapper::Nina(m) => m.read_prg(addr),
+            Mapper::Mmc5(m) => m.read_prg(addr),
+            Mapper::Vrc4(m) => m.read_prg(addr),
+            Mapper::Vrc6(m) => m.read_prg(addr),
+            Mapper::Fme7(m) => m.read_prg(addr),
+            Mapper::Sunsoft4(m) => m.read_prg(addr),
+            Mapper::Namco163(m) => m.read_prg(addr),
+            Mapper::Discrete(m) => m.read_prg(addr),
         }
     }
 
@@ -117,6 +152,66 @@ impl Mapper {
             Mapper::Namco118(m) => m.write_prg(addr, val, ppu, cpu_cycles),
             Mapper::Bnrom(m) => m.write_prg(addr, val, ppu, cpu_cycles),
             Mapper::Nina(m) => m.write_prg(addr, val, ppu, cpu_cycles),
+            Mapper::Mmc5(m) => m.write_prg(addr, val, ppu, cpu_cycles),
+            Mapper::Vrc4(m) => m.write_prg(addr, val, ppu, cpu_cycles),
+            Mapper::Vrc6(m) => m.write_prg(addr, val, ppu, cpu_cycles),
+            Mapper::Fme7(m) => m.write_prg(addr, val, ppu, cpu_cycles),
+            Mapper::Sunsoft4(m) => m.write_prg(addr, val, ppu, cpu_cycles),
+            Mapper::Namco163(m) => m.write_prg(addr, val, ppu, cpu_cycles),
+            Mapper::Discrete(m) => m.write_prg(addr, val, ppu, cpu_cycles),
+        }
+    }
+
+    /// Write to $4020-$7FFF, for boards with their registers there
+    /// (writes to $6000-$7FFF also reach the bus's WRAM)
+    pub fn write_low(&mut self, addr: u16, val: u8, ppu: &mut Ppu) {
+        match self {
+            Mapper::Discrete(m) => m.write_low(addr, val, ppu),
+            Mapper::Mmc5(m) => m.write_low(addr, val, ppu),
+            Mapper::Namco163(m) => m.write_low(addr, val),
+            _ => {}
+        }
+    }
+
+    /// Read $4020-$7FFF, for boards with registers or ROM there; None leaves
+    /// the read to the bus (open bus, or WRAM at $6000-$7FFF)
+    pub fn read_low(&mut self, addr: u16) -> Option<u8> {
+        match self {
+            Mapper::Mmc5(m) => m.read_low(addr),
+            Mapper::Fme7(m) => m.read_low(addr),
+            Mapper::Namco163(m) => m.read_low(addr),
+            _ => None,
+        }
+    }
+
+    /// Run CPU cycle driven IRQ counters (VRC4, VRC6, FME-7, Namco 163)
+    pub fn clock_cpu(&mut self, cycles: u32) {
+        match self {
+            Mapper::Vrc4(m) => m.clock_cpu(cycles),
+            Mapper::Vrc6(m) => m.clock_cpu(cycles),
+            Mapper::Fme7(m) => m.clock_cpu(cycles),
+            Mapper::Namco163(m) => m.clock_cpu(cycles),
+            _ => {}
+        }
+    }
+
+    /// Names of the expansion audio channels the cartridge adds to the mix
+    pub fn expansion_audio_channels(&self) -> &'static [&'static str] {
+        match self {
+            Mapper::Vrc6(_) => vrc6::AUDIO_CHANNELS,
+            Mapper::Namco163(_) => namco163::AUDIO_CHANNELS,
+            _ => &[],
+        }
+    }
+
+    /// Run expansion audio channel `channel` (an index into
+    /// `expansion_audio_channels`) for `cycles` CPU cycles, returning its
+    /// average output on the scale of an APU pulse channel
+    pub fn clock_audio(&mut self, channel: usize, cycles: u32) -> i32 {
+        match self {
+            Mapper::Vrc6(m) => m.clock_audio(channel, cycles),
+            Mapper::Namco163(m) => m.clock_audio(cycles),
+            _ => 0,
         }
     }
 
@@ -137,6 +232,13 @@ impl Mapper {
             Mapper::Namco118(m) => m.prg_rom(),
             Mapper::Bnrom(m) => m.prg_rom(),
             Mapper::Nina(m) => m.prg_rom(),
+            Mapper::Mmc5(m) => m.prg_rom(),
+            Mapper::Vrc4(m) => m.prg_rom(),
+            Mapper::Vrc6(m) => m.prg_rom(),
+            Mapper::Fme7(m) => m.prg_rom(),
+            Mapper::Sunsoft4(m) => m.prg_rom(),
+            Mapper::Namco163(m) => m.prg_rom(),
+            Mapper::Discrete(m) => m.prg_rom(),
         }
     }
 
@@ -157,6 +259,13 @@ impl Mapper {
             Mapper::Namco118(_) => false,
             Mapper::Bnrom(_) => false,
             Mapper::Nina(_) => false,
+            Mapper::Mmc5(m) => m.take_irq_pending(),
+            Mapper::Vrc4(m) => m.take_irq_pending(),
+            Mapper::Vrc6(m) => m.take_irq_pending(),
+            Mapper::Fme7(m) => m.take_irq_pending(),
+            Mapper::Sunsoft4(_) => false,
+            Mapper::Namco163(m) => m.take_irq_pending(),
+            Mapper::Discrete(_) => false,
         }
     }
 
@@ -167,6 +276,28 @@ impl Mapper {
         }
     }
 
+    /// Notify mapper of a PPU A12 transition at PPU dot `dot` of a running
+    /// clock, passed through the MMC3's A12 low filter
+    pub fn notify_a12_at(&mut self, a12_high: bool, dot: u64) {
+        if let Mapper::Mmc3(m) = self {
+            m.notify_a12_at(a12_high, dot);
+        }
+    }
+
+    /// Notify mapper of a rendered scanline (for the MMC5 scanline IRQ)
+    pub fn notify_scanline(&mut self) {
+        if let Mapper::Mmc5(m) = self {
+            m.notify_scanline();
+        }
+    }
+
+    /// Notify mapper that rendering stopped for VBlank
+    pub fn notify_vblank(&mut self) {
+        if let Mapper::Mmc5(m) = self {
+            m.notify_vblank();
+        }
+    }
+
     /// Notify mapper of PPU CHR reads (for MMC2/MMC4 latch switching)
     pub fn notify_chr_read(&mut self, addr: u16) {
         match self {
@@ -185,6 +316,61 @@ impl Mapper {
         }
     }
 
+    /// Serialize bank registers and IRQ counters (ROM contents are not included)
+    pub fn save_state(&self) -> serde_json::Value {
+        match self {
+            Mapper::Nrom(_) => serde_json::Value::Null,
+            Mapper::Mmc1(m) => serde_json::json!(m),
+            Mapper::Uxrom(m) => serde_json::json!(m),
+            Mapper::Cnrom(m) => serde_json::json!(m),
+            Mapper::Mmc3(m) => serde_json::json!(m),
+            Mapper::Axrom(m) => serde_json::json!(m),
+            Mapper::Mmc2(m) => serde_json::json!(m),
+            Mapper::Mmc4(m) => serde_json::json!(m),
+            Mapper::ColorDreams(m) => serde_json::json!(m),
+            Mapper::Gxrom(m) => serde_json::json!(m),
+            Mapper::Camerica(m) => serde_json::json!(m),
+            Mapper::Namco118(m) => serde_json::json!(m),
+            Mapper::Bnrom(m) => serde_json::json!(m),
+            Mapper::Nina(m) => serde_json::json!(m),
+            Mapper::Mmc5(m) => serde_json::json!(m),
+            Mapper::Vrc4(m) => serde_json::json!(m),
+            Mapper::Vrc6(m) => serde_json::json!(m),
+            Mapper::Fme7(m) => serde_json::json!(m),
+            Mapper::Sunsoft4(m) => serde_json::json!(m),
+            Mapper::Namco163(m) => serde_json::json!(m),
+            Mapper::Discrete(m) => serde_json::json!(m),
+        }
+    }
+
+    /// Restore bank registers and IRQ counters saved by [`Mapper::save_state`]
+    pub fn load_state(&mut self, state: &serde_json::Value) -> Result<(), serde_json::Error> {
+        match self {
+            Mapper::Nrom(_) => {}
+            Mapper::Mmc1(m) => m.restore_state(serde_json::from_value(state.clone())?),
+            Mapper::Uxrom(m) => m.restore_state(serde_json::from_value(state.clone())?),
+            Mapper::Cnrom(m) => m.restore_state(serde_json::from_value(state.clone())?),
+            Mapper::Mmc3(m) => m.restore_state(serde_json::from_value(state.clone())?),
+            Mapper::Axrom(m) => m.restore_state(serde_json::from_value(state.clone())?),
+            Mapper::Mmc2(m) => m.restore_state(serde_json::from_value(state.clone())?),
+            Mapper::Mmc4(m) => m.restore_state(serde_json::from_value(state.clone())?),
+            Mapper::ColorDreams(m) => m.restore_state(serde_json::from_value(state.clone())?),
+            Mapper::Gxrom(m) => m.restore_state(serde_json::from_value(state.clone())?),
+            Mapper::Camerica(m) => m.restore_state(serde_json::from_value(state.clone())?),
+            Mapper::Namco118(m) => m.restore_state(serde_json::from_value(state.clone())?),
+            Mapper::Bnrom(m) => m.restore_state(serde_json::from_value(state.clone())?),
+            Mapper::Nina(m) => m.restore_state(serde_json::from_value(state.clone())?),
+            Mapper::Mmc5(m) => m.restore_state(serde_json::from_value(state.clone())?),
+            Mapper::Vrc4(m) => m.restore_state(serde_json::from_value(state.clone())?),
+            Mapper::Vrc6(m) => m.restore_state(serde_json::from_value(state.clone())?),
+            Mapper::Fme7(m) => m.restore_state(serde_json::from_value(state.clone())?),
+            Mapper::Sunsoft4(m) => m.restore_state(serde_json::from_value(state.clone())?),
+            Mapper::Namco163(m) => m.restore_state(serde_json::from_value(state.clone())?),
+            Mapper::Discrete(m) => m.restore_state(serde_json::from_value(state.clone())?),
+        }
+        Ok(())
+    }
+
     /// Get mapper number
     pub fn mapper_number(&self) -> u8 {
         match self {
@@ -201,7 +387,132 @@ impl Mapper {
             Mapper::Gxrom(_) => 66,
             Mapper::Camerica(_) => 71,
             Mapper::Nina(_) => 79,
+            Mapper::Mmc5(_) => 5,
+            Mapper::Vrc4(m) => m.mapper_number(),
+            Mapper::Vrc6(m) => m.mapper_number(),
+            Mapper::Fme7(_) => 69,
+            Mapper::Sunsoft4(_) => 68,
+            Mapper::Namco163(_) => 19,
             Mapper::Namco118(_) => 206,
+            Mapper::Discrete(m) => m.mapper_number(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cartridge::Mirroring;
+    use emu_core::apu::TimingMode;
+
+    /// 256KB PRG and 128KB CHR where every byte identifies its bank and offset
+    fn numbered_cart(mapper: u8) -> Cartridge {
+        let fill = |len: usize| {
+            (0..len)
+                .map(|i| ((i >> 10) as u8).wrapping_mul(31) ^ (i as u8))
+                .collect()
+        };
+        Cartridge {
+            prg_rom: fill(0x40000),
+            chr_rom: fill(0x20000),
+            mapper,
+            timing: TimingMode::Ntsc,
+            battery: false,
+            mirroring: Mirroring::Vertical,
+        }
+    }
+
+    /// Drive register writes, A12 edges and CHR latch reads from a seed
+    fn exercise(mapper: &mut Mapper, ppu: &mut Ppu, seed: &mut u32, cycles: &mut u64) {
+        const REGISTERS: [u16; 12] = [
+            0x6000, 0x7FFF, 0x8000, 0x8001, 0xA000, 0xA001, 0xC000, 0xC001, 0xE000, 0xE001, 0xF000,
+            0xFFFF,
+        ];
+        for _ in 0..64 {
+            *seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            let addr = REGISTERS[(*seed >> 16) as usize % REGISTERS.len()];
+            let val = (*seed >> 8) as u8;
+            // Spread writes out so MMC1 accepts each one
+            *cycles += 2;
+            if addr < 0x8000 {
+                mapper.write_low(addr, val, ppu);
+            } else {
+                mapper.write_prg(addr, val, ppu, *cycles);
+            }
+            // One scanline's A12 rising edge per write
+            mapper.notify_a12(false);
+            mapper.notify_a12(true);
+            mapper.notify_chr_read(if val & 0x02 != 0 { 0x0FD8 } else { 0x1FE8 });
+            mapper.apply_chr_update(ppu);
+        }
+    }
+
+    #[test]
+    fn every_mapper_round_trips_through_save_state() {
+        let mut tested = 0;
+        for number in 0..=255u8 {
+            let mut ppu = Ppu::new(vec![], Mirroring::Vertical);
+            let mut mapper = Mapper::from_cart(numbered_cart(number), &mut ppu);
+            if mapper.mapper_number() != number {
+                continue; // Not supported, falls back to NROM
+            }
+            tested += 1;
+
+            let mut seed = number as u32;
+            let mut cycles = 0;
+            exercise(&mut mapper, &mut ppu, &mut seed, &mut cycles);
+
+            // Restore into a freshly powered-on cartridge, as the bus does
+            let state = mapper.save_state();
+            let mut restored_ppu = Ppu::new(vec![], Mirroring::Vertical);
+            let mut restored = Mapper::from_cart(numbered_cart(number), &mut restored_ppu);
+            restored.load_state(&state).unwrap();
+            restored_ppu.load_state(&ppu.save_state()).unwrap();
+            assert_eq!(restored.save_state(), state, "mapper {}", number);
+
+            // Both copies must behave identically from here on
+            let mut restored_seed = seed;
+            let mut restored_cycles = cycles;
+            for round in 0..8 {
+                exercise(&mut mapper, &mut ppu, &mut seed, &mut cycles);
+                exercise(
+                    &mut restored,
+                    &mut restored_ppu,
+                    &mut restored_seed,
+                    &mut restored_cycles,
+                );
+                for addr in (0x8000..=0xFFFFu16).step_by(0x100) {
+                    assert_eq!(
+                        restored.read_prg(addr),
+                        mapper.read_prg(addr),
+                        "mapper {} round {} PRG ${:04X}",
+                        number,
+                        round,
+                        addr
+                    );
+                }
+                assert_eq!(
+                    restored_ppu.chr, ppu.chr,
+                    "mapper {} round {} CHR",
+                    number, round
+                );
+                assert_eq!(
+                    serde_json::json!(restored_ppu.save_state().mirroring),
+                    serde_json::json!(ppu.save_state().mirroring),
+                    "mapper {} round {} mirroring",
+                    number,
+                    round
+                );
+                assert_eq!(
+                    restored.take_irq_pending(),
+                    mapper.take_irq_pending(),
+                    "mapper {} round {} IRQ",
+                    number,
+                    round
+                );
+            }
         }
+        // NROM plus every banked mapper
+        assert!(tested > 15, "only {} mappers tested", tested);
     }
 }
@@ -2,6 +2,7 @@ use crate::cartridge::{Cartridge, Mirroring};
 use crate::ppu::Ppu;
 #[cfg(test)]
 use emu_core::apu::TimingMode;
+use serde::{Deserialize, Serialize};
 
 /// Namco 118 / Mapper 206 - MMC3-like mapper without IRQ support
 ///
@@ -26,9 +27,11 @@ use emu_core::apu::TimingMode;
 /// - Typically smaller ROM sizes
 ///
 /// Used in games like Dragon Spirit, Famista, etc.
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Namco118 {
+    #[serde(skip)]
     prg_rom: Vec<u8>,
+    #[serde(skip)]
     chr_rom: Vec<u8>,
     bank_select: u8,
     bank_regs: [u8; 8],
@@ -171,6 +174,15 @@ impl Namco118 {
     pub fn prg_rom(&self) -> &[u8] {
         &self.prg_rom
     }
+
+    /// Restore bank registers from a save state, keeping the loaded ROM
+    pub fn restore_state(&mut self, state: Self) {
+        *self = Self {
+            prg_rom: std::mem::take(&mut self.prg_rom),
+            chr_rom: std::mem::take(&mut self.chr_rom),
+            ..state
+        };
+    }
 }
 
 #[cfg(test)]
@@ -189,6 +201,7 @@ mod tests {
             chr_rom: vec![0; 0x2000],
             mapper: 206,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Vertical,
         };
 
@@ -220,6 +233,7 @@ mod tests {
             chr_rom: vec![0; 0x2000],
             mapper: 206,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Vertical,
         };
 
@@ -251,6 +265,7 @@ mod tests {
             chr_rom: chr,
             mapper: 206,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -277,6 +292,7 @@ mod tests {
             chr_rom: vec![0; 0x2000],
             mapper: 206,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Vertical,
         };
 
@@ -303,6 +319,7 @@ mod tests {
             chr_rom: vec![0; 0x2000],
             mapper: 206,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Vertical,
         };
 
@@ -0,0 +1,399 @@
+use crate::cartridge::{Cartridge, Mirroring};
+use crate::ppu::Ppu;
+#[cfg(test)]
+use emu_core::apu::TimingMode;
+use serde::{Deserialize, Serialize};
+
+/// Namco 163 (Mapper 19) - PRG/CHR banking, nametable control, CPU cycle IRQ
+/// and wavetable expansion audio
+///
+/// # Hardware Behavior (per NESdev wiki)
+/// - **$8000-$BFFF**: Eight 1KB CHR banks, one register per $800
+/// - **$C000-$DFFF**: Nametable registers for $2000/$2400/$2800/$2C00;
+///   values $E0-$FF select CIRAM page (bit 0)
+/// - **$E000/$E800/$F000**: 8KB PRG banks at $8000/$A000/$C000 (bits 0-5),
+///   $E000 is fixed to the last bank; bit 6 of $E000 disables the sound
+/// - **$F800**: Sound RAM address (bits 0-6) with auto-increment (bit 7)
+/// - **$4800**: Sound RAM data port (read/write)
+/// - **$5000/$5800**: IRQ counter low/high (bit 7 of $5800 enables it); the
+///   15-bit counter counts up every CPU cycle and fires when it reaches $7FFF.
+///   Writing either register acknowledges the IRQ.
+/// - **Audio**: Up to 8 wavetable channels whose registers occupy the top of
+///   sound RAM ($40-$7F, 8 bytes per channel; $7F bits 4-6 hold the number of
+///   enabled channels minus one). One channel is updated every 15 CPU cycles,
+///   playing 4-bit samples packed two per byte in sound RAM.
+///
+/// # Implementation Notes
+/// The hardware switches its output between the enabled channels; they are
+/// averaged instead, which sounds the same without the switching whine.
+/// CHR-ROM nametables and CIRAM pattern tables are not supported; nametable
+/// registers only pick the CIRAM page, from which the mirroring is derived.
+///
+/// Used in Megami Tensei II, Erika to Satoru no Yume Bouken and
+/// Rolling Thunder (J).
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Namco163 {
+    #[serde(skip)]
+    prg_rom: Vec<u8>,
+    #[serde(skip)]
+    chr_rom: Vec<u8>,
+    chr_regs: [u8; 8],
+    nametable_regs: [u8; 4],
+    prg_regs: [u8; 3],
+    sound_ram: Vec<u8>,
+    /// $F800: sound RAM address and auto-increment flag
+    sound_address: u8,
+    irq_counter: u16,
+    irq_enabled: bool,
+    irq_pending: bool,
+    /// $E000 bit 6 clear
+    sound_enabled: bool,
+    /// CPU cycles until the next channel update
+    sound_timer: u8,
+    /// Channel updated next
+    sound_channel: u8,
+    /// Last output of each channel
+    channel_outputs: [i32; 8],
+}
+
+/// Sound RAM size in bytes
+const SOUND_RAM_SIZE: usize = 128;
+/// IRQ counter value at which the IRQ fires
+const IRQ_COUNTER_MAX: u16 = 0x7FFF;
+/// CPU cycles between channel updates
+const SOUND_UPDATE_CYCLES: u8 = 15;
+
+/// Expansion audio channels, mixed as one
+pub const AUDIO_CHANNELS: &[&str] = &["Namco 163"];
+
+impl Namco163 {
+    pub fn new(cart: Cartridge, ppu: &mut Ppu) -> Self {
+        let m = Self {
+            prg_rom: cart.prg_rom,
+            chr_rom: cart.chr_rom,
+            chr_regs: [0, 1, 2, 3, 4, 5, 6, 7],
+            nametable_regs: [0; 4],
+            prg_regs: [0, 1, 2],
+            sound_ram: vec![0; SOUND_RAM_SIZE],
+            sound_address: 0,
+            irq_counter: 0,
+            irq_enabled: false,
+            irq_pending: false,
+            sound_enabled: true,
+            sound_timer: SOUND_UPDATE_CYCLES,
+            sound_channel: 7,
+            channel_outputs: [0; 8],
+        };
+        m.update_chr_mapping(ppu);
+        ppu.set_mirroring(cart.mirroring);
+        m
+    }
+
+    fn read_bank(&self, bank: u8, addr: u16) -> u8 {
+        let count = std::cmp::max(1, self.prg_rom.len() / 0x2000);
+        let idx = (bank as usize % count) * 0x2000 + (addr as usize & 0x1FFF);
+        self.prg_rom.get(idx).copied().unwrap_or(0)
+    }
+
+    fn update_chr_mapping(&self, ppu: &mut Ppu) {
+        if ppu.chr.len() < 0x2000 {
+            ppu.chr.resize(0x2000, 0);
+        }
+
+        // For CHR RAM carts, skip copying (PPU owns RAM)
+        if self.chr_rom.is_empty() {
+            return;
+        }
+
+        let chr_count = std::cmp::max(1, self.chr_rom.len() / 0x0400);
+        for (i, &bank) in self.chr_regs.iter().enumerate() {
+            let dst_start = i * 0x0400;
+            let src_start = (bank as usize % chr_count) * 0x0400;
+            ppu.chr[dst_start..dst_start + 0x0400]
+                .copy_from_slice(&self.chr_rom[src_start..src_start + 0x0400]);
+        }
+    }
+
+    /// Mirroring matching the CIRAM pages the nametable registers select
+    fn update_mirroring(&self, ppu: &mut Ppu) {
+        let pages = self.nametable_regs.map(|reg| reg & 1);
+        let mirroring = match pages {
+            [0, 1, 0, 1] => Mirroring::Vertical,
+            [0, 0, 1, 1] => Mirroring::Horizontal,
+            [0, 0, 0, 0] => Mirroring::SingleScreenLower,
+            [1, 1, 1, 1] => Mirroring::SingleScreenUpper,
+            // Other arrangements have no equivalent; keep the current one
+            _ => return,
+        };
+        ppu.set_mirroring(mirroring);
+    }
+
+    pub fn read_prg(&self, addr: u16) -> u8 {
+        match addr {
+            0x8000..=0xDFFF => self.read_bank(self.prg_regs[(addr as usize - 0x8000) >> 13], addr),
+            _ => self.read_bank(0xFF, addr),
+        }
+    }
+
+    /// Read the sound RAM data port and IRQ counter in $4800-$5FFF
+    pub fn read_low(&mut self, addr: u16) -> Option<u8> {
+        match addr {
+            0x4800..=0x4FFF => {
+                let val = self.sound_ram[(self.sound_address & 0x7F) as usize];
+                self.advance_sound_address();
+                Some(val)
+            }
+            0x5000..=0x57FF => Some(self.irq_counter as u8),
+            0x5800..=0x5FFF => {
+                Some((self.irq_counter >> 8) as u8 | if self.irq_enabled { 0x80 } else { 0 })
+            }
+            _ => None,
+        }
+    }
+
+    /// Write the sound RAM data port and IRQ counter in $4800-$5FFF
+    pub fn write_low(&mut self, addr: u16, val: u8) {
+        match addr {
+            0x4800..=0x4FFF => {
+                self.sound_ram[(self.sound_address & 0x7F) as usize] = val;
+                self.advance_sound_address();
+            }
+            0x5000..=0x57FF => {
+                self.irq_counter = (self.irq_counter & 0x7F00) | val as u16;
+                self.irq_pending = false;
+            }
+            0x5800..=0x5FFF => {
+                self.irq_counter = (self.irq_counter & 0x00FF) | ((val as u16 & 0x7F) << 8);
+                self.irq_enabled = val & 0x80 != 0;
+                self.irq_pending = false;
+            }
+            _ => {}
+        }
+    }
+
+    fn advance_sound_address(&mut self) {
+        if self.sound_address & 0x80 != 0 {
+            self.sound_address = 0x80 | (self.sound_address.wrapping_add(1) & 0x7F);
+        }
+    }
+
+    pub fn write_prg(&mut self, addr: u16, val: u8, ppu: &mut Ppu, _cpu_cycles: u64) {
+        match addr {
+            0x8000..=0xBFFF => {
+                self.chr_regs[(addr as usize - 0x8000) >> 11] = val;
+                self.update_chr_mapping(ppu);
+            }
+            0xC000..=0xDFFF => {
+                self.nametable_regs[(addr as usize - 0xC000) >> 11] = val;
+                self.update_mirroring(ppu);
+            }
+            0xE000..=0xE7FF => {
+                self.prg_regs[0] = val & 0x3F;
+                self.sound_enabled = val & 0x40 == 0;
+            }
+            0xE800..=0xF7FF => self.prg_regs[(addr as usize - 0xE000) >> 11] = val & 0x3F,
+            _ => self.sound_address = val,
+        }
+    }
+
+    /// Run the IRQ counter for `cycles` CPU cycles
+    pub fn clock_cpu(&mut self, cycles: u32) {
+        if !self.irq_enabled || self.irq_counter >= IRQ_COUNTER_MAX {
+            return;
+        }
+        let counter = (self.irq_counter as u32 + cycles).min(IRQ_COUNTER_MAX as u32);
+        self.irq_counter = counter as u16;
+        if self.irq_counter == IRQ_COUNTER_MAX {
+            self.irq_pending = true;
+        }
+    }
+
+    /// Channels enabled by $7F bits 4-6: the top `n` of the 8
+    fn sound_channel_count(&self) -> u8 {
+        ((self.sound_ram[0x7F] >> 4) & 0x07) + 1
+    }
+
+    /// Advance channel `n`'s phase and latch its output
+    fn update_channel(&mut self, n: usize) {
+        let regs = &self.sound_ram[0x40 + n * 8..0x48 + n * 8];
+        let freq = regs[0] as u32 | (regs[2] as u32) << 8 | (regs[4] as u32 & 0x03) << 16;
+        let phase = regs[1] as u32 | (regs[3] as u32) << 8 | (regs[5] as u32) << 16;
+        let length = 256 - (regs[4] as u32 & 0xFC);
+        let offset = regs[6] as u32;
+        let volume = (regs[7] & 0x0F) as i32;
+
+        let phase = (phase + freq) % (length << 16);
+        let regs = &mut self.sound_ram[0x40 + n * 8..0x48 + n * 8];
+        regs[1] = phase as u8;
+        regs[3] = (phase >> 8) as u8;
+        regs[5] = (phase >> 16) as u8;
+
+        // 4-bit samples, the low nibble first
+        let sample_addr = ((phase >> 16) + offset) & 0xFF;
+        let byte = self.sound_ram[(sample_addr >> 1) as usize];
+        let sample = (byte >> ((sample_addr & 1) * 4)) & 0x0F;
+        self.channel_outputs[n] = (sample as i32 - 8) * volume;
+    }
+
+    /// Run the wavetable channels for `cycles` CPU cycles, returning their
+    /// average output
+    pub fn clock_audio(&mut self, cycles: u32) -> i32 {
+        if !self.sound_enabled {
+            return 0;
+        }
+        let mut acc = 0;
+        for _ in 0..cycles {
+            self.sound_timer -= 1;
+            if self.sound_timer == 0 {
+                self.sound_timer = SOUND_UPDATE_CYCLES;
+                let count = self.sound_channel_count();
+                let first = 8 - count;
+                if self.sound_channel < first {
+                    self.sound_channel = 7;
+                }
+                self.update_channel(self.sound_channel as usize);
+                self.sound_channel = if self.sound_channel == first {
+                    7
+                } else {
+                    self.sound_channel - 1
+                };
+            }
+            acc += self.mix_channels();
+        }
+        acc / cycles.max(1) as i32
+    }
+
+    /// Average of the enabled channels, scaled so a full-volume wave spans
+    /// about one APU pulse channel
+    fn mix_channels(&self) -> i32 {
+        let count = self.sound_channel_count();
+        let sum: i32 = self.channel_outputs[8 - count as usize..].iter().sum();
+        (sum << 7) / count as i32
+    }
+
+    pub fn take_irq_pending(&mut self) -> bool {
+        self.irq_pending
+    }
+
+    pub fn prg_rom(&self) -> &[u8] {
+        &self.prg_rom
+    }
+
+    /// Restore bank registers from a save state, keeping the loaded ROM
+    pub fn restore_state(&mut self, state: Self) {
+        *self = Self {
+            prg_rom: std::mem::take(&mut self.prg_rom),
+            chr_rom: std::mem::take(&mut self.chr_rom),
+            ..state
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn namco163() -> (Namco163, Ppu) {
+        let mut prg = vec![0; 0x40000]; // 32 banks of 8KB
+        for i in 0..32 {
+            prg[i * 0x2000] = i as u8;
+        }
+        let mut chr = vec![0; 0x8000]; // 32 banks of 1KB
+        for i in 0..32 {
+            chr[i * 0x400] = i as u8;
+        }
+        let cart = Cartridge {
+            prg_rom: prg,
+            chr_rom: chr,
+            mapper: 19,
+            timing: TimingMode::Ntsc,
+            battery: false,
+            mirroring: Mirroring::Vertical,
+        };
+        let mut ppu = Ppu::new(vec![], Mirroring::Vertical);
+        let m = Namco163::new(cart, &mut ppu);
+        (m, ppu)
+    }
+
+    #[test]
+    fn namco163_banking_and_nametables() {
+        let (mut m, mut ppu) = namco163();
+        m.write_prg(0xE000, 4, &mut ppu, 0);
+        m.write_prg(0xE800, 0xC5, &mut ppu, 0); // CHR RAM disable bits ignored
+        m.write_prg(0xF000, 6, &mut ppu, 0);
+        assert_eq!(m.read_prg(0x8000), 4);
+        assert_eq!(m.read_prg(0xA000), 5);
+        assert_eq!(m.read_prg(0xC000), 6);
+        assert_eq!(m.read_prg(0xE000), 31);
+
+        m.write_prg(0xB800, 17, &mut ppu, 0);
+        assert_eq!(ppu.chr[0x1C00], 17);
+
+        for (addr, page) in [
+            (0xC000, 0xE0),
+            (0xC800, 0xE0),
+            (0xD000, 0xE1),
+            (0xD800, 0xE1),
+        ] {
+            m.write_prg(addr, page, &mut ppu, 0);
+        }
+        assert_eq!(ppu.get_mirroring(), Mirroring::Horizontal);
+    }
+
+    #[test]
+    fn namco163_sound_ram_auto_increment() {
+        let (mut m, mut ppu) = namco163();
+        m.write_prg(0xF800, 0x80 | 0x7E, &mut ppu, 0);
+        m.write_low(0x4800, 0x11);
+        m.write_low(0x4800, 0x22);
+        m.write_low(0x4800, 0x33); // Wraps to address 0
+
+        m.write_prg(0xF800, 0x7E, &mut ppu, 0); // No auto-increment
+        assert_eq!(m.read_low(0x4800), Some(0x11));
+        assert_eq!(m.read_low(0x4800), Some(0x11));
+        m.write_prg(0xF800, 0x80, &mut ppu, 0);
+        assert_eq!(m.read_low(0x4800), Some(0x33));
+    }
+
+    #[test]
+    fn namco163_irq_counts_up_to_7fff() {
+        let (mut m, _) = namco163();
+        m.write_low(0x5000, 0xF0);
+        m.write_low(0x5800, 0xFF); // Enabled, counter $7FF0
+        assert_eq!(m.read_low(0x5800), Some(0xFF));
+        m.clock_cpu(14);
+        assert!(!m.take_irq_pending());
+        m.clock_cpu(1);
+        assert!(m.take_irq_pending());
+        assert_eq!(m.read_low(0x5000), Some(0xFF));
+
+        // Writing the counter acknowledges it
+        m.write_low(0x5000, 0);
+        assert!(!m.take_irq_pending());
+    }
+
+    #[test]
+    fn namco163_wavetable_channel() {
+        let (mut m, mut ppu) = namco163();
+        // Square wave: 4 samples of 15 then 4 of 0 at nibble address 0
+        m.write_prg(0xF800, 0x80, &mut ppu, 0);
+        for byte in [0xFF, 0xFF, 0x00, 0x00] {
+            m.write_low(0x4800, byte);
+        }
+        // Channel 7 only: one sample per update, 8-sample wave, volume 15
+        m.write_prg(0xF800, 0x80 | 0x78, &mut ppu, 0);
+        for byte in [0x00, 0x00, 0x00, 0x00, 0xF9, 0x00, 0x00, 0x0F] {
+            m.write_low(0x4800, byte);
+        }
+
+        // Each update lands on the last cycle of a 15-cycle block: samples
+        // 1-3 are high (+7 * 15), 4-7 low (-8 * 15)
+        let levels: Vec<i32> = (0..5).map(|_| m.clock_audio(15) >> 7).collect();
+        assert_eq!(levels, [7, 105, 105, 90, -120]);
+
+        // Disabling the sound silences it
+        m.write_prg(0xE000, 0x40, &mut ppu, 0);
+        assert_eq!(m.clock_audio(100), 0);
+        assert_eq!(m.read_prg(0x8000), 0);
+    }
+}
@@ -2,6 +2,7 @@ use crate::cartridge::Cartridge;
 use crate::ppu::Ppu;
 #[cfg(test)]
 use emu_core::apu::TimingMode;
+use serde::{Deserialize, Serialize};
 
 /// NINA-03/NINA-06 (Mapper 79) - AVE mapper with simple PRG/CHR switching
 ///
@@ -23,9 +24,11 @@ use emu_core::apu::TimingMode;
 /// implementation rather than a custom mapper ASIC.
 ///
 /// Used in games like Dudes with Attitude, Pyramid, F-15 City War
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Nina {
+    #[serde(skip)]
     prg_rom: Vec<u8>,
+    #[serde(skip)]
     chr_rom: Vec<u8>,
     prg_bank: u8,
     chr_bank: u8,
@@ -102,6 +105,15 @@ impl Nina {
     pub fn prg_rom(&self) -> &[u8] {
         &self.prg_rom
     }
+
+    /// Restore bank registers from a save state, keeping the loaded ROM
+    pub fn restore_state(&mut self, state: Self) {
+        *self = Self {
+            prg_rom: std::mem::take(&mut self.prg_rom),
+            chr_rom: std::mem::take(&mut self.chr_rom),
+            ..state
+        };
+    }
 }
 
 #[cfg(test)]
@@ -120,6 +132,7 @@ mod tests {
             chr_rom: vec![0; 0x2000],
             mapper: 79,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Vertical,
         };
 
@@ -152,6 +165,7 @@ mod tests {
             chr_rom: chr,
             mapper: 79,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
 
@@ -189,6 +203,7 @@ mod tests {
             chr_rom: chr,
             mapper: 79,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Vertical,
         };
 
@@ -224,6 +239,7 @@ mod tests {
             chr_rom: vec![0; 0x2000],
             mapper: 79,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Vertical,
         };
 
@@ -260,6 +276,7 @@ mod tests {
             chr_rom: chr,
             mapper: 79,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Vertical,
         };
 
@@ -47,6 +47,7 @@ mod tests {
             chr_rom: vec![],
             mapper: 0,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
         let nrom = Nrom::new(cart);
@@ -70,6 +71,7 @@ mod tests {
             chr_rom: vec![],
             mapper: 0,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
         let nrom = Nrom::new(cart);
@@ -88,6 +90,7 @@ mod tests {
             chr_rom: vec![],
             mapper: 0,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
         let nrom = Nrom::new(cart);
@@ -110,6 +113,7 @@ mod tests {
             chr_rom: vec![],
             mapper: 0,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
         let nrom = Nrom::new(cart);
@@ -135,6 +139,7 @@ mod tests {
             chr_rom: vec![],
             mapper: 0,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Horizontal,
         };
         let nrom = Nrom::new(cart);
@@ -0,0 +1,203 @@
+use crate::cartridge::{Cartridge, Mirroring};
+use crate::ppu::Ppu;
+#[cfg(test)]
+use emu_core::apu::TimingMode;
+use serde::{Deserialize, Serialize};
+
+/// Sunsoft-4 (Mapper 68) - 2KB CHR banking with CHR-ROM nametables
+///
+/// # Hardware Behavior (per NESdev wiki)
+/// - **$8000/$9000/$A000/$B000**: 2KB CHR banks at $0000/$0800/$1000/$1800
+/// - **$C000/$D000**: 1KB CHR-ROM pages used as the first/second nametable
+///   (bit 7 of the page number is always set)
+/// - **$E000**: Bits 0-1 mirroring (vertical, horizontal, one-screen lower,
+///   one-screen upper); bit 4 swaps CIRAM for the CHR-ROM nametables
+/// - **$F000**: 16KB PRG bank at $8000-$BFFF ($C000 is fixed to the last bank)
+///
+/// # Implementation Notes
+/// The PRG-RAM enable in $F000 bit 4 and the licensing timer of Nantettatte!!
+/// Baseball's sub-cartridge are not emulated; PRG-RAM at $6000 is always the
+/// bus's 8KB WRAM.
+///
+/// Used in After Burner (J), Maharaja and Nantettatte!! Baseball.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Sunsoft4 {
+    #[serde(skip)]
+    prg_rom: Vec<u8>,
+    #[serde(skip)]
+    chr_rom: Vec<u8>,
+    chr_regs: [u8; 4],
+    nametable_regs: [u8; 2],
+    /// $E000 bit 4: nametables come from CHR-ROM instead of CIRAM
+    chr_nametables: bool,
+    prg_bank: u8,
+}
+
+impl Sunsoft4 {
+    pub fn new(cart: Cartridge, ppu: &mut Ppu) -> Self {
+        let m = Self {
+            prg_rom: cart.prg_rom,
+            chr_rom: cart.chr_rom,
+            chr_regs: [0, 1, 2, 3],
+            nametable_regs: [0, 0],
+            chr_nametables: false,
+            prg_bank: 0,
+        };
+        m.update_chr_mapping(ppu);
+        ppu.set_mirroring(cart.mirroring);
+        m
+    }
+
+    fn update_chr_mapping(&self, ppu: &mut Ppu) {
+        if ppu.chr.len() < 0x2000 {
+            ppu.chr.resize(0x2000, 0);
+        }
+
+        // For CHR RAM carts, skip copying (PPU owns RAM)
+        if self.chr_rom.is_empty() {
+            return;
+        }
+
+        let chr_count = std::cmp::max(1, self.chr_rom.len() / 0x0800);
+        for (i, &bank) in self.chr_regs.iter().enumerate() {
+            let dst_start = i * 0x0800;
+            let src_start = (bank as usize % chr_count) * 0x0800;
+            ppu.chr[dst_start..dst_start + 0x0800]
+                .copy_from_slice(&self.chr_rom[src_start..src_start + 0x0800]);
+        }
+    }
+
+    fn update_nametables(&self, ppu: &mut Ppu) {
+        if !self.chr_nametables || self.chr_rom.is_empty() {
+            ppu.set_nametable_rom(&[]);
+            return;
+        }
+
+        let page_count = std::cmp::max(1, self.chr_rom.len() / 0x0400);
+        let mut pages = [0u8; 0x0800];
+        for (i, &reg) in self.nametable_regs.iter().enumerate() {
+            let src_start = ((reg | 0x80) as usize % page_count) * 0x0400;
+            pages[i * 0x0400..(i + 1) * 0x0400]
+                .copy_from_slice(&self.chr_rom[src_start..src_start + 0x0400]);
+        }
+        ppu.set_nametable_rom(&pages);
+    }
+
+    pub fn read_prg(&self, addr: u16) -> u8 {
+        let count = std::cmp::max(1, self.prg_rom.len() / 0x4000);
+        let bank = if addr < 0xC000 {
+            self.prg_bank as usize % count
+        } else {
+            count - 1
+        };
+        let idx = bank * 0x4000 + (addr as usize & 0x3FFF);
+        self.prg_rom.get(idx).copied().unwrap_or(0)
+    }
+
+    pub fn write_prg(&mut self, addr: u16, val: u8, ppu: &mut Ppu, _cpu_cycles: u64) {
+        match addr {
+            0x8000..=0xBFFF => {
+                self.chr_regs[(addr as usize - 0x8000) >> 12] = val;
+                self.update_chr_mapping(ppu);
+            }
+            0xC000..=0xDFFF => {
+                self.nametable_regs[(addr as usize - 0xC000) >> 12] = val;
+                self.update_nametables(ppu);
+            }
+            0xE000..=0xEFFF => {
+                let mirroring = match val & 0x03 {
+                    0 => Mirroring::Vertical,
+                    1 => Mirroring::Horizontal,
+                    2 => Mirroring::SingleScreenLower,
+                    _ => Mirroring::SingleScreenUpper,
+                };
+                ppu.set_mirroring(mirroring);
+                self.chr_nametables = val & 0x10 != 0;
+                self.update_nametables(ppu);
+            }
+            0xF000..=0xFFFF => self.prg_bank = val & 0x0F,
+            _ => {}
+        }
+    }
+
+    pub fn prg_rom(&self) -> &[u8] {
+        &self.prg_rom
+    }
+
+    /// Restore bank registers from a save state, keeping the loaded ROM
+    pub fn restore_state(&mut self, state: Self) {
+        *self = Self {
+            prg_rom: std::mem::take(&mut self.prg_rom),
+            chr_rom: std::mem::take(&mut self.chr_rom),
+            ..state
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sunsoft4() -> (Sunsoft4, Ppu) {
+        let mut prg = vec![0; 0x20000]; // 8 banks of 16KB
+        for i in 0..8 {
+            prg[i * 0x4000] = i as u8;
+        }
+        let mut chr = vec![0; 0x40000]; // 256 pages of 1KB
+        for i in 0..256 {
+            chr[i * 0x400] = i as u8;
+        }
+        let cart = Cartridge {
+            prg_rom: prg,
+            chr_rom: chr,
+            mapper: 68,
+            timing: TimingMode::Ntsc,
+            battery: false,
+            mirroring: Mirroring::Vertical,
+        };
+        let mut ppu = Ppu::new(vec![], Mirroring::Vertical);
+        let m = Sunsoft4::new(cart, &mut ppu);
+        (m, ppu)
+    }
+
+    #[test]
+    fn sunsoft4_banking() {
+        let (mut m, mut ppu) = sunsoft4();
+        m.write_prg(0xF000, 5, &mut ppu, 0);
+        assert_eq!(m.read_prg(0x8000), 5);
+        assert_eq!(m.read_prg(0xC000), 7);
+
+        // 2KB banks: bank 10 starts at 1KB page 20
+        m.write_prg(0xB000, 10, &mut ppu, 0);
+        assert_eq!(ppu.chr[0x1800], 20);
+
+        m.write_prg(0xE000, 1, &mut ppu, 0);
+        assert_eq!(ppu.get_mirroring(), Mirroring::Horizontal);
+        m.write_prg(0xE000, 3, &mut ppu, 0);
+        assert_eq!(ppu.get_mirroring(), Mirroring::SingleScreenUpper);
+    }
+
+    #[test]
+    fn sunsoft4_chr_rom_nametables() {
+        let (mut m, mut ppu) = sunsoft4();
+        ppu.vram[0x000] = 0x11;
+        ppu.vram[0x400] = 0x22;
+        m.write_prg(0xC000, 0x03, &mut ppu, 0);
+        m.write_prg(0xD000, 0x04, &mut ppu, 0);
+        assert_eq!(ppu.nametable_byte(0x000), 0x11);
+
+        // Enabled with vertical mirroring: $2000 -> $C000 page, $2400 -> $D000
+        m.write_prg(0xE000, 0x10, &mut ppu, 0);
+        assert_eq!(ppu.nametable_byte(0x000), 0x83);
+        assert_eq!(ppu.nametable_byte(0x400), 0x84);
+
+        // Page registers can be changed while the nametables are in ROM
+        m.write_prg(0xD000, 0x7F, &mut ppu, 0);
+        assert_eq!(ppu.nametable_byte(0x400), 0xFF);
+
+        // Back to CIRAM
+        m.write_prg(0xE000, 0x00, &mut ppu, 0);
+        assert_eq!(ppu.nametable_byte(0x000), 0x11);
+        assert_eq!(ppu.nametable_byte(0x400), 0x22);
+    }
+}
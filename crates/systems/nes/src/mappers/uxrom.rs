@@ -2,10 +2,12 @@ use crate::cartridge::Cartridge;
 use crate::ppu::Ppu;
 #[cfg(test)]
 use emu_core::apu::TimingMode;
+use serde::{Deserialize, Serialize};
 
 /// UxROM (Mapper 2) - Switchable 16KB PRG banks with fixed last bank
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Uxrom {
+    #[serde(skip)]
     prg_rom: Vec<u8>,
     bank_select: u8,
 }
@@ -46,6 +48,14 @@ impl Uxrom {
     pub fn prg_rom(&self) -> &[u8] {
         &self.prg_rom
     }
+
+    /// Restore bank registers from a save state, keeping the loaded ROM
+    pub fn restore_state(&mut self, state: Self) {
+        *self = Self {
+            prg_rom: std::mem::take(&mut self.prg_rom),
+            ..state
+        };
+    }
 }
 
 #[cfg(test)]
@@ -64,6 +74,7 @@ mod tests {
             chr_rom: vec![],
             mapper: 2,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Vertical,
         };
 
@@ -92,6 +103,7 @@ mod tests {
             chr_rom: vec![],
             mapper: 2,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Vertical,
         };
 
@@ -113,6 +125,7 @@ mod tests {
             chr_rom: vec![],
             mapper: 2,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Vertical,
         };
 
@@ -139,6 +152,7 @@ mod tests {
             chr_rom: vec![],
             mapper: 2,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Vertical,
         };
 
@@ -164,6 +178,7 @@ mod tests {
             chr_rom: vec![],
             mapper: 2,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Vertical,
         };
 
@@ -190,6 +205,7 @@ mod tests {
             chr_rom: vec![],
             mapper: 2,
             timing: TimingMode::Ntsc,
+            battery: false,
             mirroring: Mirroring::Vertical,
         };
 
@@ -0,0 +1,326 @@
+use crate::cartridge::{Cartridge, Mirroring};
+use crate::ppu::Ppu;
+#[cfg(test)]
+use emu_core::apu::TimingMode;
+use serde::{Deserialize, Serialize};
+
+/// Konami VRC2/VRC4 (Mappers 21, 22, 23, 25) - PRG/CHR banking and CPU cycle IRQ
+///
+/// # Hardware Behavior (per NESdev wiki)
+/// - **PRG ROM**: Two switchable 8KB banks ($8000-$8003, $A000-$A003) and the
+///   last two 8KB banks fixed; VRC4's swap mode ($9002 bit 1) exchanges the
+///   first switchable bank with the fixed second-last bank at $C000
+/// - **CHR ROM**: Eight 1KB banks, each set by a low nibble and a high part
+///   ($B000-$E003, two registers per bank)
+/// - **$9000**: Mirroring (vertical, horizontal, one-screen lower, one-screen
+///   upper; VRC2 only has the first two)
+/// - **IRQ** (VRC4): VRC-style counter at $F000/$F001 (latch low/high
+///   nibble), $F002 (control), $F003 (acknowledge), counting CPU cycles or
+///   scanlines (a prescaler of 341 PPU dots)
+/// - The register number comes from two CPU address lines that differ per
+///   board: A1/A2 or A6/A7 (21: VRC4a/c), A1/A0 (22: VRC2a), A0/A1 or A2/A3
+///   (23: VRC2b, VRC4e/f), A1/A0 or A3/A2 (25: VRC2c, VRC4b/d). Each mapper
+///   number decodes both of its wirings, as there is no submapper to tell
+///   them apart.
+/// - VRC2a (mapper 22) ignores the lowest CHR bank bit
+///
+/// # Implementation Notes
+/// All boards but mapper 22 are treated as VRC4: VRC2 games never write the
+/// VRC4-only registers. The VRC2 microwire latch at $6000 is not emulated;
+/// that range is WRAM.
+///
+/// Used in Ganbare Goemon Gaiden, Wai Wai World 2, Gradius II (J), Parodius
+/// Da!, TwinBee 3 and Contra (J).
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Vrc4 {
+    #[serde(skip)]
+    prg_rom: Vec<u8>,
+    #[serde(skip)]
+    chr_rom: Vec<u8>,
+    mapper: u8,
+    prg_regs: [u8; 2],
+    prg_swap: bool,
+    chr_regs: [u16; 8],
+    irq_latch: u8,
+    irq_counter: u8,
+    irq_prescaler: i16,
+    irq_enabled: bool,
+    irq_enable_after_ack: bool,
+    irq_cycle_mode: bool,
+    irq_pending: bool,
+}
+
+/// PPU dots per scanline, the IRQ prescaler period in scanline mode
+const PRESCALER_DOTS: i16 = 341;
+
+impl Vrc4 {
+    pub fn new(cart: Cartridge, ppu: &mut Ppu) -> Self {
+        let m = Self {
+            mapper: cart.mapper,
+            prg_rom: cart.prg_rom,
+            chr_rom: cart.chr_rom,
+            prg_regs: [0, 1],
+            prg_swap: false,
+            chr_regs: [0, 1, 2, 3, 4, 5, 6, 7],
+            irq_latch: 0,
+            irq_counter: 0,
+            irq_prescaler: PRESCALER_DOTS,
+            irq_enabled: false,
+            irq_enable_after_ack: false,
+            irq_cycle_mode: false,
+            irq_pending: false,
+        };
+        m.update_chr_mapping(ppu);
+        ppu.set_mirroring(cart.mirroring);
+        m
+    }
+
+    fn prg_bank_count(&self) -> usize {
+        std::cmp::max(1, self.prg_rom.len() / 0x2000)
+    }
+
+    fn update_chr_mapping(&self, ppu: &mut Ppu) {
+        if ppu.chr.len() < 0x2000 {
+            ppu.chr.resize(0x2000, 0);
+        }
+
+        // For CHR RAM carts, skip copying (PPU owns RAM)
+        if self.chr_rom.is_empty() {
+            return;
+        }
+
+        let chr_count = std::cmp::max(1, self.chr_rom.len() / 0x0400);
+        for (i, &reg) in self.chr_regs.iter().enumerate() {
+            // VRC2a leaves CHR A10 unconnected
+            let bank = if self.mapper == 22 { reg >> 1 } else { reg };
+            let dst_start = i * 0x0400;
+            let src_start = (bank as usize % chr_count) * 0x0400;
+            ppu.chr[dst_start..dst_start + 0x0400]
+                .copy_from_slice(&self.chr_rom[src_start..src_start + 0x0400]);
+        }
+    }
+
+    /// Register number (0-3) from the address lines wired on this board
+    fn register_index(&self, addr: u16) -> u16 {
+        let line = |n: u16| (addr >> n) & 1;
+        match self.mapper {
+            21 => (line(1) | line(6)) | ((line(2) | line(7)) << 1),
+            22 => line(1) | (line(0) << 1),
+            23 => (line(0) | line(2)) | ((line(1) | line(3)) << 1),
+            _ => (line(1) | line(3)) | ((line(0) | line(2)) << 1),
+        }
+    }
+
+    pub fn read_prg(&self, addr: u16) -> u8 {
+        let count = self.prg_bank_count();
+        let second_last = count.saturating_sub(2);
+        let bank = match (addr, self.prg_swap) {
+            (0x8000..=0x9FFF, false) | (0xC000..=0xDFFF, true) => self.prg_regs[0] as usize,
+            (0x8000..=0x9FFF, true) | (0xC000..=0xDFFF, false) => second_last,
+            (0xA000..=0xBFFF, _) => self.prg_regs[1] as usize,
+            _ => count - 1,
+        };
+        let idx = (bank % count) * 0x2000 + (addr as usize & 0x1FFF);
+        self.prg_rom.get(idx).copied().unwrap_or(0)
+    }
+
+    pub fn write_prg(&mut self, addr: u16, val: u8, ppu: &mut Ppu, _cpu_cycles: u64) {
+        let reg = (addr & 0xF000) | self.register_index(addr);
+        match reg {
+            0x8000..=0x8003 => self.prg_regs[0] = val & 0x1F,
+            0x9000..=0x9003 if self.mapper == 22 => {
+                // VRC2: one mirroring bit in all four registers
+                ppu.set_mirroring(if val & 0x01 == 0 {
+                    Mirroring::Vertical
+                } else {
+                    Mirroring::Horizontal
+                });
+            }
+            0x9000 | 0x9001 => {
+                let mirroring = match val & 0x03 {
+                    0 => Mirroring::Vertical,
+                    1 => Mirroring::Horizontal,
+                    2 => Mirroring::SingleScreenLower,
+                    _ => Mirroring::SingleScreenUpper,
+                };
+                ppu.set_mirroring(mirroring);
+            }
+            0x9002 | 0x9003 => self.prg_swap = val & 0x02 != 0,
+            0xA000..=0xA003 => self.prg_regs[1] = val & 0x1F,
+            0xB000..=0xE003 => {
+                // Two registers per 1KB bank: low nibble, then the high bits
+                let index = ((reg >> 12) as usize - 0xB) * 2 + ((reg as usize >> 1) & 1);
+                let bank = &mut self.chr_regs[index];
+                *bank = if reg & 1 == 0 {
+                    (*bank & 0x1F0) | (val as u16 & 0x0F)
+                } else {
+                    (*bank & 0x00F) | ((val as u16 & 0x1F) << 4)
+                };
+                self.update_chr_mapping(ppu);
+            }
+            0xF000 => self.irq_latch = (self.irq_latch & 0xF0) | (val & 0x0F),
+            0xF001 => self.irq_latch = (self.irq_latch & 0x0F) | ((val & 0x0F) << 4),
+            0xF002 => {
+                self.irq_enable_after_ack = val & 0x01 != 0;
+                self.irq_enabled = val & 0x02 != 0;
+                self.irq_cycle_mode = val & 0x04 != 0;
+                if self.irq_enabled {
+                    self.irq_counter = self.irq_latch;
+                    self.irq_prescaler = PRESCALER_DOTS;
+                }
+                self.irq_pending = false;
+            }
+            0xF003 => {
+                self.irq_pending = false;
+                self.irq_enabled = self.irq_enable_after_ack;
+            }
+            _ => {}
+        }
+    }
+
+    /// Run the IRQ counter for `cycles` CPU cycles
+    pub fn clock_cpu(&mut self, cycles: u32) {
+        if !self.irq_enabled {
+            return;
+        }
+        for _ in 0..cycles {
+            if self.irq_cycle_mode {
+                self.clock_irq_counter();
+            } else {
+                // Three PPU dots per CPU cycle
+                self.irq_prescaler -= 3;
+                if self.irq_prescaler <= 0 {
+                    self.irq_prescaler += PRESCALER_DOTS;
+                    self.clock_irq_counter();
+                }
+            }
+        }
+    }
+
+    fn clock_irq_counter(&mut self) {
+        if self.irq_counter == 0xFF {
+            self.irq_counter = self.irq_latch;
+            self.irq_pending = true;
+        } else {
+            self.irq_counter += 1;
+        }
+    }
+
+    pub fn take_irq_pending(&mut self) -> bool {
+        self.irq_pending
+    }
+
+    pub fn prg_rom(&self) -> &[u8] {
+        &self.prg_rom
+    }
+
+    /// 21, 22, 23 or 25
+    pub fn mapper_number(&self) -> u8 {
+        self.mapper
+    }
+
+    /// Restore bank registers from a save state, keeping the loaded ROM
+    pub fn restore_state(&mut self, state: Self) {
+        *self = Self {
+            prg_rom: std::mem::take(&mut self.prg_rom),
+            chr_rom: std::mem::take(&mut self.chr_rom),
+            ..state
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vrc4(mapper: u8) -> (Vrc4, Ppu) {
+        let mut prg = vec![0; 0x20000]; // 16 banks of 8KB
+        for i in 0..16 {
+            prg[i * 0x2000] = i as u8;
+        }
+        let mut chr = vec![0; 0x40000]; // 256 banks of 1KB
+        for i in 0..256 {
+            chr[i * 0x400] = i as u8;
+        }
+        let cart = Cartridge {
+            prg_rom: prg,
+            chr_rom: chr,
+            mapper,
+            timing: TimingMode::Ntsc,
+            battery: false,
+            mirroring: Mirroring::Vertical,
+        };
+        let mut ppu = Ppu::new(vec![], Mirroring::Vertical);
+        let m = Vrc4::new(cart, &mut ppu);
+        (m, ppu)
+    }
+
+    #[test]
+    fn vrc4_prg_banking_and_swap_mode() {
+        let (mut m, mut ppu) = vrc4(25);
+        m.write_prg(0x8000, 3, &mut ppu, 0);
+        m.write_prg(0xA000, 5, &mut ppu, 0);
+        assert_eq!(m.read_prg(0x8000), 3);
+        assert_eq!(m.read_prg(0xA000), 5);
+        assert_eq!(m.read_prg(0xC000), 14);
+        assert_eq!(m.read_prg(0xE000), 15);
+
+        // $9001 on mapper 25 is register 2 (A0): swap mode
+        m.write_prg(0x9001, 0x02, &mut ppu, 0);
+        assert_eq!(m.read_prg(0x8000), 14);
+        assert_eq!(m.read_prg(0xC000), 3);
+        assert_eq!(m.read_prg(0xE000), 15);
+    }
+
+    #[test]
+    fn vrc4_chr_registers_follow_board_wiring() {
+        // Bank 1 is registers 2 (low nibble) and 3 (high bits) of $B000
+        for (mapper, low, high) in [
+            (21, 0xB004, 0xB006),
+            (21, 0xB080, 0xB0C0),
+            (23, 0xB002, 0xB003),
+            (23, 0xB008, 0xB00C),
+            (25, 0xB001, 0xB003),
+            (25, 0xB004, 0xB00C),
+        ] {
+            let (mut m, mut ppu) = vrc4(mapper);
+            m.write_prg(low, 0x05, &mut ppu, 0);
+            m.write_prg(high, 0x0A, &mut ppu, 0);
+            assert_eq!(ppu.chr[0x0400], 0xA5, "mapper {} ${:04X}", mapper, low);
+        }
+
+        // VRC2a drops the lowest bank bit
+        let (mut m, mut ppu) = vrc4(22);
+        m.write_prg(0xE001, 0x09, &mut ppu, 0); // Bank 7 low nibble
+        assert_eq!(ppu.chr[0x1C00], 4);
+
+        let (mut m, mut ppu) = vrc4(21);
+        m.write_prg(0x9000, 0x03, &mut ppu, 0);
+        assert_eq!(ppu.get_mirroring(), Mirroring::SingleScreenUpper);
+    }
+
+    #[test]
+    fn vrc4_irq_counts_cpu_cycles_and_scanlines() {
+        let (mut m, mut ppu) = vrc4(23);
+        m.write_prg(0xF000, 0x00, &mut ppu, 0);
+        m.write_prg(0xF001, 0x0F, &mut ppu, 0); // Latch $F0: 16 clocks to overflow
+        m.write_prg(0xF008, 0x07, &mut ppu, 0); // Enable, cycle mode
+        m.clock_cpu(15);
+        assert!(!m.take_irq_pending());
+        m.clock_cpu(1);
+        assert!(m.take_irq_pending());
+
+        // Acknowledge keeps it enabled (E after ack set), reloaded from the latch
+        m.write_prg(0xF00C, 0, &mut ppu, 0);
+        assert!(!m.take_irq_pending());
+        m.clock_cpu(16);
+        assert!(m.take_irq_pending());
+
+        // Scanline mode: 16 scanlines of 113.67 CPU cycles
+        m.write_prg(0xF002, 0x02, &mut ppu, 0);
+        m.clock_cpu(113 * 16);
+        assert!(!m.take_irq_pending());
+        m.clock_cpu(30);
+        assert!(m.take_irq_pending());
+    }
+}
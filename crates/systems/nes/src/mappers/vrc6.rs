@@ -0,0 +1,469 @@
+use crate::cartridge::{Cartridge, Mirroring};
+use crate::ppu::Ppu;
+#[cfg(test)]
+use emu_core::apu::TimingMode;
+use serde::{Deserialize, Serialize};
+
+/// Konami VRC6 (Mappers 24/26) - PRG/CHR banking, CPU cycle IRQ and expansion audio
+///
+/// # Hardware Behavior (per NESdev wiki)
+/// - **PRG ROM**: 16KB bank at $8000 ($8000-$8003), 8KB bank at $C000
+///   ($C000-$C003), last 8KB fixed at $E000
+/// - **CHR ROM**: Eight 1KB banks ($D000-$D003, $E000-$E003)
+/// - **$B003**: PPU banking mode; bits 2-3 select mirroring (vertical,
+///   horizontal, one-screen lower, one-screen upper)
+/// - **IRQ**: VRC-style counter at $F000 (latch), $F001 (control), $F002
+///   (acknowledge), counting CPU cycles or scanlines (a prescaler of 341 PPU
+///   dots)
+/// - **Audio**: Two pulse channels ($9000-$9002, $A000-$A002) with 8 duty
+///   settings and a sawtooth channel ($B000-$B002), mixed into the APU output
+/// - Mapper 26 (VRC6b) swaps address lines A0 and A1
+///
+/// # Implementation Notes
+/// Only PPU banking mode 0 (eight 1KB CHR banks) is implemented, which is what
+/// Akumajou Densetsu, Madara and Esper Dream 2 use.
+///
+/// Used in Akumajou Densetsu (Castlevania III, J), Madara and Esper Dream 2.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Vrc6 {
+    #[serde(skip)]
+    prg_rom: Vec<u8>,
+    #[serde(skip)]
+    chr_rom: Vec<u8>,
+    /// VRC6b (mapper 26) with A0 and A1 swapped
+    swapped: bool,
+    prg_16k: u8,
+    prg_8k: u8,
+    chr_regs: [u8; 8],
+    irq_latch: u8,
+    irq_counter: u8,
+    irq_prescaler: i16,
+    irq_enabled: bool,
+    irq_enable_after_ack: bool,
+    irq_cycle_mode: bool,
+    irq_pending: bool,
+    audio: Vrc6Audio,
+}
+
+/// PPU dots per scanline, the IRQ prescaler period in scanline mode
+const PRESCALER_DOTS: i16 = 341;
+
+/// Expansion audio channels, in `clock_audio` channel order
+pub const AUDIO_CHANNELS: &[&str] = &["VRC6 Pulse 1", "VRC6 Pulse 2", "VRC6 Saw"];
+
+impl Vrc6 {
+    pub fn new(cart: Cartridge, ppu: &mut Ppu) -> Self {
+        let m = Self {
+            swapped: cart.mapper == 26,
+            prg_rom: cart.prg_rom,
+            chr_rom: cart.chr_rom,
+            prg_16k: 0,
+            prg_8k: 0,
+            chr_regs: [0, 1, 2, 3, 4, 5, 6, 7],
+            irq_latch: 0,
+            irq_counter: 0,
+            irq_prescaler: PRESCALER_DOTS,
+            irq_enabled: false,
+            irq_enable_after_ack: false,
+            irq_cycle_mode: false,
+            irq_pending: false,
+            audio: Vrc6Audio::default(),
+        };
+        m.update_chr_mapping(ppu);
+        ppu.set_mirroring(cart.mirroring);
+        m
+    }
+
+    fn prg_bank_count(&self) -> usize {
+        std::cmp::max(1, self.prg_rom.len() / 0x2000)
+    }
+
+    fn update_chr_mapping(&self, ppu: &mut Ppu) {
+        if ppu.chr.len() < 0x2000 {
+            ppu.chr.resize(0x2000, 0);
+        }
+
+        // For CHR RAM carts, skip copying (PPU owns RAM)
+        if self.chr_rom.is_empty() {
+            return;
+        }
+
+        let chr_count = std::cmp::max(1, self.chr_rom.len() / 0x0400);
+        for (i, &bank) in self.chr_regs.iter().enumerate() {
+            let dst_start = i * 0x0400;
+            let src_start = (bank as usize % chr_count) * 0x0400;
+            ppu.chr[dst_start..dst_start + 0x0400]
+                .copy_from_slice(&self.chr_rom[src_start..src_start + 0x0400]);
+        }
+    }
+
+    pub fn read_prg(&self, addr: u16) -> u8 {
+        let count = self.prg_bank_count();
+        let bank = match addr {
+            0x8000..=0xBFFF => (self.prg_16k as usize * 2) | ((addr as usize >> 13) & 1),
+            0xC000..=0xDFFF => self.prg_8k as usize,
+            _ => count - 1,
+        };
+        let idx = (bank % count) * 0x2000 + (addr as usize & 0x1FFF);
+        self.prg_rom.get(idx).copied().unwrap_or(0)
+    }
+
+    pub fn write_prg(&mut self, addr: u16, val: u8, ppu: &mut Ppu, _cpu_cycles: u64) {
+        // Register number in the low two address lines, swapped on VRC6b
+        let reg = if self.swapped {
+            (addr & 0xF000) | ((addr & 1) << 1) | ((addr >> 1) & 1)
+        } else {
+            addr & 0xF003
+        };
+        match reg {
+            0x8000..=0x8003 => self.prg_16k = val & 0x0F,
+            0x9000..=0x9003 | 0xA000..=0xA002 | 0xB000..=0xB002 => self.audio.write(reg, val),
+            0xB003 => {
+                let mirroring = match (val >> 2) & 0x03 {
+                    0 => Mirroring::Vertical,
+                    1 => Mirroring::Horizontal,
+                    2 => Mirroring::SingleScreenLower,
+                    _ => Mirroring::SingleScreenUpper,
+                };
+                ppu.set_mirroring(mirroring);
+            }
+            0xC000..=0xC003 => self.prg_8k = val & 0x1F,
+            0xD000..=0xD003 | 0xE000..=0xE003 => {
+                let index = ((reg >> 12) as usize - 0xD) * 4 + (reg as usize & 3);
+                self.chr_regs[index] = val;
+                self.update_chr_mapping(ppu);
+            }
+            0xF000 => self.irq_latch = val,
+            0xF001 => {
+                self.irq_enable_after_ack = val & 0x01 != 0;
+                self.irq_enabled = val & 0x02 != 0;
+                self.irq_cycle_mode = val & 0x04 != 0;
+                if self.irq_enabled {
+                    self.irq_counter = self.irq_latch;
+                    self.irq_prescaler = PRESCALER_DOTS;
+                }
+                self.irq_pending = false;
+            }
+            0xF002 => {
+                self.irq_pending = false;
+                self.irq_enabled = self.irq_enable_after_ack;
+            }
+            _ => {}
+        }
+    }
+
+    /// Run the IRQ counter for `cycles` CPU cycles
+    pub fn clock_cpu(&mut self, cycles: u32) {
+        if !self.irq_enabled {
+            return;
+        }
+        for _ in 0..cycles {
+            if self.irq_cycle_mode {
+                self.clock_irq_counter();
+            } else {
+                // Three PPU dots per CPU cycle
+                self.irq_prescaler -= 3;
+                if self.irq_prescaler <= 0 {
+                    self.irq_prescaler += PRESCALER_DOTS;
+                    self.clock_irq_counter();
+                }
+            }
+        }
+    }
+
+    fn clock_irq_counter(&mut self) {
+        if self.irq_counter == 0xFF {
+            self.irq_counter = self.irq_latch;
+            self.irq_pending = true;
+        } else {
+            self.irq_counter += 1;
+        }
+    }
+
+    /// Run one audio channel (see [`AUDIO_CHANNELS`]) for `cycles` CPU
+    /// cycles, returning its average output
+    pub fn clock_audio(&mut self, channel: usize, cycles: u32) -> i32 {
+        let mut acc = 0;
+        for _ in 0..cycles {
+            acc += self.audio.clock(channel);
+        }
+        acc / cycles.max(1) as i32
+    }
+
+    pub fn take_irq_pending(&mut self) -> bool {
+        self.irq_pending
+    }
+
+    pub fn prg_rom(&self) -> &[u8] {
+        &self.prg_rom
+    }
+
+    /// 24 (VRC6a) or 26 (VRC6b)
+    pub fn mapper_number(&self) -> u8 {
+        if self.swapped {
+            26
+        } else {
+            24
+        }
+    }
+
+    /// Restore bank registers from a save state, keeping the loaded ROM
+    pub fn restore_state(&mut self, state: Self) {
+        *self = Self {
+            prg_rom: std::mem::take(&mut self.prg_rom),
+            chr_rom: std::mem::take(&mut self.chr_rom),
+            ..state
+        };
+    }
+}
+
+/// VRC6 pulse channel: 4-bit volume, 16-step duty cycle
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Vrc6Pulse {
+    volume: u8,
+    /// High for steps 0..=duty of the 16
+    duty: u8,
+    /// Ignore the duty cycle and output the volume constantly
+    digitized: bool,
+    period: u16,
+    enabled: bool,
+    timer: u16,
+    step: u8,
+}
+
+impl Vrc6Pulse {
+    fn write(&mut self, reg: u16, val: u8) {
+        match reg & 3 {
+            0 => {
+                self.volume = val & 0x0F;
+                self.duty = (val >> 4) & 0x07;
+                self.digitized = val & 0x80 != 0;
+            }
+            1 => self.period = (self.period & 0x0F00) | val as u16,
+            _ => {
+                self.period = (self.period & 0x00FF) | ((val as u16 & 0x0F) << 8);
+                self.enabled = val & 0x80 != 0;
+                if !self.enabled {
+                    self.step = 15;
+                }
+            }
+        }
+    }
+
+    fn clock(&mut self, halted: bool) -> i32 {
+        if !self.enabled {
+            return 0;
+        }
+        if !halted {
+            if self.timer == 0 {
+                self.timer = self.period;
+                self.step = self.step.wrapping_sub(1) & 0x0F;
+            } else {
+                self.timer -= 1;
+            }
+        }
+        // Same scale as the APU pulse channels
+        let level = (self.volume as i32) << 10;
+        if self.digitized || self.step <= self.duty {
+            level
+        } else {
+            -level
+        }
+    }
+}
+
+/// VRC6 sawtooth channel: an accumulator that adds the rate every other
+/// timer clock and resets after 14 clocks
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Vrc6Saw {
+    rate: u8,
+    period: u16,
+    enabled: bool,
+    timer: u16,
+    step: u8,
+    accumulator: u8,
+}
+
+impl Vrc6Saw {
+    fn write(&mut self, reg: u16, val: u8) {
+        match reg & 3 {
+            0 => self.rate = val & 0x3F,
+            1 => self.period = (self.period & 0x0F00) | val as u16,
+            _ => {
+                self.period = (self.period & 0x00FF) | ((val as u16 & 0x0F) << 8);
+                self.enabled = val & 0x80 != 0;
+                if !self.enabled {
+                    self.step = 0;
+                    self.accumulator = 0;
+                }
+            }
+        }
+    }
+
+    fn clock(&mut self, halted: bool) -> i32 {
+        if !self.enabled {
+            return 0;
+        }
+        if !halted {
+            if self.timer == 0 {
+                self.timer = self.period;
+                self.step += 1;
+                if self.step == 14 {
+                    self.step = 0;
+                    self.accumulator = 0;
+                } else if self.step.is_multiple_of(2) {
+                    self.accumulator = self.accumulator.wrapping_add(self.rate);
+                }
+            } else {
+                self.timer -= 1;
+            }
+        }
+        // The top 5 bits are output, centered like the pulse channels
+        ((self.accumulator >> 3) as i32 - 16) << 10
+    }
+}
+
+/// VRC6 expansion sound
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Vrc6Audio {
+    pulse1: Vrc6Pulse,
+    pulse2: Vrc6Pulse,
+    saw: Vrc6Saw,
+    /// $9003 bit 0: stop all channel timers
+    halted: bool,
+}
+
+impl Vrc6Audio {
+    fn write(&mut self, reg: u16, val: u8) {
+        match reg {
+            0x9003 => self.halted = val & 0x01 != 0,
+            0x9000..=0x9002 => self.pulse1.write(reg, val),
+            0xA000..=0xA002 => self.pulse2.write(reg, val),
+            _ => self.saw.write(reg, val),
+        }
+    }
+
+    fn clock(&mut self, channel: usize) -> i32 {
+        match channel {
+            0 => self.pulse1.clock(self.halted),
+            1 => self.pulse2.clock(self.halted),
+            _ => self.saw.clock(self.halted),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vrc6(mapper: u8) -> (Vrc6, Ppu) {
+        let mut prg = vec![0; 0x20000]; // 16 banks of 8KB
+        for i in 0..16 {
+            prg[i * 0x2000] = i as u8;
+        }
+        let mut chr = vec![0; 0x8000]; // 32 banks of 1KB
+        for i in 0..32 {
+            chr[i * 0x400] = i as u8;
+        }
+        let cart = Cartridge {
+            prg_rom: prg,
+            chr_rom: chr,
+            mapper,
+            timing: TimingMode::Ntsc,
+            battery: false,
+            mirroring: Mirroring::Vertical,
+        };
+        let mut ppu = Ppu::new(vec![], Mirroring::Vertical);
+        let m = Vrc6::new(cart, &mut ppu);
+        (m, ppu)
+    }
+
+    #[test]
+    fn vrc6_prg_and_chr_banking() {
+        let (mut m, mut ppu) = vrc6(24);
+        m.write_prg(0x8000, 3, &mut ppu, 0); // 16KB bank 3 = 8KB banks 6, 7
+        m.write_prg(0xC000, 9, &mut ppu, 0);
+        assert_eq!(m.read_prg(0x8000), 6);
+        assert_eq!(m.read_prg(0xA000), 7);
+        assert_eq!(m.read_prg(0xC000), 9);
+        assert_eq!(m.read_prg(0xE000), 15);
+
+        m.write_prg(0xD001, 20, &mut ppu, 0); // R1
+        m.write_prg(0xE003, 31, &mut ppu, 0); // R7
+        assert_eq!(ppu.chr[0x0400], 20);
+        assert_eq!(ppu.chr[0x1C00], 31);
+
+        m.write_prg(0xB003, 0x04, &mut ppu, 0);
+        assert_eq!(ppu.get_mirroring(), Mirroring::Horizontal);
+    }
+
+    #[test]
+    fn vrc6b_swaps_address_lines() {
+        let (mut m, mut ppu) = vrc6(26);
+        // $D001 on VRC6b is register R2, $D002 is R1
+        m.write_prg(0xD001, 20, &mut ppu, 0);
+        m.write_prg(0xD002, 21, &mut ppu, 0);
+        assert_eq!(ppu.chr[0x0800], 20);
+        assert_eq!(ppu.chr[0x0400], 21);
+        // $B003 is the same on both
+        m.write_prg(0xB003, 0x08, &mut ppu, 0);
+        assert_eq!(ppu.get_mirroring(), Mirroring::SingleScreenLower);
+    }
+
+    #[test]
+    fn vrc6_irq_counts_cpu_cycles_and_scanlines() {
+        let (mut m, mut ppu) = vrc6(24);
+        m.write_prg(0xF000, 0xF0, &mut ppu, 0); // 16 clocks to overflow
+        m.write_prg(0xF001, 0x07, &mut ppu, 0); // Enable, cycle mode
+        m.clock_cpu(15);
+        assert!(!m.take_irq_pending());
+        m.clock_cpu(1);
+        assert!(m.take_irq_pending());
+
+        // Acknowledge keeps it enabled (E after ack set), reloaded from the latch
+        m.write_prg(0xF002, 0, &mut ppu, 0);
+        assert!(!m.take_irq_pending());
+        m.clock_cpu(16);
+        assert!(m.take_irq_pending());
+
+        // Scanline mode: 16 scanlines of 113.67 CPU cycles
+        m.write_prg(0xF001, 0x02, &mut ppu, 0);
+        m.clock_cpu(113 * 16);
+        assert!(!m.take_irq_pending());
+        m.clock_cpu(30);
+        assert!(m.take_irq_pending());
+    }
+
+    #[test]
+    fn vrc6_audio_channels() {
+        let (mut m, mut ppu) = vrc6(24);
+        assert!((0..3).all(|channel| m.clock_audio(channel, 100) == 0));
+
+        // Pulse 1 at full volume, 50% duty, period 15 (16 cycles per step)
+        m.write_prg(0x9000, 0x7F, &mut ppu, 0);
+        m.write_prg(0x9001, 15, &mut ppu, 0);
+        m.write_prg(0x9002, 0x80, &mut ppu, 0);
+        // High for 8 of the 16 steps: averages out over a full period
+        assert_eq!(m.clock_audio(0, 256), 0);
+
+        // Digitized mode outputs the volume constantly
+        m.write_prg(0x9000, 0x8F, &mut ppu, 0);
+        assert_eq!(m.clock_audio(0, 10), 15 << 10);
+
+        // Halting stops the timers but keeps the output
+        m.write_prg(0x9000, 0x0F, &mut ppu, 0);
+        m.write_prg(0x9003, 0x01, &mut ppu, 0);
+        let held = m.clock_audio(0, 1);
+        assert_eq!(m.clock_audio(0, 100), held);
+        m.write_prg(0x9002, 0x00, &mut ppu, 0);
+        m.write_prg(0x9003, 0x00, &mut ppu, 0);
+
+        // Sawtooth: rate 42 peaks at accumulator 252 (output 31)
+        m.write_prg(0xB000, 42, &mut ppu, 0);
+        m.write_prg(0xB001, 0, &mut ppu, 0);
+        m.write_prg(0xB002, 0x80, &mut ppu, 0);
+        let levels: Vec<i32> = (0..14).map(|_| m.clock_audio(2, 1) >> 10).collect();
+        assert_eq!(levels[12], (252 >> 3) - 16);
+        assert_eq!(levels[13], -16);
+    }
+}
@@ -0,0 +1,106 @@
+//! NES audio output stage.
+//!
+//! The [`Mixer`] combines the APU's internal channels with expansion audio
+//! channels that cartridge hardware adds to the console's audio line (VRC6
+//! pulses and saw, Namco 163 wavetable). Mappers register their channels when
+//! the cartridge is installed; each one is a callback run for every output
+//! sample, the same way the PPU reaches the mapper through A12 and CHR read
+//! callbacks.
+
+use std::fmt;
+
+/// Expansion channel callback: runs the channel for `cycles` CPU cycles and
+/// returns its average level on the scale of an APU pulse channel
+pub type ExpansionChannel = Box<dyn FnMut(u32) -> i32>;
+
+/// Number of APU channels (pulse 1, pulse 2, triangle, noise, DMC) the mix
+/// is averaged over, so expansion audio sits at the level of one of them
+const APU_CHANNEL_COUNT: i32 = 5;
+
+/// Mixes the APU and registered expansion channels into output samples
+#[derive(Default)]
+pub struct Mixer {
+    expansion: Vec<(&'static str, ExpansionChannel)>,
+}
+
+impl Mixer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an expansion channel to the mix
+    pub fn add_expansion_channel(&mut self, name: &'static str, channel: ExpansionChannel) {
+        self.expansion.push((name, channel));
+    }
+
+    /// Remove all expansion channels (when the cartridge changes)
+    pub fn clear_expansion_channels(&mut self) {
+        self.expansion.clear();
+    }
+
+    /// Names of the registered expansion channels
+    pub fn expansion_channel_names(&self) -> Vec<&'static str> {
+        self.expansion.iter().map(|(name, _)| *name).collect()
+    }
+
+    /// Mix one output sample
+    ///
+    /// `apu` is the summed average of the pulse, triangle and noise channels
+    /// and `dmc` the DMC level, both over the sample's `cycles` CPU cycles.
+    /// The expansion channels are run for the same cycles.
+    pub fn mix(&mut self, apu: i32, dmc: i32, cycles: u32) -> i16 {
+        let expansion: i32 = self
+            .expansion
+            .iter_mut()
+            .map(|(_, channel)| channel(cycles))
+            .sum();
+        let mixed = (apu + dmc + expansion) / APU_CHANNEL_COUNT;
+        mixed.clamp(i16::MIN as i32, i16::MAX as i32) as i16
+    }
+}
+
+impl fmt::Debug for Mixer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Mixer")
+            .field("expansion", &self.expansion_channel_names())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_mixer_runs_expansion_channels() {
+        let mut mixer = Mixer::new();
+        assert_eq!(mixer.mix(1000, 500, 40), 300);
+
+        let clocked = Rc::new(Cell::new(0));
+        let counter = clocked.clone();
+        mixer.add_expansion_channel(
+            "Test",
+            Box::new(move |cycles| {
+                counter.set(counter.get() + cycles);
+                1000
+            }),
+        );
+        mixer.add_expansion_channel("Quiet", Box::new(|_| 0));
+        assert_eq!(mixer.expansion_channel_names(), vec!["Test", "Quiet"]);
+        assert_eq!(mixer.mix(1000, 500, 40), 500);
+        assert_eq!(clocked.get(), 40);
+
+        mixer.clear_expansion_channels();
+        assert_eq!(mixer.mix(1000, 500, 40), 300);
+        assert_eq!(clocked.get(), 40);
+    }
+
+    #[test]
+    fn test_mixer_clamps_output() {
+        let mut mixer = Mixer::new();
+        mixer.add_expansion_channel("Loud", Box::new(|_| 1_000_000));
+        assert_eq!(mixer.mix(0, 0, 1), i16::MAX);
+    }
+}
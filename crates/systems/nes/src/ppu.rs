@@ -27,6 +27,20 @@
 //! This approach is suitable for most games but may not handle edge cases
 //! requiring precise PPU timing (mid-scanline register changes, exact sprite 0 hit timing, etc.).
 //!
+//! Scanlines drawn by `render_scanline` are scrolled from the position the
+//! real PPU keeps in its internal `v`/`t` registers: `v` takes all of `t` on
+//! the pre-render line, then moves down a row after every visible line and
+//! takes the horizontal scroll from `t` there. Scroll writes made during the
+//! frame then behave like on hardware (a `$2005` Y write waits for the next
+//! frame, a `$2006` write moves the picture at once), which is what
+//! split-screen status bars timed with sprite 0 hit or a mapper IRQ depend on.
+//!
+//! With the default [`PpuAccuracy::Frame`] lines are synthesized once the
+//! PPU time of a whole line has passed, so each line is drawn with the scroll
+//! captured when it began, and a `$2006` write during a line is taken to land
+//! in its horizontal blank. [`PpuAccuracy::Scanline`] draws each line at dot
+//! 257 from the live `v`.
+//!
 //! ## Memory Map
 //!
 //! - **$0000-$1FFF**: CHR ROM/RAM (pattern tables)
@@ -47,6 +61,7 @@
 use crate::cartridge::Mirroring;
 use emu_core::logging::{log, LogCategory, LogLevel};
 use emu_core::types::Frame;
+use serde::{Deserialize, Serialize};
 use std::cell::{Cell, RefCell};
 use std::fmt;
 
@@ -67,6 +82,11 @@ const NES_MASTER_PALETTE: [u32; 64] = [
 // When reading from palette RAM via PPUDATA, the internal buffer is filled with the mirrored nametable value.
 const PALETTE_TO_NAMETABLE_OFFSET: u16 = 0x1000;
 
+// The PPU's I/O data bus holds the last value driven onto it; bits that are not
+// refreshed fade to 0 after roughly 600ms. Measured in frames (~36 at 60Hz).
+// Reference: https://www.nesdev.org/wiki/Open_bus_behavior#PPU_open_bus
+const OPEN_BUS_DECAY_FRAMES: u32 = 36;
+
 fn nes_palette_rgb(index: u8) -> u32 {
     NES_MASTER_PALETTE[(index & 0x3F) as usize]
 }
@@ -85,6 +105,54 @@ fn palette_mirror_index(i: usize) -> usize {
     }
 }
 
+/// Snapshot of the PPU for save states.
+///
+/// `chr` holds the 8KB pattern table window: CHR-RAM contents, or the CHR-ROM
+/// banks the mapper last copied in. Mapper callbacks are not included; they
+/// stay wired to the installed cartridge.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PpuState {
+    pub chr: Vec<u8>,
+    pub vram: Vec<u8>,
+    pub palette: [u8; 32],
+    pub oam: Vec<u8>,
+    pub mirroring: Mirroring,
+    pub ctrl: u8,
+    pub mask: u8,
+    pub vblank: bool,
+    pub sprite_0_hit: bool,
+    pub sprite_overflow: bool,
+    pub nmi_pending: bool,
+    pub addr_latch: bool,
+    pub vram_addr: u16,
+    pub read_buffer: u8,
+    pub scroll_x: u8,
+    pub scroll_y: u8,
+    pub oam_addr: u8,
+    #[serde(default)]
+    pub open_bus: u8,
+    /// Temporary VRAM address (`t`) loaded into `v` by rendering
+    #[serde(default)]
+    pub temp_addr: u16,
+    #[serde(default)]
+    pub fine_x: u8,
+    /// CHR-ROM pages replacing CIRAM (empty when the nametables are CIRAM)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub nametable_rom: Vec<u8>,
+}
+
+/// How closely background scrolling follows the PPU's timing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PpuAccuracy {
+    /// Scanlines are synthesized after the PPU time of each line has passed
+    /// with rendering enabled, each scrolled by the `v` captured when it began
+    #[default]
+    Frame,
+    /// Each scanline is drawn at dot 257 of the frame's own clock, scrolled
+    /// from the PPU's internal VRAM address as the hardware updates it
+    Scanline,
+}
+
 /// NES PPU (Picture Processing Unit).
 ///
 /// Implements the 2C02 PPU with frame-based rendering.
@@ -104,8 +172,10 @@ fn palette_mirror_index(i: usize) -> usize {
 /// - `sprite_0_hit`: Sprite 0 hit flag (PPUSTATUS bit 6)
 /// - `sprite_overflow`: Sprite overflow flag (PPUSTATUS bit 5)
 /// - `nmi_pending`: Pending NMI request
-/// - `vram_addr`: Current VRAM address
+/// - `vram_addr`: Current VRAM address (`v`)
+/// - `temp_addr`, `fine_x`: Temporary VRAM address (`t`) and fine X scroll
 /// - `scroll_x`, `scroll_y`: Scroll position
+/// - `open_bus`: I/O data latch returned by write-only registers (decays per bit)
 ///
 /// # Callbacks
 ///
@@ -115,6 +185,9 @@ pub struct Ppu {
     pub chr: Vec<u8>,
     chr_is_ram: bool,
     pub vram: [u8; 0x800], // 2KB internal VRAM (nametables)
+    /// Cartridge CHR-ROM shown in place of the two CIRAM pages (Sunsoft-4);
+    /// empty when the nametables come from CIRAM
+    nametable_rom: Vec<u8>,
     pub palette: [u8; 32],
     pub oam: [u8; 256],
     mirroring: Mirroring,
@@ -136,7 +209,20 @@ pub struct Ppu {
     suppress_a12: Cell<bool>,
     scroll_x: u8,
     scroll_y: u8,
+    temp_addr: u16,
+    fine_x: u8,
+    // Scroll (`v`, fine X) the synthesized scanline being drawn started with,
+    // and whether `$2006` set `v` since then (Frame accuracy)
+    line_scroll: Cell<(u16, u8)>,
+    vram_addr_written: Cell<bool>,
     oam_addr: Cell<u8>,
+    // I/O data latch ("open bus") and the frame each bit was last refreshed on
+    open_bus: Cell<u8>,
+    open_bus_refreshed: Cell<[u32; 8]>,
+    frame_count: Cell<u32>,
+    // Emulate the hardware's diagonal OAM scan when looking for overflow
+    sprite_overflow_bug: bool,
+    accuracy: PpuAccuracy,
 }
 
 impl fmt::Debug for Ppu {
@@ -156,6 +242,7 @@ impl Ppu {
             chr,
             chr_is_ram,
             vram: [0; 0x800],
+            nametable_rom: Vec::new(),
             palette: [0; 32],
             oam: [0; 256],
             mirroring,
@@ -178,7 +265,16 @@ impl Ppu {
             suppress_a12: Cell::new(false),
             scroll_x: 0,
             scroll_y: 0,
+            temp_addr: 0,
+            fine_x: 0,
+            line_scroll: Cell::new((0, 0)),
+            vram_addr_written: Cell::new(false),
             oam_addr: Cell::new(0),
+            open_bus: Cell::new(0),
+            open_bus_refreshed: Cell::new([0; 8]),
+            frame_count: Cell::new(0),
+            sprite_overflow_bug: false,
+            accuracy: PpuAccuracy::Frame,
         }
     }
 
@@ -207,10 +303,101 @@ impl Ppu {
         (physical_table * 0x0400 + offset) as usize & 0x07FF
     }
 
+    /// Capture memory and register state for a save state
+    pub fn save_state(&self) -> PpuState {
+        let chr_window = self.chr.len().min(0x2000);
+        PpuState {
+            chr: self.chr[..chr_window].to_vec(),
+            vram: self.vram.to_vec(),
+            palette: self.palette,
+            oam: self.oam.to_vec(),
+            mirroring: self.mirroring,
+            ctrl: self.ctrl,
+            mask: self.mask,
+            vblank: self.vblank.get(),
+            sprite_0_hit: self.sprite_0_hit.get(),
+            sprite_overflow: self.sprite_overflow.get(),
+            nmi_pending: self.nmi_pending.get(),
+            addr_latch: self.addr_latch.get(),
+            vram_addr: self.vram_addr.get(),
+            read_buffer: self.read_buffer.get(),
+            scroll_x: self.scroll_x,
+            scroll_y: self.scroll_y,
+            oam_addr: self.oam_addr.get(),
+            open_bus: self.open_bus(),
+            temp_addr: self.temp_addr,
+            fine_x: self.fine_x,
+            nametable_rom: self.nametable_rom.clone(),
+        }
+    }
+
+    /// Restore memory and register state from a save state
+    pub fn load_state(&mut self, state: &PpuState) -> Result<(), String> {
+        if state.vram.len() != self.vram.len() || state.oam.len() != self.oam.len() {
+            return Err("PPU state has wrong VRAM/OAM size".to_string());
+        }
+        if !state.nametable_rom.is_empty() && state.nametable_rom.len() != self.vram.len() {
+            return Err("PPU state has wrong nametable ROM size".to_string());
+        }
+        if state.chr.len() > self.chr.len() {
+            return Err(format!(
+                "PPU state has {} bytes of CHR, cartridge has {}",
+                state.chr.len(),
+                self.chr.len()
+            ));
+        }
+
+        self.chr[..state.chr.len()].copy_from_slice(&state.chr);
+        self.vram.copy_from_slice(&state.vram);
+        self.nametable_rom.clone_from(&state.nametable_rom);
+        self.palette = state.palette;
+        self.oam.copy_from_slice(&state.oam);
+        self.mirroring = state.mirroring;
+        self.ctrl = state.ctrl;
+        self.mask = state.mask;
+        self.vblank.set(state.vblank);
+        self.sprite_0_hit.set(state.sprite_0_hit);
+        self.sprite_overflow.set(state.sprite_overflow);
+        self.nmi_pending.set(state.nmi_pending);
+        self.addr_latch.set(state.addr_latch);
+        self.vram_addr.set(state.vram_addr);
+        self.read_buffer.set(state.read_buffer);
+        self.suppress_a12.set(false);
+        self.scroll_x = state.scroll_x;
+        self.scroll_y = state.scroll_y;
+        self.temp_addr = state.temp_addr;
+        self.fine_x = state.fine_x;
+        self.capture_line_scroll();
+        self.oam_addr.set(state.oam_addr);
+        self.open_bus.set(0);
+        self.refresh_open_bus(state.open_bus, 0xFF);
+        Ok(())
+    }
+
     pub fn set_mirroring(&mut self, mirroring: Mirroring) {
         self.mirroring = mirroring;
     }
 
+    /// Show 2KB of cartridge CHR-ROM (two 1KB pages) in place of CIRAM, or
+    /// go back to CIRAM with an empty slice
+    ///
+    /// Mirroring still picks which page each nametable uses. The pages are
+    /// read-only: PPUDATA writes to them are dropped.
+    pub fn set_nametable_rom(&mut self, pages: &[u8]) {
+        debug_assert!(pages.is_empty() || pages.len() == self.vram.len());
+        self.nametable_rom.clear();
+        self.nametable_rom.extend_from_slice(pages);
+    }
+
+    /// Byte at a CIRAM index from `map_nametable_addr`, from CHR-ROM when
+    /// the cartridge replaced the nametables
+    pub(crate) fn nametable_byte(&self, idx: usize) -> u8 {
+        match self.nametable_rom.get(idx) {
+            Some(&val) => val,
+            None => self.vram[idx],
+        }
+    }
+
     pub fn get_mirroring(&self) -> Mirroring {
         self.mirroring
     }
@@ -249,6 +436,10 @@ impl Ppu {
     /// - Sprite 0 hit and sprite overflow are cleared on pre-render scanline, NOT when VBlank starts/ends
     pub fn set_vblank(&self, v: bool) {
         let prev = self.vblank.replace(v);
+        if v && !prev {
+            // One VBlank per frame: the clock for open bus decay
+            self.frame_count.set(self.frame_count.get().wrapping_add(1));
+        }
         if v && !prev && self.nmi_enabled() {
             // VBlank just started and NMI is enabled - trigger NMI
             log(LogCategory::PPU, LogLevel::Trace, || {
@@ -313,8 +504,47 @@ impl Ppu {
         self.chr.get(addr).copied().unwrap_or(0)
     }
 
-    /// Read a PPU register (very partial implementation).
+    /// Current value of the I/O data latch, with stale bits decayed to 0.
+    fn open_bus(&self) -> u8 {
+        let now = self.frame_count.get();
+        let refreshed = self.open_bus_refreshed.get();
+        let mut value = self.open_bus.get();
+        for (bit, &frame) in refreshed.iter().enumerate() {
+            if now.wrapping_sub(frame) >= OPEN_BUS_DECAY_FRAMES {
+                value &= !(1 << bit);
+            }
+        }
+        self.open_bus.set(value);
+        value
+    }
+
+    /// Drive the bits selected by `mask` onto the I/O data latch.
+    fn refresh_open_bus(&self, value: u8, mask: u8) {
+        let now = self.frame_count.get();
+        let mut refreshed = self.open_bus_refreshed.get();
+        for (bit, frame) in refreshed.iter_mut().enumerate() {
+            if mask & (1 << bit) != 0 {
+                *frame = now;
+            }
+        }
+        self.open_bus_refreshed.set(refreshed);
+        self.open_bus
+            .set((self.open_bus() & !mask) | (value & mask));
+    }
+
+    /// Read a PPU register.
+    ///
+    /// Bits the register does not drive come from the I/O data latch, which
+    /// also makes the write-only registers return the last value on the bus.
     pub fn read_register(&self, reg: u16) -> u8 {
+        let (value, driven) = self.read_register_bits(reg);
+        let value = (value & driven) | (self.open_bus() & !driven);
+        self.refresh_open_bus(value, driven);
+        value
+    }
+
+    /// Register value and the mask of bits it actually drives onto the bus.
+    fn read_register_bits(&self, reg: u16) -> (u8, u8) {
         match reg & 0x7 {
             2 => {
                 // PPUSTATUS: bit 7 = vblank, bit 6 = sprite 0 hit, bit 5 = sprite overflow
@@ -341,12 +571,19 @@ impl Ppu {
                 self.vblank.set(false);
                 self.nmi_pending.set(false);
                 self.addr_latch.set(false);
-                status
+                // Bits 0-4 are open bus
+                (status, 0xE0)
             }
             4 => {
-                // OAMDATA read: return current OAM byte at oam_addr
+                // OAMDATA read: return current OAM byte at oam_addr.
+                // Attribute bytes have no storage for bits 2-4; they read back as 0.
                 let addr = self.oam_addr.get() as usize;
-                self.oam[addr]
+                let val = self.oam[addr];
+                if addr & 3 == 2 {
+                    (val & 0xE3, 0xFF)
+                } else {
+                    (val, 0xFF)
+                }
             }
             7 => {
                 // PPUDATA read with buffered behavior.
@@ -363,11 +600,12 @@ impl Ppu {
                     // Fill buffer with the mirrored nametable value underneath
                     let mirrored_nt_addr = addr - PALETTE_TO_NAMETABLE_OFFSET;
                     let idx = self.map_nametable_addr(mirrored_nt_addr);
-                    self.read_buffer.set(self.vram[idx]);
+                    self.read_buffer.set(self.nametable_byte(idx));
 
                     let inc = if (self.ctrl & 0x04) != 0 { 32 } else { 1 };
                     self.vram_addr.set(self.vram_addr.get().wrapping_add(inc));
-                    return val;
+                    // Palette entries are 6 bits; the top two come from open bus
+                    return (val, 0x3F);
                 }
 
                 // Return buffered value, then reload buffer from current addr.
@@ -379,18 +617,22 @@ impl Ppu {
                 let inc = if (self.ctrl & 0x04) != 0 { 32 } else { 1 };
                 self.vram_addr.set(self.vram_addr.get().wrapping_add(inc));
 
-                buffered
+                (buffered, 0xFF)
             }
-            _ => 0,
+            // Write-only registers return the I/O latch
+            _ => (0, 0x00),
         }
     }
 
     pub fn write_register(&mut self, reg: u16, val: u8) {
+        // Every write, to any register, refreshes the whole I/O latch
+        self.refresh_open_bus(val, 0xFF);
         match reg & 0x7 {
             0 => {
                 // PPUCTRL
                 let old_nmi = (self.ctrl & 0x80) != 0;
                 self.ctrl = val;
+                self.temp_addr = (self.temp_addr & !0x0C00) | ((val as u16 & 0x03) << 10);
                 let new_nmi = (self.ctrl & 0x80) != 0;
                 log(LogCategory::PPU, LogLevel::Trace, || {
                     format!(
@@ -428,9 +670,14 @@ impl Ppu {
                 // PPUSCROLL (write x then y), shares latch with PPUADDR.
                 if !self.addr_latch.get() {
                     self.scroll_x = val;
+                    self.temp_addr = (self.temp_addr & !0x001F) | (val as u16 >> 3);
+                    self.fine_x = val & 0x07;
                     self.addr_latch.set(true);
                 } else {
                     self.scroll_y = val;
+                    self.temp_addr = (self.temp_addr & !0x73E0)
+                        | ((val as u16 & 0x07) << 12)
+                        | ((val as u16 & 0xF8) << 2);
                     self.addr_latch.set(false);
                     log(LogCategory::PPU, LogLevel::Trace, || {
                         format!("PPUSCROLL set: X={}, Y={}", self.scroll_x, self.scroll_y)
@@ -442,11 +689,14 @@ impl Ppu {
                 if !self.addr_latch.get() {
                     let lo = self.vram_addr.get() & 0x00FF;
                     self.vram_addr.set(((val as u16) << 8) | lo);
+                    self.temp_addr = (self.temp_addr & 0x00FF) | ((val as u16 & 0x3F) << 8);
                     self.addr_latch.set(true);
                 } else {
                     let hi = self.vram_addr.get() & 0xFF00;
                     self.vram_addr.set(hi | val as u16);
+                    self.temp_addr = (self.temp_addr & 0xFF00) | val as u16;
                     self.addr_latch.set(false);
+                    self.vram_addr_written.set(true);
                 }
             }
             7 => {
@@ -460,7 +710,9 @@ impl Ppu {
                 } else if addr < 0x3F00 {
                     // Nametable VRAM space with mirroring
                     let idx = self.map_nametable_addr(addr);
-                    self.vram[idx] = val;
+                    if self.nametable_rom.is_empty() {
+                        self.vram[idx] = val;
+                    }
                 } else {
                     // Palette RAM: $3F00-$3FFF with 32-byte mirroring
                     // (addr is already masked to 0x3FFF, so this handles $3F00-$3FFF)
@@ -495,33 +747,174 @@ impl Ppu {
         }
     }
 
+    /// Emulate the sprite overflow bug of the real PPU.
+    ///
+    /// Once 8 sprites are found on a scanline, the hardware keeps looking
+    /// for a 9th but increments the byte index along with the sprite index,
+    /// so it compares tile numbers, attributes and X positions against the
+    /// scanline instead of Y coordinates. This sets the overflow flag for
+    /// some lines with only 8 sprites and misses it for some with more.
+    /// Off by default, which gives the flag the documented meaning.
+    ///
+    /// Reference: NESdev wiki - PPU sprite evaluation, sprite overflow bug
+    pub fn set_sprite_overflow_bug(&mut self, enabled: bool) {
+        self.sprite_overflow_bug = enabled;
+    }
+
+    /// Whether the sprite overflow bug is emulated
+    pub fn sprite_overflow_bug(&self) -> bool {
+        self.sprite_overflow_bug
+    }
+
+    /// Select where `render_scanline` takes the background scroll from
+    pub fn set_accuracy(&mut self, accuracy: PpuAccuracy) {
+        self.accuracy = accuracy;
+    }
+
+    /// Background scroll source for `render_scanline`
+    pub fn accuracy(&self) -> PpuAccuracy {
+        self.accuracy
+    }
+
+    /// Dot 257 of a visible line with rendering enabled: move `v` down one
+    /// pixel row (wrapping to the next nametable after row 29, but not after
+    /// rows 30-31, which only "negative" scroll values reach) and reload its
+    /// horizontal scroll from `t`.
+    ///
+    /// Reference: NESdev wiki - PPU scrolling, "Wrapping around"
+    pub fn end_scanline_scroll(&self) {
+        let mut v = self.vram_addr.get() & 0x7FFF;
+        if v & 0x7000 != 0x7000 {
+            v += 0x1000;
+        } else {
+            v &= !0x7000;
+            let coarse_y = match (v & 0x03E0) >> 5 {
+                29 => {
+                    v ^= 0x0800;
+                    0
+                }
+                31 => 0,
+                y => y + 1,
+            };
+            v = (v & !0x03E0) | (coarse_y << 5);
+        }
+        v = (v & !0x041F) | (self.temp_addr & 0x041F);
+        self.vram_addr.set(v);
+    }
+
+    /// Pre-render line with rendering enabled: `v` takes the whole scroll
+    /// position from `t` for the next frame
+    pub fn reload_scroll(&self) {
+        self.vram_addr.set(self.temp_addr);
+    }
+
+    /// Start of a synthesized scanline: remember the scroll it is drawn with,
+    /// so writes made while it is in progress only move the lines after it
+    pub fn capture_line_scroll(&self) {
+        self.line_scroll.set((self.vram_addr.get(), self.fine_x));
+        self.vram_addr_written.set(false);
+    }
+
+    /// End of a synthesized scanline with rendering enabled: step `v` to the
+    /// next line (see `end_scanline_scroll`) and capture its scroll.
+    ///
+    /// A `$2006` write made during the line counts as made in horizontal
+    /// blank, after the PPU's own update, as split-screen code times it: the
+    /// next line starts exactly at the written address.
+    pub fn next_line_scroll(&self) {
+        if !self.vram_addr_written.get() {
+            self.end_scanline_scroll();
+        }
+        self.capture_line_scroll();
+    }
+
+    /// A12 edges of the pattern fetches over one rendered scanline.
+    ///
+    /// Each 8-dot fetch group reads the nametable and attribute bytes (A12 low)
+    /// before the two pattern bytes: background tiles at dots 1-256 and 321-336,
+    /// the eight sprite slots at 257-320, then nametable reads up to dot 340.
+    /// Returns the dots where A12 changes level, with the new level.
+    ///
+    /// `sprite_line` is the line the sprite slots are fetched for. It only
+    /// matters for 8x16 sprites, whose pattern table follows bit 0 of each
+    /// tile index; unused slots, and all slots when it is None (pre-render
+    /// line), fetch tile $FF.
+    pub fn a12_fetch_edges(&self, sprite_line: Option<u32>) -> Vec<(u16, bool)> {
+        let bg_high = (self.ctrl & 0x10) != 0;
+        let mut sprite_high = [(self.ctrl & 0x08) != 0; 8];
+        if (self.ctrl & 0x20) != 0 {
+            let mut tiles = [0xFFu8; 8];
+            if let Some(line) = sprite_line {
+                let in_range = |y: u8| (0..16).contains(&(line as i16 - (y as i16 + 1)));
+                let on_line = (0..64).map(|n| n * 4).filter(|&o| in_range(self.oam[o]));
+                for (tile, o) in tiles.iter_mut().zip(on_line) {
+                    *tile = self.oam[o + 1];
+                }
+            }
+            sprite_high = tiles.map(|tile| (tile & 1) != 0);
+        }
+
+        let groups = (0..32u16)
+            .map(|g| (1 + g * 8, bg_high))
+            .chain((0..8u16).map(|s| (257 + s * 8, sprite_high[s as usize])))
+            .chain((0..2u16).map(|g| (321 + g * 8, bg_high)));
+        let mut edges = Vec::new();
+        let mut high = false;
+        for (dot, pattern_high) in groups {
+            if high {
+                edges.push((dot, false));
+            }
+            if pattern_high {
+                edges.push((dot + 4, true));
+            }
+            high = pattern_high;
+        }
+        if high {
+            edges.push((337, false));
+        }
+        edges
+    }
+
     /// Evaluate sprites for a scanline to determine sprite overflow.
     ///
     /// The NES PPU can only display 8 sprites per scanline. If more than 8 sprites
     /// are on the same scanline, the sprite overflow flag is set.
     ///
     /// This is a simplified version of the hardware sprite evaluation process.
+    /// With `set_sprite_overflow_bug` the search for a 9th sprite follows the
+    /// hardware's diagonal walk through OAM.
     fn evaluate_sprites_for_scanline(&self, scanline: u32) {
         let sprite_size_16 = (self.ctrl & 0x20) != 0;
         let sprite_height = if sprite_size_16 { 16 } else { 8 };
+        let in_range = |y: u8| {
+            let row = (scanline as i16) - (y as i16 + 1);
+            row >= 0 && row < sprite_height
+        };
 
+        // Find the first 8 sprites on this scanline
+        let mut n = 0;
         let mut sprites_found = 0;
-
-        // Check all 64 sprites in OAM
-        for i in 0..64 {
-            let o = i * 4;
-            let y_pos = self.oam[o] as i16 + 1;
-
-            // Check if this sprite is on the current scanline
-            let row = (scanline as i16) - y_pos;
-            if row >= 0 && row < sprite_height {
+        while n < 64 && sprites_found < 8 {
+            if in_range(self.oam[n * 4]) {
                 sprites_found += 1;
+            }
+            n += 1;
+        }
+        if sprites_found < 8 {
+            return;
+        }
 
-                // If we found more than 8 sprites on this scanline, set overflow
-                if sprites_found > 8 {
-                    self.sprite_overflow.set(true);
-                    return;
-                }
+        // Look for a 9th sprite among the remaining ones
+        let mut m = 0;
+        while n < 64 {
+            if in_range(self.oam[n * 4 + m]) {
+                self.sprite_overflow.set(true);
+                return;
+            }
+            n += 1;
+            if self.sprite_overflow_bug {
+                // Hardware bug: the byte index moves with the sprite index
+                m = (m + 1) & 3;
             }
         }
     }
@@ -532,7 +925,7 @@ impl Ppu {
             self.chr_fetch(a as usize)
         } else if a < 0x3F00 {
             let idx = self.map_nametable_addr(a);
-            self.vram[idx]
+            self.nametable_byte(idx)
         } else if a < 0x4000 {
             let p = (a - 0x3F00) & 0x1F;
             self.palette[palette_mirror_index(p as usize)]
@@ -602,13 +995,13 @@ impl Ppu {
 
                     let nt_addr = 0x2000u16 + (nt as u16) * 0x0400;
                     let tile_addr = nt_addr + (ty as u16) * 32 + (tx as u16);
-                    let tile_index = self.vram[self.map_nametable_addr(tile_addr)];
+                    let tile_index = self.nametable_byte(self.map_nametable_addr(tile_addr));
 
                     // Attribute table is at 0x3C0 within the nametable.
                     let attr_x = tx / 4;
                     let attr_y = ty / 4;
                     let attr_addr = nt_addr + 0x03C0 + (attr_y as u16) * 8 + (attr_x as u16);
-                    let attr_byte = self.vram[self.map_nametable_addr(attr_addr)];
+                    let attr_byte = self.nametable_byte(self.map_nametable_addr(attr_addr));
                     let quadrant = ((ty % 4) / 2) * 2 + ((tx % 4) / 2); // 0..3
                     let shift = (quadrant * 2) as u8;
                     let palette_idx = (attr_byte >> shift) & 0x03;
@@ -807,7 +1200,6 @@ impl Ppu {
         } else {
             0x0000
         };
-        let base_nt = (self.ctrl & 0x03) as u8;
 
         let mut universal_bg_idx = self.palette[palette_mirror_index(0)];
         if (self.mask & 0x01) != 0 {
@@ -815,8 +1207,22 @@ impl Ppu {
         }
         let universal_bg = nes_palette_rgb(universal_bg_idx);
 
-        let sx = self.scroll_x as u32;
-        let sy = self.scroll_y as u32;
+        // Nametable, horizontal scroll and row within the nametable this line
+        // shows, from `v` (see `end_scanline_scroll`). Synthesized lines use
+        // the scroll captured when they began; the scanline-accurate PPU draws
+        // at dot 257 from the live registers.
+        let (v, fine_x) = match self.accuracy {
+            PpuAccuracy::Frame => self.line_scroll.get(),
+            PpuAccuracy::Scanline => (self.vram_addr.get(), self.fine_x),
+        };
+        let coarse_x = (v & 0x1F) as u32;
+        let coarse_y = ((v >> 5) & 0x1F) as u32;
+        let fine_y = ((v >> 12) & 0x07) as u32;
+        let (line_nt, sx, nt_row) = (
+            ((v >> 10) & 0x03) as u8,
+            coarse_x * 8 + fine_x as u32,
+            coarse_y * 8 + fine_y,
+        );
 
         // Track background priority for this scanline (for sprite priority).
         let mut bg_priority = [false; 256];
@@ -827,31 +1233,28 @@ impl Ppu {
                 // Clip leftmost 8 pixels if PPUMASK bit 1 is clear
                 let should_render_bg = show_bg_left || x >= 8;
                 let wx = x + sx;
-                let wy = y + sy;
 
                 let nt_x = ((wx / 256) & 1) as u8;
-                let nt_y = ((wy / 240) & 1) as u8;
                 // Choose nametable based on base XOR scroll crossing.
                 // This matches real NES PPU behavior: the nametable bits are XORed
                 // with the coarse scroll overflow to select the correct nametable.
-                let nt = base_nt ^ nt_x ^ (nt_y << 1);
+                let nt = line_nt ^ nt_x;
 
                 let world_x = wx % 256;
-                let world_y = wy % 240;
 
                 let tx = (world_x / 8) as usize;
-                let ty = (world_y / 8) as usize;
+                let ty = (nt_row / 8) as usize;
                 let fine_x = (world_x % 8) as usize;
-                let fine_y = (world_y % 8) as usize;
+                let fine_y = (nt_row % 8) as usize;
 
                 let nt_addr = 0x2000u16 + (nt as u16) * 0x0400;
                 let tile_addr = nt_addr + (ty as u16) * 32 + (tx as u16);
-                let tile_index = self.vram[self.map_nametable_addr(tile_addr)];
+                let tile_index = self.nametable_byte(self.map_nametable_addr(tile_addr));
 
                 let attr_x = tx / 4;
                 let attr_y = ty / 4;
                 let attr_addr = nt_addr + 0x03C0 + (attr_y as u16) * 8 + (attr_x as u16);
-                let attr_byte = self.vram[self.map_nametable_addr(attr_addr)];
+                let attr_byte = self.nametable_byte(self.map_nametable_addr(attr_addr));
                 let quadrant = ((ty % 4) / 2) * 2 + ((tx % 4) / 2);
                 let shift = (quadrant * 2) as u8;
                 let palette_idx = (attr_byte >> shift) & 0x03;
@@ -1363,6 +1766,37 @@ mod tests {
         assert!(ppu.sprite_overflow.get());
     }
 
+    #[test]
+    fn test_sprite_overflow_bug_diagonal_scan() {
+        let mut ppu = Ppu::new(vec![0; 0x2000], Mirroring::Horizontal);
+        ppu.mask = 0x10; // Show sprites
+
+        // 8 sprites on scanline 100, sprite 8 off it
+        for i in 0..8 {
+            ppu.oam[i * 4] = 99;
+        }
+
+        // Sprite 9 is off the line but its tile number looks like a Y on it.
+        // The buggy scan reads byte 1 of sprite 9 and reports an overflow.
+        ppu.oam[9 * 4 + 1] = 99;
+        ppu.evaluate_sprites_for_scanline(100);
+        assert!(!ppu.sprite_overflow.get());
+        ppu.set_sprite_overflow_bug(true);
+        ppu.evaluate_sprites_for_scanline(100);
+        assert!(ppu.sprite_overflow.get());
+
+        // Sprite 9 is on the line, but the buggy scan reads its tile number
+        // and misses it
+        ppu.sprite_overflow.set(false);
+        ppu.oam[9 * 4] = 99;
+        ppu.oam[9 * 4 + 1] = 0;
+        ppu.evaluate_sprites_for_scanline(100);
+        assert!(!ppu.sprite_overflow.get());
+        ppu.set_sprite_overflow_bug(false);
+        ppu.evaluate_sprites_for_scanline(100);
+        assert!(ppu.sprite_overflow.get());
+    }
+
     #[test]
     fn test_vblank_clears_sprite_flags() {
         let ppu = Ppu::new(vec![0; 0x2000], Mirroring::Horizontal);
@@ -1620,10 +2054,63 @@ mod tests {
         ppu.write_register(0, 0xAB); // PPUCTRL
         ppu.write_register(1, 0xCD); // PPUMASK
 
-        // Reading from write-only registers should return 0
-        // (Actually returns 0 from open bus, but our implementation returns 0)
-        assert_eq!(ppu.read_register(0), 0, "PPUCTRL is write-only");
-        assert_eq!(ppu.read_register(1), 0, "PPUMASK is write-only");
+        // Reading from write-only registers returns the last value on the bus
+        assert_eq!(ppu.read_register(0), 0xCD, "PPUCTRL is write-only");
+        assert_eq!(ppu.read_register(1), 0xCD, "PPUMASK is write-only");
+        assert_eq!(ppu.read_register(5), 0xCD, "PPUSCROLL is write-only");
+    }
+
+    #[test]
+    fn test_open_bus_fills_undriven_bits_and_decays() {
+        let mut ppu = Ppu::new(vec![0; 0x2000], Mirroring::Horizontal);
+        ppu.set_vblank(false);
+
+        // PPUSTATUS only drives bits 5-7
+        ppu.write_register(0, 0x1F);
+        assert_eq!(ppu.read_register(2), 0x1F);
+
+        // Palette reads only drive bits 0-5
+        ppu.write_register(6, 0x3F);
+        ppu.write_register(6, 0x00);
+        ppu.write_register(7, 0x0F);
+        ppu.write_register(6, 0x3F);
+        ppu.write_register(6, 0x00);
+        ppu.write_register(0, 0xC0);
+        assert_eq!(ppu.read_register(7), 0xCF);
+
+        // OAM attribute bytes have no bits 2-4
+        ppu.write_register(3, 0x02);
+        ppu.write_register(4, 0xFF);
+        ppu.write_register(3, 0x02);
+        assert_eq!(ppu.read_register(4), 0xE3);
+
+        // Bits fade after ~600ms of no refresh, tracked per bit
+        ppu.write_register(3, 0xFF);
+        for _ in 0..OPEN_BUS_DECAY_FRAMES - 1 {
+            ppu.set_vblank(true);
+            ppu.set_vblank(false);
+        }
+        assert_eq!(ppu.read_register(2), 0x1F); // Refreshes bits 5-7 only
+        ppu.set_vblank(true);
+        ppu.set_vblank(false);
+        assert_eq!(ppu.read_register(0), 0x00);
+    }
+
+    #[test]
+    fn test_ppudata_read_is_delayed_by_buffer() {
+        let mut ppu = Ppu::new(vec![0; 0x2000], Mirroring::Horizontal);
+
+        ppu.write_register(6, 0x20);
+        ppu.write_register(6, 0x00);
+        ppu.write_register(7, 0x11);
+        ppu.write_register(7, 0x22);
+
+        ppu.write_register(6, 0x20);
+        ppu.write_register(6, 0x00);
+        let _ = ppu.read_register(7); // Stale buffer contents
+        assert_eq!(ppu.read_register(7), 0x11);
+        assert_eq!(ppu.read_register(7), 0x22);
+        assert_eq!(ppu.vram_addr.get(), 0x2003);
     }
 
     #[test]
@@ -2458,4 +2945,166 @@ mod tests {
         // The real test is that games like Turbo Racing now work correctly
         // This test just verifies the setup works as expected
     }
+
+    #[test]
+    fn test_a12_fetch_edges_follow_pattern_tables() {
+        let mut ppu = Ppu::new(vec![0; 0x2000], Mirroring::Vertical);
+
+        // Both tables at $0000: A12 never rises
+        assert!(ppu.a12_fetch_edges(Some(10)).is_empty());
+
+        // Sprites at $1000: a rise in each sprite slot, low again for the prefetch
+        ppu.write_register(0, 0x08);
+        let edges = ppu.a12_fetch_edges(Some(10));
+        assert_eq!(edges.len(), 16);
+        assert_eq!(edges[0], (261, true));
+        assert_eq!(edges[1], (265, false));
+        assert_eq!(edges[15], (321, false));
+
+        // 8x16 sprites take the table from the tile index; empty slots fetch $FF
+        ppu.write_register(0, 0x20);
+        ppu.oam.fill(0xFF);
+        ppu.oam[0] = 9; // Covers line 10
+        ppu.oam[1] = 0x02; // Even tile: $0000
+        let edges = ppu.a12_fetch_edges(Some(10));
+        assert_eq!(edges[0], (269, true));
+        assert_eq!(edges.len(), 14);
+    }
+
+    #[test]
+    fn test_scroll_writes_update_temp_addr() {
+        // The register walkthrough from the NESdev wiki's PPU scrolling page
+        let mut ppu = Ppu::new(vec![0; 0x2000], Mirroring::Vertical);
+        ppu.write_register(0, 0x00);
+        ppu.write_register(5, 0x7D);
+        assert_eq!(ppu.temp_addr, 0x000F);
+        assert_eq!(ppu.fine_x, 5);
+        ppu.write_register(5, 0x5E);
+        assert_eq!(ppu.temp_addr, 0x616F);
+
+        ppu.write_register(6, 0x3D);
+        assert_eq!(ppu.temp_addr, 0x3D6F);
+        ppu.write_register(6, 0xF0);
+        assert_eq!(ppu.temp_addr, 0x3DF0);
+        assert_eq!(ppu.vram_addr.get(), 0x3DF0);
+
+        // PPUCTRL's nametable bits land in t
+        ppu.write_register(0, 0x03);
+        assert_eq!(ppu.temp_addr, 0x3DF0 | 0x0C00);
+    }
+
+    #[test]
+    fn test_end_scanline_scroll_wraps_rows() {
+        let mut ppu = Ppu::new(vec![0; 0x2000], Mirroring::Vertical);
+        ppu.temp_addr = 0x0405; // Nametable 1, coarse X 5
+
+        // Fine Y carries into coarse Y, horizontal bits come from t
+        ppu.vram_addr.set(0x7000 | (3 << 5) | 0x1F);
+        ppu.end_scanline_scroll();
+        assert_eq!(ppu.vram_addr.get(), (4 << 5) | 0x0405);
+
+        // Row 29 wraps to the next nametable vertically
+        ppu.vram_addr.set(0x7000 | (29 << 5));
+        ppu.end_scanline_scroll();
+        assert_eq!(ppu.vram_addr.get(), 0x0800 | 0x0405);
+
+        // Row 31 (attribute data) wraps without switching nametables
+        ppu.vram_addr.set(0x7000 | (31 << 5));
+        ppu.end_scanline_scroll();
+        assert_eq!(ppu.vram_addr.get(), 0x0405);
+
+        ppu.temp_addr = 0x1234;
+        ppu.reload_scroll();
+        assert_eq!(ppu.vram_addr.get(), 0x1234);
+    }
+
+    /// PPU with tile row 0 of nametable 0 in color 1 and the rest backdrop,
+    /// background enabled
+    fn split_screen_ppu() -> Ppu {
+        let mut ppu = Ppu::new(vec![0; 0x2000], Mirroring::Vertical);
+        ppu.chr_is_ram = true;
+        for i in 0..8 {
+            ppu.chr[0x10 + i] = 0xFF;
+        }
+        for x in 0..32 {
+            ppu.vram[ppu.map_nametable_addr(0x2000 + x)] = 0x01;
+        }
+        ppu.palette[0] = 0x0F;
+        ppu.palette[1] = 0x16;
+        ppu.write_register(1, 0x0A);
+        ppu
+    }
+
+    /// Render line `y` and move on to the next one, as the system does
+    fn draw_line(ppu: &Ppu, y: u32, frame: &mut Frame) {
+        ppu.render_scanline(y, frame);
+        match ppu.accuracy() {
+            PpuAccuracy::Frame => ppu.next_line_scroll(),
+            PpuAccuracy::Scanline => ppu.end_scanline_scroll(),
+        }
+    }
+
+    #[test]
+    fn test_scanline_accuracy_defers_vertical_scroll() {
+        let mut ppu = split_screen_ppu();
+        let mut frame = Frame::new(256, 240);
+
+        for accuracy in [PpuAccuracy::Frame, PpuAccuracy::Scanline] {
+            ppu.set_accuracy(accuracy);
+            ppu.write_register(5, 0);
+            ppu.write_register(5, 0);
+            ppu.reload_scroll();
+            ppu.capture_line_scroll();
+            draw_line(&ppu, 0, &mut frame);
+
+            // Mid-frame Y scroll write, as a status bar split does; the
+            // hardware only applies it from the next frame
+            ppu.write_register(5, 0);
+            ppu.write_register(5, 100);
+            ppu.render_scanline(1, &mut frame);
+
+            assert_eq!(frame.pixels[16], nes_palette_rgb(0x16));
+            assert_eq!(
+                frame.pixels[256 + 16],
+                nes_palette_rgb(0x16),
+                "{:?}",
+                accuracy
+            );
+        }
+    }
+
+    #[test]
+    fn test_frame_accuracy_mid_frame_ppuaddr_split() {
+        let mut ppu = split_screen_ppu();
+        let mut frame = Frame::new(256, 240);
+        ppu.write_register(5, 0);
+        ppu.write_register(5, 80);
+        ppu.reload_scroll();
+        ppu.capture_line_scroll();
+        for y in 0..100 {
+            draw_line(&ppu, y, &mut frame);
+        }
+        assert_eq!(frame.pixels[99 * 256 + 16], nes_palette_rgb(0x0F));
+
+        // `$2006` write during line 100 (coarse Y 0, fine Y 0): line 100
+        // keeps its scroll and line 101 starts at the top of nametable 0
+        ppu.write_register(6, 0x00);
+        ppu.write_register(6, 0x00);
+        draw_line(&ppu, 100, &mut frame);
+        for y in 101..110 {
+            draw_line(&ppu, y, &mut frame);
+        }
+        assert_eq!(frame.pixels[100 * 256 + 16], nes_palette_rgb(0x0F));
+        assert_eq!(frame.pixels[101 * 256 + 16], nes_palette_rgb(0x16));
+        assert_eq!(frame.pixels[108 * 256 + 16], nes_palette_rgb(0x16));
+        assert_eq!(frame.pixels[109 * 256 + 16], nes_palette_rgb(0x0F));
+
+        // The split does not carry over: the next frame starts from `t` again
+        ppu.write_register(5, 0);
+        ppu.write_register(5, 0);
+        ppu.reload_scroll();
+        ppu.capture_line_scroll();
+        ppu.render_scanline(0, &mut frame);
+        assert_eq!(frame.pixels[16], nes_palette_rgb(0x16));
+    }
 }
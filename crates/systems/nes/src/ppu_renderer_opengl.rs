@@ -984,7 +984,7 @@ impl NesPpuRenderer for OpenGLNesPpuRenderer {
                         let nt_addr =
                             0x2000u16 + (nt as u16) * 0x0400 + (ty as u16) * 32 + (tx as u16);
                         let mapped_addr = Self::map_nametable_addr(nt_addr, ppu.get_mirroring());
-                        let tile_index = ppu.vram[mapped_addr];
+                        let tile_index = ppu.nametable_byte(mapped_addr);
                         let dst_x = (nt % 2) * 32 + tx;
                         let dst_y = (nt / 2) * 30 + ty;
                         nametable_data[dst_y * 64 + dst_x] = tile_index;
@@ -1017,7 +1017,7 @@ impl NesPpuRenderer for OpenGLNesPpuRenderer {
                             + (attr_y as u16) * 8
                             + (attr_x as u16);
                         let mapped_addr = Self::map_nametable_addr(attr_addr, ppu.get_mirroring());
-                        let attr_byte = ppu.vram[mapped_addr];
+                        let attr_byte = ppu.nametable_byte(mapped_addr);
                         let dst_x = (nt % 2) * 8 + attr_x;
                         let dst_y = (nt / 2) * 8 + attr_y;
                         attribute_data[dst_y * 32 + dst_x] = attr_byte;
@@ -0,0 +1,121 @@
+//! Zapper light gun (controller port 2)
+//!
+//! The Zapper has no shift register; every read of $4017 returns its two
+//! sensors directly:
+//!
+//! - Bit 3: light sense, 0 while the photodiode sees a bright spot
+//! - Bit 4: trigger, 1 while the trigger is pulled
+//!
+//! The photodiode only sees light as the CRT beam draws the spot it is aimed
+//! at, and keeps reporting it for a few scanlines afterwards. Games such as
+//! Duck Hunt draw a frame with white boxes over the targets and poll $4017
+//! while it is displayed. Here the spot is checked as each scanline is
+//! rendered, using the pixels around the aim point.
+//!
+//! Reference: NESdev wiki - Zapper
+
+use emu_core::types::Frame;
+
+/// Scanlines the photodiode keeps reporting light after the beam passed
+const LIGHT_SCANLINES: u32 = 20;
+/// Pixels left and right of the aim point the photodiode sees
+const SENSE_RADIUS: i32 = 2;
+/// Luminance (0-255) a pixel needs to count as light
+const LIGHT_THRESHOLD: u32 = 0xC0;
+
+/// Zapper plugged into controller port 2
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Zapper {
+    /// Aim point in frame pixels; outside 0-255 x 0-239 is off screen
+    pub x: i32,
+    pub y: i32,
+    pub trigger: bool,
+    /// Scanlines left during which the photodiode reports light
+    light_scanlines: u32,
+}
+
+impl Zapper {
+    /// Value read from $4017
+    pub fn read(&self) -> u8 {
+        let light = if self.light_scanlines > 0 { 0x00 } else { 0x08 };
+        let trigger = if self.trigger { 0x10 } else { 0x00 };
+        light | trigger
+    }
+
+    /// Update the photodiode after scanline `line` of `frame` was rendered
+    pub fn scanline_rendered(&mut self, frame: &Frame, line: u32) {
+        self.light_scanlines = self.light_scanlines.saturating_sub(1);
+        if line as i32 == self.y && self.sees_light(frame) {
+            self.light_scanlines = LIGHT_SCANLINES;
+        }
+    }
+
+    /// The beam is off screen during VBlank
+    pub fn end_frame(&mut self) {
+        self.light_scanlines = 0;
+    }
+
+    fn sees_light(&self, frame: &Frame) -> bool {
+        if self.y < 0 || self.y >= frame.height as i32 {
+            return false;
+        }
+        let row = self.y as usize * frame.width as usize;
+        (self.x - SENSE_RADIUS..=self.x + SENSE_RADIUS)
+            .filter(|&x| x >= 0 && x < frame.width as i32)
+            .any(|x| luminance(frame.pixels[row + x as usize]) >= LIGHT_THRESHOLD)
+    }
+}
+
+/// Approximate luminance of an ARGB pixel
+fn luminance(pixel: u32) -> u32 {
+    let r = (pixel >> 16) & 0xFF;
+    let g = (pixel >> 8) & 0xFF;
+    let b = pixel & 0xFF;
+    (r * 299 + g * 587 + b * 114) / 1000
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_light_follows_the_beam() {
+        let mut frame = Frame::new(256, 240);
+        // White box at x 100-109 on line 50, sky blue elsewhere
+        frame.pixels.fill(0xFF4C9CFF);
+        for x in 100..110 {
+            frame.pixels[50 * 256 + x] = 0xFFFFFFFF;
+        }
+
+        let mut zapper = Zapper {
+            x: 104,
+            y: 50,
+            trigger: true,
+            ..Zapper::default()
+        };
+        assert_eq!(zapper.read(), 0x18);
+
+        for line in 0..50 {
+            zapper.scanline_rendered(&frame, line);
+        }
+        assert_eq!(zapper.read() & 0x08, 0x08);
+        zapper.scanline_rendered(&frame, 50);
+        assert_eq!(zapper.read(), 0x10);
+
+        // The light fades some scanlines after the beam passed
+        for line in 51..51 + LIGHT_SCANLINES - 1 {
+            zapper.scanline_rendered(&frame, line);
+        }
+        assert_eq!(zapper.read() & 0x08, 0x00);
+        zapper.scanline_rendered(&frame, 51 + LIGHT_SCANLINES);
+        assert_eq!(zapper.read() & 0x08, 0x08);
+
+        // Aimed at the darker sky, or off screen, no light is seen
+        zapper.x = 200;
+        zapper.scanline_rendered(&frame, 50);
+        assert_eq!(zapper.read() & 0x08, 0x08);
+        zapper.x = -50;
+        zapper.scanline_rendered(&frame, 50);
+        assert_eq!(zapper.read() & 0x08, 0x08);
+    }
+}
@@ -3,6 +3,8 @@
 //! This provides boot functionality for the PC system.
 //! The BIOS sets up the system and attempts to boot from disk.
 
+use crate::disk::FloppyFormat;
+use crate::rtc::DateTime;
 use emu_core::cpu_8086::CpuModel;
 
 pub use boot_priority::BootPriority;
@@ -12,6 +14,72 @@ pub use boot_priority::BootPriority;
 #[allow(dead_code)]
 pub const DISK_PARAMETER_TABLE_OFFSET: u16 = 0x0250;
 
+/// Offset of the shared hardware IRQ handler in the BIOS ROM (F000:0380)
+///
+/// INT 08h and INT 09h point here. The emulator services the in-service IRQ
+/// in Rust when execution reaches this address (directly or because a DOS
+/// program chained to the original vector); the ROM code behind it only sends
+/// a non-specific EOI and returns.
+pub const IRQ_HANDLER_OFFSET: u16 = 0x0380;
+
+/// Offset of the timer chain stub (INT 1Ch; IRET) used after the BIOS timer tick
+pub const TIMER_CHAIN_OFFSET: u16 = 0x0388;
+
+/// Offset of the INT 2Fh multiplex entry point in the BIOS ROM (F000:0390)
+///
+/// The INT 2Fh vector points here until DOS or a TSR hooks it. The emulator
+/// answers the multiplex IDs of its built-in drivers in Rust when execution
+/// reaches this address, so a chain that ends in the BIOS still finds them.
+pub const MULTIPLEX_ENTRY_OFFSET: u16 = 0x0390;
+
+/// Offset of the INT 33h mouse driver entry point in the BIOS ROM (F000:0394)
+///
+/// Starts with a NOP so programs that treat a null vector or a bare IRET as
+/// "no mouse driver" see one installed.
+pub const MOUSE_ENTRY_OFFSET: u16 = 0x0394;
+
+/// Offset of the XMS driver entry point returned by INT 2Fh AX=4310h (F000:0398)
+///
+/// Laid out like HIMEM.SYS: a short jump over three NOPs, so programs that
+/// hook the driver can patch it, followed by the RETF where the emulator
+/// services the call.
+pub const XMS_ENTRY_OFFSET: u16 = 0x0398;
+
+/// Offset of the RETF in the XMS entry point, where XMS calls are serviced
+pub const XMS_SERVICE_OFFSET: u16 = XMS_ENTRY_OFFSET + 5;
+
+/// Offset of the built-in DOS prompt loop in the BIOS ROM (F000:03A0)
+///
+/// `NOP; MOV AH,0Ah; INT 21h; NOP; JMP SHORT` back to the start. The
+/// emulator shows the prompt at the first NOP and runs the typed command at
+/// the second (`DOS_COMMAND_OFFSET`).
+pub const DOS_SHELL_OFFSET: u16 = 0x03A0;
+
+/// Offset of the NOP where the built-in DOS runs the typed command
+pub const DOS_COMMAND_OFFSET: u16 = DOS_SHELL_OFFSET + 5;
+
+/// Offset of the INT 21h entry point installed by the built-in DOS (F000:03A8)
+///
+/// Programs that hook INT 21h chain here; the emulator services the call in
+/// Rust when execution reaches this address.
+pub const DOS_ENTRY_OFFSET: u16 = 0x03A8;
+
+/// Offset of the INT 20h handler installed by the built-in DOS (F000:03AC):
+/// `MOV AH,00h; INT 21h`
+pub const DOS_TERMINATE_OFFSET: u16 = 0x03AC;
+
+/// Segment of the EMS device driver header in the BIOS ROM (F040:0000)
+///
+/// Programs detect EMS by finding the device name "EMMXXXX0" at offset 0Ah
+/// of the segment the INT 67h vector points to.
+pub const EMS_DEVICE_SEGMENT: u16 = 0xF040;
+
+/// Offset of the INT 67h entry point in `EMS_DEVICE_SEGMENT`
+///
+/// The emulator services EMS calls in Rust when execution reaches this
+/// address; the ROM code behind it is only an IRET.
+pub const EMS_ENTRY_OFFSET: u16 = 0x0012;
+
 mod boot_priority {
     /// Boot priority options
     #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
@@ -25,16 +93,93 @@ mod boot_priority {
         FloppyOnly,
         /// Boot from hard drive only
         HardDriveOnly,
+        /// Boot from CD-ROM first, then floppy, then hard drive
+        CdRomFirst,
     }
 }
 
+/// Low-memory word the BIOS loads into DX before jumping to the boot sector
+///
+/// Holds the drive number the boot sector was read from (DL) and zero (DH).
+/// It lies in the inter-application communication area, which nothing uses
+/// before the operating system is running.
+pub const BOOT_DRIVE_ADDR: usize = 0x04FE;
+
+/// Low-memory far pointer (offset, then segment) the BIOS jumps to once boot
+/// code is loaded: 0000:7C00 for a boot sector, or the load segment of a
+/// no-emulation CD boot image
+pub const BOOT_VECTOR_ADDR: usize = 0x04FA;
+
+/// Low-memory byte set to 1 once boot code is loaded; the BIOS calls INT 18h
+/// instead of jumping when it is 0
+pub const BOOT_READY_ADDR: usize = 0x04F9;
+
+/// BIOS data area word (0040:0072) holding the reset flag POST checks
+pub const RESET_FLAG_ADDR: u32 = 0x0472;
+
+/// Reset flag value for a warm boot (Ctrl+Alt+Del), which skips the memory
+/// test and the POST countdown
+pub const WARM_BOOT_FLAG: u16 = 0x1234;
+
+/// Physical address of the reset vector (FFFF:0000, or F000:FFF0)
+pub const RESET_VECTOR_ADDR: u32 = 0xFFFF0;
+
+/// Drive offered by the POST boot menu
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BootDevice {
+    FloppyA,
+    FloppyB,
+    HardDrive,
+    CdRom,
+}
+
+impl BootDevice {
+    /// Boot menu entries, in display order
+    pub const ALL: [BootDevice; 4] = [
+        BootDevice::FloppyA,
+        BootDevice::FloppyB,
+        BootDevice::HardDrive,
+        BootDevice::CdRom,
+    ];
+
+    /// BIOS drive number (passed to the boot sector in DL)
+    pub fn drive(self) -> u8 {
+        match self {
+            BootDevice::FloppyA => 0x00,
+            BootDevice::FloppyB => 0x01,
+            BootDevice::HardDrive => 0x80,
+            BootDevice::CdRom => 0xE0,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            BootDevice::FloppyA => "Floppy A:",
+            BootDevice::FloppyB => "Floppy B:",
+            BootDevice::HardDrive => "Hard Disk C:",
+            BootDevice::CdRom => "CD-ROM:",
+        }
+    }
+}
+
+/// Whether a drive can be booted, as listed in the boot menu
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BootDeviceStatus {
+    NotPresent,
+    /// Disk mounted, but sector 0 lacks the 0xAA55 boot signature (or the
+    /// CD has no usable El Torito boot entry)
+    NoSignature,
+    Bootable,
+}
+
 /// Generate a minimal BIOS ROM with interrupt handlers
 ///
 /// This BIOS:
 /// 1. Initializes segment registers and stack  
 /// 2. Sets up interrupt vectors in low memory
 /// 3. Provides basic INT 10h (video), INT 13h (disk), INT 16h (keyboard) handlers
-/// 4. Jumps to boot sector at 0x0000:0x7C00 (loaded by emulator)
+/// 4. Jumps to the boot code loaded by the emulator (normally the boot sector
+///    at 0x0000:0x7C00), or calls INT 18h if there is none
 ///
 /// NOTE: INT 21h (DOS API) is NOT provided by BIOS - DOS installs it during boot
 ///
@@ -185,6 +330,68 @@ pub fn generate_minimal_bios(cpu_model: CpuModel) -> Vec<u8> {
     ];
     bios[int16h_offset..int16h_offset + int16h_handler.len()].copy_from_slice(&int16h_handler);
 
+    // Hardware IRQ handler at offset 0x380 (see IRQ_HANDLER_OFFSET)
+    let irq_offset = IRQ_HANDLER_OFFSET as usize;
+    let irq_handler: Vec<u8> = vec![
+        0x50, // PUSH AX
+        0xB0, 0x20, // MOV AL, 0x20 (non-specific EOI)
+        0xE6, 0x20, // OUT 0x20, AL
+        0x58, // POP AX
+        0xCF, // IRET
+    ];
+    bios[irq_offset..irq_offset + irq_handler.len()].copy_from_slice(&irq_handler);
+
+    // Timer chain at offset 0x388: call the user timer tick handler and return
+    let chain_offset = TIMER_CHAIN_OFFSET as usize;
+    let timer_chain: Vec<u8> = vec![
+        0xCD, 0x1C, // INT 1Ch
+        0xCF, // IRET
+    ];
+    bios[chain_offset..chain_offset + timer_chain.len()].copy_from_slice(&timer_chain);
+
+    // Resident driver entry points (see MULTIPLEX_ENTRY_OFFSET and friends)
+    let multiplex_offset = MULTIPLEX_ENTRY_OFFSET as usize;
+    bios[multiplex_offset] = 0xCF; // IRET
+    let mouse_offset = MOUSE_ENTRY_OFFSET as usize;
+    bios[mouse_offset..mouse_offset + 2].copy_from_slice(&[
+        0x90, // NOP
+        0xCF, // IRET
+    ]);
+    let xms_offset = XMS_ENTRY_OFFSET as usize;
+    bios[xms_offset..xms_offset + 6].copy_from_slice(&[
+        0xEB, 0x03, // JMP SHORT XMS_SERVICE_OFFSET
+        0x90, 0x90, 0x90, // NOP (patch space for hooks)
+        0xCB, // RETF
+    ]);
+
+    // Built-in DOS prompt loop and handlers (see DOS_SHELL_OFFSET)
+    let shell_offset = DOS_SHELL_OFFSET as usize;
+    bios[shell_offset..shell_offset + 8].copy_from_slice(&[
+        0x90, // NOP (prompt)
+        0xB4, 0x0A, // MOV AH, 0Ah
+        0xCD, 0x21, // INT 21h (buffered input)
+        0x90, // NOP (run the command)
+        0xEB, 0xF8, // JMP SHORT DOS_SHELL_OFFSET
+    ]);
+    bios[DOS_ENTRY_OFFSET as usize] = 0xCF; // IRET
+    let terminate_offset = DOS_TERMINATE_OFFSET as usize;
+    bios[terminate_offset..terminate_offset + 4].copy_from_slice(&[
+        0xB4, 0x00, // MOV AH, 00h
+        0xCD, 0x21, // INT 21h (terminate)
+    ]);
+
+    // EMS device driver header at F040:0000 (see EMS_DEVICE_SEGMENT)
+    let ems_offset = ((EMS_DEVICE_SEGMENT - 0xF000) as usize) << 4;
+    let ems_header: Vec<u8> = vec![
+        0xFF, 0xFF, 0xFF, 0xFF, // Next driver (none)
+        0x00, 0xC0, // Attributes: character device, IOCTL supported
+        0x00, 0x00, // Strategy routine (unused)
+        0x00, 0x00, // Interrupt routine (unused)
+        b'E', b'M', b'M', b'X', b'X', b'X', b'X', b'0', // Device name
+        0xCF, // INT 67h entry (EMS_ENTRY_OFFSET): IRET
+    ];
+    bios[ems_offset..ems_offset + ems_header.len()].copy_from_slice(&ems_header);
+
     // NOTE: INT 21h (DOS Services) is NOT provided by BIOS
     // DOS installs its own INT 21h handler when it loads (IO.SYS/MSDOS.SYS)
     // The BIOS must not provide an INT 21h handler or it will interfere with DOS initialization
@@ -209,13 +416,13 @@ pub fn generate_minimal_bios(cpu_model: CpuModel) -> Vec<u8> {
         0xA3, 0x14, 0x00, // MOV [0x0014], AX (INT 05h vector = 0x0014)
         0xB8, 0x00, 0xF0, // MOV AX, 0xF000
         0xA3, 0x16, 0x00, // MOV [0x0016], AX
-        // INT 0x08 (Timer Tick) - stub
-        0xB8, 0x40, 0x00, // MOV AX, 0x0040
+        // INT 0x08 (Timer Tick, IRQ 0) at 0x0380
+        0xB8, 0x80, 0x03, // MOV AX, 0x0380 (shared IRQ handler)
         0xA3, 0x20, 0x00, // MOV [0x0020], AX (INT 08h vector = 0x0020)
         0xB8, 0x00, 0xF0, // MOV AX, 0xF000
         0xA3, 0x22, 0x00, // MOV [0x0022], AX
-        // INT 0x09 (Keyboard Hardware) - stub
-        0xB8, 0x40, 0x00, // MOV AX, 0x0040
+        // INT 0x09 (Keyboard Hardware, IRQ 1) at 0x0380
+        0xB8, 0x80, 0x03, // MOV AX, 0x0380 (shared IRQ handler)
         0xA3, 0x24, 0x00, // MOV [0x0024], AX (INT 09h vector = 0x0024)
         0xB8, 0x00, 0xF0, // MOV AX, 0xF000
         0xA3, 0x26, 0x00, // MOV [0x0026], AX
@@ -281,22 +488,20 @@ pub fn generate_minimal_bios(cpu_model: CpuModel) -> Vec<u8> {
         0xA3, 0xA8, 0x00, // MOV [0x00A8], AX (INT 2Ah vector = 0x00A8)
         0xB8, 0x00, 0xF0, // MOV AX, 0xF000
         0xA3, 0xAA, 0x00, // MOV [0x00AA], AX
+        // INT 0x67 (EMS) at F040:0012 (see EMS_ENTRY_OFFSET)
+        0xB8, 0x12, 0x00, // MOV AX, 0x0012
+        0xA3, 0x9C, 0x01, // MOV [0x019C], AX (INT 67h vector = 0x019C)
+        0xB8, 0x40, 0xF0, // MOV AX, 0xF040
+        0xA3, 0x9E, 0x01, // MOV [0x019E], AX
         0xFB, // STI - enable interrupts
-        // Check if boot sector is loaded by checking signature at 0x7C00 + 510
-        // We'll check if byte at 0x7DFE is 0x55 and 0x7DFF is 0xAA
-        0xBE, 0xFE, 0x7D, // MOV SI, 0x7DFE (offset of boot signature)
-        0x8A, 0x04, // MOV AL, [SI]
-        0x3C, 0x55, // CMP AL, 0x55
-        0x75, 0x0C, // JNZ skip_boot (jump 12 bytes: 3+1+2+2+5)
-        0x8A, 0x44, 0x01, // MOV AL, [SI+1]
-        0x3C, 0xAA, // CMP AL, 0xAA
-        0x75, 0x09, // JNZ skip_boot (jump 9 bytes: 2+2+5)
-        // Boot signature valid - set DL to boot drive and jump to boot sector
-        0xB2, 0x00, // MOV DL, 0x00 (boot drive = floppy A:)
-        0xB6, 0x00, // MOV DH, 0x00 (clear DH as well for safety)
-        0xEA, 0x00, 0x7C, 0x00, 0x00, // JMP FAR 0x0000:0x7C00
-        // skip_boot: No valid boot sector - infinite loop (HLT)
-        // 0xF4,       // HLT
+        // Jump to the boot code if the emulator loaded any (see BOOT_READY_ADDR)
+        0x80, 0x3E, 0xF9, 0x04, 0x00, // CMP BYTE [0x04F9], 0
+        0x74, 0x08, // JZ skip_boot (jump 8 bytes: 4+4)
+        0x8B, 0x16, 0xFE, 0x04, // MOV DX, [0x04FE] (DL = boot drive, DH = 0)
+        0xFF, 0x2E, 0xFA, 0x04, // JMP FAR [0x04FA] (usually 0000:7C00)
+        // skip_boot: No valid boot sector - INT 18h starts the built-in DOS
+        // (if enabled), otherwise it returns and we loop forever
+        0xCD, 0x18, // INT 18h
         0xEB, 0xFE, // JMP -2 (infinite loop - simpler than HLT)
     ];
     bios[0..init_code.len()].copy_from_slice(&init_code);
@@ -362,71 +567,22 @@ pub fn write_hemu_logo_to_vram(vram: &mut [u8]) {
 ///
 /// # Arguments
 /// * `vram` - Video RAM buffer to write to
+/// * `text_offset` - Offset of the text buffer in `vram` (B8000h or B0000h)
 /// * `cpu_model` - CPU model to display
 /// * `memory_kb` - Memory size in KB to display
 /// * `cpu_speed_mhz` - CPU speed in MHz to display
+/// * `now` - Date and time read from the real-time clock
 pub fn write_post_screen_to_vram(
     vram: &mut [u8],
+    text_offset: usize,
     cpu_model: CpuModel,
     memory_kb: u32,
     cpu_speed_mhz: f64,
+    now: &DateTime,
 ) {
-    use std::time::{SystemTime, UNIX_EPOCH};
-
-    // Get current system time
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default();
-
-    let total_seconds = now.as_secs();
-    let seconds_in_day = total_seconds % 86400;
-
-    // Calculate time
-    let hours = (seconds_in_day / 3600) as u8;
-    let minutes = ((seconds_in_day % 3600) / 60) as u8;
-    let seconds = (seconds_in_day % 60) as u8;
-
-    // Calculate date
-    let days_since_epoch = total_seconds / 86400;
-    let mut year = 1970;
-    let mut remaining_days = days_since_epoch as u32;
-
-    loop {
-        let days_in_year = if (year % 4 == 0 && year % 100 != 0) || (year % 400 == 0) {
-            366
-        } else {
-            365
-        };
-
-        if remaining_days >= days_in_year {
-            remaining_days -= days_in_year;
-            year += 1;
-        } else {
-            break;
-        }
-    }
-
-    // Simple month calculation
-    let days_per_month = [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
-    let mut month = 1;
-    let mut day = remaining_days + 1;
-
-    for (m, &days) in days_per_month.iter().enumerate() {
-        if day > days {
-            day -= days;
-            month = m + 2;
-        } else {
-            month = m + 1;
-            break;
-        }
-    }
-
     // Format date and time strings
-    let date_str = format!("{:02}/{:02}/{:04}", month, day, year);
-    let time_str = format!("{:02}:{:02}:{:02}", hours, minutes, seconds);
-
-    // Video RAM offset for text mode (0xB8000 - 0xA0000 = 0x18000)
-    let text_offset = 0x18000;
+    let date_str = format!("{:02}/{:02}/{:04}", now.month, now.day, now.year);
+    let time_str = format!("{:02}:{:02}:{:02}", now.hour, now.minute, now.second);
 
     // Clear screen first (fill with spaces and default attribute)
     for i in (0..4000).step_by(2) {
@@ -507,6 +663,7 @@ pub fn write_post_screen_to_vram(
     write_line(20, 2, "Press F3 to mount disks", help_attr);
     write_line(21, 2, "Press ESC to abort boot countdown", help_attr);
     write_line(22, 2, "Press F8 to save virtual machine", help_attr);
+    write_line(23, 2, "Press F12 for boot menu", help_attr);
 
     // Bottom line (white on blue)
     write_line(24, 0, &" ".repeat(80), header_attr);
@@ -522,10 +679,9 @@ pub fn write_post_screen_to_vram(
 ///
 /// # Arguments
 /// * `vram` - Video RAM buffer to write to
+/// * `text_offset` - Offset of the text buffer in `vram`
 /// * `seconds_remaining` - Number of seconds remaining before boot
-pub fn update_post_screen_countdown(vram: &mut [u8], seconds_remaining: u32) {
-    let text_offset = 0x18000;
-
+pub fn update_post_screen_countdown(vram: &mut [u8], text_offset: usize, seconds_remaining: u32) {
     let mut write_line = |row: usize, col: usize, text: &str, attr: u8| {
         let screen_offset = text_offset + (row * 80 + col) * 2;
         for (i, ch) in text.chars().enumerate() {
@@ -554,17 +710,105 @@ pub fn update_post_screen_countdown(vram: &mut [u8], seconds_remaining: u32) {
     }
 }
 
+/// Draw the boot device menu over the POST screen
+///
+/// # Arguments
+/// * `vram` - Video RAM buffer to write to
+/// * `text_offset` - Offset of the text buffer in `vram`
+/// * `devices` - Menu entries with their current status
+/// * `selected` - Index of the highlighted entry
+pub fn write_boot_menu(
+    vram: &mut [u8],
+    text_offset: usize,
+    devices: &[(BootDevice, BootDeviceStatus)],
+    selected: usize,
+) {
+    const LEFT: usize = 18;
+    const WIDTH: usize = 44;
+    const TOP: usize = 8;
+
+    let mut write_bytes = |row: usize, col: usize, text: &[u8], attr: u8| {
+        let screen_offset = text_offset + (row * 80 + col) * 2;
+        for (i, &ch) in text.iter().enumerate() {
+            let offset = screen_offset + i * 2;
+            if offset + 1 < vram.len() {
+                vram[offset] = ch;
+                vram[offset + 1] = attr;
+            }
+        }
+    };
+
+    // Double-line frame (code page 437 box drawing), white on blue
+    let frame_attr = 0x1F;
+    let rows = devices.len() + 4;
+    let mut top = vec![0xCD; WIDTH];
+    top[0] = 0xC9;
+    top[WIDTH - 1] = 0xBB;
+    let title = b" Boot Menu ";
+    let title_col = (WIDTH - title.len()) / 2;
+    top[title_col..title_col + title.len()].copy_from_slice(title);
+    write_bytes(TOP, LEFT, &top, frame_attr);
+    for row in TOP + 1..TOP + rows {
+        let mut line = vec![b' '; WIDTH];
+        line[0] = 0xBA;
+        line[WIDTH - 1] = 0xBA;
+        write_bytes(row, LEFT, &line, frame_attr);
+    }
+    let mut bottom = vec![0xCD; WIDTH];
+    bottom[0] = 0xC8;
+    bottom[WIDTH - 1] = 0xBC;
+    write_bytes(TOP + rows, LEFT, &bottom, frame_attr);
+
+    for (i, &(device, status)) in devices.iter().enumerate() {
+        let status_text = match status {
+            BootDeviceStatus::NotPresent => "Not present",
+            BootDeviceStatus::NoSignature => "No boot signature",
+            BootDeviceStatus::Bootable => "Bootable",
+        };
+        let attr = match (i == selected, status) {
+            (true, _) => 0x70,                           // Black on light gray
+            (false, BootDeviceStatus::Bootable) => 0x1F, // White on blue
+            (false, _) => 0x17,                          // Light gray on blue
+        };
+        let entry = format!(
+            " {:<14}{:<WIDTH$}",
+            device.name(),
+            status_text,
+            WIDTH = WIDTH - 20
+        );
+        write_bytes(TOP + 2 + i, LEFT + 2, entry.as_bytes(), attr);
+    }
+
+    write_bytes(
+        TOP + rows - 1,
+        LEFT + 2,
+        b"Up/Down: select  Enter: boot  ESC: cancel",
+        0x1B, // Bright cyan on blue
+    );
+}
+
+/// Floppy drive type as shown on the POST screen
+fn floppy_type_name(format: FloppyFormat) -> &'static str {
+    match format {
+        FloppyFormat::Floppy360K => "360KB 5.25\"",
+        FloppyFormat::Floppy720K => "720KB 3.5\"",
+        FloppyFormat::Floppy1_2M => "1.2MB 5.25\"",
+        FloppyFormat::Floppy1_44M => "1.44MB 3.5\"",
+    }
+}
+
 /// Update the disk drive status on the POST screen
+///
+/// Floppy types and the hard disk geometry (cylinders, sectors per track,
+/// heads) come from the CMOS setup.
 pub fn update_post_screen_mounts(
     vram: &mut [u8],
-    floppy_a: bool,
-    floppy_b: bool,
-    hard_drive: bool,
+    text_offset: usize,
+    floppy_types: [Option<FloppyFormat>; 2],
+    hard_disk: Option<(u16, u8, u8)>,
     cdrom: bool,
     boot_priority: BootPriority,
 ) {
-    let text_offset = 0x18000;
-
     let mut write_line = |row: usize, col: usize, text: &str, attr: u8| {
         let screen_offset = text_offset + (row * 80 + col) * 2;
         for (i, ch) in text.chars().enumerate() {
@@ -580,25 +824,34 @@ pub fn update_post_screen_mounts(
     let present_attr = 0x0A; // Bright green
     let absent_attr = 0x08; // Dark gray
 
-    // Floppy A
-    if floppy_a {
-        write_line(10, 4, "Floppy A: Present          ", present_attr);
-    } else {
-        write_line(10, 4, "Floppy A: Not present      ", absent_attr);
-    }
-
-    // Floppy B
-    if floppy_b {
-        write_line(11, 4, "Floppy B: Present          ", present_attr);
-    } else {
-        write_line(11, 4, "Floppy B: Not present      ", absent_attr);
+    // Floppy A and B
+    for (row, (letter, format)) in (10..).zip(['A', 'B'].into_iter().zip(floppy_types)) {
+        match format {
+            Some(format) => {
+                let text = format!("Floppy {}: Present, {}", letter, floppy_type_name(format));
+                write_line(row, 4, &format!("{:<36}", text), present_attr);
+            }
+            None => {
+                let text = format!("Floppy {}: Not present", letter);
+                write_line(row, 4, &format!("{:<36}", text), absent_attr);
+            }
+        }
     }
 
     // Hard Drive C
-    if hard_drive {
-        write_line(12, 4, "Hard Disk C: Present       ", present_attr);
+    if let Some((cylinders, sectors, heads)) = hard_disk {
+        let text = format!(
+            "Hard Disk C: Present, CHS {}/{}/{}",
+            cylinders, heads, sectors
+        );
+        write_line(12, 4, &format!("{:<36}", text), present_attr);
     } else {
-        write_line(12, 4, "Hard Disk C: Not present   ", absent_attr);
+        write_line(
+            12,
+            4,
+            &format!("{:<36}", "Hard Disk C: Not present"),
+            absent_attr,
+        );
     }
 
     // CD-ROM Drive
@@ -614,12 +867,13 @@ pub fn update_post_screen_mounts(
         BootPriority::HardDriveFirst => "Hard Drive First",
         BootPriority::FloppyOnly => "Floppy Only    ",
         BootPriority::HardDriveOnly => "Hard Drive Only",
+        BootPriority::CdRomFirst => "CD-ROM First   ",
     };
     write_line(15, 18, boot_text, 0x0E);
 
     // Update bottom message based on disk availability
     let header_attr = 0x1F;
-    if floppy_a || floppy_b || hard_drive || cdrom {
+    if floppy_types.iter().any(Option::is_some) || hard_disk.is_some() || cdrom {
         write_line(
             24,
             2,
@@ -661,6 +915,53 @@ mod tests {
         assert_eq!(bios[0], 0xFA); // CLI instruction
     }
 
+    #[test]
+    fn test_bios_irq_handler_not_overwritten() {
+        let bios = generate_minimal_bios(CpuModel::Intel8086);
+
+        // The init code is copied last and must not clobber the IRQ handler
+        let irq = IRQ_HANDLER_OFFSET as usize;
+        assert_eq!(
+            &bios[irq..irq + 7],
+            &[0x50, 0xB0, 0x20, 0xE6, 0x20, 0x58, 0xCF]
+        );
+        let chain = TIMER_CHAIN_OFFSET as usize;
+        assert_eq!(&bios[chain..chain + 3], &[0xCD, 0x1C, 0xCF]);
+    }
+
+    #[test]
+    fn test_bios_builtin_dos_stubs() {
+        let bios = generate_minimal_bios(CpuModel::Intel8086);
+
+        let shell = DOS_SHELL_OFFSET as usize;
+        assert_eq!(bios[shell], 0x90);
+        assert_eq!(&bios[shell + 1..shell + 5], &[0xB4, 0x0A, 0xCD, 0x21]);
+        assert_eq!(bios[DOS_COMMAND_OFFSET as usize], 0x90);
+        assert_eq!(bios[DOS_ENTRY_OFFSET as usize], 0xCF);
+        let terminate = DOS_TERMINATE_OFFSET as usize;
+        assert_eq!(&bios[terminate..terminate + 4], &[0xB4, 0x00, 0xCD, 0x21]);
+    }
+
+    #[test]
+    fn test_bios_ems_device_header() {
+        let bios = generate_minimal_bios(CpuModel::Intel8086);
+
+        let header = ((EMS_DEVICE_SEGMENT - 0xF000) as usize) << 4;
+        assert_eq!(&bios[header + 0x0A..header + 0x12], b"EMMXXXX0");
+        assert_eq!(bios[header + EMS_ENTRY_OFFSET as usize], 0xCF); // IRET
+    }
+
+    #[test]
+    fn test_bios_driver_entry_points() {
+        let bios = generate_minimal_bios(CpuModel::Intel8086);
+
+        assert_eq!(bios[MULTIPLEX_ENTRY_OFFSET as usize], 0xCF); // IRET
+        assert_ne!(bios[MOUSE_ENTRY_OFFSET as usize], 0xCF); // Not a bare IRET
+        let xms = XMS_ENTRY_OFFSET as usize;
+        assert_eq!(&bios[xms..xms + 2], &[0xEB, 0x03]); // HIMEM-style JMP SHORT
+        assert_eq!(bios[XMS_SERVICE_OFFSET as usize], 0xCB); // RETF
+    }
+
     #[test]
     fn test_bios_date_signature() {
         let bios = generate_minimal_bios(CpuModel::Intel8086);
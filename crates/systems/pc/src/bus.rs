@@ -7,16 +7,42 @@
 //! - 0xC0000-0xFFFFF: ROM area (256KB)
 //! - 0xF0000-0xFFFFF: BIOS ROM (64KB)
 
-use crate::bios::BootPriority;
-use crate::disk::DiskController;
+use crate::bios::{
+    BootDevice, BootDeviceStatus, BootPriority, BOOT_DRIVE_ADDR, BOOT_READY_ADDR, BOOT_VECTOR_ADDR,
+};
+use crate::cassette::Cassette;
+use crate::disk::{DiskController, FloppyFormat};
+use crate::dma::DmaController;
+use crate::dos::{self, BuiltinDos};
+use crate::dos_fs::{DirEntry, DosFsError, FatVolume};
 use crate::dpmi::DpmiDriver;
+use crate::el_torito::{self, BootEntry, BootMedia, CD_SECTOR_SIZE};
+use crate::ems::EmsDriver;
+use crate::game_port::{GamePort, GAME_PORT};
+use crate::kbc::{KeyboardController, KEYBOARD_IRQ};
 use crate::keyboard::Keyboard;
 use crate::mouse::Mouse;
-use crate::pit::Pit;
+use crate::opl2::Opl2;
+use crate::parallel::{ParallelPort, LPT1_BASE_PORT, LPT1_IRQ};
+use crate::pic::{Pic8259, PIC_COMMAND_PORT, PIC_DATA_PORT};
+use crate::pit::{Pit, XT_CPU_MHZ};
+use crate::rtc::{CmosSetup, Rtc, RTC_DATA_PORT, RTC_INDEX_PORT};
+use crate::serial::{Uart, COM1_BASE_PORT, COM1_IRQ, COM2_BASE_PORT, COM2_IRQ};
+use crate::sound_blaster::{SoundBlaster, SB_DMA_CHANNEL, SB_IRQ};
+use crate::speaker::{PcSpeaker, SpeakerInput};
+use crate::vga::{
+    apply_attributes, ega_dac_color, planar_16_color_crtc, VgaAccess, VgaLayout, VgaMemory,
+    GRAPHICS_MODE_256, MODE_13H_CRTC, MODE_13H_GRAPHICS, MODE_13H_SEQUENCER,
+    PLANAR_16_COLOR_ATTRIBUTE, PLANAR_16_COLOR_GRAPHICS, PLANAR_16_COLOR_MODES,
+    PLANAR_16_COLOR_SEQUENCER,
+};
+use crate::video_adapter_ega_software::DEFAULT_EGA_PALETTE;
+use crate::video_adapter_vga_software::DEFAULT_VGA_PALETTE;
 use crate::xms::XmsDriver;
 use emu_core::cpu_8086::Memory8086;
 use emu_core::logging::{log, LogCategory, LogLevel};
 use std::cell::Cell;
+use std::collections::BTreeSet;
 
 /// Video adapter type for equipment configuration
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -33,6 +59,39 @@ pub enum VideoAdapterType {
     Vga,
 }
 
+/// BIOS video mode 13h: 320x200, 256 colors
+pub const VGA_MODE_13H: u8 = 0x13;
+
+/// BIOS video mode 07h: 80x25 monochrome text (MDA/Hercules)
+pub const MDA_TEXT_MODE: u8 = 0x07;
+
+/// Offset of the color text buffer (B8000h) in VRAM
+pub const COLOR_TEXT_OFFSET: usize = 0x18000;
+
+/// Offset of the monochrome text buffer (B0000h) in VRAM
+pub const MONO_TEXT_OFFSET: usize = 0x10000;
+
+/// Mode control register (3D8h, 3B8h on MDA) values the BIOS programs for
+/// modes 00h-07h
+const MODE_CONTROL_VALUES: [u8; 8] = [0x2C, 0x28, 0x2D, 0x29, 0x2A, 0x2E, 0x1E, 0x29];
+
+/// Default cursor shape (start and end scan line) of color text modes
+pub const COLOR_CURSOR_SHAPE: u16 = 0x0607;
+/// Default cursor shape of the monochrome text mode
+pub const MONO_CURSOR_SHAPE: u16 = 0x0B0C;
+
+/// Hercules mode control (3B8h) bit 1: graphics mode
+const HERCULES_GRAPHICS: u8 = 0x02;
+/// Hercules mode control (3B8h) bit 7: display the page at B8000h
+const HERCULES_PAGE_1: u8 = 0x80;
+/// Hercules configuration switch (3BFh) bit 0: allow graphics mode
+const HERCULES_ALLOW_GRAPHICS: u8 = 0x01;
+/// Hercules configuration switch (3BFh) bit 1: map the page at B8000h
+const HERCULES_ENABLE_PAGE_1: u8 = 0x02;
+
+/// Approximate CPU cycles per 60 Hz display refresh at 4.77 MHz
+const XT_CYCLES_PER_FRAME: u64 = 80000;
+
 /// PC memory bus
 pub struct PcBus {
     /// Main RAM (640KB)
@@ -41,9 +100,13 @@ pub struct PcBus {
     extended_ram: Vec<u8>,
     /// Video RAM (128KB)
     vram: Vec<u8>,
+    /// Planar memory behind A0000h-AFFFFh in the EGA/VGA graphics modes
+    vga: VgaMemory,
+    /// Current BIOS video mode (INT 10h AH=00h)
+    video_mode: u8,
     /// ROM area (256KB) - includes BIOS
     rom: Vec<u8>,
-    /// Loaded executable data (deprecated, kept for backward compatibility)
+    /// .COM or .EXE program run on the built-in DOS instead of a disk boot
     executable: Option<Vec<u8>>,
     /// Keyboard controller
     pub keyboard: Keyboard,
@@ -51,42 +114,76 @@ pub struct PcBus {
     floppy_a: Option<Vec<u8>>,
     /// Floppy B disk image
     floppy_b: Option<Vec<u8>>,
+    /// Sectors of floppy A, floppy B and the hard drive written since they
+    /// were mounted or last saved
+    dirty_sectors: [BTreeSet<u32>; 3],
     /// Hard drive image
     hard_drive: Option<Vec<u8>>,
     /// CD-ROM drive image (ISO 9660)
     cdrom: Option<Vec<u8>>,
+    /// El Torito entry the system booted from, until reset; with floppy
+    /// emulation its floppy image replaces drive 00h
+    cd_boot: Option<BootEntry>,
+    /// Cassette port (IBM PC 5150 tape interface)
+    pub cassette: Cassette,
     /// Disk controller
     disk_controller: DiskController,
     /// Boot priority order
     boot_priority: BootPriority,
+    /// Device picked in the POST boot menu, overriding the priority until reset
+    boot_override: Option<BootDevice>,
     /// Flag to track if boot sector has been loaded
     boot_sector_loaded: bool,
+    /// The BIOS asked for a CPU reset (Ctrl+Alt+Del)
+    reset_requested: bool,
     /// Programmable Interval Timer (8253/8254)
     pub pit: Pit,
+    /// Programmable Interrupt Controller (8259A)
+    pub pic: Pic8259,
     /// PC speaker gate (bit 0 of port 0x61)
     speaker_gate: bool,
+    /// PC speaker data enable (bit 1 of port 0x61)
+    speaker_data: bool,
+    /// PC speaker output stage
+    pub speaker: PcSpeaker,
+    /// Time left on a BIOS beep, and the port 0x61 bits to restore after it
+    bios_beep: Option<(f64, u8)>,
+    /// 8237 DMA controller (channels 0-3)
+    pub dma: DmaController,
+    /// OPL2 FM synthesis chip (AdLib at 0x388/0x389, Sound Blaster at 0x228/0x229)
+    pub opl: Opl2,
+    /// Sound Blaster DSP at 0x220
+    pub sound_blaster: SoundBlaster,
     /// Microsoft Mouse Driver
     pub mouse: Mouse,
     /// XMS (Extended Memory Specification) driver
     pub xms: XmsDriver,
+    /// EMS (Expanded Memory Specification) driver
+    pub ems: EmsDriver,
+    /// MC146818 real-time clock and CMOS RAM
+    pub rtc: Rtc,
+    /// 8250 UARTs for COM1 (0x3F8, IRQ 4) and COM2 (0x2F8, IRQ 3)
+    pub serial: [Uart; 2],
+    /// Parallel printer port LPT1 (0x378, IRQ 7)
+    pub parallel: ParallelPort,
+    /// Game control adapter (joysticks at 0x201)
+    pub game_port: GamePort,
     /// DPMI (DOS Protected Mode Interface) driver
     pub dpmi: DpmiDriver,
+    /// Built-in DOS started when no disk boots
+    pub dos: BuiltinDos,
     /// Video adapter type for equipment configuration
     video_adapter_type: VideoAdapterType,
-    /// Keyboard controller command register (for A20 gate control)
-    kb_controller_command: u8,
-    /// Keyboard controller output port (bit 1 = A20 gate)
-    kb_controller_output_port: u8,
-    /// Keyboard controller input buffer full flag (Cell for interior mutability)
-    kb_input_buffer_full: Cell<bool>,
-    /// Keyboard controller last write was command (true) or data (false)
-    kb_last_was_command: Cell<bool>,
+    /// 8042 keyboard controller (ports 0x60/0x64, IRQ 1, A20 gate)
+    pub kbc: KeyboardController,
     /// VGA status register state (Cell for interior mutability during io_read)
     /// Bit 0: Display enable (0 = display, 1 = retrace/blanking)
     /// Bit 3: Vertical retrace (0 = no retrace, 1 = vertical retrace)
     vga_status: Cell<u8>,
     /// Cycle counter for VGA status timing (Cell for interior mutability)
     vga_status_cycles: Cell<u64>,
+    /// CPU cycles per 60 Hz display refresh at the current CPU speed
+    vga_cycles_per_frame: u64,
     /// CRTC (CRT Controller) index register - selects which CRTC register to access
     crtc_index: Cell<u8>,
     /// CRTC data registers (25 registers total for VGA)
@@ -119,6 +216,10 @@ pub struct PcBus {
     cga_mode_control: Cell<u8>,
     /// CGA Color Select Register (port 0x3D9)
     cga_color_select: Cell<u8>,
+    /// Hercules Display Mode Control Register (port 0x3B8)
+    hercules_mode_control: Cell<u8>,
+    /// Hercules Configuration Switch (port 0x3BF)
+    hercules_config: Cell<u8>,
 }
 
 impl PcBus {
@@ -146,6 +247,7 @@ impl PcBus {
 
         let mut pit = Pit::new();
         pit.reset(); // Initialize with default system timer
+        pit.set_gate(2, false); // Port 0x61 bit 0 starts low
 
         // Initialize XMS with calculated extended memory
         let mut xms = XmsDriver::new(extended_kb);
@@ -165,28 +267,46 @@ impl PcBus {
             ram,
             extended_ram,
             vram,
+            vga: VgaMemory::new(),
+            video_mode: 0x03,
             rom,
             executable: None,
             keyboard: Keyboard::new(),
             floppy_a: None,
             floppy_b: None,
+            dirty_sectors: Default::default(),
             hard_drive: None,
             cdrom: None,
+            cd_boot: None,
+            cassette: Cassette::new(),
             disk_controller: DiskController::new(),
             boot_priority: BootPriority::default(),
+            boot_override: None,
             boot_sector_loaded: false,
+            reset_requested: false,
             pit,
+            pic: Pic8259::new(),
             speaker_gate: false,
+            speaker_data: false,
+            speaker: PcSpeaker::new(),
+            bios_beep: None,
+            dma: DmaController::new(),
+            opl: Opl2::new(),
+            sound_blaster: SoundBlaster::new(),
             mouse: Mouse::new(),
             xms,
+            ems: EmsDriver::default(),
+            rtc: Rtc::new(),
+            serial: [Uart::new(), Uart::new()],
+            parallel: ParallelPort::new(),
+            game_port: GamePort::new(),
             dpmi,
+            dos: BuiltinDos::new(),
             video_adapter_type: VideoAdapterType::Cga, // Default to CGA
-            kb_controller_command: 0,
-            kb_controller_output_port: 0x02, // A20 enabled by default (bit 1 set)
-            kb_input_buffer_full: Cell::new(false), // Input buffer starts empty
-            kb_last_was_command: Cell::new(false), // No command yet
-            vga_status: Cell::new(0x00),     // Start with display active (not in retrace)
+            kbc: KeyboardController::new(),
+            vga_status: Cell::new(0x00), // Start with display active (not in retrace)
             vga_status_cycles: Cell::new(0),
+            vga_cycles_per_frame: XT_CYCLES_PER_FRAME,
             crtc_index: Cell::new(0),
             crtc_data: std::array::from_fn(|_| Cell::new(0)),
             sequencer_index: Cell::new(0),
@@ -203,6 +323,8 @@ impl PcBus {
             dac_state: Cell::new(0),
             cga_mode_control: Cell::new(0),
             cga_color_select: Cell::new(0),
+            hercules_mode_control: Cell::new(0),
+            hercules_config: Cell::new(0),
         };
 
         // Initialize Interrupt Vector Table (IVT) in low RAM
@@ -218,6 +340,7 @@ impl PcBus {
         bus.ram[0x0002] = 0x00; // segment low byte
         bus.ram[0x0003] = 0xF0; // segment high byte (F000)
 
+        bus.update_cmos_setup();
         bus
     }
 
@@ -242,6 +365,35 @@ impl PcBus {
     /// Set the video adapter type for equipment configuration
     pub fn set_video_adapter_type(&mut self, adapter_type: VideoAdapterType) {
         self.video_adapter_type = adapter_type;
+        self.update_cmos_setup();
+    }
+
+    /// Write the configuration (drives, display, memory) to the CMOS setup
+    ///
+    /// Drive types follow the mounted images, like a BIOS setup set to
+    /// auto-detect. Called whenever the configuration changes.
+    pub fn update_cmos_setup(&mut self) {
+        let floppy_type = |image: &Option<Vec<u8>>| {
+            image.as_ref().map(|data| {
+                FloppyFormat::from_size(data.len()).unwrap_or(FloppyFormat::Floppy1_44M)
+            })
+        };
+        let display_bits = match self.video_adapter_type {
+            VideoAdapterType::Mda => 0b11,
+            VideoAdapterType::Cga => 0b10,
+            VideoAdapterType::None | VideoAdapterType::Ega | VideoAdapterType::Vga => 0b00,
+        };
+        let setup = CmosSetup {
+            floppies: [floppy_type(&self.floppy_a), floppy_type(&self.floppy_b)],
+            hard_disk: self
+                .hard_drive
+                .as_ref()
+                .and_then(|_| DiskController::get_drive_params(0x80, None)),
+            display_bits,
+            base_memory_kb: self.conventional_memory_kb(),
+            extended_memory_kb: self.xms.total_extended_memory_kb(),
+        };
+        self.rtc.set_setup(&setup);
     }
 
     /// Get the video adapter type
@@ -249,25 +401,86 @@ impl PcBus {
         self.video_adapter_type
     }
 
+    /// Offset of the text buffer in VRAM
+    ///
+    /// Monochrome adapters keep their text at B0000h, color adapters at
+    /// B8000h.
+    pub fn text_buffer_offset(&self) -> usize {
+        if self.video_adapter_type == VideoAdapterType::Mda {
+            MONO_TEXT_OFFSET
+        } else {
+            COLOR_TEXT_OFFSET
+        }
+    }
+
+    /// Offset of the displayed text in VRAM
+    ///
+    /// Follows the CRTC start address (registers 0Ch/0Dh, in characters),
+    /// which INT 10h AH=05h sets to show another display page.
+    pub fn text_display_offset(&self) -> usize {
+        let start =
+            (self.crtc_data[0x0C].get() as usize) << 8 | self.crtc_data[0x0D].get() as usize;
+        self.text_buffer_offset() + ((start * 2) & 0x7FFF)
+    }
+
+    /// Characters per row of the current text mode
+    pub fn text_columns(&self) -> usize {
+        match self.video_mode {
+            0x00 | 0x01 => 40,
+            _ => 80,
+        }
+    }
+
+    /// Displayed Hercules 720x348 graphics page, as an offset into VRAM
+    ///
+    /// Returns `None` in text mode or on other adapters. Graphics mode and
+    /// the second page at B8000h only take effect once the configuration
+    /// switch (3BFh) allows them, as on the real card.
+    pub fn hercules_graphics_page(&self) -> Option<usize> {
+        if self.video_adapter_type != VideoAdapterType::Mda {
+            return None;
+        }
+        let mode = self.hercules_mode_control.get();
+        let config = self.hercules_config.get();
+        if mode & HERCULES_GRAPHICS == 0 || config & HERCULES_ALLOW_GRAPHICS == 0 {
+            return None;
+        }
+        if mode & HERCULES_PAGE_1 != 0 && config & HERCULES_ENABLE_PAGE_1 != 0 {
+            Some(COLOR_TEXT_OFFSET)
+        } else {
+            Some(MONO_TEXT_OFFSET)
+        }
+    }
+
+    /// Set the CPU clock that drives cycle-based timing
+    ///
+    /// The PIT and the display refresh run at fixed real-time rates, so both
+    /// are rescaled to keep timers and retrace at the same wall-clock rate
+    /// whatever the emulated CPU speed.
+    pub fn set_cpu_speed_mhz(&mut self, mhz: f64) {
+        if mhz > 0.0 {
+            self.pit.set_cpu_speed_mhz(mhz);
+            self.vga_cycles_per_frame =
+                ((XT_CYCLES_PER_FRAME as f64 * mhz / XT_CPU_MHZ) as u64).max(20);
+        }
+    }
+
     /// Update VGA status register based on elapsed cycles
     ///
     /// This simulates the vertical retrace timing. At 60 Hz, a frame is ~16.67ms.
-    /// Assuming 4.77 MHz (original PC), that's about 79,583 cycles per frame.
+    /// Assuming 4.77 MHz (original PC), that's about 79,583 cycles per frame;
+    /// other CPU speeds scale this via `set_cpu_speed_mhz`.
     /// We'll simulate vertical retrace for about 5% of the frame time.
     pub fn update_vga_status(&self, cycles: u64) {
         let current_cycles = self.vga_status_cycles.get() + cycles;
         self.vga_status_cycles.set(current_cycles);
 
-        // Cycles per frame at various speeds (at 60 Hz):
-        // 4.77 MHz (PC/XT): 79,583 cycles/frame
-        // We'll use a generic approach: retrace happens for ~5% of frame
-        // Frame period: ~80,000 cycles (approximate for 4.77 MHz)
-        const CYCLES_PER_FRAME: u64 = 80000;
-        const RETRACE_CYCLES: u64 = CYCLES_PER_FRAME / 20; // 5% of frame
+        let cycles_per_frame = self.vga_cycles_per_frame;
+        let retrace_cycles = cycles_per_frame / 20; // 5% of frame
 
-        let frame_position = current_cycles % CYCLES_PER_FRAME;
+        let frame_position = current_cycles % cycles_per_frame;
 
-        if frame_position < RETRACE_CYCLES {
+        if frame_position < retrace_cycles {
             // In vertical retrace (bit 3 set, bit 0 set for blanking)
             self.vga_status.set(0x09); // Bits 0 and 3 set
         } else {
@@ -299,13 +512,34 @@ impl PcBus {
         // Clear RAM but preserve ROM and executable
         self.ram.fill(0);
         self.vram.fill(0);
+        self.vga.clear();
+        self.video_mode = 0x03;
         self.keyboard.clear();
+        self.kbc.reset();
         self.disk_controller.reset();
         self.pit.reset();
+        self.pic.reset();
         self.speaker_gate = false;
+        self.speaker_data = false;
+        self.pit.set_gate(2, false);
+        self.speaker.reset();
+        self.bios_beep = None;
+        self.dma.reset();
+        self.opl.reset();
+        self.sound_blaster.reset();
         self.mouse = Mouse::new(); // Reset mouse state
                                    // XMS driver state is preserved across resets (like hardware)
+        self.ems.reset(); // Rebooting frees all EMS handles
+        self.dos.reset();
+        // The RTC and CMOS RAM are battery backed and survive resets
+        for uart in &mut self.serial {
+            uart.reset(); // Host connections stay attached
+        }
+        self.parallel.reset(); // The printer stays attached
         self.boot_sector_loaded = false;
+        self.reset_requested = false;
+        self.boot_override = None;
+        self.cd_boot = None;
         // Reset VGA status
         self.vga_status.set(0x00);
         self.vga_status_cycles.set(0);
@@ -341,6 +575,9 @@ impl PcBus {
         // Reset CGA registers
         self.cga_mode_control.set(0);
         self.cga_color_select.set(0);
+        // Reset Hercules registers (text mode, graphics locked out)
+        self.hercules_mode_control.set(0);
+        self.hercules_config.set(0);
     }
 
     /// Set boot priority
@@ -353,11 +590,53 @@ impl PcBus {
         self.boot_priority
     }
 
+    /// Ask the system to reset the CPU after the current instruction
+    pub fn request_reset(&mut self) {
+        self.reset_requested = true;
+    }
+
+    /// Take a pending CPU reset request
+    pub fn take_reset_request(&mut self) -> bool {
+        std::mem::take(&mut self.reset_requested)
+    }
+
+    /// Boot from `device` only (or by priority again with `None`) until reset
+    pub fn set_boot_override(&mut self, device: Option<BootDevice>) {
+        self.boot_override = device;
+    }
+
+    /// Disk image mounted in a boot device
+    fn boot_image(&self, device: BootDevice) -> Option<&[u8]> {
+        match device {
+            BootDevice::FloppyA => self.floppy_a.as_deref(),
+            BootDevice::FloppyB => self.floppy_b.as_deref(),
+            BootDevice::HardDrive => self.hard_drive.as_deref(),
+            BootDevice::CdRom => self.cdrom.as_deref(),
+        }
+    }
+
+    /// Check whether a device has a disk with a valid boot sector (or, for
+    /// the CD-ROM, a usable El Torito boot entry)
+    pub fn boot_device_status(&self, device: BootDevice) -> BootDeviceStatus {
+        let bootable = match (device, self.boot_image(device)) {
+            (_, None) => return BootDeviceStatus::NotPresent,
+            (BootDevice::CdRom, Some(cd)) => el_torito::parse(cd).is_ok(),
+            (_, Some(image)) => has_boot_signature(image),
+        };
+        if bootable {
+            BootDeviceStatus::Bootable
+        } else {
+            BootDeviceStatus::NoSignature
+        }
+    }
+
     /// Load boot sector from the appropriate disk based on boot priority
     ///
     /// This method attempts to load the boot sector (sector 0, 512 bytes) from
     /// the configured boot disk to memory address 0x7C00. It verifies the boot
-    /// signature (0xAA55) at the end of the sector.
+    /// signature (0xAA55) at the end of the sector. A CD-ROM boots through its
+    /// El Torito boot catalog instead (see `boot_cd`). A device chosen in the
+    /// boot menu is tried on its own instead of the priority order.
     ///
     /// Returns: true if boot sector was loaded successfully, false otherwise
     pub fn load_boot_sector(&mut self) -> bool {
@@ -366,64 +645,177 @@ impl PcBus {
             return true;
         }
 
+        // A loaded program runs on the built-in DOS instead (INT 18h)
+        if self.executable.is_some() {
+            return false;
+        }
+
         // Determine which disk(s) to try based on boot priority
-        let boot_devices: Vec<(u8, Option<&[u8]>)> = match self.boot_priority {
-            BootPriority::FloppyFirst => vec![
-                (0x00, self.floppy_a.as_deref()),
-                (0x80, self.hard_drive.as_deref()),
-            ],
-            BootPriority::HardDriveFirst => vec![
-                (0x80, self.hard_drive.as_deref()),
-                (0x00, self.floppy_a.as_deref()),
-            ],
-            BootPriority::FloppyOnly => vec![(0x00, self.floppy_a.as_deref())],
-            BootPriority::HardDriveOnly => vec![(0x80, self.hard_drive.as_deref())],
+        let boot_devices = match (self.boot_override, self.boot_priority) {
+            (Some(device), _) => vec![device],
+            (None, BootPriority::FloppyFirst) => {
+                vec![BootDevice::FloppyA, BootDevice::HardDrive]
+            }
+            (None, BootPriority::HardDriveFirst) => {
+                vec![BootDevice::HardDrive, BootDevice::FloppyA]
+            }
+            (None, BootPriority::FloppyOnly) => vec![BootDevice::FloppyA],
+            (None, BootPriority::HardDriveOnly) => vec![BootDevice::HardDrive],
+            (None, BootPriority::CdRomFirst) => {
+                vec![
+                    BootDevice::CdRom,
+                    BootDevice::FloppyA,
+                    BootDevice::HardDrive,
+                ]
+            }
         };
 
         // Try each device in order
-        for (drive, disk_image) in boot_devices {
-            if let Some(image) = disk_image {
-                // Check if disk image is large enough for boot sector
-                if image.len() < 512 {
-                    continue;
+        for device in boot_devices {
+            let drive = device.drive();
+            let Some(image) = self.boot_image(device) else {
+                continue;
+            };
+
+            if device == BootDevice::CdRom {
+                match el_torito::parse(image) {
+                    Ok(entry) if self.boot_cd(entry) => return true,
+                    Ok(_) => println!("CD-ROM boot image does not fit in memory"),
+                    Err(e) => println!("CD-ROM is not bootable: {}", e),
                 }
+                continue;
+            }
+
+            // Check if disk image is large enough for boot sector
+            if image.len() < 512 {
+                continue;
+            }
 
-                // Read boot sector (first 512 bytes)
-                let boot_sector = &image[0..512];
+            // Check for boot signature 0xAA55 at offset 510-511
+            if !has_boot_signature(image) {
+                println!("Boot sector on drive 0x{:02X} has invalid signature", drive);
+                continue;
+            }
 
-                // Check for boot signature 0xAA55 at offset 510-511
-                if boot_sector[510] != 0x55 || boot_sector[511] != 0xAA {
-                    println!("Boot sector on drive 0x{:02X} has invalid signature", drive);
-                    continue;
-                }
+            // Load boot sector (first 512 bytes) to 0x0000:0x7C00 (physical address 0x7C00)
+            let boot_sector: [u8; 512] = image[0..512].try_into().unwrap();
+            self.ram[0x7C00..0x7C00 + 512].copy_from_slice(&boot_sector);
 
-                // Load boot sector to 0x0000:0x7C00 (physical address 0x7C00)
-                self.ram[0x7C00..0x7C00 + 512].copy_from_slice(boot_sector);
+            self.set_boot_vector(0x0000, 0x7C00, drive);
 
-                // Debug: Check boot sector signature and first few bytes
-                eprintln!(
-                    "Boot sector loaded: signature={:02X}{:02X}, OEM={}",
-                    self.ram[0x7C00 + 510],
-                    self.ram[0x7C00 + 511],
-                    String::from_utf8_lossy(&self.ram[0x7C00 + 3..0x7C00 + 11])
-                );
+            // Debug: Check boot sector signature and first few bytes
+            eprintln!(
+                "Boot sector loaded: signature={:02X}{:02X}, OEM={}",
+                self.ram[0x7C00 + 510],
+                self.ram[0x7C00 + 511],
+                String::from_utf8_lossy(&self.ram[0x7C00 + 3..0x7C00 + 11])
+            );
 
-                self.boot_sector_loaded = true;
-                println!("Loaded boot sector from drive 0x{:02X}", drive);
-                return true;
-            }
+            println!("Loaded boot sector from drive 0x{:02X}", drive);
+            return true;
         }
 
         println!("No bootable disk found");
         false
     }
 
-    /// Load an executable at a specific address
-    #[allow(dead_code)]
+    /// Load the boot code of an El Torito entry on the mounted CD
+    ///
+    /// No-emulation images are loaded to their load segment and started with
+    /// DL=E0h. With floppy emulation the floppy image on the CD becomes drive
+    /// 00h (read-only) and its boot sector is loaded to 0x7C00.
+    ///
+    /// Returns: false if the boot code does not fit in memory
+    fn boot_cd(&mut self, entry: BootEntry) -> bool {
+        let Some(image) = self.cdrom.as_deref().and_then(|cd| entry.image(cd)) else {
+            return false;
+        };
+        let (segment, offset, code) = match entry.media {
+            BootMedia::NoEmulation if entry.load_segment == 0x07C0 => (0x0000, 0x7C00, image),
+            BootMedia::NoEmulation => (entry.load_segment, 0x0000, image),
+            BootMedia::Floppy(_) => (0x0000, 0x7C00, &image[..512]),
+        };
+        let start = ((segment as usize) << 4) + offset as usize;
+        let Some(dest) = self.ram.get_mut(start..start + code.len()) else {
+            return false;
+        };
+        dest.copy_from_slice(code);
+
+        self.cd_boot = Some(entry);
+        self.set_boot_vector(segment, offset, entry.drive());
+        println!(
+            "Booting CD-ROM ({}) at {:04X}:{:04X}",
+            match entry.media {
+                BootMedia::NoEmulation => "no emulation",
+                BootMedia::Floppy(_) => "floppy emulation",
+            },
+            segment,
+            offset
+        );
+        true
+    }
+
+    /// Point the BIOS boot jump at the loaded boot code
+    fn set_boot_vector(&mut self, segment: u16, offset: u16, drive: u8) {
+        self.ram[BOOT_VECTOR_ADDR..BOOT_VECTOR_ADDR + 2].copy_from_slice(&offset.to_le_bytes());
+        self.ram[BOOT_VECTOR_ADDR + 2..BOOT_VECTOR_ADDR + 4]
+            .copy_from_slice(&segment.to_le_bytes());
+        // The BIOS hands the boot drive to the boot code in DL
+        self.ram[BOOT_DRIVE_ADDR] = drive;
+        self.ram[BOOT_DRIVE_ADDR + 1] = 0;
+        self.ram[BOOT_READY_ADDR] = 1;
+        self.boot_sector_loaded = true;
+    }
+
+    /// El Torito entry the system booted from (INT 13h AH=4Bh)
+    pub fn cd_boot(&self) -> Option<BootEntry> {
+        self.cd_boot
+    }
+
+    /// Stop floppy emulation (INT 13h AH=4Bh AL=00h), giving drive 00h back
+    /// to floppy A
+    pub fn end_cd_emulation(&mut self) {
+        if matches!(
+            self.cd_boot,
+            Some(BootEntry {
+                media: BootMedia::Floppy(_),
+                ..
+            })
+        ) {
+            self.cd_boot = None;
+        }
+    }
+
+    /// Floppy image on the CD while booted with floppy emulation
+    fn emulated_floppy(&self) -> Option<&[u8]> {
+        cd_floppy_image(self.cd_boot, self.cdrom.as_deref())
+    }
+
+    /// Disk seen as BIOS drive 00h
+    fn drive_a(&self) -> Option<&[u8]> {
+        self.emulated_floppy().or(self.floppy_a.as_deref())
+    }
+
+    /// Bytes per sector of a BIOS drive for LBA transfers
+    pub fn sector_size(&self, drive: u8) -> usize {
+        if drive >= 0xE0 {
+            CD_SECTOR_SIZE
+        } else {
+            512
+        }
+    }
+
+    /// Set a .COM or .EXE program to run on the built-in DOS instead of
+    /// booting from disk (kept across resets)
     pub fn load_executable(&mut self, data: Vec<u8>) {
         self.executable = Some(data);
     }
 
+    /// Forget the program set with `load_executable`
+    pub fn clear_executable(&mut self) {
+        self.executable = None;
+    }
+
     /// Load BIOS ROM
     pub fn load_bios(&mut self, data: &[u8]) {
         // BIOS is typically loaded at 0xF0000-0xFFFFF (last 64KB of ROM area)
@@ -432,12 +824,202 @@ impl PcBus {
         self.rom[bios_offset..bios_offset + len].copy_from_slice(&data[..len]);
     }
 
-    /// Get a reference to the executable data
-    #[allow(dead_code)]
+    /// Program to run instead of booting from disk
     pub fn executable(&self) -> Option<&[u8]> {
         self.executable.as_deref()
     }
 
+    /// Current BIOS video mode
+    pub fn video_mode(&self) -> u8 {
+        self.video_mode
+    }
+
+    /// Switch video mode the way INT 10h AH=00h does
+    ///
+    /// The 16-color modes (0Dh, 0Eh, 10h, 12h) program the sequencer, CRTC,
+    /// graphics and attribute controllers for planar graphics with the
+    /// default EGA palette. Mode 13h programs the VGA registers for 320x200
+    /// chained 256-color graphics; programs reach Mode X from there by
+    /// reprogramming the sequencer and CRTC. The DAC is reloaded for every
+    /// mode, and planar memory is cleared unless `clear` is false (bit 7 of
+    /// the mode number).
+    ///
+    /// Modes 00h-07h load the CGA (or MDA/Hercules) mode control and color
+    /// select registers. Every mode starts at display page 0 with the
+    /// default cursor shape.
+    pub fn set_video_mode(&mut self, mode: u8, clear: bool) {
+        self.video_mode = mode;
+        self.dac_mask.set(0xFF);
+        if let Some(&value) = MODE_CONTROL_VALUES.get(mode as usize) {
+            if self.video_adapter_type == VideoAdapterType::Mda {
+                // Also leaves Hercules graphics
+                self.hercules_mode_control.set(value);
+            } else {
+                self.cga_mode_control.set(value);
+                self.cga_color_select
+                    .set(if mode == 0x06 { 0x3F } else { 0x30 });
+            }
+        }
+        let [cursor_start, cursor_end] = self.default_cursor_shape().to_be_bytes();
+        for (index, value) in [
+            (0x0A, cursor_start),
+            (0x0B, cursor_end),
+            (0x0C, 0),
+            (0x0D, 0),
+        ] {
+            self.crtc_data[index].set(value);
+        }
+        if PLANAR_16_COLOR_MODES.contains(&mode) {
+            self.program_vga(
+                &planar_16_color_crtc(mode),
+                &PLANAR_16_COLOR_SEQUENCER,
+                &PLANAR_16_COLOR_GRAPHICS,
+            );
+            for (index, &value) in DEFAULT_EGA_PALETTE.iter().enumerate() {
+                self.attribute_data[index].set(value);
+            }
+            for (index, value) in PLANAR_16_COLOR_ATTRIBUTE {
+                self.attribute_data[index].set(value);
+            }
+            // The 64 EGA colors, followed by the usual defaults
+            for (index, color) in DEFAULT_VGA_PALETTE.iter().enumerate() {
+                let rgb = if index < 64 {
+                    ega_dac_color(index as u8)
+                } else {
+                    [color.r, color.g, color.b]
+                };
+                for (component, value) in rgb.into_iter().enumerate() {
+                    self.dac_data[index * 3 + component].set(value);
+                }
+            }
+        } else {
+            if mode == VGA_MODE_13H {
+                self.program_vga(&MODE_13H_CRTC, &MODE_13H_SEQUENCER, &MODE_13H_GRAPHICS);
+            }
+            for (index, color) in DEFAULT_VGA_PALETTE.iter().enumerate() {
+                self.dac_data[index * 3].set(color.r);
+                self.dac_data[index * 3 + 1].set(color.g);
+                self.dac_data[index * 3 + 2].set(color.b);
+            }
+        }
+        if clear && self.supports_planar_mode(mode) {
+            self.vga.clear();
+        }
+    }
+
+    /// Load CRTC, sequencer and graphics controller registers (index, value)
+    fn program_vga(
+        &mut self,
+        crtc: &[(usize, u8)],
+        sequencer: &[(usize, u8)],
+        graphics: &[(usize, u8)],
+    ) {
+        for &(index, value) in crtc {
+            self.crtc_data[index].set(value);
+        }
+        for &(index, value) in sequencer {
+            self.sequencer_data[index].set(value);
+        }
+        for &(index, value) in graphics {
+            self.graphics_data[index].set(value);
+        }
+    }
+
+    /// Check if the video adapter can display a BIOS video mode
+    ///
+    /// An MDA or Hercules card only has the monochrome text mode 07h; color
+    /// adapters have the CGA modes 00h-06h and the planar modes they
+    /// support.
+    pub fn supports_video_mode(&self, mode: u8) -> bool {
+        if self.video_adapter_type == VideoAdapterType::Mda {
+            mode == MDA_TEXT_MODE
+        } else {
+            mode <= 0x06 || self.supports_planar_mode(mode)
+        }
+    }
+
+    /// Default cursor shape of the video adapter's text mode
+    pub fn default_cursor_shape(&self) -> u16 {
+        if self.video_adapter_type == VideoAdapterType::Mda {
+            MONO_CURSOR_SHAPE
+        } else {
+            COLOR_CURSOR_SHAPE
+        }
+    }
+
+    /// Check if the video adapter has the planar memory of a BIOS mode
+    ///
+    /// The 16-color modes 0Dh, 0Eh and 10h need an EGA or VGA; 640x480
+    /// (12h) and 256 colors (13h) need a VGA.
+    pub fn supports_planar_mode(&self, mode: u8) -> bool {
+        match mode {
+            0x0D | 0x0E | 0x10 => matches!(
+                self.video_adapter_type,
+                VideoAdapterType::Ega | VideoAdapterType::Vga
+            ),
+            0x12 | VGA_MODE_13H => self.video_adapter_type == VideoAdapterType::Vga,
+            _ => false,
+        }
+    }
+
+    /// Check if A0000h-AFFFFh is the planar graphics memory
+    fn vga_graphics_active(&self) -> bool {
+        self.supports_planar_mode(self.video_mode)
+    }
+
+    /// Sequencer and graphics controller state for a CPU access
+    fn vga_access(&self) -> VgaAccess {
+        VgaAccess {
+            memory_mode: self.sequencer_data[4].get(),
+            map_mask: self.sequencer_data[2].get(),
+            read_map: self.graphics_data[4].get(),
+            mode: self.graphics_data[5].get(),
+            data_rotate: self.graphics_data[3].get(),
+            color_compare: self.graphics_data[2].get(),
+            color_dont_care: self.graphics_data[7].get(),
+            set_reset: self.graphics_data[0].get(),
+            enable_set_reset: self.graphics_data[1].get(),
+            bit_mask: self.graphics_data[8].get(),
+        }
+    }
+
+    /// Displayed planar graphics picture as DAC palette indices
+    ///
+    /// Returns the width, height and one byte per pixel, or `None` outside
+    /// the EGA/VGA graphics modes. 16-color pixels have been through the
+    /// attribute controller palette, and the DAC pixel mask is applied. An
+    /// EGA has no DAC, so there each byte is the 6-bit EGA color instead.
+    pub fn vga_graphics_frame(&self) -> Option<(usize, usize, Vec<u8>)> {
+        if !self.vga_graphics_active() {
+            return None;
+        }
+        let crtc = std::array::from_fn(|index| self.crtc_data[index].get());
+        let graphics_mode = self.graphics_data[5].get();
+        let layout = VgaLayout::from_crtc(&crtc, graphics_mode);
+        let has_dac = self.video_adapter_type == VideoAdapterType::Vga;
+        let mut pixels = if graphics_mode & GRAPHICS_MODE_256 != 0 {
+            self.vga.scanout(&layout)
+        } else {
+            let attribute = std::array::from_fn(|index| self.attribute_data[index].get());
+            let mut pixels = self.vga.scanout_planar(&layout);
+            apply_attributes(&mut pixels, &attribute, has_dac);
+            pixels
+        };
+        if !has_dac {
+            return Some((layout.width, layout.height, pixels));
+        }
+        let mask = self.dac_mask.get();
+        if mask != 0xFF {
+            pixels.iter_mut().for_each(|p| *p &= mask);
+        }
+        Some((layout.width, layout.height, pixels))
+    }
+
+    /// DAC palette: 6-bit red, green and blue for each of the 256 colors
+    pub fn dac_palette(&self) -> [u8; 768] {
+        std::array::from_fn(|index| self.dac_data[index].get() & 0x3F)
+    }
+
     /// Get a reference to the video RAM (for rendering)
     pub fn vram(&self) -> &[u8] {
         &self.vram
@@ -461,11 +1043,15 @@ impl PcBus {
     /// Mount floppy A disk image
     pub fn mount_floppy_a(&mut self, data: Vec<u8>) {
         self.floppy_a = Some(data);
+        self.dirty_sectors[0].clear();
+        self.update_cmos_setup();
     }
 
     /// Unmount floppy A
     pub fn unmount_floppy_a(&mut self) {
         self.floppy_a = None;
+        self.dirty_sectors[0].clear();
+        self.update_cmos_setup();
     }
 
     /// Get reference to floppy A
@@ -476,11 +1062,44 @@ impl PcBus {
     /// Mount floppy B disk image
     pub fn mount_floppy_b(&mut self, data: Vec<u8>) {
         self.floppy_b = Some(data);
+        self.dirty_sectors[1].clear();
+        self.update_cmos_setup();
     }
 
     /// Unmount floppy B
     pub fn unmount_floppy_b(&mut self) {
         self.floppy_b = None;
+        self.dirty_sectors[1].clear();
+        self.update_cmos_setup();
+    }
+
+    /// Slot in `dirty_sectors` for a BIOS drive number (0x00, 0x01 or 0x80)
+    fn dirty_slot(drive: u8) -> Option<usize> {
+        match drive {
+            0x00 => Some(0),
+            0x01 => Some(1),
+            0x80 => Some(2),
+            _ => None,
+        }
+    }
+
+    /// Check if a drive (0x00/0x01 floppy, 0x80 hard drive) has been written
+    /// since it was mounted or last marked clean
+    pub fn disk_is_dirty(&self, drive: u8) -> bool {
+        self.dirty_sector_count(drive) > 0
+    }
+
+    /// Number of distinct sectors written to a drive since it was mounted or
+    /// last marked clean
+    pub fn dirty_sector_count(&self, drive: u8) -> usize {
+        Self::dirty_slot(drive).map_or(0, |slot| self.dirty_sectors[slot].len())
+    }
+
+    /// Clear the modified sectors of a drive after saving its image
+    pub fn mark_disk_clean(&mut self, drive: u8) {
+        if let Some(slot) = Self::dirty_slot(drive) {
+            self.dirty_sectors[slot].clear();
+        }
     }
 
     /// Get reference to floppy B
@@ -491,11 +1110,15 @@ impl PcBus {
     /// Mount hard drive image
     pub fn mount_hard_drive(&mut self, data: Vec<u8>) {
         self.hard_drive = Some(data);
+        self.dirty_sectors[2].clear();
+        self.update_cmos_setup();
     }
 
     /// Unmount hard drive
     pub fn unmount_hard_drive(&mut self) {
         self.hard_drive = None;
+        self.dirty_sectors[2].clear();
+        self.update_cmos_setup();
     }
 
     /// Get reference to hard drive
@@ -511,10 +1134,10 @@ impl PcBus {
     /// Unmount CD-ROM drive
     pub fn unmount_cdrom(&mut self) {
         self.cdrom = None;
+        self.cd_boot = None;
     }
 
     /// Get reference to CD-ROM
-    #[allow(dead_code)]
     pub fn cdrom(&self) -> Option<&[u8]> {
         self.cdrom.as_deref()
     }
@@ -557,18 +1180,30 @@ impl PcBus {
     /// Check if a floppy drive has a disk mounted
     pub fn has_floppy(&self, drive: u8) -> bool {
         match drive {
-            0 => self.floppy_a.is_some(),
+            0 => self.drive_a().is_some(),
             1 => self.floppy_b.is_some(),
             _ => false,
         }
     }
 
+    /// Geometry (cylinders, sectors_per_track, heads) reported for a BIOS drive
+    ///
+    /// Floppy geometry follows the mounted image.
+    pub fn drive_params(&self, drive: u8) -> Option<(u16, u8, u8)> {
+        let floppy = match drive {
+            0x00 => self.drive_a(),
+            0x01 => self.floppy_b.as_deref(),
+            _ => None,
+        };
+        crate::disk::DiskController::get_drive_params(drive, floppy)
+    }
+
     /// Perform a disk read operation
     pub fn disk_read(&mut self, request: &crate::disk::DiskRequest, buffer: &mut [u8]) -> u8 {
         let disk_image = if request.drive < 0x80 {
             // Floppy drive (0x00-0x7F)
             if request.drive == 0x00 {
-                self.floppy_a.as_deref()
+                cd_floppy_image(self.cd_boot, self.cdrom.as_deref()).or(self.floppy_a.as_deref())
             } else if request.drive == 0x01 {
                 self.floppy_b.as_deref()
             } else {
@@ -597,6 +1232,9 @@ impl PcBus {
 
     /// Perform a disk write operation
     pub fn disk_write(&mut self, request: &crate::disk::DiskRequest, buffer: &[u8]) -> u8 {
+        if request.drive == 0x00 && self.emulated_floppy().is_some() {
+            return 0x03; // Write protect error (floppy emulated from the CD)
+        }
         let disk_mut = if request.drive < 0x80 {
             // Floppy drive (0x00-0x7F)
             if request.drive == 0x00 {
@@ -619,8 +1257,14 @@ impl PcBus {
             }
         };
 
-        self.disk_controller
-            .write_sectors(request, buffer, disk_mut)
+        let lba = request.lba(disk_mut.as_deref().map(Vec::as_slice));
+        let status = self
+            .disk_controller
+            .write_sectors(request, buffer, disk_mut);
+        if let Some(lba) = lba {
+            self.note_disk_write(request.drive, lba, request.count, status);
+        }
+        status
     }
 
     /// Perform a disk read operation using LBA
@@ -628,7 +1272,7 @@ impl PcBus {
         let disk_image = if drive < 0x80 {
             // Floppy drive (0x00-0x7F)
             if drive == 0x00 {
-                self.floppy_a.as_deref()
+                cd_floppy_image(self.cd_boot, self.cdrom.as_deref()).or(self.floppy_a.as_deref())
             } else if drive == 0x01 {
                 self.floppy_b.as_deref()
             } else {
@@ -650,12 +1294,16 @@ impl PcBus {
             }
         };
 
+        let sector_size = self.sector_size(drive);
         self.disk_controller
-            .read_sectors_lba(lba, count, buffer, disk_image)
+            .read_sectors_lba(lba, count, sector_size, buffer, disk_image)
     }
 
     /// Perform a disk write operation using LBA
     pub fn disk_write_lba(&mut self, drive: u8, lba: u32, count: u8, buffer: &[u8]) -> u8 {
+        if drive == 0x00 && self.emulated_floppy().is_some() {
+            return 0x03; // Write protect error (floppy emulated from the CD)
+        }
         let disk_mut = if drive < 0x80 {
             // Floppy drive (0x00-0x7F)
             if drive == 0x00 {
@@ -677,13 +1325,87 @@ impl PcBus {
             }
         };
 
-        self.disk_controller
-            .write_sectors_lba(lba, count, buffer, disk_mut)
+        let status = self
+            .disk_controller
+            .write_sectors_lba(lba, count, buffer, disk_mut);
+        self.note_disk_write(drive, lba, count, status);
+        status
+    }
+
+    /// Disk image behind a DOS drive number (0 = A:, 1 = B:, 2 = C:)
+    pub fn dos_drive_image(&self, drive: u8) -> Option<&[u8]> {
+        match dos::bios_drive(drive)? {
+            0x00 => self.floppy_a(),
+            0x01 => self.floppy_b(),
+            _ => self.hard_drive(),
+        }
+    }
+
+    /// FAT volume on a DOS drive, with its disk image
+    pub fn dos_volume(&self, drive: u8) -> Result<(FatVolume, &[u8]), DosFsError> {
+        let image = self.dos_drive_image(drive).ok_or(DosFsError::NotReady)?;
+        Ok((FatVolume::open(image)?, image))
+    }
+
+    /// Create or replace a file on a DOS drive (see [`FatVolume::write`])
+    pub fn write_dos_file(
+        &mut self,
+        drive: u8,
+        dir: u16,
+        entry: &DirEntry,
+        data: &[u8],
+    ) -> Result<(), DosFsError> {
+        self.modify_dos_drive(drive, |volume, image| volume.write(image, dir, entry, data))
+    }
+
+    /// Delete a file on a DOS drive
+    pub fn delete_dos_file(
+        &mut self,
+        drive: u8,
+        dir: u16,
+        name: &[u8; 11],
+    ) -> Result<(), DosFsError> {
+        self.modify_dos_drive(drive, |volume, image| volume.delete(image, dir, name))
+    }
+
+    /// Change the FAT volume of a DOS drive, recording the sectors written
+    fn modify_dos_drive(
+        &mut self,
+        drive: u8,
+        change: impl FnOnce(&FatVolume, &mut [u8]) -> Result<Vec<u32>, DosFsError>,
+    ) -> Result<(), DosFsError> {
+        let bios_drive = dos::bios_drive(drive).ok_or(DosFsError::NotReady)?;
+        let image = match bios_drive {
+            0x00 => self.floppy_a.as_mut(),
+            0x01 => self.floppy_b.as_mut(),
+            _ => self.hard_drive.as_mut(),
+        }
+        .ok_or(DosFsError::NotReady)?;
+        let volume = FatVolume::open(image)?;
+        let touched = change(&volume, image)?;
+        if let Some(slot) = Self::dirty_slot(bios_drive) {
+            self.dirty_sectors[slot].extend(touched);
+        }
+        Ok(())
+    }
+
+    /// Record the sectors touched by a successful write
+    fn note_disk_write(&mut self, drive: u8, lba: u32, count: u8, status: u8) {
+        if status != 0x00 {
+            return;
+        }
+        if let Some(slot) = Self::dirty_slot(drive) {
+            self.dirty_sectors[slot].extend(lba..lba + count as u32);
+        }
     }
 
     /// Read from an I/O port
     pub fn io_read(&self, port: u16) -> u8 {
         let value = match port {
+            // 8259A PIC
+            PIC_COMMAND_PORT | PIC_DATA_PORT => self.pic.io_read(port),
+            // MC146818 RTC/CMOS
+            RTC_INDEX_PORT | RTC_DATA_PORT => self.rtc.io_read(port),
             // PIT Channel 0 (system timer)
             0x40 => {
                 // Reading would need mutable access to update read state
@@ -705,65 +1427,17 @@ impl PcBus {
                 if self.speaker_gate {
                     value |= 0x01; // Speaker gate enabled
                 }
+                if self.speaker_data {
+                    value |= 0x02; // Speaker data enabled
+                }
                 // Bit 5: PIT channel 2 output
                 if self.pit.speaker_output() {
                     value |= 0x20;
                 }
                 value
             }
-            // Port 0x60 - Keyboard controller data port
-            0x60 => {
-                // When command is 0xD0 (Read Output Port), return output port value
-                if self.kb_controller_command == 0xD0 {
-                    log(LogCategory::Interrupts, LogLevel::Debug, || {
-                        format!(
-                            "KB controller read output port: 0x{:02X}",
-                            self.kb_controller_output_port
-                        )
-                    });
-                    self.kb_controller_output_port
-                } else {
-                    // Normal keyboard data - return last scancode or 0
-                    self.keyboard.peek_scancode()
-                }
-            }
-            // Port 0x64 - Keyboard controller status port
-            0x64 => {
-                // Bit 0: Output buffer full (0 = empty, 1 = full)
-                // Bit 1: Input buffer full (0 = empty, 1 = full)
-                // Bit 2: System flag (0 = POST, 1 = warm boot)
-                // Bit 3: Command/Data (0 = data last written to 60h, 1 = command to 64h)
-                // Bit 4: Keyboard unlocked (1 = keyboard enabled)
-                // Bit 5: Transmit timeout
-                // Bit 6: Receive timeout
-                // Bit 7: Parity error
-                // Return system flag set (warm boot) + keyboard enabled + input buffer status
-                let mut status = 0x14; // Bits 2 (warm boot) and 4 (enabled) set
-                if self.kb_input_buffer_full.get() {
-                    status |= 0x02; // Set bit 1 if input buffer full
-                }
-                if self.kb_last_was_command.get() {
-                    status |= 0x08; // Set bit 3 if last write was command
-                }
-
-                // Debug: Log status reads during HIMEM execution
-                log(LogCategory::Interrupts, LogLevel::Trace, || {
-                    format!(
-                        "KB status read: 0x{:02X} (input_buffer_full={}, last_was_cmd={})",
-                        status,
-                        self.kb_input_buffer_full.get(),
-                        self.kb_last_was_command.get()
-                    )
-                });
-
-                // Simulate controller processing: clear buffer after one status read
-                // This allows software to see buffer full briefly after write
-                if self.kb_input_buffer_full.get() {
-                    self.kb_input_buffer_full.set(false);
-                }
-
-                status
-            }
+            // Keyboard controller data and status ports
+            0x60 | 0x64 => self.kbc.io_read(port),
             // Port 0x92 - System Control Port A (PS/2)
             // Bit 0: Alternate hot reset (0 = normal, 1 = reset)
             // Bit 1: A20 gate (0 = disabled, 1 = enabled)
@@ -785,7 +1459,18 @@ impl PcBus {
             0x03BA | 0x03DA => {
                 // Return current VGA status and reset attribute controller flip-flop
                 self.attribute_flipflop.set(false);
-                self.vga_status.get()
+                let status = self.vga_status.get();
+                // Hercules bit 7: vertical sync, low during retrace. Programs
+                // tell a Hercules card from an MDA by watching it toggle.
+                if port == 0x03BA && self.video_adapter_type == VideoAdapterType::Mda {
+                    if status & 0x08 != 0 {
+                        status & 0x7F
+                    } else {
+                        status | 0x80
+                    }
+                } else {
+                    status
+                }
             }
             // Port 0x3C0 - Attribute Controller Index/Data (write only, but allow reads for debugging)
             0x3C0 => self.attribute_index.get(),
@@ -884,10 +1569,27 @@ impl PcBus {
                     0xFF
                 }
             }
+            // Port 0x3B8 - Hercules Display Mode Control Register
+            0x3B8 => self.hercules_mode_control.get(),
+            // Port 0x3BF - Hercules Configuration Switch
+            0x3BF => self.hercules_config.get(),
             // Port 0x3D8 - CGA Mode Control Register
             0x3D8 => self.cga_mode_control.get(),
             // Port 0x3D9 - CGA Color Select Register
             0x3D9 => self.cga_color_select.get(),
+            // AdLib / Sound Blaster FM status
+            0x388 | 0x228 => self.opl.read_status(),
+            // Sound Blaster DSP
+            p if SoundBlaster::handles_port(p) => self.sound_blaster.io_read(p),
+            // 8250 UARTs (COM1/COM2)
+            0x3F8..=0x3FF => self.serial[0].io_read(port - COM1_BASE_PORT),
+            0x2F8..=0x2FF => self.serial[1].io_read(port - COM2_BASE_PORT),
+            // Parallel printer port (LPT1)
+            0x378..=0x37A => self.parallel.io_read(port - LPT1_BASE_PORT),
+            // Game control adapter (joysticks)
+            GAME_PORT => self.game_port.read(),
+            // DMA controller
+            p if DmaController::handles_port(p) => self.dma.io_read(p),
             _ => 0xFF, // Default for unimplemented ports
         };
 
@@ -902,6 +1604,10 @@ impl PcBus {
     /// Write to an I/O port
     pub fn io_write(&mut self, port: u16, val: u8) {
         match port {
+            // 8259A PIC
+            PIC_COMMAND_PORT | PIC_DATA_PORT => self.pic.io_write(port, val),
+            // MC146818 RTC/CMOS
+            RTC_INDEX_PORT | RTC_DATA_PORT => self.rtc.io_write(port, val),
             // PIT Channel 0 (system timer)
             0x40 => {
                 self.pit.write_channel(0, val);
@@ -920,83 +1626,29 @@ impl PcBus {
             }
             // Port B (speaker control, keyboard acknowledge, etc.)
             0x61 => {
-                self.speaker_gate = (val & 0x01) != 0;
-                // Bit 1: speaker data (directly drives speaker)
-                // We'll use this in combination with PIT channel 2
-            }
-            // Port 0x60 - Keyboard controller data port
-            0x60 => {
-                // Log data writes for debugging
-                log(LogCategory::Interrupts, LogLevel::Debug, || {
-                    format!(
-                        "KB controller data write: 0x{:02X} (command was 0x{:02X})",
-                        val, self.kb_controller_command
-                    )
-                });
-
-                self.kb_last_was_command.set(false); // Data write to port 60h
-                self.kb_input_buffer_full.set(true); // Buffer becomes full when data written
-
-                // When command is 0xD1 (Write Output Port), update output port
-                if self.kb_controller_command == 0xD1 {
-                    self.kb_controller_output_port = val;
-                    // Bit 1 controls A20 gate
-                    let a20_enabled = (val & 0x02) != 0;
-                    self.xms.set_a20_enabled(a20_enabled);
+                // A program taking over the speaker cancels a BIOS beep
+                self.bios_beep = None;
+                self.set_speaker_bits(val);
+            }
+            // Keyboard controller data and command ports
+            0x60 | 0x64 => {
+                let a20 = if port == 0x60 {
+                    self.kbc.write_data(val)
+                } else {
+                    self.kbc.write_command(val)
+                };
+                // The output port's bit 1 drives the A20 gate (HIMEM.SYS)
+                if let Some(enabled) = a20 {
+                    self.xms.set_a20_enabled(enabled);
                     log(LogCategory::Interrupts, LogLevel::Debug, || {
                         format!(
                             "A20 gate set to: {}",
-                            if a20_enabled { "enabled" } else { "disabled" }
+                            if enabled { "enabled" } else { "disabled" }
                         )
                     });
-                    self.kb_controller_command = 0; // Clear command
                 }
-            }
-            // Port 0x64 - Keyboard controller command port
-            0x64 => {
-                // Store command for next data port access
-                self.kb_controller_command = val;
-                self.kb_input_buffer_full.set(true); // Input buffer now full
-                self.kb_last_was_command.set(true); // Command write to port 64h
-
-                // Log keyboard controller commands for debugging HIMEM.SYS
-                log(LogCategory::Interrupts, LogLevel::Debug, || {
-                    format!("KB controller command: 0x{:02X}", val)
-                });
-
-                // Handle immediate commands that don't need data port access
-                match val {
-                    0xFF => {
-                        // Reset keyboard controller
-                        // Preserve A20 state during reset (don't force it to a specific value)
-                        // Only reset the command register
-                        self.kb_controller_command = 0;
-                        self.kb_input_buffer_full.set(false); // Reset clears buffer
-                        log(LogCategory::Interrupts, LogLevel::Debug, || {
-                            let a20_state = if self.xms.is_a20_enabled() {
-                                "enabled"
-                            } else {
-                                "disabled"
-                            };
-                            format!("KB controller reset - A20 state preserved ({})", a20_state)
-                        });
-                    }
-                    0xD0 => {
-                        // Read Output Port - next read from 0x60 returns output port
-                        // Command stored, will be handled on port 0x60 read
-                        // Input buffer clears immediately after accepting command
-                        self.kb_input_buffer_full.set(false);
-                    }
-                    0xD1 => {
-                        // Write Output Port - next write to 0x60 sets output port
-                        // Command stored, will be handled on port 0x60 write
-                        // Input buffer clears immediately (real hardware clears in microseconds)
-                        self.kb_input_buffer_full.set(false);
-                    }
-                    _ => {
-                        // Other commands stored but mostly ignored
-                        self.kb_input_buffer_full.set(false);
-                    }
+                if self.kbc.take_irq_edge() {
+                    self.pic.raise_irq(KEYBOARD_IRQ);
                 }
             }
             // Port 0x92 - System Control Port A (PS/2)
@@ -1098,6 +1750,17 @@ impl PcBus {
                     self.crtc_data[index].set(val);
                 }
             }
+            // Port 0x3B8 - Hercules Display Mode Control Register
+            // Bit 1: graphics mode, bit 3: video enable, bit 5: blink,
+            // bit 7: display page 1 (B8000h)
+            0x3B8 => {
+                self.hercules_mode_control.set(val);
+            }
+            // Port 0x3BF - Hercules Configuration Switch
+            // Bit 0: allow graphics mode, bit 1: map page 1 at B8000h
+            0x3BF => {
+                self.hercules_config.set(val & 0x03);
+            }
             // Port 0x3D8 - CGA Mode Control Register
             0x3D8 => {
                 self.cga_mode_control.set(val);
@@ -1106,9 +1769,182 @@ impl PcBus {
             0x3D9 => {
                 self.cga_color_select.set(val);
             }
+            // AdLib / Sound Blaster FM address and data
+            0x388 | 0x228 => self.opl.write_address(val),
+            0x389 | 0x229 => self.opl.write_data(val),
+            // Sound Blaster DSP
+            p if SoundBlaster::handles_port(p) => self.sound_blaster.io_write(p, val),
+            // 8250 UARTs (COM1/COM2)
+            0x3F8..=0x3FF => self.serial[0].io_write(port - COM1_BASE_PORT, val),
+            0x2F8..=0x2FF => self.serial[1].io_write(port - COM2_BASE_PORT, val),
+            // Parallel printer port (LPT1)
+            0x378..=0x37A => {
+                self.parallel.io_write(port - LPT1_BASE_PORT, val);
+                if self.parallel.take_irq_request() {
+                    self.pic.raise_irq(LPT1_IRQ);
+                }
+            }
+            // Any write fires the joystick one-shots
+            GAME_PORT => self.game_port.trigger(),
+            // DMA controller
+            p if DmaController::handles_port(p) => self.dma.io_write(p, val),
             _ => {} // Ignore writes to unimplemented ports
         }
     }
+
+    /// Drive the PIT channel 2 gate (bit 0) and speaker data (bit 1)
+    fn set_speaker_bits(&mut self, val: u8) {
+        self.speaker_gate = (val & 0x01) != 0;
+        self.speaker_data = (val & 0x02) != 0;
+        self.pit.set_gate(2, self.speaker_gate);
+    }
+
+    /// Signal currently on the speaker line
+    fn speaker_input(&self) -> SpeakerInput {
+        if !self.speaker_data {
+            return SpeakerInput::Level(false);
+        }
+        match self.pit.speaker_square_wave_hz() {
+            Some(hz) => SpeakerInput::Square(hz),
+            None => SpeakerInput::Level(self.pit.speaker_output()),
+        }
+    }
+
+    /// Sound a BIOS beep: a square wave with PIT `divisor` for `duration_us`
+    ///
+    /// Programs channel 2 and opens the speaker like the BIOS beep routine,
+    /// then restores port 0x61 once the time has passed in [`Self::clock_audio`].
+    /// A beep already in progress is left to finish.
+    pub fn bios_beep(&mut self, divisor: u16, duration_us: f64) {
+        if self.bios_beep_active() {
+            return;
+        }
+        let previous = (self.speaker_gate as u8) | ((self.speaker_data as u8) << 1);
+        self.pit.write_control(0b1011_0110); // Channel 2, low/high byte, mode 3
+        self.pit.write_channel(2, divisor as u8);
+        self.pit.write_channel(2, (divisor >> 8) as u8);
+        self.set_speaker_bits(previous | 0x03);
+        self.bios_beep = Some((duration_us, previous));
+    }
+
+    /// Whether a BIOS beep is still sounding
+    pub fn bios_beep_active(&self) -> bool {
+        self.bios_beep.is_some()
+    }
+
+    /// Advance the sound hardware by executed CPU cycles
+    ///
+    /// Runs the OPL2 timers, the PC speaker and any BIOS beep, and lets the
+    /// Sound Blaster pull PCM bytes from conventional memory through DMA.
+    pub fn clock_audio(&mut self, cycles: u32, cpu_speed_mhz: f64) {
+        let us = cycles as f64 / cpu_speed_mhz;
+        self.opl.advance(us);
+
+        // End a BIOS beep exactly on time, even partway through this step
+        let mut speaker_us = us;
+        if let Some((remaining, previous)) = self.bios_beep {
+            if remaining > us {
+                self.bios_beep = Some((remaining - us, previous));
+            } else {
+                self.speaker.advance(remaining, self.speaker_input());
+                speaker_us = us - remaining;
+                self.bios_beep = None;
+                self.set_speaker_bits(previous);
+            }
+        }
+        self.speaker.advance(speaker_us, self.speaker_input());
+
+        let dma = &mut self.dma;
+        let ram = &self.ram;
+        self.sound_blaster.advance(us, || {
+            dma.transfer(SB_DMA_CHANNEL)
+                .map(|addr| ram.get(addr as usize).copied().unwrap_or(0xFF))
+        });
+        if self.sound_blaster.irq_requested() {
+            self.pic.raise_irq(SB_IRQ);
+            self.sound_blaster.clear_irq_request();
+        }
+    }
+
+    /// Press or release a key on the keyboard
+    ///
+    /// Updates the BIOS keyboard state and sends the make or break code
+    /// through the keyboard controller, raising IRQ 1.
+    pub fn key_event(&mut self, key: u8, pressed: bool) {
+        if pressed {
+            self.keyboard.key_press(key);
+        } else {
+            self.keyboard.key_release(key);
+        }
+        self.kbc.key_event(key, pressed);
+        if self.kbc.take_irq_edge() {
+            self.pic.raise_irq(KEYBOARD_IRQ);
+        }
+    }
+
+    /// Advance the keyboard controller by executed CPU cycles
+    ///
+    /// Sends the next pending scancode byte and repeats a held key at the
+    /// typematic rate. Returns whether IRQ 1 was raised.
+    pub fn clock_keyboard(&mut self, cycles: u32, cpu_speed_mhz: f64) -> bool {
+        if let Some(key) = self.kbc.advance(cycles as f64 / cpu_speed_mhz) {
+            self.keyboard.key_press(key);
+        }
+        let irq = self.kbc.take_irq_edge();
+        if irq {
+            self.pic.raise_irq(KEYBOARD_IRQ);
+        }
+        irq
+    }
+
+    /// Advance the game port's joystick one-shots by executed CPU cycles
+    pub fn clock_game_port(&mut self, cycles: u32, cpu_speed_mhz: f64) {
+        self.game_port.advance(cycles as f64 / cpu_speed_mhz);
+    }
+
+    /// Advance the serial ports by executed CPU cycles
+    ///
+    /// Exchanges characters with the host connections and raises IRQ 4/3
+    /// when a UART's interrupt output goes active.
+    pub fn clock_serial(&mut self, cycles: u32, cpu_speed_mhz: f64) {
+        let us = cycles as f64 / cpu_speed_mhz;
+        for (uart, irq) in self.serial.iter_mut().zip([COM1_IRQ, COM2_IRQ]) {
+            uart.advance(us);
+            if uart.take_irq_edge() {
+                self.pic.raise_irq(irq);
+            }
+        }
+    }
+
+    /// Mix FM, digital audio and PC speaker output into `count` samples
+    pub fn audio_samples(&mut self, count: usize) -> Vec<i16> {
+        let fm = self.opl.generate_samples(count);
+        let pcm = self.sound_blaster.take_samples(count);
+        let speaker = self.speaker.take_samples(count);
+        fm.iter()
+            .zip(pcm.iter())
+            .zip(speaker.iter())
+            .map(|((&a, &b), &c)| (a as i32 + b as i32 + c as i32).clamp(-32768, 32767) as i16)
+            .collect()
+    }
+}
+
+/// Check for the 0xAA55 signature at the end of a disk's first sector
+fn has_boot_signature(image: &[u8]) -> bool {
+    image.len() >= 512 && image[510] == 0x55 && image[511] == 0xAA
+}
+
+/// Floppy image of a CD booted with El Torito floppy emulation
+fn cd_floppy_image(cd_boot: Option<BootEntry>, cdrom: Option<&[u8]>) -> Option<&[u8]> {
+    match cd_boot {
+        Some(
+            entry @ BootEntry {
+                media: BootMedia::Floppy(_),
+                ..
+            },
+        ) => entry.image(cdrom?),
+        _ => None,
+    }
 }
 
 impl Default for PcBus {
@@ -1138,6 +1974,10 @@ impl Memory8086 for PcBus {
                     0xFF
                 }
             }
+            // EGA/VGA planar graphics memory
+            0xA0000..=0xAFFFF if self.vga_graphics_active() => self
+                .vga
+                .read((effective_addr - 0xA0000) as usize, &self.vga_access()),
             // Video memory (128KB)
             0xA0000..=0xBFFFF => {
                 let offset = (effective_addr - 0xA0000) as usize;
@@ -1147,8 +1987,11 @@ impl Memory8086 for PcBus {
                     0xFF
                 }
             }
-            // ROM area (256KB) - includes BIOS
+            // ROM area (256KB) - includes BIOS and the EMS page frame
             0xC0000..=0xFFFFF => {
+                if let Some(val) = self.ems.read(effective_addr) {
+                    return val;
+                }
                 let offset = (effective_addr - 0xC0000) as usize;
                 if offset < self.rom.len() {
                     self.rom[offset]
@@ -1196,6 +2039,12 @@ impl Memory8086 for PcBus {
                     });
                 }
             }
+            // EGA/VGA planar graphics memory
+            0xA0000..=0xAFFFF if self.vga_graphics_active() => {
+                let access = self.vga_access();
+                self.vga
+                    .write((effective_addr - 0xA0000) as usize, val, &access);
+            }
             // Video memory (128KB) - writable
             0xA0000..=0xBFFFF => {
                 let offset = (effective_addr - 0xA0000) as usize;
@@ -1203,9 +2052,12 @@ impl Memory8086 for PcBus {
                     self.vram[offset] = val;
                 }
             }
-            // ROM area - read-only, writes are ignored
+            // ROM area - read-only, except for mapped EMS pages and
+            // allocated upper memory blocks
             0xC0000..=0xFFFFF => {
-                // ROM writes are ignored
+                if !self.ems.write(effective_addr, val) && self.xms.is_umb_address(effective_addr) {
+                    self.rom[(effective_addr - 0xC0000) as usize] = val;
+                }
             }
             // Extended memory (starts at 1MB = 0x100000)
             0x100000..=0xFFFFFFFF => {
@@ -1380,6 +2232,135 @@ mod tests {
         assert_eq!(bus.hard_drive().unwrap().len(), 10 * 1024 * 1024);
     }
 
+    #[test]
+    fn test_cmos_setup_follows_configuration() {
+        let mut bus = PcBus::with_memory_kb(2048);
+
+        let read_cmos = |bus: &mut PcBus, index: u8| {
+            bus.io_write(RTC_INDEX_PORT, index);
+            bus.io_read(RTC_DATA_PORT)
+        };
+
+        // No drives, CGA, 640KB base and 1408KB extended memory
+        assert_eq!(read_cmos(&mut bus, 0x10), 0x00);
+        assert_eq!(read_cmos(&mut bus, 0x12), 0x00);
+        assert_eq!(read_cmos(&mut bus, 0x14), 0x20);
+        assert_eq!(read_cmos(&mut bus, 0x16), 0x02); // 640 = 0280h
+        assert_eq!(read_cmos(&mut bus, 0x18), 0x05); // 1408 = 0580h
+
+        // Mounting drives updates the drive types and checksum
+        bus.mount_floppy_a(vec![0; 1440 * 1024]);
+        bus.mount_floppy_b(vec![0; 360 * 1024]);
+        bus.mount_hard_drive(vec![0; 10 * 1024 * 1024]);
+        bus.set_video_adapter_type(VideoAdapterType::Vga);
+        assert_eq!(read_cmos(&mut bus, 0x10), 0x41);
+        assert_eq!(read_cmos(&mut bus, 0x12), 0xF0);
+        assert_eq!(read_cmos(&mut bus, 0x19), 47);
+        assert_eq!(read_cmos(&mut bus, 0x14), 0x41);
+        assert!(bus.rtc.checksum_valid());
+
+        bus.unmount_floppy_b();
+        assert_eq!(read_cmos(&mut bus, 0x10), 0x40);
+
+        // CMOS RAM survives a reset
+        bus.io_write(RTC_INDEX_PORT, 0x40);
+        bus.io_write(RTC_DATA_PORT, 0x5A);
+        bus.reset();
+        assert_eq!(read_cmos(&mut bus, 0x40), 0x5A);
+        assert_eq!(read_cmos(&mut bus, 0x10), 0x40);
+    }
+
+    #[test]
+    fn test_serial_ports_raise_irqs() {
+        // COM2 in loopback with the received data interrupt and OUT2 enabled
+        let mut bus = PcBus::new();
+        bus.io_write(0x2F9, 0x01);
+        bus.io_write(0x2FC, 0x18);
+        bus.io_write(0x2F8, 0x42);
+        bus.clock_serial(100_000, 4.77);
+        assert_eq!(bus.pic.acknowledge(), Some(0x0B)); // IRQ 3
+        assert_eq!(bus.io_read(0x2FA), 0x04); // Received data interrupt
+        assert_eq!(bus.io_read(0x2F8), 0x42);
+        assert_eq!(bus.io_read(0x2FA), 0x01);
+        bus.pic.end_of_interrupt();
+
+        // COM1 stays quiet without OUT2
+        bus.io_write(0x3F9, 0x02);
+        bus.clock_serial(100_000, 4.77);
+        assert_eq!(bus.pic.pending_irq(), None);
+        bus.io_write(0x3FC, 0x08);
+        bus.clock_serial(1, 4.77);
+        assert_eq!(bus.pic.pending_irq(), Some(4));
+    }
+
+    #[test]
+    fn test_keyboard_scancodes_through_ports() {
+        use crate::keyboard::SCANCODE_LEFT;
+
+        let mut bus = PcBus::new();
+        bus.key_event(SCANCODE_LEFT, true);
+        // The BIOS buffer gets the key, port 60h the E0-prefixed make code
+        assert!(bus.keyboard.has_key(SCANCODE_LEFT));
+        assert_eq!(bus.pic.acknowledge(), Some(0x09));
+        assert_eq!(bus.io_read(0x64) & 0x01, 0x01);
+        assert_eq!(bus.io_read(0x60), 0xE0);
+        bus.pic.end_of_interrupt();
+
+        assert!(bus.clock_keyboard(4_770, 4.77));
+        assert_eq!(bus.pic.acknowledge(), Some(0x09));
+        assert_eq!(bus.io_read(0x60), 0x4B);
+        bus.pic.end_of_interrupt();
+
+        // Key releases reach the port but not the BIOS buffer
+        bus.keyboard.clear();
+        bus.key_event(SCANCODE_LEFT, false);
+        assert!(!bus.keyboard.has_data());
+        bus.clock_keyboard(4_770, 4.77);
+        assert_eq!(bus.io_read(0x60), 0xE0);
+        bus.clock_keyboard(4_770, 4.77);
+        assert_eq!(bus.io_read(0x60), 0xCB);
+    }
+
+    #[test]
+    fn test_keyboard_controller_a20_gate() {
+        let mut bus = PcBus::new();
+        bus.io_write(0x64, 0xD1);
+        bus.io_write(0x60, 0xDD);
+        assert!(!bus.xms.is_a20_enabled());
+        bus.io_write(0x64, 0xD0);
+        assert_eq!(bus.io_read(0x60), 0xDD);
+        bus.io_write(0x64, 0xDF);
+        assert!(bus.xms.is_a20_enabled());
+    }
+
+    #[test]
+    fn test_parallel_port_prints_and_raises_irq() {
+        let mut bus = PcBus::new();
+        let printed = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        struct Sink(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+        impl std::io::Write for Sink {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+        bus.parallel.attach(Box::new(Sink(printed.clone())));
+
+        // Strobe a byte with the acknowledge interrupt enabled
+        bus.io_write(0x378, b'P');
+        bus.io_write(0x37A, 0x1D);
+        bus.io_write(0x37A, 0x1C);
+        assert_eq!(*printed.lock().unwrap(), b"P");
+        assert_eq!(bus.pic.pending_irq(), Some(LPT1_IRQ));
+
+        // The printer stays attached across a reset
+        bus.reset();
+        assert_eq!(bus.io_read(0x379), 0xDF);
+    }
+
     #[test]
     fn test_vga_status_register() {
         let bus = PcBus::new();
@@ -1557,6 +2538,30 @@ mod tests {
         assert_eq!(bus.io_read(0x3D9), 0x30);
     }
 
+    #[test]
+    fn test_hercules_graphics_page() {
+        let mut bus = PcBus::new();
+        bus.io_write(0x3B8, 0x0A); // Graphics, video enabled
+        assert_eq!(bus.hercules_graphics_page(), None); // Not a Hercules card
+        assert_eq!(bus.text_buffer_offset(), COLOR_TEXT_OFFSET);
+
+        bus.set_video_adapter_type(VideoAdapterType::Mda);
+        assert_eq!(bus.text_buffer_offset(), MONO_TEXT_OFFSET);
+        // Graphics stays locked out until the configuration switch allows it
+        assert_eq!(bus.hercules_graphics_page(), None);
+        bus.io_write(0x3BF, 0x01);
+        assert_eq!(bus.hercules_graphics_page(), Some(MONO_TEXT_OFFSET));
+
+        // Page 1 needs its own configuration bit
+        bus.io_write(0x3B8, 0x8A);
+        assert_eq!(bus.hercules_graphics_page(), Some(MONO_TEXT_OFFSET));
+        bus.io_write(0x3BF, 0x03);
+        assert_eq!(bus.hercules_graphics_page(), Some(COLOR_TEXT_OFFSET));
+
+        bus.io_write(0x3B8, 0x28); // Back to text
+        assert_eq!(bus.hercules_graphics_page(), None);
+    }
+
     #[test]
     fn test_vga_status_resets_attribute_flipflop() {
         let bus = PcBus::new();
@@ -1573,4 +2578,172 @@ mod tests {
         bus.io_read(0x03BA);
         assert!(!bus.attribute_flipflop.get());
     }
+
+    #[test]
+    fn test_vga_mode_13h_and_mode_x() {
+        let mut bus = PcBus::new();
+        bus.set_video_adapter_type(VideoAdapterType::Vga);
+        assert!(bus.vga_graphics_frame().is_none());
+
+        bus.set_video_mode(VGA_MODE_13H, true);
+        bus.write(0xA0000 + 10 * 320 + 3, 9);
+        assert_eq!(bus.read(0xA0000 + 10 * 320 + 3), 9);
+        let (width, height, pixels) = bus.vga_graphics_frame().unwrap();
+        assert_eq!((width, height), (320, 200));
+        assert_eq!(pixels[10 * 320 + 3], 9);
+        // The text buffer is untouched
+        assert_eq!(bus.vram()[10 * 320 + 3], 0);
+
+        // DAC writes through ports 3C8h/3C9h
+        bus.io_write(0x3C8, 9);
+        for component in [63, 32, 0] {
+            bus.io_write(0x3C9, component);
+        }
+        assert_eq!(&bus.dac_palette()[27..30], &[63, 32, 0]);
+
+        // Unchain (Mode X) and write all four planes at once
+        bus.io_write(0x3C4, 0x04);
+        bus.io_write(0x3C5, 0x06);
+        bus.io_write(0x3D4, 0x14);
+        bus.io_write(0x3D5, 0x00);
+        bus.io_write(0x3D4, 0x17);
+        bus.io_write(0x3D5, 0xE3);
+        bus.io_write(0x3C4, 0x02);
+        bus.io_write(0x3C5, 0x0F);
+        bus.write(0xA0000 + 80, 5);
+        let (_, _, pixels) = bus.vga_graphics_frame().unwrap();
+        assert_eq!(&pixels[320..325], &[5, 5, 5, 5, 0]);
+
+        // Back in text mode A0000h is plain memory again
+        bus.set_video_mode(0x03, true);
+        assert!(bus.vga_graphics_frame().is_none());
+    }
+
+    #[test]
+    fn test_mode_13h_needs_vga() {
+        let mut bus = PcBus::new();
+        bus.set_video_mode(VGA_MODE_13H, true);
+        bus.write(0xA0000, 7);
+        assert_eq!(bus.vram()[0], 7);
+        assert!(bus.vga_graphics_frame().is_none());
+    }
+
+    #[test]
+    fn test_planar_16_color_modes() {
+        let mut bus = PcBus::new();
+        bus.set_video_adapter_type(VideoAdapterType::Vga);
+        bus.set_video_mode(0x12, true);
+
+        // Planes 0 and 3 (color 9) for the first eight pixels of line 1
+        bus.io_write(0x3C4, 0x02);
+        bus.io_write(0x3C5, 0x09);
+        bus.write(0xA0000 + 80, 0xFF);
+        // Reads come from the read map plane
+        bus.io_write(0x3CE, 0x04);
+        bus.io_write(0x3CF, 0x03);
+        assert_eq!(bus.read(0xA0000 + 80), 0xFF);
+
+        let (width, height, pixels) = bus.vga_graphics_frame().unwrap();
+        assert_eq!((width, height), (640, 480));
+        // Default palette register 9 is EGA color 39h (light blue)
+        assert_eq!(pixels[640], 0x39);
+        assert_eq!(pixels[648], 0x00);
+        assert_eq!(&bus.dac_palette()[0x39 * 3..0x39 * 3 + 3], &[21, 21, 63]);
+
+        // Attribute palette register 9 through port 3C0h
+        bus.io_read(0x3DA);
+        bus.io_write(0x3C0, 0x09);
+        bus.io_write(0x3C0, 0x04);
+        let (_, _, pixels) = bus.vga_graphics_frame().unwrap();
+        assert_eq!(pixels[640], 0x04);
+
+        // An EGA has no DAC: pixels are 6-bit EGA colors
+        bus.set_video_adapter_type(VideoAdapterType::Ega);
+        assert!(bus.vga_graphics_frame().is_none());
+        bus.set_video_mode(0x0D, true);
+        bus.write(0xA0000, 0x80);
+        let (width, height, pixels) = bus.vga_graphics_frame().unwrap();
+        assert_eq!((width, height), (320, 200));
+        assert_eq!(&pixels[..2], &[0x3F, 0x00]);
+    }
+
+    #[test]
+    fn test_sound_blaster_dma_playback() {
+        let mut bus = PcBus::new();
+
+        // Sample data in conventional memory
+        for i in 0..64 {
+            bus.ram[0x10000 + i] = if i % 2 == 0 { 0xFF } else { 0x00 };
+        }
+
+        // Program DMA channel 1: single-cycle, read, 64 bytes at 0x10000
+        bus.io_write(0x0A, 0x05);
+        bus.io_write(0x0C, 0x00);
+        bus.io_write(0x0B, 0x49);
+        bus.io_write(0x02, 0x00);
+        bus.io_write(0x02, 0x00);
+        bus.io_write(0x83, 0x01);
+        bus.io_write(0x03, 63);
+        bus.io_write(0x03, 0);
+        bus.io_write(0x0A, 0x01);
+
+        // Reset the DSP and start a 64-byte transfer at ~22 kHz
+        bus.io_write(0x226, 1);
+        bus.io_write(0x226, 0);
+        assert_eq!(bus.io_read(0x22A), 0xAA);
+        for byte in [0xD1, 0x40, 211, 0x14, 63, 0] {
+            bus.io_write(0x22C, byte);
+        }
+
+        // 10 ms of emulated time at 4.77 MHz
+        bus.clock_audio(47_700, 4.77);
+        // Block end raised IRQ 5 on the PIC
+        assert_eq!(bus.pic.acknowledge(), Some(0x0D));
+        assert_eq!(bus.io_read(0x08) & 0x02, 0x02);
+
+        let samples = bus.audio_samples(441);
+        assert!(samples.iter().any(|&s| s > 0x4000));
+        assert!(samples.iter().any(|&s| s < -0x4000));
+    }
+
+    #[test]
+    fn test_pc_speaker_tone() {
+        let mut bus = PcBus::new();
+
+        // Channel 2 at ~1 kHz, gate and data on for 50 ms
+        bus.io_write(0x43, 0xB6);
+        bus.io_write(0x42, 0xA9);
+        bus.io_write(0x42, 0x04);
+        bus.io_write(0x61, 0x03);
+        assert_eq!(bus.io_read(0x61) & 0x03, 0x03);
+        bus.clock_audio(238_500, 4.77);
+        bus.io_write(0x61, 0x00);
+        bus.clock_audio(238_500, 4.77);
+
+        let samples = bus.audio_samples(4410);
+        let loud = |s: &[i16]| s.iter().filter(|s| s.abs() > 4000).count();
+        assert!(loud(&samples[..2205]) > 1500);
+        assert_eq!(loud(&samples[2900..]), 0);
+    }
+
+    #[test]
+    fn test_bios_beep_restores_port_61() {
+        let mut bus = PcBus::new();
+        bus.bios_beep(1331, 100_000.0);
+        assert!(bus.bios_beep_active());
+        assert_eq!(bus.io_read(0x61) & 0x03, 0x03);
+
+        // 60 ms: still sounding
+        bus.clock_audio(286_200, 4.77);
+        assert!(bus.bios_beep_active());
+        // 60 ms more: the beep ended after 100 ms and the speaker is closed
+        bus.clock_audio(286_200, 4.77);
+        assert!(!bus.bios_beep_active());
+        assert_eq!(bus.io_read(0x61) & 0x03, 0x00);
+
+        let samples = bus.audio_samples(5292);
+        let loud = |s: &[i16]| s.iter().filter(|s| s.abs() > 4000).count();
+        assert!(loud(&samples[..4410]) > 3000);
+        assert_eq!(loud(&samples[5000..]), 0);
+    }
 }
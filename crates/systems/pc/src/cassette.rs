@@ -0,0 +1,495 @@
+//! IBM PC cassette interface (INT 15h AH=00h-03h)
+//!
+//! The original IBM PC 5150 had a cassette port driven by PIT channel 2 and
+//! read back through port 0x62. The BIOS exposes it through INT 15h:
+//! - AH=00h: Motor on
+//! - AH=01h: Motor off
+//! - AH=02h: Read data blocks
+//! - AH=03h: Write data blocks
+//!
+//! Tape data is modeled as a bit stream. On tape each bit is one cycle of a
+//! square wave: 0.5 ms for a 0 bit and 1 ms for a 1 bit. A record consists of
+//! a leader of 256 0xFF bytes, a single 0 sync bit, the sync byte 0x16, one or
+//! more 256-byte data blocks each followed by an inverted CRC-16-CCITT, and a
+//! 4-byte 0xFF trailer.
+//!
+//! Supported image formats:
+//! - `.cas`: raw tape bit stream packed MSB first
+//! - `.wav`: PCM recording of a real tape (8/16-bit, mono or stereo)
+
+use emu_core::logging::{log, LogCategory, LogLevel};
+
+/// Bytes of data in a cassette block
+const BLOCK_SIZE: usize = 256;
+/// Sync byte that follows the leader and sync bit
+const SYNC_BYTE: u8 = 0x16;
+/// Leader length written by the BIOS (in 0xFF bytes)
+const LEADER_BYTES: usize = 256;
+/// Minimum number of consecutive 1 bits accepted as a leader when reading
+const MIN_LEADER_BITS: usize = 64;
+/// Trailer length written by the BIOS (in 0xFF bytes)
+const TRAILER_BYTES: usize = 4;
+
+/// Errors reported by cassette reads, using the BIOS AH status codes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteError {
+    /// CRC mismatch in a data block (AH=01h)
+    Crc,
+    /// Tape ended in the middle of a block (AH=02h)
+    DataLost,
+    /// No leader/sync found before the end of tape (AH=04h)
+    NoData,
+}
+
+impl CassetteError {
+    /// BIOS status code returned in AH
+    pub fn status(self) -> u8 {
+        match self {
+            CassetteError::Crc => 0x01,
+            CassetteError::DataLost => 0x02,
+            CassetteError::NoData => 0x04,
+        }
+    }
+}
+
+/// Cassette tape image
+pub struct Tape {
+    /// Tape contents, one entry per bit
+    bits: Vec<bool>,
+    /// Current tape position (bit index)
+    position: usize,
+    /// Set when data has been recorded onto the tape
+    modified: bool,
+}
+
+impl Tape {
+    /// Create an empty (blank) tape
+    pub fn blank() -> Self {
+        Self {
+            bits: Vec::new(),
+            position: 0,
+            modified: false,
+        }
+    }
+
+    /// Load a tape image, detecting WAV recordings by their RIFF header and
+    /// treating anything else as a packed `.cas` bit stream
+    pub fn from_image(data: &[u8]) -> Result<Self, String> {
+        let bits = if data.starts_with(b"RIFF") {
+            decode_wav(data)?
+        } else {
+            data.iter()
+                .flat_map(|&byte| (0..8).rev().map(move |i| (byte >> i) & 1 != 0))
+                .collect()
+        };
+
+        log(LogCategory::Bus, LogLevel::Info, || {
+            format!("PC Cassette: Loaded tape ({} bits)", bits.len())
+        });
+
+        Ok(Self {
+            bits,
+            position: 0,
+            modified: false,
+        })
+    }
+
+    /// Export the tape as a packed `.cas` bit stream
+    pub fn to_cas(&self) -> Vec<u8> {
+        self.bits
+            .chunks(8)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .enumerate()
+                    .fold(0u8, |acc, (i, &bit)| acc | ((bit as u8) << (7 - i)))
+            })
+            .collect()
+    }
+
+    /// True if data has been recorded since the tape was loaded
+    pub fn is_modified(&self) -> bool {
+        self.modified
+    }
+
+    /// Rewind to the start of the tape
+    pub fn rewind(&mut self) {
+        self.position = 0;
+    }
+
+    /// Read the next record, returning up to `count` data bytes.
+    ///
+    /// Like the BIOS, whole 256-byte blocks are read until `count` bytes
+    /// have been transferred, so any remainder of the last block is skipped.
+    /// On a CRC error the bytes read so far are returned with the error.
+    pub fn read(&mut self, count: usize) -> (Vec<u8>, Option<CassetteError>) {
+        if !self.find_sync() {
+            self.position = self.bits.len();
+            return (Vec::new(), Some(CassetteError::NoData));
+        }
+
+        let mut data = Vec::with_capacity(count);
+        while data.len() < count {
+            let mut block = Vec::with_capacity(BLOCK_SIZE + 2);
+            for _ in 0..BLOCK_SIZE + 2 {
+                match self.read_byte() {
+                    Some(byte) => block.push(byte),
+                    None => {
+                        let take = block.len().min(BLOCK_SIZE).min(count - data.len());
+                        data.extend_from_slice(&block[..take]);
+                        return (data, Some(CassetteError::DataLost));
+                    }
+                }
+            }
+
+            let stored_crc = u16::from_be_bytes([block[BLOCK_SIZE], block[BLOCK_SIZE + 1]]);
+            let take = BLOCK_SIZE.min(count - data.len());
+            data.extend_from_slice(&block[..take]);
+            if stored_crc != !crc16(&block[..BLOCK_SIZE]) {
+                return (data, Some(CassetteError::Crc));
+            }
+        }
+
+        (data, None)
+    }
+
+    /// Record `data` as a new record at the current position, overwriting
+    /// anything after it (as recording over a real tape would)
+    pub fn write(&mut self, data: &[u8]) {
+        self.bits.truncate(self.position);
+
+        self.push_bytes(&[0xFF; LEADER_BYTES]);
+        self.bits.push(false); // Sync bit
+        self.push_bytes(&[SYNC_BYTE]);
+
+        for chunk in data.chunks(BLOCK_SIZE) {
+            let mut block = [0u8; BLOCK_SIZE];
+            block[..chunk.len()].copy_from_slice(chunk);
+            self.push_bytes(&block);
+            self.push_bytes(&(!crc16(&block)).to_be_bytes());
+        }
+
+        self.push_bytes(&[0xFF; TRAILER_BYTES]);
+        self.position = self.bits.len();
+        self.modified = true;
+    }
+
+    fn push_bytes(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            for i in (0..8).rev() {
+                self.bits.push((byte >> i) & 1 != 0);
+            }
+        }
+    }
+
+    /// Advance past the next leader, sync bit and sync byte
+    fn find_sync(&mut self) -> bool {
+        let mut ones = 0;
+        while self.position < self.bits.len() {
+            let bit = self.bits[self.position];
+            self.position += 1;
+            if bit {
+                ones += 1;
+                continue;
+            }
+            if ones >= MIN_LEADER_BITS {
+                let start = self.position;
+                if self.read_byte() == Some(SYNC_BYTE) {
+                    return true;
+                }
+                self.position = start;
+            }
+            ones = 0;
+        }
+        false
+    }
+
+    fn read_byte(&mut self) -> Option<u8> {
+        if self.position + 8 > self.bits.len() {
+            return None;
+        }
+        let byte = self.bits[self.position..self.position + 8]
+            .iter()
+            .fold(0u8, |acc, &bit| (acc << 1) | bit as u8);
+        self.position += 8;
+        Some(byte)
+    }
+}
+
+/// Cassette port state
+pub struct Cassette {
+    /// Inserted tape, if any
+    tape: Option<Tape>,
+    /// Cassette motor relay state
+    motor_on: bool,
+}
+
+impl Cassette {
+    pub fn new() -> Self {
+        Self {
+            tape: None,
+            motor_on: false,
+        }
+    }
+
+    /// Insert a tape
+    pub fn insert(&mut self, tape: Tape) {
+        self.tape = Some(tape);
+    }
+
+    /// Eject the current tape
+    pub fn eject(&mut self) {
+        self.tape = None;
+        self.motor_on = false;
+    }
+
+    pub fn tape(&self) -> Option<&Tape> {
+        self.tape.as_ref()
+    }
+
+    pub fn tape_mut(&mut self) -> Option<&mut Tape> {
+        self.tape.as_mut()
+    }
+
+    pub fn set_motor(&mut self, on: bool) {
+        self.motor_on = on;
+    }
+
+    #[allow(dead_code)] // Exposed for debugging/port 0x61 bit 3
+    pub fn motor_on(&self) -> bool {
+        self.motor_on
+    }
+}
+
+impl Default for Cassette {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// CRC-16-CCITT (polynomial 0x1021, initial value 0xFFFF) as used by the
+/// IBM PC cassette BIOS
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0xFFFF;
+    for &byte in data {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// Decode a WAV recording into tape bits by measuring the period between
+/// rising zero crossings (< 0.75 ms = 0 bit, < 1.5 ms = 1 bit, longer = gap)
+fn decode_wav(data: &[u8]) -> Result<Vec<bool>, String> {
+    if data.len() < 12 || &data[8..12] != b"WAVE" {
+        return Err("Not a WAVE file".to_string());
+    }
+
+    let mut channels = 0usize;
+    let mut sample_rate = 0u32;
+    let mut bits_per_sample = 0u16;
+    let mut samples: Option<&[u8]> = None;
+
+    let mut offset = 12;
+    while offset + 8 <= data.len() {
+        let id = &data[offset..offset + 4];
+        let size = u32::from_le_bytes([
+            data[offset + 4],
+            data[offset + 5],
+            data[offset + 6],
+            data[offset + 7],
+        ]) as usize;
+        let body_start = offset + 8;
+        let body_end = (body_start + size).min(data.len());
+        let body = &data[body_start..body_end];
+
+        match id {
+            b"fmt " if body.len() >= 16 => {
+                let format = u16::from_le_bytes([body[0], body[1]]);
+                if format != 1 {
+                    return Err(format!("Unsupported WAV format {} (PCM only)", format));
+                }
+                channels = u16::from_le_bytes([body[2], body[3]]) as usize;
+                sample_rate = u32::from_le_bytes([body[4], body[5], body[6], body[7]]);
+                bits_per_sample = u16::from_le_bytes([body[14], body[15]]);
+            }
+            b"data" => samples = Some(body),
+            _ => {}
+        }
+
+        // Chunks are padded to an even size
+        offset = body_start + size + (size & 1);
+    }
+
+    let samples = samples.ok_or("WAV file has no data chunk")?;
+    if channels == 0 || sample_rate == 0 {
+        return Err("WAV file has no valid fmt chunk".to_string());
+    }
+    let bytes_per_sample = match bits_per_sample {
+        8 => 1,
+        16 => 2,
+        _ => return Err(format!("Unsupported WAV sample size {}", bits_per_sample)),
+    };
+
+    // Use the first channel only
+    let frame_size = bytes_per_sample * channels;
+    let levels = samples.chunks_exact(frame_size).map(|frame| {
+        if bytes_per_sample == 1 {
+            (frame[0] as i32 - 128) << 8
+        } else {
+            i16::from_le_bytes([frame[0], frame[1]]) as i32
+        }
+    });
+
+    let zero_bit_max = sample_rate as f64 * 0.00075;
+    let one_bit_max = sample_rate as f64 * 0.0015;
+    let mut bits = Vec::new();
+    let mut high = false;
+    let mut last_rise: Option<usize> = None;
+    for (i, level) in levels.enumerate() {
+        // Small hysteresis so noise around zero doesn't create edges
+        if !high && level > 1024 {
+            high = true;
+            if let Some(prev) = last_rise {
+                let period = (i - prev) as f64;
+                if period < zero_bit_max {
+                    bits.push(false);
+                } else if period < one_bit_max {
+                    bits.push(true);
+                }
+            }
+            last_rise = Some(i);
+        } else if high && level < -1024 {
+            high = false;
+        }
+    }
+
+    Ok(bits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Render tape bits as a 16-bit mono WAV file
+    fn bits_to_wav(bits: &[bool], sample_rate: u32) -> Vec<u8> {
+        let mut pcm = Vec::new();
+        for &bit in bits {
+            let half = if bit { 0.0005 } else { 0.00025 } * sample_rate as f64;
+            for level in [i16::MAX / 2, -i16::MAX / 2] {
+                for _ in 0..half.round() as usize {
+                    pcm.extend_from_slice(&level.to_le_bytes());
+                }
+            }
+        }
+        // Trailing edge so the final bit's period is measured
+        pcm.extend_from_slice(&(i16::MAX / 2).to_le_bytes());
+
+        let mut wav = Vec::new();
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(36 + pcm.len() as u32).to_le_bytes());
+        wav.extend_from_slice(b"WAVEfmt ");
+        wav.extend_from_slice(&16u32.to_le_bytes());
+        wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+        wav.extend_from_slice(&1u16.to_le_bytes()); // Mono
+        wav.extend_from_slice(&sample_rate.to_le_bytes());
+        wav.extend_from_slice(&(sample_rate * 2).to_le_bytes());
+        wav.extend_from_slice(&2u16.to_le_bytes());
+        wav.extend_from_slice(&16u16.to_le_bytes());
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&(pcm.len() as u32).to_le_bytes());
+        wav.extend_from_slice(&pcm);
+        wav
+    }
+
+    #[test]
+    fn test_write_then_read_records() {
+        let mut tape = Tape::blank();
+        let header: Vec<u8> = (0..BLOCK_SIZE as u32).map(|i| i as u8).collect();
+        let program = b"10 PRINT \"HELLO\"".to_vec();
+        tape.write(&header);
+        tape.write(&program);
+        assert!(tape.is_modified());
+
+        tape.rewind();
+        let (data, err) = tape.read(BLOCK_SIZE);
+        assert_eq!(err, None);
+        assert_eq!(data, header);
+
+        let (data, err) = tape.read(program.len());
+        assert_eq!(err, None);
+        assert_eq!(data, program);
+
+        let (data, err) = tape.read(16);
+        assert!(data.is_empty());
+        assert_eq!(err, Some(CassetteError::NoData));
+    }
+
+    #[test]
+    fn test_cas_roundtrip() {
+        let mut tape = Tape::blank();
+        tape.write(b"CASSETTE");
+
+        let mut loaded = Tape::from_image(&tape.to_cas()).unwrap();
+        assert!(!loaded.is_modified());
+        let (data, err) = loaded.read(8);
+        assert_eq!(err, None);
+        assert_eq!(data, b"CASSETTE");
+    }
+
+    #[test]
+    fn test_crc_error_detected() {
+        let mut tape = Tape::blank();
+        tape.write(b"DATA");
+        // Flip a data bit just after leader + sync bit + sync byte
+        let first_data_bit = LEADER_BYTES * 8 + 1 + 8;
+        tape.bits[first_data_bit] = !tape.bits[first_data_bit];
+
+        tape.rewind();
+        let (data, err) = tape.read(4);
+        assert_eq!(data.len(), 4);
+        assert_eq!(err, Some(CassetteError::Crc));
+    }
+
+    #[test]
+    fn test_truncated_tape_reports_data_lost() {
+        let mut tape = Tape::blank();
+        tape.write(b"DATA");
+        tape.bits.truncate(LEADER_BYTES * 8 + 1 + 8 + 100 * 8);
+
+        tape.rewind();
+        let (data, err) = tape.read(BLOCK_SIZE);
+        assert_eq!(data.len(), 100);
+        assert_eq!(err, Some(CassetteError::DataLost));
+    }
+
+    #[test]
+    fn test_wav_decode() {
+        let mut tape = Tape::blank();
+        tape.write(b"WAV TAPE");
+
+        let wav = bits_to_wav(&tape.bits, 44100);
+        let mut loaded = Tape::from_image(&wav).unwrap();
+        let (data, err) = loaded.read(8);
+        assert_eq!(err, None);
+        assert_eq!(data, b"WAV TAPE");
+    }
+
+    #[test]
+    fn test_invalid_wav() {
+        assert!(Tape::from_image(b"RIFF\0\0\0\0JUNK").is_err());
+    }
+
+    #[test]
+    fn test_error_status_codes() {
+        assert_eq!(CassetteError::Crc.status(), 0x01);
+        assert_eq!(CassetteError::DataLost.status(), 0x02);
+        assert_eq!(CassetteError::NoData.status(), 0x04);
+    }
+}
@@ -2,7 +2,27 @@
 //!
 //! This module wraps the core 8086 CPU with PC-specific initialization and state.
 
-use crate::bus::PcBus;
+use crate::bios::{
+    DOS_COMMAND_OFFSET, DOS_ENTRY_OFFSET, DOS_SHELL_OFFSET, DOS_TERMINATE_OFFSET,
+    EMS_DEVICE_SEGMENT, EMS_ENTRY_OFFSET, IRQ_HANDLER_OFFSET, MOUSE_ENTRY_OFFSET,
+    MULTIPLEX_ENTRY_OFFSET, RESET_FLAG_ADDR, RESET_VECTOR_ADDR, TIMER_CHAIN_OFFSET, WARM_BOOT_FLAG,
+    XMS_ENTRY_OFFSET, XMS_SERVICE_OFFSET,
+};
+use crate::bus::{PcBus, MDA_TEXT_MODE, VGA_MODE_13H};
+use crate::dos::{
+    self, attributes_match, dir_listing, dir_string, dos_date_time, has_wildcards, name_matches,
+    short_name, DosPath, ExeHeader, OpenFile, ParentProcess, ShellCommand, DRIVE_COUNT,
+    ENVIRONMENT, FIRST_FILE_HANDLE, MAX_COMMAND_LINE, SHELL_SEGMENT, SHELL_STACK,
+};
+use crate::dos_fs::{
+    DirEntry, DosFsError, ATTR_ARCHIVE, ATTR_DIRECTORY, ATTR_HIDDEN, ATTR_READ_ONLY, ATTR_SYSTEM,
+};
+use crate::el_torito::{BootMedia, CD_SECTOR_SIZE};
+use crate::ems::{EmsError, PAGE_MAP_SIZE};
+use crate::host_folder::display_name;
+use crate::rtc::{from_bcd, to_bcd, DateTime as RtcDateTime};
+use crate::vga::PLANAR_16_COLOR_MODES;
+use crate::xms::XmsError;
 use emu_core::cpu_8086::{Cpu8086, CpuModel, Memory8086};
 use emu_core::logging::{log, LogCategory, LogConfig, LogLevel};
 
@@ -10,11 +30,49 @@ use emu_core::logging::{log, LogCategory, LogConfig, LogLevel};
 #[allow(dead_code)]
 const VIDEO_INTERRUPT: u8 = 0x10;
 
+/// DOS error code: invalid function number
+const DOS_ERROR_INVALID_FUNCTION: u16 = 0x0001;
+
+/// DOS error code: too many open files
+const DOS_ERROR_TOO_MANY_FILES: u16 = 0x0004;
+
 /// DOS error code: invalid file handle
 const DOS_ERROR_INVALID_HANDLE: u16 = 0x0006;
 
+/// DOS error code: insufficient memory
+const DOS_ERROR_NO_MEMORY: u16 = 0x0008;
+
+/// DOS error code: invalid memory block address
+const DOS_ERROR_INVALID_BLOCK: u16 = 0x0009;
+
+/// DOS error code: invalid format (a damaged EXE header)
+const DOS_ERROR_BAD_FORMAT: u16 = 0x000B;
+
+/// DOS error code: invalid drive
+const DOS_ERROR_INVALID_DRIVE: u16 = 0x000F;
+
+/// DOS error code: no more files (find first/next)
+const DOS_ERROR_NO_MORE_FILES: u16 = 0x0012;
+
 /// BIOS Data Area: Hard drive count at 0x0040:0x0075
 const BDA_HARD_DRIVE_COUNT: u32 = 0x475;
+/// BDA COM1-COM4 base port addresses (0040:0000h, 4 words)
+const BDA_COM_PORTS: u32 = 0x400;
+/// BDA LPT1-LPT3 base port addresses (0040:0008h, 3 words)
+const BDA_LPT_PORTS: u32 = 0x408;
+/// BDA timer tick count since midnight (0040:006Ch, 4 bytes)
+const BDA_TIMER_TICKS: u32 = 0x46C;
+/// BDA midnight rollover flag (0040:0070h)
+const BDA_MIDNIGHT_FLAG: u32 = 0x470;
+
+/// Bell (BEL via INT 10h teletype): PIT divisor 1331 (~896 Hz) for 31/64 s
+const BELL_BEEP: (u16, f64) = (1331, 484_375.0);
+
+/// Rows of the BIOS text modes
+const TEXT_ROWS: u32 = 25;
+
+/// Keyboard buffer full: PIT divisor 678 (~1760 Hz) for 4/64 s
+const KEYBOARD_FULL_BEEP: (u16, f64) = (678, 62_500.0);
 
 /// Interrupt handler priority behavior
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -90,6 +148,14 @@ fn get_interrupt_priority(int_num: u8) -> InterruptPriority {
     }
 }
 
+/// Map an XMS status to the AX result of a function that only reports success
+fn xms_status(status: XmsError) -> Result<u16, XmsError> {
+    match status {
+        XmsError::Success => Ok(0x0001),
+        error => Err(error),
+    }
+}
+
 /// PC CPU wrapper
 pub struct PcCpu {
     cpu: Cpu8086<PcBus>,
@@ -170,15 +236,50 @@ impl PcCpu {
         self.cpu.es = 0x0000;
     }
 
+    /// Check if a triple fault shut the CPU down (the AT resets it)
+    pub fn is_shut_down(&self) -> bool {
+        self.cpu.is_shut_down()
+    }
+
+    /// Check if real mode execution reached the reset vector (FFFF:0000 or
+    /// F000:FFF0), where programs jump to reboot
+    pub fn at_reset_vector(&self) -> bool {
+        !self.cpu.protected_mode().is_protected_mode()
+            && self.cpu.cs_ip_linear_address() == RESET_VECTOR_ADDR
+    }
+
     /// Check if the CPU is halted (e.g., waiting for keyboard input in INT 16h)
     pub fn is_halted(&self) -> bool {
         self.cpu.is_halted()
     }
 
+    /// Check if the CPU accepts maskable interrupts (IF flag set)
+    pub fn interrupts_enabled(&self) -> bool {
+        const FLAG_IF: u32 = 0x0200;
+        (self.cpu.flags & FLAG_IF) != 0
+    }
+
+    /// Deliver the highest priority pending IRQ from the PIC
+    ///
+    /// # Returns
+    /// * `true` if an interrupt was acknowledged and delivered
+    /// * `false` if interrupts are disabled or no unmasked IRQ is pending
+    pub fn service_pending_irq(&mut self) -> bool {
+        if !self.interrupts_enabled() {
+            return false;
+        }
+        match self.cpu.memory.pic.acknowledge() {
+            Some(vector) => self.trigger_hardware_interrupt(vector),
+            None => false,
+        }
+    }
+
     /// Trigger a hardware interrupt (e.g., from PIT, keyboard controller, etc.)
     ///
-    /// For emulated hardware interrupts (INT 0x08 timer, INT 0x09 keyboard),
-    /// this calls our emulated handler directly instead of using the IVT.
+    /// The interrupt is dispatched through the IVT (or the IDT in protected
+    /// mode), so DOS programs that hook INT 08h/09h see it. Vectors still pointing at the BIOS IRQ handler are
+    /// serviced by the emulator when execution reaches F000:0380. If no vector
+    /// is installed at all, the IRQ is serviced immediately.
     ///
     /// # Arguments
     /// * `int_num` - The interrupt vector number (0x08 for timer, 0x09 for keyboard, etc.)
@@ -187,31 +288,17 @@ impl PcCpu {
     /// * `true` if the interrupt was triggered (IF flag is set)
     /// * `false` if interrupts are disabled (IF flag is clear)
     pub fn trigger_hardware_interrupt(&mut self, int_num: u8) -> bool {
-        // Check if interrupts are enabled (IF flag)
-        const FLAG_IF: u32 = 0x0200;
-        if (self.cpu.flags & FLAG_IF) == 0 {
+        if !self.interrupts_enabled() {
             return false;
         }
 
-        // For emulated hardware interrupts, call our handler directly
-        // This ensures the emulated behavior (e.g., timer tick counter increment) occurs
-        match int_num {
-            0x08 => {
-                // Timer interrupt - call hardware timer handler (doesn't skip instruction bytes)
-                self.handle_hardware_timer_interrupt();
-                true
-            }
-            0x09 => {
-                // Keyboard interrupt - call our emulated handler
-                // TODO: Create handle_hardware_keyboard_interrupt for consistency
-                self.handle_int09h();
-                true
-            }
-            _ => {
-                // For other interrupts, use the normal hardware interrupt mechanism
-                self.cpu.trigger_hardware_interrupt(int_num)
-            }
+        if !self.cpu.protected_mode().is_protected_mode() && !self.has_interrupt_vector(int_num) {
+            let irq = int_num.wrapping_sub(self.cpu.memory.pic.vector_base());
+            self.service_bios_irq((irq < 8).then_some(irq));
+            return true;
         }
+
+        self.cpu.trigger_hardware_interrupt(int_num)
     }
 
     /// Execute one instruction
@@ -220,11 +307,35 @@ impl PcCpu {
         // Opcode 0xCD (INT) followed by interrupt number
         let cs = self.cpu.cs;
         let ip = self.cpu.ip;
-        let physical_addr = ((cs as u32) << 4) + ip;
+        let physical_addr = self.cpu.cs_ip_linear_address();
+        let protected_mode = self.cpu.protected_mode().is_protected_mode();
 
         // Peek at the instruction without advancing IP
         let opcode = self.cpu.memory.read(physical_addr);
 
+        // BIOS hardware IRQ handler (INT 08h/09h default vector)
+        if !protected_mode && cs == 0xF000 && ip == IRQ_HANDLER_OFFSET as u32 {
+            return self.handle_bios_irq();
+        }
+
+        // EMS driver entry point (INT 67h default vector)
+        if !protected_mode && cs == EMS_DEVICE_SEGMENT && ip == EMS_ENTRY_OFFSET as u32 {
+            return self.handle_ems_entry();
+        }
+
+        // Built-in resident driver entry points in the BIOS segment
+        if !protected_mode && cs == 0xF000 {
+            match ip as u16 {
+                MULTIPLEX_ENTRY_OFFSET => return self.handle_multiplex_entry(),
+                MOUSE_ENTRY_OFFSET => return self.handle_mouse_entry(),
+                XMS_SERVICE_OFFSET => return self.handle_xms_entry(),
+                DOS_SHELL_OFFSET => return self.handle_shell_prompt(),
+                DOS_COMMAND_OFFSET => return self.handle_shell_command(),
+                DOS_ENTRY_OFFSET => return self.handle_dos_entry(),
+                _ => {}
+            }
+        }
+
         // Enable PC tracing - only compute logging conditions if trace logging is enabled
         if LogConfig::global().should_log(LogCategory::CPU, LogLevel::Trace) {
             // Only log if we're in the boot sector region or low memory (not ROM)
@@ -328,7 +439,8 @@ impl PcCpu {
         // Handle INT instructions
         // We intercept INTs and handle them in Rust, but we must properly simulate
         // the CPU's INT behavior: push FLAGS/CS/IP, clear IF/TF
-        if opcode == 0xCD {
+        // In protected mode INTs go through the program's IDT instead
+        if opcode == 0xCD && !protected_mode {
             // This is an INT instruction, check the interrupt number
             let int_num = self.cpu.memory.read(physical_addr + 1);
 
@@ -340,7 +452,7 @@ impl PcCpu {
 
             // Determine appropriate log level for this interrupt
             let is_high_frequency = (int_num == 0x28 && ah == 0x02) // DOS idle
-                || (int_num == 0x16 && ah == 0x01); // Keyboard check (non-blocking)
+                || (int_num == 0x16 && matches!(ah, 0x00 | 0x01 | 0x10 | 0x11)); // Keyboard polling and waiting
 
             if is_high_frequency {
                 // Only log these at trace level to avoid spam
@@ -360,6 +472,11 @@ impl PcCpu {
                 });
             }
 
+            // Install checks for the built-in drivers skip any DOS/TSR chain
+            if int_num == 0x2F && self.multiplex_id_claimed() {
+                return self.handle_int2fh();
+            }
+
             // Determine interrupt handling priority based on interrupt range
             let priority = get_interrupt_priority(int_num);
 
@@ -455,21 +572,180 @@ impl PcCpu {
         cycles
     }
 
+    /// Physical address of the text buffer (B0000h on a monochrome adapter)
+    fn text_base(&self) -> u32 {
+        0xA0000 + self.cpu.memory.text_buffer_offset() as u32
+    }
+
+    /// Physical address of a character cell on a text page
+    fn text_cell_addr(&self, page: u8, row: u32, col: u32) -> u32 {
+        let columns = self.cpu.memory.text_columns() as u32;
+        let page_size = video_mode_page_size(self.cpu.memory.video_mode()) as u32;
+        self.text_base() + (page & 0x07) as u32 * page_size + (row * columns + col) * 2
+    }
+
+    /// Cursor position (column, row) of a page from the BIOS data area
+    fn cursor_position(&self, page: u8) -> (u32, u32) {
+        let cursor_addr = 0x450 + (page & 0x07) as u32 * 2;
+        (
+            self.cpu.memory.read(cursor_addr) as u32,
+            self.cpu.memory.read(cursor_addr + 1) as u32,
+        )
+    }
+
+    /// Store the cursor position of a page in the BIOS data area
+    fn set_cursor_position(&mut self, page: u8, col: u32, row: u32) {
+        let cursor_addr = 0x450 + (page & 0x07) as u32 * 2;
+        self.cpu.memory.write(cursor_addr, col as u8);
+        self.cpu.memory.write(cursor_addr + 1, row as u8);
+    }
+
+    /// Scroll a window of a text page up (or down) by `lines`
+    ///
+    /// The window is clipped to the screen, and scrolling by 0 lines or by
+    /// the window height or more blanks it, like the BIOS.
+    fn scroll_window(
+        &mut self,
+        page: u8,
+        (top, left): (u32, u32),
+        (bottom, right): (u32, u32),
+        lines: u32,
+        attr: u8,
+        up: bool,
+    ) {
+        let columns = self.cpu.memory.text_columns() as u32;
+        let bottom = bottom.min(TEXT_ROWS - 1);
+        let right = right.min(columns - 1);
+        if top > bottom || left > right {
+            return;
+        }
+        let height = bottom - top + 1;
+        let lines = if lines == 0 || lines > height {
+            height
+        } else {
+            lines
+        };
+
+        for i in 0..height {
+            // Copy rows away from the direction of the scroll first
+            let row = if up { top + i } else { bottom - i };
+            let source = if up && row + lines <= bottom {
+                Some(row + lines)
+            } else if !up && row >= top + lines {
+                Some(row - lines)
+            } else {
+                None
+            };
+            for col in left..=right {
+                let dest = self.text_cell_addr(page, row, col);
+                let (ch, at) = match source {
+                    Some(source) => {
+                        let src = self.text_cell_addr(page, source, col);
+                        (self.cpu.memory.read(src), self.cpu.memory.read(src + 1))
+                    }
+                    None => (b' ', attr),
+                };
+                self.cpu.memory.write(dest, ch);
+                self.cpu.memory.write(dest + 1, at);
+            }
+        }
+    }
+
+    /// Write a character `count` times from the cursor, wrapping onto the
+    /// following rows up to the end of the page; the cursor does not move
+    fn write_char_repeated(&mut self, page: u8, ch: u8, attr: Option<u8>, count: u32) {
+        let columns = self.cpu.memory.text_columns() as u32;
+        let (col, row) = self.cursor_position(page);
+        let start = row * columns + col;
+        let end = (start + count).min(TEXT_ROWS * columns);
+        for cell in start..end {
+            let addr = self.text_cell_addr(page, cell / columns, cell % columns);
+            self.cpu.memory.write(addr, ch);
+            if let Some(attr) = attr {
+                self.cpu.memory.write(addr + 1, attr);
+            }
+        }
+    }
+
     /// INT 10h, AH=00h: Set video mode
     #[allow(dead_code)] // Called from handle_int10h
     fn int10h_set_video_mode(&mut self) -> u32 {
-        // AL contains the mode number
-        // For now, we just acknowledge the mode change
-        // Actual mode switching would be done via the video adapter
-        // Common modes: 0x00-0x03 (text), 0x04-0x06 (CGA graphics), 0x0D-0x13 (VGA)
+        // AL = mode number, bit 7 set to keep video memory
+        let al = (self.cpu.ax & 0xFF) as u8;
+        let mode = al & 0x7F;
+        let clear = al & 0x80 == 0;
+
+        // Modes need the matching adapter (EGA/VGA graphics, or color
+        // versus monochrome); other BIOSes ignore the request
+        if !self.cpu.memory.supports_video_mode(mode) {
+            log(LogCategory::Interrupts, LogLevel::Debug, || {
+                format!(
+                    "INT 10h AH=00h: Mode {:02X}h not supported by the video adapter, ignored",
+                    mode
+                )
+            });
+            return 51;
+        }
+
+        // Reprograms the adapter registers for the mode
+        self.cpu.memory.set_video_mode(mode, clear);
+        let text_base = self.text_base();
+        if clear && (mode <= 0x03 || mode == MDA_TEXT_MODE) {
+            // Blank all text pages with white on black spaces
+            for offset in (0..0x4000).step_by(2) {
+                self.cpu.memory.write(text_base + offset, b' ');
+                self.cpu.memory.write(text_base + offset + 1, 0x07);
+            }
+        } else if clear && (0x04..=0x06).contains(&mode) {
+            // CGA graphics memory is cleared to the background color
+            for offset in 0..0x4000 {
+                self.cpu.memory.write(text_base + offset, 0);
+            }
+        }
+
+        // BIOS data area: mode, columns, page size and offset, cursor
+        // positions and shape, active page and the CGA mode registers
+        let page_size = video_mode_page_size(mode);
+        let [cursor_start, cursor_end] = self.cpu.memory.default_cursor_shape().to_be_bytes();
+        self.cpu.memory.write(0x449, mode);
+        self.cpu.memory.write(0x44A, video_mode_columns(mode));
+        self.cpu.memory.write(0x44B, 0);
+        self.cpu.memory.write(0x44C, page_size as u8);
+        self.cpu.memory.write(0x44D, (page_size >> 8) as u8);
+        self.cpu.memory.write(0x44E, 0);
+        self.cpu.memory.write(0x44F, 0);
+        for offset in 0..16 {
+            self.cpu.memory.write(0x450 + offset, 0);
+        }
+        self.cpu.memory.write(0x460, cursor_end);
+        self.cpu.memory.write(0x461, cursor_start);
+        self.cpu.memory.write(0x462, 0);
+        if mode <= MDA_TEXT_MODE {
+            let port = if mode == MDA_TEXT_MODE { 0x3B8 } else { 0x3D8 };
+            let mode_control = self.cpu.memory.io_read(port);
+            let color_select = self.cpu.memory.io_read(0x3D9);
+            self.cpu.memory.write(0x465, mode_control);
+            self.cpu.memory.write(0x466, color_select);
+        }
         51
     }
 
     /// INT 10h, AH=01h: Set cursor shape
     #[allow(dead_code)] // Called from handle_int10h
     fn int10h_set_cursor_shape(&mut self) -> u32 {
-        // CH = start scan line, CL = end scan line
-        // We don't render cursor, but acknowledge the call
+        // CH = start scan line (bit 5 hides the cursor), CL = end scan line
+        let start = ((self.cpu.cx >> 8) & 0xFF) as u8;
+        let end = (self.cpu.cx & 0xFF) as u8;
+
+        // BIOS data area 0x40:0x60 holds the end line, 0x40:0x61 the start
+        self.cpu.memory.write(0x460, end);
+        self.cpu.memory.write(0x461, start);
+
+        // CRTC cursor start and end registers
+        for (index, value) in [(0x0A, start), (0x0B, end)] {
+            self.cpu.memory.io_write(0x3D4, index);
+            self.cpu.memory.io_write(0x3D5, value);
+        }
         51
     }
 
@@ -479,13 +755,9 @@ impl PcCpu {
         // BH = page number, DH = row, DL = column
         // Store cursor position in BIOS data area at 0x40:0x50 + (page * 2)
         let page = ((self.cpu.bx >> 8) & 0xFF) as u8;
-        let row = ((self.cpu.dx >> 8) & 0xFF) as u8;
-        let col = (self.cpu.dx & 0xFF) as u8;
-
-        // BIOS data area cursor position storage
-        let cursor_addr = 0x450 + (page as u32 * 2);
-        self.cpu.memory.write(cursor_addr, col);
-        self.cpu.memory.write(cursor_addr + 1, row);
+        let row = (self.cpu.dx >> 8) & 0xFF;
+        let col = self.cpu.dx & 0xFF;
+        self.set_cursor_position(page, col, row);
         51
     }
 
@@ -495,14 +767,11 @@ impl PcCpu {
         // BH = page number
         // Returns: DH = row, DL = column, CH/CL = cursor shape
         let page = ((self.cpu.bx >> 8) & 0xFF) as u8;
+        let (col, row) = self.cursor_position(page);
 
-        // Read cursor position from BIOS data area
-        let cursor_addr = 0x450 + (page as u32 * 2);
-        let col = self.cpu.memory.read(cursor_addr);
-        let row = self.cpu.memory.read(cursor_addr + 1);
-
-        self.cpu.dx = ((row as u32) << 8) | (col as u32);
-        self.cpu.cx = 0x0607u32; // Default cursor shape
+        self.cpu.dx = (row << 8) | col;
+        self.cpu.cx =
+            ((self.cpu.memory.read(0x461) as u32) << 8) | self.cpu.memory.read(0x460) as u32;
         51
     }
 
@@ -511,46 +780,14 @@ impl PcCpu {
     fn int10h_scroll_up(&mut self) -> u32 {
         // AL = lines to scroll (0 = clear), BH = attribute for blank lines
         // CH,CL = row,col of upper left, DH,DL = row,col of lower right
-        let lines = (self.cpu.ax & 0xFF) as u8;
+        let lines = self.cpu.ax & 0xFF;
         let attr = ((self.cpu.bx >> 8) & 0xFF) as u8;
-        let top = (self.cpu.cx >> 8) & 0xFF;
-        let left = self.cpu.cx & 0xFF;
-        let bottom = (self.cpu.dx >> 8) & 0xFF;
-        let right = self.cpu.dx & 0xFF;
-
-        if lines == 0 {
-            // Clear window
-            for row in top..=bottom {
-                for col in left..=right {
-                    let offset = (row * 80 + col) * 2;
-                    let video_addr = 0xB8000 + offset;
-                    self.cpu.memory.write(video_addr, b' ');
-                    self.cpu.memory.write(video_addr + 1, attr);
-                }
-            }
-        } else {
-            // Scroll up by N lines
-            for row in top..=bottom {
-                for col in left..=right {
-                    let offset = (row * 80 + col) * 2;
-                    let video_addr = 0xB8000 + offset;
-
-                    if row + (lines as u32) <= bottom {
-                        // Copy from below
-                        let src_offset = ((row + (lines as u32)) * 80 + col) * 2;
-                        let src_addr = 0xB8000 + src_offset;
-                        let ch = self.cpu.memory.read(src_addr);
-                        let at = self.cpu.memory.read(src_addr + 1);
-                        self.cpu.memory.write(video_addr, ch);
-                        self.cpu.memory.write(video_addr + 1, at);
-                    } else {
-                        // Fill with blanks
-                        self.cpu.memory.write(video_addr, b' ');
-                        self.cpu.memory.write(video_addr + 1, attr);
-                    }
-                }
-            }
-        }
+        let top_left = ((self.cpu.cx >> 8) & 0xFF, self.cpu.cx & 0xFF);
+        let bottom_right = ((self.cpu.dx >> 8) & 0xFF, self.cpu.dx & 0xFF);
+
+        // Scrolling works on the active page
+        let page = self.cpu.memory.read(0x462);
+        self.scroll_window(page, top_left, bottom_right, lines, attr, true);
         51
     }
 
@@ -559,46 +796,13 @@ impl PcCpu {
     fn int10h_scroll_down(&mut self) -> u32 {
         // AL = lines to scroll (0 = clear), BH = attribute for blank lines
         // CH,CL = row,col of upper left, DH,DL = row,col of lower right
-        let lines = (self.cpu.ax & 0xFF) as u8;
+        let lines = self.cpu.ax & 0xFF;
         let attr = ((self.cpu.bx >> 8) & 0xFF) as u8;
-        let top = (self.cpu.cx >> 8) & 0xFF;
-        let left = self.cpu.cx & 0xFF;
-        let bottom = (self.cpu.dx >> 8) & 0xFF;
-        let right = self.cpu.dx & 0xFF;
-
-        if lines == 0 {
-            // Clear window
-            for row in top..=bottom {
-                for col in left..=right {
-                    let offset = (row * 80 + col) * 2;
-                    let video_addr = 0xB8000 + offset;
-                    self.cpu.memory.write(video_addr, b' ');
-                    self.cpu.memory.write(video_addr + 1, attr);
-                }
-            }
-        } else {
-            // Scroll down by N lines (iterate in reverse to avoid overwriting)
-            for row in (top..=bottom).rev() {
-                for col in left..=right {
-                    let offset = (row * 80 + col) * 2;
-                    let video_addr = 0xB8000 + offset;
-
-                    if row >= top + (lines as u32) {
-                        // Copy from above
-                        let src_offset = ((row - (lines as u32)) * 80 + col) * 2;
-                        let src_addr = 0xB8000 + src_offset;
-                        let ch = self.cpu.memory.read(src_addr);
-                        let at = self.cpu.memory.read(src_addr + 1);
-                        self.cpu.memory.write(video_addr, ch);
-                        self.cpu.memory.write(video_addr + 1, at);
-                    } else {
-                        // Fill with blanks
-                        self.cpu.memory.write(video_addr, b' ');
-                        self.cpu.memory.write(video_addr + 1, attr);
-                    }
-                }
-            }
-        }
+        let top_left = ((self.cpu.cx >> 8) & 0xFF, self.cpu.cx & 0xFF);
+        let bottom_right = ((self.cpu.dx >> 8) & 0xFF, self.cpu.dx & 0xFF);
+
+        let page = self.cpu.memory.read(0x462);
+        self.scroll_window(page, top_left, bottom_right, lines, attr, false);
         51
     }
 
@@ -608,16 +812,8 @@ impl PcCpu {
         // BH = page number
         // Returns: AL = character, AH = attribute
         let page = ((self.cpu.bx >> 8) & 0xFF) as u8;
-
-        // Get cursor position
-        let cursor_addr = 0x450 + (page as u32 * 2);
-        let col = self.cpu.memory.read(cursor_addr) as u32;
-        let row = self.cpu.memory.read(cursor_addr + 1) as u32;
-
-        // Calculate offset in text mode video memory (0xB8000)
-        // Each character is 2 bytes: char + attribute
-        let offset = (row * 80 + col) * 2;
-        let video_addr = 0xB8000 + offset;
+        let (col, row) = self.cursor_position(page);
+        let video_addr = self.text_cell_addr(page, row, col);
 
         let ch = self.cpu.memory.read(video_addr);
         let attr = self.cpu.memory.read(video_addr + 1);
@@ -630,64 +826,23 @@ impl PcCpu {
     #[allow(dead_code)] // Called from handle_int10h
     fn int10h_write_char_attr(&mut self) -> u32 {
         // AL = character, BL = attribute, BH = page, CX = count
+        // Note: Does NOT advance cursor
         let ch = (self.cpu.ax & 0xFF) as u8;
         let attr = (self.cpu.bx & 0xFF) as u8;
         let page = ((self.cpu.bx >> 8) & 0xFF) as u8;
-        let count = self.cpu.cx;
-
-        // Get cursor position
-        let cursor_addr = 0x450 + (page as u32 * 2);
-        let mut col = self.cpu.memory.read(cursor_addr) as u32;
-        let row = self.cpu.memory.read(cursor_addr + 1) as u32;
-
-        // Write character(s) to video memory
-        for _ in 0..count {
-            let offset = (row * 80 + col) * 2;
-            let video_addr = 0xB8000 + offset;
-
-            self.cpu.memory.write(video_addr, ch);
-            self.cpu.memory.write(video_addr + 1, attr);
-
-            col += 1;
-            if col >= 80 {
-                break; // Don't wrap to next line
-            }
-        }
-
+        self.write_char_repeated(page, ch, Some(attr), self.cpu.cx & 0xFFFF);
         51
     }
 
-    /// Helper function to scroll the entire screen up by N lines
-    fn scroll_screen_up(&mut self, lines: u32, attr: u8) {
-        // Scroll entire screen (0,0) to (24,79)
-        for row in 0..25 {
-            for col in 0..80 {
-                let offset = (row * 80 + col) * 2;
-                let video_addr = 0xB8000 + offset;
-
-                if row + lines < 25 {
-                    // Copy from below
-                    let src_offset = ((row + lines) * 80 + col) * 2;
-                    let src_addr = 0xB8000 + src_offset;
-                    let ch = self.cpu.memory.read(src_addr);
-                    let at = self.cpu.memory.read(src_addr + 1);
-                    self.cpu.memory.write(video_addr, ch);
-                    self.cpu.memory.write(video_addr + 1, at);
-                } else {
-                    // Fill bottom lines with blanks
-                    self.cpu.memory.write(video_addr, b' ');
-                    self.cpu.memory.write(video_addr + 1, attr);
-                }
-            }
-        }
-    }
-
     /// INT 10h, AH=0Eh: Teletype output
     #[allow(dead_code)] // Called from handle_int10h
     fn int10h_teletype_output(&mut self) -> u32 {
-        // AL = character, BH = page number, BL = foreground color (graphics mode)
+        // AL = character, BL = foreground color (graphics mode)
+        // Output goes to the active page like the AT BIOS and later, since
+        // many boot sectors call this with BH left unset
         let ch = (self.cpu.ax & 0xFF) as u8;
-        let page = ((self.cpu.bx >> 8) & 0xFF) as u8;
+        let page = self.cpu.memory.read(0x462);
+        let columns = self.cpu.memory.text_columns() as u32;
 
         // Log printable characters
         if (0x20..0x7F).contains(&ch) {
@@ -696,69 +851,64 @@ impl PcCpu {
             eprintln!(); // Carriage return + line feed for stderr
         }
 
-        // Get cursor position
-        let cursor_addr = 0x450 + (page as u32 * 2);
-        let mut col = self.cpu.memory.read(cursor_addr) as u32;
-        let mut row = self.cpu.memory.read(cursor_addr + 1) as u32;
+        let (mut col, mut row) = self.cursor_position(page);
 
         // Handle special characters
         match ch {
+            0x07 => {
+                // Bell - sound the speaker, nothing is written
+                let (divisor, duration_us) = BELL_BEEP;
+                self.cpu.memory.bios_beep(divisor, duration_us);
+            }
             0x08 => {
                 // Backspace - ONLY move cursor back, do NOT erase character
                 // DOS/applications handle erasure by outputting: backspace, space, backspace
-                if col > 0 {
-                    col = col.saturating_sub(1);
-                }
+                col = col.saturating_sub(1);
             }
             0x0A => {
                 // Line feed
                 row += 1;
-                if row >= 25 {
-                    // Scroll screen up by 1 line
-                    self.scroll_screen_up(1, 0x07);
-                    row = 24; // Stay at bottom after scroll
-                }
             }
             0x0D => {
                 // Carriage return
                 col = 0;
             }
             _ => {
-                // Normal character - write to video memory
-                let offset = (row * 80 + col) * 2;
-                let video_addr = 0xB8000 + offset;
-
+                // Normal character - the cell keeps its attribute
+                let video_addr = self.text_cell_addr(page, row, col);
                 self.cpu.memory.write(video_addr, ch);
-                // Use default attribute (white on black)
-                self.cpu.memory.write(video_addr + 1, 0x07);
 
                 col += 1;
-                if col >= 80 {
+                if col >= columns {
                     col = 0;
                     row += 1;
-                    if row >= 25 {
-                        // Scroll screen up by 1 line
-                        self.scroll_screen_up(1, 0x07);
-                        row = 24; // Stay at bottom after scroll
-                    }
                 }
             }
         }
 
-        // Update cursor position
-        self.cpu.memory.write(cursor_addr, col as u8);
-        self.cpu.memory.write(cursor_addr + 1, row as u8);
+        if row >= TEXT_ROWS {
+            // Scroll the page up by one line, blanking the new bottom line
+            // with the attribute found at the cursor
+            row = TEXT_ROWS - 1;
+            let attr = self
+                .cpu
+                .memory
+                .read(self.text_cell_addr(page, row, col) + 1);
+            self.scroll_window(page, (0, 0), (TEXT_ROWS - 1, columns - 1), 1, attr, true);
+        }
 
+        self.set_cursor_position(page, col, row);
         51
     }
 
     /// INT 10h, AH=0Fh: Get video mode
     #[allow(dead_code)] // Called from handle_int10h
     fn int10h_get_video_mode(&mut self) -> u32 {
-        // Returns: AL = mode, AH = columns, BH = page
-        // Default to mode 3 (80x25 color text)
-        self.cpu.ax = 0x5003u32; // AH=80 columns, AL=mode 3
-        self.cpu.bx &= 0x00FF; // BH=0 (page 0)
+        // Returns: AL = mode, AH = columns, BH = active page
+        let mode = self.cpu.memory.video_mode();
+        let page = self.cpu.memory.read(0x462);
+        self.cpu.ax = ((video_mode_columns(mode) as u32) << 8) | mode as u32;
+        self.cpu.bx = (self.cpu.bx & 0x00FF) | ((page as u32) << 8);
         51
     }
 
@@ -774,6 +924,7 @@ impl PcCpu {
         let length = self.cpu.cx;
         let row = (self.cpu.dx >> 8) & 0xFF;
         let mut col = self.cpu.dx & 0xFF;
+        let columns = self.cpu.memory.text_columns() as u32;
 
         // String address: ES:BP
         let string_seg = self.cpu.es as u32;
@@ -784,23 +935,19 @@ impl PcCpu {
         for i in 0..length {
             let ch = self.cpu.memory.read(string_addr + i);
 
-            let offset = (row * 80 + col) * 2;
-            let video_addr = 0xB8000 + offset;
-
+            let video_addr = self.text_cell_addr(page, row, col);
             self.cpu.memory.write(video_addr, ch);
             self.cpu.memory.write(video_addr + 1, attr);
 
             col += 1;
-            if col >= 80 {
+            if col >= columns {
                 break;
             }
         }
 
         // Update cursor position if mode bit 1 is set
         if mode & 0x02 != 0 {
-            let cursor_addr = 0x450 + (page as u32 * 2);
-            self.cpu.memory.write(cursor_addr, col as u8);
-            self.cpu.memory.write(cursor_addr + 1, row as u8);
+            self.set_cursor_position(page, col, row);
         }
 
         51
@@ -811,8 +958,18 @@ impl PcCpu {
     fn int10h_select_active_page(&mut self) -> u32 {
         // AL = new page number (0..7 for text mode)
         // Store active page in BIOS data area at 0x40:0x62
-        let page = (self.cpu.ax & 0xFF) as u8;
-        self.cpu.memory.write(0x462, page & 0x07); // Limit to 8 pages
+        let page = (self.cpu.ax & 0x07) as u8;
+        let offset = page as u32 * video_mode_page_size(self.cpu.memory.video_mode()) as u32;
+        self.cpu.memory.write(0x462, page);
+        self.cpu.memory.write(0x44E, offset as u8);
+        self.cpu.memory.write(0x44F, (offset >> 8) as u8);
+
+        // Display the page: CRTC start address, in characters
+        let start = offset / 2;
+        for (index, value) in [(0x0C, (start >> 8) as u8), (0x0D, start as u8)] {
+            self.cpu.memory.io_write(0x3D4, index);
+            self.cpu.memory.io_write(0x3D5, value);
+        }
         51
     }
 
@@ -823,27 +980,7 @@ impl PcCpu {
         // Note: Does NOT write attribute, does NOT advance cursor
         let ch = (self.cpu.ax & 0xFF) as u8;
         let page = ((self.cpu.bx >> 8) & 0xFF) as u8;
-        let count = self.cpu.cx;
-
-        // Get cursor position
-        let cursor_addr = 0x450 + (page as u32 * 2);
-        let mut col = self.cpu.memory.read(cursor_addr) as u32;
-        let row = self.cpu.memory.read(cursor_addr + 1) as u32;
-
-        // Write character(s) to video memory (character bytes only)
-        for _ in 0..count {
-            let offset = (row * 80 + col) * 2;
-            let video_addr = 0xB8000 + offset;
-
-            // Write character only, preserve existing attribute
-            self.cpu.memory.write(video_addr, ch);
-
-            col += 1;
-            if col >= 80 {
-                break; // Don't wrap to next line
-            }
-        }
-
+        self.write_char_repeated(page, ch, None, self.cpu.cx & 0xFFFF);
         51
     }
 
@@ -896,6 +1033,23 @@ impl PcCpu {
         let y = self.cpu.dx;
         let _page = ((self.cpu.bx >> 8) & 0xFF) as u8;
 
+        if let Some((addr, bit)) = self.planar_pixel_address(x, y) {
+            // Write mode 2 sets the pixel in all four planes; bit 7 of the
+            // color XORs it with the current pixel instead
+            let function = if color & 0x80 != 0 { 0x18 } else { 0x00 };
+            self.write_graphics_register(0x03, function);
+            self.write_graphics_register(0x05, 0x02);
+            self.write_graphics_register(0x08, bit);
+            self.cpu.memory.io_write(0x3C4, 0x02);
+            self.cpu.memory.io_write(0x3C5, 0x0F);
+            self.cpu.memory.read(addr); // Load the latches
+            self.cpu.memory.write(addr, color & 0x0F);
+            self.write_graphics_register(0x03, 0x00);
+            self.write_graphics_register(0x05, 0x00);
+            self.write_graphics_register(0x08, 0xFF);
+            return 51;
+        }
+
         // Mode 13h (320x200 256-color): Linear addressing
         // Each pixel is 1 byte at 0xA0000 + (y * 320 + x)
         // For other modes, this would require mode-specific calculations
@@ -917,8 +1071,19 @@ impl PcCpu {
         let y = self.cpu.dx;
         let _page = ((self.cpu.bx >> 8) & 0xFF) as u8;
 
-        // Mode 13h (320x200 256-color): Linear addressing
-        let color = if x < 320 && y < 200 {
+        let color = if let Some((addr, bit)) = self.planar_pixel_address(x, y) {
+            // One bit of the color from each plane
+            let mut color = 0;
+            for plane in 0..4 {
+                self.write_graphics_register(0x04, plane);
+                if self.cpu.memory.read(addr) & bit != 0 {
+                    color |= 1 << plane;
+                }
+            }
+            self.write_graphics_register(0x04, 0x00);
+            color
+        } else if x < 320 && y < 200 {
+            // Mode 13h (320x200 256-color): Linear addressing
             let offset = y * 320 + x;
             let video_addr = 0xA0000 + offset;
             self.cpu.memory.read(video_addr)
@@ -931,6 +1096,56 @@ impl PcCpu {
         51
     }
 
+    /// Address and bit mask of a pixel in a 16-color planar mode
+    ///
+    /// Returns `None` outside those modes or when the pixel is off screen.
+    fn planar_pixel_address(&self, x: u32, y: u32) -> Option<(u32, u8)> {
+        let mode = self.cpu.memory.video_mode();
+        if !PLANAR_16_COLOR_MODES.contains(&mode) || !self.cpu.memory.supports_planar_mode(mode) {
+            return None;
+        }
+        let (width, height) = match mode {
+            0x0D => (320, 200),
+            0x0E => (640, 200),
+            0x10 => (640, 350),
+            _ => (640, 480),
+        };
+        if x >= width || y >= height {
+            return None;
+        }
+        Some((0xA0000 + y * (width / 8) + x / 8, 0x80 >> (x & 7)))
+    }
+
+    /// Write a graphics controller register through ports 3CEh/3CFh
+    fn write_graphics_register(&mut self, index: u8, val: u8) {
+        self.cpu.memory.io_write(0x3CE, index);
+        self.cpu.memory.io_write(0x3CF, val);
+    }
+
+    /// Write attribute controller registers through port 3C0h
+    ///
+    /// Leaves the palette address source bit set so the display stays on.
+    fn write_attribute_registers(&mut self, first: u8, values: &[u8]) {
+        for (index, &value) in (first..).zip(values) {
+            // Reading input status 1 resets the index/data flip-flop
+            self.cpu.memory.io_read(0x3DA);
+            self.cpu.memory.io_write(0x3C0, index);
+            self.cpu.memory.io_write(0x3C0, value);
+        }
+        self.cpu.memory.io_read(0x3DA);
+        self.cpu.memory.io_write(0x3C0, 0x20);
+    }
+
+    /// Read an attribute controller register through ports 3C0h/3C1h
+    fn read_attribute_register(&mut self, index: u8) -> u8 {
+        self.cpu.memory.io_read(0x3DA);
+        self.cpu.memory.io_write(0x3C0, index);
+        let value = self.cpu.memory.io_read(0x3C1);
+        self.cpu.memory.io_read(0x3DA);
+        self.cpu.memory.io_write(0x3C0, 0x20);
+        value
+    }
+
     /// INT 10h, AH=10h: Palette/DAC functions
     #[allow(dead_code)] // Called from handle_int10h
     fn int10h_palette_functions(&mut self) -> u32 {
@@ -939,10 +1154,52 @@ impl PcCpu {
         // 01h = Set overscan register
         // 02h = Set all palette registers
         // 03h = Toggle intensity/blinking
-        // 10h-13h = DAC color registers
+        // 10h-17h = DAC color registers
         let al = (self.cpu.ax & 0xFF) as u8;
+        let bx = (self.cpu.bx & 0xFFFF) as u16;
 
         match al {
+            0x00 => {
+                // Set one palette register: BL = register, BH = value
+                self.write_attribute_registers(bx as u8 & 0x0F, &[(bx >> 8) as u8]);
+                51
+            }
+            0x01 => {
+                // Set the overscan (border) color: BH = value
+                self.write_attribute_registers(0x11, &[(bx >> 8) as u8]);
+                51
+            }
+            0x02 => {
+                // Set all palette registers and the overscan color from the
+                // 17-byte table at ES:DX
+                let table = ((self.cpu.es as u32) << 4) + (self.cpu.dx & 0xFFFF);
+                let values: Vec<u8> = (0..17).map(|i| self.cpu.memory.read(table + i)).collect();
+                self.write_attribute_registers(0x00, &values[..16]);
+                self.write_attribute_registers(0x11, &values[16..]);
+                51
+            }
+            0x07 => {
+                // Read one palette register: BL = register, returns BH
+                let value = self.read_attribute_register(bx as u8 & 0x0F);
+                self.cpu.bx = (self.cpu.bx & 0x00FF) | (value as u32) << 8;
+                51
+            }
+            0x08 => {
+                // Read the overscan color into BH
+                let value = self.read_attribute_register(0x11);
+                self.cpu.bx = (self.cpu.bx & 0x00FF) | (value as u32) << 8;
+                51
+            }
+            0x09 => {
+                // Read all palette registers and the overscan color into ES:DX
+                let table = ((self.cpu.es as u32) << 4) + (self.cpu.dx & 0xFFFF);
+                for index in 0..17u8 {
+                    let register = if index < 16 { index } else { 0x11 };
+                    let value = self.read_attribute_register(register);
+                    self.cpu.memory.write(table + index as u32, value);
+                }
+                51
+            }
             0x03 => {
                 // Toggle intensity/blinking
                 // BL = 0: enable intensive colors
@@ -950,6 +1207,50 @@ impl PcCpu {
                 // For now, just acknowledge
                 51
             }
+            0x10 => {
+                // Set one DAC register: BX = index, DH = red, CH = green, CL = blue
+                let red = ((self.cpu.dx >> 8) & 0xFF) as u8;
+                let green = ((self.cpu.cx >> 8) & 0xFF) as u8;
+                let blue = (self.cpu.cx & 0xFF) as u8;
+                self.cpu.memory.io_write(0x3C8, bx as u8);
+                for component in [red, green, blue] {
+                    self.cpu.memory.io_write(0x3C9, component);
+                }
+                51
+            }
+            0x12 => {
+                // Set a block of DAC registers: BX = first, CX = count,
+                // ES:DX = table of red, green, blue triples
+                let table = ((self.cpu.es as u32) << 4) + (self.cpu.dx & 0xFFFF);
+                let count = (self.cpu.cx & 0xFFFF).min(256);
+                self.cpu.memory.io_write(0x3C8, bx as u8);
+                for offset in 0..count * 3 {
+                    let component = self.cpu.memory.read(table + offset);
+                    self.cpu.memory.io_write(0x3C9, component);
+                }
+                51
+            }
+            0x15 => {
+                // Read one DAC register: BX = index, returns DH, CH, CL
+                self.cpu.memory.io_write(0x3C7, bx as u8);
+                let red = self.cpu.memory.io_read(0x3C9) as u32;
+                let green = self.cpu.memory.io_read(0x3C9) as u32;
+                let blue = self.cpu.memory.io_read(0x3C9) as u32;
+                self.cpu.dx = (self.cpu.dx & 0x00FF) | (red << 8);
+                self.cpu.cx = (green << 8) | blue;
+                51
+            }
+            0x17 => {
+                // Read a block of DAC registers into ES:DX
+                let table = ((self.cpu.es as u32) << 4) + (self.cpu.dx & 0xFFFF);
+                let count = (self.cpu.cx & 0xFFFF).min(256);
+                self.cpu.memory.io_write(0x3C7, bx as u8);
+                for offset in 0..count * 3 {
+                    let component = self.cpu.memory.io_read(0x3C9);
+                    self.cpu.memory.write(table + offset, component);
+                }
+                51
+            }
             _ => {
                 // Other palette functions - stub
                 51
@@ -1059,9 +1360,14 @@ impl PcCpu {
         let ah = ((self.cpu.ax >> 8) & 0xFF) as u8;
 
         match ah {
-            0x00 => self.int16h_read_keystroke(),
-            0x01 => self.int16h_check_keystroke(),
+            // AT enhanced keyboard functions (10h-12h) share the XT implementations;
+            // extended keys are not distinguished
+            0x00 | 0x10 => self.int16h_read_keystroke(),
+            0x01 | 0x11 => self.int16h_check_keystroke(),
             0x02 => self.int16h_get_shift_flags(),
+            0x03 => 51, // Set typematic rate: accepted, no effect
+            0x05 => self.int16h_store_keystroke(),
+            0x12 => self.int16h_get_extended_shift_flags(),
             _ => {
                 // Unsupported function
                 51
@@ -1069,9 +1375,45 @@ impl PcCpu {
         }
     }
 
+    fn read_bda_word(&self, addr: u32) -> u16 {
+        self.cpu.memory.read(addr) as u16 | ((self.cpu.memory.read(addr + 1) as u16) << 8)
+    }
+
+    fn write_bda_word(&mut self, addr: u32, value: u16) {
+        self.cpu.memory.write(addr, (value & 0xFF) as u8);
+        self.cpu.memory.write(addr + 1, (value >> 8) as u8);
+    }
+
+    /// BDA keyboard buffer bounds (start, end) as offsets in segment 0040h,
+    /// falling back to the defaults if the BDA fields are not initialized
+    fn keyboard_buffer_bounds(&self) -> (u16, u16) {
+        let start = match self.read_bda_word(Self::BDA_KB_BUFFER_START_ADDR) {
+            0 => Self::BDA_KB_BUFFER_START_OFFSET,
+            val => val,
+        };
+        let end = match self.read_bda_word(Self::BDA_KB_BUFFER_END_ADDR) {
+            0 => Self::BDA_KB_BUFFER_END_OFFSET,
+            val => val,
+        };
+        (start, end)
+    }
+
+    /// Advance a keyboard buffer pointer by one entry, wrapping at the end
+    fn next_keyboard_buffer_offset(&self, offset: u16) -> u16 {
+        let (start, end) = self.keyboard_buffer_bounds();
+        if offset + 2 >= end {
+            start
+        } else {
+            offset + 2
+        }
+    }
+
     /// Synchronize BIOS Data Area keyboard buffer with internal keyboard buffer
-    /// This allows programs to read the keyboard buffer directly from memory (BDA at 0040:001Eh)
-    /// instead of only using INT 16h services
+    ///
+    /// This is the work the BIOS INT 09h handler does on real hardware. It runs
+    /// on every keyboard IRQ and INT 16h call, so programs can poll the buffer
+    /// head/tail pointers (0040:001Ah/001Ch) directly instead of only using
+    /// INT 16h services.
     fn sync_bda_keyboard_buffer(&mut self) {
         // BDA keyboard buffer structure:
         // 0040:001Ah - Buffer head (offset to next character to read)
@@ -1089,30 +1431,9 @@ impl PcCpu {
         self.cpu.memory.write(0x497, shift_flags); // Mirror shift flags to 0x497
 
         // Read current head and tail pointers
-        let mut head_offset = self.cpu.memory.read(Self::BDA_KB_BUFFER_HEAD_ADDR) as u16
-            | ((self.cpu.memory.read(Self::BDA_KB_BUFFER_HEAD_ADDR + 1) as u16) << 8);
-        let mut tail_offset = self.cpu.memory.read(Self::BDA_KB_BUFFER_TAIL_ADDR) as u16
-            | ((self.cpu.memory.read(Self::BDA_KB_BUFFER_TAIL_ADDR + 1) as u16) << 8);
-
-        // Read buffer boundaries (or use defaults if not initialized)
-        let buffer_start = {
-            let val = self.cpu.memory.read(Self::BDA_KB_BUFFER_START_ADDR) as u16
-                | ((self.cpu.memory.read(Self::BDA_KB_BUFFER_START_ADDR + 1) as u16) << 8);
-            if val == 0 {
-                Self::BDA_KB_BUFFER_START_OFFSET
-            } else {
-                val
-            }
-        };
-        let buffer_end = {
-            let val = self.cpu.memory.read(Self::BDA_KB_BUFFER_END_ADDR) as u16
-                | ((self.cpu.memory.read(Self::BDA_KB_BUFFER_END_ADDR + 1) as u16) << 8);
-            if val == 0 {
-                Self::BDA_KB_BUFFER_END_OFFSET
-            } else {
-                val
-            }
-        };
+        let mut head_offset = self.read_bda_word(Self::BDA_KB_BUFFER_HEAD_ADDR);
+        let mut tail_offset = self.read_bda_word(Self::BDA_KB_BUFFER_TAIL_ADDR);
+        let (buffer_start, buffer_end) = self.keyboard_buffer_bounds();
 
         // Validate and initialize head/tail if invalid
         let head_invalid =
@@ -1124,60 +1445,46 @@ impl PcCpu {
             // Initialize empty buffer: head = tail = buffer_start
             head_offset = buffer_start;
             tail_offset = buffer_start;
-
-            // Write head and tail pointers back to BDA
-            self.cpu
-                .memory
-                .write(Self::BDA_KB_BUFFER_HEAD_ADDR, (head_offset & 0xFF) as u8);
-            self.cpu.memory.write(
-                Self::BDA_KB_BUFFER_HEAD_ADDR + 1,
-                ((head_offset >> 8) & 0xFF) as u8,
-            );
-            self.cpu
-                .memory
-                .write(Self::BDA_KB_BUFFER_TAIL_ADDR, (tail_offset & 0xFF) as u8);
-            self.cpu.memory.write(
-                Self::BDA_KB_BUFFER_TAIL_ADDR + 1,
-                ((tail_offset >> 8) & 0xFF) as u8,
-            );
+            self.write_bda_word(Self::BDA_KB_BUFFER_HEAD_ADDR, head_offset);
+            self.write_bda_word(Self::BDA_KB_BUFFER_TAIL_ADDR, tail_offset);
 
             // Initialize buffer start/end pointers if not set
             if buffer_start == Self::BDA_KB_BUFFER_START_OFFSET
                 && buffer_end == Self::BDA_KB_BUFFER_END_OFFSET
             {
-                self.cpu
-                    .memory
-                    .write(Self::BDA_KB_BUFFER_START_ADDR, (buffer_start & 0xFF) as u8);
-                self.cpu.memory.write(
-                    Self::BDA_KB_BUFFER_START_ADDR + 1,
-                    ((buffer_start >> 8) & 0xFF) as u8,
-                );
-                self.cpu
-                    .memory
-                    .write(Self::BDA_KB_BUFFER_END_ADDR, (buffer_end & 0xFF) as u8);
-                self.cpu.memory.write(
-                    Self::BDA_KB_BUFFER_END_ADDR + 1,
-                    ((buffer_end >> 8) & 0xFF) as u8,
-                );
+                self.write_bda_word(Self::BDA_KB_BUFFER_START_ADDR, buffer_start);
+                self.write_bda_word(Self::BDA_KB_BUFFER_END_ADDR, buffer_end);
             }
         }
 
         // Transfer all available keys from internal buffer to BDA buffer
         // We peek and remove from internal buffer, adding to BDA buffer
         while let Some(scancode) = self.cpu.memory.keyboard.peek_make_code() {
-            let ascii = self.scancode_to_ascii(scancode);
-
-            // Calculate next tail position
-            let mut new_tail = tail_offset + 2;
-            if new_tail >= buffer_end {
-                new_tail = buffer_start; // Wrap around
+            // Ctrl+Alt+Del: set the warm boot flag and jump to the reset vector
+            let keyboard = &self.cpu.memory.keyboard;
+            if scancode == crate::keyboard::SCANCODE_DELETE
+                && keyboard.is_ctrl_pressed()
+                && keyboard.is_alt_pressed()
+            {
+                self.cpu.memory.keyboard.read_scancode();
+                self.write_bda_word(RESET_FLAG_ADDR, WARM_BOOT_FLAG);
+                self.cpu.memory.request_reset();
+                log(LogCategory::Interrupts, LogLevel::Info, || {
+                    "INT 09h: Ctrl+Alt+Del, warm boot".to_string()
+                });
+                break;
             }
 
+            let ascii = self.scancode_to_ascii(scancode);
+
             // Check if buffer is full
+            let new_tail = self.next_keyboard_buffer_offset(tail_offset);
             if new_tail == head_offset {
-                // Buffer full - don't add more keys
-                // In a real BIOS, this might beep
-                break;
+                // Buffer full - the BIOS drops the keystroke and beeps
+                self.cpu.memory.keyboard.read_scancode();
+                let (divisor, duration_us) = KEYBOARD_FULL_BEEP;
+                self.cpu.memory.bios_beep(divisor, duration_us);
+                continue;
             }
 
             // Write scancode and ASCII to BDA buffer
@@ -1187,13 +1494,7 @@ impl PcCpu {
 
             // Update tail pointer
             tail_offset = new_tail;
-            self.cpu
-                .memory
-                .write(Self::BDA_KB_BUFFER_TAIL_ADDR, (tail_offset & 0xFF) as u8);
-            self.cpu.memory.write(
-                Self::BDA_KB_BUFFER_TAIL_ADDR + 1,
-                ((tail_offset >> 8) & 0xFF) as u8,
-            );
+            self.write_bda_word(Self::BDA_KB_BUFFER_TAIL_ADDR, tail_offset);
 
             // Remove from internal buffer now that it's in BDA
             self.cpu.memory.keyboard.read_scancode();
@@ -1209,66 +1510,32 @@ impl PcCpu {
         }
     }
 
-    /// INT 16h, AH=00h: Read keystroke (blocking)
-    fn int16h_read_keystroke(&mut self) -> u32 {
-        // Returns: AH = scan code, AL = ASCII character
-        // Note: In a real BIOS, this would block until a key is available
-        // We emulate blocking by halting the CPU until keyboard input arrives
-
-        // Read from BDA keyboard buffer
-        // Read current head and tail pointers
-        let head_offset = self.cpu.memory.read(Self::BDA_KB_BUFFER_HEAD_ADDR) as u16
-            | ((self.cpu.memory.read(Self::BDA_KB_BUFFER_HEAD_ADDR + 1) as u16) << 8);
-        let tail_offset = self.cpu.memory.read(Self::BDA_KB_BUFFER_TAIL_ADDR) as u16
-            | ((self.cpu.memory.read(Self::BDA_KB_BUFFER_TAIL_ADDR + 1) as u16) << 8);
-
-        // Read buffer boundaries
-        let buffer_start = {
-            let val = self.cpu.memory.read(Self::BDA_KB_BUFFER_START_ADDR) as u16
-                | ((self.cpu.memory.read(Self::BDA_KB_BUFFER_START_ADDR + 1) as u16) << 8);
-            if val == 0 {
-                Self::BDA_KB_BUFFER_START_OFFSET
-            } else {
-                val
-            }
-        };
-        let buffer_end = {
-            let val = self.cpu.memory.read(Self::BDA_KB_BUFFER_END_ADDR) as u16
-                | ((self.cpu.memory.read(Self::BDA_KB_BUFFER_END_ADDR + 1) as u16) << 8);
-            if val == 0 {
-                Self::BDA_KB_BUFFER_END_OFFSET
-            } else {
-                val
-            }
-        };
-
-        // Check if buffer is empty
+    /// Keystroke at the head of the BDA buffer as (scancode, ASCII), if any
+    fn peek_keystroke(&self) -> Option<(u8, u8)> {
+        let head_offset = self.read_bda_word(Self::BDA_KB_BUFFER_HEAD_ADDR);
+        let tail_offset = self.read_bda_word(Self::BDA_KB_BUFFER_TAIL_ADDR);
         if head_offset == tail_offset {
-            // No keys in buffer - halt CPU to wait for input
-            // This emulates the blocking behavior of INT 16h AH=00h
-            // The CPU will remain halted until keyboard input arrives and unhalts it
-            self.cpu.set_halted(true);
-            self.cpu.ax = 0x0000u32;
-            return 51;
+            return None;
         }
-
-        // Read scancode and ASCII from buffer
         let addr = 0x400 + head_offset as u32;
-        let ascii = self.cpu.memory.read(addr);
-        let scancode = self.cpu.memory.read(addr + 1);
+        Some((self.cpu.memory.read(addr + 1), self.cpu.memory.read(addr)))
+    }
+
+    /// INT 16h, AH=00h: Read keystroke (blocking)
+    fn int16h_read_keystroke(&mut self) -> u32 {
+        // Returns: AH = scan code, AL = ASCII character
+        // A real BIOS spins with interrupts enabled until INT 09h puts a key in
+        // the buffer. We do the same by re-executing the INT instruction, so
+        // the timer and other IRQs keep running while the program waits.
+        let Some((scancode, ascii)) = self.peek_keystroke() else {
+            self.cpu.ip = self.cpu.ip.wrapping_sub(2);
+            return 51;
+        };
 
         // Advance head pointer
-        let mut new_head = head_offset + 2;
-        if new_head >= buffer_end {
-            new_head = buffer_start; // Wrap around
-        }
-        self.cpu
-            .memory
-            .write(Self::BDA_KB_BUFFER_HEAD_ADDR, (new_head & 0xFF) as u8);
-        self.cpu.memory.write(
-            Self::BDA_KB_BUFFER_HEAD_ADDR + 1,
-            ((new_head >> 8) & 0xFF) as u8,
-        );
+        let head_offset = self.read_bda_word(Self::BDA_KB_BUFFER_HEAD_ADDR);
+        let new_head = self.next_keyboard_buffer_offset(head_offset);
+        self.write_bda_word(Self::BDA_KB_BUFFER_HEAD_ADDR, new_head);
 
         // Log at trace level for debugging
         log(LogCategory::Interrupts, LogLevel::Trace, || {
@@ -1286,9 +1553,6 @@ impl PcCpu {
 
         // AH = scan code, AL = ASCII character
         self.cpu.ax = ((scancode as u32) << 8) | (ascii as u32);
-
-        // Ensure CPU is not halted when we return a key
-        self.cpu.set_halted(false);
         51
     }
 
@@ -1296,32 +1560,17 @@ impl PcCpu {
     fn int16h_check_keystroke(&mut self) -> u32 {
         // Returns: ZF = 1 if no key available, ZF = 0 if key available
         // If key available: AH = scan code, AL = ASCII character
-
-        // Read from BDA keyboard buffer
-        // Read current head and tail pointers
-        let head_offset = self.cpu.memory.read(Self::BDA_KB_BUFFER_HEAD_ADDR) as u16
-            | ((self.cpu.memory.read(Self::BDA_KB_BUFFER_HEAD_ADDR + 1) as u16) << 8);
-        let tail_offset = self.cpu.memory.read(Self::BDA_KB_BUFFER_TAIL_ADDR) as u16
-            | ((self.cpu.memory.read(Self::BDA_KB_BUFFER_TAIL_ADDR + 1) as u16) << 8);
-
-        // Check if buffer is empty
-        if head_offset == tail_offset {
-            // No keys available
-            self.set_zero_flag(true); // ZF = 1 (no key)
-            self.cpu.ax = 0x0000u32;
-        } else {
-            // Key available - peek at it (don't remove from buffer)
-            let addr = 0x400 + head_offset as u32;
-            let ascii = self.cpu.memory.read(addr);
-            let scancode = self.cpu.memory.read(addr + 1);
-
-            // Set ZF = 0 (key available)
-            self.set_zero_flag(false);
-
-            // AH = scan code, AL = ASCII character
-            self.cpu.ax = ((scancode as u32) << 8) | (ascii as u32);
+        // The key stays in the buffer
+        match self.peek_keystroke() {
+            Some((scancode, ascii)) => {
+                self.set_zero_flag(false);
+                self.cpu.ax = ((scancode as u32) << 8) | (ascii as u32);
+            }
+            None => {
+                self.set_zero_flag(true);
+                self.cpu.ax = 0x0000u32;
+            }
         }
-
         51
     }
 
@@ -1336,6 +1585,39 @@ impl PcCpu {
         51
     }
 
+    /// INT 16h, AH=05h: Store keystroke in keyboard buffer
+    fn int16h_store_keystroke(&mut self) -> u32 {
+        // CH = scan code, CL = ASCII character
+        // Returns: AL = 00h if stored, 01h if the buffer is full
+        let tail_offset = self.read_bda_word(Self::BDA_KB_BUFFER_TAIL_ADDR);
+        let new_tail = self.next_keyboard_buffer_offset(tail_offset);
+        if new_tail == self.read_bda_word(Self::BDA_KB_BUFFER_HEAD_ADDR) {
+            self.cpu.ax = (self.cpu.ax & 0xFF00) | 0x01;
+            return 51;
+        }
+
+        let addr = 0x400 + tail_offset as u32;
+        self.cpu.memory.write(addr, (self.cpu.cx & 0xFF) as u8);
+        self.cpu
+            .memory
+            .write(addr + 1, ((self.cpu.cx >> 8) & 0xFF) as u8);
+        self.write_bda_word(Self::BDA_KB_BUFFER_TAIL_ADDR, new_tail);
+        self.cpu.ax &= 0xFF00;
+        51
+    }
+
+    /// INT 16h, AH=12h: Get extended shift flags
+    fn int16h_get_extended_shift_flags(&mut self) -> u32 {
+        // Returns: AL = shift flags (as AH=02h)
+        // AH = extended flags (bit 0 = left Ctrl, bit 1 = left Alt, ...)
+        // Left and right keys are not tracked separately, so Ctrl and Alt are
+        // reported as the left keys and the other bits stay clear
+        let flags = self.cpu.memory.keyboard.get_shift_flags();
+        let extended = (flags >> 2) & 0x03;
+        self.cpu.ax = ((extended as u32) << 8) | (flags as u32);
+        51
+    }
+
     /// Handle INT 20h - DOS: Program terminate
     #[allow(dead_code)] // Called dynamically based on interrupt number
     fn handle_int20h(&mut self) -> u32 {
@@ -1354,6 +1636,13 @@ impl PcCpu {
         // Skip the INT 21h instruction (2 bytes: 0xCD 0x21)
         // We intercept before CPU executes it, so just advance IP past it
         self.cpu.ip = self.cpu.ip.wrapping_add(2);
+        self.int21h_dispatch()
+    }
+
+    /// Run the INT 21h function in AH (IP is already past the INT instruction)
+    fn int21h_dispatch(&mut self) -> u32 {
+        // Console input functions read the BDA keyboard buffer
+        self.sync_bda_keyboard_buffer();
 
         // Get function code from AH register
         let ah = ((self.cpu.ax >> 8) & 0xFF) as u8;
@@ -1362,24 +1651,44 @@ impl PcCpu {
             0x00 => self.int21h_terminate(),            // Program terminate
             0x01 => self.int21h_read_char_stdin(),      // Read character from stdin
             0x02 => self.int21h_write_char_stdout(),    // Write character to stdout
+            0x05 => self.int21h_write_char_printer(),   // Write character to printer
             0x06 => self.int21h_direct_console_io(),    // Direct console I/O
             0x07 => self.int21h_direct_stdin(),         // Direct stdin input (no echo)
             0x08 => self.int21h_stdin_no_echo(),        // Read stdin without echo
             0x09 => self.int21h_write_string(),         // Write string to stdout
             0x0A => self.int21h_buffered_input(),       // Buffered input
             0x0B => self.int21h_check_stdin(),          // Check stdin status
+            0x0E => self.int21h_select_disk(),          // Select default drive
+            0x19 => self.int21h_get_current_disk(),     // Get default drive
+            0x1A => self.int21h_set_dta(),              // Set disk transfer area
             0x25 => self.int21h_set_interrupt_vector(), // Set interrupt vector
+            0x2A => self.int21h_get_date(),             // Get system date
+            0x2C => self.int21h_get_time(),             // Get system time
+            0x2F => self.int21h_get_dta(),              // Get disk transfer area
             0x30 => self.int21h_get_dos_version(),      // Get DOS version
+            0x31 => self.int21h_keep_process(),         // Terminate and stay resident
             0x35 => self.int21h_get_interrupt_vector(), // Get interrupt vector
+            0x36 => self.int21h_get_free_space(),       // Get free disk space
+            0x3B => self.int21h_change_dir(),           // Change current directory
             0x3C => self.int21h_create_file(),          // Create or truncate file
             0x3D => self.int21h_open_file(),            // Open existing file
             0x3E => self.int21h_close_file(),           // Close file handle
             0x3F => self.int21h_read_file(),            // Read from file or device
             0x40 => self.int21h_write_file(),           // Write to file or device
+            0x41 => self.int21h_delete_file(),          // Delete file
+            0x42 => self.int21h_seek(),                 // Move file pointer
+            0x43 => self.int21h_file_attributes(),      // Get or set file attributes
+            0x47 => self.int21h_get_current_dir(),      // Get current directory
             0x48 => self.int21h_allocate_memory(),      // Allocate memory
             0x49 => self.int21h_free_memory(),          // Free memory
             0x4A => self.int21h_resize_memory(),        // Resize memory block
+            0x4B => self.int21h_exec(),                 // Load and execute program
             0x4C => self.int21h_terminate_with_code(),  // Terminate with return code
+            0x4D => self.int21h_get_return_code(),      // Get return code of child
+            0x4E => self.int21h_find_first(),           // Find first matching file
+            0x4F => self.int21h_find_next(),            // Find next matching file
+            0x50 => self.int21h_set_psp(),              // Set current PSP
+            0x51 | 0x62 => self.int21h_get_psp(),       // Get current PSP
             _ => {
                 // Unsupported function - log and return
                 self.log_stub_interrupt(0x21, Some(ah), "DOS API (unsupported subfunction)");
@@ -1391,7 +1700,10 @@ impl PcCpu {
     /// INT 21h, AH=00h: Program terminate
     #[allow(dead_code)] // Called from handle_int21h
     fn int21h_terminate(&mut self) -> u32 {
-        // Same as INT 20h
+        // Same as INT 20h; without the built-in DOS there is nothing to return to
+        if self.cpu.memory.dos.is_running() {
+            return self.dos_terminate(0, None);
+        }
         51
     }
 
@@ -1434,6 +1746,23 @@ impl PcCpu {
         51
     }
 
+    /// INT 21h, AH=05h: Write character to printer
+    #[allow(dead_code)] // Called from handle_int21h
+    fn int21h_write_char_printer(&mut self) -> u32 {
+        // DL = character to print
+        let ch = (self.cpu.dx & 0xFF) as u8;
+        self.print_to_lpt1(ch);
+        51
+    }
+
+    /// Send a byte to LPT1 for the DOS printer device (PRN)
+    fn print_to_lpt1(&mut self, byte: u8) {
+        let base = self.read_bda_word(BDA_LPT_PORTS);
+        if base != 0 {
+            strobe_printer(&mut self.cpu.memory, base, byte);
+        }
+    }
+
     /// INT 21h, AH=06h: Direct console I/O
     #[allow(dead_code)] // Called from handle_int21h
     fn int21h_direct_console_io(&mut self) -> u32 {
@@ -1558,15 +1887,59 @@ impl PcCpu {
     #[allow(dead_code)] // Called from handle_int21h
     fn int21h_buffered_input(&mut self) -> u32 {
         // DS:DX = pointer to input buffer
-        // Buffer format: [max_length, actual_length, ...characters...]
-        // For now, just return empty input
+        // Buffer format: [max_length, actual_length, ...characters..., CR]
+        // Keys are taken as they arrive; the line typed so far is kept between
+        // retries of the INT instruction, like the blocking INT 16h read
         let ds = self.cpu.ds as u32;
         let dx = self.cpu.dx;
         let buffer_addr = (ds << 4) + dx;
+        let max_length = self.cpu.memory.read(buffer_addr) as usize;
+        if max_length == 0 {
+            return 51;
+        }
 
-        // Set actual length to 0
-        self.cpu.memory.write(buffer_addr + 1, 0);
+        let saved_ax = self.cpu.ax;
+        let mut line = self.cpu.memory.dos.line.take().unwrap_or_default();
+        while let Some((_, ascii)) = self.peek_keystroke() {
+            self.int16h_read_keystroke();
+            match ascii {
+                // Enter: store the line and return
+                0x0D => {
+                    self.cpu.memory.write(buffer_addr + 1, line.len() as u8);
+                    for (i, &byte) in line.iter().enumerate() {
+                        self.cpu.memory.write(buffer_addr + 2 + i as u32, byte);
+                    }
+                    self.cpu
+                        .memory
+                        .write(buffer_addr + 2 + line.len() as u32, 0x0D);
+                    self.dos_print_bytes(b"\r");
+                    self.cpu.ax = saved_ax;
+                    return 51;
+                }
+                0x08 => {
+                    if line.pop().is_some() {
+                        self.dos_print_bytes(b"\x08 \x08");
+                    }
+                }
+                // Esc: start over on a new line
+                0x1B => {
+                    line.clear();
+                    self.dos_print_bytes(b"\\\r\n");
+                }
+                // Extended keys (arrows, function keys) are ignored
+                0x00 | 0xE0 => {}
+                _ if line.len() + 1 < max_length => {
+                    line.push(ascii);
+                    self.dos_print_bytes(&[ascii]);
+                }
+                _ => self.dos_print_bytes(&[0x07]),
+            }
+        }
 
+        // Wait for more keys
+        self.cpu.memory.dos.line = Some(line);
+        self.cpu.ax = saved_ax;
+        self.cpu.ip = self.cpu.ip.wrapping_sub(2);
         51
     }
 
@@ -1600,7 +1973,9 @@ impl PcCpu {
     #[allow(dead_code)] // Called from handle_int21h
     fn int21h_set_interrupt_vector(&mut self) -> u32 {
         // AL = interrupt number, DS:DX = new vector
-        // For now, just acknowledge (interrupt vectors not fully emulated)
+        let vector = (self.cpu.ax & 0xFF) * 4;
+        self.write_word(vector, self.cpu.dx as u16);
+        self.write_word(vector + 2, self.cpu.ds);
         51
     }
 
@@ -1609,9 +1984,9 @@ impl PcCpu {
     fn int21h_get_interrupt_vector(&mut self) -> u32 {
         // AL = interrupt number
         // Returns: ES:BX = interrupt vector
-        // For now, return a dummy value
-        self.cpu.es = 0x0000;
-        self.cpu.bx = 0x0000u32;
+        let vector = (self.cpu.ax & 0xFF) * 4;
+        self.cpu.bx = self.read_word(vector) as u32;
+        self.cpu.es = self.read_word(vector + 2);
         51
     }
 
@@ -1660,6 +2035,23 @@ impl PcCpu {
             )
         });
 
+        // The built-in DOS keeps track of every block
+        if self.cpu.memory.dos.is_running() {
+            let owner = self.cpu.memory.dos.psp;
+            let result = self
+                .cpu
+                .memory
+                .dos
+                .allocate(requested_paragraphs as u16, owner);
+            return match result {
+                Ok(segment) => self.dos_result(Ok(segment)),
+                Err(largest) => {
+                    self.cpu.bx = largest as u32;
+                    self.dos_result(Err(DOS_ERROR_NO_MEMORY))
+                }
+            };
+        }
+
         // Simple allocation strategy: allocate memory above the program's data segment
         // This is a minimal fallback implementation for standalone programs (no DOS loaded).
         // When DOS is loaded, DOS handles INT 21h and this code is not executed.
@@ -1748,6 +2140,16 @@ impl PcCpu {
             format!("INT 0x21 AH=0x49: Free memory at segment 0x{:04X}", segment)
         });
 
+        if self.cpu.memory.dos.is_running() {
+            if self.cpu.memory.dos.free(segment) {
+                self.set_carry_flag(false);
+            } else {
+                self.cpu.ax = DOS_ERROR_INVALID_BLOCK as u32;
+                self.set_carry_flag(true);
+            }
+            return 51;
+        }
+
         // For our simple allocator, we don't actually track freed blocks
         // Just return success
         // A real implementation would maintain a linked list of free blocks
@@ -1777,6 +2179,22 @@ impl PcCpu {
             )
         });
 
+        if self.cpu.memory.dos.is_running() {
+            match self.cpu.memory.dos.resize(segment, new_size as u16) {
+                Ok(()) => self.set_carry_flag(false),
+                Err(0) => {
+                    self.cpu.ax = DOS_ERROR_INVALID_BLOCK as u32;
+                    self.set_carry_flag(true);
+                }
+                Err(max) => {
+                    self.cpu.bx = max as u32;
+                    self.cpu.ax = DOS_ERROR_NO_MEMORY as u32;
+                    self.set_carry_flag(true);
+                }
+            }
+            return 51;
+        }
+
         // For our simple allocator, we always succeed
         // In reality, we would need to check if the new size fits and potentially move the block
         // Most programs use this to shrink their initial PSP allocation to make room for
@@ -1790,7 +2208,10 @@ impl PcCpu {
     #[allow(dead_code)] // Called from handle_int21h
     fn int21h_terminate_with_code(&mut self) -> u32 {
         // AL = return code
-        // For now, just halt like INT 20h
+        // Without the built-in DOS, just halt like INT 20h
+        if self.cpu.memory.dos.is_running() {
+            return self.dos_terminate(self.cpu.ax as u8, None);
+        }
         51
     }
 
@@ -1801,12 +2222,10 @@ impl PcCpu {
         // CX = file attributes
         // Returns: CF clear if success, AX = file handle
         //          CF set if error, AX = error code (03h = path not found, 04h = no handles, 05h = access denied)
-
-        // For now, return "path not found" error
-        // In a real implementation, we would create the file on the mounted disk
-        self.cpu.ax = (self.cpu.ax & 0xFF00) | 0x03; // Path not found
-        self.set_carry_flag(true);
-        51
+        let filename = self.read_asciiz(self.cpu.ds, self.cpu.dx);
+        let attr = (self.cpu.cx as u8) & (ATTR_READ_ONLY | ATTR_HIDDEN | ATTR_SYSTEM);
+        let result = self.dos_open_file(&filename, Some(attr));
+        self.dos_result(result)
     }
 
     /// INT 21h, AH=3Dh: Open existing file
@@ -1918,14 +2337,14 @@ impl PcCpu {
                 });
             }
             _ => {
-                // Not a recognized device - treat as file (not supported)
-                // Return "file not found" error
-                self.cpu.ax = (self.cpu.ax & 0xFF00) | 0x02; // File not found
-                self.set_carry_flag(true);
-
-                emu_core::logging::log(LogCategory::Interrupts, LogLevel::Debug, || {
-                    format!("INT 0x21 AH=0x3D: File '{}' not found (not a device, file I/O not supported)", filename)
-                });
+                // Not a recognized device - a file on one of the FAT drives
+                let result = self.dos_open_file(&filename, None);
+                if result.is_err() {
+                    emu_core::logging::log(LogCategory::Interrupts, LogLevel::Debug, || {
+                        format!("INT 0x21 AH=0x3D: File '{}' not found", filename)
+                    });
+                }
+                self.dos_result(result);
             }
         }
 
@@ -1942,11 +2361,15 @@ impl PcCpu {
         let handle = self.cpu.bx;
 
         // Standard handles (0-4) cannot be closed
-        // File handles >= 5 are user files, but not supported yet
-        if handle >= 5 {
-            // Return "invalid handle" error since we don't support file I/O
-            self.cpu.ax = DOS_ERROR_INVALID_HANDLE as u32;
-            self.set_carry_flag(true);
+        // File handles >= 5 are files opened on the FAT drives
+        if handle >= FIRST_FILE_HANDLE as u32 {
+            match self.dos_close_file(handle as u16) {
+                Ok(()) => self.set_carry_flag(false),
+                Err(code) => {
+                    self.cpu.ax = code as u32;
+                    self.set_carry_flag(true);
+                }
+            }
         } else {
             // Standard handles: succeed but do nothing (can't close stdin/stdout/stderr)
             self.set_carry_flag(false);
@@ -1969,11 +2392,24 @@ impl PcCpu {
         // 0 = stdin, 1 = stdout, 2 = stderr, 3 = stdaux, 4 = stdprn
         // Handles >= 5 are user-opened files
 
-        if handle >= 5 {
-            // File handles >= 5 are not supported (no file system implementation yet)
-            // Return "invalid handle" error
-            self.cpu.ax = DOS_ERROR_INVALID_HANDLE as u32;
-            self.set_carry_flag(true);
+        if handle >= FIRST_FILE_HANDLE as u32 {
+            let count = (self.cpu.cx & 0xFFFF) as usize;
+            let bytes = self.cpu.memory.dos.file_mut(handle as u16).map(|file| {
+                let start = (file.position as usize).min(file.data.len());
+                let end = (start + count).min(file.data.len());
+                file.position += (end - start) as u32;
+                file.data[start..end].to_vec()
+            });
+            match bytes {
+                Some(bytes) => {
+                    self.write_bytes(self.cpu.ds, self.cpu.dx, &bytes);
+                    self.dos_result(Ok(bytes.len() as u16));
+                }
+                None => {
+                    self.cpu.ax = DOS_ERROR_INVALID_HANDLE as u32;
+                    self.set_carry_flag(true);
+                }
+            }
         } else {
             // Standard handles: return 0 bytes read (EOF)
             // This is correct behavior for stdin when no input is available
@@ -1999,132 +2435,1283 @@ impl PcCpu {
         // 0 = stdin, 1 = stdout, 2 = stderr, 3 = stdaux, 4 = stdprn
         // Handles >= 5 are user-opened files
 
-        if handle >= 5 {
-            // File handles >= 5 are not supported (no file system implementation yet)
-            // Return "invalid handle" error
-            self.cpu.ax = DOS_ERROR_INVALID_HANDLE as u32;
-            self.set_carry_flag(true);
+        if handle >= FIRST_FILE_HANDLE as u32 {
+            // Files are written back to the disk image on close
+            let bytes = self.read_bytes(self.cpu.ds, self.cpu.dx, cx as usize);
+            match self.cpu.memory.dos.file_mut(handle as u16) {
+                Some(file) => {
+                    let start = file.position as usize;
+                    if bytes.is_empty() {
+                        // Writing nothing sets the file size to the position
+                        file.data.resize(start, 0);
+                    } else {
+                        let end = start + bytes.len();
+                        if file.data.len() < end {
+                            file.data.resize(end, 0);
+                        }
+                        file.data[start..end].copy_from_slice(&bytes);
+                        file.position = end as u32;
+                    }
+                    file.modified = true;
+                    self.dos_result(Ok(cx as u16));
+                }
+                None => {
+                    self.cpu.ax = DOS_ERROR_INVALID_HANDLE as u32;
+                    self.set_carry_flag(true);
+                }
+            }
         } else {
-            // Standard handles: report all bytes written (but don't actually write)
-            // Real implementation would write to console/device
+            let bytes = self.read_bytes(self.cpu.ds, self.cpu.dx, cx as usize);
+            match handle {
+                // stdout and stderr go to the screen
+                1 | 2 => self.dos_print_bytes(&bytes),
+                // stdprn goes to LPT1
+                4 => {
+                    for byte in bytes {
+                        self.print_to_lpt1(byte);
+                    }
+                }
+                _ => {}
+            }
             self.cpu.ax = cx; // Report all bytes written
             self.set_carry_flag(false);
         }
         51
     }
 
-    /// Handle INT 05h - Print Screen / BOUND Exception
-    #[allow(dead_code)] // Called dynamically based on interrupt number
-    fn handle_int05h(&mut self) -> u32 {
-        // Skip the INT 05h instruction (2 bytes: 0xCD 0x05)
-        self.cpu.ip = self.cpu.ip.wrapping_add(2);
+    /// INT 21h, AH=0Eh: Select default drive
+    fn int21h_select_disk(&mut self) -> u32 {
+        // DL = drive (0 = A:)
+        // Returns: AL = number of drive letters
+        let drive = self.cpu.dx as u8;
+        if drive < DRIVE_COUNT && self.cpu.memory.dos_drive_image(drive).is_some() {
+            self.cpu.memory.dos.current_drive = drive;
+        }
+        self.set_al(DRIVE_COUNT);
+        51
+    }
 
-        // Log stub call
-        self.log_stub_interrupt(0x05, None, "Print Screen/BOUND Exception");
+    /// INT 21h, AH=19h: Get default drive
+    fn int21h_get_current_disk(&mut self) -> u32 {
+        // Returns: AL = drive (0 = A:)
+        self.set_al(self.cpu.memory.dos.current_drive);
+        51
+    }
 
-        // INT 05h is used for:
-        // 1. Print Screen (Shift+PrtSc) - not implemented in emulator
-        // 2. BOUND instruction exception - array bounds check failure
-        // For now, just return (ignore the exception)
+    /// INT 21h, AH=1Ah: Set disk transfer area address
+    fn int21h_set_dta(&mut self) -> u32 {
+        // DS:DX = new DTA (used by find first/next)
+        self.cpu.memory.dos.dta = (self.cpu.ds, self.cpu.dx as u16);
         51
     }
 
-    /// Perform timer tick logic (increment BIOS timer counter)
-    /// This is called by both software INT 08h and hardware timer interrupts
-    fn do_timer_tick(&mut self) {
-        // Increment the timer tick counter in BIOS data area
-        // Timer ticks stored at 0x0040:0x006C (4 bytes, little-endian)
-        let tick_addr = 0x046C;
-        let mut ticks = self.cpu.memory.read(tick_addr) as u32;
-        ticks |= (self.cpu.memory.read(tick_addr + 1) as u32) << 8;
-        ticks |= (self.cpu.memory.read(tick_addr + 2) as u32) << 16;
-        ticks |= (self.cpu.memory.read(tick_addr + 3) as u32) << 24;
+    /// INT 21h, AH=2Fh: Get disk transfer area address
+    fn int21h_get_dta(&mut self) -> u32 {
+        // Returns: ES:BX = current DTA
+        let (segment, offset) = self.cpu.memory.dos.dta;
+        self.cpu.es = segment;
+        self.cpu.bx = offset as u32;
+        51
+    }
 
-        ticks = ticks.wrapping_add(1);
+    /// INT 21h, AH=2Ah: Get system date
+    fn int21h_get_date(&mut self) -> u32 {
+        // Returns: CX = year, DH = month, DL = day, AL = day of week (0 = Sunday)
+        let now = self.cpu.memory.rtc.date_time();
+        self.cpu.cx = now.year.clamp(1980, 2099) as u32;
+        self.cpu.dx = ((now.month as u32) << 8) | now.day as u32;
+        self.set_al(now.weekday());
+        51
+    }
 
-        // Check for midnight rollover (1573040 ticks = 24 hours at 18.2065 ticks/sec)
-        if ticks >= 0x001800B0 {
-            // 1573040 decimal = 0x1800B0
-            ticks = 0;
-            // Set midnight flag at 0x0040:0x0070
-            self.cpu.memory.write(0x0470, 1);
+    /// INT 21h, AH=2Ch: Get system time
+    fn int21h_get_time(&mut self) -> u32 {
+        // Returns: CH = hour, CL = minutes, DH = seconds, DL = hundredths
+        let now = self.cpu.memory.rtc.date_time();
+        self.cpu.cx = ((now.hour as u32) << 8) | now.minute as u32;
+        self.cpu.dx = (now.second as u32) << 8;
+        51
+    }
+
+    /// INT 21h, AH=31h: Terminate and stay resident
+    fn int21h_keep_process(&mut self) -> u32 {
+        // AL = return code, DX = paragraphs to keep
+        if self.cpu.memory.dos.is_running() {
+            return self.dos_terminate(self.cpu.ax as u8, Some(self.cpu.dx as u16));
         }
+        self.log_stub_interrupt(0x21, Some(0x31), "Terminate and stay resident");
+        51
+    }
 
-        // Write back the updated tick count
-        self.cpu.memory.write(tick_addr, (ticks & 0xFF) as u8);
-        self.cpu
-            .memory
-            .write(tick_addr + 1, ((ticks >> 8) & 0xFF) as u8);
-        self.cpu
-            .memory
-            .write(tick_addr + 2, ((ticks >> 16) & 0xFF) as u8);
-        self.cpu
+    /// INT 21h, AH=36h: Get free disk space
+    fn int21h_get_free_space(&mut self) -> u32 {
+        // DL = drive (0 = default, 1 = A:)
+        // Returns: AX = sectors per cluster (FFFFh if the drive is invalid),
+        //          BX = free clusters, CX = bytes per sector, DX = total clusters
+        let drive = match self.cpu.dx as u8 {
+            0 => self.cpu.memory.dos.current_drive,
+            drive => drive - 1,
+        };
+        let space = self
+            .cpu
             .memory
-            .write(tick_addr + 3, ((ticks >> 24) & 0xFF) as u8);
+            .dos_volume(drive)
+            .ok()
+            .map(|(volume, image)| {
+                let cluster_bytes = volume.cluster_bytes();
+                let free_clusters = volume.free_bytes(image) / cluster_bytes as u64;
+                (cluster_bytes, free_clusters, volume.cluster_count())
+            });
+        match space {
+            Some((cluster_bytes, free_clusters, total_clusters)) => {
+                self.cpu.ax = (cluster_bytes / 512) as u32;
+                self.cpu.bx = free_clusters.min(0xFFFF) as u32;
+                self.cpu.cx = 512;
+                self.cpu.dx = total_clusters.min(0xFFFF) as u32;
+            }
+            None => self.cpu.ax = 0xFFFF,
+        }
+        51
     }
 
-    /// Handle INT 08h - Timer Tick (System Timer) - SOFTWARE interrupt version
-    /// Called when CPU executes INT 08h instruction
-    #[allow(dead_code)] // Called dynamically based on interrupt number
-    fn handle_int08h(&mut self) -> u32 {
-        // Skip the INT 08h instruction (2 bytes: 0xCD 0x08)
-        self.cpu.ip = self.cpu.ip.wrapping_add(2);
+    /// INT 21h, AH=3Bh: Change current directory
+    fn int21h_change_dir(&mut self) -> u32 {
+        // DS:DX = ASCIIZ path
+        let path = self.read_asciiz(self.cpu.ds, self.cpu.dx);
+        let result = self.dos_change_dir(&path).map(|()| 0);
+        self.dos_result(result)
+    }
+
+    /// INT 21h, AH=41h: Delete file
+    fn int21h_delete_file(&mut self) -> u32 {
+        // DS:DX = ASCIIZ filename
+        let filename = self.read_asciiz(self.cpu.ds, self.cpu.dx);
+        let result = self.dos_delete_files(&filename).map(|_| 0);
+        self.dos_result(result)
+    }
+
+    /// INT 21h, AH=42h: Move file pointer
+    fn int21h_seek(&mut self) -> u32 {
+        // BX = handle, AL = origin (0 = start, 1 = current, 2 = end),
+        // CX:DX = signed offset
+        // Returns: DX:AX = new position
+        let handle = self.cpu.bx as u16;
+        let origin = self.cpu.ax as u8;
+        let offset = (((self.cpu.cx & 0xFFFF) << 16) | (self.cpu.dx & 0xFFFF)) as i32 as i64;
+        let result = if handle < FIRST_FILE_HANDLE {
+            // Devices have no position
+            Ok(0)
+        } else {
+            match self.cpu.memory.dos.file_mut(handle) {
+                Some(file) => {
+                    let base = match origin {
+                        0 => Some(0),
+                        1 => Some(file.position as i64),
+                        2 => Some(file.data.len() as i64),
+                        _ => None,
+                    };
+                    match base {
+                        Some(base) => {
+                            file.position = (base + offset).clamp(0, u32::MAX as i64) as u32;
+                            Ok(file.position)
+                        }
+                        None => Err(DOS_ERROR_INVALID_FUNCTION),
+                    }
+                }
+                None => Err(DOS_ERROR_INVALID_HANDLE),
+            }
+        };
+        match result {
+            Ok(position) => {
+                self.cpu.dx = position >> 16;
+                self.dos_result(Ok(position as u16))
+            }
+            Err(code) => self.dos_result(Err(code)),
+        }
+    }
 
-        // Perform timer tick logic
-        self.do_timer_tick();
+    /// INT 21h, AH=43h: Get or set file attributes
+    fn int21h_file_attributes(&mut self) -> u32 {
+        // AL = 00h get (returns CX = attributes), 01h set from CX
+        // DS:DX = ASCIIZ filename
+        let filename = self.read_asciiz(self.cpu.ds, self.cpu.dx);
+        let set = match self.cpu.ax as u8 {
+            0x00 => None,
+            0x01 => Some(self.cpu.cx as u8),
+            _ => return self.dos_result(Err(DOS_ERROR_INVALID_FUNCTION)),
+        };
+        match self.dos_file_attributes(&filename, set) {
+            Ok(attr) => {
+                self.cpu.cx = attr as u32;
+                self.set_carry_flag(false);
+                51
+            }
+            Err(code) => self.dos_result(Err(code)),
+        }
+    }
 
-        // Call INT 1Ch (user timer tick handler)
-        // This is the standard PC/AT BIOS behavior - INT 08h chains to INT 1Ch
-        // Programs can hook INT 1Ch to execute code on every timer tick
-        // Since we can't directly trigger an interrupt from here (trigger_interrupt is private),
-        // we'll note that programs expecting INT 1Ch will need to hook INT 08h instead
-        // The BIOS default INT 1Ch handler is just an IRET at F000:0040
+    /// INT 21h, AH=47h: Get current directory
+    fn int21h_get_current_dir(&mut self) -> u32 {
+        // DL = drive (0 = default, 1 = A:), DS:SI = 64-byte buffer
+        // Returns: the path without drive and leading backslash
+        let drive = match self.cpu.dx as u8 {
+            0 => self.cpu.memory.dos.current_drive,
+            drive => drive - 1,
+        };
+        if drive >= DRIVE_COUNT {
+            return self.dos_result(Err(DOS_ERROR_INVALID_DRIVE));
+        }
+        let path = dir_string(drive, self.cpu.memory.dos.current_dir(drive));
+        let mut bytes = path.as_bytes()[3..].to_vec();
+        bytes.push(0);
+        self.write_bytes(self.cpu.ds, self.cpu.si, &bytes);
+        self.dos_result(Ok(0x0100))
+    }
+
+    /// INT 21h, AH=4Bh: Load and execute a program
+    fn int21h_exec(&mut self) -> u32 {
+        // AL = 00h (load and execute), DS:DX = ASCIIZ program name,
+        // ES:BX = parameter block (environment, command tail, FCBs)
+        if !self.cpu.memory.dos.is_running() || self.cpu.ax as u8 != 0x00 {
+            self.log_stub_interrupt(0x21, Some(0x4B), "Exec (built-in DOS, AL=00h only)");
+            return self.dos_result(Err(DOS_ERROR_INVALID_FUNCTION));
+        }
+        let filename = self.read_asciiz(self.cpu.ds, self.cpu.dx);
+        let block = ((self.cpu.es as u32) << 4) + (self.cpu.bx & 0xFFFF);
+        let tail_offset = self.read_word(block + 2);
+        let tail_segment = self.read_word(block + 4);
+        let tail_addr = ((tail_segment as u32) << 4) + tail_offset as u32;
+        let tail_length = self.cpu.memory.read(tail_addr) as usize;
+        let tail = self.read_bytes(tail_segment, tail_offset as u32 + 1, tail_length);
+
+        let parent = ParentProcess {
+            psp: self.cpu.memory.dos.psp,
+            dta: self.cpu.memory.dos.dta,
+            cs: self.cpu.cs,
+            ip: self.cpu.ip as u16,
+            ss: self.cpu.ss,
+            sp: self.cpu.sp as u16,
+            ds: self.cpu.ds,
+            es: self.cpu.es,
+        };
+        match self.dos_load_program(&filename, &tail) {
+            Ok(()) => {
+                self.cpu.memory.dos.parents.push(parent);
+                51
+            }
+            Err(code) => self.dos_result(Err(code)),
+        }
+    }
 
+    /// INT 21h, AH=4Dh: Get return code of the last program
+    fn int21h_get_return_code(&mut self) -> u32 {
+        // Returns: AL = exit code, AH = termination type (0 = normal, 3 = TSR)
+        self.cpu.ax = self.cpu.memory.dos.return_code as u32;
+        self.cpu.memory.dos.return_code = 0;
         51
     }
 
-    /// Handle hardware timer interrupt from PIT
-    /// Called when PIT generates IRQ 0, does NOT skip instruction bytes
-    fn handle_hardware_timer_interrupt(&mut self) {
-        // For hardware interrupts, we DON'T skip instruction bytes
-        // (there's no INT instruction to skip)
-
-        // Perform timer tick logic
-        self.do_timer_tick();
-
-        // Hardware interrupts don't return cycle counts
+    /// INT 21h, AH=4Eh: Find first matching file
+    fn int21h_find_first(&mut self) -> u32 {
+        // DS:DX = ASCIIZ file spec (wildcards allowed), CX = search attributes
+        // Returns: the match in the DTA, or CF set and AX = error code
+        let spec = self.read_asciiz(self.cpu.ds, self.cpu.dx);
+        let search = self.cpu.cx as u8;
+        let matches = self.dos_path(&spec).and_then(|path| {
+            self.dos_match_files(&path, search)
+                .map_err(DosFsError::code)
+        });
+        match matches {
+            Ok((_, matches)) => {
+                self.cpu.memory.dos.search = matches;
+                self.dos_find_next()
+            }
+            Err(code) => {
+                self.cpu.memory.dos.search.clear();
+                self.dos_result(Err(code))
+            }
+        }
     }
 
-    /// Handle INT 09h - Keyboard Hardware Interrupt
-    /// Called by keyboard hardware when a key is pressed or released
-    #[allow(dead_code)] // Called dynamically based on interrupt number
-    fn handle_int09h(&mut self) -> u32 {
-        // Skip the INT 09h instruction (2 bytes: 0xCD 0x09)
-        self.cpu.ip = self.cpu.ip.wrapping_add(2);
+    /// INT 21h, AH=4Fh: Find next matching file
+    fn int21h_find_next(&mut self) -> u32 {
+        self.dos_find_next()
+    }
 
-        // Log stub call (partial implementation)
-        self.log_stub_interrupt(0x09, None, "Keyboard Hardware Interrupt (partial stub)");
+    /// INT 21h, AH=50h: Set current PSP
+    fn int21h_set_psp(&mut self) -> u32 {
+        // BX = PSP segment
+        self.cpu.memory.dos.psp = self.cpu.bx as u16;
+        51
+    }
 
-        // Hardware keyboard interrupt
-        // This is typically triggered by keyboard controller when a key is pressed
-        // The BIOS interrupt handler would:
-        // 1. Read scancode from keyboard port (60h)
-        // 2. Convert to ASCII if printable
-        // 3. Store in keyboard buffer at 0040:001Eh
-        // 4. Update buffer pointers
-        // 5. Send EOI to interrupt controller
+    /// INT 21h, AH=51h/62h: Get current PSP
+    fn int21h_get_psp(&mut self) -> u32 {
+        // Returns: BX = PSP segment
+        self.cpu.bx = self.cpu.memory.dos.psp as u32;
+        51
+    }
 
-        // For emulator, keyboard input is handled by INT 16h services
-        // We just acknowledge the interrupt
+    /// Finish an INT 21h call: CF clear and a value in AX, or CF set and a
+    /// DOS error code in AX
+    fn dos_result(&mut self, result: Result<u16, u16>) -> u32 {
+        let (ax, failed) = match result {
+            Ok(value) => (value, false),
+            Err(code) => (code, true),
+        };
+        self.cpu.ax = ax as u32;
+        self.set_carry_flag(failed);
         51
     }
 
-    /// Handle INT 11h - Equipment List
-    /// Returns equipment flags in AX
-    #[allow(dead_code)] // Called dynamically based on interrupt number
-    fn handle_int11h(&mut self) -> u32 {
-        // Skip the INT 11h instruction (2 bytes: 0xCD 0x11)
-        self.cpu.ip = self.cpu.ip.wrapping_add(2);
+    /// Read an ASCIIZ string (a path name) at segment:offset
+    fn read_asciiz(&self, segment: u16, offset: u32) -> String {
+        let mut text = String::new();
+        for i in 0..128 {
+            let byte = self
+                .cpu
+                .memory
+                .read(((segment as u32) << 4) + ((offset + i) & 0xFFFF));
+            if byte == 0 {
+                break;
+            }
+            text.push(byte as char);
+        }
+        text
+    }
+
+    /// Read bytes at segment:offset (the offset wraps within the segment)
+    fn read_bytes(&self, segment: u16, offset: u32, len: usize) -> Vec<u8> {
+        (0..len as u32)
+            .map(|i| {
+                self.cpu
+                    .memory
+                    .read(((segment as u32) << 4) + ((offset + i) & 0xFFFF))
+            })
+            .collect()
+    }
+
+    /// Write bytes at segment:offset (the offset wraps within the segment)
+    fn write_bytes(&mut self, segment: u16, offset: u32, bytes: &[u8]) {
+        for (i, &byte) in bytes.iter().enumerate() {
+            self.cpu.memory.write(
+                ((segment as u32) << 4) + ((offset + i as u32) & 0xFFFF),
+                byte,
+            );
+        }
+    }
+
+    /// Print text on the console with INT 10h teletype output ("\n" is CR LF)
+    fn dos_print(&mut self, text: &str) {
+        self.dos_print_bytes(text.replace('\n', "\r\n").as_bytes());
+    }
+
+    /// Print bytes on the console with INT 10h teletype output
+    fn dos_print_bytes(&mut self, bytes: &[u8]) {
+        let saved_ax = self.cpu.ax;
+        for &byte in bytes {
+            self.cpu.ax = (saved_ax & 0xFF00) | byte as u32;
+            self.int10h_teletype_output();
+        }
+        self.cpu.ax = saved_ax;
+    }
+
+    /// Current date and time as packed DOS (time, date)
+    fn dos_now(&self) -> (u16, u16) {
+        dos_date_time(&self.cpu.memory.rtc.date_time())
+    }
+
+    /// Resolve a path against the current drive and directory
+    fn dos_path(&self, path: &str) -> Result<DosPath, u16> {
+        self.cpu
+            .memory
+            .dos
+            .parse_path(path)
+            .ok_or(DosFsError::PathNotFound.code())
+    }
+
+    /// Read a whole file: (directory cluster, entry, contents)
+    fn dos_read_file(&self, path: &DosPath) -> Result<(u16, DirEntry, Vec<u8>), DosFsError> {
+        let name = path
+            .name
+            .filter(|name| !has_wildcards(name))
+            .ok_or(DosFsError::FileNotFound)?;
+        let (volume, image) = self.cpu.memory.dos_volume(path.drive)?;
+        let dir = volume.find_dir(image, &path.dirs)?;
+        match volume.find(image, dir, &name) {
+            Some(entry) if !entry.is_dir() => {
+                let data = volume.read(image, &entry);
+                Ok((dir, entry, data))
+            }
+            _ => Err(DosFsError::FileNotFound),
+        }
+    }
+
+    /// Entries matching a path that may contain wildcards, with the cluster
+    /// of their directory (see `dos::attributes_match` for `search`)
+    fn dos_match_files(
+        &self,
+        path: &DosPath,
+        search: u8,
+    ) -> Result<(u16, Vec<DirEntry>), DosFsError> {
+        let pattern = path.name.unwrap_or([b'?'; 11]);
+        let (volume, image) = self.cpu.memory.dos_volume(path.drive)?;
+        let dir = volume.find_dir(image, &path.dirs)?;
+        let matches = volume
+            .list(image, dir)
+            .into_iter()
+            .filter(|entry| {
+                attributes_match(search, entry.attr) && name_matches(&pattern, &entry.name)
+            })
+            .collect();
+        Ok((dir, matches))
+    }
+
+    /// Open a file for a new handle, or create it with the given attributes
+    fn dos_open_file(&mut self, filename: &str, create: Option<u8>) -> Result<u16, u16> {
+        let path = self.dos_path(filename)?;
+        let (dir, entry, data) = match create {
+            Some(attr) => {
+                let name = path
+                    .name
+                    .filter(|name| !has_wildcards(name))
+                    .ok_or(DosFsError::PathNotFound.code())?;
+                let dir = {
+                    let (volume, image) = self
+                        .cpu
+                        .memory
+                        .dos_volume(path.drive)
+                        .map_err(DosFsError::code)?;
+                    volume
+                        .find_dir(image, &path.dirs)
+                        .map_err(DosFsError::code)?
+                };
+                let (time, date) = self.dos_now();
+                let entry = DirEntry::file(name, attr | ATTR_ARCHIVE, time, date);
+                self.cpu
+                    .memory
+                    .write_dos_file(path.drive, dir, &entry, &[])
+                    .map_err(DosFsError::code)?;
+                (dir, entry, Vec::new())
+            }
+            None => self.dos_read_file(&path).map_err(|error| match error {
+                // No disk in the drive: the file isn't there either
+                DosFsError::NotReady => DosFsError::FileNotFound.code(),
+                error => error.code(),
+            })?,
+        };
+        let owner = self.cpu.memory.dos.psp;
+        self.cpu
+            .memory
+            .dos
+            .add_file(OpenFile {
+                drive: path.drive,
+                dir,
+                entry,
+                data,
+                position: 0,
+                modified: false,
+                owner,
+            })
+            .ok_or(DOS_ERROR_TOO_MANY_FILES)
+    }
+
+    /// Close a file handle, writing the file back if it was changed
+    fn dos_close_file(&mut self, handle: u16) -> Result<(), u16> {
+        let file = self
+            .cpu
+            .memory
+            .dos
+            .take_file(handle)
+            .ok_or(DOS_ERROR_INVALID_HANDLE)?;
+        if file.modified {
+            let (time, date) = self.dos_now();
+            let entry = DirEntry {
+                attr: file.entry.attr | ATTR_ARCHIVE,
+                time,
+                date,
+                ..file.entry
+            };
+            self.cpu
+                .memory
+                .write_dos_file(file.drive, file.dir, &entry, &file.data)
+                .map_err(DosFsError::code)?;
+        }
+        Ok(())
+    }
+
+    /// Delete the files matching a name (wildcards allowed); returns how many
+    fn dos_delete_files(&mut self, filename: &str) -> Result<usize, u16> {
+        let path = self.dos_path(filename)?;
+        if path.name.is_none() {
+            return Err(DosFsError::FileNotFound.code());
+        }
+        let (dir, matches) = self.dos_match_files(&path, 0).map_err(DosFsError::code)?;
+        if matches.is_empty() {
+            return Err(DosFsError::FileNotFound.code());
+        }
+        for entry in &matches {
+            if entry.attr & ATTR_READ_ONLY != 0 {
+                return Err(DosFsError::AccessDenied.code());
+            }
+            self.cpu
+                .memory
+                .delete_dos_file(path.drive, dir, &entry.name)
+                .map_err(DosFsError::code)?;
+        }
+        Ok(matches.len())
+    }
+
+    /// Get the attributes of a file or directory, optionally changing them
+    /// first (files only)
+    fn dos_file_attributes(&mut self, filename: &str, set: Option<u8>) -> Result<u8, u16> {
+        let path = self.dos_path(filename)?;
+        if path.name.is_none_or(|name| has_wildcards(&name)) {
+            return Err(DosFsError::FileNotFound.code());
+        }
+        let (dir, matches) = self
+            .dos_match_files(&path, ATTR_HIDDEN | ATTR_SYSTEM | ATTR_DIRECTORY)
+            .map_err(DosFsError::code)?;
+        let entry = matches
+            .into_iter()
+            .next()
+            .ok_or(DosFsError::FileNotFound.code())?;
+        let Some(attr) = set else {
+            return Ok(entry.attr);
+        };
+        if entry.is_dir() {
+            return Err(DosFsError::AccessDenied.code());
+        }
+        let (_, _, data) = self.dos_read_file(&path).map_err(DosFsError::code)?;
+        let attr = attr & (ATTR_READ_ONLY | ATTR_HIDDEN | ATTR_SYSTEM | ATTR_ARCHIVE);
+        self.cpu
+            .memory
+            .write_dos_file(path.drive, dir, &DirEntry { attr, ..entry }, &data)
+            .map_err(DosFsError::code)?;
+        Ok(attr)
+    }
+
+    /// Make a directory the current one on its drive
+    fn dos_change_dir(&mut self, path: &str) -> Result<(), u16> {
+        let path = self
+            .dos_path(path)?
+            .into_dir()
+            .ok_or(DosFsError::PathNotFound.code())?;
+        {
+            let (volume, image) = self
+                .cpu
+                .memory
+                .dos_volume(path.drive)
+                .map_err(DosFsError::code)?;
+            volume
+                .find_dir(image, &path.dirs)
+                .map_err(DosFsError::code)?;
+        }
+        self.cpu.memory.dos.set_current_dir(path.drive, path.dirs);
+        Ok(())
+    }
+
+    /// Put the next find first/next match in the DTA
+    fn dos_find_next(&mut self) -> u32 {
+        if self.cpu.memory.dos.search.is_empty() {
+            return self.dos_result(Err(DOS_ERROR_NO_MORE_FILES));
+        }
+        let entry = self.cpu.memory.dos.search.remove(0);
+
+        // DTA layout: 21 reserved bytes, attribute, time, date, size, name
+        let mut record = [0u8; 43];
+        record[0x15] = entry.attr;
+        record[0x16..0x18].copy_from_slice(&entry.time.to_le_bytes());
+        record[0x18..0x1A].copy_from_slice(&entry.date.to_le_bytes());
+        record[0x1A..0x1E].copy_from_slice(&entry.size.to_le_bytes());
+        let name = display_name(&entry.name);
+        record[0x1E..0x1E + name.len()].copy_from_slice(name.as_bytes());
+
+        let (segment, offset) = self.cpu.memory.dos.dta;
+        self.write_bytes(segment, offset as u32, &record);
+        self.dos_result(Ok(0))
+    }
+
+    /// Load a .COM or .EXE file into a new process and jump to it
+    fn dos_load_program(&mut self, filename: &str, tail: &[u8]) -> Result<(), u16> {
+        let path = self.dos_path(filename)?;
+        let (_, entry, data) = self.dos_read_file(&path).map_err(|error| match error {
+            DosFsError::NotReady => DosFsError::FileNotFound.code(),
+            error => error.code(),
+        })?;
+        let dir = path.dir_string();
+        let separator = if dir.ends_with('\\') { "" } else { "\\" };
+        let program_path = format!("{}{}{}", dir, separator, display_name(&entry.name));
+        self.dos_load_image(&program_path, &data, tail)
+    }
+
+    /// Start a program from its file contents
+    ///
+    /// The program gets an environment block and the largest free memory
+    /// block (or what its EXE header asks for). An EXE image is loaded right
+    /// after the PSP, its relocation table applied and CS:IP and SS:SP taken
+    /// from the header; a .COM file is loaded at PSP:0100. Registers are only
+    /// changed on success.
+    fn dos_load_image(&mut self, program_path: &str, data: &[u8], tail: &[u8]) -> Result<(), u16> {
+        let header = ExeHeader::parse(data);
+        if header.is_some_and(|header| !header.is_valid(data)) {
+            return Err(DOS_ERROR_BAD_FORMAT);
+        }
+
+        // Environment: the variables, then a count and the program's path
+        let mut environment = Vec::new();
+        for variable in ENVIRONMENT {
+            environment.extend_from_slice(variable.as_bytes());
+            environment.push(0);
+        }
+        environment.extend_from_slice(&[0, 1, 0]);
+        environment.extend_from_slice(program_path.as_bytes());
+        environment.push(0);
+
+        // Memory needed (minimum) and wanted (maximum) in paragraphs,
+        // including the 256-byte PSP
+        let (min_paragraphs, max_paragraphs) = match &header {
+            Some(header) => {
+                let image = 0x10 + header.image_bytes.div_ceil(16) as u32;
+                (
+                    image + header.min_alloc as u32,
+                    image.saturating_add(header.max_alloc as u32),
+                )
+            }
+            None => {
+                if data.len() > 0xFF00 {
+                    return Err(DOS_ERROR_NO_MEMORY);
+                }
+                // The code plus some stack; a .COM file gets all memory
+                (0x20 + data.len().div_ceil(16) as u32, 0xFFFF)
+            }
+        };
+
+        let dos = &mut self.cpu.memory.dos;
+        let parent_psp = dos.psp;
+        let env_segment = dos
+            .allocate(environment.len().div_ceil(16) as u16, parent_psp)
+            .map_err(|_| DOS_ERROR_NO_MEMORY)?;
+        let available = dos.largest_free() as u32;
+        if available < min_paragraphs {
+            dos.free(env_segment);
+            return Err(DOS_ERROR_NO_MEMORY);
+        }
+        let paragraphs = max_paragraphs.clamp(min_paragraphs, available) as u16;
+        let psp = dos
+            .allocate(paragraphs, 0)
+            .map_err(|_| DOS_ERROR_NO_MEMORY)?;
+        dos.set_owner(env_segment, psp);
+        dos.psp = psp;
+        dos.dta = (psp, 0x80);
+
+        self.write_bytes(env_segment, 0, &environment);
+        self.dos_build_psp(psp, paragraphs, env_segment, parent_psp, tail);
+
+        let (cs, ip, ss, sp) = match header {
+            Some(header) => {
+                let load_segment = psp + 0x10;
+                let load_addr = (load_segment as u32) << 4;
+                let start = header.header_bytes.min(data.len());
+                let end = (header.header_bytes + header.image_bytes).min(data.len());
+                for (i, &byte) in data[start..end].iter().enumerate() {
+                    self.cpu.memory.write(load_addr + i as u32, byte);
+                }
+                for (segment, offset) in header.relocations(data) {
+                    let addr = ((load_segment.wrapping_add(segment) as u32) << 4) + offset as u32;
+                    let value = self.read_word(addr);
+                    self.write_word(addr, value.wrapping_add(load_segment));
+                }
+                (
+                    load_segment.wrapping_add(header.cs),
+                    header.ip,
+                    load_segment.wrapping_add(header.ss),
+                    header.sp,
+                )
+            }
+            None => {
+                self.write_bytes(psp, 0x100, data);
+                // Stack at the top of the segment with a 0 return address
+                // (RET runs the INT 20h at PSP:0000)
+                let sp = if paragraphs >= 0x1000 {
+                    0xFFFE
+                } else {
+                    paragraphs * 16 - 2
+                };
+                self.write_word(((psp as u32) << 4) + sp as u32, 0);
+                (psp, 0x0100, psp, sp)
+            }
+        };
+
+        emu_core::logging::log(LogCategory::Interrupts, LogLevel::Info, || {
+            format!(
+                "DOS: running {} at {:04X}:{:04X} (PSP {:04X}, {} paragraphs)",
+                program_path, cs, ip, psp, paragraphs
+            )
+        });
+
+        self.cpu.cs = cs;
+        self.cpu.ip = ip as u32;
+        self.cpu.ss = ss;
+        self.cpu.sp = sp as u32;
+        self.cpu.ds = psp;
+        self.cpu.es = psp;
+        self.cpu.ax = 0;
+        self.cpu.bx = 0;
+        self.cpu.cx = 0x00FF;
+        self.cpu.dx = psp as u32;
+        Ok(())
+    }
+
+    /// Fill in a program segment prefix
+    fn dos_build_psp(&mut self, psp: u16, paragraphs: u16, env: u16, parent: u16, tail: &[u8]) {
+        let mut prefix = [0u8; 0x100];
+        // INT 20h for programs that return with RET
+        prefix[0x00..0x02].copy_from_slice(&[0xCD, 0x20]);
+        prefix[0x02..0x04].copy_from_slice(&psp.wrapping_add(paragraphs).to_le_bytes());
+        // Terminate, Ctrl-Break and critical error addresses: the prompt
+        for offset in [0x0A, 0x0E, 0x12] {
+            prefix[offset..offset + 2].copy_from_slice(&DOS_SHELL_OFFSET.to_le_bytes());
+            prefix[offset + 2..offset + 4].copy_from_slice(&0xF000u16.to_le_bytes());
+        }
+        let parent = if parent == 0 { psp } else { parent };
+        prefix[0x16..0x18].copy_from_slice(&parent.to_le_bytes());
+        // Job file table: the five standard devices, the rest unused
+        for (i, slot) in prefix[0x18..0x2C].iter_mut().enumerate() {
+            *slot = if i < FIRST_FILE_HANDLE as usize {
+                i as u8
+            } else {
+                0xFF
+            };
+        }
+        prefix[0x2C..0x2E].copy_from_slice(&env.to_le_bytes());
+        prefix[0x32..0x34].copy_from_slice(&20u16.to_le_bytes());
+        prefix[0x34..0x36].copy_from_slice(&0x18u16.to_le_bytes());
+        prefix[0x36..0x38].copy_from_slice(&psp.to_le_bytes());
+        // INT 21h; RETF for CALL PSP:0050
+        prefix[0x50..0x53].copy_from_slice(&[0xCD, 0x21, 0xCB]);
+
+        // FCBs from the first two arguments
+        let tail_text = String::from_utf8_lossy(tail).to_string();
+        let mut arguments = tail_text.split_whitespace();
+        for fcb in [0x5C, 0x6C] {
+            let argument = arguments.next().unwrap_or("");
+            let bytes = argument.as_bytes();
+            let (drive, name) = if bytes.len() >= 2 && bytes[1] == b':' {
+                (
+                    bytes[0].to_ascii_uppercase().wrapping_sub(b'A' - 1),
+                    &argument[2..],
+                )
+            } else {
+                (0, argument)
+            };
+            prefix[fcb] = drive;
+            let name = short_name(name)
+                .filter(|_| !name.is_empty())
+                .unwrap_or([b' '; 11]);
+            prefix[fcb + 1..fcb + 12].copy_from_slice(&name);
+        }
+
+        // Command tail
+        let length = tail.len().min(126);
+        prefix[0x80] = length as u8;
+        prefix[0x81..0x81 + length].copy_from_slice(&tail[..length]);
+        prefix[0x81 + length] = 0x0D;
+
+        self.write_bytes(psp, 0, &prefix);
+    }
+
+    /// End the running program and return to its parent or to the prompt
+    ///
+    /// `keep` is the number of paragraphs a resident program keeps.
+    fn dos_terminate(&mut self, code: u8, keep: Option<u16>) -> u32 {
+        let psp = self.cpu.memory.dos.psp;
+        match keep {
+            Some(paragraphs) => {
+                let _ = self.cpu.memory.dos.resize(psp, paragraphs);
+            }
+            None => {
+                for handle in self.cpu.memory.dos.files_owned_by(psp) {
+                    let _ = self.dos_close_file(handle);
+                }
+                self.cpu.memory.dos.free_owned_by(psp);
+            }
+        }
+        let termination_type = if keep.is_some() { 0x03 } else { 0x00 };
+        self.cpu.memory.dos.return_code = (termination_type << 8) | code as u16;
+
+        emu_core::logging::log(LogCategory::Interrupts, LogLevel::Info, || {
+            format!("DOS: program at PSP {:04X} exited with code {}", psp, code)
+        });
+
+        match self.cpu.memory.dos.parents.pop() {
+            Some(parent) => {
+                let dos = &mut self.cpu.memory.dos;
+                dos.psp = parent.psp;
+                dos.dta = parent.dta;
+                self.cpu.cs = parent.cs;
+                self.cpu.ip = parent.ip as u32;
+                self.cpu.ss = parent.ss;
+                self.cpu.sp = parent.sp as u32;
+                self.cpu.ds = parent.ds;
+                self.cpu.es = parent.es;
+                self.set_carry_flag(false);
+            }
+            None => self.dos_enter_shell(),
+        }
+        51
+    }
+
+    /// Start the built-in DOS (INT 18h when no disk boots)
+    fn start_builtin_dos(&mut self) -> u32 {
+        let memory_kb = self.read_word(0x413) as u32;
+        let memory_top = (memory_kb * 64).min(0xA000) as u16;
+        // First drive with a FAT volume: A:, then C:, then B:
+        let drive = [0, 2, 1]
+            .into_iter()
+            .find(|&drive| self.cpu.memory.dos_volume(drive).is_ok())
+            .unwrap_or(0);
+        self.cpu.memory.dos.start(memory_top, drive);
+
+        // INT 20h and INT 21h go to the built-in DOS
+        self.write_word(0x20 * 4, DOS_TERMINATE_OFFSET);
+        self.write_word(0x20 * 4 + 2, 0xF000);
+        self.write_word(0x21 * 4, DOS_ENTRY_OFFSET);
+        self.write_word(0x21 * 4 + 2, 0xF000);
+
+        emu_core::logging::log(LogCategory::Interrupts, LogLevel::Info, || {
+            format!(
+                "INT 18h: no bootable disk, starting the built-in DOS on {}:",
+                (b'A' + drive) as char
+            )
+        });
+        self.dos_enter_shell();
+
+        // A program loaded with the machine runs straight away and returns
+        // to the prompt when it exits
+        let Some(program) = self.cpu.memory.executable().map(<[u8]>::to_vec) else {
+            self.dos_print(
+                "\nNo bootable disk - starting the built-in DOS\nType HELP for a list of commands\n",
+            );
+            return 51;
+        };
+        let name = if ExeHeader::parse(&program).is_some() {
+            "PROGRAM.EXE"
+        } else {
+            "PROGRAM.COM"
+        };
+        let path = format!("{}:\\{}", (b'A' + drive) as char, name);
+        if let Err(code) = self.dos_load_image(&path, &program, &[]) {
+            self.dos_print(&format!("{}\n", dos::error_message(code)));
+        }
+        51
+    }
+
+    /// Go to the prompt loop in the BIOS ROM with the shell's stack
+    fn dos_enter_shell(&mut self) {
+        let dos = &mut self.cpu.memory.dos;
+        dos.psp = 0;
+        dos.dta = (SHELL_SEGMENT, 0x80);
+        dos.search.clear();
+        dos.line = None;
+        self.cpu.cs = 0xF000;
+        self.cpu.ip = DOS_SHELL_OFFSET as u32;
+        (self.cpu.ss, self.cpu.sp) = (SHELL_STACK.0, SHELL_STACK.1 as u32);
+        self.cpu.ds = SHELL_SEGMENT;
+        self.cpu.es = SHELL_SEGMENT;
+        // Keyboard and timer interrupts must run while the prompt waits
+        self.cpu.flags |= 0x0200;
+    }
+
+    /// Handle the INT 21h entry point of the built-in DOS (F000:DOS_ENTRY_OFFSET)
+    ///
+    /// Reached through the INT 21h vector, e.g. when a program that hooked
+    /// INT 21h chains to the old handler. The interrupt frame is popped and the
+    /// call is serviced like a direct INT 21h.
+    fn handle_dos_entry(&mut self) -> u32 {
+        let frame_sp = self.cpu.sp;
+        let frame = ((self.cpu.ss as u32) << 4) + (frame_sp & 0xFFFF);
+        let ip = self.read_word(frame) as u32;
+        let cs = self.read_word(frame + 2);
+        let flags = self.read_word(frame + 4) as u32;
+        self.cpu.ip = ip;
+        self.cpu.cs = cs;
+        self.cpu.flags = flags;
+        self.cpu.sp = frame_sp.wrapping_add(6) & 0xFFFF;
+
+        let cycles = self.int21h_dispatch();
+
+        // A blocking call rewound to its INT instruction: wait here instead
+        if self.cpu.cs == cs && self.cpu.ip == ip.wrapping_sub(2) {
+            self.cpu.sp = frame_sp;
+            self.cpu.cs = 0xF000;
+            self.cpu.ip = DOS_ENTRY_OFFSET as u32;
+        }
+        cycles
+    }
+
+    /// Show the prompt and point DS:DX at the command line buffer
+    /// (F000:DOS_SHELL_OFFSET)
+    fn handle_shell_prompt(&mut self) -> u32 {
+        let drive = self.cpu.memory.dos.current_drive;
+        let prompt = dir_string(drive, self.cpu.memory.dos.current_dir(drive));
+        self.dos_print(&format!("\n{}>", prompt));
+
+        let buffer = (SHELL_SEGMENT as u32) << 4;
+        self.cpu.memory.write(buffer, MAX_COMMAND_LINE);
+        self.cpu.memory.write(buffer + 1, 0);
+        self.cpu.ds = SHELL_SEGMENT;
+        self.cpu.dx = 0;
+        self.cpu.ip = self.cpu.ip.wrapping_add(1);
+        51
+    }
+
+    /// Run the command typed at the prompt (F000:DOS_COMMAND_OFFSET)
+    fn handle_shell_command(&mut self) -> u32 {
+        self.cpu.ip = self.cpu.ip.wrapping_add(1);
+        let buffer = (SHELL_SEGMENT as u32) << 4;
+        let length = self.cpu.memory.read(buffer + 1) as usize;
+        let line = String::from_utf8_lossy(&self.read_bytes(SHELL_SEGMENT, 2, length)).to_string();
+
+        let command = ShellCommand::parse(&line);
+        if command != ShellCommand::Empty {
+            self.dos_print("\n");
+        }
+        match command {
+            ShellCommand::Empty => {}
+            ShellCommand::Drive(drive) => {
+                if drive < DRIVE_COUNT && self.cpu.memory.dos_drive_image(drive).is_some() {
+                    self.cpu.memory.dos.current_drive = drive;
+                } else {
+                    self.dos_print("Invalid drive specification\n");
+                }
+            }
+            ShellCommand::Dir(args) => self.shell_dir(&args),
+            ShellCommand::Type(file) => self.shell_type(&file),
+            ShellCommand::Copy(from, to) => self.shell_copy(&from, &to),
+            ShellCommand::Del(files) => {
+                if files.is_empty() {
+                    self.dos_print("Required parameter missing\n");
+                } else if let Err(code) = self.dos_delete_files(&files) {
+                    self.dos_print(&format!("{}\n", dos::error_message(code)));
+                }
+            }
+            ShellCommand::Cd(dir) => {
+                if dir.is_empty() {
+                    let drive = self.cpu.memory.dos.current_drive;
+                    let current = dir_string(drive, self.cpu.memory.dos.current_dir(drive));
+                    self.dos_print(&format!("{}\n", current));
+                } else if self.dos_change_dir(&dir).is_err() {
+                    self.dos_print("Invalid directory\n");
+                }
+            }
+            ShellCommand::Cls => {
+                // Set the current video mode again
+                let saved_ax = self.cpu.ax;
+                self.cpu.ax = self.cpu.memory.read(0x449) as u32;
+                self.int10h_set_video_mode();
+                self.cpu.ax = saved_ax;
+            }
+            ShellCommand::Ver => self.dos_print("\nHemulator built-in DOS version 5.00\n"),
+            ShellCommand::Echo(text) if text.is_empty() => self.dos_print("ECHO is on\n"),
+            ShellCommand::Echo(text) => self.dos_print(&format!("{}\n", text)),
+            ShellCommand::Help => self.dos_print(dos::SHELL_HELP),
+            ShellCommand::Run(program, tail) => self.shell_run(&program, &tail),
+        }
+        51
+    }
+
+    /// DIR: list a directory, or the files matching a pattern
+    fn shell_dir(&mut self, args: &str) {
+        // Switches such as /W and /P are ignored
+        let target = args
+            .split_whitespace()
+            .find(|arg| !arg.starts_with('/'))
+            .unwrap_or("");
+        let listing = self.dos_path(target).and_then(|mut path| {
+            let (volume, image) = self
+                .cpu
+                .memory
+                .dos_volume(path.drive)
+                .map_err(DosFsError::code)?;
+            let mut dir = volume
+                .find_dir(image, &path.dirs)
+                .map_err(DosFsError::code)?;
+            // "DIR GAMES" lists the GAMES directory
+            if let Some(name) = path.name.filter(|name| !has_wildcards(name)) {
+                if let Some(entry) = volume.find(image, dir, &name).filter(DirEntry::is_dir) {
+                    dir = entry.cluster;
+                    path.dirs.push(name);
+                    path.name = None;
+                }
+            }
+            dir_listing(
+                volume.label(image).as_deref(),
+                path.drive,
+                &path.dirs,
+                &volume.list(image, dir),
+                &path.name.unwrap_or([b'?'; 11]),
+                volume.free_bytes(image),
+            )
+            .ok_or(DosFsError::FileNotFound.code())
+        });
+        match listing {
+            Ok(text) => self.dos_print(&text),
+            Err(code) => self.dos_print(&format!("{}\n", dos::error_message(code))),
+        }
+    }
+
+    /// TYPE: print a text file up to its end-of-file mark (Ctrl-Z)
+    fn shell_type(&mut self, file: &str) {
+        if file.is_empty() {
+            self.dos_print("Required parameter missing\n");
+            return;
+        }
+        let data = self
+            .dos_path(file)
+            .and_then(|path| self.dos_read_file(&path).map_err(DosFsError::code));
+        match data {
+            Ok((_, _, data)) => {
+                let end = data.iter().position(|&b| b == 0x1A).unwrap_or(data.len());
+                self.dos_print_bytes(&data[..end]);
+            }
+            Err(code) => self.dos_print(&format!("{}\n", dos::error_message(code))),
+        }
+    }
+
+    /// COPY: copy files (wildcards allowed) to a file, directory or drive
+    fn shell_copy(&mut self, from: &str, to: &str) {
+        if from.is_empty() {
+            self.dos_print("Required parameter missing\n");
+            return;
+        }
+        match self.dos_copy_files(from, to) {
+            Ok(count) => self.dos_print(&format!("{:>9} file(s) copied\n", count)),
+            Err(code) => self.dos_print(&format!("{}\n", dos::error_message(code))),
+        }
+    }
+
+    /// Copy the files matching `from` to `to`; returns how many
+    fn dos_copy_files(&mut self, from: &str, to: &str) -> Result<usize, u16> {
+        let source = self.dos_path(from)?;
+        if source.name.is_none() {
+            return Err(DosFsError::FileNotFound.code());
+        }
+        let (source_dir, matches) = self.dos_match_files(&source, 0).map_err(DosFsError::code)?;
+        if matches.is_empty() {
+            return Err(DosFsError::FileNotFound.code());
+        }
+
+        // A target directory or drive keeps the source names
+        let target = self.dos_path(to)?;
+        let (target_dir, target_name) = {
+            let (volume, image) = self
+                .cpu
+                .memory
+                .dos_volume(target.drive)
+                .map_err(DosFsError::code)?;
+            let dir = volume
+                .find_dir(image, &target.dirs)
+                .map_err(DosFsError::code)?;
+            match target.name.filter(|name| !has_wildcards(name)) {
+                Some(name) => match volume.find(image, dir, &name).filter(DirEntry::is_dir) {
+                    Some(subdir) => (subdir.cluster, None),
+                    None => (dir, Some(name)),
+                },
+                None => (dir, None),
+            }
+        };
+        // Several files can't be copied to one
+        if target_name.is_some() && matches.len() > 1 {
+            return Err(DosFsError::AccessDenied.code());
+        }
+
+        for entry in &matches {
+            let name = target_name.unwrap_or(entry.name);
+            if target.drive == source.drive && target_dir == source_dir && name == entry.name {
+                return Err(DosFsError::AccessDenied.code());
+            }
+            let data = {
+                let (volume, image) = self
+                    .cpu
+                    .memory
+                    .dos_volume(source.drive)
+                    .map_err(DosFsError::code)?;
+                volume.read(image, entry)
+            };
+            let copy = DirEntry::file(name, entry.attr | ATTR_ARCHIVE, entry.time, entry.date);
+            self.cpu
+                .memory
+                .write_dos_file(target.drive, target_dir, &copy, &data)
+                .map_err(DosFsError::code)?;
+        }
+        Ok(matches.len())
+    }
+
+    /// Run NAME, NAME.COM or NAME.EXE from the prompt
+    fn shell_run(&mut self, program: &str, tail: &str) {
+        let has_extension = program
+            .rsplit(['\\', ':'])
+            .next()
+            .is_some_and(|name| name.contains('.'));
+        let candidates: Vec<String> = if has_extension {
+            vec![program.to_string()]
+        } else {
+            ["COM", "EXE", "BAT"]
+                .iter()
+                .map(|extension| format!("{}.{}", program, extension))
+                .collect()
+        };
+
+        for name in candidates {
+            let upper = name.to_ascii_uppercase();
+            if !(upper.ends_with(".COM") || upper.ends_with(".EXE") || upper.ends_with(".BAT")) {
+                continue;
+            }
+            let found = self.cpu.memory.dos.parse_path(&name).is_some_and(|path| {
+                path.name.is_some_and(|n| !has_wildcards(&n))
+                    && self
+                        .dos_match_files(&path, 0)
+                        .is_ok_and(|(_, matches)| !matches.is_empty())
+            });
+            if !found {
+                continue;
+            }
+            if upper.ends_with(".BAT") {
+                self.dos_print("Batch files are not supported\n");
+            } else if let Err(code) = self.dos_load_program(&name, tail.as_bytes()) {
+                self.dos_print(&format!("{}\n", dos::error_message(code)));
+            }
+            return;
+        }
+        self.dos_print("Bad command or file name\n");
+    }
+
+    /// Handle INT 05h - Print Screen / BOUND Exception
+    #[allow(dead_code)] // Called dynamically based on interrupt number
+    fn handle_int05h(&mut self) -> u32 {
+        // Skip the INT 05h instruction (2 bytes: 0xCD 0x05)
+        self.cpu.ip = self.cpu.ip.wrapping_add(2);
+
+        // Log stub call
+        self.log_stub_interrupt(0x05, None, "Print Screen/BOUND Exception");
+
+        // INT 05h is used for:
+        // 1. Print Screen (Shift+PrtSc) - not implemented in emulator
+        // 2. BOUND instruction exception - array bounds check failure
+        // For now, just return (ignore the exception)
+        51
+    }
+
+    /// Perform timer tick logic (increment BIOS timer counter)
+    /// This is called by both software INT 08h and hardware timer interrupts
+    fn do_timer_tick(&mut self) {
+        // Increment the timer tick counter in BIOS data area
+        // Timer ticks stored at 0x0040:0x006C (4 bytes, little-endian)
+        let tick_addr = 0x046C;
+        let mut ticks = self.cpu.memory.read(tick_addr) as u32;
+        ticks |= (self.cpu.memory.read(tick_addr + 1) as u32) << 8;
+        ticks |= (self.cpu.memory.read(tick_addr + 2) as u32) << 16;
+        ticks |= (self.cpu.memory.read(tick_addr + 3) as u32) << 24;
+
+        ticks = ticks.wrapping_add(1);
+
+        // Check for midnight rollover (1573040 ticks = 24 hours at 18.2065 ticks/sec)
+        if ticks >= 0x001800B0 {
+            // 1573040 decimal = 0x1800B0
+            ticks = 0;
+            // Set midnight flag at 0x0040:0x0070
+            self.cpu.memory.write(0x0470, 1);
+        }
+
+        // Write back the updated tick count
+        self.cpu.memory.write(tick_addr, (ticks & 0xFF) as u8);
+        self.cpu
+            .memory
+            .write(tick_addr + 1, ((ticks >> 8) & 0xFF) as u8);
+        self.cpu
+            .memory
+            .write(tick_addr + 2, ((ticks >> 16) & 0xFF) as u8);
+        self.cpu
+            .memory
+            .write(tick_addr + 3, ((ticks >> 24) & 0xFF) as u8);
+    }
+
+    /// Handle INT 08h - Timer Tick (System Timer) - SOFTWARE interrupt version
+    /// Called when CPU executes INT 08h instruction
+    #[allow(dead_code)] // Called dynamically based on interrupt number
+    fn handle_int08h(&mut self) -> u32 {
+        // Skip the INT 08h instruction (2 bytes: 0xCD 0x08)
+        self.cpu.ip = self.cpu.ip.wrapping_add(2);
+
+        // Perform timer tick logic
+        self.do_timer_tick();
+
+        // Call INT 1Ch (user timer tick handler)
+        // This is the standard PC/AT BIOS behavior - INT 08h chains to INT 1Ch
+        // Programs can hook INT 1Ch to execute code on every timer tick
+        // Since we can't directly trigger an interrupt from here (trigger_interrupt is private),
+        // we'll note that programs expecting INT 1Ch will need to hook INT 08h instead
+        // The BIOS default INT 1Ch handler is just an IRET at F000:0040
+
+        51
+    }
+
+    /// Handle the BIOS hardware IRQ handler at F000:0380
+    ///
+    /// Reached when the CPU dispatches INT 08h/09h through the default vector,
+    /// or when a DOS handler chains to the original BIOS vector. Services the
+    /// IRQ currently in service, then returns (or, for the timer, continues
+    /// into INT 1Ch if a program has hooked it).
+    fn handle_bios_irq(&mut self) -> u32 {
+        let irq = self.cpu.memory.pic.in_service_irq();
+        self.service_bios_irq(irq);
+
+        // The BIOS timer handler calls the user timer tick (INT 1Ch)
+        if irq == Some(0) && self.is_interrupt_overridden(0x1C) {
+            self.cpu.ip = TIMER_CHAIN_OFFSET as u32;
+        } else {
+            self.simulate_iret();
+        }
+        51
+    }
+
+    /// Perform the BIOS work for a hardware IRQ and send EOI to the PIC
+    fn service_bios_irq(&mut self, irq: Option<u8>) {
+        match irq {
+            Some(0) => self.do_timer_tick(),
+            // Take the scancode byte and move keystrokes into the BDA buffer,
+            // as the INT 09h handler does
+            Some(1) => {
+                self.cpu.memory.kbc.io_read(0x60);
+                self.sync_bda_keyboard_buffer();
+            }
+            _ => {}
+        }
+        self.cpu.memory.pic.end_of_interrupt();
+    }
+
+    /// Handle INT 09h - Keyboard Hardware Interrupt
+    /// Called by keyboard hardware when a key is pressed or released
+    #[allow(dead_code)] // Called dynamically based on interrupt number
+    fn handle_int09h(&mut self) -> u32 {
+        // Skip the INT 09h instruction (2 bytes: 0xCD 0x09)
+        self.cpu.ip = self.cpu.ip.wrapping_add(2);
+
+        // Hardware keyboard interrupt
+        // The BIOS interrupt handler converts pending scancodes to ASCII and
+        // stores them in the keyboard buffer at 0040:001Eh, updating the tail
+        // pointer. A software INT 09h does the same buffer update as the IRQ.
+        self.sync_bda_keyboard_buffer();
+        51
+    }
+
+    /// Handle INT 11h - Equipment List
+    /// Returns equipment flags in AX
+    #[allow(dead_code)] // Called dynamically based on interrupt number
+    fn handle_int11h(&mut self) -> u32 {
+        // Skip the INT 11h instruction (2 bytes: 0xCD 0x11)
+        self.cpu.ip = self.cpu.ip.wrapping_add(2);
 
         // Equipment list word format:
         // Bit 0: Floppy drive(s) installed
@@ -2176,11 +3763,11 @@ impl PcCpu {
         // Bit 8: DMA installed (0 = yes, 1 = no) - we say no
         // equipment_flags |= 0b0000_0001_0000_0000; // Not set (DMA present)
 
-        // Bits 9-11: Number of serial ports (1 port)
-        equipment_flags |= 0b0000_0010_0000_0000;
+        // Bits 9-11: Number of serial ports (COM1 and COM2)
+        equipment_flags |= 0b0000_0100_0000_0000;
 
-        // Bit 12: Game adapter - not installed
-        // equipment_flags |= 0b0001_0000_0000_0000; // Not set
+        // Bit 12: Game adapter installed
+        equipment_flags |= 0b0001_0000_0000_0000;
 
         // Bit 13: Serial printer - not installed
         // equipment_flags |= 0b0010_0000_0000_0000; // Not set
@@ -2318,25 +3905,9 @@ impl PcCpu {
         // Get function code from AH register
         let ah = ((self.cpu.ax >> 8) & 0xFF) as u8;
 
-        // Count INT 13h calls for debugging
-        static mut INT13H_CALL_COUNT: u32 = 0;
-        unsafe {
-            INT13H_CALL_COUNT += 1;
-            let count = INT13H_CALL_COUNT; // Copy value to avoid shared reference
-            if count % 10 == 1 {
-                log(LogCategory::Bus, LogLevel::Debug, || {
-                    format!("INT 13h call #{}", count)
-                });
-            }
-            if count > 1000 {
-                log(LogCategory::Bus, LogLevel::Debug, || {
-                    "!!! INT 13h called over 1000 times! Stopping...".to_string()
-                });
-                self.cpu.ax = (self.cpu.ax & 0x00FF) | (0x01 << 8); // Error
-                self.set_carry_flag(true);
-                return 51;
-            }
-        }
+        log(LogCategory::Bus, LogLevel::Trace, || {
+            format!("INT 13h AH={:02X}h DL={:02X}h", ah, self.cpu.dx & 0xFF)
+        });
 
         // Execute the appropriate INT 13h function
         // These functions will set AX (status in AH) and carry flag
@@ -2360,7 +3931,8 @@ impl PcCpu {
             0x46 => self.int13h_eject_media(), // CD-ROM: Eject media
             0x47 => self.int13h_extended_seek(), // CD-ROM: Extended seek
             0x48 => self.int13h_get_extended_params(),
-            0x4E => self.int13h_get_media_status(), // CD-ROM: Get media status
+            0x4B => self.int13h_cd_emulation_status(), // El Torito: Terminate/get emulation status
+            0x4E => self.int13h_get_media_status(),    // CD-ROM: Get media status
             _ => {
                 log(LogCategory::Stubs, LogLevel::Warn, || {
                     format!("!!! UNSUPPORTED INT 13h function: AH=0x{:02X} !!!", ah)
@@ -2648,8 +4220,6 @@ impl PcCpu {
 
     /// INT 13h, AH=08h: Get drive parameters
     fn int13h_get_drive_params(&mut self) -> u32 {
-        use crate::disk::DiskController;
-
         // DL = drive number
         let drive = (self.cpu.dx & 0xFF) as u8;
 
@@ -2679,16 +4249,16 @@ impl PcCpu {
         }
 
         // Get drive parameters
-        if let Some((cylinders, sectors_per_track, heads)) = DiskController::get_drive_params(drive)
-        {
+        if let Some((cylinders, sectors_per_track, heads)) = self.cpu.memory.drive_params(drive) {
             eprintln!(
                 "INT 13h AH=08h: Returning C={}, H={}, S={}",
                 cylinders, heads, sectors_per_track
             );
 
-            // BL = drive type (for floppies)
+            // BL = drive type from the CMOS setup (for floppies)
             if drive < 0x80 {
-                self.cpu.bx = (self.cpu.bx & 0xFF00) | 0x04; // 1.44MB floppy
+                let drive_type = self.cpu.memory.rtc.floppy_type_code(drive);
+                self.cpu.bx = (self.cpu.bx & 0xFF00) | u32::from(drive_type);
             } else {
                 self.cpu.bx &= 0xFF00; // Hard drive
             }
@@ -2825,8 +4395,6 @@ impl PcCpu {
 
     /// INT 13h, AH=15h: Get disk type
     fn int13h_get_disk_type(&mut self) -> u32 {
-        use crate::disk::DiskController;
-
         // DL = drive number
         let drive = (self.cpu.dx & 0xFF) as u8;
 
@@ -2854,8 +4422,7 @@ impl PcCpu {
                 return 51;
             }
 
-            if let Some((cylinders, sectors_per_track, heads)) =
-                DiskController::get_drive_params(drive)
+            if let Some((cylinders, sectors_per_track, heads)) = self.cpu.memory.drive_params(drive)
             {
                 // AH = 03h (fixed disk)
                 self.cpu.ax = (self.cpu.ax & 0x00FF) | (0x03 << 8);
@@ -3116,8 +4683,8 @@ impl PcCpu {
             self.cpu.memory.read(dap_addr + 11),
         ]);
 
-        // Read sectors using LBA
-        let buffer_size = (num_sectors as usize) * 512;
+        // Read sectors using LBA (2048-byte sectors on a CD-ROM)
+        let buffer_size = (num_sectors as usize) * self.cpu.memory.sector_size(drive);
         let mut buffer = vec![0u8; buffer_size];
 
         // Perform LBA read
@@ -3265,8 +4832,6 @@ impl PcCpu {
 
     /// INT 13h, AH=48h: Get Extended Drive Parameters
     fn int13h_get_extended_params(&mut self) -> u32 {
-        use crate::disk::DiskController;
-
         // DS:SI = pointer to result buffer
         // DL = drive number
 
@@ -3274,8 +4839,26 @@ impl PcCpu {
         let ds = self.cpu.ds;
         let si = self.cpu.si;
 
-        // Get drive parameters
-        if let Some((cylinders, sectors_per_track, heads)) = DiskController::get_drive_params(drive)
+        // Get drive parameters: (flags, cylinders, sectors per track, heads,
+        // total sectors, bytes per sector)
+        let params = if drive == 0xE0 {
+            // CD-ROM: removable media without CHS geometry
+            self.cpu.memory.cdrom().map(|cd| {
+                let total = (cd.len() / CD_SECTOR_SIZE) as u64;
+                (0x0004u16, 0u16, 0u8, 0u8, total, CD_SECTOR_SIZE as u16)
+            })
+        } else {
+            self.cpu
+                .memory
+                .drive_params(drive)
+                .map(|(cylinders, sectors_per_track, heads)| {
+                    let total = cylinders as u64 * heads as u64 * sectors_per_track as u64;
+                    (0x0002, cylinders, sectors_per_track, heads, total, 512)
+                })
+        };
+
+        if let Some((flags, cylinders, sectors_per_track, heads, total_sectors, bytes_per_sector)) =
+            params
         {
             let buffer_addr = ((ds as u32) << 4) + si;
 
@@ -3301,8 +4884,9 @@ impl PcCpu {
             // Bit 0: DMA boundary errors handled transparently
             // Bit 1: geometry is valid (CHS)
             // Bit 2: removable media
-            self.cpu.memory.write(buffer_addr + 2, 0x02); // Geometry valid
-            self.cpu.memory.write(buffer_addr + 3, 0x00);
+            let [flags_lo, flags_hi] = flags.to_le_bytes();
+            self.cpu.memory.write(buffer_addr + 2, flags_lo);
+            self.cpu.memory.write(buffer_addr + 3, flags_hi);
 
             // Offset 4-7: Number of physical cylinders (dword)
             let cyl_bytes = (cylinders as u32).to_le_bytes();
@@ -3326,7 +4910,6 @@ impl PcCpu {
             self.cpu.memory.write(buffer_addr + 15, spt_bytes[3]);
 
             // Offset 16-23: Total number of sectors (qword)
-            let total_sectors = cylinders as u64 * heads as u64 * sectors_per_track as u64;
             let total_bytes = total_sectors.to_le_bytes();
             for i in 0..8 {
                 self.cpu
@@ -3335,8 +4918,9 @@ impl PcCpu {
             }
 
             // Offset 24-25: Bytes per sector (word)
-            self.cpu.memory.write(buffer_addr + 24, 0x00); // 512 bytes
-            self.cpu.memory.write(buffer_addr + 25, 0x02);
+            let [size_lo, size_hi] = bytes_per_sector.to_le_bytes();
+            self.cpu.memory.write(buffer_addr + 24, size_lo);
+            self.cpu.memory.write(buffer_addr + 25, size_hi);
 
             // AH = 0 (success)
             self.cpu.ax &= 0x00FF;
@@ -3350,6 +4934,56 @@ impl PcCpu {
         51
     }
 
+    /// INT 13h, AH=4Bh: Terminate disk emulation / get status (El Torito)
+    fn int13h_cd_emulation_status(&mut self) -> u32 {
+        // AL = 00h: terminate emulation, 01h: get status only
+        // DS:SI = pointer to 13h-byte specification packet
+        let al = (self.cpu.ax & 0xFF) as u8;
+
+        let Some(entry) = self.cpu.memory.cd_boot().filter(|_| al <= 0x01) else {
+            // Not booted from a CD (or unknown subfunction)
+            self.cpu.ax = (self.cpu.ax & 0x00FF) | (0x01 << 8);
+            self.set_carry_flag(true);
+            return 51;
+        };
+
+        // Geometry of the emulated floppy as INT 13h AH=08h reports it
+        let (max_cylinder, sectors_per_track, max_head) = match entry.media {
+            BootMedia::NoEmulation => (0, 0, 0),
+            BootMedia::Floppy(format) => {
+                let (cylinders, sectors_per_track, heads) = format.geometry();
+                (cylinders - 1, sectors_per_track, heads - 1)
+            }
+        };
+
+        let mut packet = [0u8; 0x13];
+        packet[0x00] = 0x13; // Packet size
+        packet[0x01] = entry.media_type();
+        packet[0x02] = entry.drive();
+        packet[0x03] = 0x00; // Controller index
+        packet[0x04..0x08].copy_from_slice(&entry.load_rba.to_le_bytes());
+        // 08h-09h: device specification, 0Ah-0Bh: user buffer segment (unused)
+        packet[0x0C..0x0E].copy_from_slice(&entry.load_segment.to_le_bytes());
+        packet[0x0E..0x10].copy_from_slice(&entry.sector_count.to_le_bytes());
+        packet[0x10] = max_cylinder as u8;
+        packet[0x11] = sectors_per_track | ((max_cylinder >> 2) as u8 & 0xC0);
+        packet[0x12] = max_head;
+
+        let packet_addr = ((self.cpu.ds as u32) << 4) + self.cpu.si;
+        for (i, &byte) in packet.iter().enumerate() {
+            self.cpu.memory.write(packet_addr + i as u32, byte);
+        }
+
+        if al == 0x00 {
+            self.cpu.memory.end_cd_emulation();
+        }
+
+        self.cpu.ax &= 0x00FF; // AH = 0 (success)
+        self.set_carry_flag(false);
+
+        51
+    }
+
     /// INT 13h, AH=45h: Lock/unlock drive (CD-ROM)
     fn int13h_lock_unlock(&mut self) -> u32 {
         // AL = 0: unlock drive, 1: lock drive
@@ -3528,6 +5162,12 @@ impl PcCpu {
         }
     }
 
+    /// Check if an interrupt vector is installed (not 0000:0000)
+    fn has_interrupt_vector(&self, int_num: u8) -> bool {
+        let vector_offset = (int_num as u32) * 4;
+        (0..4).any(|i| self.cpu.memory.read(vector_offset + i) != 0)
+    }
+
     /// Check if an interrupt vector has been overridden by DOS/OS
     ///
     /// Returns true if the OS has installed its own handler (vector doesn't point to BIOS ROM).
@@ -3583,68 +5223,50 @@ impl PcCpu {
     /// INT 1Ah, AH=00h - Read system clock counter
     #[allow(dead_code)] // Called from handle_int1ah
     fn int1ah_read_system_clock(&mut self) -> u32 {
-        // Return tick count since midnight
-        // PC timer ticks at 18.2065 Hz (65536 PIT ticks)
-        // We'll use system time to calculate ticks since midnight
-
-        use std::time::{SystemTime, UNIX_EPOCH};
-
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_default();
-
-        // Get seconds since midnight
-        let total_seconds = now.as_secs();
-        let seconds_since_midnight = total_seconds % 86400;
-
-        // Convert to ticks (18.2065 ticks per second)
-        let ticks = (seconds_since_midnight as f64 * 18.2065) as u32;
+        // Return the tick count since midnight kept by the timer interrupt in
+        // the BDA (0040:006Ch, seeded from the RTC at POST), at 18.2065 Hz
+        let ticks = u32::from_le_bytes(std::array::from_fn(|i| {
+            self.cpu.memory.read(BDA_TIMER_TICKS + i as u32)
+        }));
 
         // CX:DX contains tick count
         self.cpu.cx = (ticks >> 16) & 0xFFFF;
         self.cpu.dx = ticks & 0xFFFF;
 
-        // AL = midnight flag (0 = no midnight crossover since last read)
-        self.cpu.ax &= 0xFF00;
+        // AL = midnight flag (non-zero if midnight passed since the last read),
+        // cleared by reading it
+        let midnight = self.cpu.memory.read(BDA_MIDNIGHT_FLAG);
+        self.cpu.memory.write(BDA_MIDNIGHT_FLAG, 0);
+        self.set_al(midnight);
 
         51
     }
 
-    /// INT 1Ah, AH=01h - Set system clock counter (stub - read-only)
+    /// INT 1Ah, AH=01h - Set system clock counter
     #[allow(dead_code)] // Called from handle_int1ah
     fn int1ah_set_system_clock(&mut self) -> u32 {
-        // Read-only implementation - ignore the set request
+        // CX:DX = new tick count
+        let ticks = ((self.cpu.cx & 0xFFFF) << 16) | (self.cpu.dx & 0xFFFF);
+        for (i, byte) in ticks.to_le_bytes().into_iter().enumerate() {
+            self.cpu.memory.write(BDA_TIMER_TICKS + i as u32, byte);
+        }
+        self.cpu.memory.write(BDA_MIDNIGHT_FLAG, 0);
         51
     }
 
     /// INT 1Ah, AH=02h - Read real-time clock time (AT, PS/2)
     #[allow(dead_code)] // Called from handle_int1ah
     fn int1ah_read_real_time_clock(&mut self) -> u32 {
-        use std::time::{SystemTime, UNIX_EPOCH};
-
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_default();
-
-        // Get local time broken down (using simple UTC for now)
-        let total_seconds = now.as_secs();
-        let seconds_in_day = total_seconds % 86400;
-
-        let hours = (seconds_in_day / 3600) as u8;
-        let minutes = ((seconds_in_day % 3600) / 60) as u8;
-        let seconds = (seconds_in_day % 60) as u8;
+        let rtc = &self.cpu.memory.rtc;
+        let now = rtc.date_time();
+        let dst = rtc.daylight_saving();
 
         // CH = hours (BCD)
         // CL = minutes (BCD)
         // DH = seconds (BCD)
-        // DL = daylight savings flag (0)
-
-        let hours_bcd = ((hours / 10) << 4) | (hours % 10);
-        let minutes_bcd = ((minutes / 10) << 4) | (minutes % 10);
-        let seconds_bcd = ((seconds / 10) << 4) | (seconds % 10);
-
-        self.cpu.cx = ((hours_bcd as u32) << 8) | (minutes_bcd as u32);
-        self.cpu.dx = (seconds_bcd as u32) << 8;
+        // DL = daylight savings flag
+        self.cpu.cx = (u32::from(to_bcd(now.hour)) << 8) | u32::from(to_bcd(now.minute));
+        self.cpu.dx = (u32::from(to_bcd(now.second)) << 8) | u32::from(dst);
 
         // Clear carry flag (success)
         self.set_carry_flag(false);
@@ -3652,75 +5274,45 @@ impl PcCpu {
         51
     }
 
-    /// INT 1Ah, AH=03h - Set real-time clock time (stub - read-only)
+    /// INT 1Ah, AH=03h - Set real-time clock time (AT, PS/2)
     #[allow(dead_code)] // Called from handle_int1ah
     fn int1ah_set_real_time_clock(&mut self) -> u32 {
-        // Read-only implementation - ignore the set request
+        // CH = hours, CL = minutes, DH = seconds (BCD), DL = daylight savings flag
+        let mut date_time = self.cpu.memory.rtc.date_time();
+        let time = (|| {
+            Some((
+                from_bcd((self.cpu.cx >> 8) as u8)?,
+                from_bcd(self.cpu.cx as u8)?,
+                from_bcd((self.cpu.dx >> 8) as u8)?,
+            ))
+        })();
+        let Some((hour, minute, second)) = time else {
+            self.set_carry_flag(true);
+            return 51;
+        };
+        date_time.hour = hour;
+        date_time.minute = minute;
+        date_time.second = second;
+        if self.set_rtc_date_time(date_time) {
+            let dst = self.cpu.dx & 0x01 != 0;
+            self.cpu.memory.rtc.set_daylight_saving(dst);
+        }
         51
     }
 
     /// INT 1Ah, AH=04h - Read real-time clock date (AT, PS/2)
     #[allow(dead_code)] // Called from handle_int1ah
     fn int1ah_read_date(&mut self) -> u32 {
-        use std::time::{SystemTime, UNIX_EPOCH};
-
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_default();
-
-        // Convert to days since epoch
-        let days_since_epoch = now.as_secs() / 86400;
-
-        // Calculate year, month, day (simple algorithm for demonstration)
-        // This is a simplified calculation - a proper implementation would use chrono
-        let mut year = 1970;
-        let mut remaining_days = days_since_epoch as u32;
-
-        loop {
-            let days_in_year = if (year % 4 == 0 && year % 100 != 0) || (year % 400 == 0) {
-                366
-            } else {
-                365
-            };
-
-            if remaining_days >= days_in_year {
-                remaining_days -= days_in_year;
-                year += 1;
-            } else {
-                break;
-            }
-        }
-
-        // Simple month calculation (assuming non-leap year for simplicity)
-        let days_per_month = [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
-        let mut month = 1;
-        let mut day = remaining_days + 1;
-
-        for (m, &days) in days_per_month.iter().enumerate() {
-            if day > days {
-                day -= days;
-                month = m + 2;
-            } else {
-                month = m + 1;
-                break;
-            }
-        }
+        let now = self.cpu.memory.rtc.date_time();
 
         // CH = century (BCD) - 19 or 20
         // CL = year (BCD) - 00-99
         // DH = month (BCD) - 01-12
         // DL = day (BCD) - 01-31
-
-        let century = year / 100;
-        let year_part = year % 100;
-
-        let century_bcd = ((century / 10) << 4) | (century % 10);
-        let year_bcd = ((year_part / 10) << 4) | (year_part % 10);
-        let month_bcd = ((month / 10) << 4) | (month % 10);
-        let day_bcd = ((day / 10) << 4) | (day % 10);
-
-        self.cpu.cx = ((century_bcd as u32) << 8) | (year_bcd as u32);
-        self.cpu.dx = ((month_bcd as u32) << 8) | day_bcd;
+        let century = to_bcd(now.year.div_euclid(100).clamp(0, 99) as u8);
+        let year = to_bcd(now.year.rem_euclid(100) as u8);
+        self.cpu.cx = (u32::from(century) << 8) | u32::from(year);
+        self.cpu.dx = (u32::from(to_bcd(now.month)) << 8) | u32::from(to_bcd(now.day));
 
         // Clear carry flag (success)
         self.set_carry_flag(false);
@@ -3728,13 +5320,40 @@ impl PcCpu {
         51
     }
 
-    /// INT 1Ah, AH=05h - Set real-time clock date (stub - read-only)
+    /// INT 1Ah, AH=05h - Set real-time clock date (AT, PS/2)
     #[allow(dead_code)] // Called from handle_int1ah
     fn int1ah_set_date(&mut self) -> u32 {
-        // Read-only implementation - ignore the set request
+        // CH = century, CL = year, DH = month, DL = day (BCD)
+        let mut date_time = self.cpu.memory.rtc.date_time();
+        let date = (|| {
+            Some((
+                from_bcd((self.cpu.cx >> 8) as u8)?,
+                from_bcd(self.cpu.cx as u8)?,
+                from_bcd((self.cpu.dx >> 8) as u8)?,
+                from_bcd(self.cpu.dx as u8)?,
+            ))
+        })();
+        let Some((century, year, month, day)) = date else {
+            self.set_carry_flag(true);
+            return 51;
+        };
+        date_time.year = i64::from(century) * 100 + i64::from(year);
+        date_time.month = month;
+        date_time.day = day;
+        self.set_rtc_date_time(date_time);
         51
     }
 
+    /// Set the RTC for INT 1Ah AH=03h/05h, setting CF if the date or time is invalid
+    fn set_rtc_date_time(&mut self, date_time: RtcDateTime) -> bool {
+        let valid = date_time.is_valid();
+        if valid {
+            self.cpu.memory.rtc.set_time(date_time.timestamp());
+        }
+        self.set_carry_flag(!valid);
+        valid
+    }
+
     /// INT 1Ah, AH=B1h - PCI BIOS Services
     #[allow(dead_code)] // Called from handle_int1ah
     fn int1ah_pci_bios(&mut self) -> u32 {
@@ -3878,7 +5497,20 @@ impl PcCpu {
     fn handle_int33h(&mut self) -> u32 {
         // Skip the INT 33h instruction (2 bytes: 0xCD 0x33)
         self.cpu.ip = self.cpu.ip.wrapping_add(2);
+        self.int33h_dispatch()
+    }
 
+    /// Handle the INT 33h mouse driver entry point at F000:0394
+    ///
+    /// Reached when a program chains to the default INT 33h vector.
+    fn handle_mouse_entry(&mut self) -> u32 {
+        let cycles = self.int33h_dispatch();
+        self.simulate_iret();
+        cycles
+    }
+
+    /// Run the INT 33h function in AX
+    fn int33h_dispatch(&mut self) -> u32 {
         // Get function code from AX register
         let ax = self.cpu.ax;
 
@@ -4016,9 +5648,14 @@ impl PcCpu {
         let ah = ((self.cpu.ax >> 8) & 0xFF) as u8;
 
         match ah {
+            0x00 => self.int15h_cassette_motor(true),
+            0x01 => self.int15h_cassette_motor(false),
+            0x02 => self.int15h_cassette_read(),
+            0x03 => self.int15h_cassette_write(),
             0x24 => self.int15h_a20_gate_control(),
             0x41 => self.int15h_wait_on_external_event(),
             0x4F => self.int15h_keyboard_intercept(),
+            0x84 => self.int15h_joystick(),
             0x86 => self.int15h_wait(),
             0x87 => self.int15h_extended_memory_block_move(),
             0x88 => self.int15h_get_extended_memory_size(),
@@ -4057,6 +5694,88 @@ impl PcCpu {
         }
     }
 
+    /// Finish a cassette call: CF and AH report the status
+    fn int15h_cassette_status(&mut self, status: u8) {
+        self.cpu.ax = (self.cpu.ax & 0x00FF) | ((status as u32) << 8);
+        self.set_carry_flag(status != 0);
+    }
+
+    /// INT 15h, AH=00h/01h - Cassette Motor On/Off
+    /// Returns AH=86h (no cassette) if no tape is inserted, like AT-class BIOSes
+    fn int15h_cassette_motor(&mut self, on: bool) -> u32 {
+        if self.cpu.memory.cassette.tape().is_none() {
+            self.int15h_cassette_status(0x86);
+            return 51;
+        }
+
+        self.cpu.memory.cassette.set_motor(on);
+        self.int15h_cassette_status(0x00);
+        51
+    }
+
+    /// INT 15h, AH=02h - Read Cassette Blocks
+    /// Reads the next record into ES:BX (CX = byte count)
+    /// Returns DX = bytes read, ES:BX = pointer past last byte,
+    /// AH = status (01h CRC error, 02h data lost, 04h no data found)
+    fn int15h_cassette_read(&mut self) -> u32 {
+        let Some(tape) = self.cpu.memory.cassette.tape_mut() else {
+            self.int15h_cassette_status(0x86);
+            return 51;
+        };
+
+        let (data, error) = tape.read(self.cpu.cx as usize);
+        let base = (self.cpu.es as u32) << 4;
+        for (i, &byte) in data.iter().enumerate() {
+            let offset = (self.cpu.bx as u16).wrapping_add(i as u16);
+            self.cpu.memory.write(base + offset as u32, byte);
+        }
+
+        log(LogCategory::Bus, LogLevel::Debug, || {
+            format!(
+                "INT 15h AH=02h: Cassette read {} of {} bytes ({:?})",
+                data.len(),
+                self.cpu.cx,
+                error
+            )
+        });
+
+        self.cpu.dx = data.len() as u32;
+        self.cpu.bx = (self.cpu.bx as u16).wrapping_add(data.len() as u16) as u32;
+        self.int15h_cassette_status(error.map_or(0x00, |e| e.status()));
+        51
+    }
+
+    /// INT 15h, AH=03h - Write Cassette Blocks
+    /// Records CX bytes from ES:BX as a new record
+    /// Returns ES:BX = pointer past last byte, CX = 0
+    fn int15h_cassette_write(&mut self) -> u32 {
+        if self.cpu.memory.cassette.tape().is_none() {
+            self.int15h_cassette_status(0x86);
+            return 51;
+        }
+
+        let base = (self.cpu.es as u32) << 4;
+        let count = self.cpu.cx as u16;
+        let data: Vec<u8> = (0..count)
+            .map(|i| {
+                let offset = (self.cpu.bx as u16).wrapping_add(i);
+                self.cpu.memory.read(base + offset as u32)
+            })
+            .collect();
+
+        log(LogCategory::Bus, LogLevel::Debug, || {
+            format!("INT 15h AH=03h: Cassette write {} bytes", data.len())
+        });
+
+        if let Some(tape) = self.cpu.memory.cassette.tape_mut() {
+            tape.write(&data);
+        }
+        self.cpu.bx = (self.cpu.bx as u16).wrapping_add(count) as u32;
+        self.cpu.cx = 0;
+        self.int15h_cassette_status(0x00);
+        51
+    }
+
     /// INT 15h, AH=87h - Move Extended Memory Block
     /// Copies data between conventional and extended memory
     #[allow(dead_code)] // Called from handle_int15h
@@ -4484,6 +6203,32 @@ impl PcCpu {
         51
     }
 
+    /// INT 15h, AH=84h - Joystick support
+    /// DX=0000h: AL = switch settings (bits 4-7, 0 = pressed)
+    /// DX=0001h: AX, BX, CX, DX = positions of A X, A Y, B X, B Y
+    #[allow(dead_code)] // Called from handle_int15h
+    fn int15h_joystick(&mut self) -> u32 {
+        let port = &self.cpu.memory.game_port;
+        match self.cpu.dx & 0xFFFF {
+            0x0000 => {
+                self.cpu.ax = (self.cpu.ax & !0xFF) | port.switches() as u32;
+            }
+            0x0001 => {
+                let [ax, bx, cx, dx] = port.positions();
+                self.cpu.ax = ax as u32;
+                self.cpu.bx = bx as u32;
+                self.cpu.cx = cx as u32;
+                self.cpu.dx = dx as u32;
+            }
+            _ => {
+                self.set_carry_flag(true);
+                return 51;
+            }
+        }
+        self.set_carry_flag(false);
+        51
+    }
+
     /// INT 15h, AH=86h - Wait (Delay)
     /// Used by programs to create precise delays
     #[allow(dead_code)] // Called from handle_int15h
@@ -4539,93 +6284,131 @@ impl PcCpu {
     }
 
     /// Handle INT 14h - Serial Port Services
+    ///
+    /// Works on the UART whose base port is listed in the BIOS data area
+    /// (0040:0000 + 2*DX), like the ROM BIOS. Transmit and receive do not
+    /// busy-wait: a receive with no data waiting returns a timeout at once.
     #[allow(dead_code)] // Called dynamically based on interrupt number
     fn handle_int14h(&mut self) -> u32 {
         // Skip the INT 14h instruction (2 bytes: 0xCD 0x14)
         self.cpu.ip = self.cpu.ip.wrapping_add(2);
 
-        // Get function code from AH register
         let ah = ((self.cpu.ax >> 8) & 0xFF) as u8;
+        let al = (self.cpu.ax & 0xFF) as u8;
+        let port_number = (self.cpu.dx & 0xFFFF) as u16;
+        let base = if port_number < 4 {
+            self.read_bda_word(BDA_COM_PORTS + u32::from(port_number) * 2)
+        } else {
+            0
+        };
 
-        // Log stub call
-        self.log_stub_interrupt(0x14, Some(ah), "Serial Port Services (stub)");
+        // Line status in AH, bit 7 = timeout
+        const TIMEOUT: u8 = 0x80;
+        const LSR_DATA_READY: u8 = 0x01;
+        const LSR_ERRORS: u8 = 0x1E;
+        if base == 0 {
+            self.cpu.ax = (self.cpu.ax & 0x00FF) | (u32::from(TIMEOUT) << 8);
+            return 51;
+        }
 
+        let bus = &mut self.cpu.memory;
         match ah {
             0x00 => {
-                // Initialize serial port
-                // AL = port parameters (baud rate, parity, stop bits, word length)
-                // DX = port number (0-3)
-                // Returns: AH = line status, AL = modem status
-                self.cpu.ax = 0x0000u32; // Success
-                51
+                // Initialize port
+                // AL bits 7-5: baud rate (110-9600), bits 4-3: parity,
+                // bit 2: stop bits, bits 1-0: word length
+                const DIVISORS: [u16; 8] = [1047, 768, 384, 192, 96, 48, 24, 12];
+                let divisor = DIVISORS[usize::from(al >> 5)];
+                bus.io_write(base + 3, 0x80); // DLAB
+                bus.io_write(base, divisor as u8);
+                bus.io_write(base + 1, (divisor >> 8) as u8);
+                bus.io_write(base + 3, al & 0x1F);
+                bus.io_write(base + 1, 0x00); // Interrupts off
+                let status =
+                    (u16::from(bus.io_read(base + 5)) << 8) | u16::from(bus.io_read(base + 6));
+                self.cpu.ax = u32::from(status);
             }
             0x01 => {
-                // Transmit character
-                // AL = character to send, DX = port number
-                // Returns: AH = status (bit 7 = timeout if set)
-                self.cpu.ax &= 0x00FF; // AH = 0 (success)
-                51
+                // Transmit character in AL
+                bus.io_write(base + 4, 0x03); // DTR, RTS
+                bus.io_write(base, al);
+                let lsr = bus.io_read(base + 5);
+                self.cpu.ax = (u32::from(lsr & !TIMEOUT) << 8) | u32::from(al);
             }
             0x02 => {
-                // Receive character
-                // DX = port number
-                // Returns: AH = status, AL = received character
-                self.cpu.ax = 0x0000u32; // No data available
-                51
+                // Receive character into AL
+                bus.io_write(base + 4, 0x01); // DTR
+                let lsr = bus.io_read(base + 5);
+                self.cpu.ax = if lsr & LSR_DATA_READY != 0 {
+                    let byte = bus.io_read(base);
+                    (u32::from(lsr & LSR_ERRORS) << 8) | u32::from(byte)
+                } else {
+                    u32::from(lsr | TIMEOUT) << 8
+                };
             }
             0x03 => {
-                // Get port status
-                // DX = port number
-                // Returns: AH = line status, AL = modem status
-                self.cpu.ax = 0x0000u32;
-                51
+                // Get status: AH = line status, AL = modem status
+                let status =
+                    (u16::from(bus.io_read(base + 5)) << 8) | u16::from(bus.io_read(base + 6));
+                self.cpu.ax = u32::from(status);
             }
             _ => {
-                // Unsupported function
-                51
+                self.log_stub_interrupt(0x14, Some(ah), "Serial Port Services");
             }
         }
+        51
     }
 
     /// Handle INT 17h - Printer Services
+    ///
+    /// Works on the printer port whose base port is listed in the BIOS data
+    /// area (0040:0008 + 2*DX). The emulated printer is never busy, so no
+    /// busy-wait or timeout loop is needed.
     #[allow(dead_code)] // Called dynamically based on interrupt number
     fn handle_int17h(&mut self) -> u32 {
         // Skip the INT 17h instruction (2 bytes: 0xCD 0x17)
         self.cpu.ip = self.cpu.ip.wrapping_add(2);
 
-        // Get function code from AH register
         let ah = ((self.cpu.ax >> 8) & 0xFF) as u8;
+        let al = (self.cpu.ax & 0xFF) as u8;
+        let printer = (self.cpu.dx & 0xFFFF) as u16;
+        let base = if printer < 3 {
+            self.read_bda_word(BDA_LPT_PORTS + u32::from(printer) * 2)
+        } else {
+            0
+        };
 
-        // Log stub call
-        self.log_stub_interrupt(0x17, Some(ah), "Printer Services (stub)");
+        // Printer status in AH: bit 7 = not busy, 6 = acknowledge,
+        // 5 = out of paper, 4 = selected, 3 = I/O error, 0 = timeout
+        const TIMEOUT: u8 = 0x01;
+        if base == 0 {
+            self.cpu.ax = (self.cpu.ax & 0x00FF) | (u32::from(TIMEOUT) << 8);
+            return 51;
+        }
 
+        let bus = &mut self.cpu.memory;
         match ah {
             0x00 => {
-                // Print character
-                // AL = character to print, DX = printer number (0-2)
-                // Returns: AH = printer status
-                self.cpu.ax = (self.cpu.ax & 0x00FF) | 0x9000; // AH = 0x90 (ready, no errors)
-                51
+                // Print character in AL
+                strobe_printer(bus, base, al);
             }
             0x01 => {
-                // Initialize printer
-                // DX = printer number
-                // Returns: AH = printer status
-                self.cpu.ax = (self.cpu.ax & 0x00FF) | 0x9000; // AH = 0x90 (ready)
-                51
+                // Initialize printer: pulse /INIT
+                bus.io_write(base + 2, 0x08);
+                bus.io_write(base + 2, 0x0C);
             }
             0x02 => {
-                // Get printer status
-                // DX = printer number
-                // Returns: AH = printer status
-                self.cpu.ax = (self.cpu.ax & 0x00FF) | 0x9000; // AH = 0x90 (ready)
-                51
+                // Get status
             }
             _ => {
-                // Unsupported function
-                51
+                self.log_stub_interrupt(0x17, Some(ah), "Printer Services");
+                return 51;
             }
         }
+        // Status port bits 3-7, with /ACK and /ERROR inverted
+        let status = (bus.io_read(base + 1) & 0xF8) ^ 0x48;
+        self.cpu.ax = (self.cpu.ax & 0x00FF) | (u32::from(status) << 8);
+        51
     }
 
     /// Handle INT 18h - Cassette BASIC / Boot Failure
@@ -4635,6 +6418,13 @@ impl PcCpu {
         // Skip the INT 18h instruction (2 bytes: 0xCD 0x18)
         self.cpu.ip = self.cpu.ip.wrapping_add(2);
 
+        // No bootable disk: start the built-in DOS instead of ROM BASIC
+        // (always when a program was loaded to run on it)
+        let dos = &self.cpu.memory.dos;
+        if (dos.is_enabled() || self.cpu.memory.executable().is_some()) && !dos.is_running() {
+            return self.start_builtin_dos();
+        }
+
         // Log stub call
         self.log_stub_interrupt(0x18, None, "Cassette BASIC / Boot Failure (stub)");
 
@@ -4778,117 +6568,677 @@ impl PcCpu {
         // Skip the INT 2Ah instruction (2 bytes: 0xCD 0x2A)
         self.cpu.ip = self.cpu.ip.wrapping_add(2);
 
-        // Get function code from AH register
+        // Get function code from AH register
+        let ah = ((self.cpu.ax >> 8) & 0xFF) as u8;
+
+        // Log the call for debugging
+        log(LogCategory::Stubs, LogLevel::Debug, || {
+            format!(
+                "INT 0x2A AH=0x{:02X} called from {:04X}:{:04X}",
+                ah,
+                self.cpu.cs,
+                self.cpu.ip.wrapping_sub(2)
+            )
+        });
+
+        // Network Installation API stub
+        // All functions return AL=0 (not installed/not supported)
+        self.cpu.ax &= 0xFF00; // AL = 0 (not installed)
+        self.set_carry_flag(true); // CF = 1 (error/not installed)
+
+        51
+    }
+
+    /// Handle INT 2Fh - Multiplex interrupt
+    #[allow(dead_code)] // Called dynamically based on interrupt number
+    fn handle_int2fh(&mut self) -> u32 {
+        // Skip the INT 2Fh instruction (2 bytes: 0xCD 0x2F)
+        self.cpu.ip = self.cpu.ip.wrapping_add(2);
+        self.int2fh_dispatch()
+    }
+
+    /// Handle the INT 2Fh multiplex entry point at F000:0390
+    ///
+    /// Reached through the default vector or when DOS or a TSR chains to the
+    /// handler it replaced. Answers like `handle_int2fh`, then returns to the
+    /// caller.
+    fn handle_multiplex_entry(&mut self) -> u32 {
+        let cycles = self.int2fh_dispatch();
+        self.update_flags_on_stack();
+        self.simulate_iret();
+        cycles
+    }
+
+    /// Check if AX names a multiplex function owned by a built-in driver
+    ///
+    /// The built-in drivers sit at the head of the INT 2Fh chain, like TSRs
+    /// loaded after DOS, so these calls are answered even when DOS or a TSR
+    /// has hooked INT 2Fh without chaining back to the BIOS.
+    fn multiplex_id_claimed(&self) -> bool {
+        let ah = ((self.cpu.ax >> 8) & 0xFF) as u8;
+        let al = (self.cpu.ax & 0xFF) as u8;
+        match ah {
+            0x15 => self.cpu.memory.has_cdrom(),
+            0x16 => al == 0x87 && self.cpu.memory.dpmi.is_installed(),
+            0x43 => self.cpu.memory.xms.is_installed(),
+            _ => false,
+        }
+    }
+
+    /// Run the INT 2Fh function in AH
+    fn int2fh_dispatch(&mut self) -> u32 {
+        // Get function code from AH register
+        let ah = ((self.cpu.ax >> 8) & 0xFF) as u8;
+
+        match ah {
+            0x11 => self.int2fh_network_installation_check(),
+            0x15 => self.int2fh_mscdex(),
+            0x16 => self.int2fh_windows_services(),
+            0x43 => self.int2fh_xms_installation_check(),
+            0x48 => self.int2fh_doskey_installation_check(),
+            _ => {
+                // Unsupported function - log and return
+                self.log_stub_interrupt(
+                    0x2F,
+                    Some(ah),
+                    "Multiplex Interrupt (unsupported subfunction)",
+                );
+                51
+            }
+        }
+    }
+
+    /// INT 2Fh, AH=11h - Network Redirector / Installation Check
+    #[allow(dead_code)] // Called from handle_int2fh
+    fn int2fh_network_installation_check(&mut self) -> u32 {
+        // AL contains subfunction
+        // This is used by DOS to check for network redirector
+        // We don't support networking, so AL is left at 0x00: "not installed,
+        // OK to install" (0xFF would claim a redirector is present)
+        51
+    }
+
+    /// INT 2Fh, AH=15h - MSCDEX CD-ROM extensions
+    ///
+    /// Present only while a CD-ROM image is mounted. The CD-ROM gets the
+    /// drive letter after the floppies and the hard drive.
+    fn int2fh_mscdex(&mut self) -> u32 {
+        let al = (self.cpu.ax & 0xFF) as u8;
+        if !self.cpu.memory.has_cdrom() {
+            // Installation check: BX = 0 drive letters means not installed
+            if al == 0x00 {
+                self.cpu.bx = 0x0000;
+            }
+            return 51;
+        }
+
+        let letter = self.cdrom_drive_letter();
+        match al {
+            0x00 => {
+                // Installation check: BX = drive count, CX = first drive
+                self.cpu.bx = 0x0001;
+                self.cpu.cx = letter as u32;
+            }
+            0x08 => return self.int2fh_mscdex_read_sectors(letter),
+            0x0B => {
+                // Drive check: AX nonzero if CX is a CD-ROM drive, BX = ADADh
+                self.cpu.ax = if self.cpu.cx & 0xFFFF == letter as u32 {
+                    0x5AD8
+                } else {
+                    0x0000
+                };
+                self.cpu.bx = 0xADAD;
+            }
+            0x0C => {
+                // Version: 2.23
+                self.cpu.bx = 0x0217;
+            }
+            0x0D => {
+                // Drive letters into ES:BX, one byte per CD-ROM drive
+                let addr = ((self.cpu.es as u32) << 4) + (self.cpu.bx & 0xFFFF);
+                self.cpu.memory.write(addr, letter);
+            }
+            _ => {
+                self.log_stub_interrupt(0x2F, Some(0x15), "MSCDEX (unsupported subfunction)");
+            }
+        }
+        51
+    }
+
+    /// INT 2Fh, AX=1508h - MSCDEX absolute disk read
+    ///
+    /// CX = drive, SI:DI = first sector, DX = sector count, ES:BX = buffer.
+    /// Sectors are 2048 bytes.
+    fn int2fh_mscdex_read_sectors(&mut self, letter: u8) -> u32 {
+        const SECTOR_SIZE: usize = 2048;
+
+        if self.cpu.cx & 0xFFFF != letter as u32 {
+            self.cpu.ax = 0x000F; // Invalid drive
+            self.set_carry_flag(true);
+            return 51;
+        }
+
+        let start = ((self.cpu.si & 0xFFFF) << 16 | (self.cpu.di & 0xFFFF)) as usize;
+        let count = (self.cpu.dx & 0xFFFF) as usize;
+        let image_sectors = self
+            .cpu
+            .memory
+            .cdrom()
+            .map_or(0, |image| image.len() / SECTOR_SIZE);
+        if start + count > image_sectors {
+            self.cpu.ax = 0x0015; // Drive not ready
+            self.set_carry_flag(true);
+            return 51;
+        }
+
+        let data = self.cpu.memory.cdrom().map_or_else(Vec::new, |image| {
+            image[start * SECTOR_SIZE..(start + count) * SECTOR_SIZE].to_vec()
+        });
+        let es = self.cpu.es;
+        let bx = self.cpu.bx as u16;
+        for (i, byte) in data.into_iter().enumerate() {
+            let offset = bx.wrapping_add(i as u16);
+            self.cpu.write_byte(es, offset, byte);
+        }
+        self.set_carry_flag(false);
+        51
+    }
+
+    /// Drive number (0 = A:) MSCDEX reports for the CD-ROM
+    fn cdrom_drive_letter(&self) -> u8 {
+        if self.cpu.memory.has_hard_drive() {
+            3 // D:
+        } else {
+            2 // C:
+        }
+    }
+
+    /// INT 2Fh, AH=16h - Windows enhanced mode and DPMI services
+    #[allow(dead_code)] // Called from handle_int2fh
+    fn int2fh_windows_services(&mut self) -> u32 {
+        let al = (self.cpu.ax & 0xFF) as u8;
+
+        match al {
+            // Windows enhanced mode installation check: not running
+            0x00 => self.set_al(0x00),
+            // Release VM time slice: supported
+            0x80 => self.set_al(0x00),
+            0x87 => return self.int2fh_dpmi_installation_check(),
+            _ => {}
+        }
+        51
+    }
+
+    /// INT 2Fh, AH=43h - XMS Installation Check
+    #[allow(dead_code)] // Called from handle_int2fh
+    fn int2fh_xms_installation_check(&mut self) -> u32 {
+        let al = (self.cpu.ax & 0xFF) as u8;
+
+        match al {
+            0x00 => {
+                // XMS installation check: AL = 0x80 if installed
+                if self.cpu.memory.xms.is_installed() {
+                    self.set_al(0x80);
+                } else {
+                    self.set_al(0x00);
+                }
+                51
+            }
+            0x10 if self.cpu.memory.xms.is_installed() => {
+                // Get XMS driver address in ES:BX
+                self.cpu.es = 0xF000; // BIOS segment
+                self.cpu.bx = XMS_ENTRY_OFFSET as u32;
+                51
+            }
+            _ => 51,
+        }
+    }
+
+    /// INT 2Fh, AH=48h - DOSKEY Installation Check
+    ///
+    /// There is no built-in DOSKEY, so AX=4800h returns with AL still 00h
+    /// (not installed) and programs fall back to plain buffered input.
+    fn int2fh_doskey_installation_check(&mut self) -> u32 {
+        51
+    }
+
+    /// INT 2Fh, AX=1687h - DPMI Installation Check
+    #[allow(dead_code)] // Called from handle_int2fh
+    fn int2fh_dpmi_installation_check(&mut self) -> u32 {
+        if self.cpu.memory.dpmi.is_installed() {
+            // DPMI is installed
+            self.cpu.ax = 0x0000u32; // AX = 0 (supported)
+            self.cpu.bx = 0x0001u32; // BX = flags (bit 0 = 32-bit support)
+            self.cpu.cx = self.cpu.memory.dpmi.processor_type() as u32; // Processor type
+            self.cpu.dx = self.cpu.memory.dpmi.version() as u32; // DPMI version (BCD)
+
+            // Entry point in ES:DI
+            self.cpu.es = self.cpu.memory.dpmi.entry_segment();
+            self.cpu.di = self.cpu.memory.dpmi.entry_offset() as u32;
+        }
+        // Otherwise AX stays nonzero (1687h): not supported
+        51
+    }
+
+    /// Handle the XMS driver entry point (far call to F000:0398)
+    ///
+    /// Performs the XMS function in AH, then returns to the caller. On
+    /// success AX = 0001h (or the function's result); on failure AX = 0000h
+    /// and BL holds the error code.
+    fn handle_xms_entry(&mut self) -> u32 {
+        let ah = ((self.cpu.ax >> 8) & 0xFF) as u8;
+        let bx = self.cpu.bx as u16;
+        let dx = self.cpu.dx as u16;
+
+        let result = match ah {
+            // Get version: AX = XMS version, BX = driver revision, DX = HMA exists
+            0x00 => {
+                let hma = self.cpu.memory.xms.total_extended_memory_kb() >= 64;
+                self.cpu.bx = 0x0100;
+                self.cpu.dx = hma as u32;
+                Ok(self.cpu.memory.xms.get_version())
+            }
+            0x01 => xms_status(self.cpu.memory.xms.request_hma(dx)),
+            0x02 => xms_status(self.cpu.memory.xms.release_hma()),
+            0x03 => xms_status(self.cpu.memory.xms.global_enable_a20()),
+            0x04 => xms_status(self.cpu.memory.xms.global_disable_a20()),
+            0x05 => xms_status(self.cpu.memory.xms.local_enable_a20()),
+            0x06 => xms_status(self.cpu.memory.xms.local_disable_a20()),
+            // Query A20: AX = 1 if enabled
+            0x07 => {
+                self.cpu.bx &= !0xFF;
+                Ok(self.cpu.memory.xms.query_a20() as u16)
+            }
+            // Query free memory: AX = largest block, DX = total free (KB)
+            0x08 => {
+                let (largest, total) = self.cpu.memory.xms.query_free_extended_memory();
+                self.cpu.dx = total as u32;
+                if largest == 0 {
+                    Err(XmsError::AllExtendedMemoryAllocated)
+                } else {
+                    self.cpu.bx &= !0xFF;
+                    Ok(largest)
+                }
+            }
+            // Allocate block: DX = size in KB, returns DX = handle
+            0x09 => {
+                let (handle, status) = self.cpu.memory.xms.allocate_extended_memory(dx);
+                if status == XmsError::Success {
+                    self.cpu.dx = handle as u32;
+                }
+                xms_status(status)
+            }
+            0x0A => xms_status(self.cpu.memory.xms.free_extended_memory(dx)),
+            0x0B => xms_status(self.xms_move_block()),
+            // Lock block: returns its linear address in DX:BX
+            0x0C => {
+                let (address, status) = self.cpu.memory.xms.lock_extended_memory(dx);
+                if status == XmsError::Success {
+                    self.cpu.dx = address >> 16;
+                    self.cpu.bx = address & 0xFFFF;
+                }
+                xms_status(status)
+            }
+            0x0D => xms_status(self.cpu.memory.xms.unlock_extended_memory(dx)),
+            // Handle information: BH = lock count, BL = free handles, DX = size
+            0x0E => {
+                let (locks, free, size_kb, status) = self.cpu.memory.xms.get_handle_information(dx);
+                if status == XmsError::Success {
+                    self.cpu.bx = (locks as u32) << 8 | free as u32;
+                    self.cpu.dx = size_kb as u32;
+                }
+                xms_status(status)
+            }
+            // Reallocate block: a block that had to move takes its data along
+            0x0F => {
+                let (_, _, old_size_kb, _) = self.cpu.memory.xms.get_handle_information(dx);
+                let old_address = self.cpu.memory.xms.block_address(dx);
+                let status = self.cpu.memory.xms.reallocate_extended_memory(dx, bx);
+                if let (Some(from), Some(to)) = (old_address, self.cpu.memory.xms.block_address(dx))
+                {
+                    if from != to {
+                        self.copy_linear(from, to, old_size_kb as u32 * 1024);
+                    }
+                }
+                xms_status(status)
+            }
+            // Request UMB: DX = paragraphs, returns BX = segment, DX = size
+            // (or DX = largest available on failure)
+            0x10 => {
+                let (segment, size, status) = self.cpu.memory.xms.request_umb(dx);
+                if status == XmsError::Success {
+                    self.cpu.bx = segment as u32;
+                }
+                self.cpu.dx = size as u32;
+                xms_status(status)
+            }
+            0x11 => xms_status(self.cpu.memory.xms.release_umb(dx)),
+            _ => Err(XmsError::NotImplemented),
+        };
+
+        log(LogCategory::Interrupts, LogLevel::Debug, || {
+            format!("XMS AH=0x{:02X} -> {:?}", ah, result)
+        });
+        match result {
+            Ok(ax) => self.cpu.ax = ax as u32,
+            Err(error) => {
+                self.cpu.ax = 0x0000;
+                self.cpu.bx = (self.cpu.bx & !0xFF) | error as u32;
+            }
+        }
+
+        // RETF
+        let sp_addr = ((self.cpu.ss as u32) << 4) + (self.cpu.sp & 0xFFFF);
+        self.cpu.ip = self.read_word(sp_addr) as u32;
+        self.cpu.cs = self.read_word(sp_addr + 2);
+        self.cpu.sp = self.cpu.sp.wrapping_add(4) & 0xFFFF;
+        51
+    }
+
+    /// XMS AH=0Bh - Move extended memory block described at DS:SI
+    ///
+    /// The structure holds the length, then the source and destination as a
+    /// handle followed by a 32-bit offset (a real-mode seg:off for handle 0).
+    fn xms_move_block(&mut self) -> XmsError {
+        let addr = self.ds_si();
+        let read_dword = |cpu: &Self, addr: u32| {
+            cpu.read_word(addr) as u32 | (cpu.read_word(addr + 2) as u32) << 16
+        };
+        let length = read_dword(self, addr);
+        let src_handle = self.read_word(addr + 4);
+        let src_offset = read_dword(self, addr + 6);
+        let dest_handle = self.read_word(addr + 10);
+        let dest_offset = read_dword(self, addr + 12);
+
+        let (src, dest, status) = self.cpu.memory.xms.move_extended_memory(
+            src_handle,
+            src_offset,
+            dest_handle,
+            dest_offset,
+            length,
+        );
+        if status != XmsError::Success {
+            return status;
+        }
+
+        self.copy_linear(src, dest, length);
+        XmsError::Success
+    }
+
+    /// Copy memory between linear addresses for the XMS driver
+    ///
+    /// The driver enables A20 for the duration of the copy. Overlapping
+    /// ranges are handled by reading the source first.
+    fn copy_linear(&mut self, src: u32, dest: u32, length: u32) {
+        let a20 = self.cpu.memory.xms.is_a20_enabled();
+        self.cpu.memory.xms.set_a20_enabled(true);
+        let data: Vec<u8> = (0..length).map(|i| self.cpu.memory.read(src + i)).collect();
+        for (i, byte) in data.into_iter().enumerate() {
+            self.cpu.memory.write(dest + i as u32, byte);
+        }
+        self.cpu.memory.xms.set_a20_enabled(a20);
+    }
+
+    /// Handle the EMS driver entry point at F040:0012 (INT 67h)
+    ///
+    /// Reached when a program calls INT 67h through the default vector or
+    /// chains to it. Performs the EMS function in AH, returns the status in
+    /// AH, then returns to the caller.
+    fn handle_ems_entry(&mut self) -> u32 {
         let ah = ((self.cpu.ax >> 8) & 0xFF) as u8;
+        let al = (self.cpu.ax & 0xFF) as u8;
+        let bx = self.cpu.bx as u16;
+        let dx = self.cpu.dx as u16;
+
+        let status = match ah {
+            // Get status
+            0x40 => EmsError::Success,
+            // Get page frame segment
+            0x41 => {
+                self.cpu.bx = self.cpu.memory.ems.page_frame() as u32;
+                EmsError::Success
+            }
+            // Get unallocated page count
+            0x42 => {
+                let (free, total) = self.cpu.memory.ems.unallocated_pages();
+                self.cpu.bx = free as u32;
+                self.cpu.dx = total as u32;
+                EmsError::Success
+            }
+            // Allocate pages (AH=5Ah also allows zero pages)
+            0x43 | 0x5A => {
+                let (handle, status) = self.cpu.memory.ems.allocate_pages(bx, ah == 0x5A);
+                if status == EmsError::Success {
+                    self.cpu.dx = handle as u32;
+                }
+                status
+            }
+            // Map/unmap handle page
+            0x44 => self.cpu.memory.ems.map_page(al, dx, bx),
+            // Deallocate pages
+            0x45 => self.cpu.memory.ems.deallocate_pages(dx),
+            // Get version
+            0x46 => {
+                self.set_al(self.cpu.memory.ems.get_version());
+                EmsError::Success
+            }
+            // Save/restore page map
+            0x47 => self.cpu.memory.ems.save_page_map(dx),
+            0x48 => self.cpu.memory.ems.restore_page_map(dx),
+            // Get handle count
+            0x4B => {
+                self.cpu.bx = self.cpu.memory.ems.handle_count() as u32;
+                EmsError::Success
+            }
+            // Get handle pages
+            0x4C => {
+                let (pages, status) = self.cpu.memory.ems.handle_pages(dx);
+                self.cpu.bx = pages as u32;
+                status
+            }
+            // Get all handle pages
+            0x4D => {
+                let entries = self.cpu.memory.ems.all_handle_pages();
+                let mut addr = self.es_di();
+                for (handle, pages) in &entries {
+                    self.write_word(addr, *handle);
+                    self.write_word(addr + 2, *pages);
+                    addr += 4;
+                }
+                self.cpu.bx = entries.len() as u32;
+                EmsError::Success
+            }
+            0x4E => self.ems_page_map_to_from_memory(al),
+            0x50 => self.ems_map_multiple(al),
+            // Reallocate pages
+            0x51 => {
+                let (pages, status) = self.cpu.memory.ems.reallocate_pages(dx, bx);
+                self.cpu.bx = pages as u32;
+                status
+            }
+            0x53 => self.ems_handle_name(al, dx),
+            0x54 => self.ems_handle_directory(al),
+            // Get mappable physical address array
+            0x58 => {
+                let pages = self.cpu.memory.ems.mappable_pages();
+                if al == 0x00 {
+                    let mut addr = self.es_di();
+                    for (segment, physical) in &pages {
+                        self.write_word(addr, *segment);
+                        self.write_word(addr + 2, *physical);
+                        addr += 4;
+                    }
+                }
+                if al <= 0x01 {
+                    self.cpu.cx = pages.len() as u32;
+                    EmsError::Success
+                } else {
+                    EmsError::InvalidSubfunction
+                }
+            }
+            _ => EmsError::UndefinedFunction,
+        };
 
-        // Log the call for debugging
-        log(LogCategory::Stubs, LogLevel::Debug, || {
+        log(LogCategory::Interrupts, LogLevel::Debug, || {
             format!(
-                "INT 0x2A AH=0x{:02X} called from {:04X}:{:04X}",
-                ah,
-                self.cpu.cs,
-                self.cpu.ip.wrapping_sub(2)
+                "EMS AH=0x{:02X} AL=0x{:02X} -> 0x{:02X}",
+                ah, al, status as u8
             )
         });
-
-        // Network Installation API stub
-        // All functions return AL=0 (not installed/not supported)
-        self.cpu.ax &= 0xFF00; // AL = 0 (not installed)
-        self.set_carry_flag(true); // CF = 1 (error/not installed)
-
+        self.cpu.ax = (self.cpu.ax & !0xFF00) | ((status as u32) << 8);
+        self.simulate_iret();
         51
     }
 
-    /// Handle INT 2Fh - Multiplex interrupt
-    #[allow(dead_code)] // Called dynamically based on interrupt number
-    fn handle_int2fh(&mut self) -> u32 {
-        // Skip the INT 2Fh instruction (2 bytes: 0xCD 0x2F)
-        self.cpu.ip = self.cpu.ip.wrapping_add(2);
-
-        // Get function code from AH register
-        let ah = ((self.cpu.ax >> 8) & 0xFF) as u8;
-
-        match ah {
-            0x11 => self.int2fh_network_installation_check(),
-            0x16 => self.int2fh_dpmi_installation_check(),
-            0x43 => self.int2fh_xms_installation_check(),
-            _ => {
-                // Unsupported function - log and return
-                self.log_stub_interrupt(
-                    0x2F,
-                    Some(ah),
-                    "Multiplex Interrupt (unsupported subfunction)",
-                );
-                51
+    /// EMS AH=4Eh - Get/set page map to/from memory
+    fn ems_page_map_to_from_memory(&mut self, al: u8) -> EmsError {
+        match al {
+            // Get page map into ES:DI
+            0x00 | 0x02 => {
+                if al == 0x02 {
+                    let status = self.ems_set_page_map_from_ds_si();
+                    if status != EmsError::Success {
+                        return status;
+                    }
+                }
+                let map = self.cpu.memory.ems.get_page_map();
+                let addr = self.es_di();
+                for (i, byte) in map.iter().enumerate() {
+                    self.cpu.memory.write(addr + i as u32, *byte);
+                }
+                EmsError::Success
+            }
+            // Set page map from DS:SI
+            0x01 => self.ems_set_page_map_from_ds_si(),
+            // Get page map size
+            0x03 => {
+                self.set_al(PAGE_MAP_SIZE as u8);
+                EmsError::Success
             }
+            _ => EmsError::InvalidSubfunction,
         }
     }
 
-    /// INT 2Fh, AH=11h - Network Redirector / Installation Check
-    #[allow(dead_code)] // Called from handle_int2fh
-    fn int2fh_network_installation_check(&mut self) -> u32 {
-        // AL contains subfunction
-        // This is used by DOS to check for network redirector
-        // We don't support networking, so return "not installed"
-        // AL = 0xFF means "not installed"
-        self.cpu.ax = (self.cpu.ax & 0xFF00) | 0xFFu32;
-        51
+    fn ems_set_page_map_from_ds_si(&mut self) -> EmsError {
+        let addr = self.ds_si();
+        let mut map = [0u8; PAGE_MAP_SIZE];
+        for (i, byte) in map.iter_mut().enumerate() {
+            *byte = self.cpu.memory.read(addr + i as u32);
+        }
+        self.cpu.memory.ems.set_page_map(&map)
     }
 
-    /// INT 2Fh, AH=43h - XMS Installation Check
-    #[allow(dead_code)] // Called from handle_int2fh
-    fn int2fh_xms_installation_check(&mut self) -> u32 {
-        let al = (self.cpu.ax & 0xFF) as u8;
+    /// EMS AH=50h - Map multiple pages for handle DX
+    ///
+    /// DS:SI holds CX entries of (logical page, physical page) for AL=00h, or
+    /// (logical page, segment) for AL=01h.
+    fn ems_map_multiple(&mut self, al: u8) -> EmsError {
+        if al > 0x01 {
+            return EmsError::InvalidSubfunction;
+        }
+        let handle = self.cpu.dx as u16;
+        let frame = self.cpu.memory.ems.page_frame();
+        let mut addr = self.ds_si();
+        for _ in 0..self.cpu.cx as u16 {
+            let logical = self.read_word(addr);
+            let target = self.read_word(addr + 2);
+            addr += 4;
+
+            let physical = if al == 0x00 {
+                target
+            } else if target >= frame && (target - frame).is_multiple_of(0x0400) {
+                (target - frame) / 0x0400
+            } else {
+                return EmsError::PhysicalPageOutOfRange;
+            };
+            let physical = u8::try_from(physical).unwrap_or(u8::MAX);
+            let status = self.cpu.memory.ems.map_page(physical, handle, logical);
+            if status != EmsError::Success {
+                return status;
+            }
+        }
+        EmsError::Success
+    }
 
+    /// EMS AH=53h - Get (AL=00h, into ES:DI) or set (AL=01h, from DS:SI) handle name
+    fn ems_handle_name(&mut self, al: u8, handle: u16) -> EmsError {
         match al {
             0x00 => {
-                // XMS installation check
-                if self.cpu.memory.xms.is_installed() {
-                    self.cpu.ax = 0x80 << 8; // AL = 0x80 = installed
-                } else {
-                    self.cpu.ax = 0x00u32; // Not installed
+                let (name, status) = self.cpu.memory.ems.handle_name(handle);
+                if status == EmsError::Success {
+                    let addr = self.es_di();
+                    for (i, byte) in name.iter().enumerate() {
+                        self.cpu.memory.write(addr + i as u32, *byte);
+                    }
                 }
-                51
+                status
             }
-            0x10 => {
-                // Get XMS driver address
-                // Return ES:BX pointing to XMS driver entry point in BIOS ROM
-                // The entry point is at F000:E010 in the BIOS ROM
-                self.cpu.es = 0xF000; // BIOS segment
-                self.cpu.bx = 0xE010u32; // Offset of XMS entry point
-                51
+            0x01 => {
+                let name = self.read_name(self.ds_si());
+                self.cpu.memory.ems.set_handle_name(handle, name)
             }
-            _ => 51,
+            _ => EmsError::InvalidSubfunction,
         }
     }
 
-    /// INT 2Fh, AH=16h - DPMI Installation Check
-    #[allow(dead_code)] // Called from handle_int2fh
-    fn int2fh_dpmi_installation_check(&mut self) -> u32 {
-        let al = (self.cpu.ax & 0xFF) as u8;
-
+    /// EMS AH=54h - Handle directory (AL=00h), search by name (AL=01h), or
+    /// total handle count (AL=02h)
+    fn ems_handle_directory(&mut self, al: u8) -> EmsError {
         match al {
             0x00 => {
-                // DPMI installation check
-                if self.cpu.memory.dpmi.is_installed() {
-                    // DPMI is installed
-                    self.cpu.ax = 0x0000u32; // AX = 0 (supported)
-                    self.cpu.bx = 0x0001u32; // BX = flags (bit 0 = 32-bit support)
-                    self.cpu.cx = self.cpu.memory.dpmi.processor_type() as u32; // Processor type
-                    self.cpu.dx = self.cpu.memory.dpmi.version() as u32; // DPMI version (BCD)
-
-                    // Entry point in ES:DI
-                    self.cpu.es = self.cpu.memory.dpmi.entry_segment();
-                    self.cpu.di = self.cpu.memory.dpmi.entry_offset() as u32;
-                } else {
-                    self.cpu.ax = 0x8001u32; // Not supported
+                let entries = self.cpu.memory.ems.handle_directory();
+                let mut addr = self.es_di();
+                for (handle, name) in &entries {
+                    self.write_word(addr, *handle);
+                    for (i, byte) in name.iter().enumerate() {
+                        self.cpu.memory.write(addr + 2 + i as u32, *byte);
+                    }
+                    addr += 10;
                 }
-                51
+                self.set_al(entries.len() as u8);
+                EmsError::Success
             }
-            _ => 51,
+            0x01 => {
+                let name = self.read_name(self.ds_si());
+                let (handle, status) = self.cpu.memory.ems.search_handle_name(name);
+                if status == EmsError::Success {
+                    self.cpu.dx = handle as u32;
+                }
+                status
+            }
+            0x02 => {
+                self.cpu.bx = self.cpu.memory.ems.total_handles() as u32;
+                EmsError::Success
+            }
+            _ => EmsError::InvalidSubfunction,
         }
     }
 
+    fn es_di(&self) -> u32 {
+        ((self.cpu.es as u32) << 4) + (self.cpu.di & 0xFFFF)
+    }
+
+    fn ds_si(&self) -> u32 {
+        ((self.cpu.ds as u32) << 4) + (self.cpu.si & 0xFFFF)
+    }
+
+    fn set_al(&mut self, val: u8) {
+        self.cpu.ax = (self.cpu.ax & !0xFF) | val as u32;
+    }
+
+    fn read_word(&self, addr: u32) -> u16 {
+        self.cpu.memory.read(addr) as u16 | (self.cpu.memory.read(addr + 1) as u16) << 8
+    }
+
+    fn write_word(&mut self, addr: u32, val: u16) {
+        self.cpu.memory.write(addr, val as u8);
+        self.cpu.memory.write(addr + 1, (val >> 8) as u8);
+    }
+
+    fn read_name(&self, addr: u32) -> [u8; 8] {
+        std::array::from_fn(|i| self.cpu.memory.read(addr + i as u32))
+    }
+
     /// Handle INT 31h - DPMI services
     #[allow(dead_code)] // Called dynamically based on interrupt number
     fn handle_int31h(&mut self) -> u32 {
@@ -5352,6 +7702,34 @@ impl PcCpu {
     }
 }
 
+/// Latch a byte into the printer port at `base` and pulse STROBE
+fn strobe_printer(bus: &mut PcBus, base: u16, byte: u8) {
+    bus.io_write(base, byte);
+    bus.io_write(base + 2, 0x0D);
+    bus.io_write(base + 2, 0x0C);
+}
+
+/// Text columns of a BIOS video mode
+fn video_mode_columns(mode: u8) -> u8 {
+    match mode {
+        0x00 | 0x01 | 0x04 | 0x05 | 0x0D | VGA_MODE_13H => 40,
+        _ => 80,
+    }
+}
+
+/// Bytes per display page of a BIOS video mode (BDA 0x40:0x4C)
+fn video_mode_page_size(mode: u8) -> u16 {
+    match mode {
+        0x00 | 0x01 => 0x0800,
+        0x02 | 0x03 | MDA_TEXT_MODE => 0x1000,
+        0x0D => 0x2000,
+        0x10 => 0x8000,
+        0x12 => 0xA000,
+        VGA_MODE_13H => 0xFA00,
+        _ => 0x4000,
+    }
+}
+
 /// Convert PC scancode to ASCII character (simplified mapping)
 /// This is kept for compatibility but should not be used internally
 #[allow(dead_code)]
@@ -5444,6 +7822,7 @@ pub struct CpuRegisters {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::bus::VideoAdapterType;
 
     #[test]
     fn test_cpu_initialization() {
@@ -5909,6 +8288,106 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_int13h_el_torito_floppy_emulation() {
+        let mut floppy = vec![0; 1474560];
+        floppy[0] = 0xEB;
+        floppy[510] = 0x55;
+        floppy[511] = 0xAA;
+        floppy[512] = 0x42; // Second sector
+        let mut bus = PcBus::new();
+        bus.mount_cdrom(crate::el_torito::bootable_cd(0x02, 0, &floppy));
+        bus.set_boot_priority(crate::BootPriority::CdRomFirst);
+        assert!(bus.load_boot_sector());
+        assert_eq!(bus.read_ram(0x7C00), 0xEB);
+        assert_eq!(bus.read_ram(crate::bios::BOOT_DRIVE_ADDR), 0x00);
+
+        let mut cpu = PcCpu::new(bus);
+        cpu.cpu.cs = 0x0000;
+        cpu.cpu.ip = 0x1000;
+        let int13 = |cpu: &mut PcCpu| {
+            cpu.cpu.ip = 0x1000;
+            cpu.cpu.memory.write(0x1000, 0xCD);
+            cpu.cpu.memory.write(0x1001, 0x13);
+            cpu.step();
+        };
+
+        // Drive 00h reads the floppy image on the CD
+        cpu.cpu.ax = 0x0201;
+        cpu.cpu.cx = 0x0002;
+        cpu.cpu.dx = 0x0000;
+        cpu.cpu.es = 0x0000;
+        cpu.cpu.bx = 0x2000;
+        int13(&mut cpu);
+        assert_eq!(cpu.cpu.flags & 0x0001, 0);
+        assert_eq!(cpu.cpu.memory.read(0x2000), 0x42);
+
+        // ...and is write protected
+        cpu.cpu.ax = 0x0301;
+        int13(&mut cpu);
+        assert_eq!((cpu.cpu.ax >> 8) & 0xFF, 0x03);
+        assert_eq!(cpu.cpu.flags & 0x0001, 1);
+
+        // AH=4Bh AL=01h fills the specification packet
+        cpu.cpu.ax = 0x4B01;
+        cpu.cpu.ds = 0x0000;
+        cpu.cpu.si = 0x3000;
+        int13(&mut cpu);
+        assert_eq!(cpu.cpu.flags & 0x0001, 0);
+        let packet: Vec<u8> = (0..0x13).map(|i| cpu.cpu.memory.read(0x3000 + i)).collect();
+        assert_eq!(&packet[..4], &[0x13, 0x02, 0x00, 0x00]);
+        assert_eq!(packet[4], 20); // Image LBA
+        assert_eq!(&packet[0x10..], &[79, 18, 1]);
+
+        // AL=00h terminates emulation; drive 00h is empty again
+        cpu.cpu.ax = 0x4B00;
+        int13(&mut cpu);
+        assert_eq!(cpu.cpu.flags & 0x0001, 0);
+        assert!(!cpu.cpu.memory.has_floppy(0));
+        cpu.cpu.ax = 0x4B01;
+        int13(&mut cpu);
+        assert_eq!(cpu.cpu.flags & 0x0001, 1);
+    }
+
+    #[test]
+    fn test_int13h_cdrom_uses_2048_byte_sectors() {
+        let mut cd = vec![0; 64 * 1024];
+        cd[2048 * 3] = 0xC3;
+        cd[2048 * 4 - 1] = 0xCD;
+        let mut bus = PcBus::new();
+        bus.mount_cdrom(cd);
+        let mut cpu = PcCpu::new(bus);
+        cpu.cpu.cs = 0x0000;
+        cpu.cpu.ip = 0x1000;
+        cpu.cpu.memory.write(0x1000, 0xCD);
+        cpu.cpu.memory.write(0x1001, 0x13);
+
+        // Disk address packet: 1 sector at LBA 3 to 0000:4000
+        let dap = [0x10, 0, 1, 0, 0x00, 0x40, 0, 0, 3, 0, 0, 0, 0, 0, 0, 0];
+        for (i, &byte) in dap.iter().enumerate() {
+            cpu.cpu.memory.write(0x3000 + i as u32, byte);
+        }
+        cpu.cpu.ax = 0x4200;
+        cpu.cpu.dx = 0x00E0;
+        cpu.cpu.ds = 0x0000;
+        cpu.cpu.si = 0x3000;
+        cpu.step();
+        assert_eq!(cpu.cpu.flags & 0x0001, 0);
+        assert_eq!(cpu.cpu.memory.read(0x4000), 0xC3);
+        assert_eq!(cpu.cpu.memory.read(0x4000 + 2047), 0xCD);
+
+        // AH=48h reports 2048-byte sectors on removable media
+        cpu.cpu.ip = 0x1000;
+        cpu.cpu.memory.write(0x5000, 0x1A);
+        cpu.cpu.ax = 0x4800;
+        cpu.cpu.si = 0x5000;
+        cpu.step();
+        assert_eq!(cpu.cpu.flags & 0x0001, 0);
+        assert_eq!(cpu.cpu.memory.read(0x5002), 0x04);
+        assert_eq!(cpu.cpu.memory.read(0x5010), 32);
+        assert_eq!(cpu.cpu.memory.read(0x5019), 0x08);
+    }
+
     #[test]
     fn test_int13h_set_media_type_invalid_drive() {
         let bus = PcBus::new(); // No floppy mounted
@@ -6277,6 +8756,120 @@ mod tests {
         assert!(!cpu.cpu.memory.keyboard.has_data());
     }
 
+    /// Put `INT 16h` at 0000:1000h and point the CPU at it
+    fn setup_int16h_call(cpu: &mut PcCpu, ax: u32) {
+        cpu.cpu.cs = 0x0000;
+        cpu.cpu.ip = 0x1000;
+        cpu.cpu.memory.write(0x1000, 0xCD); // INT
+        cpu.cpu.memory.write(0x1001, 0x16); // 16h
+        cpu.cpu.ax = ax;
+    }
+
+    #[test]
+    fn test_int16h_read_keystroke_waits_without_halting() {
+        use crate::keyboard::SCANCODE_A;
+
+        let mut cpu = PcCpu::new(PcBus::new());
+
+        // No key yet: the INT is retried instead of halting the CPU
+        setup_int16h_call(&mut cpu, 0x0000);
+        cpu.step();
+        assert!(!cpu.is_halted());
+        assert_eq!(cpu.cpu.ip, 0x1000);
+
+        cpu.cpu.memory.keyboard.key_press(SCANCODE_A);
+        cpu.step();
+        assert_eq!(cpu.cpu.ip, 0x1002);
+        assert_eq!(cpu.cpu.ax, ((SCANCODE_A as u32) << 8) | b'a' as u32);
+    }
+
+    #[test]
+    fn test_keyboard_irq_fills_bda_buffer() {
+        use crate::keyboard::SCANCODE_B;
+
+        let mut cpu = PcCpu::new(PcBus::new());
+        cpu.cpu.memory.keyboard.key_press(SCANCODE_B);
+        cpu.service_bios_irq(Some(1));
+
+        // The key is visible through the BDA head/tail pointers without INT 16h
+        let head = cpu.read_bda_word(0x041A);
+        let tail = cpu.read_bda_word(0x041C);
+        assert_eq!(tail, head + 2);
+        assert_eq!(cpu.cpu.memory.read(0x400 + head as u32), b'b');
+        assert_eq!(cpu.cpu.memory.read(0x400 + head as u32 + 1), SCANCODE_B);
+    }
+
+    #[test]
+    fn test_int16h_store_and_enhanced_read() {
+        let mut cpu = PcCpu::new(PcBus::new());
+
+        // AH=05h: store scancode 2Ch / 'z'
+        setup_int16h_call(&mut cpu, 0x0500);
+        cpu.cpu.cx = 0x2C7A;
+        cpu.step();
+        assert_eq!(cpu.cpu.ax & 0xFF, 0x00);
+
+        // AH=11h: enhanced check sees it without removing it
+        setup_int16h_call(&mut cpu, 0x1100);
+        cpu.step();
+        assert_eq!(cpu.cpu.flags & 0x0040, 0);
+        assert_eq!(cpu.cpu.ax, 0x2C7A);
+
+        // AH=10h: enhanced read removes it
+        setup_int16h_call(&mut cpu, 0x1000);
+        cpu.step();
+        assert_eq!(cpu.cpu.ax, 0x2C7A);
+        setup_int16h_call(&mut cpu, 0x0100);
+        cpu.step();
+        assert_eq!(cpu.cpu.flags & 0x0040, 0x0040);
+    }
+
+    #[test]
+    fn test_int16h_store_keystroke_buffer_full() {
+        let mut cpu = PcCpu::new(PcBus::new());
+
+        // The 16-entry buffer holds 15 keys (head == tail means empty)
+        for i in 0..16 {
+            setup_int16h_call(&mut cpu, 0x0500);
+            cpu.cpu.cx = 0x1E61;
+            cpu.step();
+            let expected = if i < 15 { 0x00 } else { 0x01 };
+            assert_eq!(cpu.cpu.ax & 0xFF, expected, "store {}", i);
+        }
+    }
+
+    #[test]
+    fn test_keyboard_buffer_full_beeps() {
+        use crate::keyboard::SCANCODE_A;
+        let mut cpu = PcCpu::new(PcBus::new());
+
+        // 15 keys fill the BDA buffer; the 16th is dropped with a beep
+        for _ in 0..16 {
+            assert!(!cpu.cpu.memory.bios_beep_active());
+            cpu.cpu.memory.keyboard.key_press(SCANCODE_A);
+            cpu.sync_bda_keyboard_buffer();
+        }
+        assert!(cpu.cpu.memory.bios_beep_active());
+        assert_eq!(cpu.cpu.memory.keyboard.peek_make_code(), None);
+    }
+
+    #[test]
+    fn test_int10h_teletype_bell() {
+        let mut cpu = PcCpu::new(PcBus::new());
+        cpu.cpu.cs = 0x0000;
+        cpu.cpu.ip = 0x1000;
+        cpu.cpu.memory.write(0x1000, 0xCD); // INT 10h
+        cpu.cpu.memory.write(0x1001, 0x10);
+        cpu.cpu.ax = 0x0E07; // AH=0Eh teletype, AL=BEL
+        cpu.cpu.bx = 0x0000;
+        cpu.step();
+
+        assert!(cpu.cpu.memory.bios_beep_active());
+        // The cursor did not move and nothing was drawn
+        assert_eq!(cpu.cpu.memory.read(0x450), 0);
+        assert_eq!(cpu.cpu.memory.read(0xB8000), 0);
+    }
+
     #[test]
     fn test_int10h_select_active_page() {
         let bus = PcBus::new();
@@ -6286,19 +8879,211 @@ mod tests {
         cpu.cpu.cs = 0x0000;
         cpu.cpu.ip = 0x1000;
 
-        // Write INT 10h instruction
-        let addr = ((cpu.cpu.cs as u32) << 4) + cpu.cpu.ip;
-        cpu.cpu.memory.write(addr, 0xCD); // INT
-        cpu.cpu.memory.write(addr + 1, 0x10); // 10h
+        // Write INT 10h instruction
+        let addr = ((cpu.cpu.cs as u32) << 4) + cpu.cpu.ip;
+        cpu.cpu.memory.write(addr, 0xCD); // INT
+        cpu.cpu.memory.write(addr + 1, 0x10); // 10h
+
+        // AH=05h, AL=3 (select page 3)
+        cpu.cpu.ax = 0x0503;
+
+        // Execute INT 10h
+        cpu.step();
+
+        // Verify page was stored in BIOS data area
+        assert_eq!(cpu.cpu.memory.read(0x462), 3);
+        // Page offset and CRTC start address of the 4KB page
+        assert_eq!(cpu.cpu.memory.read(0x44F), 0x30);
+        assert_eq!(cpu.cpu.memory.text_display_offset(), 0x18000 + 0x3000);
+    }
+
+    /// Call INT 10h from 0000:1000h with AX set
+    fn int10h(cpu: &mut PcCpu, ax: u32) {
+        cpu.cpu.cs = 0x0000;
+        cpu.cpu.ip = 0x1000;
+        cpu.cpu.memory.write(0x1000, 0xCD);
+        cpu.cpu.memory.write(0x1001, 0x10);
+        cpu.cpu.ax = ax;
+        cpu.step();
+    }
+
+    #[test]
+    fn test_int10h_cursor_shape() {
+        let mut cpu = PcCpu::new(PcBus::new());
+
+        // AH=01h: hidden cursor (bit 5 of the start line)
+        cpu.cpu.cx = 0x2607;
+        int10h(&mut cpu, 0x0100);
+        assert_eq!(cpu.cpu.memory.read(0x460), 0x07);
+        assert_eq!(cpu.cpu.memory.read(0x461), 0x26);
+        cpu.cpu.memory.io_write(0x3D4, 0x0A);
+        assert_eq!(cpu.cpu.memory.io_read(0x3D5), 0x26);
+
+        // AH=03h returns it with the cursor position
+        cpu.cpu.memory.write(0x450, 12);
+        cpu.cpu.memory.write(0x451, 3);
+        cpu.cpu.bx = 0x0000;
+        cpu.cpu.cx = 0;
+        int10h(&mut cpu, 0x0300);
+        assert_eq!(cpu.cpu.cx, 0x2607);
+        assert_eq!(cpu.cpu.dx, 0x030C);
+
+        // Mode sets restore the default shape
+        int10h(&mut cpu, 0x0003);
+        int10h(&mut cpu, 0x0300);
+        assert_eq!(cpu.cpu.cx, 0x0607);
+    }
+
+    #[test]
+    fn test_int10h_teletype_wrap_and_scroll() {
+        let mut cpu = PcCpu::new(PcBus::new());
+        let cell = |row: u32, col: u32| 0xB8000 + (row * 80 + col) * 2;
+
+        // Bottom right corner of a blue bottom row; row 1 holds an 'X'
+        cpu.cpu.memory.write(0x450, 79);
+        cpu.cpu.memory.write(0x451, 24);
+        for col in 0..80 {
+            cpu.cpu.memory.write(cell(24, col) + 1, 0x1F);
+        }
+        cpu.cpu.memory.write(cell(1, 0), b'X');
+        cpu.cpu.bx = 0x0000;
+        int10h(&mut cpu, 0x0E41);
+
+        // The 'A' kept its attribute and scrolled up with the screen
+        assert_eq!(cpu.cpu.memory.read(cell(23, 79)), b'A');
+        assert_eq!(cpu.cpu.memory.read(cell(23, 79) + 1), 0x1F);
+        assert_eq!(cpu.cpu.memory.read(cell(0, 0)), b'X');
+        // The new line is blanked with the attribute at the cursor
+        assert_eq!(cpu.cpu.memory.read(cell(24, 0)), b' ');
+        assert_eq!(cpu.cpu.memory.read(cell(24, 0) + 1), 0x1F);
+        assert_eq!(cpu.cpu.memory.read(0x450), 0);
+        assert_eq!(cpu.cpu.memory.read(0x451), 24);
+
+        // Line feed on the last row scrolls as well
+        cpu.cpu.memory.write(cell(24, 0), b'Y');
+        int10h(&mut cpu, 0x0E0A);
+        assert_eq!(cpu.cpu.memory.read(cell(23, 0)), b'Y');
+        assert_eq!(cpu.cpu.memory.read(0x451), 24);
+    }
+
+    #[test]
+    fn test_int10h_text_pages() {
+        let mut cpu = PcCpu::new(PcBus::new());
+
+        // Teletype to the active page 1 uses its own cursor and memory,
+        // whatever BH holds
+        int10h(&mut cpu, 0x0501);
+        cpu.cpu.memory.write(0x452, 5);
+        cpu.cpu.bx = 0x0500;
+        int10h(&mut cpu, 0x0E42);
+        assert_eq!(cpu.cpu.memory.read(0xB9000 + 10), b'B');
+        assert_eq!(cpu.cpu.memory.read(0xB8000 + 10), 0);
+        assert_eq!(cpu.cpu.memory.read(0x452), 6);
+        assert_eq!(cpu.cpu.memory.read(0x450), 0);
+
+        // AH=08h reads the page given in BH
+        cpu.cpu.memory.write(0x452, 5);
+        cpu.cpu.bx = 0x0100;
+        int10h(&mut cpu, 0x0800);
+        assert_eq!(cpu.cpu.ax & 0xFF, b'B' as u32);
+
+        // AH=0Fh reports the active page
+        cpu.cpu.bx = 0;
+        int10h(&mut cpu, 0x0F00);
+        assert_eq!(cpu.cpu.bx >> 8, 1);
+    }
+
+    #[test]
+    fn test_int10h_write_char_attr_wraps() {
+        let mut cpu = PcCpu::new(PcBus::new());
+
+        // Four characters from column 78 continue on the next row
+        cpu.cpu.memory.write(0x450, 78);
+        cpu.cpu.bx = 0x001E;
+        cpu.cpu.cx = 4;
+        int10h(&mut cpu, 0x09DB);
+        for col in [78, 79, 80, 81] {
+            assert_eq!(cpu.cpu.memory.read(0xB8000 + col * 2), 0xDB);
+            assert_eq!(cpu.cpu.memory.read(0xB8000 + col * 2 + 1), 0x1E);
+        }
+        assert_eq!(cpu.cpu.memory.read(0xB8000 + 82 * 2), 0);
+        // The cursor stays put
+        assert_eq!(cpu.cpu.memory.read(0x450), 78);
+
+        // Writing stops at the end of the page
+        cpu.cpu.memory.write(0x450, 79);
+        cpu.cpu.memory.write(0x451, 24);
+        cpu.cpu.cx = 10;
+        int10h(&mut cpu, 0x0A41);
+        assert_eq!(cpu.cpu.memory.read(0xB8000 + 1999 * 2), b'A');
+        assert_eq!(cpu.cpu.memory.read(0xB8000 + 2000 * 2), 0);
+    }
+
+    #[test]
+    fn test_int10h_scroll_clips_window() {
+        let mut cpu = PcCpu::new(PcBus::new());
+        cpu.cpu.memory.write(0xB8000 + 160, b'1');
+        cpu.cpu.memory.write(0xB8000 + 24 * 160 + 158, b'Z');
+
+        // A lower right corner past the screen means the whole screen
+        cpu.cpu.bx = 0x0700;
+        cpu.cpu.cx = 0x0000;
+        cpu.cpu.dx = 0xFFFF;
+        int10h(&mut cpu, 0x0601);
+        assert_eq!(cpu.cpu.memory.read(0xB8000), b'1');
+        assert_eq!(cpu.cpu.memory.read(0xB8000 + 23 * 160 + 158), b'Z');
+        assert_eq!(cpu.cpu.memory.read(0xB8000 + 24 * 160 + 158), b' ');
+        // Nothing past the page was touched
+        assert_eq!(cpu.cpu.memory.read(0xB8000 + 25 * 160), 0);
+
+        // More lines than the window blanks it
+        cpu.cpu.dx = 0x184F;
+        int10h(&mut cpu, 0x0740);
+        assert_eq!(cpu.cpu.memory.read(0xB8000), b' ');
+    }
 
-        // AH=05h, AL=3 (select page 3)
-        cpu.cpu.ax = 0x0503;
+    #[test]
+    fn test_int10h_set_mode_reconfigures_adapter() {
+        let mut cpu = PcCpu::new(PcBus::new());
+
+        // Mode 01h: 40x25 color text
+        int10h(&mut cpu, 0x0001);
+        assert_eq!(cpu.cpu.memory.video_mode(), 0x01);
+        assert_eq!(cpu.cpu.memory.text_columns(), 40);
+        assert_eq!(cpu.cpu.memory.io_read(0x3D8), 0x28);
+        assert_eq!(cpu.cpu.memory.read(0x44A), 40);
+        assert_eq!(cpu.cpu.memory.read(0x44D), 0x08); // 2KB pages
+        assert_eq!(cpu.cpu.memory.read(0x465), 0x28);
+
+        // Teletype wraps after 40 columns
+        cpu.cpu.memory.write(0x450, 39);
+        cpu.cpu.bx = 0x0000;
+        int10h(&mut cpu, 0x0E41);
+        assert_eq!(cpu.cpu.memory.read(0xB8000 + 39 * 2), b'A');
+        assert_eq!(cpu.cpu.memory.read(0x450), 0);
+        assert_eq!(cpu.cpu.memory.read(0x451), 1);
 
-        // Execute INT 10h
-        cpu.step();
+        // Mode 06h: 640x200 graphics, memory cleared
+        int10h(&mut cpu, 0x0006);
+        assert_eq!(cpu.cpu.memory.io_read(0x3D8), 0x1E);
+        assert_eq!(cpu.cpu.memory.io_read(0x3D9), 0x3F);
+        assert_eq!(cpu.cpu.memory.read(0xB8000 + 39 * 2), 0);
 
-        // Verify page was stored in BIOS data area
-        assert_eq!(cpu.cpu.memory.read(0x462), 3);
+        // The monochrome mode needs an MDA; a CGA ignores it
+        int10h(&mut cpu, 0x0007);
+        assert_eq!(cpu.cpu.memory.video_mode(), 0x06);
+
+        let mut bus = PcBus::new();
+        bus.set_video_adapter_type(VideoAdapterType::Mda);
+        let mut cpu = PcCpu::new(bus);
+        int10h(&mut cpu, 0x0003);
+        assert_eq!(cpu.cpu.memory.video_mode(), 0x03); // Unchanged default
+        assert_eq!(cpu.cpu.memory.read(0x449), 0);
+        int10h(&mut cpu, 0x0007);
+        assert_eq!(cpu.cpu.memory.read(0x449), 0x07);
+        assert_eq!(cpu.cpu.memory.read(0xB0000), b' ');
+        int10h(&mut cpu, 0x0300);
+        assert_eq!(cpu.cpu.cx, 0x0B0C);
     }
 
     #[test]
@@ -6547,6 +9332,93 @@ mod tests {
         assert_eq!(cpu.cpu.memory.read(video_addr), b'1');
     }
 
+    #[test]
+    fn test_int10h_set_mode_13h() {
+        let mut bus = PcBus::new();
+        bus.set_video_adapter_type(VideoAdapterType::Vga);
+        let mut cpu = PcCpu::new(bus);
+        cpu.cpu.cs = 0x0000;
+        cpu.cpu.ip = 0x1000;
+        let int10h = |cpu: &mut PcCpu, ax: u32| {
+            cpu.cpu.ip = 0x1000;
+            cpu.cpu.memory.write(0x1000, 0xCD);
+            cpu.cpu.memory.write(0x1001, 0x10);
+            cpu.cpu.ax = ax;
+            cpu.step();
+        };
+
+        int10h(&mut cpu, 0x0013);
+        assert_eq!(cpu.cpu.memory.video_mode(), 0x13);
+        assert_eq!(cpu.cpu.memory.read(0x449), 0x13);
+        int10h(&mut cpu, 0x0F00);
+        assert_eq!(cpu.cpu.ax & 0xFFFF, 0x2813); // 40 columns, mode 13h
+
+        // AX=1010h: set DAC register 1 to DH/CH/CL
+        cpu.cpu.bx = 0x0001;
+        cpu.cpu.dx = 0x3F00;
+        cpu.cpu.cx = 0x1020;
+        int10h(&mut cpu, 0x1010);
+        assert_eq!(&cpu.cpu.memory.dac_palette()[3..6], &[0x3F, 0x10, 0x20]);
+
+        // AX=1017h: read it back into ES:DX
+        cpu.cpu.es = 0x0000;
+        cpu.cpu.dx = 0x2000;
+        cpu.cpu.cx = 1;
+        int10h(&mut cpu, 0x1017);
+        assert_eq!(cpu.cpu.memory.read(0x2000), 0x3F);
+        assert_eq!(cpu.cpu.memory.read(0x2002), 0x20);
+
+        // Mode 3 leaves graphics and blanks the text screen
+        int10h(&mut cpu, 0x0003);
+        assert_eq!(cpu.cpu.memory.video_mode(), 0x03);
+        assert_eq!(cpu.cpu.memory.read(0xB8000), b' ');
+        assert_eq!(cpu.cpu.memory.read(0xB8001), 0x07);
+    }
+
+    #[test]
+    fn test_int10h_planar_pixels_and_palette() {
+        let mut bus = PcBus::new();
+        bus.set_video_adapter_type(VideoAdapterType::Ega);
+        let mut cpu = PcCpu::new(bus);
+        cpu.cpu.cs = 0x0000;
+        let int10h = |cpu: &mut PcCpu, ax: u32| {
+            cpu.cpu.ip = 0x1000;
+            cpu.cpu.memory.write(0x1000, 0xCD);
+            cpu.cpu.memory.write(0x1001, 0x10);
+            cpu.cpu.ax = ax;
+            cpu.step();
+        };
+
+        // 640x480 needs a VGA
+        int10h(&mut cpu, 0x0012);
+        assert_eq!(cpu.cpu.memory.video_mode(), 0x03);
+
+        int10h(&mut cpu, 0x0010);
+        assert_eq!(cpu.cpu.memory.video_mode(), 0x10);
+
+        // AH=0Ch: pixel (9, 2) in color 0Ah, then XOR it with 03h
+        cpu.cpu.cx = 9;
+        cpu.cpu.dx = 2;
+        int10h(&mut cpu, 0x0C0A);
+        int10h(&mut cpu, 0x0D00);
+        assert_eq!(cpu.cpu.ax & 0xFF, 0x0A);
+        int10h(&mut cpu, 0x0C83);
+        int10h(&mut cpu, 0x0D00);
+        assert_eq!(cpu.cpu.ax & 0xFF, 0x09);
+
+        // AX=1000h: palette register 9 becomes bright white
+        cpu.cpu.bx = 0x3F09;
+        int10h(&mut cpu, 0x1000);
+        cpu.cpu.bx = 0x0009;
+        int10h(&mut cpu, 0x1007);
+        assert_eq!(cpu.cpu.bx >> 8, 0x3F);
+
+        let (width, height, pixels) = cpu.cpu.memory.vga_graphics_frame().unwrap();
+        assert_eq!((width, height), (640, 350));
+        assert_eq!(pixels[2 * 640 + 9], 0x3F);
+        assert_eq!(pixels[2 * 640 + 8], 0x00);
+    }
+
     #[test]
     fn test_int10h_display_combination() {
         let bus = PcBus::new();
@@ -6813,6 +9685,197 @@ mod tests {
         assert!(!cpu.get_carry_flag());
     }
 
+    #[test]
+    fn test_int1ah_time_services() {
+        use crate::rtc::RtcClock;
+
+        let mut bus = PcBus::new();
+        // 1995-08-24 09:30:15
+        bus.rtc.set_clock(RtcClock::Frozen { time: 809_256_615 });
+        let mut cpu = PcCpu::new(bus);
+
+        let int1ah = |cpu: &mut PcCpu, ax: u32, cx: u32, dx: u32| {
+            cpu.cpu.cs = 0x0000;
+            cpu.cpu.ip = 0x1000;
+            cpu.cpu.memory.write(0x1000, 0xCD); // INT
+            cpu.cpu.memory.write(0x1001, 0x1A); // 1Ah
+            cpu.cpu.ax = ax;
+            cpu.cpu.cx = cx;
+            cpu.cpu.dx = dx;
+            cpu.step();
+        };
+
+        // AH=02h/04h: time and date in BCD from the RTC
+        int1ah(&mut cpu, 0x0200, 0, 0);
+        assert_eq!(cpu.cpu.cx, 0x0930);
+        assert_eq!(cpu.cpu.dx, 0x1500);
+        int1ah(&mut cpu, 0x0400, 0, 0);
+        assert_eq!(cpu.cpu.cx, 0x1995);
+        assert_eq!(cpu.cpu.dx, 0x0824);
+        assert_eq!(cpu.cpu.flags & 0x0001, 0);
+
+        // AH=03h/05h: set the time and date, kept by the frozen clock
+        int1ah(&mut cpu, 0x0300, 0x2359, 0x5801);
+        int1ah(&mut cpu, 0x0500, 0x2001, 0x1231);
+        assert_eq!(cpu.cpu.flags & 0x0001, 0);
+        assert_eq!(
+            cpu.cpu.memory.rtc.date_time().to_string(),
+            "2001-12-31 23:59:58"
+        );
+        assert!(cpu.cpu.memory.rtc.daylight_saving());
+
+        // Invalid BCD or dates set CF and leave the clock alone
+        int1ah(&mut cpu, 0x0500, 0x2001, 0x0230);
+        assert_eq!(cpu.cpu.flags & 0x0001, 1);
+        int1ah(&mut cpu, 0x0300, 0x2A00, 0x0000);
+        assert_eq!(cpu.cpu.flags & 0x0001, 1);
+        assert_eq!(
+            cpu.cpu.memory.rtc.date_time().to_string(),
+            "2001-12-31 23:59:58"
+        );
+
+        // AH=01h/00h: the tick counter lives in the BDA with the midnight flag
+        int1ah(&mut cpu, 0x0100, 0x0012, 0x3456);
+        cpu.cpu.memory.write(BDA_MIDNIGHT_FLAG, 1);
+        int1ah(&mut cpu, 0x0000, 0, 0);
+        assert_eq!(cpu.cpu.cx, 0x0012);
+        assert_eq!(cpu.cpu.dx, 0x3456);
+        assert_eq!(cpu.cpu.ax & 0xFF, 1);
+        int1ah(&mut cpu, 0x0000, 0, 0);
+        assert_eq!(cpu.cpu.ax & 0xFF, 0); // Flag cleared by the previous read
+    }
+
+    #[test]
+    fn test_int14h_serial_services() {
+        use crate::serial::SerialBackend;
+        use std::collections::VecDeque;
+        use std::sync::{Arc, Mutex};
+
+        struct Modem {
+            sent: Arc<Mutex<Vec<u8>>>,
+            input: VecDeque<u8>,
+        }
+        impl SerialBackend for Modem {
+            fn poll(&mut self) {}
+            fn send(&mut self, byte: u8) {
+                self.sent.lock().unwrap().push(byte);
+            }
+            fn receive(&mut self) -> Option<u8> {
+                self.input.pop_front()
+            }
+            fn connected(&self) -> bool {
+                true
+            }
+        }
+
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let mut bus = PcBus::new();
+        bus.serial[0].attach(Box::new(Modem {
+            sent: Arc::clone(&sent),
+            input: VecDeque::from(vec![b'Z']),
+        }));
+        bus.write(BDA_COM_PORTS, 0xF8);
+        bus.write(BDA_COM_PORTS + 1, 0x03); // COM1 at 3F8h
+        let mut cpu = PcCpu::new(bus);
+
+        let int14h = |cpu: &mut PcCpu, ax: u32, dx: u32| {
+            cpu.cpu.cs = 0x0000;
+            cpu.cpu.ip = 0x1000;
+            cpu.cpu.memory.write(0x1000, 0xCD); // INT
+            cpu.cpu.memory.write(0x1001, 0x14); // 14h
+            cpu.cpu.ax = ax;
+            cpu.cpu.dx = dx;
+            cpu.step();
+        };
+
+        // AH=00h: 1200 baud, no parity, 1 stop bit, 8 bits
+        int14h(&mut cpu, 0x0083, 0);
+        assert_eq!(cpu.cpu.memory.serial[0].baud_rate(), 1200);
+        assert_eq!(cpu.cpu.ax & 0xFFF0, 0x60B0); // THR empty; CTS, DSR, DCD
+
+        // AH=01h: transmit
+        int14h(&mut cpu, 0x0141, 0);
+        assert_eq!(*sent.lock().unwrap(), b"A");
+        assert_eq!(cpu.cpu.ax & 0x80FF, 0x0041);
+
+        // AH=02h: nothing received yet times out, then the byte arrives
+        int14h(&mut cpu, 0x0200, 0);
+        assert_ne!(cpu.cpu.ax & 0x8000, 0);
+        cpu.cpu.memory.clock_serial(100_000, 4.77);
+        int14h(&mut cpu, 0x0300, 0);
+        assert_eq!((cpu.cpu.ax >> 8) & 0x01, 0x01); // Data ready
+        int14h(&mut cpu, 0x0200, 0);
+        assert_eq!(cpu.cpu.ax, u32::from(b'Z'));
+
+        // COM3 is not installed
+        int14h(&mut cpu, 0x0300, 2);
+        assert_eq!(cpu.cpu.ax & 0xFF00, 0x8000);
+    }
+
+    #[test]
+    fn test_int17h_printer() {
+        use std::sync::{Arc, Mutex};
+
+        struct Paper(Arc<Mutex<Vec<u8>>>);
+        impl std::io::Write for Paper {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let mut bus = PcBus::new();
+        bus.write(BDA_LPT_PORTS, 0x78);
+        bus.write(BDA_LPT_PORTS + 1, 0x03); // LPT1 at 378h
+        let mut cpu = PcCpu::new(bus);
+
+        let call = |cpu: &mut PcCpu, int: u8, ax: u32, dx: u32| {
+            cpu.cpu.cs = 0x0000;
+            cpu.cpu.ip = 0x1000;
+            cpu.cpu.memory.write(0x1000, 0xCD); // INT
+            cpu.cpu.memory.write(0x1001, int);
+            cpu.cpu.ax = ax;
+            cpu.cpu.dx = dx;
+            cpu.step();
+        };
+
+        // No printer attached: not busy, I/O error, not selected
+        call(&mut cpu, 0x17, 0x0041, 0);
+        assert_eq!(cpu.cpu.ax, 0x8841);
+
+        let paper = Arc::new(Mutex::new(Vec::new()));
+        cpu.cpu
+            .memory
+            .parallel
+            .attach(Box::new(Paper(Arc::clone(&paper))));
+        call(&mut cpu, 0x17, 0x0100, 0);
+        assert_eq!(cpu.cpu.ax >> 8, 0x90); // Not busy, selected
+
+        // AH=00h prints AL and reports the acknowledge
+        call(&mut cpu, 0x17, 0x0041, 0);
+        assert_eq!(cpu.cpu.ax, 0xD041);
+        call(&mut cpu, 0x17, 0x0200, 0);
+        assert_eq!(cpu.cpu.ax >> 8, 0x90);
+
+        // DOS printer output: AH=05h and writes to handle 4 (stdprn)
+        call(&mut cpu, 0x21, 0x0500, u32::from(b'B'));
+        cpu.cpu.ds = 0x0000;
+        cpu.cpu.memory.write(0x2000, b'C');
+        cpu.cpu.memory.write(0x2001, b'\n');
+        cpu.cpu.bx = 4;
+        cpu.cpu.cx = 2;
+        call(&mut cpu, 0x21, 0x4000, 0x2000);
+        assert_eq!(cpu.cpu.ax, 2);
+        assert_eq!(*paper.lock().unwrap(), b"ABC\n");
+
+        // LPT2 is not installed
+        call(&mut cpu, 0x17, 0x0200, 1);
+        assert_eq!(cpu.cpu.ax >> 8, 0x01);
+    }
+
     #[test]
     fn test_int11h_equipment_list() {
         use crate::bus::VideoAdapterType;
@@ -6994,6 +10057,72 @@ mod tests {
         assert_eq!(model, 0xFE);
     }
 
+    #[test]
+    fn test_int15h_cassette_write_and_read() {
+        let mut bus = PcBus::new();
+        bus.cassette.insert(crate::cassette::Tape::blank());
+        let mut cpu = PcCpu::new(bus);
+        cpu.cpu.cs = 0x0000;
+        cpu.cpu.ip = 0x1000;
+
+        let run_int15h = |cpu: &mut PcCpu, ax: u32| {
+            let addr = ((cpu.cpu.cs as u32) << 4) + cpu.cpu.ip;
+            cpu.cpu.memory.write(addr, 0xCD); // INT
+            cpu.cpu.memory.write(addr + 1, 0x15); // 15h
+            cpu.cpu.ax = ax;
+            cpu.step();
+        };
+
+        // Write "BASIC" from 0000:2000 to tape
+        for (i, &b) in b"BASIC".iter().enumerate() {
+            cpu.cpu.memory.write(0x2000 + i as u32, b);
+        }
+        cpu.cpu.es = 0x0000;
+        cpu.cpu.bx = 0x2000;
+        cpu.cpu.cx = 5;
+        run_int15h(&mut cpu, 0x0300);
+        assert!(!cpu.get_carry_flag());
+        assert_eq!(cpu.cpu.bx, 0x2005);
+        assert_eq!(cpu.cpu.cx, 0);
+
+        // Rewind and read it back into 0000:3000
+        cpu.cpu.memory.cassette.tape_mut().unwrap().rewind();
+        cpu.cpu.bx = 0x3000;
+        cpu.cpu.cx = 5;
+        run_int15h(&mut cpu, 0x0200);
+        assert!(!cpu.get_carry_flag());
+        assert_eq!(cpu.cpu.dx, 5);
+        assert_eq!(cpu.cpu.bx, 0x3005);
+        for (i, &b) in b"BASIC".iter().enumerate() {
+            assert_eq!(cpu.cpu.memory.read(0x3000 + i as u32), b);
+        }
+
+        // End of tape: no data found
+        cpu.cpu.cx = 5;
+        run_int15h(&mut cpu, 0x0200);
+        assert!(cpu.get_carry_flag());
+        assert_eq!((cpu.cpu.ax >> 8) & 0xFF, 0x04);
+    }
+
+    #[test]
+    fn test_int15h_cassette_without_tape() {
+        let bus = PcBus::new();
+        let mut cpu = PcCpu::new(bus);
+        cpu.cpu.cs = 0x0000;
+        cpu.cpu.ip = 0x1000;
+
+        let addr = ((cpu.cpu.cs as u32) << 4) + cpu.cpu.ip;
+        cpu.cpu.memory.write(addr, 0xCD); // INT
+        cpu.cpu.memory.write(addr + 1, 0x15); // 15h
+        cpu.cpu.ax = 0x0000; // AH=00h - Motor on
+
+        cpu.step();
+
+        // No cassette: CF set, AH=86h
+        assert!(cpu.get_carry_flag());
+        assert_eq!((cpu.cpu.ax >> 8) & 0xFF, 0x86);
+    }
+
     #[test]
     fn test_int15h_wait_on_external_event() {
         // Test INT 15h AH=41h (Wait on External Event) - should return not supported
@@ -7072,6 +10201,42 @@ mod tests {
         assert_eq!(cpu.cpu.ax & 0xFF, 0x1C); // AL should be unchanged
     }
 
+    #[test]
+    fn test_int15h_joystick() {
+        use crate::game_port::JoystickState;
+
+        let bus = PcBus::new();
+        let mut cpu = PcCpu::new(bus);
+        cpu.cpu.memory.game_port.set_joysticks(Some(JoystickState {
+            axes: [i16::MIN, i16::MAX, 0, 0],
+            buttons: 0b0001,
+        }));
+        let addr = 0x1000;
+        for offset in [0, 2] {
+            cpu.cpu.memory.write(addr + offset, 0xCD); // INT 15h
+            cpu.cpu.memory.write(addr + offset + 1, 0x15);
+        }
+        cpu.cpu.cs = 0x0000;
+        cpu.cpu.ip = 0x1000;
+
+        // DX=0000h: read switches (button A1 pressed)
+        cpu.cpu.ax = 0x8400;
+        cpu.cpu.dx = 0x0000;
+        cpu.step();
+        assert!(!cpu.get_carry_flag());
+        assert_eq!(cpu.cpu.ax & 0xFF, 0xE0);
+
+        // DX=0001h: read positions
+        cpu.cpu.ax = 0x8400;
+        cpu.cpu.dx = 0x0001;
+        cpu.step();
+        assert!(!cpu.get_carry_flag());
+        assert_eq!(
+            (cpu.cpu.ax, cpu.cpu.bx, cpu.cpu.cx, cpu.cpu.dx),
+            (0, 255, 128, 128)
+        );
+    }
+
     #[test]
     fn test_int15h_wait() {
         // Test INT 15h AH=86h (Wait) - should return success
@@ -7120,8 +10285,8 @@ mod tests {
         // Execute INT 2Fh
         cpu.step();
 
-        // AL should be 0xFF (not installed)
-        assert_eq!(cpu.cpu.ax & 0xFF, 0xFF);
+        // AL should stay 0x00 (not installed; 0xFF would mean installed)
+        assert_eq!(cpu.cpu.ax & 0xFF, 0x00);
     }
 
     #[test]
@@ -7966,4 +11131,322 @@ mod tests {
         assert_eq!(get_interrupt_priority(0x78), InterruptPriority::Bios);
         assert_eq!(get_interrupt_priority(0xFF), InterruptPriority::Bios);
     }
+
+    /// Set up a CPU running a `JMP $` loop at 0000:1000 with interrupts enabled
+    /// and INT 08h pointing at `segment:offset`
+    fn setup_irq_test(segment: u16, offset: u16) -> PcCpu {
+        let mut cpu = PcCpu::new(PcBus::new());
+        cpu.cpu.cs = 0x0000;
+        cpu.cpu.ip = 0x1000;
+        cpu.cpu.ss = 0x0000;
+        cpu.cpu.sp = 0x8000;
+        cpu.cpu.flags |= 0x0200; // IF
+        cpu.cpu.memory.write(0x1000, 0xEB); // JMP $
+        cpu.cpu.memory.write(0x1001, 0xFE);
+
+        cpu.cpu.memory.write(0x20, offset as u8);
+        cpu.cpu.memory.write(0x21, (offset >> 8) as u8);
+        cpu.cpu.memory.write(0x22, segment as u8);
+        cpu.cpu.memory.write(0x23, (segment >> 8) as u8);
+        cpu
+    }
+
+    #[test]
+    fn test_irq0_delivered_to_hooked_handler() {
+        let mut cpu = setup_irq_test(0x0000, 0x2000);
+        let handler = [
+            0xFE, 0x06, 0x00, 0x30, // INC BYTE [0x3000]
+            0xB0, 0x20, // MOV AL, 0x20
+            0xE6, 0x20, // OUT 0x20, AL (EOI)
+            0xCF, // IRET
+        ];
+        for (i, &b) in handler.iter().enumerate() {
+            cpu.cpu.memory.write(0x2000 + i as u32, b);
+        }
+
+        cpu.bus_mut().pic.raise_irq(0);
+        assert!(cpu.service_pending_irq());
+        assert_eq!(cpu.cpu.ip, 0x2000);
+        for _ in 0..4 {
+            cpu.step();
+        }
+
+        assert_eq!(cpu.cpu.memory.read(0x3000), 1);
+        assert_eq!(cpu.cpu.ip, 0x1000);
+        assert!(cpu.interrupts_enabled());
+        assert_eq!(cpu.bus().pic.in_service_irq(), None);
+    }
+
+    #[test]
+    fn test_irq0_bios_handler_ticks_and_sends_eoi() {
+        let mut cpu = setup_irq_test(0xF000, IRQ_HANDLER_OFFSET);
+
+        cpu.bus_mut().pic.raise_irq(0);
+        assert!(cpu.service_pending_irq());
+        assert_eq!(cpu.bus().pic.in_service_irq(), Some(0));
+        cpu.step();
+
+        assert_eq!(cpu.cpu.memory.read(0x046C), 1);
+        assert_eq!((cpu.cpu.cs, cpu.cpu.ip), (0x0000, 0x1000));
+        assert_eq!(cpu.bus().pic.in_service_irq(), None);
+    }
+
+    /// Set up a CPU with the BIOS ROM loaded, INT 67h pointing at the EMS
+    /// entry point, and `program` at 0000:1000
+    fn setup_ems_test(program: &[u8]) -> PcCpu {
+        setup_bios_test(PcBus::new(), program)
+    }
+
+    /// Like `setup_ems_test`, on a bus with a chosen memory size
+    fn setup_bios_test(mut bus: PcBus, program: &[u8]) -> PcCpu {
+        bus.load_bios(&crate::bios::generate_minimal_bios(CpuModel::Intel8086));
+        let mut cpu = PcCpu::new(bus);
+        cpu.cpu.cs = 0x0000;
+        cpu.cpu.ip = 0x1000;
+        cpu.cpu.ss = 0x0000;
+        cpu.cpu.sp = 0x8000;
+        for (i, &b) in program.iter().enumerate() {
+            cpu.cpu.memory.write(0x1000 + i as u32, b);
+        }
+        cpu.write_word(0x67 * 4, EMS_ENTRY_OFFSET);
+        cpu.write_word(0x67 * 4 + 2, EMS_DEVICE_SEGMENT);
+        cpu
+    }
+
+    #[test]
+    fn test_ems_allocate_map_and_access_page_frame() {
+        let mut cpu = setup_ems_test(&[
+            0xB4, 0x43, // MOV AH, 43h (allocate pages)
+            0xBB, 0x02, 0x00, // MOV BX, 2
+            0xCD, 0x67, // INT 67h
+            0xB8, 0x00, 0x44, // MOV AX, 4400h (map to physical page 0)
+            0xBB, 0x01, 0x00, // MOV BX, 1 (logical page 1)
+            0xCD, 0x67, // INT 67h
+            0xB8, 0x00, 0xE0, // MOV AX, E000h
+            0x8E, 0xC0, // MOV ES, AX
+            0x26, 0xC6, 0x06, 0x05, 0x00, 0x77, // MOV BYTE ES:[0005], 77h
+            0xEB, 0xFE, // JMP $
+        ]);
+
+        // Programs detect EMS through the device name in the INT 67h segment
+        let header = (EMS_DEVICE_SEGMENT as u32) << 4;
+        let name: Vec<u8> = (0..8)
+            .map(|i| cpu.cpu.memory.read(header + 0x0A + i))
+            .collect();
+        assert_eq!(name, b"EMMXXXX0");
+
+        for _ in 0..11 {
+            cpu.step();
+        }
+        assert_eq!((cpu.cpu.cs, cpu.cpu.ip), (0x0000, 0x101A));
+        assert_eq!(cpu.cpu.sp, 0x8000);
+        let handle = cpu.cpu.dx as u16;
+        assert_eq!(cpu.bus().ems.handle_pages(handle), (2, EmsError::Success));
+        assert_eq!(cpu.cpu.memory.read(0xE0005), 0x77);
+
+        // The byte lives in logical page 1, not in the frame itself
+        cpu.bus_mut().ems.map_page(0, handle, 0);
+        assert_eq!(cpu.cpu.memory.read(0xE0005), 0x00);
+        cpu.bus_mut().ems.map_page(3, handle, 1);
+        assert_eq!(cpu.cpu.memory.read(0xEC005), 0x77);
+    }
+
+    #[test]
+    fn test_ems_reports_errors_in_ah() {
+        let mut cpu = setup_ems_test(&[
+            0xB4, 0x45, // MOV AH, 45h (deallocate)
+            0xBA, 0x63, 0x00, // MOV DX, 99
+            0xCD, 0x67, // INT 67h
+            0x88, 0xE3, // MOV BL, AH
+            0xB4, 0x99, // MOV AH, 99h (undefined)
+            0xCD, 0x67, // INT 67h
+            0xEB, 0xFE, // JMP $
+        ]);
+        for _ in 0..8 {
+            cpu.step();
+        }
+
+        assert_eq!(cpu.cpu.bx & 0xFF, 0x83); // Invalid handle
+        assert_eq!(cpu.cpu.ax >> 8, 0x84); // Undefined function
+    }
+
+    /// Far call the XMS driver entry point with the given registers and run
+    /// until it returns to 0000:1000
+    fn call_xms(cpu: &mut PcCpu, ax: u16, dx: u16, si: u16) {
+        cpu.cpu.ax = ax as u32;
+        cpu.cpu.dx = dx as u32;
+        cpu.cpu.si = si as u32;
+        cpu.cpu.sp -= 4;
+        let sp = cpu.cpu.sp;
+        cpu.write_word(sp, 0x1000); // Return IP
+        cpu.write_word(sp + 2, 0x0000); // Return CS
+        cpu.cpu.cs = 0xF000;
+        cpu.cpu.ip = XMS_ENTRY_OFFSET as u32;
+        cpu.step(); // JMP SHORT over the hook patch space
+        cpu.step(); // Serviced at the RETF
+        assert_eq!((cpu.cpu.cs, cpu.cpu.ip), (0x0000, 0x1000));
+        assert_eq!(cpu.cpu.sp, 0x8000);
+    }
+
+    #[test]
+    fn test_xms_entry_point_from_multiplex() {
+        let bus = PcBus::with_memory_kb(640 + 1024);
+        let mut cpu = setup_bios_test(
+            bus,
+            &[
+                0xB8, 0x00, 0x43, // MOV AX, 4300h
+                0xCD, 0x2F, // INT 2Fh
+                0x88, 0xC1, // MOV CL, AL
+                0xB8, 0x10, 0x43, // MOV AX, 4310h
+                0xCD, 0x2F, // INT 2Fh
+                0xEB, 0xFE, // JMP $
+            ],
+        );
+        for _ in 0..5 {
+            cpu.step();
+        }
+        assert_eq!(cpu.cpu.cx & 0xFF, 0x80); // Installed
+        assert_eq!((cpu.cpu.es, cpu.cpu.bx as u16), (0xF000, XMS_ENTRY_OFFSET));
+
+        // Get version
+        cpu.cpu.ip = 0x1000;
+        call_xms(&mut cpu, 0x0000, 0, 0);
+        assert_eq!(cpu.cpu.ax, 0x0200);
+        assert_eq!(cpu.cpu.dx, 1); // HMA exists
+
+        // Allocate 1KB and copy 4 bytes from 0000:0700 into it and back out
+        call_xms(&mut cpu, 0x0900, 1, 0);
+        assert_eq!(cpu.cpu.ax, 1);
+        let handle = cpu.cpu.dx as u16;
+        for (i, &b) in [1u8, 2, 3, 4].iter().enumerate() {
+            cpu.cpu.memory.write(0x0700 + i as u32, b);
+        }
+        let write_move = |cpu: &mut PcCpu, src: (u16, u32), dest: (u16, u32)| {
+            cpu.write_word(0x0600, 4);
+            cpu.write_word(0x0602, 0);
+            cpu.write_word(0x0604, src.0);
+            cpu.write_word(0x0606, src.1 as u16);
+            cpu.write_word(0x0608, (src.1 >> 16) as u16);
+            cpu.write_word(0x060A, dest.0);
+            cpu.write_word(0x060C, dest.1 as u16);
+            cpu.write_word(0x060E, (dest.1 >> 16) as u16);
+        };
+        cpu.cpu.ds = 0x0000;
+        write_move(&mut cpu, (0, 0x0000_0700), (handle, 2));
+        call_xms(&mut cpu, 0x0B00, 0, 0x0600);
+        assert_eq!(cpu.cpu.ax, 1);
+        write_move(&mut cpu, (handle, 2), (0, 0x0000_0800));
+        call_xms(&mut cpu, 0x0B00, 0, 0x0600);
+        assert_eq!(cpu.cpu.ax, 1);
+        let copied: Vec<u8> = (0..4).map(|i| cpu.cpu.memory.read(0x0800 + i)).collect();
+        assert_eq!(copied, [1, 2, 3, 4]);
+
+        // The data sits 2 bytes into the block's locked address
+        call_xms(&mut cpu, 0x0C00, handle, 0);
+        assert_eq!(cpu.cpu.ax, 1);
+        let address = (cpu.cpu.dx << 16) | cpu.cpu.bx;
+        assert!(address >= 0x100000);
+        cpu.bus_mut().xms.set_a20_enabled(true);
+        assert_eq!(cpu.cpu.memory.read(address + 2), 1);
+
+        // Errors return AX = 0 with the code in BL
+        call_xms(&mut cpu, 0x0A00, handle, 0);
+        assert_eq!(cpu.cpu.ax, 0);
+        assert_eq!(cpu.cpu.bx & 0xFF, XmsError::BlockLocked as u32);
+    }
+
+    #[test]
+    fn test_multiplex_install_checks_survive_hooked_int2fh() {
+        let mut cpu = setup_ems_test(&[
+            0xB8, 0x00, 0x43, // MOV AX, 4300h
+            0xCD, 0x2F, // INT 2Fh
+            0x88, 0xC3, // MOV BL, AL
+            0xB8, 0x00, 0x11, // MOV AX, 1100h
+            0xCD, 0x2F, // INT 2Fh
+        ]);
+        // A DOS handler that does not chain: returns AL = 01h for anything
+        cpu.cpu.memory.write(0x20100, 0xB0); // MOV AL, 01h
+        cpu.cpu.memory.write(0x20101, 0x01);
+        cpu.cpu.memory.write(0x20102, 0xCF); // IRET
+        cpu.write_word(0x2F * 4, 0x0100);
+        cpu.write_word(0x2F * 4 + 2, 0x2000);
+
+        for _ in 0..3 {
+            cpu.step();
+        }
+        assert_eq!(cpu.cpu.bx & 0xFF, 0x80); // XMS still answered by the BIOS
+        for _ in 0..2 {
+            cpu.step();
+        }
+        assert_eq!((cpu.cpu.cs, cpu.cpu.ip), (0x2000, 0x0100)); // Others go to DOS
+    }
+
+    #[test]
+    fn test_multiplex_entry_answers_chained_calls() {
+        let mut cpu = setup_ems_test(&[0xEB, 0xFE]); // JMP $
+                                                     // A TSR chaining with PUSHF / CALL FAR leaves an IRET frame
+        cpu.cpu.sp = 0x7FFA;
+        cpu.write_word(0x7FFA, 0x1000); // IP
+        cpu.write_word(0x7FFC, 0x0000); // CS
+        cpu.write_word(0x7FFE, 0x0202); // FLAGS
+        cpu.cpu.cs = 0xF000;
+        cpu.cpu.ip = MULTIPLEX_ENTRY_OFFSET as u32;
+        cpu.cpu.ax = 0x4300;
+
+        cpu.step();
+        assert_eq!(cpu.cpu.ax & 0xFF, 0x80);
+        assert_eq!((cpu.cpu.cs, cpu.cpu.ip), (0x0000, 0x1000));
+        assert_eq!(cpu.cpu.sp, 0x8000);
+    }
+
+    #[test]
+    fn test_mscdex_reports_mounted_cdrom() {
+        let mut cpu = setup_ems_test(&[
+            0xB8, 0x00, 0x15, // MOV AX, 1500h
+            0xCD, 0x2F, // INT 2Fh
+            0xB8, 0x08, 0x15, // MOV AX, 1508h
+            0xCD, 0x2F, // INT 2Fh
+            0xEB, 0xFE, // JMP $
+        ]);
+        cpu.step();
+        cpu.step();
+        assert_eq!(cpu.cpu.bx, 0); // No CD-ROM: MSCDEX not installed
+
+        let mut iso = vec![0u8; 4 * 2048];
+        iso[2 * 2048..3 * 2048].fill(0xCD);
+        cpu.bus_mut().mount_cdrom(iso);
+        cpu.cpu.ip = 0x1000;
+        cpu.step();
+        cpu.step();
+        assert_eq!(cpu.cpu.bx, 1);
+        assert_eq!(cpu.cpu.cx, 2); // C: without a hard drive
+
+        // Read sector 2 into 0000:3000
+        cpu.cpu.es = 0x0000;
+        cpu.cpu.bx = 0x3000;
+        cpu.cpu.si = 0;
+        cpu.cpu.di = 2;
+        cpu.cpu.dx = 1;
+        cpu.step();
+        cpu.step();
+        assert!(!cpu.get_carry_flag());
+        assert_eq!(cpu.cpu.memory.read(0x3000), 0xCD);
+        assert_eq!(cpu.cpu.memory.read(0x3000 + 2047), 0xCD);
+        assert_eq!(cpu.cpu.memory.read(0x3000 + 2048), 0x00);
+    }
+
+    #[test]
+    fn test_irq_waits_while_interrupts_disabled() {
+        let mut cpu = setup_irq_test(0x0000, 0x2000);
+        cpu.cpu.flags &= !0x0200;
+
+        cpu.bus_mut().pic.raise_irq(0);
+        assert!(!cpu.service_pending_irq());
+        assert_eq!(cpu.cpu.ip, 0x1000);
+
+        // Still pending once interrupts are enabled again
+        cpu.cpu.flags |= 0x0200;
+        assert!(cpu.service_pending_irq());
+        assert_eq!(cpu.cpu.ip, 0x2000);
+    }
 }
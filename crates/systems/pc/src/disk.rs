@@ -17,6 +17,47 @@ pub struct DiskRequest {
     pub count: u8,
 }
 
+impl DiskRequest {
+    /// Sectors per track and heads of this drive's CHS addressing
+    ///
+    /// Floppies take the geometry of the mounted image; the hard drive is
+    /// addressed as the 10MB format.
+    fn chs_geometry(&self, disk_image: Option<&[u8]>) -> (u8, u8) {
+        if self.drive < 0x80 {
+            let (_, sectors_per_track, heads) =
+                disk_image.map_or(FloppyFormat::Floppy1_44M.geometry(), floppy_geometry);
+            (sectors_per_track, heads)
+        } else {
+            (17, 4)
+        }
+    }
+
+    /// Linear sector number addressed by this request on `disk_image`, or
+    /// None for sector 0
+    ///
+    /// SYSLINUX and some bootloaders use a hybrid addressing scheme:
+    /// when C=0, H=0 and SPT < S < 64, S is treated as a direct LBA + 1.
+    /// Otherwise the standard formula LBA = (C × HPC + H) × SPT + (S - 1) applies.
+    pub fn lba(&self, disk_image: Option<&[u8]>) -> Option<u32> {
+        if self.sector == 0 {
+            return None;
+        }
+        let (sectors_per_track, heads) = self.chs_geometry(disk_image);
+        if self.cylinder == 0
+            && self.head == 0
+            && self.sector > sectors_per_track
+            && self.sector < 64
+        {
+            Some(self.sector as u32 - 1)
+        } else {
+            Some(
+                (self.cylinder as u32 * heads as u32 + self.head as u32) * sectors_per_track as u32
+                    + (self.sector as u32 - 1),
+            )
+        }
+    }
+}
+
 /// Disk controller state
 pub struct DiskController {
     /// Last operation status
@@ -59,40 +100,12 @@ impl DiskController {
             }
         };
 
-        // Calculate disk parameters based on drive type
-        let (sectors_per_track, heads) = if request.drive < 0x80 {
-            // Floppy: assume 1.44MB format
-            (18, 2)
-        } else {
-            // Hard drive: assume 10MB format
-            (17, 4)
-        };
-
-        // Calculate LBA (Logical Block Address)
-        // SYSLINUX and some bootloaders use a hybrid addressing scheme:
-        // When C=0, H=0, and S > SPT (but S < 64), treat S as a direct LBA (linear sector number)
-        // This is only valid for the boot sector stage, not for normal operation
-        // Otherwise use standard CHS formula: LBA = (C × HPC + H) × SPT + (S - 1)
-        let lba = if request.cylinder == 0
-            && request.head == 0
-            && request.sector > sectors_per_track
-            && request.sector < 64
-        {
-            // Linear sector addressing (used by SYSLINUX boot sector)
-            if std::env::var("EMU_LOG_BUS").is_ok() {
-                eprintln!(
-                    "Disk read: Using linear addressing for S={} > SPT={}",
-                    request.sector, sectors_per_track
-                );
-            }
-            request.sector as u32 - 1
-        } else {
-            // Standard CHS addressing
-            // Note: We don't validate CHS parameters here - we let the bounds check below
-            // handle out-of-range requests. This is more permissive and matches real BIOS behavior.
-            ((request.cylinder as u32 * heads as u32 + request.head as u32)
-                * sectors_per_track as u32)
-                + (request.sector as u32 - 1)
+        // CHS sectors are numbered from 1
+        // Note: We don't validate CHS parameters here - we let the bounds check below
+        // handle out-of-range requests. This is more permissive and matches real BIOS behavior.
+        let Some(lba) = request.lba(Some(disk_image)) else {
+            self.status = 0x04; // Sector not found
+            return self.status;
         };
 
         // Each sector is 512 bytes
@@ -101,6 +114,7 @@ impl DiskController {
 
         // Log LBA calculation for debugging
         if std::env::var("EMU_LOG_BUS").is_ok() {
+            let (sectors_per_track, heads) = request.chs_geometry(Some(disk_image));
             eprintln!(
                 "Disk read: C={} H={} S={} -> LBA={} offset=0x{:X} (SPT={}, heads={})",
                 request.cylinder,
@@ -158,38 +172,10 @@ impl DiskController {
             }
         };
 
-        // Calculate disk parameters based on drive type
-        let (sectors_per_track, heads) = if request.drive < 0x80 {
-            // Floppy: assume 1.44MB format
-            (18, 2)
-        } else {
-            // Hard drive: assume 10MB format
-            (17, 4)
-        };
-
-        // Calculate LBA (Logical Block Address)
-        // SYSLINUX and some bootloaders use a hybrid addressing scheme:
-        // When C=0, H=0, and S > SPT (but S < 64), treat S as a direct LBA (linear sector number)
-        // This is only valid for the boot sector stage, not for normal operation
-        // Otherwise use standard CHS formula: LBA = (C × HPC + H) × SPT + (S - 1)
-        let lba = if request.cylinder == 0
-            && request.head == 0
-            && request.sector > sectors_per_track
-            && request.sector < 64
-        {
-            // Linear sector addressing (used by SYSLINUX boot sector)
-            if std::env::var("EMU_LOG_BUS").is_ok() {
-                eprintln!(
-                    "Disk write: Using linear addressing for S={} > SPT={}",
-                    request.sector, sectors_per_track
-                );
-            }
-            request.sector as u32 - 1
-        } else {
-            // Standard CHS addressing
-            ((request.cylinder as u32 * heads as u32 + request.head as u32)
-                * sectors_per_track as u32)
-                + (request.sector as u32 - 1)
+        // CHS sectors are numbered from 1
+        let Some(lba) = request.lba(Some(disk_image.as_slice())) else {
+            self.status = 0x04; // Sector not found
+            return self.status;
         };
 
         let sector_size = 512;
@@ -216,6 +202,7 @@ impl DiskController {
         &mut self,
         lba: u32,
         count: u8,
+        sector_size: usize,
         buffer: &mut [u8],
         disk_image: Option<&[u8]>,
     ) -> u8 {
@@ -228,18 +215,17 @@ impl DiskController {
             }
         };
 
-        // Each sector is 512 bytes
-        let sector_size = 512;
-        let offset = (lba * sector_size) as usize;
+        // 512-byte sectors, or 2048 bytes on a CD-ROM
+        let offset = lba as usize * sector_size;
 
         // Check if read is within bounds
-        if offset + (count as usize * sector_size as usize) > disk_image.len() {
+        if offset + (count as usize * sector_size) > disk_image.len() {
             self.status = 0x04; // Sector not found
             return self.status;
         }
 
         // Copy data from disk image to buffer
-        let bytes_to_copy = (count as usize * sector_size as usize).min(buffer.len());
+        let bytes_to_copy = (count as usize * sector_size).min(buffer.len());
         buffer[..bytes_to_copy].copy_from_slice(&disk_image[offset..offset + bytes_to_copy]);
 
         self.status = 0x00; // Success
@@ -285,12 +271,13 @@ impl DiskController {
 
     /// Get drive parameters
     ///
+    /// Floppy parameters come from the mounted `disk_image`, or the 1.44MB
+    /// format when the drive is empty.
+    ///
     /// Returns: (cylinders, sectors_per_track, heads) or None if invalid drive
-    #[allow(dead_code)]
-    pub fn get_drive_params(drive: u8) -> Option<(u16, u8, u8)> {
+    pub fn get_drive_params(drive: u8, disk_image: Option<&[u8]>) -> Option<(u16, u8, u8)> {
         if drive < 0x80 {
-            // Floppy drive - 1.44MB format
-            Some((80, 18, 2))
+            Some(disk_image.map_or(FloppyFormat::Floppy1_44M.geometry(), floppy_geometry))
         } else if drive == 0x80 {
             // Hard drive C: - 10MB
             Some((306, 17, 4))
@@ -330,6 +317,18 @@ impl FloppyFormat {
         }
     }
 
+    /// Detect the format of a floppy image from its size
+    pub fn from_size(size: usize) -> Option<Self> {
+        [
+            FloppyFormat::Floppy360K,
+            FloppyFormat::Floppy720K,
+            FloppyFormat::Floppy1_2M,
+            FloppyFormat::Floppy1_44M,
+        ]
+        .into_iter()
+        .find(|format| format.size_bytes() == size)
+    }
+
     /// Get the geometry (cylinders, sectors_per_track, heads) for this format
     pub fn geometry(&self) -> (u16, u8, u8) {
         match self {
@@ -341,6 +340,73 @@ impl FloppyFormat {
     }
 }
 
+/// Geometries of floppy formats recognised by image size alone, beyond the
+/// standard [`FloppyFormat`]s: (size in bytes, cylinders, sectors per track, heads)
+const FLOPPY_SIZE_GEOMETRIES: [(usize, u16, u8, u8); 5] = [
+    (163_840, 40, 8, 1),    // 160KB single-sided
+    (184_320, 40, 9, 1),    // 180KB single-sided
+    (327_680, 40, 8, 2),    // 320KB
+    (1_720_320, 80, 21, 2), // DMF 1.68MB
+    (2_949_120, 80, 36, 2), // 2.88MB ED
+];
+
+/// Get the geometry (cylinders, sectors_per_track, heads) of a floppy image
+///
+/// The BIOS Parameter Block in the image's boot sector is used when it
+/// describes a floppy that fits the image, so formats that share a size with
+/// another one or have none of the standard sizes (DMF, 2.88MB) are addressed
+/// correctly. Otherwise the geometry is inferred from the image size, falling
+/// back to the 1.44MB format.
+pub fn floppy_geometry(disk_image: &[u8]) -> (u16, u8, u8) {
+    if let Some(geometry) = bpb_geometry(disk_image) {
+        return geometry;
+    }
+    if let Some(format) = FloppyFormat::from_size(disk_image.len()) {
+        return format.geometry();
+    }
+    FLOPPY_SIZE_GEOMETRIES
+        .iter()
+        .find(|(size, ..)| *size == disk_image.len())
+        .map_or(FloppyFormat::Floppy1_44M.geometry(), |&(_, c, s, h)| {
+            (c, s, h)
+        })
+}
+
+/// Floppy geometry described by the boot sector's BIOS Parameter Block
+///
+/// Returns None when the boot sector has no plausible floppy BPB (DOS 1.x
+/// disks, unformatted images) or it describes more sectors than the image holds.
+fn bpb_geometry(disk_image: &[u8]) -> Option<(u16, u8, u8)> {
+    let boot = disk_image.get(..512)?;
+    let word = |offset: usize| u16::from_le_bytes([boot[offset], boot[offset + 1]]);
+
+    // A BPB follows the boot sector's initial jump
+    if boot[0] != 0xEB && boot[0] != 0xE9 {
+        return None;
+    }
+    let bytes_per_sector = word(11);
+    let media_descriptor = boot[21];
+    let sectors_per_track = word(24);
+    let heads = word(26);
+    if bytes_per_sector != 512
+        || media_descriptor < 0xF0
+        || !(1..=63).contains(&sectors_per_track)
+        || !(1..=2).contains(&heads)
+    {
+        return None;
+    }
+
+    let total_sectors = match word(19) {
+        0 => u32::from_le_bytes([boot[32], boot[33], boot[34], boot[35]]),
+        total => u32::from(total),
+    };
+    let cylinders = total_sectors / (u32::from(sectors_per_track) * u32::from(heads));
+    if !(1..=84).contains(&cylinders) || total_sectors as usize * 512 > disk_image.len() {
+        return None;
+    }
+    Some((cylinders as u16, sectors_per_track as u8, heads as u8))
+}
+
 /// Standard hard drive formats
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum HardDriveFormat {
@@ -508,7 +574,7 @@ mod tests {
 
     #[test]
     fn test_get_floppy_params() {
-        let params = DiskController::get_drive_params(0x00);
+        let params = DiskController::get_drive_params(0x00, None);
         assert!(params.is_some());
 
         let (cylinders, sectors, heads) = params.unwrap();
@@ -519,7 +585,7 @@ mod tests {
 
     #[test]
     fn test_get_hard_drive_params() {
-        let params = DiskController::get_drive_params(0x80);
+        let params = DiskController::get_drive_params(0x80, None);
         assert!(params.is_some());
 
         let (cylinders, sectors, heads) = params.unwrap();
@@ -578,6 +644,65 @@ mod tests {
         assert_eq!(h, 2);
     }
 
+    /// Floppy image whose boot sector has a BPB for the given geometry
+    fn floppy_with_bpb(cylinders: u16, sectors_per_track: u8, heads: u8) -> Vec<u8> {
+        let total = cylinders as usize * sectors_per_track as usize * heads as usize;
+        let mut image = vec![0; total * 512];
+        image[0] = 0xEB;
+        image[11..13].copy_from_slice(&512u16.to_le_bytes());
+        image[19..21].copy_from_slice(&(total as u16).to_le_bytes());
+        image[21] = 0xF0;
+        image[24..26].copy_from_slice(&u16::from(sectors_per_track).to_le_bytes());
+        image[26..28].copy_from_slice(&u16::from(heads).to_le_bytes());
+        image
+    }
+
+    #[test]
+    fn test_floppy_geometry_from_bpb() {
+        let dmf = floppy_with_bpb(80, 21, 2);
+        assert_eq!(floppy_geometry(&dmf), (80, 21, 2));
+        assert_eq!(
+            DiskController::get_drive_params(0x00, Some(&dmf)),
+            Some((80, 21, 2))
+        );
+
+        // A 720KB-sized image formatted with 10 sectors per track on 72 cylinders
+        let odd = floppy_with_bpb(72, 10, 2);
+        assert_eq!(floppy_geometry(&odd), (72, 10, 2));
+
+        // CHS reads on the DMF image use its 21 sectors per track
+        let mut dmf = dmf;
+        dmf[(2 * 21 + 20) * 512] = 0x5A; // C=1 H=0 S=21
+        let mut controller = DiskController::new();
+        let mut buffer = vec![0; 512];
+        let request = DiskRequest {
+            drive: 0x00,
+            cylinder: 1,
+            head: 0,
+            sector: 21,
+            count: 1,
+        };
+        assert_eq!(
+            controller.read_sectors(&request, &mut buffer, Some(&dmf)),
+            0x00
+        );
+        assert_eq!(buffer[0], 0x5A);
+    }
+
+    #[test]
+    fn test_floppy_geometry_falls_back_to_size() {
+        // No BPB: geometry from the image size
+        assert_eq!(floppy_geometry(&vec![0; 737_280]), (80, 9, 2));
+        assert_eq!(floppy_geometry(&vec![0; 2_949_120]), (80, 36, 2));
+        assert_eq!(floppy_geometry(&vec![0; 1_720_320]), (80, 21, 2));
+        assert_eq!(floppy_geometry(&vec![0; 1000]), (80, 18, 2));
+
+        // A BPB describing more sectors than the image holds is ignored
+        let mut image = floppy_with_bpb(80, 18, 2);
+        image.truncate(737_280);
+        assert_eq!(floppy_geometry(&image), (80, 9, 2));
+    }
+
     #[test]
     fn test_hard_drive_format_geometry() {
         let (c, s, h) = HardDriveFormat::HardDrive20M.geometry();
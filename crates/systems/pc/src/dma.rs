@@ -0,0 +1,244 @@
+//! Intel 8237 DMA controller (channels 0-3)
+//!
+//! The first DMA controller of the PC/XT provides four 8-bit channels. Only
+//! the register interface and the address/count bookkeeping are emulated;
+//! devices pull bytes through [`DmaController::transfer`] at their own pace.
+//!
+//! Ports:
+//! - 0x00-0x07: Channel address/count registers (low byte then high byte)
+//! - 0x08: Status (terminal count flags) / command
+//! - 0x0A: Single channel mask
+//! - 0x0B: Mode
+//! - 0x0C: Clear byte pointer flip-flop
+//! - 0x0D: Master clear
+//! - 0x0E: Clear mask register
+//! - 0x0F: Write all mask bits
+//! - 0x87, 0x83, 0x81, 0x82: Page registers for channels 0-3
+//!
+//! Channel 1 is used by the Sound Blaster for 8-bit PCM playback.
+
+use std::cell::Cell;
+
+/// Page register port for each channel
+const PAGE_PORTS: [u16; 4] = [0x87, 0x83, 0x81, 0x82];
+
+/// Mode register bit 4: auto-initialize
+const MODE_AUTO_INIT: u8 = 0x10;
+/// Mode register bit 5: address decrement
+const MODE_DECREMENT: u8 = 0x20;
+
+#[derive(Debug, Clone, Default)]
+struct DmaChannel {
+    base_address: u16,
+    base_count: u16,
+    current_address: u16,
+    current_count: u16,
+    page: u8,
+    mode: u8,
+    masked: bool,
+}
+
+/// 8237 DMA controller
+pub struct DmaController {
+    channels: [DmaChannel; 4],
+    /// Byte pointer flip-flop (false = low byte next)
+    flip_flop: Cell<bool>,
+    /// Terminal count flags (status bits 0-3, cleared when read)
+    terminal_count: Cell<u8>,
+}
+
+impl DmaController {
+    pub fn new() -> Self {
+        let mut dma = Self {
+            channels: Default::default(),
+            flip_flop: Cell::new(false),
+            terminal_count: Cell::new(0),
+        };
+        dma.reset();
+        dma
+    }
+
+    /// Master clear: all channels masked, flip-flop cleared
+    pub fn reset(&mut self) {
+        for ch in &mut self.channels {
+            *ch = DmaChannel {
+                masked: true,
+                ..Default::default()
+            };
+        }
+        self.flip_flop.set(false);
+        self.terminal_count.set(0);
+    }
+
+    /// Check whether a port belongs to the DMA controller
+    pub fn handles_port(port: u16) -> bool {
+        port <= 0x0F || PAGE_PORTS.contains(&port)
+    }
+
+    /// Read a DMA controller port
+    pub fn io_read(&self, port: u16) -> u8 {
+        match port {
+            0x00..=0x07 => {
+                let ch = &self.channels[(port / 2) as usize];
+                let value = if port.is_multiple_of(2) {
+                    ch.current_address
+                } else {
+                    ch.current_count
+                };
+                let high = self.flip_flop.get();
+                self.flip_flop.set(!high);
+                if high {
+                    (value >> 8) as u8
+                } else {
+                    value as u8
+                }
+            }
+            0x08 => {
+                let status = self.terminal_count.get();
+                self.terminal_count.set(0);
+                status
+            }
+            _ => match PAGE_PORTS.iter().position(|&p| p == port) {
+                Some(ch) => self.channels[ch].page,
+                None => 0xFF,
+            },
+        }
+    }
+
+    /// Write a DMA controller port
+    pub fn io_write(&mut self, port: u16, val: u8) {
+        match port {
+            0x00..=0x07 => {
+                let high = self.flip_flop.get();
+                self.flip_flop.set(!high);
+                let ch = &mut self.channels[(port / 2) as usize];
+                let reg = if port.is_multiple_of(2) {
+                    &mut ch.base_address
+                } else {
+                    &mut ch.base_count
+                };
+                *reg = if high {
+                    (*reg & 0x00FF) | ((val as u16) << 8)
+                } else {
+                    (*reg & 0xFF00) | val as u16
+                };
+                ch.current_address = ch.base_address;
+                ch.current_count = ch.base_count;
+            }
+            0x0A => self.channels[(val & 0x03) as usize].masked = val & 0x04 != 0,
+            0x0B => self.channels[(val & 0x03) as usize].mode = val,
+            0x0C => self.flip_flop.set(false),
+            0x0D => self.reset(),
+            0x0E => {
+                for ch in &mut self.channels {
+                    ch.masked = false;
+                }
+            }
+            0x0F => {
+                for (i, ch) in self.channels.iter_mut().enumerate() {
+                    ch.masked = val & (1 << i) != 0;
+                }
+            }
+            _ => {
+                if let Some(ch) = PAGE_PORTS.iter().position(|&p| p == port) {
+                    self.channels[ch].page = val;
+                }
+            }
+        }
+    }
+
+    /// Transfer one byte on a channel, returning the physical address to
+    /// access, or None if the channel is masked
+    ///
+    /// At terminal count the channel either reloads (auto-initialize mode)
+    /// or masks itself.
+    pub fn transfer(&mut self, channel: usize) -> Option<u32> {
+        let ch = self.channels.get_mut(channel)?;
+        if ch.masked {
+            return None;
+        }
+
+        let address = ((ch.page as u32) << 16) | ch.current_address as u32;
+        ch.current_address = if ch.mode & MODE_DECREMENT != 0 {
+            ch.current_address.wrapping_sub(1)
+        } else {
+            ch.current_address.wrapping_add(1)
+        };
+
+        if ch.current_count == 0 {
+            // Terminal count reached (count register holds length - 1)
+            self.terminal_count
+                .set(self.terminal_count.get() | (1 << channel));
+            if ch.mode & MODE_AUTO_INIT != 0 {
+                ch.current_address = ch.base_address;
+                ch.current_count = ch.base_count;
+            } else {
+                ch.current_count = 0xFFFF;
+                ch.masked = true;
+            }
+        } else {
+            ch.current_count -= 1;
+        }
+
+        Some(address)
+    }
+}
+
+impl Default for DmaController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Program a channel the way DOS software sets up Sound Blaster playback
+    fn program(dma: &mut DmaController, address: u32, length: u16, mode: u8) {
+        dma.io_write(0x0A, 0x05); // Mask channel 1
+        dma.io_write(0x0C, 0x00); // Clear flip-flop
+        dma.io_write(0x0B, mode);
+        dma.io_write(0x02, address as u8);
+        dma.io_write(0x02, (address >> 8) as u8);
+        dma.io_write(0x83, (address >> 16) as u8);
+        dma.io_write(0x03, (length - 1) as u8);
+        dma.io_write(0x03, ((length - 1) >> 8) as u8);
+        dma.io_write(0x0A, 0x01); // Unmask channel 1
+    }
+
+    #[test]
+    fn test_single_cycle_transfer() {
+        let mut dma = DmaController::new();
+        assert_eq!(dma.transfer(1), None); // Masked after reset
+
+        program(&mut dma, 0x12340, 3, 0x49); // Single, increment, read, ch 1
+        assert_eq!(dma.transfer(1), Some(0x12340));
+        assert_eq!(dma.transfer(1), Some(0x12341));
+        assert_eq!(dma.transfer(1), Some(0x12342));
+        // Terminal count masks the channel
+        assert_eq!(dma.transfer(1), None);
+        assert_eq!(dma.io_read(0x08) & 0x02, 0x02);
+        assert_eq!(dma.io_read(0x08) & 0x02, 0x00);
+    }
+
+    #[test]
+    fn test_auto_init_transfer() {
+        let mut dma = DmaController::new();
+        program(&mut dma, 0x20000, 2, 0x59); // Auto-init, increment, read, ch 1
+        assert_eq!(dma.transfer(1), Some(0x20000));
+        assert_eq!(dma.transfer(1), Some(0x20001));
+        assert_eq!(dma.transfer(1), Some(0x20000));
+    }
+
+    #[test]
+    fn test_read_current_count() {
+        let mut dma = DmaController::new();
+        program(&mut dma, 0x0000, 0x100, 0x49);
+        dma.transfer(1);
+        dma.io_write(0x0C, 0x00);
+        let low = dma.io_read(0x03);
+        let high = dma.io_read(0x03);
+        assert_eq!(u16::from_le_bytes([low, high]), 0xFE);
+    }
+}
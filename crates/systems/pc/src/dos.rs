@@ -0,0 +1,789 @@
+//! Built-in DOS
+//!
+//! A small DOS-compatible environment for running single programs without a
+//! DOS boot disk. When no disk boots, the BIOS calls INT 18h, which starts a
+//! COMMAND-like prompt (DIR, TYPE, COPY, DEL, CD, ...) on the mounted drives
+//! and runs .COM and .EXE files from them. INT 21h file services work on the
+//! FAT images directly (see `dos_fs`).
+//!
+//! This module holds the DOS state: current drive and directories, open
+//! files, memory blocks and the running processes. The INT 21h services and
+//! the shell itself are in `cpu.rs`.
+//!
+//! Limitations:
+//! - No memory control blocks in memory; programs that walk the MCB chain
+//!   won't find any
+//! - No batch files, redirection or pipes
+//! - Directories cannot be created or removed
+
+use crate::dos_fs::{DirEntry, ATTR_DIRECTORY, ATTR_HIDDEN, ATTR_SYSTEM, ATTR_VOLUME_ID};
+use crate::host_folder::display_name;
+use crate::rtc::DateTime;
+
+/// Segment of the shell's command line buffer
+pub const SHELL_SEGMENT: u16 = 0x0060;
+
+/// Shell stack (SS:SP) used while the prompt is running
+pub const SHELL_STACK: (u16, u16) = (0x0080, 0x0800);
+
+/// First segment available to programs
+pub const FIRST_PROGRAM_SEGMENT: u16 = 0x0100;
+
+/// Longest command line accepted by the prompt
+pub const MAX_COMMAND_LINE: u8 = 127;
+
+/// Environment variables every program starts with
+pub const ENVIRONMENT: &[&str] = &["COMSPEC=A:\\COMMAND.COM", "PATH=", "PROMPT=$P$G"];
+
+/// HELP text of the prompt
+pub const SHELL_HELP: &str = "\
+DIR [path]          List files
+TYPE file           Show a text file
+COPY from to        Copy files (wildcards allowed)
+DEL file            Delete files (wildcards allowed)
+CD [dir]            Show or change the current directory
+A: B: C:            Change the current drive
+CLS                 Clear the screen
+VER                 Show the DOS version
+ECHO text           Print text
+Anything else runs a .COM or .EXE file from the current directory.
+";
+
+/// First handle number used for files (0-4 are the standard devices)
+pub const FIRST_FILE_HANDLE: u16 = 5;
+
+/// Most files that can be open at the same time
+const MAX_OPEN_FILES: usize = 30;
+
+/// DOS drives backed by disk images: A:, B: and C:
+pub const DRIVE_COUNT: u8 = 3;
+
+/// BIOS drive number behind a DOS drive number (0 = A:)
+pub fn bios_drive(drive: u8) -> Option<u8> {
+    match drive {
+        0 => Some(0x00),
+        1 => Some(0x01),
+        2 => Some(0x80),
+        _ => None,
+    }
+}
+
+/// Convert one path component ("GAME.EXE", "*.TXT") to directory entry form
+///
+/// Names are upper-cased and truncated to 8.3 like DOS does; `*` fills the
+/// rest of the name or extension with `?`. Returns None for an empty or
+/// invalid component.
+pub fn short_name(component: &str) -> Option<[u8; 11]> {
+    if component.is_empty() || component == "." || component == ".." {
+        return None;
+    }
+    let (base, ext) = match component.rfind('.') {
+        Some(i) => (&component[..i], &component[i + 1..]),
+        None => (component, ""),
+    };
+    if base.is_empty() || ext.contains('.') {
+        return None;
+    }
+
+    let mut name = [b' '; 11];
+    for (field, text) in [(0..8, base), (8..11, ext)] {
+        for (pos, c) in field.clone().zip(text.bytes()) {
+            match c {
+                b'*' => {
+                    name[pos..field.end].fill(b'?');
+                    break;
+                }
+                b' ' | b'"' | b'/' | b'\\' | b'[' | b']' | b':' | b'|' | b'<' | b'>' | b'+'
+                | b'=' | b';' | b',' => return None,
+                0x00..=0x1F => return None,
+                _ => name[pos] = c.to_ascii_uppercase(),
+            }
+        }
+    }
+    Some(name)
+}
+
+/// Check a name against a pattern with `?` wildcards (both in entry form)
+pub fn name_matches(pattern: &[u8; 11], name: &[u8; 11]) -> bool {
+    pattern.iter().zip(name).all(|(&p, &n)| p == b'?' || p == n)
+}
+
+/// Check whether a name in entry form contains wildcards
+pub fn has_wildcards(name: &[u8; 11]) -> bool {
+    name.contains(&b'?')
+}
+
+/// A path split into drive, directories (from the root) and final name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DosPath {
+    pub drive: u8,
+    pub dirs: Vec<[u8; 11]>,
+    /// Last component, None if the path names a directory ("A:", "\GAMES\")
+    pub name: Option<[u8; 11]>,
+}
+
+impl DosPath {
+    /// "A:\GAMES" form of the directory part
+    pub fn dir_string(&self) -> String {
+        dir_string(self.drive, &self.dirs)
+    }
+
+    /// The whole path as a list of directories (for CD and DIR)
+    pub fn into_dir(mut self) -> Option<Self> {
+        if let Some(name) = self.name.take() {
+            if has_wildcards(&name) {
+                return None;
+            }
+            self.dirs.push(name);
+        }
+        Some(self)
+    }
+}
+
+/// "A:\GAMES\DOOM" form of a directory
+pub fn dir_string(drive: u8, dirs: &[[u8; 11]]) -> String {
+    let mut s = format!("{}:\\", (b'A' + drive) as char);
+    let names: Vec<String> = dirs.iter().map(display_name).collect();
+    s.push_str(&names.join("\\"));
+    s
+}
+
+/// Message the prompt shows for a DOS error code
+pub fn error_message(code: u16) -> &'static str {
+    match code {
+        0x02 => "File not found",
+        0x03 => "Path not found",
+        0x04 => "Too many open files",
+        0x05 => "Access denied",
+        0x08 => "Insufficient memory",
+        0x0B => "Invalid program file",
+        0x15 => "Drive not ready",
+        0x27 => "Insufficient disk space",
+        _ => "General failure",
+    }
+}
+
+/// Packed DOS (time, date) of a clock reading, as stored in directory entries
+pub fn dos_date_time(now: &DateTime) -> (u16, u16) {
+    let year = (now.year - 1980).clamp(0, 127) as u16;
+    let date = (year << 9) | ((now.month as u16) << 5) | now.day as u16;
+    let time = ((now.hour as u16) << 11) | ((now.minute as u16) << 5) | (now.second as u16 / 2);
+    (time, date)
+}
+
+/// Whether find first/next with search attributes `search` returns an entry
+///
+/// Hidden, system and directory entries are only found when asked for; a
+/// search for exactly the volume label attribute finds only the label.
+pub fn attributes_match(search: u8, attr: u8) -> bool {
+    if search == ATTR_VOLUME_ID {
+        return attr & ATTR_VOLUME_ID != 0;
+    }
+    attr & ATTR_VOLUME_ID == 0 && attr & (ATTR_HIDDEN | ATTR_SYSTEM | ATTR_DIRECTORY) & !search == 0
+}
+
+/// DIR output for the entries of a directory that match `pattern`
+///
+/// Returns None if nothing matches.
+pub fn dir_listing(
+    label: Option<&str>,
+    drive: u8,
+    dirs: &[[u8; 11]],
+    entries: &[DirEntry],
+    pattern: &[u8; 11],
+    free_bytes: u64,
+) -> Option<String> {
+    let matches: Vec<&DirEntry> = entries
+        .iter()
+        .filter(|entry| attributes_match(ATTR_DIRECTORY, entry.attr))
+        .filter(|entry| name_matches(pattern, &entry.name))
+        .collect();
+    if matches.is_empty() {
+        return None;
+    }
+
+    let drive_letter = (b'A' + drive) as char;
+    let mut out = match label {
+        Some(label) => format!(" Volume in drive {} is {}\n", drive_letter, label),
+        None => format!(" Volume in drive {} has no label\n", drive_letter),
+    };
+    out.push_str(&format!(" Directory of {}\n\n", dir_string(drive, dirs)));
+
+    let mut files = 0;
+    let mut bytes = 0u64;
+    for entry in &matches {
+        let base = String::from_utf8_lossy(&entry.name[..8])
+            .trim_end()
+            .to_string();
+        let ext = String::from_utf8_lossy(&entry.name[8..])
+            .trim_end()
+            .to_string();
+        let size = if entry.is_dir() {
+            "<DIR>    ".to_string()
+        } else {
+            files += 1;
+            bytes += entry.size as u64;
+            format!("{:>9}", entry.size)
+        };
+        let year = (1980 + (entry.date >> 9)) % 100;
+        let hour = entry.time >> 11;
+        out.push_str(&format!(
+            "{:<8} {:<3} {} {:02}-{:02}-{:02} {:>2}:{:02}{}\n",
+            base,
+            ext,
+            size,
+            (entry.date >> 5) & 0x0F,
+            entry.date & 0x1F,
+            year,
+            match hour % 12 {
+                0 => 12,
+                h => h,
+            },
+            (entry.time >> 5) & 0x3F,
+            if hour < 12 { 'a' } else { 'p' },
+        ));
+    }
+    out.push_str(&format!(
+        "{:>9} file(s) {:>14} bytes\n{:>24} bytes free\n",
+        files, bytes, free_bytes
+    ));
+    Some(out)
+}
+
+/// Memory owned by a process (or the environment of one)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct MemoryBlock {
+    segment: u16,
+    paragraphs: u16,
+    /// PSP segment of the owning process
+    owner: u16,
+}
+
+/// A file opened through INT 21h
+///
+/// The whole file is read on open and written back on close, so programs can
+/// seek and write anywhere without touching the disk image until then.
+#[derive(Debug, Clone)]
+pub struct OpenFile {
+    pub drive: u8,
+    /// Cluster of the directory holding the file
+    pub dir: u16,
+    pub entry: DirEntry,
+    pub data: Vec<u8>,
+    pub position: u32,
+    /// Written since it was opened (or last flushed)
+    pub modified: bool,
+    /// PSP of the process that opened it
+    pub owner: u16,
+}
+
+/// State of a program that started a child with INT 21h AH=4Bh
+#[derive(Debug, Clone, Copy)]
+pub struct ParentProcess {
+    pub psp: u16,
+    pub dta: (u16, u16),
+    pub cs: u16,
+    pub ip: u16,
+    pub ss: u16,
+    pub sp: u16,
+    pub ds: u16,
+    pub es: u16,
+}
+
+/// Header fields of an MZ executable that the loader needs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExeHeader {
+    /// Offset of the load module in the file
+    pub header_bytes: usize,
+    /// Bytes of the load module
+    pub image_bytes: usize,
+    pub min_alloc: u16,
+    pub max_alloc: u16,
+    pub ss: u16,
+    pub sp: u16,
+    pub ip: u16,
+    pub cs: u16,
+    pub relocation_count: usize,
+    pub relocation_offset: usize,
+}
+
+impl ExeHeader {
+    /// Parse the header of an "MZ" (or "ZM") executable; None for a .COM file
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        if data.len() < 0x1C || !(data[..2] == *b"MZ" || data[..2] == *b"ZM") {
+            return None;
+        }
+        let word = |offset: usize| u16::from_le_bytes([data[offset], data[offset + 1]]);
+        let last_page = word(0x02) as usize;
+        let pages = word(0x04) as usize;
+        let header_bytes = word(0x08) as usize * 16;
+        let mut file_bytes = pages * 512;
+        if last_page != 0 {
+            file_bytes = file_bytes.saturating_sub(512 - last_page);
+        }
+        let image_bytes = file_bytes.min(data.len()).saturating_sub(header_bytes);
+        Some(Self {
+            header_bytes,
+            image_bytes,
+            min_alloc: word(0x0A),
+            max_alloc: word(0x0C),
+            ss: word(0x0E),
+            sp: word(0x10),
+            ip: word(0x14),
+            cs: word(0x16),
+            relocation_count: word(0x06) as usize,
+            relocation_offset: word(0x18) as usize,
+        })
+    }
+
+    /// Whether the header describes a load module and relocation table that
+    /// are actually in the file
+    pub fn is_valid(&self, data: &[u8]) -> bool {
+        self.header_bytes >= 0x1C
+            && self.header_bytes <= data.len()
+            && self.image_bytes > 0
+            && self.relocation_offset + self.relocation_count * 4 <= data.len()
+    }
+
+    /// Relocation entries as (segment, offset) pairs within the load module
+    pub fn relocations<'a>(&self, data: &'a [u8]) -> impl Iterator<Item = (u16, u16)> + 'a {
+        let start = self.relocation_offset;
+        data.get(start..)
+            .unwrap_or(&[])
+            .chunks_exact(4)
+            .take(self.relocation_count)
+            .map(|r| {
+                let offset = u16::from_le_bytes([r[0], r[1]]);
+                let segment = u16::from_le_bytes([r[2], r[3]]);
+                (segment, offset)
+            })
+    }
+}
+
+/// A command typed at the prompt
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShellCommand {
+    Empty,
+    /// "A:" - change the current drive
+    Drive(u8),
+    Dir(String),
+    Type(String),
+    Copy(String, String),
+    Del(String),
+    Cd(String),
+    Cls,
+    Ver,
+    Echo(String),
+    Help,
+    /// Anything else: a program name and its command tail
+    Run(String, String),
+}
+
+impl ShellCommand {
+    /// Split a command line into a built-in command or a program to run
+    pub fn parse(line: &str) -> Self {
+        let line = line.trim();
+        if line.is_empty() {
+            return ShellCommand::Empty;
+        }
+        let bytes = line.as_bytes();
+        if bytes.len() == 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' {
+            return ShellCommand::Drive(bytes[0].to_ascii_uppercase() - b'A');
+        }
+
+        // "CD.." and "CD\" are valid without a space
+        let split = line
+            .find(|c: char| c.is_whitespace() || ((c == '.' || c == '\\') && is_cd(line)))
+            .unwrap_or(line.len());
+        let (word, rest) = line.split_at(split);
+        let args = rest.trim().to_string();
+        let arg_list: Vec<&str> = args.split_whitespace().collect();
+        match word.to_ascii_uppercase().as_str() {
+            "DIR" => ShellCommand::Dir(args),
+            "TYPE" => ShellCommand::Type(args),
+            "COPY" => ShellCommand::Copy(
+                arg_list.first().unwrap_or(&"").to_string(),
+                arg_list.get(1).unwrap_or(&"").to_string(),
+            ),
+            "DEL" | "ERASE" => ShellCommand::Del(args),
+            "CD" | "CHDIR" => ShellCommand::Cd(args),
+            "CLS" => ShellCommand::Cls,
+            "VER" => ShellCommand::Ver,
+            "ECHO" => ShellCommand::Echo(rest.strip_prefix(' ').unwrap_or(rest).to_string()),
+            "HELP" | "?" => ShellCommand::Help,
+            _ => ShellCommand::Run(word.to_string(), rest.to_string()),
+        }
+    }
+}
+
+fn is_cd(line: &str) -> bool {
+    let upper = line.to_ascii_uppercase();
+    upper.starts_with("CD.") || upper.starts_with("CD\\")
+}
+
+/// Built-in DOS state
+pub struct BuiltinDos {
+    /// Start the built-in DOS when no disk boots
+    enabled: bool,
+    /// The shell has been started (INT 18h)
+    running: bool,
+    /// Current drive (0 = A:)
+    pub current_drive: u8,
+    /// Current directory of each drive
+    current_dirs: [Vec<[u8; 11]>; DRIVE_COUNT as usize],
+    /// Open files, by handle minus `FIRST_FILE_HANDLE`
+    files: Vec<Option<OpenFile>>,
+    /// Memory blocks in use, sorted by segment
+    blocks: Vec<MemoryBlock>,
+    /// End of conventional memory (segment)
+    memory_top: u16,
+    /// PSP of the running program (0 while the prompt runs)
+    pub psp: u16,
+    /// Disk transfer area (segment, offset)
+    pub dta: (u16, u16),
+    /// Programs waiting for a child started with AH=4Bh
+    pub parents: Vec<ParentProcess>,
+    /// Exit code and termination type of the last program (AH=4Dh)
+    pub return_code: u16,
+    /// Remaining matches of the last find first/next search
+    pub search: Vec<DirEntry>,
+    /// Characters typed so far in a buffered input call (AH=0Ah)
+    pub line: Option<Vec<u8>>,
+}
+
+impl Default for BuiltinDos {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BuiltinDos {
+    pub fn new() -> Self {
+        Self {
+            enabled: true,
+            running: false,
+            current_drive: 0,
+            current_dirs: Default::default(),
+            files: Vec::new(),
+            blocks: Vec::new(),
+            memory_top: 0xA000,
+            psp: 0,
+            dta: (0, 0x80),
+            parents: Vec::new(),
+            return_code: 0,
+            search: Vec::new(),
+            line: None,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    /// Start the shell with conventional memory ending at `memory_top`
+    /// (segment), on the given drive
+    pub fn start(&mut self, memory_top: u16, drive: u8) {
+        let enabled = self.enabled;
+        *self = Self::new();
+        self.enabled = enabled;
+        self.running = true;
+        self.memory_top = memory_top;
+        self.current_drive = drive;
+    }
+
+    /// Stop the shell and forget all state (on reset)
+    pub fn reset(&mut self) {
+        let enabled = self.enabled;
+        *self = Self::new();
+        self.enabled = enabled;
+    }
+
+    /// Current directory of a drive
+    pub fn current_dir(&self, drive: u8) -> &[[u8; 11]] {
+        self.current_dirs
+            .get(drive as usize)
+            .map_or(&[], |dirs| dirs.as_slice())
+    }
+
+    pub fn set_current_dir(&mut self, drive: u8, dirs: Vec<[u8; 11]>) {
+        if let Some(current) = self.current_dirs.get_mut(drive as usize) {
+            *current = dirs;
+        }
+    }
+
+    /// Split a DOS path, resolving it against the current drive and directory
+    ///
+    /// Handles drive letters, "\" and "/" separators, "." and "..". Returns
+    /// None if a component is not a valid 8.3 name.
+    pub fn parse_path(&self, path: &str) -> Option<DosPath> {
+        let path = path.trim();
+        let bytes = path.as_bytes();
+        let (drive, rest) = if bytes.len() >= 2 && bytes[1] == b':' {
+            if !bytes[0].is_ascii_alphabetic() {
+                return None;
+            }
+            (bytes[0].to_ascii_uppercase() - b'A', &path[2..])
+        } else {
+            (self.current_drive, path)
+        };
+
+        let mut dirs = if rest.starts_with(['\\', '/']) {
+            Vec::new()
+        } else {
+            self.current_dir(drive).to_vec()
+        };
+        let components: Vec<&str> = rest.split(['\\', '/']).collect();
+        let last = components.len() - 1;
+        let mut name = None;
+        for (i, component) in components.into_iter().enumerate() {
+            match component {
+                "" | "." => {}
+                ".." => {
+                    dirs.pop();
+                }
+                _ => {
+                    let short = short_name(component)?;
+                    if i == last {
+                        name = Some(short);
+                    } else if has_wildcards(&short) {
+                        return None;
+                    } else {
+                        dirs.push(short);
+                    }
+                }
+            }
+        }
+        Some(DosPath { drive, dirs, name })
+    }
+
+    /// Register an open file, returning its handle
+    pub fn add_file(&mut self, file: OpenFile) -> Option<u16> {
+        let index = match self.files.iter().position(Option::is_none) {
+            Some(index) => index,
+            None if self.files.len() < MAX_OPEN_FILES => {
+                self.files.push(None);
+                self.files.len() - 1
+            }
+            None => return None,
+        };
+        self.files[index] = Some(file);
+        Some(index as u16 + FIRST_FILE_HANDLE)
+    }
+
+    pub fn file_mut(&mut self, handle: u16) -> Option<&mut OpenFile> {
+        let index = handle.checked_sub(FIRST_FILE_HANDLE)? as usize;
+        self.files.get_mut(index)?.as_mut()
+    }
+
+    /// Remove a file from the table (on close)
+    pub fn take_file(&mut self, handle: u16) -> Option<OpenFile> {
+        let index = handle.checked_sub(FIRST_FILE_HANDLE)? as usize;
+        self.files.get_mut(index)?.take()
+    }
+
+    /// Handles of the files a process left open
+    pub fn files_owned_by(&self, psp: u16) -> Vec<u16> {
+        self.files
+            .iter()
+            .enumerate()
+            .filter(|(_, file)| file.as_ref().is_some_and(|f| f.owner == psp))
+            .map(|(i, _)| i as u16 + FIRST_FILE_HANDLE)
+            .collect()
+    }
+
+    /// Free gaps between the used blocks, as (segment, paragraphs)
+    fn free_ranges(&self) -> Vec<(u16, u16)> {
+        let mut ranges = Vec::new();
+        let mut start = FIRST_PROGRAM_SEGMENT;
+        for block in &self.blocks {
+            if block.segment > start {
+                ranges.push((start, block.segment - start));
+            }
+            start = start.max(block.segment + block.paragraphs);
+        }
+        if self.memory_top > start {
+            ranges.push((start, self.memory_top - start));
+        }
+        ranges
+    }
+
+    /// Size of the largest free block in paragraphs
+    pub fn largest_free(&self) -> u16 {
+        self.free_ranges()
+            .into_iter()
+            .map(|(_, size)| size)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Allocate memory for a process (first fit)
+    ///
+    /// Returns the segment, or the largest free block size on failure.
+    pub fn allocate(&mut self, paragraphs: u16, owner: u16) -> Result<u16, u16> {
+        let (segment, _) = self
+            .free_ranges()
+            .into_iter()
+            .find(|&(_, size)| size >= paragraphs)
+            .ok_or_else(|| self.largest_free())?;
+        // A block owned by "itself" gets the new segment as owner (a PSP)
+        let owner = if owner == 0 { segment } else { owner };
+        self.blocks.push(MemoryBlock {
+            segment,
+            paragraphs: paragraphs.max(1),
+            owner,
+        });
+        self.blocks.sort_by_key(|block| block.segment);
+        Ok(segment)
+    }
+
+    /// Hand a block over to another process
+    pub fn set_owner(&mut self, segment: u16, owner: u16) {
+        if let Some(block) = self.blocks.iter_mut().find(|b| b.segment == segment) {
+            block.owner = owner;
+        }
+    }
+
+    /// Free a block; false if no block starts at `segment`
+    pub fn free(&mut self, segment: u16) -> bool {
+        let before = self.blocks.len();
+        self.blocks.retain(|block| block.segment != segment);
+        self.blocks.len() != before
+    }
+
+    /// Grow or shrink a block in place
+    ///
+    /// Returns the largest size the block could have on failure (0 if no
+    /// block starts at `segment`).
+    pub fn resize(&mut self, segment: u16, paragraphs: u16) -> Result<(), u16> {
+        let index = self
+            .blocks
+            .iter()
+            .position(|block| block.segment == segment)
+            .ok_or(0u16)?;
+        let limit = self
+            .blocks
+            .get(index + 1)
+            .map_or(self.memory_top, |next| next.segment);
+        let max = limit - segment;
+        if paragraphs > max {
+            return Err(max);
+        }
+        self.blocks[index].paragraphs = paragraphs.max(1);
+        Ok(())
+    }
+
+    /// Free every block owned by a process
+    pub fn free_owned_by(&mut self, psp: u16) {
+        self.blocks.retain(|block| block.owner != psp);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_names() {
+        assert_eq!(short_name("game.exe"), Some(*b"GAME    EXE"));
+        assert_eq!(short_name("README"), Some(*b"README     "));
+        assert_eq!(short_name("longfilename.text"), Some(*b"LONGFILETEX"));
+        assert_eq!(short_name("*.*"), Some(*b"???????????"));
+        assert_eq!(short_name("A*.C?M"), Some(*b"A???????C?M"));
+        assert_eq!(short_name("bad name"), None);
+        assert_eq!(short_name(".."), None);
+        assert!(name_matches(b"????????COM", b"HELLO   COM"));
+        assert!(!name_matches(b"????????COM", b"HELLO   EXE"));
+    }
+
+    #[test]
+    fn test_parse_path() {
+        let mut dos = BuiltinDos::new();
+        dos.set_current_dir(0, vec![*b"GAMES      "]);
+
+        let path = dos.parse_path("doom.exe").unwrap();
+        assert_eq!(path.drive, 0);
+        assert_eq!(path.dirs, vec![*b"GAMES      "]);
+        assert_eq!(path.name, Some(*b"DOOM    EXE"));
+
+        let path = dos.parse_path("..\\README.TXT").unwrap();
+        assert!(path.dirs.is_empty());
+        assert_eq!(path.name, Some(*b"README  TXT"));
+
+        let path = dos.parse_path("c:/util/").unwrap();
+        assert_eq!(path.drive, 2);
+        assert_eq!(path.dir_string(), "C:\\UTIL");
+        assert_eq!(path.name, None);
+
+        assert_eq!(dos.parse_path("\\*\\file").map(|p| p.name), None);
+    }
+
+    #[test]
+    fn test_memory_blocks() {
+        let mut dos = BuiltinDos::new();
+        dos.start(0x9FC0, 0);
+        let all = dos.largest_free();
+        assert_eq!(all, 0x9FC0 - FIRST_PROGRAM_SEGMENT);
+
+        // A program gets all memory, shrinks, then allocates above itself
+        let psp = dos.allocate(all, 0).unwrap();
+        assert_eq!(psp, FIRST_PROGRAM_SEGMENT);
+        assert_eq!(dos.allocate(0x100, psp), Err(0));
+        dos.resize(psp, 0x1000).unwrap();
+        let block = dos.allocate(0x100, psp).unwrap();
+        assert_eq!(block, psp + 0x1000);
+        assert_eq!(dos.resize(psp, 0x2000), Err(0x1000));
+
+        dos.free_owned_by(psp);
+        assert_eq!(dos.largest_free(), all);
+    }
+
+    #[test]
+    fn test_exe_header() {
+        let mut exe = vec![0u8; 0x200 + 0x30];
+        exe[..2].copy_from_slice(b"MZ");
+        exe[0x02..0x04].copy_from_slice(&0x30u16.to_le_bytes()); // Bytes on last page
+        exe[0x04..0x06].copy_from_slice(&2u16.to_le_bytes()); // Pages
+        exe[0x06..0x08].copy_from_slice(&1u16.to_le_bytes()); // Relocations
+        exe[0x08..0x0A].copy_from_slice(&0x20u16.to_le_bytes()); // Header paragraphs
+        exe[0x16..0x18].copy_from_slice(&0x0010u16.to_le_bytes()); // CS
+        exe[0x18..0x1A].copy_from_slice(&0x1Cu16.to_le_bytes()); // Relocation table
+        exe[0x1C..0x20].copy_from_slice(&[0x04, 0x00, 0x10, 0x00]);
+
+        let header = ExeHeader::parse(&exe).unwrap();
+        assert_eq!(header.header_bytes, 0x200);
+        assert_eq!(header.image_bytes, 0x30);
+        assert_eq!(header.cs, 0x0010);
+        assert_eq!(header.relocations(&exe).collect::<Vec<_>>(), [(0x10, 0x04)]);
+        assert!(header.is_valid(&exe));
+        // Cut off before the load module
+        assert!(!header.is_valid(&exe[..0x100]));
+        assert!(ExeHeader::parse(&[0xCD, 0x20]).is_none());
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(ShellCommand::parse("  "), ShellCommand::Empty);
+        assert_eq!(ShellCommand::parse("c:"), ShellCommand::Drive(2));
+        assert_eq!(
+            ShellCommand::parse("dir *.com"),
+            ShellCommand::Dir("*.com".into())
+        );
+        assert_eq!(ShellCommand::parse("cd.."), ShellCommand::Cd("..".into()));
+        assert_eq!(
+            ShellCommand::parse("copy a.txt b.txt"),
+            ShellCommand::Copy("a.txt".into(), "b.txt".into())
+        );
+        assert_eq!(
+            ShellCommand::parse("game.exe /nosound"),
+            ShellCommand::Run("game.exe".into(), " /nosound".into())
+        );
+    }
+}
@@ -0,0 +1,481 @@
+//! FAT file access for the built-in DOS
+//!
+//! Reads and writes files directly in a mounted FAT12/FAT16 disk image (a
+//! floppy, or the first partition of a hard drive). Directory entries are
+//! addressed by 8.3 names in directory entry form (`b"GAME    EXE"`).
+//!
+//! Writes replace a whole file at a time and report the image sectors they
+//! changed, so the bus can track them like INT 13h writes. Subdirectories
+//! can be read and written but not created or grown.
+
+use crate::host_folder::{
+    fat_entry, set_fat_entry, Layout, DIR_ENTRY_SIZE, END_OF_CHAIN, SECTOR_SIZE,
+};
+pub(crate) use crate::host_folder::{ATTR_ARCHIVE, ATTR_DIRECTORY, ATTR_VOLUME_ID};
+
+/// Read-only file attribute
+pub const ATTR_READ_ONLY: u8 = 0x01;
+/// Hidden file attribute
+pub const ATTR_HIDDEN: u8 = 0x02;
+/// System file attribute
+pub const ATTR_SYSTEM: u8 = 0x04;
+
+/// Attribute value of a long file name entry
+const ATTR_LONG_NAME: u8 = 0x0F;
+
+/// Marker in the first name byte of a deleted entry
+const DELETED: u8 = 0xE5;
+
+/// Directory cluster meaning "the root directory"
+pub const ROOT_DIR: u16 = 0;
+
+/// Errors from file system operations, convertible to DOS error codes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum DosFsError {
+    #[error("File not found")]
+    FileNotFound,
+    #[error("Path not found")]
+    PathNotFound,
+    #[error("Access denied")]
+    AccessDenied,
+    #[error("Insufficient disk space")]
+    DiskFull,
+    #[error("Drive not ready")]
+    NotReady,
+}
+
+impl DosFsError {
+    /// DOS error code (returned in AX by INT 21h)
+    pub fn code(self) -> u16 {
+        match self {
+            DosFsError::FileNotFound => 0x02,
+            DosFsError::PathNotFound => 0x03,
+            DosFsError::AccessDenied => 0x05,
+            DosFsError::DiskFull => 0x27,
+            DosFsError::NotReady => 0x15,
+        }
+    }
+}
+
+/// A file or directory listed in a directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirEntry {
+    /// 8.3 name in directory entry form
+    pub name: [u8; 11],
+    pub attr: u8,
+    /// DOS packed time and date of the last write
+    pub time: u16,
+    pub date: u16,
+    /// First cluster (0 for an empty file)
+    pub cluster: u16,
+    pub size: u32,
+}
+
+impl DirEntry {
+    /// New entry for a file written at the given DOS time and date
+    pub fn file(name: [u8; 11], attr: u8, time: u16, date: u16) -> Self {
+        Self {
+            name,
+            attr,
+            time,
+            date,
+            cluster: 0,
+            size: 0,
+        }
+    }
+
+    pub fn is_dir(&self) -> bool {
+        self.attr & ATTR_DIRECTORY != 0
+    }
+
+    fn parse(raw: &[u8]) -> Self {
+        let mut name = [0u8; 11];
+        name.copy_from_slice(&raw[..11]);
+        Self {
+            name,
+            attr: raw[11],
+            time: u16::from_le_bytes([raw[22], raw[23]]),
+            date: u16::from_le_bytes([raw[24], raw[25]]),
+            cluster: u16::from_le_bytes([raw[26], raw[27]]),
+            size: u32::from_le_bytes([raw[28], raw[29], raw[30], raw[31]]),
+        }
+    }
+
+    fn encode(&self) -> [u8; DIR_ENTRY_SIZE] {
+        let mut raw = [0u8; DIR_ENTRY_SIZE];
+        raw[..11].copy_from_slice(&self.name);
+        raw[11] = self.attr;
+        raw[22..24].copy_from_slice(&self.time.to_le_bytes());
+        raw[24..26].copy_from_slice(&self.date.to_le_bytes());
+        raw[26..28].copy_from_slice(&self.cluster.to_le_bytes());
+        raw[28..32].copy_from_slice(&self.size.to_le_bytes());
+        raw
+    }
+}
+
+/// A FAT volume found in a disk image
+#[derive(Debug, Clone)]
+pub struct FatVolume {
+    layout: Layout,
+}
+
+impl FatVolume {
+    /// Find the FAT volume on a floppy image or the first partition of a
+    /// hard drive image
+    pub fn open(image: &[u8]) -> Result<Self, DosFsError> {
+        Layout::from_image(image)
+            .map(|layout| Self { layout })
+            .map_err(|_| DosFsError::NotReady)
+    }
+
+    fn fat<'a>(&self, image: &'a [u8]) -> &'a [u8] {
+        &image[self.layout.fat_start()..self.layout.fat_start() + self.layout.fat_bytes()]
+    }
+
+    fn is_data_cluster(&self, cluster: usize) -> bool {
+        (2..self.layout.cluster_count() + 2).contains(&cluster)
+    }
+
+    /// Clusters of a chain, stopping at the end marker or a bad link
+    fn chain(&self, image: &[u8], start: u16) -> Vec<usize> {
+        let fat = self.fat(image);
+        let fat16 = self.layout.is_fat16();
+        let mut clusters = Vec::new();
+        let mut cluster = start as usize;
+        // Bound the walk so a corrupted (looping) chain still terminates
+        while self.is_data_cluster(cluster) && clusters.len() < self.layout.cluster_count() {
+            clusters.push(cluster);
+            cluster = fat_entry(fat, fat16, cluster);
+        }
+        clusters
+    }
+
+    /// Image offsets of every entry slot in a directory
+    fn dir_slots(&self, image: &[u8], dir: u16) -> Vec<usize> {
+        if dir == ROOT_DIR {
+            let start = self.layout.root_start();
+            return (0..self.layout.root_bytes() / DIR_ENTRY_SIZE)
+                .map(|i| start + i * DIR_ENTRY_SIZE)
+                .collect();
+        }
+        let per_cluster = self.layout.cluster_bytes() / DIR_ENTRY_SIZE;
+        self.chain(image, dir)
+            .into_iter()
+            .flat_map(|cluster| {
+                let start = self.layout.cluster_offset(cluster);
+                (0..per_cluster).map(move |i| start + i * DIR_ENTRY_SIZE)
+            })
+            .filter(|&offset| offset + DIR_ENTRY_SIZE <= image.len())
+            .collect()
+    }
+
+    /// Used entries of a directory with their image offsets
+    fn entries(&self, image: &[u8], dir: u16) -> Vec<(usize, DirEntry)> {
+        let mut entries = Vec::new();
+        for offset in self.dir_slots(image, dir) {
+            let raw = &image[offset..offset + DIR_ENTRY_SIZE];
+            match raw[0] {
+                0x00 => break,
+                DELETED => continue,
+                _ => {}
+            }
+            if raw[11] == ATTR_LONG_NAME {
+                continue;
+            }
+            entries.push((offset, DirEntry::parse(raw)));
+        }
+        entries
+    }
+
+    /// Files, subdirectories and the volume label of a directory
+    pub fn list(&self, image: &[u8], dir: u16) -> Vec<DirEntry> {
+        self.entries(image, dir)
+            .into_iter()
+            .map(|(_, entry)| entry)
+            .collect()
+    }
+
+    /// Look up a file or subdirectory (not the volume label) by name
+    pub fn find(&self, image: &[u8], dir: u16, name: &[u8; 11]) -> Option<DirEntry> {
+        self.list(image, dir)
+            .into_iter()
+            .find(|entry| &entry.name == name && entry.attr & ATTR_VOLUME_ID == 0)
+    }
+
+    /// Follow subdirectory names from the root to a directory cluster
+    pub fn find_dir(&self, image: &[u8], path: &[[u8; 11]]) -> Result<u16, DosFsError> {
+        let mut dir = ROOT_DIR;
+        for name in path {
+            match self.find(image, dir, name) {
+                Some(entry) if entry.is_dir() => dir = entry.cluster,
+                _ => return Err(DosFsError::PathNotFound),
+            }
+        }
+        Ok(dir)
+    }
+
+    /// Volume label from the root directory, or else from the extended boot
+    /// sector fields
+    pub fn label(&self, image: &[u8]) -> Option<String> {
+        let from_root = self
+            .list(image, ROOT_DIR)
+            .into_iter()
+            .find(|entry| entry.attr & ATTR_VOLUME_ID != 0)
+            .map(|entry| entry.name.to_vec());
+        let from_boot = || {
+            let boot = image.get(self.layout.hidden_sectors * SECTOR_SIZE..)?;
+            if boot.get(38) != Some(&0x29) {
+                return None; // No extended boot signature
+            }
+            boot.get(43..54).map(<[u8]>::to_vec)
+        };
+        let label = from_root.or_else(from_boot)?;
+        let label = String::from_utf8_lossy(&label).trim_end().to_string();
+        (!label.is_empty() && label != "NO NAME").then_some(label)
+    }
+
+    /// Contents of a file
+    pub fn read(&self, image: &[u8], entry: &DirEntry) -> Vec<u8> {
+        let mut data = Vec::with_capacity(entry.size as usize);
+        for cluster in self.chain(image, entry.cluster) {
+            let offset = self.layout.cluster_offset(cluster);
+            match image.get(offset..offset + self.layout.cluster_bytes()) {
+                Some(bytes) => data.extend_from_slice(bytes),
+                None => break,
+            }
+        }
+        data.truncate(entry.size as usize);
+        data
+    }
+
+    /// Bytes per cluster
+    pub fn cluster_bytes(&self) -> usize {
+        self.layout.cluster_bytes()
+    }
+
+    /// Number of data clusters on the volume
+    pub fn cluster_count(&self) -> usize {
+        self.layout.cluster_count()
+    }
+
+    /// Bytes in unallocated clusters
+    pub fn free_bytes(&self, image: &[u8]) -> u64 {
+        let fat = self.fat(image);
+        let fat16 = self.layout.is_fat16();
+        let free = (2..self.layout.cluster_count() + 2)
+            .filter(|&cluster| fat_entry(fat, fat16, cluster) == 0)
+            .count();
+        free as u64 * self.layout.cluster_bytes() as u64
+    }
+
+    /// Create or replace a file in a directory
+    ///
+    /// `entry` supplies the name, attributes and timestamp. Returns the image
+    /// sectors that were changed; the image is left untouched on error.
+    pub fn write(
+        &self,
+        image: &mut [u8],
+        dir: u16,
+        entry: &DirEntry,
+        data: &[u8],
+    ) -> Result<Vec<u32>, DosFsError> {
+        let existing = self
+            .entries(image, dir)
+            .into_iter()
+            .find(|(_, e)| e.name == entry.name && e.attr & ATTR_VOLUME_ID == 0);
+        if let Some((_, old)) = &existing {
+            if old.is_dir() || old.attr & ATTR_READ_ONLY != 0 {
+                return Err(DosFsError::AccessDenied);
+            }
+        }
+        let slot = match &existing {
+            Some((offset, _)) => *offset,
+            None => self
+                .dir_slots(image, dir)
+                .into_iter()
+                .find(|&offset| matches!(image[offset], 0x00 | DELETED))
+                .ok_or(DosFsError::AccessDenied)?,
+        };
+
+        // Work on a copy of the FAT so a full disk leaves the image as it was
+        let fat16 = self.layout.is_fat16();
+        let mut fat = self.fat(image).to_vec();
+        if let Some((_, old)) = &existing {
+            for cluster in self.chain(image, old.cluster) {
+                set_fat_entry(&mut fat, fat16, cluster, 0);
+            }
+        }
+        let cluster_bytes = self.layout.cluster_bytes();
+        let needed = data.len().div_ceil(cluster_bytes);
+        let clusters: Vec<usize> = (2..self.layout.cluster_count() + 2)
+            .filter(|&cluster| fat_entry(&fat, fat16, cluster) == 0)
+            .take(needed)
+            .collect();
+        if clusters.len() < needed {
+            return Err(DosFsError::DiskFull);
+        }
+        for (i, &cluster) in clusters.iter().enumerate() {
+            let next = clusters.get(i + 1).map_or(END_OF_CHAIN, |&c| c as u32);
+            set_fat_entry(&mut fat, fat16, cluster, next);
+        }
+
+        let mut touched = Vec::new();
+        for (chunk, &cluster) in data.chunks(cluster_bytes).zip(&clusters) {
+            let offset = self.layout.cluster_offset(cluster);
+            image[offset..offset + chunk.len()].copy_from_slice(chunk);
+            image[offset + chunk.len()..offset + cluster_bytes].fill(0);
+            touched.extend(sectors(offset, cluster_bytes));
+        }
+        touched.extend(self.store_fat(image, &fat));
+
+        let mut new_entry = entry.clone();
+        new_entry.cluster = clusters.first().map_or(0, |&c| c as u16);
+        new_entry.size = data.len() as u32;
+        image[slot..slot + DIR_ENTRY_SIZE].copy_from_slice(&new_entry.encode());
+        touched.extend(sectors(slot, DIR_ENTRY_SIZE));
+        Ok(touched)
+    }
+
+    /// Delete a file, returning the image sectors that were changed
+    pub fn delete(
+        &self,
+        image: &mut [u8],
+        dir: u16,
+        name: &[u8; 11],
+    ) -> Result<Vec<u32>, DosFsError> {
+        let (slot, entry) = self
+            .entries(image, dir)
+            .into_iter()
+            .find(|(_, e)| &e.name == name && e.attr & ATTR_VOLUME_ID == 0)
+            .ok_or(DosFsError::FileNotFound)?;
+        if entry.is_dir() || entry.attr & ATTR_READ_ONLY != 0 {
+            return Err(DosFsError::AccessDenied);
+        }
+
+        let fat16 = self.layout.is_fat16();
+        let mut fat = self.fat(image).to_vec();
+        for cluster in self.chain(image, entry.cluster) {
+            set_fat_entry(&mut fat, fat16, cluster, 0);
+        }
+        let mut touched = self.store_fat(image, &fat);
+        image[slot] = DELETED;
+        touched.extend(sectors(slot, 1));
+        Ok(touched)
+    }
+
+    /// Copy a modified FAT into every FAT of the volume, returning the
+    /// sectors that changed
+    fn store_fat(&self, image: &mut [u8], fat: &[u8]) -> Vec<u32> {
+        let mut touched = Vec::new();
+        for copy in 0..self.layout.fat_count {
+            let start = self.layout.fat_start() + copy * self.layout.fat_bytes();
+            for (i, sector) in fat.chunks(SECTOR_SIZE).enumerate() {
+                let offset = start + i * SECTOR_SIZE;
+                if image[offset..offset + sector.len()] != *sector {
+                    image[offset..offset + sector.len()].copy_from_slice(sector);
+                    touched.push((offset / SECTOR_SIZE) as u32);
+                }
+            }
+        }
+        touched
+    }
+}
+
+/// Image sectors covering a byte range
+fn sectors(offset: usize, len: usize) -> impl Iterator<Item = u32> {
+    let first = offset / SECTOR_SIZE;
+    let last = (offset + len - 1) / SECTOR_SIZE;
+    (first..=last).map(|sector| sector as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::host_folder::{build_host_folder_image, HostDriveKind};
+    use std::fs;
+
+    fn test_image(name: &str, kind: HostDriveKind) -> Vec<u8> {
+        let dir = std::env::temp_dir().join(format!("hemulator_dos_fs_{}", name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("games")).unwrap();
+        fs::write(dir.join("readme.txt"), b"Hello from the disk\r\n").unwrap();
+        fs::write(dir.join("games").join("big.dat"), vec![0x5A; 5000]).unwrap();
+        let image = build_host_folder_image(&dir, kind).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        image
+    }
+
+    #[test]
+    fn test_read_files_and_subdirectories() {
+        for kind in [HostDriveKind::Floppy, HostDriveKind::HardDrive] {
+            let image = test_image("read", kind);
+            let volume = FatVolume::open(&image).unwrap();
+            assert_eq!(volume.label(&image).as_deref(), Some("HOST FOLDER"));
+
+            let readme = volume.find(&image, ROOT_DIR, b"README  TXT").unwrap();
+            assert_eq!(volume.read(&image, &readme), b"Hello from the disk\r\n");
+
+            let games = volume.find_dir(&image, &[*b"GAMES      "]).unwrap();
+            assert_ne!(games, ROOT_DIR);
+            let big = volume.find(&image, games, b"BIG     DAT").unwrap();
+            assert_eq!(volume.read(&image, &big), vec![0x5A; 5000]);
+
+            assert_eq!(
+                volume.find_dir(&image, &[*b"MISSING    "]),
+                Err(DosFsError::PathNotFound)
+            );
+        }
+    }
+
+    #[test]
+    fn test_write_replace_and_delete() {
+        let mut image = test_image("write", HostDriveKind::Floppy);
+        let volume = FatVolume::open(&image).unwrap();
+        let free = volume.free_bytes(&image);
+        let games = volume.find_dir(&image, &[*b"GAMES      "]).unwrap();
+
+        let entry = DirEntry::file(*b"SAVE    DAT", 0x20, 0, 0x21);
+        let touched = volume.write(&mut image, games, &entry, &[1; 1500]).unwrap();
+        assert!(!touched.is_empty());
+        let saved = volume.find(&image, games, b"SAVE    DAT").unwrap();
+        assert_eq!(saved.size, 1500);
+        assert_eq!(volume.read(&image, &saved), vec![1; 1500]);
+        assert_eq!(volume.free_bytes(&image), free - 1536);
+
+        // Replacing the file releases its old clusters
+        volume.write(&mut image, games, &entry, b"short").unwrap();
+        let saved = volume.find(&image, games, b"SAVE    DAT").unwrap();
+        assert_eq!(volume.read(&image, &saved), b"short");
+        assert_eq!(volume.free_bytes(&image), free - 512);
+
+        volume.delete(&mut image, games, b"SAVE    DAT").unwrap();
+        assert!(volume.find(&image, games, b"SAVE    DAT").is_none());
+        assert_eq!(volume.free_bytes(&image), free);
+        assert_eq!(
+            volume.delete(&mut image, games, b"SAVE    DAT"),
+            Err(DosFsError::FileNotFound)
+        );
+    }
+
+    #[test]
+    fn test_full_disk_leaves_image_unchanged() {
+        let mut image = test_image("full", HostDriveKind::Floppy);
+        let volume = FatVolume::open(&image).unwrap();
+        let before = image.clone();
+        let entry = DirEntry::file(*b"HUGE    BIN", 0x20, 0, 0x21);
+        let huge = vec![0; volume.free_bytes(&image) as usize + 1];
+        assert_eq!(
+            volume.write(&mut image, ROOT_DIR, &entry, &huge),
+            Err(DosFsError::DiskFull)
+        );
+        assert!(image == before);
+    }
+
+    #[test]
+    fn test_boot_sector_claiming_more_clusters_than_the_fat() {
+        let mut image = test_image("corrupt", HostDriveKind::Floppy);
+        // One sector per FAT cannot describe the 2847 clusters of a 1.44MB disk
+        image[22..24].copy_from_slice(&1u16.to_le_bytes());
+        assert_eq!(FatVolume::open(&image).unwrap_err(), DosFsError::NotReady);
+    }
+}
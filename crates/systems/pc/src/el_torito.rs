@@ -0,0 +1,209 @@
+//! El Torito bootable CD-ROM format
+//!
+//! A bootable ISO 9660 image has a Boot Record Volume Descriptor at sector 17
+//! that points to the boot catalog. The catalog starts with a validation
+//! entry, followed by the initial (default) entry telling the BIOS how to
+//! boot: load a number of 512-byte virtual sectors and jump to them ("no
+//! emulation"), or treat a floppy image stored on the CD as drive 00h and
+//! boot its first sector.
+
+use crate::disk::FloppyFormat;
+
+/// Size of a CD-ROM sector (and of one boot catalog RBA unit)
+pub const CD_SECTOR_SIZE: usize = 2048;
+
+/// Size of the virtual sectors counted by the initial entry
+const VIRTUAL_SECTOR_SIZE: usize = 512;
+/// Sector holding the Boot Record Volume Descriptor
+const BOOT_RECORD_SECTOR: usize = 17;
+/// Boot system identifier of an El Torito boot record
+const EL_TORITO_ID: &[u8] = b"EL TORITO SPECIFICATION";
+/// Load segment used when the initial entry leaves it at 0
+const DEFAULT_LOAD_SEGMENT: u16 = 0x07C0;
+
+/// Errors found while looking for an El Torito boot entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum ElToritoError {
+    #[error("No El Torito boot record")]
+    NoBootRecord,
+    #[error("Invalid boot catalog")]
+    InvalidCatalog,
+    #[error("Initial boot entry is not bootable")]
+    NotBootable,
+    #[error("Unsupported boot media type {0:02X}h")]
+    UnsupportedMedia(u8),
+    #[error("Boot image extends past the end of the CD")]
+    Truncated,
+}
+
+/// How the initial entry is booted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BootMedia {
+    /// Boot code loaded to memory and run directly, the CD stays drive E0h
+    NoEmulation,
+    /// Floppy image on the CD, seen as drive 00h
+    Floppy(FloppyFormat),
+}
+
+/// Initial (default) entry of the boot catalog
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BootEntry {
+    pub media: BootMedia,
+    /// Segment the no-emulation boot code is loaded to
+    pub load_segment: u16,
+    /// Number of 512-byte virtual sectors to load (no emulation)
+    pub sector_count: u16,
+    /// CD sector where the boot image starts
+    pub load_rba: u32,
+}
+
+impl BootEntry {
+    /// Media type byte of the boot catalog and the INT 13h AH=4Bh packet
+    pub fn media_type(&self) -> u8 {
+        match self.media {
+            BootMedia::NoEmulation => 0x00,
+            BootMedia::Floppy(FloppyFormat::Floppy1_2M) => 0x01,
+            BootMedia::Floppy(_) => 0x02,
+        }
+    }
+
+    /// BIOS drive number the boot code is started with
+    pub fn drive(&self) -> u8 {
+        match self.media {
+            BootMedia::NoEmulation => 0xE0,
+            BootMedia::Floppy(_) => 0x00,
+        }
+    }
+
+    /// Bytes of the boot image: the code to load, or the emulated floppy
+    pub fn image<'a>(&self, cd: &'a [u8]) -> Option<&'a [u8]> {
+        let start = self.load_rba as usize * CD_SECTOR_SIZE;
+        let len = match self.media {
+            BootMedia::NoEmulation => self.sector_count as usize * VIRTUAL_SECTOR_SIZE,
+            BootMedia::Floppy(format) => format.size_bytes(),
+        };
+        cd.get(start..start + len)
+    }
+}
+
+/// Find the initial boot entry of a CD image
+pub fn parse(cd: &[u8]) -> Result<BootEntry, ElToritoError> {
+    let record = sector(cd, BOOT_RECORD_SECTOR).ok_or(ElToritoError::NoBootRecord)?;
+    if record[0] != 0x00 || &record[1..6] != b"CD001" || !record[7..].starts_with(EL_TORITO_ID) {
+        return Err(ElToritoError::NoBootRecord);
+    }
+    let catalog_lba = u32::from_le_bytes([record[0x47], record[0x48], record[0x49], record[0x4A]]);
+    let catalog = sector(cd, catalog_lba as usize).ok_or(ElToritoError::InvalidCatalog)?;
+
+    // Validation entry: header ID 01h, key 55h AAh, words summing to zero
+    let checksum = catalog[..32].chunks_exact(2).fold(0u16, |sum, word| {
+        sum.wrapping_add(u16::from_le_bytes([word[0], word[1]]))
+    });
+    if catalog[0] != 0x01 || catalog[30..32] != [0x55, 0xAA] || checksum != 0 {
+        return Err(ElToritoError::InvalidCatalog);
+    }
+
+    let initial = &catalog[32..64];
+    if initial[0] != 0x88 {
+        return Err(ElToritoError::NotBootable);
+    }
+    let media = match initial[1] & 0x0F {
+        0x00 => BootMedia::NoEmulation,
+        0x01 => BootMedia::Floppy(FloppyFormat::Floppy1_2M),
+        0x02 => BootMedia::Floppy(FloppyFormat::Floppy1_44M),
+        other => return Err(ElToritoError::UnsupportedMedia(other)),
+    };
+    let load_segment = match u16::from_le_bytes([initial[2], initial[3]]) {
+        0 => DEFAULT_LOAD_SEGMENT,
+        segment => segment,
+    };
+    let entry = BootEntry {
+        media,
+        load_segment,
+        sector_count: u16::from_le_bytes([initial[6], initial[7]]),
+        load_rba: u32::from_le_bytes([initial[8], initial[9], initial[10], initial[11]]),
+    };
+    if entry.image(cd).is_none() {
+        return Err(ElToritoError::Truncated);
+    }
+    Ok(entry)
+}
+
+fn sector(cd: &[u8], lba: usize) -> Option<&[u8]> {
+    cd.get(lba * CD_SECTOR_SIZE..(lba + 1) * CD_SECTOR_SIZE)
+}
+
+/// Build a CD image with a boot catalog at sector 18 and the boot image at
+/// sector 20
+#[cfg(test)]
+pub(crate) fn bootable_cd(media_type: u8, load_segment: u16, image: &[u8]) -> Vec<u8> {
+    let mut cd = vec![0; 20 * CD_SECTOR_SIZE + image.len().next_multiple_of(CD_SECTOR_SIZE)];
+
+    let record = &mut cd[17 * CD_SECTOR_SIZE..];
+    record[1..6].copy_from_slice(b"CD001");
+    record[6] = 0x01;
+    record[7..7 + EL_TORITO_ID.len()].copy_from_slice(EL_TORITO_ID);
+    record[0x47] = 18;
+
+    let catalog = &mut cd[18 * CD_SECTOR_SIZE..];
+    catalog[0] = 0x01;
+    catalog[30] = 0x55;
+    catalog[31] = 0xAA;
+    let sum = catalog[..32].chunks_exact(2).fold(0u16, |sum, word| {
+        sum.wrapping_add(u16::from_le_bytes([word[0], word[1]]))
+    });
+    catalog[28..30].copy_from_slice(&0u16.wrapping_sub(sum).to_le_bytes());
+    catalog[32] = 0x88;
+    catalog[33] = media_type;
+    catalog[34..36].copy_from_slice(&load_segment.to_le_bytes());
+    let sectors = image.len().div_ceil(VIRTUAL_SECTOR_SIZE) as u16;
+    catalog[38..40].copy_from_slice(&sectors.to_le_bytes());
+    catalog[40] = 20;
+
+    cd[20 * CD_SECTOR_SIZE..20 * CD_SECTOR_SIZE + image.len()].copy_from_slice(image);
+    cd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_no_emulation_entry() {
+        let cd = bootable_cd(0x00, 0, &[0xFA; 2048]);
+        let entry = parse(&cd).unwrap();
+        assert_eq!(entry.media, BootMedia::NoEmulation);
+        assert_eq!(entry.load_segment, 0x07C0);
+        assert_eq!(entry.sector_count, 4);
+        assert_eq!(entry.load_rba, 20);
+        assert_eq!(entry.drive(), 0xE0);
+        assert_eq!(entry.image(&cd).unwrap(), &[0xFA; 2048]);
+    }
+
+    #[test]
+    fn test_parse_floppy_emulation_entry() {
+        let floppy = vec![0xF6; FloppyFormat::Floppy1_44M.size_bytes()];
+        let cd = bootable_cd(0x02, 0, &floppy);
+        let entry = parse(&cd).unwrap();
+        assert_eq!(entry.media, BootMedia::Floppy(FloppyFormat::Floppy1_44M));
+        assert_eq!(entry.media_type(), 0x02);
+        assert_eq!(entry.drive(), 0x00);
+        assert_eq!(entry.image(&cd).unwrap().len(), floppy.len());
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_images() {
+        assert_eq!(parse(&[0; 64 * 1024]), Err(ElToritoError::NoBootRecord));
+
+        let mut cd = bootable_cd(0x00, 0, &[0; 512]);
+        cd[18 * CD_SECTOR_SIZE + 28] ^= 1;
+        assert_eq!(parse(&cd), Err(ElToritoError::InvalidCatalog));
+
+        let cd = bootable_cd(0x04, 0, &[0; 512]);
+        assert_eq!(parse(&cd), Err(ElToritoError::UnsupportedMedia(0x04)));
+
+        // A 1.44MB floppy entry on a CD holding only 512 bytes of image
+        let cd = bootable_cd(0x02, 0, &[0; 512]);
+        assert_eq!(parse(&cd), Err(ElToritoError::Truncated));
+    }
+}
@@ -0,0 +1,636 @@
+//! EMS (Expanded Memory Specification) Driver Implementation
+//!
+//! Provides LIM EMS 4.0 expanded memory through INT 67h. Expanded memory is
+//! banked into the real-mode address space through a 64KB page frame made of
+//! four 16KB physical pages. Many DOS games (e.g. Wing Commander, Ultima VII)
+//! require EMS rather than XMS.
+//!
+//! The expanded memory is backed by its own storage, like an expansion board
+//! (e.g. the Intel Above Board), rather than being carved out of extended memory.
+//!
+//! Implements the following features:
+//! - Page allocation, reallocation and deallocation per handle
+//! - Mapping logical pages into the page frame
+//! - Saving/restoring the page map (per handle and to/from memory)
+//! - Handle names and the handle directory
+
+/// EMS version number (4.0, BCD)
+const EMS_VERSION: u8 = 0x40;
+
+/// Size of an EMS page in bytes (16KB)
+pub const EMS_PAGE_SIZE: usize = 0x4000;
+
+/// Number of physical pages in the page frame
+pub const PHYSICAL_PAGES: usize = 4;
+
+/// Default page frame segment
+pub const DEFAULT_PAGE_FRAME: u16 = 0xE000;
+
+/// Default expanded memory size in KB
+pub const DEFAULT_EMS_KB: u32 = 2048;
+
+/// Largest expanded memory size supported by EMS 4.0 (32MB)
+pub const MAX_EMS_KB: u32 = 32 * 1024;
+
+/// Number of handles (handle 0 is reserved for the operating system)
+const MAX_HANDLES: usize = 255;
+
+/// Bytes needed to store the page map (handle and logical page per physical page)
+pub const PAGE_MAP_SIZE: usize = PHYSICAL_PAGES * 4;
+
+/// Handle value meaning "unmap this physical page"
+const UNMAP_PAGE: u16 = 0xFFFF;
+
+/// Check whether a segment can hold the 64KB page frame
+///
+/// The frame must be 16KB aligned and lie in the upper memory area below the
+/// BIOS (C000-EFFF).
+pub fn is_valid_page_frame(segment: u16) -> bool {
+    (0xC000..=0xE000).contains(&segment) && segment.is_multiple_of(0x0400)
+}
+
+/// EMS status codes (returned in AH)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum EmsError {
+    Success = 0x00,
+    InvalidHandle = 0x83,
+    UndefinedFunction = 0x84,
+    NoHandlesAvailable = 0x85,
+    MappingContextError = 0x86,
+    InsufficientTotalPages = 0x87,
+    InsufficientFreePages = 0x88,
+    ZeroPagesRequested = 0x89,
+    LogicalPageOutOfRange = 0x8A,
+    PhysicalPageOutOfRange = 0x8B,
+    ContextAlreadySaved = 0x8D,
+    NoSavedContext = 0x8E,
+    InvalidSubfunction = 0x8F,
+    HandleNameNotFound = 0xA0,
+    DuplicateHandleName = 0xA1,
+    InvalidPageMap = 0xA3,
+}
+
+/// Pages owned by a handle
+#[derive(Debug, Clone, Default)]
+struct EmsHandle {
+    /// EMS page numbers, indexed by logical page
+    pages: Vec<u16>,
+    /// Handle name (all zeros if unnamed)
+    name: [u8; 8],
+}
+
+/// A logical page mapped into a physical page of the frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Mapping {
+    handle: u16,
+    logical: u16,
+    /// EMS page backing the logical page
+    page: u16,
+}
+
+/// EMS Driver state
+pub struct EmsDriver {
+    /// Page frame segment
+    page_frame: u16,
+    /// Expanded memory storage (EMS_PAGE_SIZE bytes per page)
+    memory: Vec<u8>,
+    /// Whether each EMS page is allocated
+    allocated: Vec<bool>,
+    /// Allocated handles
+    handles: Vec<Option<EmsHandle>>,
+    /// Current contents of the page frame
+    mapping: [Option<Mapping>; PHYSICAL_PAGES],
+    /// Page maps saved with AH=47h, by handle
+    saved_maps: Vec<Option<[Option<Mapping>; PHYSICAL_PAGES]>>,
+}
+
+impl EmsDriver {
+    /// Create a new EMS driver with the given memory size and page frame
+    ///
+    /// The size is rounded down to whole 16KB pages and limited to 32MB.
+    pub fn new(size_kb: u32, page_frame: u16) -> Self {
+        let total_pages = (size_kb.min(MAX_EMS_KB) / 16) as usize;
+        let mut handles = vec![None; MAX_HANDLES];
+        // Handle 0 belongs to the operating system and always exists
+        handles[0] = Some(EmsHandle::default());
+        Self {
+            page_frame,
+            memory: vec![0; total_pages * EMS_PAGE_SIZE],
+            allocated: vec![false; total_pages],
+            handles,
+            mapping: [None; PHYSICAL_PAGES],
+            saved_maps: vec![None; MAX_HANDLES],
+        }
+    }
+
+    /// Free all handles and unmap the page frame (e.g. on reboot)
+    pub fn reset(&mut self) {
+        *self = Self::new(self.total_kb(), self.page_frame);
+    }
+
+    /// Get the EMS version (AH=46h)
+    pub fn get_version(&self) -> u8 {
+        EMS_VERSION
+    }
+
+    /// Get the page frame segment (AH=41h)
+    pub fn page_frame(&self) -> u16 {
+        self.page_frame
+    }
+
+    /// Total expanded memory in KB
+    pub fn total_kb(&self) -> u32 {
+        (self.allocated.len() * 16) as u32
+    }
+
+    /// Get unallocated and total page counts (AH=42h)
+    pub fn unallocated_pages(&self) -> (u16, u16) {
+        let free = self.allocated.iter().filter(|a| !**a).count();
+        (free as u16, self.allocated.len() as u16)
+    }
+
+    /// Allocate pages to a new handle (AH=43h, or AH=5Ah if `allow_zero`)
+    /// Returns: (handle, error_code)
+    pub fn allocate_pages(&mut self, count: u16, allow_zero: bool) -> (u16, EmsError) {
+        if count == 0 && !allow_zero {
+            return (0, EmsError::ZeroPagesRequested);
+        }
+        if count as usize > self.allocated.len() {
+            return (0, EmsError::InsufficientTotalPages);
+        }
+        if count > self.unallocated_pages().0 {
+            return (0, EmsError::InsufficientFreePages);
+        }
+        let Some(handle) = self.handles.iter().position(Option::is_none) else {
+            return (0, EmsError::NoHandlesAvailable);
+        };
+
+        let pages = self.take_free_pages(count as usize);
+        self.handles[handle] = Some(EmsHandle {
+            pages,
+            name: [0; 8],
+        });
+        (handle as u16, EmsError::Success)
+    }
+
+    /// Map a logical page of a handle into a physical page (AH=44h)
+    ///
+    /// A logical page of 0xFFFF unmaps the physical page.
+    pub fn map_page(&mut self, physical: u8, handle: u16, logical: u16) -> EmsError {
+        let physical = physical as usize;
+        if physical >= PHYSICAL_PAGES {
+            return EmsError::PhysicalPageOutOfRange;
+        }
+        let Some(owner) = self.handle(handle) else {
+            return EmsError::InvalidHandle;
+        };
+        if logical == UNMAP_PAGE {
+            self.mapping[physical] = None;
+            return EmsError::Success;
+        }
+        let Some(&page) = owner.pages.get(logical as usize) else {
+            return EmsError::LogicalPageOutOfRange;
+        };
+        self.mapping[physical] = Some(Mapping {
+            handle,
+            logical,
+            page,
+        });
+        EmsError::Success
+    }
+
+    /// Release a handle and its pages (AH=45h)
+    pub fn deallocate_pages(&mut self, handle: u16) -> EmsError {
+        if self.handle(handle).is_none() {
+            return EmsError::InvalidHandle;
+        }
+        if self.saved_maps[handle as usize].is_some() {
+            return EmsError::MappingContextError;
+        }
+        self.resize_handle(handle, 0);
+        // The operating system handle is never released, only emptied
+        if handle != 0 {
+            self.handles[handle as usize] = None;
+        }
+        EmsError::Success
+    }
+
+    /// Save the page map for a handle (AH=47h)
+    pub fn save_page_map(&mut self, handle: u16) -> EmsError {
+        if self.handle(handle).is_none() {
+            return EmsError::InvalidHandle;
+        }
+        let slot = &mut self.saved_maps[handle as usize];
+        if slot.is_some() {
+            return EmsError::ContextAlreadySaved;
+        }
+        *slot = Some(self.mapping);
+        EmsError::Success
+    }
+
+    /// Restore the page map saved for a handle (AH=48h)
+    pub fn restore_page_map(&mut self, handle: u16) -> EmsError {
+        if self.handle(handle).is_none() {
+            return EmsError::InvalidHandle;
+        }
+        match self.saved_maps[handle as usize].take() {
+            Some(saved) => {
+                self.mapping = saved;
+                EmsError::Success
+            }
+            None => EmsError::NoSavedContext,
+        }
+    }
+
+    /// Number of open handles, including the operating system handle (AH=4Bh)
+    pub fn handle_count(&self) -> u16 {
+        self.handles.iter().flatten().count() as u16
+    }
+
+    /// Number of pages owned by a handle (AH=4Ch)
+    /// Returns: (page_count, error_code)
+    pub fn handle_pages(&self, handle: u16) -> (u16, EmsError) {
+        match self.handle(handle) {
+            Some(owner) => (owner.pages.len() as u16, EmsError::Success),
+            None => (0, EmsError::InvalidHandle),
+        }
+    }
+
+    /// Handle and page count of every open handle (AH=4Dh)
+    pub fn all_handle_pages(&self) -> Vec<(u16, u16)> {
+        self.open_handles()
+            .map(|(handle, owner)| (handle, owner.pages.len() as u16))
+            .collect()
+    }
+
+    /// Current page map in the format used by AH=4Eh
+    pub fn get_page_map(&self) -> [u8; PAGE_MAP_SIZE] {
+        let mut map = [0; PAGE_MAP_SIZE];
+        for (entry, mapping) in map.chunks_exact_mut(4).zip(&self.mapping) {
+            let (handle, logical) =
+                mapping.map_or((UNMAP_PAGE, UNMAP_PAGE), |m| (m.handle, m.logical));
+            entry[..2].copy_from_slice(&handle.to_le_bytes());
+            entry[2..].copy_from_slice(&logical.to_le_bytes());
+        }
+        map
+    }
+
+    /// Restore a page map produced by `get_page_map` (AH=4Eh)
+    pub fn set_page_map(&mut self, map: &[u8; PAGE_MAP_SIZE]) -> EmsError {
+        let mut mapping = [None; PHYSICAL_PAGES];
+        for (slot, entry) in mapping.iter_mut().zip(map.chunks_exact(4)) {
+            let handle = u16::from_le_bytes([entry[0], entry[1]]);
+            let logical = u16::from_le_bytes([entry[2], entry[3]]);
+            if handle == UNMAP_PAGE {
+                continue;
+            }
+            let page = self
+                .handle(handle)
+                .and_then(|owner| owner.pages.get(logical as usize));
+            match page {
+                Some(&page) => {
+                    *slot = Some(Mapping {
+                        handle,
+                        logical,
+                        page,
+                    })
+                }
+                None => return EmsError::InvalidPageMap,
+            }
+        }
+        self.mapping = mapping;
+        EmsError::Success
+    }
+
+    /// Change the number of pages owned by a handle (AH=51h)
+    /// Returns: (page_count, error_code)
+    pub fn reallocate_pages(&mut self, handle: u16, count: u16) -> (u16, EmsError) {
+        let Some(owner) = self.handle(handle) else {
+            return (0, EmsError::InvalidHandle);
+        };
+        let current = owner.pages.len();
+        let count = count as usize;
+        if count > self.allocated.len() {
+            return (current as u16, EmsError::InsufficientTotalPages);
+        }
+        if count > current + self.unallocated_pages().0 as usize {
+            return (current as u16, EmsError::InsufficientFreePages);
+        }
+        self.resize_handle(handle, count);
+        (count as u16, EmsError::Success)
+    }
+
+    /// Get the name of a handle (AH=53h AL=00h)
+    /// Returns: (name, error_code)
+    pub fn handle_name(&self, handle: u16) -> ([u8; 8], EmsError) {
+        match self.handle(handle) {
+            Some(owner) => (owner.name, EmsError::Success),
+            None => ([0; 8], EmsError::InvalidHandle),
+        }
+    }
+
+    /// Set the name of a handle (AH=53h AL=01h)
+    pub fn set_handle_name(&mut self, handle: u16, name: [u8; 8]) -> EmsError {
+        if self.handle(handle).is_none() {
+            return EmsError::InvalidHandle;
+        }
+        let duplicate = name != [0; 8]
+            && self
+                .open_handles()
+                .any(|(other, owner)| other != handle && owner.name == name);
+        if duplicate {
+            return EmsError::DuplicateHandleName;
+        }
+        if let Some(owner) = self.handles[handle as usize].as_mut() {
+            owner.name = name;
+        }
+        EmsError::Success
+    }
+
+    /// Handle and name of every open handle (AH=54h AL=00h)
+    pub fn handle_directory(&self) -> Vec<(u16, [u8; 8])> {
+        self.open_handles()
+            .map(|(handle, owner)| (handle, owner.name))
+            .collect()
+    }
+
+    /// Find the handle with a given name (AH=54h AL=01h)
+    /// Returns: (handle, error_code)
+    pub fn search_handle_name(&self, name: [u8; 8]) -> (u16, EmsError) {
+        match self.open_handles().find(|(_, owner)| owner.name == name) {
+            Some((handle, _)) => (handle, EmsError::Success),
+            None => (0, EmsError::HandleNameNotFound),
+        }
+    }
+
+    /// Total number of handles (AH=54h AL=02h)
+    pub fn total_handles(&self) -> u16 {
+        MAX_HANDLES as u16
+    }
+
+    /// Segment and physical page number of each page frame page (AH=58h)
+    pub fn mappable_pages(&self) -> Vec<(u16, u16)> {
+        (0..PHYSICAL_PAGES as u16)
+            .map(|page| (self.page_frame + page * 0x0400, page))
+            .collect()
+    }
+
+    /// Read a byte from the page frame
+    ///
+    /// Returns `None` if the address is outside the page frame or its
+    /// physical page is unmapped.
+    pub fn read(&self, addr: u32) -> Option<u8> {
+        self.translate(addr).map(|offset| self.memory[offset])
+    }
+
+    /// Write a byte to the page frame
+    ///
+    /// Returns `false` if the address is outside the page frame or its
+    /// physical page is unmapped.
+    pub fn write(&mut self, addr: u32, val: u8) -> bool {
+        match self.translate(addr) {
+            Some(offset) => {
+                self.memory[offset] = val;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Offset into expanded memory for a linear address in the page frame
+    fn translate(&self, addr: u32) -> Option<usize> {
+        let frame_offset = addr.checked_sub((self.page_frame as u32) << 4)? as usize;
+        if frame_offset >= PHYSICAL_PAGES * EMS_PAGE_SIZE {
+            return None;
+        }
+        let mapping = self.mapping[frame_offset / EMS_PAGE_SIZE]?;
+        Some(mapping.page as usize * EMS_PAGE_SIZE + frame_offset % EMS_PAGE_SIZE)
+    }
+
+    fn handle(&self, handle: u16) -> Option<&EmsHandle> {
+        self.handles.get(handle as usize)?.as_ref()
+    }
+
+    fn open_handles(&self) -> impl Iterator<Item = (u16, &EmsHandle)> {
+        self.handles
+            .iter()
+            .enumerate()
+            .filter_map(|(handle, owner)| Some((handle as u16, owner.as_ref()?)))
+    }
+
+    /// Mark `count` free pages as allocated and return their numbers
+    fn take_free_pages(&mut self, count: usize) -> Vec<u16> {
+        let mut pages = Vec::with_capacity(count);
+        for (page, allocated) in self.allocated.iter_mut().enumerate() {
+            if pages.len() == count {
+                break;
+            }
+            if !*allocated {
+                *allocated = true;
+                pages.push(page as u16);
+            }
+        }
+        pages
+    }
+
+    /// Grow or shrink a handle, unmapping any page that no longer exists
+    ///
+    /// The caller must have checked that enough pages are free.
+    fn resize_handle(&mut self, handle: u16, count: usize) {
+        let current = self.handle(handle).map_or(0, |owner| owner.pages.len());
+        let added = if count > current {
+            self.take_free_pages(count - current)
+        } else {
+            Vec::new()
+        };
+        let Some(owner) = self.handles[handle as usize].as_mut() else {
+            return;
+        };
+        if count < current {
+            for page in owner.pages.drain(count..) {
+                self.allocated[page as usize] = false;
+            }
+        }
+        owner.pages.extend(added);
+
+        for slot in &mut self.mapping {
+            if slot.is_some_and(|m| m.handle == handle && m.logical as usize >= count) {
+                *slot = None;
+            }
+        }
+    }
+}
+
+impl Default for EmsDriver {
+    fn default() -> Self {
+        Self::new(DEFAULT_EMS_KB, DEFAULT_PAGE_FRAME)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAME: u32 = 0xE0000;
+
+    #[test]
+    fn test_ems_creation() {
+        let ems = EmsDriver::new(1024, 0xE000);
+        assert_eq!(ems.get_version(), 0x40);
+        assert_eq!(ems.page_frame(), 0xE000);
+        assert_eq!(ems.unallocated_pages(), (64, 64));
+        // Only the operating system handle is open
+        assert_eq!(ems.handle_count(), 1);
+    }
+
+    #[test]
+    fn test_allocate_and_deallocate() {
+        let mut ems = EmsDriver::new(256, 0xE000);
+
+        let (handle, error) = ems.allocate_pages(10, false);
+        assert_eq!(error, EmsError::Success);
+        assert_ne!(handle, 0);
+        assert_eq!(ems.unallocated_pages(), (6, 16));
+        assert_eq!(ems.handle_pages(handle), (10, EmsError::Success));
+
+        assert_eq!(
+            ems.allocate_pages(7, false).1,
+            EmsError::InsufficientFreePages
+        );
+        assert_eq!(
+            ems.allocate_pages(17, false).1,
+            EmsError::InsufficientTotalPages
+        );
+        assert_eq!(ems.allocate_pages(0, false).1, EmsError::ZeroPagesRequested);
+        assert_eq!(ems.allocate_pages(0, true).1, EmsError::Success);
+
+        assert_eq!(ems.deallocate_pages(handle), EmsError::Success);
+        assert_eq!(ems.unallocated_pages(), (16, 16));
+        assert_eq!(ems.deallocate_pages(handle), EmsError::InvalidHandle);
+    }
+
+    #[test]
+    fn test_page_mapping() {
+        let mut ems = EmsDriver::new(256, 0xE000);
+        let (handle, _) = ems.allocate_pages(2, false);
+
+        // Unmapped frame pages are not backed by expanded memory
+        assert_eq!(ems.read(FRAME), None);
+
+        assert_eq!(ems.map_page(0, handle, 0), EmsError::Success);
+        assert_eq!(ems.map_page(1, handle, 1), EmsError::Success);
+        assert!(ems.write(FRAME + 5, 0xAA));
+        assert!(ems.write(FRAME + 0x4005, 0xBB));
+
+        // Swap the two logical pages between physical pages 0 and 1
+        ems.map_page(0, handle, 1);
+        ems.map_page(1, handle, 0);
+        assert_eq!(ems.read(FRAME + 5), Some(0xBB));
+        assert_eq!(ems.read(FRAME + 0x4005), Some(0xAA));
+
+        // The same logical page can be mapped twice
+        ems.map_page(3, handle, 1);
+        assert_eq!(ems.read(FRAME + 0xC005), Some(0xBB));
+
+        assert_eq!(ems.map_page(4, handle, 0), EmsError::PhysicalPageOutOfRange);
+        assert_eq!(ems.map_page(0, handle, 2), EmsError::LogicalPageOutOfRange);
+        assert_eq!(ems.map_page(0, 99, 0), EmsError::InvalidHandle);
+
+        assert_eq!(ems.map_page(0, handle, 0xFFFF), EmsError::Success);
+        assert_eq!(ems.read(FRAME + 5), None);
+        assert_eq!(ems.read(FRAME + 0x10000), None);
+    }
+
+    #[test]
+    fn test_save_and_restore_page_map() {
+        let mut ems = EmsDriver::new(256, 0xE000);
+        let (handle, _) = ems.allocate_pages(2, false);
+        ems.map_page(0, handle, 0);
+
+        assert_eq!(ems.save_page_map(handle), EmsError::Success);
+        assert_eq!(ems.save_page_map(handle), EmsError::ContextAlreadySaved);
+        ems.map_page(0, handle, 1);
+        // A handle with a saved context cannot be released
+        assert_eq!(ems.deallocate_pages(handle), EmsError::MappingContextError);
+
+        assert_eq!(ems.restore_page_map(handle), EmsError::Success);
+        assert_eq!(ems.get_page_map()[..4], [handle as u8, 0, 0, 0]);
+        assert_eq!(ems.restore_page_map(handle), EmsError::NoSavedContext);
+
+        // Round trip through the AH=4Eh format
+        let map = ems.get_page_map();
+        ems.map_page(0, handle, 0xFFFF);
+        assert_eq!(ems.set_page_map(&map), EmsError::Success);
+        assert_eq!(ems.get_page_map(), map);
+    }
+
+    #[test]
+    fn test_reallocate_unmaps_removed_pages() {
+        let mut ems = EmsDriver::new(256, 0xE000);
+        let (handle, _) = ems.allocate_pages(4, false);
+        ems.map_page(0, handle, 3);
+
+        assert_eq!(ems.reallocate_pages(handle, 2), (2, EmsError::Success));
+        assert_eq!(ems.unallocated_pages().0, 14);
+        assert_eq!(ems.read(FRAME), None);
+
+        assert_eq!(ems.reallocate_pages(handle, 16), (16, EmsError::Success));
+        assert_eq!(ems.unallocated_pages().0, 0);
+        assert_eq!(
+            ems.reallocate_pages(handle, 17),
+            (16, EmsError::InsufficientTotalPages)
+        );
+    }
+
+    #[test]
+    fn test_handle_names() {
+        let mut ems = EmsDriver::new(256, 0xE000);
+        let (first, _) = ems.allocate_pages(1, false);
+        let (second, _) = ems.allocate_pages(1, false);
+
+        assert_eq!(ems.set_handle_name(first, *b"GAMEDATA"), EmsError::Success);
+        assert_eq!(
+            ems.set_handle_name(second, *b"GAMEDATA"),
+            EmsError::DuplicateHandleName
+        );
+        assert_eq!(ems.handle_name(first), (*b"GAMEDATA", EmsError::Success));
+        assert_eq!(
+            ems.search_handle_name(*b"GAMEDATA"),
+            (first, EmsError::Success)
+        );
+        assert_eq!(
+            ems.search_handle_name(*b"MISSING!").1,
+            EmsError::HandleNameNotFound
+        );
+        assert_eq!(ems.handle_directory().len(), 3);
+    }
+
+    #[test]
+    fn test_custom_page_frame() {
+        let mut ems = EmsDriver::new(64, 0xD000);
+        let (handle, _) = ems.allocate_pages(1, false);
+        ems.map_page(2, handle, 0);
+
+        assert!(ems.write(0xD8000, 0x42));
+        assert_eq!(ems.read(0xD8000), Some(0x42));
+        assert_eq!(ems.read(FRAME), None);
+        assert_eq!(ems.mappable_pages()[2], (0xD800, 2));
+
+        assert!(is_valid_page_frame(0xD000));
+        assert!(!is_valid_page_frame(0xE800));
+        assert!(!is_valid_page_frame(0xD100));
+    }
+
+    #[test]
+    fn test_reset_frees_everything() {
+        let mut ems = EmsDriver::new(128, 0xE000);
+        let (handle, _) = ems.allocate_pages(3, false);
+        ems.map_page(0, handle, 0);
+
+        ems.reset();
+        assert_eq!(ems.unallocated_pages(), (8, 8));
+        assert_eq!(ems.handle_count(), 1);
+        assert_eq!(ems.read(FRAME), None);
+    }
+}
@@ -0,0 +1,43 @@
+//! Entry points for the fuzz targets in `fuzz/`
+//!
+//! The disk and CD parsers are internal to the crate; these wrappers run
+//! them over arbitrary bytes the way the BIOS and built-in DOS do. Only built
+//! with the `fuzzing` feature.
+
+use crate::dos_fs::{FatVolume, ROOT_DIR};
+use crate::{disk, el_torito};
+
+/// Directory levels walked below the root
+const MAX_DEPTH: usize = 4;
+
+/// Parse a floppy or hard drive image: the boot sector geometry, then the
+/// FAT volume's directories, files, label and free space
+pub fn fat_image(image: &[u8]) {
+    disk::floppy_geometry(image);
+    let Ok(volume) = FatVolume::open(image) else {
+        return;
+    };
+    volume.label(image);
+    volume.free_bytes(image);
+    walk(&volume, image, ROOT_DIR, 0);
+}
+
+fn walk(volume: &FatVolume, image: &[u8], dir: u16, depth: usize) {
+    for entry in volume.list(image, dir) {
+        if entry.is_dir() {
+            // "." and ".." point back up the tree
+            if depth < MAX_DEPTH && entry.name[0] != b'.' {
+                walk(volume, image, entry.cluster, depth + 1);
+            }
+        } else {
+            volume.read(image, &entry);
+        }
+    }
+}
+
+/// Look for an El Torito boot entry and its boot image on a CD image
+pub fn el_torito_image(cd: &[u8]) {
+    if let Ok(entry) = el_torito::parse(cd) {
+        entry.image(cd);
+    }
+}
@@ -0,0 +1,154 @@
+//! IBM game control adapter (joystick port at 0x201)
+//!
+//! Writing any value to port 0x201 fires four one-shot timers, one per
+//! joystick axis. Each timer's output (bits 0-3: A X, A Y, B X, B Y) stays
+//! high for a time proportional to the axis potentiometer's resistance,
+//! so programs find the stick position by counting loop iterations until the
+//! bit drops. Bits 4-7 are the buttons (A1, A2, B1, B2), 0 while pressed.
+//!
+//! One-shot time: 24.2 us + 0.011 us per ohm, with a 0-100 kOhm stick. An
+//! axis with nothing connected never times out.
+
+/// Game port I/O address
+pub const GAME_PORT: u16 = 0x201;
+
+/// Resistance of a stick pushed all the way right or down
+const MAX_RESISTANCE_OHMS: f64 = 100_000.0;
+
+/// Joysticks plugged into the game port, as host input
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct JoystickState {
+    /// Axis positions from -32768 (left/up) to 32767 (right/down), in the
+    /// order joystick A X, A Y, B X, B Y
+    pub axes: [i16; 4],
+    /// Buttons held (bit 0 = A1, bit 1 = A2, bit 2 = B1, bit 3 = B2)
+    pub buttons: u8,
+}
+
+/// Game control adapter
+#[derive(Debug, Default)]
+pub struct GamePort {
+    joysticks: Option<JoystickState>,
+    /// Time since the one-shots were last fired (None before the first write)
+    elapsed_us: Option<f64>,
+}
+
+impl GamePort {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Plug in joysticks with the given state, or unplug them (`None`)
+    pub fn set_joysticks(&mut self, joysticks: Option<JoystickState>) {
+        self.joysticks = joysticks;
+    }
+
+    /// Fire the one-shots (any write to port 0x201)
+    pub fn trigger(&mut self) {
+        self.elapsed_us = Some(0.0);
+    }
+
+    /// Advance the one-shots by `us` microseconds
+    pub fn advance(&mut self, us: f64) {
+        if let Some(elapsed) = &mut self.elapsed_us {
+            // Far beyond the longest one-shot; stop counting
+            *elapsed = (*elapsed + us).min(1_000_000.0);
+        }
+    }
+
+    /// Read port 0x201
+    pub fn read(&self) -> u8 {
+        let Some(joysticks) = self.joysticks else {
+            // Axes never time out, no buttons pressed
+            return 0xFF;
+        };
+        let elapsed = self.elapsed_us.unwrap_or(f64::INFINITY);
+        let mut value = (!joysticks.buttons & 0x0F) << 4;
+        for (bit, &axis) in joysticks.axes.iter().enumerate() {
+            if elapsed < one_shot_us(axis) {
+                value |= 1 << bit;
+            }
+        }
+        value
+    }
+
+    /// Button bits as read from port 0x201 (bits 4-7, 0 = pressed), for
+    /// INT 15h AH=84h DX=0000h
+    pub fn switches(&self) -> u8 {
+        self.read() & 0xF0
+    }
+
+    /// Axis positions for INT 15h AH=84h DX=0001h, 0-255 with the stick
+    /// centered at about 128 (0 when no joystick is plugged in)
+    pub fn positions(&self) -> [u16; 4] {
+        match self.joysticks {
+            Some(joysticks) => joysticks
+                .axes
+                .map(|axis| ((axis as i32 + 32768) >> 8) as u16),
+            None => [0; 4],
+        }
+    }
+}
+
+/// One-shot time for a host axis value
+fn one_shot_us(axis: i16) -> f64 {
+    let ohms = (axis as f64 + 32768.0) / 65535.0 * MAX_RESISTANCE_OHMS;
+    24.2 + 0.011 * ohms
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Count how long each axis bit stays high, in microseconds
+    fn measure(port: &mut GamePort) -> [u32; 4] {
+        let mut times = [0; 4];
+        port.trigger();
+        for us in 0..2000 {
+            let value = port.read();
+            for (bit, time) in times.iter_mut().enumerate() {
+                if value & (1 << bit) != 0 {
+                    *time = us + 1;
+                }
+            }
+            port.advance(1.0);
+        }
+        times
+    }
+
+    #[test]
+    fn test_axis_timing_follows_position() {
+        let mut port = GamePort::new();
+        port.set_joysticks(Some(JoystickState {
+            axes: [i16::MIN, 0, i16::MAX, 0],
+            buttons: 0,
+        }));
+        let times = measure(&mut port);
+        assert_eq!(times[0], 25);
+        assert_eq!(times[1], 575);
+        assert_eq!(times[2], 1125);
+        assert_eq!(times[3], times[1]);
+    }
+
+    #[test]
+    fn test_buttons_are_active_low() {
+        let mut port = GamePort::new();
+        assert_eq!(port.read(), 0xFF);
+
+        port.set_joysticks(Some(JoystickState {
+            axes: [0; 4],
+            buttons: 0b0101,
+        }));
+        // Before the one-shots are fired the axis bits are low
+        assert_eq!(port.read(), 0xA0);
+        assert_eq!(port.switches(), 0xA0);
+        assert_eq!(port.positions(), [128; 4]);
+    }
+
+    #[test]
+    fn test_unplugged_axes_never_time_out() {
+        let mut port = GamePort::new();
+        assert_eq!(measure(&mut port), [2000; 4]);
+        assert_eq!(port.positions(), [0; 4]);
+    }
+}
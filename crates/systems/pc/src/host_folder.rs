@@ -0,0 +1,905 @@
+//! Host folder drives
+//!
+//! Exposes a directory on the host as a DOS drive by synthesizing a FAT image
+//! from its contents when it is mounted: FAT12 on a 1.44MB floppy, or FAT16 in
+//! a single partition on a hard drive with the geometry the BIOS reports for
+//! drive 80h. DOS sees an ordinary disk, so no INT 21h hooks are needed.
+//!
+//! Writes made by DOS only change the in-memory image. [`sync_host_folder`]
+//! copies new and modified files back to the host folder; deletions and
+//! renames are not propagated.
+//!
+//! Host names are converted to 8.3 names (upper case, invalid characters
+//! replaced, `NAME~1.EXT` for names that don't fit). Hidden files (names
+//! starting with a dot) are skipped.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub(crate) const SECTOR_SIZE: usize = 512;
+pub(crate) const DIR_ENTRY_SIZE: usize = 32;
+
+pub(crate) const ATTR_VOLUME_ID: u8 = 0x08;
+pub(crate) const ATTR_DIRECTORY: u8 = 0x10;
+pub(crate) const ATTR_ARCHIVE: u8 = 0x20;
+
+/// End-of-chain marker (masked to 12 or 16 bits when stored)
+pub(crate) const END_OF_CHAIN: u32 = 0xFFFF;
+
+/// Deepest folder nesting that is copied into the image
+const MAX_DEPTH: usize = 8;
+
+/// Hard drive geometry reported by INT 13h AH=08h for drive 80h
+const HD_CYLINDERS: usize = 306;
+const HD_HEADS: usize = 4;
+const HD_SECTORS_PER_TRACK: usize = 17;
+
+/// Shown by the master boot record if the BIOS tries to boot the drive
+const NOT_BOOTABLE_MESSAGE: &[u8] = b"Host folder drive is not bootable\r\n\0";
+
+/// Master boot record code: print the message above, then hand over to
+/// INT 18h (which starts the built-in DOS, if enabled) and halt
+///
+/// ```text
+/// xor ax,ax / mov ds,ax / xor bx,bx / mov si,7C19h
+/// next: lodsb / test al,al / jz done / mov ah,0Eh / int 10h / jmp next
+/// done: int 18h / halt: hlt / jmp halt
+/// ```
+const MBR_CODE: [u8; 25] = [
+    0x31, 0xC0, 0x8E, 0xD8, 0x31, 0xDB, 0xBE, 0x19, 0x7C, 0xAC, 0x84, 0xC0, 0x74, 0x06, 0xB4, 0x0E,
+    0xCD, 0x10, 0xEB, 0xF5, 0xCD, 0x18, 0xF4, 0xEB, 0xFD,
+];
+
+/// Kind of drive a host folder is exposed as
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HostDriveKind {
+    /// 1.44MB FAT12 floppy
+    Floppy,
+    /// 10MB FAT16 hard drive with one partition
+    HardDrive,
+}
+
+/// Errors that can occur when building or syncing a host folder drive
+#[derive(Debug, thiserror::Error)]
+pub enum HostFolderError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Folder contents do not fit on the drive ({0} bytes available)")]
+    TooLarge(usize),
+    #[error("Too many entries in the top-level folder ({count}, maximum {max})")]
+    RootDirectoryFull { count: usize, max: usize },
+    #[error("Disk image does not contain a FAT file system")]
+    NotFat,
+}
+
+/// Position and size of the FAT volume structures
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Layout {
+    /// Sectors before the volume boot sector (partition offset)
+    pub(crate) hidden_sectors: usize,
+    /// Sectors in the volume
+    total_sectors: usize,
+    sectors_per_cluster: usize,
+    reserved_sectors: usize,
+    pub(crate) fat_count: usize,
+    root_entries: usize,
+    sectors_per_fat: usize,
+    media: u8,
+    sectors_per_track: usize,
+    heads: usize,
+}
+
+impl Layout {
+    fn for_kind(kind: HostDriveKind) -> Self {
+        match kind {
+            HostDriveKind::Floppy => Self {
+                hidden_sectors: 0,
+                total_sectors: 2880,
+                sectors_per_cluster: 1,
+                reserved_sectors: 1,
+                fat_count: 2,
+                root_entries: 224,
+                sectors_per_fat: 9,
+                media: 0xF0,
+                sectors_per_track: 18,
+                heads: 2,
+            },
+            HostDriveKind::HardDrive => {
+                // Partition starts on the second track, like DOS FDISK
+                let hidden_sectors = HD_SECTORS_PER_TRACK;
+                let mut layout = Self {
+                    hidden_sectors,
+                    total_sectors: HD_CYLINDERS * HD_HEADS * HD_SECTORS_PER_TRACK - hidden_sectors,
+                    sectors_per_cluster: 4,
+                    reserved_sectors: 1,
+                    fat_count: 2,
+                    root_entries: 512,
+                    sectors_per_fat: 1,
+                    media: 0xF8,
+                    sectors_per_track: HD_SECTORS_PER_TRACK,
+                    heads: HD_HEADS,
+                };
+                // Grow the FAT until it can describe every data cluster
+                while (layout.cluster_count() + 2) * 2 > layout.sectors_per_fat * SECTOR_SIZE {
+                    layout.sectors_per_fat += 1;
+                }
+                layout
+            }
+        }
+    }
+
+    /// Read the layout from the boot sector of a floppy or the first
+    /// partition of a hard drive
+    pub(crate) fn from_image(image: &[u8]) -> Result<Self, HostFolderError> {
+        let first = image.get(..SECTOR_SIZE).ok_or(HostFolderError::NotFat)?;
+        let hidden_sectors = if matches!(first[0], 0xEB | 0xE9) {
+            0
+        } else if first[510..512] == [0x55, 0xAA] {
+            u32::from_le_bytes([first[0x1C6], first[0x1C7], first[0x1C8], first[0x1C9]]) as usize
+        } else {
+            return Err(HostFolderError::NotFat);
+        };
+
+        let start = hidden_sectors * SECTOR_SIZE;
+        let boot = image
+            .get(start..start + SECTOR_SIZE)
+            .ok_or(HostFolderError::NotFat)?;
+        let word = |offset: usize| u16::from_le_bytes([boot[offset], boot[offset + 1]]) as usize;
+        let total_sectors = match word(19) {
+            0 => u32::from_le_bytes([boot[32], boot[33], boot[34], boot[35]]) as usize,
+            n => n,
+        };
+        let layout = Self {
+            hidden_sectors,
+            total_sectors,
+            sectors_per_cluster: boot[13] as usize,
+            reserved_sectors: word(14),
+            fat_count: boot[16] as usize,
+            root_entries: word(17),
+            sectors_per_fat: word(22),
+            media: boot[21],
+            sectors_per_track: word(24),
+            heads: word(26),
+        };
+        if word(11) != SECTOR_SIZE
+            || layout.sectors_per_cluster == 0
+            || layout.fat_count == 0
+            || layout.sectors_per_fat == 0
+            || layout.data_start() > image.len()
+            || !layout.fat_covers_clusters()
+        {
+            return Err(HostFolderError::NotFat);
+        }
+        Ok(layout)
+    }
+
+    fn volume_start(&self) -> usize {
+        self.hidden_sectors * SECTOR_SIZE
+    }
+
+    pub(crate) fn fat_start(&self) -> usize {
+        self.volume_start() + self.reserved_sectors * SECTOR_SIZE
+    }
+
+    pub(crate) fn fat_bytes(&self) -> usize {
+        self.sectors_per_fat * SECTOR_SIZE
+    }
+
+    pub(crate) fn root_start(&self) -> usize {
+        self.fat_start() + self.fat_count * self.fat_bytes()
+    }
+
+    pub(crate) fn root_bytes(&self) -> usize {
+        self.root_entries * DIR_ENTRY_SIZE
+    }
+
+    fn data_start(&self) -> usize {
+        self.root_start() + self.root_bytes().div_ceil(SECTOR_SIZE) * SECTOR_SIZE
+    }
+
+    pub(crate) fn cluster_bytes(&self) -> usize {
+        self.sectors_per_cluster * SECTOR_SIZE
+    }
+
+    /// Number of data clusters
+    pub(crate) fn cluster_count(&self) -> usize {
+        let data_sectors = (self.volume_start() + self.total_sectors * SECTOR_SIZE)
+            .saturating_sub(self.data_start())
+            / SECTOR_SIZE;
+        data_sectors / self.sectors_per_cluster
+    }
+
+    /// The FAT has an entry for every data cluster (a corrupted boot sector
+    /// can claim more sectors than its FAT describes)
+    fn fat_covers_clusters(&self) -> bool {
+        let entries = self.cluster_count() + 2;
+        let needed = if self.is_fat16() {
+            entries * 2
+        } else {
+            (entries * 3).div_ceil(2)
+        };
+        needed <= self.fat_bytes()
+    }
+
+    /// FAT16 is used from 4085 clusters up, FAT12 below
+    pub(crate) fn is_fat16(&self) -> bool {
+        self.cluster_count() >= 4085
+    }
+
+    pub(crate) fn cluster_offset(&self, cluster: usize) -> usize {
+        self.data_start() + (cluster - 2) * self.cluster_bytes()
+    }
+}
+
+/// A file or folder found in the host folder
+#[derive(Debug)]
+struct HostEntry {
+    /// 8.3 name as stored in a directory entry
+    short_name: [u8; 11],
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    children: Option<Vec<HostEntry>>,
+}
+
+/// List a host folder recursively, assigning unique 8.3 names
+fn scan_folder(dir: &Path, depth: usize) -> io::Result<Vec<HostEntry>> {
+    let mut names: Vec<(String, PathBuf)> = fs::read_dir(dir)?
+        .filter_map(Result::ok)
+        .map(|e| (e.file_name().to_string_lossy().into_owned(), e.path()))
+        .filter(|(name, _)| !name.starts_with('.'))
+        .collect();
+    names.sort();
+
+    let mut entries: Vec<HostEntry> = Vec::new();
+    for (name, path) in names {
+        let meta = fs::metadata(&path)?;
+        let children = if meta.is_dir() {
+            if depth >= MAX_DEPTH {
+                continue;
+            }
+            Some(scan_folder(&path, depth + 1)?)
+        } else if meta.is_file() {
+            None
+        } else {
+            continue;
+        };
+        let used: Vec<[u8; 11]> = entries.iter().map(|e| e.short_name).collect();
+        entries.push(HostEntry {
+            short_name: short_name(&name, &used),
+            path,
+            modified: meta.modified().ok(),
+            children,
+        });
+    }
+    Ok(entries)
+}
+
+/// Upper-case a name component, keeping only characters valid in 8.3 names
+///
+/// Returns the converted bytes and whether anything besides case changed.
+fn clean_component(s: &str) -> (Vec<u8>, bool) {
+    let mut lossy = false;
+    let mut out = Vec::new();
+    for c in s.chars() {
+        let c = c.to_ascii_uppercase();
+        if c.is_ascii_alphanumeric() || "!#$%&'()-@^_`{}~".contains(c) {
+            out.push(c as u8);
+        } else {
+            lossy = true;
+            if c != ' ' && c != '.' {
+                out.push(b'_');
+            }
+        }
+    }
+    (out, lossy)
+}
+
+/// Pick an 8.3 name for a host file name that doesn't clash with `used`
+fn short_name(name: &str, used: &[[u8; 11]]) -> [u8; 11] {
+    let (base, ext) = match name.rfind('.') {
+        Some(i) if i > 0 => (&name[..i], &name[i + 1..]),
+        _ => (name, ""),
+    };
+    let (base, base_lossy) = clean_component(base);
+    let (ext, ext_lossy) = clean_component(ext);
+    let ext3 = &ext[..ext.len().min(3)];
+
+    let make = |base: &[u8]| {
+        let mut n = [b' '; 11];
+        n[..base.len()].copy_from_slice(base);
+        n[8..8 + ext3.len()].copy_from_slice(ext3);
+        n
+    };
+
+    if !base.is_empty() && !base_lossy && !ext_lossy && base.len() <= 8 && ext.len() <= 3 {
+        let n = make(&base);
+        if !used.contains(&n) {
+            return n;
+        }
+    }
+
+    // NAME~1.EXT, NAME~2.EXT, ... shortening the base as the suffix grows
+    let mut i = 1;
+    loop {
+        let suffix = format!("~{}", i);
+        let keep = (8 - suffix.len()).min(base.len());
+        let mut b = base[..keep].to_vec();
+        b.extend_from_slice(suffix.as_bytes());
+        let n = make(&b);
+        if !used.contains(&n) {
+            return n;
+        }
+        i += 1;
+    }
+}
+
+/// "NAME.EXT" form of an 8.3 directory entry name
+pub(crate) fn display_name(short_name: &[u8; 11]) -> String {
+    let base = String::from_utf8_lossy(&short_name[..8])
+        .trim_end()
+        .to_string();
+    let ext = String::from_utf8_lossy(&short_name[8..])
+        .trim_end()
+        .to_string();
+    if ext.is_empty() {
+        base
+    } else {
+        format!("{}.{}", base, ext)
+    }
+}
+
+/// DOS (time, date) for a host modification time, in UTC
+pub(crate) fn dos_timestamp(time: Option<SystemTime>) -> (u16, u16) {
+    let secs = time
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = secs / 86400 + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+
+    // DOS dates start in 1980
+    if year < 1980 {
+        return (0, (1 << 5) | 1);
+    }
+    let rem = secs % 86400;
+    let time = ((rem / 3600) << 11) | (((rem / 60) % 60) << 5) | ((rem % 60) / 2);
+    let date = ((year.min(2107) - 1980) << 9) | (month << 5) | day;
+    (time as u16, date as u16)
+}
+
+fn dir_entry(
+    short_name: &[u8; 11],
+    attr: u8,
+    cluster: u16,
+    size: u32,
+    modified: Option<SystemTime>,
+) -> [u8; DIR_ENTRY_SIZE] {
+    let mut entry = [0u8; DIR_ENTRY_SIZE];
+    entry[..11].copy_from_slice(short_name);
+    entry[11] = attr;
+    let (time, date) = dos_timestamp(modified);
+    entry[22..24].copy_from_slice(&time.to_le_bytes());
+    entry[24..26].copy_from_slice(&date.to_le_bytes());
+    entry[26..28].copy_from_slice(&cluster.to_le_bytes());
+    entry[28..32].copy_from_slice(&size.to_le_bytes());
+    entry
+}
+
+pub(crate) fn set_fat_entry(fat: &mut [u8], fat16: bool, cluster: usize, value: u32) {
+    if fat16 {
+        fat[cluster * 2..cluster * 2 + 2].copy_from_slice(&(value as u16).to_le_bytes());
+    } else {
+        let offset = cluster * 3 / 2;
+        let value = value & 0xFFF;
+        if cluster & 1 == 0 {
+            fat[offset] = value as u8;
+            fat[offset + 1] = (fat[offset + 1] & 0xF0) | (value >> 8) as u8;
+        } else {
+            fat[offset] = (fat[offset] & 0x0F) | ((value << 4) as u8);
+            fat[offset + 1] = (value >> 4) as u8;
+        }
+    }
+}
+
+pub(crate) fn fat_entry(fat: &[u8], fat16: bool, cluster: usize) -> usize {
+    if fat16 {
+        u16::from_le_bytes([fat[cluster * 2], fat[cluster * 2 + 1]]) as usize
+    } else {
+        let offset = cluster * 3 / 2;
+        let word = u16::from_le_bytes([fat[offset], fat[offset + 1]]) as usize;
+        if cluster & 1 == 0 {
+            word & 0xFFF
+        } else {
+            word >> 4
+        }
+    }
+}
+
+/// Writes files and folders into consecutive clusters of a new image
+struct ImageBuilder {
+    image: Vec<u8>,
+    layout: Layout,
+    /// Cluster chain values, indexed by cluster number
+    fat: Vec<u32>,
+    next_cluster: usize,
+}
+
+impl ImageBuilder {
+    /// Allocate a chain for `bytes` of data and copy it in; returns the first
+    /// cluster, or 0 for empty data
+    fn store(&mut self, data: &[u8]) -> Result<u16, HostFolderError> {
+        let count = data.len().div_ceil(self.layout.cluster_bytes());
+        if count == 0 {
+            return Ok(0);
+        }
+        let end = self.next_cluster + count;
+        if end > self.layout.cluster_count() + 2 {
+            return Err(HostFolderError::TooLarge(
+                self.layout.cluster_count() * self.layout.cluster_bytes(),
+            ));
+        }
+        let start = self.next_cluster;
+        for cluster in start..end {
+            self.fat[cluster] = if cluster + 1 == end {
+                END_OF_CHAIN
+            } else {
+                cluster as u32 + 1
+            };
+        }
+        let offset = self.layout.cluster_offset(start);
+        self.image[offset..offset + data.len()].copy_from_slice(data);
+        self.next_cluster = end;
+        Ok(start as u16)
+    }
+
+    /// Reserve clusters for a subdirectory with `entries` entries
+    fn reserve_directory(&mut self, entries: usize) -> Result<u16, HostFolderError> {
+        let bytes = (entries * DIR_ENTRY_SIZE).div_ceil(self.layout.cluster_bytes())
+            * self.layout.cluster_bytes();
+        self.store(&vec![0; bytes])
+    }
+
+    /// Store the contents of a folder and return its directory entries
+    fn write_folder(
+        &mut self,
+        entries: &[HostEntry],
+        cluster: u16,
+        parent: u16,
+        modified: Option<SystemTime>,
+    ) -> Result<Vec<u8>, HostFolderError> {
+        let mut dir = Vec::new();
+        if cluster != 0 {
+            dir.extend_from_slice(&dir_entry(
+                b".          ",
+                ATTR_DIRECTORY,
+                cluster,
+                0,
+                modified,
+            ));
+            dir.extend_from_slice(&dir_entry(
+                b"..         ",
+                ATTR_DIRECTORY,
+                parent,
+                0,
+                modified,
+            ));
+        }
+        for entry in entries {
+            match &entry.children {
+                Some(children) => {
+                    let start = self.reserve_directory(children.len() + 2)?;
+                    let contents = self.write_folder(children, start, cluster, entry.modified)?;
+                    let offset = self.layout.cluster_offset(start as usize);
+                    self.image[offset..offset + contents.len()].copy_from_slice(&contents);
+                    dir.extend_from_slice(&dir_entry(
+                        &entry.short_name,
+                        ATTR_DIRECTORY,
+                        start,
+                        0,
+                        entry.modified,
+                    ));
+                }
+                None => {
+                    let data = fs::read(&entry.path)?;
+                    let size = u32::try_from(data.len()).map_err(|_| {
+                        HostFolderError::TooLarge(
+                            self.layout.cluster_count() * self.layout.cluster_bytes(),
+                        )
+                    })?;
+                    let start = self.store(&data)?;
+                    dir.extend_from_slice(&dir_entry(
+                        &entry.short_name,
+                        ATTR_ARCHIVE,
+                        start,
+                        size,
+                        entry.modified,
+                    ));
+                }
+            }
+        }
+        Ok(dir)
+    }
+
+    /// Write the boot records and FATs and return the finished image
+    fn finish(mut self, kind: HostDriveKind) -> Vec<u8> {
+        let layout = self.layout.clone();
+        let fat16 = layout.is_fat16();
+
+        let boot = &mut self.image[layout.volume_start()..layout.volume_start() + SECTOR_SIZE];
+        write_boot_sector(boot, &layout, kind);
+
+        let mut fat = vec![0u8; layout.fat_bytes()];
+        set_fat_entry(&mut fat, fat16, 0, 0xFF00 | layout.media as u32);
+        set_fat_entry(&mut fat, fat16, 1, END_OF_CHAIN);
+        for (cluster, &value) in self.fat.iter().enumerate().skip(2) {
+            set_fat_entry(&mut fat, fat16, cluster, value);
+        }
+        for copy in 0..layout.fat_count {
+            let offset = layout.fat_start() + copy * layout.fat_bytes();
+            self.image[offset..offset + fat.len()].copy_from_slice(&fat);
+        }
+
+        if kind == HostDriveKind::HardDrive {
+            write_master_boot_record(&mut self.image[..SECTOR_SIZE], &layout);
+        }
+        self.image
+    }
+}
+
+/// Volume boot sector with a DOS 4.0 extended BIOS parameter block
+///
+/// The floppy boot sector has no 55AAh signature, so the BIOS skips it and
+/// boots from the next device instead.
+fn write_boot_sector(boot: &mut [u8], layout: &Layout, kind: HostDriveKind) {
+    boot[0..3].copy_from_slice(&[0xEB, 0x3C, 0x90]); // jmp short 3Eh / nop
+    boot[3..11].copy_from_slice(b"HEMULATR");
+    boot[11..13].copy_from_slice(&(SECTOR_SIZE as u16).to_le_bytes());
+    boot[13] = layout.sectors_per_cluster as u8;
+    boot[14..16].copy_from_slice(&(layout.reserved_sectors as u16).to_le_bytes());
+    boot[16] = layout.fat_count as u8;
+    boot[17..19].copy_from_slice(&(layout.root_entries as u16).to_le_bytes());
+    if layout.total_sectors < 0x10000 {
+        boot[19..21].copy_from_slice(&(layout.total_sectors as u16).to_le_bytes());
+    } else {
+        boot[32..36].copy_from_slice(&(layout.total_sectors as u32).to_le_bytes());
+    }
+    boot[21] = layout.media;
+    boot[22..24].copy_from_slice(&(layout.sectors_per_fat as u16).to_le_bytes());
+    boot[24..26].copy_from_slice(&(layout.sectors_per_track as u16).to_le_bytes());
+    boot[26..28].copy_from_slice(&(layout.heads as u16).to_le_bytes());
+    boot[28..32].copy_from_slice(&(layout.hidden_sectors as u32).to_le_bytes());
+    boot[36] = if kind == HostDriveKind::HardDrive {
+        0x80
+    } else {
+        0x00
+    };
+    boot[38] = 0x29; // Extended boot signature
+    boot[39..43].copy_from_slice(&0x484F_5354u32.to_le_bytes()); // Volume serial
+    boot[43..54].copy_from_slice(b"HOST FOLDER");
+    boot[54..62].copy_from_slice(if layout.is_fat16() {
+        b"FAT16   "
+    } else {
+        b"FAT12   "
+    });
+    boot[62..65].copy_from_slice(&[0xF4, 0xEB, 0xFD]); // hlt / jmp $-1
+    if kind == HostDriveKind::HardDrive {
+        boot[510..512].copy_from_slice(&[0x55, 0xAA]);
+    }
+}
+
+/// CHS address of a sector in the partition table's packed format
+fn packed_chs(lba: usize, layout: &Layout) -> [u8; 3] {
+    let cylinder = lba / (layout.heads * layout.sectors_per_track);
+    let head = (lba / layout.sectors_per_track) % layout.heads;
+    let sector = lba % layout.sectors_per_track + 1;
+    [
+        head as u8,
+        (sector as u8) | (((cylinder >> 8) as u8 & 0x03) << 6),
+        cylinder as u8,
+    ]
+}
+
+/// Master boot record with a single FAT16 partition
+fn write_master_boot_record(mbr: &mut [u8], layout: &Layout) {
+    mbr[..MBR_CODE.len()].copy_from_slice(&MBR_CODE);
+    mbr[MBR_CODE.len()..MBR_CODE.len() + NOT_BOOTABLE_MESSAGE.len()]
+        .copy_from_slice(NOT_BOOTABLE_MESSAGE);
+
+    let last = layout.hidden_sectors + layout.total_sectors - 1;
+    let entry = &mut mbr[0x1BE..0x1CE];
+    entry[0] = 0x00; // Not active
+    entry[1..4].copy_from_slice(&packed_chs(layout.hidden_sectors, layout));
+    entry[4] = 0x04; // FAT16, under 32MB
+    entry[5..8].copy_from_slice(&packed_chs(last, layout));
+    entry[8..12].copy_from_slice(&(layout.hidden_sectors as u32).to_le_bytes());
+    entry[12..16].copy_from_slice(&(layout.total_sectors as u32).to_le_bytes());
+    mbr[510..512].copy_from_slice(&[0x55, 0xAA]);
+}
+
+/// Build a disk image containing the files and folders of `dir`
+pub fn build_host_folder_image(
+    dir: &Path,
+    kind: HostDriveKind,
+) -> Result<Vec<u8>, HostFolderError> {
+    let layout = Layout::for_kind(kind);
+    let entries = scan_folder(dir, 0)?;
+    if entries.len() > layout.root_entries {
+        return Err(HostFolderError::RootDirectoryFull {
+            count: entries.len(),
+            max: layout.root_entries,
+        });
+    }
+
+    let size = (layout.hidden_sectors + layout.total_sectors) * SECTOR_SIZE;
+    let mut builder = ImageBuilder {
+        image: vec![0; size],
+        fat: vec![0; layout.cluster_count() + 2],
+        layout,
+        next_cluster: 2,
+    };
+    let root = builder.write_folder(&entries, 0, 0, None)?;
+    let root_start = builder.layout.root_start();
+    builder.image[root_start..root_start + root.len()].copy_from_slice(&root);
+    Ok(builder.finish(kind))
+}
+
+/// Read a cluster chain, stopping at the end marker or after `limit` bytes
+fn read_chain(image: &[u8], layout: &Layout, start: usize, limit: Option<usize>) -> Vec<u8> {
+    let fat = &image[layout.fat_start()..layout.fat_start() + layout.fat_bytes()];
+    let fat16 = layout.is_fat16();
+    let last_cluster = layout.cluster_count() + 1;
+
+    let mut data = Vec::new();
+    let mut cluster = start;
+    // Bound the walk so a corrupted (looping) chain still terminates
+    for _ in 0..layout.cluster_count() {
+        if !(2..=last_cluster).contains(&cluster) || limit.is_some_and(|l| data.len() >= l) {
+            break;
+        }
+        let offset = layout.cluster_offset(cluster);
+        match image.get(offset..offset + layout.cluster_bytes()) {
+            Some(bytes) => data.extend_from_slice(bytes),
+            None => break,
+        }
+        cluster = fat_entry(fat, fat16, cluster);
+    }
+    if let Some(limit) = limit {
+        data.truncate(limit);
+    }
+    data
+}
+
+/// Copy the files in a directory (and its subdirectories) to the host
+fn sync_directory(
+    image: &[u8],
+    layout: &Layout,
+    raw: &[u8],
+    dir: &Path,
+    host: &[HostEntry],
+    depth: usize,
+) -> Result<usize, HostFolderError> {
+    let mut written = 0;
+    for entry in raw.chunks_exact(DIR_ENTRY_SIZE) {
+        match entry[0] {
+            0x00 => break,    // End of directory
+            0xE5 => continue, // Deleted
+            b'.' => continue, // "." and ".."
+            _ => {}
+        }
+        let attr = entry[11];
+        if attr & ATTR_VOLUME_ID != 0 {
+            continue; // Volume label or long file name entry
+        }
+
+        let mut short_name = [0u8; 11];
+        short_name.copy_from_slice(&entry[..11]);
+        let cluster = u16::from_le_bytes([entry[26], entry[27]]) as usize;
+        let size = u32::from_le_bytes([entry[28], entry[29], entry[30], entry[31]]) as usize;
+
+        // Files that came from the host keep their original (long) names
+        let existing = host.iter().find(|e| e.short_name == short_name);
+        let path = existing
+            .map(|e| e.path.clone())
+            .unwrap_or_else(|| dir.join(display_name(&short_name)));
+
+        if attr & ATTR_DIRECTORY != 0 {
+            if depth >= MAX_DEPTH {
+                continue;
+            }
+            fs::create_dir_all(&path)?;
+            let children = existing.and_then(|e| e.children.as_deref()).unwrap_or(&[]);
+            let contents = read_chain(image, layout, cluster, None);
+            written += sync_directory(image, layout, &contents, &path, children, depth + 1)?;
+        } else {
+            let data = read_chain(image, layout, cluster, Some(size));
+            if fs::read(&path).map_or(true, |old| old != data) {
+                fs::write(&path, &data)?;
+                written += 1;
+            }
+        }
+    }
+    Ok(written)
+}
+
+/// Copy new and modified files from a host folder drive's image back to the
+/// folder it was built from
+///
+/// Returns the number of files written.
+pub fn sync_host_folder(image: &[u8], dir: &Path) -> Result<usize, HostFolderError> {
+    let layout = Layout::from_image(image)?;
+    let host = scan_folder(dir, 0)?;
+    let root = image
+        .get(layout.root_start()..layout.root_start() + layout.root_bytes())
+        .ok_or(HostFolderError::NotFat)?;
+    sync_directory(image, &layout, root, dir, &host, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("hemulator_host_folder_{}", name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Find a file in the root directory by 8.3 name and read it
+    fn read_root_file(image: &[u8], name: &[u8; 11]) -> Option<Vec<u8>> {
+        let layout = Layout::from_image(image).unwrap();
+        let root = &image[layout.root_start()..layout.root_start() + layout.root_bytes()];
+        root.chunks_exact(DIR_ENTRY_SIZE)
+            .find(|e| &e[..11] == name)
+            .map(|e| {
+                let cluster = u16::from_le_bytes([e[26], e[27]]) as usize;
+                let size = u32::from_le_bytes([e[28], e[29], e[30], e[31]]) as usize;
+                read_chain(image, &layout, cluster, Some(size))
+            })
+    }
+
+    #[test]
+    fn test_short_names() {
+        assert_eq!(&short_name("game.exe", &[]), b"GAME    EXE");
+        assert_eq!(&short_name("README", &[]), b"README     ");
+        assert_eq!(&short_name("LongFileName.text", &[]), b"LONGFI~1TEX");
+        assert_eq!(&short_name("my game.com", &[]), b"MYGAME~1COM");
+
+        let used = [*b"GAME    EXE"];
+        assert_eq!(&short_name("Game.exe", &used), b"GAME~1  EXE");
+        assert_eq!(display_name(b"GAME~1  EXE"), "GAME~1.EXE");
+    }
+
+    #[test]
+    fn test_dos_timestamp() {
+        // 2024-03-15 13:45:30 UTC
+        let time = UNIX_EPOCH + std::time::Duration::from_secs(1_710_510_330);
+        let (t, d) = dos_timestamp(Some(time));
+        assert_eq!(d, ((2024 - 1980) << 9) | (3 << 5) | 15);
+        assert_eq!(t, (13 << 11) | (45 << 5) | 15);
+    }
+
+    #[test]
+    fn test_fat12_entries_round_trip() {
+        let mut fat = vec![0u8; 16];
+        set_fat_entry(&mut fat, false, 2, 0x123);
+        set_fat_entry(&mut fat, false, 3, 0xABC);
+        set_fat_entry(&mut fat, false, 4, END_OF_CHAIN);
+        assert_eq!(fat_entry(&fat, false, 2), 0x123);
+        assert_eq!(fat_entry(&fat, false, 3), 0xABC);
+        assert_eq!(fat_entry(&fat, false, 4), 0xFFF);
+    }
+
+    #[test]
+    fn test_floppy_image_contains_files() {
+        let dir = test_dir("floppy");
+        let big: Vec<u8> = (0..2000u32).map(|i| i as u8).collect();
+        fs::write(dir.join("game.com"), [0xCD, 0x20]).unwrap();
+        fs::write(dir.join("data.bin"), &big).unwrap();
+        fs::write(dir.join(".hidden"), b"skip").unwrap();
+        fs::create_dir(dir.join("sub")).unwrap();
+        fs::write(dir.join("sub").join("inner.txt"), b"hello").unwrap();
+
+        let image = build_host_folder_image(&dir, HostDriveKind::Floppy).unwrap();
+        assert_eq!(image.len(), 1_474_560);
+        assert_eq!(&image[54..62], b"FAT12   ");
+        // No boot signature, so the BIOS falls through to the next drive
+        assert_ne!(&image[510..512], &[0x55, 0xAA]);
+
+        assert_eq!(
+            read_root_file(&image, b"GAME    COM"),
+            Some(vec![0xCD, 0x20])
+        );
+        assert_eq!(read_root_file(&image, b"DATA    BIN"), Some(big));
+        assert_eq!(read_root_file(&image, b"HIDDEN     "), None);
+
+        let layout = Layout::from_image(&image).unwrap();
+        assert_eq!(layout, Layout::for_kind(HostDriveKind::Floppy));
+        let root = &image[layout.root_start()..layout.root_start() + layout.root_bytes()];
+        let sub = root
+            .chunks_exact(DIR_ENTRY_SIZE)
+            .find(|e| &e[..11] == b"SUB        ")
+            .unwrap();
+        assert_eq!(sub[11], ATTR_DIRECTORY);
+        let cluster = u16::from_le_bytes([sub[26], sub[27]]) as usize;
+        let contents = read_chain(&image, &layout, cluster, None);
+        assert_eq!(&contents[..11], b".          ");
+        assert_eq!(&contents[64..75], b"INNER   TXT");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_hard_drive_image_is_partitioned_fat16() {
+        let dir = test_dir("hard_drive");
+        fs::write(dir.join("prog.exe"), b"MZ").unwrap();
+
+        let image = build_host_folder_image(&dir, HostDriveKind::HardDrive).unwrap();
+        assert_eq!(image.len(), 306 * 4 * 17 * SECTOR_SIZE);
+        assert_eq!(&image[510..512], &[0x55, 0xAA]);
+        assert_eq!(image[0x1C2], 0x04);
+
+        let layout = Layout::from_image(&image).unwrap();
+        assert_eq!(layout, Layout::for_kind(HostDriveKind::HardDrive));
+        assert!(layout.is_fat16());
+        assert_eq!(read_root_file(&image, b"PROG    EXE"), Some(b"MZ".to_vec()));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_folder_too_large() {
+        let dir = test_dir("too_large");
+        fs::write(dir.join("big.dat"), vec![0u8; 1_500_000]).unwrap();
+        assert!(matches!(
+            build_host_folder_image(&dir, HostDriveKind::Floppy),
+            Err(HostFolderError::TooLarge(_))
+        ));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_sync_writes_changed_and_new_files() {
+        let dir = test_dir("sync");
+        fs::write(dir.join("Config.txt"), b"old").unwrap();
+        fs::write(dir.join("keep.txt"), b"same").unwrap();
+        let mut image = build_host_folder_image(&dir, HostDriveKind::Floppy).unwrap();
+        let layout = Layout::from_image(&image).unwrap();
+
+        // Nothing changed yet
+        assert_eq!(sync_host_folder(&image, &dir).unwrap(), 0);
+
+        // Change CONFIG.TXT in place and add SAVE.DAT, as DOS would
+        let root_start = layout.root_start();
+        let config = image[root_start..]
+            .chunks_exact(DIR_ENTRY_SIZE)
+            .position(|e| &e[..11] == b"CONFIG  TXT")
+            .unwrap();
+        let entry = root_start + config * DIR_ENTRY_SIZE;
+        let cluster = u16::from_le_bytes([image[entry + 26], image[entry + 27]]) as usize;
+        let offset = layout.cluster_offset(cluster);
+        image[offset..offset + 3].copy_from_slice(b"new");
+
+        let free = root_start + 2 * DIR_ENTRY_SIZE;
+        let save = dir_entry(b"SAVE    DAT", ATTR_ARCHIVE, 0, 0, None);
+        image[free..free + DIR_ENTRY_SIZE].copy_from_slice(&save);
+
+        assert_eq!(sync_host_folder(&image, &dir).unwrap(), 2);
+        assert_eq!(fs::read(dir.join("Config.txt")).unwrap(), b"new");
+        assert_eq!(fs::read(dir.join("SAVE.DAT")).unwrap(), b"");
+        assert_eq!(fs::read(dir.join("keep.txt")).unwrap(), b"same");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,612 @@
+//! Intel 8042 keyboard controller and the keyboard behind it
+//!
+//! Ports:
+//! - 0x60: Data. Reading takes the output buffer; writing sends a byte to the
+//!   keyboard, or the parameter of the last controller command
+//! - 0x64: Status (read) / controller command (write)
+//!
+//! Key presses and releases become make and break codes, in scan code set 1
+//! when the controller translates (the default) and set 2 otherwise. Keys of
+//! the gray cursor block and right Ctrl/Alt are sent with an E0 prefix.
+//! Each byte loaded into the output buffer raises IRQ 1 while the command
+//! byte enables it, and the next byte follows once the previous one was read.
+//!
+//! The keyboard answers the usual host commands (LEDs, echo, identify, scan
+//! code set, typematic rate and delay, enable/disable, reset) and repeats
+//! the last key held down at the programmed typematic rate.
+
+use crate::keyboard::*;
+use emu_core::logging::{log, LogCategory, LogLevel};
+use std::cell::Cell;
+use std::collections::VecDeque;
+
+/// IRQ line of the keyboard (INT 09h)
+pub const KEYBOARD_IRQ: u8 = 1;
+
+/// Command byte: raise IRQ 1 when the output buffer fills
+const CMD_IRQ_ENABLE: u8 = 0x01;
+/// Command byte: system flag (reported in bit 2 of the status register)
+const CMD_SYSTEM_FLAG: u8 = 0x04;
+/// Command byte: keyboard interface disabled
+const CMD_KEYBOARD_DISABLED: u8 = 0x10;
+/// Command byte: auxiliary (mouse) interface disabled
+const CMD_AUX_DISABLED: u8 = 0x20;
+/// Command byte: translate set 2 codes from the keyboard to set 1
+const CMD_TRANSLATE: u8 = 0x40;
+/// Command byte after POST
+const DEFAULT_COMMAND_BYTE: u8 = CMD_TRANSLATE | CMD_SYSTEM_FLAG | CMD_IRQ_ENABLE;
+
+/// Status: output buffer full
+const STATUS_OUTPUT_FULL: u8 = 0x01;
+/// Status: input buffer full
+const STATUS_INPUT_FULL: u8 = 0x02;
+/// Status: last write was a command (port 64h) rather than data
+const STATUS_COMMAND: u8 = 0x08;
+/// Status: keyboard not inhibited
+const STATUS_UNLOCKED: u8 = 0x10;
+
+/// Output port bit 1 drives the A20 gate
+const OUTPUT_PORT_A20: u8 = 0x02;
+
+/// Keyboard replies
+const ACK: u8 = 0xFA;
+const RESEND: u8 = 0xFE;
+const SELF_TEST_PASSED: u8 = 0xAA;
+const ECHO: u8 = 0xEE;
+
+/// Typematic setting after reset: 500 ms delay, 10.9 characters per second
+const DEFAULT_TYPEMATIC: u8 = 0x2B;
+
+/// Time for the keyboard to clock one byte to the controller
+const BYTE_TIME_US: f64 = 1000.0;
+
+/// Most bytes the keyboard holds while the output buffer is full
+const MAX_QUEUED_BYTES: usize = 16;
+
+/// Scan code set 2 codes for the set 1 codes 00h-58h
+#[rustfmt::skip]
+const SET2_CODES: [u8; 0x59] = [
+    0x00, 0x76, 0x16, 0x1E, 0x26, 0x25, 0x2E, 0x36, // 00-07
+    0x3D, 0x3E, 0x46, 0x45, 0x4E, 0x55, 0x66, 0x0D, // 08-0F
+    0x15, 0x1D, 0x24, 0x2D, 0x2C, 0x35, 0x3C, 0x43, // 10-17
+    0x44, 0x4D, 0x54, 0x5B, 0x5A, 0x14, 0x1C, 0x1B, // 18-1F
+    0x23, 0x2B, 0x34, 0x33, 0x3B, 0x42, 0x4B, 0x4C, // 20-27
+    0x52, 0x0E, 0x12, 0x5D, 0x1A, 0x22, 0x21, 0x2A, // 28-2F
+    0x32, 0x31, 0x3A, 0x41, 0x49, 0x4A, 0x59, 0x7C, // 30-37
+    0x11, 0x29, 0x58, 0x05, 0x06, 0x04, 0x0C, 0x03, // 38-3F
+    0x0B, 0x83, 0x0A, 0x01, 0x09, 0x77, 0x7E, 0x6C, // 40-47
+    0x75, 0x7D, 0x7B, 0x6B, 0x73, 0x74, 0x79, 0x69, // 48-4F
+    0x72, 0x7A, 0x70, 0x71, 0x84, 0x00, 0x61, 0x78, // 50-57
+    0x07,                                           // 58
+];
+
+/// Set 1 code sent after an E0 prefix, for keys that have one
+///
+/// The gray cursor keys share their codes with the numeric keypad; right
+/// Ctrl and Alt share theirs with the left keys.
+fn extended_code(key: u8) -> Option<u8> {
+    match key {
+        SCANCODE_RIGHT_CTRL => Some(SCANCODE_LEFT_CTRL),
+        SCANCODE_RIGHT_ALT => Some(SCANCODE_LEFT_ALT),
+        SCANCODE_HOME | SCANCODE_UP | SCANCODE_PAGE_UP | SCANCODE_LEFT | SCANCODE_RIGHT
+        | SCANCODE_END | SCANCODE_DOWN | SCANCODE_PAGE_DOWN | SCANCODE_INSERT | SCANCODE_DELETE => {
+            Some(key)
+        }
+        _ => None,
+    }
+}
+
+/// Bytes the keyboard sends for a key, in scan code set 1 or 2
+pub fn scancode_bytes(key: u8, pressed: bool, set2: bool) -> Vec<u8> {
+    let (extended, code) = match extended_code(key) {
+        Some(code) => (true, code),
+        None => (false, key),
+    };
+    let mut bytes = Vec::with_capacity(3);
+    if extended {
+        bytes.push(0xE0);
+    }
+    if set2 {
+        if !pressed {
+            bytes.push(0xF0);
+        }
+        bytes.push(SET2_CODES.get(code as usize).copied().unwrap_or(0));
+    } else {
+        bytes.push(if pressed { code } else { code | 0x80 });
+    }
+    bytes
+}
+
+/// Key held down for typematic repeat
+struct HeldKey {
+    key: u8,
+    /// Time until the next repeat
+    remaining_us: f64,
+}
+
+/// 8042 keyboard controller with an attached AT keyboard
+pub struct KeyboardController {
+    /// Output buffer and its full flag (cleared by reading port 60h)
+    output: Cell<u8>,
+    output_full: Cell<bool>,
+    /// Input buffer full flag, cleared after one status read so software
+    /// polling for it sees the write being taken
+    input_full: Cell<bool>,
+    /// Whether the last write went to port 64h
+    last_was_command: Cell<bool>,
+    /// Controller command waiting for its parameter on port 60h (0 = none)
+    pending_command: u8,
+    command_byte: u8,
+    /// Output port (bit 0 = system reset, bit 1 = A20 gate)
+    output_port: u8,
+    /// Time before the keyboard can send the next byte
+    byte_wait_us: f64,
+    /// IRQ 1 raised since the last `take_irq_edge` call
+    irq_pending: bool,
+
+    /// Bytes the keyboard has not sent yet
+    queue: VecDeque<u8>,
+    /// Keyboard command waiting for its parameter
+    pending_keyboard_command: Option<u8>,
+    /// Last byte sent by the keyboard, for the resend command
+    last_sent: u8,
+    /// Scan code set selected on the keyboard (1 or 2)
+    scan_set: u8,
+    scanning: bool,
+    /// LED state (bit 0 = Scroll Lock, bit 1 = Num Lock, bit 2 = Caps Lock)
+    leds: u8,
+    /// Typematic rate (bits 0-4) and delay (bits 5-6)
+    typematic: u8,
+    held: Option<HeldKey>,
+}
+
+impl KeyboardController {
+    pub fn new() -> Self {
+        Self {
+            output: Cell::new(0),
+            output_full: Cell::new(false),
+            input_full: Cell::new(false),
+            last_was_command: Cell::new(false),
+            pending_command: 0,
+            command_byte: DEFAULT_COMMAND_BYTE,
+            output_port: OUTPUT_PORT_A20 | 0x01, // A20 enabled, not in reset
+            byte_wait_us: 0.0,
+            irq_pending: false,
+            queue: VecDeque::with_capacity(MAX_QUEUED_BYTES),
+            pending_keyboard_command: None,
+            last_sent: 0,
+            scan_set: 2,
+            scanning: true,
+            leds: 0,
+            typematic: DEFAULT_TYPEMATIC,
+            held: None,
+        }
+    }
+
+    /// Reset the controller and keyboard, keeping the A20 gate as it is
+    pub fn reset(&mut self) {
+        let output_port = self.output_port;
+        *self = Self::new();
+        self.output_port = output_port;
+    }
+
+    /// Read port 60h or 64h
+    pub fn io_read(&self, port: u16) -> u8 {
+        if port == 0x60 {
+            self.output_full.set(false);
+            return self.output.get();
+        }
+
+        let mut status = STATUS_UNLOCKED | (self.command_byte & CMD_SYSTEM_FLAG);
+        if self.output_full.get() {
+            status |= STATUS_OUTPUT_FULL;
+        }
+        if self.input_full.get() {
+            status |= STATUS_INPUT_FULL;
+        }
+        if self.last_was_command.get() {
+            status |= STATUS_COMMAND;
+        }
+        // The controller takes a written byte within microseconds
+        self.input_full.set(false);
+        status
+    }
+
+    /// Write the data port (60h)
+    ///
+    /// Returns the new A20 gate state when the byte was written to the
+    /// output port.
+    pub fn write_data(&mut self, val: u8) -> Option<bool> {
+        self.last_was_command.set(false);
+        self.input_full.set(true);
+
+        let command = std::mem::take(&mut self.pending_command);
+        log(LogCategory::Interrupts, LogLevel::Debug, || {
+            format!(
+                "KB controller data write: 0x{:02X} (command was 0x{:02X})",
+                val, command
+            )
+        });
+        match command {
+            0x60 => self.command_byte = val,
+            0xD1 => {
+                self.output_port = val;
+                return Some(val & OUTPUT_PORT_A20 != 0);
+            }
+            // Write keyboard output buffer, as if the keyboard had sent it
+            0xD2 => self.load_output(val),
+            // Write auxiliary output buffer / write to auxiliary device:
+            // no PS/2 mouse is attached
+            0xD3 | 0xD4 => {}
+            _ => self.keyboard_command(val),
+        }
+        None
+    }
+
+    /// Write the command port (64h)
+    ///
+    /// Returns the new A20 gate state for the commands that change it.
+    pub fn write_command(&mut self, val: u8) -> Option<bool> {
+        self.last_was_command.set(true);
+        self.input_full.set(false);
+        self.pending_command = 0;
+
+        log(LogCategory::Interrupts, LogLevel::Debug, || {
+            format!("KB controller command: 0x{:02X}", val)
+        });
+        match val {
+            0x20 => self.load_output(self.command_byte),
+            0x60 | 0xD1..=0xD4 => self.pending_command = val,
+            0xA7 => self.command_byte |= CMD_AUX_DISABLED,
+            0xA8 => self.command_byte &= !CMD_AUX_DISABLED,
+            // Interface tests pass, controller self test passes
+            0xA9 | 0xAB => self.load_output(0x00),
+            0xAA => self.load_output(0x55),
+            0xAD => self.command_byte |= CMD_KEYBOARD_DISABLED,
+            0xAE => self.command_byte &= !CMD_KEYBOARD_DISABLED,
+            // Input port: keyboard not inhibited
+            0xC0 => self.load_output(0x80),
+            0xD0 => self.load_output(self.output_port),
+            0xDD | 0xDF => {
+                if val == 0xDF {
+                    self.output_port |= OUTPUT_PORT_A20;
+                } else {
+                    self.output_port &= !OUTPUT_PORT_A20;
+                }
+                return Some(val == 0xDF);
+            }
+            0xE0 => self.load_output(0x00),
+            // Pulse output port lines (the reset line is not wired up)
+            0xF0..=0xFF => {}
+            _ => log(LogCategory::Stubs, LogLevel::Debug, || {
+                format!("KB controller command 0x{:02X} ignored", val)
+            }),
+        }
+        None
+    }
+
+    /// Press or release a key (simplified set 1 code from the `keyboard` module)
+    pub fn key_event(&mut self, key: u8, pressed: bool) {
+        if pressed && self.scanning {
+            self.held = Some(HeldKey {
+                key,
+                remaining_us: self.typematic_delay_us(),
+            });
+        } else if self.held.as_ref().is_some_and(|held| held.key == key) {
+            self.held = None;
+        }
+        self.send_key(key, pressed);
+    }
+
+    /// Advance by `us` microseconds of emulated time
+    ///
+    /// Moves the next byte from the keyboard into the output buffer and
+    /// returns the held key when it repeats.
+    pub fn advance(&mut self, us: f64) -> Option<u8> {
+        self.byte_wait_us = (self.byte_wait_us - us).max(0.0);
+
+        let rate_us = self.typematic_rate_us();
+        let repeated = self.held.as_mut().and_then(|held| {
+            held.remaining_us -= us;
+            if held.remaining_us > 0.0 {
+                return None;
+            }
+            held.remaining_us = rate_us;
+            Some(held.key)
+        });
+        if let Some(key) = repeated {
+            self.send_key(key, true);
+        }
+
+        self.load_from_keyboard();
+        repeated
+    }
+
+    /// Whether IRQ 1 was raised since the last call
+    pub fn take_irq_edge(&mut self) -> bool {
+        std::mem::take(&mut self.irq_pending)
+    }
+
+    /// LED state set by the host (bit 0 = Scroll Lock, bit 1 = Num Lock,
+    /// bit 2 = Caps Lock)
+    pub fn leds(&self) -> u8 {
+        self.leds
+    }
+
+    /// Scan code set the guest reads from port 60h
+    pub fn scan_code_set(&self) -> u8 {
+        if self.command_byte & CMD_TRANSLATE != 0 {
+            1
+        } else {
+            self.scan_set
+        }
+    }
+
+    fn send_key(&mut self, key: u8, pressed: bool) {
+        if !self.scanning {
+            return;
+        }
+        for byte in scancode_bytes(key, pressed, self.scan_code_set() == 2) {
+            // A keyboard whose buffer is full drops further keys
+            if self.queue.len() < MAX_QUEUED_BYTES {
+                self.queue.push_back(byte);
+            }
+        }
+        self.load_from_keyboard();
+    }
+
+    /// Reply to the host, discarding key bytes not sent yet
+    fn reply(&mut self, bytes: &[u8]) {
+        self.queue.clear();
+        self.queue.extend(bytes);
+        self.load_from_keyboard();
+    }
+
+    /// Handle a byte sent to the keyboard
+    fn keyboard_command(&mut self, val: u8) {
+        if let Some(command) = self.pending_keyboard_command.take() {
+            match command {
+                0xED => self.leds = val & 0x07,
+                0xF0 if val == 0 => {
+                    // Report the current set, translated like any other byte
+                    let id = match (self.command_byte & CMD_TRANSLATE != 0, self.scan_set) {
+                        (true, 1) => 0x43,
+                        (true, _) => 0x41,
+                        (false, set) => set,
+                    };
+                    self.reply(&[ACK, id]);
+                    return;
+                }
+                0xF0 if matches!(val, 1 | 2) => self.scan_set = val,
+                0xF3 => self.typematic = val & 0x7F,
+                _ => {}
+            }
+            self.reply(&[ACK]);
+            return;
+        }
+
+        match val {
+            0xED | 0xF0 | 0xF3 => {
+                self.pending_keyboard_command = Some(val);
+                self.reply(&[ACK]);
+            }
+            0xEE => self.reply(&[ECHO]),
+            0xF2 => {
+                let id = if self.command_byte & CMD_TRANSLATE != 0 {
+                    0x41
+                } else {
+                    0x83
+                };
+                self.reply(&[ACK, 0xAB, id]);
+            }
+            0xF4 => {
+                self.scanning = true;
+                self.reply(&[ACK]);
+            }
+            0xF5 | 0xF6 => {
+                self.set_keyboard_defaults();
+                self.scanning = val == 0xF6;
+                self.reply(&[ACK]);
+            }
+            0xFE => self.reply(&[self.last_sent]),
+            0xFF => {
+                self.set_keyboard_defaults();
+                self.scanning = true;
+                self.reply(&[ACK, SELF_TEST_PASSED]);
+            }
+            _ => self.reply(&[RESEND]),
+        }
+    }
+
+    fn set_keyboard_defaults(&mut self) {
+        self.scan_set = 2;
+        self.typematic = DEFAULT_TYPEMATIC;
+        self.held = None;
+    }
+
+    /// Move the next keyboard byte into an empty output buffer
+    fn load_from_keyboard(&mut self) {
+        if self.output_full.get()
+            || self.byte_wait_us > 0.0
+            || self.command_byte & CMD_KEYBOARD_DISABLED != 0
+        {
+            return;
+        }
+        if let Some(byte) = self.queue.pop_front() {
+            self.last_sent = byte;
+            self.byte_wait_us = BYTE_TIME_US;
+            self.load_output(byte);
+        }
+    }
+
+    fn load_output(&mut self, byte: u8) {
+        self.output.set(byte);
+        self.output_full.set(true);
+        if self.command_byte & CMD_IRQ_ENABLE != 0 {
+            self.irq_pending = true;
+        }
+    }
+
+    /// Delay before a held key starts repeating (250-1000 ms)
+    fn typematic_delay_us(&self) -> f64 {
+        250_000.0 * (((self.typematic >> 5) & 0x03) as f64 + 1.0)
+    }
+
+    /// Interval between repeats (2-30 characters per second)
+    fn typematic_rate_us(&self) -> f64 {
+        let a = (self.typematic & 0x07) as f64;
+        let b = ((self.typematic >> 3) & 0x03) as u32;
+        (8.0 + a) * (1u32 << b) as f64 * 4170.0
+    }
+}
+
+impl Default for KeyboardController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Read every byte the keyboard sends, as an IRQ 1 handler would
+    fn drain(kbc: &mut KeyboardController) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        loop {
+            kbc.advance(BYTE_TIME_US);
+            if kbc.io_read(0x64) & STATUS_OUTPUT_FULL == 0 {
+                return bytes;
+            }
+            assert!(kbc.take_irq_edge());
+            bytes.push(kbc.io_read(0x60));
+        }
+    }
+
+    #[test]
+    fn test_make_and_break_codes_in_set_1() {
+        let mut kbc = KeyboardController::new();
+        kbc.key_event(SCANCODE_A, true);
+        kbc.key_event(SCANCODE_A, false);
+        kbc.key_event(SCANCODE_UP, true);
+        kbc.key_event(SCANCODE_UP, false);
+        kbc.key_event(SCANCODE_RIGHT_CTRL, true);
+        assert_eq!(
+            drain(&mut kbc),
+            [0x1E, 0x9E, 0xE0, 0x48, 0xE0, 0xC8, 0xE0, 0x1D]
+        );
+    }
+
+    #[test]
+    fn test_set_2_without_translation() {
+        let mut kbc = KeyboardController::new();
+        kbc.write_command(0x60);
+        kbc.write_data(CMD_IRQ_ENABLE | CMD_SYSTEM_FLAG);
+        assert_eq!(kbc.scan_code_set(), 2);
+
+        kbc.key_event(SCANCODE_A, true);
+        kbc.key_event(SCANCODE_A, false);
+        kbc.key_event(SCANCODE_HOME, true);
+        kbc.key_event(SCANCODE_HOME, false);
+        assert_eq!(
+            drain(&mut kbc),
+            [0x1C, 0xF0, 0x1C, 0xE0, 0x6C, 0xE0, 0xF0, 0x6C]
+        );
+    }
+
+    #[test]
+    fn test_next_byte_waits_for_read() {
+        let mut kbc = KeyboardController::new();
+        kbc.key_event(SCANCODE_END, true);
+        assert!(kbc.take_irq_edge());
+        kbc.advance(BYTE_TIME_US * 4.0);
+        assert!(!kbc.take_irq_edge());
+        assert_eq!(kbc.io_read(0x60), 0xE0);
+        // Port 60h keeps the last byte until the next one arrives
+        assert_eq!(kbc.io_read(0x60), 0xE0);
+        kbc.advance(BYTE_TIME_US);
+        assert!(kbc.take_irq_edge());
+        assert_eq!(kbc.io_read(0x60), 0x4F);
+    }
+
+    #[test]
+    fn test_keyboard_commands() {
+        let mut kbc = KeyboardController::new();
+
+        // Set LEDs: ACK for the command and for its parameter
+        kbc.write_data(0xED);
+        assert_eq!(drain(&mut kbc), [ACK]);
+        kbc.write_data(0x05);
+        assert_eq!(drain(&mut kbc), [ACK]);
+        assert_eq!(kbc.leds(), 0x05);
+
+        kbc.write_data(0xEE);
+        assert_eq!(drain(&mut kbc), [ECHO]);
+        kbc.write_data(0xF2);
+        assert_eq!(drain(&mut kbc), [ACK, 0xAB, 0x41]);
+        kbc.write_data(0xF0);
+        kbc.write_data(0x00);
+        assert_eq!(drain(&mut kbc), [ACK, ACK, 0x41]);
+        kbc.write_data(0xFF);
+        assert_eq!(drain(&mut kbc), [ACK, SELF_TEST_PASSED]);
+        kbc.write_data(0x12);
+        assert_eq!(drain(&mut kbc), [RESEND]);
+
+        // Disabled scanning sends no keys
+        kbc.write_data(0xF5);
+        drain(&mut kbc);
+        kbc.key_event(SCANCODE_A, true);
+        assert!(drain(&mut kbc).is_empty());
+    }
+
+    #[test]
+    fn test_controller_commands() {
+        let mut kbc = KeyboardController::new();
+        kbc.write_command(0x20);
+        assert_eq!(kbc.io_read(0x60), DEFAULT_COMMAND_BYTE);
+        kbc.write_command(0xAA);
+        assert_eq!(kbc.io_read(0x60), 0x55);
+
+        // A20 through the output port
+        kbc.write_command(0xD1);
+        assert_eq!(kbc.write_data(0x01), Some(false));
+        kbc.write_command(0xD0);
+        assert_eq!(kbc.io_read(0x60), 0x01);
+        assert_eq!(kbc.write_command(0xDF), Some(true));
+
+        // Status: input buffer full until the controller takes the byte
+        kbc.write_command(0xD1);
+        kbc.write_data(0x03);
+        assert_eq!(kbc.io_read(0x64), 0x16);
+        assert_eq!(kbc.io_read(0x64), 0x14);
+
+        // With the keyboard disabled, bytes wait in the keyboard
+        kbc.write_command(0xAD);
+        kbc.key_event(SCANCODE_A, true);
+        kbc.advance(BYTE_TIME_US);
+        assert_eq!(kbc.io_read(0x64) & STATUS_OUTPUT_FULL, 0);
+        kbc.write_command(0xAE);
+        assert_eq!(drain(&mut kbc), [0x1E]);
+    }
+
+    #[test]
+    fn test_typematic_repeat() {
+        let mut kbc = KeyboardController::new();
+        // 250 ms delay, 30 characters per second
+        kbc.write_data(0xF3);
+        kbc.write_data(0x00);
+        drain(&mut kbc);
+
+        kbc.key_event(SCANCODE_B, true);
+        assert_eq!(drain(&mut kbc), [0x30]);
+        assert_eq!(kbc.advance(240_000.0), None);
+        assert_eq!(kbc.advance(10_000.0), Some(SCANCODE_B));
+        assert_eq!(kbc.advance(33_000.0), None);
+        assert_eq!(kbc.advance(400.0), Some(SCANCODE_B));
+        assert_eq!(drain(&mut kbc), [0x30, 0x30]);
+
+        kbc.key_event(SCANCODE_B, false);
+        assert_eq!(kbc.advance(1_000_000.0), None);
+        assert_eq!(drain(&mut kbc), [0xB0]);
+    }
+}
@@ -118,9 +118,12 @@ impl Keyboard {
 
     /// Check if ESC key is in the buffer (for boot abort)
     pub fn has_esc(&self) -> bool {
-        self.scancode_buffer
-            .iter()
-            .any(|&code| code == SCANCODE_ESC)
+        self.has_key(SCANCODE_ESC)
+    }
+
+    /// Check if a key's make code is in the buffer (for POST hotkeys)
+    pub fn has_key(&self, key: u8) -> bool {
+        self.scancode_buffer.iter().any(|&code| code == key)
     }
 
     /// Get the current shift flags (for INT 16h AH=02h)
@@ -224,7 +227,20 @@ pub const SCANCODE_F7: u8 = 0x41;
 pub const SCANCODE_F8: u8 = 0x42;
 pub const SCANCODE_F9: u8 = 0x43;
 pub const SCANCODE_F10: u8 = 0x44;
-// Extended scancodes (normally E0-prefixed, but we use simplified values)
+pub const SCANCODE_HOME: u8 = 0x47;
+pub const SCANCODE_UP: u8 = 0x48;
+pub const SCANCODE_PAGE_UP: u8 = 0x49;
+pub const SCANCODE_LEFT: u8 = 0x4B;
+pub const SCANCODE_RIGHT: u8 = 0x4D;
+pub const SCANCODE_END: u8 = 0x4F;
+pub const SCANCODE_DOWN: u8 = 0x50;
+pub const SCANCODE_PAGE_DOWN: u8 = 0x51;
+pub const SCANCODE_INSERT: u8 = 0x52;
+pub const SCANCODE_DELETE: u8 = 0x53;
+pub const SCANCODE_F11: u8 = 0x57;
+pub const SCANCODE_F12: u8 = 0x58;
+// Right Ctrl/Alt have no set 1 code of their own; the keyboard controller
+// (kbc.rs) sends them as E0 1D / E0 38, like the gray cursor keys above
 pub const SCANCODE_RIGHT_CTRL: u8 = 0x5D; // Right Ctrl (extended scancode E0 1D)
 pub const SCANCODE_RIGHT_ALT: u8 = 0x5E; // Right Alt/AltGr (extended scancode E0 38)
 
@@ -247,6 +263,20 @@ pub fn sdl2_scancode_to_pc(sdl_scancode: u32) -> Option<u8> {
         65 => Some(SCANCODE_F8),  // SDL_SCANCODE_F8
         66 => Some(SCANCODE_F9),  // SDL_SCANCODE_F9
         67 => Some(SCANCODE_F10), // SDL_SCANCODE_F10
+        68 => Some(SCANCODE_F11), // SDL_SCANCODE_F11
+        69 => Some(SCANCODE_F12), // SDL_SCANCODE_F12
+        // Arrow keys
+        82 => Some(SCANCODE_UP),    // SDL_SCANCODE_UP
+        81 => Some(SCANCODE_DOWN),  // SDL_SCANCODE_DOWN
+        80 => Some(SCANCODE_LEFT),  // SDL_SCANCODE_LEFT
+        79 => Some(SCANCODE_RIGHT), // SDL_SCANCODE_RIGHT
+        // Navigation block
+        73 => Some(SCANCODE_INSERT),    // SDL_SCANCODE_INSERT
+        74 => Some(SCANCODE_HOME),      // SDL_SCANCODE_HOME
+        75 => Some(SCANCODE_PAGE_UP),   // SDL_SCANCODE_PAGEUP
+        76 => Some(SCANCODE_DELETE),    // SDL_SCANCODE_DELETE
+        77 => Some(SCANCODE_END),       // SDL_SCANCODE_END
+        78 => Some(SCANCODE_PAGE_DOWN), // SDL_SCANCODE_PAGEDOWN
         // Number row
         39 => Some(SCANCODE_0), // SDL_SCANCODE_0
         30 => Some(SCANCODE_1), // SDL_SCANCODE_1
@@ -2,24 +2,62 @@
 //!
 //! This module provides a basic IBM PC/XT compatible emulator using the 8086 CPU core.
 //! It supports loading and running DOS executables (.COM and .EXE files).
+//! Audio is produced by an AdLib (OPL2) FM synthesizer and a Sound Blaster DSP.
+//!
+//! # Usage Example
+//!
+//! ```
+//! use emu_core::System;
+//! use emu_pc::PcSystem;
+//!
+//! let mut pc = PcSystem::new();
+//! // Media slots: BIOS, FloppyA, FloppyB, HardDrive
+//! assert!(pc.mount_points().iter().any(|mp| mp.id == "FloppyA"));
+//!
+//! // Without a boot disk the built-in BIOS shows its POST screen
+//! let frame = pc.step_frame().unwrap();
+//! assert_eq!(frame.pixels.len(), (frame.width * frame.height) as usize);
+//! ```
 
 #![allow(clippy::upper_case_acronyms)]
 
 mod bios;
 mod bus;
+mod cassette; // IBM PC cassette interface (INT 15h AH=00h-03h)
 mod cpu;
 mod disk;
+mod dma; // Intel 8237 DMA controller
+mod dos; // Built-in DOS shell and INT 21h file services when no disk boots
+mod dos_fs; // FAT file access for the built-in DOS
 mod dpmi; // DPMI (DOS Protected Mode Interface) driver
+mod el_torito; // El Torito bootable CD-ROM images
+mod ems; // EMS (LIM 4.0 Expanded Memory Specification) driver
 mod font; // Shared IBM PC ROM font data
+#[cfg(feature = "fuzzing")]
+pub mod fuzzing; // Parser entry points for the fuzz targets
+mod game_port; // Game control adapter (joysticks at port 201h)
+mod host_folder; // Host directories exposed as synthesized FAT drives
+mod kbc; // Intel 8042 keyboard controller (ports 60h/64h, scan code sets 1/2)
 mod keyboard;
 mod mouse; // Microsoft Mouse Driver (INT 33h)
+mod opl2; // Yamaha YM3812 (OPL2) FM synthesis (AdLib)
+mod parallel; // Parallel printer port (LPT1)
+mod pic; // Programmable Interrupt Controller (8259A)
 mod pit; // Programmable Interval Timer (8253/8254)
+mod printer; // Host capture files for the printer port
+mod rtc; // MC146818 real-time clock and CMOS RAM
+mod serial; // 8250 UART (COM1/COM2)
+mod serial_host; // Host TCP and pty connections for the serial ports
+mod sound_blaster; // Sound Blaster 2.0 DSP (digital audio)
+mod speaker; // PC speaker (PIT channel 2)
+mod vga; // VGA 256-color planar memory (mode 13h, Mode X)
 mod video;
 mod video_adapter;
 mod video_adapter_cga_graphics; // CGA graphics modes with mode switching
 mod video_adapter_ega_hardware; // EGA hardware renderer (OpenGL stub)
 mod video_adapter_ega_software; // EGA software renderer
 mod video_adapter_hardware; // Example stub for hardware-accelerated rendering
+mod video_adapter_hercules; // Hercules/MDA monochrome text and 720x348 graphics
 mod video_adapter_software;
 mod video_adapter_vga_hardware; // VGA hardware renderer (OpenGL stub)
 mod video_adapter_vga_software; // VGA software renderer
@@ -30,21 +68,34 @@ use bus::PcBus;
 use cpu::PcCpu;
 use emu_core::{
     cpu_8086::{CpuModel, Memory8086},
+    frame_pool::{FramePool, FramePoolStats},
     types::Frame,
     MountPointInfo, System,
 };
 use serde_json::Value;
+use std::time::{Duration, Instant};
 use thiserror::Error;
 pub use video_adapter::VideoAdapter;
+use video_adapter_hercules::{HERCULES_GRAPHICS_HEIGHT, HERCULES_GRAPHICS_WIDTH};
 pub use video_adapter_software::SoftwareCgaAdapter;
 
 pub use bios::BootPriority; // Export boot priority
 pub use bus::VideoAdapterType; // Export video adapter type
-pub use disk::{create_blank_floppy, create_blank_hard_drive, FloppyFormat, HardDriveFormat}; // Export disk utilities for GUI
+pub use disk::{
+    create_blank_floppy, create_blank_hard_drive, floppy_geometry, FloppyFormat, HardDriveFormat,
+}; // Export disk utilities for GUI
+pub use ems::{DEFAULT_EMS_KB, DEFAULT_PAGE_FRAME as DEFAULT_EMS_PAGE_FRAME}; // EMS defaults for GUI configuration
 pub use emu_core::cpu_8086::CpuModel as PcCpuModel; // Re-export for external use
+pub use game_port::JoystickState; // Joystick input for the game port
+pub use host_folder::{build_host_folder_image, sync_host_folder, HostDriveKind, HostFolderError}; // Host folder drives for GUI
 pub use keyboard::*; // Export keyboard scancodes for GUI integration
+pub use printer::{PrinterFormat, PrinterOutput}; // Printer capture files for GUI/CLI
+pub use rtc::{DateTime as RtcDateTime, RtcClock}; // Real-time clock configuration for GUI
+pub use serial::SerialBackend; // Custom host side for the serial ports
+pub use serial_host::SerialBinding; // Serial port host connections for GUI/CLI
 pub use video_adapter_cga_graphics::{CgaGraphicsAdapter, CgaMode}; // Export CGA graphics adapter and modes
 pub use video_adapter_ega_software::{EgaMode, SoftwareEgaAdapter}; // Export EGA software adapter and modes
+pub use video_adapter_hercules::{HerculesMode, SoftwareHerculesAdapter}; // Export Hercules adapter and modes
 pub use video_adapter_vga_software::{SoftwareVgaAdapter, VgaMode}; // Export VGA software adapter and modes
 
 #[derive(Debug, Error)]
@@ -55,6 +106,71 @@ pub enum PcError {
     InvalidExecutable,
     #[error("Invalid mount point: {0}")]
     InvalidMountPoint(String),
+    #[error("Invalid cassette image: {0}")]
+    InvalidCassette(String),
+    #[error("Invalid EMS page frame segment: {0:04X}h (must be C000h-E000h on a 16KB boundary)")]
+    InvalidEmsPageFrame(u16),
+    #[error("Invalid CMOS RAM image: expected {expected} bytes, got {actual}")]
+    InvalidCmosRam { expected: usize, actual: usize },
+    #[error("Invalid serial port: COM{0} (COM1 and COM2 are available)")]
+    InvalidSerialPort(usize),
+    #[error("Cannot connect serial port to {binding}: {reason}")]
+    SerialConnection { binding: String, reason: String },
+    #[error("Cannot capture printer output to {output}: {reason}")]
+    PrinterCapture { output: String, reason: String },
+}
+
+/// Slowest fixed CPU speed accepted by `PcSystem::set_cpu_speed_mhz`
+pub const MIN_CPU_SPEED_MHZ: f64 = 1.0;
+/// Fastest fixed CPU speed accepted by `PcSystem::set_cpu_speed_mhz`
+pub const MAX_CPU_SPEED_MHZ: f64 = 1000.0;
+
+/// Wall-clock time an unlimited-speed frame may spend executing instructions
+const UNLIMITED_FRAME_BUDGET: Duration = Duration::from_millis(12);
+/// Instructions executed between wall-clock checks in unlimited mode
+const UNLIMITED_CHECK_INTERVAL: u32 = 4096;
+
+/// CPU clock speed setting, independent of the emulated CPU model
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum CpuSpeed {
+    /// Historical clock speed of the CPU model (see `PcSystem::cpu_speed_for_model`)
+    #[default]
+    Model,
+    /// Fixed clock speed in MHz
+    Fixed(f64),
+    /// Run as many cycles per frame as the host allows (turbo)
+    Unlimited,
+}
+
+impl std::fmt::Display for CpuSpeed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CpuSpeed::Model => write!(f, "model"),
+            CpuSpeed::Fixed(mhz) => write!(f, "{}", mhz),
+            CpuSpeed::Unlimited => write!(f, "unlimited"),
+        }
+    }
+}
+
+impl std::str::FromStr for CpuSpeed {
+    type Err = String;
+
+    /// Parse "model", "unlimited" (or "turbo"), or a clock speed in MHz
+    /// such as "8" or "33.3 MHz"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.trim().to_ascii_lowercase();
+        match lower.as_str() {
+            "model" | "default" => Ok(CpuSpeed::Model),
+            "unlimited" | "turbo" => Ok(CpuSpeed::Unlimited),
+            other => {
+                let number = other.strip_suffix("mhz").unwrap_or(other).trim();
+                match number.parse::<f64>() {
+                    Ok(mhz) if mhz.is_finite() && mhz > 0.0 => Ok(CpuSpeed::Fixed(mhz)),
+                    _ => Err(format!("Invalid CPU speed: {}", s)),
+                }
+            }
+        }
+    }
 }
 
 /// PC system state
@@ -63,8 +179,16 @@ pub struct PcSystem {
     cycles: u64,
     frame_cycles: u64,
     video: Box<dyn VideoAdapter>,
-    boot_started: bool,     // Track if boot sector has started executing
-    boot_delay_frames: u32, // Frames to wait at POST screen (5 seconds = 300 frames at 60Hz)
+    boot_started: bool,       // Track if boot sector has started executing
+    boot_delay_frames: u32,   // Frames to wait at POST screen (5 seconds = 300 frames at 60Hz)
+    boot_menu: Option<usize>, // Highlighted entry while the F12 boot menu is open
+    warm_boot: bool,          // Rebooted with Ctrl+Alt+Del (POST keeps the warm boot flag)
+    cpu_speed: CpuSpeed,      // Clock speed setting (model default, fixed or unlimited)
+    measured_speed_mhz: f64,  // Effective speed of the last unlimited-mode frame
+    serial_bindings: [Option<SerialBinding>; 2], // Host connections of COM1/COM2
+    printer_output: Option<PrinterOutput>, // Host capture file of LPT1
+    planar_graphics: bool,    // The adapter is showing an EGA/VGA or Hercules graphics mode
+    frame_pool: FramePool,    // Buffers of frames the frontend has handed back
 }
 
 impl Default for PcSystem {
@@ -89,7 +213,7 @@ impl PcSystem {
     /// # Arguments
     /// * `cpu_model` - CPU model (Intel8086, Intel8088, Intel80186, Intel80188, Intel80286, Intel80386)
     /// * `memory_kb` - Memory size in KB (256-640, will be clamped to valid range)
-    /// * `video_adapter` - Video adapter (CGA, EGA, VGA)
+    /// * `video_adapter` - Video adapter (CGA, EGA, VGA, Hercules)
     pub fn with_config(
         cpu_model: CpuModel,
         memory_kb: u32,
@@ -107,7 +231,16 @@ impl PcSystem {
 
         // Write BIOS POST screen to video RAM with actual CPU model and memory
         let cpu_speed_mhz = Self::cpu_speed_for_model(cpu_model);
-        bios::write_post_screen_to_vram(bus.vram_mut(), cpu_model, memory_kb, cpu_speed_mhz);
+        let now = bus.rtc.date_time();
+        let text_offset = bus.text_buffer_offset();
+        bios::write_post_screen_to_vram(
+            bus.vram_mut(),
+            text_offset,
+            cpu_model,
+            memory_kb,
+            cpu_speed_mhz,
+            &now,
+        );
 
         let cpu = PcCpu::with_model(bus, cpu_model);
 
@@ -118,9 +251,95 @@ impl PcSystem {
             video: video_adapter,
             boot_started: false,
             boot_delay_frames: 300, // 5 seconds at 60 Hz
+            boot_menu: None,
+            warm_boot: false,
+            cpu_speed: CpuSpeed::Model,
+            measured_speed_mhz: cpu_speed_mhz,
+            serial_bindings: [None, None],
+            printer_output: None,
+            planar_graphics: false,
+            frame_pool: FramePool::new(),
         }
     }
 
+    /// Render video memory to a frame through the video adapter
+    ///
+    /// A VGA showing a 256-color mode is switched to the size the CRTC
+    /// displays and gets the DAC palette, and a Hercules card in graphics
+    /// mode is handed its displayed page; otherwise the text buffer (B8000h,
+    /// or B0000h on a monochrome adapter) is rendered.
+    fn render_frame(&mut self) -> Frame {
+        let bus = self.cpu.bus();
+        if let Some((width, height, indices)) = bus.vga_graphics_frame() {
+            // Text and graphics modes can share a resolution (EGA 640x350),
+            // so switch the adapter whenever graphics start
+            if !self.planar_graphics
+                || (self.video.fb_width(), self.video.fb_height()) != (width, height)
+            {
+                self.video.resize(width, height);
+                self.planar_graphics = true;
+            }
+            self.video.set_dac_palette(&bus.dac_palette());
+
+            // Crop or pad layouts the adapter has no matching mode for
+            let (fb_width, fb_height) = (self.video.fb_width(), self.video.fb_height());
+            let mut pixels = vec![0u8; fb_width * fb_height];
+            for y in 0..fb_height.min(height) {
+                let len = fb_width.min(width);
+                pixels[y * fb_width..y * fb_width + len]
+                    .copy_from_slice(&indices[y * width..y * width + len]);
+            }
+
+            let mut frame = self.frame_pool.acquire(fb_width as u32, fb_height as u32);
+            self.video.render(&pixels, &mut frame.pixels);
+            return frame;
+        }
+
+        if let Some(page_offset) = bus.hercules_graphics_page() {
+            if !self.planar_graphics {
+                self.video
+                    .resize(HERCULES_GRAPHICS_WIDTH, HERCULES_GRAPHICS_HEIGHT);
+                self.planar_graphics = true;
+            }
+            let mut frame = self
+                .frame_pool
+                .acquire(self.video.fb_width() as u32, self.video.fb_height() as u32);
+            self.video
+                .render(&bus.vram()[page_offset..], &mut frame.pixels);
+            return frame;
+        }
+
+        // Back to text mode (and the default palette) after a graphics mode
+        if self.planar_graphics {
+            self.video.reset();
+            self.planar_graphics = false;
+        }
+
+        let mut frame = self
+            .frame_pool
+            .acquire(self.video.fb_width() as u32, self.video.fb_height() as u32);
+        let text = &bus.vram()[bus.text_display_offset()..];
+        if bus.text_columns() == 80 {
+            self.video.render(text, &mut frame.pixels);
+            return frame;
+        }
+
+        // 40-column modes: lay the rows out as the left half of an 80-column
+        // screen, then stretch each character cell to double width
+        let mut cells = vec![0u8; 80 * 25 * 2];
+        for (row, line) in text.chunks(40 * 2).take(25).enumerate() {
+            cells[row * 160..row * 160 + line.len()].copy_from_slice(line);
+        }
+        self.video.render(&cells, &mut frame.pixels);
+        let width = frame.width as usize;
+        for line in frame.pixels.chunks_mut(width) {
+            for x in (0..width).rev() {
+                line[x] = line[x / 2];
+            }
+        }
+        frame
+    }
+
     /// Detect video adapter type from adapter name
     fn detect_video_adapter_type(name: &str) -> VideoAdapterType {
         let name_lower = name.to_lowercase();
@@ -130,7 +349,10 @@ impl PcSystem {
             VideoAdapterType::Ega
         } else if name_lower.contains("cga") {
             VideoAdapterType::Cga
-        } else if name_lower.contains("mda") || name_lower.contains("monochrome") {
+        } else if name_lower.contains("mda")
+            || name_lower.contains("monochrome")
+            || name_lower.contains("hercules")
+        {
             VideoAdapterType::Mda
         } else {
             // Default to CGA if unknown
@@ -143,10 +365,45 @@ impl PcSystem {
         self.cpu.model()
     }
 
-    /// Get the CPU clock speed in MHz based on CPU model
-    /// Returns the historical clock speed for each processor
+    /// Get the effective CPU clock speed in MHz
+    ///
+    /// Returns the historical clock speed of the CPU model unless a speed has
+    /// been set with `set_cpu_speed`. In unlimited mode this is the speed
+    /// measured over the last frame.
     pub fn cpu_speed_mhz(&self) -> f64 {
-        Self::cpu_speed_for_model(self.cpu.model())
+        match self.cpu_speed {
+            CpuSpeed::Model => Self::cpu_speed_for_model(self.cpu.model()),
+            CpuSpeed::Fixed(mhz) => mhz,
+            CpuSpeed::Unlimited => self.measured_speed_mhz,
+        }
+    }
+
+    /// Get the CPU clock speed setting
+    pub fn cpu_speed(&self) -> CpuSpeed {
+        self.cpu_speed
+    }
+
+    /// Set the CPU clock speed independently of the CPU model
+    ///
+    /// Fixed speeds are clamped to `MIN_CPU_SPEED_MHZ..=MAX_CPU_SPEED_MHZ`.
+    /// Timers (PIT, display retrace) keep running at real-time rates.
+    pub fn set_cpu_speed(&mut self, speed: CpuSpeed) {
+        self.cpu_speed = match speed {
+            CpuSpeed::Fixed(mhz) if mhz.is_nan() => CpuSpeed::Model,
+            CpuSpeed::Fixed(mhz) => {
+                CpuSpeed::Fixed(mhz.clamp(MIN_CPU_SPEED_MHZ, MAX_CPU_SPEED_MHZ))
+            }
+            other => other,
+        };
+        if self.cpu_speed == CpuSpeed::Unlimited {
+            // Start from the model speed until the first frame is measured
+            self.measured_speed_mhz = Self::cpu_speed_for_model(self.cpu.model());
+        }
+    }
+
+    /// Set a fixed CPU clock speed in MHz
+    pub fn set_cpu_speed_mhz(&mut self, mhz: f64) {
+        self.set_cpu_speed(CpuSpeed::Fixed(mhz));
     }
 
     /// Get the CPU clock speed in MHz for a given CPU model (static method)
@@ -177,44 +434,39 @@ impl PcSystem {
         self.cpu.set_model(model);
     }
 
-    /// Load a DOS executable into memory
-    #[allow(dead_code)]
-    fn load_executable(&mut self, data: &[u8]) -> Result<(), PcError> {
-        // Check for MZ header (DOS .EXE)
-        if data.len() >= 2 && &data[0..2] == b"MZ" {
-            // For now, just store it - full EXE parsing would be needed for proper loading
-            self.cpu.bus_mut().load_executable(data.to_vec());
-            return Ok(());
-        }
-
-        // Otherwise, assume it's a .COM file
-        // COM files are loaded at 0x0100 and are limited to 64KB - 256 bytes
-        if data.len() > 0xFF00 {
-            return Err(PcError::InvalidExecutable);
+    /// Load a DOS .COM or .EXE program to run instead of booting from disk
+    ///
+    /// After POST the built-in DOS starts (even when disabled) and runs the
+    /// program: an EXE is loaded at the segment after its PSP with its
+    /// relocations applied and CS:IP and SS:SP from its header. Mounted disks
+    /// stay available as drives, and exiting the program leaves the DOS
+    /// prompt. Used the next time the BIOS boots (after a reset).
+    pub fn load_executable(&mut self, data: &[u8]) -> Result<(), PcError> {
+        match dos::ExeHeader::parse(data) {
+            Some(header) if !header.is_valid(data) => return Err(PcError::InvalidExecutable),
+            Some(_) => {}
+            // COM files are loaded at PSP:0100 and are limited to 64KB - 256 bytes
+            None if data.is_empty() || data.len() > 0xFF00 => {
+                return Err(PcError::InvalidExecutable)
+            }
+            None => {}
         }
-
-        // Load COM file at 0x0000:0x0100 (physical address 0x0100)
         self.cpu.bus_mut().load_executable(data.to_vec());
-
-        // Copy to memory at 0x0100
-        for (i, &byte) in data.iter().enumerate() {
-            let addr = 0x0100 + i as u32;
-            self.cpu.bus_mut().write(addr, byte);
-        }
-
         Ok(())
     }
 
     /// Handle keyboard input (called by GUI)
     pub fn key_press(&mut self, scancode: u8) {
-        self.cpu.bus_mut().keyboard.key_press(scancode);
-        // Unhalt the CPU if it was waiting for keyboard input (INT 16h AH=00h)
+        // The keyboard controller raises IRQ 1 (INT 09h) for each scancode byte
+        self.cpu.bus_mut().key_event(scancode, true);
+        // Wake the CPU if a HLT instruction was waiting for an interrupt
         self.cpu.unhalt();
     }
 
     /// Handle keyboard release (called by GUI)
     pub fn key_release(&mut self, scancode: u8) {
-        self.cpu.bus_mut().keyboard.key_release(scancode);
+        self.cpu.bus_mut().key_event(scancode, false);
+        self.cpu.unhalt();
     }
 
     /// Handle keyboard input from SDL2 scancode (called by GUI with SDL2 backend)
@@ -233,6 +485,198 @@ impl PcSystem {
         }
     }
 
+    /// Plug joysticks into the game port with the given state, or unplug
+    /// them (`None`); called by the GUI every frame with gamepad input
+    pub fn set_joystick(&mut self, state: Option<JoystickState>) {
+        self.cpu.bus_mut().game_port.set_joysticks(state);
+    }
+
+    /// True once a program has installed the mouse driver (INT 33h AX=0000h),
+    /// which is when the GUI offers to capture the host mouse
+    pub fn mouse_driver_installed(&self) -> bool {
+        self.cpu.bus().mouse.is_installed()
+    }
+
+    /// Move the mouse by `dx`/`dy` mickeys and set its buttons (bit 0 =
+    /// left, bit 1 = right, bit 2 = middle); called by the GUI every frame
+    /// while the mouse is captured
+    pub fn move_mouse(&mut self, dx: i32, dy: i32, buttons: u16) {
+        // Keep the driver's mickey scaling within i16
+        const MAX_MICKEYS: i32 = 1024;
+        let mouse = &mut self.cpu.bus_mut().mouse;
+        mouse.update_position_delta(
+            dx.clamp(-MAX_MICKEYS, MAX_MICKEYS) as i16,
+            dy.clamp(-MAX_MICKEYS, MAX_MICKEYS) as i16,
+        );
+        mouse.update_buttons(mouse::MouseButtons::from_word(buttons));
+    }
+
+    /// Keyboard LEDs as last set by the guest (bit 0 = Scroll Lock,
+    /// bit 1 = Num Lock, bit 2 = Caps Lock)
+    pub fn keyboard_leds(&self) -> u8 {
+        self.cpu.bus().kbc.leds()
+    }
+
+    /// Configure expanded memory (EMS)
+    ///
+    /// Replaces the EMS driver, releasing all handles. The size is rounded down
+    /// to 16KB pages (0 disables expanded memory); the page frame must be a
+    /// 16KB aligned segment from C000h to E000h.
+    pub fn configure_ems(&mut self, size_kb: u32, page_frame: u16) -> Result<(), PcError> {
+        if !ems::is_valid_page_frame(page_frame) {
+            return Err(PcError::InvalidEmsPageFrame(page_frame));
+        }
+        self.cpu.bus_mut().ems = ems::EmsDriver::new(size_kb, page_frame);
+        Ok(())
+    }
+
+    /// Get the expanded memory size in KB
+    pub fn ems_kb(&self) -> u32 {
+        self.cpu.bus().ems.total_kb()
+    }
+
+    /// Get the EMS page frame segment
+    pub fn ems_page_frame(&self) -> u16 {
+        self.cpu.bus().ems.page_frame()
+    }
+
+    /// Enable or disable the built-in DOS started when no disk boots
+    pub fn set_builtin_dos(&mut self, enabled: bool) {
+        self.cpu.bus_mut().dos.set_enabled(enabled);
+    }
+
+    /// Whether the built-in DOS starts when no disk boots
+    pub fn builtin_dos(&self) -> bool {
+        self.cpu.bus().dos.is_enabled()
+    }
+
+    /// Get the real-time clock source
+    pub fn rtc_clock(&self) -> RtcClock {
+        self.cpu.bus().rtc.clock()
+    }
+
+    /// Set the real-time clock source: host time shifted by an offset, or
+    /// frozen at a fixed time
+    pub fn set_rtc_clock(&mut self, clock: RtcClock) {
+        self.cpu.bus_mut().rtc.set_clock(clock);
+    }
+
+    /// Get the current real-time clock date and time
+    pub fn rtc_date_time(&self) -> RtcDateTime {
+        self.cpu.bus().rtc.date_time()
+    }
+
+    /// Get the battery-backed CMOS RAM (bytes 0Eh-7Fh) for saving
+    pub fn cmos_ram(&self) -> Vec<u8> {
+        self.cpu.bus().rtc.cmos_ram().to_vec()
+    }
+
+    /// Restore CMOS RAM saved with `cmos_ram`
+    ///
+    /// The drive, display and memory setup values are then refreshed from the
+    /// current configuration, so only the remaining bytes carry over.
+    pub fn load_cmos_ram(&mut self, data: &[u8]) -> Result<(), PcError> {
+        let bus = self.cpu.bus_mut();
+        if !bus.rtc.load_cmos_ram(data) {
+            return Err(PcError::InvalidCmosRam {
+                expected: rtc::CMOS_SIZE - rtc::CMOS_RAM_START,
+                actual: data.len(),
+            });
+        }
+        bus.update_cmos_setup();
+        Ok(())
+    }
+
+    /// Connect a serial port (0 = COM1, 1 = COM2) to the host
+    ///
+    /// Replaces any previous connection of the port.
+    pub fn attach_serial(&mut self, port: usize, binding: &SerialBinding) -> Result<(), PcError> {
+        if port >= 2 {
+            return Err(PcError::InvalidSerialPort(port + 1));
+        }
+        let backend = binding.open().map_err(|e| PcError::SerialConnection {
+            binding: binding.to_string(),
+            reason: e.to_string(),
+        })?;
+        self.attach_serial_backend(port, backend)?;
+        self.serial_bindings[port] = Some(binding.clone());
+        Ok(())
+    }
+
+    /// Connect a serial port (0 = COM1, 1 = COM2) to a custom host backend
+    pub fn attach_serial_backend(
+        &mut self,
+        port: usize,
+        backend: Box<dyn SerialBackend>,
+    ) -> Result<(), PcError> {
+        let uart = self
+            .cpu
+            .bus_mut()
+            .serial
+            .get_mut(port)
+            .ok_or(PcError::InvalidSerialPort(port + 1))?;
+        uart.attach(backend);
+        self.serial_bindings[port] = None;
+        Ok(())
+    }
+
+    /// Disconnect a serial port from the host
+    pub fn detach_serial(&mut self, port: usize) {
+        if let Some(uart) = self.cpu.bus_mut().serial.get_mut(port) {
+            uart.detach();
+            self.serial_bindings[port] = None;
+        }
+    }
+
+    /// Host connection a serial port was attached to with `attach_serial`
+    pub fn serial_binding(&self, port: usize) -> Option<&SerialBinding> {
+        self.serial_bindings.get(port)?.as_ref()
+    }
+
+    /// Check if a serial port (0 = COM1, 1 = COM2) is connected to the host
+    pub fn is_serial_attached(&self, port: usize) -> bool {
+        self.cpu
+            .bus()
+            .serial
+            .get(port)
+            .is_some_and(|uart| uart.is_attached())
+    }
+
+    /// Capture the output of the printer port (LPT1) to a host file
+    ///
+    /// Output is appended to an existing file. Replaces any previous capture.
+    pub fn attach_printer(&mut self, output: &PrinterOutput) -> Result<(), PcError> {
+        let printer = output.open().map_err(|e| PcError::PrinterCapture {
+            output: output.to_string(),
+            reason: e.to_string(),
+        })?;
+        self.attach_printer_backend(printer);
+        self.printer_output = Some(output.clone());
+        Ok(())
+    }
+
+    /// Send the output of the printer port (LPT1) to a custom host writer
+    pub fn attach_printer_backend(&mut self, printer: Box<dyn std::io::Write + Send>) {
+        self.cpu.bus_mut().parallel.attach(printer);
+        self.printer_output = None;
+    }
+
+    /// Stop capturing printer output, flushing anything still buffered
+    pub fn detach_printer(&mut self) {
+        self.cpu.bus_mut().parallel.detach();
+        self.printer_output = None;
+    }
+
+    /// Host file the printer port was attached to with `attach_printer`
+    pub fn printer_output(&self) -> Option<&PrinterOutput> {
+        self.printer_output.as_ref()
+    }
+
+    /// Check if the printer port has a printer attached
+    pub fn is_printer_attached(&self) -> bool {
+        self.cpu.bus().parallel.is_attached()
+    }
+
     /// Set boot priority
     pub fn set_boot_priority(&mut self, priority: bios::BootPriority) {
         self.cpu.bus_mut().set_boot_priority(priority);
@@ -256,7 +700,10 @@ impl PcSystem {
     /// sys.set_video_adapter(Box::new(SoftwareVgaAdapter::new()));
     /// ```
     pub fn set_video_adapter(&mut self, adapter: Box<dyn VideoAdapter>) {
+        let adapter_type = Self::detect_video_adapter_type(adapter.name());
+        self.cpu.bus_mut().set_video_adapter_type(adapter_type);
         self.video = adapter;
+        self.planar_graphics = false;
     }
 
     /// Get the current video adapter name
@@ -279,11 +726,84 @@ impl PcSystem {
         (self.video.fb_width(), self.video.fb_height())
     }
 
+    /// Handle keys for the POST boot menu and redraw it
+    ///
+    /// Up/Down move the highlight, Enter boots the highlighted device if it
+    /// is bootable and ESC closes the menu, resuming the countdown.
+    fn step_boot_menu(&mut self, mut selected: usize) {
+        let devices = bios::BootDevice::ALL;
+        while self.cpu.bus().keyboard.has_data() {
+            let device = devices[selected];
+            match self.cpu.bus_mut().keyboard.read_scancode() {
+                keyboard::SCANCODE_UP => selected = selected.saturating_sub(1),
+                keyboard::SCANCODE_DOWN => selected = (selected + 1).min(devices.len() - 1),
+                keyboard::SCANCODE_ENTER
+                    if self.cpu.bus().boot_device_status(device)
+                        == bios::BootDeviceStatus::Bootable =>
+                {
+                    self.cpu.bus_mut().set_boot_override(Some(device));
+                    self.cpu.bus_mut().keyboard.clear();
+                    self.boot_menu = None;
+                    // Finish the countdown on the next frame
+                    self.boot_delay_frames = 1;
+                    return;
+                }
+                keyboard::SCANCODE_ESC => {
+                    self.cpu.bus_mut().keyboard.clear();
+                    self.boot_menu = None;
+                    return;
+                }
+                _ => {}
+            }
+        }
+
+        self.boot_menu = Some(selected);
+        let entries: Vec<_> = devices
+            .iter()
+            .map(|&device| (device, self.cpu.bus().boot_device_status(device)))
+            .collect();
+        let text_offset = self.cpu.bus().text_buffer_offset();
+        bios::write_boot_menu(
+            self.cpu.bus_mut().vram_mut(),
+            text_offset,
+            &entries,
+            selected,
+        );
+    }
+
     /// Trigger boot sector loading (called before first execution or on reset)
     fn ensure_boot_sector_loaded(&mut self) {
         self.cpu.bus_mut().load_boot_sector();
     }
 
+    /// Restart after a CPU reset: Ctrl+Alt+Del, a program jumping to the
+    /// reset vector, or a triple fault
+    ///
+    /// With the warm boot flag set (1234h at 0040:0072) POST boots straight
+    /// away, as a BIOS skips its memory test; otherwise, and always after a
+    /// triple fault, it is a cold boot like the reset button.
+    fn reboot(&mut self) {
+        use emu_core::cpu_8086::Memory8086;
+
+        let bus = self.cpu.bus();
+        let reset_flag = u16::from_le_bytes([
+            bus.read(bios::RESET_FLAG_ADDR),
+            bus.read(bios::RESET_FLAG_ADDR + 1),
+        ]);
+        let warm = reset_flag == bios::WARM_BOOT_FLAG && !self.cpu.is_shut_down();
+        emu_core::logging::log(
+            emu_core::logging::LogCategory::Bus,
+            emu_core::logging::LogLevel::Info,
+            || format!("Reboot ({})", if warm { "warm" } else { "cold" }),
+        );
+
+        self.reset();
+        if warm {
+            self.warm_boot = true;
+            self.boot_delay_frames = 1;
+        }
+    }
+
     /// Get debug information
     pub fn debug_info(&self) -> DebugInfo {
         let regs = self.cpu.get_registers();
@@ -305,10 +825,11 @@ impl PcSystem {
 
     /// Update POST screen with current mount status
     pub fn update_post_screen(&mut self) {
-        // Get mount status first (immutable borrows)
-        let floppy_a = self.cpu.bus().floppy_a().is_some();
-        let floppy_b = self.cpu.bus().floppy_b().is_some();
-        let hard_drive = self.cpu.bus().hard_drive().is_some();
+        // Get drive types from the CMOS setup first (immutable borrows)
+        let rtc = &self.cpu.bus().rtc;
+        let floppy_types = [rtc.floppy_type(0), rtc.floppy_type(1)];
+        let hard_disk = rtc.hard_disk();
+        let now = rtc.date_time();
         let cdrom = self.cpu.bus().has_cdrom();
         let boot_priority = self.cpu.bus().boot_priority();
 
@@ -318,13 +839,28 @@ impl PcSystem {
         let cpu_speed_mhz = self.cpu_speed_mhz();
 
         // Now get mutable borrow to update VRAM
+        let text_offset = self.cpu.bus().text_buffer_offset();
         let vram = self.cpu.bus_mut().vram_mut();
 
         // Rewrite entire POST screen with current config
-        bios::write_post_screen_to_vram(vram, cpu_model, memory_kb, cpu_speed_mhz);
+        bios::write_post_screen_to_vram(
+            vram,
+            text_offset,
+            cpu_model,
+            memory_kb,
+            cpu_speed_mhz,
+            &now,
+        );
 
         // Update mount status
-        bios::update_post_screen_mounts(vram, floppy_a, floppy_b, hard_drive, cdrom, boot_priority);
+        bios::update_post_screen_mounts(
+            vram,
+            text_offset,
+            floppy_types,
+            hard_disk,
+            cdrom,
+            boot_priority,
+        );
     }
 
     /// Get a reference to floppy A disk image (for saving)
@@ -337,11 +873,99 @@ impl PcSystem {
         self.cpu.bus().floppy_b()
     }
 
+    /// Check if floppy A (0) or B (1) has been written by the guest since it
+    /// was mounted or last saved
+    pub fn floppy_is_dirty(&self, drive: u8) -> bool {
+        drive < 2 && self.cpu.bus().disk_is_dirty(drive)
+    }
+
+    /// Clear the modified flag of floppy A (0) or B (1) once it has been
+    /// written back to disk
+    pub fn mark_floppy_saved(&mut self, drive: u8) {
+        if drive < 2 {
+            self.cpu.bus_mut().mark_disk_clean(drive);
+        }
+    }
+
     /// Get a reference to hard drive disk image (for saving)
     pub fn get_hard_drive(&self) -> Option<&[u8]> {
         self.cpu.bus().hard_drive()
     }
 
+    /// BIOS drive number behind a writable disk mount point
+    fn writable_drive(mount_point_id: &str) -> Option<u8> {
+        match mount_point_id {
+            "FloppyA" => Some(0x00),
+            "FloppyB" => Some(0x01),
+            "HardDrive" => Some(0x80),
+            _ => None,
+        }
+    }
+
+    /// Copy out a disk image including everything the guest wrote to it
+    ///
+    /// Accepts "FloppyA", "FloppyB" or "HardDrive"; returns None if the
+    /// mount point is empty or not a writable disk.
+    pub fn export_disk(&self, mount_point_id: &str) -> Option<Vec<u8>> {
+        let bus = self.cpu.bus();
+        let image = match mount_point_id {
+            "FloppyA" => bus.floppy_a(),
+            "FloppyB" => bus.floppy_b(),
+            "HardDrive" => bus.hard_drive(),
+            _ => None,
+        };
+        image.map(<[u8]>::to_vec)
+    }
+
+    /// Check if a disk mount point has been written by the guest since it
+    /// was mounted or last saved
+    pub fn disk_is_dirty(&self, mount_point_id: &str) -> bool {
+        Self::writable_drive(mount_point_id)
+            .is_some_and(|drive| self.cpu.bus().disk_is_dirty(drive))
+    }
+
+    /// Number of distinct sectors written to a disk mount point since it was
+    /// mounted or last saved
+    pub fn dirty_sector_count(&self, mount_point_id: &str) -> usize {
+        Self::writable_drive(mount_point_id)
+            .map_or(0, |drive| self.cpu.bus().dirty_sector_count(drive))
+    }
+
+    /// Clear the modified sectors of a disk mount point once its image has
+    /// been written back to disk
+    pub fn mark_disk_saved(&mut self, mount_point_id: &str) {
+        if let Some(drive) = Self::writable_drive(mount_point_id) {
+            self.cpu.bus_mut().mark_disk_clean(drive);
+        }
+    }
+
+    /// Get audio samples from the AdLib (OPL2), Sound Blaster and PC speaker output
+    pub fn get_audio_samples(&mut self, count: usize) -> Vec<i16> {
+        self.cpu.bus_mut().audio_samples(count)
+    }
+
+    /// Export the inserted cassette tape as a `.cas` bit stream (for saving
+    /// programs recorded with INT 15h AH=03h, e.g. BASIC's SAVE "CAS1:")
+    pub fn export_cassette(&self) -> Option<Vec<u8>> {
+        self.cpu.bus().cassette.tape().map(|tape| tape.to_cas())
+    }
+
+    /// Check if the inserted cassette has been recorded on
+    pub fn cassette_is_modified(&self) -> bool {
+        self.cpu
+            .bus()
+            .cassette
+            .tape()
+            .is_some_and(|tape| tape.is_modified())
+    }
+
+    /// Rewind the inserted cassette to the beginning
+    pub fn rewind_cassette(&mut self) {
+        if let Some(tape) = self.cpu.bus_mut().cassette.tape_mut() {
+            tape.rewind();
+        }
+    }
+
     /// Read BDA (BIOS Data Area) values for displaying system information
     /// BDA is located at 0x0040:0x0000 (physical address 0x0400-0x04FF)
     pub fn read_bda_values(&self) -> BdaValues {
@@ -418,25 +1042,57 @@ impl System for PcSystem {
         self.frame_cycles = 0;
         self.boot_started = false;
         self.boot_delay_frames = 300; // 5 seconds at 60 Hz
+        self.boot_menu = None;
+        self.warm_boot = false;
 
         // Write BIOS POST screen to video RAM with current config
         let cpu_model = self.cpu.model();
         let memory_kb = self.cpu.bus().memory_kb();
         let cpu_speed_mhz = self.cpu_speed_mhz();
+        let now = self.cpu.bus().rtc.date_time();
+        let text_offset = self.cpu.bus().text_buffer_offset();
         let vram = self.cpu.bus_mut().vram_mut();
-        bios::write_post_screen_to_vram(vram, cpu_model, memory_kb, cpu_speed_mhz);
+        bios::write_post_screen_to_vram(
+            vram,
+            text_offset,
+            cpu_model,
+            memory_kb,
+            cpu_speed_mhz,
+            &now,
+        );
     }
 
     fn step_frame(&mut self) -> Result<Frame, Self::Error> {
         // Calculate cycles per frame based on CPU speed
         // At 60 Hz: cycles_per_frame = (cpu_speed_mhz * 1_000_000) / 60
+        // In unlimited mode the frame is bounded by wall-clock time instead
         let cpu_speed_mhz = self.cpu_speed_mhz();
-        let cycles_per_frame = ((cpu_speed_mhz * 1_000_000.0) / 60.0) as u32;
+        let unlimited = self.cpu_speed == CpuSpeed::Unlimited;
+        let cycles_per_frame = if unlimited {
+            u32::MAX
+        } else {
+            ((cpu_speed_mhz * 1_000_000.0) / 60.0) as u32
+        };
+        self.cpu.bus_mut().set_cpu_speed_mhz(cpu_speed_mhz);
 
         // Boot delay: Wait at POST screen for 5 seconds before loading boot sector
         if !self.boot_started && self.boot_delay_frames > 0 {
-            // Check for ESC key to abort boot
-            if self.cpu.bus().keyboard.has_esc() {
+            if let Some(selected) = self.boot_menu {
+                // Boot menu open - countdown is paused until a device is chosen
+                self.step_boot_menu(selected);
+            } else if self.cpu.bus().keyboard.has_key(keyboard::SCANCODE_F12) {
+                // F12 pressed - open the boot menu on the first bootable device
+                self.cpu.bus_mut().keyboard.clear();
+                let selected = bios::BootDevice::ALL
+                    .iter()
+                    .position(|&device| {
+                        self.cpu.bus().boot_device_status(device)
+                            == bios::BootDeviceStatus::Bootable
+                    })
+                    .unwrap_or(0);
+                self.step_boot_menu(selected);
+            } else if self.cpu.bus().keyboard.has_esc() {
+                // Check for ESC key to abort boot
                 // ESC pressed - skip boot delay and halt
                 self.boot_delay_frames = 0;
                 // Don't set boot_started to true - this keeps system at POST screen
@@ -444,13 +1100,21 @@ impl System for PcSystem {
                 let cpu_model = self.cpu_model();
                 let memory_kb = self.memory_kb();
                 let cpu_speed_mhz = self.cpu_speed_mhz();
+                let now = self.rtc_date_time();
+                let text_buffer_offset = self.cpu.bus().text_buffer_offset();
                 let vram_mut = self.cpu.bus_mut().vram_mut();
 
                 // Refresh POST screen
-                bios::write_post_screen_to_vram(vram_mut, cpu_model, memory_kb, cpu_speed_mhz);
+                bios::write_post_screen_to_vram(
+                    vram_mut,
+                    text_buffer_offset,
+                    cpu_model,
+                    memory_kb,
+                    cpu_speed_mhz,
+                    &now,
+                );
 
                 // Write abort message
-                let text_buffer_offset = 0x18000;
                 let abort_msg = b"Boot aborted by user (ESC key pressed)";
                 let row = 20; // Row 20 (near bottom)
                 let col = 20; // Column 20 (centered-ish)
@@ -476,16 +1140,28 @@ impl System for PcSystem {
 
                 // Update countdown after updating POST screen (60 frames per second)
                 let seconds_remaining = self.boot_delay_frames.div_ceil(60); // Round up
+                let text_buffer_offset = self.cpu.bus().text_buffer_offset();
                 let vram_mut = self.cpu.bus_mut().vram_mut();
-                bios::update_post_screen_countdown(vram_mut, seconds_remaining);
+                bios::update_post_screen_countdown(vram_mut, text_buffer_offset, seconds_remaining);
 
                 // If delay expired, allow boot to proceed
                 if self.boot_delay_frames == 0 {
                     self.boot_started = true;
 
+                    // POST reads the setup from CMOS; a bad checksum (e.g. a
+                    // hand-edited project file) reloads the detected setup
+                    if !self.cpu.bus().rtc.checksum_valid() {
+                        emu_core::logging::log(
+                            emu_core::logging::LogCategory::Bus,
+                            emu_core::logging::LogLevel::Warn,
+                            || "POST: CMOS checksum error - defaults loaded".to_string(),
+                        );
+                        self.cpu.bus_mut().update_cmos_setup();
+                    }
+
                     // Clear the POST screen before loading boot sector
+                    let text_buffer_offset = self.cpu.bus().text_buffer_offset();
                     let vram_mut = self.cpu.bus_mut().vram_mut();
-                    let text_buffer_offset = 0x18000;
                     // Clear entire text buffer (80x25 x 2 bytes = 4000 bytes)
                     for i in 0..4000 {
                         if text_buffer_offset + i < vram_mut.len() {
@@ -552,12 +1228,16 @@ impl System for PcSystem {
                         .write(0x40F, ((ebda_segment >> 8) & 0xFF) as u8);
 
                     // Equipment list word at 0x0040:0x0010
+                    // Low byte from the CMOS equipment byte:
                     // Bit 0: Floppy drives installed
-                    // Bits 4-5: Initial video mode (10=CGA 80x25)
+                    // Bits 4-5: Initial video mode (00=EGA/VGA, 10=CGA 80x25, 11=MDA)
                     // Bits 6-7: Number of floppy drives - 1
-                    // Bits 9-11: Number of serial ports (1)
+                    // High byte:
+                    // Bits 9-11: Number of serial ports (2)
+                    // Bit 12: Game adapter installed
                     // Bits 14-15: Number of parallel printers (1)
-                    let equipment_word: u16 = 0b0100_0010_0010_0001; // LPT1, COM1, Floppy, CGA
+                    let equipment_word: u16 =
+                        0b0101_0100_0000_0000 | u16::from(self.cpu.bus().rtc.equipment()); // LPT1, game port, COM1-2
                     self.cpu
                         .bus_mut()
                         .write(0x410, (equipment_word & 0xFF) as u8);
@@ -592,16 +1272,20 @@ impl System for PcSystem {
                     self.cpu.bus_mut().write(0x482, 0x3E); // Buffer end = 0x003E (32 bytes)
                     self.cpu.bus_mut().write(0x483, 0x00);
 
-                    // Video mode at 0x0040:0x0049 (03h = CGA 80x25 color text)
-                    self.cpu.bus_mut().write(0x449, 0x03);
+                    // Video mode at 0x0040:0x0049 (03h = CGA 80x25 color text,
+                    // 07h = MDA 80x25 monochrome text)
+                    let mono = self.cpu.bus().video_adapter_type() == VideoAdapterType::Mda;
+                    self.cpu
+                        .bus_mut()
+                        .write(0x449, if mono { bus::MDA_TEXT_MODE } else { 0x03 });
 
                     // Screen columns at 0x0040:0x004A (80 columns)
                     self.cpu.bus_mut().write(0x44A, 80);
                     self.cpu.bus_mut().write(0x44B, 0);
 
-                    // Video buffer size at 0x0040:0x004C (4000 bytes for 80x25)
-                    self.cpu.bus_mut().write(0x44C, 0xA0); // 4000 low byte
-                    self.cpu.bus_mut().write(0x44D, 0x0F); // 4000 high byte
+                    // Video page size at 0x0040:0x004C (4096 bytes for 80x25)
+                    self.cpu.bus_mut().write(0x44C, 0x00);
+                    self.cpu.bus_mut().write(0x44D, 0x10);
 
                     // Video buffer offset at 0x0040:0x004E (current page offset)
                     self.cpu.bus_mut().write(0x44E, 0x00);
@@ -612,15 +1296,20 @@ impl System for PcSystem {
                         self.cpu.bus_mut().write(0x450 + i, 0); // All cursors at (0,0)
                     }
 
-                    // Cursor shape at 0x0040:0x0060 (start/end scan lines)
-                    self.cpu.bus_mut().write(0x460, 0x0D); // Start line 13
-                    self.cpu.bus_mut().write(0x461, 0x0E); // End line 14
+                    // Cursor shape at 0x0040:0x0060 (end scan line, then start)
+                    let [cursor_start, cursor_end] =
+                        self.cpu.bus().default_cursor_shape().to_be_bytes();
+                    self.cpu.bus_mut().write(0x460, cursor_end);
+                    self.cpu.bus_mut().write(0x461, cursor_start);
 
                     // Active video page at 0x0040:0x0062
                     self.cpu.bus_mut().write(0x462, 0x00); // Page 0
 
-                    // Video adapter base port at 0x0040:0x0063 (0x3D4 for CGA)
-                    self.cpu.bus_mut().write(0x463, 0xD4);
+                    // Video adapter base port at 0x0040:0x0063 (0x3D4 for CGA,
+                    // 0x3B4 for MDA)
+                    self.cpu
+                        .bus_mut()
+                        .write(0x463, if mono { 0xB4 } else { 0xD4 });
                     self.cpu.bus_mut().write(0x464, 0x03); // 0x03D4
 
                     // CGA mode register value at 0x0040:0x0065
@@ -630,10 +1319,12 @@ impl System for PcSystem {
                     self.cpu.bus_mut().write(0x466, 0x30); // Default palette
 
                     // Timer tick count at 0x0040:0x006C (4 bytes, updated by INT 08h)
-                    self.cpu.bus_mut().write(0x46C, 0x00);
-                    self.cpu.bus_mut().write(0x46D, 0x00);
-                    self.cpu.bus_mut().write(0x46E, 0x00);
-                    self.cpu.bus_mut().write(0x46F, 0x00);
+                    // POST sets it from the RTC time of day (1573040 ticks per day)
+                    let seconds_of_day = self.rtc_date_time().seconds_of_day();
+                    let ticks = (u64::from(seconds_of_day) * 0x1800B0 / 86400) as u32;
+                    for (i, byte) in ticks.to_le_bytes().into_iter().enumerate() {
+                        self.cpu.bus_mut().write(0x46C + i as u32, byte);
+                    }
 
                     // Midnight flag at 0x0040:0x0070
                     self.cpu.bus_mut().write(0x470, 0x00);
@@ -642,11 +1333,19 @@ impl System for PcSystem {
                     self.cpu.bus_mut().write(0x471, 0x00);
 
                     // Reset flag at 0x0040:0x0072 (warm boot indicator)
-                    self.cpu.bus_mut().write(0x472, 0x00);
-                    self.cpu.bus_mut().write(0x473, 0x00);
+                    let reset_flag = if self.warm_boot {
+                        bios::WARM_BOOT_FLAG
+                    } else {
+                        0
+                    };
+                    for (i, byte) in reset_flag.to_le_bytes().into_iter().enumerate() {
+                        self.cpu
+                            .bus_mut()
+                            .write(bios::RESET_FLAG_ADDR + i as u32, byte);
+                    }
 
-                    // Number of hard drives at 0x0040:0x0075
-                    let hard_drive_count = if self.cpu.bus().hard_drive().is_some() {
+                    // Number of hard drives at 0x0040:0x0075 (from the CMOS disk type)
+                    let hard_drive_count = if self.cpu.bus().rtc.hard_disk().is_some() {
                         1u8
                     } else {
                         0u8
@@ -694,6 +1393,20 @@ impl System for PcSystem {
                     self.cpu.bus_mut().write(0x7A, 0x00); // Segment low
                     self.cpu.bus_mut().write(0x7B, 0xF0); // Segment high (F000:0250)
 
+                    // INT 0x2F (Multiplex) and INT 0x33 (Mouse) point at the
+                    // built-in driver entry points, so programs that check for
+                    // a resident driver before calling it find one
+                    for (vector, offset) in [
+                        (0xBCu32, bios::MULTIPLEX_ENTRY_OFFSET),
+                        (0xCCu32, bios::MOUSE_ENTRY_OFFSET),
+                    ] {
+                        let [offset_low, offset_high] = offset.to_le_bytes();
+                        self.cpu.bus_mut().write(vector, offset_low);
+                        self.cpu.bus_mut().write(vector + 1, offset_high);
+                        self.cpu.bus_mut().write(vector + 2, 0x00); // Segment low
+                        self.cpu.bus_mut().write(vector + 3, 0xF0); // Segment high
+                    }
+
                     // NOTE: INT 0x21 (DOS Services) is NOT set up by BIOS
                     // DOS will install its own INT 21h handler when it loads (IO.SYS/MSDOS.SYS)
                     // Leave the INT 21h vector completely uninitialized (0x0000:0x0000)
@@ -724,16 +1437,8 @@ impl System for PcSystem {
                 }
             }
 
-            // Create frame buffer and render POST screen
-            let mut frame = Frame::new(self.video.fb_width() as u32, self.video.fb_height() as u32);
-            let vram = self.cpu.bus().vram();
-            let text_buffer_offset = 0x18000;
-            if vram.len() > text_buffer_offset {
-                self.video
-                    .render(&vram[text_buffer_offset..], &mut frame.pixels);
-            }
-
-            return Ok(frame);
+            // Render POST screen
+            return Ok(self.render_frame());
         }
 
         // Normal execution after boot delay
@@ -742,48 +1447,89 @@ impl System for PcSystem {
             self.ensure_boot_sector_loaded();
         }
 
-        // Create frame buffer for text mode 80x25 (640x400 pixels)
-        let mut frame = Frame::new(self.video.fb_width() as u32, self.video.fb_height() as u32);
-
         let mut cycles_this_frame = 0u32;
+        let deadline = unlimited.then(|| Instant::now() + UNLIMITED_FRAME_BUDGET);
+        let mut steps = 0u32;
+        let mut out_of_time = false;
 
         // Execute until we've completed a frame (or CPU is halted waiting for input)
         while cycles_this_frame < cycles_per_frame {
-            // If CPU is halted (e.g., INT 16h AH=00h waiting for keyboard input),
-            // break out of the loop to allow GUI to process keyboard events
+            // If CPU is halted (HLT waiting for an interrupt), break out of the
+            // loop to allow GUI to process keyboard events
             if self.cpu.is_halted() {
                 break;
             }
 
+            if let Some(deadline) = deadline {
+                steps += 1;
+                if steps.is_multiple_of(UNLIMITED_CHECK_INTERVAL) && Instant::now() >= deadline {
+                    out_of_time = true;
+                    break;
+                }
+            }
+
             let cycles = self.cpu.step();
-            cycles_this_frame += cycles;
+            cycles_this_frame = cycles_this_frame.saturating_add(cycles);
             self.cycles += cycles as u64;
             self.frame_cycles += cycles as u64;
 
+            // Ctrl+Alt+Del, a jump to the reset vector or a triple fault
+            // restarts the machine
+            if self.cpu.bus_mut().take_reset_request()
+                || self.cpu.at_reset_vector()
+                || self.cpu.is_shut_down()
+            {
+                self.reboot();
+                return Ok(self.render_frame());
+            }
+
             // Clock the PIT with the cycles executed
             let timer_interrupt = self.cpu.bus_mut().pit.clock(cycles);
             if timer_interrupt {
-                // Timer interrupt - trigger INT 08h (IRQ 0)
-                self.cpu.trigger_hardware_interrupt(0x08);
+                // Timer output drives IRQ 0 (INT 08h)
+                self.cpu.bus_mut().pic.raise_irq(0);
                 self.cpu.bus_mut().pit.clear_timer_interrupt();
             }
 
+            // Run sound hardware (OPL2 timers, Sound Blaster DMA playback)
+            self.cpu.bus_mut().clock_audio(cycles, cpu_speed_mhz);
+
+            // Run the serial ports (COM1/COM2)
+            self.cpu.bus_mut().clock_serial(cycles, cpu_speed_mhz);
+
+            // Send pending scancode bytes and typematic repeats
+            self.cpu.bus_mut().clock_keyboard(cycles, cpu_speed_mhz);
+
+            // Time the joystick one-shots
+            self.cpu.bus_mut().clock_game_port(cycles, cpu_speed_mhz);
+
+            // Deliver the highest priority pending IRQ through the PIC
+            self.cpu.service_pending_irq();
+
             // Update VGA status register for vertical retrace simulation
             self.cpu.bus().update_vga_status(cycles as u64);
         }
 
-        // Render video memory to frame buffer
-        // CGA text mode video RAM starts at 0xB8000
-        let vram = self.cpu.bus().vram();
-        // The text mode buffer is at offset 0x18000 in VRAM (0xB8000 - 0xA0000)
-        let text_buffer_offset = 0x18000;
+        // Time keeps passing for the sound hardware, serial ports and keyboard
+        // while the CPU waits for input, so a beep at the prompt plays out in full
+        let frame_cycles = (cpu_speed_mhz * 1_000_000.0 / 60.0) as u32;
+        if self.cpu.is_halted() && cycles_this_frame < frame_cycles {
+            let idle = frame_cycles - cycles_this_frame;
+            self.cpu.bus_mut().clock_audio(idle, cpu_speed_mhz);
+            self.cpu.bus_mut().clock_serial(idle, cpu_speed_mhz);
+            // A repeating key or the rest of a scancode wakes the CPU
+            if self.cpu.bus_mut().clock_keyboard(idle, cpu_speed_mhz) {
+                self.cpu.unhalt();
+            }
+        }
 
-        if vram.len() > text_buffer_offset {
-            self.video
-                .render(&vram[text_buffer_offset..], &mut frame.pixels);
+        // A full unlimited frame is one 60 Hz frame of emulated time
+        if out_of_time {
+            self.measured_speed_mhz =
+                (cycles_this_frame as f64 * 60.0 / 1_000_000.0).max(MIN_CPU_SPEED_MHZ);
         }
 
-        Ok(frame)
+        Ok(self.render_frame())
     }
 
     fn save_state(&self) -> Value {
@@ -843,6 +1589,18 @@ impl System for PcSystem {
                 extensions: vec!["iso".to_string(), "cue".to_string()],
                 required: false,
             },
+            MountPointInfo {
+                id: "Cassette".to_string(),
+                name: "Cassette Tape".to_string(),
+                extensions: vec!["cas".to_string(), "wav".to_string()],
+                required: false,
+            },
+            MountPointInfo {
+                id: "Executable".to_string(),
+                name: "DOS Program".to_string(),
+                extensions: vec!["com".to_string(), "exe".to_string()],
+                required: false,
+            },
         ]
     }
 
@@ -916,6 +1674,17 @@ impl System for PcSystem {
                 self.cpu.bus_mut().mount_cdrom(data.to_vec());
                 Ok(())
             }
+            "Cassette" => {
+                // Empty data inserts a blank tape for recording
+                let tape = if data.is_empty() {
+                    cassette::Tape::blank()
+                } else {
+                    cassette::Tape::from_image(data).map_err(PcError::InvalidCassette)?
+                };
+                self.cpu.bus_mut().cassette.insert(tape);
+                Ok(())
+            }
+            "Executable" => self.load_executable(data),
             _ => Err(PcError::InvalidMountPoint(mount_point_id.to_string())),
         }
     }
@@ -950,6 +1719,14 @@ impl System for PcSystem {
                 self.cpu.bus_mut().unmount_cdrom();
                 Ok(())
             }
+            "Cassette" => {
+                self.cpu.bus_mut().cassette.eject();
+                Ok(())
+            }
+            "Executable" => {
+                self.cpu.bus_mut().clear_executable();
+                Ok(())
+            }
             _ => Err(PcError::InvalidMountPoint(mount_point_id.to_string())),
         }
     }
@@ -960,9 +1737,19 @@ impl System for PcSystem {
             "FloppyA" => self.cpu.bus().floppy_a().is_some(),
             "FloppyB" => self.cpu.bus().floppy_b().is_some(),
             "HardDrive" => self.cpu.bus().hard_drive().is_some(),
+            "Cassette" => self.cpu.bus().cassette.tape().is_some(),
+            "Executable" => self.cpu.bus().executable().is_some(),
             _ => false,
         }
     }
+
+    fn recycle_frame(&mut self, frame: Frame) {
+        self.frame_pool.release(frame);
+    }
+
+    fn frame_pool_stats(&self) -> Option<FramePoolStats> {
+        Some(self.frame_pool.stats())
+    }
 }
 
 #[cfg(test)]
@@ -991,6 +1778,29 @@ mod tests {
         assert_eq!(sys.cycles, 0);
     }
 
+    #[test]
+    fn test_move_mouse() {
+        let mut sys = PcSystem::new();
+        assert!(!sys.mouse_driver_installed());
+        sys.cpu.bus_mut().mouse.reset();
+        assert!(sys.mouse_driver_installed());
+
+        // Vertical motion is halved by the default 16:8 mickey ratio
+        sys.move_mouse(10, 10, 0x01);
+        assert_eq!(
+            sys.cpu.bus().mouse.get_position_and_buttons(),
+            (0x01, 330, 105)
+        );
+        assert_eq!(sys.cpu.bus_mut().mouse.get_button_press_info(0).1, 1);
+
+        // Huge host motion clamps to the screen instead of overflowing
+        sys.move_mouse(i32::MAX, i32::MIN, 0);
+        assert_eq!(
+            sys.cpu.bus().mouse.get_position_and_buttons(),
+            (0x00, 639, 0)
+        );
+    }
+
     #[test]
     fn test_load_com_file() {
         let mut sys = PcSystem::new();
@@ -1027,7 +1837,7 @@ mod tests {
         let sys = PcSystem::new();
         let mps = sys.mount_points();
 
-        assert_eq!(mps.len(), 5);
+        assert_eq!(mps.len(), 7);
 
         // Check BIOS mount point
         assert_eq!(mps[0].id, "BIOS");
@@ -1050,6 +1860,33 @@ mod tests {
         assert_eq!(mps[4].id, "CDROM");
         assert!(!mps[4].required);
         assert!(mps[4].extensions.contains(&"iso".to_string()));
+
+        // Check Cassette
+        assert_eq!(mps[5].id, "Cassette");
+        assert!(!mps[5].required);
+        assert!(mps[5].extensions.contains(&"wav".to_string()));
+
+        // Check DOS program
+        assert_eq!(mps[6].id, "Executable");
+        assert!(mps[6].extensions.contains(&"exe".to_string()));
+    }
+
+    #[test]
+    fn test_mount_cassette() {
+        let mut sys = PcSystem::new();
+        assert!(!sys.is_mounted("Cassette"));
+
+        // Empty data inserts a blank tape
+        sys.mount("Cassette", &[]).unwrap();
+        assert!(sys.is_mounted("Cassette"));
+        assert!(!sys.cassette_is_modified());
+        assert_eq!(sys.export_cassette(), Some(Vec::new()));
+
+        assert!(sys.mount("Cassette", b"RIFF\0\0\0\0JUNK").is_err());
+
+        sys.unmount("Cassette").unwrap();
+        assert!(!sys.is_mounted("Cassette"));
+        assert_eq!(sys.export_cassette(), None);
     }
 
     #[test]
@@ -1155,6 +1992,71 @@ mod tests {
         assert_eq!(status, 0x03);
     }
 
+    #[test]
+    fn test_floppy_write_tracking() {
+        use crate::disk::DiskRequest;
+
+        let mut sys = PcSystem::new();
+        let floppy = crate::create_blank_floppy(crate::FloppyFormat::Floppy1_44M);
+        sys.mount("FloppyA", &floppy).unwrap();
+        sys.mount("FloppyB", &floppy).unwrap();
+        assert!(!sys.floppy_is_dirty(0));
+
+        // Failed write (sector 0 is invalid) leaves the image clean
+        let mut request = DiskRequest {
+            drive: 0x00,
+            cylinder: 0,
+            head: 0,
+            sector: 0,
+            count: 1,
+        };
+        assert_ne!(sys.cpu.bus_mut().disk_write(&request, &[0x55; 512]), 0);
+        assert!(!sys.floppy_is_dirty(0));
+
+        request.sector = 2;
+        assert_eq!(sys.cpu.bus_mut().disk_write(&request, &[0x55; 512]), 0);
+        assert!(sys.floppy_is_dirty(0));
+        assert!(!sys.floppy_is_dirty(1));
+
+        assert_eq!(&sys.get_floppy_a().unwrap()[512..1024], &[0x55; 512][..]);
+        assert!(sys.floppy_is_dirty(0));
+        sys.mark_floppy_saved(0);
+        assert!(!sys.floppy_is_dirty(0));
+
+        // LBA writes to B are tracked too; unmounting discards the flag
+        assert_eq!(sys.cpu.bus_mut().disk_write_lba(0x01, 5, 1, &[1; 512]), 0);
+        assert!(sys.floppy_is_dirty(1));
+        sys.unmount("FloppyB").unwrap();
+        assert!(!sys.floppy_is_dirty(1));
+        assert!(sys.get_floppy_b().is_none());
+    }
+
+    #[test]
+    fn test_export_disk_after_writes() {
+        let mut sys = PcSystem::new();
+        let hd = crate::create_blank_hard_drive(crate::HardDriveFormat::HardDrive20M);
+        sys.mount("HardDrive", &hd).unwrap();
+        assert!(!sys.disk_is_dirty("HardDrive"));
+        assert!(sys.export_disk("FloppyA").is_none());
+        assert!(sys.export_disk("CDROM").is_none());
+
+        // Overlapping writes count each sector once
+        let bus = sys.cpu.bus_mut();
+        assert_eq!(bus.disk_write_lba(0x80, 10, 2, &[0xAA; 1024]), 0);
+        assert_eq!(bus.disk_write_lba(0x80, 11, 2, &[0xBB; 1024]), 0);
+        assert!(sys.disk_is_dirty("HardDrive"));
+        assert_eq!(sys.dirty_sector_count("HardDrive"), 3);
+
+        let image = sys.export_disk("HardDrive").unwrap();
+        assert_eq!(image.len(), hd.len());
+        assert_eq!(&image[10 * 512..11 * 512], &[0xAA; 512][..]);
+        assert_eq!(&image[11 * 512..13 * 512], &[0xBB; 1024][..]);
+
+        sys.mark_disk_saved("HardDrive");
+        assert!(!sys.disk_is_dirty("HardDrive"));
+        assert_eq!(sys.dirty_sector_count("HardDrive"), 0);
+    }
+
     #[test]
     fn test_supports_save_states() {
         let sys = PcSystem::new();
@@ -1188,6 +2090,17 @@ mod tests {
         assert_eq!(frame.height, 400);
     }
 
+    #[test]
+    fn test_recycled_frames_are_reused() {
+        let mut sys = PcSystem::new();
+        let frame = sys.step_frame().unwrap();
+        sys.recycle_frame(frame);
+        let frame = sys.step_frame().unwrap();
+        assert_eq!((frame.width, frame.height), (640, 400));
+        let stats = sys.frame_pool_stats().unwrap();
+        assert_eq!((stats.reused, stats.allocated), (1, 1));
+    }
+
     #[test]
     fn test_mount_multiple_disks() {
         let mut sys = PcSystem::new();
@@ -1374,6 +2287,160 @@ mod tests {
         assert_eq!(bus.read_ram(0x7C00 + 510), 0x00);
     }
 
+    #[test]
+    fn test_boot_menu_selects_device() {
+        let mut sys = PcSystem::new();
+
+        let mut floppy = vec![0; 1474560];
+        floppy[0] = 0xEA;
+        floppy[510] = 0x55;
+        floppy[511] = 0xAA;
+        let mut hd = vec![0; 10653696];
+        hd[0] = 0xB8;
+        hd[510] = 0x55;
+        hd[511] = 0xAA;
+        assert!(sys.mount("FloppyA", &floppy).is_ok());
+        assert!(sys.mount("HardDrive", &hd).is_ok());
+        sys.set_boot_priority(crate::BootPriority::FloppyFirst);
+
+        // F12 opens the menu on the first bootable device and pauses the countdown
+        sys.key_press(SCANCODE_F12);
+        sys.step_frame().unwrap();
+        assert_eq!(sys.boot_menu, Some(0));
+        let frames_left = sys.boot_delay_frames;
+
+        // Floppy B is not present, so Enter on it is ignored
+        sys.key_press(SCANCODE_DOWN);
+        sys.key_press(SCANCODE_ENTER);
+        sys.step_frame().unwrap();
+        assert_eq!(sys.boot_menu, Some(1));
+        assert_eq!(sys.boot_delay_frames, frames_left);
+
+        // Pick the hard drive over the floppy-first priority
+        sys.key_press(SCANCODE_DOWN);
+        sys.key_press(SCANCODE_ENTER);
+        sys.step_frame().unwrap();
+        assert_eq!(sys.boot_menu, None);
+        sys.step_frame().unwrap();
+
+        let bus = sys.cpu.bus();
+        assert_eq!(bus.read_ram(0x7C00), 0xB8);
+        assert_eq!(bus.read_ram(bios::BOOT_DRIVE_ADDR), 0x80);
+    }
+
+    #[test]
+    fn test_boot_menu_escape_resumes_countdown() {
+        let mut sys = PcSystem::new();
+
+        sys.key_press(SCANCODE_F12);
+        sys.step_frame().unwrap();
+        assert_eq!(sys.boot_menu, Some(0));
+        let frames_left = sys.boot_delay_frames;
+
+        // ESC closes the menu without aborting the boot
+        sys.key_press(SCANCODE_ESC);
+        sys.step_frame().unwrap();
+        assert_eq!(sys.boot_menu, None);
+        assert_eq!(sys.boot_delay_frames, frames_left);
+        sys.step_frame().unwrap();
+        assert_eq!(sys.boot_delay_frames, frames_left - 1);
+    }
+
+    /// System booting a floppy whose boot sector runs `code`, past POST
+    fn boot_floppy_code(model: CpuModel, code: &[u8]) -> PcSystem {
+        let mut sys = PcSystem::with_cpu_model(model);
+        let mut floppy = vec![0; 1474560];
+        floppy[..code.len()].copy_from_slice(code);
+        floppy[510] = 0x55;
+        floppy[511] = 0xAA;
+        sys.mount("FloppyA", &floppy).unwrap();
+        sys.set_boot_priority(crate::BootPriority::FloppyFirst);
+
+        sys.boot_delay_frames = 1;
+        let _ = sys.step_frame();
+        assert!(sys.boot_started);
+        sys
+    }
+
+    #[test]
+    fn test_ctrl_alt_del_warm_boots() {
+        use crate::keyboard::{SCANCODE_DELETE, SCANCODE_LEFT_ALT, SCANCODE_LEFT_CTRL};
+        use emu_core::cpu_8086::Memory8086;
+
+        // STI; JMP $
+        let mut sys = boot_floppy_code(CpuModel::Intel8086, &[0xFB, 0xEB, 0xFE]);
+        let _ = sys.step_frame();
+        assert_eq!(sys.cpu.bus().read(0x472), 0x00);
+
+        sys.key_press(SCANCODE_LEFT_CTRL);
+        sys.key_press(SCANCODE_LEFT_ALT);
+        sys.key_press(SCANCODE_DELETE);
+        let _ = sys.step_frame();
+
+        // POST runs again without the countdown and keeps the warm boot flag
+        assert!(!sys.boot_started);
+        assert_eq!(sys.boot_delay_frames, 1);
+        sys.key_release(SCANCODE_DELETE);
+        sys.key_release(SCANCODE_LEFT_ALT);
+        sys.key_release(SCANCODE_LEFT_CTRL);
+        let _ = sys.step_frame();
+        assert!(sys.boot_started);
+        let bus = sys.cpu.bus();
+        assert_eq!((bus.read(0x472), bus.read(0x473)), (0x34, 0x12));
+        assert_eq!(bus.read_ram(0x7C00), 0xFB, "boot sector reloaded");
+    }
+
+    #[test]
+    fn test_jump_to_reset_vector_reboots() {
+        // JMP FAR FFFF:0000 without the warm boot flag is a cold boot
+        let mut sys = boot_floppy_code(CpuModel::Intel8086, &[0xEA, 0x00, 0x00, 0xFF, 0xFF]);
+        let _ = sys.step_frame();
+
+        assert!(!sys.boot_started);
+        assert_eq!(sys.boot_delay_frames, 300);
+        assert_eq!(sys.cycles, 0);
+    }
+
+    #[test]
+    fn test_triple_fault_resets() {
+        // CLI; LIDT CS:[7C10h] (empty IDT); MOV AX,1; LMSW AX; INT 3
+        let mut code = vec![
+            0xFA, 0x2E, 0x0F, 0x01, 0x1E, 0x10, 0x7C, 0xB8, 0x01, 0x00, 0x0F, 0x01, 0xF0, 0xCC,
+            0xEB, 0xFE,
+        ];
+        code.extend([0; 6]);
+        let mut sys = boot_floppy_code(CpuModel::Intel80286, &code);
+        let _ = sys.step_frame();
+
+        assert!(!sys.cpu.is_shut_down());
+        assert!(!sys.boot_started);
+        assert_eq!(sys.boot_delay_frames, 300);
+    }
+
+    #[test]
+    fn test_boot_from_no_emulation_cd() {
+        let mut sys = PcSystem::new();
+
+        // MOV [0x0500], DL; JMP $ - loaded to 2000:0000
+        let code = [0x88, 0x16, 0x00, 0x05, 0xEB, 0xFE];
+        let cd = el_torito::bootable_cd(0x00, 0x2000, &code);
+        assert!(sys.mount("CDROM", &cd).is_ok());
+        sys.set_boot_priority(crate::BootPriority::CdRomFirst);
+        assert_eq!(
+            sys.cpu.bus().boot_device_status(bios::BootDevice::CdRom),
+            bios::BootDeviceStatus::Bootable
+        );
+
+        sys.boot_delay_frames = 1;
+        for _ in 0..5 {
+            sys.step_frame().unwrap();
+        }
+
+        let bus = sys.cpu.bus();
+        assert_eq!(bus.read_ram(0x20000), 0x88);
+        assert_eq!(bus.read_ram(0x0500), 0xE0);
+        assert_eq!(sys.cpu.get_registers().cs, 0x2000);
+    }
     #[test]
     fn test_boot_sector_smoke_test() {
         // This test uses the test boot sector from test_roms/pc/basic_boot/boot.bin
@@ -1693,6 +2760,212 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_cpu_speed_setting() {
+        let mut sys = PcSystem::with_cpu_model(CpuModel::Intel80286);
+        assert_eq!(sys.cpu_speed(), CpuSpeed::Model);
+        assert_eq!(sys.cpu_speed_mhz(), 12.0);
+
+        sys.set_cpu_speed_mhz(33.0);
+        assert_eq!(sys.cpu_speed(), CpuSpeed::Fixed(33.0));
+        assert_eq!(sys.cpu_speed_mhz(), 33.0);
+
+        // Out-of-range speeds are clamped
+        sys.set_cpu_speed_mhz(0.1);
+        assert_eq!(sys.cpu_speed_mhz(), MIN_CPU_SPEED_MHZ);
+        sys.set_cpu_speed_mhz(1e6);
+        assert_eq!(sys.cpu_speed_mhz(), MAX_CPU_SPEED_MHZ);
+
+        // Speed setting is independent of (and survives) a model change
+        sys.set_cpu_speed_mhz(8.0);
+        sys.set_cpu_model(CpuModel::Intel80386);
+        assert_eq!(sys.cpu_speed_mhz(), 8.0);
+
+        sys.set_cpu_speed(CpuSpeed::Model);
+        assert_eq!(sys.cpu_speed_mhz(), 20.0);
+    }
+
+    #[test]
+    fn test_cmos_ram_save_and_load() {
+        let mut sys = PcSystem::new();
+        sys.set_rtc_clock(RtcClock::Frozen { time: 809_256_615 });
+        assert_eq!(sys.rtc_date_time().to_string(), "1995-08-24 09:30:15");
+
+        // A program stores a byte in CMOS RAM through ports 70h/71h
+        sys.cpu.bus_mut().io_write(0x70, 0x50);
+        sys.cpu.bus_mut().io_write(0x71, 0x42);
+        let saved = sys.cmos_ram();
+
+        // Restoring into a machine with a floppy keeps the byte, while the
+        // drive setup still reflects the new machine
+        let mut restored = PcSystem::new();
+        let floppy = crate::create_blank_floppy(crate::FloppyFormat::Floppy720K);
+        restored.mount("FloppyA", &floppy).unwrap();
+        restored.load_cmos_ram(&saved).unwrap();
+        assert_eq!(restored.cpu.bus().rtc.read_register(0x50), 0x42);
+        assert_eq!(
+            restored.cpu.bus().rtc.floppy_type(0),
+            Some(FloppyFormat::Floppy720K)
+        );
+
+        assert!(matches!(
+            restored.load_cmos_ram(&saved[..10]),
+            Err(PcError::InvalidCmosRam { actual: 10, .. })
+        ));
+    }
+
+    #[test]
+    fn test_serial_port_binding() {
+        let mut sys = PcSystem::new();
+        let binding: SerialBinding = "tcp-listen:127.0.0.1:0".parse().unwrap();
+        sys.attach_serial(1, &binding).unwrap();
+        assert!(sys.is_serial_attached(1));
+        assert!(!sys.is_serial_attached(0));
+        assert_eq!(sys.serial_binding(1), Some(&binding));
+
+        // The connection survives a reset
+        sys.reset();
+        assert!(sys.is_serial_attached(1));
+
+        sys.detach_serial(1);
+        assert!(!sys.is_serial_attached(1));
+        assert_eq!(sys.serial_binding(1), None);
+
+        assert!(matches!(
+            sys.attach_serial(2, &binding),
+            Err(PcError::InvalidSerialPort(3))
+        ));
+        assert!(matches!(
+            sys.attach_serial(0, &SerialBinding::Pty("/nonexistent/tty".to_string())),
+            Err(PcError::SerialConnection { .. })
+        ));
+    }
+
+    #[test]
+    fn test_printer_capture() {
+        let dir = std::env::temp_dir().join(format!("hemu_lpt_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("lpt1.txt");
+        let _ = std::fs::remove_file(&path);
+        let output: PrinterOutput = format!("text:{}", path.display()).parse().unwrap();
+
+        let mut sys = PcSystem::new();
+        sys.attach_printer(&output).unwrap();
+        assert!(sys.is_printer_attached());
+        assert_eq!(sys.printer_output(), Some(&output));
+
+        // Print through the port: bold on, text, CR/LF
+        for &byte in b"\x1BEHello\r\n" {
+            let bus = sys.cpu.bus_mut();
+            bus.io_write(0x378, byte);
+            bus.io_write(0x37A, 0x0D);
+            bus.io_write(0x37A, 0x0C);
+        }
+        sys.detach_printer();
+        assert!(!sys.is_printer_attached());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "Hello\n");
+
+        let missing = PrinterOutput {
+            format: PrinterFormat::Raw,
+            path: dir.join("missing").join("lpt1.prn").display().to_string(),
+        };
+        assert!(matches!(
+            sys.attach_printer(&missing),
+            Err(PcError::PrinterCapture { .. })
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_cpu_speed_parse() {
+        assert_eq!("model".parse::<CpuSpeed>(), Ok(CpuSpeed::Model));
+        assert_eq!("Unlimited".parse::<CpuSpeed>(), Ok(CpuSpeed::Unlimited));
+        assert_eq!("turbo".parse::<CpuSpeed>(), Ok(CpuSpeed::Unlimited));
+        assert_eq!("8".parse::<CpuSpeed>(), Ok(CpuSpeed::Fixed(8.0)));
+        assert_eq!("33.3 MHz".parse::<CpuSpeed>(), Ok(CpuSpeed::Fixed(33.3)));
+        assert!("fast".parse::<CpuSpeed>().is_err());
+        assert!("-5".parse::<CpuSpeed>().is_err());
+
+        for speed in [CpuSpeed::Model, CpuSpeed::Fixed(4.77), CpuSpeed::Unlimited] {
+            assert_eq!(speed.to_string().parse::<CpuSpeed>(), Ok(speed));
+        }
+    }
+
+    #[test]
+    fn test_fixed_cpu_speed_frame_cycles() {
+        let mut sys = PcSystem::new();
+        sys.boot_delay_frames = 0;
+        sys.boot_started = true;
+        sys.set_cpu_speed_mhz(1.0);
+
+        // Spin in place (JMP $) so the frame is bounded only by the cycle budget
+        sys.cpu.bus_mut().write(0x0500, 0xEB);
+        sys.cpu.bus_mut().write(0x0501, 0xFE);
+        sys.cpu.set_cs(0x0000);
+        sys.cpu.set_ip(0x0500);
+
+        let _ = sys.step_frame();
+
+        // 1 MHz at 60 Hz = 16,666 cycles (plus the last instruction's overshoot)
+        assert!(sys.cycles >= 16_666, "ran {} cycles", sys.cycles);
+        assert!(sys.cycles <= 16_666 + 200, "ran {} cycles", sys.cycles);
+    }
+
+    #[test]
+    fn test_bell_plays_while_halted() {
+        let mut sys = PcSystem::new();
+        sys.boot_delay_frames = 0;
+        sys.boot_started = true;
+
+        // MOV AX, 0E07h; INT 10h (teletype BEL); HLT
+        let program = [0xB8, 0x07, 0x0E, 0xCD, 0x10, 0xF4];
+        for (i, &byte) in program.iter().enumerate() {
+            sys.cpu.bus_mut().write(0x0500 + i as u32, byte);
+        }
+        sys.cpu.set_cs(0x0000);
+        sys.cpu.set_ip(0x0500);
+
+        // The CPU halts in the first frame, but the beep sounds in every
+        // frame for its full ~0.5 s rather than stalling with the CPU
+        for frame in 0..25 {
+            let _ = sys.step_frame();
+            let samples = sys.get_audio_samples(735);
+            assert!(
+                samples.iter().any(|s| s.abs() > 4000),
+                "frame {} is silent",
+                frame
+            );
+        }
+        for _ in 0..10 {
+            let _ = sys.step_frame();
+        }
+        assert!(!sys.cpu.bus().bios_beep_active());
+    }
+
+    #[test]
+    fn test_unlimited_cpu_speed_measures_speed() {
+        let mut sys = PcSystem::new();
+        sys.boot_delay_frames = 0;
+        sys.boot_started = true;
+        sys.set_cpu_speed(CpuSpeed::Unlimited);
+        assert_eq!(sys.cpu_speed_mhz(), 4.77);
+
+        // Spin in place (JMP $) so the frame only ends on the time budget
+        sys.cpu.bus_mut().write(0x0500, 0xEB);
+        sys.cpu.bus_mut().write(0x0501, 0xFE);
+        sys.cpu.set_cs(0x0000);
+        sys.cpu.set_ip(0x0500);
+
+        let _ = sys.step_frame();
+
+        assert_eq!(sys.cpu_speed(), CpuSpeed::Unlimited);
+        assert!(sys.cycles > 0);
+        assert_eq!(
+            sys.cpu_speed_mhz(),
+            (sys.cycles as f64 * 60.0 / 1_000_000.0).max(MIN_CPU_SPEED_MHZ)
+        );
+    }
+
     #[test]
     fn test_post_screen_display() {
         // Test that the BIOS displays the POST screen
@@ -1928,6 +3201,113 @@ mod tests {
         assert_eq!(sys.framebuffer_dimensions(), (640, 400));
     }
 
+    #[test]
+    fn test_vga_mode_13h_rendering() {
+        let mut sys = PcSystem::with_config(
+            CpuModel::Intel8086,
+            640,
+            Box::new(SoftwareVgaAdapter::new()),
+        );
+        sys.cpu.bus_mut().set_video_mode(0x13, true);
+        sys.cpu.bus_mut().write(0xA0000 + 320 + 1, 4); // Red in the default palette
+
+        let frame = sys.render_frame();
+        assert_eq!((frame.width, frame.height), (320, 200));
+        assert_eq!(frame.pixels[320 + 1], 0xFFAA0000);
+        assert_eq!(frame.pixels[0], 0xFF000000);
+
+        // The VGA returns to 720x400 text
+        sys.cpu.bus_mut().set_video_mode(0x03, true);
+        let frame = sys.render_frame();
+        assert_eq!((frame.width, frame.height), (720, 400));
+    }
+
+    #[test]
+    fn test_ega_16_color_rendering() {
+        let mut sys = PcSystem::with_config(
+            CpuModel::Intel8086,
+            640,
+            Box::new(SoftwareEgaAdapter::new()),
+        );
+        sys.cpu.bus_mut().set_video_mode(0x10, true);
+        // Color 0Eh (yellow) in the leftmost pixel of line 1
+        sys.cpu.bus_mut().io_write(0x3C4, 0x02);
+        sys.cpu.bus_mut().io_write(0x3C5, 0x0E);
+        sys.cpu.bus_mut().write(0xA0000 + 80, 0x80);
+
+        let frame = sys.render_frame();
+        assert_eq!((frame.width, frame.height), (640, 350));
+        assert_eq!(frame.pixels[640], 0xFFFFFF55);
+        assert_eq!(frame.pixels[641], 0xFF000000);
+
+        // Text mode has the same resolution but shows the text buffer again
+        sys.cpu.bus_mut().set_video_mode(0x03, true);
+        sys.cpu.bus_mut().write(0xB8000, 0xDB); // Full block
+        sys.cpu.bus_mut().write(0xB8001, 0x0F);
+        let frame = sys.render_frame();
+        assert_eq!((frame.width, frame.height), (640, 350));
+        assert_eq!(frame.pixels[5 * 640], 0xFFFFFFFF);
+    }
+
+    #[test]
+    fn test_hercules_rendering() {
+        let mut sys = PcSystem::with_config(
+            CpuModel::Intel8086,
+            640,
+            Box::new(SoftwareHerculesAdapter::new()),
+        );
+        assert_eq!(sys.cpu.bus().video_adapter_type(), VideoAdapterType::Mda);
+
+        // Text comes from the monochrome buffer at B0000h
+        sys.cpu.bus_mut().write(0xB0000, 0xDB); // Full block
+        sys.cpu.bus_mut().write(0xB0001, 0x0F); // High intensity
+        let frame = sys.render_frame();
+        assert_eq!((frame.width, frame.height), (720, 350));
+        assert_eq!(frame.pixels[5 * 720], 0xFF55FF55);
+
+        // 720x348 graphics once the configuration switch allows it
+        sys.cpu.bus_mut().io_write(0x3BF, 0x01);
+        sys.cpu.bus_mut().io_write(0x3B8, 0x0A);
+        sys.cpu.bus_mut().write(0xB2000, 0x80); // Row 1, pixel 0
+        let frame = sys.render_frame();
+        assert_eq!((frame.width, frame.height), (720, 348));
+        assert_eq!(frame.pixels[720], 0xFF55FF55);
+        assert_eq!(frame.pixels[721], 0xFF000000);
+
+        // Back to text
+        sys.cpu.bus_mut().io_write(0x3B8, 0x08);
+        let frame = sys.render_frame();
+        assert_eq!((frame.width, frame.height), (720, 350));
+    }
+
+    #[test]
+    fn test_text_display_page_and_40_columns() {
+        let mut sys = PcSystem::new();
+        let lit = |frame: &Frame, x: usize| frame.pixels[5 * 640 + x] == 0xFFFFFFFF;
+
+        // The CRTC start address selects the displayed page (page 1 here)
+        sys.cpu.bus_mut().write(0xB9000, 0xDB);
+        sys.cpu.bus_mut().write(0xB9001, 0x0F);
+        sys.cpu.bus_mut().write(0xB9002, b' ');
+        sys.cpu.bus_mut().write(0xB9003, 0x00);
+        sys.cpu.bus_mut().io_write(0x3D4, 0x0C);
+        sys.cpu.bus_mut().io_write(0x3D5, 0x08);
+        let frame = sys.render_frame();
+        assert!(lit(&frame, 7));
+        assert!(!lit(&frame, 8));
+
+        // 40-column characters are twice as wide
+        sys.cpu.bus_mut().set_video_mode(0x01, false);
+        sys.cpu.bus_mut().write(0xB8000, 0xDB);
+        sys.cpu.bus_mut().write(0xB8001, 0x0F);
+        sys.cpu.bus_mut().write(0xB8002, b' ');
+        sys.cpu.bus_mut().write(0xB8003, 0x00);
+        let frame = sys.render_frame();
+        assert_eq!((frame.width, frame.height), (640, 400));
+        assert!(lit(&frame, 15));
+        assert!(!lit(&frame, 16));
+    }
+
     #[test]
     fn test_mount_validation_invalid_bios() {
         let mut sys = PcSystem::new();
@@ -2532,6 +3912,13 @@ mod boot_output_tests {
         use emu_core::cpu_8086::Memory8086;
 
         let mut sys = PcSystem::new();
+        // Noon, so POST seeds half a day of timer ticks
+        sys.set_rtc_clock(RtcClock::Frozen {
+            time: "1995-08-24 12:00:00"
+                .parse::<RtcDateTime>()
+                .unwrap()
+                .timestamp(),
+        });
 
         // The BDA is initialized when boot_delay_frames reaches 0
         // Set it to 1 so that the first step_frame() will decrement it to 0
@@ -2607,12 +3994,16 @@ mod boot_output_tests {
         let video_port = (bus.read(0x463) as u16) | ((bus.read(0x464) as u16) << 8);
         assert_eq!(video_port, 0x03D4, "Video base port should be 0x03D4");
 
-        // 0x046C: Timer tick count (4 bytes, should be initialized to 0)
+        // 0x046C: Timer tick count (4 bytes, set from the RTC time of day)
         let ticks = (bus.read(0x46C) as u32)
             | ((bus.read(0x46D) as u32) << 8)
             | ((bus.read(0x46E) as u32) << 16)
             | ((bus.read(0x46F) as u32) << 24);
-        assert_eq!(ticks, 0, "Timer tick count should be 0 initially");
+        assert_eq!(
+            ticks,
+            0x1800B0 / 2,
+            "Timer tick count should be set to noon"
+        );
 
         // 0x0475: Hard drive count
         let hd_count = bus.read(0x475);
@@ -2708,4 +4099,181 @@ mod boot_output_tests {
         config.set_level(LogCategory::CPU, old_level);
         config.set_rate_limit(old_rate_limit);
     }
+
+    #[test]
+    fn test_host_folder_hard_drive_boot_message() {
+        let dir = std::env::temp_dir().join("hemulator_host_folder_boot");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("game.com"), [0xCD, 0x20]).unwrap();
+        let hd = build_host_folder_image(&dir, HostDriveKind::HardDrive).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let mut sys = PcSystem::new();
+        assert!(sys.mount("HardDrive", &hd).is_ok());
+        sys.set_boot_priority(crate::BootPriority::HardDriveFirst);
+        sys.boot_delay_frames = 0;
+        sys.boot_started = false;
+        sys.cpu.bus_mut().vram_mut().fill(0);
+
+        // Booting the drive runs the MBR stub, which prints a message
+        let _ = sys.step_frame();
+        sys.cpu.set_cs(0x0000);
+        sys.cpu.set_ip(0x7C00);
+        sys.cpu.unhalt();
+        for _ in 0..5 {
+            let _ = sys.step_frame();
+        }
+
+        let vram = sys.cpu.bus().vram();
+        let text: String = (0..80 * 25)
+            .map(|i| vram[0x18000 + i * 2] as char)
+            .collect();
+        assert!(
+            text.contains("Host folder drive is not bootable"),
+            "Screen text: {}",
+            text.trim_matches('\0')
+        );
+    }
+
+    #[test]
+    fn test_builtin_dos_runs_programs_from_a_floppy() {
+        let dir = std::env::temp_dir().join("hemulator_builtin_dos");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        // Print a string, then exit
+        let mut hello = vec![
+            0xB4, 0x09, // MOV AH, 09h
+            0xBA, 0x0C, 0x01, // MOV DX, 010Ch
+            0xCD, 0x21, // INT 21h
+            0xB8, 0x00, 0x4C, // MOV AX, 4C00h
+            0xCD, 0x21, // INT 21h
+        ];
+        hello.extend_from_slice(b"Hello from DOS$");
+        std::fs::write(dir.join("hello.com"), &hello).unwrap();
+        // Create OUT.TXT containing "DATA", then exit
+        let mut write = vec![
+            0xB4, 0x3C, // MOV AH, 3Ch
+            0x31, 0xC9, // XOR CX, CX
+            0xBA, 0x1E, 0x01, // MOV DX, 011Eh
+            0xCD, 0x21, // INT 21h
+            0x89, 0xC3, // MOV BX, AX
+            0xB4, 0x40, // MOV AH, 40h
+            0xB9, 0x04, 0x00, // MOV CX, 4
+            0xBA, 0x26, 0x01, // MOV DX, 0126h
+            0xCD, 0x21, // INT 21h
+            0xB4, 0x3E, // MOV AH, 3Eh
+            0xCD, 0x21, // INT 21h
+            0xB8, 0x00, 0x4C, // MOV AX, 4C00h
+            0xCD, 0x21, // INT 21h
+        ];
+        write.extend_from_slice(b"OUT.TXT\0DATA");
+        std::fs::write(dir.join("write.com"), &write).unwrap();
+        let floppy = build_host_folder_image(&dir, HostDriveKind::Floppy).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let mut sys = PcSystem::new();
+        assert!(sys.mount("FloppyA", &floppy).is_ok());
+
+        // The floppy has no boot signature, so the BIOS starts the built-in DOS
+        for _ in 0..600 {
+            if sys.cpu.bus().dos.is_running() {
+                break;
+            }
+            let _ = sys.step_frame();
+        }
+        assert!(sys.cpu.bus().dos.is_running());
+
+        let type_line = |sys: &mut PcSystem, keys: &[u8]| {
+            for &key in keys.iter().chain(&[SCANCODE_ENTER]) {
+                sys.key_press(key);
+                let _ = sys.step_frame();
+                sys.key_release(key);
+                let _ = sys.step_frame();
+            }
+            for _ in 0..5 {
+                let _ = sys.step_frame();
+            }
+        };
+        type_line(
+            &mut sys,
+            &[SCANCODE_H, SCANCODE_E, SCANCODE_L, SCANCODE_L, SCANCODE_O],
+        );
+        type_line(
+            &mut sys,
+            &[SCANCODE_W, SCANCODE_R, SCANCODE_I, SCANCODE_T, SCANCODE_E],
+        );
+
+        let vram = sys.cpu.bus().vram();
+        let text: String = (0..80 * 25)
+            .map(|i| vram[0x18000 + i * 2] as char)
+            .collect();
+        assert!(text.contains("Hello from DOS"), "Screen text: {}", text);
+        assert!(text.contains("A:\\>write"), "Screen text: {}", text);
+
+        // Back at the prompt, with the new file on the floppy
+        let bus = sys.cpu.bus();
+        assert_eq!(bus.dos.psp, 0);
+        let (volume, image) = bus.dos_volume(0).unwrap();
+        let entry = volume
+            .find(image, crate::dos_fs::ROOT_DIR, b"OUT     TXT")
+            .unwrap();
+        assert_eq!(volume.read(image, &entry), b"DATA");
+    }
+
+    #[test]
+    fn test_load_exe_runs_on_builtin_dos() {
+        // Code: load DS with the data segment (relocated), print, exit
+        let mut image = vec![
+            0xB8, 0x02, 0x00, // MOV AX, 0002h (segment of the message)
+            0x8E, 0xD8, // MOV DS, AX
+            0xB4, 0x09, // MOV AH, 09h
+            0xBA, 0x00, 0x00, // MOV DX, 0000h
+            0xCD, 0x21, // INT 21h
+            0xB8, 0x00, 0x4C, // MOV AX, 4C00h
+            0xCD, 0x21, // INT 21h
+        ];
+        image.resize(0x20, 0x90);
+        image.extend_from_slice(b"Hello from an EXE$");
+
+        let mut exe = vec![0u8; 0x20];
+        let file_len = (exe.len() + image.len()) as u16;
+        exe[..2].copy_from_slice(b"MZ");
+        exe[0x02..0x04].copy_from_slice(&(file_len % 512).to_le_bytes()); // Bytes on last page
+        exe[0x04..0x06].copy_from_slice(&1u16.to_le_bytes()); // Pages
+        exe[0x06..0x08].copy_from_slice(&1u16.to_le_bytes()); // Relocations
+        exe[0x08..0x0A].copy_from_slice(&2u16.to_le_bytes()); // Header paragraphs
+        exe[0x0A..0x0C].copy_from_slice(&0x11u16.to_le_bytes()); // Minimum allocation
+        exe[0x0C..0x0E].copy_from_slice(&0xFFFFu16.to_le_bytes()); // Maximum allocation
+        exe[0x0E..0x10].copy_from_slice(&4u16.to_le_bytes()); // SS
+        exe[0x10..0x12].copy_from_slice(&0x100u16.to_le_bytes()); // SP
+        exe[0x18..0x1A].copy_from_slice(&0x1Cu16.to_le_bytes()); // Relocation table
+        exe[0x1C..0x20].copy_from_slice(&[0x01, 0x00, 0x00, 0x00]); // 0000:0001
+        exe.extend_from_slice(&image);
+
+        let mut sys = PcSystem::new();
+        assert!(sys.load_executable(&exe[..0x20]).is_err());
+        assert!(sys.mount("Executable", &exe).is_ok());
+        assert!(sys.is_mounted("Executable"));
+
+        for _ in 0..600 {
+            if sys.cpu.bus().dos.is_running() {
+                break;
+            }
+            let _ = sys.step_frame();
+        }
+        assert!(sys.cpu.bus().dos.is_running());
+        for _ in 0..10 {
+            let _ = sys.step_frame();
+        }
+
+        let vram = sys.cpu.bus().vram();
+        let text: String = (0..80 * 25)
+            .map(|i| vram[0x18000 + i * 2] as char)
+            .collect();
+        assert!(text.contains("Hello from an EXE"), "Screen text: {}", text);
+        // Back at the prompt after the program exits
+        assert_eq!(sys.cpu.bus().dos.psp, 0);
+        assert_eq!(sys.cpu.bus().dos.return_code, 0);
+    }
 }
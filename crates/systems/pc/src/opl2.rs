@@ -0,0 +1,647 @@
+//! Yamaha YM3812 (OPL2) FM synthesis chip
+//!
+//! The OPL2 is the music chip of the AdLib card (ports 0x388/0x389) and the
+//! Sound Blaster (also mirrored at base+8/base+9). It provides:
+//! - 9 two-operator FM channels (FM or additive connection, modulator feedback)
+//! - Per-operator ADSR envelope, key scaling, tremolo and vibrato
+//! - 4 waveforms (sine, half-sine, absolute sine, quarter sine)
+//! - Two timers with status flags, used by software to detect the chip
+//!
+//! Samples are synthesized on demand at the host output rate. Rhythm mode
+//! (register 0xBD bit 5) is not emulated; channels 6-8 keep playing as
+//! melodic channels.
+
+use std::f64::consts::PI;
+
+/// Host output sample rate in Hz
+pub const SAMPLE_RATE: f64 = 44100.0;
+
+/// Native OPL2 sample rate (3.579545 MHz / 72)
+const OPL_RATE: f64 = 49716.0;
+
+/// Attenuation (dB) at which an operator is considered silent
+const MAX_ATTENUATION: f64 = 96.0;
+
+/// Output level of one channel at full volume
+const CHANNEL_AMPLITUDE: f64 = 4096.0;
+
+/// Timer 1 and timer 2 tick periods in microseconds
+const TIMER1_PERIOD_US: f64 = 80.0;
+const TIMER2_PERIOD_US: f64 = 320.0;
+
+/// Frequency multiplier (MULT register value 0-15)
+const MULTIPLIERS: [f64; 16] = [
+    0.5, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0, 10.0, 12.0, 12.0, 15.0, 15.0,
+];
+
+/// Key scale level attenuation (dB) at block 7 for the top 4 F-number bits
+const KSL_TABLE: [f64; 16] = [
+    0.0, 9.0, 12.0, 13.875, 15.0, 16.125, 16.875, 17.625, 18.0, 18.75, 19.125, 19.5, 19.875, 20.25,
+    20.625, 21.0,
+];
+
+/// Operator register offsets (0x00-0x15) to operator slot, None for unused offsets
+const SLOT_FOR_OFFSET: [Option<usize>; 22] = [
+    Some(0),
+    Some(1),
+    Some(2),
+    Some(3),
+    Some(4),
+    Some(5),
+    None,
+    None,
+    Some(6),
+    Some(7),
+    Some(8),
+    Some(9),
+    Some(10),
+    Some(11),
+    None,
+    None,
+    Some(12),
+    Some(13),
+    Some(14),
+    Some(15),
+    Some(16),
+    Some(17),
+];
+
+/// Envelope generator phase
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EnvelopeState {
+    Attack,
+    Decay,
+    Sustain,
+    Release,
+    Off,
+}
+
+/// One FM operator (slot)
+#[derive(Debug, Clone)]
+struct Operator {
+    // Register 0x20: AM / VIB / EG-TYP / KSR / MULT
+    tremolo: bool,
+    vibrato: bool,
+    sustained: bool,
+    ksr: bool,
+    mult: u8,
+    // Register 0x40: KSL / TL
+    ksl: u8,
+    total_level: u8,
+    // Register 0x60: AR / DR
+    attack_rate: u8,
+    decay_rate: u8,
+    // Register 0x80: SL / RR
+    sustain_level: u8,
+    release_rate: u8,
+    // Register 0xE0: waveform select
+    waveform: u8,
+
+    /// Phase in cycles (0.0-1.0)
+    phase: f64,
+    /// Envelope attenuation in dB (0 = full volume)
+    envelope: f64,
+    state: EnvelopeState,
+}
+
+impl Operator {
+    fn new() -> Self {
+        Self {
+            tremolo: false,
+            vibrato: false,
+            sustained: false,
+            ksr: false,
+            mult: 0,
+            ksl: 0,
+            total_level: 0,
+            attack_rate: 0,
+            decay_rate: 0,
+            sustain_level: 0,
+            release_rate: 0,
+            waveform: 0,
+            phase: 0.0,
+            envelope: MAX_ATTENUATION,
+            state: EnvelopeState::Off,
+        }
+    }
+
+    fn key_on(&mut self) {
+        self.phase = 0.0;
+        self.state = EnvelopeState::Attack;
+    }
+
+    fn key_off(&mut self) {
+        if self.state != EnvelopeState::Off {
+            self.state = EnvelopeState::Release;
+        }
+    }
+
+    /// Effective envelope rate (0-63) including key scale rate
+    fn effective_rate(&self, rate: u8, key_code: u8) -> u8 {
+        if rate == 0 {
+            return 0;
+        }
+        let offset = if self.ksr { key_code } else { key_code >> 2 };
+        (rate * 4 + offset).min(63)
+    }
+
+    /// Advance the envelope by one output sample
+    fn step_envelope(&mut self, key_code: u8) {
+        // Time in ms to sweep the full 96 dB range at rate 4; each rate step
+        // of 4 halves the time
+        let sweep_db = |base_ms: f64, rate: u8| -> f64 {
+            if rate < 4 {
+                return 0.0;
+            }
+            let ms = base_ms / 2f64.powf((rate as f64 - 4.0) / 4.0);
+            MAX_ATTENUATION / (ms * SAMPLE_RATE / 1000.0)
+        };
+        let sustain_db = if self.sustain_level == 15 {
+            93.0
+        } else {
+            self.sustain_level as f64 * 3.0
+        };
+
+        match self.state {
+            EnvelopeState::Attack => {
+                let rate = self.effective_rate(self.attack_rate, key_code);
+                if rate >= 60 {
+                    self.envelope = 0.0;
+                } else {
+                    self.envelope -= sweep_db(2826.24, rate);
+                }
+                if self.envelope <= 0.0 {
+                    self.envelope = 0.0;
+                    self.state = EnvelopeState::Decay;
+                }
+            }
+            EnvelopeState::Decay => {
+                let rate = self.effective_rate(self.decay_rate, key_code);
+                self.envelope += sweep_db(39280.64, rate);
+                if self.envelope >= sustain_db {
+                    self.envelope = sustain_db;
+                    self.state = EnvelopeState::Sustain;
+                }
+            }
+            EnvelopeState::Sustain => {
+                // Percussive sounds (EG-TYP = 0) keep decaying at the release rate
+                if !self.sustained {
+                    let rate = self.effective_rate(self.release_rate, key_code);
+                    self.envelope += sweep_db(39280.64, rate);
+                }
+            }
+            EnvelopeState::Release => {
+                let rate = self.effective_rate(self.release_rate, key_code);
+                self.envelope += sweep_db(39280.64, rate);
+            }
+            EnvelopeState::Off => {}
+        }
+
+        if self.envelope >= MAX_ATTENUATION {
+            self.envelope = MAX_ATTENUATION;
+            if self.state != EnvelopeState::Attack {
+                self.state = EnvelopeState::Off;
+            }
+        }
+    }
+
+    /// Key scale level attenuation in dB
+    fn ksl_attenuation(&self, fnum: u16, block: u8) -> f64 {
+        if self.ksl == 0 {
+            return 0.0;
+        }
+        let base = (KSL_TABLE[(fnum >> 6) as usize & 0x0F] - 6.0 * (7 - block) as f64).max(0.0);
+        match self.ksl {
+            1 => base,
+            2 => base * 0.5,
+            _ => base * 2.0,
+        }
+    }
+
+    /// Compute the operator output (-1.0..1.0) for a phase offset in cycles
+    fn output(&self, modulation: f64, attenuation: f64, waveform_enabled: bool) -> f64 {
+        let total = self.envelope + attenuation;
+        if self.state == EnvelopeState::Off || total >= MAX_ATTENUATION {
+            return 0.0;
+        }
+        let phase = (self.phase + modulation).rem_euclid(1.0);
+        let sine = (2.0 * PI * phase).sin();
+        let wave = match if waveform_enabled { self.waveform } else { 0 } {
+            0 => sine,
+            1 => sine.max(0.0),
+            2 => sine.abs(),
+            _ => {
+                if phase % 0.5 < 0.25 {
+                    sine.abs()
+                } else {
+                    0.0
+                }
+            }
+        };
+        wave * 10f64.powf(-total / 20.0)
+    }
+}
+
+/// One two-operator channel
+#[derive(Debug, Clone)]
+struct Channel {
+    fnum: u16,
+    block: u8,
+    key_on: bool,
+    feedback: u8,
+    additive: bool,
+    /// Last two modulator outputs (for feedback)
+    feedback_history: [f64; 2],
+}
+
+impl Channel {
+    fn new() -> Self {
+        Self {
+            fnum: 0,
+            block: 0,
+            key_on: false,
+            feedback: 0,
+            additive: false,
+            feedback_history: [0.0; 2],
+        }
+    }
+
+    /// Base frequency in Hz
+    fn frequency(&self) -> f64 {
+        self.fnum as f64 * OPL_RATE / (1u32 << (20 - self.block as u32)) as f64
+    }
+}
+
+/// Yamaha YM3812 (OPL2)
+pub struct Opl2 {
+    /// Register address latch
+    address: u8,
+    /// Raw register file (for rhythm/depth flags and register reads in tests)
+    registers: [u8; 256],
+    operators: Vec<Operator>,
+    channels: Vec<Channel>,
+    /// Register 0x01 bit 5: waveform select enable
+    waveform_enabled: bool,
+    /// Register 0x08 bit 6: keyboard split (note select)
+    note_select: bool,
+
+    // Timers
+    timer1_reload: u8,
+    timer2_reload: u8,
+    timer1_counter: u8,
+    timer2_counter: u8,
+    timer1_running: bool,
+    timer2_running: bool,
+    timer1_masked: bool,
+    timer2_masked: bool,
+    timer1_elapsed_us: f64,
+    timer2_elapsed_us: f64,
+    status: u8,
+
+    /// LFO time in seconds (tremolo and vibrato)
+    lfo_time: f64,
+}
+
+impl Opl2 {
+    pub fn new() -> Self {
+        Self {
+            address: 0,
+            registers: [0; 256],
+            operators: vec![Operator::new(); 18],
+            channels: vec![Channel::new(); 9],
+            waveform_enabled: false,
+            note_select: false,
+            timer1_reload: 0,
+            timer2_reload: 0,
+            timer1_counter: 0,
+            timer2_counter: 0,
+            timer1_running: false,
+            timer2_running: false,
+            timer1_masked: false,
+            timer2_masked: false,
+            timer1_elapsed_us: 0.0,
+            timer2_elapsed_us: 0.0,
+            status: 0,
+            lfo_time: 0.0,
+        }
+    }
+
+    /// Reset the chip to power-on state
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Write the register address (port 0x388)
+    pub fn write_address(&mut self, value: u8) {
+        self.address = value;
+    }
+
+    /// Read the status register (port 0x388)
+    ///
+    /// Bit 7: IRQ, bit 6: timer 1 overflow, bit 5: timer 2 overflow.
+    /// The OPL2 also returns bits 1-2 set, which software uses to tell it
+    /// apart from an OPL3.
+    pub fn read_status(&self) -> u8 {
+        self.status | 0x06
+    }
+
+    /// Write a value to the latched register (port 0x389)
+    pub fn write_data(&mut self, value: u8) {
+        let reg = self.address;
+        self.registers[reg as usize] = value;
+
+        match reg {
+            0x01 => self.waveform_enabled = value & 0x20 != 0,
+            0x02 => self.timer1_reload = value,
+            0x03 => self.timer2_reload = value,
+            0x04 => {
+                if value & 0x80 != 0 {
+                    // IRQ reset: clear all status flags, other bits ignored
+                    self.status = 0;
+                    return;
+                }
+                self.timer1_masked = value & 0x40 != 0;
+                self.timer2_masked = value & 0x20 != 0;
+                if value & 0x01 != 0 && !self.timer1_running {
+                    self.timer1_counter = self.timer1_reload;
+                    self.timer1_elapsed_us = 0.0;
+                }
+                if value & 0x02 != 0 && !self.timer2_running {
+                    self.timer2_counter = self.timer2_reload;
+                    self.timer2_elapsed_us = 0.0;
+                }
+                self.timer1_running = value & 0x01 != 0;
+                self.timer2_running = value & 0x02 != 0;
+            }
+            0x08 => self.note_select = value & 0x40 != 0,
+            0x20..=0x35 => {
+                if let Some(op) = self.operator_mut(reg - 0x20) {
+                    op.tremolo = value & 0x80 != 0;
+                    op.vibrato = value & 0x40 != 0;
+                    op.sustained = value & 0x20 != 0;
+                    op.ksr = value & 0x10 != 0;
+                    op.mult = value & 0x0F;
+                }
+            }
+            0x40..=0x55 => {
+                if let Some(op) = self.operator_mut(reg - 0x40) {
+                    op.ksl = value >> 6;
+                    op.total_level = value & 0x3F;
+                }
+            }
+            0x60..=0x75 => {
+                if let Some(op) = self.operator_mut(reg - 0x60) {
+                    op.attack_rate = value >> 4;
+                    op.decay_rate = value & 0x0F;
+                }
+            }
+            0x80..=0x95 => {
+                if let Some(op) = self.operator_mut(reg - 0x80) {
+                    op.sustain_level = value >> 4;
+                    op.release_rate = value & 0x0F;
+                }
+            }
+            0xA0..=0xA8 => {
+                let ch = &mut self.channels[(reg - 0xA0) as usize];
+                ch.fnum = (ch.fnum & 0x300) | value as u16;
+            }
+            0xB0..=0xB8 => {
+                let index = (reg - 0xB0) as usize;
+                let ch = &mut self.channels[index];
+                ch.fnum = (ch.fnum & 0xFF) | (((value & 0x03) as u16) << 8);
+                ch.block = (value >> 2) & 0x07;
+                let key_on = value & 0x20 != 0;
+                if key_on != ch.key_on {
+                    ch.key_on = key_on;
+                    for op in self.channel_operators(index) {
+                        if key_on {
+                            self.operators[op].key_on();
+                        } else {
+                            self.operators[op].key_off();
+                        }
+                    }
+                }
+            }
+            0xC0..=0xC8 => {
+                let ch = &mut self.channels[(reg - 0xC0) as usize];
+                ch.feedback = (value >> 1) & 0x07;
+                ch.additive = value & 0x01 != 0;
+            }
+            0xE0..=0xF5 => {
+                if let Some(op) = self.operator_mut(reg - 0xE0) {
+                    op.waveform = value & 0x03;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn operator_mut(&mut self, offset: u8) -> Option<&mut Operator> {
+        SLOT_FOR_OFFSET
+            .get(offset as usize)
+            .copied()
+            .flatten()
+            .map(|slot| &mut self.operators[slot])
+    }
+
+    /// Operator slots (modulator, carrier) of a channel
+    fn channel_operators(&self, channel: usize) -> [usize; 2] {
+        let base = (channel / 3) * 6 + channel % 3;
+        [base, base + 3]
+    }
+
+    /// Advance the timers by elapsed emulated time
+    pub fn advance(&mut self, us: f64) {
+        if self.timer1_running {
+            self.timer1_elapsed_us += us;
+            while self.timer1_elapsed_us >= TIMER1_PERIOD_US {
+                self.timer1_elapsed_us -= TIMER1_PERIOD_US;
+                self.timer1_counter = self.timer1_counter.wrapping_add(1);
+                if self.timer1_counter == 0 {
+                    self.timer1_counter = self.timer1_reload;
+                    if !self.timer1_masked {
+                        self.status |= 0xC0;
+                    }
+                }
+            }
+        }
+        if self.timer2_running {
+            self.timer2_elapsed_us += us;
+            while self.timer2_elapsed_us >= TIMER2_PERIOD_US {
+                self.timer2_elapsed_us -= TIMER2_PERIOD_US;
+                self.timer2_counter = self.timer2_counter.wrapping_add(1);
+                if self.timer2_counter == 0 {
+                    self.timer2_counter = self.timer2_reload;
+                    if !self.timer2_masked {
+                        self.status |= 0xA0;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Synthesize `count` samples at the host output rate
+    pub fn generate_samples(&mut self, count: usize) -> Vec<i16> {
+        let mut samples = Vec::with_capacity(count);
+        let dt = 1.0 / SAMPLE_RATE;
+        let depth = self.registers[0xBD];
+        let tremolo_depth = if depth & 0x80 != 0 { 4.8 } else { 1.0 };
+        let vibrato_cents = if depth & 0x40 != 0 { 14.0 } else { 7.0 };
+
+        for _ in 0..count {
+            self.lfo_time += dt;
+            let tremolo = tremolo_depth * 0.5 * (1.0 + (2.0 * PI * 3.7 * self.lfo_time).sin());
+            let vibrato =
+                2f64.powf(vibrato_cents / 1200.0 * (2.0 * PI * 6.1 * self.lfo_time).sin());
+
+            let mut mix = 0.0;
+            for index in 0..self.channels.len() {
+                mix += self.channel_sample(index, tremolo, vibrato);
+            }
+            samples.push((mix * CHANNEL_AMPLITUDE).clamp(-32768.0, 32767.0) as i16);
+        }
+
+        samples
+    }
+
+    /// Compute one sample of a channel and advance its operators
+    fn channel_sample(&mut self, index: usize, tremolo: f64, vibrato: f64) -> f64 {
+        let [m, c] = self.channel_operators(index);
+        let ch = &self.channels[index];
+        if self.operators[m].state == EnvelopeState::Off
+            && self.operators[c].state == EnvelopeState::Off
+        {
+            return 0.0;
+        }
+
+        let (fnum, block) = (ch.fnum, ch.block);
+        let split_bit = if self.note_select {
+            fnum >> 8
+        } else {
+            fnum >> 9
+        } & 1;
+        let key_code = (block << 1) | split_bit as u8;
+        let base_freq = ch.frequency();
+
+        let attenuation = |op: &Operator| {
+            op.total_level as f64 * 0.75
+                + op.ksl_attenuation(fnum, block)
+                + if op.tremolo { tremolo } else { 0.0 }
+        };
+
+        // Modulator with self-feedback: FB 1 = pi/16 ... FB 7 = 4 pi
+        let feedback = if ch.feedback > 0 {
+            (ch.feedback_history[0] + ch.feedback_history[1]) / 2.0
+                * 2f64.powi(ch.feedback as i32 - 6)
+        } else {
+            0.0
+        };
+        let modulator = &self.operators[m];
+        let mod_out = modulator.output(feedback, attenuation(modulator), self.waveform_enabled);
+        let carrier = &self.operators[c];
+        let out = if ch.additive {
+            mod_out + carrier.output(0.0, attenuation(carrier), self.waveform_enabled)
+        } else {
+            // Full modulator output shifts the carrier phase by 4 pi
+            carrier.output(mod_out * 2.0, attenuation(carrier), self.waveform_enabled)
+        };
+
+        let ch = &mut self.channels[index];
+        ch.feedback_history = [ch.feedback_history[1], mod_out];
+
+        for slot in [m, c] {
+            let op = &mut self.operators[slot];
+            let freq =
+                base_freq * MULTIPLIERS[op.mult as usize] * if op.vibrato { vibrato } else { 1.0 };
+            op.phase = (op.phase + freq / SAMPLE_RATE).fract();
+            op.step_envelope(key_code);
+        }
+
+        out
+    }
+}
+
+impl Default for Opl2 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(opl: &mut Opl2, reg: u8, value: u8) {
+        opl.write_address(reg);
+        opl.write_data(value);
+    }
+
+    #[test]
+    fn test_adlib_detection_sequence() {
+        let mut opl = Opl2::new();
+
+        // Reset both timers and IRQ
+        write(&mut opl, 0x04, 0x60);
+        write(&mut opl, 0x04, 0x80);
+        assert_eq!(opl.read_status() & 0xE0, 0x00);
+
+        // Start timer 1 one tick before overflow and wait > 80us
+        write(&mut opl, 0x02, 0xFF);
+        write(&mut opl, 0x04, 0x21);
+        opl.advance(100.0);
+        assert_eq!(opl.read_status() & 0xE0, 0xC0);
+
+        // Reset clears the flags again
+        write(&mut opl, 0x04, 0x60);
+        write(&mut opl, 0x04, 0x80);
+        assert_eq!(opl.read_status() & 0xE0, 0x00);
+    }
+
+    #[test]
+    fn test_masked_timer_sets_no_flag() {
+        let mut opl = Opl2::new();
+        write(&mut opl, 0x03, 0xFF);
+        write(&mut opl, 0x04, 0x22); // Start timer 2, mask timer 2
+        opl.advance(1000.0);
+        assert_eq!(opl.read_status() & 0xE0, 0x00);
+    }
+
+    #[test]
+    fn test_silent_when_no_key_on() {
+        let mut opl = Opl2::new();
+        let samples = opl.generate_samples(256);
+        assert!(samples.iter().all(|&s| s == 0));
+    }
+
+    #[test]
+    fn test_key_on_produces_tone_and_key_off_releases() {
+        let mut opl = Opl2::new();
+        // Channel 0: carrier (slot 3, offset 0x03) full volume, instant attack
+        write(&mut opl, 0x23, 0x21); // Sustained, MULT=1
+        write(&mut opl, 0x43, 0x00); // TL=0
+        write(&mut opl, 0x63, 0xF0); // AR=15, DR=0
+        write(&mut opl, 0x83, 0x0F); // SL=0, RR=15
+                                     // Modulator silent
+        write(&mut opl, 0x40, 0x3F);
+        // A4 (~440 Hz): fnum 0x244, block 4
+        write(&mut opl, 0xA0, 0x44);
+        write(&mut opl, 0xB0, 0x32);
+
+        let samples = opl.generate_samples(4410);
+        let peak = samples.iter().map(|s| s.unsigned_abs()).max().unwrap();
+        assert!(peak > 3000, "peak {}", peak);
+
+        // Count zero crossings over 0.1s: ~440 Hz gives ~88
+        let crossings = samples
+            .windows(2)
+            .filter(|w| (w[0] < 0) != (w[1] < 0))
+            .count();
+        assert!((80..=96).contains(&crossings), "crossings {}", crossings);
+
+        // Key off with fastest release fades out quickly
+        write(&mut opl, 0xB0, 0x12);
+        opl.generate_samples(4410);
+        let tail = opl.generate_samples(64);
+        assert!(tail.iter().all(|&s| s == 0));
+    }
+}
@@ -0,0 +1,275 @@
+//! Standard PC parallel printer port (LPT1)
+//!
+//! Registers relative to the base port:
+//! - +0: Data latch (read/write)
+//! - +1: Status (read only): /BUSY, /ACK, paper end, select, /ERROR
+//! - +2: Control: STROBE, AUTOFEED, /INIT, SELECT IN, IRQ enable
+//!
+//! A byte is handed to the printer when the guest pulses STROBE. The
+//! printer is an optional host writer (see [`crate::printer`]) that takes
+//! every byte immediately, so the port never reports busy. Without one the
+//! port reports an offline printer with an error, which DOS turns into a
+//! "printer not ready" message rather than waiting forever.
+
+use emu_core::logging::{log, LogCategory, LogLevel};
+use std::cell::Cell;
+use std::io::Write;
+
+/// LPT1 base port
+pub const LPT1_BASE_PORT: u16 = 0x378;
+/// LPT1 IRQ line (INT 0Fh)
+pub const LPT1_IRQ: u8 = 7;
+
+const REG_DATA: u16 = 0;
+const REG_STATUS: u16 = 1;
+const REG_CONTROL: u16 = 2;
+
+/// Status: printer error (active low)
+const STATUS_NOT_ERROR: u8 = 0x08;
+/// Status: printer selected (online)
+const STATUS_SELECT: u8 = 0x10;
+/// Status: acknowledge (active low)
+const STATUS_NOT_ACK: u8 = 0x40;
+/// Status: printer busy (active low)
+const STATUS_NOT_BUSY: u8 = 0x80;
+/// Status bits 0-2 are not connected and read high
+const STATUS_UNUSED: u8 = 0x07;
+
+/// Control: strobe (a 1 pulls the line low)
+const CONTROL_STROBE: u8 = 0x01;
+/// Control: initialize printer (active low)
+const CONTROL_NOT_INIT: u8 = 0x04;
+/// Control: select printer
+const CONTROL_SELECT_IN: u8 = 0x08;
+/// Control: raise IRQ 7 on acknowledge
+const CONTROL_IRQ_ENABLE: u8 = 0x10;
+/// Control bits 5-7 are not implemented and read high
+const CONTROL_UNUSED: u8 = 0xE0;
+
+/// Parallel printer port
+pub struct ParallelPort {
+    data: u8,
+    control: u8,
+    /// The next status read shows the acknowledge pulse of the last byte
+    ack_pulse: Cell<bool>,
+    /// A write to the host failed; reported as a printer error
+    fault: bool,
+    /// IRQ requested by an acknowledge with interrupts enabled
+    irq_request: bool,
+    printer: Option<Box<dyn Write + Send>>,
+}
+
+impl ParallelPort {
+    /// Create a port with no printer attached
+    pub fn new() -> Self {
+        Self {
+            data: 0,
+            control: CONTROL_NOT_INIT | CONTROL_SELECT_IN,
+            ack_pulse: Cell::new(false),
+            fault: false,
+            irq_request: false,
+            printer: None,
+        }
+    }
+
+    /// Reset the port registers, keeping the printer attached
+    pub fn reset(&mut self) {
+        self.data = 0;
+        self.control = CONTROL_NOT_INIT | CONTROL_SELECT_IN;
+        self.ack_pulse.set(false);
+        self.irq_request = false;
+        self.flush();
+    }
+
+    /// Attach a printer, replacing (and flushing) any previous one
+    pub fn attach(&mut self, printer: Box<dyn Write + Send>) {
+        self.detach();
+        self.printer = Some(printer);
+    }
+
+    /// Flush and remove the printer
+    pub fn detach(&mut self) {
+        self.flush();
+        self.printer = None;
+        self.fault = false;
+    }
+
+    /// Check if a printer is attached
+    pub fn is_attached(&self) -> bool {
+        self.printer.is_some()
+    }
+
+    /// Read a port register (`offset` from the base port)
+    pub fn io_read(&self, offset: u16) -> u8 {
+        match offset {
+            REG_DATA => self.data,
+            REG_STATUS => self.status(),
+            REG_CONTROL => self.control | CONTROL_UNUSED,
+            _ => 0xFF,
+        }
+    }
+
+    /// Write a port register (`offset` from the base port)
+    pub fn io_write(&mut self, offset: u16, val: u8) {
+        match offset {
+            REG_DATA => self.data = val,
+            REG_CONTROL => {
+                let strobe = val & CONTROL_STROBE != 0 && self.control & CONTROL_STROBE == 0;
+                let init = val & CONTROL_NOT_INIT == 0 && self.control & CONTROL_NOT_INIT != 0;
+                self.control = val & !CONTROL_UNUSED;
+                if init {
+                    // Initializing the printer clears a previous write error
+                    self.fault = false;
+                }
+                if strobe {
+                    self.print(self.data);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Status register as seen by the guest
+    fn status(&self) -> u8 {
+        if self.printer.is_none() || self.fault {
+            // Offline with an error, but not busy
+            return STATUS_NOT_BUSY | STATUS_NOT_ACK | STATUS_UNUSED;
+        }
+        let ack = if self.ack_pulse.replace(false) {
+            0
+        } else {
+            STATUS_NOT_ACK
+        };
+        STATUS_NOT_BUSY | ack | STATUS_SELECT | STATUS_NOT_ERROR | STATUS_UNUSED
+    }
+
+    /// Hand a strobed byte to the printer
+    fn print(&mut self, byte: u8) {
+        if self.fault {
+            return;
+        }
+        let Some(printer) = self.printer.as_mut() else {
+            return;
+        };
+        let mut result = printer.write_all(&[byte]);
+        // Keep the host file current at line and page ends
+        if result.is_ok() && matches!(byte, b'\n' | 0x0C) {
+            result = printer.flush();
+        }
+        if let Err(e) = result {
+            log(LogCategory::Bus, LogLevel::Warn, || {
+                format!("LPT1: printer output failed: {}", e)
+            });
+            self.fault = true;
+            return;
+        }
+        self.ack_pulse.set(true);
+        if self.control & CONTROL_IRQ_ENABLE != 0 {
+            self.irq_request = true;
+        }
+    }
+
+    /// Flush buffered printer output to the host
+    pub fn flush(&mut self) {
+        if let Some(printer) = self.printer.as_mut() {
+            if printer.flush().is_err() {
+                self.fault = true;
+            }
+        }
+    }
+
+    /// Take a pending acknowledge interrupt request
+    pub fn take_irq_request(&mut self) -> bool {
+        std::mem::take(&mut self.irq_request)
+    }
+}
+
+impl Default for ParallelPort {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for ParallelPort {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    /// Printer that records everything written to it
+    #[derive(Clone, Default)]
+    struct Capture(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Capture {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn strobe(port: &mut ParallelPort, byte: u8) {
+        port.io_write(REG_DATA, byte);
+        port.io_write(
+            REG_CONTROL,
+            CONTROL_NOT_INIT | CONTROL_SELECT_IN | CONTROL_STROBE,
+        );
+        port.io_write(REG_CONTROL, CONTROL_NOT_INIT | CONTROL_SELECT_IN);
+    }
+
+    #[test]
+    fn test_strobe_prints_byte() {
+        let capture = Capture::default();
+        let mut port = ParallelPort::new();
+        port.attach(Box::new(capture.clone()));
+        assert_eq!(port.io_read(REG_STATUS), 0xDF);
+
+        strobe(&mut port, b'H');
+        strobe(&mut port, b'i');
+        // Writing data without a strobe prints nothing
+        port.io_write(REG_DATA, b'!');
+        assert_eq!(*capture.0.lock().unwrap(), b"Hi");
+
+        // The acknowledge pulse shows once
+        assert_eq!(port.io_read(REG_STATUS) & STATUS_NOT_ACK, 0);
+        assert_eq!(port.io_read(REG_STATUS), 0xDF);
+    }
+
+    #[test]
+    fn test_no_printer_reports_offline() {
+        let mut port = ParallelPort::new();
+        strobe(&mut port, b'A');
+        let status = port.io_read(REG_STATUS);
+        assert_ne!(status & STATUS_NOT_BUSY, 0, "must not hang on busy");
+        assert_eq!(status & (STATUS_SELECT | STATUS_NOT_ERROR), 0);
+    }
+
+    #[test]
+    fn test_ack_irq() {
+        let mut port = ParallelPort::new();
+        port.attach(Box::new(Capture::default()));
+        strobe(&mut port, b'A');
+        assert!(!port.take_irq_request());
+
+        port.io_write(
+            REG_CONTROL,
+            CONTROL_NOT_INIT | CONTROL_SELECT_IN | CONTROL_IRQ_ENABLE,
+        );
+        port.io_write(REG_DATA, b'B');
+        port.io_write(
+            REG_CONTROL,
+            CONTROL_NOT_INIT | CONTROL_SELECT_IN | CONTROL_IRQ_ENABLE | CONTROL_STROBE,
+        );
+        assert!(port.take_irq_request());
+        assert!(!port.take_irq_request());
+        assert_eq!(port.io_read(REG_CONTROL), 0xFD);
+    }
+}
@@ -0,0 +1,304 @@
+//! Intel 8259A Programmable Interrupt Controller (PIC)
+//!
+//! The PC/XT has a single 8259A at ports 0x20/0x21 that funnels the eight
+//! hardware interrupt lines to the CPU:
+//! - IRQ 0: PIT channel 0 (system timer, INT 08h)
+//! - IRQ 1: Keyboard (INT 09h)
+//! - IRQ 5: Sound Blaster (INT 0Dh)
+//! - IRQ 6: Floppy disk controller (INT 0Eh)
+//!
+//! Requests are latched in the IRR, filtered by the IMR and resolved by fixed
+//! priority (IRQ 0 highest). An acknowledged request moves to the ISR, where it
+//! blocks itself and lower priority requests until the handler sends an EOI.
+//!
+//! Ports:
+//! - 0x20: ICW1 / OCW2 (EOI) / OCW3 (register select) on write, IRR or ISR on read
+//! - 0x21: ICW2-ICW4 during initialization, IMR (OCW1) otherwise
+
+/// Command port
+pub const PIC_COMMAND_PORT: u16 = 0x20;
+/// Data (mask) port
+pub const PIC_DATA_PORT: u16 = 0x21;
+
+/// Vector base programmed by the BIOS (IRQ 0 = INT 08h)
+const DEFAULT_VECTOR_BASE: u8 = 0x08;
+
+/// Initialization sequence state
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InitState {
+    Ready,
+    /// Waiting for ICW2 (vector base)
+    Icw2,
+    /// Waiting for ICW3 (cascade configuration)
+    Icw3,
+    /// Waiting for ICW4 (mode)
+    Icw4,
+}
+
+/// 8259A interrupt controller
+#[derive(Debug, Clone)]
+pub struct Pic8259 {
+    /// Interrupt Request Register: lines waiting for service
+    irr: u8,
+    /// In-Service Register: lines whose handler is running
+    isr: u8,
+    /// Interrupt Mask Register: masked lines are never delivered
+    imr: u8,
+    /// Vector number of IRQ 0 (low 3 bits are always 0)
+    vector_base: u8,
+    init_state: InitState,
+    /// ICW1 bit 1: single controller, no ICW3
+    single: bool,
+    /// ICW1 bit 0: ICW4 will follow
+    needs_icw4: bool,
+    /// ICW4 bit 1: EOI is implied by the acknowledge
+    auto_eoi: bool,
+    /// OCW3: port 0x20 reads return the ISR instead of the IRR
+    read_isr: bool,
+}
+
+impl Pic8259 {
+    pub fn new() -> Self {
+        let mut pic = Self {
+            irr: 0,
+            isr: 0,
+            imr: 0,
+            vector_base: DEFAULT_VECTOR_BASE,
+            init_state: InitState::Ready,
+            single: true,
+            needs_icw4: true,
+            auto_eoi: false,
+            read_isr: false,
+        };
+        pic.reset();
+        pic
+    }
+
+    /// Reset to the state left by the BIOS: vectors at INT 08h, all lines
+    /// unmasked, nothing pending
+    pub fn reset(&mut self) {
+        self.irr = 0;
+        self.isr = 0;
+        self.imr = 0;
+        self.vector_base = DEFAULT_VECTOR_BASE;
+        self.init_state = InitState::Ready;
+        self.single = true;
+        self.needs_icw4 = true;
+        self.auto_eoi = false;
+        self.read_isr = false;
+    }
+
+    /// Read the command (0x20) or data (0x21) port
+    pub fn io_read(&self, port: u16) -> u8 {
+        if port == PIC_DATA_PORT {
+            self.imr
+        } else if self.read_isr {
+            self.isr
+        } else {
+            self.irr
+        }
+    }
+
+    /// Write the command (0x20) or data (0x21) port
+    pub fn io_write(&mut self, port: u16, val: u8) {
+        if port == PIC_COMMAND_PORT {
+            if val & 0x10 != 0 {
+                // ICW1: start initialization sequence
+                self.irr = 0;
+                self.isr = 0;
+                self.imr = 0;
+                self.auto_eoi = false;
+                self.read_isr = false;
+                self.single = val & 0x02 != 0;
+                self.needs_icw4 = val & 0x01 != 0;
+                self.init_state = InitState::Icw2;
+            } else if val & 0x08 != 0 {
+                // OCW3: select register for reads (RR=1, RIS selects ISR)
+                if val & 0x02 != 0 {
+                    self.read_isr = val & 0x01 != 0;
+                }
+            } else {
+                // OCW2: end of interrupt
+                match val & 0xE0 {
+                    // Non-specific EOI
+                    0x20 => self.end_of_interrupt(),
+                    // Specific EOI
+                    0x60 => self.isr &= !(1 << (val & 0x07)),
+                    _ => {}
+                }
+            }
+            return;
+        }
+
+        match self.init_state {
+            InitState::Ready => self.imr = val,
+            InitState::Icw2 => {
+                self.vector_base = val & 0xF8;
+                self.init_state = if !self.single {
+                    InitState::Icw3
+                } else if self.needs_icw4 {
+                    InitState::Icw4
+                } else {
+                    InitState::Ready
+                };
+            }
+            InitState::Icw3 => {
+                self.init_state = if self.needs_icw4 {
+                    InitState::Icw4
+                } else {
+                    InitState::Ready
+                };
+            }
+            InitState::Icw4 => {
+                self.auto_eoi = val & 0x02 != 0;
+                self.init_state = InitState::Ready;
+            }
+        }
+    }
+
+    /// Latch a request on an IRQ line (edge triggered)
+    pub fn raise_irq(&mut self, irq: u8) {
+        self.irr |= 1 << (irq & 0x07);
+    }
+
+    /// Highest priority IRQ that may interrupt the CPU now, if any
+    pub fn pending_irq(&self) -> Option<u8> {
+        let requests = self.irr & !self.imr;
+        if requests == 0 {
+            return None;
+        }
+        let irq = requests.trailing_zeros() as u8;
+        // A request only gets through if nothing of equal or higher priority
+        // is in service
+        match self.in_service_irq() {
+            Some(active) if active <= irq => None,
+            _ => Some(irq),
+        }
+    }
+
+    /// Acknowledge the highest priority pending IRQ and return its vector
+    pub fn acknowledge(&mut self) -> Option<u8> {
+        let irq = self.pending_irq()?;
+        self.irr &= !(1 << irq);
+        if !self.auto_eoi {
+            self.isr |= 1 << irq;
+        }
+        Some(self.vector_base | irq)
+    }
+
+    /// Highest priority IRQ currently in service
+    pub fn in_service_irq(&self) -> Option<u8> {
+        if self.isr == 0 {
+            None
+        } else {
+            Some(self.isr.trailing_zeros() as u8)
+        }
+    }
+
+    /// Non-specific EOI: clear the highest priority in-service IRQ
+    pub fn end_of_interrupt(&mut self) {
+        if let Some(irq) = self.in_service_irq() {
+            self.isr &= !(1 << irq);
+        }
+    }
+
+    /// Vector number of IRQ 0 as programmed by ICW2
+    pub fn vector_base(&self) -> u8 {
+        self.vector_base
+    }
+}
+
+impl Default for Pic8259 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_priority_and_eoi() {
+        let mut pic = Pic8259::new();
+        pic.raise_irq(1);
+        pic.raise_irq(0);
+
+        // IRQ 0 wins and blocks IRQ 1 until EOI
+        assert_eq!(pic.acknowledge(), Some(0x08));
+        assert_eq!(pic.acknowledge(), None);
+        pic.io_write(0x20, 0x20);
+        assert_eq!(pic.acknowledge(), Some(0x09));
+        pic.io_write(0x20, 0x20);
+        assert_eq!(pic.in_service_irq(), None);
+    }
+
+    #[test]
+    fn test_higher_priority_nests() {
+        let mut pic = Pic8259::new();
+        pic.raise_irq(5);
+        assert_eq!(pic.acknowledge(), Some(0x0D));
+
+        // IRQ 0 may interrupt the IRQ 5 handler
+        pic.raise_irq(0);
+        assert_eq!(pic.acknowledge(), Some(0x08));
+        pic.io_write(0x20, 0x20);
+        assert_eq!(pic.in_service_irq(), Some(5));
+
+        // Specific EOI for IRQ 5
+        pic.io_write(0x20, 0x65);
+        assert_eq!(pic.in_service_irq(), None);
+    }
+
+    #[test]
+    fn test_mask_register() {
+        let mut pic = Pic8259::new();
+        pic.io_write(0x21, 0x02); // Mask keyboard
+        assert_eq!(pic.io_read(0x21), 0x02);
+
+        pic.raise_irq(1);
+        assert_eq!(pic.acknowledge(), None);
+        // Request stays latched and is delivered once unmasked
+        pic.io_write(0x21, 0x00);
+        assert_eq!(pic.acknowledge(), Some(0x09));
+    }
+
+    #[test]
+    fn test_initialization_sequence() {
+        let mut pic = Pic8259::new();
+        pic.raise_irq(3);
+
+        // ICW1: edge triggered, single, ICW4 needed; ICW2: base 0x50; ICW4: 8086, auto EOI
+        pic.io_write(0x20, 0x13);
+        pic.io_write(0x21, 0x50);
+        pic.io_write(0x21, 0x03);
+        assert_eq!(pic.vector_base(), 0x50);
+        assert_eq!(pic.io_read(0x21), 0x00);
+
+        // ICW1 cleared pending requests
+        assert_eq!(pic.acknowledge(), None);
+
+        // Auto EOI leaves nothing in service
+        pic.raise_irq(6);
+        assert_eq!(pic.acknowledge(), Some(0x56));
+        assert_eq!(pic.in_service_irq(), None);
+
+        // Subsequent data writes go to the IMR
+        pic.io_write(0x21, 0xFE);
+        assert_eq!(pic.io_read(0x21), 0xFE);
+    }
+
+    #[test]
+    fn test_read_irr_and_isr() {
+        let mut pic = Pic8259::new();
+        pic.raise_irq(0);
+        pic.raise_irq(4);
+        assert_eq!(pic.io_read(0x20), 0x11); // IRR by default
+
+        pic.acknowledge();
+        pic.io_write(0x20, 0x0B); // OCW3: read ISR
+        assert_eq!(pic.io_read(0x20), 0x01);
+        pic.io_write(0x20, 0x0A); // OCW3: read IRR
+        assert_eq!(pic.io_read(0x20), 0x10);
+    }
+}
@@ -90,6 +90,8 @@ struct PitChannel {
     counting: bool,
     /// Latched value (for read-back)
     latched_value: Option<u16>,
+    /// Gate input (channels 0/1 are tied high, channel 2 follows port 61h bit 0)
+    gate: bool,
 }
 
 impl PitChannel {
@@ -103,6 +105,7 @@ impl PitChannel {
             output: false,
             counting: false,
             latched_value: None,
+            gate: true,
         }
     }
 
@@ -116,6 +119,7 @@ impl PitChannel {
         self.output = false;
         self.counting = false;
         self.latched_value = None;
+        self.gate = true;
     }
 
     /// Write a value to the channel
@@ -198,11 +202,42 @@ impl PitChannel {
         }
     }
 
+    /// Drive the gate input
+    ///
+    /// A low gate suspends counting in modes 0, 2, 3 and 4. A rising edge
+    /// restarts the count in modes 1, 2, 3 and 5.
+    fn set_gate(&mut self, level: bool) {
+        let rising = level && !self.gate;
+        self.gate = level;
+        if rising {
+            match self.mode {
+                PitMode::RateGenerator | PitMode::SquareWave => {
+                    self.counter = self.reload;
+                    self.output = true;
+                }
+                PitMode::HardwareOneShot | PitMode::HardwareStrobe => {
+                    self.counter = self.reload;
+                }
+                _ => {}
+            }
+        }
+    }
+
     /// Clock the channel (decrement counter)
     fn clock(&mut self) -> bool {
         if !self.counting {
             return false;
         }
+        let gated = matches!(
+            self.mode,
+            PitMode::InterruptOnTerminalCount
+                | PitMode::RateGenerator
+                | PitMode::SquareWave
+                | PitMode::SoftwareStrobe
+        );
+        if gated && !self.gate {
+            return false;
+        }
 
         // Get the effective reload value (0 means 65536)
         let effective_reload = if self.reload == 0 {
@@ -227,9 +262,9 @@ impl PitChannel {
                 }
             }
             PitMode::RateGenerator => {
-                // Mode 2: Divide by N counter
-                if self.counter > 1 {
-                    self.counter -= 1;
+                // Mode 2: Divide by N counter (a count of 0 is 65536)
+                if self.counter != 1 {
+                    self.counter = self.counter.wrapping_sub(1);
                     self.output = true;
                     false
                 } else {
@@ -240,26 +275,19 @@ impl PitChannel {
                 }
             }
             PitMode::SquareWave => {
-                // Mode 3: Square wave generator
-                if self.counter > 0 {
-                    self.counter -= 1;
-                    if self.counter == 0 {
-                        self.counter = self.reload;
-                        self.output = !self.output;
-                        true
-                    } else {
-                        // Also toggle at half period for even divisors
-                        let half_period = (effective_reload / 2) as u16;
-                        if half_period > 0 && self.counter == half_period {
-                            self.output = !self.output;
-                        }
-                        false
-                    }
-                } else {
-                    // Counter is 0, reload and toggle
+                // Mode 3: Square wave generator (a count of 0 is 65536)
+                self.counter = self.counter.wrapping_sub(1);
+                if self.counter == 0 {
                     self.counter = self.reload;
                     self.output = !self.output;
                     true
+                } else {
+                    // Also toggle at half period for even divisors
+                    let half_period = (effective_reload / 2) as u16;
+                    if half_period > 0 && self.counter == half_period {
+                        self.output = !self.output;
+                    }
+                    false
                 }
             }
             _ => {
@@ -276,7 +304,11 @@ impl PitChannel {
 
     /// Get the output state
     fn output(&self) -> bool {
-        self.output
+        // Modes 2 and 3 hold the output high while the gate is low
+        match self.mode {
+            PitMode::RateGenerator | PitMode::SquareWave if !self.gate => true,
+            _ => self.output,
+        }
     }
 
     /// Get the current frequency in Hz
@@ -290,6 +322,9 @@ impl PitChannel {
     }
 }
 
+/// CPU clock of the original IBM PC/XT in MHz
+pub const XT_CPU_MHZ: f64 = 4.77;
+
 /// Intel 8253/8254 Programmable Interval Timer
 pub struct Pit {
     /// Three channels
@@ -298,6 +333,8 @@ pub struct Pit {
     accumulated_ticks: f64,
     /// System timer interrupt flag (channel 0)
     timer_interrupt: bool,
+    /// CPU cycles per PIT tick (4.0 for a 4.77 MHz CPU)
+    cpu_cycles_per_tick: f64,
 }
 
 impl Pit {
@@ -307,6 +344,7 @@ impl Pit {
             channels: [PitChannel::new(), PitChannel::new(), PitChannel::new()],
             accumulated_ticks: 0.0,
             timer_interrupt: false,
+            cpu_cycles_per_tick: 4.0,
         }
     }
 
@@ -382,12 +420,22 @@ impl Pit {
         }
     }
 
+    /// Set the CPU clock the PIT is driven from
+    ///
+    /// The PIT input stays at ~1.19 MHz regardless of CPU speed, so a faster
+    /// CPU executes more cycles per PIT tick. Non-positive speeds are ignored.
+    pub fn set_cpu_speed_mhz(&mut self, mhz: f64) {
+        if mhz > 0.0 {
+            self.cpu_cycles_per_tick = 4.0 * mhz / XT_CPU_MHZ;
+        }
+    }
+
     /// Clock the PIT with CPU cycles
     /// Returns true if a timer interrupt should be generated
     pub fn clock(&mut self, cpu_cycles: u32) -> bool {
         // Convert CPU cycles to PIT ticks
-        // CPU runs at ~4.77 MHz, PIT at ~1.19 MHz (1/4 speed)
-        let pit_ticks = cpu_cycles as f64 / 4.0;
+        // At 4.77 MHz the PIT runs at ~1.19 MHz (1/4 speed)
+        let pit_ticks = cpu_cycles as f64 / self.cpu_cycles_per_tick;
         self.accumulated_ticks += pit_ticks;
 
         let mut interrupt = false;
@@ -435,6 +483,22 @@ impl Pit {
         self.channels[2].frequency()
     }
 
+    /// Drive a channel's gate input
+    pub fn set_gate(&mut self, channel: usize, level: bool) {
+        if channel < 3 {
+            self.channels[channel].set_gate(level);
+        }
+    }
+
+    /// Frequency of the square wave on channel 2, if it is producing one
+    ///
+    /// Channel 2 must be in mode 3 with a count loaded and its gate high.
+    pub fn speaker_square_wave_hz(&self) -> Option<f64> {
+        let channel = &self.channels[2];
+        (channel.mode == PitMode::SquareWave && channel.counting && channel.gate)
+            .then(|| channel.frequency())
+    }
+
     /// Get channel 0 frequency (system timer)
     pub fn system_timer_frequency(&self) -> f64 {
         self.channels[0].frequency()
@@ -572,6 +636,39 @@ mod tests {
         assert!((freq - 1000.0).abs() < 1.0);
     }
 
+    #[test]
+    fn test_channel_2_gate() {
+        let mut pit = Pit::new();
+        pit.write_control(0b10110110);
+        pit.write_channel(2, 0x00);
+        pit.write_channel(2, 0x01); // Divisor 256
+        assert!(pit.speaker_square_wave_hz().is_some());
+
+        // Gate low: counting stops and the output is held high
+        pit.set_gate(2, false);
+        assert_eq!(pit.speaker_square_wave_hz(), None);
+        assert!(pit.speaker_output());
+        pit.clock(4 * 50);
+        assert_eq!(pit.channels[2].counter, 256);
+
+        // Rising edge restarts the count
+        pit.set_gate(2, true);
+        pit.clock(4 * 10);
+        assert_eq!(pit.channels[2].counter, 246);
+        let hz = pit.speaker_square_wave_hz().unwrap();
+        assert!((hz - PIT_FREQUENCY / 256.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_default_timer_rate() {
+        // A count of 0 divides by 65536 (~18.2 Hz), not by 1
+        let mut pit = Pit::new();
+        pit.reset();
+        assert!(!pit.clock(4 * 65535));
+        assert!(pit.clock(4));
+        assert!(!pit.clock(4 * 1000));
+    }
+
     #[test]
     fn test_timer_interrupt() {
         let mut pit = Pit::new();
@@ -631,6 +728,30 @@ mod tests {
         assert_eq!((ch2_high as u16) << 8 | ch2_low as u16, 0x3000);
     }
 
+    #[test]
+    fn test_pit_scales_with_cpu_speed() {
+        // Same wall-clock time at 4.77 MHz and 9.54 MHz should produce
+        // the same number of PIT ticks
+        let mut slow = Pit::new();
+        let mut fast = Pit::new();
+        fast.set_cpu_speed_mhz(XT_CPU_MHZ * 2.0);
+        for pit in [&mut slow, &mut fast] {
+            pit.write_control(0b00110100); // Ch 0, lo/hi, mode 2
+            pit.write_channel(0, 0x00);
+            pit.write_channel(0, 0x10);
+        }
+
+        slow.clock(400);
+        fast.clock(800);
+
+        slow.write_control(0b00000000); // Latch ch 0
+        fast.write_control(0b00000000);
+        let slow_count = slow.read_channel(0) as u16 | (slow.read_channel(0) as u16) << 8;
+        let fast_count = fast.read_channel(0) as u16 | (fast.read_channel(0) as u16) << 8;
+        assert_eq!(slow_count, fast_count);
+        assert!(slow_count < 0x1000);
+    }
+
     #[test]
     fn test_pit_frequency_constant() {
         // Verify the PIT frequency constant
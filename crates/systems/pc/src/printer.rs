@@ -0,0 +1,252 @@
+//! Host capture files for the emulated printer
+//!
+//! Bytes the guest sends to LPT1 are appended to a host file, either:
+//! - `raw:<path>`: exactly as sent, including printer control codes, for
+//!   printing on a real printer or converting with another tool
+//! - `text:<path>`: as plain UTF-8 text, with CR/LF pairs turned into host
+//!   line ends, Epson ESC/P control sequences and graphics removed, and
+//!   code page 437 characters (box drawing, accents) translated
+//!
+//! A path without a prefix is captured raw.
+
+use std::fs::OpenOptions;
+use std::io::{self, BufWriter, Write};
+
+/// How printer output is written to the capture file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrinterFormat {
+    /// Bytes as sent by the guest
+    Raw,
+    /// Plain text with printer control codes removed
+    Text,
+}
+
+/// Host file capturing the output of LPT1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrinterOutput {
+    pub format: PrinterFormat,
+    pub path: String,
+}
+
+impl PrinterOutput {
+    /// Open the capture file for appending
+    pub fn open(&self) -> io::Result<Box<dyn Write + Send>> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        let file = BufWriter::new(file);
+        Ok(match self.format {
+            PrinterFormat::Raw => Box::new(file),
+            PrinterFormat::Text => Box::new(TextPrinter::new(file)),
+        })
+    }
+}
+
+impl std::fmt::Display for PrinterOutput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.format {
+            PrinterFormat::Raw => write!(f, "raw:{}", self.path),
+            PrinterFormat::Text => write!(f, "text:{}", self.path),
+        }
+    }
+}
+
+impl std::str::FromStr for PrinterOutput {
+    type Err = String;
+
+    /// Parse "raw:path", "text:path" or a bare path (raw)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (format, path) = match s.split_once(':') {
+            Some((kind, path)) if kind.eq_ignore_ascii_case("raw") => (PrinterFormat::Raw, path),
+            Some((kind, path)) if kind.eq_ignore_ascii_case("text") => (PrinterFormat::Text, path),
+            _ => (PrinterFormat::Raw, s),
+        };
+        if path.is_empty() {
+            return Err(format!("Invalid printer output: {}", s));
+        }
+        Ok(PrinterOutput {
+            format,
+            path: path.to_string(),
+        })
+    }
+}
+
+/// Code page 437 characters 80h-FFh
+const CP437_HIGH: &str = "ÇüéâäàåçêëèïîìÄÅÉæÆôöòûùÿÖÜ¢£¥₧ƒáíóúñÑªº¿⌐¬½¼¡«»\
+░▒▓│┤╡╢╖╕╣║╗╝╜╛┐└┴┬├─┼╞╟╚╔╩╦╠═╬╧╨╤╥╙╘╒╓╫╪┘┌█▄▌▐▀\
+αßΓπΣσµτΦΘΩδ∞φε∩≡±≥≤⌠⌡÷≈°∙·√ⁿ²■\u{A0}";
+
+/// Position within an ESC/P control sequence
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Escape {
+    /// Plain text
+    None,
+    /// After ESC, waiting for the command
+    Command,
+    /// Skipping `n` more parameter bytes
+    Params(usize),
+    /// Skipping a parameter list ended by NUL (tab stops)
+    UntilNul,
+    /// ESC C: a page length in lines, or 0 followed by a length in inches
+    PageLength,
+    /// ESC *: waiting for the graphics density
+    GraphicsMode,
+    /// Bit-image graphics: waiting for the column count (low byte first)
+    GraphicsCount {
+        bytes_per_column: usize,
+        low: Option<u8>,
+    },
+    /// Skipping `n` more bytes of bit-image data
+    Data(usize),
+}
+
+/// Converts printer output to plain UTF-8 text
+struct TextPrinter<W: Write> {
+    inner: W,
+    escape: Escape,
+}
+
+impl<W: Write> TextPrinter<W> {
+    fn new(inner: W) -> Self {
+        Self {
+            inner,
+            escape: Escape::None,
+        }
+    }
+
+    /// Convert one byte, appending any text it produces to `out`
+    fn convert(&mut self, byte: u8, out: &mut String) {
+        self.escape = match self.escape {
+            Escape::None if byte == 0x1B => Escape::Command,
+            Escape::None => {
+                match byte {
+                    b'\n' | b'\t' | 0x0C => out.push(byte as char),
+                    // CR, backspace and the other control codes move the
+                    // print head or change the font
+                    0x00..=0x1F | 0x7F => {}
+                    0x20..=0x7E => out.push(byte as char),
+                    _ => out.extend(CP437_HIGH.chars().nth(byte as usize - 0x80)),
+                }
+                Escape::None
+            }
+            Escape::Command => match byte {
+                // Bit-image graphics: n1 n2, then n1 + 256 * n2 columns
+                b'K' | b'L' | b'Y' | b'Z' => Escape::GraphicsCount {
+                    bytes_per_column: 1,
+                    low: None,
+                },
+                b'*' => Escape::GraphicsMode,
+                b'C' => Escape::PageLength,
+                b'!' | b'-' | b'3' | b'A' | b'I' | b'J' | b'N' | b'Q' | b'R' | b'S' | b'U'
+                | b'W' | b'a' | b'k' | b'l' | b'p' | b'r' | b't' | b'w' | b'x' => Escape::Params(1),
+                // Absolute and relative horizontal position
+                b'$' | b'\\' => Escape::Params(2),
+                b'B' | b'D' => Escape::UntilNul,
+                _ => Escape::None,
+            },
+            Escape::Params(n) if n > 1 => Escape::Params(n - 1),
+            Escape::Data(n) if n > 1 => Escape::Data(n - 1),
+            Escape::Params(_) | Escape::Data(_) => Escape::None,
+            Escape::UntilNul if byte != 0 => Escape::UntilNul,
+            Escape::UntilNul => Escape::None,
+            Escape::PageLength if byte == 0 => Escape::Params(1),
+            Escape::PageLength => Escape::None,
+            // 8-dot modes use one byte per column, 24-dot modes three and
+            // 48-dot modes six
+            Escape::GraphicsMode => Escape::GraphicsCount {
+                bytes_per_column: match byte {
+                    0..=7 => 1,
+                    32..=40 => 3,
+                    _ => 6,
+                },
+                low: None,
+            },
+            Escape::GraphicsCount {
+                bytes_per_column,
+                low: None,
+            } => Escape::GraphicsCount {
+                bytes_per_column,
+                low: Some(byte),
+            },
+            Escape::GraphicsCount {
+                bytes_per_column,
+                low: Some(low),
+            } => match (low as usize | (byte as usize) << 8) * bytes_per_column {
+                0 => Escape::None,
+                n => Escape::Data(n),
+            },
+        };
+    }
+}
+
+impl<W: Write> Write for TextPrinter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut text = String::new();
+        for &byte in buf {
+            self.convert(byte, &mut text);
+        }
+        self.inner.write_all(text.as_bytes())?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_text(bytes: &[u8]) -> String {
+        let mut printer = TextPrinter::new(Vec::new());
+        printer.write_all(bytes).unwrap();
+        String::from_utf8(printer.inner).unwrap()
+    }
+
+    #[test]
+    fn test_parse_printer_output() {
+        let text: PrinterOutput = "text:/tmp/lpt1.txt".parse().unwrap();
+        assert_eq!(text.format, PrinterFormat::Text);
+        assert_eq!(text.path, "/tmp/lpt1.txt");
+        assert_eq!(text.to_string(), "text:/tmp/lpt1.txt");
+
+        let bare: PrinterOutput = "out.prn".parse().unwrap();
+        assert_eq!(bare.format, PrinterFormat::Raw);
+        assert_eq!(bare.to_string(), "raw:out.prn");
+        assert_eq!(
+            "RAW:C:\\out.prn".parse::<PrinterOutput>().unwrap().path,
+            "C:\\out.prn"
+        );
+
+        assert!("text:".parse::<PrinterOutput>().is_err());
+        assert!("".parse::<PrinterOutput>().is_err());
+    }
+
+    #[test]
+    fn test_text_conversion() {
+        assert_eq!(CP437_HIGH.chars().count(), 128);
+        assert_eq!(to_text(b"Hello\r\nWorld\r\n\x0C"), "Hello\nWorld\n\x0C");
+        // Code page 437 box drawing and accents
+        assert_eq!(to_text(&[0xC9, 0xCD, 0xBB, b' ', 0x82]), "╔═╗ é");
+        // Bold on/off, underline with a parameter, reset
+        assert_eq!(
+            to_text(b"\x1B@\x1BEbold\x1BF \x1B-\x01u\x1B-\x00"),
+            "bold u"
+        );
+        // Page length in inches (ESC C 0 n) and in lines (ESC C n)
+        assert_eq!(to_text(b"\x1BC\x00\x0Ba\x1BC\x42b"), "ab");
+    }
+
+    #[test]
+    fn test_text_skips_graphics() {
+        // ESC K with 3 columns of data that look like text
+        assert_eq!(to_text(b"a\x1BK\x03\x00ABCb"), "ab");
+        // ESC * mode 33 (3 bytes per column), 2 columns
+        assert_eq!(to_text(b"a\x1B*\x21\x02\x00ABCDEFb"), "ab");
+        // Tab stops end with NUL
+        assert_eq!(to_text(b"\x1BD\x08\x10\x00x"), "x");
+    }
+}
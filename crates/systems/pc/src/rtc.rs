@@ -0,0 +1,836 @@
+//! Motorola MC146818 Real-Time Clock and CMOS RAM
+//!
+//! The PC/AT keeps the time of day and the BIOS setup in a battery-backed
+//! MC146818 with 128 bytes of RAM. The first 14 bytes are the clock and its
+//! status registers; the rest holds the setup values read by the BIOS POST:
+//! - 0x10: Floppy drive types (high nibble drive A, low nibble drive B)
+//! - 0x12: Hard disk types (0Fh = extended type in 0x19, type 47 = user
+//!   defined geometry in 0x1B-0x23)
+//! - 0x14: Equipment byte (floppy count, display type)
+//! - 0x15-0x18: Base and extended memory in KB
+//! - 0x2E-0x2F: Checksum of 0x10-0x2D
+//! - 0x30-0x31: Extended memory found by POST
+//! - 0x32: Century (BCD)
+//!
+//! The clock follows the host clock (in UTC), optionally shifted by an offset
+//! or frozen at a fixed time. Setting the time from the guest adjusts the
+//! offset (or the frozen time) so the change survives like it would on a
+//! battery-backed clock. Alarm and periodic interrupts (IRQ 8) are not
+//! generated.
+//!
+//! Ports:
+//! - 0x70: Register index (bit 7 masks NMI), write only
+//! - 0x71: Data of the selected register
+
+use crate::disk::FloppyFormat;
+use emu_core::logging::{log, LogCategory, LogLevel};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Index port
+pub const RTC_INDEX_PORT: u16 = 0x70;
+/// Data port
+pub const RTC_DATA_PORT: u16 = 0x71;
+
+/// Size of the CMOS RAM including the clock registers
+pub const CMOS_SIZE: usize = 128;
+/// First byte of general purpose RAM after the clock registers
+pub const CMOS_RAM_START: usize = 0x0E;
+
+const REG_SECONDS: u8 = 0x00;
+const REG_MINUTES: u8 = 0x02;
+const REG_HOURS: u8 = 0x04;
+const REG_WEEKDAY: u8 = 0x06;
+const REG_DAY: u8 = 0x07;
+const REG_MONTH: u8 = 0x08;
+const REG_YEAR: u8 = 0x09;
+const REG_STATUS_A: u8 = 0x0A;
+const REG_STATUS_B: u8 = 0x0B;
+const REG_STATUS_C: u8 = 0x0C;
+const REG_STATUS_D: u8 = 0x0D;
+const REG_FLOPPY_TYPES: u8 = 0x10;
+const REG_HARD_DISK_TYPES: u8 = 0x12;
+const REG_EQUIPMENT: u8 = 0x14;
+const REG_BASE_MEMORY: u8 = 0x15;
+const REG_EXTENDED_MEMORY: u8 = 0x17;
+const REG_HARD_DISK_0_TYPE: u8 = 0x19;
+const REG_HARD_DISK_0_PARAMS: u8 = 0x1B;
+const REG_CHECKSUM: u8 = 0x2E;
+const REG_POST_EXTENDED_MEMORY: u8 = 0x30;
+const REG_CENTURY: u8 = 0x32;
+
+/// Bytes covered by the setup checksum
+const CHECKSUM_RANGE: std::ops::RangeInclusive<u8> = 0x10..=0x2D;
+
+/// Status A: update in progress
+const STATUS_A_UIP: u8 = 0x80;
+/// Status A default: 32.768 kHz time base, 1024 Hz periodic rate
+const STATUS_A_DEFAULT: u8 = 0x26;
+/// Status B: updates halted while the time is being set
+const STATUS_B_SET: u8 = 0x80;
+/// Status B: binary (rather than BCD) data mode
+const STATUS_B_BINARY: u8 = 0x04;
+/// Status B: 24 hour mode
+const STATUS_B_24_HOUR: u8 = 0x02;
+/// Status B: daylight saving time enable
+const STATUS_B_DST: u8 = 0x01;
+/// Status D: valid RAM and time (battery good)
+const STATUS_D_VRT: u8 = 0x80;
+
+/// Hard disk type number meaning "user defined geometry"
+const USER_DEFINED_DISK_TYPE: u8 = 47;
+
+/// Microseconds at the end of each second during which UIP is set
+/// (244us set-up time plus the 1984us update cycle)
+const UIP_WINDOW_MICROS: u32 = 2228;
+
+/// Time source of the real-time clock
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RtcClock {
+    /// Host time (UTC) shifted by a number of seconds
+    Host { offset_secs: i64 },
+    /// Stopped at a fixed time (seconds since 1970-01-01)
+    Frozen { time: i64 },
+}
+
+impl Default for RtcClock {
+    fn default() -> Self {
+        RtcClock::Host { offset_secs: 0 }
+    }
+}
+
+/// Calendar date and time of day
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateTime {
+    pub year: i64,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+impl DateTime {
+    /// Convert seconds since 1970-01-01 00:00:00
+    pub fn from_timestamp(secs: i64) -> Self {
+        let days = secs.div_euclid(86400);
+        let rem = secs.rem_euclid(86400);
+
+        // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z - era * 146_097;
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + i64::from(month <= 2);
+
+        Self {
+            year,
+            month: month as u8,
+            day: day as u8,
+            hour: (rem / 3600) as u8,
+            minute: (rem / 60 % 60) as u8,
+            second: (rem % 60) as u8,
+        }
+    }
+
+    /// Seconds since 1970-01-01 00:00:00
+    pub fn timestamp(&self) -> i64 {
+        let month = i64::from(self.month);
+        let year = if month <= 2 { self.year - 1 } else { self.year };
+        let era = year.div_euclid(400);
+        let yoe = year - era * 400;
+        let mp = (month + 9) % 12;
+        let doy = (153 * mp + 2) / 5 + i64::from(self.day) - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        let days = era * 146_097 + doe - 719_468;
+        days * 86400
+            + i64::from(self.hour) * 3600
+            + i64::from(self.minute) * 60
+            + i64::from(self.second)
+    }
+
+    /// Day of the week as stored in register 06h (1 = Sunday)
+    pub fn weekday(&self) -> u8 {
+        // 1970-01-01 was a Thursday
+        (self.timestamp().div_euclid(86400) + 4).rem_euclid(7) as u8 + 1
+    }
+
+    /// Seconds since midnight
+    pub fn seconds_of_day(&self) -> u32 {
+        u32::from(self.hour) * 3600 + u32::from(self.minute) * 60 + u32::from(self.second)
+    }
+
+    /// Check that the fields form a real date and time
+    pub fn is_valid(&self) -> bool {
+        let days_in_month = match self.month {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            2 if self.year % 4 == 0 && (self.year % 100 != 0 || self.year % 400 == 0) => 29,
+            2 => 28,
+            _ => return false,
+        };
+        (1..=days_in_month).contains(&self.day)
+            && self.hour < 24
+            && self.minute < 60
+            && self.second < 60
+    }
+}
+
+impl std::fmt::Display for DateTime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+}
+
+impl std::str::FromStr for DateTime {
+    type Err = String;
+
+    /// Parse "YYYY-MM-DD HH:MM:SS", "YYYY-MM-DDTHH:MM:SS" or "YYYY-MM-DD"
+    /// (midnight)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid date/time: {} (expected YYYY-MM-DD HH:MM:SS)", s);
+        let s = s.trim();
+        let (date, time) = match s.split_once([' ', 'T']) {
+            Some((date, time)) => (date, time.trim()),
+            None => (s, "00:00:00"),
+        };
+
+        let date: Vec<&str> = date.split('-').collect();
+        let time: Vec<&str> = time.split(':').collect();
+        if date.len() != 3 || !(2..=3).contains(&time.len()) {
+            return Err(invalid());
+        }
+        let field = |text: &str| text.parse::<u8>().map_err(|_| invalid());
+
+        let date_time = Self {
+            year: date[0].parse().map_err(|_| invalid())?,
+            month: field(date[1])?,
+            day: field(date[2])?,
+            hour: field(time[0])?,
+            minute: field(time[1])?,
+            second: time.get(2).map_or(Ok(0), |text| field(text))?,
+        };
+        if date_time.is_valid() {
+            Ok(date_time)
+        } else {
+            Err(invalid())
+        }
+    }
+}
+
+/// Current host time in seconds since 1970-01-01
+fn host_time() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Convert a binary value to packed BCD
+pub fn to_bcd(value: u8) -> u8 {
+    ((value / 10) << 4) | (value % 10)
+}
+
+/// Convert packed BCD to binary (None if a digit is not decimal)
+pub fn from_bcd(value: u8) -> Option<u8> {
+    let (high, low) = (value >> 4, value & 0x0F);
+    (high < 10 && low < 10).then_some(high * 10 + low)
+}
+
+/// CMOS floppy drive type code for a disk format (0 = no drive)
+fn floppy_type_code(format: Option<FloppyFormat>) -> u8 {
+    match format {
+        None => 0,
+        Some(FloppyFormat::Floppy360K) => 1,
+        Some(FloppyFormat::Floppy1_2M) => 2,
+        Some(FloppyFormat::Floppy720K) => 3,
+        Some(FloppyFormat::Floppy1_44M) => 4,
+    }
+}
+
+/// MC146818 real-time clock with CMOS RAM
+pub struct Rtc {
+    /// Register selected through port 0x70
+    index: u8,
+    /// NMI mask written along with the index
+    nmi_disabled: bool,
+    /// Register and RAM contents; clock registers are generated on read
+    /// unless updates are halted with the SET bit
+    cmos: [u8; CMOS_SIZE],
+    clock: RtcClock,
+}
+
+impl Rtc {
+    pub fn new() -> Self {
+        let mut cmos = [0; CMOS_SIZE];
+        cmos[REG_STATUS_A as usize] = STATUS_A_DEFAULT;
+        cmos[REG_STATUS_B as usize] = STATUS_B_24_HOUR;
+        cmos[REG_STATUS_D as usize] = STATUS_D_VRT;
+        let mut rtc = Self {
+            index: 0,
+            nmi_disabled: false,
+            cmos,
+            clock: RtcClock::default(),
+        };
+        rtc.update_checksum();
+        rtc
+    }
+
+    /// Get the time source
+    pub fn clock(&self) -> RtcClock {
+        self.clock
+    }
+
+    /// Set the time source
+    pub fn set_clock(&mut self, clock: RtcClock) {
+        self.clock = clock;
+    }
+
+    /// Current time in seconds since 1970-01-01
+    pub fn time(&self) -> i64 {
+        match self.clock {
+            RtcClock::Host { offset_secs } => host_time().saturating_add(offset_secs),
+            RtcClock::Frozen { time } => time,
+        }
+    }
+
+    /// Set the current time, keeping the clock running (or frozen)
+    pub fn set_time(&mut self, time: i64) {
+        self.clock = match self.clock {
+            RtcClock::Host { .. } => RtcClock::Host {
+                offset_secs: time - host_time(),
+            },
+            RtcClock::Frozen { .. } => RtcClock::Frozen { time },
+        };
+        log(LogCategory::Bus, LogLevel::Info, || {
+            format!("RTC: time set to {}", DateTime::from_timestamp(time))
+        });
+    }
+
+    /// Current date and time
+    pub fn date_time(&self) -> DateTime {
+        DateTime::from_timestamp(self.time())
+    }
+
+    /// Whether daylight saving time is enabled (status B bit 0)
+    pub fn daylight_saving(&self) -> bool {
+        self.cmos[REG_STATUS_B as usize] & STATUS_B_DST != 0
+    }
+
+    /// Enable or disable daylight saving time
+    pub fn set_daylight_saving(&mut self, enabled: bool) {
+        let status_b = &mut self.cmos[REG_STATUS_B as usize];
+        if enabled {
+            *status_b |= STATUS_B_DST;
+        } else {
+            *status_b &= !STATUS_B_DST;
+        }
+    }
+
+    /// Read the index (0x70) or data (0x71) port
+    pub fn io_read(&self, port: u16) -> u8 {
+        if port == RTC_DATA_PORT {
+            self.read_register(self.index)
+        } else {
+            0xFF // Index register is write only
+        }
+    }
+
+    /// Write the index (0x70) or data (0x71) port
+    pub fn io_write(&mut self, port: u16, val: u8) {
+        if port == RTC_DATA_PORT {
+            self.write_register(self.index, val);
+        } else {
+            self.index = val & 0x7F;
+            self.nmi_disabled = val & 0x80 != 0;
+        }
+    }
+
+    /// Whether the last index write masked NMI
+    #[allow(dead_code)] // No NMI sources are emulated yet
+    pub fn nmi_disabled(&self) -> bool {
+        self.nmi_disabled
+    }
+
+    /// Read a register or RAM byte
+    pub fn read_register(&self, index: u8) -> u8 {
+        let index = index & 0x7F;
+        let status_b = self.cmos[REG_STATUS_B as usize];
+        if status_b & STATUS_B_SET == 0 {
+            if let Some(val) = self.clock_register(index, &self.date_time()) {
+                return val;
+            }
+        }
+        match index {
+            REG_STATUS_A => {
+                let mut val = self.cmos[REG_STATUS_A as usize] & !STATUS_A_UIP;
+                if status_b & STATUS_B_SET == 0 && Self::update_in_progress() {
+                    val |= STATUS_A_UIP;
+                }
+                val
+            }
+            // No interrupt flags are ever raised
+            REG_STATUS_C => 0x00,
+            REG_STATUS_D => STATUS_D_VRT,
+            _ => self.cmos[index as usize],
+        }
+    }
+
+    /// Write a register or RAM byte
+    pub fn write_register(&mut self, index: u8, val: u8) {
+        let index = index & 0x7F;
+        match index {
+            REG_STATUS_A => {
+                // UIP is read only
+                self.cmos[index as usize] = val & !STATUS_A_UIP;
+            }
+            REG_STATUS_B => {
+                let was_set = self.cmos[index as usize] & STATUS_B_SET != 0;
+                if val & STATUS_B_SET != 0 && !was_set {
+                    // Halting updates: latch the time so it can be edited
+                    self.latch_time();
+                    self.cmos[index as usize] = val;
+                } else if val & STATUS_B_SET == 0 && was_set {
+                    // Resuming updates: the edited registers become the time
+                    self.cmos[index as usize] = val;
+                    self.commit_time();
+                } else if !was_set && (val ^ self.cmos[index as usize]) & 0x06 != 0 {
+                    // Changing the data mode re-encodes the clock registers
+                    self.cmos[index as usize] = val;
+                    self.latch_time();
+                } else {
+                    self.cmos[index as usize] = val;
+                }
+            }
+            // Read only
+            REG_STATUS_C | REG_STATUS_D => {}
+            _ if self.is_clock_register(index) => {
+                let halted = self.cmos[REG_STATUS_B as usize] & STATUS_B_SET != 0;
+                if !halted {
+                    self.latch_time();
+                }
+                self.cmos[index as usize] = val;
+                if !halted {
+                    self.commit_time();
+                }
+            }
+            _ => self.cmos[index as usize] = val,
+        }
+    }
+
+    /// Battery-backed RAM after the clock registers (0x0E-0x7F), for saving
+    pub fn cmos_ram(&self) -> &[u8] {
+        &self.cmos[CMOS_RAM_START..]
+    }
+
+    /// Restore battery-backed RAM saved with `cmos_ram`
+    ///
+    /// Returns false (leaving the contents unchanged) if the length is wrong.
+    pub fn load_cmos_ram(&mut self, data: &[u8]) -> bool {
+        if data.len() != CMOS_SIZE - CMOS_RAM_START {
+            return false;
+        }
+        self.cmos[CMOS_RAM_START..].copy_from_slice(data);
+        true
+    }
+
+    /// Check the setup checksum (0x2E-0x2F) against bytes 0x10-0x2D
+    pub fn checksum_valid(&self) -> bool {
+        let stored = u16::from_be_bytes([
+            self.cmos[REG_CHECKSUM as usize],
+            self.cmos[REG_CHECKSUM as usize + 1],
+        ]);
+        stored == self.checksum()
+    }
+
+    /// Floppy drive type of drive A (0) or B (1)
+    pub fn floppy_type(&self, drive: u8) -> Option<FloppyFormat> {
+        let types = self.cmos[REG_FLOPPY_TYPES as usize];
+        let code = if drive == 0 { types >> 4 } else { types & 0x0F };
+        match code {
+            1 => Some(FloppyFormat::Floppy360K),
+            2 => Some(FloppyFormat::Floppy1_2M),
+            3 => Some(FloppyFormat::Floppy720K),
+            4 => Some(FloppyFormat::Floppy1_44M),
+            _ => None,
+        }
+    }
+
+    /// CMOS type code of floppy drive A (0) or B (1), as returned by INT 13h
+    pub fn floppy_type_code(&self, drive: u8) -> u8 {
+        floppy_type_code(self.floppy_type(drive))
+    }
+
+    /// Geometry of hard disk C: (cylinders, sectors per track, heads), if configured
+    pub fn hard_disk(&self) -> Option<(u16, u8, u8)> {
+        if self.cmos[REG_HARD_DISK_TYPES as usize] & 0xF0 == 0 {
+            return None;
+        }
+        let params = &self.cmos[REG_HARD_DISK_0_PARAMS as usize..];
+        Some((
+            u16::from_le_bytes([params[0], params[1]]),
+            params[7],
+            params[2],
+        ))
+    }
+
+    /// Equipment byte, laid out like the low byte of the INT 11h equipment word
+    pub fn equipment(&self) -> u8 {
+        self.cmos[REG_EQUIPMENT as usize]
+    }
+
+    /// Write the setup values the BIOS POST reads and update the checksum
+    pub fn set_setup(&mut self, setup: &CmosSetup) {
+        let [floppy_a, floppy_b] = setup.floppies;
+        self.cmos[REG_FLOPPY_TYPES as usize] =
+            (floppy_type_code(floppy_a) << 4) | floppy_type_code(floppy_b);
+
+        // Hard disk C: as type 47 with its geometry (AMI/Award layout)
+        let params = REG_HARD_DISK_0_PARAMS as usize;
+        if let Some((cylinders, sectors, heads)) = setup.hard_disk {
+            self.cmos[REG_HARD_DISK_TYPES as usize] = 0xF0;
+            self.cmos[REG_HARD_DISK_0_TYPE as usize] = USER_DEFINED_DISK_TYPE;
+            self.cmos[params..params + 2].copy_from_slice(&cylinders.to_le_bytes());
+            self.cmos[params + 2] = heads;
+            self.cmos[params + 3..params + 5].copy_from_slice(&0xFFFFu16.to_le_bytes()); // No precompensation
+            self.cmos[params + 5..params + 7].copy_from_slice(&cylinders.to_le_bytes()); // Landing zone
+            self.cmos[params + 7] = sectors;
+        } else {
+            self.cmos[REG_HARD_DISK_TYPES as usize] = 0x00;
+            self.cmos[REG_HARD_DISK_0_TYPE as usize] = 0;
+            self.cmos[params..params + 8].fill(0);
+        }
+
+        // Equipment byte: bits 7-6 floppy count - 1, bits 5-4 display, bit 0 floppies present
+        let floppy_count = setup.floppies.iter().filter(|f| f.is_some()).count() as u8;
+        let mut equipment = (setup.display_bits & 0x03) << 4;
+        if floppy_count > 0 {
+            equipment |= ((floppy_count - 1) << 6) | 0x01;
+        }
+        self.cmos[REG_EQUIPMENT as usize] = equipment;
+
+        let base_kb = setup.base_memory_kb.min(640) as u16;
+        let extended_kb = setup.extended_memory_kb.min(0xFFFF) as u16;
+        let base = REG_BASE_MEMORY as usize;
+        self.cmos[base..base + 2].copy_from_slice(&base_kb.to_le_bytes());
+        let extended = REG_EXTENDED_MEMORY as usize;
+        self.cmos[extended..extended + 2].copy_from_slice(&extended_kb.to_le_bytes());
+        let post = REG_POST_EXTENDED_MEMORY as usize;
+        self.cmos[post..post + 2].copy_from_slice(&extended_kb.to_le_bytes());
+
+        self.update_checksum();
+    }
+
+    /// Sum of the bytes covered by the setup checksum
+    fn checksum(&self) -> u16 {
+        CHECKSUM_RANGE
+            .map(|index| u16::from(self.cmos[index as usize]))
+            .sum()
+    }
+
+    fn update_checksum(&mut self) {
+        let [high, low] = self.checksum().to_be_bytes();
+        self.cmos[REG_CHECKSUM as usize] = high;
+        self.cmos[REG_CHECKSUM as usize + 1] = low;
+    }
+
+    /// Whether a register holds part of the current time or date
+    fn is_clock_register(&self, index: u8) -> bool {
+        matches!(
+            index,
+            REG_SECONDS | REG_MINUTES | REG_HOURS | REG_WEEKDAY | REG_DAY | REG_MONTH | REG_YEAR
+        ) || index == REG_CENTURY
+    }
+
+    /// Encode a clock register from a date and time in the current data mode
+    fn clock_register(&self, index: u8, now: &DateTime) -> Option<u8> {
+        let status_b = self.cmos[REG_STATUS_B as usize];
+        let binary = status_b & STATUS_B_BINARY != 0;
+        let encode = |value: u8| if binary { value } else { to_bcd(value) };
+        let val = match index {
+            REG_SECONDS => encode(now.second),
+            REG_MINUTES => encode(now.minute),
+            REG_HOURS if status_b & STATUS_B_24_HOUR != 0 => encode(now.hour),
+            REG_HOURS => {
+                // 12 hour mode: 12, 1-11 with bit 7 set after noon
+                let hour12 = match now.hour % 12 {
+                    0 => 12,
+                    hour => hour,
+                };
+                encode(hour12) | if now.hour >= 12 { 0x80 } else { 0 }
+            }
+            REG_WEEKDAY => encode(now.weekday()),
+            REG_DAY => encode(now.day),
+            REG_MONTH => encode(now.month),
+            REG_YEAR => encode(now.year.rem_euclid(100) as u8),
+            REG_CENTURY => encode(now.year.div_euclid(100).clamp(0, 99) as u8),
+            _ => return None,
+        };
+        Some(val)
+    }
+
+    /// Decode a clock register written by the guest in the current data mode
+    fn decode_register(&self, index: u8) -> Option<u8> {
+        let status_b = self.cmos[REG_STATUS_B as usize];
+        let raw = self.cmos[index as usize];
+        let decode = |value: u8| {
+            if status_b & STATUS_B_BINARY != 0 {
+                Some(value)
+            } else {
+                from_bcd(value)
+            }
+        };
+        if index == REG_HOURS && status_b & STATUS_B_24_HOUR == 0 {
+            let hour12 = decode(raw & 0x7F)?;
+            let pm = raw & 0x80 != 0;
+            return Some(hour12 % 12 + if pm { 12 } else { 0 });
+        }
+        decode(raw)
+    }
+
+    /// Copy the current time into the clock registers
+    fn latch_time(&mut self) {
+        let now = self.date_time();
+        for index in [
+            REG_SECONDS,
+            REG_MINUTES,
+            REG_HOURS,
+            REG_WEEKDAY,
+            REG_DAY,
+            REG_MONTH,
+            REG_YEAR,
+            REG_CENTURY,
+        ] {
+            if let Some(val) = self.clock_register(index, &now) {
+                self.cmos[index as usize] = val;
+            }
+        }
+    }
+
+    /// Set the clock from the contents of the clock registers
+    fn commit_time(&mut self) {
+        let fields = (|| {
+            let century = self.decode_register(REG_CENTURY)?;
+            let year = self.decode_register(REG_YEAR)?;
+            Some(DateTime {
+                year: i64::from(century) * 100 + i64::from(year),
+                month: self.decode_register(REG_MONTH)?,
+                day: self.decode_register(REG_DAY)?,
+                hour: self.decode_register(REG_HOURS)?,
+                minute: self.decode_register(REG_MINUTES)?,
+                second: self.decode_register(REG_SECONDS)?,
+            })
+        })();
+        match fields {
+            Some(date_time) if date_time.is_valid() => self.set_time(date_time.timestamp()),
+            _ => log(LogCategory::Bus, LogLevel::Warn, || {
+                "RTC: ignoring invalid time written to the clock registers".to_string()
+            }),
+        }
+    }
+
+    /// Whether the once-a-second update cycle is running, based on the host clock
+    ///
+    /// The oscillator keeps running even when the reported time is frozen, so
+    /// programs that wait for an update still make progress.
+    fn update_in_progress() -> bool {
+        let micros = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.subsec_micros())
+            .unwrap_or(0);
+        micros >= 1_000_000 - UIP_WINDOW_MICROS
+    }
+}
+
+impl Default for Rtc {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Setup values written to CMOS from the machine configuration
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CmosSetup {
+    /// Drive types of A: and B:
+    pub floppies: [Option<FloppyFormat>; 2],
+    /// Geometry of C: (cylinders, sectors per track, heads)
+    pub hard_disk: Option<(u16, u8, u8)>,
+    /// Equipment byte display bits (00 = EGA/VGA, 10 = CGA 80x25, 11 = MDA)
+    pub display_bits: u8,
+    pub base_memory_kb: u32,
+    pub extended_memory_kb: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 1995-08-24 09:30:15 UTC
+    const TEST_TIME: i64 = 809_256_615;
+
+    fn frozen_rtc() -> Rtc {
+        let mut rtc = Rtc::new();
+        rtc.set_clock(RtcClock::Frozen { time: TEST_TIME });
+        rtc
+    }
+
+    #[test]
+    fn test_date_time_conversion() {
+        let date_time = DateTime::from_timestamp(TEST_TIME);
+        assert_eq!(date_time.to_string(), "1995-08-24 09:30:15");
+        assert_eq!(date_time.weekday(), 5); // Thursday
+        assert_eq!(date_time.timestamp(), TEST_TIME);
+
+        // Leap day and dates before 1970 round trip
+        for text in [
+            "2000-02-29 23:59:59",
+            "1969-12-31 00:00:00",
+            "1980-01-01 00:00:00",
+        ] {
+            let parsed: DateTime = text.parse().unwrap();
+            assert_eq!(DateTime::from_timestamp(parsed.timestamp()), parsed);
+            assert_eq!(parsed.to_string(), text);
+        }
+
+        assert_eq!(
+            "1995-08-24".parse::<DateTime>().unwrap().timestamp(),
+            TEST_TIME - (9 * 3600 + 30 * 60 + 15)
+        );
+        assert!("1995-02-29 00:00:00".parse::<DateTime>().is_err());
+        assert!("tomorrow".parse::<DateTime>().is_err());
+    }
+
+    #[test]
+    fn test_clock_registers() {
+        let rtc = frozen_rtc();
+        assert_eq!(rtc.read_register(REG_SECONDS), 0x15);
+        assert_eq!(rtc.read_register(REG_MINUTES), 0x30);
+        assert_eq!(rtc.read_register(REG_HOURS), 0x09);
+        assert_eq!(rtc.read_register(REG_WEEKDAY), 0x05);
+        assert_eq!(rtc.read_register(REG_DAY), 0x24);
+        assert_eq!(rtc.read_register(REG_MONTH), 0x08);
+        assert_eq!(rtc.read_register(REG_YEAR), 0x95);
+        assert_eq!(rtc.read_register(REG_CENTURY), 0x19);
+        assert_eq!(rtc.read_register(REG_STATUS_D), STATUS_D_VRT);
+    }
+
+    #[test]
+    fn test_ports_and_data_modes() {
+        let mut rtc = frozen_rtc();
+
+        // Binary, 12 hour mode through the ports
+        rtc.io_write(RTC_INDEX_PORT, REG_STATUS_B);
+        rtc.io_write(RTC_DATA_PORT, STATUS_B_BINARY);
+        rtc.io_write(RTC_INDEX_PORT, 0x80 | REG_HOURS);
+        assert!(rtc.nmi_disabled());
+        assert_eq!(rtc.io_read(RTC_DATA_PORT), 9);
+        assert_eq!(rtc.io_read(RTC_INDEX_PORT), 0xFF);
+
+        rtc.set_time(TEST_TIME + 12 * 3600); // 21:30
+        assert_eq!(rtc.read_register(REG_HOURS), 0x80 | 9);
+    }
+
+    #[test]
+    fn test_set_time_through_registers() {
+        let mut rtc = frozen_rtc();
+
+        // Halt updates, write 2001-02-03 04:05:06 in BCD, resume
+        rtc.write_register(REG_STATUS_B, STATUS_B_SET | STATUS_B_24_HOUR);
+        for (index, val) in [
+            (REG_CENTURY, 0x20),
+            (REG_YEAR, 0x01),
+            (REG_MONTH, 0x02),
+            (REG_DAY, 0x03),
+            (REG_HOURS, 0x04),
+            (REG_MINUTES, 0x05),
+            (REG_SECONDS, 0x06),
+        ] {
+            rtc.write_register(index, val);
+        }
+        // Time does not change until updates resume
+        assert_eq!(rtc.time(), TEST_TIME);
+        rtc.write_register(REG_STATUS_B, STATUS_B_24_HOUR);
+        assert_eq!(rtc.date_time().to_string(), "2001-02-03 04:05:06");
+
+        // A single register written while running keeps the other fields
+        rtc.write_register(REG_MINUTES, 0x59);
+        assert_eq!(rtc.date_time().to_string(), "2001-02-03 04:59:06");
+
+        // Invalid values are ignored
+        rtc.write_register(REG_MONTH, 0x13);
+        assert_eq!(rtc.date_time().to_string(), "2001-02-03 04:59:06");
+    }
+
+    #[test]
+    fn test_host_clock_offset() {
+        let mut rtc = Rtc::new();
+        let start = rtc.time();
+        assert!((start - host_time()).abs() <= 1);
+
+        // Setting the time stores an offset from the host clock
+        rtc.set_time(TEST_TIME);
+        match rtc.clock() {
+            RtcClock::Host { offset_secs } => {
+                assert!((offset_secs - (TEST_TIME - host_time())).abs() <= 1)
+            }
+            clock => panic!("unexpected clock {:?}", clock),
+        }
+        assert!((rtc.time() - TEST_TIME).abs() <= 1);
+    }
+
+    #[test]
+    fn test_setup_and_checksum() {
+        let mut rtc = Rtc::new();
+        rtc.set_setup(&CmosSetup {
+            floppies: [
+                Some(FloppyFormat::Floppy1_44M),
+                Some(FloppyFormat::Floppy360K),
+            ],
+            hard_disk: Some((306, 17, 4)),
+            display_bits: 0b10,
+            base_memory_kb: 640,
+            extended_memory_kb: 3072,
+        });
+
+        assert_eq!(rtc.read_register(REG_FLOPPY_TYPES), 0x41);
+        assert_eq!(rtc.floppy_type(1), Some(FloppyFormat::Floppy360K));
+        assert_eq!(rtc.floppy_type_code(0), 4);
+        assert_eq!(rtc.hard_disk(), Some((306, 17, 4)));
+        assert_eq!(rtc.read_register(REG_HARD_DISK_TYPES), 0xF0);
+        assert_eq!(rtc.read_register(REG_HARD_DISK_0_TYPE), 47);
+        assert_eq!(
+            rtc.read_register(REG_HARD_DISK_0_PARAMS),
+            (306 & 0xFF) as u8
+        );
+        assert_eq!(rtc.read_register(REG_HARD_DISK_0_PARAMS + 2), 4);
+        assert_eq!(rtc.read_register(REG_HARD_DISK_0_PARAMS + 7), 17);
+        assert_eq!(rtc.equipment(), 0x61);
+        assert_eq!(rtc.read_register(REG_BASE_MEMORY + 1), 0x02); // 640 = 0280h
+        assert_eq!(rtc.read_register(REG_EXTENDED_MEMORY + 1), 0x0C); // 3072 = 0C00h
+        assert!(rtc.checksum_valid());
+
+        // A guest write to a setup byte invalidates the checksum
+        rtc.write_register(REG_EQUIPMENT, 0x00);
+        assert!(!rtc.checksum_valid());
+    }
+
+    #[test]
+    fn test_cmos_ram_persistence() {
+        let mut rtc = Rtc::new();
+        rtc.write_register(0x40, 0xA5);
+        let saved = rtc.cmos_ram().to_vec();
+        assert_eq!(saved.len(), CMOS_SIZE - CMOS_RAM_START);
+
+        let mut restored = Rtc::new();
+        assert!(restored.load_cmos_ram(&saved));
+        assert_eq!(restored.read_register(0x40), 0xA5);
+        assert!(!restored.load_cmos_ram(&saved[1..]));
+    }
+}
@@ -0,0 +1,560 @@
+//! National Semiconductor 8250 UART (COM1/COM2)
+//!
+//! Registers relative to the base port (DLAB = bit 7 of the line control
+//! register):
+//! - +0: Receive buffer (read) / transmit holding register (write),
+//!   divisor latch low byte when DLAB is set
+//! - +1: Interrupt enable, divisor latch high byte when DLAB is set
+//! - +2: Interrupt identification (read only)
+//! - +3: Line control (word length, stop bits, parity, DLAB)
+//! - +4: Modem control (DTR, RTS, OUT1, OUT2, loopback)
+//! - +5: Line status
+//! - +6: Modem status
+//! - +7: Scratch
+//!
+//! Characters are exchanged with an optional [`SerialBackend`] on the host
+//! and paced at the programmed baud rate, so a guest polling the line
+//! status register sees one character per character time. Received bytes
+//! wait in the backend until the guest has read the previous one, so the
+//! receiver only overruns in loopback mode. On the PC the interrupt output only reaches the
+//! PIC while OUT2 is set.
+
+use std::cell::Cell;
+
+/// COM1 base port
+pub const COM1_BASE_PORT: u16 = 0x3F8;
+/// COM1 IRQ line (INT 0Ch)
+pub const COM1_IRQ: u8 = 4;
+/// COM2 base port
+pub const COM2_BASE_PORT: u16 = 0x2F8;
+/// COM2 IRQ line (INT 0Bh)
+pub const COM2_IRQ: u8 = 3;
+
+/// Input clock of the baud rate generator divided by 16 (1.8432 MHz / 16)
+const BAUD_CLOCK: u32 = 115_200;
+/// Interval between backend polls in emulated microseconds
+const POLL_INTERVAL_US: f64 = 1000.0;
+
+const REG_DATA: u16 = 0;
+const REG_IER: u16 = 1;
+const REG_IIR: u16 = 2;
+const REG_LCR: u16 = 3;
+const REG_MCR: u16 = 4;
+const REG_LSR: u16 = 5;
+const REG_MSR: u16 = 6;
+
+/// IER: received data available
+const IER_RX_DATA: u8 = 0x01;
+/// IER: transmit holding register empty
+const IER_THRE: u8 = 0x02;
+/// IER: receiver line status
+const IER_LINE_STATUS: u8 = 0x04;
+/// IER: modem status
+const IER_MODEM_STATUS: u8 = 0x08;
+
+/// IIR: no interrupt pending
+const IIR_NONE: u8 = 0x01;
+const IIR_MODEM_STATUS: u8 = 0x00;
+const IIR_THRE: u8 = 0x02;
+const IIR_RX_DATA: u8 = 0x04;
+const IIR_LINE_STATUS: u8 = 0x06;
+
+/// LCR: divisor latch access bit
+const LCR_DLAB: u8 = 0x80;
+/// LCR: parity enable
+const LCR_PARITY: u8 = 0x08;
+/// LCR: two stop bits
+const LCR_STOP_BITS: u8 = 0x04;
+
+const MCR_DTR: u8 = 0x01;
+const MCR_RTS: u8 = 0x02;
+const MCR_OUT1: u8 = 0x04;
+/// MCR: OUT2, gates the interrupt output on PC serial cards
+const MCR_OUT2: u8 = 0x08;
+const MCR_LOOPBACK: u8 = 0x10;
+
+/// LSR: data ready
+const LSR_DATA_READY: u8 = 0x01;
+/// LSR: overrun error
+const LSR_OVERRUN: u8 = 0x02;
+/// LSR: transmit holding register empty
+const LSR_THRE: u8 = 0x20;
+/// LSR: transmitter empty
+const LSR_TEMT: u8 = 0x40;
+
+const MSR_DELTA_CTS: u8 = 0x01;
+const MSR_DELTA_DSR: u8 = 0x02;
+const MSR_TRAILING_RI: u8 = 0x04;
+const MSR_DELTA_DCD: u8 = 0x08;
+const MSR_CTS: u8 = 0x10;
+const MSR_DSR: u8 = 0x20;
+const MSR_RI: u8 = 0x40;
+const MSR_DCD: u8 = 0x80;
+
+/// Host side of a serial port
+///
+/// Backends are polled from the emulation thread and must not block.
+pub trait SerialBackend: Send {
+    /// Exchange pending data with the host (called about once per
+    /// emulated millisecond)
+    fn poll(&mut self);
+    /// Queue a byte transmitted by the guest
+    fn send(&mut self, byte: u8);
+    /// Take the next byte received from the host
+    fn receive(&mut self) -> Option<u8>;
+    /// Whether the other end is connected (reported as DCD, DSR and CTS)
+    fn connected(&self) -> bool;
+}
+
+/// 8250 UART
+pub struct Uart {
+    /// Divisor latch (baud rate = 115200 / divisor)
+    divisor: u16,
+    interrupt_enable: u8,
+    line_control: u8,
+    modem_control: u8,
+    scratch: u8,
+
+    /// Receive buffer register and data ready flag
+    receive_buffer: Cell<u8>,
+    data_ready: Cell<bool>,
+    /// Overrun error (only possible in loopback mode, where a character
+    /// arrives whether or not the previous one was read)
+    overrun: Cell<bool>,
+    /// Transmit holding register empty, and time left on the current character
+    thr_empty: bool,
+    transmit_remaining_us: f64,
+    /// THRE interrupt pending (cleared by reading IIR or writing THR)
+    thre_pending: Cell<bool>,
+    /// Modem status inputs and their delta bits
+    modem_status: Cell<u8>,
+
+    /// Time since the last character was moved into the receive buffer
+    receive_elapsed_us: f64,
+    poll_elapsed_us: f64,
+    /// Character being looped back to the receiver in loopback mode
+    loopback: Option<u8>,
+    backend: Option<Box<dyn SerialBackend>>,
+
+    /// Interrupt output level at the last `take_irq_edge` call
+    irq_line: bool,
+}
+
+impl Uart {
+    pub fn new() -> Self {
+        Self {
+            divisor: 12, // 9600 baud
+            interrupt_enable: 0,
+            line_control: 0x03, // 8N1
+            modem_control: 0,
+            scratch: 0,
+            receive_buffer: Cell::new(0),
+            data_ready: Cell::new(false),
+            overrun: Cell::new(false),
+            thr_empty: true,
+            transmit_remaining_us: 0.0,
+            thre_pending: Cell::new(false),
+            modem_status: Cell::new(0),
+            receive_elapsed_us: 0.0,
+            poll_elapsed_us: 0.0,
+            loopback: None,
+            backend: None,
+            irq_line: false,
+        }
+    }
+
+    /// Reset the registers, keeping the host backend attached
+    pub fn reset(&mut self) {
+        let backend = self.backend.take();
+        *self = Self::new();
+        self.backend = backend;
+        self.update_modem_inputs();
+        // Power-on deltas are not reported
+        self.modem_status.set(self.modem_status.get() & 0xF0);
+    }
+
+    /// Attach a host backend, replacing the previous one
+    pub fn attach(&mut self, backend: Box<dyn SerialBackend>) {
+        self.backend = Some(backend);
+        self.update_modem_inputs();
+    }
+
+    /// Detach the host backend
+    pub fn detach(&mut self) {
+        self.backend = None;
+        self.update_modem_inputs();
+    }
+
+    /// Check if a host backend is attached
+    pub fn is_attached(&self) -> bool {
+        self.backend.is_some()
+    }
+
+    /// Current baud rate
+    pub fn baud_rate(&self) -> u32 {
+        BAUD_CLOCK / u32::from(self.divisor.max(1))
+    }
+
+    /// Time to send one character (start bit, data, parity and stop bits)
+    fn character_time_us(&self) -> f64 {
+        let data_bits = 5 + u32::from(self.line_control & 0x03);
+        let parity_bits = u32::from(self.line_control & LCR_PARITY != 0);
+        let stop_bits = if self.line_control & LCR_STOP_BITS != 0 {
+            2
+        } else {
+            1
+        };
+        let bits = 1 + data_bits + parity_bits + stop_bits;
+        bits as f64 * 1_000_000.0 / self.baud_rate() as f64
+    }
+
+    fn dlab(&self) -> bool {
+        self.line_control & LCR_DLAB != 0
+    }
+
+    fn loopback_mode(&self) -> bool {
+        self.modem_control & MCR_LOOPBACK != 0
+    }
+
+    /// Read a register (offset 0-7 from the base port)
+    pub fn io_read(&self, offset: u16) -> u8 {
+        match offset & 7 {
+            REG_DATA if self.dlab() => self.divisor as u8,
+            REG_DATA => {
+                self.data_ready.set(false);
+                self.receive_buffer.get()
+            }
+            REG_IER if self.dlab() => (self.divisor >> 8) as u8,
+            REG_IER => self.interrupt_enable,
+            REG_IIR => {
+                let iir = self.interrupt_id();
+                if iir == IIR_THRE {
+                    self.thre_pending.set(false);
+                }
+                iir
+            }
+            REG_LCR => self.line_control,
+            REG_MCR => self.modem_control,
+            REG_LSR => {
+                let lsr = self.line_status();
+                self.overrun.set(false);
+                lsr
+            }
+            REG_MSR => {
+                let msr = self.modem_status.get();
+                self.modem_status.set(msr & 0xF0);
+                msr
+            }
+            _ => self.scratch, // +7
+        }
+    }
+
+    /// Write a register (offset 0-7 from the base port)
+    pub fn io_write(&mut self, offset: u16, val: u8) {
+        match offset & 7 {
+            REG_DATA if self.dlab() => self.divisor = (self.divisor & 0xFF00) | u16::from(val),
+            REG_DATA => self.transmit(val),
+            REG_IER if self.dlab() => {
+                self.divisor = (self.divisor & 0x00FF) | (u16::from(val) << 8)
+            }
+            REG_IER => {
+                let enabling_thre = val & IER_THRE != 0 && self.interrupt_enable & IER_THRE == 0;
+                self.interrupt_enable = val & 0x0F;
+                // Enabling the THRE interrupt with an empty THR raises it at once
+                if enabling_thre && self.thr_empty {
+                    self.thre_pending.set(true);
+                }
+            }
+            REG_IIR => {} // Read only on the 8250
+            REG_LCR => self.line_control = val,
+            REG_MCR => {
+                self.modem_control = val & 0x1F;
+                self.update_modem_inputs();
+            }
+            REG_LSR | REG_MSR => {}  // Factory test only
+            _ => self.scratch = val, // +7
+        }
+    }
+
+    /// Line status register value
+    pub fn line_status(&self) -> u8 {
+        let mut lsr = 0;
+        if self.data_ready.get() {
+            lsr |= LSR_DATA_READY;
+        }
+        if self.overrun.get() {
+            lsr |= LSR_OVERRUN;
+        }
+        if self.thr_empty {
+            lsr |= LSR_THRE | LSR_TEMT;
+        }
+        lsr
+    }
+
+    /// Highest priority pending interrupt as reported in the IIR
+    fn interrupt_id(&self) -> u8 {
+        let ier = self.interrupt_enable;
+        if ier & IER_LINE_STATUS != 0 && self.overrun.get() {
+            IIR_LINE_STATUS
+        } else if ier & IER_RX_DATA != 0 && self.data_ready.get() {
+            IIR_RX_DATA
+        } else if ier & IER_THRE != 0 && self.thre_pending.get() {
+            IIR_THRE
+        } else if ier & IER_MODEM_STATUS != 0 && self.modem_status.get() & 0x0F != 0 {
+            IIR_MODEM_STATUS
+        } else {
+            IIR_NONE
+        }
+    }
+
+    /// Interrupt output as seen by the PIC (gated by OUT2)
+    fn irq_output(&self) -> bool {
+        self.modem_control & MCR_OUT2 != 0 && self.interrupt_id() != IIR_NONE
+    }
+
+    /// Check for a rising edge on the interrupt output since the last call
+    pub fn take_irq_edge(&mut self) -> bool {
+        let line = self.irq_output();
+        let edge = line && !self.irq_line;
+        self.irq_line = line;
+        edge
+    }
+
+    fn transmit(&mut self, byte: u8) {
+        self.thr_empty = false;
+        self.thre_pending.set(false);
+        self.transmit_remaining_us = self.character_time_us();
+        if self.loopback_mode() {
+            self.loopback = Some(byte);
+        } else if let Some(backend) = self.backend.as_mut() {
+            backend.send(byte);
+        }
+    }
+
+    /// Recompute the modem status inputs (loopback outputs or the backend
+    /// connection) and set the delta bits for any change
+    fn update_modem_inputs(&mut self) {
+        let inputs = if self.loopback_mode() {
+            let mcr = self.modem_control;
+            let mut inputs = 0;
+            if mcr & MCR_RTS != 0 {
+                inputs |= MSR_CTS;
+            }
+            if mcr & MCR_DTR != 0 {
+                inputs |= MSR_DSR;
+            }
+            if mcr & MCR_OUT1 != 0 {
+                inputs |= MSR_RI;
+            }
+            if mcr & MCR_OUT2 != 0 {
+                inputs |= MSR_DCD;
+            }
+            inputs
+        } else if self.backend.as_ref().is_some_and(|b| b.connected()) {
+            MSR_CTS | MSR_DSR | MSR_DCD
+        } else {
+            0
+        };
+
+        let old = self.modem_status.get();
+        let changed = (old ^ inputs) & 0xF0;
+        let mut deltas = old & 0x0F;
+        if changed & MSR_CTS != 0 {
+            deltas |= MSR_DELTA_CTS;
+        }
+        if changed & MSR_DSR != 0 {
+            deltas |= MSR_DELTA_DSR;
+        }
+        if old & MSR_RI != 0 && inputs & MSR_RI == 0 {
+            deltas |= MSR_TRAILING_RI;
+        }
+        if changed & MSR_DCD != 0 {
+            deltas |= MSR_DELTA_DCD;
+        }
+        self.modem_status.set(inputs | deltas);
+    }
+
+    /// Advance the UART by `us` microseconds of emulated time
+    pub fn advance(&mut self, us: f64) {
+        let character_time = self.character_time_us();
+
+        if !self.thr_empty {
+            self.transmit_remaining_us -= us;
+            if self.transmit_remaining_us <= 0.0 {
+                self.thr_empty = true;
+                self.thre_pending.set(true);
+                if let Some(byte) = self.loopback.take() {
+                    self.overrun.set(self.data_ready.get());
+                    self.receive_buffer.set(byte);
+                    self.data_ready.set(true);
+                }
+            }
+        }
+
+        if let Some(backend) = self.backend.as_mut() {
+            self.poll_elapsed_us += us;
+            if self.poll_elapsed_us >= POLL_INTERVAL_US {
+                self.poll_elapsed_us = 0.0;
+                backend.poll();
+                self.update_modem_inputs();
+            }
+        }
+
+        // Move the next character into the receive buffer once the previous
+        // one has been read and a character time has passed
+        self.receive_elapsed_us = (self.receive_elapsed_us + us).min(character_time);
+        if !self.data_ready.get()
+            && !self.loopback_mode()
+            && self.receive_elapsed_us >= character_time
+        {
+            if let Some(byte) = self.backend.as_mut().and_then(|backend| backend.receive()) {
+                self.receive_buffer.set(byte);
+                self.data_ready.set(true);
+                self.receive_elapsed_us = 0.0;
+            }
+        }
+    }
+}
+
+impl Default for Uart {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+    use std::sync::{Arc, Mutex};
+
+    const REG_SCRATCH: u16 = 7;
+
+    /// Backend that records sent bytes and replays queued input
+    #[derive(Clone, Default)]
+    struct TestBackend {
+        sent: Arc<Mutex<Vec<u8>>>,
+        input: Arc<Mutex<VecDeque<u8>>>,
+    }
+
+    impl SerialBackend for TestBackend {
+        fn poll(&mut self) {}
+        fn send(&mut self, byte: u8) {
+            self.sent.lock().unwrap().push(byte);
+        }
+        fn receive(&mut self) -> Option<u8> {
+            self.input.lock().unwrap().pop_front()
+        }
+        fn connected(&self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn test_divisor_and_character_time() {
+        let mut uart = Uart::new();
+        assert_eq!(uart.baud_rate(), 9600);
+
+        // 2400 baud 7E1 = 10 bits per character
+        uart.io_write(REG_LCR, LCR_DLAB);
+        uart.io_write(REG_DATA, 0x30);
+        uart.io_write(REG_IER, 0x00);
+        assert_eq!(uart.io_read(REG_DATA), 0x30);
+        uart.io_write(REG_LCR, 0x1A);
+        assert_eq!(uart.baud_rate(), 2400);
+        assert!((uart.character_time_us() - 10.0 * 1_000_000.0 / 2400.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_transmit_and_receive_pacing() {
+        let backend = TestBackend::default();
+        let mut uart = Uart::new();
+        uart.attach(Box::new(backend.clone()));
+        backend.input.lock().unwrap().extend(b"AB");
+
+        uart.io_write(REG_DATA, b'x');
+        assert_eq!(*backend.sent.lock().unwrap(), b"x");
+        assert_eq!(uart.line_status() & LSR_THRE, 0);
+
+        // 8N1 at 9600 baud: about 1042 us per character
+        uart.advance(1100.0);
+        assert_ne!(uart.line_status() & LSR_THRE, 0);
+        assert_ne!(uart.line_status() & LSR_DATA_READY, 0);
+        assert_eq!(uart.io_read(REG_DATA), b'A');
+        assert_eq!(uart.line_status() & LSR_DATA_READY, 0);
+
+        // The next character needs another character time
+        uart.advance(500.0);
+        assert_eq!(uart.line_status() & LSR_DATA_READY, 0);
+        uart.advance(600.0);
+        assert_eq!(uart.io_read(REG_DATA), b'B');
+    }
+
+    #[test]
+    fn test_interrupts() {
+        let backend = TestBackend::default();
+        let mut uart = Uart::new();
+        uart.attach(Box::new(backend.clone()));
+        assert_eq!(uart.io_read(REG_IIR), IIR_NONE);
+
+        // Enabling THRE with an empty THR interrupts immediately, but only
+        // reaches the PIC with OUT2 set
+        uart.io_write(REG_IER, IER_RX_DATA | IER_THRE);
+        assert!(!uart.take_irq_edge());
+        uart.io_write(REG_MCR, MCR_DTR | MCR_RTS | MCR_OUT2);
+        assert!(uart.take_irq_edge());
+        assert!(!uart.take_irq_edge());
+        assert_eq!(uart.io_read(REG_IIR), IIR_THRE);
+        assert_eq!(uart.io_read(REG_IIR), IIR_NONE);
+        uart.take_irq_edge();
+
+        // Received data has priority over THRE
+        backend.input.lock().unwrap().push_back(0x55);
+        uart.io_write(REG_DATA, 0x01);
+        uart.advance(2000.0);
+        assert!(uart.take_irq_edge());
+        assert_eq!(uart.io_read(REG_IIR), IIR_RX_DATA);
+        assert_eq!(uart.io_read(REG_DATA), 0x55);
+        assert_eq!(uart.io_read(REG_IIR), IIR_THRE);
+    }
+
+    #[test]
+    fn test_loopback_mode() {
+        let backend = TestBackend::default();
+        let mut uart = Uart::new();
+        uart.attach(Box::new(backend.clone()));
+        assert_eq!(uart.modem_status.get() & 0xF0, MSR_CTS | MSR_DSR | MSR_DCD);
+
+        uart.io_write(REG_MCR, MCR_LOOPBACK | MCR_RTS | MCR_OUT1);
+        assert_eq!(uart.modem_status.get() & 0xF0, MSR_CTS | MSR_RI);
+        uart.io_read(REG_MSR);
+        assert_eq!(uart.modem_status.get() & 0x0F, 0);
+
+        uart.io_write(REG_DATA, 0xA5);
+        uart.advance(2000.0);
+        assert_eq!(uart.io_read(REG_DATA), 0xA5);
+        assert!(backend.sent.lock().unwrap().is_empty());
+
+        // A second character before the first is read overruns
+        uart.io_write(REG_IER, IER_LINE_STATUS);
+        uart.io_write(REG_DATA, 0x01);
+        uart.advance(2000.0);
+        uart.io_write(REG_DATA, 0x02);
+        uart.advance(2000.0);
+        assert_eq!(uart.io_read(REG_IIR), IIR_LINE_STATUS);
+        assert_ne!(uart.io_read(REG_LSR) & LSR_OVERRUN, 0);
+        assert_eq!(uart.io_read(REG_LSR) & LSR_OVERRUN, 0);
+        assert_eq!(uart.io_read(REG_DATA), 0x02);
+    }
+
+    #[test]
+    fn test_reset_keeps_backend() {
+        let mut uart = Uart::new();
+        uart.attach(Box::new(TestBackend::default()));
+        uart.io_write(REG_SCRATCH, 0x42);
+        uart.reset();
+        assert!(uart.is_attached());
+        assert_eq!(uart.io_read(REG_SCRATCH), 0);
+        assert_eq!(uart.io_read(REG_MSR), MSR_CTS | MSR_DSR | MSR_DCD);
+    }
+}
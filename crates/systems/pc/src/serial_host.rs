@@ -0,0 +1,400 @@
+//! Host connections for the emulated serial ports
+//!
+//! A serial port can be bound to:
+//! - `tcp:<host>:<port>`: a TCP connection to another program (for example
+//!   a second emulator instance listening for a null-modem link), retried
+//!   every second until the server answers
+//! - `tcp-listen:[<address>:]<port>`: a TCP listener accepting one client at
+//!   a time, on 127.0.0.1 unless an address is given
+//! - `pty:<path>`: an existing pseudo-terminal or serial device, such as one
+//!   end of a pair created with `socat -d -d pty,raw,echo=0 pty,raw,echo=0`
+//!
+//! Sockets are non-blocking and devices are served by reader and writer
+//! threads, so the emulation thread never waits on the host.
+
+use crate::serial::SerialBackend;
+use emu_core::logging::{log, LogCategory, LogLevel};
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// Where a serial port is connected on the host
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SerialBinding {
+    /// Connect to a TCP server (`host:port`)
+    Tcp(String),
+    /// Listen for a TCP client (`address:port`)
+    TcpListen(String),
+    /// Open a pseudo-terminal or serial device
+    Pty(String),
+}
+
+impl SerialBinding {
+    /// Open the host side of the binding
+    pub fn open(&self) -> io::Result<Box<dyn SerialBackend>> {
+        Ok(match self {
+            SerialBinding::Tcp(address) => Box::new(TcpSerial::connect(address)),
+            SerialBinding::TcpListen(address) => Box::new(TcpSerial::listen(address)?),
+            SerialBinding::Pty(path) => Box::new(DeviceSerial::open(path)?),
+        })
+    }
+}
+
+impl std::fmt::Display for SerialBinding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SerialBinding::Tcp(address) => write!(f, "tcp:{}", address),
+            SerialBinding::TcpListen(address) => write!(f, "tcp-listen:{}", address),
+            SerialBinding::Pty(path) => write!(f, "pty:{}", path),
+        }
+    }
+}
+
+impl std::str::FromStr for SerialBinding {
+    type Err = String;
+
+    /// Parse "tcp:host:port", "tcp-listen:[address:]port" or "pty:path"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (kind, target) = s
+            .split_once(':')
+            .ok_or_else(|| format!("Invalid serial binding: {}", s))?;
+        let has_port = |address: &str| {
+            address
+                .rsplit_once(':')
+                .is_some_and(|(host, port)| !host.is_empty() && port.parse::<u16>().is_ok())
+        };
+        match kind.to_ascii_lowercase().as_str() {
+            "tcp" if has_port(target) => Ok(SerialBinding::Tcp(target.to_string())),
+            "tcp-listen" if target.parse::<u16>().is_ok() => {
+                Ok(SerialBinding::TcpListen(format!("127.0.0.1:{}", target)))
+            }
+            "tcp-listen" if has_port(target) => Ok(SerialBinding::TcpListen(target.to_string())),
+            "pty" if !target.is_empty() => Ok(SerialBinding::Pty(target.to_string())),
+            _ => Err(format!("Invalid serial binding: {}", s)),
+        }
+    }
+}
+
+/// Delay between attempts to reach a TCP server
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// How a TCP serial port finds its peer
+enum TcpPeer {
+    /// Connect to a server, retrying in a background thread until it answers
+    Connect {
+        address: String,
+        pending: Option<Receiver<TcpStream>>,
+    },
+    /// Accept one client at a time
+    Listen(TcpListener),
+}
+
+/// Serial port carried over a TCP connection
+///
+/// A dropped connection is re-established (or the next client accepted),
+/// like a modem waiting for carrier.
+struct TcpSerial {
+    peer: TcpPeer,
+    stream: Option<TcpStream>,
+    input: VecDeque<u8>,
+    output: Vec<u8>,
+    /// Tells a pending connect thread to give up
+    closed: Arc<AtomicBool>,
+}
+
+impl TcpSerial {
+    fn connect(address: &str) -> Self {
+        Self {
+            peer: TcpPeer::Connect {
+                address: address.to_string(),
+                pending: None,
+            },
+            stream: None,
+            input: VecDeque::new(),
+            output: Vec::new(),
+            closed: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    fn listen(address: &str) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        log(LogCategory::Bus, LogLevel::Info, || {
+            format!("Serial: listening on {}", address)
+        });
+        Ok(Self {
+            peer: TcpPeer::Listen(listener),
+            stream: None,
+            input: VecDeque::new(),
+            output: Vec::new(),
+            closed: Arc::new(AtomicBool::new(false)),
+        })
+    }
+
+    /// Start a thread that keeps trying to reach the server
+    fn spawn_connector(address: String, closed: Arc<AtomicBool>) -> Receiver<TcpStream> {
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            while !closed.load(Ordering::Relaxed) {
+                if let Ok(stream) = TcpStream::connect(&address) {
+                    let _ = tx.send(stream);
+                    return;
+                }
+                thread::sleep(RECONNECT_DELAY);
+            }
+        });
+        rx
+    }
+
+    /// Check for a new connection
+    fn find_peer(&mut self) -> Option<TcpStream> {
+        match &mut self.peer {
+            TcpPeer::Listen(listener) => {
+                let (stream, peer) = listener.accept().ok()?;
+                log(LogCategory::Bus, LogLevel::Info, || {
+                    format!("Serial: accepted connection from {}", peer)
+                });
+                Some(stream)
+            }
+            TcpPeer::Connect { address, pending } => {
+                let rx = pending.get_or_insert_with(|| {
+                    Self::spawn_connector(address.clone(), Arc::clone(&self.closed))
+                });
+                let stream = rx.try_recv().ok()?;
+                *pending = None;
+                log(LogCategory::Bus, LogLevel::Info, || {
+                    format!("Serial: connected to {}", address)
+                });
+                Some(stream)
+            }
+        }
+    }
+
+    fn disconnect(&mut self) {
+        if self.stream.take().is_some() {
+            log(LogCategory::Bus, LogLevel::Info, || {
+                "Serial: TCP connection closed".to_string()
+            });
+        }
+        self.output.clear();
+    }
+}
+
+impl Drop for TcpSerial {
+    fn drop(&mut self) {
+        self.closed.store(true, Ordering::Relaxed);
+    }
+}
+
+impl SerialBackend for TcpSerial {
+    fn poll(&mut self) {
+        if self.stream.is_none() {
+            let Some(stream) = self.find_peer() else {
+                return;
+            };
+            if stream.set_nonblocking(true).is_err() {
+                return;
+            }
+            let _ = stream.set_nodelay(true);
+            self.stream = Some(stream);
+        }
+
+        let Some(stream) = self.stream.as_mut() else {
+            return;
+        };
+        let mut closed = false;
+
+        if !self.output.is_empty() {
+            match stream.write(&self.output) {
+                Ok(written) => {
+                    self.output.drain(..written);
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+                Err(_) => closed = true,
+            }
+        }
+
+        let mut buffer = [0u8; 512];
+        while !closed {
+            match stream.read(&mut buffer) {
+                Ok(0) => closed = true,
+                Ok(count) => self.input.extend(&buffer[..count]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(_) => closed = true,
+            }
+        }
+
+        if closed {
+            self.disconnect();
+        }
+    }
+
+    fn send(&mut self, byte: u8) {
+        // Without a connection the byte is lost, as on a line with no carrier
+        if self.stream.is_some() {
+            self.output.push(byte);
+        }
+    }
+
+    fn receive(&mut self) -> Option<u8> {
+        self.input.pop_front()
+    }
+
+    fn connected(&self) -> bool {
+        self.stream.is_some()
+    }
+}
+
+/// Serial port carried by a pseudo-terminal or serial device
+struct DeviceSerial {
+    input: Receiver<u8>,
+    output: Sender<u8>,
+    open: Arc<AtomicBool>,
+}
+
+impl DeviceSerial {
+    fn open(path: &str) -> io::Result<Self> {
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        let reader = file.try_clone()?;
+        let open = Arc::new(AtomicBool::new(true));
+        let (input_tx, input) = mpsc::channel();
+        let (output, output_rx) = mpsc::channel();
+
+        let reader_open = Arc::clone(&open);
+        thread::spawn(move || Self::read_loop(reader, input_tx, reader_open));
+        let writer_open = Arc::clone(&open);
+        thread::spawn(move || Self::write_loop(file, output_rx, writer_open));
+
+        log(LogCategory::Bus, LogLevel::Info, || {
+            format!("Serial: opened {}", path)
+        });
+        Ok(Self {
+            input,
+            output,
+            open,
+        })
+    }
+
+    fn read_loop(mut file: File, input: Sender<u8>, open: Arc<AtomicBool>) {
+        let mut buffer = [0u8; 512];
+        loop {
+            match file.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(count) => {
+                    if buffer[..count].iter().any(|&b| input.send(b).is_err()) {
+                        break;
+                    }
+                }
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(_) => break,
+            }
+        }
+        open.store(false, Ordering::Relaxed);
+    }
+
+    fn write_loop(mut file: File, output: Receiver<u8>, open: Arc<AtomicBool>) {
+        while let Ok(byte) = output.recv() {
+            if file.write_all(&[byte]).is_err() {
+                break;
+            }
+        }
+        open.store(false, Ordering::Relaxed);
+    }
+}
+
+impl SerialBackend for DeviceSerial {
+    fn poll(&mut self) {}
+
+    fn send(&mut self, byte: u8) {
+        let _ = self.output.send(byte);
+    }
+
+    fn receive(&mut self) -> Option<u8> {
+        self.input.try_recv().ok()
+    }
+
+    fn connected(&self) -> bool {
+        self.open.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bindings() {
+        assert_eq!(
+            "tcp:localhost:5555".parse(),
+            Ok(SerialBinding::Tcp("localhost:5555".to_string()))
+        );
+        assert_eq!(
+            "tcp-listen:5555".parse(),
+            Ok(SerialBinding::TcpListen("127.0.0.1:5555".to_string()))
+        );
+        assert_eq!(
+            "TCP-LISTEN:0.0.0.0:23".parse(),
+            Ok(SerialBinding::TcpListen("0.0.0.0:23".to_string()))
+        );
+        assert_eq!(
+            "pty:/dev/pts/3".parse(),
+            Ok(SerialBinding::Pty("/dev/pts/3".to_string()))
+        );
+        assert!("tcp:localhost".parse::<SerialBinding>().is_err());
+        assert!("udp:localhost:5555".parse::<SerialBinding>().is_err());
+        assert!("pty:".parse::<SerialBinding>().is_err());
+
+        let binding = SerialBinding::Tcp("10.0.0.2:2323".to_string());
+        assert_eq!(binding.to_string().parse(), Ok(binding));
+    }
+
+    #[test]
+    fn test_tcp_null_modem() {
+        // Two ports linked over loopback TCP, like two emulator instances
+        let mut server = TcpSerial::listen("127.0.0.1:0").unwrap();
+        let TcpPeer::Listen(listener) = &server.peer else {
+            unreachable!()
+        };
+        let address = listener.local_addr().unwrap();
+        let mut client = TcpSerial::connect(&address.to_string());
+
+        let poll_until = |server: &mut TcpSerial,
+                          client: &mut TcpSerial,
+                          done: &dyn Fn(&TcpSerial, &TcpSerial) -> bool| {
+            for _ in 0..400 {
+                server.poll();
+                client.poll();
+                if done(server, client) {
+                    return true;
+                }
+                thread::sleep(Duration::from_millis(5));
+            }
+            false
+        };
+        assert!(poll_until(&mut server, &mut client, &|s, c| s.connected()
+            && c.connected()));
+
+        client.send(b'h');
+        client.send(b'i');
+        server.send(b'!');
+        assert!(poll_until(&mut server, &mut client, &|s, c| s.input.len()
+            == 2
+            && c.input.len() == 1));
+        assert_eq!(server.receive(), Some(b'h'));
+        assert_eq!(server.receive(), Some(b'i'));
+        assert_eq!(client.receive(), Some(b'!'));
+
+        // Closing one end drops the carrier on the other
+        drop(client);
+        let mut idle = TcpSerial::listen("127.0.0.1:0").unwrap();
+        assert!(poll_until(&mut server, &mut idle, &|s, _| !s.connected()));
+    }
+}
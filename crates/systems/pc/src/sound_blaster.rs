@@ -0,0 +1,426 @@
+//! Sound Blaster 2.0 DSP (digital sound processor)
+//!
+//! Emulates the DSP of a Sound Blaster 2.0 at the default configuration
+//! `BLASTER=A220 I5 D1 T3`:
+//! - 0x226: DSP reset
+//! - 0x22A: Read data
+//! - 0x22C: Write command/data (read: write buffer status)
+//! - 0x22E: Read buffer status (reading acknowledges the 8-bit IRQ)
+//!
+//! 8-bit PCM is played through DMA channel 1 in single-cycle (0x14, 0x91)
+//! or auto-init (0x1C, 0x90) mode, raising IRQ 5 at the end of each block.
+//! Direct output (0x10) is also supported. The OPL2 FM chip at base+8/base+9
+//! is handled by [`crate::opl2::Opl2`].
+//!
+//! Output is held between DSP samples and produced at the host sample rate
+//! so it can be mixed with the OPL2 output.
+
+use crate::opl2::SAMPLE_RATE;
+use emu_core::logging::{log, LogCategory, LogLevel};
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+
+/// Default base I/O port
+pub const SB_BASE_PORT: u16 = 0x220;
+/// Default DMA channel
+pub const SB_DMA_CHANNEL: usize = 1;
+/// Default IRQ line (INT 0Dh)
+pub const SB_IRQ: u8 = 5;
+
+/// DSP version reported by command 0xE1 (Sound Blaster 2.01)
+const DSP_VERSION: (u8, u8) = (0x02, 0x01);
+
+/// Maximum buffered output (1 second) if the host stops pulling samples
+const MAX_BUFFERED_SAMPLES: usize = SAMPLE_RATE as usize;
+
+/// Sound Blaster DSP
+pub struct SoundBlaster {
+    /// Last value written to the reset port
+    reset_latch: bool,
+    /// Bytes waiting to be read from the data port
+    read_queue: RefCell<VecDeque<u8>>,
+    /// Command being assembled and its parameters
+    command: Option<u8>,
+    params: Vec<u8>,
+    /// Test register (commands 0xE4/0xE8)
+    test_register: u8,
+
+    /// Transfer time constant (sample rate = 1 MHz / (256 - tc))
+    time_constant: u8,
+    /// Block transfer size minus one (command 0x48)
+    block_size: u16,
+    speaker_on: bool,
+
+    // DMA playback
+    dma_active: bool,
+    dma_paused: bool,
+    auto_init: bool,
+    exit_auto_init: bool,
+    /// Bytes left in the current block
+    remaining: u32,
+    dma_elapsed_us: f64,
+
+    /// Current DAC output level
+    level: i16,
+    output_elapsed_us: f64,
+    output: VecDeque<i16>,
+
+    /// 8-bit IRQ line (acknowledged by reading 0x22E)
+    irq_line: Cell<bool>,
+    /// IRQ waiting to be delivered to the CPU
+    irq_request: bool,
+}
+
+impl SoundBlaster {
+    pub fn new() -> Self {
+        Self {
+            reset_latch: false,
+            read_queue: RefCell::new(VecDeque::new()),
+            command: None,
+            params: Vec::new(),
+            test_register: 0,
+            time_constant: 0,
+            block_size: 0,
+            speaker_on: false,
+            dma_active: false,
+            dma_paused: false,
+            auto_init: false,
+            exit_auto_init: false,
+            remaining: 0,
+            dma_elapsed_us: 0.0,
+            level: 0,
+            output_elapsed_us: 0.0,
+            output: VecDeque::new(),
+            irq_line: Cell::new(false),
+            irq_request: false,
+        }
+    }
+
+    /// Reset the DSP to power-on state
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Check whether a port belongs to the DSP
+    pub fn handles_port(port: u16) -> bool {
+        port >= SB_BASE_PORT && matches!(port - SB_BASE_PORT, 0x06 | 0x0A | 0x0C | 0x0E)
+    }
+
+    /// Read a DSP port
+    pub fn io_read(&self, port: u16) -> u8 {
+        match port - SB_BASE_PORT {
+            0x0A => self.read_queue.borrow_mut().pop_front().unwrap_or(0xFF),
+            // Write buffer status: bit 7 clear = ready for a command
+            0x0C => 0x7F,
+            0x0E => {
+                self.irq_line.set(false);
+                if self.read_queue.borrow().is_empty() {
+                    0x7F
+                } else {
+                    0xFF
+                }
+            }
+            _ => 0xFF,
+        }
+    }
+
+    /// Write a DSP port
+    pub fn io_write(&mut self, port: u16, val: u8) {
+        match port - SB_BASE_PORT {
+            0x06 => {
+                let latch = val & 0x01 != 0;
+                if self.reset_latch && !latch {
+                    self.reset();
+                    self.read_queue.borrow_mut().push_back(0xAA);
+                }
+                self.reset_latch = latch;
+            }
+            0x0C => self.write_command(val),
+            _ => {}
+        }
+    }
+
+    /// Number of parameter bytes following a command
+    fn param_count(command: u8) -> usize {
+        match command {
+            0x10 | 0x40 | 0xE0 | 0xE4 => 1,
+            0x14 | 0x16 | 0x17 | 0x24 | 0x48 | 0x74..=0x77 | 0x80 => 2,
+            _ => 0,
+        }
+    }
+
+    fn write_command(&mut self, val: u8) {
+        let command = match self.command {
+            Some(command) => {
+                self.params.push(val);
+                command
+            }
+            None => {
+                self.params.clear();
+                val
+            }
+        };
+        if self.params.len() < Self::param_count(command) {
+            self.command = Some(command);
+            return;
+        }
+        self.command = None;
+
+        let word =
+            || self.params[0] as u32 | (self.params.get(1).copied().unwrap_or(0) as u32) << 8;
+
+        match command {
+            // Direct 8-bit DAC output
+            0x10 => self.level = Self::pcm_to_level(self.params[0]),
+            // 8-bit single-cycle DMA output
+            0x14 => {
+                let length = word() + 1;
+                self.start_dma(length, false);
+            }
+            // 8-bit auto-init DMA output
+            0x1C => self.start_dma(self.block_size as u32 + 1, true),
+            // Direct ADC input (no microphone: silence)
+            0x20 => self.queue(0x80),
+            // Set time constant
+            0x40 => self.time_constant = self.params[0],
+            // Set block transfer size
+            0x48 => self.block_size = word() as u16,
+            // Silence block: just raise the IRQ when done
+            0x80 => self.raise_irq(),
+            // High-speed auto-init / single-cycle DMA output
+            0x90 => self.start_dma(self.block_size as u32 + 1, true),
+            0x91 => self.start_dma(self.block_size as u32 + 1, false),
+            0xD0 => self.dma_paused = true,
+            0xD1 => self.speaker_on = true,
+            0xD3 => self.speaker_on = false,
+            0xD4 => self.dma_paused = false,
+            0xD8 => self.queue(if self.speaker_on { 0xFF } else { 0x00 }),
+            0xDA => self.exit_auto_init = true,
+            // DSP identification: returns the inverted byte
+            0xE0 => self.queue(!self.params[0]),
+            0xE1 => {
+                self.queue(DSP_VERSION.0);
+                self.queue(DSP_VERSION.1);
+            }
+            0xE4 => self.test_register = self.params[0],
+            0xE8 => self.queue(self.test_register),
+            // Trigger an 8-bit IRQ (used by drivers to detect the IRQ line)
+            0xF2 => self.raise_irq(),
+            _ => {
+                log(LogCategory::Stubs, LogLevel::Debug, || {
+                    format!("Sound Blaster: unsupported DSP command 0x{:02X}", command)
+                });
+            }
+        }
+    }
+
+    fn queue(&self, value: u8) {
+        self.read_queue.borrow_mut().push_back(value);
+    }
+
+    fn start_dma(&mut self, length: u32, auto_init: bool) {
+        self.dma_active = true;
+        self.dma_paused = false;
+        self.auto_init = auto_init;
+        self.exit_auto_init = false;
+        self.remaining = length;
+        self.dma_elapsed_us = 0.0;
+    }
+
+    fn raise_irq(&mut self) {
+        self.irq_line.set(true);
+        self.irq_request = true;
+    }
+
+    /// Convert an unsigned 8-bit sample to a signed output level
+    fn pcm_to_level(sample: u8) -> i16 {
+        ((sample as i16) - 128) << 8
+    }
+
+    /// DSP sample period in microseconds
+    fn sample_period_us(&self) -> f64 {
+        (256 - self.time_constant as u32) as f64
+    }
+
+    /// Output one DMA byte and handle the end of the block
+    fn play_dma_sample(&mut self, sample: u8) {
+        self.level = Self::pcm_to_level(sample);
+        self.remaining -= 1;
+        if self.remaining == 0 {
+            self.raise_irq();
+            if self.auto_init && !self.exit_auto_init {
+                self.remaining = self.block_size as u32 + 1;
+            } else {
+                self.dma_active = false;
+            }
+        }
+    }
+
+    /// Whether an IRQ is waiting to be delivered
+    pub fn irq_requested(&self) -> bool {
+        self.irq_request
+    }
+
+    /// Mark the pending IRQ as delivered
+    pub fn clear_irq_request(&mut self) {
+        self.irq_request = false;
+    }
+
+    /// Advance by elapsed emulated time, pulling DMA bytes through `fetch`
+    ///
+    /// `fetch` returns None when the DMA channel is masked, which stalls
+    /// playback until the channel is unmasked.
+    pub fn advance(&mut self, us: f64, mut fetch: impl FnMut() -> Option<u8>) {
+        let output_period = 1_000_000.0 / SAMPLE_RATE;
+        let mut remaining_us = us;
+
+        // Interleave DMA sample fetches and output samples so that a long
+        // time slice still produces the intermediate levels
+        while remaining_us > 0.0 {
+            let dma_running = self.dma_active && !self.dma_paused;
+            let to_dma = if dma_running {
+                (self.sample_period_us() - self.dma_elapsed_us).max(0.0)
+            } else {
+                f64::INFINITY
+            };
+            let to_output = (output_period - self.output_elapsed_us).max(0.0);
+            let step = to_dma.min(to_output);
+            if step > remaining_us {
+                if dma_running {
+                    self.dma_elapsed_us += remaining_us;
+                }
+                self.output_elapsed_us += remaining_us;
+                break;
+            }
+            remaining_us -= step;
+            self.output_elapsed_us += step;
+            if dma_running {
+                self.dma_elapsed_us += step;
+            }
+
+            if dma_running && self.dma_elapsed_us >= self.sample_period_us() {
+                self.dma_elapsed_us = 0.0;
+                // A masked channel stalls playback until it is unmasked
+                if let Some(sample) = fetch() {
+                    self.play_dma_sample(sample);
+                }
+            }
+            if self.output_elapsed_us >= output_period {
+                self.output_elapsed_us -= output_period;
+                if self.output.len() >= MAX_BUFFERED_SAMPLES {
+                    self.output.pop_front();
+                }
+                self.output
+                    .push_back(if self.speaker_on { self.level } else { 0 });
+            }
+        }
+    }
+
+    /// Take up to `count` buffered output samples, padding with silence
+    pub fn take_samples(&mut self, count: usize) -> Vec<i16> {
+        let mut samples: Vec<i16> = self.output.drain(..count.min(self.output.len())).collect();
+        samples.resize(count, 0);
+        samples
+    }
+}
+
+impl Default for SoundBlaster {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reset_dsp(sb: &mut SoundBlaster) {
+        sb.io_write(0x226, 1);
+        sb.io_write(0x226, 0);
+    }
+
+    #[test]
+    fn test_reset_and_version() {
+        let mut sb = SoundBlaster::new();
+        reset_dsp(&mut sb);
+        assert_eq!(sb.io_read(0x22E) & 0x80, 0x80);
+        assert_eq!(sb.io_read(0x22A), 0xAA);
+        assert_eq!(sb.io_read(0x22E) & 0x80, 0x00);
+
+        sb.io_write(0x22C, 0xE1);
+        assert_eq!(sb.io_read(0x22A), 0x02);
+        assert_eq!(sb.io_read(0x22A), 0x01);
+
+        sb.io_write(0x22C, 0xE0);
+        sb.io_write(0x22C, 0x55);
+        assert_eq!(sb.io_read(0x22A), 0xAA);
+    }
+
+    #[test]
+    fn test_single_cycle_dma_raises_irq() {
+        let mut sb = SoundBlaster::new();
+        reset_dsp(&mut sb);
+        sb.io_write(0x22C, 0xD1); // Speaker on
+        sb.io_write(0x22C, 0x40); // Time constant for ~10 kHz
+        sb.io_write(0x22C, 156);
+        sb.io_write(0x22C, 0x14); // 4-byte single-cycle transfer
+        sb.io_write(0x22C, 0x03);
+        sb.io_write(0x22C, 0x00);
+
+        let data = [0xFFu8, 0xFF, 0x00, 0x00];
+        let mut index = 0;
+        sb.advance(1000.0, || {
+            let b = data.get(index).copied();
+            index += 1;
+            b
+        });
+
+        assert_eq!(index, 4);
+        assert!(sb.irq_requested());
+        // Reading 0x22E acknowledges the IRQ line
+        sb.io_read(0x22E);
+        assert!(!sb.irq_line.get());
+
+        let samples = sb.take_samples(44);
+        assert!(samples.iter().any(|&s| s > 0x7000));
+        assert!(samples.iter().any(|&s| s < -0x7000));
+    }
+
+    #[test]
+    fn test_auto_init_dma_repeats_blocks() {
+        let mut sb = SoundBlaster::new();
+        reset_dsp(&mut sb);
+        sb.io_write(0x22C, 0x40);
+        sb.io_write(0x22C, 156); // 100us per sample
+        sb.io_write(0x22C, 0x48);
+        sb.io_write(0x22C, 0x01); // Block of 2 bytes
+        sb.io_write(0x22C, 0x00);
+        sb.io_write(0x22C, 0x1C);
+
+        let mut fetched = 0;
+        sb.advance(1000.0, || {
+            fetched += 1;
+            Some(0x80)
+        });
+        assert_eq!(fetched, 10);
+        assert!(sb.irq_requested());
+
+        // Exit auto-init: playback stops at the end of the current block
+        sb.io_write(0x22C, 0xDA);
+        fetched = 0;
+        sb.advance(1000.0, || {
+            fetched += 1;
+            Some(0x80)
+        });
+        assert_eq!(fetched, 2);
+    }
+
+    #[test]
+    fn test_speaker_off_is_silent() {
+        let mut sb = SoundBlaster::new();
+        sb.io_write(0x22C, 0x10);
+        sb.io_write(0x22C, 0xFF);
+        sb.advance(1000.0, || None);
+        assert!(sb.take_samples(44).iter().all(|&s| s == 0));
+    }
+}
@@ -0,0 +1,195 @@
+//! PC speaker
+//!
+//! The speaker is driven by PIT channel 2 ANDed with bit 1 of port 61h.
+//! Programs either load a divisor into channel 2 (mode 3) and open both
+//! bits for a tone, or hold the gate low and toggle bit 1 directly to
+//! bit-bang a waveform.
+//!
+//! Tones are synthesized analytically from the channel 2 frequency rather
+//! than by sampling the PIT output, so the speaker does not need the PIT to
+//! be clocked at audio resolution. Each output sample is the average level
+//! over its period, followed by a DC-blocking filter so a speaker held high
+//! settles back to silence.
+//!
+//! Output is produced by emulated time, but unlike the Sound Blaster it is
+//! kept close to the host: leading silence beyond a short backlog is dropped
+//! when samples are pulled, so a beep starts within a couple of frames of
+//! the code that triggered it.
+
+use crate::opl2::SAMPLE_RATE;
+use std::collections::VecDeque;
+
+/// Peak-to-peak output amplitude
+const AMPLITUDE: f64 = 16_000.0;
+
+/// DC-blocking filter coefficient (~35 Hz corner at 44.1 kHz)
+const DC_BLOCK: f64 = 0.995;
+
+/// Queued output kept beyond one pull before silence is dropped (~50 ms)
+const LATENCY_SAMPLES: usize = SAMPLE_RATE as usize / 20;
+
+/// Maximum buffered output (0.5 seconds) if the host stops pulling samples
+const MAX_BUFFERED_SAMPLES: usize = SAMPLE_RATE as usize / 2;
+
+/// Signal on the speaker line
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SpeakerInput {
+    /// Constant level (speaker off, or port 61h bit 1 toggled directly)
+    Level(bool),
+    /// Square wave from PIT channel 2 at the given frequency in Hz
+    Square(f64),
+}
+
+/// PC speaker output stage
+pub struct PcSpeaker {
+    /// Position within the square wave period (0.0..1.0, high for the first half)
+    phase: f64,
+    /// Time accumulated into the current output sample (microseconds)
+    sample_elapsed_us: f64,
+    /// Integrated level of the current output sample (microseconds high)
+    sample_high_us: f64,
+    /// Previous DC-blocker input
+    filter_input: f64,
+    /// Previous DC-blocker output
+    filter_output: f64,
+    /// Samples waiting for the host
+    output: VecDeque<i16>,
+}
+
+impl PcSpeaker {
+    /// Create a silent speaker
+    pub fn new() -> Self {
+        Self {
+            phase: 0.0,
+            sample_elapsed_us: 0.0,
+            sample_high_us: 0.0,
+            filter_input: 0.0,
+            filter_output: 0.0,
+            output: VecDeque::new(),
+        }
+    }
+
+    /// Reset to silence and drop queued output
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Advance by `us` microseconds of emulated time with `input` on the line
+    pub fn advance(&mut self, us: f64, input: SpeakerInput) {
+        let sample_us = 1_000_000.0 / SAMPLE_RATE;
+        let mut remaining = us;
+        while remaining > 0.0 {
+            let step = remaining.min(sample_us - self.sample_elapsed_us);
+            self.sample_high_us += self.high_time(step, input);
+            self.sample_elapsed_us += step;
+            remaining -= step;
+
+            if self.sample_elapsed_us >= sample_us - 1e-9 {
+                let level = self.sample_high_us / sample_us;
+                self.sample_elapsed_us = 0.0;
+                self.sample_high_us = 0.0;
+                self.emit(level);
+            }
+        }
+    }
+
+    /// Time the line spends high during the next `us` microseconds
+    fn high_time(&mut self, us: f64, input: SpeakerInput) -> f64 {
+        match input {
+            SpeakerInput::Level(high) => {
+                if high {
+                    us
+                } else {
+                    0.0
+                }
+            }
+            SpeakerInput::Square(hz) => {
+                // Integral of the square wave over whole and partial periods
+                let high_cycles = |x: f64| x.floor() * 0.5 + x.fract().min(0.5);
+                let start = self.phase;
+                let end = start + us * hz / 1_000_000.0;
+                self.phase = end.fract();
+                (high_cycles(end) - high_cycles(start)) * 1_000_000.0 / hz
+            }
+        }
+    }
+
+    /// Filter one averaged level (0.0..1.0) and queue it
+    fn emit(&mut self, level: f64) {
+        let filtered = level - self.filter_input + DC_BLOCK * self.filter_output;
+        self.filter_input = level;
+        self.filter_output = filtered;
+
+        if self.output.len() >= MAX_BUFFERED_SAMPLES {
+            self.output.pop_front();
+        }
+        let sample = (filtered * AMPLITUDE).clamp(-32768.0, 32767.0) as i16;
+        self.output.push_back(sample);
+    }
+
+    /// Take `count` samples, padding with silence if not enough are ready
+    ///
+    /// Leading silence beyond `count` plus a short backlog is discarded
+    /// first, so a tone that starts now is heard now.
+    pub fn take_samples(&mut self, count: usize) -> Vec<i16> {
+        let backlog = count + LATENCY_SAMPLES;
+        while self.output.len() > backlog && self.output.front() == Some(&0) {
+            self.output.pop_front();
+        }
+        let mut samples: Vec<i16> = self.output.drain(..count.min(self.output.len())).collect();
+        samples.resize(count, 0);
+        samples
+    }
+}
+
+impl Default for PcSpeaker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_square_wave_duration() {
+        let mut speaker = PcSpeaker::new();
+        // 100 ms of 1 kHz in small steps, like per-instruction clocking
+        for _ in 0..50_000 {
+            speaker.advance(2.0, SpeakerInput::Square(1000.0));
+        }
+        speaker.advance(100_000.0, SpeakerInput::Level(false));
+
+        let samples = speaker.take_samples(8820);
+        let loud = |s: &[i16]| s.iter().filter(|s| s.abs() > 4000).count();
+        // The tone fills the first 100 ms and stops once the release click decays
+        assert!(loud(&samples[..4410]) > 4000);
+        assert_eq!(loud(&samples[5000..]), 0);
+    }
+
+    #[test]
+    fn test_held_level_settles_to_silence() {
+        let mut speaker = PcSpeaker::new();
+        speaker.advance(200_000.0, SpeakerInput::Level(true));
+        let samples = speaker.take_samples(8820);
+        assert!(samples[0] > 10_000);
+        assert!(samples[8000..].iter().all(|s| s.abs() < 100));
+    }
+
+    #[test]
+    fn test_leading_silence_is_dropped() {
+        let mut speaker = PcSpeaker::new();
+        // A second of silence builds up, then a short click
+        speaker.advance(1_000_000.0, SpeakerInput::Level(false));
+        speaker.advance(1_000.0, SpeakerInput::Level(true));
+        speaker.advance(1_000.0, SpeakerInput::Level(false));
+
+        // The click is within the first pull plus the allowed backlog
+        let mut pulled = speaker.take_samples(735);
+        pulled.extend(speaker.take_samples(735));
+        pulled.extend(speaker.take_samples(735));
+        pulled.extend(speaker.take_samples(735));
+        assert!(pulled.iter().any(|&s| s > 10_000));
+    }
+}
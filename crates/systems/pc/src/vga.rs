@@ -0,0 +1,578 @@
+//! EGA/VGA planar graphics memory
+//!
+//! In the graphics modes the 64KB window at A0000h is backed by four 64KB
+//! bit planes.
+//!
+//! In the 16-color modes (0Dh, 0Eh, 10h and 12h) each plane holds one bit of
+//! every pixel, eight pixels per byte, and the four bits form the color that
+//! goes through the attribute controller palette. Writes go to the planes
+//! enabled in the map mask and are shaped by the graphics controller: data
+//! rotation, set/reset, a logical function (AND, OR or XOR) with the latches
+//! and the bit mask. Reads return the plane selected by the read map, or in
+//! read mode 1 a bit for each pixel matching the color compare register.
+//!
+//! The 256-color modes hold one byte per pixel:
+//! - Chained (chain-4, mode 13h as set by the BIOS): the low two address bits
+//!   pick the plane, so memory looks linear to the CPU. As on real hardware a
+//!   byte lands at its address with those bits cleared, which is why Mode X
+//!   programs clear the screen after unchaining.
+//! - Unchained (Mode X): one address covers four neighbouring pixels. Writes
+//!   go to every plane enabled in the sequencer map mask and reads come from
+//!   the plane selected by the graphics controller read map. Every read loads
+//!   the four latches, which write mode 1 stores back to copy four pixels at
+//!   once.
+//!
+//! The picture is scanned out the way the CRTC fetches it: from the start
+//! address, with the row offset and byte, word or doubleword addressing, for
+//! as many lines as the vertical display end allows. Page flipping, hardware
+//! scrolling and 320x240 layouts therefore work without special cases.
+
+use std::cell::Cell;
+
+/// Size of one bit plane in bytes
+pub const PLANE_SIZE: usize = 0x10000;
+
+/// CRTC register values of BIOS mode 13h (index, value)
+pub const MODE_13H_CRTC: [(usize, u8); 9] = [
+    (0x01, 0x4F), // Horizontal display end (80 character clocks)
+    (0x07, 0x1F), // Overflow (bit 8 of the vertical display end)
+    (0x09, 0x41), // Maximum scan line: each row scanned twice
+    (0x0C, 0x00), // Start address high
+    (0x0D, 0x00), // Start address low
+    (0x12, 0x8F), // Vertical display end (400 lines)
+    (0x13, 0x28), // Row offset (80 addresses per row)
+    (0x14, 0x40), // Underline location: doubleword addressing
+    (0x17, 0xA3), // Mode control: word mode off, byte mode off
+];
+
+/// Sequencer register values of BIOS mode 13h (index, value)
+pub const MODE_13H_SEQUENCER: [(usize, u8); 2] = [
+    (0x02, 0x0F), // Map mask: all planes
+    (0x04, 0x0E), // Memory mode: chain-4, odd/even off, extended memory
+];
+
+/// Graphics controller register values of BIOS mode 13h (index, value)
+pub const MODE_13H_GRAPHICS: [(usize, u8); 9] = [
+    (0x00, 0x00), // Set/reset
+    (0x01, 0x00), // Enable set/reset
+    (0x02, 0x00), // Color compare
+    (0x03, 0x00), // Data rotate
+    (0x04, 0x00), // Read map select
+    (0x05, 0x40), // Graphics mode: 256-color shift, write mode 0
+    (0x06, 0x05), // Miscellaneous: graphics, A0000h-AFFFFh
+    (0x07, 0x0F), // Color don't care
+    (0x08, 0xFF), // Bit mask
+];
+
+/// BIOS 16-color graphics modes: 320x200, 640x200, 640x350 and 640x480
+pub const PLANAR_16_COLOR_MODES: [u8; 4] = [0x0D, 0x0E, 0x10, 0x12];
+
+/// CRTC register values of a BIOS 16-color mode (index, value)
+pub fn planar_16_color_crtc(mode: u8) -> [(usize, u8); 9] {
+    // Horizontal display end, overflow, maximum scan line, vertical display
+    // end and row offset
+    let (display_end_h, overflow, max_scan, display_end_v, offset) = match mode {
+        // 320x200 and 640x200: 400 lines, each scanned twice
+        0x0D => (0x27, 0x1F, 0xC0, 0x8F, 0x14),
+        0x0E => (0x4F, 0x1F, 0xC0, 0x8F, 0x28),
+        // 640x350
+        0x10 => (0x4F, 0x1F, 0x40, 0x5D, 0x28),
+        // 640x480
+        _ => (0x4F, 0x3E, 0x40, 0xDF, 0x28),
+    };
+    [
+        (0x01, display_end_h),
+        (0x07, overflow),
+        (0x09, max_scan),
+        (0x0C, 0x00),
+        (0x0D, 0x00),
+        (0x12, display_end_v),
+        (0x13, offset),
+        (0x14, 0x00), // Underline location: byte addressing
+        (0x17, 0xE3), // Mode control: byte mode
+    ]
+}
+
+/// Sequencer register values of the BIOS 16-color modes (index, value)
+pub const PLANAR_16_COLOR_SEQUENCER: [(usize, u8); 2] = [
+    (0x02, 0x0F), // Map mask: all planes
+    (0x04, 0x06), // Memory mode: odd/even off, extended memory
+];
+
+/// Graphics controller register values of the BIOS 16-color modes (index, value)
+pub const PLANAR_16_COLOR_GRAPHICS: [(usize, u8); 9] = [
+    (0x00, 0x00), // Set/reset
+    (0x01, 0x00), // Enable set/reset
+    (0x02, 0x00), // Color compare
+    (0x03, 0x00), // Data rotate
+    (0x04, 0x00), // Read map select
+    (0x05, 0x00), // Graphics mode: write mode 0, read mode 0
+    (0x06, 0x05), // Miscellaneous: graphics, A0000h-AFFFFh
+    (0x07, 0x0F), // Color don't care
+    (0x08, 0xFF), // Bit mask
+];
+
+/// Attribute controller mode control, overscan, color plane enable,
+/// horizontal panning and color select of the BIOS 16-color modes
+/// (index, value); the 16 palette registers get the default EGA palette
+pub const PLANAR_16_COLOR_ATTRIBUTE: [(usize, u8); 5] = [
+    (0x10, 0x01), // Mode control: graphics
+    (0x11, 0x00), // Overscan color
+    (0x12, 0x0F), // Color plane enable: all planes
+    (0x13, 0x00), // Horizontal pixel panning
+    (0x14, 0x00), // Color select
+];
+
+/// Graphics controller mode bit: 256-color shift mode
+pub const GRAPHICS_MODE_256: u8 = 0x40;
+/// Graphics controller mode bit: read mode 1 (color compare)
+const GRAPHICS_MODE_READ_COMPARE: u8 = 0x08;
+/// Attribute mode control bit: palette bits 5-4 come from the color select
+const ATTRIBUTE_MODE_P54S: u8 = 0x80;
+
+/// Sequencer memory mode bit: chain-4 addressing
+const MEMORY_MODE_CHAIN4: u8 = 0x08;
+/// CRTC underline location bit: doubleword addressing
+const UNDERLINE_DOUBLEWORD: u8 = 0x40;
+/// CRTC mode control bit: byte addressing (clear for word addressing)
+const MODE_CONTROL_BYTE: u8 = 0x40;
+/// CRTC maximum scan line bit: scan every line twice
+const MAX_SCAN_LINE_DOUBLE: u8 = 0x80;
+
+/// Register state that controls a CPU access to video memory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VgaAccess {
+    /// Sequencer memory mode (chain-4 bit)
+    pub memory_mode: u8,
+    /// Sequencer map mask (planes written)
+    pub map_mask: u8,
+    /// Graphics controller read map select
+    pub read_map: u8,
+    /// Graphics controller mode (write mode in bits 0-1, read mode in bit 3)
+    pub mode: u8,
+    /// Graphics controller data rotate (count in bits 0-2, logical function
+    /// in bits 3-4)
+    pub data_rotate: u8,
+    /// Graphics controller color compare (read mode 1)
+    pub color_compare: u8,
+    /// Graphics controller color don't care (read mode 1)
+    pub color_dont_care: u8,
+    /// Graphics controller set/reset value
+    pub set_reset: u8,
+    /// Graphics controller enable set/reset
+    pub enable_set_reset: u8,
+    /// Graphics controller bit mask
+    pub bit_mask: u8,
+}
+
+/// Layout of the displayed picture, from the CRTC registers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VgaLayout {
+    /// Visible width in pixels
+    pub width: usize,
+    /// Visible height in lines
+    pub height: usize,
+    /// Address of the first pixel
+    start: usize,
+    /// Address counter increment per row
+    row_offset: usize,
+    /// Address counter to plane offset multiplier (1, 2 or 4)
+    scale: usize,
+}
+
+impl VgaLayout {
+    /// Decode the layout from the 25 CRTC registers and the graphics
+    /// controller mode register
+    pub fn from_crtc(crtc: &[u8; 25], graphics_mode: u8) -> Self {
+        let overflow = crtc[0x07];
+        let display_end = crtc[0x12] as usize
+            | ((overflow as usize >> 1) & 1) << 8
+            | ((overflow as usize >> 6) & 1) << 9;
+        let mut scan_lines = (crtc[0x09] & 0x1F) as usize + 1;
+        if crtc[0x09] & MAX_SCAN_LINE_DOUBLE != 0 {
+            scan_lines *= 2;
+        }
+        let scale = if crtc[0x14] & UNDERLINE_DOUBLEWORD != 0 {
+            4
+        } else if crtc[0x17] & MODE_CONTROL_BYTE != 0 {
+            1
+        } else {
+            2
+        };
+        // Four 8-bit pixels or eight 4-bit pixels per character clock
+        let pixels_per_clock = if graphics_mode & GRAPHICS_MODE_256 != 0 {
+            4
+        } else {
+            8
+        };
+        Self {
+            width: (crtc[0x01] as usize + 1) * pixels_per_clock,
+            height: (display_end + 1) / scan_lines,
+            start: (crtc[0x0C] as usize) << 8 | crtc[0x0D] as usize,
+            row_offset: crtc[0x13] as usize * 2,
+            scale,
+        }
+    }
+}
+
+/// Planar memory of the EGA/VGA graphics modes
+pub struct VgaMemory {
+    /// Planes 0-3, one after another
+    planes: Vec<u8>,
+    /// Latches loaded by the last read, one per plane
+    latches: Cell<[u8; 4]>,
+}
+
+impl VgaMemory {
+    /// Create cleared video memory
+    pub fn new() -> Self {
+        Self {
+            planes: vec![0; PLANE_SIZE * 4],
+            latches: Cell::new([0; 4]),
+        }
+    }
+
+    /// Clear all planes and latches
+    pub fn clear(&mut self) {
+        self.planes.fill(0);
+        self.latches.set([0; 4]);
+    }
+
+    /// Byte at `offset` in `plane`
+    fn plane_byte(&self, plane: usize, offset: usize) -> u8 {
+        self.planes[plane * PLANE_SIZE + (offset & (PLANE_SIZE - 1))]
+    }
+
+    /// Load the latches from `offset` in all four planes
+    fn load_latches(&self, offset: usize) {
+        self.latches
+            .set(std::array::from_fn(|plane| self.plane_byte(plane, offset)));
+    }
+
+    /// CPU read at `addr` within the A0000h window
+    pub fn read(&self, addr: usize, access: &VgaAccess) -> u8 {
+        if access.memory_mode & MEMORY_MODE_CHAIN4 != 0 {
+            let offset = addr & !3;
+            self.load_latches(offset);
+            self.plane_byte(addr & 3, offset)
+        } else if access.mode & GRAPHICS_MODE_READ_COMPARE != 0 {
+            // A bit is set for each pixel whose color matches in every
+            // plane that is not "don't care"
+            self.load_latches(addr);
+            let latches = self.latches.get();
+            let mut differ = 0;
+            for (plane, &latch) in latches.iter().enumerate() {
+                if access.color_dont_care & (1 << plane) != 0 {
+                    differ |= latch ^ expand(access.color_compare & (1 << plane) != 0);
+                }
+            }
+            !differ
+        } else {
+            self.load_latches(addr);
+            self.plane_byte((access.read_map & 3) as usize, addr)
+        }
+    }
+
+    /// CPU write at `addr` within the A0000h window
+    pub fn write(&mut self, addr: usize, val: u8, access: &VgaAccess) {
+        let (planes, offset) = if access.memory_mode & MEMORY_MODE_CHAIN4 != 0 {
+            (access.map_mask & (1 << (addr & 3)), addr & !3)
+        } else {
+            (access.map_mask, addr)
+        };
+        let offset = offset & (PLANE_SIZE - 1);
+        let latches = self.latches.get();
+        let rotated = val.rotate_right((access.data_rotate & 7) as u32);
+        for (plane, &latch) in latches.iter().enumerate() {
+            if planes & (1 << plane) == 0 {
+                continue;
+            }
+            let set_reset = expand(access.set_reset & (1 << plane) != 0);
+            let (data, mask) = match access.mode & 3 {
+                0 if access.enable_set_reset & (1 << plane) != 0 => (set_reset, access.bit_mask),
+                0 => (rotated, access.bit_mask),
+                // Copy the latches loaded by the last read
+                1 => {
+                    self.planes[plane * PLANE_SIZE + offset] = latch;
+                    continue;
+                }
+                2 => (expand(val & (1 << plane) != 0), access.bit_mask),
+                _ => (set_reset, access.bit_mask & rotated),
+            };
+            let data = match (access.data_rotate >> 3) & 3 {
+                1 => data & latch,
+                2 => data | latch,
+                3 => data ^ latch,
+                _ => data,
+            };
+            self.planes[plane * PLANE_SIZE + offset] = (data & mask) | (latch & !mask);
+        }
+    }
+
+    /// Palette indices of the displayed 256-color picture, row by row
+    pub fn scanout(&self, layout: &VgaLayout) -> Vec<u8> {
+        let mut pixels = Vec::with_capacity(layout.width * layout.height);
+        for y in 0..layout.height {
+            let row = layout.start + y * layout.row_offset;
+            for x in 0..layout.width {
+                let offset = (row + x / 4) * layout.scale;
+                pixels.push(self.plane_byte(x & 3, offset));
+            }
+        }
+        pixels
+    }
+
+    /// 4-bit colors of the displayed 16-color picture, row by row
+    ///
+    /// Bit n of each color comes from plane n.
+    pub fn scanout_planar(&self, layout: &VgaLayout) -> Vec<u8> {
+        let mut pixels = Vec::with_capacity(layout.width * layout.height);
+        for y in 0..layout.height {
+            let row = layout.start + y * layout.row_offset;
+            for x in 0..layout.width {
+                let offset = (row + x / 8) * layout.scale;
+                let bit = 7 - (x & 7);
+                let color = (0..4).fold(0, |color, plane| {
+                    color | ((self.plane_byte(plane, offset) >> bit) & 1) << plane
+                });
+                pixels.push(color);
+            }
+        }
+        pixels
+    }
+}
+
+impl Default for VgaMemory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Expand one bit to a whole byte
+fn expand(bit: bool) -> u8 {
+    if bit {
+        0xFF
+    } else {
+        0x00
+    }
+}
+
+/// Map 4-bit colors through the attribute controller
+///
+/// With `dac` (VGA) the result is a DAC index, with the color select
+/// register supplying bits 4-7; without it (EGA) it is the 6-bit color of
+/// the palette register.
+pub fn apply_attributes(pixels: &mut [u8], attribute: &[u8; 21], dac: bool) {
+    let mode = attribute[0x10];
+    let plane_enable = attribute[0x12] & 0x0F;
+    let color_select = attribute[0x14];
+    let lookup: [u8; 16] = std::array::from_fn(|color| {
+        let value = attribute[color & plane_enable as usize] & 0x3F;
+        if !dac {
+            value
+        } else if mode & ATTRIBUTE_MODE_P54S != 0 {
+            (value & 0x0F) | (color_select & 0x0F) << 4
+        } else {
+            value | (color_select & 0x0C) << 4
+        }
+    });
+    for pixel in pixels {
+        *pixel = lookup[(*pixel & 0x0F) as usize];
+    }
+}
+
+/// DAC value (6-bit red, green, blue) of an EGA color
+///
+/// EGA colors are `rgbRGB`: the upper-case bits give two thirds of the full
+/// intensity and the lower-case bits one third. The BIOS loads these into
+/// the first 64 DAC registers in the 16-color modes.
+pub fn ega_dac_color(color: u8) -> [u8; 3] {
+    let component = |primary: u8, secondary: u8| {
+        ((color >> primary) & 1) * 42 + ((color >> secondary) & 1) * 21
+    };
+    [component(2, 5), component(1, 4), component(0, 3)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mode_13h_crtc() -> [u8; 25] {
+        let mut crtc = [0u8; 25];
+        for (index, value) in MODE_13H_CRTC {
+            crtc[index] = value;
+        }
+        crtc
+    }
+
+    fn access(memory_mode: u8, map_mask: u8) -> VgaAccess {
+        VgaAccess {
+            memory_mode,
+            map_mask,
+            read_map: 0,
+            mode: 0,
+            data_rotate: 0,
+            color_compare: 0,
+            color_dont_care: 0,
+            set_reset: 0,
+            enable_set_reset: 0,
+            bit_mask: 0xFF,
+        }
+    }
+
+    #[test]
+    fn test_mode_13h_layout() {
+        let layout = VgaLayout::from_crtc(&mode_13h_crtc(), GRAPHICS_MODE_256);
+        assert_eq!((layout.width, layout.height), (320, 200));
+
+        // Mode X: byte addressing and 480 lines scanned twice
+        let mut crtc = mode_13h_crtc();
+        crtc[0x14] = 0x00;
+        crtc[0x17] = 0xE3;
+        crtc[0x12] = 0xDF;
+        crtc[0x07] = 0x3E;
+        let layout = VgaLayout::from_crtc(&crtc, GRAPHICS_MODE_256);
+        assert_eq!((layout.width, layout.height), (320, 240));
+    }
+
+    #[test]
+    fn test_chained_memory_is_linear() {
+        let mut vga = VgaMemory::new();
+        let chained = access(MEMORY_MODE_CHAIN4 | 0x06, 0x0F);
+        // Pixel (5, 1) of mode 13h
+        vga.write(320 + 5, 0x2A, &chained);
+        assert_eq!(vga.read(320 + 5, &chained), 0x2A);
+        // Plane 1, at the address with the plane bits cleared
+        assert_eq!(vga.plane_byte(1, 320 + 4), 0x2A);
+
+        let pixels = vga.scanout(&VgaLayout::from_crtc(&mode_13h_crtc(), GRAPHICS_MODE_256));
+        assert_eq!(pixels.len(), 64000);
+        assert_eq!(pixels[320 + 5], 0x2A);
+        assert_eq!(pixels.iter().filter(|&&p| p != 0).count(), 1);
+    }
+
+    #[test]
+    fn test_unchained_plane_writes() {
+        let mut vga = VgaMemory::new();
+        let mut crtc = mode_13h_crtc();
+        crtc[0x14] = 0x00;
+        crtc[0x17] = 0xE3;
+        let layout = VgaLayout::from_crtc(&crtc, GRAPHICS_MODE_256);
+
+        // Planes 0 and 2 at address 81: pixels 4 and 6 of line 1
+        vga.write(81, 0x11, &access(0x06, 0x05));
+        let pixels = vga.scanout(&layout);
+        assert_eq!(&pixels[320 + 4..320 + 8], &[0x11, 0x00, 0x11, 0x00]);
+
+        // Reads come from the selected plane
+        let mut read = access(0x06, 0x0F);
+        read.read_map = 2;
+        assert_eq!(vga.read(81, &read), 0x11);
+        read.read_map = 1;
+        assert_eq!(vga.read(81, &read), 0x00);
+    }
+
+    #[test]
+    fn test_latch_copy_and_page_flip() {
+        let mut vga = VgaMemory::new();
+        vga.write(0, 0x01, &access(0x06, 0x01));
+        vga.write(0, 0x02, &access(0x06, 0x02));
+        vga.write(0, 0x03, &access(0x06, 0x04));
+        vga.write(0, 0x04, &access(0x06, 0x08));
+
+        // Copy four pixels from address 0 to the second page with write mode 1
+        let mut copy = access(0x06, 0x0F);
+        vga.read(0, &copy);
+        copy.mode = 1;
+        vga.write(16000, 0xEE, &copy);
+
+        let mut crtc = mode_13h_crtc();
+        crtc[0x14] = 0x00;
+        crtc[0x17] = 0xE3;
+        crtc[0x0C] = (16000 >> 8) as u8;
+        crtc[0x0D] = (16000 & 0xFF) as u8;
+        let pixels = vga.scanout(&VgaLayout::from_crtc(&crtc, GRAPHICS_MODE_256));
+        assert_eq!(&pixels[..4], &[0x01, 0x02, 0x03, 0x04]);
+    }
+
+    fn planar_crtc(mode: u8) -> [u8; 25] {
+        let mut crtc = [0u8; 25];
+        for (index, value) in planar_16_color_crtc(mode) {
+            crtc[index] = value;
+        }
+        crtc
+    }
+
+    #[test]
+    fn test_planar_16_color_layouts() {
+        for (mode, size) in [
+            (0x0D, (320, 200)),
+            (0x0E, (640, 200)),
+            (0x10, (640, 350)),
+            (0x12, (640, 480)),
+        ] {
+            let layout = VgaLayout::from_crtc(&planar_crtc(mode), 0);
+            assert_eq!((layout.width, layout.height), size, "mode {:02X}h", mode);
+        }
+    }
+
+    #[test]
+    fn test_planar_write_modes() {
+        let mut vga = VgaMemory::new();
+        let layout = VgaLayout::from_crtc(&planar_crtc(0x12), 0);
+
+        // Write mode 2: color 0Ch to the leftmost pixel of line 1
+        let mut write = access(0x06, 0x0F);
+        write.mode = 2;
+        write.bit_mask = 0x80;
+        vga.read(80, &write);
+        vga.write(80, 0x0C, &write);
+        let pixels = vga.scanout_planar(&layout);
+        assert_eq!(&pixels[640..642], &[0x0C, 0x00]);
+
+        // Set/reset fills the next pixel with color 3, keeping the first
+        write.mode = 0;
+        write.set_reset = 0x03;
+        write.enable_set_reset = 0x0F;
+        write.bit_mask = 0x40;
+        vga.read(80, &write);
+        vga.write(80, 0xFF, &write);
+        let pixels = vga.scanout_planar(&layout);
+        assert_eq!(&pixels[640..643], &[0x0C, 0x03, 0x00]);
+
+        // XOR with the latches inverts three pixels
+        write.enable_set_reset = 0;
+        write.data_rotate = 0x18;
+        write.bit_mask = 0xFF;
+        vga.read(80, &write);
+        vga.write(80, 0xE0, &write);
+        let pixels = vga.scanout_planar(&layout);
+        assert_eq!(&pixels[640..643], &[0x03, 0x0C, 0x0F]);
+
+        // Read mode 1 finds the pixels of color 0Fh
+        let mut compare = access(0x06, 0x0F);
+        compare.mode = GRAPHICS_MODE_READ_COMPARE;
+        compare.color_compare = 0x0F;
+        compare.color_dont_care = 0x0F;
+        assert_eq!(vga.read(80, &compare), 0x20);
+    }
+
+    #[test]
+    fn test_attribute_palette() {
+        let mut attribute = [0u8; 21];
+        attribute[0x03] = 0x3B;
+        attribute[0x12] = 0x0F;
+        let mut pixels = [3, 0];
+        apply_attributes(&mut pixels, &attribute, false);
+        assert_eq!(pixels, [0x3B, 0x00]);
+
+        // VGA: color select bits 7-6 pick a group of 64 DAC registers
+        attribute[0x14] = 0x04;
+        let mut pixels = [3];
+        apply_attributes(&mut pixels, &attribute, true);
+        assert_eq!(pixels, [0x7B]);
+
+        assert_eq!(ega_dac_color(0x14), [42, 21, 0]);
+        assert_eq!(ega_dac_color(0x3F), [63, 63, 63]);
+    }
+}
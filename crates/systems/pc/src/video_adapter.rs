@@ -72,6 +72,12 @@ pub trait VideoAdapter: Send {
     /// * `pixels` - Output pixel buffer (ARGB8888 format)
     fn render(&self, vram: &[u8], pixels: &mut [u32]);
 
+    /// Load the 256-color DAC palette (6-bit red, green, blue per color)
+    ///
+    /// Called before rendering a VGA 256-color mode. Adapters without
+    /// 256-color modes ignore it.
+    fn set_dac_palette(&mut self, _dac: &[u8; 768]) {}
+
     /// Reset the adapter to initial state
     #[allow(dead_code)] // Used by implementations, kept for API completeness
     fn reset(&mut self);
@@ -9,6 +9,15 @@
 //! - **Graphics Mode 4**: 320x200, 4 colors (2 bits per pixel)
 //! - **Graphics Mode 6**: 640x200, 2 colors (1 bit per pixel)
 //!
+//! # Composite Artifact Colors
+//!
+//! On a composite (NTSC) monitor the 640x200 mode does not look black and
+//! white: each pixel is a quarter of a color carrier cycle, so every group
+//! of 4 pixels is seen as one of 16 artifact colors. Games such as
+//! Ultima II and California Games draw their 160x200 16-color screens this
+//! way. [`CgaGraphicsAdapter::set_composite`] switches between the RGB and
+//! composite looks at any time.
+//!
 //! # Mode Switching
 //!
 //! The adapter detects the current mode from the mode control register in VRAM.
@@ -32,6 +41,27 @@ pub enum CgaMode {
     Graphics640x200,
 }
 
+/// Artifact colors seen on a composite monitor in 640x200 mode, indexed by
+/// a group of 4 pixels with the leftmost pixel in bit 3
+const COMPOSITE_ARTIFACT_COLORS: [u32; 16] = [
+    0xFF000000, // 0000 Black
+    0xFF006E31, // 0001 Dark green
+    0xFF3109FF, // 0010 Dark blue
+    0xFF008AFF, // 0011 Medium blue
+    0xFFA70031, // 0100 Red
+    0xFF767676, // 0101 Gray
+    0xFFEC11FF, // 0110 Magenta
+    0xFFBB92FF, // 0111 Light purple
+    0xFF315A00, // 1000 Olive
+    0xFF00DB00, // 1001 Green
+    0xFF767676, // 1010 Gray
+    0xFF45F7BB, // 1011 Aquamarine
+    0xFFEC6300, // 1100 Orange
+    0xFFBBE400, // 1101 Yellow-green
+    0xFFFF7FBB, // 1110 Pink
+    0xFFFFFFFF, // 1111 White
+];
+
 /// CGA graphics adapter with mode switching support
 pub struct CgaGraphicsAdapter {
     /// Framebuffer
@@ -44,6 +74,8 @@ pub struct CgaGraphicsAdapter {
     /// Character cell size
     char_width: usize,
     char_height: usize,
+    /// Render 640x200 graphics as composite artifact colors
+    composite: bool,
 }
 
 impl CgaGraphicsAdapter {
@@ -56,9 +88,21 @@ impl CgaGraphicsAdapter {
             text_height: 25,
             char_width: 8,
             char_height: 16,
+            composite: false,
         }
     }
 
+    /// Show 640x200 graphics as seen on a composite monitor (artifact
+    /// colors) instead of an RGB monitor (black and white)
+    pub fn set_composite(&mut self, composite: bool) {
+        self.composite = composite;
+    }
+
+    /// Whether 640x200 graphics are shown with composite artifact colors
+    pub fn is_composite(&self) -> bool {
+        self.composite
+    }
+
     /// Set the video mode
     pub fn set_mode(&mut self, mode: CgaMode) {
         if self.mode != mode {
@@ -226,6 +270,19 @@ impl CgaGraphicsAdapter {
 
                 let byte = vram[offset];
 
+                if self.composite {
+                    // Each nibble is one color carrier cycle, seen as a
+                    // single artifact color 4 pixels wide
+                    for (half, nibble) in [byte >> 4, byte & 0x0F].into_iter().enumerate() {
+                        let color = COMPOSITE_ARTIFACT_COLORS[nibble as usize];
+                        let start = y * WIDTH + x * 8 + half * 4;
+                        if let Some(group) = pixels.get_mut(start..start + 4) {
+                            group.fill(color);
+                        }
+                    }
+                    continue;
+                }
+
                 // Each byte contains 8 pixels (1 bit each)
                 for pixel in 0..8 {
                     let pixel_x = x * 8 + pixel;
@@ -396,6 +453,34 @@ mod tests {
         assert!(white_pixels > 0);
     }
 
+    #[test]
+    fn test_graphics_640x200_composite() {
+        let mut adapter = CgaGraphicsAdapter::new();
+        adapter.set_mode(CgaMode::Graphics640x200);
+        assert!(!adapter.is_composite());
+
+        let mut vram = vec![0u8; 0x4000];
+        vram[0] = 0x4F; // Red, then white
+        vram[0x2000] = 0x9C; // Odd scanline: green, then orange
+
+        adapter.set_composite(true);
+        let mut pixels = vec![0u32; 640 * 200];
+        adapter.render(&vram, &mut pixels);
+
+        assert!(pixels[0..4].iter().all(|&p| p == 0xFFA70031));
+        assert!(pixels[4..8].iter().all(|&p| p == 0xFFFFFFFF));
+        assert!(pixels[640..644].iter().all(|&p| p == 0xFF00DB00));
+        assert!(pixels[644..648].iter().all(|&p| p == 0xFFEC6300));
+        assert_eq!(pixels[8], 0xFF000000);
+
+        // Switching back shows the same memory in black and white
+        adapter.set_composite(false);
+        adapter.render(&vram, &mut pixels);
+        assert_eq!(pixels[0], 0xFF000000);
+        assert_eq!(pixels[1], 0xFFFFFFFF);
+        assert_eq!(pixels[640], 0xFFFFFFFF);
+    }
+
     #[test]
     fn test_adapter_reset() {
         let mut adapter = CgaGraphicsAdapter::new();
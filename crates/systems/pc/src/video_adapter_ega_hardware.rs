@@ -102,6 +102,7 @@ impl HardwareEgaAdapter {
         match self.mode {
             EgaMode::Text80x25 => (640, 350),
             EgaMode::Graphics640x350 => (640, 350),
+            EgaMode::Graphics640x200 => (640, 200),
             EgaMode::Graphics320x200 => (320, 200),
         }
     }
@@ -123,6 +124,7 @@ impl VideoAdapter for HardwareEgaAdapter {
     fn init(&mut self, width: usize, height: usize) {
         self.mode = match (width, height) {
             (640, 350) => EgaMode::Graphics640x350,
+            (640, 200) => EgaMode::Graphics640x200,
             (320, 200) => EgaMode::Graphics320x200,
             _ => EgaMode::Text80x25,
         };
@@ -156,14 +158,11 @@ impl VideoAdapter for HardwareEgaAdapter {
         // Real OpenGL implementation would:
         //
         // **For Graphics Modes:**
-        // 1. Upload 4 plane textures to GPU (R, G, B, A channels or separate textures)
-        // 2. Bind planar-to-packed shader
-        // 3. Render fullscreen quad that:
-        //    - Samples all 4 planes
-        //    - Combines into 4-bit color index
-        //    - Looks up color in palette texture/uniform
-        //    - Outputs final ARGB color
-        // 4. Read back framebuffer to pixels array
+        // 1. Upload the 6-bit color of each pixel as a texture
+        // 2. Render fullscreen quad that:
+        //    - Samples the color index
+        //    - Expands the rgbRGB bits to ARGB
+        // 3. Read back framebuffer to pixels array
         //
         // **For Text Mode:**
         // 1. Upload VRAM (char codes + attributes) as texture
@@ -7,11 +7,16 @@
 //!
 //! - Text modes: 80x25 characters at 640x350 pixels
 //! - Graphics modes:
-//!   - 640x350 16-color (high resolution)
-//!   - 320x200 16-color (medium resolution, CGA compatible)
-//! - 64-color palette (6-bit: 2 bits each for R, G, B)
+//!   - 640x350 16-color (high resolution, mode 10h)
+//!   - 640x200 16-color (mode 0Eh)
+//!   - 320x200 16-color (medium resolution, mode 0Dh)
+//! - 64-color palette (6-bit `rgbRGB`: two intensity bits each for R, G, B)
 //! - 16 colors can be selected from the 64-color palette at a time
 //! - Planar memory organization (4 bit planes)
+//!
+//! The bit planes and the attribute controller palette live on the bus
+//! (`crate::vga`); in graphics modes this adapter is handed the 6-bit color
+//! of every pixel.
 
 use super::font;
 use super::video_adapter::VideoAdapter;
@@ -25,6 +30,8 @@ pub enum EgaMode {
     Text80x25,
     /// Graphics mode: 640x350, 16 colors
     Graphics640x350,
+    /// Graphics mode: 640x200, 16 colors
+    Graphics640x200,
     /// Graphics mode: 320x200, 16 colors (CGA compatible)
     Graphics320x200,
 }
@@ -42,12 +49,15 @@ pub struct EgaColor {
 
 impl EgaColor {
     /// Create from 6-bit palette index
+    ///
+    /// The index is `rgbRGB`: bits 2-0 are the high intensity bits of red,
+    /// green and blue, bits 5-3 the low intensity bits.
     pub fn from_palette_index(index: u8) -> Self {
-        let index = index & 0x3F; // 6-bit palette
+        let component = |high: u8, low: u8| ((index >> high) & 1) << 1 | ((index >> low) & 1);
         Self {
-            r: (index >> 4) & 0x03,
-            g: (index >> 2) & 0x03,
-            b: index & 0x03,
+            r: component(2, 5),
+            g: component(1, 4),
+            b: component(0, 3),
         }
     }
 
@@ -130,6 +140,7 @@ impl SoftwareEgaAdapter {
         match self.mode {
             EgaMode::Text80x25 => (640, 350),
             EgaMode::Graphics640x350 => (640, 350),
+            EgaMode::Graphics640x200 => (640, 200),
             EgaMode::Graphics320x200 => (320, 200),
         }
     }
@@ -217,70 +228,17 @@ impl SoftwareEgaAdapter {
         }
     }
 
-    /// Render graphics mode 640x350 (planar, 16 colors)
-    fn render_graphics_640x350(&self, vram: &[u8], pixels: &mut [u32]) {
-        const WIDTH: usize = 640;
-        const HEIGHT: usize = 350;
-
-        // EGA uses planar memory (4 bit planes)
-        // Each plane is 28,000 bytes (640 * 350 / 8)
-        const PLANE_SIZE: usize = 28000;
+    /// Render a 16-color graphics mode
+    ///
+    /// `vram` holds the 6-bit color of each pixel, row by row, as produced
+    /// by the attribute controller from the bit planes.
+    fn render_graphics(&self, vram: &[u8], pixels: &mut [u32]) {
+        let (width, height) = self.get_mode_resolution();
 
         pixels.fill(0xFF000000);
 
-        for y in 0..HEIGHT {
-            for x in 0..WIDTH {
-                let byte_offset = y * (WIDTH / 8) + (x / 8);
-                let bit_offset = 7 - (x % 8);
-
-                // Read from all 4 planes to get 4-bit color
-                let mut color = 0u8;
-                for plane in 0..4 {
-                    let plane_offset = plane * PLANE_SIZE + byte_offset;
-                    if plane_offset < vram.len() {
-                        let bit = (vram[plane_offset] >> bit_offset) & 1;
-                        color |= bit << plane;
-                    }
-                }
-
-                let pixel_idx = y * WIDTH + x;
-                if pixel_idx < pixels.len() {
-                    pixels[pixel_idx] = self.get_palette_color(color);
-                }
-            }
-        }
-    }
-
-    /// Render graphics mode 320x200 (planar, 16 colors)
-    fn render_graphics_320x200(&self, vram: &[u8], pixels: &mut [u32]) {
-        const WIDTH: usize = 320;
-        const HEIGHT: usize = 200;
-
-        // Each plane is 8,000 bytes (320 * 200 / 8)
-        const PLANE_SIZE: usize = 8000;
-
-        pixels.fill(0xFF000000);
-
-        for y in 0..HEIGHT {
-            for x in 0..WIDTH {
-                let byte_offset = y * (WIDTH / 8) + (x / 8);
-                let bit_offset = 7 - (x % 8);
-
-                // Read from all 4 planes to get 4-bit color
-                let mut color = 0u8;
-                for plane in 0..4 {
-                    let plane_offset = plane * PLANE_SIZE + byte_offset;
-                    if plane_offset < vram.len() {
-                        let bit = (vram[plane_offset] >> bit_offset) & 1;
-                        color |= bit << plane;
-                    }
-                }
-
-                let pixel_idx = y * WIDTH + x;
-                if pixel_idx < pixels.len() {
-                    pixels[pixel_idx] = self.get_palette_color(color);
-                }
-            }
+        for (pixel, &color) in pixels.iter_mut().zip(vram).take(width * height) {
+            *pixel = EgaColor::from_palette_index(color).to_argb();
         }
     }
 }
@@ -296,6 +254,7 @@ impl VideoAdapter for SoftwareEgaAdapter {
         // Detect mode based on resolution
         self.mode = match (width, height) {
             (640, 350) => EgaMode::Graphics640x350,
+            (640, 200) => EgaMode::Graphics640x200,
             (320, 200) => EgaMode::Graphics320x200,
             _ => EgaMode::Text80x25, // Default to text mode
         };
@@ -323,8 +282,9 @@ impl VideoAdapter for SoftwareEgaAdapter {
     fn render(&self, vram: &[u8], pixels: &mut [u32]) {
         match self.mode {
             EgaMode::Text80x25 => self.render_text_mode(vram, pixels),
-            EgaMode::Graphics640x350 => self.render_graphics_640x350(vram, pixels),
-            EgaMode::Graphics320x200 => self.render_graphics_320x200(vram, pixels),
+            EgaMode::Graphics640x350 | EgaMode::Graphics640x200 | EgaMode::Graphics320x200 => {
+                self.render_graphics(vram, pixels)
+            }
         }
     }
 
@@ -354,6 +314,11 @@ mod tests {
 
         let color = EgaColor::from_palette_index(0x3F);
         assert_eq!(color.to_argb(), 0xFFFFFFFF); // White
+
+        // Default palette entries: brown, dark gray and light blue
+        assert_eq!(EgaColor::from_palette_index(0x14).to_argb(), 0xFFAA5500);
+        assert_eq!(EgaColor::from_palette_index(0x38).to_argb(), 0xFF555555);
+        assert_eq!(EgaColor::from_palette_index(0x39).to_argb(), 0xFF5555FF);
     }
 
     #[test]
@@ -379,6 +344,9 @@ mod tests {
         assert_eq!(adapter.get_mode(), EgaMode::Graphics320x200);
         assert_eq!(adapter.fb_width(), 320);
         assert_eq!(adapter.fb_height(), 200);
+
+        adapter.init(640, 200);
+        assert_eq!(adapter.get_mode(), EgaMode::Graphics640x200);
     }
 
     #[test]
@@ -415,20 +383,18 @@ mod tests {
         let mut adapter = SoftwareEgaAdapter::new();
         adapter.set_mode(EgaMode::Graphics640x350);
 
-        // Create test pattern in VRAM (4 planes)
-        let mut vram = vec![0u8; 112000]; // 4 planes * 28000 bytes
-
-        // Fill first plane with pattern
-        for byte in vram.iter_mut().take(100) {
-            *byte = 0xFF;
-        }
+        // One 6-bit color per pixel
+        let mut vram = vec![0u8; 640 * 350];
+        vram[..100].fill(0x3F);
+        vram[640] = 0x14;
 
         let mut pixels = vec![0u32; 640 * 350];
         adapter.render(&vram, &mut pixels);
 
-        // Check that some pixels were rendered
         let non_black = pixels.iter().filter(|&&p| p != 0xFF000000).count();
-        assert!(non_black > 0);
+        assert_eq!(non_black, 101);
+        assert_eq!(pixels[0], 0xFFFFFFFF);
+        assert_eq!(pixels[640], 0xFFAA5500);
     }
 
     #[test]
@@ -436,20 +402,15 @@ mod tests {
         let mut adapter = SoftwareEgaAdapter::new();
         adapter.set_mode(EgaMode::Graphics320x200);
 
-        // Create test pattern in VRAM (4 planes)
-        let mut vram = vec![0u8; 32000]; // 4 planes * 8000 bytes
-
-        // Fill with pattern
-        for byte in vram.iter_mut().take(100) {
-            *byte = 0xAA;
-        }
+        let mut vram = vec![0u8; 320 * 200];
+        vram[..100].fill(0x01);
 
         let mut pixels = vec![0u32; 320 * 200];
         adapter.render(&vram, &mut pixels);
 
-        // Check that pixels were rendered
-        let non_black = pixels.iter().filter(|&&p| p != 0xFF000000).count();
-        assert!(non_black > 0);
+        // Blue
+        assert!(pixels[..100].iter().all(|&p| p == 0xFF0000AA));
+        assert_eq!(pixels[100], 0xFF000000);
     }
 
     #[test]
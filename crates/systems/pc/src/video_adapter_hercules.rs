@@ -0,0 +1,298 @@
+//! Software Hercules Graphics Card - MDA text and 720x348 monochrome graphics
+//!
+//! This module implements the `VideoAdapter` trait for the Hercules Graphics
+//! Card, a superset of IBM's Monochrome Display Adapter (MDA).
+//!
+//! # Hercules Specifications
+//!
+//! - Text mode: 80x25 characters in 9x14 cells (720x350), text buffer at B0000h
+//! - Graphics mode: 720x348, 1 bit per pixel, two 32KB pages at B0000h and B8000h
+//! - Graphics rows are interleaved over four 8KB banks: row `y` starts at
+//!   `(y % 4) * 2000h + (y / 4) * 90`
+//! - Port 3B8h selects text or graphics and the displayed page; port 3BFh
+//!   has to allow graphics and the second page first (see `PcBus`)
+//!
+//! # Monochrome Attributes
+//!
+//! MDA attributes are not colors: bit 3 selects high intensity, bit 7
+//! blinking, foreground 1 with background 0 underlines, 00h and 08h are
+//! invisible and 70h is reverse video. Blinking text is shown steadily.
+//!
+//! The adapter switches to graphics when resized to 720x348 and back to text
+//! on reset, like the EGA and VGA adapters.
+
+use super::font;
+use super::video_adapter::VideoAdapter;
+use emu_core::types::Frame;
+
+/// Hercules graphics width in pixels
+pub const HERCULES_GRAPHICS_WIDTH: usize = 720;
+/// Hercules graphics height in pixels
+pub const HERCULES_GRAPHICS_HEIGHT: usize = 348;
+
+/// Bytes per graphics row
+const GRAPHICS_BYTES_PER_ROW: usize = HERCULES_GRAPHICS_WIDTH / 8;
+/// Size of each of the four interleaved graphics banks
+const GRAPHICS_BANK_SIZE: usize = 0x2000;
+
+/// Text mode dimensions
+const TEXT_COLUMNS: usize = 80;
+const TEXT_ROWS: usize = 25;
+const CHAR_WIDTH: usize = 9;
+const CHAR_HEIGHT: usize = 14;
+/// Scanline of the character cell drawn for underlined text
+const UNDERLINE_ROW: usize = 12;
+
+/// Green phosphor shades of a monochrome monitor
+const MONO_BLACK: u32 = 0xFF000000;
+const MONO_NORMAL: u32 = 0xFF00AA00;
+const MONO_BRIGHT: u32 = 0xFF55FF55;
+
+/// Hercules display modes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HerculesMode {
+    /// MDA text mode: 80x25 characters at 720x350
+    #[default]
+    Text80x25,
+    /// Graphics mode: 720x348, monochrome
+    Graphics720x348,
+}
+
+/// Software-based Hercules Graphics Card
+pub struct SoftwareHerculesAdapter {
+    /// Framebuffer
+    framebuffer: Frame,
+    /// Current display mode
+    mode: HerculesMode,
+}
+
+impl SoftwareHerculesAdapter {
+    /// Create a new Hercules adapter (starts in text mode)
+    pub fn new() -> Self {
+        Self {
+            framebuffer: Frame::new(
+                (TEXT_COLUMNS * CHAR_WIDTH) as u32,
+                (TEXT_ROWS * CHAR_HEIGHT) as u32,
+            ),
+            mode: HerculesMode::Text80x25,
+        }
+    }
+
+    /// Get current mode
+    pub fn get_mode(&self) -> HerculesMode {
+        self.mode
+    }
+
+    /// Get resolution for the current mode
+    fn get_mode_resolution(&self) -> (usize, usize) {
+        match self.mode {
+            HerculesMode::Text80x25 => (TEXT_COLUMNS * CHAR_WIDTH, TEXT_ROWS * CHAR_HEIGHT),
+            HerculesMode::Graphics720x348 => (HERCULES_GRAPHICS_WIDTH, HERCULES_GRAPHICS_HEIGHT),
+        }
+    }
+
+    /// Render MDA text mode from the text buffer
+    fn render_text_mode(&self, vram: &[u8], pixels: &mut [u32]) {
+        if vram.len() < TEXT_COLUMNS * TEXT_ROWS * 2 {
+            return;
+        }
+
+        pixels.fill(MONO_BLACK);
+
+        for row in 0..TEXT_ROWS {
+            for col in 0..TEXT_COLUMNS {
+                let cell_offset = (row * TEXT_COLUMNS + col) * 2;
+                self.render_char(
+                    vram[cell_offset],
+                    vram[cell_offset + 1],
+                    col * CHAR_WIDTH,
+                    row * CHAR_HEIGHT,
+                    pixels,
+                );
+            }
+        }
+    }
+
+    /// Render a single 9x14 character cell
+    fn render_char(&self, char_code: u8, attr: u8, x: usize, y: usize, pixels: &mut [u32]) {
+        let (fg, bg) = match attr & 0x77 {
+            0x00 => (MONO_BLACK, MONO_BLACK),
+            0x70 => (MONO_BLACK, MONO_NORMAL),
+            _ if attr & 0x08 != 0 => (MONO_BRIGHT, MONO_BLACK),
+            _ => (MONO_NORMAL, MONO_BLACK),
+        };
+        let underline = attr & 0x77 == 0x01;
+        // Line drawing characters extend into the ninth column
+        let line_drawing = (0xC0..=0xDF).contains(&char_code);
+        let glyph = font::get_font_8x14(char_code);
+        let fb_width = TEXT_COLUMNS * CHAR_WIDTH;
+
+        for (row, &bits) in glyph.iter().enumerate().take(CHAR_HEIGHT) {
+            let line = y + row;
+            for col in 0..CHAR_WIDTH {
+                let lit = if underline && row == UNDERLINE_ROW {
+                    true
+                } else if col < 8 {
+                    (bits >> (7 - col)) & 1 != 0
+                } else {
+                    line_drawing && bits & 1 != 0
+                };
+                if let Some(pixel) = pixels.get_mut(line * fb_width + x + col) {
+                    *pixel = if lit { fg } else { bg };
+                }
+            }
+        }
+    }
+
+    /// Render a 720x348 graphics page
+    fn render_graphics(&self, vram: &[u8], pixels: &mut [u32]) {
+        pixels.fill(MONO_BLACK);
+
+        for y in 0..HERCULES_GRAPHICS_HEIGHT {
+            let row_offset = (y % 4) * GRAPHICS_BANK_SIZE + (y / 4) * GRAPHICS_BYTES_PER_ROW;
+            let Some(row) = vram.get(row_offset..row_offset + GRAPHICS_BYTES_PER_ROW) else {
+                return;
+            };
+            let line = &mut pixels[y * HERCULES_GRAPHICS_WIDTH..][..HERCULES_GRAPHICS_WIDTH];
+            for (x, pixel) in line.iter_mut().enumerate() {
+                if (row[x / 8] >> (7 - x % 8)) & 1 != 0 {
+                    *pixel = MONO_BRIGHT;
+                }
+            }
+        }
+    }
+}
+
+impl Default for SoftwareHerculesAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VideoAdapter for SoftwareHerculesAdapter {
+    fn init(&mut self, width: usize, height: usize) {
+        // Detect mode based on resolution
+        self.mode = if (width, height) == (HERCULES_GRAPHICS_WIDTH, HERCULES_GRAPHICS_HEIGHT) {
+            HerculesMode::Graphics720x348
+        } else {
+            HerculesMode::Text80x25
+        };
+        let (width, height) = self.get_mode_resolution();
+        self.framebuffer = Frame::new(width as u32, height as u32);
+    }
+
+    fn get_frame(&self) -> &Frame {
+        &self.framebuffer
+    }
+
+    fn get_frame_mut(&mut self) -> &mut Frame {
+        &mut self.framebuffer
+    }
+
+    fn fb_width(&self) -> usize {
+        self.get_mode_resolution().0
+    }
+
+    fn fb_height(&self) -> usize {
+        self.get_mode_resolution().1
+    }
+
+    fn render(&self, vram: &[u8], pixels: &mut [u32]) {
+        let (width, height) = self.get_mode_resolution();
+        if pixels.len() < width * height {
+            return;
+        }
+        match self.mode {
+            HerculesMode::Text80x25 => self.render_text_mode(vram, pixels),
+            HerculesMode::Graphics720x348 => self.render_graphics(vram, pixels),
+        }
+    }
+
+    fn reset(&mut self) {
+        self.init(0, 0);
+    }
+
+    fn name(&self) -> &str {
+        "Software Hercules Adapter"
+    }
+
+    fn resize(&mut self, width: usize, height: usize) {
+        self.init(width, height);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adapter_creation() {
+        let adapter = SoftwareHerculesAdapter::new();
+        assert_eq!(adapter.fb_width(), 720);
+        assert_eq!(adapter.fb_height(), 350);
+        assert_eq!(adapter.get_mode(), HerculesMode::Text80x25);
+        assert_eq!(adapter.name(), "Software Hercules Adapter");
+        assert!(!adapter.is_hardware_accelerated());
+    }
+
+    #[test]
+    fn test_mode_switching() {
+        let mut adapter = SoftwareHerculesAdapter::new();
+        adapter.resize(720, 348);
+        assert_eq!(adapter.get_mode(), HerculesMode::Graphics720x348);
+        assert_eq!((adapter.fb_width(), adapter.fb_height()), (720, 348));
+
+        adapter.reset();
+        assert_eq!(adapter.get_mode(), HerculesMode::Text80x25);
+        assert_eq!((adapter.fb_width(), adapter.fb_height()), (720, 350));
+    }
+
+    #[test]
+    fn test_text_attributes() {
+        let adapter = SoftwareHerculesAdapter::new();
+        let mut vram = vec![0u8; 4000];
+        let cell = |col: usize| col * CHAR_WIDTH;
+
+        vram[0] = 0xDB; // Full block, normal
+        vram[1] = 0x07;
+        vram[2] = 0xDB; // Full block, bright
+        vram[3] = 0x0F;
+        vram[4] = 0xDB; // Full block, invisible
+        vram[5] = 0x00;
+        vram[6] = b' '; // Space, reverse video
+        vram[7] = 0x70;
+        vram[8] = b' '; // Space, underlined
+        vram[9] = 0x01;
+
+        let mut pixels = vec![0u32; 720 * 350];
+        adapter.render(&vram, &mut pixels);
+
+        let at = |x: usize, y: usize| pixels[y * 720 + x];
+        assert_eq!(at(cell(0) + 4, 6), MONO_NORMAL);
+        // Line drawing characters fill the ninth column
+        assert_eq!(at(cell(0) + 8, 6), MONO_NORMAL);
+        assert_eq!(at(cell(1) + 4, 6), MONO_BRIGHT);
+        assert_eq!(at(cell(2) + 4, 6), MONO_BLACK);
+        assert_eq!(at(cell(3) + 4, 6), MONO_NORMAL);
+        assert_eq!(at(cell(4) + 4, 6), MONO_BLACK);
+        assert_eq!(at(cell(4) + 4, UNDERLINE_ROW), MONO_NORMAL);
+    }
+
+    #[test]
+    fn test_graphics_interleave() {
+        let mut adapter = SoftwareHerculesAdapter::new();
+        adapter.resize(720, 348);
+        let mut vram = vec![0u8; 0x8000];
+        vram[0] = 0x80; // Row 0, pixel 0
+        vram[0x2000 + 89] = 0x01; // Row 1, pixel 719
+        vram[0x6000 + 90 * 86 + 1] = 0x40; // Row 347, pixel 9
+
+        let mut pixels = vec![0u32; 720 * 348];
+        adapter.render(&vram, &mut pixels);
+
+        assert_eq!(pixels[0], MONO_BRIGHT);
+        assert_eq!(pixels[720 + 719], MONO_BRIGHT);
+        assert_eq!(pixels[347 * 720 + 9], MONO_BRIGHT);
+        assert_eq!(pixels.iter().filter(|&&p| p == MONO_BRIGHT).count(), 3);
+    }
+}
@@ -109,7 +109,10 @@ impl HardwareVgaAdapter {
         match self.mode {
             VgaMode::Text80x25 => (720, 400),
             VgaMode::Graphics320x200 => (320, 200),
+            VgaMode::Graphics320x240 => (320, 240),
             VgaMode::Graphics640x480 => (640, 480),
+            VgaMode::Graphics640x350 => (640, 350),
+            VgaMode::Graphics640x200 => (640, 200),
         }
     }
 
@@ -137,7 +140,10 @@ impl VideoAdapter for HardwareVgaAdapter {
         self.mode = match (width, height) {
             (720, 400) => VgaMode::Text80x25,
             (320, 200) => VgaMode::Graphics320x200,
+            (320, 240) => VgaMode::Graphics320x240,
             (640, 480) => VgaMode::Graphics640x480,
+            (640, 350) => VgaMode::Graphics640x350,
+            (640, 200) => VgaMode::Graphics640x200,
             _ => VgaMode::Text80x25,
         };
         self.framebuffer = Frame::new(width as u32, height as u32);
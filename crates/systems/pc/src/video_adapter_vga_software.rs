@@ -8,8 +8,14 @@
 //! - Text modes: 80x25 characters at 720x400 pixels (9x16 font)
 //! - Graphics modes:
 //!   - 320x200 256-color (Mode 13h) - most popular VGA mode
-//!   - 640x480 16-color (planar memory, 4 bit planes)
+//!   - 320x240 256-color (unchained "Mode X")
+//!   - 640x480, 640x350, 640x200 and 320x200 16-color (planar memory, 4 bit
+//!     planes)
 //! - 256-color palette (18-bit RGB: 6 bits per channel)
+//!
+//! The bit planes, attribute controller and DAC registers live on the bus
+//! (`crate::vga`); in graphics modes this adapter is handed the DAC index of
+//! every pixel and the DAC palette.
 //! - Multiple font sizes: 8x16, 9x16 (text mode)
 
 use super::font;
@@ -24,8 +30,14 @@ pub enum VgaMode {
     Text80x25,
     /// Graphics mode 13h: 320x200, 256 colors (most popular)
     Graphics320x200,
-    /// Graphics mode: 640x480, 16 colors (planar)
+    /// Unchained graphics mode ("Mode X"): 320x240, 256 colors
+    Graphics320x240,
+    /// Graphics mode 12h: 640x480, 16 colors (planar)
     Graphics640x480,
+    /// Graphics mode 10h: 640x350, 16 colors (planar)
+    Graphics640x350,
+    /// Graphics mode 0Eh: 640x200, 16 colors (planar)
+    Graphics640x200,
 }
 
 /// VGA color in 256-color palette (18-bit RGB)
@@ -180,7 +192,10 @@ impl SoftwareVgaAdapter {
         match self.mode {
             VgaMode::Text80x25 => (720, 400),
             VgaMode::Graphics320x200 => (320, 200),
+            VgaMode::Graphics320x240 => (320, 240),
             VgaMode::Graphics640x480 => (640, 480),
+            VgaMode::Graphics640x350 => (640, 350),
+            VgaMode::Graphics640x200 => (640, 200),
         }
     }
 
@@ -278,23 +293,25 @@ impl SoftwareVgaAdapter {
         }
     }
 
-    /// Render graphics mode 13h: 320x200, 256 colors
-    fn render_graphics_320x200(&self, vram: &[u8], pixels: &mut [u32]) {
-        const WIDTH: usize = 320;
-        const HEIGHT: usize = 200;
+    /// Render a graphics mode
+    ///
+    /// `vram` holds one DAC index per pixel, row by row, as scanned out of
+    /// the VGA planes (through the attribute controller in the 16-color
+    /// modes).
+    fn render_graphics(&self, vram: &[u8], pixels: &mut [u32]) {
+        let (width, height) = self.get_mode_resolution();
 
-        // VGA Mode 13h uses linear addressing (1 byte per pixel)
         pixels.fill(0xFF000000);
 
-        for y in 0..HEIGHT {
-            for x in 0..WIDTH {
-                let offset = y * WIDTH + x;
+        for y in 0..height {
+            for x in 0..width {
+                let offset = y * width + x;
                 if offset >= vram.len() {
                     break;
                 }
 
                 let color_index = vram[offset];
-                let pixel_idx = y * WIDTH + x;
+                let pixel_idx = y * width + x;
 
                 if pixel_idx < pixels.len() {
                     pixels[pixel_idx] = self.get_palette_color(color_index);
@@ -302,40 +319,6 @@ impl SoftwareVgaAdapter {
             }
         }
     }
-
-    /// Render graphics mode 640x480 (planar, 16 colors)
-    fn render_graphics_640x480(&self, vram: &[u8], pixels: &mut [u32]) {
-        const WIDTH: usize = 640;
-        const HEIGHT: usize = 480;
-
-        // VGA 640x480x16 uses planar memory (4 bit planes)
-        // Each plane is 38,400 bytes (640 * 480 / 8)
-        const PLANE_SIZE: usize = 38400;
-
-        pixels.fill(0xFF000000);
-
-        for y in 0..HEIGHT {
-            for x in 0..WIDTH {
-                let byte_offset = y * (WIDTH / 8) + (x / 8);
-                let bit_offset = 7 - (x % 8);
-
-                // Read from all 4 planes to get 4-bit color
-                let mut color = 0u8;
-                for plane in 0..4 {
-                    let plane_offset = plane * PLANE_SIZE + byte_offset;
-                    if plane_offset < vram.len() {
-                        let bit = (vram[plane_offset] >> bit_offset) & 1;
-                        color |= bit << plane;
-                    }
-                }
-
-                let pixel_idx = y * WIDTH + x;
-                if pixel_idx < pixels.len() {
-                    pixels[pixel_idx] = self.get_palette_color(color);
-                }
-            }
-        }
-    }
 }
 
 impl Default for SoftwareVgaAdapter {
@@ -350,9 +333,13 @@ impl VideoAdapter for SoftwareVgaAdapter {
         self.mode = match (width, height) {
             (720, 400) => VgaMode::Text80x25,
             (320, 200) => VgaMode::Graphics320x200,
+            (320, 240) => VgaMode::Graphics320x240,
             (640, 480) => VgaMode::Graphics640x480,
+            (640, 350) => VgaMode::Graphics640x350,
+            (640, 200) => VgaMode::Graphics640x200,
             _ => VgaMode::Text80x25, // Default to text mode
         };
+        let (width, height) = self.get_mode_resolution();
         self.framebuffer = Frame::new(width as u32, height as u32);
     }
 
@@ -377,8 +364,17 @@ impl VideoAdapter for SoftwareVgaAdapter {
     fn render(&self, vram: &[u8], pixels: &mut [u32]) {
         match self.mode {
             VgaMode::Text80x25 => self.render_text_mode(vram, pixels),
-            VgaMode::Graphics320x200 => self.render_graphics_320x200(vram, pixels),
-            VgaMode::Graphics640x480 => self.render_graphics_640x480(vram, pixels),
+            _ => self.render_graphics(vram, pixels),
+        }
+    }
+
+    fn set_dac_palette(&mut self, dac: &[u8; 768]) {
+        for (color, rgb) in self.palette.iter_mut().zip(dac.chunks_exact(3)) {
+            *color = VgaColor {
+                r: rgb[0],
+                g: rgb[1],
+                b: rgb[2],
+            };
         }
     }
 
@@ -501,25 +497,42 @@ mod tests {
         assert!(non_black > 0);
     }
 
+    #[test]
+    fn test_mode_x_rendering_with_dac_palette() {
+        let mut adapter = SoftwareVgaAdapter::new();
+        adapter.resize(320, 240);
+        assert_eq!(adapter.get_mode(), VgaMode::Graphics320x240);
+        assert_eq!((adapter.fb_width(), adapter.fb_height()), (320, 240));
+
+        let mut dac = [0u8; 768];
+        dac[3 * 7..3 * 7 + 3].copy_from_slice(&[63, 0, 63]);
+        adapter.set_dac_palette(&dac);
+
+        let mut vram = vec![0u8; 320 * 240];
+        vram[320 * 239 + 319] = 7;
+        let mut pixels = vec![0u32; 320 * 240];
+        adapter.render(&vram, &mut pixels);
+        assert_eq!(pixels[320 * 239 + 319], 0xFFFF00FF);
+        assert_eq!(pixels[0], 0xFF000000);
+    }
+
     #[test]
     fn test_graphics_640x480_rendering() {
         let mut adapter = SoftwareVgaAdapter::new();
         adapter.set_mode(VgaMode::Graphics640x480);
 
-        // Create test pattern in VRAM (4 planes)
-        let mut vram = vec![0u8; 153600]; // 4 planes * 38400 bytes
-
-        // Fill first plane with pattern
-        for byte in vram.iter_mut().take(100) {
-            *byte = 0xFF;
-        }
+        // One DAC index per pixel
+        let mut vram = vec![0u8; 640 * 480];
+        vram[..100].fill(15);
+        vram[640 * 479 + 639] = 1;
 
         let mut pixels = vec![0u32; 640 * 480];
         adapter.render(&vram, &mut pixels);
 
-        // Check that some pixels were rendered
         let non_black = pixels.iter().filter(|&&p| p != 0xFF000000).count();
-        assert!(non_black > 0);
+        assert_eq!(non_black, 101);
+        assert_eq!(pixels[0], 0xFFFFFFFF);
+        assert_eq!(pixels[640 * 480 - 1], 0xFF0000AA);
     }
 
     #[test]
@@ -19,6 +19,9 @@ const XMS_VERSION: u16 = 0x0200;
 /// Size of the High Memory Area in bytes (64KB - 16 bytes)
 const HMA_SIZE: u32 = 65520;
 
+/// Linear address where extended memory starts (1MB)
+const EXTENDED_MEMORY_BASE: u32 = 0x100000;
+
 /// XMS error codes
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u8)]
@@ -66,6 +69,13 @@ struct ExtendedMemoryBlock {
     linear_address: u32,
 }
 
+impl ExtendedMemoryBlock {
+    /// Offset of the block from the start of extended memory in KB
+    fn start_kb(&self) -> u32 {
+        (self.linear_address - EXTENDED_MEMORY_BASE) / 1024
+    }
+}
+
 /// Upper Memory Block (UMB) descriptor
 #[derive(Debug, Clone)]
 struct UpperMemoryBlock {
@@ -201,9 +211,57 @@ impl XmsDriver {
     /// Query free extended memory (AH=08h)
     /// Returns: (largest_free_block_kb, total_free_kb)
     pub fn query_free_extended_memory(&self) -> (u16, u16) {
-        // For simplicity, we report all free memory as one contiguous block
+        let largest = self.largest_free_kb(None).min(0xFFFF) as u16;
         let free = self.free_extended_kb.min(0xFFFF) as u16;
-        (free, free)
+        (largest, free)
+    }
+
+    /// Extended memory blocks as (start_kb, end_kb), highest first
+    fn used_ranges(&self, ignore: Option<u16>) -> Vec<(u32, u32)> {
+        let mut ranges: Vec<(u32, u32)> = self
+            .emb_blocks
+            .values()
+            .filter(|block| Some(block.handle) != ignore)
+            .map(|block| (block.start_kb(), block.start_kb() + block.size_kb as u32))
+            .collect();
+        ranges.sort_unstable_by(|a, b| b.cmp(a));
+        ranges
+    }
+
+    /// Gaps between blocks as (start_kb, size_kb), highest first
+    fn free_ranges(&self, ignore: Option<u16>) -> Vec<(u32, u32)> {
+        let mut gaps = Vec::new();
+        let mut top = self.total_extended_kb;
+        for (start, end) in self.used_ranges(ignore) {
+            if end < top {
+                gaps.push((end, top - end));
+            }
+            top = top.min(start);
+        }
+        if top > 0 {
+            gaps.push((0, top));
+        }
+        gaps
+    }
+
+    /// Size of the largest free gap in KB
+    fn largest_free_kb(&self, ignore: Option<u16>) -> u32 {
+        self.free_ranges(ignore)
+            .iter()
+            .map(|&(_, size)| size)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Find room for a block of `size_kb`, returning its linear address
+    ///
+    /// Blocks are placed top-down so the HMA at the bottom of extended
+    /// memory stays clear until memory is nearly exhausted.
+    fn find_free_range(&self, size_kb: u32, ignore: Option<u16>) -> Option<u32> {
+        self.free_ranges(ignore)
+            .iter()
+            .find(|&&(_, size)| size >= size_kb)
+            .map(|&(start, size)| EXTENDED_MEMORY_BASE + (start + size - size_kb) * 1024)
     }
 
     /// Allocate extended memory block (AH=09h)
@@ -221,6 +279,10 @@ impl XmsDriver {
             return (0, XmsError::AllHandlesInUse);
         }
 
+        let Some(linear_address) = self.find_free_range(size_kb as u32, None) else {
+            return (0, XmsError::AllExtendedMemoryAllocated);
+        };
+
         let handle = self.next_handle;
         self.next_handle += 1;
 
@@ -228,7 +290,7 @@ impl XmsDriver {
             handle,
             size_kb,
             lock_count: 0,
-            linear_address: 0x100000 + (self.total_extended_kb - self.free_extended_kb) * 1024,
+            linear_address,
         };
 
         self.free_extended_kb -= size_kb as u32;
@@ -253,18 +315,55 @@ impl XmsDriver {
     }
 
     /// Move extended memory block (AH=0Bh)
-    /// This is a stub - actual memory copying would happen in the bus
+    /// Validates the move and resolves both ends to linear addresses; the
+    /// caller copies the bytes. Handle 0 means the offset is a real-mode
+    /// segment:offset pointer.
+    /// Returns: (source_address, dest_address, error_code)
     pub fn move_extended_memory(
         &self,
-        _src_handle: u16,
-        _src_offset: u32,
-        _dest_handle: u16,
-        _dest_offset: u32,
-        _length: u32,
-    ) -> XmsError {
-        // In a real implementation, this would copy memory between EMBs
-        // For now, we just validate handles
-        XmsError::Success
+        src_handle: u16,
+        src_offset: u32,
+        dest_handle: u16,
+        dest_offset: u32,
+        length: u32,
+    ) -> (u32, u32, XmsError) {
+        if !length.is_multiple_of(2) {
+            return (0, 0, XmsError::InvalidLength);
+        }
+
+        let src = self.resolve_move_address(src_handle, src_offset, length);
+        let dest = self.resolve_move_address(dest_handle, dest_offset, length);
+        match (src, dest) {
+            (Err(XmsError::InvalidHandle), _) => (0, 0, XmsError::InvalidSourceHandle),
+            (Err(_), _) => (0, 0, XmsError::InvalidSourceOffset),
+            (_, Err(XmsError::InvalidHandle)) => (0, 0, XmsError::InvalidDestHandle),
+            (_, Err(_)) => (0, 0, XmsError::InvalidDestOffset),
+            (Ok(src), Ok(dest)) => {
+                // Only forward moves within one block are allowed to overlap
+                if src_handle != 0 && src_handle == dest_handle && src < dest && dest < src + length
+                {
+                    return (0, 0, XmsError::InvalidOverlap);
+                }
+                (src, dest, XmsError::Success)
+            }
+        }
+    }
+
+    /// Resolve one end of a move to a linear address
+    fn resolve_move_address(&self, handle: u16, offset: u32, length: u32) -> Result<u32, XmsError> {
+        if handle == 0 {
+            let segment = offset >> 16;
+            return Ok((segment << 4) + (offset & 0xFFFF));
+        }
+
+        let block = self
+            .emb_blocks
+            .get(&handle)
+            .ok_or(XmsError::InvalidHandle)?;
+        match offset.checked_add(length) {
+            Some(end) if end <= block.size_kb as u32 * 1024 => Ok(block.linear_address + offset),
+            _ => Err(XmsError::InvalidLength),
+        }
     }
 
     /// Lock extended memory block (AH=0Ch)
@@ -318,26 +417,49 @@ impl XmsDriver {
             }
 
             let old_size = block.size_kb;
+            let start_kb = block.start_kb();
             if new_size_kb > old_size {
-                // Growing the block
+                // Growing the block, in place when the memory above it is free
                 let additional = (new_size_kb - old_size) as u32;
                 if additional > self.free_extended_kb {
                     return XmsError::AllExtendedMemoryAllocated;
                 }
+                let fits_in_place = self.free_ranges(Some(handle)).iter().any(|&(start, size)| {
+                    start <= start_kb && start_kb + new_size_kb as u32 <= start + size
+                });
+                let linear_address = if fits_in_place {
+                    EXTENDED_MEMORY_BASE + start_kb * 1024
+                } else {
+                    match self.find_free_range(new_size_kb as u32, Some(handle)) {
+                        Some(address) => address,
+                        None => return XmsError::AllExtendedMemoryAllocated,
+                    }
+                };
                 self.free_extended_kb -= additional;
+                if let Some(block) = self.emb_blocks.get_mut(&handle) {
+                    block.linear_address = linear_address;
+                    block.size_kb = new_size_kb;
+                }
             } else {
                 // Shrinking the block
                 let released = (old_size - new_size_kb) as u32;
                 self.free_extended_kb += released;
+                block.size_kb = new_size_kb;
             }
 
-            block.size_kb = new_size_kb;
             XmsError::Success
         } else {
             XmsError::InvalidHandle
         }
     }
 
+    /// Get the linear address of an extended memory block
+    pub fn block_address(&self, handle: u16) -> Option<u32> {
+        self.emb_blocks
+            .get(&handle)
+            .map(|block| block.linear_address)
+    }
+
     /// Request Upper Memory Block (AH=10h)
     /// Returns: (segment, actual_size_para, error_code)
     pub fn request_umb(&mut self, size_para: u16) -> (u16, u16, XmsError) {
@@ -380,6 +502,14 @@ impl XmsDriver {
         XmsError::InvalidUmbSegment
     }
 
+    /// Check whether a linear address lies in an allocated UMB
+    pub fn is_umb_address(&self, addr: u32) -> bool {
+        self.umb_blocks.iter().any(|umb| {
+            let start = (umb.segment as u32) << 4;
+            umb.allocated && (start..start + ((umb.size_para as u32) << 4)).contains(&addr)
+        })
+    }
+
     /// Check if driver is installed
     pub fn is_installed(&self) -> bool {
         self.installed
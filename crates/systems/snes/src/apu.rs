@@ -0,0 +1,421 @@
+//! SNES audio processing unit (S-SMP)
+//!
+//! The APU is a self-contained computer: an SPC700 CPU with 64KB of audio
+//! RAM, three timers and the S-DSP. The main CPU cannot reach the audio RAM;
+//! it uploads the sound driver and its data through four bidirectional
+//! ports ($2140-$2143 on the main bus, $F4-$F7 on the SPC700 side), talking
+//! to the boot program in the 64-byte IPL ROM at $FFC0.
+//!
+//! The IPL ROM here is a from-scratch program implementing the documented
+//! upload protocol: it announces itself with $AA/$BB on ports 0/1, waits for
+//! $CC, then receives blocks of bytes acknowledged through port 0 and jumps
+//! to the given address when a block with command 0 arrives.
+
+use crate::dsp::{Dsp, DSP_SAMPLE_RATE};
+use emu_core::cpu_spc700::{CpuSpc700, MemorySpc700};
+use std::cell::Cell;
+use std::collections::VecDeque;
+
+/// SPC700 clock in Hz (24.576 MHz / 24)
+const SPC_CLOCK_HZ: u64 = 1_024_000;
+/// Rate of the main CPU cycle units fed to `tick` (21.477 MHz master / 4)
+const MAIN_CLOCK_HZ: u64 = 5_369_318;
+/// SPC700 cycles per DSP output sample
+const CYCLES_PER_SAMPLE: u32 = SPC_CLOCK_HZ as u32 / DSP_SAMPLE_RATE;
+/// Host output rate of `take_samples`
+const OUTPUT_SAMPLE_RATE: u32 = 44100;
+/// Maximum buffered output (1 second) if the host stops pulling samples
+const MAX_BUFFERED_SAMPLES: usize = OUTPUT_SAMPLE_RATE as usize;
+
+/// Boot program mapped at $FFC0-$FFFF while CONTROL bit 7 is set
+#[rustfmt::skip]
+const IPL_ROM: [u8; 64] = [
+    // $FFC0: set up the stack and clear the zero page
+    0xCD, 0xEF,       // MOV X, #$EF
+    0xBD,             // MOV SP, X
+    0xE8, 0x00,       // MOV A, #$00
+    0xC6,             // MOV (X), A
+    0x1D,             // DEC X
+    0xD0, 0xFC,       // BNE $FFC5
+    // $FFC9: announce ready and wait for $CC
+    0x8F, 0xAA, 0xF4, // MOV $F4, #$AA
+    0x8F, 0xBB, 0xF5, // MOV $F5, #$BB
+    0x78, 0xCC, 0xF4, // CMP $F4, #$CC
+    0xD0, 0xFB,       // BNE $FFCF
+    // $FFD4: command: address from ports 2/3, kick from port 0, command
+    // from port 1 (0 = jump to the address)
+    0xBA, 0xF6,       // MOVW YA, $F6
+    0xDA, 0x00,       // MOVW $00, YA
+    0xBA, 0xF4,       // MOVW YA, $F4
+    0xC4, 0xF4,       // MOV $F4, A
+    0xDD,             // MOV A, Y
+    0x5D,             // MOV X, A
+    0xF0, 0x17,       // BEQ $FFF7
+    // $FFE0: wait for the first byte (index 0)
+    0xEB, 0xF4,       // MOV Y, $F4
+    0xD0, 0xFC,       // BNE $FFE0
+    // $FFE4: store the byte from port 1 and acknowledge its index
+    0xE4, 0xF5,       // MOV A, $F5
+    0xCB, 0xF4,       // MOV $F4, Y
+    0xD7, 0x00,       // MOV [$00]+Y, A
+    0xFC,             // INC Y
+    0xD0, 0x02,       // BNE $FFEF
+    0xAB, 0x01,       // INC $01
+    // $FFEF: next index is a byte; a larger jump starts a new command
+    0x7E, 0xF4,       // CMP Y, $F4
+    0xF0, 0xF1,       // BEQ $FFE4
+    0x10, 0xFA,       // BPL $FFEF
+    0x2F, 0xDD,       // BRA $FFD4
+    // $FFF7: jump to the uploaded program (X = 0)
+    0x1F, 0x00, 0x00, // JMP [$0000+X]
+    0x00, 0x00, 0x00, 0x00,
+    // $FFFE: reset vector
+    0xC0, 0xFF,
+];
+
+/// One of the three APU timers
+#[derive(Debug, Clone, Copy, Default)]
+struct Timer {
+    /// $FA-$FC: divider (0 = 256)
+    target: u8,
+    /// Internal stage counter, compared against the target
+    stage: u8,
+    /// SPC700 cycles until the next stage tick
+    cycles: u32,
+}
+
+/// The SPC700's view of the audio RAM and I/O registers
+pub struct ApuMemory {
+    /// 64KB audio RAM, shared with the DSP
+    ram: Vec<u8>,
+    dsp: Dsp,
+    /// $F1 CONTROL: timer enables (bits 0-2), IPL ROM enable (bit 7)
+    control: u8,
+    /// $F2: selected DSP register
+    dsp_addr: u8,
+    /// Ports as written by the main CPU ($2140-$2143), read at $F4-$F7
+    cpu_to_apu: [u8; 4],
+    /// Ports as written by the SPC700 ($F4-$F7), read at $2140-$2143
+    apu_to_cpu: [u8; 4],
+    timers: [Timer; 3],
+    /// $FD-$FF: 4-bit timer outputs, cleared when read
+    timer_outputs: [Cell<u8>; 3],
+}
+
+impl ApuMemory {
+    fn new() -> Self {
+        Self {
+            ram: vec![0; 0x10000],
+            dsp: Dsp::new(),
+            control: 0x80,
+            dsp_addr: 0,
+            cpu_to_apu: [0; 4],
+            apu_to_cpu: [0; 4],
+            timers: [Timer::default(); 3],
+            timer_outputs: Default::default(),
+        }
+    }
+
+    fn reset(&mut self) {
+        self.dsp.reset();
+        self.control = 0x80;
+        self.dsp_addr = 0;
+        self.cpu_to_apu = [0; 4];
+        self.apu_to_cpu = [0; 4];
+        self.timers = [Timer::default(); 3];
+        for output in &self.timer_outputs {
+            output.set(0);
+        }
+    }
+
+    /// Advance the enabled timers by `cycles` SPC700 cycles. Timers 0 and
+    /// 1 tick at 8kHz, timer 2 at 64kHz.
+    fn tick_timers(&mut self, cycles: u32) {
+        for (i, timer) in self.timers.iter_mut().enumerate() {
+            if self.control & (1 << i) == 0 {
+                continue;
+            }
+            let period = if i == 2 { 16 } else { 128 };
+            timer.cycles += cycles;
+            while timer.cycles >= period {
+                timer.cycles -= period;
+                timer.stage = timer.stage.wrapping_add(1);
+                if timer.stage == timer.target {
+                    timer.stage = 0;
+                    let output = &self.timer_outputs[i];
+                    output.set((output.get() + 1) & 0x0F);
+                }
+            }
+        }
+    }
+
+    fn write_control(&mut self, val: u8) {
+        // Enabling a timer restarts it
+        for (i, timer) in self.timers.iter_mut().enumerate() {
+            let bit = 1 << i;
+            if val & bit != 0 && self.control & bit == 0 {
+                timer.stage = 0;
+                timer.cycles = 0;
+                self.timer_outputs[i].set(0);
+            }
+        }
+        // Bits 4 and 5 clear the input latches of ports 0/1 and 2/3
+        if val & 0x10 != 0 {
+            self.cpu_to_apu[0] = 0;
+            self.cpu_to_apu[1] = 0;
+        }
+        if val & 0x20 != 0 {
+            self.cpu_to_apu[2] = 0;
+            self.cpu_to_apu[3] = 0;
+        }
+        self.control = val;
+    }
+}
+
+impl MemorySpc700 for ApuMemory {
+    fn read(&self, addr: u16) -> u8 {
+        match addr {
+            0x00F2 => self.dsp_addr,
+            0x00F3 => self.dsp.read(self.dsp_addr),
+            0x00F4..=0x00F7 => self.cpu_to_apu[addr as usize - 0xF4],
+            0x00FD..=0x00FF => self.timer_outputs[addr as usize - 0xFD].take(),
+            // TEST, CONTROL and the timer targets are write-only
+            0x00F0 | 0x00F1 | 0x00FA..=0x00FC => 0,
+            0xFFC0..=0xFFFF if self.control & 0x80 != 0 => IPL_ROM[addr as usize - 0xFFC0],
+            _ => self.ram[addr as usize],
+        }
+    }
+
+    fn write(&mut self, addr: u16, val: u8) {
+        match addr {
+            0x00F1 => self.write_control(val),
+            0x00F2 => self.dsp_addr = val,
+            0x00F3 => self.dsp.write(self.dsp_addr, val),
+            0x00F4..=0x00F7 => self.apu_to_cpu[addr as usize - 0xF4] = val,
+            0x00FA..=0x00FC => self.timers[addr as usize - 0xFA].target = val,
+            0x00F0 | 0x00FD..=0x00FF => {}
+            // Writes under the IPL ROM reach the RAM
+            _ => self.ram[addr as usize] = val,
+        }
+    }
+}
+
+/// SNES audio processing unit
+pub struct Apu {
+    cpu: CpuSpc700<ApuMemory>,
+    /// Main clock units not yet converted to SPC700 cycles
+    clock_remainder: u64,
+    /// SPC700 cycles owed to (positive) or run ahead of (negative) the
+    /// main CPU
+    cycle_budget: i64,
+    /// SPC700 cycles since the last DSP sample
+    sample_cycles: u32,
+    /// Phase of the 32kHz to 44.1kHz conversion
+    resample_phase: u32,
+    /// Mono output samples at 44.1kHz
+    output: VecDeque<i16>,
+}
+
+impl Apu {
+    pub fn new() -> Self {
+        let mut apu = Self {
+            cpu: CpuSpc700::new(ApuMemory::new()),
+            clock_remainder: 0,
+            cycle_budget: 0,
+            sample_cycles: 0,
+            resample_phase: 0,
+            output: VecDeque::new(),
+        };
+        apu.reset();
+        apu
+    }
+
+    /// Reset the APU: the IPL ROM runs again, audio RAM is kept
+    pub fn reset(&mut self) {
+        self.cpu.memory.reset();
+        self.cpu.reset();
+        self.clock_remainder = 0;
+        self.cycle_budget = 0;
+        self.sample_cycles = 0;
+        self.resample_phase = 0;
+        self.output.clear();
+    }
+
+    /// Read an APU port from the main CPU side ($2140-$2143)
+    pub fn read_port(&self, port: usize) -> u8 {
+        self.cpu.memory.apu_to_cpu[port & 3]
+    }
+
+    /// Write an APU port from the main CPU side ($2140-$2143)
+    pub fn write_port(&mut self, port: usize, val: u8) {
+        self.cpu.memory.cpu_to_apu[port & 3] = val;
+    }
+
+    /// Run the APU for `cycles` main CPU cycle units (master clock / 4)
+    pub fn tick(&mut self, cycles: u32) {
+        self.clock_remainder += cycles as u64 * SPC_CLOCK_HZ;
+        self.cycle_budget += (self.clock_remainder / MAIN_CLOCK_HZ) as i64;
+        self.clock_remainder %= MAIN_CLOCK_HZ;
+
+        while self.cycle_budget > 0 {
+            let spc_cycles = self.cpu.step();
+            self.cycle_budget -= spc_cycles as i64;
+            self.cpu.memory.tick_timers(spc_cycles);
+
+            self.sample_cycles += spc_cycles;
+            while self.sample_cycles >= CYCLES_PER_SAMPLE {
+                self.sample_cycles -= CYCLES_PER_SAMPLE;
+                self.generate_sample();
+            }
+        }
+    }
+
+    /// Mix one 32kHz DSP sample down to mono and queue it at 44.1kHz
+    fn generate_sample(&mut self) {
+        let memory = &mut self.cpu.memory;
+        let (left, right) = memory.dsp.generate_sample(&mut memory.ram);
+        let mono = ((left as i32 + right as i32) / 2) as i16;
+
+        self.resample_phase += OUTPUT_SAMPLE_RATE;
+        while self.resample_phase >= DSP_SAMPLE_RATE {
+            self.resample_phase -= DSP_SAMPLE_RATE;
+            if self.output.len() >= MAX_BUFFERED_SAMPLES {
+                self.output.pop_front();
+            }
+            self.output.push_back(mono);
+        }
+    }
+
+    /// Take up to `count` buffered 44.1kHz samples, padding with silence
+    pub fn take_samples(&mut self, count: usize) -> Vec<i16> {
+        let mut samples: Vec<i16> = self.output.drain(..count.min(self.output.len())).collect();
+        samples.resize(count, 0);
+        samples
+    }
+}
+
+impl Default for Apu {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Main CPU cycle units for roughly `n` SPC700 cycles
+    fn main_cycles(n: u32) -> u32 {
+        (n as u64 * MAIN_CLOCK_HZ / SPC_CLOCK_HZ) as u32 + 1
+    }
+
+    /// Run the APU until port 0 reads `val`, as the main CPU does
+    fn wait_for_port0(apu: &mut Apu, val: u8) {
+        for _ in 0..1000 {
+            if apu.read_port(0) == val {
+                return;
+            }
+            apu.tick(main_cycles(16));
+        }
+        panic!("APU never echoed {val:#04X} on port 0");
+    }
+
+    /// Upload `data` to `addr` and start it through the IPL protocol
+    fn upload_and_run(apu: &mut Apu, addr: u16, data: &[u8]) {
+        wait_for_port0(apu, 0xAA);
+        assert_eq!(apu.read_port(1), 0xBB);
+
+        apu.write_port(2, addr as u8);
+        apu.write_port(3, (addr >> 8) as u8);
+        apu.write_port(1, 0x01);
+        apu.write_port(0, 0xCC);
+        wait_for_port0(apu, 0xCC);
+
+        for (i, &byte) in data.iter().enumerate() {
+            apu.write_port(1, byte);
+            apu.write_port(0, i as u8);
+            wait_for_port0(apu, i as u8);
+        }
+
+        // Command 0 jumps to the address
+        let kick = (data.len() as u8).wrapping_add(1).max(1);
+        apu.write_port(2, addr as u8);
+        apu.write_port(3, (addr >> 8) as u8);
+        apu.write_port(1, 0x00);
+        apu.write_port(0, kick);
+        wait_for_port0(apu, kick);
+    }
+
+    #[test]
+    fn test_ipl_rom_size_and_vector() {
+        assert_eq!(IPL_ROM.len(), 64);
+        let apu = Apu::new();
+        assert_eq!(apu.cpu.pc, 0xFFC0);
+    }
+
+    #[test]
+    fn test_ipl_upload_and_execute() {
+        let mut apu = Apu::new();
+        // MOV $F5, #$5A ; BRA -2
+        let program = [0x8F, 0x5A, 0xF5, 0x2F, 0xFE];
+        upload_and_run(&mut apu, 0x0300, &program);
+        assert_eq!(&apu.cpu.memory.ram[0x0300..0x0305], &program);
+
+        apu.tick(main_cycles(64));
+        assert_eq!(apu.read_port(1), 0x5A);
+        assert!((0x0303..=0x0304).contains(&apu.cpu.pc));
+    }
+
+    #[test]
+    fn test_ipl_rom_can_be_unmapped() {
+        let mut memory = ApuMemory::new();
+        memory.write(0xFFC0, 0x12);
+        assert_eq!(memory.read(0xFFC0), 0xCD);
+        memory.write(0x00F1, 0x00);
+        assert_eq!(memory.read(0xFFC0), 0x12);
+    }
+
+    #[test]
+    fn test_timer_outputs() {
+        let mut memory = ApuMemory::new();
+        memory.write(0x00FA, 2); // Timer 0 every 2 ticks of 8kHz
+        memory.write(0x00FC, 0); // Timer 2 every 256 ticks of 64kHz
+        memory.write(0x00F1, 0x01);
+        memory.tick_timers(128 * 6);
+        assert_eq!(memory.read(0x00FD), 3);
+        // Reading clears the output
+        assert_eq!(memory.read(0x00FD), 0);
+
+        // Enabling timer 2 leaves timer 0 running
+        memory.write(0x00F1, 0x05);
+        memory.tick_timers(16 * 255);
+        assert_eq!(memory.read(0x00FD), 15);
+        assert_eq!(memory.read(0x00FF), 0);
+        memory.tick_timers(16);
+        assert_eq!(memory.read(0x00FF), 1);
+    }
+
+    #[test]
+    fn test_dsp_register_access() {
+        let mut memory = ApuMemory::new();
+        memory.write(0x00F2, 0x0C);
+        memory.write(0x00F3, 0x7F);
+        assert_eq!(memory.read(0x00F3), 0x7F);
+        assert_eq!(memory.dsp.read(0x0C), 0x7F);
+    }
+
+    #[test]
+    fn test_output_rate() {
+        let mut apu = Apu::new();
+        // One second of main CPU time yields about one second of audio
+        for _ in 0..100 {
+            apu.tick(MAIN_CLOCK_HZ as u32 / 100);
+        }
+        let buffered = apu.output.len() as i64;
+        assert!(
+            (buffered - OUTPUT_SAMPLE_RATE as i64).abs() < 100,
+            "{buffered}"
+        );
+        assert_eq!(apu.take_samples(10).len(), 10);
+    }
+}
@@ -1,12 +1,24 @@
 //! SNES memory bus implementation
 
+use crate::apu::Apu;
 use crate::cartridge::Cartridge;
+use crate::dma::{DmaChannel, DMA_CHANNELS};
 use crate::ppu::Ppu;
 use crate::SnesError;
+use emu_core::apu::TimingMode;
 use emu_core::cpu_65c816::Memory65c816;
 use emu_core::logging::{log, LogCategory, LogLevel};
 use std::cell::Cell;
 
+/// Frame cycle at which VBlank begins (matches the system's visible period)
+const VBLANK_START_CYCLE: u32 = 76400;
+/// Duration of the auto-joypad read (about three scanlines)
+const AUTO_JOYPAD_CYCLES: u32 = 3 * 341;
+/// CPU cycles per DMA byte (8 master clocks)
+const DMA_BYTE_CYCLES: u32 = 2;
+/// CPU cycles of set-up per active DMA/HDMA channel (8 master clocks)
+const DMA_CHANNEL_CYCLES: u32 = 2;
+
 /// SNES memory bus
 pub struct SnesBus {
     /// 128KB WRAM (work RAM)
@@ -23,12 +35,22 @@ pub struct SnesBus {
     /// Controller state (16 bits per controller)
     /// Button mapping: B Y Select Start Up Down Left Right A X L R 0 0 0 0
     pub controller_state: [u16; 2],
-    /// Controller shift registers for serial readout
+    /// Buttons latched by the last auto-joypad read ($4218-$421B)
+    joy_data: [u16; 2],
+    /// Controller shift registers for serial readout (MSB first)
     controller_shift: [Cell<u16>; 2],
     /// Controller strobe state
     controller_strobe: bool,
     /// Auto-joypad read enable ($4200 bit 0)
     auto_joypad_enable: bool,
+    /// DMA channel registers ($4300-$437F)
+    dma_channels: [DmaChannel; DMA_CHANNELS],
+    /// HDMAEN ($420C): channels running HDMA this frame
+    hdma_enable: u8,
+    /// CPU cycles taken by DMA since the CPU last ran
+    dma_cycles: u32,
+    /// Audio processing unit, reached through $2140-$2143
+    apu: Apu,
 }
 
 impl SnesBus {
@@ -40,9 +62,14 @@ impl SnesBus {
             frame_counter: 0,
             frame_cycle: 0,
             controller_state: [0; 2],
+            joy_data: [0; 2],
             controller_shift: [Cell::new(0), Cell::new(0)],
             controller_strobe: false,
             auto_joypad_enable: true, // Default to enabled
+            dma_channels: [DmaChannel::new(); DMA_CHANNELS],
+            hdma_enable: 0,
+            dma_cycles: 0,
+            apu: Apu::new(),
         }
     }
 
@@ -81,6 +108,17 @@ impl SnesBus {
     /// Update cycle counter within frame (called after each CPU step)
     pub fn tick_cycles(&mut self, cycles: u32) {
         self.frame_cycle += cycles;
+        self.apu.tick(cycles);
+    }
+
+    /// Reset the APU, which restarts its IPL boot program
+    pub fn reset_apu(&mut self) {
+        self.apu.reset();
+    }
+
+    /// Take up to `count` mono 44.1kHz audio samples
+    pub fn audio_samples(&mut self, count: usize) -> Vec<i16> {
+        self.apu.take_samples(count)
     }
 
     /// Check if currently in VBlank period
@@ -90,7 +128,34 @@ impl SnesBus {
         // VBlank starts after visible scanlines complete
         // Roughly 224/262 scanlines = ~85.5% of frame
         // So VBlank starts at cycle ~76,400 out of 89,342
-        self.frame_cycle >= 76400
+        self.frame_cycle >= VBLANK_START_CYCLE
+    }
+
+    /// Auto-joypad read, run by the system at the start of VBlank
+    ///
+    /// When enabled via $4200 bit 0, both controllers are clocked 16 times
+    /// and the buttons land in JOY1/JOY2 ($4218-$421B). The serial ports
+    /// have then been shifted empty and read back 1s until the next strobe.
+    pub fn auto_joypad_read(&mut self) {
+        if !self.auto_joypad_enable {
+            return;
+        }
+        self.joy_data = self.controller_state;
+        for shift in &self.controller_shift {
+            shift.set(0xFFFF);
+        }
+    }
+
+    /// Next serial bit from controller `idx` ($4016/$4017 bit 0)
+    fn read_serial(&self, idx: usize) -> u8 {
+        if self.controller_strobe {
+            // While strobed, the shift register keeps reloading: B button
+            return (self.controller_state[idx] >> 15) as u8;
+        }
+        // Buttons come out MSB first (B, Y, Select, ...); 1s shift in behind
+        let cur = self.controller_shift[idx].get();
+        self.controller_shift[idx].set((cur << 1) | 1);
+        (cur >> 15) as u8
     }
 
     /// Set controller state (16 buttons) for controller `idx` (0 or 1).
@@ -108,6 +173,148 @@ impl SnesBus {
         }
     }
 
+    /// Stop all DMA and HDMA, as on console reset
+    pub fn reset_dma(&mut self) {
+        self.hdma_enable = 0;
+        self.dma_cycles = 0;
+    }
+
+    /// CPU cycles the CPU was halted for by DMA and HDMA since the last call
+    pub fn take_dma_cycles(&mut self) -> u32 {
+        std::mem::take(&mut self.dma_cycles)
+    }
+
+    /// Copy one byte between an A-bus address and a $21xx B-bus register
+    fn transfer_byte(&mut self, a_addr: u32, b_reg: u8, b_to_a: bool) {
+        let b_addr = 0x2100 | b_reg as u32;
+        if b_to_a {
+            let val = self.read(b_addr);
+            self.write(a_addr, val);
+        } else {
+            let val = self.read(a_addr);
+            self.write(b_addr, val);
+        }
+    }
+
+    /// General DMA ($420B): run each selected channel to completion,
+    /// lowest channel first
+    fn start_dma(&mut self, channels: u8) {
+        for index in 0..DMA_CHANNELS {
+            if channels & (1 << index) == 0 {
+                continue;
+            }
+            let mut channel = self.dma_channels[index];
+            let pattern = channel.pattern();
+            let step = channel.a_step();
+            // A count of zero transfers 64KB
+            let bytes = if channel.count == 0 {
+                0x10000
+            } else {
+                channel.count as u32
+            };
+            log(LogCategory::Bus, LogLevel::Debug, || {
+                format!(
+                    "SNES Bus: DMA channel {} - {} bytes, ${:02X}:{:04X} {} $21{:02X} (mode {})",
+                    index,
+                    bytes,
+                    channel.a_bank,
+                    channel.a_address,
+                    if channel.b_to_a() { "<-" } else { "->" },
+                    channel.b_address,
+                    channel.params & 0x07
+                )
+            });
+
+            for byte in 0..bytes as usize {
+                let a_addr = (channel.a_bank as u32) << 16 | channel.a_address as u32;
+                let b_reg = channel
+                    .b_address
+                    .wrapping_add(pattern[byte % pattern.len()]);
+                self.transfer_byte(a_addr, b_reg, channel.b_to_a());
+                channel.a_address = channel.a_address.wrapping_add(step);
+            }
+            channel.count = 0;
+            self.dma_channels[index] = channel;
+            self.dma_cycles += DMA_CHANNEL_CYCLES + bytes * DMA_BYTE_CYCLES;
+        }
+    }
+
+    /// Read the next HDMA table entry: the line count, plus the data
+    /// pointer for indirect channels
+    fn hdma_load_entry(&mut self, channel: &mut DmaChannel) {
+        let table = (channel.a_bank as u32) << 16;
+        channel.line_counter = self.read(table | channel.table_address as u32);
+        channel.table_address = channel.table_address.wrapping_add(1);
+        // A zero line count ends the table for this frame
+        channel.hdma_terminated = channel.line_counter == 0;
+        if channel.hdma_indirect() && !channel.hdma_terminated {
+            let low = self.read(table | channel.table_address as u32);
+            channel.table_address = channel.table_address.wrapping_add(1);
+            let high = self.read(table | channel.table_address as u32);
+            channel.table_address = channel.table_address.wrapping_add(1);
+            channel.count = u16::from_le_bytes([low, high]);
+        }
+        channel.hdma_do_transfer = true;
+    }
+
+    /// HDMA frame start: point the enabled channels at their tables and
+    /// load the first entries
+    pub fn hdma_init(&mut self) {
+        for index in 0..DMA_CHANNELS {
+            if self.hdma_enable & (1 << index) == 0 {
+                continue;
+            }
+            let mut channel = self.dma_channels[index];
+            channel.table_address = channel.a_address;
+            self.hdma_load_entry(&mut channel);
+            self.dma_channels[index] = channel;
+        }
+    }
+
+    /// HDMA for one scanline, run in the HBlank before the line is drawn
+    ///
+    /// Each active channel writes one transfer unit on the first line of an
+    /// entry (every line if the entry's repeat bit is set), then counts the
+    /// entry's lines down and moves on to the next one.
+    pub fn hdma_scanline(&mut self) {
+        for index in 0..DMA_CHANNELS {
+            if self.hdma_enable & (1 << index) == 0 {
+                continue;
+            }
+            let mut channel = self.dma_channels[index];
+            if channel.hdma_terminated {
+                continue;
+            }
+
+            if channel.hdma_do_transfer {
+                let pattern = channel.pattern();
+                for &offset in pattern {
+                    let a_addr = if channel.hdma_indirect() {
+                        let addr = (channel.indirect_bank as u32) << 16 | channel.count as u32;
+                        channel.count = channel.count.wrapping_add(1);
+                        addr
+                    } else {
+                        let addr = (channel.a_bank as u32) << 16 | channel.table_address as u32;
+                        channel.table_address = channel.table_address.wrapping_add(1);
+                        addr
+                    };
+                    let b_reg = channel.b_address.wrapping_add(offset);
+                    self.transfer_byte(a_addr, b_reg, channel.b_to_a());
+                }
+                self.dma_cycles += pattern.len() as u32 * DMA_BYTE_CYCLES;
+            }
+            self.dma_cycles += DMA_CHANNEL_CYCLES;
+
+            // Bits 0-6 count the entry's lines; bit 7 repeats the transfer
+            channel.line_counter = channel.line_counter.wrapping_sub(1);
+            channel.hdma_do_transfer = channel.line_counter & 0x80 != 0;
+            if channel.line_counter & 0x7F == 0 {
+                self.hdma_load_entry(&mut channel);
+            }
+            self.dma_channels[index] = channel;
+        }
+    }
+
     pub fn get_rom_size(&self) -> usize {
         if let Some(ref cart) = self.cartridge {
             cart.rom_size()
@@ -116,6 +323,16 @@ impl SnesBus {
         }
     }
 
+    /// Region code from the mounted cartridge's header
+    pub fn cartridge_region(&self) -> Option<u8> {
+        self.cartridge.as_ref().map(|cart| cart.region())
+    }
+
+    /// Video standard implied by the mounted cartridge's header region code
+    pub fn cartridge_timing(&self) -> Option<TimingMode> {
+        self.cartridge.as_ref().map(|cart| cart.timing())
+    }
+
     pub fn has_smc_header(&self) -> bool {
         if let Some(ref cart) = self.cartridge {
             cart.has_smc_header()
@@ -144,6 +361,8 @@ impl Memory65c816 for SnesBus {
                     0x0000..=0x1FFF => self.wram[offset as usize],
                     // Hardware registers (PPU: $2100-$213F)
                     0x2100..=0x213F => self.ppu.read_register(offset),
+                    // $2140-$217F - APU ports (four registers, mirrored)
+                    0x2140..=0x217F => self.apu.read_port(offset as usize),
                     // $4200 - NMITIMEN - Interrupt Enable and Joypad Request
                     0x4200 => {
                         // Bit 7: NMI enable
@@ -155,76 +374,37 @@ impl Memory65c816 for SnesBus {
                         }
                     }
                     // $4016 - JOYSER0 - Controller 1 Serial Data
-                    0x4016 => {
-                        // Bit 0: Serial data for controller 1
-                        // Bits 1-7: Open bus (typically 0)
-                        if self.controller_strobe {
-                            // While strobed, return bit 0 of the current state
-                            (self.controller_state[0] & 1) as u8
-                        } else {
-                            // Shift out the latched state
-                            let cur = self.controller_shift[0].get();
-                            let bit = (cur & 1) as u8;
-                            self.controller_shift[0].set(cur >> 1);
-                            bit
-                        }
-                    }
+                    // Bit 0: Serial data for controller 1
+                    // Bits 1-7: Open bus (typically 0)
+                    0x4016 => self.read_serial(0),
                     // $4017 - JOYSER1 - Controller 2 Serial Data
-                    0x4017 => {
-                        // Bit 0: Serial data for controller 2
-                        // Bits 1-4: Not used (0x1E if nothing connected)
-                        if self.controller_strobe {
-                            (self.controller_state[1] & 1) as u8
-                        } else {
-                            let cur = self.controller_shift[1].get();
-                            let bit = (cur & 1) as u8;
-                            self.controller_shift[1].set(cur >> 1);
-                            bit
-                        }
+                    // Bit 0: Serial data for controller 2
+                    // Bits 2-4: Always set
+                    0x4017 => 0x1C | self.read_serial(1),
+                    // $4218-$421F - JOYxL/JOYxH - Buttons from the last auto-joypad read
+                    0x4218 => self.joy_data[0] as u8, // JOY1L
+                    0x4219 => (self.joy_data[0] >> 8) as u8, // JOY1H
+                    0x421A => self.joy_data[1] as u8, // JOY2L
+                    0x421B => (self.joy_data[1] >> 8) as u8, // JOY2H
+                    0x421C => 0,                      // JOY3L (not implemented)
+                    0x421D => 0,                      // JOY3H (not implemented)
+                    0x421E => 0,                      // JOY4L (not implemented)
+                    0x421F => 0,                      // JOY4H (not implemented)
+                    // $4300-$437F - DMA channel registers
+                    0x4300..=0x437F => {
+                        self.dma_channels[(offset as usize >> 4) & 0x07].read(offset as u8 & 0x0F)
                     }
-                    // $4218-$421F - JOYxL/JOYxH - Auto-joypad read (only valid when auto-read enabled)
-                    0x4218 => {
-                        if self.auto_joypad_enable {
-                            (self.controller_state[0] & 0xFF) as u8 // JOY1L
-                        } else {
-                            0 // Return 0 when auto-read disabled
-                        }
-                    }
-                    0x4219 => {
-                        if self.auto_joypad_enable {
-                            ((self.controller_state[0] >> 8) & 0xFF) as u8 // JOY1H
-                        } else {
-                            0
-                        }
-                    }
-                    0x421A => {
-                        if self.auto_joypad_enable {
-                            (self.controller_state[1] & 0xFF) as u8 // JOY2L
-                        } else {
-                            0
-                        }
-                    }
-                    0x421B => {
-                        if self.auto_joypad_enable {
-                            ((self.controller_state[1] >> 8) & 0xFF) as u8 // JOY2H
-                        } else {
-                            0
-                        }
-                    }
-                    0x421C => 0, // JOY3L (not implemented)
-                    0x421D => 0, // JOY3H (not implemented)
-                    0x421E => 0, // JOY4L (not implemented)
-                    0x421F => 0, // JOY4H (not implemented)
                     // $4212 - HVBJOY - H/V Blank and Joypad Status
                     0x4212 => {
                         // Bit 7: VBlank flag (set during VBlank period)
                         // Bit 6: HBlank flag (not implemented)
                         // Bit 0: Auto-joypad read in progress (0 = finished)
-                        if self.is_in_vblank() {
-                            0x80 // VBlank
-                        } else {
-                            0x00 // Not in VBlank
-                        }
+                        let vblank = if self.is_in_vblank() { 0x80 } else { 0x00 };
+                        // The auto-read takes about three scanlines from the start of VBlank
+                        let busy = self.auto_joypad_enable
+                            && (VBLANK_START_CYCLE..VBLANK_START_CYCLE + AUTO_JOYPAD_CYCLES)
+                                .contains(&self.frame_cycle);
+                        vblank | busy as u8
                     }
                     // Other hardware registers
                     0x2000..=0x5FFF => {
@@ -274,6 +454,8 @@ impl Memory65c816 for SnesBus {
                     0x0000..=0x1FFF => self.wram[offset as usize] = val,
                     // $2100-$213F - PPU registers
                     0x2100..=0x213F => self.ppu.write_register(offset, val),
+                    // $2140-$217F - APU ports (four registers, mirrored)
+                    0x2140..=0x217F => self.apu.write_port(offset as usize, val),
                     // $4200 - NMITIMEN - Interrupt Enable and Joypad Request
                     0x4200 => {
                         // Bit 7: NMI enable
@@ -328,6 +510,13 @@ impl Memory65c816 for SnesBus {
                             self.controller_shift[1].set(self.controller_state[1]);
                         }
                     }
+                    // $420B - MDMAEN - Start general DMA on the selected channels
+                    0x420B => self.start_dma(val),
+                    // $420C - HDMAEN - Channels running HDMA
+                    0x420C => self.hdma_enable = val,
+                    // $4300-$437F - DMA channel registers
+                    0x4300..=0x437F => self.dma_channels[(offset as usize >> 4) & 0x07]
+                        .write(offset as u8 & 0x0F, val),
                     // Other hardware registers
                     0x2000..=0x5FFF => {} // Stub - ignore writes
                     // WRAM (full at $6000-$7FFF in banks $00-$3F)
@@ -369,11 +558,19 @@ mod tests {
         // Set controller 1 state: B button (bit 15)
         bus.set_controller(0, 0x8000);
 
-        // Read auto-joypad registers
+        // JOY1 holds the last auto-read, not the live buttons
+        assert_eq!(bus.read(0x4219), 0x00);
+        bus.auto_joypad_read();
         let joy1l = bus.read(0x4218);
         let joy1h = bus.read(0x4219);
         assert_eq!(joy1l, 0x00);
         assert_eq!(joy1h, 0x80); // B button
+
+        // Disabled auto-read keeps the previous result
+        bus.write(0x4200, 0x00);
+        bus.set_controller(0, 0x0000);
+        bus.auto_joypad_read();
+        assert_eq!(bus.read(0x4219), 0x80);
     }
 
     #[test]
@@ -387,34 +584,37 @@ mod tests {
         bus.write(0x4016, 1);
         bus.write(0x4016, 0);
 
-        // Read bits serially (SNES sends LSB first)
+        // Read bits serially (SNES sends B first, i.e. MSB first)
         let mut bits_read = 0u16;
-        for i in 0..16 {
+        for _ in 0..16 {
             let bit = bus.read(0x4016) & 1;
-            bits_read |= (bit as u16) << i;
+            bits_read = (bits_read << 1) | bit as u16;
         }
 
         assert_eq!(bits_read, 0x0080); // Should match the A button state
+
+        // Past the 16 buttons the port reads 1s
+        assert_eq!(bus.read(0x4016) & 1, 1);
     }
 
     #[test]
     fn test_controller_strobe() {
         let mut bus = SnesBus::new();
 
-        // Set controller state
-        bus.set_controller(0, 0x1234);
+        // Set controller state: B pressed
+        bus.set_controller(0, 0x9234);
 
-        // Strobe on - should read current bit 0
+        // Strobe on - keeps returning the B button
         bus.write(0x4016, 1);
-        let bit_strobed = bus.read(0x4016) & 1;
-        assert_eq!(bit_strobed, 0); // bit 0 of 0x1234 is 0
+        assert_eq!(bus.read(0x4016) & 1, 1);
+        assert_eq!(bus.read(0x4016) & 1, 1);
 
         // Strobe off - latch and shift
         bus.write(0x4016, 0);
 
-        // Read first bit
-        let bit0 = bus.read(0x4016) & 1;
-        assert_eq!(bit0, 0); // LSB of 0x1234
+        // B, then Y (not pressed)
+        assert_eq!(bus.read(0x4016) & 1, 1);
+        assert_eq!(bus.read(0x4016) & 1, 0);
     }
 
     #[test]
@@ -426,6 +626,7 @@ mod tests {
         bus.set_controller(1, 0x5555);
 
         // Read auto-joypad registers
+        bus.auto_joypad_read();
         assert_eq!(bus.read(0x4218), 0xAA); // JOY1L
         assert_eq!(bus.read(0x4219), 0xAA); // JOY1H
         assert_eq!(bus.read(0x421A), 0x55); // JOY2L
@@ -439,7 +640,176 @@ mod tests {
         let bit1_0 = bus.read(0x4016) & 1;
         let bit2_0 = bus.read(0x4017) & 1;
 
-        assert_eq!(bit1_0, 0); // LSB of 0xAAAA
-        assert_eq!(bit2_0, 1); // LSB of 0x5555
+        assert_eq!(bit1_0, 1); // MSB of 0xAAAA
+        assert_eq!(bit2_0, 0); // MSB of 0x5555
+        assert_eq!(bus.read(0x4017) & 0x1C, 0x1C);
+    }
+
+    #[test]
+    fn test_auto_joypad_empties_serial_ports() {
+        let mut bus = SnesBus::new();
+        bus.set_controller(0, 0x0000);
+        bus.write(0x4016, 1);
+        bus.write(0x4016, 0);
+        assert_eq!(bus.read(0x4016) & 1, 0);
+
+        // The auto-read clocks all 16 bits out
+        bus.auto_joypad_read();
+        assert_eq!(bus.read(0x4016) & 1, 1);
+    }
+
+    #[test]
+    fn test_auto_joypad_busy_flag() {
+        let mut bus = SnesBus::new();
+        bus.tick_cycles(VBLANK_START_CYCLE - 1);
+        assert_eq!(bus.read(0x4212), 0x00);
+        bus.tick_cycles(1);
+        assert_eq!(bus.read(0x4212), 0x81);
+        bus.tick_cycles(AUTO_JOYPAD_CYCLES);
+        assert_eq!(bus.read(0x4212), 0x80);
+
+        // No busy flag with auto-read disabled
+        bus.tick_frame();
+        bus.write(0x4200, 0x00);
+        bus.tick_cycles(VBLANK_START_CYCLE);
+        assert_eq!(bus.read(0x4212), 0x80);
+    }
+
+    /// Read VRAM word `addr` through $2116/$2117 and $2139/$213A
+    fn read_vram_word(bus: &mut SnesBus, addr: u16) -> u16 {
+        bus.write(0x2116, addr as u8);
+        bus.write(0x2117, (addr >> 8) as u8);
+        u16::from_le_bytes([bus.read(0x2139), bus.read(0x213A)])
+    }
+
+    #[test]
+    fn test_general_dma_to_vram() {
+        let mut bus = SnesBus::new();
+        bus.write(0x2100, 0x80); // Forced blank: VRAM is writable
+        bus.write(0x2115, 0x80); // Increment after the high byte
+        bus.write(0x2116, 0x00);
+        bus.write(0x2117, 0x10);
+        for (i, val) in [0x11, 0x22, 0x33, 0x44, 0x55, 0x66].into_iter().enumerate() {
+            bus.write(0x7E2000 + i as u32, val);
+        }
+
+        // Channel 3, mode 1 ($2118/$2119 alternating), 6 bytes from $7E:2000
+        bus.write(0x4330, 0x01);
+        bus.write(0x4331, 0x18);
+        bus.write(0x4332, 0x00);
+        bus.write(0x4333, 0x20);
+        bus.write(0x4334, 0x7E);
+        bus.write(0x4335, 0x06);
+        bus.write(0x4336, 0x00);
+        bus.write(0x420B, 0x08);
+
+        assert_eq!(read_vram_word(&mut bus, 0x1000), 0x2211);
+        assert_eq!(read_vram_word(&mut bus, 0x1001), 0x4433);
+        assert_eq!(read_vram_word(&mut bus, 0x1002), 0x6655);
+
+        // The count runs down to zero and the A-bus address moves on
+        assert_eq!(bus.read(0x4335), 0x00);
+        assert_eq!(bus.read(0x4336), 0x00);
+        assert_eq!(bus.read(0x4332), 0x06);
+        assert_eq!(bus.read(0x4333), 0x20);
+        assert_eq!(
+            bus.take_dma_cycles(),
+            DMA_CHANNEL_CYCLES + 6 * DMA_BYTE_CYCLES
+        );
+        assert_eq!(bus.take_dma_cycles(), 0);
+    }
+
+    #[test]
+    fn test_general_dma_b_to_a_decrement() {
+        let mut bus = SnesBus::new();
+        bus.write(0x2100, 0x80);
+        bus.write(0x2116, 0x00);
+        bus.write(0x2117, 0x00);
+        bus.write(0x2118, 0xAB);
+        bus.write(0x2116, 0x00);
+
+        // Channel 0, B->A, decrement, mode 0 from $2139 into $00:0103 downwards
+        bus.write(0x4300, 0x90);
+        bus.write(0x4301, 0x39);
+        bus.write(0x4302, 0x03);
+        bus.write(0x4303, 0x01);
+        bus.write(0x4304, 0x00);
+        bus.write(0x4305, 0x03);
+        bus.write(0x4306, 0x00);
+        bus.write(0x420B, 0x01);
+
+        assert_eq!(bus.read(0x0100), 0x00);
+        assert_eq!(bus.read(0x0101), 0xAB);
+        assert_eq!(bus.read(0x0102), 0xAB);
+        assert_eq!(bus.read(0x0103), 0xAB);
+        assert_eq!(bus.read(0x4302), 0x00);
+    }
+
+    #[test]
+    fn test_hdma_direct_table() {
+        let mut bus = SnesBus::new();
+        bus.write(0x2100, 0x80);
+        bus.write(0x2115, 0x80);
+        bus.write(0x2116, 0x00);
+        bus.write(0x2117, 0x00);
+
+        // Two lines with one transfer, then two lines transferring every
+        // line (repeat bit), then the end marker
+        let table = [0x02, 0x01, 0x10, 0x82, 0x02, 0x20, 0x03, 0x30, 0x00];
+        for (i, val) in table.into_iter().enumerate() {
+            bus.write(0x7E3000 + i as u32, val);
+        }
+        bus.write(0x4370, 0x01);
+        bus.write(0x4371, 0x18);
+        bus.write(0x4372, 0x00);
+        bus.write(0x4373, 0x30);
+        bus.write(0x4374, 0x7E);
+        bus.write(0x420C, 0x80);
+
+        bus.hdma_init();
+        for _ in 0..6 {
+            bus.hdma_scanline();
+        }
+
+        assert_eq!(read_vram_word(&mut bus, 0), 0x1001);
+        assert_eq!(read_vram_word(&mut bus, 1), 0x2002);
+        assert_eq!(read_vram_word(&mut bus, 2), 0x3003);
+        assert_eq!(read_vram_word(&mut bus, 3), 0x0000);
+        assert_eq!(bus.read(0x4378), 0x09);
+        assert_eq!(bus.read(0x437A), 0x00);
+    }
+
+    #[test]
+    fn test_hdma_indirect_table() {
+        let mut bus = SnesBus::new();
+        bus.write(0x2100, 0x80);
+        bus.write(0x2115, 0x80);
+        bus.write(0x2116, 0x00);
+        bus.write(0x2117, 0x00);
+
+        // One entry of three repeated lines whose data lives at $7E:4000
+        for (i, val) in [0x83, 0x00, 0x40, 0x00].into_iter().enumerate() {
+            bus.write(0x7E3000 + i as u32, val);
+        }
+        for (i, val) in [0x01, 0x02, 0x03, 0x04, 0x05, 0x06].into_iter().enumerate() {
+            bus.write(0x7E4000 + i as u32, val);
+        }
+        bus.write(0x4310, 0x41); // Indirect, mode 1
+        bus.write(0x4311, 0x18);
+        bus.write(0x4312, 0x00);
+        bus.write(0x4313, 0x30);
+        bus.write(0x4314, 0x7E);
+        bus.write(0x4317, 0x7E);
+        bus.write(0x420C, 0x02);
+
+        bus.hdma_init();
+        for _ in 0..4 {
+            bus.hdma_scanline();
+        }
+
+        assert_eq!(read_vram_word(&mut bus, 0), 0x0201);
+        assert_eq!(read_vram_word(&mut bus, 1), 0x0403);
+        assert_eq!(read_vram_word(&mut bus, 2), 0x0605);
+        assert_eq!(read_vram_word(&mut bus, 3), 0x0000);
     }
 }
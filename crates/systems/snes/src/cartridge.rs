@@ -1,8 +1,16 @@
 //! SNES cartridge implementation
 
 use crate::SnesError;
+use emu_core::apu::TimingMode;
 use emu_core::logging::{log, LogCategory, LogLevel};
 
+/// Offset of the internal header in LoROM images
+const LOROM_HEADER: usize = 0x7FC0;
+/// Offset of the internal header in HiROM images
+const HIROM_HEADER: usize = 0xFFC0;
+/// Offset of the destination/region code within the internal header
+const REGION_OFFSET: usize = 0x19;
+
 /// SNES cartridge
 pub struct Cartridge {
     /// ROM data
@@ -11,6 +19,8 @@ pub struct Cartridge {
     ram: Vec<u8>,
     /// Header offset (512 bytes if SMC header present)
     header_offset: usize,
+    /// Destination/region code from the internal header ($xFD9)
+    region: u8,
 }
 
 impl Cartridge {
@@ -45,11 +55,15 @@ impl Cartridge {
             ));
         }
 
+        let region = rom_data[Self::find_internal_header(rom_data) + REGION_OFFSET];
+
         log(LogCategory::Bus, LogLevel::Info, || {
             format!(
-                "SNES Cartridge: Loaded ROM - Size: {} KB, SMC Header: {}",
+                "SNES Cartridge: Loaded ROM - Size: {} KB, SMC Header: {}, Region: ${:02X} ({:?})",
                 rom_data.len() / 1024,
-                if header_offset > 0 { "Yes" } else { "No" }
+                if header_offset > 0 { "Yes" } else { "No" },
+                region,
+                Self::timing_for_region(region)
             )
         });
 
@@ -57,9 +71,51 @@ impl Cartridge {
             rom: rom_data.to_vec(),
             ram: vec![0; 0x8000], // 32KB SRAM (standard size)
             header_offset,
+            region,
         })
     }
 
+    /// Locate the internal header, preferring the location whose checksum
+    /// and checksum complement add up to $FFFF. Defaults to LoROM.
+    fn find_internal_header(rom: &[u8]) -> usize {
+        let checksum_valid = |base: usize| {
+            if rom.len() < base + 0x20 {
+                return false;
+            }
+            let complement = u16::from_le_bytes([rom[base + 0x1C], rom[base + 0x1D]]);
+            let checksum = u16::from_le_bytes([rom[base + 0x1E], rom[base + 0x1F]]);
+            checksum ^ complement == 0xFFFF
+        };
+
+        if !checksum_valid(LOROM_HEADER) && checksum_valid(HIROM_HEADER) {
+            HIROM_HEADER
+        } else {
+            LOROM_HEADER
+        }
+    }
+
+    /// Map a header region code to its video standard.
+    ///
+    /// Codes $02-$0C (Europe, Scandinavia, France, Netherlands, Spain,
+    /// Germany, Italy, China, Indonesia) and $11 (Australia) are PAL;
+    /// everything else (Japan, North America, Korea, Canada, Brazil) is NTSC.
+    pub fn timing_for_region(region: u8) -> TimingMode {
+        match region {
+            0x02..=0x0C | 0x11 => TimingMode::Pal,
+            _ => TimingMode::Ntsc,
+        }
+    }
+
+    /// Region code from the cartridge header
+    pub fn region(&self) -> u8 {
+        self.region
+    }
+
+    /// Video standard implied by the cartridge header region code
+    pub fn timing(&self) -> TimingMode {
+        Self::timing_for_region(self.region)
+    }
+
     pub fn read(&self, addr: u32) -> u8 {
         let bank = (addr >> 16) as u8;
         let offset = (addr & 0xFFFF) as u16;
@@ -144,6 +200,38 @@ mod tests {
         assert_eq!(cart.rom.len(), 0x8000);
     }
 
+    #[test]
+    fn test_region_from_lorom_header() {
+        let mut data = vec![0; 0x8000];
+        data[LOROM_HEADER + REGION_OFFSET] = 0x02; // Europe
+
+        let cart = Cartridge::load(&data).unwrap();
+        assert_eq!(cart.region(), 0x02);
+        assert_eq!(cart.timing(), TimingMode::Pal);
+    }
+
+    #[test]
+    fn test_region_from_hirom_header() {
+        let mut data = vec![0; 0x10000];
+        data[HIROM_HEADER + REGION_OFFSET] = 0x09; // Germany
+        data[HIROM_HEADER + 0x1C] = 0xFF; // Complement $FFFF, checksum $0000
+        data[HIROM_HEADER + 0x1D] = 0xFF;
+
+        let cart = Cartridge::load(&data).unwrap();
+        assert_eq!(cart.region(), 0x09);
+        assert_eq!(cart.timing(), TimingMode::Pal);
+    }
+
+    #[test]
+    fn test_timing_for_region() {
+        assert_eq!(Cartridge::timing_for_region(0x00), TimingMode::Ntsc); // Japan
+        assert_eq!(Cartridge::timing_for_region(0x01), TimingMode::Ntsc); // North America
+        assert_eq!(Cartridge::timing_for_region(0x0C), TimingMode::Pal); // Indonesia
+        assert_eq!(Cartridge::timing_for_region(0x0D), TimingMode::Ntsc); // Korea
+        assert_eq!(Cartridge::timing_for_region(0x10), TimingMode::Ntsc); // Brazil
+        assert_eq!(Cartridge::timing_for_region(0x11), TimingMode::Pal); // Australia
+    }
+
     #[test]
     fn test_read_rom() {
         let mut data = vec![0; 0x8000];
@@ -19,8 +19,10 @@ impl SnesCpu {
         self.cpu.reset();
     }
 
+    /// Run one instruction; the CPU cycles include any time it was halted
+    /// for DMA or HDMA since the last step
     pub fn step(&mut self) -> u32 {
-        self.cpu.step()
+        self.cpu.step() + self.cpu.memory.take_dma_cycles()
     }
 
     pub fn bus(&self) -> &SnesBus {
@@ -0,0 +1,190 @@
+//! SNES DMA controller
+//!
+//! Eight channels, each programmed through $43x0-$43xA, move data between
+//! the A-bus (WRAM and cartridge space) and the B-bus registers at
+//! $2100-$21FF. General DMA runs all at once when channels are started via
+//! MDMAEN ($420B), halting the CPU. HDMA channels enabled in HDMAEN ($420C)
+//! instead walk a table once per frame and write a few bytes in each
+//! scanline's HBlank, which is how games change scroll, color and window
+//! registers partway down the screen.
+//!
+//! This module holds the channel registers and table state; the transfers
+//! themselves run on the bus, which owns both sides of the copy.
+
+/// Number of DMA channels
+pub const DMA_CHANNELS: usize = 8;
+
+/// B-bus register offsets written for each transfer unit, by DMAP mode
+/// (bits 0-2). Modes 6 and 7 repeat modes 2 and 3.
+const TRANSFER_PATTERNS: [&[u8]; 8] = [
+    &[0],
+    &[0, 1],
+    &[0, 0],
+    &[0, 0, 1, 1],
+    &[0, 1, 2, 3],
+    &[0, 1, 0, 1],
+    &[0, 0],
+    &[0, 0, 1, 1],
+];
+
+/// One DMA/HDMA channel
+#[derive(Debug, Clone, Copy)]
+pub struct DmaChannel {
+    /// $43x0 DMAP: direction, HDMA indirect, address step and transfer mode
+    pub params: u8,
+    /// $43x1 BBAD: B-bus register ($21xx)
+    pub b_address: u8,
+    /// $43x2-$43x3 A1T: A-bus address (HDMA table start)
+    pub a_address: u16,
+    /// $43x4 A1B: A-bus bank (HDMA table bank)
+    pub a_bank: u8,
+    /// $43x5-$43x6 DAS: byte count (HDMA indirect address)
+    pub count: u16,
+    /// $43x7 DASB: HDMA indirect data bank
+    pub indirect_bank: u8,
+    /// $43x8-$43x9 A2A: current HDMA table address
+    pub table_address: u16,
+    /// $43xA NTRL: HDMA line counter and repeat flag
+    pub line_counter: u8,
+    /// $43xB/$43xF: unused read/write byte
+    pub unused: u8,
+    /// HDMA: transfer on the next scanline
+    pub hdma_do_transfer: bool,
+    /// HDMA: the table's end marker has been reached this frame
+    pub hdma_terminated: bool,
+}
+
+impl DmaChannel {
+    pub fn new() -> Self {
+        Self {
+            params: 0xFF,
+            b_address: 0xFF,
+            a_address: 0xFFFF,
+            a_bank: 0xFF,
+            count: 0xFFFF,
+            indirect_bank: 0xFF,
+            table_address: 0xFFFF,
+            line_counter: 0xFF,
+            unused: 0xFF,
+            hdma_do_transfer: false,
+            hdma_terminated: true,
+        }
+    }
+
+    /// B-bus to A-bus transfer (DMAP bit 7)
+    pub fn b_to_a(&self) -> bool {
+        self.params & 0x80 != 0
+    }
+
+    /// HDMA table holds pointers to the data (DMAP bit 6)
+    pub fn hdma_indirect(&self) -> bool {
+        self.params & 0x40 != 0
+    }
+
+    /// B-bus register offsets for one transfer unit
+    pub fn pattern(&self) -> &'static [u8] {
+        TRANSFER_PATTERNS[(self.params & 0x07) as usize]
+    }
+
+    /// Signed A-bus address step after each general DMA byte
+    /// (DMAP bit 3: fixed, bit 4: decrement)
+    pub fn a_step(&self) -> u16 {
+        match self.params & 0x18 {
+            0x00 => 1,
+            0x10 => 0xFFFF,
+            _ => 0,
+        }
+    }
+
+    /// Read a channel register (`reg` is the low nibble of $43xR)
+    pub fn read(&self, reg: u8) -> u8 {
+        match reg {
+            0x0 => self.params,
+            0x1 => self.b_address,
+            0x2 => self.a_address as u8,
+            0x3 => (self.a_address >> 8) as u8,
+            0x4 => self.a_bank,
+            0x5 => self.count as u8,
+            0x6 => (self.count >> 8) as u8,
+            0x7 => self.indirect_bank,
+            0x8 => self.table_address as u8,
+            0x9 => (self.table_address >> 8) as u8,
+            0xA => self.line_counter,
+            0xB | 0xF => self.unused,
+            // $43xC-$43xE are open bus
+            _ => 0,
+        }
+    }
+
+    /// Write a channel register (`reg` is the low nibble of $43xR)
+    pub fn write(&mut self, reg: u8, val: u8) {
+        match reg {
+            0x0 => self.params = val,
+            0x1 => self.b_address = val,
+            0x2 => self.a_address = (self.a_address & 0xFF00) | val as u16,
+            0x3 => self.a_address = (self.a_address & 0x00FF) | (val as u16) << 8,
+            0x4 => self.a_bank = val,
+            0x5 => self.count = (self.count & 0xFF00) | val as u16,
+            0x6 => self.count = (self.count & 0x00FF) | (val as u16) << 8,
+            0x7 => self.indirect_bank = val,
+            0x8 => self.table_address = (self.table_address & 0xFF00) | val as u16,
+            0x9 => self.table_address = (self.table_address & 0x00FF) | (val as u16) << 8,
+            0xA => self.line_counter = val,
+            0xB | 0xF => self.unused = val,
+            _ => {}
+        }
+    }
+}
+
+impl Default for DmaChannel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_channel_registers() {
+        let mut channel = DmaChannel::new();
+        for (reg, val) in (0x0..=0xA).zip(0x10..) {
+            channel.write(reg, val);
+        }
+        assert_eq!(channel.params, 0x10);
+        assert_eq!(channel.b_address, 0x11);
+        assert_eq!(channel.a_address, 0x1312);
+        assert_eq!(channel.a_bank, 0x14);
+        assert_eq!(channel.count, 0x1615);
+        assert_eq!(channel.indirect_bank, 0x17);
+        assert_eq!(channel.table_address, 0x1918);
+        assert_eq!(channel.line_counter, 0x1A);
+        for (reg, val) in (0x0..=0xA).zip(0x10..) {
+            assert_eq!(channel.read(reg), val);
+        }
+
+        // $43xB and $43xF share one byte
+        channel.write(0xB, 0x42);
+        assert_eq!(channel.read(0xF), 0x42);
+    }
+
+    #[test]
+    fn test_transfer_modes_and_steps() {
+        let mut channel = DmaChannel::new();
+        channel.params = 0x01;
+        assert_eq!(channel.pattern(), &[0, 1]);
+        assert_eq!(channel.a_step(), 1);
+        assert!(!channel.b_to_a());
+
+        channel.params = 0x93; // B->A, decrement, mode 3
+        assert_eq!(channel.pattern(), &[0, 0, 1, 1]);
+        assert_eq!(channel.a_step(), 0xFFFF);
+        assert!(channel.b_to_a());
+
+        channel.params = 0x4C; // Indirect HDMA, fixed, mode 4
+        assert_eq!(channel.pattern(), &[0, 1, 2, 3]);
+        assert_eq!(channel.a_step(), 0);
+        assert!(channel.hdma_indirect());
+    }
+}
@@ -0,0 +1,626 @@
+//! SNES S-DSP (sound generator)
+//!
+//! The S-DSP mixes eight voices of BRR-compressed samples from the shared
+//! 64KB audio RAM into a 32kHz stereo stream. Each voice has its own pitch,
+//! volume and ADSR or GAIN envelope; voices can be replaced by the noise
+//! generator, frequency-modulated by the previous voice, and fed into an
+//! echo unit that keeps a delay line in audio RAM and filters it through an
+//! 8-tap FIR filter.
+//!
+//! The SPC700 reaches the 128 registers through $F2 (address) and $F3
+//! (data). The mixer works one output sample at a time rather than in the
+//! hardware's 32-step pipeline, and resamples with linear interpolation
+//! instead of the Gaussian filter.
+
+/// Output sample rate in Hz
+pub const DSP_SAMPLE_RATE: u32 = 32000;
+
+/// Number of voices
+const VOICES: usize = 8;
+
+// Global registers
+const REG_MVOLL: usize = 0x0C;
+const REG_MVOLR: usize = 0x1C;
+const REG_EVOLL: usize = 0x2C;
+const REG_EVOLR: usize = 0x3C;
+const REG_KON: usize = 0x4C;
+const REG_KOFF: usize = 0x5C;
+const REG_FLG: usize = 0x6C;
+const REG_ENDX: usize = 0x7C;
+const REG_EFB: usize = 0x0D;
+const REG_PMON: usize = 0x2D;
+const REG_NON: usize = 0x3D;
+const REG_EON: usize = 0x4D;
+const REG_DIR: usize = 0x5D;
+const REG_ESA: usize = 0x6D;
+const REG_EDL: usize = 0x7D;
+
+// Per-voice registers (offset from $x0)
+const VREG_VOLL: usize = 0x0;
+const VREG_VOLR: usize = 0x1;
+const VREG_PITCHL: usize = 0x2;
+const VREG_PITCHH: usize = 0x3;
+const VREG_SRCN: usize = 0x4;
+const VREG_ADSR1: usize = 0x5;
+const VREG_ADSR2: usize = 0x6;
+const VREG_GAIN: usize = 0x7;
+const VREG_ENVX: usize = 0x8;
+const VREG_OUTX: usize = 0x9;
+
+// FLG bits
+const FLG_RESET: u8 = 0x80;
+const FLG_MUTE: u8 = 0x40;
+const FLG_ECHO_DISABLE: u8 = 0x20;
+
+/// Samples between envelope and noise steps for each of the 32 rates
+/// (rate 0 never steps)
+const RATE_PERIODS: [u16; 32] = [
+    0, 2048, 1536, 1280, 1024, 768, 640, 512, 384, 320, 256, 192, 160, 128, 96, 80, 64, 48, 40, 32,
+    24, 20, 16, 12, 10, 8, 6, 5, 4, 3, 2, 1,
+];
+
+/// Maximum envelope level (11 bits)
+const ENV_MAX: i32 = 0x7FF;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EnvelopeMode {
+    Attack,
+    Decay,
+    Sustain,
+    Release,
+}
+
+/// Playback state of one voice
+#[derive(Debug, Clone, Copy)]
+struct Voice {
+    /// Address of the BRR block being played
+    brr_addr: u16,
+    /// Decoded samples of the current block
+    block: [i16; 16],
+    /// Next sample to take from `block`
+    block_pos: usize,
+    /// Header flags of the current block: end and loop
+    block_end: bool,
+    block_loop: bool,
+    /// Last two decoded samples, for the BRR prediction filters
+    history: [i16; 2],
+    /// Samples being interpolated between (older, newer)
+    interp: [i16; 2],
+    /// Position between the interpolated samples (12-bit fraction)
+    pitch_counter: u32,
+    /// Envelope level (0-$7FF)
+    env: i32,
+    env_mode: EnvelopeMode,
+    /// Samples since the last envelope step
+    env_counter: u16,
+    /// Output of the last sample, for pitch modulation of the next voice
+    output: i32,
+}
+
+impl Voice {
+    fn new() -> Self {
+        Self {
+            brr_addr: 0,
+            block: [0; 16],
+            block_pos: 0,
+            block_end: false,
+            block_loop: false,
+            history: [0; 2],
+            interp: [0; 2],
+            pitch_counter: 0,
+            env: 0,
+            env_mode: EnvelopeMode::Release,
+            env_counter: 0,
+            output: 0,
+        }
+    }
+
+    /// Decode the 9-byte BRR block at `brr_addr`
+    fn decode_block(&mut self, ram: &[u8]) {
+        let header = ram[self.brr_addr as usize];
+        let shift = header >> 4;
+        let filter = (header >> 2) & 0x03;
+        self.block_loop = header & 0x02 != 0;
+        self.block_end = header & 0x01 != 0;
+
+        for i in 0..16 {
+            let byte = ram[self.brr_addr.wrapping_add(1 + i as u16 / 2) as usize];
+            let nibble = if i % 2 == 0 { byte >> 4 } else { byte & 0x0F };
+            // Sign-extend the nibble
+            let mut s = ((nibble as i32) << 28) >> 28;
+            s = if shift <= 12 {
+                (s << shift) >> 1
+            } else {
+                // Shifts 13-15 only keep the sign
+                if s < 0 {
+                    -2048
+                } else {
+                    0
+                }
+            };
+
+            let p1 = self.history[0] as i32 >> 1;
+            let p2 = self.history[1] as i32 >> 1;
+            s += match filter {
+                0 => 0,
+                1 => p1 + ((-p1) >> 4),
+                2 => p1 * 2 + ((-p1 * 3) >> 5) - p2 + (p2 >> 4),
+                _ => p1 * 2 + ((-p1 * 13) >> 6) - p2 + ((p2 * 3) >> 4),
+            };
+
+            // Clamp to 16 bits, then keep 15 bits scaled back to 16
+            let s = ((s.clamp(-0x8000, 0x7FFF) << 1) as i16) as i32;
+            self.history = [s as i16, self.history[0]];
+            self.block[i] = s as i16;
+        }
+    }
+
+    /// Start the sample `srcn` from the sample directory at `dir`
+    fn key_on(&mut self, ram: &[u8], dir: u16, srcn: u8) {
+        let entry = dir.wrapping_add(srcn as u16 * 4);
+        self.brr_addr = read_word(ram, entry);
+        self.history = [0; 2];
+        self.interp = [0; 2];
+        self.pitch_counter = 0;
+        self.env = 0;
+        self.env_mode = EnvelopeMode::Attack;
+        self.env_counter = 0;
+        self.block_pos = 0;
+        self.decode_block(ram);
+    }
+
+    /// Step to the next BRR sample, following the loop point at block
+    /// ends. Returns true when an end block has been passed.
+    fn next_sample(&mut self, ram: &[u8], dir: u16, srcn: u8) -> bool {
+        let sample = self.block[self.block_pos];
+        self.interp = [self.interp[1], sample];
+        self.block_pos += 1;
+        if self.block_pos < 16 {
+            return false;
+        }
+
+        self.block_pos = 0;
+        let ended = self.block_end;
+        if ended {
+            let entry = dir.wrapping_add(srcn as u16 * 4);
+            self.brr_addr = read_word(ram, entry.wrapping_add(2));
+            if !self.block_loop {
+                self.env = 0;
+                self.env_mode = EnvelopeMode::Release;
+            }
+        } else {
+            self.brr_addr = self.brr_addr.wrapping_add(9);
+        }
+        self.decode_block(ram);
+        ended
+    }
+
+    /// Advance the ADSR or GAIN envelope by one sample
+    fn step_envelope(&mut self, adsr1: u8, adsr2: u8, gain: u8) {
+        if self.env_mode == EnvelopeMode::Release {
+            self.env = (self.env - 8).max(0);
+            return;
+        }
+
+        let (rate, step) = if adsr1 & 0x80 != 0 {
+            match self.env_mode {
+                EnvelopeMode::Attack => {
+                    let rate = (adsr1 & 0x0F) * 2 + 1;
+                    (rate, if rate == 31 { 1024 } else { 32 })
+                }
+                EnvelopeMode::Decay => {
+                    let rate = ((adsr1 >> 4) & 0x07) * 2 + 16;
+                    (rate, -(((self.env - 1) >> 8) + 1))
+                }
+                _ => (adsr2 & 0x1F, -(((self.env - 1) >> 8) + 1)),
+            }
+        } else if gain & 0x80 == 0 {
+            // Direct gain
+            self.env = (gain as i32 & 0x7F) * 16;
+            return;
+        } else {
+            let rate = gain & 0x1F;
+            let step = match (gain >> 5) & 0x03 {
+                0 => -32,
+                1 => -(((self.env - 1) >> 8) + 1),
+                2 => 32,
+                _ => {
+                    if self.env < 0x600 {
+                        32
+                    } else {
+                        8
+                    }
+                }
+            };
+            (rate, step)
+        };
+
+        let period = RATE_PERIODS[rate as usize];
+        if period == 0 {
+            return;
+        }
+        self.env_counter += 1;
+        if self.env_counter < period {
+            return;
+        }
+        self.env_counter = 0;
+        self.env = (self.env + step).clamp(0, ENV_MAX);
+
+        if adsr1 & 0x80 != 0 {
+            match self.env_mode {
+                EnvelopeMode::Attack if self.env >= 0x7E0 => {
+                    self.env_mode = EnvelopeMode::Decay;
+                }
+                EnvelopeMode::Decay if (self.env >> 8) as u8 == adsr2 >> 5 => {
+                    self.env_mode = EnvelopeMode::Sustain;
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+fn read_word(ram: &[u8], addr: u16) -> u16 {
+    ram[addr as usize] as u16 | (ram[addr.wrapping_add(1) as usize] as u16) << 8
+}
+
+fn clamp16(val: i32) -> i32 {
+    val.clamp(-0x8000, 0x7FFF)
+}
+
+/// SNES S-DSP state
+pub struct Dsp {
+    /// Register file ($00-$7F)
+    regs: [u8; 128],
+    voices: [Voice; VOICES],
+    /// Voices keyed on since the last sample
+    pending_kon: u8,
+    /// Noise generator shift register (15 bits)
+    noise: i32,
+    noise_counter: u16,
+    /// Byte offset into the echo buffer
+    echo_pos: u16,
+    /// Last eight echo buffer samples per channel, oldest first
+    echo_history: [[i32; 2]; 8],
+}
+
+impl Dsp {
+    pub fn new() -> Self {
+        let mut dsp = Self {
+            regs: [0; 128],
+            voices: [Voice::new(); VOICES],
+            pending_kon: 0,
+            noise: 0x4000,
+            noise_counter: 0,
+            echo_pos: 0,
+            echo_history: [[0; 2]; 8],
+        };
+        dsp.reset();
+        dsp
+    }
+
+    /// Power-on state: soft reset, muted, echo writes disabled
+    pub fn reset(&mut self) {
+        self.regs[REG_FLG] = FLG_RESET | FLG_MUTE | FLG_ECHO_DISABLE;
+        self.regs[REG_KON] = 0;
+        self.regs[REG_KOFF] = 0;
+        self.regs[REG_ENDX] = 0;
+        self.voices = [Voice::new(); VOICES];
+        self.pending_kon = 0;
+        self.noise = 0x4000;
+        self.noise_counter = 0;
+        self.echo_pos = 0;
+        self.echo_history = [[0; 2]; 8];
+    }
+
+    /// Read a DSP register ($80-$FF mirror $00-$7F)
+    pub fn read(&self, addr: u8) -> u8 {
+        self.regs[(addr & 0x7F) as usize]
+    }
+
+    /// Write a DSP register (writes to $80-$FF are ignored)
+    pub fn write(&mut self, addr: u8, val: u8) {
+        let addr = addr as usize;
+        if addr >= 0x80 {
+            return;
+        }
+        match addr {
+            REG_KON => self.pending_kon |= val,
+            // Writing ENDX clears every flag
+            REG_ENDX => {
+                self.regs[REG_ENDX] = 0;
+                return;
+            }
+            _ => {}
+        }
+        self.regs[addr] = val;
+    }
+
+    fn voice_reg(&self, voice: usize, reg: usize) -> u8 {
+        self.regs[voice << 4 | reg]
+    }
+
+    /// Generate one stereo sample (left, right), reading samples from and
+    /// writing echo data to the audio RAM
+    pub fn generate_sample(&mut self, ram: &mut [u8]) -> (i16, i16) {
+        let flg = self.regs[REG_FLG];
+        let dir = (self.regs[REG_DIR] as u16) << 8;
+
+        // Soft reset holds every voice silent
+        if flg & FLG_RESET != 0 {
+            for voice in &mut self.voices {
+                voice.env = 0;
+                voice.env_mode = EnvelopeMode::Release;
+            }
+        }
+
+        // Key on
+        let kon = std::mem::take(&mut self.pending_kon);
+        for v in 0..VOICES {
+            if kon & (1 << v) != 0 {
+                let srcn = self.voice_reg(v, VREG_SRCN);
+                self.voices[v].key_on(ram, dir, srcn);
+                self.regs[REG_ENDX] &= !(1 << v);
+            }
+        }
+
+        // Key off
+        let koff = self.regs[REG_KOFF];
+        for (v, voice) in self.voices.iter_mut().enumerate() {
+            if koff & (1 << v) != 0 {
+                voice.env_mode = EnvelopeMode::Release;
+            }
+        }
+
+        // Noise generator
+        let noise_period = RATE_PERIODS[(flg & 0x1F) as usize];
+        if noise_period != 0 {
+            self.noise_counter += 1;
+            if self.noise_counter >= noise_period {
+                self.noise_counter = 0;
+                let feedback = (self.noise << 13) ^ (self.noise << 14);
+                self.noise = (feedback & 0x4000) ^ (self.noise >> 1);
+            }
+        }
+        let noise_sample = ((self.noise << 1) as i16) as i32;
+
+        let pmon = self.regs[REG_PMON];
+        let non = self.regs[REG_NON];
+        let eon = self.regs[REG_EON];
+        let (mut main_l, mut main_r) = (0i32, 0i32);
+        let (mut echo_l, mut echo_r) = (0i32, 0i32);
+        let mut prev_output = 0;
+
+        for v in 0..VOICES {
+            let mask = 1u8 << v;
+            let adsr1 = self.voice_reg(v, VREG_ADSR1);
+            let adsr2 = self.voice_reg(v, VREG_ADSR2);
+            let gain = self.voice_reg(v, VREG_GAIN);
+            let srcn = self.voice_reg(v, VREG_SRCN);
+            let pitch = (self.voice_reg(v, VREG_PITCHL) as i32
+                | (self.voice_reg(v, VREG_PITCHH) as i32) << 8)
+                & 0x3FFF;
+            let vol_l = self.voice_reg(v, VREG_VOLL) as i8 as i32;
+            let vol_r = self.voice_reg(v, VREG_VOLR) as i8 as i32;
+
+            let voice = &mut self.voices[v];
+            voice.step_envelope(adsr1, adsr2, gain);
+
+            let sample = if non & mask != 0 {
+                noise_sample
+            } else {
+                let frac = (voice.pitch_counter & 0x0FFF) as i32;
+                let [older, newer] = voice.interp.map(|s| s as i32);
+                older + (((newer - older) * frac) >> 12)
+            };
+            let output = (sample * voice.env) >> 11;
+            voice.output = output;
+
+            // Advance through the sample at the voice's pitch, modulated by
+            // the previous voice's output when PMON selects it
+            let mut step = pitch;
+            if v > 0 && pmon & mask != 0 {
+                step += ((prev_output >> 5) * pitch) >> 10;
+            }
+            voice.pitch_counter += step.clamp(0, 0x3FFF) as u32;
+            while voice.pitch_counter >= 0x1000 {
+                voice.pitch_counter -= 0x1000;
+                if voice.next_sample(ram, dir, srcn) {
+                    self.regs[REG_ENDX] |= mask;
+                }
+            }
+
+            self.regs[v << 4 | VREG_ENVX] = (voice.env >> 4) as u8;
+            self.regs[v << 4 | VREG_OUTX] = (output >> 8) as u8;
+            prev_output = output;
+
+            let l = (output * vol_l) >> 7;
+            let r = (output * vol_r) >> 7;
+            main_l += l;
+            main_r += r;
+            if eon & mask != 0 {
+                echo_l += l;
+                echo_r += r;
+            }
+        }
+
+        // Echo: read the delay line, filter it, then write the new input
+        // with feedback
+        let esa = (self.regs[REG_ESA] as u16) << 8;
+        let echo_len = ((self.regs[REG_EDL] & 0x0F) as u16 * 2048).max(4);
+        let addr = esa.wrapping_add(self.echo_pos);
+        self.echo_history.rotate_left(1);
+        self.echo_history[7] = [
+            read_word(ram, addr) as i16 as i32 >> 1,
+            read_word(ram, addr.wrapping_add(2)) as i16 as i32 >> 1,
+        ];
+
+        let mut fir = [0i32; 2];
+        for (tap, history) in self.echo_history.iter().enumerate() {
+            let coefficient = self.regs[tap << 4 | 0x0F] as i8 as i32;
+            fir[0] += (history[0] * coefficient) >> 6;
+            fir[1] += (history[1] * coefficient) >> 6;
+        }
+        let fir = fir.map(|s| clamp16(s) & !1);
+
+        if flg & FLG_ECHO_DISABLE == 0 {
+            let efb = self.regs[REG_EFB] as i8 as i32;
+            for (channel, input) in [echo_l, echo_r].into_iter().enumerate() {
+                let val = clamp16(clamp16(input) + ((fir[channel] * efb) >> 7)) & !1;
+                let channel_addr = addr.wrapping_add(channel as u16 * 2);
+                ram[channel_addr as usize] = val as u8;
+                ram[channel_addr.wrapping_add(1) as usize] = (val >> 8) as u8;
+            }
+        }
+        self.echo_pos += 4;
+        if self.echo_pos >= echo_len {
+            self.echo_pos = 0;
+        }
+
+        if flg & FLG_MUTE != 0 {
+            return (0, 0);
+        }
+
+        let mvol_l = self.regs[REG_MVOLL] as i8 as i32;
+        let mvol_r = self.regs[REG_MVOLR] as i8 as i32;
+        let evol_l = self.regs[REG_EVOLL] as i8 as i32;
+        let evol_r = self.regs[REG_EVOLR] as i8 as i32;
+        let left = clamp16(clamp16((main_l * mvol_l) >> 7) + ((fir[0] * evol_l) >> 7));
+        let right = clamp16(clamp16((main_r * mvol_r) >> 7) + ((fir[1] * evol_r) >> 7));
+        (left as i16, right as i16)
+    }
+}
+
+impl Default for Dsp {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Audio RAM with a sample directory at $0100 whose entry 0 points to
+    /// a looping BRR sample at $0200
+    fn ram_with_sample(block: [u8; 9]) -> Vec<u8> {
+        let mut ram = vec![0; 0x10000];
+        ram[0x0100..0x0104].copy_from_slice(&[0x00, 0x02, 0x00, 0x02]);
+        ram[0x0200..0x0209].copy_from_slice(&block);
+        ram
+    }
+
+    /// DSP with voice 0 set up to play sample 0 at full volume
+    fn dsp_with_voice(adsr1: u8, adsr2: u8, gain: u8) -> Dsp {
+        let mut dsp = Dsp::new();
+        dsp.write(REG_FLG as u8, FLG_ECHO_DISABLE);
+        dsp.write(REG_DIR as u8, 0x01);
+        dsp.write(REG_MVOLL as u8, 0x7F);
+        dsp.write(REG_MVOLR as u8, 0x7F);
+        dsp.write(VREG_VOLL as u8, 0x7F);
+        dsp.write(VREG_VOLR as u8, 0x7F);
+        dsp.write(VREG_PITCHH as u8, 0x10); // One sample per output sample
+        dsp.write(VREG_ADSR1 as u8, adsr1);
+        dsp.write(VREG_ADSR2 as u8, adsr2);
+        dsp.write(VREG_GAIN as u8, gain);
+        dsp
+    }
+
+    #[test]
+    fn test_brr_decode_shift_and_filter() {
+        let mut ram = vec![0; 0x10000];
+        // Shift 12, filter 0: nibble 1 = 2048 (4096 at 16-bit scale),
+        // nibble -1 = -2048
+        ram[0..9].copy_from_slice(&[0xC0, 0x1F, 0, 0, 0, 0, 0, 0, 0]);
+        let mut voice = Voice::new();
+        voice.decode_block(&ram);
+        assert_eq!(voice.block[0], 4096);
+        assert_eq!(voice.block[1], -4096);
+        assert_eq!(voice.block[2], 0);
+
+        // Filter 1 carries 15/16 of the previous sample forward
+        ram[0] = 0xC4;
+        ram[1] = 0x10;
+        let mut voice = Voice::new();
+        voice.decode_block(&ram);
+        assert_eq!(voice.block[0], 4096);
+        assert_eq!(voice.block[1], 3840);
+        assert!(voice.block[2] < voice.block[1]);
+    }
+
+    #[test]
+    fn test_brr_end_and_loop_flags() {
+        // End + loop block
+        let mut ram = ram_with_sample([0xC3, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11]);
+        let mut dsp = dsp_with_voice(0x8F, 0xE0, 0);
+        dsp.write(REG_KON as u8, 0x01);
+        for _ in 0..20 {
+            dsp.generate_sample(&mut ram);
+        }
+        assert_eq!(dsp.read(REG_ENDX as u8) & 0x01, 0x01);
+        // Looping keeps the envelope up
+        assert!(dsp.voices[0].env > 0);
+
+        // Writing ENDX clears it
+        dsp.write(REG_ENDX as u8, 0xFF);
+        assert_eq!(dsp.read(REG_ENDX as u8), 0);
+    }
+
+    #[test]
+    fn test_adsr_attack_decay_sustain_release() {
+        let mut ram = ram_with_sample([0xC2, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11]);
+        // Fastest attack and decay, sustain level 4/8, no sustain decay
+        let mut dsp = dsp_with_voice(0xFF, 0x80, 0);
+        dsp.write(REG_KON as u8, 0x01);
+        for _ in 0..4 {
+            dsp.generate_sample(&mut ram);
+        }
+        assert_eq!(dsp.voices[0].env_mode, EnvelopeMode::Decay);
+        for _ in 0..300 {
+            dsp.generate_sample(&mut ram);
+        }
+        assert_eq!(dsp.voices[0].env_mode, EnvelopeMode::Sustain);
+        assert_eq!(dsp.voices[0].env >> 8, 4);
+        assert_eq!(dsp.read(VREG_ENVX as u8), (dsp.voices[0].env >> 4) as u8);
+
+        dsp.write(REG_KOFF as u8, 0x01);
+        for _ in 0..256 {
+            dsp.generate_sample(&mut ram);
+        }
+        assert_eq!(dsp.voices[0].env, 0);
+    }
+
+    #[test]
+    fn test_direct_gain_voice_output() {
+        let mut ram = ram_with_sample([0xC2, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11]);
+        let mut dsp = dsp_with_voice(0x00, 0x00, 0x7F);
+        dsp.write(REG_KON as u8, 0x01);
+        let samples: Vec<(i16, i16)> = (0..8).map(|_| dsp.generate_sample(&mut ram)).collect();
+        let (left, right) = samples[7];
+        assert!(left > 0);
+        assert_eq!(left, right);
+        assert!(dsp.read(VREG_OUTX as u8) > 0);
+
+        // Muting silences the output but not the voice
+        dsp.write(REG_FLG as u8, FLG_MUTE | FLG_ECHO_DISABLE);
+        assert_eq!(dsp.generate_sample(&mut ram), (0, 0));
+        assert!(dsp.voices[0].env > 0);
+    }
+
+    #[test]
+    fn test_echo_writes_delay_line() {
+        let mut ram = ram_with_sample([0xC2, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11]);
+        let mut dsp = dsp_with_voice(0x00, 0x00, 0x7F);
+        dsp.write(REG_FLG as u8, 0);
+        dsp.write(REG_EON as u8, 0x01);
+        dsp.write(REG_ESA as u8, 0x80);
+        dsp.write(REG_EDL as u8, 0x01);
+        dsp.write(REG_KON as u8, 0x01);
+        for _ in 0..8 {
+            dsp.generate_sample(&mut ram);
+        }
+        assert!(ram[0x8000..0x8800].iter().any(|&b| b != 0));
+        // Nothing is written past the 2KB delay line
+        assert!(ram[0x8800..0x9000].iter().all(|&b| b == 0));
+    }
+}
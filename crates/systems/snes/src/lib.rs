@@ -5,17 +5,40 @@
 //!
 //! - **CPU**: WDC 65C816 (16-bit processor running at ~3.58 MHz)
 //! - **PPU**: Picture Processing Unit (stub implementation)
-//! - **APU**: SPC700 audio processor (stub implementation)
+//! - **APU**: SPC700 sound CPU and S-DSP, mixed to 44.1kHz mono
 //! - **Memory**: 128KB WRAM + cartridge ROM/RAM
-//! - **Timing**: NTSC (3.58 MHz CPU, ~60 Hz frame rate)
+//! - **Timing**: NTSC (262 scanlines, ~60 Hz) or PAL (312 scanlines, ~50 Hz),
+//!   selected from the cartridge header region code or an explicit override
+//!
+//! # Usage Example
+//!
+//! ```
+//! use emu_core::System;
+//! use emu_snes::SnesSystem;
+//!
+//! // A blank 32KB LoROM image
+//! let rom = vec![0u8; 0x8000];
+//!
+//! let mut snes = SnesSystem::new();
+//! snes.mount("Cartridge", &rom).unwrap();
+//! snes.set_controller(0, 0x1000); // Start held on controller 1
+//!
+//! let frame = snes.step_frame().unwrap();
+//! assert_eq!(frame.pixels.len(), (frame.width * frame.height) as usize);
+//! ```
 
 #![allow(clippy::upper_case_acronyms)]
 
+mod apu;
 mod bus;
 mod cartridge;
 mod cpu;
+mod dma;
+mod dsp;
 mod ppu;
 pub mod ppu_renderer;
+#[cfg(feature = "opengl")]
+pub mod ppu_renderer_opengl;
 
 use emu_core::logging::{log, LogCategory, LogLevel};
 
@@ -39,7 +62,12 @@ pub mod controller {
 
 use bus::SnesBus;
 use cpu::SnesCpu;
-use emu_core::{types::Frame, MountPointInfo, System};
+use emu_core::{
+    apu::TimingMode,
+    frame_pool::{FramePool, FramePoolStats},
+    types::Frame,
+    MountPointInfo, System,
+};
 use ppu_renderer::{SnesPpuRenderer, SoftwareSnesPpuRenderer};
 use thiserror::Error;
 
@@ -57,6 +85,10 @@ pub enum SnesError {
 pub struct DebugInfo {
     pub rom_size: usize,
     pub has_smc_header: bool,
+    /// Cartridge header region code
+    pub region: u8,
+    /// Active video standard
+    pub timing: TimingMode,
     pub pc: u16,
     pub pbr: u8,
     pub emulation_mode: bool,
@@ -68,11 +100,19 @@ pub struct SnesSystem {
     frame_cycles: u32,
     current_cycles: u32,
     renderer: Box<dyn SnesPpuRenderer>,
+    /// Active video standard
+    timing: TimingMode,
+    /// User-selected video standard (None = follow cartridge header)
+    timing_override: Option<TimingMode>,
+    /// Buffers of frames the frontend has handed back
+    frame_pool: FramePool,
 }
 
-// SNES timing constants (NTSC)
-const SNES_FRAME_CYCLES: u32 = 89342; // ~3.58MHz / 60Hz
-const SNES_VISIBLE_CYCLES: u32 = 76400; // ~85.5% of frame before VBlank
+// SNES timing constants (1364 master clocks per scanline, CPU cycles = master / 4)
+const SNES_FRAME_CYCLES: u32 = 89342; // NTSC: 262 scanlines, ~60Hz
+const SNES_FRAME_CYCLES_PAL: u32 = 106392; // PAL: 312 scanlines, ~50Hz
+const SNES_VISIBLE_CYCLES: u32 = 76400; // VBlank starts after scanline 224 in both modes
+const SNES_VISIBLE_LINES: u32 = 224;
 
 impl SnesSystem {
     /// Create a new SNES system
@@ -83,9 +123,50 @@ impl SnesSystem {
             frame_cycles: SNES_FRAME_CYCLES,
             current_cycles: 0,
             renderer: Box::new(SoftwareSnesPpuRenderer::new()),
+            timing: TimingMode::Ntsc,
+            timing_override: None,
+            frame_pool: FramePool::new(),
         }
     }
 
+    /// Get the active video standard (NTSC/PAL)
+    pub fn timing(&self) -> TimingMode {
+        self.timing
+    }
+
+    /// Force a video standard, or pass `None` to follow the cartridge header
+    /// region code (NTSC when no cartridge is mounted)
+    pub fn set_timing_override(&mut self, timing: Option<TimingMode>) {
+        self.timing_override = timing;
+        self.apply_timing();
+    }
+
+    /// Get the user-selected video standard override, if any
+    pub fn timing_override(&self) -> Option<TimingMode> {
+        self.timing_override
+    }
+
+    fn apply_timing(&mut self) {
+        let timing = self
+            .timing_override
+            .or_else(|| self.cpu.bus().cartridge_timing())
+            .unwrap_or(TimingMode::Ntsc);
+
+        log(LogCategory::PPU, LogLevel::Info, || {
+            format!("SNES: Using {:?} timing", timing)
+        });
+        self.timing = timing;
+        self.frame_cycles = match timing {
+            TimingMode::Ntsc => SNES_FRAME_CYCLES,
+            // There is no SNES Dendy; it runs like a PAL console
+            TimingMode::Pal | TimingMode::Dendy => SNES_FRAME_CYCLES_PAL,
+        };
+        self.cpu
+            .bus_mut()
+            .ppu_mut()
+            .set_pal(timing != TimingMode::Ntsc);
+    }
+
     /// Get debug information for the SNES system
     pub fn get_debug_info(&self) -> DebugInfo {
         let bus = self.cpu.bus();
@@ -99,6 +180,8 @@ impl SnesSystem {
         DebugInfo {
             rom_size: cartridge_info.0,
             has_smc_header: cartridge_info.1,
+            region: bus.cartridge_region().unwrap_or(0),
+            timing: self.timing,
             pc: self.cpu.cpu.pc,
             pbr: self.cpu.cpu.pbr,
             emulation_mode: self.cpu.cpu.emulation,
@@ -111,6 +194,32 @@ impl SnesSystem {
     pub fn set_controller(&mut self, idx: usize, state: u16) {
         self.cpu.bus_mut().set_controller(idx, state);
     }
+
+    /// Take `count` mono audio samples at 44.1kHz, padded with silence when
+    /// the APU has not produced enough yet
+    pub fn get_audio_samples(&mut self, count: usize) -> Vec<i16> {
+        self.cpu.bus_mut().audio_samples(count)
+    }
+
+    /// Enable OpenGL hardware rendering (requires OpenGL feature)
+    /// This should be called from the frontend after obtaining a GL context
+    #[cfg(feature = "opengl")]
+    pub fn enable_opengl_renderer(&mut self, gl: glow::Context) -> Result<(), String> {
+        use crate::ppu_renderer_opengl::OpenGLSnesPpuRenderer;
+
+        self.renderer = Box::new(OpenGLSnesPpuRenderer::new(gl)?);
+
+        log(LogCategory::PPU, LogLevel::Info, || {
+            "SNES PPU switched to OpenGL hardware renderer".to_string()
+        });
+
+        Ok(())
+    }
+
+    /// Get the name of the current renderer
+    pub fn renderer_name(&self) -> &str {
+        self.renderer.name()
+    }
 }
 
 impl Default for SnesSystem {
@@ -127,6 +236,8 @@ impl System for SnesSystem {
             "SNES: System reset".to_string()
         });
         self.cpu.reset();
+        self.cpu.bus_mut().reset_dma();
+        self.cpu.bus_mut().reset_apu();
         self.current_cycles = 0;
     }
 
@@ -142,19 +253,26 @@ impl System for SnesSystem {
             "SNES: Frame start, VBlank cleared".to_string()
         });
 
-        // Execute CPU cycles for visible portion
-        while self.current_cycles < SNES_VISIBLE_CYCLES {
-            let cycles = self.cpu.step();
-            self.current_cycles += cycles;
-            // Update cycle counter in bus for VBlank timing
-            self.cpu.bus_mut().tick_cycles(cycles);
+        // Execute the visible scanlines. HDMA runs in the HBlank before each
+        // line, and the line is drawn with the registers as they stand then.
+        self.cpu.bus_mut().hdma_init();
+        for line in 0..SNES_VISIBLE_LINES {
+            self.cpu.bus_mut().hdma_scanline();
+            self.renderer
+                .render_lines(self.cpu.bus().ppu(), line as usize..line as usize + 1);
+
+            let line_end = SNES_VISIBLE_CYCLES * (line + 1) / SNES_VISIBLE_LINES;
+            while self.current_cycles < line_end {
+                let cycles = self.cpu.step();
+                self.current_cycles += cycles;
+                // Update cycle counter in bus for VBlank timing
+                self.cpu.bus_mut().tick_cycles(cycles);
+            }
         }
 
-        // Render frame at end of visible scanlines
-        self.renderer.render_frame(self.cpu.bus().ppu());
-
         // Enter VBlank and trigger NMI if enabled
         self.cpu.bus_mut().ppu_mut().set_vblank(true);
+        self.cpu.bus_mut().auto_joypad_read();
         log(LogCategory::PPU, LogLevel::Debug, || {
             format!(
                 "SNES: VBlank started (cycle {}), NMI enabled: {}",
@@ -192,7 +310,10 @@ impl System for SnesSystem {
             "SNES: Frame end, VBlank cleared".to_string()
         });
 
-        Ok(self.renderer.get_frame().clone())
+        let rendered = self.renderer.get_frame();
+        let mut frame = self.frame_pool.acquire(rendered.width, rendered.height);
+        frame.pixels.copy_from_slice(&rendered.pixels);
+        Ok(frame)
     }
 
     fn save_state(&self) -> serde_json::Value {
@@ -256,6 +377,7 @@ impl System for SnesSystem {
             format!("SNES: Mounting cartridge ({} bytes)", data.len())
         });
         self.cpu.bus_mut().load_cartridge(data)?;
+        self.apply_timing();
         self.reset();
         Ok(())
     }
@@ -272,12 +394,25 @@ impl System for SnesSystem {
             "SNES: Unmounting cartridge".to_string()
         });
         self.cpu.bus_mut().unload_cartridge();
+        self.apply_timing();
         Ok(())
     }
 
     fn is_mounted(&self, mount_point_id: &str) -> bool {
         mount_point_id == "Cartridge" && self.cpu.bus().has_cartridge()
     }
+
+    fn frame_rate(&self) -> f64 {
+        self.timing.frame_rate_hz()
+    }
+
+    fn recycle_frame(&mut self, frame: Frame) {
+        self.frame_pool.release(frame);
+    }
+
+    fn frame_pool_stats(&self) -> Option<FramePoolStats> {
+        Some(self.frame_pool.stats())
+    }
 }
 
 #[cfg(test)]
@@ -305,6 +440,37 @@ mod tests {
         // Should not panic
     }
 
+    #[test]
+    fn test_region_selects_timing() {
+        let mut rom = vec![0; 0x8000];
+        rom[0x7FD9] = 0x02; // Europe
+
+        let mut sys = SnesSystem::new();
+        assert_eq!(sys.timing(), TimingMode::Ntsc);
+        sys.mount("Cartridge", &rom).unwrap();
+        assert_eq!(sys.timing(), TimingMode::Pal);
+        assert_eq!(sys.frame_cycles, SNES_FRAME_CYCLES_PAL);
+        assert_eq!(sys.cpu.bus().ppu().read_register(0x213F) & 0x10, 0x10);
+
+        sys.unmount("Cartridge").unwrap();
+        assert_eq!(sys.timing(), TimingMode::Ntsc);
+        assert_eq!(sys.frame_cycles, SNES_FRAME_CYCLES);
+    }
+
+    #[test]
+    fn test_timing_override() {
+        let rom = vec![0; 0x8000]; // Region $00 (Japan, NTSC)
+
+        let mut sys = SnesSystem::new();
+        sys.set_timing_override(Some(TimingMode::Pal));
+        sys.mount("Cartridge", &rom).unwrap();
+        assert_eq!(sys.timing(), TimingMode::Pal);
+
+        sys.set_timing_override(None);
+        assert_eq!(sys.timing(), TimingMode::Ntsc);
+        assert_eq!(sys.cpu.bus().ppu().read_register(0x213F) & 0x10, 0);
+    }
+
     #[test]
     fn test_save_load_state() {
         let sys = SnesSystem::new();
@@ -314,6 +480,18 @@ mod tests {
         assert!(sys2.load_state(&state).is_ok());
     }
 
+    #[test]
+    fn test_golden_save_state() {
+        // Saved after 30 frames of the test ROM
+        emu_core::save_state::check_golden_state(
+            &mut SnesSystem::new(),
+            "Cartridge",
+            include_bytes!("../../../../test_roms/snes/test.sfc"),
+            include_str!("../../../../test_roms/snes/test.state.json"),
+        )
+        .unwrap();
+    }
+
     #[test]
     fn test_snes_smoke_test_rom() {
         // Load the test ROM
@@ -432,6 +610,160 @@ mod tests {
         assert_eq!(snes.cpu.bus().controller_state[1], 0x4070);
     }
 
+    #[test]
+    fn test_controller_auto_read_each_frame() {
+        let mut snes = SnesSystem::new();
+        snes.set_controller(0, controller::START | controller::R);
+        snes.set_controller(1, controller::Y);
+        snes.step_frame().unwrap();
+
+        use emu_core::cpu_65c816::Memory65c816;
+        let bus = snes.cpu.bus_mut();
+        assert_eq!(bus.read(0x4218), 0x10); // JOY1L: R
+        assert_eq!(bus.read(0x4219), 0x10); // JOY1H: Start
+        assert_eq!(bus.read(0x421B), 0x40); // JOY2H: Y
+    }
+
+    #[test]
+    fn test_two_players_held_together() {
+        use emu_core::cpu_65c816::Memory65c816;
+
+        let mut snes = SnesSystem::new();
+        // Both players change their input between the same two frames
+        for (p1, p2) in [
+            (
+                controller::LEFT | controller::B,
+                controller::RIGHT | controller::A,
+            ),
+            (controller::SELECT, controller::START | controller::L),
+        ] {
+            snes.set_controller(0, p1);
+            snes.set_controller(1, p2);
+            snes.step_frame().unwrap();
+
+            let bus = snes.cpu.bus_mut();
+            let joy1 = bus.read(0x4218) as u16 | (bus.read(0x4219) as u16) << 8;
+            let joy2 = bus.read(0x421A) as u16 | (bus.read(0x421B) as u16) << 8;
+            assert_eq!((joy1, joy2), (p1, p2));
+        }
+    }
+
+    #[test]
+    fn test_hdma_backdrop_gradient() {
+        use emu_core::cpu_65c816::Memory65c816;
+
+        let mut snes = SnesSystem::new();
+        let bus = snes.cpu.bus_mut();
+        // HDMA table: 16 lines of red, 16 lines of blue, then stop. Mode 3
+        // writes $2121 twice (CGRAM address 0) and $2122 twice (the color).
+        let table = [
+            0x10, 0x00, 0x00, 0x1F, 0x00, 0x10, 0x00, 0x00, 0x00, 0x7C, 0x00,
+        ];
+        for (i, val) in table.into_iter().enumerate() {
+            bus.write(0x7E1000 + i as u32, val);
+        }
+        bus.write(0x4300, 0x03);
+        bus.write(0x4301, 0x21);
+        bus.write(0x4302, 0x00);
+        bus.write(0x4303, 0x10);
+        bus.write(0x4304, 0x7E);
+        bus.write(0x420C, 0x01);
+
+        let frame = snes.step_frame().unwrap();
+        let row = |y: usize| frame.pixels[y * 256];
+        assert_eq!(row(0), row(15));
+        assert_eq!(row(16), row(31));
+        assert_ne!(row(0), row(16));
+        // The last color written stays for the rest of the frame
+        assert_eq!(row(223), row(16));
+        assert!(frame.pixels[..16 * 256].iter().all(|&p| p == row(0)));
+    }
+
+    #[test]
+    fn test_hdma_mode7_matrix() {
+        use emu_core::cpu_65c816::Memory65c816;
+
+        let mut snes = SnesSystem::new();
+        let bus = snes.cpu.bus_mut();
+        bus.write(0x002105, 0x07); // Mode 7
+        bus.write(0x00212C, 0x01);
+
+        // Tilemap columns alternate between tile 1 and tile 2 (low bytes,
+        // stepping the address after each $2118 write)
+        bus.write(0x002115, 0x80);
+        bus.write(0x002116, 0x00);
+        bus.write(0x002117, 0x00);
+        for i in 0..128 * 32 {
+            bus.write(0x002118, 1 + (i % 2) as u8);
+        }
+        // Tile 1 is color 1, tile 2 is color 2 (high bytes)
+        bus.write(0x002115, 0x00);
+        bus.write(0x002116, 0x40);
+        bus.write(0x002117, 0x00);
+        for i in 0..128 {
+            bus.write(0x002119, 1 + (i / 64) as u8);
+        }
+        bus.write(0x002121, 0x01);
+        for val in [0x1F, 0x00, 0x00, 0x7C] {
+            bus.write(0x002122, val);
+        }
+        bus.write(0x00211E, 0x00);
+        bus.write(0x00211E, 0x01);
+
+        // HDMA mode 2 writes M7A twice per line: 1.0 for the top half of
+        // the screen, then 2.0
+        let table = [0x70, 0x00, 0x01, 0x70, 0x00, 0x02, 0x00];
+        for (i, val) in table.into_iter().enumerate() {
+            bus.write(0x7E1000 + i as u32, val);
+        }
+        bus.write(0x4300, 0x02);
+        bus.write(0x4301, 0x1B);
+        bus.write(0x4302, 0x00);
+        bus.write(0x4303, 0x10);
+        bus.write(0x4304, 0x7E);
+        bus.write(0x420C, 0x01);
+
+        let frame = snes.step_frame().unwrap();
+        let pixel = |x: usize, y: usize| frame.pixels[y * 256 + x];
+        assert_ne!(pixel(0, 0), pixel(8, 0));
+        assert_eq!(pixel(8, 111), pixel(8, 0));
+        // At double scale the second tile column starts at x = 4
+        assert_eq!(pixel(4, 112), pixel(8, 0));
+        assert_eq!(pixel(8, 112), pixel(0, 0));
+    }
+
+    #[test]
+    fn test_apu_handshake_and_audio() {
+        use emu_core::cpu_65c816::Memory65c816;
+
+        let mut snes = SnesSystem::new();
+        snes.step_frame().unwrap();
+
+        // The IPL ROM is waiting for an upload, as games expect at boot
+        let bus = snes.cpu.bus_mut();
+        assert_eq!(bus.read(0x002140), 0xAA);
+        assert_eq!(bus.read(0x002141), 0xBB);
+        // The four ports repeat through $217F
+        assert_eq!(bus.read(0x80217C), 0xAA);
+
+        // About one frame of 44.1kHz audio was produced
+        let samples = snes.get_audio_samples(735);
+        assert_eq!(samples.len(), 735);
+        assert!(samples.iter().all(|&s| s == 0));
+    }
+
+    #[test]
+    fn test_recycled_frames_are_reused() {
+        let mut snes = SnesSystem::new();
+        for _ in 0..3 {
+            let frame = snes.step_frame().unwrap();
+            assert_eq!((frame.width, frame.height), (256, 224));
+            snes.recycle_frame(frame);
+        }
+        let stats = snes.frame_pool_stats().unwrap();
+        assert_eq!((stats.reused, stats.allocated), (2, 1));
+    }
+
     #[test]
     fn test_enhanced_rom() {
         // Load the enhanced test ROM
@@ -1,10 +1,17 @@
 //! SNES PPU (Picture Processing Unit) - Functional Implementation
 //!
-//! This is a functional PPU implementation supporting Modes 0 & 1, sprites, and scrolling.
+//! This is a functional PPU implementation supporting Modes 0, 1, 5, 6 & 7, sprites, and scrolling.
 //!
 //! **Implemented Features**:
 //! - Mode 0: 4 BG layers, 2bpp each (4 colors per tile)
 //! - Mode 1: 2 BG layers 4bpp + 1 BG layer 2bpp (most common commercial mode)
+//! - Mode 5: 512-pixel hires, BG1 4bpp + BG2 2bpp (16-pixel-wide tiles)
+//! - Mode 6: 512-pixel hires, BG1 4bpp
+//! - Mode 7: one 8bpp 1024x1024 BG rotated and scaled by the M7A-M7D
+//!   matrix ($211A-$2120), with the signed multiply result at $2134-$2136
+//! - Pseudo-hires ($2133 bit 3): sub-screen and main screen pixels interleaved
+//! - Interlace ($2133 bits 0-1): 448-line BGs in Modes 5/6, OBJ interlace
+//! - 8x8 and 16x16 BG tiles, 10-bit tile numbers, planar 2bpp/4bpp tile format
 //! - Sprite rendering: 128 sprites, 4bpp, multiple size modes, priority rendering
 //! - Full scrolling support on all BG layers
 //! - VRAM access via registers $2115-$2119 (with increment control)
@@ -12,32 +19,107 @@
 //! - OAM access via $2101-$2104
 //! - Screen enable/disable via $2100 (force blank + brightness)
 //! - Layer enable/disable via $212C (main screen designation)
+//! - Sub-screen designation via $212D and fixed color via $2132 (hires only)
 //! - Status registers: $213F (STAT78), $4212 (HVBJOY)
 //!
+//! **Frame Sizes**: Frames are 256x224. Hires and interlaced screens are
+//! output as 512x448 (the other axis line- or pixel-doubled), which keeps the
+//! same 8:7 shape so frontends can scale either size the same way.
+//!
 //! **NOT Implemented** (future enhancements):
-//! - PPU Modes 2-7 (only used by ~40% of games)
+//! - PPU Modes 2-4 (only used by ~40% of games)
+//! - Mode 7 EXTBG (BG2 with per-pixel priority) and mosaic
+//! - Mode 6 offset-per-tile scrolling
+//! - Overscan (239-line) mode ($2133 bit 2)
 //! - Windows and color windows ($2123-$212B)
 //! - HDMA effects
 //! - Mosaic effects ($2106)
-//! - Color math ($2130-$2132)
-//! - Sub-screen support ($212D)
+//! - Color math ($2130-$2131); the sub-screen is only shown by hires modes
 
 use emu_core::logging::{log, LogCategory, LogLevel};
 use emu_core::types::Frame;
+use std::ops::Range;
 
 const VRAM_SIZE: usize = 0x10000; // 64KB VRAM
 const CGRAM_SIZE: usize = 512; // 256 colors * 2 bytes per color
 const OAM_SIZE: usize = 544; // 512 bytes main OAM + 32 bytes high table
 
-/// Parameters for rendering a single tile
-struct TileRenderParams {
-    tile_x: usize,
-    tile_y: usize,
-    tile_index: u8,
-    chr_base: usize,
-    palette: usize,
-    flip_x: bool,
-    flip_y: bool,
+const SCREEN_WIDTH: usize = 256; // Normal horizontal resolution
+const SCREEN_HEIGHT: usize = 224; // Visible scanlines per field
+
+/// Words in a packed per-scanline register snapshot (`Ppu::line_registers`)
+#[cfg(feature = "opengl")]
+pub(crate) const LINE_REGISTER_WORDS: usize = 12;
+
+/// Output geometry of a rendered frame
+///
+/// Normal frames are 256x224. Hires or interlaced frames are 512x448, with
+/// BGs sampled at full resolution only on the axes the mode provides.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ScreenLayout {
+    /// Frame width (256 or 512)
+    width: usize,
+    /// Frame height (224 or 448)
+    height: usize,
+    /// Mode 5/6 or pseudo-hires: even columns come from the sub-screen
+    hires: bool,
+    /// Modes 5/6: BGs are sampled at 512 pixels per line
+    hires_bg: bool,
+    /// Modes 5/6 with interlace: BGs are sampled at 448 lines
+    interlace_bg: bool,
+    /// OBJ interlace: each sprite row covers a single 448-line row
+    obj_interlace: bool,
+}
+
+impl ScreenLayout {
+    /// Output pixels per 256-pixel column
+    fn x_scale(&self) -> usize {
+        self.width / SCREEN_WIDTH
+    }
+
+    /// Output lines per 224-line scanline
+    fn y_scale(&self) -> usize {
+        self.height / SCREEN_HEIGHT
+    }
+}
+
+/// One screen (main or sub) being rendered, with its per-pixel priorities
+///
+/// The buffer covers a band of output lines; pixels outside it are dropped.
+struct ScreenBuffer {
+    layout: ScreenLayout,
+    /// Output lines covered by the buffer
+    lines: Range<usize>,
+    pixels: Vec<u32>,
+    /// Priority levels: 0 (backdrop), 1 (BG priority 0), 2 (sprites 0-1),
+    /// 3 (BG priority 1), 4 (sprites 2-3), 5 (Mode 1 BG3 priority toggle)
+    priority: Vec<u8>,
+}
+
+impl ScreenBuffer {
+    fn new(layout: ScreenLayout, lines: Range<usize>) -> Self {
+        let size = layout.width * lines.len();
+        Self {
+            layout,
+            lines,
+            pixels: vec![0; size],
+            priority: vec![0; size],
+        }
+    }
+
+    /// Draw a pixel (at a whole-frame offset) if it is at or above what is
+    /// already there
+    ///
+    /// Later layers are in front, so they win ties.
+    fn plot(&mut self, offset: usize, color: u32, render_priority: u8) {
+        let Some(offset) = offset.checked_sub(self.lines.start * self.layout.width) else {
+            return;
+        };
+        if offset < self.pixels.len() && render_priority >= self.priority[offset] {
+            self.pixels[offset] = color;
+            self.priority[offset] = render_priority;
+        }
+    }
 }
 
 /// Minimal SNES PPU implementation
@@ -78,6 +160,9 @@ pub struct Ppu {
     /// H/V-blank flag and joypad status ($4212)
     hvbjoy: u8,
 
+    /// PAL console (reported in $213F bit 4)
+    pal: bool,
+
     /// Screen display register ($2100) - bit 7 = force blank, bits 0-3 = brightness
     screen_display: u8,
 
@@ -121,6 +206,20 @@ pub struct Ppu {
     /// Bits 0-4: Enable BG1-4 and OBJ on main screen
     tm: u8,
 
+    /// Sub-screen designation ($212D)
+    /// Bits 0-4: Enable BG1-4 and OBJ on the sub-screen (shown by hires modes)
+    ts: u8,
+
+    /// Fixed color ($2132), 15-bit BGR - the sub-screen backdrop
+    fixed_color: u16,
+
+    /// Screen mode select ($2133)
+    /// Bit 0: Screen interlace
+    /// Bit 1: OBJ interlace
+    /// Bit 2: Overscan (239 lines)
+    /// Bit 3: Pseudo-hires (512 pixels from main + sub-screen)
+    setini: u8,
+
     /// BG1 horizontal scroll offset ($210D) - 10-bit value, written twice
     bg1_hofs: u16,
     /// BG1 vertical scroll offset ($210E) - 10-bit value, written twice
@@ -142,6 +241,25 @@ pub struct Ppu {
     scroll_prev: u8,
     /// Latch for scroll register writes
     scroll_latch: bool,
+
+    /// Mode 7 settings ($211A)
+    /// Bits 6-7: Outside the 1024x1024 plane: wrap (0/1), transparent (2),
+    /// tile 0 (3)
+    /// Bit 1: Vertical flip, Bit 0: Horizontal flip
+    m7sel: u8,
+    /// Mode 7 matrix ($211B-$211E), signed 8.8 fixed point
+    m7a: i16,
+    m7b: i16,
+    m7c: i16,
+    m7d: i16,
+    /// Mode 7 center ($211F-$2120), signed 13-bit
+    m7x: i16,
+    m7y: i16,
+    /// Mode 7 scroll, written through BG1HOFS/BG1VOFS, signed 13-bit
+    m7hofs: i16,
+    m7vofs: i16,
+    /// Previous byte written to a Mode 7 register (shared write latch)
+    m7_prev: u8,
 }
 
 impl Ppu {
@@ -162,6 +280,7 @@ impl Ppu {
             nmi_pending: false,
             nmi_enable: false,
             hvbjoy: 0,
+            pal: false,
             screen_display: 0x80, // Start with screen blanked
             bgmode: 0,
             bg1sc: 0,
@@ -172,6 +291,9 @@ impl Ppu {
             bg34nba: 0,
             obsel: 0,
             tm: 0,
+            ts: 0,
+            fixed_color: 0,
+            setini: 0,
             bg1_hofs: 0,
             bg1_vofs: 0,
             bg2_hofs: 0,
@@ -182,9 +304,32 @@ impl Ppu {
             bg4_vofs: 0,
             scroll_prev: 0,
             scroll_latch: false,
+            m7sel: 0,
+            m7a: 0,
+            m7b: 0,
+            m7c: 0,
+            m7d: 0,
+            m7x: 0,
+            m7y: 0,
+            m7hofs: 0,
+            m7vofs: 0,
+            m7_prev: 0,
         }
     }
 
+    /// Combine a Mode 7 register write with the previous one (low byte
+    /// first, then high byte)
+    fn m7_word(&mut self, val: u8) -> u16 {
+        let word = (val as u16) << 8 | self.m7_prev as u16;
+        self.m7_prev = val;
+        word
+    }
+
+    /// Sign-extend a 13-bit Mode 7 center or scroll value
+    fn sign_extend_13(word: u16) -> i16 {
+        ((word << 3) as i16) >> 3
+    }
+
     /// Write to PPU registers
     pub fn write_register(&mut self, addr: u16, val: u8) {
         // Track open bus for PPU1 registers ($2100-$213F)
@@ -284,8 +429,11 @@ impl Ppu {
                 self.bg34nba = val;
             }
 
-            // $210D - BG1HOFS - BG1 Horizontal Scroll (2 writes)
+            // $210D - BG1HOFS - BG1 Horizontal Scroll (2 writes), also
+            // M7HOFS through the Mode 7 latch
             0x210D => {
+                let word = self.m7_word(val);
+                self.m7hofs = Self::sign_extend_13(word);
                 if !self.scroll_latch {
                     self.scroll_prev = val;
                     self.scroll_latch = true;
@@ -295,8 +443,11 @@ impl Ppu {
                 }
             }
 
-            // $210E - BG1VOFS - BG1 Vertical Scroll (2 writes)
+            // $210E - BG1VOFS - BG1 Vertical Scroll (2 writes), also
+            // M7VOFS through the Mode 7 latch
             0x210E => {
+                let word = self.m7_word(val);
+                self.m7vofs = Self::sign_extend_13(word);
                 if !self.scroll_latch {
                     self.scroll_prev = val;
                     self.scroll_latch = true;
@@ -446,6 +597,21 @@ impl Ppu {
                 }
             }
 
+            // $211A - M7SEL - Mode 7 Settings
+            0x211A => {
+                self.m7sel = val;
+            }
+
+            // $211B-$211E - M7A-M7D - Mode 7 Matrix (2 writes each)
+            0x211B => self.m7a = self.m7_word(val) as i16,
+            0x211C => self.m7b = self.m7_word(val) as i16,
+            0x211D => self.m7c = self.m7_word(val) as i16,
+            0x211E => self.m7d = self.m7_word(val) as i16,
+
+            // $211F-$2120 - M7X/M7Y - Mode 7 Center (2 writes each)
+            0x211F => self.m7x = Self::sign_extend_13(self.m7_word(val)),
+            0x2120 => self.m7y = Self::sign_extend_13(self.m7_word(val)),
+
             // $2121 - CGADD - CGRAM Address
             0x2121 => {
                 self.cgram_addr = val;
@@ -506,9 +672,9 @@ impl Ppu {
                 // Stub: Accept window configuration but don't implement
             }
 
-            // $212D - TS - Sub-screen Designation (stub - not implemented)
+            // $212D - TS - Sub-screen Designation
             0x212D => {
-                // Stub: Accept write but don't implement sub-screen
+                self.ts = val;
             }
 
             // $212E-$212F - Window mask designation (stub - not implemented)
@@ -516,11 +682,43 @@ impl Ppu {
                 // Stub: Accept window mask but don't implement
             }
 
-            // $2130-$2133 - Color math and screen mode registers (stub - not implemented)
-            0x2130..=0x2133 => {
+            // $2130-$2131 - Color math registers (stub - not implemented)
+            0x2130 | 0x2131 => {
                 // Stub: Accept color math configuration but don't implement
             }
 
+            // $2132 - COLDATA - Fixed Color Data
+            // Bits 5-7 select which of R/G/B receive the intensity in bits 0-4
+            0x2132 => {
+                let intensity = (val & 0x1F) as u16;
+                if val & 0x20 != 0 {
+                    self.fixed_color = (self.fixed_color & !0x001F) | intensity;
+                }
+                if val & 0x40 != 0 {
+                    self.fixed_color = (self.fixed_color & !0x03E0) | (intensity << 5);
+                }
+                if val & 0x80 != 0 {
+                    self.fixed_color = (self.fixed_color & !0x7C00) | (intensity << 10);
+                }
+            }
+
+            // $2133 - SETINI - Screen Mode/Video Select
+            0x2133 => {
+                let changed = self.setini ^ val;
+                self.setini = val;
+
+                if changed & 0x0B != 0 {
+                    log(LogCategory::PPU, LogLevel::Info, || {
+                        format!(
+                            "SNES PPU: Interlace {}, OBJ interlace {}, pseudo-hires {}",
+                            val & 0x01 != 0,
+                            val & 0x02 != 0,
+                            val & 0x08 != 0
+                        )
+                    });
+                }
+            }
+
             // Other registers - stub (just accept writes)
             _ => {
                 log(LogCategory::PPU, LogLevel::Debug, || {
@@ -536,14 +734,12 @@ impl Ppu {
     /// Read from PPU registers
     pub fn read_register(&self, addr: u16) -> u8 {
         match addr {
-            // $2134 - MPYL - Multiplication Result (low byte) - stub
-            0x2134 => 0,
-
-            // $2135 - MPYM - Multiplication Result (middle byte) - stub
-            0x2135 => 0,
-
-            // $2136 - MPYH - Multiplication Result (high byte) - stub
-            0x2136 => 0,
+            // $2134-$2136 - MPYL/MPYM/MPYH - Signed product of M7A and
+            // the last byte written to M7B
+            0x2134..=0x2136 => {
+                let product = self.m7a as i32 * (self.m7b >> 8) as i8 as i32;
+                (product >> ((addr - 0x2134) * 8)) as u8
+            }
 
             // $2137 - SLHV - Software Latch for H/V Counter
             0x2137 => {
@@ -602,10 +798,13 @@ impl Ppu {
             0x213F => {
                 // Bit 7: NMI flag (cleared on read)
                 // Bit 6: Master/slave mode
+                // Bit 4: Frame rate (0 = NTSC 60Hz, 1 = PAL 50Hz)
                 // Bits 0-3: PPU version
                 // Note: In real hardware, reading this clears the NMI flag
                 // But we can't do that in a &self method. The caller should call clear_nmi_flag()
-                (if self.nmi_flag { 0x80 } else { 0x00 }) | 0x01 // Version 1
+                (if self.nmi_flag { 0x80 } else { 0x00 })
+                    | (if self.pal { 0x10 } else { 0x00 })
+                    | 0x01 // Version 1
             }
 
             // $4212 - HVBJOY - H/V-Blank and Joypad Status
@@ -629,18 +828,170 @@ impl Ppu {
     }
 
     /// Render a frame
+    ///
+    /// Returns a 256x224 frame, or 512x448 in hires and interlaced modes.
     pub fn render_frame(&self) -> Frame {
-        let mut frame = Frame::new(256, 224); // SNES resolution
+        let mut frame = Frame::new(0, 0);
+        self.render_into(&mut frame);
+        frame
+    }
+
+    /// Render a frame into an existing one, resizing it to the output size
+    /// and reusing its pixel buffer
+    pub fn render_into(&self, frame: &mut Frame) {
+        self.render_lines_into(frame, 0..SCREEN_HEIGHT);
+    }
 
-        // Priority buffer: tracks the priority level of each pixel
-        // Priority levels: 0 (backdrop) to 7 (highest sprite priority)
-        // We use 255 as "unset" since it's higher than any valid priority
-        let mut priority_buffer = vec![255u8; 256 * 224];
+    /// Render a band of scanlines (0-223) into a frame with the current
+    /// register state, leaving the other lines alone
+    ///
+    /// Drawing each line as the beam reaches it lets mid-frame register
+    /// changes (HDMA gradients, split scrolling) show up. The frame is
+    /// resized to the current output size first.
+    pub fn render_lines_into(&self, frame: &mut Frame, lines: Range<usize>) {
+        let layout = self.screen_layout();
+        frame.width = layout.width as u32;
+        frame.height = layout.height as u32;
+        frame.pixels.resize(layout.width * layout.height, 0);
+
+        let y_scale = layout.y_scale();
+        let rows = lines.start.min(SCREEN_HEIGHT) * y_scale..lines.end.min(SCREEN_HEIGHT) * y_scale;
+        let target = &mut frame.pixels[rows.start * layout.width..rows.end * layout.width];
 
         // NOTE: We render even when screen is blanked (bit 7 set)
         // This is not hardware-accurate but allows commercial ROMs to display
         // something during boot sequences before they unblank the screen
 
+        // SNES backdrop is CGRAM color 0 (not transparent)
+        let main = self.render_screen(self.tm, layout, rows.clone(), self.get_color(0));
+
+        if layout.hires {
+            // Hires: the sub-screen supplies the even (left) half-pixels and
+            // the main screen the odd ones. The sub-screen backdrop is the
+            // fixed color.
+            let sub =
+                self.render_screen(self.ts, layout, rows, Self::rgb15_to_argb(self.fixed_color));
+            for (i, pixel) in target.iter_mut().enumerate() {
+                *pixel = if i.is_multiple_of(2) { sub[i] } else { main[i] };
+            }
+        } else {
+            target.copy_from_slice(&main);
+        }
+    }
+
+    /// Output frame size (width, height) for the current BG mode and $2133
+    /// settings
+    #[cfg(feature = "opengl")]
+    pub(crate) fn output_size(&self) -> (usize, usize) {
+        let layout = self.screen_layout();
+        (layout.width, layout.height)
+    }
+
+    /// VRAM contents (for hardware renderers)
+    #[cfg(feature = "opengl")]
+    pub(crate) fn vram(&self) -> &[u8] {
+        &self.vram
+    }
+
+    /// CGRAM contents: 256 little-endian 15-bit colors (for hardware
+    /// renderers)
+    #[cfg(feature = "opengl")]
+    pub(crate) fn cgram(&self) -> &[u8] {
+        &self.cgram
+    }
+
+    /// Snapshot of the registers that shape one scanline, packed for a
+    /// hardware renderer:
+    ///
+    /// - 0: BGMODE | TM << 8 | TS << 16 | SETINI << 24
+    /// - 1: BG1SC | BG2SC << 8 | BG3SC << 16 | BG4SC << 24
+    /// - 2: BG12NBA | BG34NBA << 8 | M7SEL << 16
+    /// - 3-6: BGnHOFS | BGnVOFS << 16
+    /// - 7-10: M7A | M7B << 16, M7C | M7D << 16, M7X | M7Y << 16 and
+    ///   M7HOFS | M7VOFS << 16 (16-bit two's complement)
+    /// - 11: Fixed color (15-bit)
+    #[cfg(feature = "opengl")]
+    pub(crate) fn line_registers(&self) -> [u32; LINE_REGISTER_WORDS] {
+        let pair = |low: i16, high: i16| low as u16 as u32 | (high as u16 as u32) << 16;
+        [
+            u32::from_le_bytes([self.bgmode, self.tm, self.ts, self.setini]),
+            u32::from_le_bytes([self.bg1sc, self.bg2sc, self.bg3sc, self.bg4sc]),
+            u32::from_le_bytes([self.bg12nba, self.bg34nba, self.m7sel, 0]),
+            self.bg1_hofs as u32 | (self.bg1_vofs as u32) << 16,
+            self.bg2_hofs as u32 | (self.bg2_vofs as u32) << 16,
+            self.bg3_hofs as u32 | (self.bg3_vofs as u32) << 16,
+            self.bg4_hofs as u32 | (self.bg4_vofs as u32) << 16,
+            pair(self.m7a, self.m7b),
+            pair(self.m7c, self.m7d),
+            pair(self.m7x, self.m7y),
+            pair(self.m7hofs, self.m7vofs),
+            self.fixed_color as u32,
+        ]
+    }
+
+    /// Render only the sprites of a band of scanlines (0-223), for hardware
+    /// renderers that draw the BGs themselves
+    ///
+    /// Returns the band's output rows at the current output width. Each
+    /// pixel holds the sprite's RGB color with its render priority in the
+    /// top byte (2 for OBJ priorities 0-1, 4 for 2-3), or 0 where no sprite
+    /// is drawn. Sprite evaluation (including the per-line limits) is the
+    /// same as in software rendering.
+    #[cfg(feature = "opengl")]
+    pub(crate) fn render_obj_lines(&self, lines: Range<usize>) -> Vec<u32> {
+        let layout = self.screen_layout();
+        let y_scale = layout.y_scale();
+        let rows = lines.start.min(SCREEN_HEIGHT) * y_scale..lines.end.min(SCREEN_HEIGHT) * y_scale;
+
+        let mut screen = ScreenBuffer::new(layout, rows);
+        self.render_sprites_priority(&mut screen, 0, 1);
+        self.render_sprites_priority(&mut screen, 2, 3);
+
+        screen
+            .pixels
+            .iter()
+            .zip(&screen.priority)
+            .map(|(&color, &priority)| match priority {
+                0 => 0,
+                _ => color & 0x00FF_FFFF | (priority as u32) << 24,
+            })
+            .collect()
+    }
+
+    /// Get the output geometry for the current BG mode and $2133 settings
+    fn screen_layout(&self) -> ScreenLayout {
+        let bg_mode = self.bgmode & 0x07;
+        let hires_bg = bg_mode == 5 || bg_mode == 6;
+        let hires = hires_bg || self.setini & 0x08 != 0;
+        let interlace = self.setini & 0x01 != 0;
+        let (width, height) = if hires || interlace {
+            (SCREEN_WIDTH * 2, SCREEN_HEIGHT * 2)
+        } else {
+            (SCREEN_WIDTH, SCREEN_HEIGHT)
+        };
+
+        ScreenLayout {
+            width,
+            height,
+            hires,
+            hires_bg,
+            interlace_bg: hires_bg && interlace,
+            obj_interlace: interlace && self.setini & 0x02 != 0,
+        }
+    }
+
+    /// Render the output lines `rows` of the main screen or sub-screen with
+    /// the given layer designation (bits 0-4: BG1-4 and OBJ, as in
+    /// $212C/$212D)
+    fn render_screen(
+        &self,
+        layers: u8,
+        layout: ScreenLayout,
+        rows: Range<usize>,
+        backdrop_color: u32,
+    ) -> Vec<u32> {
+        let mut screen = ScreenBuffer::new(layout, rows);
+
         // Get BG mode (bits 0-2 of BGMODE register)
         let bg_mode = self.bgmode & 0x07;
 
@@ -654,41 +1005,41 @@ impl Ppu {
                 // 4. Sprites with priority=2-3
 
                 // Render priority 0 BG layers
-                if self.tm & 0x08 != 0 {
-                    self.render_bg_layer_2bpp_priority(&mut frame, &mut priority_buffer, 3, 0);
+                if layers & 0x08 != 0 {
+                    self.render_bg_layer_priority(&mut screen, 3, 2, 0, 1);
                 }
-                if self.tm & 0x04 != 0 {
-                    self.render_bg_layer_2bpp_priority(&mut frame, &mut priority_buffer, 2, 0);
+                if layers & 0x04 != 0 {
+                    self.render_bg_layer_priority(&mut screen, 2, 2, 0, 1);
                 }
-                if self.tm & 0x02 != 0 {
-                    self.render_bg_layer_2bpp_priority(&mut frame, &mut priority_buffer, 1, 0);
+                if layers & 0x02 != 0 {
+                    self.render_bg_layer_priority(&mut screen, 1, 2, 0, 1);
                 }
-                if self.tm & 0x01 != 0 {
-                    self.render_bg_layer_2bpp_priority(&mut frame, &mut priority_buffer, 0, 0);
+                if layers & 0x01 != 0 {
+                    self.render_bg_layer_priority(&mut screen, 0, 2, 0, 1);
                 }
 
                 // Render sprites with priority 0-1
-                if self.tm & 0x10 != 0 {
-                    self.render_sprites_priority(&mut frame, &mut priority_buffer, 0, 1);
+                if layers & 0x10 != 0 {
+                    self.render_sprites_priority(&mut screen, 0, 1);
                 }
 
                 // Render priority 1 BG layers
-                if self.tm & 0x08 != 0 {
-                    self.render_bg_layer_2bpp_priority(&mut frame, &mut priority_buffer, 3, 1);
+                if layers & 0x08 != 0 {
+                    self.render_bg_layer_priority(&mut screen, 3, 2, 1, 3);
                 }
-                if self.tm & 0x04 != 0 {
-                    self.render_bg_layer_2bpp_priority(&mut frame, &mut priority_buffer, 2, 1);
+                if layers & 0x04 != 0 {
+                    self.render_bg_layer_priority(&mut screen, 2, 2, 1, 3);
                 }
-                if self.tm & 0x02 != 0 {
-                    self.render_bg_layer_2bpp_priority(&mut frame, &mut priority_buffer, 1, 1);
+                if layers & 0x02 != 0 {
+                    self.render_bg_layer_priority(&mut screen, 1, 2, 1, 3);
                 }
-                if self.tm & 0x01 != 0 {
-                    self.render_bg_layer_2bpp_priority(&mut frame, &mut priority_buffer, 0, 1);
+                if layers & 0x01 != 0 {
+                    self.render_bg_layer_priority(&mut screen, 0, 2, 1, 3);
                 }
 
                 // Render sprites with priority 2-3
-                if self.tm & 0x10 != 0 {
-                    self.render_sprites_priority(&mut frame, &mut priority_buffer, 2, 3);
+                if layers & 0x10 != 0 {
+                    self.render_sprites_priority(&mut screen, 2, 3);
                 }
             }
             // Mode 1: 2 BG layers (4bpp) + 1 BG layer (2bpp)
@@ -708,87 +1059,129 @@ impl Ppu {
                 if !bg3_priority_high {
                     // Normal priority mode
                     // Render priority 0 BG layers
-                    if self.tm & 0x04 != 0 {
-                        self.render_bg_layer_2bpp_priority(&mut frame, &mut priority_buffer, 2, 0);
+                    if layers & 0x04 != 0 {
+                        self.render_bg_layer_priority(&mut screen, 2, 2, 0, 1);
                     }
-                    if self.tm & 0x02 != 0 {
-                        self.render_bg_layer_4bpp_priority(&mut frame, &mut priority_buffer, 1, 0);
+                    if layers & 0x02 != 0 {
+                        self.render_bg_layer_priority(&mut screen, 1, 4, 0, 1);
                     }
-                    if self.tm & 0x01 != 0 {
-                        self.render_bg_layer_4bpp_priority(&mut frame, &mut priority_buffer, 0, 0);
+                    if layers & 0x01 != 0 {
+                        self.render_bg_layer_priority(&mut screen, 0, 4, 0, 1);
                     }
 
                     // Render sprites with priority 0-1
-                    if self.tm & 0x10 != 0 {
-                        self.render_sprites_priority(&mut frame, &mut priority_buffer, 0, 1);
+                    if layers & 0x10 != 0 {
+                        self.render_sprites_priority(&mut screen, 0, 1);
                     }
 
                     // Render priority 1 BG layers
-                    if self.tm & 0x04 != 0 {
-                        self.render_bg_layer_2bpp_priority(&mut frame, &mut priority_buffer, 2, 1);
+                    if layers & 0x04 != 0 {
+                        self.render_bg_layer_priority(&mut screen, 2, 2, 1, 3);
                     }
-                    if self.tm & 0x02 != 0 {
-                        self.render_bg_layer_4bpp_priority(&mut frame, &mut priority_buffer, 1, 1);
+                    if layers & 0x02 != 0 {
+                        self.render_bg_layer_priority(&mut screen, 1, 4, 1, 3);
                     }
-                    if self.tm & 0x01 != 0 {
-                        self.render_bg_layer_4bpp_priority(&mut frame, &mut priority_buffer, 0, 1);
+                    if layers & 0x01 != 0 {
+                        self.render_bg_layer_priority(&mut screen, 0, 4, 1, 3);
                     }
 
                     // Render sprites with priority 2-3
-                    if self.tm & 0x10 != 0 {
-                        self.render_sprites_priority(&mut frame, &mut priority_buffer, 2, 3);
+                    if layers & 0x10 != 0 {
+                        self.render_sprites_priority(&mut screen, 2, 3);
                     }
                 } else {
-                    // BG3 priority toggle mode: BG3 renders above all sprites
+                    // BG3 priority toggle mode: priority 1 BG3 tiles render
+                    // above all sprites, priority 0 ones stay at the back
+                    if layers & 0x04 != 0 {
+                        self.render_bg_layer_priority(&mut screen, 2, 2, 0, 1);
+                    }
+
                     // Render priority 0 BG1 and BG2
-                    if self.tm & 0x02 != 0 {
-                        self.render_bg_layer_4bpp_priority(&mut frame, &mut priority_buffer, 1, 0);
+                    if layers & 0x02 != 0 {
+                        self.render_bg_layer_priority(&mut screen, 1, 4, 0, 1);
                     }
-                    if self.tm & 0x01 != 0 {
-                        self.render_bg_layer_4bpp_priority(&mut frame, &mut priority_buffer, 0, 0);
+                    if layers & 0x01 != 0 {
+                        self.render_bg_layer_priority(&mut screen, 0, 4, 0, 1);
                     }
 
                     // Render sprites with priority 0-1
-                    if self.tm & 0x10 != 0 {
-                        self.render_sprites_priority(&mut frame, &mut priority_buffer, 0, 1);
+                    if layers & 0x10 != 0 {
+                        self.render_sprites_priority(&mut screen, 0, 1);
                     }
 
                     // Render priority 1 BG1 and BG2
-                    if self.tm & 0x02 != 0 {
-                        self.render_bg_layer_4bpp_priority(&mut frame, &mut priority_buffer, 1, 1);
+                    if layers & 0x02 != 0 {
+                        self.render_bg_layer_priority(&mut screen, 1, 4, 1, 3);
                     }
-                    if self.tm & 0x01 != 0 {
-                        self.render_bg_layer_4bpp_priority(&mut frame, &mut priority_buffer, 0, 1);
+                    if layers & 0x01 != 0 {
+                        self.render_bg_layer_priority(&mut screen, 0, 4, 1, 3);
                     }
 
                     // Render sprites with priority 2-3
-                    if self.tm & 0x10 != 0 {
-                        self.render_sprites_priority(&mut frame, &mut priority_buffer, 2, 3);
+                    if layers & 0x10 != 0 {
+                        self.render_sprites_priority(&mut screen, 2, 3);
                     }
 
-                    // Render BG3 last (above all sprites)
-                    if self.tm & 0x04 != 0 {
-                        // Use a very high priority value to ensure BG3 is always on top
-                        self.render_bg_layer_2bpp_priority(&mut frame, &mut priority_buffer, 2, 7);
+                    // Render priority 1 BG3 last (above all sprites)
+                    if layers & 0x04 != 0 {
+                        self.render_bg_layer_priority(&mut screen, 2, 2, 1, 5);
                     }
                 }
             }
+            // Mode 5: BG1 4bpp + BG2 2bpp, Mode 6: BG1 4bpp - both 512-pixel hires
+            5 | 6 => {
+                let bg2_enabled = bg_mode == 5 && layers & 0x02 != 0;
+
+                // Render priority 0 BG layers (BG2->BG1)
+                if bg2_enabled {
+                    self.render_bg_layer_priority(&mut screen, 1, 2, 0, 1);
+                }
+                if layers & 0x01 != 0 {
+                    self.render_bg_layer_priority(&mut screen, 0, 4, 0, 1);
+                }
+
+                // Render sprites with priority 0-1
+                if layers & 0x10 != 0 {
+                    self.render_sprites_priority(&mut screen, 0, 1);
+                }
+
+                // Render priority 1 BG layers (BG2->BG1)
+                if bg2_enabled {
+                    self.render_bg_layer_priority(&mut screen, 1, 2, 1, 3);
+                }
+                if layers & 0x01 != 0 {
+                    self.render_bg_layer_priority(&mut screen, 0, 4, 1, 3);
+                }
+
+                // Render sprites with priority 2-3
+                if layers & 0x10 != 0 {
+                    self.render_sprites_priority(&mut screen, 2, 3);
+                }
+            }
+            // Mode 7: one rotated and scaled 8bpp BG, behind all sprites
+            7 => {
+                if layers & 0x01 != 0 {
+                    self.render_mode7(&mut screen, 1);
+                }
+                if layers & 0x10 != 0 {
+                    self.render_sprites_priority(&mut screen, 0, 1);
+                    self.render_sprites_priority(&mut screen, 2, 3);
+                }
+            }
             _ => {
                 // Other modes not yet implemented - leave frame blank
             }
         }
 
         // Fill backdrop color for all pixels that weren't rendered
-        // SNES backdrop is CGRAM color 0 (not transparent)
-        let backdrop_color = self.get_color(0);
-        for (i, &priority) in priority_buffer.iter().enumerate() {
-            if priority == 255 {
+        for (pixel, &priority) in screen.pixels.iter_mut().zip(&screen.priority) {
+            if priority == 0 {
                 // No layer rendered here - use backdrop color
-                frame.pixels[i] = backdrop_color;
+                *pixel = backdrop_color;
             }
         }
 
-        frame
+        screen.pixels
     }
 
     /// Get VRAM address increment amount based on VMAIN register
@@ -800,6 +1193,11 @@ impl Ppu {
         }
     }
 
+    /// Set whether the console is a PAL unit (reported via $213F)
+    pub fn set_pal(&mut self, pal: bool) {
+        self.pal = pal;
+    }
+
     /// Set V-blank flag (called by system during vertical blanking)
     pub fn set_vblank(&mut self, vblank: bool) {
         if vblank {
@@ -919,168 +1317,22 @@ impl Ppu {
         block_offset + in_block_offset
     }
 
-    /// Get a single pixel color index from a tile in Mode 0 (2bpp)
-    /// Returns CGRAM color index (0-255) or 0 for transparent
-    #[allow(clippy::too_many_arguments)]
-    fn get_tile_pixel_mode0(
-        &self,
-        tile_index: u8,
-        chr_base: usize,
-        pixel_x: usize,
-        pixel_y: usize,
-        palette: usize,
-        flip_x: bool,
-        flip_y: bool,
-    ) -> u8 {
-        // In Mode 0, each tile is 16 bytes (8 rows * 2 bytes per row for 2bpp)
-        let tile_data_base = chr_base + (tile_index as usize * 16);
-
-        // Apply flip
-        let actual_row = if flip_y { 7 - pixel_y } else { pixel_y };
-        let actual_col = if flip_x { pixel_x } else { 7 - pixel_x };
-
-        // Read two bitplanes for this row
-        let bp0_addr = tile_data_base + actual_row;
-        let bp1_addr = tile_data_base + actual_row + 8;
-
-        let bp0 = if bp0_addr < VRAM_SIZE {
-            self.vram[bp0_addr]
-        } else {
-            0
-        };
-        let bp1 = if bp1_addr < VRAM_SIZE {
-            self.vram[bp1_addr]
-        } else {
-            0
-        };
-
-        // Extract color index from bitplanes
-        let bit = actual_col;
-        let bit0 = (bp0 >> bit) & 1;
-        let bit1 = (bp1 >> bit) & 1;
-        let color_index = (bit1 << 1) | bit0;
-
-        // Return CGRAM index (palette * 4 + color_index)
-        // Mode 0: each BG layer has 8 palettes of 4 colors each
-        (palette * 4 + color_index as usize) as u8
-    }
-
-    /// Render a single 8x8 tile in Mode 0 (2bpp)
-    /// This is kept for backward compatibility but is no longer used by render_bg_layer
-    #[allow(dead_code)]
-    fn render_tile_mode0(&self, frame: &mut Frame, params: &TileRenderParams) {
-        // In Mode 0, each tile is 16 bytes (8 rows * 2 bytes per row for 2bpp)
-        let tile_data_base = params.chr_base + (params.tile_index as usize * 16);
-
-        for row in 0..8 {
-            let actual_row = if params.flip_y { 7 - row } else { row };
-            let pixel_y = params.tile_y * 8 + row;
-            if pixel_y >= 224 {
-                break;
-            }
-
-            // Read two bitplanes for this row
-            let bp0_addr = tile_data_base + actual_row;
-            let bp1_addr = tile_data_base + actual_row + 8;
-
-            let bp0 = if bp0_addr < VRAM_SIZE {
-                self.vram[bp0_addr]
-            } else {
-                0
-            };
-            let bp1 = if bp1_addr < VRAM_SIZE {
-                self.vram[bp1_addr]
-            } else {
-                0
-            };
-
-            for col in 0..8 {
-                let actual_col = if params.flip_x { col } else { 7 - col };
-                let pixel_x = params.tile_x * 8 + col;
-                if pixel_x >= 256 {
-                    break;
-                }
-
-                // Extract color index from bitplanes
-                let bit = actual_col;
-                let bit0 = (bp0 >> bit) & 1;
-                let bit1 = (bp1 >> bit) & 1;
-                let color_index = (bit1 << 1) | bit0;
-
-                // Skip transparent pixels (color 0)
-                if color_index == 0 {
-                    continue;
-                }
-
-                // In Mode 0, each BG layer has 8 palettes of 4 colors each
-                // Palette base = palette * 4 colors
-                let cgram_index = (params.palette * 4 + color_index as usize) as u8;
-                let color = self.get_color(cgram_index);
-
-                // Set pixel in frame
-                let frame_offset = pixel_y * 256 + pixel_x;
-                frame.pixels[frame_offset] = color;
-            }
+    /// Get the color index (0-15) of one pixel of an 8x8 tile
+    ///
+    /// Tiles are stored as interleaved bitplane pairs: each row is one VRAM
+    /// word holding plane 0 in the low byte and plane 1 in the high byte.
+    /// A 4bpp tile follows the 8 words of planes 0/1 with 8 words of planes 2/3.
+    fn get_tile_pixel(&self, tile_addr: usize, bpp: usize, pixel_x: usize, pixel_y: usize) -> u8 {
+        let bit = 7 - pixel_x;
+        let mut color_index = 0;
+        for pair in 0..bpp / 2 {
+            let row_addr = (tile_addr + pair * 16 + pixel_y * 2) % VRAM_SIZE;
+            let low = self.vram[row_addr];
+            let high = self.vram[row_addr + 1];
+            color_index |= ((low >> bit) & 1) << (pair * 2);
+            color_index |= ((high >> bit) & 1) << (pair * 2 + 1);
         }
-    }
-
-    /// Get a single pixel color index from a tile in 4bpp mode (16 colors)
-    #[allow(clippy::too_many_arguments)]
-    fn get_tile_pixel_4bpp(
-        &self,
-        tile_index: u8,
-        chr_base: usize,
-        pixel_x: usize,
-        pixel_y: usize,
-        palette: usize,
-        flip_x: bool,
-        flip_y: bool,
-    ) -> u8 {
-        // In 4bpp mode, each tile is 32 bytes (8 rows * 4 bytes per row)
-        let tile_data_base = chr_base + (tile_index as usize * 32);
-
-        // Apply flip
-        let actual_row = if flip_y { 7 - pixel_y } else { pixel_y };
-        let actual_col = if flip_x { pixel_x } else { 7 - pixel_x };
-
-        // Read four bitplanes for this row
-        let bp0_addr = tile_data_base + actual_row;
-        let bp1_addr = tile_data_base + actual_row + 8;
-        let bp2_addr = tile_data_base + actual_row + 16;
-        let bp3_addr = tile_data_base + actual_row + 24;
-
-        let bp0 = if bp0_addr < VRAM_SIZE {
-            self.vram[bp0_addr]
-        } else {
-            0
-        };
-        let bp1 = if bp1_addr < VRAM_SIZE {
-            self.vram[bp1_addr]
-        } else {
-            0
-        };
-        let bp2 = if bp2_addr < VRAM_SIZE {
-            self.vram[bp2_addr]
-        } else {
-            0
-        };
-        let bp3 = if bp3_addr < VRAM_SIZE {
-            self.vram[bp3_addr]
-        } else {
-            0
-        };
-
-        // Extract color index from bitplanes
-        let bit = actual_col;
-        let bit0 = (bp0 >> bit) & 1;
-        let bit1 = (bp1 >> bit) & 1;
-        let bit2 = (bp2 >> bit) & 1;
-        let bit3 = (bp3 >> bit) & 1;
-        let color_index = (bit3 << 3) | (bit2 << 2) | (bit1 << 1) | bit0;
-
-        // Return CGRAM index (palette * 16 + color_index)
-        // Mode 1: each BG layer has 8 palettes of 16 colors each
-        (palette * 16 + color_index as usize) as u8
+        color_index
     }
 
     /// Get sprite sizes based on OBSEL register
@@ -1100,35 +1352,58 @@ impl Ppu {
         }
     }
 
-    /// Get OBJ base address in VRAM
-    fn get_obj_base_address(&self) -> usize {
-        // Bits 0-2: Name base (in 8KB units, base offset in VRAM)
-        // Bits 3-4: Name select (4KB gap between sprite tables)
+    /// Get the VRAM byte addresses of the two OBJ name tables
+    fn get_obj_base_address(&self) -> [usize; 2] {
+        // Bits 0-2: Name base, in 8K-word (16KB) units
+        // Bits 3-4: Name select, gap between the tables in 4K-word (8KB) units
+        //           minus one
         let name_base = (self.obsel & 0x07) as usize;
         let name_select = ((self.obsel >> 3) & 0x03) as usize;
 
-        // SNES sprite tiles can be located at various positions in VRAM
-        // Name base is in 8KB (0x2000 byte) units
-        // Name select adds a 4KB (0x1000 byte) gap
-        // The base calculation should be: name_base * 0x2000 + name_select * 0x1000
-        (name_base * 0x2000) + (name_select * 0x1000)
+        let first = name_base * 0x4000;
+        let second = (first + (name_select + 1) * 0x2000) % VRAM_SIZE;
+        [first, second]
     }
 
-    /// Render a single BG layer in 2bpp mode with priority handling
-    fn render_bg_layer_2bpp_priority(
+    /// Render a single BG layer with priority handling
+    ///
+    /// `bpp` is 2 or 4. Only tiles whose priority bit equals `tile_priority`
+    /// are drawn, at `render_priority` in the priority buffer.
+    fn render_bg_layer_priority(
         &self,
-        frame: &mut Frame,
-        priority_buffer: &mut [u8],
+        screen: &mut ScreenBuffer,
         bg_index: usize,
-        filter_priority: u8,
+        bpp: usize,
+        tile_priority: u8,
+        render_priority: u8,
     ) {
         // Get tilemap and CHR base addresses for this BG
         let (tilemap_base, chr_base) = self.get_bg_addresses(bg_index);
 
+        let layout = screen.layout;
+
+        // BGMODE bits 4-7 select 16x16 tiles for BG1-BG4; hires modes always
+        // use 16-pixel-wide tiles (16x8 or 16x16)
+        let tile_height = if self.bgmode & (0x10 << bg_index) != 0 {
+            16
+        } else {
+            8
+        };
+        let tile_width = if layout.hires_bg { 16 } else { tile_height };
+
         // Get tilemap size for this layer
         let (tilemap_width, tilemap_height) = self.get_tilemap_size(bg_index);
-        let tilemap_pixel_width = tilemap_width * 8;
-        let tilemap_pixel_height = tilemap_height * 8;
+        let tilemap_pixel_width = tilemap_width * tile_width;
+        let tilemap_pixel_height = tilemap_height * tile_height;
+
+        // Mode 0 gives each BG layer its own 32-color slice of CGRAM
+        let palette_base = if self.bgmode & 0x07 == 0 {
+            bg_index * 32
+        } else {
+            0
+        };
+        let colors_per_palette = 1 << bpp;
+        let bytes_per_tile = 8 * bpp;
 
         // Get scroll offsets for this layer
         let (hofs, vofs) = match bg_index {
@@ -1139,26 +1414,41 @@ impl Ppu {
             _ => (0, 0),
         };
 
-        // Render all visible tiles
-        for screen_y in 0..224 {
-            for screen_x in 0..256 {
-                // Calculate world position with scrolling
-                let world_x = ((screen_x as u16 + hofs) % tilemap_pixel_width as u16) as usize;
-                let world_y = ((screen_y as u16 + vofs) % tilemap_pixel_height as u16) as usize;
+        // Hires modes scroll horizontally in 256-pixel units over a 512-pixel
+        // line; lower resolutions repeat each BG pixel across the output
+        let (x_scale, y_scale) = (layout.x_scale(), layout.y_scale());
+        let hofs = if layout.hires_bg {
+            hofs as usize * 2
+        } else {
+            hofs as usize
+        };
 
-                // Get tile and pixel position
-                let tile_x = world_x / 8;
-                let tile_y = world_y / 8;
-                let pixel_x_in_tile = world_x % 8;
-                let pixel_y_in_tile = world_y % 8;
+        // Render the visible tiles on the buffer's lines
+        for screen_y in screen.lines.clone() {
+            // Interlaced Modes 5/6 show both fields' lines (448 in total)
+            let bg_y = if layout.interlace_bg {
+                screen_y
+            } else {
+                screen_y / y_scale
+            };
+            let world_y = (bg_y + vofs as usize) % tilemap_pixel_height;
 
-                // Get tilemap entry
-                let tilemap_offset = self.get_tilemap_offset(tile_x, tile_y, tilemap_width);
-                let tilemap_addr = tilemap_base + tilemap_offset;
+            for screen_x in 0..layout.width {
+                // Calculate world position with scrolling
+                let bg_x = if layout.hires_bg {
+                    screen_x
+                } else {
+                    screen_x / x_scale
+                };
+                let world_x = (bg_x + hofs) % tilemap_pixel_width;
 
-                if tilemap_addr + 1 >= VRAM_SIZE {
-                    continue;
-                }
+                // Get tilemap entry
+                let tilemap_offset = self.get_tilemap_offset(
+                    world_x / tile_width,
+                    world_y / tile_height,
+                    tilemap_width,
+                );
+                let tilemap_addr = (tilemap_base + tilemap_offset) % VRAM_SIZE;
 
                 // Read tile entry (format: vhopppcc cccccccc)
                 // v = vertical flip (bit 15 of 16-bit entry, bit 7 of tile_high)
@@ -1169,138 +1459,115 @@ impl Ppu {
                 let tile_low = self.vram[tilemap_addr];
                 let tile_high = self.vram[tilemap_addr + 1];
 
-                let tile_index = tile_low;
-                let palette = ((tile_high >> 2) & 0x07) as usize;
-                let flip_x = (tile_high & 0x40) != 0;
-                let flip_y = (tile_high & 0x80) != 0;
                 let priority = if (tile_high & 0x20) != 0 { 1 } else { 0 };
 
                 // Skip if this tile doesn't match the priority we're rendering
-                if priority != filter_priority {
+                if priority != tile_priority {
                     continue;
                 }
 
-                // Get pixel color from tile
-                let color = self.get_tile_pixel_mode0(
-                    tile_index,
-                    chr_base,
-                    pixel_x_in_tile,
-                    pixel_y_in_tile,
-                    palette,
-                    flip_x,
-                    flip_y,
-                );
+                let tile_number = (tile_low as usize) | ((tile_high as usize & 0x03) << 8);
+                let palette = ((tile_high >> 2) & 0x07) as usize;
+                let flip_x = (tile_high & 0x40) != 0;
+                let flip_y = (tile_high & 0x80) != 0;
 
-                // Skip transparent pixels (color 0)
-                if color == 0 {
-                    continue;
+                // Pixel within the (possibly 16-pixel) tile, after flipping
+                let mut pixel_x = world_x % tile_width;
+                let mut pixel_y = world_y % tile_height;
+                if flip_x {
+                    pixel_x = tile_width - 1 - pixel_x;
                 }
-
-                // Calculate rendering priority (0-7 scale)
-                // Priority 0 BG = priority level 1, Priority 1 BG = priority level 3
-                let render_priority = if filter_priority == 0 { 1 } else { 3 };
-
-                // Draw pixel if it has equal or higher priority
-                let frame_offset = screen_y * 256 + screen_x;
-                if render_priority <= priority_buffer[frame_offset] {
-                    frame.pixels[frame_offset] = self.get_color(color);
-                    priority_buffer[frame_offset] = render_priority;
+                if flip_y {
+                    pixel_y = tile_height - 1 - pixel_y;
                 }
-            }
-        }
-    }
 
-    /// Render a single BG layer in 4bpp mode with priority handling
-    fn render_bg_layer_4bpp_priority(
-        &self,
-        frame: &mut Frame,
-        priority_buffer: &mut [u8],
-        bg_index: usize,
-        filter_priority: u8,
-    ) {
-        // Get tilemap and CHR base addresses for this BG
-        let (tilemap_base, chr_base) = self.get_bg_addresses(bg_index);
+                // A 16x16 tile is made of tiles N, N+1, N+16 and N+17
+                // (16x8 hires tiles use N and N+1)
+                let sub_tile = (pixel_y / 8) * 16 + pixel_x / 8;
+                let tile_addr = chr_base + ((tile_number + sub_tile) & 0x3FF) * bytes_per_tile;
 
-        // Get tilemap size for this layer
-        let (tilemap_width, tilemap_height) = self.get_tilemap_size(bg_index);
-        let tilemap_pixel_width = tilemap_width * 8;
-        let tilemap_pixel_height = tilemap_height * 8;
-
-        // Get scroll offsets for this layer
-        let (hofs, vofs) = match bg_index {
-            0 => (self.bg1_hofs, self.bg1_vofs),
-            1 => (self.bg2_hofs, self.bg2_vofs),
-            2 => (self.bg3_hofs, self.bg3_vofs),
-            3 => (self.bg4_hofs, self.bg4_vofs),
-            _ => (0, 0),
-        };
-
-        // Render all visible tiles
-        for screen_y in 0..224 {
-            for screen_x in 0..256 {
-                // Calculate world position with scrolling
-                let world_x = ((screen_x as u16 + hofs) % tilemap_pixel_width as u16) as usize;
-                let world_y = ((screen_y as u16 + vofs) % tilemap_pixel_height as u16) as usize;
-
-                // Get tile and pixel position
-                let tile_x = world_x / 8;
-                let tile_y = world_y / 8;
-                let pixel_x_in_tile = world_x % 8;
-                let pixel_y_in_tile = world_y % 8;
-
-                // Get tilemap entry
-                let tilemap_offset = self.get_tilemap_offset(tile_x, tile_y, tilemap_width);
-                let tilemap_addr = tilemap_base + tilemap_offset;
-
-                if tilemap_addr + 1 >= VRAM_SIZE {
-                    continue;
-                }
-
-                // Read tile entry (format: vhopppcc cccccccc)
-                // v = vertical flip (bit 15 of 16-bit entry, bit 7 of tile_high)
-                // h = horizontal flip (bit 14 of 16-bit entry, bit 6 of tile_high)
-                // o = priority (bit 13 of 16-bit entry, bit 5 of tile_high)
-                // ppp = palette (bits 12-10 of 16-bit entry, bits 4-2 of tile_high)
-                // cccccccccc = tile number (bits 9-0)
-                let tile_low = self.vram[tilemap_addr];
-                let tile_high = self.vram[tilemap_addr + 1];
+                let color_index =
+                    self.get_tile_pixel(tile_addr % VRAM_SIZE, bpp, pixel_x % 8, pixel_y % 8);
 
-                let tile_index = tile_low;
-                let palette = ((tile_high >> 2) & 0x07) as usize;
-                let flip_x = (tile_high & 0x40) != 0;
-                let flip_y = (tile_high & 0x80) != 0;
-                let priority = if (tile_high & 0x20) != 0 { 1 } else { 0 };
-
-                // Skip if this tile doesn't match the priority we're rendering
-                if priority != filter_priority {
+                // Skip transparent pixels (color 0 of every palette)
+                if color_index == 0 {
                     continue;
                 }
 
-                // Get pixel color from tile (4bpp)
-                let color = self.get_tile_pixel_4bpp(
-                    tile_index,
-                    chr_base,
-                    pixel_x_in_tile,
-                    pixel_y_in_tile,
-                    palette,
-                    flip_x,
-                    flip_y,
+                let cgram_index =
+                    palette_base + palette * colors_per_palette + color_index as usize;
+                screen.plot(
+                    screen_y * layout.width + screen_x,
+                    self.get_color(cgram_index as u8),
+                    render_priority,
                 );
+            }
+        }
+    }
 
-                // Skip transparent pixels (color 0)
-                if color == 0 {
+    /// Render the Mode 7 BG
+    ///
+    /// The BG is a 1024x1024-pixel plane of 8x8 tiles: the tilemap (128x128
+    /// tile numbers) sits in the low bytes of the first 16K VRAM words and
+    /// the 8bpp tile pixels in the high bytes. Each screen pixel is mapped
+    /// onto the plane through the M7A-M7D matrix around the center
+    /// (M7X, M7Y), after scrolling by M7HOFS/M7VOFS. HDMA can change the
+    /// matrix between lines for perspective effects.
+    fn render_mode7(&self, screen: &mut ScreenBuffer, render_priority: u8) {
+        let layout = screen.layout;
+        let (x_scale, y_scale) = (layout.x_scale(), layout.y_scale());
+        let [a, b, c, d] = [self.m7a, self.m7b, self.m7c, self.m7d].map(i32::from);
+        let (center_x, center_y) = (self.m7x as i32, self.m7y as i32);
+
+        // Scroll relative to the center, wrapped to a signed 10-bit value
+        let clip = |v: i32| {
+            if v & 0x2000 != 0 {
+                v | !0x3FF
+            } else {
+                v & 0x3FF
+            }
+        };
+        let h = clip(self.m7hofs as i32 - center_x);
+        let v = clip(self.m7vofs as i32 - center_y);
+
+        let screen_over = self.m7sel >> 6;
+        let flip_x = self.m7sel & 0x01 != 0;
+        let flip_y = self.m7sel & 0x02 != 0;
+
+        for screen_y in screen.lines.clone() {
+            let line = screen_y / y_scale;
+            let y = if flip_y { 255 - line } else { line } as i32;
+
+            // Plane position of the line's first pixel (8.8 fixed point);
+            // the hardware drops the low 6 bits of each product
+            let start_x = ((a * h) & !63) + ((b * v) & !63) + ((b * y) & !63) + (center_x << 8);
+            let start_y = ((c * h) & !63) + ((d * v) & !63) + ((d * y) & !63) + (center_y << 8);
+
+            for column in 0..SCREEN_WIDTH {
+                let x = if flip_x { 255 - column } else { column } as i32;
+                let plane_x = (start_x + a * x) >> 8;
+                let plane_y = (start_y + c * x) >> 8;
+
+                let outside = !(0..1024).contains(&plane_x) || !(0..1024).contains(&plane_y);
+                let (plane_x, plane_y) = (plane_x as usize & 1023, plane_y as usize & 1023);
+                let tile = match (outside, screen_over) {
+                    (true, 2) => continue,
+                    (true, 3) => 0,
+                    _ => self.vram[((plane_y / 8) * 128 + plane_x / 8) * 2] as usize,
+                };
+
+                let color_index = self.vram[(tile * 64 + (plane_y % 8) * 8 + plane_x % 8) * 2 + 1];
+                if color_index == 0 {
                     continue;
                 }
 
-                // Calculate rendering priority (0-7 scale)
-                // Priority 0 BG = priority level 1, Priority 1 BG = priority level 3
-                let render_priority = if filter_priority == 0 { 1 } else { 3 };
-
-                // Draw pixel if it has equal or higher priority
-                let frame_offset = screen_y * 256 + screen_x;
-                if render_priority <= priority_buffer[frame_offset] {
-                    frame.pixels[frame_offset] = self.get_color(color);
-                    priority_buffer[frame_offset] = render_priority;
+                let color = self.get_color(color_index);
+                for sub_x in 0..x_scale {
+                    screen.plot(
+                        screen_y * layout.width + column * x_scale + sub_x,
+                        color,
+                        render_priority,
+                    );
                 }
             }
         }
@@ -1309,16 +1576,15 @@ impl Ppu {
     /// Render sprites with priority filtering
     fn render_sprites_priority(
         &self,
-        frame: &mut Frame,
-        priority_buffer: &mut [u8],
+        screen: &mut ScreenBuffer,
         min_priority: u8,
         max_priority: u8,
     ) {
         // Get sprite size configuration from OBSEL register
         let (small_size, large_size) = self.get_sprite_sizes();
 
-        // Get OBJ base address
-        let obj_base = self.get_obj_base_address();
+        // Get OBJ name table addresses
+        let obj_tables = self.get_obj_base_address();
 
         // Track sprites and tiles per scanline (SNES hardware limits)
         // - Maximum 32 sprites per scanline
@@ -1349,15 +1615,19 @@ impl Ppu {
                 0
             };
 
-            // Bit 0 of high_bits: X MSB (9th bit of X coordinate)
+            // Bit 0 of high_bits: X MSB (sign of the 9-bit X coordinate)
             // Bit 1 of high_bits: Size toggle (0=small, 1=large)
-            let x = x | (((high_bits & 0x01) as i16) << 8);
+            let x = if high_bits & 0x01 != 0 { x - 256 } else { x };
             let is_large = (high_bits & 0x02) != 0;
 
             // Get sprite size
             let (width, height) = if is_large { large_size } else { small_size };
 
+            // Sprites hanging off the bottom of the 256-line Y space wrap to the top
+            let y = if y + height as i16 > 256 { y - 256 } else { y };
+
             // Parse attributes
+            let name_table = (attr & 0x01) as usize;
             let palette = ((attr >> 1) & 0x07) as usize;
             let sprite_priority = (attr >> 4) & 0x03;
             let flip_x = (attr & 0x40) != 0;
@@ -1404,14 +1674,27 @@ impl Ppu {
                 tiles_per_scanline[scanline] += tiles_wide;
             }
 
+            // Skip sprites that miss the lines being drawn
+            let layout = screen.layout;
+            let (first_line, line_count) = if layout.obj_interlace {
+                (y as isize * 2, height as isize)
+            } else {
+                let y_scale = layout.y_scale() as isize;
+                (y as isize * y_scale, height as isize * y_scale)
+            };
+            if first_line + line_count <= screen.lines.start as isize
+                || first_line >= screen.lines.end as isize
+            {
+                continue;
+            }
+
             // Render sprite pixels with priority
             self.render_sprite_priority(
-                frame,
-                priority_buffer,
+                screen,
                 x,
                 y,
                 tile,
-                obj_base,
+                obj_tables[name_table],
                 palette,
                 sprite_priority,
                 width,
@@ -1426,8 +1709,7 @@ impl Ppu {
     #[allow(clippy::too_many_arguments)]
     fn render_sprite_priority(
         &self,
-        frame: &mut Frame,
-        priority_buffer: &mut [u8],
+        screen: &mut ScreenBuffer,
         x: i16,
         y: i16,
         tile: u8,
@@ -1448,13 +1730,22 @@ impl Ppu {
         // Sprite priority 0-1 = priority level 2, Sprite priority 2-3 = priority level 4
         let render_priority = if sprite_priority < 2 { 2 } else { 4 };
 
+        // Sprites are always 256-pixel resolution; hires and interlaced
+        // frames repeat each sprite pixel to fill the larger output
+        let layout = screen.layout;
+        let (x_scale, y_scale) = (layout.x_scale(), layout.y_scale());
+
         for ty in 0..tiles_high {
             for tx in 0..tiles_wide {
-                // Calculate tile number (tiles are arranged in rows)
-                let tile_num = tile as usize + (ty * 16) + tx;
+                // Flipping mirrors the whole sprite, so the tile order flips too
+                let src_tx = if flip_x { tiles_wide - 1 - tx } else { tx };
+                let src_ty = if flip_y { tiles_high - 1 - ty } else { ty };
 
-                // Calculate tile data address
-                let tile_addr = obj_base + (tile_num * 32);
+                // The name table is a 16x16 grid of tiles; rows and columns
+                // wrap within it
+                let row = ((tile as usize >> 4) + src_ty) & 0x0F;
+                let col = ((tile as usize & 0x0F) + src_tx) & 0x0F;
+                let tile_addr = (obj_base + (row * 16 + col) * 32) % VRAM_SIZE;
 
                 // Render this 8x8 tile
                 for py in 0..8 {
@@ -1466,46 +1757,12 @@ impl Ppu {
                         let screen_x = x + (tx * 8) as i16 + px as i16;
                         let screen_y = y + (ty * 8) as i16 + py as i16;
 
-                        // Bounds check
-                        if !(0..256).contains(&screen_x) || !(0..224).contains(&screen_y) {
+                        // Bounds check (lines are checked in output space below)
+                        if !(0..SCREEN_WIDTH as i16).contains(&screen_x) {
                             continue;
                         }
 
-                        // Read 4 bitplanes for this pixel
-                        let row_offset = actual_py;
-                        let bp0_addr = tile_addr + row_offset;
-                        let bp1_addr = tile_addr + row_offset + 8;
-                        let bp2_addr = tile_addr + row_offset + 16;
-                        let bp3_addr = tile_addr + row_offset + 24;
-
-                        let bp0 = if bp0_addr < VRAM_SIZE {
-                            self.vram[bp0_addr]
-                        } else {
-                            0
-                        };
-                        let bp1 = if bp1_addr < VRAM_SIZE {
-                            self.vram[bp1_addr]
-                        } else {
-                            0
-                        };
-                        let bp2 = if bp2_addr < VRAM_SIZE {
-                            self.vram[bp2_addr]
-                        } else {
-                            0
-                        };
-                        let bp3 = if bp3_addr < VRAM_SIZE {
-                            self.vram[bp3_addr]
-                        } else {
-                            0
-                        };
-
-                        // Extract color index from bitplanes
-                        let bit = actual_px;
-                        let bit0 = (bp0 >> bit) & 1;
-                        let bit1 = (bp1 >> bit) & 1;
-                        let bit2 = (bp2 >> bit) & 1;
-                        let bit3 = (bp3 >> bit) & 1;
-                        let color_index = (bit3 << 3) | (bit2 << 2) | (bit1 << 1) | bit0;
+                        let color_index = self.get_tile_pixel(tile_addr, 4, actual_px, actual_py);
 
                         // Skip transparent pixels
                         if color_index == 0 {
@@ -1516,13 +1773,24 @@ impl Ppu {
                         let cgram_index = (128 + palette * 16 + color_index as usize) as u8;
                         let color = self.get_color(cgram_index);
 
-                        // Draw pixel if it has equal or higher priority
-                        let frame_offset = screen_y as usize * 256 + screen_x as usize;
-                        if frame_offset < frame.pixels.len()
-                            && render_priority <= priority_buffer[frame_offset]
-                        {
-                            frame.pixels[frame_offset] = color;
-                            priority_buffer[frame_offset] = render_priority;
+                        // OBJ interlace draws each sprite row on one 448-line
+                        // row (sprites appear half height); otherwise a row
+                        // covers every output line of its scanline
+                        let (first_line, line_count) = if layout.obj_interlace {
+                            (y as isize * 2 + (screen_y - y) as isize, 1)
+                        } else {
+                            (screen_y as isize * y_scale as isize, y_scale)
+                        };
+
+                        for line in first_line..first_line + line_count as isize {
+                            if !(0..layout.height as isize).contains(&line) {
+                                continue;
+                            }
+                            let row_offset = line as usize * layout.width;
+                            for column in 0..x_scale {
+                                let offset = row_offset + screen_x as usize * x_scale + column;
+                                screen.plot(offset, color, render_priority);
+                            }
                         }
                     }
                 }
@@ -1540,8 +1808,11 @@ impl Ppu {
         // SNES color format: 15-bit BGR (0bbbbbgggggrrrrr)
         let low = self.cgram[addr];
         let high = self.cgram[addr + 1];
-        let color15 = (low as u16) | ((high as u16) << 8);
+        Self::rgb15_to_argb((low as u16) | ((high as u16) << 8))
+    }
 
+    /// Convert a 15-bit BGR color (0bbbbbgggggrrrrr) to ARGB
+    fn rgb15_to_argb(color15: u16) -> u32 {
         // Convert from 5-bit per channel to 8-bit per channel
         // Simple shift by 3 (matches test expectations)
         let r = ((color15 & 0x001F) << 3) as u8;
@@ -1922,27 +2193,23 @@ mod tests {
 
         // Name base = 0, name select = 0
         ppu.obsel = 0x00;
-        let base = ppu.get_obj_base_address();
         assert_eq!(
-            base, 0x0000,
-            "OBSEL=0x00: name_base=0, name_select=0 -> 0*0x2000 + 0*0x1000 = 0x0000"
+            ppu.get_obj_base_address(),
+            [0x0000, 0x2000],
+            "OBSEL=0x00: first table at 0, second 4K words (8KB) later"
         );
 
         // Name base = 2, name select = 1
         ppu.obsel = 0x0A; // Bits 0-2 = 2 (0b010), Bits 3-4 = 1 (0b01)
-        let base = ppu.get_obj_base_address();
         assert_eq!(
-            base, 0x5000,
-            "OBSEL=0x0A: name_base=2, name_select=1 -> 2*0x2000 + 1*0x1000 = 0x5000"
+            ppu.get_obj_base_address(),
+            [0x8000, 0xC000],
+            "OBSEL=0x0A: name_base=2 -> 2*0x4000, gap (1+1)*0x2000"
         );
 
-        // Name base = 0, name select = 1
-        ppu.obsel = 0x08; // Bits 0-2 = 0, Bits 3-4 = 1
-        let base = ppu.get_obj_base_address();
-        assert_eq!(
-            base, 0x1000,
-            "OBSEL=0x08: name_base=0, name_select=1 -> 0x1000"
-        );
+        // Name base = 3, name select = 3: the second table wraps around VRAM
+        ppu.obsel = 0x1B;
+        assert_eq!(ppu.get_obj_base_address(), [0xC000, 0x4000]);
     }
 
     #[test]
@@ -2411,4 +2678,484 @@ mod tests {
             "Mode 1 with typical commercial settings should produce visible output"
         );
     }
+
+    /// Store an 8x8 tile of color indices in SNES planar format
+    fn put_tile(ppu: &mut Ppu, addr: usize, bpp: usize, pixels: &[[u8; 8]; 8]) {
+        for (row, line) in pixels.iter().enumerate() {
+            for (col, &ci) in line.iter().enumerate() {
+                let bit = 7 - col;
+                for plane in 0..bpp {
+                    let byte = addr + (plane / 2) * 16 + row * 2 + (plane & 1);
+                    if (ci >> plane) & 1 != 0 {
+                        ppu.vram[byte] |= 1 << bit;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Tile whose pixels all use one color index
+    fn solid_tile(ci: u8) -> [[u8; 8]; 8] {
+        [[ci; 8]; 8]
+    }
+
+    fn set_color(ppu: &mut Ppu, index: usize, color15: u16) {
+        ppu.cgram[index * 2] = color15 as u8;
+        ppu.cgram[index * 2 + 1] = (color15 >> 8) as u8;
+    }
+
+    #[test]
+    fn test_bg_tile_format() {
+        let mut ppu = Ppu::new();
+        ppu.bgmode = 0x01; // Mode 1, BG1 is 4bpp
+        ppu.bg12nba = 0x01; // BG1 CHR at $2000
+        ppu.tm = 0x01;
+
+        // Tile $101 has color 9 in its leftmost column and 0 elsewhere
+        let mut pixels = solid_tile(0);
+        for row in pixels.iter_mut() {
+            row[0] = 9;
+        }
+        put_tile(&mut ppu, 0x2000 + 0x101 * 32, 4, &pixels);
+        // Tile $001 would be picked if the tile number were truncated to 8 bits
+        put_tile(&mut ppu, 0x2000 + 32, 4, &solid_tile(1));
+
+        // Tilemap entry (0,0): tile $101, palette 2
+        ppu.vram[0] = 0x01;
+        ppu.vram[1] = (2 << 2) | 0x01;
+
+        set_color(&mut ppu, 0, 0x0000);
+        set_color(&mut ppu, 2 * 16, 0x7C00); // Color 0 of palette 2 is never drawn
+        set_color(&mut ppu, 2 * 16 + 9, 0x001F);
+
+        let frame = ppu.render_frame();
+        assert_eq!(frame.pixels[0], 0xFFF80000, "Leftmost pixel is bit 7");
+        assert_eq!(frame.pixels[256 * 7], 0xFFF80000);
+        assert_eq!(frame.pixels[1], 0xFF000000, "Color 0 shows the backdrop");
+        assert_eq!(frame.pixels[7], 0xFF000000);
+    }
+
+    #[test]
+    fn test_mode0_layer_palettes() {
+        let mut ppu = Ppu::new();
+        ppu.bgmode = 0x00;
+        ppu.bg2sc = 0x04; // BG2 tilemap at $0800
+        ppu.bg12nba = 0x10; // BG2 CHR at $2000
+        ppu.tm = 0x02;
+
+        put_tile(&mut ppu, 0x2000, 2, &solid_tile(3));
+        // Tilemap entry (0,0): tile 0, palette 1
+        ppu.vram[0x0800 + 1] = 1 << 2;
+
+        // BG2 colors start at CGRAM 32 in Mode 0
+        set_color(&mut ppu, 4 + 3, 0x7C00);
+        set_color(&mut ppu, 32 + 4 + 3, 0x03E0);
+
+        let frame = ppu.render_frame();
+        assert_eq!(frame.pixels[0], 0xFF00F800);
+    }
+
+    #[test]
+    fn test_bg_16x16_tiles() {
+        let mut ppu = Ppu::new();
+        ppu.bgmode = 0x11; // Mode 1, BG1 uses 16x16 tiles
+        ppu.bg12nba = 0x01;
+        ppu.tm = 0x01;
+
+        // A 16x16 tile at N=2 uses tiles 2, 3, 18 and 19
+        put_tile(&mut ppu, 0x2000 + 2 * 32, 4, &solid_tile(1));
+        put_tile(&mut ppu, 0x2000 + 3 * 32, 4, &solid_tile(2));
+        put_tile(&mut ppu, 0x2000 + 18 * 32, 4, &solid_tile(3));
+        put_tile(&mut ppu, 0x2000 + 19 * 32, 4, &solid_tile(4));
+        for ci in 1..5 {
+            set_color(&mut ppu, ci, ci as u16);
+        }
+
+        // Entry (0,0): tile 2; entry (1,0): tile 2 flipped horizontally
+        ppu.vram[0] = 2;
+        ppu.vram[2] = 2;
+        ppu.vram[3] = 0x40;
+
+        let frame = ppu.render_frame();
+        let ci_at = |x: usize, y: usize| (frame.pixels[y * 256 + x] >> 19) & 0x1F;
+        assert_eq!(ci_at(0, 0), 1);
+        assert_eq!(ci_at(8, 0), 2);
+        assert_eq!(ci_at(0, 8), 3);
+        assert_eq!(ci_at(15, 15), 4);
+        // Flipping swaps the left and right halves
+        assert_eq!(ci_at(16, 0), 2);
+        assert_eq!(ci_at(24, 0), 1);
+    }
+
+    #[test]
+    fn test_sprite_tiles_and_flip() {
+        let mut ppu = Ppu::new();
+        ppu.tm = 0x10;
+        ppu.obsel = 0x01; // Tables at $4000 and $6000, 8x8/16x16
+
+        // 16x16 sprite at X=-8 (9th X bit set) made of tiles $10, $11, $20, $21
+        // in the second name table, flipped horizontally
+        put_tile(&mut ppu, 0x6000 + 0x10 * 32, 4, &solid_tile(1));
+        put_tile(&mut ppu, 0x6000 + 0x11 * 32, 4, &solid_tile(2));
+        put_tile(&mut ppu, 0x6000 + 0x20 * 32, 4, &solid_tile(3));
+        put_tile(&mut ppu, 0x6000 + 0x21 * 32, 4, &solid_tile(4));
+        for ci in 1..5 {
+            set_color(&mut ppu, 128 + ci, ci as u16);
+        }
+        ppu.oam[0] = 248;
+        ppu.oam[1] = 20;
+        ppu.oam[2] = 0x10;
+        ppu.oam[3] = 0x40 | 0x01;
+        ppu.oam[512] = 0x03; // X MSB, large
+                             // Move the other sprites off screen
+        for i in 1..128 {
+            ppu.oam[i * 4 + 1] = 224;
+        }
+
+        let frame = ppu.render_frame();
+        let ci_at = |x: usize, y: usize| (frame.pixels[y * 256 + x] >> 19) & 0x1F;
+        // Only the right half is on screen; flipped, it shows tiles $10/$20
+        assert_eq!(ci_at(0, 20), 1);
+        assert_eq!(ci_at(7, 28), 3);
+        assert_eq!(ci_at(8, 20), 0);
+    }
+
+    #[test]
+    fn test_sprite_pixel_order() {
+        let mut ppu = Ppu::new();
+        ppu.tm = 0x10;
+
+        let mut pixels = solid_tile(0);
+        pixels[0][0] = 1;
+        put_tile(&mut ppu, 0, 4, &pixels);
+        set_color(&mut ppu, 129, 0x001F);
+        ppu.oam[0] = 100;
+        ppu.oam[1] = 50;
+        for i in 1..128 {
+            ppu.oam[i * 4 + 1] = 224;
+        }
+
+        let frame = ppu.render_frame();
+        assert_eq!(frame.pixels[50 * 256 + 100], 0xFFF80000);
+        assert_eq!(frame.pixels[50 * 256 + 107], 0xFF000000);
+
+        // Horizontal flip moves the pixel to the right edge
+        ppu.oam[3] = 0x40;
+        let frame = ppu.render_frame();
+        assert_eq!(frame.pixels[50 * 256 + 100], 0xFF000000);
+        assert_eq!(frame.pixels[50 * 256 + 107], 0xFFF80000);
+    }
+
+    #[test]
+    fn test_sprite_bg_priority() {
+        let mut ppu = Ppu::new();
+        ppu.bgmode = 0x01;
+        ppu.bg12nba = 0x01;
+        ppu.obsel = 0x02; // Sprite tiles at $8000
+        ppu.tm = 0x11;
+
+        // BG1: tile (0,0) low priority, tile (1,0) high priority
+        put_tile(&mut ppu, 0x2000, 4, &solid_tile(1));
+        ppu.vram[3] = 0x20;
+        set_color(&mut ppu, 1, 0x001F);
+
+        // Priority 1 sprite covering both tiles
+        put_tile(&mut ppu, 0x8000, 4, &solid_tile(1));
+        put_tile(&mut ppu, 0x8000 + 32, 4, &solid_tile(1));
+        set_color(&mut ppu, 129, 0x03E0);
+        ppu.oam[0] = 0;
+        ppu.oam[1] = 0;
+        ppu.oam[3] = 1 << 4;
+        ppu.oam[4] = 8;
+        ppu.oam[5] = 0;
+        ppu.oam[6] = 1;
+        ppu.oam[7] = 1 << 4;
+        for i in 2..128 {
+            ppu.oam[i * 4 + 1] = 224;
+        }
+
+        let frame = ppu.render_frame();
+        assert_eq!(frame.pixels[0], 0xFF00F800, "Sprite above low priority BG");
+        assert_eq!(frame.pixels[8], 0xFFF80000, "High priority BG above sprite");
+    }
+
+    #[test]
+    #[cfg(feature = "opengl")]
+    fn test_render_obj_lines() {
+        let mut ppu = Ppu::new();
+        ppu.obsel = 0x02; // Sprite tiles at $8000
+        put_tile(&mut ppu, 0x8000, 4, &solid_tile(1));
+        set_color(&mut ppu, 129, 0x03E0);
+        ppu.oam[0] = 16;
+        ppu.oam[1] = 4;
+        ppu.oam[3] = 3 << 4; // Priority 3
+        for i in 1..128 {
+            ppu.oam[i * 4 + 1] = 224;
+        }
+
+        let obj = ppu.render_obj_lines(8..14);
+        assert_eq!(obj.len(), 256 * 6);
+        assert_eq!(obj[16], 0x0400F800, "Green with render priority 4");
+        assert_eq!(obj[15], 0, "No sprite");
+        assert_eq!(obj[4 * 256 + 16], 0, "Below the sprite");
+    }
+
+    #[test]
+    fn test_mode5_hires() {
+        let mut ppu = Ppu::new();
+        ppu.bgmode = 0x05; // Mode 5, BG1 4bpp with 16x8 tiles
+        ppu.bg12nba = 0x01;
+        ppu.tm = 0x01;
+        ppu.ts = 0x01;
+
+        // A 16x8 hires tile at N=2 uses tiles 2 and 3
+        put_tile(&mut ppu, 0x2000 + 2 * 32, 4, &solid_tile(1));
+        put_tile(&mut ppu, 0x2000 + 3 * 32, 4, &solid_tile(2));
+        for ci in 1..3 {
+            set_color(&mut ppu, ci, ci as u16);
+        }
+        ppu.vram[0] = 2;
+
+        let frame = ppu.render_frame();
+        assert_eq!((frame.width, frame.height), (512, 448));
+        let ci_at = |x: usize, y: usize| (frame.pixels[y * 512 + x] >> 19) & 0x1F;
+        assert_eq!(ci_at(0, 0), 1);
+        assert_eq!(ci_at(7, 0), 1);
+        assert_eq!(ci_at(8, 0), 2);
+        assert_eq!(ci_at(15, 0), 2);
+        assert_eq!(ci_at(16, 0), 0, "Next tilemap entry is 16 hires pixels on");
+        // Without interlace each BG line is shown twice
+        assert_eq!(ci_at(0, 15), 1);
+        assert_eq!(ci_at(0, 16), 0);
+
+        // With BG1 only on the main screen, even columns show the fixed color
+        ppu.ts = 0x00;
+        ppu.write_register(0x2132, 0x20 | 0x05); // Fixed color red = 5
+        let frame = ppu.render_frame();
+        let ci_at = |x: usize, y: usize| (frame.pixels[y * 512 + x] >> 19) & 0x1F;
+        assert_eq!(ci_at(0, 0), 5);
+        assert_eq!(ci_at(1, 0), 1);
+        assert_eq!(ci_at(2, 0), 5);
+    }
+
+    #[test]
+    fn test_mode5_interlace() {
+        let mut ppu = Ppu::new();
+        ppu.bgmode = 0x05;
+        ppu.bg12nba = 0x01;
+        ppu.tm = 0x01;
+        ppu.ts = 0x01;
+        ppu.write_register(0x2133, 0x01); // Interlace
+
+        put_tile(&mut ppu, 0x2000 + 2 * 32, 4, &solid_tile(1));
+        put_tile(&mut ppu, 0x2000 + 3 * 32, 4, &solid_tile(1));
+        set_color(&mut ppu, 1, 1);
+        ppu.vram[0] = 2;
+
+        // Both fields are woven together: a tile covers 8 of the 448 lines
+        let frame = ppu.render_frame();
+        assert_eq!((frame.width, frame.height), (512, 448));
+        let ci_at = |x: usize, y: usize| (frame.pixels[y * 512 + x] >> 19) & 0x1F;
+        assert_eq!(ci_at(0, 7), 1);
+        assert_eq!(ci_at(0, 8), 0);
+
+        // Vertical scroll counts 448-line rows
+        ppu.bg1_vofs = 4;
+        let frame = ppu.render_frame();
+        let ci_at = |x: usize, y: usize| (frame.pixels[y * 512 + x] >> 19) & 0x1F;
+        assert_eq!(ci_at(0, 3), 1);
+        assert_eq!(ci_at(0, 4), 0);
+    }
+
+    #[test]
+    fn test_pseudo_hires() {
+        let mut ppu = Ppu::new();
+        ppu.bgmode = 0x01;
+        ppu.bg12nba = 0x01;
+        ppu.tm = 0x01;
+        ppu.write_register(0x2133, 0x08); // Pseudo-hires
+        ppu.write_register(0x2132, 0x40 | 0x1F); // Fixed color green = 31
+
+        put_tile(&mut ppu, 0x2000, 4, &solid_tile(3));
+        set_color(&mut ppu, 3, 0x001F);
+
+        let frame = ppu.render_frame();
+        assert_eq!((frame.width, frame.height), (512, 448));
+        // Sub-screen (fixed color) on even columns, main screen on odd ones
+        assert_eq!(frame.pixels[0], 0xFF00F800);
+        assert_eq!(frame.pixels[1], 0xFFF80000);
+        assert_eq!(frame.pixels[2], 0xFF00F800);
+        assert_eq!(frame.pixels[447 * 512 + 511], 0xFFF80000);
+
+        // Back to normal resolution
+        ppu.write_register(0x2133, 0x00);
+        let frame = ppu.render_frame();
+        assert_eq!((frame.width, frame.height), (256, 224));
+        assert_eq!(frame.pixels[0], 0xFFF80000);
+    }
+
+    #[test]
+    fn test_obj_interlace() {
+        let mut ppu = Ppu::new();
+        ppu.tm = 0x10;
+        put_tile(&mut ppu, 0, 4, &solid_tile(1));
+        set_color(&mut ppu, 129, 1);
+        ppu.oam[1] = 10; // 8x8 sprite 0 at (0, 10)
+        for i in 1..128 {
+            ppu.oam[i * 4 + 1] = 224;
+        }
+
+        // Interlace alone: each sprite row is repeated on both fields
+        ppu.write_register(0x2133, 0x01);
+        let frame = ppu.render_frame();
+        let ci_at = |x: usize, y: usize| (frame.pixels[y * 512 + x] >> 19) & 0x1F;
+        assert_eq!(ci_at(0, 19), 0);
+        assert_eq!(ci_at(0, 20), 1);
+        assert_eq!(ci_at(15, 35), 1);
+        assert_eq!(ci_at(16, 20), 0);
+        assert_eq!(ci_at(0, 36), 0);
+
+        // OBJ interlace: sprite rows go on alternate fields, so it is half height
+        ppu.write_register(0x2133, 0x03);
+        let frame = ppu.render_frame();
+        let ci_at = |x: usize, y: usize| (frame.pixels[y * 512 + x] >> 19) & 0x1F;
+        assert_eq!(ci_at(0, 20), 1);
+        assert_eq!(ci_at(0, 27), 1);
+        assert_eq!(ci_at(0, 28), 0);
+    }
+
+    #[test]
+    fn test_mode7_registers() {
+        let mut ppu = Ppu::new();
+        ppu.write_register(0x211A, 0xC3);
+        assert_eq!(ppu.m7sel, 0xC3);
+
+        // Each matrix register takes the low byte, then the high byte
+        ppu.write_register(0x211B, 0x00);
+        ppu.write_register(0x211B, 0x01);
+        ppu.write_register(0x211E, 0x80);
+        ppu.write_register(0x211E, 0xFF);
+        assert_eq!(ppu.m7a, 0x0100);
+        assert_eq!(ppu.m7d, -0x80);
+
+        // Center and scroll are signed 13-bit
+        ppu.write_register(0x211F, 0xFF);
+        ppu.write_register(0x211F, 0x1F);
+        assert_eq!(ppu.m7x, -1);
+        ppu.write_register(0x210D, 0x34);
+        ppu.write_register(0x210D, 0x02);
+        assert_eq!(ppu.m7hofs, 0x234);
+        assert_eq!(ppu.bg1_hofs, 0x234);
+
+        // MPY: M7A times the high byte of M7B, as a signed 24-bit result
+        ppu.write_register(0x211B, 0x34);
+        ppu.write_register(0x211B, 0x12);
+        ppu.write_register(0x211C, 0x00);
+        ppu.write_register(0x211C, 0xFE); // -2
+        let mpy = (0x2134..=0x2136).map(|addr| ppu.read_register(addr) as u32);
+        let product = mpy.enumerate().fold(0, |acc, (i, b)| acc | b << (i * 8));
+        assert_eq!(product, (-0x2468i32 as u32) & 0xFF_FFFF);
+    }
+
+    /// Set up a Mode 7 plane whose tile columns alternate between tile 1
+    /// (color 1) and tile 2 (color 2), with an identity matrix
+    fn mode7_stripes() -> Ppu {
+        let mut ppu = Ppu::new();
+        ppu.bgmode = 0x07;
+        ppu.tm = 0x01;
+        ppu.m7a = 0x100;
+        ppu.m7d = 0x100;
+        for i in 0..128 * 128 {
+            ppu.vram[i * 2] = 1 + (i % 2) as u8;
+        }
+        for pixel in 0..64 {
+            ppu.vram[(64 + pixel) * 2 + 1] = 1;
+            ppu.vram[(128 + pixel) * 2 + 1] = 2;
+        }
+        for ci in 1..3 {
+            set_color(&mut ppu, ci, ci as u16);
+        }
+        ppu
+    }
+
+    #[test]
+    fn test_mode7_rendering() {
+        let mut ppu = mode7_stripes();
+        let frame = ppu.render_frame();
+        let ci_at = |x: usize, y: usize| (frame.pixels[y * 256 + x] >> 19) & 0x1F;
+        assert_eq!(ci_at(0, 0), 1);
+        assert_eq!(ci_at(7, 100), 1);
+        assert_eq!(ci_at(8, 0), 2);
+        assert_eq!(ci_at(16, 200), 1);
+
+        // Scroll by 4 pixels
+        ppu.m7hofs = 4;
+        let frame = ppu.render_frame();
+        let ci_at = |x: usize, y: usize| (frame.pixels[y * 256 + x] >> 19) & 0x1F;
+        assert_eq!(ci_at(3, 0), 1);
+        assert_eq!(ci_at(4, 0), 2);
+
+        // M7A = 2.0 shrinks the plane to half width
+        ppu.m7hofs = 0;
+        ppu.m7a = 0x200;
+        let frame = ppu.render_frame();
+        let ci_at = |x: usize, y: usize| (frame.pixels[y * 256 + x] >> 19) & 0x1F;
+        assert_eq!(ci_at(3, 0), 1);
+        assert_eq!(ci_at(4, 0), 2);
+        assert_eq!(ci_at(8, 0), 1);
+
+        // A 90 degree rotation turns the columns into rows
+        ppu.m7a = 0;
+        ppu.m7b = 0x100;
+        ppu.m7c = 0x100;
+        ppu.m7d = 0;
+        let frame = ppu.render_frame();
+        let ci_at = |x: usize, y: usize| (frame.pixels[y * 256 + x] >> 19) & 0x1F;
+        assert_eq!(ci_at(100, 0), 1);
+        assert_eq!(ci_at(100, 8), 2);
+        assert_eq!(ci_at(200, 16), 1);
+
+        // Horizontal flip mirrors the screen
+        ppu.m7b = 0;
+        ppu.m7c = 0;
+        ppu.m7a = 0x100;
+        ppu.m7d = 0x100;
+        ppu.m7sel = 0x01;
+        let frame = ppu.render_frame();
+        let ci_at = |x: usize, y: usize| (frame.pixels[y * 256 + x] >> 19) & 0x1F;
+        assert_eq!(ci_at(255, 0), 1);
+        assert_eq!(ci_at(247, 0), 2);
+    }
+
+    #[test]
+    fn test_mode7_screen_over() {
+        let mut ppu = mode7_stripes();
+        set_color(&mut ppu, 0, 31);
+        // Scrolled 8 pixels left of the plane
+        ppu.m7hofs = -8;
+
+        // Wrap around: the last tile column is tile 2
+        let frame = ppu.render_frame();
+        let ci_at = |x: usize, y: usize| (frame.pixels[y * 256 + x] >> 19) & 0x1F;
+        assert_eq!(ci_at(0, 0), 2);
+        assert_eq!(ci_at(8, 0), 1);
+
+        // Transparent: the backdrop shows through
+        ppu.m7sel = 0x80;
+        let frame = ppu.render_frame();
+        let ci_at = |x: usize, y: usize| (frame.pixels[y * 256 + x] >> 19) & 0x1F;
+        assert_eq!(ci_at(0, 0), 31);
+        assert_eq!(ci_at(8, 0), 1);
+
+        // Tile 0 fill: tile 0's pixels, here color 3
+        for pixel in 0..64 {
+            ppu.vram[pixel * 2 + 1] = 3;
+        }
+        set_color(&mut ppu, 3, 3);
+        ppu.m7sel = 0xC0;
+        let frame = ppu.render_frame();
+        let ci_at = |x: usize, y: usize| (frame.pixels[y * 256 + x] >> 19) & 0x1F;
+        assert_eq!(ci_at(0, 0), 3);
+        assert_eq!(ci_at(8, 0), 1);
+    }
 }
@@ -7,12 +7,13 @@
 //!
 //! The SnesPpuRenderer follows the common renderer pattern defined in `emu_core::renderer::Renderer`:
 //! - **Software Renderer**: CPU-based tile/sprite rendering, maximum compatibility
-//! - **Hardware Renderer** (future): GPU-accelerated rendering for performance
+//! - **OpenGL Renderer** (`opengl` feature): GPU-accelerated BG sampling and compositing,
+//!   see `ppu_renderer_opengl`
 //!
 //! # Architecture
 //!
 //! ```text
-//! SnesSystem (state) -> SnesPpuRenderer trait -> {Software, Hardware} implementations
+//! SnesSystem (state) -> SnesPpuRenderer trait -> {Software, OpenGL} implementations
 //!                            ↓
 //!                 (follows emu_core::renderer::Renderer pattern)
 //! ```
@@ -24,6 +25,7 @@ use emu_core::renderer::Renderer;
 use emu_core::types::Frame;
 
 use crate::ppu::Ppu;
+use std::ops::Range;
 
 /// Trait for SNES PPU rendering backends
 ///
@@ -39,14 +41,22 @@ use crate::ppu::Ppu;
 /// - `name()`: Get renderer name
 ///
 /// # SNES-Specific Methods
-/// - Frame-based rendering (256x224 resolution)
+/// - Frame-based rendering (256x224, or 512x448 for hires/interlace)
 /// - PPU state rendering (background layers, sprites)
+/// - Scanline rendering, so mid-frame register changes (HDMA) take effect
 pub trait SnesPpuRenderer: Renderer {
     /// Render a complete frame using PPU state
     ///
     /// # Arguments
     /// * `ppu` - PPU chip state (registers, VRAM, CGRAM)
     fn render_frame(&mut self, ppu: &Ppu);
+
+    /// Render a band of scanlines (0-223) using the current PPU state
+    ///
+    /// # Arguments
+    /// * `ppu` - PPU chip state (registers, VRAM, CGRAM)
+    /// * `lines` - Scanlines to draw; the rest of the frame is kept
+    fn render_lines(&mut self, ppu: &Ppu, lines: Range<usize>);
 }
 
 /// Software SNES PPU renderer (CPU-based tile/sprite rendering)
@@ -95,9 +105,12 @@ impl Renderer for SoftwareSnesPpuRenderer {
 
 impl SnesPpuRenderer for SoftwareSnesPpuRenderer {
     fn render_frame(&mut self, ppu: &Ppu) {
-        // Delegate to the PPU's existing render logic and copy the result
-        let rendered = ppu.render_frame();
-        self.framebuffer = rendered;
+        // Delegate to the PPU's render logic, reusing the framebuffer
+        ppu.render_into(&mut self.framebuffer);
+    }
+
+    fn render_lines(&mut self, ppu: &Ppu, lines: Range<usize>) {
+        ppu.render_lines_into(&mut self.framebuffer, lines);
     }
 }
 
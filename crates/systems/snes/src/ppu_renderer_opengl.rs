@@ -0,0 +1,494 @@
+//! OpenGL SNES PPU Renderer - GPU-accelerated BG rendering and compositing
+//!
+//! This module implements the `SnesPpuRenderer` trait using OpenGL. The BG
+//! layers (including Mode 7's per-pixel matrix transform and the 512-pixel
+//! hires modes) are sampled and composited in a fragment shader; sprites are
+//! evaluated on the CPU with the software renderer's code, so the per-line
+//! sprite limits match exactly.
+//!
+//! **Architecture**:
+//! - Uses OpenGL 3.3 Core Profile
+//! - Renders to FBO (Framebuffer Object) for offscreen rendering
+//! - As the beam reaches each scanline, the line's registers, CGRAM and
+//!   sprite pixels are captured, so HDMA effects (gradients, split scrolling,
+//!   Mode 7 perspective) look the same as with the software renderer
+//! - After the last visible line, VRAM and the captured lines are uploaded
+//!   as integer textures and one full-screen pass draws the frame
+//! - Pixels are read back to the Frame right after the pass
+//!
+//! **Limitations**:
+//! - Requires OpenGL 3.3+ support
+//! - VRAM is taken as it stands at the end of the visible frame, so writes
+//!   during a mid-frame force blank only show up from the next frame
+//! - A frame whose output size changes partway down uses the final size
+//!
+//! **Integration**:
+//! - Requires OpenGL context from frontend (SDL2)
+//! - Feature-gated behind `opengl` feature flag
+//! - Falls back to software renderer if GL context unavailable
+
+#[cfg(feature = "opengl")]
+use super::ppu::{Ppu, LINE_REGISTER_WORDS};
+#[cfg(feature = "opengl")]
+use super::ppu_renderer::SnesPpuRenderer;
+#[cfg(feature = "opengl")]
+use emu_core::renderer::Renderer;
+#[cfg(feature = "opengl")]
+use emu_core::types::Frame;
+#[cfg(feature = "opengl")]
+use glow::HasContext;
+#[cfg(feature = "opengl")]
+use std::ops::Range;
+
+/// Visible scanlines per frame
+#[cfg(feature = "opengl")]
+const LINES: usize = 224;
+
+/// Bytes of CGRAM captured per scanline
+#[cfg(feature = "opengl")]
+const CGRAM_BYTES: usize = 512;
+
+/// Largest output size (hires or interlace)
+#[cfg(feature = "opengl")]
+const MAX_WIDTH: usize = 512;
+#[cfg(feature = "opengl")]
+const MAX_HEIGHT: usize = 448;
+
+/// Wrapper for glow::Context that implements Send
+/// Safety: OpenGL contexts are generally safe to send between threads as long as
+/// they're not actively being used on multiple threads simultaneously. The SNES
+/// renderer is only used from the emulation thread, so this is safe.
+#[cfg(feature = "opengl")]
+struct SendContext(glow::Context);
+
+#[cfg(feature = "opengl")]
+unsafe impl Send for SendContext {}
+
+#[cfg(feature = "opengl")]
+impl std::ops::Deref for SendContext {
+    type Target = glow::Context;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// OpenGL-based SNES PPU renderer
+#[cfg(feature = "opengl")]
+pub struct OpenGLSnesPpuRenderer {
+    gl: SendContext,
+    framebuffer: Frame,
+
+    // OpenGL resources
+    fbo: glow::Framebuffer,
+    color_texture: glow::Texture,
+    /// Size the color texture is allocated at
+    target_size: (usize, usize),
+    program: glow::Program,
+    vao: glow::VertexArray,
+
+    // SNES-specific textures
+    vram_texture: glow::Texture,
+    cgram_texture: glow::Texture,
+    line_texture: glow::Texture,
+    obj_texture: glow::Texture,
+
+    // State captured per scanline
+    /// `Ppu::line_registers` for each line
+    line_registers: Vec<u32>,
+    /// CGRAM for each line
+    line_cgram: Vec<u8>,
+    /// Sprite layer (`Ppu::render_obj_lines`), rows of `MAX_WIDTH` pixels
+    obj_pixels: Vec<u32>,
+}
+
+#[cfg(feature = "opengl")]
+impl OpenGLSnesPpuRenderer {
+    /// Create a new OpenGL SNES PPU renderer with the given GL context
+    pub fn new(gl: glow::Context) -> Result<Self, String> {
+        let gl = SendContext(gl);
+        unsafe {
+            // Create framebuffer for offscreen rendering
+            let fbo = gl
+                .create_framebuffer()
+                .map_err(|e| format!("Failed to create framebuffer: {}", e))?;
+            let color_texture = create_texture(&gl)?;
+            allocate_color_texture(&gl, color_texture, 256, 224);
+
+            gl.bind_framebuffer(glow::FRAMEBUFFER, Some(fbo));
+            gl.framebuffer_texture_2d(
+                glow::FRAMEBUFFER,
+                glow::COLOR_ATTACHMENT0,
+                glow::TEXTURE_2D,
+                Some(color_texture),
+                0,
+            );
+
+            // Check framebuffer status
+            let status = gl.check_framebuffer_status(glow::FRAMEBUFFER);
+            gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+            if status != glow::FRAMEBUFFER_COMPLETE {
+                return Err(format!("Framebuffer incomplete: {:x}", status));
+            }
+
+            let program = create_program(&gl)?;
+
+            // The full-screen triangle is generated from gl_VertexID, but the
+            // core profile still needs a vertex array bound to draw
+            let vao = gl
+                .create_vertex_array()
+                .map_err(|e| format!("Failed to create VAO: {}", e))?;
+
+            Ok(Self {
+                framebuffer: Frame::new(256, 224),
+                fbo,
+                color_texture,
+                target_size: (256, 224),
+                program,
+                vao,
+                vram_texture: create_texture(&gl)?,
+                cgram_texture: create_texture(&gl)?,
+                line_texture: create_texture(&gl)?,
+                obj_texture: create_texture(&gl)?,
+                gl,
+                line_registers: vec![0; LINES * LINE_REGISTER_WORDS],
+                line_cgram: vec![0; LINES * CGRAM_BYTES],
+                obj_pixels: vec![0; MAX_WIDTH * MAX_HEIGHT],
+            })
+        }
+    }
+
+    /// Capture the registers, CGRAM and sprites of a band of scanlines
+    fn capture_lines(&mut self, ppu: &Ppu, lines: Range<usize>) {
+        let lines = lines.start.min(LINES)..lines.end.min(LINES);
+        if lines.is_empty() {
+            return;
+        }
+
+        let registers = ppu.line_registers();
+        for line in lines.clone() {
+            self.line_registers[line * LINE_REGISTER_WORDS..(line + 1) * LINE_REGISTER_WORDS]
+                .copy_from_slice(&registers);
+            self.line_cgram[line * CGRAM_BYTES..(line + 1) * CGRAM_BYTES]
+                .copy_from_slice(ppu.cgram());
+        }
+
+        let (width, height) = ppu.output_size();
+        let first_row = lines.start * (height / LINES);
+        let obj = ppu.render_obj_lines(lines);
+        for (i, row) in obj.chunks_exact(width).enumerate() {
+            let start = (first_row + i) * MAX_WIDTH;
+            self.obj_pixels[start..start + width].copy_from_slice(row);
+        }
+    }
+
+    /// Draw the captured frame on the GPU and read it back
+    fn draw(&mut self, ppu: &Ppu) {
+        let (width, height) = ppu.output_size();
+        let gl = &self.gl;
+        unsafe {
+            if self.target_size != (width, height) {
+                allocate_color_texture(gl, self.color_texture, width, height);
+                self.target_size = (width, height);
+            }
+
+            gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 1);
+            upload_integer_texture(
+                gl,
+                self.vram_texture,
+                glow::R8UI,
+                glow::UNSIGNED_BYTE,
+                (256, 256),
+                ppu.vram(),
+            );
+            upload_integer_texture(
+                gl,
+                self.cgram_texture,
+                glow::R16UI,
+                glow::UNSIGNED_SHORT,
+                (256, LINES),
+                &self.line_cgram,
+            );
+            upload_integer_texture(
+                gl,
+                self.line_texture,
+                glow::R32UI,
+                glow::UNSIGNED_INT,
+                (LINE_REGISTER_WORDS, LINES),
+                &words_to_bytes(&self.line_registers),
+            );
+            upload_integer_texture(
+                gl,
+                self.obj_texture,
+                glow::R32UI,
+                glow::UNSIGNED_INT,
+                (MAX_WIDTH, MAX_HEIGHT),
+                &words_to_bytes(&self.obj_pixels),
+            );
+
+            gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.fbo));
+            gl.viewport(0, 0, width as i32, height as i32);
+            gl.disable(glow::BLEND);
+            gl.disable(glow::DEPTH_TEST);
+            gl.disable(glow::SCISSOR_TEST);
+            gl.use_program(Some(self.program));
+
+            let textures = [
+                ("uVram", self.vram_texture),
+                ("uCgram", self.cgram_texture),
+                ("uLines", self.line_texture),
+                ("uObj", self.obj_texture),
+            ];
+            for (unit, (name, texture)) in textures.into_iter().enumerate() {
+                gl.active_texture(glow::TEXTURE0 + unit as u32);
+                gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+                let location = gl.get_uniform_location(self.program, name);
+                gl.uniform_1_i32(location.as_ref(), unit as i32);
+            }
+            let size = gl.get_uniform_location(self.program, "uSize");
+            gl.uniform_2_i32(size.as_ref(), width as i32, height as i32);
+
+            gl.bind_vertex_array(Some(self.vao));
+            gl.draw_arrays(glow::TRIANGLES, 0, 3);
+            gl.bind_vertex_array(None);
+            gl.active_texture(glow::TEXTURE0);
+
+            // Framebuffer row 0 is scanline 0, so the rows come back in order
+            let mut rgba = vec![0u8; width * height * 4];
+            gl.pixel_store_i32(glow::PACK_ALIGNMENT, 4);
+            gl.read_pixels(
+                0,
+                0,
+                width as i32,
+                height as i32,
+                glow::RGBA,
+                glow::UNSIGNED_BYTE,
+                glow::PixelPackData::Slice(&mut rgba),
+            );
+            gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+
+            let error = gl.get_error();
+            if error != glow::NO_ERROR {
+                eprintln!("OpenGL error in SNES PPU renderer: 0x{:X}", error);
+            }
+
+            self.framebuffer.width = width as u32;
+            self.framebuffer.height = height as u32;
+            self.framebuffer.pixels.clear();
+            self.framebuffer.pixels.extend(
+                rgba.chunks_exact(4)
+                    .map(|p| 0xFF00_0000 | (p[0] as u32) << 16 | (p[1] as u32) << 8 | p[2] as u32),
+            );
+        }
+    }
+}
+
+/// Create a texture sampled with nearest filtering and no mipmaps (integer
+/// textures are incomplete otherwise)
+#[cfg(feature = "opengl")]
+unsafe fn create_texture(gl: &SendContext) -> Result<glow::Texture, String> {
+    let texture = gl
+        .create_texture()
+        .map_err(|e| format!("Failed to create texture: {}", e))?;
+    gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+    for (parameter, value) in [
+        (glow::TEXTURE_MIN_FILTER, glow::NEAREST),
+        (glow::TEXTURE_MAG_FILTER, glow::NEAREST),
+        (glow::TEXTURE_WRAP_S, glow::CLAMP_TO_EDGE),
+        (glow::TEXTURE_WRAP_T, glow::CLAMP_TO_EDGE),
+    ] {
+        gl.tex_parameter_i32(glow::TEXTURE_2D, parameter, value as i32);
+    }
+    gl.bind_texture(glow::TEXTURE_2D, None);
+    Ok(texture)
+}
+
+/// (Re)allocate the RGBA color texture the frame is drawn into
+#[cfg(feature = "opengl")]
+unsafe fn allocate_color_texture(
+    gl: &SendContext,
+    texture: glow::Texture,
+    width: usize,
+    height: usize,
+) {
+    gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+    gl.tex_image_2d(
+        glow::TEXTURE_2D,
+        0,
+        glow::RGBA8 as i32,
+        width as i32,
+        height as i32,
+        0,
+        glow::RGBA,
+        glow::UNSIGNED_BYTE,
+        None,
+    );
+    gl.bind_texture(glow::TEXTURE_2D, None);
+}
+
+/// Upload a single-channel unsigned integer texture
+#[cfg(feature = "opengl")]
+unsafe fn upload_integer_texture(
+    gl: &SendContext,
+    texture: glow::Texture,
+    internal_format: u32,
+    data_type: u32,
+    (width, height): (usize, usize),
+    data: &[u8],
+) {
+    gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+    gl.tex_image_2d(
+        glow::TEXTURE_2D,
+        0,
+        internal_format as i32,
+        width as i32,
+        height as i32,
+        0,
+        glow::RED_INTEGER,
+        data_type,
+        Some(data),
+    );
+}
+
+/// Native-endian bytes of a word buffer, as GL reads them
+#[cfg(feature = "opengl")]
+fn words_to_bytes(words: &[u32]) -> Vec<u8> {
+    words.iter().flat_map(|word| word.to_ne_bytes()).collect()
+}
+
+#[cfg(feature = "opengl")]
+unsafe fn compile_shader(
+    gl: &SendContext,
+    shader_type: u32,
+    source: &str,
+) -> Result<glow::Shader, String> {
+    let shader = gl
+        .create_shader(shader_type)
+        .map_err(|e| format!("Failed to create shader: {}", e))?;
+
+    gl.shader_source(shader, source);
+    gl.compile_shader(shader);
+
+    if !gl.get_shader_compile_status(shader) {
+        let log = gl.get_shader_info_log(shader);
+        gl.delete_shader(shader);
+        return Err(format!("Shader compilation failed: {}", log));
+    }
+
+    Ok(shader)
+}
+
+/// Create the program that draws and composites the screen
+#[cfg(feature = "opengl")]
+fn create_program(gl: &SendContext) -> Result<glow::Program, String> {
+    unsafe {
+        let vertex_shader = compile_shader(
+            gl,
+            glow::VERTEX_SHADER,
+            include_str!("shaders/ppu_vertex.glsl"),
+        )?;
+        let fragment_shader = compile_shader(
+            gl,
+            glow::FRAGMENT_SHADER,
+            include_str!("shaders/ppu_fragment.glsl"),
+        )?;
+
+        let program = gl
+            .create_program()
+            .map_err(|e| format!("Failed to create program: {}", e))?;
+
+        gl.attach_shader(program, vertex_shader);
+        gl.attach_shader(program, fragment_shader);
+        gl.link_program(program);
+
+        gl.detach_shader(program, vertex_shader);
+        gl.detach_shader(program, fragment_shader);
+        gl.delete_shader(vertex_shader);
+        gl.delete_shader(fragment_shader);
+
+        if !gl.get_program_link_status(program) {
+            let log = gl.get_program_info_log(program);
+            gl.delete_program(program);
+            return Err(format!("Program linking failed: {}", log));
+        }
+
+        Ok(program)
+    }
+}
+
+#[cfg(feature = "opengl")]
+impl Renderer for OpenGLSnesPpuRenderer {
+    fn get_frame(&self) -> &Frame {
+        &self.framebuffer
+    }
+
+    fn clear(&mut self, color: u32) {
+        // The GPU target is fully redrawn each frame; only the CPU copy
+        // needs clearing
+        for pixel in &mut self.framebuffer.pixels {
+            *pixel = color;
+        }
+    }
+
+    fn reset(&mut self) {
+        self.clear(0xFF000000); // Black
+    }
+
+    fn resize(&mut self, width: u32, height: u32) {
+        self.framebuffer = Frame::new(width, height);
+    }
+
+    fn name(&self) -> &str {
+        "SNES OpenGL Renderer"
+    }
+
+    fn is_hardware_accelerated(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(feature = "opengl")]
+impl SnesPpuRenderer for OpenGLSnesPpuRenderer {
+    fn render_frame(&mut self, ppu: &Ppu) {
+        self.render_lines(ppu, 0..LINES);
+    }
+
+    fn render_lines(&mut self, ppu: &Ppu, lines: Range<usize>) {
+        let last_line = lines.end >= LINES;
+        self.capture_lines(ppu, lines);
+
+        // The whole frame is drawn in one pass once the beam is done
+        if last_line {
+            self.draw(ppu);
+        }
+    }
+}
+
+#[cfg(feature = "opengl")]
+impl Drop for OpenGLSnesPpuRenderer {
+    fn drop(&mut self) {
+        unsafe {
+            self.gl.delete_framebuffer(self.fbo);
+            self.gl.delete_texture(self.color_texture);
+            self.gl.delete_program(self.program);
+            self.gl.delete_vertex_array(self.vao);
+            self.gl.delete_texture(self.vram_texture);
+            self.gl.delete_texture(self.cgram_texture);
+            self.gl.delete_texture(self.line_texture);
+            self.gl.delete_texture(self.obj_texture);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "opengl"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_words_to_bytes() {
+        let bytes = words_to_bytes(&[0x1234_5678, 0x9ABC_DEF0]);
+        assert_eq!(bytes.len(), 8);
+        assert_eq!(&bytes[..4], &0x1234_5678u32.to_ne_bytes());
+        assert_eq!(&bytes[4..], &0x9ABC_DEF0u32.to_ne_bytes());
+    }
+}
@@ -0,0 +1,270 @@
+#version 330 core
+
+// Fragment shader drawing the SNES BG layers and compositing the screen
+// Each output pixel follows Ppu::render_screen: BGs are sampled with the
+// registers and CGRAM of their scanline, sprites come pre-rendered from the
+// CPU, and the layer with the highest render priority wins (later layers win
+// ties). Row 0 of the framebuffer is scanline 0, so no flip is needed.
+
+uniform usampler2D uVram;   // 256x256 R8UI, byte address = y * 256 + x
+uniform usampler2D uCgram;  // 256x224 R16UI, CGRAM as it was on each line
+uniform usampler2D uLines;  // 12x224 R32UI, Ppu::line_registers per line
+uniform usampler2D uObj;    // 512x448 R32UI, sprite RGB | render priority << 24
+uniform ivec2 uSize;        // Output size (256x224, or 512x448)
+
+out vec4 FragColor;
+
+int line;
+int xScale;
+int yScale;
+int mode;
+bool hiresBg;
+bool interlaceBg;
+
+vec4 bestColor;
+int bestPriority;
+
+uint reg(int index) {
+    return texelFetch(uLines, ivec2(index, line), 0).r;
+}
+
+int vram(int addr) {
+    addr &= 0xFFFF;
+    return int(texelFetch(uVram, ivec2(addr & 255, addr >> 8), 0).r);
+}
+
+// Sign-extend the low 16 bits of a register word
+int sext16(uint value) {
+    return int(value << 16) >> 16;
+}
+
+vec4 rgb15(uint color) {
+    return vec4(
+        float((color & 31u) << 3),
+        float(((color >> 5) & 31u) << 3),
+        float(((color >> 10) & 31u) << 3),
+        255.0
+    ) / 255.0;
+}
+
+vec4 cgramColor(int index) {
+    return rgb15(texelFetch(uCgram, ivec2(index & 255, line), 0).r);
+}
+
+// Color index (0-15) of one pixel of an 8x8 tile of interleaved bitplanes
+int tilePixel(int tileAddr, int bpp, int x, int y) {
+    int bit = 7 - x;
+    int index = 0;
+    for (int pair = 0; pair < bpp / 2; pair++) {
+        int row = (tileAddr + pair * 16 + y * 2) & 0xFFFF;
+        index |= ((vram(row) >> bit) & 1) << (pair * 2);
+        index |= ((vram(row + 1) >> bit) & 1) << (pair * 2 + 1);
+    }
+    return index;
+}
+
+// Sample a tiled BG (Modes 0, 1, 5, 6): x = CGRAM index (0 = transparent),
+// y = tile priority bit
+ivec2 bgSample(int bg, int bpp, int ox, int oy) {
+    uint sc = (reg(1) >> (8 * bg)) & 255u;
+    int tilemapBase = int((sc >> 2) & 63u) << 11;
+    int chrBase = int((reg(2) >> (4 * bg)) & 15u) << 13;
+
+    int tileHeight = (reg(0) & (0x10u << bg)) != 0u ? 16 : 8;
+    int tileWidth = hiresBg ? 16 : tileHeight;
+    int mapWidth = (sc & 1u) != 0u ? 64 : 32;
+    int mapHeight = (sc & 2u) != 0u ? 64 : 32;
+
+    uint scroll = reg(3 + bg);
+    int hofs = int(scroll & 0xFFFFu);
+    int vofs = int(scroll >> 16);
+    if (hiresBg) {
+        hofs *= 2;
+    }
+
+    int bgX = hiresBg ? ox : ox / xScale;
+    int bgY = interlaceBg ? oy : oy / yScale;
+    int worldX = (bgX + hofs) % (mapWidth * tileWidth);
+    int worldY = (bgY + vofs) % (mapHeight * tileHeight);
+
+    // Tilemaps are made of 32x32-entry blocks
+    int tileX = worldX / tileWidth;
+    int tileY = worldY / tileHeight;
+    int block = mapWidth == 64 ? (tileY / 32) * 2 + tileX / 32 : tileY / 32;
+    int entry = tilemapBase + block * 2048 + ((tileY % 32) * 32 + tileX % 32) * 2;
+    int low = vram(entry);
+    int high = vram(entry + 1);
+
+    int priority = (high >> 5) & 1;
+    int tile = low | ((high & 3) << 8);
+    int palette = (high >> 2) & 7;
+
+    int pixelX = worldX % tileWidth;
+    int pixelY = worldY % tileHeight;
+    if ((high & 0x40) != 0) {
+        pixelX = tileWidth - 1 - pixelX;
+    }
+    if ((high & 0x80) != 0) {
+        pixelY = tileHeight - 1 - pixelY;
+    }
+
+    int subTile = (pixelY / 8) * 16 + pixelX / 8;
+    int tileAddr = chrBase + ((tile + subTile) & 0x3FF) * 8 * bpp;
+    int index = tilePixel(tileAddr, bpp, pixelX % 8, pixelY % 8);
+    if (index == 0) {
+        return ivec2(0, priority);
+    }
+
+    int paletteBase = mode == 0 ? bg * 32 : 0;
+    return ivec2((paletteBase + palette * (1 << bpp) + index) & 255, priority);
+}
+
+// Wrap a Mode 7 scroll offset to a signed 10-bit value
+int clip10(int value) {
+    return (value & 0x2000) != 0 ? (value | ~0x3FF) : (value & 0x3FF);
+}
+
+// Sample the Mode 7 BG: the CGRAM index, 0 = transparent
+int mode7Sample(int ox) {
+    int a = sext16(reg(7));
+    int b = sext16(reg(7) >> 16);
+    int c = sext16(reg(8));
+    int d = sext16(reg(8) >> 16);
+    int centerX = sext16(reg(9));
+    int centerY = sext16(reg(9) >> 16);
+    int h = clip10(sext16(reg(10)) - centerX);
+    int v = clip10(sext16(reg(10) >> 16) - centerY);
+
+    uint sel = (reg(2) >> 16) & 255u;
+    int y = (sel & 2u) != 0u ? 255 - line : line;
+    int column = ox / xScale;
+    int x = (sel & 1u) != 0u ? 255 - column : column;
+
+    int startX = ((a * h) & ~63) + ((b * v) & ~63) + ((b * y) & ~63) + (centerX << 8);
+    int startY = ((c * h) & ~63) + ((d * v) & ~63) + ((d * y) & ~63) + (centerY << 8);
+    int planeX = (startX + a * x) >> 8;
+    int planeY = (startY + c * x) >> 8;
+
+    bool outside = planeX < 0 || planeX >= 1024 || planeY < 0 || planeY >= 1024;
+    int screenOver = int(sel >> 6);
+    planeX &= 1023;
+    planeY &= 1023;
+
+    int tile;
+    if (outside && screenOver == 2) {
+        return 0;
+    } else if (outside && screenOver == 3) {
+        tile = 0;
+    } else {
+        tile = vram(((planeY / 8) * 128 + planeX / 8) * 2);
+    }
+    return vram((tile * 64 + (planeY % 8) * 8 + planeX % 8) * 2 + 1);
+}
+
+void plot(vec4 color, int priority) {
+    if (priority >= bestPriority) {
+        bestColor = color;
+        bestPriority = priority;
+    }
+}
+
+void plotBg(uint layers, int bg, ivec2 bgPixel, int tilePriority, int renderPriority) {
+    if ((layers & (1u << bg)) != 0u && bgPixel.x != 0 && bgPixel.y == tilePriority) {
+        plot(cgramColor(bgPixel.x), renderPriority);
+    }
+}
+
+// Composite one screen (main or sub) from the layers enabled in `layers`
+vec4 composite(uint layers, vec4 backdrop, ivec2 bgPixels[4], uint obj) {
+    bestColor = backdrop;
+    bestPriority = 0;
+
+    if (mode == 0) {
+        for (int bg = 3; bg >= 0; bg--) {
+            plotBg(layers, bg, bgPixels[bg], 0, 1);
+        }
+        for (int bg = 3; bg >= 0; bg--) {
+            plotBg(layers, bg, bgPixels[bg], 1, 3);
+        }
+    } else if (mode == 1) {
+        // BGMODE bit 3 lifts priority 1 BG3 tiles above all sprites
+        bool bg3High = (reg(0) & 0x08u) != 0u;
+        for (int bg = 2; bg >= 0; bg--) {
+            plotBg(layers, bg, bgPixels[bg], 0, 1);
+        }
+        for (int bg = 2; bg >= 0; bg--) {
+            plotBg(layers, bg, bgPixels[bg], 1, bg == 2 && bg3High ? 5 : 3);
+        }
+    } else if (hiresBg) {
+        for (int bg = 1; bg >= 0; bg--) {
+            plotBg(layers, bg, bgPixels[bg], 0, 1);
+        }
+        for (int bg = 1; bg >= 0; bg--) {
+            plotBg(layers, bg, bgPixels[bg], 1, 3);
+        }
+    } else if (mode == 7) {
+        plotBg(layers, 0, bgPixels[0], 0, 1);
+    } else {
+        // Modes 2-4 are not implemented: backdrop only
+        return backdrop;
+    }
+
+    // Sprite render priorities (2 and 4) never tie with a BG, so the
+    // sprite layer can be plotted last
+    int objPriority = int(obj >> 24);
+    if ((layers & 0x10u) != 0u && objPriority != 0) {
+        vec4 objColor = vec4(
+            float((obj >> 16) & 255u),
+            float((obj >> 8) & 255u),
+            float(obj & 255u),
+            255.0
+        ) / 255.0;
+        plot(objColor, objPriority);
+    }
+
+    return bestColor;
+}
+
+void main() {
+    int ox = int(gl_FragCoord.x);
+    int oy = int(gl_FragCoord.y);
+    xScale = uSize.x / 256;
+    yScale = uSize.y / 224;
+    line = oy / yScale;
+
+    uint r0 = reg(0);
+    mode = int(r0 & 7u);
+    uint tm = (r0 >> 8) & 255u;
+    uint ts = (r0 >> 16) & 255u;
+    uint setini = r0 >> 24;
+    hiresBg = mode == 5 || mode == 6;
+    bool hires = (hiresBg || (setini & 8u) != 0u) && xScale == 2;
+    interlaceBg = hiresBg && (setini & 1u) != 0u;
+
+    // Sample each BG once for both screens. Mode 1 BG3 and Mode 5 BG2 are
+    // 2bpp; the other Mode 1/5/6 BGs are 4bpp.
+    uint used = tm | (hires ? ts : 0u);
+    ivec2 bgPixels[4] = ivec2[4](ivec2(0), ivec2(0), ivec2(0), ivec2(0));
+    if (mode == 7) {
+        if ((used & 1u) != 0u) {
+            bgPixels[0] = ivec2(mode7Sample(ox), 0);
+        }
+    } else if (mode <= 1 || hiresBg) {
+        int layerCount = mode == 0 ? 4 : (mode == 1 ? 3 : (mode == 5 ? 2 : 1));
+        for (int bg = 0; bg < layerCount; bg++) {
+            if ((used & (1u << bg)) != 0u) {
+                int bpp = (mode == 0 || bg == 2 || (mode == 5 && bg == 1)) ? 2 : 4;
+                bgPixels[bg] = bgSample(bg, bpp, ox, oy);
+            }
+        }
+    }
+
+    uint obj = texelFetch(uObj, ivec2(ox, oy), 0).r;
+
+    // Hires: the sub-screen (fixed color backdrop) supplies the even columns
+    if (hires && (ox & 1) == 0) {
+        FragColor = composite(ts, rgb15(reg(11)), bgPixels, obj);
+    } else {
+        FragColor = composite(tm, cgramColor(0), bgPixels, obj);
+    }
+}
@@ -0,0 +1,13 @@
+//! Mount arbitrary bytes as a Game Boy cartridge and run a frame if it loads
+
+#![no_main]
+
+use emu_core::System;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let mut system = emu_gb::GbSystem::new();
+    if system.mount("Cartridge", data).is_ok() {
+        let _ = system.step_frame();
+    }
+});
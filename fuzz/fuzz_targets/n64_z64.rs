@@ -0,0 +1,14 @@
+//! Mount arbitrary bytes as an N64 ROM (.z64, .n64 or .v64 byte order) and
+//! run a frame if it loads
+
+#![no_main]
+
+use emu_core::System;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let mut system = emu_n64::N64System::new();
+    if system.mount("Cartridge", data).is_ok() {
+        let _ = system.step_frame();
+    }
+});
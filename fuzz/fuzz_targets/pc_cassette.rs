@@ -0,0 +1,11 @@
+//! Mount arbitrary bytes as a cassette tape (WAV recording or `.cas` bits)
+
+#![no_main]
+
+use emu_core::System;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let mut system = emu_pc::PcSystem::new();
+    let _ = system.mount("Cassette", data);
+});
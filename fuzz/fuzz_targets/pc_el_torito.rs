@@ -0,0 +1,9 @@
+//! Parse arbitrary bytes as a bootable CD image
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    emu_pc::fuzzing::el_torito_image(data);
+});
@@ -0,0 +1,10 @@
+//! Parse arbitrary bytes as a disk image: boot sector geometry, then the
+//! FAT volume's directories and files
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    emu_pc::fuzzing::fat_image(data);
+});
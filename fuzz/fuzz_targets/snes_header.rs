@@ -0,0 +1,14 @@
+//! Mount arbitrary bytes as a SNES ROM (SMC header and internal header)
+//! and run a frame if it loads
+
+#![no_main]
+
+use emu_core::System;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let mut system = emu_snes::SnesSystem::new();
+    if system.mount("Cartridge", data).is_ok() {
+        let _ = system.step_frame();
+    }
+});
@@ -19,40 +19,49 @@ def mips_instr(opcode):
 
 def f3dex_vtx(addr, count, start_idx):
     """Generate F3DEX G_VTX command"""
-    # word0: cmd(0x01) | count(8 bits at 20-12) | start_idx(7 bits at 16-1)
-    word0 = (0x01 << 24) | ((count & 0xFF) << 12) | ((start_idx & 0x7F) << 1)
+    # word0: cmd(0x04) | start_idx*2 (bits 23-16) | count (bits 15-10) | size-1
+    word0 = (0x04 << 24) | ((start_idx * 2) << 16) | ((count & 0x3F) << 10) | (count * 16 - 1)
     word1 = addr
     return struct.pack('>I', word0) + struct.pack('>I', word1)
 
 def f3dex_tri1(v0, v1, v2):
     """Generate F3DEX G_TRI1 command"""
     # Vertex indices need to be multiplied by 2 (vtx buffer stride)
-    word0 = (0x05 << 24) | ((v0*2) << 16) | ((v1*2) << 8) | (v2*2)
-    word1 = 0
+    word0 = 0xBF << 24
+    word1 = ((v0*2) << 16) | ((v1*2) << 8) | (v2*2)
     return struct.pack('>I', word0) + struct.pack('>I', word1)
 
 def f3dex_tri2(v0, v1, v2, v3, v4, v5):
     """Generate F3DEX G_TRI2 command (two triangles)"""
-    word0 = (0x06 << 24) | ((v0*2) << 16) | ((v1*2) << 8) | (v2*2)
+    word0 = (0xB1 << 24) | ((v0*2) << 16) | ((v1*2) << 8) | (v2*2)
     word1 = ((v3*2) << 16) | ((v4*2) << 8) | (v5*2)
     return struct.pack('>I', word0) + struct.pack('>I', word1)
 
 def f3dex_mtx(addr, flags):
     """Generate F3DEX G_MTX command"""
-    # flags: 0x01=PUSH, 0x02=NOPUSH/LOAD (0=MUL), 0x04=PROJECTION
-    word0 = (0xDA << 24) | (flags & 0xFF)
+    # flags: 0x01=PUSH (0=NOPUSH), 0x02=LOAD (0=MUL), 0x04=PROJECTION
+    word0 = (0x01 << 24) | ((flags & 0xFF) << 16) | 64
     word1 = addr
     return struct.pack('>I', word0) + struct.pack('>I', word1)
 
 def f3dex_enddl():
     """Generate F3DEX G_ENDDL command"""
-    return struct.pack('>I', 0xDF000000) + struct.pack('>I', 0)
+    return struct.pack('>I', 0xB8000000) + struct.pack('>I', 0)
 
 def create_vertex(x, y, z, r, g, b, a=255):
     """Create a vertex in N64 format (16 bytes)"""
     # Position (3x i16), flags (u16), tex coords (2x i16), color (4x u8)
     return struct.pack('>hhhHhh4B', x, y, z, 0, 0, 0, r, g, b, a)
 
+def pack_matrix(matrix):
+    """Pack 16 16.16 fixed-point values as an N64 Mtx (64 bytes)
+
+    The integer halves of all 16 values come first, then the fraction halves.
+    """
+    integer = b''.join(struct.pack('>H', (x >> 16) & 0xFFFF) for x in matrix)
+    fraction = b''.join(struct.pack('>H', x & 0xFFFF) for x in matrix)
+    return integer + fraction
+
 def create_matrix_identity():
     """Create 4x4 identity matrix in N64 format (16.16 fixed point, 64 bytes)"""
     matrix = []
@@ -61,7 +70,7 @@ def create_matrix_identity():
             matrix.append(0x00010000)  # 1.0 in 16.16 fixed point
         else:
             matrix.append(0x00000000)
-    return b''.join(struct.pack('>i', x) for x in matrix)
+    return pack_matrix(matrix)
 
 def create_matrix_projection(fov, aspect, near, far):
     """Create perspective projection matrix in N64 format"""
@@ -75,7 +84,7 @@ def create_matrix_projection(fov, aspect, near, far):
     matrix[11] = -65536  # [2,3] = -1.0
     matrix[14] = int((2 * far * near / (near - far)) * 65536.0)  # [3,2]
     
-    return b''.join(struct.pack('>i', x) for x in matrix)
+    return pack_matrix(matrix)
 
 def create_matrix_translate(x, y, z):
     """Create translation matrix in N64 format"""
@@ -89,7 +98,7 @@ def create_matrix_translate(x, y, z):
     matrix[13] = int(y * 65536.0)
     matrix[14] = int(z * 65536.0)
     
-    return b''.join(struct.pack('>i', m) for m in matrix)
+    return pack_matrix(matrix)
 
 def build_rom():
     """Build the 3D Pong N64 test ROM"""
@@ -264,10 +273,10 @@ def build_rom():
     display_list = bytearray()
     
     # Load projection matrix
-    display_list.extend(f3dex_mtx(0x80100000, 0x04 | 0x00))  # PROJECTION | LOAD
+    display_list.extend(f3dex_mtx(0x80100000, 0x04 | 0x02))  # PROJECTION | LOAD
     
     # Load camera matrix (modelview)
-    display_list.extend(f3dex_mtx(0x80100080, 0x00))  # MODELVIEW | LOAD
+    display_list.extend(f3dex_mtx(0x80100080, 0x02))  # MODELVIEW | LOAD
     
     # === Draw Left Paddle ===
     # Load left paddle vertices (4 vertices starting at buffer index 0)
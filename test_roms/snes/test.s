@@ -129,36 +129,33 @@ RESET:
     stx $2116               ; VRAM address
     
     ; Tile 0: Blue square (all pixels use color 3 = binary 11)
-    ; Bitplane 0: all 1s (bytes 0-7), Bitplane 1: all 1s (bytes 8-15)
-    ; Write as 16-bit words to VRAM
+    ; Each row is one word: bitplane 0 in the low byte, bitplane 1 in the high byte
     lda #$FF
     ldx #$0008              ; 16 bytes = 8 words
-:   sta $2118               ; Low byte
-    sta $2119               ; High byte (same as low for tile 0)
+:   sta $2118               ; Bitplane 0
+    sta $2119               ; Bitplane 1
     dex
     bne :-
     
     ; Tile 1: Red square (color 2 = binary 10)
-    ; Bitplane 0: all 0s (bytes 0-7), Bitplane 1: all 1s (bytes 8-15)
-    ; Write as 16-bit words
-    stz $2118               ; Bitplane 0, row 0 (low byte)
-    stz $2119               ; Bitplane 0, row 1 (high byte)
-    stz $2118               ; Bitplane 0, row 2
-    stz $2119               ; Bitplane 0, row 3
-    stz $2118               ; Bitplane 0, row 4
-    stz $2119               ; Bitplane 0, row 5
-    stz $2118               ; Bitplane 0, row 6
-    stz $2119               ; Bitplane 0, row 7
-    
+    ; Bitplane 0: all 0s (low bytes), Bitplane 1: all 1s (high bytes)
     lda #$FF
-    sta $2118               ; Bitplane 1, row 0 (low byte)
-    sta $2119               ; Bitplane 1, row 1 (high byte)
-    sta $2118               ; Bitplane 1, row 2
-    sta $2119               ; Bitplane 1, row 3
-    sta $2118               ; Bitplane 1, row 4
-    sta $2119               ; Bitplane 1, row 5
-    sta $2118               ; Bitplane 1, row 6
-    sta $2119               ; Bitplane 1, row 7
+    stz $2118               ; Row 0, bitplane 0
+    sta $2119               ; Row 0, bitplane 1
+    stz $2118               ; Row 1
+    sta $2119
+    stz $2118               ; Row 2
+    sta $2119
+    stz $2118               ; Row 3
+    sta $2119
+    stz $2118               ; Row 4
+    sta $2119
+    stz $2118               ; Row 5
+    sta $2119
+    stz $2118               ; Row 6
+    sta $2119
+    stz $2118               ; Row 7
+    sta $2119
     
     ; Upload tilemap to VRAM
     ; Set VRAM address to $0000 (tilemap for BG1)